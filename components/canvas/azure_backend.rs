@@ -130,6 +130,8 @@ impl<'a> CanvasPaintState<'a> {
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
             shadow_color: Color::Azure(azure_hl::Color::transparent()),
+            font_style: None,
+            filters: vec![],
         }
     }
 }
@@ -545,6 +547,12 @@ impl<'a> StrokeOptions<'a> {
             StrokeOptions::Azure(options) => options.line_cap = val.to_azure_style(),
         }
     }
+    pub fn set_line_dash(&mut self, _segments: Vec<f32>) {
+        warn!("no support for dashed lines");
+    }
+    pub fn set_line_dash_offset(&mut self, _offset: f32) {
+        warn!("no support for dashed lines");
+    }
 }
 
 pub trait ToAzureStyle {
@@ -714,6 +722,10 @@ impl ToAzurePattern for FillOrStrokeStyle {
                     &Transform2D::identity(),
                 ))
             },
+            FillOrStrokeStyle::ConicGradient(_) => {
+                warn!("Conic gradients are not supported by the Azure backend.");
+                return None;
+            },
             FillOrStrokeStyle::Surface(ref surface_style) => {
                 let source_surface = drawtarget
                     .create_source_surface_from_data(
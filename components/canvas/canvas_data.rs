@@ -3,15 +3,18 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::canvas_paint_thread::AntialiasMode;
+use crate::filters;
 use canvas_traits::canvas::*;
 use cssparser::RGBA;
 use euclid::default::{Point2D, Rect, Size2D, Transform2D, Vector2D};
 use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
 use num_traits::ToPrimitive;
+use servo_arc::Arc as ServoArc;
 #[allow(unused_imports)]
 use std::marker::PhantomData;
 use std::mem;
 use std::sync::Arc;
+use style::properties::style_structs::Font as FontStyleStruct;
 use webrender::api::DirtyRect;
 use webrender_api::units::RectExt as RectExt_;
 
@@ -200,6 +203,140 @@ impl<'a> PathBuilderRef<'a> {
         );
     }
 
+    fn arc_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, radius: f32) {
+        let cp0 = match self.current_point() {
+            Some(p) => p,
+            None => return,
+        };
+        let cp1 = *cp1;
+        let cp2 = *cp2;
+
+        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
+            self.line_to(&cp1);
+            return;
+        }
+
+        // if all three control points lie on a single straight line,
+        // connect the first two by a straight line
+        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
+        if direction == 0.0 {
+            self.line_to(&cp1);
+            return;
+        }
+
+        // otherwise, draw the Arc
+        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
+        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
+        let d = {
+            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
+            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
+            let sinx = (1.0 - cosx.powi(2)).sqrt();
+            radius / ((1.0 - cosx) / sinx)
+        };
+
+        // first tangent point
+        let anx = (cp1.x - cp0.x) / a2.sqrt();
+        let any = (cp1.y - cp0.y) / a2.sqrt();
+        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
+
+        // second tangent point
+        let bnx = (cp1.x - cp2.x) / b2.sqrt();
+        let bny = (cp1.y - cp2.y) / b2.sqrt();
+        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
+
+        // arc center and angles
+        let anticlockwise = direction < 0.0;
+        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
+        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
+        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
+        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
+
+        self.line_to(&tp1);
+        if [cx, cy, angle_start, angle_end]
+            .iter()
+            .all(|x| x.is_finite())
+        {
+            self.arc(
+                &Point2D::new(cx, cy),
+                radius,
+                angle_start,
+                angle_end,
+                anticlockwise,
+            );
+        }
+    }
+
+    fn round_rect(&mut self, rect: &Rect<f32>, radii: &CornerRadii) {
+        // Each corner is approximated by a single cubic bezier curve.
+        const KAPPA: f32 = 0.552_284_8;
+        let (left, top, right, bottom) = (rect.min_x(), rect.min_y(), rect.max_x(), rect.max_y());
+        let (upper_left, upper_right, lower_right, lower_left) = (
+            radii.upper_left,
+            radii.upper_right,
+            radii.lower_right,
+            radii.lower_left,
+        );
+
+        self.move_to(&Point2D::new(left + upper_left.width, top));
+        self.line_to(&Point2D::new(right - upper_right.width, top));
+        self.bezier_curve_to(
+            &Point2D::new(right - upper_right.width * (1. - KAPPA), top),
+            &Point2D::new(right, top + upper_right.height * (1. - KAPPA)),
+            &Point2D::new(right, top + upper_right.height),
+        );
+        self.line_to(&Point2D::new(right, bottom - lower_right.height));
+        self.bezier_curve_to(
+            &Point2D::new(right, bottom - lower_right.height * (1. - KAPPA)),
+            &Point2D::new(right - lower_right.width * (1. - KAPPA), bottom),
+            &Point2D::new(right - lower_right.width, bottom),
+        );
+        self.line_to(&Point2D::new(left + lower_left.width, bottom));
+        self.bezier_curve_to(
+            &Point2D::new(left + lower_left.width * (1. - KAPPA), bottom),
+            &Point2D::new(left, bottom - lower_left.height * (1. - KAPPA)),
+            &Point2D::new(left, bottom - lower_left.height),
+        );
+        self.line_to(&Point2D::new(left, top + upper_left.height));
+        self.bezier_curve_to(
+            &Point2D::new(left, top + upper_left.height * (1. - KAPPA)),
+            &Point2D::new(left + upper_left.width * (1. - KAPPA), top),
+            &Point2D::new(left + upper_left.width, top),
+        );
+        self.builder.close();
+        self.move_to(&rect.origin);
+    }
+
+    /// Replays the segments recorded by a `Path2D` on this builder.
+    fn append_segments(&mut self, segments: &[PathSegment]) {
+        for segment in segments {
+            match *segment {
+                PathSegment::AddPath(ref segments, ref transform) => PathBuilderRef {
+                    builder: &mut *self.builder,
+                    transform: transform.post_transform(&self.transform),
+                }
+                .append_segments(segments),
+                PathSegment::Arc(ref center, radius, start, end, ccw) => {
+                    self.arc(center, radius, start, end, ccw)
+                },
+                PathSegment::ArcTo(ref cp1, ref cp2, radius) => self.arc_to(cp1, cp2, radius),
+                PathSegment::BezierCurveTo(ref cp1, ref cp2, ref endpoint) => {
+                    self.bezier_curve_to(cp1, cp2, endpoint)
+                },
+                PathSegment::ClosePath => self.builder.close(),
+                PathSegment::Ellipse(ref center, radius_x, radius_y, rotation, start, end, ccw) => {
+                    self.ellipse(center, radius_x, radius_y, rotation, start, end, ccw)
+                },
+                PathSegment::LineTo(ref point) => self.line_to(point),
+                PathSegment::MoveTo(ref point) => self.move_to(point),
+                PathSegment::QuadraticCurveTo(ref cp, ref endpoint) => {
+                    self.quadratic_curve_to(cp, endpoint)
+                },
+                PathSegment::Rect(ref rect) => self.rect(rect),
+                PathSegment::RoundRect(ref rect, ref radii) => self.round_rect(rect, radii),
+            }
+        }
+    }
+
     fn current_point(&mut self) -> Option<Point2D<f32>> {
         let inverse = match self.transform.inverse() {
             Some(i) => i,
@@ -454,6 +591,20 @@ impl<'a> CanvasData<'a> {
             image_data.into()
         };
 
+        if !self.state.filters.is_empty() {
+            self.draw_with_filters(|draw_target, draw_options| {
+                write_image(
+                    draw_target,
+                    image_data,
+                    source_rect.size,
+                    dest_rect,
+                    smoothing_enabled,
+                    draw_options,
+                );
+            });
+            return;
+        }
+
         let draw_options = self.state.draw_options.clone();
         let writer = |draw_target: &mut dyn GenericDrawTarget| {
             write_image(
@@ -510,7 +661,12 @@ impl<'a> CanvasData<'a> {
                 .unwrap_or(rect.size),
         );
 
-        if self.need_to_draw_shadow() {
+        if !self.state.filters.is_empty() {
+            let pattern = self.state.fill_style.clone();
+            self.draw_with_filters(|draw_target, draw_options| {
+                draw_target.fill_rect(&draw_rect, pattern, Some(draw_options));
+            });
+        } else if self.need_to_draw_shadow() {
             self.draw_with_shadow(&draw_rect, |new_draw_target: &mut dyn GenericDrawTarget| {
                 new_draw_target.fill_rect(
                     &draw_rect,
@@ -536,7 +692,24 @@ impl<'a> CanvasData<'a> {
             return; // Paint nothing if gradient size is zero.
         }
 
-        if self.need_to_draw_shadow() {
+        if !self.state.filters.is_empty() {
+            let pattern = self.state.stroke_style.clone();
+            let mut stroke_opts = self.state.stroke_opts.clone();
+            self.draw_with_filters(|draw_target, draw_options| {
+                if rect.size.width == 0. || rect.size.height == 0. {
+                    stroke_opts.set_line_cap(LineCapStyle::Butt);
+                    draw_target.stroke_line(
+                        rect.origin,
+                        rect.bottom_right(),
+                        pattern,
+                        &stroke_opts,
+                        draw_options,
+                    );
+                } else {
+                    draw_target.stroke_rect(rect, pattern, &stroke_opts, draw_options);
+                }
+            });
+        } else if self.need_to_draw_shadow() {
             self.draw_with_shadow(&rect, |new_draw_target: &mut dyn GenericDrawTarget| {
                 new_draw_target.stroke_rect(
                     rect,
@@ -645,11 +818,11 @@ impl<'a> CanvasData<'a> {
         }
 
         self.ensure_path();
-        self.drawtarget.fill(
-            &self.path().clone(),
-            self.state.fill_style.clone(),
-            &self.state.draw_options,
-        );
+        let path = self.path().clone();
+        let pattern = self.state.fill_style.clone();
+        self.draw_with_filters(|draw_target, draw_options| {
+            draw_target.fill(&path, pattern, draw_options);
+        });
     }
 
     pub fn stroke(&mut self) {
@@ -658,12 +831,12 @@ impl<'a> CanvasData<'a> {
         }
 
         self.ensure_path();
-        self.drawtarget.stroke(
-            &self.path().clone(),
-            self.state.stroke_style.clone(),
-            &self.state.stroke_opts,
-            &self.state.draw_options,
-        );
+        let path = self.path().clone();
+        let pattern = self.state.stroke_style.clone();
+        let stroke_opts = self.state.stroke_opts.clone();
+        self.draw_with_filters(|draw_target, draw_options| {
+            draw_target.stroke(&path, pattern, &stroke_opts, draw_options);
+        });
     }
 
     pub fn clip(&mut self) {
@@ -672,6 +845,48 @@ impl<'a> CanvasData<'a> {
         self.drawtarget.push_clip(&path);
     }
 
+    /// Builds a user-space path out of the segments of a `Path2D`, leaving
+    /// the current default path untouched.
+    fn path_from_segments(&self, segments: &[PathSegment]) -> Path {
+        let mut builder = self.drawtarget.create_path_builder();
+        PathBuilderRef {
+            builder: &mut builder,
+            transform: Transform2D::identity(),
+        }
+        .append_segments(segments);
+        builder.finish()
+    }
+
+    pub fn fill_path(&mut self, segments: &[PathSegment]) {
+        if self.state.fill_style.is_zero_size_gradient() {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        let path = self.path_from_segments(segments);
+        let pattern = self.state.fill_style.clone();
+        self.draw_with_filters(|draw_target, draw_options| {
+            draw_target.fill(&path, pattern, draw_options);
+        });
+    }
+
+    pub fn stroke_path(&mut self, segments: &[PathSegment]) {
+        if self.state.stroke_style.is_zero_size_gradient() {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        let path = self.path_from_segments(segments);
+        let pattern = self.state.stroke_style.clone();
+        let stroke_opts = self.state.stroke_opts.clone();
+        self.draw_with_filters(|draw_target, draw_options| {
+            draw_target.stroke(&path, pattern, &stroke_opts, draw_options);
+        });
+    }
+
+    pub fn clip_path(&mut self, segments: &[PathSegment]) {
+        let path = self.path_from_segments(segments);
+        self.drawtarget.push_clip(&path);
+    }
+
     pub fn is_point_in_path(
         &mut self,
         x: f64,
@@ -691,6 +906,19 @@ impl<'a> CanvasData<'a> {
         chan.send(result).unwrap();
     }
 
+    pub fn is_point_in_path_path(
+        &mut self,
+        segments: &[PathSegment],
+        x: f64,
+        y: f64,
+        _fill_rule: FillRule,
+        chan: IpcSender<bool>,
+    ) {
+        let path = self.path_from_segments(segments);
+        let result = path.contains_point(x, y, &self.drawtarget.get_transform());
+        chan.send(result).unwrap();
+    }
+
     pub fn move_to(&mut self, point: &Point2D<f32>) {
         self.path_builder().move_to(point);
     }
@@ -768,6 +996,10 @@ impl<'a> CanvasData<'a> {
         self.path_builder().rect(rect);
     }
 
+    pub fn round_rect(&mut self, rect: &Rect<f32>, radii: &CornerRadii) {
+        self.path_builder().round_rect(rect, radii);
+    }
+
     pub fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         self.path_builder().quadratic_curve_to(cp, endpoint);
     }
@@ -794,66 +1026,7 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn arc_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, radius: f32) {
-        let cp0 = match self.path_builder().current_point() {
-            Some(p) => p,
-            None => return,
-        };
-        let cp1 = *cp1;
-        let cp2 = *cp2;
-
-        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // if all three control points lie on a single straight line,
-        // connect the first two by a straight line
-        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
-        if direction == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // otherwise, draw the Arc
-        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
-        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
-        let d = {
-            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
-            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
-            let sinx = (1.0 - cosx.powi(2)).sqrt();
-            radius / ((1.0 - cosx) / sinx)
-        };
-
-        // first tangent point
-        let anx = (cp1.x - cp0.x) / a2.sqrt();
-        let any = (cp1.y - cp0.y) / a2.sqrt();
-        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
-
-        // second tangent point
-        let bnx = (cp1.x - cp2.x) / b2.sqrt();
-        let bny = (cp1.y - cp2.y) / b2.sqrt();
-        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
-
-        // arc center and angles
-        let anticlockwise = direction < 0.0;
-        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
-        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
-
-        self.line_to(&tp1);
-        if [cx, cy, angle_start, angle_end]
-            .iter()
-            .all(|x| x.is_finite())
-        {
-            self.arc(
-                &Point2D::new(cx, cy),
-                radius,
-                angle_start,
-                angle_end,
-                anticlockwise,
-            );
-        }
+        self.path_builder().arc_to(cp1, cp2, radius);
     }
 
     pub fn ellipse(
@@ -903,6 +1076,14 @@ impl<'a> CanvasData<'a> {
         self.state.stroke_opts.set_miter_limit(limit);
     }
 
+    pub fn set_line_dash(&mut self, segments: Vec<f32>) {
+        self.state.stroke_opts.set_line_dash(segments);
+    }
+
+    pub fn set_line_dash_offset(&mut self, offset: f32) {
+        self.state.stroke_opts.set_line_dash_offset(offset);
+    }
+
     pub fn set_transform(&mut self, transform: &Transform2D<f32>) {
         // If there is an in-progress path, store the existing transformation required
         // to move between device and user space.
@@ -1032,6 +1213,76 @@ impl<'a> CanvasData<'a> {
         self.backend.set_shadow_color(value, &mut self.state);
     }
 
+    pub fn set_font(&mut self, font_style: FontStyleStruct) {
+        self.state.font_style = Some(ServoArc::new(font_style));
+    }
+
+    pub fn font_style(&self) -> Option<ServoArc<FontStyleStruct>> {
+        self.state.font_style.clone()
+    }
+
+    pub fn set_filter(&mut self, filters: Vec<FilterFunction>) {
+        self.state.filters = filters;
+    }
+
+    /// Draws with `draw` directly when there is no filter, or else into a
+    /// transparent layer the size of the canvas which is composited once
+    /// filtered, <https://html.spec.whatwg.org/multipage/#drawing-model>
+    fn draw_with_filters<F>(&mut self, draw: F)
+    where
+        F: FnOnce(&mut dyn GenericDrawTarget, &DrawOptions),
+    {
+        if self.state.filters.is_empty() {
+            draw(&mut *self.drawtarget, &self.state.draw_options);
+            return;
+        }
+
+        let size = self.drawtarget.get_size();
+        let mut layer = self
+            .drawtarget
+            .create_similar_draw_target(&size, self.drawtarget.get_format());
+        layer.set_transform(&self.state.transform);
+        let layer_draw_options = self.backend.recreate_paint_state(&self.state).draw_options;
+        draw(&mut *layer, &layer_draw_options);
+
+        let mut pixels = layer.snapshot_data_owned();
+        filters::apply_filters(&mut pixels, size, &self.state.filters);
+        let stride = size.width * 4;
+        let surface = match self
+            .drawtarget
+            .create_source_surface_from_data(&pixels, size, stride)
+        {
+            Some(surface) => surface,
+            None => return,
+        };
+
+        // The filtered layer is in device space.
+        self.drawtarget.set_transform(&Transform2D::identity());
+        if self.need_to_draw_shadow() {
+            self.drawtarget.draw_surface_with_shadow(
+                surface,
+                &Point2D::zero(),
+                &self.state.shadow_color,
+                &Vector2D::new(
+                    self.state.shadow_offset_x as f32,
+                    self.state.shadow_offset_y as f32,
+                ),
+                (self.state.shadow_blur / 2.0f64) as f32,
+                self.backend.get_composition_op(&self.state.draw_options),
+            );
+        } else {
+            let rect = Rect::from_size(size.to_f64());
+            self.drawtarget.draw_surface(
+                surface,
+                rect,
+                rect,
+                Filter::Point,
+                &self.state.draw_options,
+            );
+        }
+        self.drawtarget.set_transform(&self.state.transform);
+    }
+
     // https://html.spec.whatwg.org/multipage/#when-shadows-are-drawn
     fn need_to_draw_shadow(&self) -> bool {
         self.backend.need_to_draw_shadow(&self.state.shadow_color) &&
@@ -1124,6 +1375,11 @@ pub struct CanvasPaintState<'a> {
     pub shadow_offset_y: f64,
     pub shadow_blur: f64,
     pub shadow_color: Color,
+    /// The style of the `font` attribute, or `None` for the default
+    /// `10px sans-serif`.
+    pub font_style: Option<ServoArc<FontStyleStruct>>,
+    /// The filter functions of the `filter` attribute.
+    pub filters: Vec<FilterFunction>,
 }
 
 /// It writes an image to the destination target
@@ -4,14 +4,32 @@
 
 use crate::canvas_data::*;
 use crate::ConstellationCanvasMsg;
+use app_units::Au;
 use canvas_traits::canvas::*;
 use crossbeam_channel::{select, unbounded, Sender};
 use euclid::default::Size2D;
+use gfx::font::{FontRef, RunMetrics, ShapingFlags, ShapingOptions};
+use gfx::font_cache_thread::FontCacheThread;
+use gfx::font_context::FontContext;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use ordered_float::NotNan;
+use profile_traits::mem::{ProfilerChan, Report, ReportKind, ReportsChan};
+use servo_arc::Arc;
 use std::borrow::ToOwned;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::thread;
+use style::properties::longhands::font_variant_caps::computed_value::T as FontVariantCaps;
+use style::properties::style_structs::Font as FontStyleStruct;
+use style::values::computed::font::{
+    FontFamily, FontFamilyList, FontSize, FontStretch, FontWeight, GenericFontFamily,
+    SingleFontFamily,
+};
+use style::values::computed::NonNegativeLength;
+use style::values::generics::font::FontStyle;
+use unicode_script::Script;
 
 pub enum AntialiasMode {
     Default,
@@ -21,83 +39,112 @@ pub enum AntialiasMode {
 pub struct CanvasPaintThread<'a> {
     canvases: HashMap<CanvasId, CanvasData<'a>>,
     next_canvas_id: CanvasId,
+    /// The fonts used to shape text, shared by all canvases.
+    font_context: FontContext<FontCacheThread>,
+    /// The style of the default canvas font, `10px sans-serif`, used until
+    /// a canvas sets its `font` attribute.
+    font_style: Arc<FontStyleStruct>,
 }
 
 impl<'a> CanvasPaintThread<'a> {
-    fn new() -> CanvasPaintThread<'a> {
+    fn new(font_cache_thread: FontCacheThread) -> CanvasPaintThread<'a> {
         CanvasPaintThread {
             canvases: HashMap::new(),
             next_canvas_id: CanvasId(0),
+            font_context: FontContext::new(font_cache_thread),
+            font_style: Arc::new(default_font_style()),
         }
     }
 
     /// Creates a new `CanvasPaintThread` and returns an `IpcSender` to
     /// communicate with it.
-    pub fn start() -> (Sender<ConstellationCanvasMsg>, IpcSender<CanvasMsg>) {
+    pub fn start(
+        font_cache_thread: FontCacheThread,
+        mem_profiler_chan: ProfilerChan,
+    ) -> (Sender<ConstellationCanvasMsg>, IpcSender<CanvasMsg>) {
         let (ipc_sender, ipc_receiver) = ipc::channel::<CanvasMsg>().unwrap();
         let msg_receiver = ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(ipc_receiver);
         let (create_sender, create_receiver) = unbounded();
+        let reporter_sender = create_sender.clone();
         thread::Builder::new()
             .name("CanvasThread".to_owned())
             .spawn(move || {
-                let mut canvas_paint_thread = CanvasPaintThread::new();
-                loop {
-                    select! {
-                        recv(msg_receiver) -> msg => {
-                            match msg {
-                                Ok(CanvasMsg::Canvas2d(message, canvas_id)) => {
-                                    canvas_paint_thread.process_canvas_2d_message(message, canvas_id);
-                                },
-                                Ok(CanvasMsg::Close(canvas_id)) => {
-                                    canvas_paint_thread.canvases.remove(&canvas_id);
-                                },
-                                Ok(CanvasMsg::Recreate(size, canvas_id)) => {
-                                    canvas_paint_thread.canvas(canvas_id).recreate(size);
-                                },
-                                Ok(CanvasMsg::FromScript(message, canvas_id)) => match message {
-                                    FromScriptMsg::SendPixels(chan) => {
-                                        canvas_paint_thread.canvas(canvas_id).send_pixels(chan);
+                let mut canvas_paint_thread = CanvasPaintThread::new(font_cache_thread);
+                mem_profiler_chan.run_with_memory_reporting(
+                    || loop {
+                        select! {
+                            recv(msg_receiver) -> msg => {
+                                match msg {
+                                    Ok(CanvasMsg::Canvas2d(message, canvas_id)) => {
+                                        canvas_paint_thread.process_canvas_2d_message(message, canvas_id);
                                     },
-                                },
-                                Ok(CanvasMsg::FromLayout(message, canvas_id)) => match message {
-                                    FromLayoutMsg::SendData(chan) => {
-                                        canvas_paint_thread.canvas(canvas_id).send_data(chan);
+                                    Ok(CanvasMsg::Close(canvas_id)) => {
+                                        canvas_paint_thread.canvases.remove(&canvas_id);
                                     },
-                                },
-                                Err(e) => {
-                                    warn!("Error on CanvasPaintThread receive ({})", e);
-                                },
+                                    Ok(CanvasMsg::Recreate(size, canvas_id)) => {
+                                        canvas_paint_thread.canvas(canvas_id).recreate(size);
+                                    },
+                                    Ok(CanvasMsg::FromScript(message, canvas_id)) => match message {
+                                        FromScriptMsg::SendPixels(chan) => {
+                                            canvas_paint_thread.canvas(canvas_id).send_pixels(chan);
+                                        },
+                                    },
+                                    Ok(CanvasMsg::FromLayout(message, canvas_id)) => match message {
+                                        FromLayoutMsg::SendData(chan) => {
+                                            canvas_paint_thread.canvas(canvas_id).send_data(chan);
+                                        },
+                                    },
+                                    Err(e) => {
+                                        warn!("Error on CanvasPaintThread receive ({})", e);
+                                    },
+                                }
                             }
-                        }
-                        recv(create_receiver) -> msg => {
-                            match msg {
-                                Ok(ConstellationCanvasMsg::Create {
-                                    id_sender: creator,
-                                    size,
-                                    webrender_sender: webrenderer_api_sender,
-                                    antialias
-                                }) => {
-                                    let canvas_id = canvas_paint_thread.create_canvas(
+                            recv(create_receiver) -> msg => {
+                                match msg {
+                                    Ok(ConstellationCanvasMsg::Create {
+                                        id_sender: creator,
                                         size,
-                                        webrenderer_api_sender,
-                                        antialias,
-                                    );
-                                    creator.send(canvas_id).unwrap();
-                                },
-                                Ok(ConstellationCanvasMsg::Exit) => break,
-                                Err(e) => {
-                                    warn!("Error on CanvasPaintThread receive ({})", e);
-                                },
+                                        webrender_sender: webrenderer_api_sender,
+                                        antialias
+                                    }) => {
+                                        let canvas_id = canvas_paint_thread.create_canvas(
+                                            size,
+                                            webrenderer_api_sender,
+                                            antialias,
+                                        );
+                                        creator.send(canvas_id).unwrap();
+                                    },
+                                    Ok(ConstellationCanvasMsg::CollectReports(reports_chan)) => {
+                                        canvas_paint_thread.collect_reports(reports_chan);
+                                    },
+                                    Ok(ConstellationCanvasMsg::Exit) => break,
+                                    Err(e) => {
+                                        warn!("Error on CanvasPaintThread receive ({})", e);
+                                    },
+                                }
                             }
                         }
-                    }
-                }
+                    },
+                    "canvas-reporter".to_owned(),
+                    reporter_sender,
+                    ConstellationCanvasMsg::CollectReports,
+                );
             })
             .expect("Thread spawning failed");
 
         (create_sender, ipc_sender)
     }
 
+    fn collect_reports(&self, reports_chan: ReportsChan) {
+        let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+        // The fonts, and the shaped text caches they own, are shared by all canvases.
+        reports_chan.send(vec![Report {
+            path: path!["canvas-thread", "font-context"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size: self.font_context.size_of(&mut ops),
+        }]);
+    }
+
     pub fn create_canvas(
         &mut self,
         size: Size2D<u64>,
@@ -130,11 +177,21 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::BeginPath => self.canvas(canvas_id).begin_path(),
             Canvas2dMsg::ClosePath => self.canvas(canvas_id).close_path(),
             Canvas2dMsg::Fill => self.canvas(canvas_id).fill(),
+            Canvas2dMsg::FillPath(ref segments) => self.canvas(canvas_id).fill_path(segments),
             Canvas2dMsg::Stroke => self.canvas(canvas_id).stroke(),
+            Canvas2dMsg::StrokePath(ref segments) => self.canvas(canvas_id).stroke_path(segments),
             Canvas2dMsg::Clip => self.canvas(canvas_id).clip(),
+            Canvas2dMsg::ClipPath(ref segments) => self.canvas(canvas_id).clip_path(segments),
             Canvas2dMsg::IsPointInPath(x, y, fill_rule, chan) => self
                 .canvas(canvas_id)
                 .is_point_in_path(x, y, fill_rule, chan),
+            Canvas2dMsg::IsPointInPathPath(ref segments, x, y, fill_rule, chan) => self
+                .canvas(canvas_id)
+                .is_point_in_path_path(segments, x, y, fill_rule, chan),
+            Canvas2dMsg::MeasureText(text, chan) => {
+                let metrics = self.measure_text(canvas_id, &text);
+                chan.send(metrics).unwrap()
+            },
             Canvas2dMsg::DrawImage(
                 imagedata,
                 image_size,
@@ -175,6 +232,9 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::MoveTo(ref point) => self.canvas(canvas_id).move_to(point),
             Canvas2dMsg::LineTo(ref point) => self.canvas(canvas_id).line_to(point),
             Canvas2dMsg::Rect(ref rect) => self.canvas(canvas_id).rect(rect),
+            Canvas2dMsg::RoundRect(ref rect, ref radii) => {
+                self.canvas(canvas_id).round_rect(rect, radii)
+            },
             Canvas2dMsg::QuadraticCurveTo(ref cp, ref pt) => {
                 self.canvas(canvas_id).quadratic_curve_to(cp, pt)
             },
@@ -198,6 +258,10 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::SetLineCap(cap) => self.canvas(canvas_id).set_line_cap(cap),
             Canvas2dMsg::SetLineJoin(join) => self.canvas(canvas_id).set_line_join(join),
             Canvas2dMsg::SetMiterLimit(limit) => self.canvas(canvas_id).set_miter_limit(limit),
+            Canvas2dMsg::SetLineDash(segments) => self.canvas(canvas_id).set_line_dash(segments),
+            Canvas2dMsg::SetLineDashOffset(offset) => {
+                self.canvas(canvas_id).set_line_dash_offset(offset)
+            },
             Canvas2dMsg::SetTransform(ref matrix) => self.canvas(canvas_id).set_transform(matrix),
             Canvas2dMsg::SetGlobalAlpha(alpha) => self.canvas(canvas_id).set_global_alpha(alpha),
             Canvas2dMsg::SetGlobalComposition(op) => {
@@ -219,10 +283,117 @@ impl<'a> CanvasPaintThread<'a> {
             },
             Canvas2dMsg::SetShadowBlur(value) => self.canvas(canvas_id).set_shadow_blur(value),
             Canvas2dMsg::SetShadowColor(color) => self.canvas(canvas_id).set_shadow_color(color),
+            Canvas2dMsg::SetFont(font_style) => self.canvas(canvas_id).set_font(font_style),
+            Canvas2dMsg::SetFilter(filters) => self.canvas(canvas_id).set_filter(filters),
         }
     }
 
     fn canvas(&mut self, canvas_id: CanvasId) -> &mut CanvasData<'a> {
         self.canvases.get_mut(&canvas_id).expect("Bogus canvas id")
     }
+
+    /// Shapes `text` with the canvas font and measures the resulting glyph runs,
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-measuretext>
+    fn measure_text(&mut self, canvas_id: CanvasId, text: &str) -> TextMetrics {
+        let font_style = self
+            .canvas(canvas_id)
+            .font_style()
+            .unwrap_or_else(|| self.font_style.clone());
+        let font_group = self.font_context.font_group(font_style);
+        let mut font_group = font_group.borrow_mut();
+
+        // The font-wide metrics come from the first available font.
+        let first_font = match font_group.first(&mut self.font_context) {
+            Some(font) => font,
+            None => return TextMetrics::default(),
+        };
+
+        // Split the text into runs that are each rendered with a single font.
+        let mut runs: Vec<(FontRef, usize)> = vec![];
+        for (index, character) in text.char_indices() {
+            let font = font_group
+                .find_by_codepoint(&mut self.font_context, character)
+                .unwrap_or_else(|| first_font.clone());
+            if runs
+                .last()
+                .map_or(true, |(last, _)| !Rc::ptr_eq(last, &font))
+            {
+                runs.push((font, index));
+            }
+        }
+
+        let options = ShapingOptions {
+            letter_spacing: None,
+            word_spacing: (Au(0), NotNan::new(0.0).unwrap()),
+            script: Script::Common,
+            flags: ShapingFlags::empty(),
+        };
+        let mut advance = Au(0);
+        let (mut ascent, mut descent) = (Au(0), Au(0));
+        for (i, &(ref font, start)) in runs.iter().enumerate() {
+            let end = runs.get(i + 1).map_or(text.len(), |&(_, end)| end);
+            let mut font = font.borrow_mut();
+            let glyphs = font.shape_text(&text[start..end], &options);
+            let run = RunMetrics::new(
+                glyphs.total_advance(),
+                font.metrics.ascent,
+                font.metrics.descent,
+            );
+            advance += run.advance_width;
+            ascent = ascent.max(run.ascent);
+            descent = descent.max(run.descent);
+        }
+
+        // FIXME(#125): the actual bounding box uses the ascent and descent of
+        // the runs, as glyph extents are not available from gfx.
+        let font = first_font.borrow();
+        let metrics = &font.metrics;
+        let em_height_ascent = if metrics.ascent + metrics.descent > Au(0) {
+            metrics.em_size.scale_by(
+                metrics.ascent.to_f32_px() / (metrics.ascent + metrics.descent).to_f32_px(),
+            )
+        } else {
+            metrics.em_size
+        };
+        TextMetrics {
+            width: advance.to_f32_px(),
+            actual_bounding_box_left: 0.,
+            actual_bounding_box_right: advance.to_f32_px(),
+            font_bounding_box_ascent: metrics.ascent.to_f32_px(),
+            font_bounding_box_descent: metrics.descent.to_f32_px(),
+            actual_bounding_box_ascent: ascent.to_f32_px(),
+            actual_bounding_box_descent: descent.to_f32_px(),
+            em_height_ascent: em_height_ascent.to_f32_px(),
+            em_height_descent: (metrics.em_size - em_height_ascent).to_f32_px(),
+            // Fonts do not expose a hanging baseline, so approximate it from
+            // the ascent like other engines do.
+            hanging_baseline: metrics.ascent.to_f32_px() * 0.8,
+            alphabetic_baseline: 0.,
+            ideographic_baseline: -metrics.descent.to_f32_px(),
+        }
+    }
+}
+
+/// The computed style of `10px sans-serif`, the initial value of the
+/// `font` attribute of canvas contexts.
+fn default_font_style() -> FontStyleStruct {
+    let mut style = FontStyleStruct {
+        font_family: FontFamily {
+            families: FontFamilyList::new(Box::new([SingleFontFamily::Generic(
+                GenericFontFamily::SansSerif,
+            )])),
+            is_system_font: false,
+        },
+        font_style: FontStyle::Normal,
+        font_variant_caps: FontVariantCaps::Normal,
+        font_weight: FontWeight::normal(),
+        font_size: FontSize {
+            size: NonNegativeLength::new(10.),
+            keyword_info: None,
+        },
+        font_stretch: FontStretch::hundred(),
+        hash: 0,
+    };
+    style.compute_font_hash();
+    style
 }
@@ -0,0 +1,239 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The filter functions of the `filter` attribute of a 2D context, applied
+//! in order to premultiplied BGRA8 pixels.
+//!
+//! <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+
+use canvas_traits::canvas::FilterFunction;
+use euclid::default::Size2D;
+
+/// A color matrix of the RGB channels, whose last column is an offset.
+type ColorMatrix = [[f32; 4]; 3];
+
+pub fn apply_filters(pixels: &mut [u8], size: Size2D<i32>, filters: &[FilterFunction]) {
+    for filter in filters {
+        match *filter {
+            FilterFunction::Blur(std_deviation) => blur(pixels, size, std_deviation),
+            FilterFunction::DropShadow {
+                offset_x,
+                offset_y,
+                blur: radius,
+                color,
+            } => {
+                let alpha = color.alpha_f32();
+                let mut shadow = vec![0; pixels.len()];
+                let (width, height) = (size.width as isize, size.height as isize);
+                let (dx, dy) = (offset_x.round() as isize, offset_y.round() as isize);
+                for y in 0..height {
+                    for x in 0..width {
+                        let (source_x, source_y) = (x - dx, y - dy);
+                        if source_x < 0 || source_x >= width || source_y < 0 || source_y >= height {
+                            continue;
+                        }
+                        let source_alpha = pixels[((source_y * width + source_x) * 4 + 3) as usize];
+                        let a = source_alpha as f32 / 255. * alpha;
+                        let i = ((y * width + x) * 4) as usize;
+                        shadow[i] = (color.blue as f32 * a).round() as u8;
+                        shadow[i + 1] = (color.green as f32 * a).round() as u8;
+                        shadow[i + 2] = (color.red as f32 * a).round() as u8;
+                        shadow[i + 3] = (255. * a).round() as u8;
+                    }
+                }
+                blur(&mut shadow, size, radius / 2.);
+                // The source is composited over its shadow.
+                for (source, shadow) in pixels.chunks_mut(4).zip(shadow.chunks(4)) {
+                    let remaining = 255 - source[3] as u32;
+                    for c in 0..4 {
+                        source[c] = source[c]
+                            .saturating_add(((shadow[c] as u32 * remaining + 127) / 255) as u8);
+                    }
+                }
+            },
+            FilterFunction::Opacity(amount) => {
+                let amount = amount.max(0.).min(1.);
+                for channel in pixels.iter_mut() {
+                    *channel = (*channel as f32 * amount).round() as u8;
+                }
+            },
+            ref filter => apply_color_matrix(pixels, &color_matrix(filter)),
+        }
+    }
+}
+
+fn color_matrix(filter: &FilterFunction) -> ColorMatrix {
+    match *filter {
+        FilterFunction::Brightness(a) => [[a, 0., 0., 0.], [0., a, 0., 0.], [0., 0., a, 0.]],
+        FilterFunction::Contrast(a) => {
+            let offset = 0.5 - 0.5 * a;
+            [
+                [a, 0., 0., offset],
+                [0., a, 0., offset],
+                [0., 0., a, offset],
+            ]
+        },
+        FilterFunction::Grayscale(a) => {
+            let s = 1. - a.max(0.).min(1.);
+            [
+                [
+                    0.2126 + 0.7874 * s,
+                    0.7152 - 0.7152 * s,
+                    0.0722 - 0.0722 * s,
+                    0.,
+                ],
+                [
+                    0.2126 - 0.2126 * s,
+                    0.7152 + 0.2848 * s,
+                    0.0722 - 0.0722 * s,
+                    0.,
+                ],
+                [
+                    0.2126 - 0.2126 * s,
+                    0.7152 - 0.7152 * s,
+                    0.0722 + 0.9278 * s,
+                    0.,
+                ],
+            ]
+        },
+        FilterFunction::HueRotate(degrees) => {
+            let (s, c) = degrees.to_radians().sin_cos();
+            [
+                [
+                    0.213 + c * 0.787 - s * 0.213,
+                    0.715 - c * 0.715 - s * 0.715,
+                    0.072 - c * 0.072 + s * 0.928,
+                    0.,
+                ],
+                [
+                    0.213 - c * 0.213 + s * 0.143,
+                    0.715 + c * 0.285 + s * 0.140,
+                    0.072 - c * 0.072 - s * 0.283,
+                    0.,
+                ],
+                [
+                    0.213 - c * 0.213 - s * 0.787,
+                    0.715 - c * 0.715 + s * 0.715,
+                    0.072 + c * 0.928 + s * 0.072,
+                    0.,
+                ],
+            ]
+        },
+        FilterFunction::Invert(a) => {
+            let a = a.max(0.).min(1.);
+            let slope = 1. - 2. * a;
+            [[slope, 0., 0., a], [0., slope, 0., a], [0., 0., slope, a]]
+        },
+        FilterFunction::Saturate(s) => [
+            [0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.],
+            [0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.],
+            [0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.],
+        ],
+        FilterFunction::Sepia(a) => {
+            let s = 1. - a.max(0.).min(1.);
+            [
+                [0.393 + 0.607 * s, 0.769 - 0.769 * s, 0.189 - 0.189 * s, 0.],
+                [0.349 - 0.349 * s, 0.686 + 0.314 * s, 0.168 - 0.168 * s, 0.],
+                [0.272 - 0.272 * s, 0.534 - 0.534 * s, 0.131 + 0.869 * s, 0.],
+            ]
+        },
+        FilterFunction::Blur(..) |
+        FilterFunction::DropShadow { .. } |
+        FilterFunction::Opacity(..) => unreachable!("Not a color matrix filter function."),
+    }
+}
+
+/// Applies `matrix` to the unpremultiplied colors of `pixels`.
+fn apply_color_matrix(pixels: &mut [u8], matrix: &ColorMatrix) {
+    for pixel in pixels.chunks_mut(4) {
+        let alpha = pixel[3] as f32 / 255.;
+        if alpha == 0. {
+            continue;
+        }
+        let rgb = [
+            pixel[2] as f32 / 255. / alpha,
+            pixel[1] as f32 / 255. / alpha,
+            pixel[0] as f32 / 255. / alpha,
+        ];
+        let mut filtered = [0.; 3];
+        for (row, value) in matrix.iter().zip(filtered.iter_mut()) {
+            *value = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2] + row[3];
+        }
+        for (i, value) in filtered.iter().enumerate() {
+            pixel[2 - i] = (value.max(0.).min(1.) * alpha * 255.).round() as u8;
+        }
+    }
+}
+
+/// Approximates a gaussian blur of `std_deviation` pixels with three box
+/// blurs, as the spec suggests,
+/// <https://drafts.fxtf.org/filter-effects/#feGaussianBlurElement>
+fn blur(pixels: &mut [u8], size: Size2D<i32>, std_deviation: f32) {
+    if std_deviation <= 0. {
+        return;
+    }
+    let d = (std_deviation * 3. * (2. * std::f32::consts::PI).sqrt() / 4. + 0.5).floor() as usize;
+    if d <= 1 {
+        return;
+    }
+    // The extents of each box before and after the current pixel.
+    let boxes = if d % 2 == 1 {
+        [(d / 2, d / 2), (d / 2, d / 2), (d / 2, d / 2)]
+    } else {
+        [(d / 2, d / 2 - 1), (d / 2 - 1, d / 2), (d / 2, d / 2)]
+    };
+    let (width, height) = (size.width as usize, size.height as usize);
+    let mut scratch = vec![0; pixels.len()];
+    for &(before, after) in &boxes {
+        box_blur(
+            pixels,
+            &mut scratch,
+            width,
+            height,
+            4,
+            width * 4,
+            (before, after),
+        );
+        box_blur(
+            &scratch,
+            pixels,
+            height,
+            width,
+            width * 4,
+            4,
+            (before, after),
+        );
+    }
+}
+
+/// Blurs `lines` lines of `length` pixels each, whose pixels are `step`
+/// bytes apart and whose lines are `stride` bytes apart, from `source` into
+/// `destination`. Pixels out of bounds are transparent black.
+fn box_blur(
+    source: &[u8],
+    destination: &mut [u8],
+    length: usize,
+    lines: usize,
+    step: usize,
+    stride: usize,
+    (before, after): (usize, usize),
+) {
+    let divisor = (before + after + 1) as u32;
+    for line in 0..lines {
+        let start = line * stride;
+        for channel in 0..4 {
+            let value = |i: usize| source[start + i * step + channel] as u32;
+            let mut sum: u32 = (0..after.min(length)).map(value).sum();
+            for i in 0..length {
+                if i + after < length {
+                    sum += value(i + after);
+                }
+                destination[start + i * step + channel] = ((sum + divisor / 2) / divisor) as u8;
+                if i >= before {
+                    sum -= value(i - before);
+                }
+            }
+        }
+    }
+}
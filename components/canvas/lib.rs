@@ -7,11 +7,14 @@
 use canvas_traits::canvas::CanvasId;
 use crossbeam_channel::Sender;
 use euclid::default::Size2D;
+use profile_traits::mem::ReportsChan;
 
 #[macro_use]
 extern crate bitflags;
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate profile_traits;
 
 #[cfg(feature = "canvas2d-azure")]
 mod azure_backend;
@@ -23,6 +26,7 @@ pub use webgl_mode::WebGLComm;
 
 pub mod canvas_data;
 pub mod canvas_paint_thread;
+mod filters;
 mod webgl_limits;
 mod webgl_mode;
 pub mod webgl_thread;
@@ -34,5 +38,6 @@ pub enum ConstellationCanvasMsg {
         webrender_sender: webrender_api::RenderApiSender,
         antialias: bool,
     },
+    CollectReports(ReportsChan),
     Exit,
 }
@@ -105,6 +105,8 @@ impl<'a> CanvasPaintState<'a> {
                 b: 0,
                 a: 0,
             }),
+            font_style: None,
+            filters: vec![],
         }
     }
 }
@@ -118,7 +120,8 @@ impl Pattern<'_> {
                 match p {
                     LinearGradient(g, ..) |
                     RadialGradient(g, ..) |
-                    TwoCircleRadialGradient(g, ..) => g.stops.is_empty(),
+                    TwoCircleRadialGradient(g, ..) |
+                    SweepGradient(g, ..) => g.stops.is_empty(),
                     _ => false,
                 }
             },
@@ -152,6 +155,16 @@ impl<'a> StrokeOptions<'a> {
             StrokeOptions::Raqote(options, _) => options.cap = val.to_raqote_style(),
         }
     }
+    pub fn set_line_dash(&mut self, segments: Vec<f32>) {
+        match self {
+            StrokeOptions::Raqote(options, _) => options.dash_array = segments,
+        }
+    }
+    pub fn set_line_dash_offset(&mut self, offset: f32) {
+        match self {
+            StrokeOptions::Raqote(options, _) => options.dash_offset = offset,
+        }
+    }
     pub fn as_raqote(&self) -> &raqote::StrokeStyle {
         match self {
             StrokeOptions::Raqote(options, _) => options,
@@ -684,6 +697,20 @@ impl<'a> ToRaqoteSource<'a> for FillOrStrokeStyle {
                     raqote::Spread::Pad,
                 ))
             },
+            ConicGradient(style) => {
+                let stops = style.stops.into_iter().map(|s| s.to_raqote()).collect();
+                let gradient = raqote::Gradient { stops };
+                let center = Point2D::new(style.x as f32, style.y as f32);
+                // The start angle is relative to the x axis, like raqote's.
+                let start_angle = style.start_angle.to_degrees() as f32;
+                Some(raqote::Source::new_sweep_gradient(
+                    gradient,
+                    center,
+                    start_angle,
+                    start_angle + 360.,
+                    raqote::Spread::Pad,
+                ))
+            },
             Surface(ref surface) => {
                 let data = &surface.surface_data[..];
                 Some(raqote::Source::Image(
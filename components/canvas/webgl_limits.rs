@@ -22,6 +22,29 @@ impl GLLimitsDetect for GLLimits {
         let max_vertex_texture_image_units = gl.get_integer(gl::MAX_VERTEX_TEXTURE_IMAGE_UNITS);
         let max_transform_feedback_separate_attribs =
             gl.get_integer(gl::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS);
+        let max_uniform_buffer_bindings = gl.get_integer(gl::MAX_UNIFORM_BUFFER_BINDINGS);
+        let uniform_buffer_offset_alignment = gl.get_integer(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT);
+
+        // These limits only exist in GLES 3 and desktop GL 3 contexts, they are
+        // left to 0 elsewhere and only exposed to WebGL 2 content.
+        let max_3d_texture_size = gl.try_get_integer(gl::MAX_3D_TEXTURE_SIZE).unwrap_or(0);
+        let max_array_texture_layers = gl
+            .try_get_integer(gl::MAX_ARRAY_TEXTURE_LAYERS)
+            .unwrap_or(0);
+        let max_vertex_output_vectors = gl
+            .try_get_integer(gl::MAX_VERTEX_OUTPUT_COMPONENTS)
+            .unwrap_or(0) /
+            4;
+        let max_fragment_input_vectors = gl
+            .try_get_integer(gl::MAX_FRAGMENT_INPUT_COMPONENTS)
+            .unwrap_or(0) /
+            4;
+        let min_program_texel_offset = gl
+            .try_get_integer(gl::MIN_PROGRAM_TEXEL_OFFSET)
+            .unwrap_or(0) as i32;
+        let max_program_texel_offset = gl
+            .try_get_integer(gl::MAX_PROGRAM_TEXEL_OFFSET)
+            .unwrap_or(0);
 
         // TODO: better value for this?
         let max_client_wait_timeout_webgl = std::time::Duration::new(1, 0);
@@ -70,6 +93,14 @@ impl GLLimitsDetect for GLLimits {
             max_vertex_uniform_vectors,
             max_client_wait_timeout_webgl,
             max_transform_feedback_separate_attribs,
+            max_uniform_buffer_bindings,
+            uniform_buffer_offset_alignment,
+            max_3d_texture_size,
+            max_array_texture_layers,
+            max_vertex_output_vectors,
+            max_fragment_input_vectors,
+            min_program_texel_offset,
+            max_program_texel_offset,
         }
     }
 }
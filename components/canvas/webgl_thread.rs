@@ -6,6 +6,7 @@ use crate::webgl_limits::GLLimitsDetect;
 use byteorder::{ByteOrder, NativeEndian, WriteBytesExt};
 use canvas_traits::webgl;
 use canvas_traits::webgl::ActiveAttribInfo;
+use canvas_traits::webgl::ActiveUniformBlockInfo;
 use canvas_traits::webgl::ActiveUniformInfo;
 use canvas_traits::webgl::AlphaTreatment;
 use canvas_traits::webgl::DOMToTextureCommand;
@@ -1090,6 +1091,19 @@ impl WebGLImpl {
                 width,
                 height,
             ) => gl.copy_tex_sub_image_2d(target, level, xoffset, yoffset, x, y, width, height),
+            WebGLCommand::CopyTexSubImage3D(
+                target,
+                level,
+                xoffset,
+                yoffset,
+                zoffset,
+                x,
+                y,
+                width,
+                height,
+            ) => gl.copy_tex_sub_image_3d(
+                target, level, xoffset, yoffset, zoffset, x, y, width, height,
+            ),
             WebGLCommand::CullFace(mode) => gl.cull_face(mode),
             WebGLCommand::DepthFunc(func) => gl.depth_func(func),
             WebGLCommand::DepthMask(flag) => {
@@ -1245,6 +1259,50 @@ impl WebGLImpl {
             WebGLCommand::TransformFeedbackVaryings(program, ref varyings, buffer_mode) => {
                 gl.transform_feedback_varyings(program.get(), varyings.as_slice(), buffer_mode);
             },
+            WebGLCommand::BindBufferBase(target, index, id) => {
+                gl.bind_buffer_base(target, index, id.map_or(0, WebGLBufferId::get))
+            },
+            WebGLCommand::BindBufferRange(target, index, id, offset, size) => gl.bind_buffer_range(
+                target,
+                index,
+                id.map_or(0, WebGLBufferId::get),
+                offset as isize,
+                size as isize,
+            ),
+            WebGLCommand::GetUniformBlockIndex(program_id, ref name, ref sender) => {
+                let name = to_name_in_compiled_shader(name);
+                let index = gl.get_uniform_block_index(program_id.get(), &name);
+                sender.send(index).unwrap();
+            },
+            WebGLCommand::GetUniformIndices(program_id, ref names, ref sender) => {
+                let names = names
+                    .iter()
+                    .map(|name| to_name_in_compiled_shader(name))
+                    .collect::<Vec<_>>();
+                let name_strs = names.iter().map(|name| name.as_str()).collect::<Vec<_>>();
+                let indices = gl.get_uniform_indices(program_id.get(), &name_strs);
+                sender.send(indices).unwrap();
+            },
+            WebGLCommand::GetActiveUniforms(program_id, ref indices, pname, ref sender) => {
+                let results = gl.get_active_uniforms_iv(program_id.get(), indices.clone(), pname);
+                sender.send(results).unwrap();
+            },
+            WebGLCommand::GetActiveUniformBlockName(program_id, block_idx, ref sender) => {
+                let name = gl.get_active_uniform_block_name(program_id.get(), block_idx);
+                sender.send(from_name_in_compiled_shader(&name)).unwrap();
+            },
+            WebGLCommand::GetActiveUniformBlockParameter(
+                program_id,
+                block_idx,
+                pname,
+                ref sender,
+            ) => {
+                let results = gl.get_active_uniform_block_iv(program_id.get(), block_idx, pname);
+                sender.send(results).unwrap();
+            },
+            WebGLCommand::UniformBlockBinding(program_id, block_idx, block_binding) => {
+                gl.uniform_block_binding(program_id.get(), block_idx, block_binding)
+            },
             WebGLCommand::GetFramebufferAttachmentParameter(
                 target,
                 attachment,
@@ -1418,6 +1476,78 @@ impl WebGLImpl {
                     &pixels,
                 );
             },
+            WebGLCommand::TexImage3D {
+                target,
+                level,
+                internal_format,
+                width,
+                height,
+                depth,
+                format,
+                data_type,
+                unpacking_alignment,
+                ref data,
+            } => {
+                gl.pixel_store_i(gl::UNPACK_ALIGNMENT, unpacking_alignment as i32);
+                gl.tex_image_3d(
+                    target,
+                    level as i32,
+                    internal_format as i32,
+                    width as i32,
+                    height as i32,
+                    depth as i32,
+                    0,
+                    format,
+                    data_type,
+                    Some(&*data),
+                );
+            },
+            WebGLCommand::TexSubImage3D {
+                target,
+                level,
+                xoffset,
+                yoffset,
+                zoffset,
+                width,
+                height,
+                depth,
+                format,
+                data_type,
+                unpacking_alignment,
+                ref data,
+            } => {
+                gl.pixel_store_i(gl::UNPACK_ALIGNMENT, unpacking_alignment as i32);
+                gl.tex_sub_image_3d(
+                    target,
+                    level as i32,
+                    xoffset,
+                    yoffset,
+                    zoffset,
+                    width as i32,
+                    height as i32,
+                    depth as i32,
+                    format,
+                    data_type,
+                    &*data,
+                );
+            },
+            WebGLCommand::TexStorage2D(target, levels, internal_format, width, height) => gl
+                .tex_storage_2d(
+                    target,
+                    levels as i32,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                ),
+            WebGLCommand::TexStorage3D(target, levels, internal_format, width, height, depth) => gl
+                .tex_storage_3d(
+                    target,
+                    levels as i32,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                    depth as i32,
+                ),
             WebGLCommand::CompressedTexImage2D {
                 target,
                 level,
@@ -1883,6 +2013,7 @@ impl WebGLImpl {
                 linked: false,
                 active_attribs: vec![].into(),
                 active_uniforms: vec![].into(),
+                active_uniform_blocks: vec![].into(),
                 transform_feedback_length: Default::default(),
                 transform_feedback_mode: Default::default(),
             };
@@ -1938,6 +2069,29 @@ impl WebGLImpl {
             })
             .collect::<Vec<_>>()
             .into();
+
+        let mut num_active_uniform_blocks = [0];
+        unsafe {
+            gl.get_program_iv(
+                program.get(),
+                gl::ACTIVE_UNIFORM_BLOCKS,
+                &mut num_active_uniform_blocks,
+            );
+        }
+        let active_uniform_blocks = (0..num_active_uniform_blocks[0] as u32)
+            .map(|i| {
+                let name = gl.get_active_uniform_block_name(program.get(), i);
+                let size =
+                    gl.get_active_uniform_block_iv(program.get(), i, gl::UNIFORM_BLOCK_DATA_SIZE)
+                        [0];
+                ActiveUniformBlockInfo {
+                    name: from_name_in_compiled_shader(&name),
+                    size,
+                }
+            })
+            .collect::<Vec<_>>()
+            .into();
+
         let mut transform_feedback_length = [0];
         unsafe {
             gl.get_program_iv(
@@ -1958,6 +2112,7 @@ impl WebGLImpl {
             linked: true,
             active_attribs,
             active_uniforms,
+            active_uniform_blocks,
             transform_feedback_length: transform_feedback_length[0],
             transform_feedback_mode: transform_feedback_mode[0],
         }
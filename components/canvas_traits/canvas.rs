@@ -8,6 +8,7 @@ use ipc_channel::ipc::{IpcBytesReceiver, IpcBytesSender, IpcSender, IpcSharedMem
 use serde_bytes::ByteBuf;
 use std::default::Default;
 use std::str::FromStr;
+use style::properties::style_structs::Font as FontStyleStruct;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum FillRule {
@@ -42,28 +43,36 @@ pub enum Canvas2dMsg {
     BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
     ClearRect(Rect<f32>),
     Clip,
+    ClipPath(Vec<PathSegment>),
     ClosePath,
     Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
     Fill,
+    FillPath(Vec<PathSegment>),
     FillText(String, f64, f64, Option<f64>),
     FillRect(Rect<f32>),
     GetImageData(Rect<u64>, Size2D<u64>, IpcBytesSender),
     IsPointInPath(f64, f64, FillRule, IpcSender<bool>),
+    IsPointInPathPath(Vec<PathSegment>, f64, f64, FillRule, IpcSender<bool>),
     LineTo(Point2D<f32>),
+    MeasureText(String, IpcSender<TextMetrics>),
     MoveTo(Point2D<f32>),
     PutImageData(Rect<u64>, IpcBytesReceiver),
     QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
     Rect(Rect<f32>),
+    RoundRect(Rect<f32>, CornerRadii),
     RestoreContext,
     SaveContext,
     StrokeRect(Rect<f32>),
     Stroke,
+    StrokePath(Vec<PathSegment>),
     SetFillStyle(FillOrStrokeStyle),
     SetStrokeStyle(FillOrStrokeStyle),
     SetLineWidth(f32),
     SetLineCap(LineCapStyle),
     SetLineJoin(LineJoinStyle),
     SetMiterLimit(f32),
+    SetLineDash(Vec<f32>),
+    SetLineDashOffset(f32),
     SetGlobalAlpha(f32),
     SetGlobalComposition(CompositionOrBlending),
     SetTransform(Transform2D<f32>),
@@ -71,6 +80,75 @@ pub enum Canvas2dMsg {
     SetShadowOffsetY(f64),
     SetShadowBlur(f64),
     SetShadowColor(RGBA),
+    SetFont(FontStyleStruct),
+    SetFilter(Vec<FilterFunction>),
+}
+
+/// A filter function of the `filter` attribute, with its lengths in pixels
+/// and its angles in degrees,
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum FilterFunction {
+    Blur(f32),
+    Brightness(f32),
+    Contrast(f32),
+    DropShadow {
+        offset_x: f32,
+        offset_y: f32,
+        blur: f32,
+        color: RGBA,
+    },
+    Grayscale(f32),
+    HueRotate(f32),
+    Invert(f32),
+    Opacity(f32),
+    Saturate(f32),
+    Sepia(f32),
+}
+
+/// The measurements of a run of text, in CSS pixels, relative to the
+/// alphabetic baseline and the start of the text,
+/// <https://html.spec.whatwg.org/multipage/#textmetrics>
+#[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub actual_bounding_box_left: f32,
+    pub actual_bounding_box_right: f32,
+    pub font_bounding_box_ascent: f32,
+    pub font_bounding_box_descent: f32,
+    pub actual_bounding_box_ascent: f32,
+    pub actual_bounding_box_descent: f32,
+    pub em_height_ascent: f32,
+    pub em_height_descent: f32,
+    pub hanging_baseline: f32,
+    pub alphabetic_baseline: f32,
+    pub ideographic_baseline: f32,
+}
+
+/// The radii of the corners of a rounded rectangle.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct CornerRadii {
+    pub upper_left: Size2D<f32>,
+    pub upper_right: Size2D<f32>,
+    pub lower_right: Size2D<f32>,
+    pub lower_left: Size2D<f32>,
+}
+
+/// A path building operation recorded by a `Path2D`, which the paint
+/// thread replays when the path is used for drawing.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub enum PathSegment {
+    AddPath(Vec<PathSegment>, Transform2D<f32>),
+    Arc(Point2D<f32>, f32, f32, f32, bool),
+    ArcTo(Point2D<f32>, Point2D<f32>, f32),
+    BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
+    ClosePath,
+    Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
+    LineTo(Point2D<f32>),
+    MoveTo(Point2D<f32>),
+    QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
+    Rect(Rect<f32>),
+    RoundRect(Rect<f32>, CornerRadii),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -149,6 +227,30 @@ impl RadialGradientStyle {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ConicGradientStyle {
+    pub start_angle: f64,
+    pub x: f64,
+    pub y: f64,
+    pub stops: Vec<CanvasGradientStop>,
+}
+
+impl ConicGradientStyle {
+    pub fn new(
+        start_angle: f64,
+        x: f64,
+        y: f64,
+        stops: Vec<CanvasGradientStop>,
+    ) -> ConicGradientStyle {
+        ConicGradientStyle {
+            start_angle,
+            x,
+            y,
+            stops,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SurfaceStyle {
     pub surface_data: ByteBuf,
@@ -178,6 +280,7 @@ pub enum FillOrStrokeStyle {
     Color(RGBA),
     LinearGradient(LinearGradientStyle),
     RadialGradient(RadialGradientStyle),
+    ConicGradient(ConicGradientStyle),
     Surface(SurfaceStyle),
 }
 
@@ -265,6 +265,7 @@ pub enum WebGLCommand {
     CompileShader(WebGLShaderId, String),
     CopyTexImage2D(u32, i32, u32, i32, i32, i32, i32, i32),
     CopyTexSubImage2D(u32, i32, i32, i32, i32, i32, i32, i32),
+    CopyTexSubImage3D(u32, i32, i32, i32, i32, i32, i32, i32, i32),
     CreateBuffer(WebGLSender<Option<WebGLBufferId>>),
     CreateFramebuffer(WebGLSender<Option<WebGLTransparentFramebufferId>>),
     CreateRenderbuffer(WebGLSender<Option<WebGLRenderbufferId>>),
@@ -302,6 +303,14 @@ pub enum WebGLCommand {
     ResumeTransformFeedback(),
     GetTransformFeedbackVarying(WebGLProgramId, u32, WebGLSender<(i32, u32, String)>),
     TransformFeedbackVaryings(WebGLProgramId, Vec<String>, u32),
+    BindBufferBase(u32, u32, Option<WebGLBufferId>),
+    BindBufferRange(u32, u32, Option<WebGLBufferId>, i64, i64),
+    GetUniformBlockIndex(WebGLProgramId, String, WebGLSender<u32>),
+    GetUniformIndices(WebGLProgramId, Vec<String>, WebGLSender<Vec<u32>>),
+    GetActiveUniforms(WebGLProgramId, Vec<u32>, u32, WebGLSender<Vec<i32>>),
+    GetActiveUniformBlockName(WebGLProgramId, u32, WebGLSender<String>),
+    GetActiveUniformBlockParameter(WebGLProgramId, u32, u32, WebGLSender<Vec<i32>>),
+    UniformBlockBinding(WebGLProgramId, u32, u32),
     PolygonOffset(f32, f32),
     RenderbufferStorage(u32, u32, i32, i32),
     ReadPixels(Rect<u32>, u32, u32, IpcBytesSender),
@@ -381,6 +390,34 @@ pub enum WebGLCommand {
         pixel_format: Option<PixelFormat>,
         data: TruncatedDebug<IpcSharedMemory>,
     },
+    TexImage3D {
+        target: u32,
+        level: u32,
+        internal_format: u32,
+        width: u32,
+        height: u32,
+        depth: u32,
+        format: u32,
+        data_type: u32,
+        unpacking_alignment: u32,
+        data: TruncatedDebug<IpcSharedMemory>,
+    },
+    TexSubImage3D {
+        target: u32,
+        level: u32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        width: u32,
+        height: u32,
+        depth: u32,
+        format: u32,
+        data_type: u32,
+        unpacking_alignment: u32,
+        data: TruncatedDebug<IpcSharedMemory>,
+    },
+    TexStorage2D(u32, u32, u32, u32, u32),
+    TexStorage3D(u32, u32, u32, u32, u32, u32),
     CompressedTexImage2D {
         target: u32,
         level: u32,
@@ -671,6 +708,8 @@ pub struct ProgramLinkInfo {
     pub active_attribs: Box<[ActiveAttribInfo]>,
     /// The list of active uniforms.
     pub active_uniforms: Box<[ActiveUniformInfo]>,
+    /// The list of active uniform blocks.
+    pub active_uniform_blocks: Box<[ActiveUniformBlockInfo]>,
     /// The number of varying variables
     pub transform_feedback_length: i32,
     /// The buffer mode used when transform feedback is active
@@ -701,6 +740,15 @@ pub struct ActiveUniformInfo {
     pub type_: u32,
 }
 
+/// Description of a single uniform block.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ActiveUniformBlockInfo {
+    /// The name of the uniform block.
+    pub name: String,
+    /// The size of the uniform block.
+    pub size: i32,
+}
+
 impl ActiveUniformInfo {
     pub fn name(&self) -> Cow<str> {
         if self.size.is_some() {
@@ -992,4 +1040,12 @@ pub struct GLLimits {
     pub max_vertex_uniform_vectors: u32,
     pub max_client_wait_timeout_webgl: std::time::Duration,
     pub max_transform_feedback_separate_attribs: u32,
+    pub max_uniform_buffer_bindings: u32,
+    pub uniform_buffer_offset_alignment: u32,
+    pub max_3d_texture_size: u32,
+    pub max_array_texture_layers: u32,
+    pub max_vertex_output_vectors: u32,
+    pub max_fragment_input_vectors: u32,
+    pub min_program_texel_offset: i32,
+    pub max_program_texel_offset: u32,
 }
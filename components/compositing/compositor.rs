@@ -27,10 +27,12 @@ use num_traits::FromPrimitive;
 #[cfg(feature = "gl")]
 use pixels::PixelFormat;
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, TouchEvent, WheelEvent};
+use script_traits::CompositorEvent::{
+    MouseButtonEvent, MouseMoveEvent, MouseMovementEvent, PenEvent, TouchEvent, WheelEvent,
+};
 use script_traits::{AnimationState, AnimationTickType, ConstellationMsg, LayoutControlMsg};
 use script_traits::{
-    MouseButton, MouseEventType, ScrollState, TouchEventType, TouchId, WheelDelta,
+    MouseButton, MouseEventType, PenState, ScrollState, TouchEventType, TouchId, WheelDelta,
 };
 use script_traits::{UntrustedNodeAddress, WindowSizeData, WindowSizeType};
 use servo_geometry::{DeviceIndependentPixel, FramebufferUintLength};
@@ -43,7 +45,9 @@ use std::rc::Rc;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use time::{now, precise_time_ns, precise_time_s};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePoint, LayoutVector2D};
+use webrender_api::units::{
+    DeviceIntPoint, DeviceIntSize, DevicePoint, DeviceVector2D, LayoutPoint, LayoutVector2D,
+};
 use webrender_api::{self, HitTestFlags, HitTestResult, ScrollLocation};
 use webvr_traits::WebVRMainThreadHeartbeat;
 
@@ -165,6 +169,9 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Whether we're waiting on a recomposite after dispatching a scroll.
     waiting_for_results_of_scroll: bool,
 
+    /// Smooth scrolls requested by script that are still in progress.
+    smooth_scrolls: Vec<SmoothScroll>,
+
     /// Used by the logic that determines when it is safe to output an
     /// image for the reftest framework.
     ready_to_save_state: ReadyState,
@@ -223,6 +230,36 @@ struct ScrollZoomEvent {
     event_count: u32,
 }
 
+/// The duration of a smooth scroll, in seconds.
+const SMOOTH_SCROLL_DURATION: f64 = 0.3;
+
+/// A scroll node moving smoothly from one scroll position to another.
+#[derive(Clone, Copy)]
+struct SmoothScroll {
+    /// The scroll node being scrolled.
+    scroll_id: webrender_api::ExternalScrollId,
+    /// The scroll position at the start of the animation.
+    start: LayoutPoint,
+    /// The scroll position at the end of the animation.
+    end: LayoutPoint,
+    /// The time at which the animation started.
+    start_time: f64,
+}
+
+impl SmoothScroll {
+    /// Returns the scroll position at the given time, and whether the
+    /// animation has finished.
+    fn position_at(&self, now: f64) -> (LayoutPoint, bool) {
+        let progress = ((now - self.start_time) / SMOOTH_SCROLL_DURATION).min(1.0) as f32;
+        if progress >= 1.0 {
+            return (self.end, true);
+        }
+        // An ease-in-out curve, so that the scroll neither starts nor stops abruptly.
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        (self.start.lerp(self.end, eased), false)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 enum CompositionRequest {
     NoCompositingNecessary,
@@ -298,6 +335,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             touch_handler: TouchHandler::new(),
             pending_scroll_zoom_events: Vec::new(),
             waiting_for_results_of_scroll: false,
+            smooth_scrolls: Vec::new(),
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
             page_zoom: Scale::new(1.0),
@@ -433,6 +471,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.touch_handler.on_event_processed(result);
             },
 
+            (Msg::SmoothScroll(scroll_id, position), ShutdownState::NotShuttingDown) => {
+                self.start_smooth_scroll(scroll_id, position);
+            },
+
+            (Msg::StopSmoothScroll(scroll_id, position), ShutdownState::NotShuttingDown) => {
+                self.stop_smooth_scroll(scroll_id, position);
+            },
+
             (Msg::CreatePng(rect, reply), ShutdownState::NotShuttingDown) => {
                 let res = self.composite_specific_target(CompositeTarget::WindowAndPng, rect);
                 if let Err(ref e) = res {
@@ -684,12 +730,15 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         let old_coords = self.embedder_coordinates;
         self.embedder_coordinates = self.window.get_coordinates();
 
-        // A size change could also mean a resolution change.
-        if self.embedder_coordinates.hidpi_factor != old_coords.hidpi_factor {
+        // A size change could also mean a resolution change, such as when
+        // the window moves to a monitor with a different scale factor.
+        let hidpi_factor_changed =
+            self.embedder_coordinates.hidpi_factor != old_coords.hidpi_factor;
+        if hidpi_factor_changed {
             self.update_zoom_transform();
         }
 
-        if self.embedder_coordinates.viewport == old_coords.viewport {
+        if self.embedder_coordinates.viewport == old_coords.viewport && !hidpi_factor_changed {
             return;
         }
 
@@ -776,10 +825,16 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             return;
         }
 
+        self.cursor_pos = cursor;
         let results = self.hit_test_at_point(cursor);
         if let Some(item) = results.items.first() {
             let node_address = Some(UntrustedNodeAddress(item.tag.0 as *const c_void));
-            let event = MouseMoveEvent(Some(item.point_in_viewport.to_untyped()), node_address, 0);
+            let event = MouseMoveEvent(
+                Some(item.point_in_viewport.to_untyped()),
+                node_address,
+                Some(item.point_relative_to_item.to_untyped()),
+                0,
+            );
             let pipeline_id = PipelineId::from_webrender(item.pipeline);
             let msg = ConstellationMsg::ForwardEvent(pipeline_id, event);
             if let Err(e) = self.constellation_chan.send(msg) {
@@ -789,6 +844,21 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Forwards the movement of a locked mouse pointer to the pipeline under
+    /// the point where the pointer was locked.
+    pub fn on_mouse_window_movement(&mut self, movement: DeviceVector2D) {
+        let results = self.hit_test_at_point(self.cursor_pos);
+        if let Some(item) = results.items.first() {
+            let movement = movement / self.device_pixels_per_page_px();
+            let event = MouseMovementEvent(movement.to_untyped(), 0);
+            let pipeline_id = PipelineId::from_webrender(item.pipeline);
+            let msg = ConstellationMsg::ForwardEvent(pipeline_id, event);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending event to constellation failed ({:?}).", e);
+            }
+        }
+    }
+
     fn send_touch_event(
         &self,
         event_type: TouchEventType,
@@ -847,6 +917,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn on_touch_move(&mut self, identifier: TouchId, point: DevicePoint) {
+        // Script sees every move of an active touch point, even the ones that
+        // also scroll or zoom the page.
+        if self.touch_handler.is_active(identifier) {
+            self.send_touch_event(TouchEventType::Move, identifier, point);
+        }
+
         match self.touch_handler.on_touch_move(identifier, point) {
             TouchAction::Scroll(delta) => self.on_scroll_window_event(
                 ScrollLocation::Delta(LayoutVector2D::from_untyped(delta.to_untyped())),
@@ -863,9 +939,6 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                     event_count: 1,
                 });
             },
-            TouchAction::DispatchEvent => {
-                self.send_touch_event(TouchEventType::Move, identifier, point);
-            },
             _ => {},
         }
     }
@@ -884,6 +957,32 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.send_touch_event(TouchEventType::Cancel, identifier, point);
     }
 
+    /// Pens don't scroll or zoom the page, so their events are always
+    /// forwarded to script.
+    pub fn on_pen_event(
+        &mut self,
+        event_type: TouchEventType,
+        identifier: TouchId,
+        point: DevicePoint,
+        state: PenState,
+    ) {
+        let results = self.hit_test_at_point(point);
+        if let Some(item) = results.items.first() {
+            let event = PenEvent(
+                event_type,
+                identifier,
+                item.point_in_viewport.to_untyped(),
+                Some(UntrustedNodeAddress(item.tag.0 as *const c_void)),
+                state,
+            );
+            let pipeline_id = PipelineId::from_webrender(item.pipeline);
+            let msg = ConstellationMsg::ForwardEvent(pipeline_id, event);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending event to constellation failed ({:?}).", e);
+            }
+        }
+    }
+
     /// <http://w3c.github.io/touch-events/#mouse-events>
     fn simulate_mouse_click(&mut self, p: DevicePoint) {
         let button = MouseButton::Left;
@@ -915,6 +1014,11 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn on_scroll_window_event(&mut self, scroll_location: ScrollLocation, cursor: DeviceIntPoint) {
+        // Scrolling by the user interrupts any smooth scroll requested by script.
+        let interrupted: Vec<_> = self.smooth_scrolls.drain(..).collect();
+        for smooth_scroll in interrupted {
+            self.notify_smooth_scroll_ended(smooth_scroll.scroll_id);
+        }
         self.pending_scroll_zoom_events.push(ScrollZoomEvent {
             magnification: 1.0,
             scroll_location: scroll_location,
@@ -1011,7 +1115,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 pipeline_ids.push(*pipeline_id);
             }
         }
+        self.tick_smooth_scrolls();
         let animation_state = if pipeline_ids.is_empty() &&
+            self.smooth_scrolls.is_empty() &&
             !self.webvr_heartbeats_racing() &&
             !self.webxr_main_thread.running()
         {
@@ -1025,6 +1131,101 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    fn start_smooth_scroll(
+        &mut self,
+        scroll_id: webrender_api::ExternalScrollId,
+        end: LayoutPoint,
+    ) {
+        // WebRender reports scroll offsets, which are the negated scroll positions.
+        let start = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .find(|state| state.id == scroll_id)
+            .map_or(LayoutPoint::zero(), |state| {
+                LayoutPoint::zero() - state.scroll_offset
+            });
+        self.smooth_scrolls
+            .retain(|smooth_scroll| smooth_scroll.scroll_id != scroll_id);
+        self.smooth_scrolls.push(SmoothScroll {
+            scroll_id,
+            start,
+            end,
+            start_time: precise_time_s(),
+        });
+        self.process_animations();
+    }
+
+    /// Stops the smooth scroll of the given scroll node, if any, and moves
+    /// the node to `position` right away.
+    fn stop_smooth_scroll(
+        &mut self,
+        scroll_id: webrender_api::ExternalScrollId,
+        position: LayoutPoint,
+    ) {
+        self.smooth_scrolls
+            .retain(|smooth_scroll| smooth_scroll.scroll_id != scroll_id);
+
+        let mut txn = webrender_api::Transaction::new();
+        txn.scroll_node_with_id(
+            position,
+            scroll_id,
+            webrender_api::ScrollClamping::ToContentBounds,
+        );
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        self.send_viewport_rects();
+    }
+
+    /// Moves every smooth scroll in progress to its current position, and
+    /// reports the final scroll positions once scrolls finish.
+    fn tick_smooth_scrolls(&mut self) {
+        if self.smooth_scrolls.is_empty() {
+            return;
+        }
+
+        let now = precise_time_s();
+        let mut txn = webrender_api::Transaction::new();
+        let mut finished = vec![];
+        self.smooth_scrolls.retain(|smooth_scroll| {
+            let (position, done) = smooth_scroll.position_at(now);
+            txn.scroll_node_with_id(
+                position,
+                smooth_scroll.scroll_id,
+                webrender_api::ScrollClamping::ToContentBounds,
+            );
+            if done {
+                finished.push(smooth_scroll.scroll_id);
+            }
+            !done
+        });
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+
+        if finished.is_empty() {
+            return;
+        }
+        self.send_viewport_rects();
+        for scroll_id in finished {
+            self.notify_smooth_scroll_ended(scroll_id);
+        }
+    }
+
+    /// Tells script that a smooth scroll it requested is over, so that it can
+    /// fire `scrollend` events.
+    fn notify_smooth_scroll_ended(&self, scroll_id: webrender_api::ExternalScrollId) {
+        let pipeline_id = scroll_id.pipeline_id().from_webrender();
+        let msg = ConstellationMsg::SmoothScrollEnded(pipeline_id, scroll_id);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!(
+                "Sending smooth scroll end to constellation failed ({:?}).",
+                e
+            );
+        }
+    }
+
     fn webvr_heartbeats_racing(&self) -> bool {
         self.webvr_heartbeats.iter().any(|hb| hb.heart_racing())
     }
@@ -1144,6 +1345,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
     // Check if any pipelines currently have active animations or animation callbacks.
     fn animations_active(&self) -> bool {
+        if !self.smooth_scrolls.is_empty() {
+            return true;
+        }
+
         for (_, details) in &self.pipeline_details {
             // If animations are currently running, then don't bother checking
             // with the constellation if the output image is stable.
@@ -20,7 +20,7 @@ use std::fmt::{Debug, Error, Formatter};
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webrender_api;
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPoint};
 use webvr_traits::WebVRMainThreadHeartbeat;
 
 /// Sends messages to the compositor.
@@ -81,6 +81,10 @@ pub enum Msg {
     Recomposite(CompositingReason),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Script has requested a smooth scroll of the given scroll node to a new position.
+    SmoothScroll(webrender_api::ExternalScrollId, LayoutPoint),
+    /// Script has stopped a smooth scroll of the given scroll node, moving it to a new position.
+    StopSmoothScroll(webrender_api::ExternalScrollId, LayoutPoint),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(Option<Rect<f32, CSSPixel>>, IpcSender<Option<Image>>),
     /// Alerts the compositor that the viewport has been constrained in some manner
@@ -129,6 +133,8 @@ impl Debug for Msg {
             Msg::SetFrameTree(..) => write!(f, "SetFrameTree"),
             Msg::Recomposite(..) => write!(f, "Recomposite"),
             Msg::TouchEventProcessed(..) => write!(f, "TouchEventProcessed"),
+            Msg::SmoothScroll(..) => write!(f, "SmoothScroll"),
+            Msg::StopSmoothScroll(..) => write!(f, "StopSmoothScroll"),
             Msg::CreatePng(..) => write!(f, "CreatePng"),
             Msg::ViewportConstrained(..) => write!(f, "ViewportConstrained"),
             Msg::IsReadyToSaveImageReply(..) => write!(f, "IsReadyToSaveImageReply"),
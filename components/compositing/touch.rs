@@ -62,8 +62,6 @@ pub enum TouchAction {
     Scroll(Vector2D<f32, DevicePixel>),
     /// Zoom by a magnification factor and scroll by the provided offset.
     Zoom(f32, Vector2D<f32, DevicePixel>),
-    /// Send a JavaScript event to content.
-    DispatchEvent,
     /// Don't do anything.
     NoAction,
 }
@@ -77,18 +75,23 @@ impl TouchHandler {
     }
 
     pub fn on_touch_down(&mut self, id: TouchId, point: Point2D<f32, DevicePixel>) {
+        // The embedder may reuse the identifier of a touch point whose end we never saw.
+        self.active_touch_points.retain(|t| t.id != id);
         let point = TouchPoint::new(id, point);
         self.active_touch_points.push(point);
 
+        // Script can prevent the default action of the gesture, such as a pinch zoom,
+        // from the touchstart event of every new touch point.
         self.state = match self.state {
-            Nothing => WaitingForScript,
-            Touching | Panning => Pinching,
-            WaitingForScript => WaitingForScript,
             DefaultPrevented => DefaultPrevented,
-            Pinching | MultiTouch => MultiTouch,
+            _ => WaitingForScript,
         };
     }
 
+    pub fn is_active(&self, id: TouchId) -> bool {
+        self.active_touch_points.iter().any(|t| t.id == id)
+    }
+
     pub fn on_touch_move(&mut self, id: TouchId, point: Point2D<f32, DevicePixel>) -> TouchAction {
         let idx = match self.active_touch_points.iter_mut().position(|t| t.id == id) {
             Some(i) => i,
@@ -116,7 +119,7 @@ impl TouchHandler {
                 let delta = point - old_point;
                 TouchAction::Scroll(delta)
             },
-            DefaultPrevented => TouchAction::DispatchEvent,
+            DefaultPrevented => TouchAction::NoAction,
             Pinching => {
                 let (d0, c0) = self.pinch_distance_and_center();
                 self.active_touch_points[idx].point = point;
@@ -199,17 +202,21 @@ impl TouchHandler {
         }
     }
 
+    /// Script reports the result of every touchstart event, and of the touchmove
+    /// events whose default action was prevented.
     pub fn on_event_processed(&mut self, result: EventResult) {
-        if let WaitingForScript = self.state {
-            self.state = match result {
-                EventResult::DefaultPrevented => DefaultPrevented,
-                EventResult::DefaultAllowed => match self.touch_count() {
-                    1 => Touching,
-                    2 => Pinching,
-                    _ => MultiTouch,
-                },
-            }
-        }
+        self.state = match (self.state, result) {
+            (WaitingForScript, EventResult::DefaultAllowed) => match self.touch_count() {
+                1 => Touching,
+                2 => Pinching,
+                _ => MultiTouch,
+            },
+            // The touch sequence ended, or it already scrolls the page and can no
+            // longer be canceled.
+            (Nothing, _) | (Panning, _) => self.state,
+            (_, EventResult::DefaultPrevented) => DefaultPrevented,
+            (state, EventResult::DefaultAllowed) => state,
+        };
     }
 
     fn touch_count(&self) -> usize {
@@ -4,13 +4,16 @@
 
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
-use embedder_traits::EventLoopWaker;
+use embedder_traits::{EventLoopWaker, ScreenOrientationState};
 use euclid::Scale;
 #[cfg(feature = "gl")]
 use gleam::gl;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
-use script_traits::{MediaSessionActionType, MouseButton, TouchEventType, TouchId, WheelDelta};
+use script_traits::{
+    GamepadEvent, MediaSessionActionType, MouseButton, PenState, TouchEventType, TouchId,
+    WheelDelta,
+};
 use servo_geometry::DeviceIndependentPixel;
 use servo_media::player::context::{GlApi, GlContext, NativeDisplay};
 use servo_url::ServoUrl;
@@ -20,8 +23,8 @@ use std::rc::Rc;
 use std::time::Duration;
 use style_traits::DevicePixel;
 
-use webrender_api::units::DevicePoint;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use webrender_api::units::{DevicePoint, DeviceVector2D};
 use webrender_api::ScrollLocation;
 use webvr::VRServiceManager;
 use webvr_traits::WebVRMainThreadHeartbeat;
@@ -64,8 +67,13 @@ pub enum WindowEvent {
     MouseWindowEventClass(MouseWindowEvent),
     /// Sent when a mouse move.
     MouseWindowMoveEventClass(DevicePoint),
+    /// Sent when the mouse moves while the pointer is locked, with the distance
+    /// it moved by.
+    MouseWindowMovement(DeviceVector2D),
     /// Touch event: type, identifier, point
     Touch(TouchEventType, TouchId, DevicePoint),
+    /// Pen event: type, identifier, point, pen state
+    Pen(TouchEventType, TouchId, DevicePoint, PenState),
     /// Sent when user moves the mouse wheel.
     Wheel(WheelDelta, DevicePoint),
     /// Sent when the user scrolls. The first point is the delta and the second point is the
@@ -83,6 +91,10 @@ pub enum WindowEvent {
     Quit,
     /// Sent when the user exits from fullscreen mode
     ExitFullScreen(TopLevelBrowsingContextId),
+    /// Sent when the user unlocks the mouse pointer
+    ExitPointerLock(TopLevelBrowsingContextId),
+    /// Sent when the screen is rotated
+    ChangeScreenOrientation(ScreenOrientationState),
     /// Sent when a key input state changes
     Keyboard(KeyboardEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
@@ -96,6 +108,10 @@ pub enum WindowEvent {
     /// Make a top level browsing context visible, hiding the previous
     /// visible one.
     SelectBrowser(TopLevelBrowsingContextId),
+    /// Sent when a top level browsing context can no longer be seen, for
+    /// example because its window is minimized or occluded, or when it can be
+    /// seen again.
+    ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
     /// Toggles a debug flag in WebRender
     ToggleWebRenderDebug(WebRenderDebugOption),
     /// Capture current WebRender
@@ -105,6 +121,8 @@ pub enum WindowEvent {
     /// Sent when the user triggers a media action through the UA exposed media UI
     /// (play, pause, seek, etc.).
     MediaSessionAction(MediaSessionActionType),
+    /// Sent when a gamepad is connected or disconnected, or when one of its inputs changes.
+    Gamepad(GamepadEvent),
 }
 
 impl Debug for WindowEvent {
@@ -118,7 +136,9 @@ impl Debug for WindowEvent {
             WindowEvent::LoadUrl(..) => write!(f, "LoadUrl"),
             WindowEvent::MouseWindowEventClass(..) => write!(f, "Mouse"),
             WindowEvent::MouseWindowMoveEventClass(..) => write!(f, "MouseMove"),
+            WindowEvent::MouseWindowMovement(..) => write!(f, "MouseMovement"),
             WindowEvent::Touch(..) => write!(f, "Touch"),
+            WindowEvent::Pen(..) => write!(f, "Pen"),
             WindowEvent::Wheel(..) => write!(f, "Wheel"),
             WindowEvent::Scroll(..) => write!(f, "Scroll"),
             WindowEvent::Zoom(..) => write!(f, "Zoom"),
@@ -131,11 +151,15 @@ impl Debug for WindowEvent {
             WindowEvent::SendError(..) => write!(f, "SendError"),
             WindowEvent::CloseBrowser(..) => write!(f, "CloseBrowser"),
             WindowEvent::SelectBrowser(..) => write!(f, "SelectBrowser"),
+            WindowEvent::ChangeBrowserVisibility(..) => write!(f, "ChangeBrowserVisibility"),
             WindowEvent::ToggleWebRenderDebug(..) => write!(f, "ToggleWebRenderDebug"),
             WindowEvent::CaptureWebRender => write!(f, "CaptureWebRender"),
             WindowEvent::ToggleSamplingProfiler(..) => write!(f, "ToggleSamplingProfiler"),
             WindowEvent::ExitFullScreen(..) => write!(f, "ExitFullScreen"),
+            WindowEvent::ExitPointerLock(..) => write!(f, "ExitPointerLock"),
+            WindowEvent::ChangeScreenOrientation(..) => write!(f, "ChangeScreenOrientation"),
             WindowEvent::MediaSessionAction(..) => write!(f, "MediaSessionAction"),
+            WindowEvent::Gamepad(..) => write!(f, "Gamepad"),
         }
     }
 }
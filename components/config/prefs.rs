@@ -162,20 +162,40 @@ mod gen {
                 webgpu: {
                     enabled: bool,
                 },
+                audioworklet: {
+                    enabled: bool,
+                },
                 bluetooth: {
                     enabled: bool,
                     testing: {
                         enabled: bool,
                     }
                 },
+                caches: {
+                    enabled: bool,
+                },
                 canvas_text: {
                     #[serde(rename = "dom.canvas-text.enabled")]
                     enabled: bool,
                 },
+                clipboard: {
+                    enabled: bool,
+                },
                 composition_event: {
                     #[serde(rename = "dom.compositionevent.enabled")]
                     enabled: bool,
                 },
+                cookie_store: {
+                    enabled: bool,
+                },
+                cross_origin_isolation: {
+                    enabled: bool,
+                },
+                crypto: {
+                    subtle: {
+                        enabled: bool,
+                    },
+                },
                 custom_elements: {
                     #[serde(rename = "dom.customelements.enabled")]
                     enabled: bool,
@@ -184,6 +204,15 @@ mod gen {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                encrypted_media: {
+                    enabled: bool,
+                },
+                event_listener: {
+                    passive_by_default: bool,
+                },
+                fontface: {
+                    enabled: bool,
+                },
                 forcetouch: {
                     enabled: bool,
                 },
@@ -193,6 +222,9 @@ mod gen {
                 gamepad: {
                     enabled: bool,
                 },
+                media_source: {
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
@@ -207,15 +239,33 @@ mod gen {
                 mutation_observer: {
                     enabled: bool,
                 },
+                navigation: {
+                    enabled: bool,
+                },
+                navigator: {
+                    hardware_concurrency: i64,
+                },
+                notifications: {
+                    enabled: bool,
+                },
                 offscreen_canvas: {
                     enabled: bool,
                 },
+                payment_request: {
+                    enabled: bool,
+                },
                 permissions: {
                     enabled: bool,
                     testing: {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                reporting: {
+                    enabled: bool,
+                },
+                scheduler: {
+                    enabled: bool,
+                },
                 serviceworker: {
                     enabled: bool,
                     timeout_seconds: i64,
@@ -228,6 +278,15 @@ mod gen {
                 shadowdom: {
                     enabled: bool,
                 },
+                sharedworker: {
+                    enabled: bool,
+                },
+                speech_synthesis: {
+                    enabled: bool,
+                },
+                storage_manager: {
+                    enabled: bool,
+                },
                 svg: {
                     enabled: bool,
                 },
@@ -277,6 +336,12 @@ mod gen {
                     #[serde(default)]
                     enabled: bool,
                 },
+                web_animations: {
+                    enabled: bool,
+                },
+                webauthn: {
+                    enabled: bool,
+                },
                 webgl: {
                     dom_to_texture: {
                         enabled: bool,
@@ -440,6 +505,14 @@ mod gen {
                 columns: {
                     enabled: bool,
                 },
+                parallel_box_construction: {
+                    max_depth: i64,
+                    threshold: i64,
+                },
+                #[serde(rename = "layout.prefers-color-scheme")]
+                prefers_color_scheme: String,
+                #[serde(rename = "layout.prefers-reduced-motion")]
+                prefers_reduced_motion: bool,
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 viewport: {
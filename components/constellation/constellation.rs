@@ -112,8 +112,9 @@ use compositing::compositor_thread::Msg as ToCompositorMsg;
 use compositing::SendableFrameTree;
 use crossbeam_channel::{after, never, unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
+use embedder_traits::ScreenOrientationState;
 use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy, EventLoopWaker};
-use embedder_traits::{MediaSessionEvent, MediaSessionPlaybackState};
+use embedder_traits::{MediaSessionEvent, MediaSessionPlaybackState, PermissionName};
 use euclid::{default::Size2D as UntypedSize2D, Size2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
@@ -125,14 +126,16 @@ use keyboard_types::KeyboardEvent;
 use layout_traits::LayoutThreadFactory;
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use media::{GLPlayerThreads, WindowGLContext};
-use msg::constellation_msg::{BackgroundHangMonitorRegister, HangMonitorAlert, SamplerControlMsg};
+use msg::constellation_msg::{
+    BackgroundHangMonitorRegister, BroadcastChannelRouterId, HangMonitorAlert, SamplerControlMsg,
+};
 use msg::constellation_msg::{
     BrowsingContextGroupId, BrowsingContextId, HistoryStateId, PipelineId,
     TopLevelBrowsingContextId,
 };
 use msg::constellation_msg::{
     MessagePortId, MessagePortRouterId, PipelineNamespace, PipelineNamespaceId,
-    PipelineNamespaceRequest, TraversalDirection,
+    PipelineNamespaceRequest, SharedWorkerId, TraversalDirection,
 };
 use net_traits::pub_domains::reg_host;
 use net_traits::request::RequestBuilder;
@@ -140,23 +143,25 @@ use net_traits::storage_thread::{StorageThreadMsg, StorageType};
 use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
 use profile_traits::mem;
 use profile_traits::time;
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
+use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, MouseMovementEvent};
 use script_traits::{webdriver_msg, LogEntry, ScriptToConstellationChan, ServiceWorkerMsg};
 use script_traits::{
-    AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, CompositorEvent,
+    AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg,
+    CompositorEvent,
 };
 use script_traits::{
     ConstellationControlMsg, ConstellationMsg as FromCompositorMsg, DiscardBrowsingContext,
 };
 use script_traits::{DocumentActivity, DocumentState, LayoutControlMsg, LoadData, LoadOrigin};
+use script_traits::{GamepadEvent, MediaSessionActionType, MouseEventType};
 use script_traits::{HistoryEntryReplacement, IFrameSizeMsg, WindowSizeData, WindowSizeType};
 use script_traits::{
     IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, TimerSchedulerMsg,
 };
 use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory};
-use script_traits::{MediaSessionActionType, MouseEventType};
 use script_traits::{MessagePortMsg, PortMessageTask, StructuredSerializedData};
 use script_traits::{SWManagerMsg, ScopeThings, UpdatePipelineIdReason, WebDriverCommandMsg};
+use script_traits::{SharedWorkerControlMsg, SharedWorkerKey};
 use serde::{Deserialize, Serialize};
 use servo_config::{opts, pref};
 use servo_rand::{random, Rng, ServoRng, SliceRandom};
@@ -214,6 +219,24 @@ struct MessagePortInfo {
     entangled_with: Option<MessagePortId>,
 }
 
+/// Info related to a shared worker tracked by the constellation.
+struct SharedWorkerInfo {
+    /// Tells this worker apart from the workers that ran, or will run, under
+    /// the same key.
+    id: SharedWorkerId,
+
+    /// The channel used to connect new `SharedWorker` objects to the worker.
+    control_sender: IpcSender<SharedWorkerControlMsg>,
+
+    /// The pipelines that have connected to the worker, which is terminated
+    /// once all of them have exited.
+    connected_pipelines: HashSet<PipelineId>,
+
+    /// The event loop whose script process runs the worker thread,
+    /// the worker is gone along with it.
+    host: Weak<EventLoop>,
+}
+
 /// Servo supports tabs (referred to as browsers), so `Constellation` needs to
 /// store browser specific data for bookkeeping.
 struct Browser {
@@ -398,6 +421,15 @@ pub struct Constellation<Message, LTF, STF> {
     /// A map of router-id to ipc-sender, to route messages to ports.
     message_port_routers: HashMap<MessagePortRouterId, IpcSender<MessagePortMsg>>,
 
+    /// A map of broadcast routers to their IPC sender.
+    broadcast_routers: HashMap<BroadcastChannelRouterId, IpcSender<BroadcastMsg>>,
+
+    /// A map of origin to a map of channel-name to a list of relevant routers.
+    broadcast_channels: HashMap<ImmutableOrigin, HashMap<String, Vec<BroadcastChannelRouterId>>>,
+
+    /// The running shared workers.
+    shared_workers: HashMap<SharedWorkerKey, SharedWorkerInfo>,
+
     /// The set of all the pipelines in the browser.  (See the `pipeline` module
     /// for more details.)
     pipelines: HashMap<PipelineId, Pipeline>,
@@ -427,6 +459,9 @@ pub struct Constellation<Message, LTF, STF> {
     /// The size of the top-level window.
     window_size: WindowSizeData,
 
+    /// The orientation of the screen, once the embedder reported it.
+    screen_orientation: Option<ScreenOrientationState>,
+
     /// Bits of state used to interact with the webdriver implementation
     webdriver: WebDriverData,
 
@@ -907,7 +942,10 @@ where
                     }),
                 );
 
-                let (canvas_chan, ipc_canvas_chan) = CanvasPaintThread::start();
+                let (canvas_chan, ipc_canvas_chan) = CanvasPaintThread::start(
+                    state.font_cache_thread.clone(),
+                    state.mem_profiler_chan.clone(),
+                );
 
                 let mut constellation: Constellation<Message, LTF, STF> = Constellation {
                     namespace_receiver,
@@ -940,6 +978,9 @@ where
                     browsing_context_group_next_id: Default::default(),
                     message_ports: HashMap::new(),
                     message_port_routers: HashMap::new(),
+                    broadcast_routers: HashMap::new(),
+                    broadcast_channels: HashMap::new(),
+                    shared_workers: HashMap::new(),
                     pipelines: HashMap::new(),
                     browsing_contexts: HashMap::new(),
                     pending_changes: vec![],
@@ -949,6 +990,7 @@ where
                     time_profiler_chan: state.time_profiler_chan,
                     mem_profiler_chan: state.mem_profiler_chan,
                     window_size: initial_window_size,
+                    screen_orientation: None,
                     phantom: PhantomData,
                     webdriver: WebDriverData::new(),
                     timer_scheduler: TimerScheduler::new(),
@@ -1115,6 +1157,27 @@ where
         }
     }
 
+    /// The origin of the document that a load creates a pipeline for.
+    /// This must match the logic in the script thread when determining the proper origin.
+    fn origin_of_load(
+        &self,
+        load_data: &LoadData,
+        parent_pipeline_id: Option<PipelineId>,
+    ) -> ImmutableOrigin {
+        if load_data.url.as_str() != "about:blank" {
+            return load_data.url.origin();
+        }
+        parent_pipeline_id
+            .and_then(|pipeline_id| self.pipelines.get(&pipeline_id))
+            .or_else(|| {
+                load_data
+                    .creator_pipeline_id
+                    .and_then(|pipeline_id| self.pipelines.get(&pipeline_id))
+            })
+            .map(|pipeline| pipeline.origin.clone())
+            .unwrap_or_else(ImmutableOrigin::new_opaque)
+    }
+
     /// Helper function for creating a pipeline
     fn new_pipeline(
         &mut self,
@@ -1141,6 +1204,7 @@ where
             pipeline_id, browsing_context_id
         );
 
+        let origin = self.origin_of_load(&load_data, parent_pipeline_id);
         let (event_loop, host) = match sandbox {
             IFrameSandboxState::IFrameSandboxed => (None, None),
             IFrameSandboxState::IFrameUnsandboxed => {
@@ -1222,6 +1286,7 @@ where
             },
             event_loop,
             load_data,
+            origin,
             prev_visibility: is_visible,
             webrender_api_sender: self.webrender_api_ipc_sender.clone(),
             webrender_image_api_sender: self.webrender_image_api_sender.clone(),
@@ -1644,6 +1709,9 @@ where
             FromCompositorMsg::SelectBrowser(top_level_browsing_context_id) => {
                 self.send_frame_tree(top_level_browsing_context_id);
             },
+            FromCompositorMsg::ChangeBrowserVisibility(top_level_browsing_context_id, visible) => {
+                self.handle_change_browser_visibility(top_level_browsing_context_id, visible);
+            },
             // Handle a forward or back request
             FromCompositorMsg::TraverseHistory(top_level_browsing_context_id, direction) => {
                 self.handle_traverse_history_msg(top_level_browsing_context_id, direction);
@@ -1654,6 +1722,9 @@ where
             FromCompositorMsg::TickAnimation(pipeline_id, tick_type) => {
                 self.handle_tick_animation(pipeline_id, tick_type)
             },
+            FromCompositorMsg::SmoothScrollEnded(pipeline_id, scroll_id) => {
+                self.handle_smooth_scroll_ended(pipeline_id, scroll_id)
+            },
             FromCompositorMsg::WebDriverCommand(command) => {
                 self.handle_webdriver_msg(command);
             },
@@ -1687,9 +1758,18 @@ where
             FromCompositorMsg::ExitFullScreen(top_level_browsing_context_id) => {
                 self.handle_exit_fullscreen_msg(top_level_browsing_context_id);
             },
+            FromCompositorMsg::ExitPointerLock(top_level_browsing_context_id) => {
+                self.handle_exit_pointer_lock_msg(top_level_browsing_context_id);
+            },
+            FromCompositorMsg::ChangeScreenOrientation(orientation) => {
+                self.handle_change_screen_orientation_msg(orientation);
+            },
             FromCompositorMsg::MediaSessionAction(action) => {
                 self.handle_media_session_action_msg(action);
             },
+            FromCompositorMsg::Gamepad(event) => {
+                self.handle_gamepad_msg(event);
+            },
         }
     }
 
@@ -1738,6 +1818,25 @@ where
             FromScriptMsg::EntanglePorts(port1, port2) => {
                 self.handle_entangle_messageports(port1, port2);
             },
+            FromScriptMsg::NewBroadcastChannelRouter(router_id, ipc_sender, origin) => {
+                self.handle_new_broadcast_channel_router(router_id, ipc_sender, origin);
+            },
+            FromScriptMsg::RemoveBroadcastChannelRouter(router_id, origin) => {
+                self.handle_remove_broadcast_channel_router(router_id, origin);
+            },
+            FromScriptMsg::NewBroadcastChannelNameInRouter(router_id, channel_name, origin) => {
+                self.handle_new_broadcast_channel_name_in_router(router_id, channel_name, origin);
+            },
+            FromScriptMsg::RemoveBroadcastChannelNameInRouter(router_id, channel_name, origin) => {
+                self.handle_remove_broadcast_channel_name_in_router(
+                    router_id,
+                    channel_name,
+                    origin,
+                );
+            },
+            FromScriptMsg::ScheduleBroadcast(router_id, message) => {
+                self.handle_schedule_broadcast(router_id, message);
+            },
             FromScriptMsg::ForwardToEmbedder(embedder_msg) => {
                 self.embedder_proxy
                     .send((Some(source_top_ctx_id), embedder_msg));
@@ -1855,12 +1954,23 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetScreenAvailSize(send));
             },
+            FromScriptMsg::GetScreenOrientation(send) => {
+                if let Err(e) = send.send(self.screen_orientation) {
+                    warn!("Failed to send screen orientation ({}).", e);
+                }
+            },
             FromScriptMsg::LogEntry(thread_name, entry) => {
                 self.handle_log_entry(Some(source_top_ctx_id), thread_name, entry);
             },
             FromScriptMsg::TouchEventProcessed(result) => self
                 .compositor_proxy
                 .send(ToCompositorMsg::TouchEventProcessed(result)),
+            FromScriptMsg::SmoothScroll(scroll_id, position) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::SmoothScroll(scroll_id, position)),
+            FromScriptMsg::StopSmoothScroll(scroll_id, position) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::StopSmoothScroll(scroll_id, position)),
             FromScriptMsg::GetBrowsingContextInfo(pipeline_id, sender) => {
                 let result = self
                     .pipelines
@@ -1903,6 +2013,32 @@ where
             FromScriptMsg::RegisterServiceWorker(scope_things, scope) => {
                 self.handle_register_serviceworker(scope_things, scope);
             },
+            FromScriptMsg::ConnectToSharedWorker(
+                key,
+                id,
+                data,
+                control_sender,
+                response_sender,
+            ) => {
+                self.handle_connect_to_shared_worker(
+                    source_pipeline_id,
+                    key,
+                    id,
+                    data,
+                    control_sender,
+                    response_sender,
+                );
+            },
+            FromScriptMsg::SharedWorkerClosed(key, id) => {
+                // A worker replacing the closed one may already run under its key.
+                let is_closed_worker = self
+                    .shared_workers
+                    .get(&key)
+                    .map_or(false, |info| info.id == id);
+                if is_closed_worker {
+                    self.shared_workers.remove(&key);
+                }
+            },
             FromScriptMsg::ForwardDOMMessage(msg_vec, scope_url) => {
                 if let Some(ref mgr) = self.swmanager_chan {
                     let _ = mgr.send(ServiceWorkerMsg::ForwardDOMMessage(msg_vec, scope_url));
@@ -1920,6 +2056,9 @@ where
                     new_value,
                 );
             },
+            FromScriptMsg::BroadcastPermissionChange(origin, name) => {
+                self.handle_broadcast_permission_change(origin, name);
+            },
             FromScriptMsg::MediaSessionEvent(pipeline_id, event) => {
                 // Unlikely at this point, but we may receive events coming from
                 // different media sessions, so we set the active media session based
@@ -2241,6 +2380,108 @@ where
         }
     }
 
+    /// Broadcast a message to all the same-origin channels of a given name,
+    /// in every global except the one the message originates from.
+    fn handle_schedule_broadcast(
+        &self,
+        router_id: BroadcastChannelRouterId,
+        message: BroadcastMsg,
+    ) {
+        let routers = match self
+            .broadcast_channels
+            .get(&message.origin)
+            .and_then(|channels| channels.get(&message.channel_name))
+        {
+            Some(routers) => routers,
+            None => return,
+        };
+        for router in routers.iter().filter(|router| **router != router_id) {
+            match self.broadcast_routers.get(router) {
+                Some(sender) => {
+                    if let Err(e) = sender.send(message.clone()) {
+                        warn!("Failed to broadcast message to router {}: {:?}", router, e);
+                    }
+                },
+                None => warn!("Broadcasting to unknown router {}.", router),
+            }
+        }
+    }
+
+    fn handle_new_broadcast_channel_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        broadcast_ipc_sender: IpcSender<BroadcastMsg>,
+        origin: ImmutableOrigin,
+    ) {
+        if self
+            .broadcast_routers
+            .insert(router_id, broadcast_ipc_sender)
+            .is_some()
+        {
+            warn!(
+                "Multiple attempts to add broadcast-channel router {}.",
+                router_id
+            );
+        }
+        self.broadcast_channels.entry(origin).or_default();
+    }
+
+    fn handle_remove_broadcast_channel_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        origin: ImmutableOrigin,
+    ) {
+        if let Some(channels) = self.broadcast_channels.get_mut(&origin) {
+            for routers in channels.values_mut() {
+                routers.retain(|router| *router != router_id);
+            }
+            channels.retain(|_name, routers| !routers.is_empty());
+        }
+        if self.broadcast_routers.remove(&router_id).is_none() {
+            warn!(
+                "Attempt to remove unknown broadcast-channel router {}.",
+                router_id
+            );
+        }
+    }
+
+    fn handle_new_broadcast_channel_name_in_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        channel_name: String,
+        origin: ImmutableOrigin,
+    ) {
+        let routers = self
+            .broadcast_channels
+            .entry(origin)
+            .or_default()
+            .entry(channel_name)
+            .or_default();
+        if !routers.contains(&router_id) {
+            routers.push(router_id);
+        }
+    }
+
+    fn handle_remove_broadcast_channel_name_in_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        channel_name: String,
+        origin: ImmutableOrigin,
+    ) {
+        if let Some(channels) = self.broadcast_channels.get_mut(&origin) {
+            let is_empty = match channels.get_mut(&channel_name) {
+                Some(routers) => {
+                    routers.retain(|router| *router != router_id);
+                    routers.is_empty()
+                },
+                None => false,
+            };
+            if is_empty {
+                channels.remove(&channel_name);
+            }
+        }
+    }
+
     fn handle_new_messageport_router(
         &mut self,
         router_id: MessagePortRouterId,
@@ -2251,6 +2492,31 @@ where
 
     fn handle_remove_messageport_router(&mut self, router_id: MessagePortRouterId) {
         self.message_port_routers.remove(&router_id);
+        // Ports still managed by the router belong to a global that is going away,
+        // remove them so that their entangled ports, wherever they live, are notified.
+        let managed_ports: Vec<MessagePortId> = self
+            .message_ports
+            .iter()
+            .filter_map(|(port_id, info)| match info.state {
+                TransferState::Managed(id) if id == router_id => Some(*port_id),
+                _ => None,
+            })
+            .collect();
+        let entangled_ports: Vec<(MessagePortId, MessagePortId)> = managed_ports
+            .iter()
+            .filter_map(|port_id| {
+                let info = self.message_ports.remove(port_id)?;
+                info.entangled_with
+                    .map(|entangled_id| (*port_id, entangled_id))
+            })
+            .collect();
+        for (port_id, entangled_id) in entangled_ports {
+            // Ports entangled with each other in the removed global need no notification.
+            if managed_ports.contains(&entangled_id) {
+                continue;
+            }
+            self.handle_entangled_messageport_removed(port_id, entangled_id);
+        }
     }
 
     fn handle_new_messageport(&mut self, router_id: MessagePortRouterId, port_id: MessagePortId) {
@@ -2280,10 +2546,18 @@ where
                 );
             },
         };
-        let entangled_id = match entangled {
-            Some(id) => id,
-            None => return,
-        };
+        if let Some(entangled_id) = entangled {
+            self.handle_entangled_messageport_removed(port_id, entangled_id);
+        }
+    }
+
+    /// Let the entangled port of a removed port know about the removal,
+    /// or mark it for removal once its transfer completes.
+    fn handle_entangled_messageport_removed(
+        &mut self,
+        port_id: MessagePortId,
+        entangled_id: MessagePortId,
+    ) {
         let info = match self.message_ports.get_mut(&entangled_id) {
             Some(info) => info,
             None => {
@@ -2310,6 +2584,8 @@ where
             },
             TransferState::Managed(router_id) => router_id,
         };
+        // The global managing the entangled port removes it without telling us.
+        self.message_ports.remove(&entangled_id);
         if let Some(sender) = self.message_port_routers.get(&router_id) {
             let _ = sender.send(MessagePortMsg::RemoveMessagePort(entangled_id));
         } else {
@@ -2336,6 +2612,93 @@ where
         }
     }
 
+    fn handle_connect_to_shared_worker(
+        &mut self,
+        pipeline_id: PipelineId,
+        key: SharedWorkerKey,
+        id: SharedWorkerId,
+        data: StructuredSerializedData,
+        control_sender: IpcSender<SharedWorkerControlMsg>,
+        response_sender: IpcSender<bool>,
+    ) {
+        let host = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => Rc::downgrade(&pipeline.event_loop),
+            None => {
+                return warn!(
+                    "Unknown pipeline {} connecting to shared worker.",
+                    pipeline_id
+                )
+            },
+        };
+        // A worker whose hosting script process went away is replaced by a new one.
+        let is_new = match self.shared_workers.get(&key) {
+            Some(info) => info.host.upgrade().is_none(),
+            None => true,
+        };
+        if is_new {
+            let info = SharedWorkerInfo {
+                id,
+                control_sender,
+                connected_pipelines: HashSet::new(),
+                host,
+            };
+            self.shared_workers.insert(key.clone(), info);
+        }
+        let info = match self.shared_workers.get_mut(&key) {
+            Some(info) => info,
+            None => return,
+        };
+        info.connected_pipelines.insert(pipeline_id);
+        if let Err(e) = info
+            .control_sender
+            .send(SharedWorkerControlMsg::Connect(data))
+        {
+            // The worker is gone, the next connection starts a new one.
+            warn!("Connecting to shared worker failed ({:?}).", e);
+            self.shared_workers.remove(&key);
+        }
+        if let Err(e) = response_sender.send(is_new) {
+            warn!(
+                "Sending reply to connect to shared worker failed ({:?}).",
+                e
+            );
+        }
+    }
+
+    /// Terminate the shared workers that no longer have any connected pipeline.
+    fn disconnect_shared_workers(&mut self, pipeline_id: PipelineId) {
+        let mut disconnected = vec![];
+        for (key, info) in self.shared_workers.iter_mut() {
+            if info.connected_pipelines.remove(&pipeline_id) && info.connected_pipelines.is_empty()
+            {
+                disconnected.push(key.clone());
+            }
+        }
+        for key in disconnected {
+            if let Some(info) = self.shared_workers.remove(&key) {
+                let _ = info.control_sender.send(SharedWorkerControlMsg::Terminate);
+            }
+        }
+        self.remove_hostless_shared_workers();
+    }
+
+    /// Forget about the shared workers whose hosting event loop has exited,
+    /// which takes down the worker along with its script process.
+    /// In single-process mode the worker thread outlives it, and is terminated.
+    fn remove_hostless_shared_workers(&mut self) {
+        let hostless: Vec<SharedWorkerKey> = self
+            .shared_workers
+            .iter()
+            .filter(|(_, info)| info.host.upgrade().is_none())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in hostless {
+            if let Some(info) = self.shared_workers.remove(&key) {
+                let _ = info.control_sender.send(SharedWorkerControlMsg::Terminate);
+            }
+        }
+    }
+
     fn handle_register_serviceworker(&self, scope_things: ScopeThings, scope: ServoUrl) {
         if let Some(ref mgr) = self.swmanager_chan {
             let _ = mgr.send(ServiceWorkerMsg::RegisterServiceWorker(scope_things, scope));
@@ -2353,9 +2716,12 @@ where
         old_value: Option<String>,
         new_value: Option<String>,
     ) {
-        let origin = url.origin();
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.origin.clone(),
+            None => url.origin(),
+        };
         for pipeline in self.pipelines.values() {
-            if (pipeline.id != pipeline_id) && (pipeline.url.origin() == origin) {
+            if (pipeline.id != pipeline_id) && (pipeline.origin == origin) {
                 let msg = ConstellationControlMsg::DispatchStorageEvent(
                     pipeline.id,
                     storage,
@@ -2374,6 +2740,20 @@ where
         }
     }
 
+    fn handle_broadcast_permission_change(&self, origin: ImmutableOrigin, name: PermissionName) {
+        for pipeline in self.pipelines.values() {
+            if pipeline.url.origin() == origin {
+                let msg = ConstellationControlMsg::DispatchPermissionChange(pipeline.id, name);
+                if let Err(err) = pipeline.event_loop.send(msg) {
+                    warn!(
+                        "Failed to broadcast permission change to pipeline {} ({:?}).",
+                        pipeline.id, err
+                    );
+                }
+            }
+        }
+    }
+
     fn handle_exit(&mut self) {
         // TODO: add a timer, which forces shutdown if threads aren't responsive.
         if self.shutting_down {
@@ -2540,6 +2920,7 @@ where
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("Pipeline {:?} exited.", pipeline_id);
         self.pipelines.remove(&pipeline_id);
+        self.disconnect_shared_workers(pipeline_id);
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
@@ -2695,8 +3076,14 @@ where
                     self.pressed_mouse_buttons,
                 )
             },
-            MouseMoveEvent(point, node_address, _) => {
-                MouseMoveEvent(point, node_address, self.pressed_mouse_buttons)
+            MouseMoveEvent(point, node_address, point_in_node, _) => MouseMoveEvent(
+                point,
+                node_address,
+                point_in_node,
+                self.pressed_mouse_buttons,
+            ),
+            MouseMovementEvent(movement, _) => {
+                MouseMovementEvent(movement, self.pressed_mouse_buttons)
             },
             _ => event,
         };
@@ -3011,6 +3398,7 @@ where
                 },
             };
         let is_private = is_private || is_parent_private;
+        let origin = self.origin_of_load(&load_info.load_data, Some(parent_pipeline_id));
         let pipeline = Pipeline::new(
             new_pipeline_id,
             browsing_context_id,
@@ -3021,6 +3409,7 @@ where
             self.compositor_proxy.clone(),
             is_parent_visible,
             load_info.load_data,
+            origin,
         );
 
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
@@ -3073,6 +3462,7 @@ where
                     );
                 },
             };
+        let origin = self.origin_of_load(&load_data, None);
         let pipeline = Pipeline::new(
             new_pipeline_id,
             new_browsing_context_id,
@@ -3083,6 +3473,7 @@ where
             self.compositor_proxy.clone(),
             is_opener_visible,
             load_data,
+            origin,
         );
 
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
@@ -3151,6 +3542,26 @@ where
             ))
     }
 
+    fn handle_smooth_scroll_ended(
+        &mut self,
+        pipeline_id: PipelineId,
+        scroll_id: webrender_api::ExternalScrollId,
+    ) {
+        let msg = ConstellationControlMsg::SmoothScrollEnded(pipeline_id, scroll_id);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return warn!(
+                    "Pipeline {:?} got smooth scroll end after closure.",
+                    pipeline_id
+                )
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_tick_animation(&mut self, pipeline_id: PipelineId, tick_type: AnimationTickType) {
         let result = match tick_type {
             AnimationTickType::Script => {
@@ -3849,6 +4260,40 @@ where
         }
     }
 
+    fn handle_gamepad_msg(&mut self, event: GamepadEvent) {
+        // Send to the focused browsing contexts' current pipeline.
+        let focused_browsing_context_id = match self
+            .active_browser_id
+            .and_then(|browser_id| self.browsers.get(&browser_id))
+        {
+            Some(browser) => browser.focused_browsing_context_id,
+            None => return debug!("Got gamepad event without a focused browser."),
+        };
+        let pipeline_id = match self.browsing_contexts.get(&focused_browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                return warn!(
+                    "Got gamepad event for nonexistent browsing context {}.",
+                    focused_browsing_context_id,
+                );
+            },
+        };
+        let msg =
+            ConstellationControlMsg::SendEvent(pipeline_id, CompositorEvent::GamepadEvent(event));
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return debug!(
+                    "Pipeline {:?} got gamepad event after closure.",
+                    pipeline_id
+                );
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_reload_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
@@ -4018,6 +4463,32 @@ where
         result
     }
 
+    /// Updates the visibility of the browsing contexts of a top level
+    /// browsing context that the embedder hid or showed.
+    fn handle_change_browser_visibility(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        visible: bool,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let browsing_context_ids: Vec<_> = self
+            .all_descendant_browsing_contexts_iter(browsing_context_id)
+            .map(|browsing_context| browsing_context.id)
+            .collect();
+        for browsing_context_id in browsing_context_ids {
+            let pipeline_id = match self.browsing_contexts.get_mut(&browsing_context_id) {
+                Some(browsing_context) if browsing_context.is_visible != visible => {
+                    browsing_context.is_visible = visible;
+                    browsing_context.pipeline_id
+                },
+                _ => continue,
+            };
+            if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+                pipeline.notify_visibility(visible);
+            }
+        }
+    }
+
     fn handle_visibility_change_complete(&mut self, pipeline_id: PipelineId, visibility: bool) {
         let browsing_context_id = match self.pipelines.get(&pipeline_id) {
             Some(pipeline) => pipeline.browsing_context_id,
@@ -4663,6 +5134,36 @@ where
         self.switch_fullscreen_mode(browsing_context_id);
     }
 
+    /// Called when the user unlocks the mouse pointer. Any document of the top
+    /// level browsing context may hold the lock, so all of them are told.
+    fn handle_exit_pointer_lock_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        for browsing_context in self.all_descendant_browsing_contexts_iter(browsing_context_id) {
+            let pipeline = match self.pipelines.get(&browsing_context.pipeline_id) {
+                Some(pipeline) => pipeline,
+                None => continue,
+            };
+            let msg = ConstellationControlMsg::ExitPointerLock(pipeline.id);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!("Sending exit pointer lock failed ({}).", e);
+            }
+        }
+    }
+
+    /// Called when the screen is rotated.
+    fn handle_change_screen_orientation_msg(&mut self, orientation: ScreenOrientationState) {
+        self.screen_orientation = Some(orientation);
+        for pipeline in self.pipelines.values() {
+            let msg = ConstellationControlMsg::ChangeScreenOrientation(pipeline.id, orientation);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!("Sending screen orientation change failed ({}).", e);
+            }
+        }
+    }
+
     /// Handle updating actual viewport / zoom due to @viewport rules
     fn handle_viewport_constrained_msg(
         &mut self,
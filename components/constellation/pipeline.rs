@@ -37,7 +37,7 @@ use script_traits::{NewLayoutInfo, SWManagerMsg, SWManagerSenders};
 use script_traits::{ScriptThreadFactory, TimerSchedulerMsg, WindowSizeData};
 use servo_config::opts::{self, Opts};
 use servo_config::{prefs, prefs::PrefValue};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use std::collections::{HashMap, HashSet};
 #[cfg(not(windows))]
 use std::env;
@@ -89,6 +89,10 @@ pub struct Pipeline {
     /// The Load Data used to create this pipeline.
     pub load_data: LoadData,
 
+    /// The origin of the document of this pipeline. An about:blank document
+    /// has the origin of its parent or creator document.
+    pub origin: ImmutableOrigin,
+
     /// The active history state for this pipeline.
     pub history_state_id: Option<HistoryStateId>,
 
@@ -174,6 +178,9 @@ pub struct InitialPipelineState {
     /// Information about the page to load.
     pub load_data: LoadData,
 
+    /// The origin of the document to load.
+    pub origin: ImmutableOrigin,
+
     /// Whether the browsing context in which pipeline is embedded is visible
     /// for the purposes of scheduling and resource management. This field is
     /// only used to notify script and compositor threads after spawning
@@ -347,6 +354,7 @@ impl Pipeline {
             state.compositor_proxy,
             state.prev_visibility,
             state.load_data,
+            state.origin,
         );
         Ok(NewPipeline {
             pipeline,
@@ -366,6 +374,7 @@ impl Pipeline {
         compositor_proxy: CompositorProxy,
         is_visible: bool,
         load_data: LoadData,
+        origin: ImmutableOrigin,
     ) -> Pipeline {
         let pipeline = Pipeline {
             id: id,
@@ -379,6 +388,7 @@ impl Pipeline {
             children: vec![],
             running_animations: false,
             load_data: load_data,
+            origin: origin,
             history_state_id: None,
             history_states: HashSet::new(),
             completely_loaded: false,
@@ -11,14 +11,22 @@ extern crate num_derive;
 #[macro_use]
 extern crate serde;
 
+pub mod payment;
 pub mod resources;
+pub mod speech;
+pub mod webauthn;
 
 use crossbeam_channel::{Receiver, Sender};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
-use msg::constellation_msg::{InputMethodType, PipelineId, TopLevelBrowsingContextId};
+use msg::constellation_msg::{
+    InputMethodType, NotificationId, PipelineId, TopLevelBrowsingContextId,
+};
+use payment::{PaymentComplete, PaymentDetails, PaymentSheet, PaymentSheetEvent};
 use servo_url::ServoUrl;
+use speech::SpeechRequest;
 use std::fmt::{Debug, Error, Formatter};
+use webauthn::{AuthenticatorError, AuthenticatorRequest, AuthenticatorResponse};
 use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
 
 pub use webxr_api::MainThreadWaker as EventLoopWaker;
@@ -124,6 +132,8 @@ pub enum EmbedderMsg {
     AllowOpeningBrowser(IpcSender<bool>),
     /// A new browser was created by script
     BrowserCreated(TopLevelBrowsingContextId),
+    /// The features requested by script for a browser it created
+    SetWindowFeatures(WindowFeatures),
     /// Wether or not to unload a document
     AllowUnload(IpcSender<bool>),
     /// Sends an unconsumed key event back to the embedder.
@@ -142,6 +152,13 @@ pub enum EmbedderMsg {
     HistoryChanged(Vec<ServoUrl>, usize),
     /// Enter or exit fullscreen
     SetFullscreenState(bool),
+    /// Lock the mouse pointer to the window and hide it, or release it
+    SetPointerLockState(bool),
+    /// Lock the orientation of the screen. The embedder answers whether it
+    /// supports the lock.
+    LockScreenOrientation(ScreenOrientationLock, IpcSender<bool>),
+    /// Let the screen rotate freely again
+    UnlockScreenOrientation,
     /// The load of a page has begun
     LoadStart,
     /// The load of a page has completed
@@ -154,6 +171,38 @@ pub enum EmbedderMsg {
     GetSelectedBluetoothDevice(Vec<String>, IpcSender<Option<String>>),
     /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
     SelectFiles(Vec<FilterPattern>, bool, IpcSender<Option<Vec<String>>>),
+    /// Open a picker for a date, time or color input, pre-filled with its current value.
+    /// The chosen value is sent back, or None if the picker was dismissed.
+    ShowInputPicker(InputMethodType, String, IpcSender<Option<String>>),
+    /// Open dialog to ask the user whether to grant a permission.
+    PromptPermission(PermissionPrompt, IpcSender<PermissionRequest>),
+    /// Display a notification to the user.
+    ShowNotification(Notification),
+    /// Stop displaying a notification, if it is still displayed.
+    CloseNotification(NotificationId),
+    /// Run an operation of an authenticator, to create or use a Web Authentication credential.
+    Authenticate(
+        AuthenticatorRequest,
+        IpcSender<Result<AuthenticatorResponse, AuthenticatorError>>,
+    ),
+    /// Whether the platform has an authenticator that can verify the user.
+    IsUserVerifyingPlatformAuthenticatorAvailable(IpcSender<bool>),
+    /// The identifiers of the payment methods the user can pay with.
+    GetSupportedPaymentMethods(IpcSender<Vec<String>>),
+    /// Show a payment sheet to the user, who can pay with one of its methods.
+    /// The sender reports what the user does with it.
+    ShowPaymentSheet(PaymentSheet, IpcSender<PaymentSheetEvent>),
+    /// Update the details of the payment sheet with the given id, after the
+    /// user changed the shipping address or option.
+    UpdatePaymentSheet(String, PaymentDetails),
+    /// Close the payment sheet with the given id. The embedder answers
+    /// whether it could, which it can't once the payment is processed.
+    AbortPayment(String, IpcSender<bool>),
+    /// Close the payment sheet with the given id, once the page processed
+    /// the payment of the user.
+    CompletePayment(String, PaymentComplete),
+    /// A request to the speech synthesizer.
+    Speech(SpeechRequest),
     /// Request to present an IME to the user when an editable element is focused.
     ShowIME(InputMethodType),
     /// Request to hide the IME when the editable element is blurred.
@@ -186,27 +235,122 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::CloseBrowser => write!(f, "CloseBrowser"),
             EmbedderMsg::HistoryChanged(..) => write!(f, "HistoryChanged"),
             EmbedderMsg::SetFullscreenState(..) => write!(f, "SetFullscreenState"),
+            EmbedderMsg::SetPointerLockState(..) => write!(f, "SetPointerLockState"),
+            EmbedderMsg::LockScreenOrientation(..) => write!(f, "LockScreenOrientation"),
+            EmbedderMsg::UnlockScreenOrientation => write!(f, "UnlockScreenOrientation"),
             EmbedderMsg::LoadStart => write!(f, "LoadStart"),
             EmbedderMsg::LoadComplete => write!(f, "LoadComplete"),
             EmbedderMsg::Panic(..) => write!(f, "Panic"),
             EmbedderMsg::GetSelectedBluetoothDevice(..) => write!(f, "GetSelectedBluetoothDevice"),
             EmbedderMsg::SelectFiles(..) => write!(f, "SelectFiles"),
+            EmbedderMsg::ShowInputPicker(..) => write!(f, "ShowInputPicker"),
+            EmbedderMsg::PromptPermission(..) => write!(f, "PromptPermission"),
+            EmbedderMsg::ShowNotification(..) => write!(f, "ShowNotification"),
+            EmbedderMsg::CloseNotification(..) => write!(f, "CloseNotification"),
+            EmbedderMsg::Authenticate(..) => write!(f, "Authenticate"),
+            EmbedderMsg::IsUserVerifyingPlatformAuthenticatorAvailable(..) => {
+                write!(f, "IsUserVerifyingPlatformAuthenticatorAvailable")
+            },
+            EmbedderMsg::GetSupportedPaymentMethods(..) => write!(f, "GetSupportedPaymentMethods"),
+            EmbedderMsg::ShowPaymentSheet(..) => write!(f, "ShowPaymentSheet"),
+            EmbedderMsg::UpdatePaymentSheet(..) => write!(f, "UpdatePaymentSheet"),
+            EmbedderMsg::AbortPayment(..) => write!(f, "AbortPayment"),
+            EmbedderMsg::CompletePayment(..) => write!(f, "CompletePayment"),
+            EmbedderMsg::Speech(..) => write!(f, "Speech"),
             EmbedderMsg::ShowIME(..) => write!(f, "ShowIME"),
             EmbedderMsg::HideIME => write!(f, "HideIME"),
             EmbedderMsg::Shutdown => write!(f, "Shutdown"),
             EmbedderMsg::AllowOpeningBrowser(..) => write!(f, "AllowOpeningBrowser"),
             EmbedderMsg::BrowserCreated(..) => write!(f, "BrowserCreated"),
+            EmbedderMsg::SetWindowFeatures(..) => write!(f, "SetWindowFeatures"),
             EmbedderMsg::ReportProfile(..) => write!(f, "ReportProfile"),
             EmbedderMsg::MediaSessionEvent(..) => write!(f, "MediaSessionEvent"),
         }
     }
 }
 
+/// The features requested for a browser opened with `window.open`,
+/// https://html.spec.whatwg.org/multipage/#set-up-browsing-context-features
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WindowFeatures {
+    /// Whether a popup window, rather than a new tab, was requested.
+    pub popup: bool,
+    /// The requested position of the window on the screen, in device pixels.
+    pub left: Option<i32>,
+    pub top: Option<i32>,
+    /// The requested size of the viewport, in device pixels.
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FilterPattern(pub String);
 
+/// A request to the user to grant a permission,
+/// https://w3c.github.io/permissions/#request-permission-to-use
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PermissionPrompt {
+    /// Request the permission to use a feature.
+    Request(PermissionName),
+    /// Request the permission to use a feature that is only safe to use in a secure context,
+    /// from a context that may not be secure.
+    Insecure(PermissionName),
+}
+
+/// The answer of the user to a permission prompt.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PermissionRequest {
+    Granted,
+    Denied,
+}
+
+/// https://w3c.github.io/permissions/#permission-registry
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PermissionName {
+    Geolocation,
+    Notifications,
+    Push,
+    Midi,
+    Camera,
+    Microphone,
+    Speaker,
+    DeviceInfo,
+    BackgroundSync,
+    Bluetooth,
+    PersistentStorage,
+    ClipboardRead,
+    ClipboardWrite,
+}
+
+/// https://notifications.spec.whatwg.org/#concept-notification
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Notification {
+    /// Identifies the notification to close it
+    pub id: NotificationId,
+    /// Title
+    pub title: String,
+    /// Body
+    pub body: String,
+    /// Tag, a notification replaces any displayed notification with the same tag
+    pub tag: String,
+    /// Icon
+    pub icon: Option<ServoUrl>,
+    /// Image
+    pub image: Option<ServoUrl>,
+    /// Badge
+    pub badge: Option<ServoUrl>,
+    /// Creation time, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Whether the user should be alerted again when the notification replaces another one
+    pub renotify: bool,
+    /// Whether the notification should be displayed without sound or vibration
+    pub silent: bool,
+    /// Whether the notification should stay displayed until the user dismisses it
+    pub require_interaction: bool,
+}
+
 /// https://w3c.github.io/mediasession/#mediametadata
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MediaMetadata {
@@ -268,3 +412,64 @@ pub enum MediaSessionEvent {
     /// Indicates that the position state is set.
     SetPositionState(MediaPositionState),
 }
+
+/// https://w3c.github.io/screen-orientation/#dom-orientationtype
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScreenOrientationType {
+    PortraitPrimary,
+    PortraitSecondary,
+    LandscapePrimary,
+    LandscapeSecondary,
+}
+
+/// The orientation of the screen, reported by the embedder when it changes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ScreenOrientationState {
+    pub orientation_type: ScreenOrientationType,
+    /// The angle in degrees from the natural orientation of the screen.
+    pub angle: u16,
+}
+
+/// https://w3c.github.io/screen-orientation/#dom-orientationlocktype
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScreenOrientationLock {
+    Any,
+    Natural,
+    Landscape,
+    Portrait,
+    PortraitPrimary,
+    PortraitSecondary,
+    LandscapePrimary,
+    LandscapeSecondary,
+}
+
+impl ScreenOrientationLock {
+    /// Whether a screen in the given orientation satisfies this lock.
+    pub fn allows(&self, orientation: &ScreenOrientationState) -> bool {
+        let orientation_type = orientation.orientation_type;
+        match *self {
+            ScreenOrientationLock::Any => true,
+            ScreenOrientationLock::Natural => orientation.angle == 0,
+            ScreenOrientationLock::Landscape => {
+                orientation_type == ScreenOrientationType::LandscapePrimary ||
+                    orientation_type == ScreenOrientationType::LandscapeSecondary
+            },
+            ScreenOrientationLock::Portrait => {
+                orientation_type == ScreenOrientationType::PortraitPrimary ||
+                    orientation_type == ScreenOrientationType::PortraitSecondary
+            },
+            ScreenOrientationLock::PortraitPrimary => {
+                orientation_type == ScreenOrientationType::PortraitPrimary
+            },
+            ScreenOrientationLock::PortraitSecondary => {
+                orientation_type == ScreenOrientationType::PortraitSecondary
+            },
+            ScreenOrientationLock::LandscapePrimary => {
+                orientation_type == ScreenOrientationType::LandscapePrimary
+            },
+            ScreenOrientationLock::LandscapeSecondary => {
+                orientation_type == ScreenOrientationType::LandscapeSecondary
+            },
+        }
+    }
+}
@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The payment sheet that an embedder shows to let the user pay for a
+//! `PaymentRequest`, and what the user does with it,
+//! https://w3c.github.io/payment-request/
+
+/// https://w3c.github.io/payment-request/#dom-paymentcurrencyamount
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentCurrencyAmount {
+    /// A three letter ISO 4217 currency code, in uppercase.
+    pub currency: String,
+    /// A valid decimal monetary value, such as "-10.50".
+    pub value: String,
+}
+
+/// https://w3c.github.io/payment-request/#dom-paymentitem
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentItem {
+    pub label: String,
+    pub amount: PaymentCurrencyAmount,
+    /// Whether the amount is not final yet.
+    pub pending: bool,
+}
+
+/// https://w3c.github.io/payment-request/#dom-paymentshippingoption
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentShippingOption {
+    pub id: String,
+    pub label: String,
+    pub amount: PaymentCurrencyAmount,
+    pub selected: bool,
+}
+
+/// A payment method the page accepts.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentMethod {
+    /// The payment method identifier, such as "basic-card" or an https URL.
+    pub supported_method: String,
+    /// The JSON serialization of the data that is specific to the method.
+    pub data: Option<String>,
+}
+
+/// Changes of the total and display items that apply when the user pays
+/// with a given payment method.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentDetailsModifier {
+    pub supported_method: String,
+    pub total: Option<PaymentItem>,
+    pub additional_display_items: Vec<PaymentItem>,
+    /// The JSON serialization of the data that is specific to the method.
+    pub data: Option<String>,
+}
+
+/// What the user pays for.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentDetails {
+    pub total: PaymentItem,
+    pub display_items: Vec<PaymentItem>,
+    /// The shipping options the user can choose from, which is empty when
+    /// the page can't ship to the current shipping address.
+    pub shipping_options: Vec<PaymentShippingOption>,
+    pub modifiers: Vec<PaymentDetailsModifier>,
+    /// An error the page wants to show to the user, such as the reason why
+    /// it can't ship to the current shipping address.
+    pub error: Option<String>,
+}
+
+/// https://w3c.github.io/payment-request/#dom-paymentshippingtype
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PaymentShippingType {
+    Shipping,
+    Delivery,
+    Pickup,
+}
+
+/// The information the page asks the user for.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentOptions {
+    pub request_payer_name: bool,
+    pub request_payer_email: bool,
+    pub request_payer_phone: bool,
+    /// The kind of shipping address the user is asked for, if any.
+    pub shipping_type: Option<PaymentShippingType>,
+}
+
+/// A payment request to show to the user, with the payment methods that
+/// the embedder supports.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentSheet {
+    /// The id of the request, which identifies it in later messages.
+    pub id: String,
+    pub methods: Vec<PaymentMethod>,
+    pub details: PaymentDetails,
+    pub options: PaymentOptions,
+}
+
+/// https://w3c.github.io/payment-request/#physical-addresses
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PaymentAddress {
+    pub country: String,
+    pub address_line: Vec<String>,
+    pub region: String,
+    pub city: String,
+    pub dependent_locality: String,
+    pub postal_code: String,
+    pub sorting_code: String,
+    pub organization: String,
+    pub recipient: String,
+    pub phone: String,
+}
+
+/// What the user agreed to pay with.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaymentResponseData {
+    pub method_name: String,
+    /// The JSON serialization of the data the payment method returned, such
+    /// as a token the merchant can charge.
+    pub details: String,
+    pub shipping_address: Option<PaymentAddress>,
+    pub shipping_option: Option<String>,
+    pub payer_name: Option<String>,
+    pub payer_email: Option<String>,
+    pub payer_phone: Option<String>,
+}
+
+/// What the user does with a payment sheet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PaymentSheetEvent {
+    /// The user chose another shipping address. The sheet waits for an
+    /// `EmbedderMsg::UpdatePaymentSheet` before letting the user pay.
+    ShippingAddressChange(PaymentAddress),
+    /// The user chose the shipping option with the given id. The sheet
+    /// waits for an `EmbedderMsg::UpdatePaymentSheet` before letting the user
+    /// pay.
+    ShippingOptionChange(String),
+    /// The user accepted to pay. The sheet waits for an
+    /// `EmbedderMsg::CompletePayment` before closing.
+    Accept(PaymentResponseData),
+    /// The user closed the sheet without paying.
+    Abort,
+}
+
+/// https://w3c.github.io/payment-request/#dom-paymentcomplete
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PaymentComplete {
+    Fail,
+    Success,
+    Unknown,
+}
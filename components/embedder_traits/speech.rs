@@ -0,0 +1,136 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The speech synthesizers that an embedder speaks the utterances of
+//! `speechSynthesis` with, https://wicg.github.io/speech-api/#tts-section
+
+use ipc_channel::ipc::IpcSender;
+
+/// https://wicg.github.io/speech-api/#speechsynthesisvoice
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechVoice {
+    /// A URI that identifies the voice among the others of the synthesizer.
+    pub uri: String,
+    pub name: String,
+    /// A BCP 47 language tag.
+    pub lang: String,
+    /// Whether the voice is synthesized locally, and not by a remote service.
+    pub local_service: bool,
+    /// Whether the voice speaks the utterances that don't ask for one.
+    pub default: bool,
+}
+
+/// An utterance to speak.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechUtterance {
+    /// The id of the utterance, which identifies it in later messages.
+    pub id: String,
+    pub text: String,
+    /// A BCP 47 language tag, or the empty string for the language of the
+    /// default voice.
+    pub lang: String,
+    /// The URI of the voice the page chose, if any.
+    pub voice_uri: Option<String>,
+    /// Between 0 and 1.
+    pub volume: f32,
+    /// Between 0.1 and 10, relative to the default rate of the voice.
+    pub rate: f32,
+    /// Between 0 and 2, relative to the default pitch of the voice.
+    pub pitch: f32,
+}
+
+/// https://wicg.github.io/speech-api/#enumdef-speechsynthesiserrorcode
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SpeechSynthesisError {
+    /// Another utterance started being spoken before this one completed.
+    Interrupted,
+    AudioBusy,
+    AudioHardware,
+    Network,
+    SynthesisUnavailable,
+    SynthesisFailed,
+    LanguageUnavailable,
+    VoiceUnavailable,
+    TextTooLong,
+    InvalidArgument,
+    NotAllowed,
+}
+
+/// https://wicg.github.io/speech-api/#dom-speechsynthesisevent-name
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SpeechBoundary {
+    Word,
+    Sentence,
+}
+
+/// What happens while an utterance is spoken. The last event of an
+/// utterance is either `End` or `Error`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum SpeechEvent {
+    Start,
+    /// The synthesizer reached the word or sentence that starts at the
+    /// given character index.
+    Boundary {
+        boundary: SpeechBoundary,
+        char_index: u32,
+        char_length: u32,
+    },
+    /// The synthesizer reached an SSML mark.
+    Mark {
+        name: String,
+        char_index: u32,
+    },
+    Pause,
+    Resume,
+    End,
+    Error(SpeechSynthesisError),
+}
+
+/// A text-to-speech engine that an embedder gives access to, such as the
+/// one of the platform. It speaks a single utterance at a time.
+pub trait SpeechSynthesizer {
+    fn voices(&mut self) -> Vec<SpeechVoice>;
+
+    /// Starts speaking the utterance, and reports what happens to `events`.
+    /// The utterance being spoken, if any, is interrupted.
+    fn speak(&mut self, utterance: SpeechUtterance, events: IpcSender<SpeechEvent>);
+
+    /// Pauses the utterance with the given id, if it's being spoken.
+    fn pause(&mut self, id: &str);
+
+    /// Resumes the utterance with the given id, if it's paused.
+    fn resume(&mut self, id: &str);
+
+    /// Stops speaking the utterance with the given id, if it's being spoken,
+    /// without reporting anything else about it.
+    fn cancel(&mut self, id: &str);
+
+    fn handle_request(&mut self, request: SpeechRequest) {
+        match request {
+            SpeechRequest::GetVoices(sender) => {
+                if let Err(e) = sender.send(self.voices()) {
+                    warn!("Failed to send the speech voices: {}", e);
+                }
+            },
+            SpeechRequest::Speak(utterance, events) => self.speak(utterance, events),
+            SpeechRequest::Pause(id) => self.pause(&id),
+            SpeechRequest::Resume(id) => self.resume(&id),
+            SpeechRequest::Cancel(id) => self.cancel(&id),
+        }
+    }
+}
+
+/// A request to the speech synthesizer of the embedder.
+#[derive(Deserialize, Serialize)]
+pub enum SpeechRequest {
+    /// The voices of the synthesizer.
+    GetVoices(IpcSender<Vec<SpeechVoice>>),
+    Speak(SpeechUtterance, IpcSender<SpeechEvent>),
+    /// Pause the utterance with the given id.
+    Pause(String),
+    /// Resume the utterance with the given id.
+    Resume(String),
+    /// Cancel the utterance with the given id.
+    Cancel(String),
+}
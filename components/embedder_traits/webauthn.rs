@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The operations of the authenticators that Web Authentication credentials
+//! are created and used with, as defined by CTAP2,
+//! https://fidoalliance.org/specs/fido-v2.0-ps-20190130/fido-client-to-authenticator-protocol-v2.0-ps-20190130.html
+
+/// https://w3c.github.io/webauthn/#dictdef-publickeycredentialrpentity
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+/// https://w3c.github.io/webauthn/#dictdef-publickeycredentialuserentity
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct User {
+    /// The user handle of the account.
+    pub id: Vec<u8>,
+    pub name: String,
+    pub display_name: String,
+}
+
+/// https://w3c.github.io/webauthn/#enumdef-authenticatorattachment
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AuthenticatorAttachment {
+    /// An authenticator built in the device, such as a fingerprint reader.
+    Platform,
+    /// A roaming authenticator, such as a security key.
+    CrossPlatform,
+}
+
+/// The parameters of authenticatorMakeCredential.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MakeCredentialRequest {
+    /// The SHA-256 hash of the client data of the request.
+    pub client_data_hash: Vec<u8>,
+    pub rp: RelyingParty,
+    pub user: User,
+    /// The COSE identifiers of the algorithms the credential can use, by
+    /// order of preference.
+    pub algorithms: Vec<i32>,
+    /// The ids of the credentials the authenticator must not already hold.
+    pub exclude_list: Vec<Vec<u8>>,
+    /// Whether the credential must be stored on the authenticator.
+    pub resident_key: bool,
+    /// Whether the user must be verified, and not only be present.
+    pub user_verification: bool,
+    pub attachment: Option<AuthenticatorAttachment>,
+}
+
+/// The attestation of a new credential, returned by authenticatorMakeCredential.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MakeCredentialResponse {
+    /// The attestation statement format identifier, such as "packed" or "none".
+    pub format: String,
+    /// https://w3c.github.io/webauthn/#authenticator-data
+    pub authenticator_data: Vec<u8>,
+    /// The CBOR encoding of the attestation statement.
+    pub attestation_statement: Vec<u8>,
+}
+
+/// The parameters of authenticatorGetAssertion.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAssertionRequest {
+    pub rp_id: String,
+    /// The SHA-256 hash of the client data of the request.
+    pub client_data_hash: Vec<u8>,
+    /// The ids of the credentials that can be used, any credential of the
+    /// relying party when it's empty.
+    pub allow_list: Vec<Vec<u8>>,
+    /// Whether the user must be verified, and not only be present.
+    pub user_verification: bool,
+}
+
+/// An assertion signed with a credential, returned by authenticatorGetAssertion.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAssertionResponse {
+    pub credential_id: Vec<u8>,
+    /// https://w3c.github.io/webauthn/#authenticator-data
+    pub authenticator_data: Vec<u8>,
+    pub signature: Vec<u8>,
+    /// The user handle of the credential, if it is stored on the authenticator.
+    pub user_handle: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum AuthenticatorRequest {
+    MakeCredential(MakeCredentialRequest),
+    GetAssertion(GetAssertionRequest),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum AuthenticatorResponse {
+    MakeCredential(MakeCredentialResponse),
+    GetAssertion(GetAssertionResponse),
+}
+
+/// The errors of the operations of an authenticator, which are exposed to
+/// the page as the matching `DOMException`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AuthenticatorError {
+    /// The user didn't consent to the operation, couldn't be verified, or no
+    /// authenticator holds a usable credential. CTAP2_ERR_OPERATION_DENIED,
+    /// CTAP2_ERR_PIN_INVALID and CTAP2_ERR_NO_CREDENTIALS.
+    NotAllowed,
+    /// The authenticator already holds one of the excluded credentials.
+    /// CTAP2_ERR_CREDENTIAL_EXCLUDED.
+    InvalidState,
+    /// None of the algorithms or options of the request is supported.
+    /// CTAP2_ERR_UNSUPPORTED_ALGORITHM and CTAP2_ERR_UNSUPPORTED_OPTION.
+    NotSupported,
+    /// The authenticator failed to perform the operation.
+    Unknown,
+}
+
+/// An authenticator that an embedder gives access to, such as a security key
+/// it talks to over CTAP2, or one built in the platform.
+pub trait Authenticator {
+    /// authenticatorMakeCredential, which prompts the user for consent.
+    fn make_credential(
+        &mut self,
+        request: MakeCredentialRequest,
+    ) -> Result<MakeCredentialResponse, AuthenticatorError>;
+
+    /// authenticatorGetAssertion, which prompts the user for consent.
+    fn get_assertion(
+        &mut self,
+        request: GetAssertionRequest,
+    ) -> Result<GetAssertionResponse, AuthenticatorError>;
+
+    /// Whether the authenticator is built in the platform and can verify the
+    /// user.
+    fn is_user_verifying_platform_authenticator(&self) -> bool {
+        false
+    }
+
+    fn handle_request(
+        &mut self,
+        request: AuthenticatorRequest,
+    ) -> Result<AuthenticatorResponse, AuthenticatorError> {
+        match request {
+            AuthenticatorRequest::MakeCredential(request) => self
+                .make_credential(request)
+                .map(AuthenticatorResponse::MakeCredential),
+            AuthenticatorRequest::GetAssertion(request) => self
+                .get_assertion(request)
+                .map(AuthenticatorResponse::GetAssertion),
+        }
+    }
+}
@@ -13,6 +13,7 @@ use crate::text::shaping::ShaperMethods;
 use crate::text::Shaper;
 use app_units::Au;
 use euclid::default::{Point2D, Rect, Size2D};
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use ordered_float::NotNan;
 use servo_atoms::Atom;
 use smallvec::SmallVec;
@@ -211,6 +212,21 @@ struct ShapeCacheEntry {
     options: ShapingOptions,
 }
 
+impl MallocSizeOf for Font {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        // FIXME: Measure the platform handle and the shaper.
+        let shape_cache = self.shape_cache.borrow();
+        let mut n = shape_cache.shallow_size_of(ops);
+        for (entry, glyphs) in shape_cache.iter() {
+            // The font holds the primary reference to its shaped glyph stores,
+            // but `std::sync::Arc` doesn't expose its heap allocation, so only
+            // the contents are measured.
+            n += entry.text.size_of(ops) + (**glyphs).size_of(ops);
+        }
+        n + self.glyph_advance_cache.borrow().shallow_size_of(ops)
+    }
+}
+
 impl Font {
     pub fn shape_text(&mut self, text: &str, options: &ShapingOptions) -> Arc<GlyphStore> {
         let this = self as *const Font;
@@ -113,8 +113,9 @@ pub enum Command {
         Au,
         IpcSender<webrender_api::FontInstanceKey>,
     ),
-    AddWebFont(LowercaseString, EffectiveSources, IpcSender<()>),
-    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<()>),
+    AddWebFont(LowercaseString, EffectiveSources, IpcSender<bool>),
+    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<bool>),
+    AddWebFontData(LowercaseString, Vec<u8>, IpcSender<bool>),
     Exit(IpcSender<()>),
     Ping,
 }
@@ -138,6 +139,9 @@ struct FontCache {
     webrender_api: webrender_api::RenderApi,
     webrender_fonts: HashMap<Atom, webrender_api::FontKey>,
     font_instances: HashMap<(webrender_api::FontKey, Au), webrender_api::FontInstanceKey>,
+    /// The number of web fonts that were added from their data rather than
+    /// from a URL, which identifies the next one.
+    web_font_data_count: usize,
 }
 
 fn populate_generic_fonts() -> HashMap<FontFamilyName, LowercaseString> {
@@ -201,7 +205,10 @@ impl FontCache {
                 Command::AddDownloadedWebFont(family_name, url, bytes, result) => {
                     let templates = &mut self.web_families.get_mut(&family_name).unwrap();
                     templates.add_template(Atom::from(url.to_string()), Some(bytes));
-                    drop(result.send(()));
+                    drop(result.send(true));
+                },
+                Command::AddWebFontData(family_name, bytes, result) => {
+                    self.handle_add_web_font_data(family_name, bytes, result);
                 },
                 Command::Ping => (),
                 Command::Exit(result) => {
@@ -216,12 +223,13 @@ impl FontCache {
         &mut self,
         family_name: LowercaseString,
         mut sources: EffectiveSources,
-        sender: IpcSender<()>,
+        sender: IpcSender<bool>,
     ) {
         let src = if let Some(src) = sources.next() {
             src
         } else {
-            sender.send(()).unwrap();
+            // None of the sources could be loaded.
+            sender.send(false).unwrap();
             return;
         };
 
@@ -235,7 +243,11 @@ impl FontCache {
                 // https://drafts.csswg.org/css-fonts/#font-fetching-requirements
                 let url = match url_source.url.url() {
                     Some(url) => url.clone(),
-                    None => return,
+                    None => {
+                        let msg = Command::AddWebFont(family_name, sources, sender);
+                        self.channel_to_self.send(msg).unwrap();
+                        return;
+                    },
                 };
 
                 let request = RequestBuilder::new(url.clone()).destination(Destination::Font);
@@ -313,7 +325,7 @@ impl FontCache {
                     templates.add_template(Atom::from(&*path), None);
                 });
                 if found {
-                    sender.send(()).unwrap();
+                    sender.send(true).unwrap();
                 } else {
                     let msg = Command::AddWebFont(family_name, sources, sender);
                     self.channel_to_self.send(msg).unwrap();
@@ -322,6 +334,32 @@ impl FontCache {
         }
     }
 
+    /// Adds a web font from the data of a font file, such as the one a
+    /// `FontFace` was constructed with.
+    fn handle_add_web_font_data(
+        &mut self,
+        family_name: LowercaseString,
+        bytes: Vec<u8>,
+        sender: IpcSender<bool>,
+    ) {
+        let bytes = match fontsan::process(&bytes) {
+            Ok(san) => san,
+            Err(_) => {
+                debug!("Sanitiser rejected web font data: family={}", family_name);
+                let _ = sender.send(false);
+                return;
+            },
+        };
+        // The font has no URL to identify its template by.
+        let identifier = Atom::from(format!("data:{}", self.web_font_data_count));
+        self.web_font_data_count += 1;
+        self.web_families
+            .entry(family_name)
+            .or_insert_with(FontTemplates::new)
+            .add_template(identifier, Some(bytes));
+        let _ = sender.send(true);
+    }
+
     fn refresh_local_families(&mut self) {
         self.local_families.clear();
         for_each_available_family(|family_name| {
@@ -463,6 +501,7 @@ impl FontCacheThread {
                     webrender_api,
                     webrender_fonts: HashMap::new(),
                     font_instances: HashMap::new(),
+                    web_font_data_count: 0,
                 };
 
                 cache.refresh_local_families();
@@ -473,11 +512,13 @@ impl FontCacheThread {
         FontCacheThread { chan: chan }
     }
 
+    /// Loads a web font from its sources, and sends whether one of them
+    /// could be loaded once it's done.
     pub fn add_web_font(
         &self,
         family: FamilyName,
         sources: EffectiveSources,
-        sender: IpcSender<()>,
+        sender: IpcSender<bool>,
     ) {
         self.chan
             .send(Command::AddWebFont(
@@ -488,6 +529,18 @@ impl FontCacheThread {
             .unwrap();
     }
 
+    /// Adds a web font from the data of a font file, and sends whether the
+    /// data was a valid font once it's done.
+    pub fn add_web_font_data(&self, family: FamilyName, data: Vec<u8>, sender: IpcSender<bool>) {
+        self.chan
+            .send(Command::AddWebFontData(
+                LowercaseString::new(&family.name),
+                data,
+                sender,
+            ))
+            .unwrap();
+    }
+
     pub fn exit(&self) {
         let (response_chan, response_port) = ipc::channel().unwrap();
         self.chan
@@ -11,7 +11,7 @@ use crate::platform::font::FontHandle;
 pub use crate::platform::font_context::FontContextHandle;
 use app_units::Au;
 use fnv::FnvHasher;
-use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use servo_arc::Arc;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -205,7 +205,11 @@ impl<S: FontSource> FontContext<S> {
 impl<S: FontSource> MallocSizeOf for FontContext<S> {
     fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
         // FIXME(njn): Measure other fields eventually.
-        self.platform_handle.size_of(ops)
+        let mut n = self.platform_handle.size_of(ops) + self.font_cache.shallow_size_of(ops);
+        for font in self.font_cache.values().flatten() {
+            n += font.borrow().size_of(ops);
+        }
+        n
     }
 }
 
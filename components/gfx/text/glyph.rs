@@ -4,6 +4,7 @@
 
 use app_units::Au;
 use euclid::default::Point2D;
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use range::{self, EachIndex, Range, RangeIndex};
 use std::cmp::{Ordering, PartialOrd};
 use std::vec::Vec;
@@ -193,6 +194,12 @@ struct DetailedGlyphStore {
     lookup_is_sorted: bool,
 }
 
+impl MallocSizeOf for DetailedGlyphStore {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.detail_buffer.shallow_size_of(ops) + self.detail_lookup.shallow_size_of(ops)
+    }
+}
+
 impl<'a> DetailedGlyphStore {
     fn new() -> DetailedGlyphStore {
         DetailedGlyphStore {
@@ -434,6 +441,12 @@ pub struct GlyphStore {
     is_rtl: bool,
 }
 
+impl MallocSizeOf for GlyphStore {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.entry_buffer.shallow_size_of(ops) + self.detail_store.size_of(ops)
+    }
+}
+
 impl<'a> GlyphStore {
     /// Initializes the glyph store, but doesn't actually shape anything.
     ///
@@ -37,8 +37,27 @@ pub fn update_animation_state<E>(
     E: TElement,
 {
     let mut new_running_animations = vec![];
-    while let Ok(animation) = new_animations_receiver.try_recv() {
+    while let Ok(mut animation) = new_animations_receiver.try_recv() {
         let mut should_push = true;
+        if let Animation::Script(ref node, _, id, ref mut state) = animation {
+            // The local time of the animation advances from now on, and the
+            // new state replaces the previous state of the animation, if any.
+            state.synced_at = Some(timer.seconds());
+            if let Some(ref mut animations) = running_animations.get_mut(node) {
+                for anim in animations.iter_mut() {
+                    if let Animation::Script(_, _, anim_id, ref anim_state) = *anim {
+                        if id == anim_id {
+                            if state.cascade_style.is_none() {
+                                state.cascade_style = anim_state.cascade_style.clone();
+                            }
+                            *anim = animation.clone();
+                            should_push = false;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
         if let Animation::Keyframes(ref node, _, ref name, ref state) = animation {
             // If the animation was already present in the list for the
             // node, just update its state, else push the new animation to
@@ -88,6 +107,7 @@ pub fn update_animation_state<E>(
                         // iterating.
                         now < state.started_at + state.duration || state.tick()
                     },
+                    Animation::Script(_, _, _, ref state) => state.is_running(now),
                 };
 
             debug!(
@@ -166,7 +186,7 @@ pub fn update_animation_state<E>(
 pub fn recalc_style_for_animations<E>(
     context: &LayoutContext,
     flow: &mut dyn Flow,
-    animations: &FxHashMap<OpaqueNode, Vec<Animation>>,
+    animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
 ) -> FxHashSet<OpaqueNode>
 where
     E: TElement,
@@ -179,16 +199,24 @@ where
 fn do_recalc_style_for_animations<E>(
     context: &LayoutContext,
     flow: &mut dyn Flow,
-    animations: &FxHashMap<OpaqueNode, Vec<Animation>>,
+    animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
     invalid_nodes: &mut FxHashSet<OpaqueNode>,
 ) where
     E: TElement,
 {
     let mut damage = RestyleDamage::empty();
     flow.mutate_fragments(&mut |fragment| {
-        if let Some(ref animations) = animations.get(&fragment.node) {
+        if let Some(animations) = animations.get_mut(&fragment.node) {
             invalid_nodes.remove(&fragment.node);
-            for animation in animations.iter() {
+            for animation in animations.iter_mut() {
+                // The animations that script created before the node was
+                // restyled take the values their keyframes don't specify
+                // from the style the fragment has before they apply.
+                if let Animation::Script(_, _, _, ref mut state) = *animation {
+                    if state.cascade_style.is_none() {
+                        state.cascade_style = Some(fragment.style.clone());
+                    }
+                }
                 let old_style = fragment.style.clone();
                 update_style_for_animation::<E>(
                     &context.style_context,
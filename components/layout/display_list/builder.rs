@@ -1867,7 +1867,8 @@ impl Fragment {
             },
             SpecificFragmentInfo::Canvas(ref canvas_fragment_info) => {
                 let image_key = match canvas_fragment_info.source {
-                    CanvasFragmentSource::WebGL(image_key) => image_key,
+                    CanvasFragmentSource::WebGL(image_key) |
+                    CanvasFragmentSource::WebGPU(image_key) => image_key,
                     CanvasFragmentSource::Image(ref ipc_renderer) => match *ipc_renderer {
                         Some(ref ipc_renderer) => {
                             let ipc_renderer = ipc_renderer.lock().unwrap();
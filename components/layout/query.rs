@@ -30,6 +30,8 @@ use script_layout_interface::StyleData;
 use script_layout_interface::{LayoutElementType, LayoutNodeType};
 use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::UntrustedNodeAddress;
+use servo_arc::Arc as ServoArc;
+use servo_url::ServoUrl;
 use std::cmp::{max, min};
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
@@ -39,8 +41,14 @@ use style::computed_values::visibility::T as Visibility;
 use style::context::{StyleContext, ThreadLocalStyleContext};
 use style::dom::TElement;
 use style::logical_geometry::{BlockFlowDirection, InlineBaseDirection, WritingMode};
-use style::properties::{style_structs, LonghandId, PropertyDeclarationId, PropertyId};
+use style::properties::style_structs::{self, Font};
+use style::properties::{
+    parse_one_declaration_into, Importance, LonghandId, PropertyDeclarationBlock,
+    PropertyDeclarationId, PropertyId, SourcePropertyDeclaration,
+};
 use style::selector_parser::PseudoElement;
+use style::shared_lock::SharedRwLock;
+use style_traits::ParsingMode;
 use style_traits::{CSSPixel, ToCss};
 use webrender_api::ExternalScrollId;
 
@@ -74,6 +82,9 @@ pub struct LayoutThreadData {
     /// A queued response for the resolved style property of an element.
     pub resolved_style_response: String,
 
+    /// A queued response for the resolved font style for canvas.
+    pub resolved_font_style_response: Option<ServoArc<Font>>,
+
     /// A queued response for the offset parent/rect of a node.
     pub offset_parent_response: OffsetParentResponse,
 
@@ -174,6 +185,13 @@ impl LayoutRPC for LayoutRPCImpl {
         ResolvedStyleResponse(rw_data.resolved_style_response.clone())
     }
 
+    /// Retrieves the resolved font style for canvas.
+    fn resolved_font_style(&self) -> Option<ServoArc<Font>> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.resolved_font_style_response.clone()
+    }
+
     fn offset_parent(&self) -> OffsetParentResponse {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -796,6 +814,71 @@ where
     style.computed_value_to_string(PropertyDeclarationId::Longhand(longhand_id))
 }
 
+/// Resolves the font property `value` of a canvas context against the style
+/// of its canvas element, as the `font` attribute requires.
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-font>
+pub fn process_resolved_font_style_request<'a, N>(
+    context: &LayoutContext,
+    node: N,
+    value: &str,
+    property: &PropertyId,
+    url_data: ServoUrl,
+    shared_lock: &SharedRwLock,
+) -> Option<ServoArc<Font>>
+where
+    N: LayoutNode,
+{
+    use style::stylist::RuleInclusion;
+    use style::traversal::resolve_style;
+
+    // 1. Parse the given font property value.
+    let quirks_mode = context.style_context.quirks_mode();
+    let mut declarations = SourcePropertyDeclaration::new();
+    if parse_one_declaration_into(
+        &mut declarations,
+        property.clone(),
+        value,
+        &url_data,
+        None,
+        ParsingMode::DEFAULT,
+        quirks_mode,
+    )
+    .is_err()
+    {
+        return None;
+    }
+    let mut block = PropertyDeclarationBlock::new();
+    block.extend(declarations.drain(), Importance::Normal);
+
+    // 2. Get the resolved style of the canvas element, which is the parent
+    // of the font.
+    let element = node.as_element().unwrap();
+    let parent_style = if element.get_data().is_some() {
+        node.to_threadsafe().as_element().unwrap().resolved_style()
+    } else {
+        let mut tlc = ThreadLocalStyleContext::new(&context.style_context);
+        let mut context = StyleContext {
+            shared: &context.style_context,
+            thread_local: &mut tlc,
+        };
+        let styles = resolve_style(&mut context, element, RuleInclusion::All, None);
+        styles.primary().clone()
+    };
+
+    // 3. Compute the parsed value against that style.
+    Some(
+        context
+            .style_context
+            .stylist
+            .compute_for_declarations::<N::ConcreteElement>(
+                &context.style_context.guards,
+                &*parent_style,
+                ServoArc::new(shared_lock.wrap(block)),
+            )
+            .clone_font(),
+    )
+}
+
 /// The primary resolution logic, which assumes that the element is styled.
 fn process_resolved_style_request_internal<'a, N>(
     requested_node: N,
@@ -0,0 +1,169 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Animations that script created with the Web Animations API.
+
+use crate::context::LayoutContext;
+use crossbeam_channel::Receiver;
+use fxhash::FxHashMap;
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::PipelineId;
+use script_layout_interface::wrapper_traits::LayoutNode;
+use script_traits::{AnimationState, LayoutMsg as ConstellationMsg};
+use style::animation::{update_style_for_animation, Animation};
+use style::dom::{OpaqueNode, TElement, TNode};
+use style::font_metrics::ServoMetricsProvider;
+use style::timer::Timer;
+
+/// Processes the animations that script sent since the last reflow, and
+/// expires the ones that completed, moving them to `expired_animations`.
+///
+/// This layout only runs the animations that script created, so the CSS
+/// animations and transitions that style recalculation discovers are dropped.
+pub fn update_animation_state(
+    constellation_chan: &IpcSender<ConstellationMsg>,
+    running_animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
+    expired_animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
+    new_animations_receiver: &Receiver<Animation>,
+    pipeline_id: PipelineId,
+    timer: &Timer,
+) {
+    while let Ok(mut animation) = new_animations_receiver.try_recv() {
+        let id = match animation {
+            Animation::Script(_, _, id, ref mut state) => {
+                // The local time of the animation advances from now on.
+                state.synced_at = Some(timer.seconds());
+                id
+            },
+            Animation::Transition(..) | Animation::Keyframes(..) => continue,
+        };
+
+        // The new state replaces the previous state of the animation, if any.
+        let animations = running_animations
+            .entry(*animation.node())
+            .or_insert_with(Vec::new);
+        let previous = animations.iter_mut().find(|other| match **other {
+            Animation::Script(_, _, other_id, _) => other_id == id,
+            Animation::Transition(..) | Animation::Keyframes(..) => false,
+        });
+        match previous {
+            Some(previous) => {
+                if let (Animation::Script(_, _, _, state), Animation::Script(_, _, _, previous)) =
+                    (&mut animation, &*previous)
+                {
+                    if state.cascade_style.is_none() {
+                        state.cascade_style = previous.cascade_style.clone();
+                    }
+                }
+                *previous = animation;
+            },
+            None => animations.push(animation),
+        }
+    }
+
+    if running_animations.is_empty() {
+        // Nothing to do. Return early so we don't flood the compositor with
+        // `ChangeRunningAnimationsState` messages.
+        return;
+    }
+
+    let now = timer.seconds();
+    running_animations.retain(|node, animations| {
+        let (still_running, expired): (Vec<_>, Vec<_>) =
+            animations
+                .drain(..)
+                .partition(|animation| match *animation {
+                    Animation::Script(_, _, _, ref state) => state.is_running(now),
+                    Animation::Transition(..) | Animation::Keyframes(..) => false,
+                });
+        if !expired.is_empty() {
+            expired_animations
+                .entry(*node)
+                .or_insert_with(Vec::new)
+                .extend(expired);
+        }
+        *animations = still_running;
+        !animations.is_empty()
+    });
+
+    let animation_state = if running_animations.is_empty() {
+        AnimationState::NoAnimationsPresent
+    } else {
+        AnimationState::AnimationsPresent
+    };
+
+    constellation_chan
+        .send(ConstellationMsg::ChangeRunningAnimationsState(
+            pipeline_id,
+            animation_state,
+        ))
+        .unwrap();
+}
+
+/// Applies the running animations to the style of the elements they run on,
+/// and restores the style of the elements whose animations expired.
+///
+/// Elements keep their animated style until they are restyled, so the
+/// animations always apply on top of the style the element has without them.
+pub fn recalc_style_for_animations<N>(
+    context: &LayoutContext,
+    root: N,
+    running_animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
+    expired_animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
+) where
+    N: LayoutNode,
+{
+    if running_animations.is_empty() && expired_animations.is_empty() {
+        return;
+    }
+
+    for node in root.traverse_preorder() {
+        let element = match node.as_element() {
+            Some(element) => element,
+            None => continue,
+        };
+        let expired = expired_animations.remove(&node.opaque());
+        let animations = running_animations.get_mut(&node.opaque());
+        if expired.is_none() && animations.is_none() {
+            continue;
+        }
+        let mut data = match element.mutate_data() {
+            Some(data) => data,
+            None => continue,
+        };
+
+        // The animations that script created before the element was
+        // restyled take the values their keyframes don't specify from the
+        // style the element has before they apply.
+        let cascade_style = animations
+            .iter()
+            .flat_map(|animations| animations.iter())
+            .chain(expired.iter().flatten())
+            .find_map(|animation| match *animation {
+                Animation::Script(_, _, _, ref state) => state.cascade_style.clone(),
+                Animation::Transition(..) | Animation::Keyframes(..) => None,
+            })
+            .unwrap_or_else(|| data.styles.primary().clone());
+
+        let mut style = cascade_style.clone();
+        for animation in animations.into_iter().flatten() {
+            if let Animation::Script(_, _, _, ref mut state) = *animation {
+                if state.cascade_style.is_none() {
+                    state.cascade_style = Some(cascade_style.clone());
+                }
+            }
+            update_style_for_animation::<N::ConcreteElement>(
+                &context.style_context,
+                animation,
+                &mut style,
+                &ServoMetricsProvider,
+            );
+        }
+        data.styles.primary = Some(style);
+    }
+
+    // The elements that are not in the document anymore have no style to
+    // restore.
+    expired_animations.clear();
+}
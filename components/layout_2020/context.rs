@@ -4,15 +4,26 @@
 
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context::FontContext;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use msg::constellation_msg::PipelineId;
+use script_traits::Painter;
 use std::cell::RefCell;
 use std::sync::Mutex;
-use style::context::SharedStyleContext;
+use style::context::{RegisteredSpeculativePainter, SharedStyleContext};
+use style::Atom;
 
 pub struct LayoutContext<'a> {
     pub id: PipelineId,
     pub style_context: SharedStyleContext<'a>,
     pub font_cache_thread: Mutex<FontCacheThread>,
+    /// Block containers with fewer block-level children than this are
+    /// constructed sequentially rather than with rayon.
+    pub parallel_box_construction_threshold: usize,
+    /// Block containers nested deeper than this in the document are constructed
+    /// sequentially, their ancestors already spread the work over the layout threads.
+    pub parallel_box_construction_max_depth: usize,
+    /// Paint worklets
+    pub registered_painters: &'a dyn RegisteredPainters,
 }
 
 impl<'a> LayoutContext<'a> {
@@ -22,6 +33,15 @@ impl<'a> LayoutContext<'a> {
     }
 }
 
+/// A registered painter
+pub trait RegisteredPainter: RegisteredSpeculativePainter + Painter {}
+
+/// A set of registered painters
+pub trait RegisteredPainters: Sync {
+    /// Look up a painter
+    fn get(&self, name: &Atom) -> Option<&dyn RegisteredPainter>;
+}
+
 pub(crate) type LayoutFontContext = FontContext<FontCacheThread>;
 
 thread_local!(static FONT_CONTEXT: RefCell<Option<LayoutFontContext>> = RefCell::new(None));
@@ -36,3 +56,12 @@ where
         }))
     })
 }
+
+pub fn malloc_size_of_persistent_local_context(ops: &mut MallocSizeOfOps) -> usize {
+    FONT_CONTEXT.with(|font_context| {
+        font_context
+            .borrow()
+            .as_ref()
+            .map_or(0, |font_context| font_context.size_of(ops))
+    })
+}
@@ -2,25 +2,39 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::context::LayoutContext;
 use crate::fragments::{BoxFragment, Fragment};
 use crate::geom::physical::{Rect, Vec2};
 use crate::style_ext::ComputedValuesExt;
-use euclid::{Point2D, SideOffsets2D};
+use embedder_traits::Cursor;
+use euclid::{Point2D, SideOffsets2D, Size2D};
 use gfx::text::glyph::GlyphStore;
 use std::sync::Arc;
+use style::computed_values::background_clip::single_value::T as BackgroundClip;
+use style::computed_values::background_origin::single_value::T as BackgroundOrigin;
+use style::computed_values::visibility::T as Visibility;
+use style::properties::ComputedValues;
 use style::values::computed::{BorderStyle, Length};
+use style::values::generics::image::{GenericImageLayer, Image, PaintWorklet};
+use style_traits::ToCss;
 use webrender_api::{self as wr, units, CommonItemProperties, PrimitiveFlags};
 
-pub struct DisplayListBuilder {
+pub struct DisplayListBuilder<'a> {
     pipeline_id: wr::PipelineId,
+    pub context: &'a LayoutContext<'a>,
     pub wr: wr::DisplayListBuilder,
     pub is_contentful: bool,
 }
 
-impl DisplayListBuilder {
-    pub fn new(pipeline_id: wr::PipelineId, viewport_size: wr::units::LayoutSize) -> Self {
+impl<'a> DisplayListBuilder<'a> {
+    pub fn new(
+        pipeline_id: wr::PipelineId,
+        context: &'a LayoutContext,
+        viewport_size: wr::units::LayoutSize,
+    ) -> Self {
         Self {
             pipeline_id,
+            context,
             is_contentful: false,
             wr: wr::DisplayListBuilder::new(pipeline_id, viewport_size),
         }
@@ -52,6 +66,9 @@ impl Fragment {
                 }
             },
             Fragment::Text(t) => {
+                if !is_visible(&t.parent_style) {
+                    return;
+                }
                 is_contentful.0 = true;
                 let rect = t
                     .content_rect
@@ -78,6 +95,9 @@ impl Fragment {
             },
             Fragment::Image(i) => {
                 use style::computed_values::image_rendering::T as ImageRendering;
+                if !is_visible(&i.style) {
+                    return;
+                }
                 is_contentful.0 = true;
                 let rect = i
                     .content_rect
@@ -104,6 +124,24 @@ impl Fragment {
                     wr::ColorF::WHITE,
                 );
             },
+            Fragment::Rule(r) => {
+                if !is_visible(&r.style) {
+                    return;
+                }
+                let rect = r
+                    .rect
+                    .to_physical(r.style.writing_mode(), containing_block)
+                    .translate(&containing_block.top_left);
+                let common = CommonItemProperties {
+                    clip_rect: rect.into(),
+                    clip_id: wr::ClipId::root(builder.pipeline_id),
+                    spatial_id: wr::SpatialId::root_scroll_node(builder.pipeline_id),
+                    hit_info: None,
+                    // TODO(gw): Make use of the WR backface visibility functionality.
+                    flags: PrimitiveFlags::default(),
+                };
+                builder.wr.push_rect(&common, rgba(r.style.clone_color()))
+            },
         }
     }
 }
@@ -129,28 +167,153 @@ impl BoxFragment {
             flags: PrimitiveFlags::default(),
         };
 
-        self.background_display_items(builder, &common);
-        self.border_display_items(builder, &common, border_rect);
+        // The box itself is neither painted nor hit tested, but `visibility`
+        // is inherited and descendants with `visibility: visible` still are.
         let content_rect = self
             .content_rect
             .to_physical(self.style.writing_mode(), containing_block)
             .translate(&containing_block.top_left);
+        if is_visible(&self.style) {
+            self.hit_test_display_items(builder, &common);
+            let padding_rect = self
+                .padding_rect()
+                .to_physical(self.style.writing_mode(), containing_block)
+                .translate(&containing_block.top_left)
+                .into();
+            let boxes = BackgroundBoxes {
+                border: border_rect,
+                padding: padding_rect,
+                content: content_rect.clone().into(),
+            };
+            self.background_display_items(builder, is_contentful, &common, &boxes);
+            self.border_display_items(builder, &common, border_rect);
+        }
         for child in &self.children {
             child.build_display_list(builder, is_contentful, &content_rect)
         }
     }
 
+    /// Makes the border box a target of hit testing, in front of the
+    /// backgrounds of its ancestors but behind its own content.
+    fn hit_test_display_items(
+        &self,
+        builder: &mut DisplayListBuilder,
+        common: &CommonItemProperties,
+    ) {
+        let tag = match self.tag {
+            Some(tag) => tag,
+            None => return,
+        };
+        // FIXME: Honor `cursor` and `pointer-events` once layout 2020 supports them.
+        let common = CommonItemProperties {
+            clip_rect: common.clip_rect,
+            clip_id: common.clip_id,
+            spatial_id: common.spatial_id,
+            hit_info: Some((tag.0 as u64, Cursor::Default as u16)),
+            flags: common.flags,
+        };
+        builder.wr.push_rect(&common, wr::ColorF::TRANSPARENT)
+    }
+
     fn background_display_items(
         &self,
         builder: &mut DisplayListBuilder,
+        is_contentful: &mut IsContentful,
         common: &CommonItemProperties,
+        boxes: &BackgroundBoxes,
     ) {
-        let background_color = self
-            .style
-            .resolve_color(self.style.clone_background_color());
+        let b = self.style.get_background();
+        let layers = &b.background_image.0;
+
+        // https://drafts.csswg.org/css-backgrounds/#background-color
+        // The background color is clipped like the bottom-most layer.
+        let background_color = self.style.resolve_color(b.background_color);
         if background_color.alpha > 0 {
-            builder.wr.push_rect(common, rgba(background_color))
+            let clip = *get_cyclic(&b.background_clip.0, layers.len().saturating_sub(1));
+            let common = CommonItemProperties {
+                clip_rect: boxes.clip_rect(clip),
+                ..*common
+            };
+            builder.wr.push_rect(&common, rgba(background_color))
         }
+
+        // The first layer is painted on top, so layers are painted last to first.
+        for (index, layer) in layers.iter().enumerate().rev() {
+            let clip = *get_cyclic(&b.background_clip.0, index);
+            let origin = *get_cyclic(&b.background_origin.0, index);
+            let common = CommonItemProperties {
+                clip_rect: boxes.clip_rect(clip),
+                ..*common
+            };
+            match layer {
+                GenericImageLayer::Image(Image::PaintWorklet(paint_worklet)) => self
+                    .paint_worklet_display_item(
+                        builder,
+                        is_contentful,
+                        &common,
+                        paint_worklet,
+                        boxes.positioning_area(origin),
+                    ),
+                // TODO: url() images and gradients.
+                GenericImageLayer::Image(_) | GenericImageLayer::None => {},
+            }
+        }
+    }
+
+    /// https://drafts.css-houdini.org/css-paint-api/#paint-notation
+    ///
+    /// A paint image has no intrinsic dimensions, so it is drawn
+    /// at the size of its background positioning area.
+    fn paint_worklet_display_item(
+        &self,
+        builder: &mut DisplayListBuilder,
+        is_contentful: &mut IsContentful,
+        common: &CommonItemProperties,
+        paint_worklet: &PaintWorklet,
+        positioning_area: units::LayoutRect,
+    ) {
+        if positioning_area.size.is_empty_or_negative() {
+            return;
+        }
+        // Worklets that are not registered yet paint nothing, layout is
+        // run again once they are.
+        let painter = match builder.context.registered_painters.get(&paint_worklet.name) {
+            Some(painter) => painter,
+            None => return,
+        };
+        let properties = painter
+            .properties()
+            .iter()
+            .filter_map(|(name, id)| id.as_shorthand().err().map(|id| (name, id)))
+            .map(|(name, id)| (name.clone(), self.style.computed_value_to_string(id)))
+            .collect();
+        let arguments = paint_worklet
+            .arguments
+            .iter()
+            .map(|argument| argument.to_css_string())
+            .collect();
+        let size = Size2D::new(positioning_area.size.width, positioning_area.size.height);
+        let device_pixel_ratio = builder.context.style_context.device_pixel_ratio();
+
+        // TODO: load the images in `missing_image_urls` once
+        // layout 2020 has access to the image cache.
+        let image_key =
+            match painter.draw_a_paint_image(size, device_pixel_ratio, properties, arguments) {
+                Ok(result) => match result.image_key {
+                    Some(image_key) => image_key,
+                    None => return,
+                },
+                Err(_) => return,
+            };
+        is_contentful.0 = true;
+        builder.wr.push_image(
+            common,
+            positioning_area,
+            wr::ImageRendering::Auto,
+            wr::AlphaType::PremultipliedAlpha,
+            image_key,
+            wr::ColorF::WHITE,
+        );
     }
 
     fn border_display_items(
@@ -196,6 +359,48 @@ impl BoxFragment {
     }
 }
 
+/// The boxes of a fragment that background layers are positioned in and clipped to.
+struct BackgroundBoxes {
+    border: units::LayoutRect,
+    padding: units::LayoutRect,
+    content: units::LayoutRect,
+}
+
+impl BackgroundBoxes {
+    /// https://drafts.csswg.org/css-backgrounds/#background-origin
+    fn positioning_area(&self, origin: BackgroundOrigin) -> units::LayoutRect {
+        match origin {
+            BackgroundOrigin::BorderBox => self.border,
+            BackgroundOrigin::PaddingBox => self.padding,
+            BackgroundOrigin::ContentBox => self.content,
+        }
+    }
+
+    /// https://drafts.csswg.org/css-backgrounds/#background-clip
+    fn clip_rect(&self, clip: BackgroundClip) -> units::LayoutRect {
+        match clip {
+            BackgroundClip::BorderBox => self.border,
+            BackgroundClip::PaddingBox => self.padding,
+            BackgroundClip::ContentBox => self.content,
+        }
+    }
+}
+
+/// Background properties with fewer values than `background-image`
+/// are repeated to match its number of layers.
+fn get_cyclic<T>(values: &[T], index: usize) -> &T {
+    &values[index % values.len()]
+}
+
+/// https://drafts.csswg.org/css-box/#visibility-prop
+///
+/// Invisible boxes are still laid out, but are neither painted nor hit tested.
+/// FIXME: `visibility: collapse` should hide table rows and columns
+/// once tables are supported; for now it is treated like `hidden`.
+fn is_visible(style: &ComputedValues) -> bool {
+    style.get_inherited_box().visibility == Visibility::Visible
+}
+
 fn rgba(rgba: cssparser::RGBA) -> wr::ColorF {
     wr::ColorF::new(
         rgba.red_f32(),
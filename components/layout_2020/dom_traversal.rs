@@ -9,12 +9,17 @@ use crate::replaced::ReplacedContent;
 use crate::style_ext::{Display, DisplayGeneratingBox, DisplayInside, DisplayOutside};
 use crate::wrapper::GetRawData;
 use atomic_refcell::{AtomicRefCell, AtomicRefMut};
+use html5ever::LocalName;
 use net_traits::image::base::Image as NetImage;
-use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
+use script_layout_interface::wrapper_traits::ThreadSafeLayoutNode;
+use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutElement};
+use selectors::Element;
 use servo_arc::Arc as ServoArc;
+use std::iter::Flatten;
 use std::marker::PhantomData as marker;
+use std::option::IntoIter as OptionIntoIter;
 use std::sync::Arc;
-use style::dom::TNode;
+use style::dom::{LayoutIterator, OpaqueNode, TElement, TNode};
 use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 use style::values::computed::Length;
@@ -55,8 +60,12 @@ where
     fn handle_text(&mut self, text: String, parent_style: &ServoArc<ComputedValues>);
 
     /// Or pseudo-element
+    ///
+    /// `tag` is the DOM element that generated the box, if any,
+    /// so that fragments can later be found again by layout queries.
     fn handle_element(
         &mut self,
+        tag: Option<OpaqueNode>,
         style: &ServoArc<ComputedValues>,
         display: DisplayGeneratingBox,
         contents: Contents<Node>,
@@ -73,14 +82,12 @@ fn traverse_children_of<'dom, Node>(
 {
     traverse_pseudo_element(WhichPseudoElement::Before, parent_element, context, handler);
 
-    let mut next = parent_element.first_child();
-    while let Some(child) = next {
+    for child in parent_element.flat_tree_children() {
         if let Some(contents) = child.as_text() {
             handler.handle_text(contents, &child.style(context));
         } else if child.is_element() {
             traverse_element(child, context, handler);
         }
-        next = child.next_sibling();
     }
 
     traverse_pseudo_element(WhichPseudoElement::After, parent_element, context, handler);
@@ -107,8 +114,14 @@ fn traverse_element<'dom, Node>(
                 traverse_children_of(element, context, handler)
             }
         },
-        Display::GeneratingBox(display) => {
+        Display::GeneratingBox(mut display) => {
+            // FIXME: this should be `display: math` from the UA stylesheet.
+            if element.is_mathml_element() && element.has_local_name(&local_name!("math")) {
+                let DisplayGeneratingBox::OutsideInside { inside, .. } = &mut display;
+                *inside = DisplayInside::MathML;
+            }
             handler.handle_element(
+                Some(element.opaque()),
                 &style,
                 display,
                 replaced.map_or(Contents::OfElement(element), Contents::Replaced),
@@ -138,7 +151,7 @@ fn traverse_pseudo_element<'dom, Node>(
                 let items = generate_pseudo_element_content(&style, element, context);
                 let contents = Contents::OfPseudoElement(items);
                 let box_slot = element.pseudo_element_box_slot(which);
-                handler.handle_element(&style, display, contents, box_slot);
+                handler.handle_element(None, &style, display, contents, box_slot);
             },
         }
     }
@@ -177,6 +190,7 @@ fn traverse_pseudo_element_contents<'dom, Node>(
                         Display::GeneratingBox(display_inline)
                 );
                 handler.handle_element(
+                    None,
                     item_style,
                     display_inline,
                     Contents::Replaced(contents),
@@ -296,12 +310,21 @@ impl Drop for BoxSlot<'_> {
     }
 }
 
+/// The children of a node in the flat tree.
+pub(crate) type FlatTreeChildren<Node> = Flatten<
+    OptionIntoIter<
+        LayoutIterator<<<Node as TNode>::ConcreteElement as TElement>::TraversalChildrenIterator>,
+    >,
+>;
+
 pub(crate) trait NodeExt<'dom>: 'dom + Copy + LayoutNode + Send + Sync {
     fn is_element(self) -> bool;
     fn as_text(self) -> Option<String>;
     fn as_image(self) -> Option<(Option<Arc<NetImage>>, Vec2<Length>)>;
-    fn first_child(self) -> Option<Self>;
-    fn next_sibling(self) -> Option<Self>;
+    fn is_mathml_element(self) -> bool;
+    fn has_local_name(self, local_name: &LocalName) -> bool;
+    fn get_attribute(self, local_name: &LocalName) -> Option<String>;
+    fn flat_tree_children(self) -> FlatTreeChildren<Self>;
     fn parent_node(self) -> Option<Self>;
     fn style(self, context: &LayoutContext) -> ServoArc<ComputedValues>;
 
@@ -348,12 +371,31 @@ where
         Some((resource, size))
     }
 
-    fn first_child(self) -> Option<Self> {
-        TNode::first_child(&self)
+    fn is_mathml_element(self) -> bool {
+        self.to_threadsafe()
+            .as_element()
+            .map_or(false, |element| element.has_namespace(&ns!(mathml)))
+    }
+
+    fn has_local_name(self, local_name: &LocalName) -> bool {
+        self.to_threadsafe().as_element().map_or(false, |element| {
+            Element::has_local_name(&element, local_name)
+        })
+    }
+
+    fn get_attribute(self, local_name: &LocalName) -> Option<String> {
+        let element = self.to_threadsafe().as_element()?;
+        element.get_attr(&ns!(), local_name).map(String::from)
     }
 
-    fn next_sibling(self) -> Option<Self> {
-        TNode::next_sibling(&self)
+    /// The children of a shadow host in the flat tree are those of its
+    /// shadow root, and the children of a slot are its assigned nodes,
+    /// <https://drafts.csswg.org/css-scoping/#flat-tree>
+    fn flat_tree_children(self) -> FlatTreeChildren<Self> {
+        TNode::as_element(&self)
+            .map(|element| element.traversal_children())
+            .into_iter()
+            .flatten()
     }
 
     fn parent_node(self) -> Option<Self> {
@@ -395,39 +437,25 @@ where
 
     fn unset_boxes_in_subtree(self) {
         assert!(self.is_element());
-        assert!(self.parent_node().is_some());
-
-        let mut node = self;
-        loop {
-            if node.is_element() {
-                let traverse_children = {
-                    let mut layout_data = node.layout_data_mut();
-                    layout_data.pseudo_elements = None;
-                    let self_box = layout_data.self_box.borrow_mut().take();
-                    self_box.is_some()
-                };
-                if traverse_children {
-                    // Only descend into children if we removed a box.
-                    // If there wasn’t one, then descendants don’t have boxes either.
-                    if let Some(child) = node.first_child() {
-                        node = child;
-                        continue;
-                    }
-                }
-            }
-            let mut next_is_a_sibling_of = node;
-            node = loop {
-                if let Some(sibling) = next_is_a_sibling_of.next_sibling() {
-                    break sibling;
-                } else {
-                    next_is_a_sibling_of = node
-                        .parent_node()
-                        .expect("reached the root while traversing only a subtree");
-                }
+
+        // The boxes of slotted nodes and shadow trees are in the flat tree,
+        // so it is walked instead of the DOM tree.
+        let mut elements = vec![self];
+        while let Some(element) = elements.pop() {
+            let traverse_children = {
+                let mut layout_data = element.layout_data_mut();
+                layout_data.pseudo_elements = None;
+                let self_box = layout_data.self_box.borrow_mut().take();
+                self_box.is_some()
             };
-            if next_is_a_sibling_of == self {
-                // Don’t go outside the subtree.
-                return;
+            if traverse_children {
+                // Only descend into children if we removed a box.
+                // If there wasn’t one, then descendants don’t have boxes either.
+                elements.extend(
+                    element
+                        .flat_tree_children()
+                        .filter(|child| child.is_element()),
+                );
             }
         }
     }
@@ -4,6 +4,7 @@
 
 use crate::flow::inline::InlineLevelBox;
 use crate::flow::BlockLevelBox;
+use crate::mathml::MathBox;
 use atomic_refcell::AtomicRefCell;
 use servo_arc::Arc;
 
@@ -23,4 +24,5 @@ pub(super) enum LayoutBox {
     DisplayContents,
     BlockLevel(Arc<BlockLevelBox>),
     InlineLevel(Arc<InlineLevelBox>),
+    Math(Arc<MathBox>),
 }
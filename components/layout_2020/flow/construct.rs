@@ -16,6 +16,7 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rayon_croissant::ParallelIteratorExt;
 use servo_arc::Arc;
 use std::convert::{TryFrom, TryInto};
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 
@@ -40,20 +41,24 @@ impl BlockFormattingContext {
 
 enum IntermediateBlockLevelBox<Node> {
     SameFormattingContextBlock {
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         contents: IntermediateBlockContainer<Node>,
     },
     Independent {
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
     },
     OutOfFlowAbsolutelyPositionedBox {
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
     },
     OutOfFlowFloatBox {
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
@@ -135,6 +140,10 @@ impl BlockContainer {
         contents: NonReplacedContents<impl NodeExt<'dom>>,
         content_sizes: ContentSizesRequest,
     ) -> (BlockContainer, ContainsFloats, BoxContentSizes) {
+        let node = match contents {
+            NonReplacedContents::OfElement(ref node) => Some(*node),
+            NonReplacedContents::OfPseudoElement(_) => None,
+        };
         let mut builder = BlockContainerBuilder {
             context,
             block_container_style,
@@ -185,10 +194,8 @@ impl BlockContainer {
             contains_floats: builder.contains_floats,
             outer_content_sizes_of_children: ContentSizes::zero(),
         };
-        let iter = builder.block_level_boxes.into_par_iter();
-        let iter = iter.mapfold_reduce_into(
-            &mut target,
-            |target, (intermediate, box_slot): (Intermediate<_>, BoxSlot<'_>)| {
+        let mapfold =
+            |target: &mut Target, (intermediate, box_slot): (Intermediate<_>, BoxSlot<'_>)| {
                 let (block_level_box, box_contains_floats) = intermediate.finish(
                     context,
                     content_sizes
@@ -197,16 +204,34 @@ impl BlockContainer {
                 target.contains_floats |= box_contains_floats;
                 box_slot.set(LayoutBox::BlockLevel(block_level_box.clone()));
                 block_level_box
-            },
-            |left, right| {
-                left.contains_floats |= right.contains_floats;
-                if content_sizes.requests_inline() {
-                    left.outer_content_sizes_of_children
-                        .max_assign(&right.outer_content_sizes_of_children)
-                }
-            },
-        );
-        let container = BlockContainer::BlockLevelBoxes(iter.collect());
+            };
+        // For small or deeply nested containers the overhead of spawning rayon jobs
+        // and reducing their results dominates, so build them sequentially.
+        let sequential = builder.block_level_boxes.len() <
+            context.parallel_box_construction_threshold ||
+            node.map_or(false, |node| {
+                is_deeper_than(node, context.parallel_box_construction_max_depth)
+            });
+        let block_level_boxes = if sequential {
+            builder
+                .block_level_boxes
+                .into_iter()
+                .map(|intermediate| mapfold(&mut target, intermediate))
+                .collect()
+        } else {
+            builder
+                .block_level_boxes
+                .into_par_iter()
+                .mapfold_reduce_into(&mut target, &mapfold, |left, right| {
+                    left.contains_floats |= right.contains_floats;
+                    if content_sizes.requests_inline() {
+                        left.outer_content_sizes_of_children
+                            .max_assign(&right.outer_content_sizes_of_children)
+                    }
+                })
+                .collect()
+        };
+        let container = BlockContainer::BlockLevelBoxes(block_level_boxes);
 
         let Target {
             contains_floats,
@@ -223,6 +248,7 @@ where
 {
     fn handle_element(
         &mut self,
+        tag: Option<OpaqueNode>,
         style: &Arc<ComputedValues>,
         display: DisplayGeneratingBox,
         contents: Contents<Node>,
@@ -231,7 +257,7 @@ where
         match display {
             DisplayGeneratingBox::OutsideInside { outside, inside } => match outside {
                 DisplayOutside::Inline => box_slot.set(LayoutBox::InlineLevel(
-                    self.handle_inline_level_element(style, inside, contents),
+                    self.handle_inline_level_element(tag, style, inside, contents),
                 )),
                 DisplayOutside::Block => {
                     let box_style = style.get_box();
@@ -239,15 +265,22 @@ where
                     // https://drafts.csswg.org/css2/visuren.html#dis-pos-flo
                     if box_style.position.is_absolutely_positioned() {
                         self.handle_absolutely_positioned_element(
+                            tag,
                             style.clone(),
                             inside,
                             contents,
                             box_slot,
                         )
                     } else if box_style.float.is_floating() {
-                        self.handle_float_element(style.clone(), inside, contents, box_slot)
+                        self.handle_float_element(tag, style.clone(), inside, contents, box_slot)
                     } else {
-                        self.handle_block_level_element(style.clone(), inside, contents, box_slot)
+                        self.handle_block_level_element(
+                            tag,
+                            style.clone(),
+                            inside,
+                            contents,
+                            box_slot,
+                        )
                     }
                 },
             },
@@ -296,10 +329,19 @@ where
                 if let Some(i) = input.bytes().position(|b| b.is_ascii_whitespace()) {
                     let (non_whitespace, rest) = input.split_at(i);
                     output.push_str(non_whitespace);
-                    output.push(' ');
                     if let Some(i) = rest.bytes().position(|b| !b.is_ascii_whitespace()) {
-                        input = &rest[i..];
+                        let (whitespace, rest) = rest.split_at(i);
+                        let removed = whitespace.contains('\n') &&
+                            output.chars().next_back().map_or(false, |before| {
+                                let after = rest.chars().next().unwrap();
+                                segment_break_is_removed(before, after)
+                            });
+                        if !removed {
+                            output.push(' ');
+                        }
+                        input = rest;
                     } else {
+                        output.push(' ');
                         break;
                     }
                 } else {
@@ -319,6 +361,38 @@ where
     }
 }
 
+/// https://drafts.csswg.org/css-text-3/#line-break-transform
+///
+/// A collapsible segment break between two East Asian Full-width, Wide or
+/// Half-width characters, neither of them Hangul, is removed rather than
+/// turned into a space.
+///
+/// FIXME: this only looks at segment breaks within a single text node.
+fn segment_break_is_removed(before: char, after: char) -> bool {
+    is_wide_non_hangul(before) && is_wide_non_hangul(after)
+}
+
+/// Whether the East Asian Width of `c` is F, W or H, excluding Hangul.
+/// http://www.unicode.org/reports/tr11/
+fn is_wide_non_hangul(c: char) -> bool {
+    match c as u32 {
+        0x2E80..=0x303E |
+        0x3041..=0x312F |
+        0x3190..=0x33FF |
+        0x3400..=0x4DBF |
+        0x4E00..=0x9FFF |
+        0xA000..=0xA4CF |
+        0xF900..=0xFAFF |
+        0xFE30..=0xFE4F |
+        0xFF00..=0xFF60 |
+        0xFF61..=0xFF9F |
+        0xFFE0..=0xFFE6 |
+        0x20000..=0x2FFFD |
+        0x30000..=0x3FFFD => true,
+        _ => false,
+    }
+}
+
 impl<'dom, Node> BlockContainerBuilder<'dom, '_, Node>
 where
     Node: NodeExt<'dom>,
@@ -360,6 +434,7 @@ where
 
     fn handle_inline_level_element(
         &mut self,
+        tag: Option<OpaqueNode>,
         style: &Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
@@ -369,6 +444,7 @@ where
             // Whatever happened before, all we need to do before recurring
             // is to remember this ongoing inline level box.
             self.ongoing_inline_boxes_stack.push(InlineBox {
+                tag,
                 style: style.clone(),
                 first_fragment: true,
                 last_fragment: false,
@@ -390,6 +466,7 @@ where
             Arc::new(InlineLevelBox::Atomic(
                 IndependentFormattingContext::construct(
                     self.context,
+                    tag,
                     style.clone(),
                     display_inside,
                     contents,
@@ -403,6 +480,7 @@ where
 
     fn handle_block_level_element(
         &mut self,
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
@@ -419,6 +497,7 @@ where
                 .rev()
                 .map(|ongoing| {
                     let fragmented = InlineBox {
+                        tag: ongoing.tag,
                         style: ongoing.style.clone(),
                         first_fragment: ongoing.first_fragment,
                         // The fragmented boxes before the block level element
@@ -454,10 +533,12 @@ where
         let intermediate_box = match contents.try_into() {
             Ok(contents) => match display_inside {
                 DisplayInside::Flow => IntermediateBlockLevelBox::SameFormattingContextBlock {
+                    tag,
                     style,
                     contents: IntermediateBlockContainer::Deferred { contents },
                 },
                 _ => IntermediateBlockLevelBox::Independent {
+                    tag,
                     style,
                     display_inside,
                     contents: contents.into(),
@@ -466,6 +547,7 @@ where
             Err(contents) => {
                 let contents = Contents::Replaced(contents);
                 IntermediateBlockLevelBox::Independent {
+                    tag,
                     style,
                     display_inside,
                     contents,
@@ -477,6 +559,7 @@ where
 
     fn handle_absolutely_positioned_element(
         &mut self,
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
@@ -484,6 +567,7 @@ where
     ) {
         if !self.has_ongoing_inline_formatting_context() {
             let box_ = IntermediateBlockLevelBox::OutOfFlowAbsolutelyPositionedBox {
+                tag,
                 style,
                 contents,
                 display_inside,
@@ -491,7 +575,13 @@ where
             self.block_level_boxes.push((box_, box_slot));
         } else {
             let box_ = Arc::new(InlineLevelBox::OutOfFlowAbsolutelyPositionedBox(
-                AbsolutelyPositionedBox::construct(self.context, style, display_inside, contents),
+                AbsolutelyPositionedBox::construct(
+                    self.context,
+                    tag,
+                    style,
+                    display_inside,
+                    contents,
+                ),
             ));
             self.current_inline_level_boxes().push(box_.clone());
             box_slot.set(LayoutBox::InlineLevel(box_))
@@ -500,6 +590,7 @@ where
 
     fn handle_float_element(
         &mut self,
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<Node>,
@@ -509,6 +600,7 @@ where
 
         if !self.has_ongoing_inline_formatting_context() {
             let box_ = IntermediateBlockLevelBox::OutOfFlowFloatBox {
+                tag,
                 style,
                 contents,
                 display_inside,
@@ -517,6 +609,7 @@ where
         } else {
             let box_ = Arc::new(InlineLevelBox::OutOfFlowFloatBox(FloatBox::construct(
                 self.context,
+                tag,
                 style,
                 display_inside,
                 contents,
@@ -550,6 +643,7 @@ where
         });
 
         let box_ = IntermediateBlockLevelBox::SameFormattingContextBlock {
+            tag: None,
             style: anonymous_style.clone(),
             contents: IntermediateBlockContainer::InlineFormattingContext(std::mem::take(
                 &mut self.ongoing_inline_formatting_context,
@@ -584,7 +678,11 @@ where
         max_assign_in_flow_outer_content_sizes_to: Option<&mut ContentSizes>,
     ) -> (Arc<BlockLevelBox>, ContainsFloats) {
         match self {
-            IntermediateBlockLevelBox::SameFormattingContextBlock { style, contents } => {
+            IntermediateBlockLevelBox::SameFormattingContextBlock {
+                tag,
+                style,
+                contents,
+            } => {
                 let (contents, contains_floats, box_content_sizes) = contents.finish(
                     context,
                     &style,
@@ -596,11 +694,15 @@ where
                 if let Some(to) = max_assign_in_flow_outer_content_sizes_to {
                     to.max_assign(&box_content_sizes.outer_inline(&style))
                 }
-                let block_level_box =
-                    Arc::new(BlockLevelBox::SameFormattingContextBlock { contents, style });
+                let block_level_box = Arc::new(BlockLevelBox::SameFormattingContextBlock {
+                    tag,
+                    contents,
+                    style,
+                });
                 (block_level_box, contains_floats)
             },
             IntermediateBlockLevelBox::Independent {
+                tag,
                 style,
                 display_inside,
                 contents,
@@ -611,6 +713,7 @@ where
                 );
                 let contents = IndependentFormattingContext::construct(
                     context,
+                    tag,
                     style,
                     display_inside,
                     contents,
@@ -625,22 +728,30 @@ where
                 )
             },
             IntermediateBlockLevelBox::OutOfFlowAbsolutelyPositionedBox {
+                tag,
                 style,
                 display_inside,
                 contents,
             } => {
                 let block_level_box = Arc::new(BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(
-                    AbsolutelyPositionedBox::construct(context, style, display_inside, contents),
+                    AbsolutelyPositionedBox::construct(
+                        context,
+                        tag,
+                        style,
+                        display_inside,
+                        contents,
+                    ),
                 ));
                 (block_level_box, ContainsFloats::No)
             },
             IntermediateBlockLevelBox::OutOfFlowFloatBox {
+                tag,
                 style,
                 display_inside,
                 contents,
             } => {
                 let block_level_box = Arc::new(BlockLevelBox::OutOfFlowFloatBox(
-                    FloatBox::construct(context, style, display_inside, contents),
+                    FloatBox::construct(context, tag, style, display_inside, contents),
                 ));
                 (block_level_box, ContainsFloats::Yes)
             },
@@ -690,3 +801,50 @@ impl std::ops::BitOrAssign for ContainsFloats {
         }
     }
 }
+
+/// Whether the node has more than `max_depth` ancestors, walking up at most
+/// that many of them.
+fn is_deeper_than<'dom>(node: impl NodeExt<'dom>, max_depth: usize) -> bool {
+    let mut ancestor = node;
+    for _ in 0..=max_depth {
+        ancestor = match ancestor.parent_node() {
+            Some(parent) => parent,
+            None => return false,
+        };
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_wide_non_hangul, segment_break_is_removed};
+
+    #[test]
+    fn wide_characters() {
+        // Han, Hiragana, Katakana, fullwidth and halfwidth forms.
+        for &c in &['日', '本', 'か', 'ア', 'Ａ', '，', 'ｱ', '￥'] {
+            assert!(is_wide_non_hangul(c), "{:?} is wide", c);
+        }
+        for &c in &['a', '1', ' ', 'é', '“', 'Ω'] {
+            assert!(!is_wide_non_hangul(c), "{:?} is not wide", c);
+        }
+    }
+
+    #[test]
+    fn hangul_is_not_wide() {
+        for &c in &['한', '국', 'ㄱ', 'ﾡ'] {
+            assert!(!is_wide_non_hangul(c), "{:?} is Hangul", c);
+        }
+    }
+
+    #[test]
+    fn segment_breaks() {
+        assert!(segment_break_is_removed('語', '中'));
+        assert!(segment_break_is_removed('か', 'Ａ'));
+        assert!(!segment_break_is_removed('語', 'a'));
+        assert!(!segment_break_is_removed('a', '中'));
+        assert!(!segment_break_is_removed('a', 'b'));
+        assert!(!segment_break_is_removed('한', '국'));
+        assert!(!segment_break_is_removed('語', '한'));
+    }
+}
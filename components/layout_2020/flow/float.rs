@@ -8,9 +8,10 @@ use crate::formatting_contexts::IndependentFormattingContext;
 use crate::sizing::ContentSizesRequest;
 use crate::style_ext::{ComputedValuesExt, DisplayInside};
 use servo_arc::Arc;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) struct FloatBox {
     pub contents: IndependentFormattingContext,
 }
@@ -29,6 +30,7 @@ impl FloatContext {
 impl FloatBox {
     pub fn construct<'dom>(
         context: &LayoutContext,
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<impl NodeExt<'dom>>,
@@ -37,6 +39,7 @@ impl FloatBox {
         Self {
             contents: IndependentFormattingContext::construct(
                 context,
+                tag,
                 style,
                 display_inside,
                 contents,
@@ -4,7 +4,7 @@
 
 use crate::context::LayoutContext;
 use crate::flow::float::FloatBox;
-use crate::flow::FlowLayout;
+use crate::flow::{size_of_boxes, FlowLayout};
 use crate::formatting_contexts::IndependentFormattingContext;
 use crate::fragments::CollapsedBlockMargins;
 use crate::fragments::{AnonymousFragment, BoxFragment, Fragment, TextFragment};
@@ -15,9 +15,13 @@ use crate::style_ext::{ComputedValuesExt, Display, DisplayGeneratingBox, Display
 use crate::{relative_adjustement, ContainingBlock};
 use app_units::Au;
 use gfx::text::text_run::GlyphRun;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use servo_arc::Arc;
+use style::computed_values::overflow_x::T as Overflow;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::{Length, LengthPercentage, Percentage};
+use style::values::generics::text::LineHeight;
 use style::Zero;
 use webrender_api::FontInstanceKey;
 
@@ -26,7 +30,7 @@ pub(crate) struct InlineFormattingContext {
     pub(super) inline_level_boxes: Vec<Arc<InlineLevelBox>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) enum InlineLevelBox {
     InlineBox(InlineBox),
     TextRun(TextRun),
@@ -37,6 +41,7 @@ pub(crate) enum InlineLevelBox {
 
 #[derive(Debug)]
 pub(crate) struct InlineBox {
+    pub tag: Option<OpaqueNode>,
     pub style: Arc<ComputedValues>,
     pub first_fragment: bool,
     pub last_fragment: bool,
@@ -44,20 +49,36 @@ pub(crate) struct InlineBox {
 }
 
 /// https://www.w3.org/TR/css-display-3/#css-text-run
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) struct TextRun {
+    #[ignore_malloc_size_of = "Shared with the style system"]
     pub parent_style: Arc<ComputedValues>,
     pub text: String,
 }
 
+impl MallocSizeOf for InlineFormattingContext {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        size_of_boxes(&self.inline_level_boxes, ops)
+    }
+}
+
+impl MallocSizeOf for InlineBox {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        size_of_boxes(&self.children, ops)
+    }
+}
+
 struct InlineNestingLevelState<'box_tree> {
     remaining_boxes: std::slice::Iter<'box_tree, Arc<InlineLevelBox>>,
     fragments_so_far: Vec<Fragment>,
+    /// The baseline of each of `fragments_so_far`, or its bottom margin edge if it has none.
+    baselines_so_far: Vec<Length>,
     inline_start: Length,
     max_block_size_of_fragments_so_far: Length,
 }
 
 struct PartialInlineBoxFragment<'box_tree> {
+    tag: Option<OpaqueNode>,
     style: Arc<ComputedValues>,
     start_corner: Vec2<Length>,
     padding: Sides<Length>,
@@ -209,6 +230,7 @@ impl InlineFormattingContext {
             current_nesting_level: InlineNestingLevelState {
                 remaining_boxes: self.inline_level_boxes.iter(),
                 fragments_so_far: Vec::with_capacity(self.inline_level_boxes.len()),
+                baselines_so_far: Vec::with_capacity(self.inline_level_boxes.len()),
                 inline_start: Length::zero(),
                 max_block_size_of_fragments_so_far: Length::zero(),
             },
@@ -217,7 +239,7 @@ impl InlineFormattingContext {
             if let Some(child) = ifc.current_nesting_level.remaining_boxes.next() {
                 match &**child {
                     InlineLevelBox::InlineBox(inline) => {
-                        let partial = inline.start_layout(&mut ifc);
+                        let partial = inline.start_layout(layout_context, &mut ifc);
                         ifc.partial_inline_boxes_stack.push(partial)
                     },
                     InlineLevelBox::TextRun(run) => run.layout(layout_context, &mut ifc),
@@ -271,12 +293,73 @@ impl InlineFormattingContext {
     }
 }
 
+impl InlineNestingLevelState<'_> {
+    fn push_fragment(&mut self, fragment: Fragment, baseline: Option<Length>) {
+        let block_end = margin_box_block_end(&fragment);
+        self.max_block_size_of_fragments_so_far
+            .max_assign(block_end);
+        self.baselines_so_far.push(baseline.unwrap_or(block_end));
+        self.fragments_so_far.push(fragment);
+    }
+
+    /// https://drafts.csswg.org/css2/visudet.html#propdef-vertical-align
+    ///
+    /// Moves the fragments laid out so far in the block direction so that their
+    /// baselines line up, as for `vertical-align: baseline`.
+    /// Returns the resulting baseline (if any fragment was laid out) and block size.
+    fn align_baselines(&mut self) -> (Option<Length>, Length) {
+        let baseline = self
+            .baselines_so_far
+            .iter()
+            .fold(None, |max: Option<Length>, &baseline| {
+                Some(max.map_or(baseline, |max| max.max(baseline)))
+            });
+        for (fragment, fragment_baseline) in self
+            .fragments_so_far
+            .iter_mut()
+            .zip(self.baselines_so_far.drain(..))
+        {
+            let offset = baseline.unwrap() - fragment_baseline;
+            move_in_block_direction(fragment, offset);
+            self.max_block_size_of_fragments_so_far
+                .max_assign(margin_box_block_end(fragment));
+        }
+        (baseline, self.max_block_size_of_fragments_so_far)
+    }
+}
+
+fn margin_box_block_end(fragment: &Fragment) -> Length {
+    let (rect, pbm_block_end) = match fragment {
+        Fragment::Box(box_) => (
+            &box_.content_rect,
+            box_.padding.block_end + box_.border.block_end + box_.margin.block_end,
+        ),
+        Fragment::Anonymous(anonymous) => (&anonymous.rect, Length::zero()),
+        Fragment::Text(text) => (&text.content_rect, Length::zero()),
+        Fragment::Image(image) => (&image.content_rect, Length::zero()),
+        Fragment::Rule(rule) => (&rule.rect, Length::zero()),
+    };
+    rect.start_corner.block + rect.size.block + pbm_block_end
+}
+
+fn move_in_block_direction(fragment: &mut Fragment, offset: Length) {
+    let start_corner = match fragment {
+        Fragment::Box(box_) => &mut box_.content_rect.start_corner,
+        Fragment::Anonymous(anonymous) => &mut anonymous.rect.start_corner,
+        Fragment::Text(text) => &mut text.content_rect.start_corner,
+        Fragment::Image(image) => &mut image.content_rect.start_corner,
+        Fragment::Rule(rule) => &mut rule.rect.start_corner,
+    };
+    start_corner.block += offset;
+}
+
 impl LinesBoxes {
     fn finish_line(
         &mut self,
         top_nesting_level: &mut InlineNestingLevelState,
         containing_block: &ContainingBlock,
     ) {
+        top_nesting_level.align_baselines();
         let start_corner = Vec2 {
             inline: Length::zero(),
             block: self.next_line_block_position,
@@ -300,6 +383,7 @@ impl LinesBoxes {
 impl InlineBox {
     fn start_layout<'box_tree>(
         &'box_tree self,
+        layout_context: &LayoutContext,
         ifc: &mut InlineFormattingContextState<'box_tree, '_>,
     ) -> PartialInlineBoxFragment<'box_tree> {
         let style = self.style.clone();
@@ -326,7 +410,25 @@ impl InlineBox {
             ifc.containing_block.inline_size,
             ifc.containing_block.block_size,
         );
+
+        // https://drafts.csswg.org/css2/visuren.html#phantom-line-box
+        // An inline box with non-zero margins, padding or borders, or one that sets
+        // its own line-height, still contributes a strut to its line even when it has
+        // no in-flow content, so that empty `<span>`s used as spacers take up room.
+        let has_non_zero_pbm = [&padding, &border, &margin].iter().any(|sides| {
+            sides.inline_sum() != Length::zero() || sides.block_sum() != Length::zero()
+        });
+        let has_explicit_line_height = match style.get_inherited_text().line_height {
+            LineHeight::Normal => false,
+            LineHeight::Number(_) | LineHeight::Length(_) => true,
+        };
+        let strut = if has_non_zero_pbm || has_explicit_line_height {
+            line_height(&style, || font_line_gap(layout_context, &style))
+        } else {
+            Length::zero()
+        };
         PartialInlineBoxFragment {
+            tag: self.tag,
             style,
             start_corner,
             padding,
@@ -338,8 +440,9 @@ impl InlineBox {
                 InlineNestingLevelState {
                     remaining_boxes: self.children.iter(),
                     fragments_so_far: Vec::with_capacity(self.children.len()),
+                    baselines_so_far: Vec::with_capacity(self.children.len()),
                     inline_start: ifc.inline_position,
-                    max_block_size_of_fragments_so_far: Length::zero(),
+                    max_block_size_of_fragments_so_far: strut,
                 },
             ),
         }
@@ -353,13 +456,15 @@ impl<'box_tree> PartialInlineBoxFragment<'box_tree> {
         inline_position: &mut Length,
         at_line_break: bool,
     ) {
+        let (baseline, block_size) = nesting_level.align_baselines();
         let mut fragment = BoxFragment {
+            tag: self.tag,
             style: self.style.clone(),
             children: std::mem::take(&mut nesting_level.fragments_so_far),
             content_rect: Rect {
                 size: Vec2 {
                     inline: *inline_position - self.start_corner.inline,
-                    block: nesting_level.max_block_size_of_fragments_so_far,
+                    block: block_size,
                 },
                 start_corner: self.start_corner.clone(),
             },
@@ -378,17 +483,9 @@ impl<'box_tree> PartialInlineBoxFragment<'box_tree> {
             fragment.border.inline_end = Length::zero();
             fragment.margin.inline_end = Length::zero();
         }
+        let baseline = baseline.map(|baseline| fragment.content_rect.start_corner.block + baseline);
         self.parent_nesting_level
-            .max_block_size_of_fragments_so_far
-            .max_assign(
-                fragment.content_rect.size.block +
-                    fragment.padding.block_sum() +
-                    fragment.border.block_sum() +
-                    fragment.margin.block_sum(),
-            );
-        self.parent_nesting_level
-            .fragments_so_far
-            .push(Fragment::Box(fragment));
+            .push_fragment(Fragment::Box(fragment), baseline);
     }
 }
 
@@ -417,14 +514,18 @@ fn layout_atomic<'box_tree>(
         ifc.containing_block.block_size,
     );
 
-    let fragment = match atomic.as_replaced() {
+    // https://drafts.csswg.org/css2/visudet.html#propdef-vertical-align
+    // The baseline of an atomic inline is the baseline of its last line box,
+    // or its bottom margin edge if it has none or if its `overflow` is not `visible`.
+    let (fragment, content_baseline) = match atomic.as_replaced() {
         Ok(replaced) => {
             // FIXME: implement https://drafts.csswg.org/css2/visudet.html#inline-replaced-width
             // and https://drafts.csswg.org/css2/visudet.html#inline-replaced-height
             let size = Vec2::zero();
             let fragments = replaced.make_fragments(&atomic.style, size.clone());
             let content_rect = Rect { start_corner, size };
-            BoxFragment {
+            let fragment = BoxFragment {
+                tag: atomic.tag,
                 style: atomic.style.clone(),
                 children: fragments,
                 content_rect,
@@ -432,7 +533,8 @@ fn layout_atomic<'box_tree>(
                 border,
                 margin,
                 block_margins_collapsed_with_children: CollapsedBlockMargins::zero(),
-            }
+            };
+            (fragment, None)
         },
         Err(non_replaced) => {
             let box_size = atomic.style.box_size();
@@ -469,7 +571,12 @@ fn layout_atomic<'box_tree>(
                     inline: inline_size,
                 },
             };
-            BoxFragment {
+            let baseline = match atomic.style.get_box().overflow_x {
+                Overflow::Visible => independent_layout.last_baseline,
+                _ => None,
+            };
+            let fragment = BoxFragment {
+                tag: atomic.tag,
                 style: atomic.style.clone(),
                 children: independent_layout.fragments,
                 content_rect,
@@ -477,14 +584,41 @@ fn layout_atomic<'box_tree>(
                 border,
                 margin,
                 block_margins_collapsed_with_children: CollapsedBlockMargins::zero(),
-            }
+            };
+            (fragment, baseline)
         },
     };
 
     ifc.inline_position += pbm.inline_end;
+    let baseline =
+        content_baseline.map(|baseline| fragment.content_rect.start_corner.block + baseline);
     ifc.current_nesting_level
-        .fragments_so_far
-        .push(Fragment::Box(fragment));
+        .push_fragment(Fragment::Box(fragment), baseline);
+}
+
+/// https://drafts.csswg.org/css2/visudet.html#propdef-line-height
+///
+/// `font_line_gap` is only called for `line-height: normal`, since looking up
+/// the first available font is not free.
+fn line_height(style: &ComputedValues, font_line_gap: impl FnOnce() -> Au) -> Length {
+    let font_size = style.get_font().font_size.size.0;
+    match style.get_inherited_text().line_height {
+        LineHeight::Normal => font_line_gap().into(),
+        LineHeight::Number(n) => font_size * n.0,
+        LineHeight::Length(l) => l.0,
+    }
+}
+
+fn font_line_gap(layout_context: &LayoutContext, style: &ComputedValues) -> Au {
+    crate::context::with_thread_local_font_context(layout_context, |font_context| {
+        let font_group = font_context.font_group(style.clone_font());
+        let font = font_group
+            .borrow_mut()
+            .first(font_context)
+            .expect("could not find font");
+        let line_gap = font.borrow().metrics.line_gap;
+        line_gap
+    })
 }
 
 struct BreakAndShapeResult {
@@ -554,8 +688,6 @@ impl TextRun {
     }
 
     fn layout(&self, layout_context: &LayoutContext, ifc: &mut InlineFormattingContextState) {
-        use style::values::generics::text::LineHeight;
-
         let BreakAndShapeResult {
             font_ascent,
             font_line_gap,
@@ -563,7 +695,6 @@ impl TextRun {
             runs,
             break_at_start: _,
         } = self.break_and_shape(layout_context);
-        let font_size = self.parent_style.get_font().font_size.size.0;
         let mut runs = runs.iter();
         loop {
             let mut glyphs = vec![];
@@ -594,11 +725,7 @@ impl TextRun {
                     break;
                 }
             }
-            let line_height = match self.parent_style.get_inherited_text().line_height {
-                LineHeight::Normal => font_line_gap.into(),
-                LineHeight::Number(n) => font_size * n.0,
-                LineHeight::Length(l) => l.0,
-            };
+            let line_height = line_height(&self.parent_style, || font_line_gap);
             let content_rect = Rect {
                 start_corner: Vec2 {
                     block: Length::zero(),
@@ -610,18 +737,16 @@ impl TextRun {
                 },
             };
             ifc.inline_position += advance_width;
-            ifc.current_nesting_level
-                .max_block_size_of_fragments_so_far
-                .max_assign(line_height);
-            ifc.current_nesting_level
-                .fragments_so_far
-                .push(Fragment::Text(TextFragment {
+            ifc.current_nesting_level.push_fragment(
+                Fragment::Text(TextFragment {
                     parent_style: self.parent_style.clone(),
                     content_rect,
                     ascent: font_ascent.into(),
                     font_key,
                     glyphs,
-                }));
+                }),
+                Some(font_ascent.into()),
+            );
             if runs.is_empty() {
                 break;
             } else {
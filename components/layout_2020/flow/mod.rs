@@ -8,7 +8,7 @@ use crate::context::LayoutContext;
 use crate::flow::float::{FloatBox, FloatContext};
 use crate::flow::inline::InlineFormattingContext;
 use crate::formatting_contexts::{IndependentFormattingContext, IndependentLayout};
-use crate::fragments::{AnonymousFragment, BoxFragment, Fragment};
+use crate::fragments::{first_baseline, last_baseline, AnonymousFragment, BoxFragment, Fragment};
 use crate::fragments::{CollapsedBlockMargins, CollapsedMargin};
 use crate::geom::flow_relative::{Rect, Sides, Vec2};
 use crate::positioned::adjust_static_positions;
@@ -16,9 +16,12 @@ use crate::positioned::{AbsolutelyPositionedBox, AbsolutelyPositionedFragment};
 use crate::replaced::ReplacedContent;
 use crate::style_ext::{ComputedValuesExt, Position};
 use crate::{relative_adjustement, ContainingBlock};
+use malloc_size_of::MallocUnconditionalSizeOf;
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use rayon_croissant::ParallelIteratorExt;
 use servo_arc::Arc;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::{Length, LengthOrAuto, LengthPercentage, LengthPercentageOrAuto};
 use style::values::generics::length::MaxSize;
@@ -31,7 +34,7 @@ mod root;
 
 pub use root::{BoxTreeRoot, FragmentTreeRoot};
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) struct BlockFormattingContext {
     pub contents: BlockContainer,
     pub contains_floats: bool,
@@ -43,9 +46,11 @@ pub(crate) enum BlockContainer {
     InlineFormattingContext(InlineFormattingContext),
 }
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) enum BlockLevelBox {
     SameFormattingContextBlock {
+        tag: Option<OpaqueNode>,
+        #[ignore_malloc_size_of = "Shared with the style system"]
         style: Arc<ComputedValues>,
         contents: BlockContainer,
     },
@@ -54,6 +59,31 @@ pub(crate) enum BlockLevelBox {
     Independent(IndependentFormattingContext),
 }
 
+impl MallocSizeOf for BlockContainer {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        match self {
+            BlockContainer::BlockLevelBoxes(boxes) => size_of_boxes(boxes, ops),
+            BlockContainer::InlineFormattingContext(ifc) => ifc.size_of(ops),
+        }
+    }
+}
+
+/// Measures a list of boxes owned by the box tree.
+///
+/// The box tree holds the primary reference to each of its boxes, the only
+/// other references being the ones stashed in the layout data of DOM nodes,
+/// so the `Arc`s are measured unconditionally.
+pub(crate) fn size_of_boxes<T: MallocSizeOf>(
+    boxes: &Vec<Arc<T>>,
+    ops: &mut MallocSizeOfOps,
+) -> usize {
+    boxes.shallow_size_of(ops) +
+        boxes
+            .iter()
+            .map(|box_| box_.unconditional_size_of(ops))
+            .sum::<usize>()
+}
+
 struct FlowLayout {
     pub fragments: Vec<Fragment>,
     pub content_block_size: Length,
@@ -92,6 +122,8 @@ impl BlockFormattingContext {
                 .collapsed_through
         );
         IndependentLayout {
+            first_baseline: first_baseline(&flow_layout.fragments),
+            last_baseline: last_baseline(&flow_layout.fragments),
             fragments: flow_layout.fragments,
             content_block_size: flow_layout.content_block_size +
                 flow_layout.collapsible_margins_in_children.end.solve(),
@@ -275,28 +307,32 @@ impl BlockLevelBox {
         float_context: Option<&mut FloatContext>,
     ) -> Fragment {
         match self {
-            BlockLevelBox::SameFormattingContextBlock { style, contents } => {
-                Fragment::Box(layout_in_flow_non_replaced_block_level(
-                    layout_context,
-                    containing_block,
-                    absolutely_positioned_fragments,
-                    style,
-                    BlockLevelKind::SameFormattingContextBlock,
-                    |containing_block, nested_abspos, collapsible_with_parent_start_margin| {
-                        contents.layout(
-                            layout_context,
-                            containing_block,
-                            tree_rank,
-                            nested_abspos,
-                            float_context,
-                            collapsible_with_parent_start_margin,
-                        )
-                    },
-                ))
-            },
+            BlockLevelBox::SameFormattingContextBlock {
+                tag,
+                style,
+                contents,
+            } => Fragment::Box(layout_in_flow_non_replaced_block_level(
+                layout_context,
+                containing_block,
+                absolutely_positioned_fragments,
+                *tag,
+                style,
+                BlockLevelKind::SameFormattingContextBlock,
+                |containing_block, nested_abspos, collapsible_with_parent_start_margin| {
+                    contents.layout(
+                        layout_context,
+                        containing_block,
+                        tree_rank,
+                        nested_abspos,
+                        float_context,
+                        collapsible_with_parent_start_margin,
+                    )
+                },
+            )),
             BlockLevelBox::Independent(contents) => match contents.as_replaced() {
                 Ok(replaced) => Fragment::Box(layout_in_flow_replaced_block_level(
                     containing_block,
+                    contents.tag,
                     &contents.style,
                     replaced,
                 )),
@@ -304,6 +340,7 @@ impl BlockLevelBox {
                     layout_context,
                     containing_block,
                     absolutely_positioned_fragments,
+                    contents.tag,
                     &contents.style,
                     BlockLevelKind::EstablishesAnIndependentFormattingContext,
                     |containing_block, nested_abspos, _| {
@@ -345,6 +382,7 @@ fn layout_in_flow_non_replaced_block_level<'a>(
     layout_context: &LayoutContext,
     containing_block: &ContainingBlock,
     absolutely_positioned_fragments: &mut Vec<AbsolutelyPositionedFragment<'a>>,
+    tag: Option<OpaqueNode>,
     style: &Arc<ComputedValues>,
     block_level_kind: BlockLevelKind,
     layout_contents: impl FnOnce(
@@ -497,6 +535,7 @@ fn layout_in_flow_non_replaced_block_level<'a>(
         )
     }
     BoxFragment {
+        tag,
         style: style.clone(),
         children: flow_layout.fragments,
         content_rect,
@@ -512,6 +551,7 @@ fn layout_in_flow_non_replaced_block_level<'a>(
 /// https://drafts.csswg.org/css2/visudet.html#inline-replaced-height
 fn layout_in_flow_replaced_block_level<'a>(
     containing_block: &ContainingBlock,
+    tag: Option<OpaqueNode>,
     style: &Arc<ComputedValues>,
     replaced: &ReplacedContent,
 ) -> BoxFragment {
@@ -674,6 +714,7 @@ fn layout_in_flow_replaced_block_level<'a>(
         size,
     };
     BoxFragment {
+        tag,
         style: style.clone(),
         children: fragments,
         content_rect,
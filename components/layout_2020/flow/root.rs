@@ -12,19 +12,26 @@ use crate::formatting_contexts::IndependentFormattingContext;
 use crate::fragments::Fragment;
 use crate::geom;
 use crate::geom::flow_relative::Vec2;
+use crate::geom::physical;
 use crate::positioned::AbsolutelyPositionedBox;
 use crate::replaced::ReplacedContent;
 use crate::sizing::ContentSizesRequest;
 use crate::style_ext::{Direction, Display, DisplayGeneratingBox, DisplayInside, WritingMode};
 use crate::{ContainingBlock, DefiniteContainingBlock};
+use app_units::Au;
+use euclid::default::{Point2D, Rect, Size2D};
 use rayon::iter::{IntoParallelRefIterator, ParallelExtend, ParallelIterator};
 use script_layout_interface::wrapper_traits::LayoutNode;
 use servo_arc::Arc;
+use style::dom::OpaqueNode;
 use style::values::computed::{Length, LengthOrAuto};
 use style::Zero;
 use style_traits::CSSPixel;
 
+#[derive(MallocSizeOf)]
 pub struct BoxTreeRoot(BlockFormattingContext);
+
+#[derive(MallocSizeOf)]
 pub struct FragmentTreeRoot(Vec<Fragment>);
 
 impl BoxTreeRoot {
@@ -60,19 +67,20 @@ fn construct_for_root_element<'dom>(
         Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { inside, .. }) => inside,
     };
 
+    let tag = Some(root_element.opaque());
     let contents = replaced.map_or(Contents::OfElement(root_element), Contents::Replaced);
     if box_style.position.is_absolutely_positioned() {
         (
             ContainsFloats::No,
             vec![Arc::new(BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(
-                AbsolutelyPositionedBox::construct(context, style, display_inside, contents),
+                AbsolutelyPositionedBox::construct(context, tag, style, display_inside, contents),
             ))],
         )
     } else if box_style.float.is_floating() {
         (
             ContainsFloats::Yes,
             vec![Arc::new(BlockLevelBox::OutOfFlowFloatBox(
-                FloatBox::construct(context, style, display_inside, contents),
+                FloatBox::construct(context, tag, style, display_inside, contents),
             ))],
         )
     } else {
@@ -81,6 +89,7 @@ fn construct_for_root_element<'dom>(
             vec![Arc::new(BlockLevelBox::Independent(
                 IndependentFormattingContext::construct(
                     context,
+                    tag,
                     style,
                     display_inside,
                     contents,
@@ -153,4 +162,69 @@ impl FragmentTreeRoot {
         }
         is_contentful
     }
+
+    /// The border boxes of the fragments generated by `requested_node`,
+    /// relative to the initial containing block.
+    pub fn border_boxes_for_node(
+        &self,
+        requested_node: OpaqueNode,
+        viewport_size: webrender_api::units::LayoutSize,
+    ) -> Vec<Rect<Au>> {
+        let containing_block = physical::Rect {
+            top_left: physical::Vec2 {
+                x: Length::zero(),
+                y: Length::zero(),
+            },
+            size: physical::Vec2 {
+                x: Length::new(viewport_size.width),
+                y: Length::new(viewport_size.height),
+            },
+        };
+        let mut border_boxes = Vec::new();
+        for fragment in &self.0 {
+            collect_border_boxes(
+                fragment,
+                requested_node,
+                &containing_block,
+                &mut border_boxes,
+            )
+        }
+        border_boxes
+    }
+}
+
+fn collect_border_boxes(
+    fragment: &Fragment,
+    requested_node: OpaqueNode,
+    containing_block: &physical::Rect<Length>,
+    border_boxes: &mut Vec<Rect<Au>>,
+) {
+    let (rect, children) = match fragment {
+        Fragment::Box(b) => {
+            let mode = b.style.writing_mode();
+            if b.tag == Some(requested_node) {
+                let border_rect = b
+                    .border_rect()
+                    .to_physical(mode, containing_block)
+                    .translate(&containing_block.top_left);
+                border_boxes.push(Rect::new(
+                    Point2D::new(
+                        Au::from(border_rect.top_left.x),
+                        Au::from(border_rect.top_left.y),
+                    ),
+                    Size2D::new(Au::from(border_rect.size.x), Au::from(border_rect.size.y)),
+                ));
+            }
+            (
+                b.content_rect.to_physical(mode, containing_block),
+                &b.children,
+            )
+        },
+        Fragment::Anonymous(a) => (a.rect.to_physical(a.mode, containing_block), &a.children),
+        Fragment::Text(_) | Fragment::Image(_) | Fragment::Rule(_) => return,
+    };
+    let rect = rect.translate(&containing_block.top_left);
+    for child in children {
+        collect_border_boxes(child, requested_node, &rect, border_boxes)
+    }
 }
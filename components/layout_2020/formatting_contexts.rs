@@ -6,6 +6,7 @@ use crate::context::LayoutContext;
 use crate::dom_traversal::{Contents, NodeExt};
 use crate::flow::BlockFormattingContext;
 use crate::fragments::Fragment;
+use crate::mathml::MathFormattingContext;
 use crate::positioned::AbsolutelyPositionedFragment;
 use crate::replaced::ReplacedContent;
 use crate::sizing::{BoxContentSizes, ContentSizesRequest};
@@ -13,12 +14,15 @@ use crate::style_ext::DisplayInside;
 use crate::ContainingBlock;
 use servo_arc::Arc;
 use std::convert::TryInto;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::Length;
 
 /// https://drafts.csswg.org/css-display/#independent-formatting-context
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) struct IndependentFormattingContext {
+    pub tag: Option<OpaqueNode>,
+    #[ignore_malloc_size_of = "Shared with the style system"]
     pub style: Arc<ComputedValues>,
 
     /// If it was requested during construction
@@ -30,16 +34,25 @@ pub(crate) struct IndependentFormattingContext {
 pub(crate) struct IndependentLayout {
     pub fragments: Vec<Fragment>,
     pub content_block_size: Length,
+
+    /// https://drafts.csswg.org/css-align/#baseline-export
+    ///
+    /// Relative to the start corner of the content box,
+    /// or `None` if this formatting context has no baseline set.
+    pub first_baseline: Option<Length>,
+    pub last_baseline: Option<Length>,
 }
 
 // Private so that code outside of this module cannot match variants.
 // It should got through methods instead.
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 enum IndependentFormattingContextContents {
     Flow(BlockFormattingContext),
 
     // Not called FC in specs, but behaves close enough
     Replaced(ReplacedContent),
+
+    MathML(MathFormattingContext),
     // Other layout modes go here
 }
 
@@ -47,11 +60,13 @@ pub(crate) struct NonReplacedIFC<'a>(NonReplacedIFCKind<'a>);
 
 enum NonReplacedIFCKind<'a> {
     Flow(&'a BlockFormattingContext),
+    MathML(&'a MathFormattingContext),
 }
 
 impl IndependentFormattingContext {
     pub fn construct<'dom>(
         context: &LayoutContext,
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<impl NodeExt<'dom>>,
@@ -69,6 +84,15 @@ impl IndependentFormattingContext {
                     );
                     (Contents::Flow(bfc), box_content_sizes)
                 },
+                DisplayInside::MathML => {
+                    let (mfc, box_content_sizes) = MathFormattingContext::construct(
+                        context,
+                        &style,
+                        non_replaced,
+                        content_sizes,
+                    );
+                    (Contents::MathML(mfc), box_content_sizes)
+                },
             },
             Err(replaced) => {
                 // The `content_sizes` field is not used by layout code:
@@ -79,6 +103,7 @@ impl IndependentFormattingContext {
             },
         };
         Self {
+            tag,
             style,
             contents,
             content_sizes,
@@ -92,6 +117,7 @@ impl IndependentFormattingContext {
         match &self.contents {
             Contents::Replaced(r) => Ok(r),
             Contents::Flow(f) => Err(NR(Kind::Flow(f))),
+            Contents::MathML(m) => Err(NR(Kind::MathML(m))),
         }
     }
 }
@@ -111,6 +137,12 @@ impl<'a> NonReplacedIFC<'a> {
                 tree_rank,
                 absolutely_positioned_fragments,
             ),
+            NonReplacedIFCKind::MathML(mfc) => mfc.layout(
+                layout_context,
+                containing_block,
+                tree_rank,
+                absolutely_positioned_fragments,
+            ),
         }
     }
 }
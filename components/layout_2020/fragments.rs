@@ -7,19 +7,27 @@ use crate::style_ext::{Direction, WritingMode};
 use gfx::text::glyph::GlyphStore;
 use servo_arc::Arc as ServoArc;
 use std::sync::Arc;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::Length;
 use style::Zero;
 use webrender_api::{FontInstanceKey, ImageKey};
 
+#[derive(MallocSizeOf)]
 pub(crate) enum Fragment {
     Box(BoxFragment),
     Anonymous(AnonymousFragment),
     Text(TextFragment),
     Image(ImageFragment),
+    Rule(RuleFragment),
 }
 
+#[derive(MallocSizeOf)]
 pub(crate) struct BoxFragment {
+    /// The element that generated this box,
+    /// or `None` for anonymous boxes and pseudo-elements.
+    pub tag: Option<OpaqueNode>,
+    #[ignore_malloc_size_of = "Shared with the style system"]
     pub style: ServoArc<ComputedValues>,
     pub children: Vec<Fragment>,
 
@@ -35,39 +43,55 @@ pub(crate) struct BoxFragment {
     pub block_margins_collapsed_with_children: CollapsedBlockMargins,
 }
 
+#[derive(MallocSizeOf)]
 pub(crate) struct CollapsedBlockMargins {
     pub collapsed_through: bool,
     pub start: CollapsedMargin,
     pub end: CollapsedMargin,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, MallocSizeOf)]
 pub(crate) struct CollapsedMargin {
     max_positive: Length,
     min_negative: Length,
 }
 
 /// Can contain child fragments with relative coordinates, but does not contribute to painting itself.
+#[derive(MallocSizeOf)]
 pub(crate) struct AnonymousFragment {
     pub rect: Rect<Length>,
     pub children: Vec<Fragment>,
     pub mode: (WritingMode, Direction),
 }
 
+#[derive(MallocSizeOf)]
 pub(crate) struct TextFragment {
+    #[ignore_malloc_size_of = "Shared with the style system"]
     pub parent_style: ServoArc<ComputedValues>,
     pub content_rect: Rect<Length>,
     pub ascent: Length,
     pub font_key: FontInstanceKey,
+    #[ignore_malloc_size_of = "Measured as part of the font shape cache"]
     pub glyphs: Vec<Arc<GlyphStore>>,
 }
 
+#[derive(MallocSizeOf)]
 pub(crate) struct ImageFragment {
+    #[ignore_malloc_size_of = "Shared with the style system"]
     pub style: ServoArc<ComputedValues>,
     pub content_rect: Rect<Length>,
     pub image_key: ImageKey,
 }
 
+/// A solid rectangle painted with the `color` of its style, such as the bar
+/// of a MathML fraction.
+#[derive(MallocSizeOf)]
+pub(crate) struct RuleFragment {
+    #[ignore_malloc_size_of = "Shared with the style system"]
+    pub style: ServoArc<ComputedValues>,
+    pub rect: Rect<Length>,
+}
+
 impl AnonymousFragment {
     pub fn no_op(mode: (WritingMode, Direction)) -> Self {
         Self {
@@ -79,10 +103,45 @@ impl AnonymousFragment {
 }
 
 impl BoxFragment {
+    pub fn padding_rect(&self) -> Rect<Length> {
+        self.content_rect.inflate(&self.padding)
+    }
+
     pub fn border_rect(&self) -> Rect<Length> {
-        self.content_rect
-            .inflate(&self.padding)
-            .inflate(&self.border)
+        self.padding_rect().inflate(&self.border)
+    }
+}
+
+/// The position of the first baseline of text in the given fragments,
+/// relative to their containing block.
+pub(crate) fn first_baseline(fragments: &[Fragment]) -> Option<Length> {
+    fragments.iter().find_map(Fragment::first_baseline)
+}
+
+/// The position of the last baseline of text in the given fragments,
+/// relative to their containing block.
+pub(crate) fn last_baseline(fragments: &[Fragment]) -> Option<Length> {
+    fragments.iter().rev().find_map(Fragment::last_baseline)
+}
+
+impl Fragment {
+    fn first_baseline(&self) -> Option<Length> {
+        self.baseline(first_baseline)
+    }
+
+    fn last_baseline(&self) -> Option<Length> {
+        self.baseline(last_baseline)
+    }
+
+    fn baseline(&self, in_children: fn(&[Fragment]) -> Option<Length>) -> Option<Length> {
+        match self {
+            Fragment::Text(text) => Some(text.content_rect.start_corner.block + text.ascent),
+            Fragment::Box(box_) => in_children(&box_.children)
+                .map(|baseline| box_.content_rect.start_corner.block + baseline),
+            Fragment::Anonymous(anonymous) => in_children(&anonymous.children)
+                .map(|baseline| anonymous.rect.start_corner.block + baseline),
+            Fragment::Image(_) | Fragment::Rule(_) => None,
+        }
     }
 }
 
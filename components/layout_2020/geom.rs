@@ -14,19 +14,19 @@ pub type Size<U> = euclid::Size2D<f32, U>;
 pub type Rect<U> = euclid::Rect<f32, U>;
 
 pub(crate) mod physical {
-    #[derive(Clone)]
+    #[derive(Clone, MallocSizeOf)]
     pub(crate) struct Vec2<T> {
         pub x: T,
         pub y: T,
     }
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, MallocSizeOf)]
     pub(crate) struct Rect<T> {
         pub top_left: Vec2<T>,
         pub size: Vec2<T>,
     }
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, MallocSizeOf)]
     pub(crate) struct Sides<T> {
         pub top: T,
         pub left: T,
@@ -36,19 +36,19 @@ pub(crate) mod physical {
 }
 
 pub(crate) mod flow_relative {
-    #[derive(Clone)]
+    #[derive(Clone, MallocSizeOf)]
     pub(crate) struct Vec2<T> {
         pub inline: T,
         pub block: T,
     }
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, MallocSizeOf)]
     pub(crate) struct Rect<T> {
         pub start_corner: Vec2<T>,
         pub size: Vec2<T>,
     }
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, MallocSizeOf)]
     pub(crate) struct Sides<T> {
         pub inline_start: T,
         pub inline_end: T,
@@ -6,6 +6,12 @@
 #![feature(exact_size_is_empty)]
 #![feature(matches_macro)]
 
+#[macro_use]
+extern crate html5ever;
+#[macro_use]
+extern crate malloc_size_of_derive;
+
+pub mod animation;
 pub mod context;
 pub mod data;
 pub mod display_list;
@@ -15,6 +21,7 @@ mod flow;
 mod formatting_contexts;
 mod fragments;
 mod geom;
+mod mathml;
 mod opaque_node;
 mod positioned;
 pub mod query;
@@ -0,0 +1,794 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! MathML Core layout.
+//!
+//! https://w3c.github.io/mathml-core/
+//!
+//! The `<math>` element establishes a math formatting context. Its MathML
+//! descendants are laid out with the algorithms of MathML Core, using default
+//! values for the OpenType MATH constants since fonts are not queried for
+//! their MATH table yet. Token elements, non-MathML elements and text are
+//! laid out as independent flow roots.
+
+use crate::context::LayoutContext;
+use crate::dom_traversal::{BoxSlot, Contents, NodeExt, NonReplacedContents};
+use crate::dom_traversal::{PseudoElementContentItem, TraversalHandler};
+use crate::element_data::LayoutBox;
+use crate::flow::size_of_boxes;
+use crate::formatting_contexts::{IndependentFormattingContext, IndependentLayout};
+use crate::fragments::RuleFragment;
+use crate::fragments::{AnonymousFragment, BoxFragment, CollapsedBlockMargins, Fragment};
+use crate::geom::flow_relative::{Rect, Vec2};
+use crate::positioned::AbsolutelyPositionedFragment;
+use crate::sizing::{BoxContentSizes, ContentSizes, ContentSizesRequest};
+use crate::style_ext::{ComputedValuesExt, DisplayGeneratingBox, DisplayInside};
+use crate::ContainingBlock;
+use html5ever::LocalName;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use servo_arc::Arc;
+use style::dom::OpaqueNode;
+use style::properties::ComputedValues;
+use style::selector_parser::PseudoElement;
+use style::values::computed::Length;
+use style::Zero;
+
+/// Default values of the OpenType MATH constants, in ems.
+/// https://w3c.github.io/mathml-core/#layout-constants-mathconstants
+const AXIS_HEIGHT: f32 = 0.25;
+const FRACTION_RULE_THICKNESS: f32 = 1. / 18.;
+const FRACTION_GAP_MIN: f32 = 1. / 18.;
+const SUPERSCRIPT_SHIFT_UP: f32 = 0.45;
+const SUPERSCRIPT_BOTTOM_MIN: f32 = 0.125;
+const SUBSCRIPT_SHIFT_DOWN: f32 = 0.25;
+const SUBSCRIPT_TOP_MAX: f32 = 0.4;
+const SUB_SUPERSCRIPT_GAP_MIN: f32 = 4. / 18.;
+const SPACE_AFTER_SCRIPT: f32 = 1. / 24.;
+
+/// The contents of a `<math>` element, which lays them out like `<mrow>`.
+#[derive(Debug)]
+pub(crate) struct MathFormattingContext {
+    children: Vec<Arc<MathBox>>,
+}
+
+#[derive(Debug)]
+pub(crate) enum MathBox {
+    /// A token element (`<mi>`, `<mn>`, `<mo>`, `<ms>`, `<mtext>`), a
+    /// non-MathML element, or text directly inside a MathML layout element.
+    Token {
+        contents: IndependentFormattingContext,
+        /// Whether this is an operator that stretches to the size of its row.
+        /// https://w3c.github.io/mathml-core/#dfn-stretchy
+        stretchy: bool,
+    },
+    Layout {
+        tag: Option<OpaqueNode>,
+        style: Arc<ComputedValues>,
+        kind: MathLayoutKind,
+        children: Vec<Arc<MathBox>>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum MathLayoutKind {
+    /// `<mrow>`, and unknown MathML elements.
+    /// https://w3c.github.io/mathml-core/#horizontally-group-sub-expressions-mrow
+    Row,
+    /// https://w3c.github.io/mathml-core/#fractions-mfrac
+    Fraction,
+    /// https://w3c.github.io/mathml-core/#subscripts-and-superscripts-msub-msup-msubsup
+    Subscript,
+    Superscript,
+    SubSuperscript,
+}
+
+enum MathElement {
+    Layout(MathLayoutKind),
+    Token,
+    Operator,
+}
+
+impl MathElement {
+    fn for_node<'dom>(node: impl NodeExt<'dom>) -> Option<Self> {
+        if !node.is_mathml_element() {
+            return None;
+        }
+        let is = |name: LocalName| node.has_local_name(&name);
+        Some(if is(local_name!("mfrac")) {
+            MathElement::Layout(MathLayoutKind::Fraction)
+        } else if is(local_name!("msub")) {
+            MathElement::Layout(MathLayoutKind::Subscript)
+        } else if is(local_name!("msup")) {
+            MathElement::Layout(MathLayoutKind::Superscript)
+        } else if is(local_name!("msubsup")) {
+            MathElement::Layout(MathLayoutKind::SubSuperscript)
+        } else if is(local_name!("mo")) {
+            MathElement::Operator
+        } else if is(local_name!("mi")) ||
+            is(local_name!("mn")) ||
+            is(local_name!("ms")) ||
+            is(local_name!("mtext"))
+        {
+            MathElement::Token
+        } else {
+            MathElement::Layout(MathLayoutKind::Row)
+        })
+    }
+}
+
+/// Whether an `<mo>` element stretches, from its `stretchy` attribute or
+/// otherwise from the fences and separators of the operator dictionary.
+/// https://w3c.github.io/mathml-core/#operator-dictionary
+fn is_stretchy_operator<'dom>(node: impl NodeExt<'dom>) -> bool {
+    match node
+        .get_attribute(&local_name!("stretchy"))
+        .as_ref()
+        .map(|s| &**s)
+    {
+        Some("true") => return true,
+        Some("false") => return false,
+        _ => {},
+    }
+    let mut text = String::new();
+    for child in node.flat_tree_children() {
+        if let Some(contents) = child.as_text() {
+            text.push_str(&contents);
+        }
+    }
+    let mut chars = text.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            "()[]{}|\u{2016}\u{2308}\u{2309}\u{230A}\u{230B}\u{27E8}\u{27E9}".contains(c)
+        },
+        _ => false,
+    }
+}
+
+struct MathChildrenBuilder<'a> {
+    context: &'a LayoutContext<'a>,
+    children: Vec<Arc<MathBox>>,
+    anonymous_style: Option<Arc<ComputedValues>>,
+}
+
+impl<'dom, Node> TraversalHandler<'dom, Node> for MathChildrenBuilder<'_>
+where
+    Node: NodeExt<'dom>,
+{
+    fn handle_text(&mut self, text: String, parent_style: &Arc<ComputedValues>) {
+        // Whitespace between MathML elements is not rendered.
+        if text.chars().all(|c| c.is_ascii_whitespace()) {
+            return;
+        }
+        let context = self.context;
+        let style = self
+            .anonymous_style
+            .get_or_insert_with(|| {
+                context
+                    .shared_context()
+                    .stylist
+                    .style_for_anonymous::<Node::ConcreteElement>(
+                        &context.shared_context().guards,
+                        &PseudoElement::ServoText,
+                        parent_style,
+                    )
+            })
+            .clone();
+        let contents =
+            NonReplacedContents::<Node>::OfPseudoElement(vec![PseudoElementContentItem::Text(
+                text,
+            )]);
+        self.children.push(Arc::new(MathBox::Token {
+            contents: IndependentFormattingContext::construct(
+                context,
+                None,
+                style,
+                DisplayInside::FlowRoot,
+                contents.into(),
+                ContentSizesRequest::Inline,
+            ),
+            stretchy: false,
+        }))
+    }
+
+    fn handle_element(
+        &mut self,
+        tag: Option<OpaqueNode>,
+        style: &Arc<ComputedValues>,
+        _display: DisplayGeneratingBox,
+        contents: Contents<Node>,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        let box_ = Arc::new(MathBox::construct(
+            self.context,
+            tag,
+            style.clone(),
+            contents,
+        ));
+        box_slot.set(LayoutBox::Math(box_.clone()));
+        self.children.push(box_)
+    }
+}
+
+fn construct_children<'dom>(
+    context: &LayoutContext,
+    style: &Arc<ComputedValues>,
+    contents: NonReplacedContents<impl NodeExt<'dom>>,
+) -> Vec<Arc<MathBox>> {
+    let mut builder = MathChildrenBuilder {
+        context,
+        children: Vec::new(),
+        anonymous_style: None,
+    };
+    contents.traverse(style, context, &mut builder);
+    builder.children
+}
+
+impl MathFormattingContext {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        style: &Arc<ComputedValues>,
+        contents: NonReplacedContents<impl NodeExt<'dom>>,
+        content_sizes: ContentSizesRequest,
+    ) -> (Self, BoxContentSizes) {
+        let children = construct_children(context, style, contents);
+        let content_sizes = content_sizes.compute(|| row_content_sizes(&children));
+        (Self { children }, content_sizes)
+    }
+
+    pub fn layout<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        containing_block: &ContainingBlock,
+        tree_rank: usize,
+        absolutely_positioned_fragments: &mut Vec<AbsolutelyPositionedFragment<'a>>,
+    ) -> IndependentLayout {
+        let mut state = LayoutState {
+            layout_context,
+            containing_block,
+            tree_rank,
+            absolutely_positioned_fragments,
+        };
+        let row = layout_row(&mut state, &self.children);
+        IndependentLayout {
+            fragments: row.fragments,
+            content_block_size: row.ascent + row.descent,
+            first_baseline: Some(row.ascent),
+            last_baseline: Some(row.ascent),
+        }
+    }
+}
+
+impl MathBox {
+    fn construct<'dom>(
+        context: &LayoutContext,
+        tag: Option<OpaqueNode>,
+        style: Arc<ComputedValues>,
+        contents: Contents<impl NodeExt<'dom>>,
+    ) -> Self {
+        let element = match &contents {
+            Contents::OfElement(node) => Some(*node),
+            Contents::Replaced(_) | Contents::OfPseudoElement(_) => None,
+        };
+        match element.and_then(MathElement::for_node) {
+            Some(MathElement::Layout(kind)) => {
+                let children = construct_children(
+                    context,
+                    &style,
+                    NonReplacedContents::OfElement(element.unwrap()),
+                );
+                MathBox::Layout {
+                    tag,
+                    style,
+                    kind,
+                    children,
+                }
+            },
+            element_kind => MathBox::Token {
+                stretchy: match element_kind {
+                    Some(MathElement::Operator) => is_stretchy_operator(element.unwrap()),
+                    _ => false,
+                },
+                contents: IndependentFormattingContext::construct(
+                    context,
+                    tag,
+                    style,
+                    DisplayInside::FlowRoot,
+                    contents,
+                    ContentSizesRequest::Inline,
+                ),
+            },
+        }
+    }
+
+    fn style(&self) -> &Arc<ComputedValues> {
+        match self {
+            MathBox::Token { contents, .. } => &contents.style,
+            MathBox::Layout { style, .. } => style,
+        }
+    }
+
+    /// The outer inline content sizes of this box. Math layout never breaks
+    /// lines, so both min-content and max-content are the same.
+    fn outer_content_sizes(&self) -> ContentSizes {
+        let (style, kind, children) = match self {
+            MathBox::Token { contents, .. } => {
+                return contents.content_sizes.outer_inline(&contents.style)
+            },
+            MathBox::Layout {
+                style,
+                kind,
+                children,
+            } => (style, kind, children),
+        };
+        let child = |index: usize| {
+            children.get(index).map_or(Length::zero(), |child| {
+                child.outer_content_sizes().max_content
+            })
+        };
+        let em = font_size(style);
+        let max_content = match kind {
+            MathLayoutKind::Row => row_content_sizes(children).max_content,
+            MathLayoutKind::Fraction => child(0).max(child(1)),
+            MathLayoutKind::Subscript | MathLayoutKind::Superscript => {
+                child(0) + child(1) + em * SPACE_AFTER_SCRIPT
+            },
+            MathLayoutKind::SubSuperscript => {
+                child(0) + child(1).max(child(2)) + em * SPACE_AFTER_SCRIPT
+            },
+        };
+        BoxContentSizes::Inline(ContentSizes {
+            min_content: max_content,
+            max_content,
+        })
+        .outer_inline(style)
+    }
+}
+
+fn row_content_sizes(children: &[Arc<MathBox>]) -> ContentSizes {
+    let mut sizes = ContentSizes::zero();
+    for child in children {
+        let child = child.outer_content_sizes();
+        sizes.max_content += child.max_content;
+    }
+    sizes.min_content = sizes.max_content;
+    sizes
+}
+
+fn font_size(style: &ComputedValues) -> Length {
+    style.get_font().font_size.size.0
+}
+
+struct LayoutState<'s, 'a, 'b> {
+    layout_context: &'s LayoutContext<'s>,
+    containing_block: &'s ContainingBlock,
+    tree_rank: usize,
+    absolutely_positioned_fragments: &'b mut Vec<AbsolutelyPositionedFragment<'a>>,
+}
+
+/// The result of laying out a list of math boxes, with fragments relative to
+/// the start corner of the list and the baseline at `ascent`.
+struct MathRowLayout {
+    fragments: Vec<Fragment>,
+    inline_size: Length,
+    ascent: Length,
+    descent: Length,
+}
+
+/// A laid out math box, whose margin box starts at the origin.
+struct MathLayout {
+    fragment: BoxFragment,
+    /// The inline size of the margin box.
+    inline_size: Length,
+    /// The distance from the top of the margin box to the baseline.
+    ascent: Length,
+    /// The distance from the baseline to the bottom of the margin box.
+    descent: Length,
+}
+
+impl MathLayout {
+    fn block_size(&self) -> Length {
+        self.ascent + self.descent
+    }
+
+    /// Moves the margin box start corner to the given position.
+    fn into_fragment_at(mut self, start_corner: Vec2<Length>) -> Fragment {
+        self.fragment.content_rect.start_corner += &start_corner;
+        Fragment::Box(self.fragment)
+    }
+
+    /// Grows a stretchy operator to cover the given ascent and descent,
+    /// keeping its contents vertically centered.
+    ///
+    /// FIXME: the glyph itself should be stretched, using the glyph variants
+    /// and assemblies of the font's MATH table.
+    fn stretch_to(&mut self, ascent: Length, descent: Length) {
+        let extra = (ascent + descent) - self.block_size();
+        if extra <= Length::zero() {
+            return;
+        }
+        let children = std::mem::replace(&mut self.fragment.children, Vec::new());
+        let content_size = self.fragment.content_rect.size.clone();
+        self.fragment.children = vec![Fragment::Anonymous(AnonymousFragment {
+            rect: Rect {
+                start_corner: Vec2 {
+                    inline: Length::zero(),
+                    block: extra / 2.,
+                },
+                size: content_size,
+            },
+            children,
+            mode: self.fragment.style.writing_mode(),
+        })];
+        self.fragment.content_rect.size.block += extra;
+        self.ascent = ascent;
+        self.descent = descent;
+    }
+}
+
+fn layout_row<'a>(
+    state: &mut LayoutState<'_, 'a, '_>,
+    children: &'a [Arc<MathBox>],
+) -> MathRowLayout {
+    let mut layouts: Vec<_> = children
+        .iter()
+        .map(|child| (child.layout(state), child.is_stretchy()))
+        .collect();
+
+    let non_stretchy = || layouts.iter().filter(|(_, stretchy)| !stretchy);
+    let mut ascent = non_stretchy()
+        .map(|(layout, _)| layout.ascent)
+        .fold(Length::zero(), Length::max);
+    let mut descent = non_stretchy()
+        .map(|(layout, _)| layout.descent)
+        .fold(Length::zero(), Length::max);
+    if non_stretchy().next().is_some() {
+        for (layout, _) in layouts.iter_mut().filter(|(_, stretchy)| *stretchy) {
+            layout.stretch_to(ascent, descent);
+        }
+    }
+    for (layout, _) in &layouts {
+        ascent.max_assign(layout.ascent);
+        descent.max_assign(layout.descent);
+    }
+
+    let mut inline_position = Length::zero();
+    let fragments = layouts
+        .into_iter()
+        .map(|(layout, _)| {
+            let start_corner = Vec2 {
+                inline: inline_position,
+                block: ascent - layout.ascent,
+            };
+            inline_position += layout.inline_size;
+            layout.into_fragment_at(start_corner)
+        })
+        .collect();
+    MathRowLayout {
+        fragments,
+        inline_size: inline_position,
+        ascent,
+        descent,
+    }
+}
+
+impl MathBox {
+    fn is_stretchy(&self) -> bool {
+        match self {
+            MathBox::Token { stretchy, .. } => *stretchy,
+            MathBox::Layout { .. } => false,
+        }
+    }
+
+    fn layout<'a>(&'a self, state: &mut LayoutState<'_, 'a, '_>) -> MathLayout {
+        match self {
+            MathBox::Token { contents, .. } => layout_token(state, contents),
+            MathBox::Layout {
+                tag,
+                style,
+                kind,
+                children,
+            } => {
+                let row = match kind {
+                    MathLayoutKind::Row => layout_row(state, children),
+                    MathLayoutKind::Fraction => layout_fraction(state, style, children),
+                    MathLayoutKind::Subscript => {
+                        layout_scripts(state, style, children, Some(1), None)
+                    },
+                    MathLayoutKind::Superscript => {
+                        layout_scripts(state, style, children, None, Some(1))
+                    },
+                    MathLayoutKind::SubSuperscript => {
+                        layout_scripts(state, style, children, Some(1), Some(2))
+                    },
+                };
+                wrap_in_box(state, *tag, style, row)
+            },
+        }
+    }
+}
+
+/// Adds the padding, border and margin of a math layout element around its
+/// laid out children.
+fn wrap_in_box(
+    state: &LayoutState,
+    tag: Option<OpaqueNode>,
+    style: &Arc<ComputedValues>,
+    row: MathRowLayout,
+) -> MathLayout {
+    let cbis = state.containing_block.inline_size;
+    let padding = style.padding().percentages_relative_to(cbis);
+    let border = style.border_width();
+    let margin = style
+        .margin()
+        .percentages_relative_to(cbis)
+        .auto_is(Length::zero);
+    let pbm = &(&padding + &border) + &margin;
+    let fragment = BoxFragment {
+        tag,
+        style: style.clone(),
+        children: row.fragments,
+        content_rect: Rect {
+            start_corner: Vec2 {
+                inline: pbm.inline_start,
+                block: pbm.block_start,
+            },
+            size: Vec2 {
+                inline: row.inline_size,
+                block: row.ascent + row.descent,
+            },
+        },
+        padding,
+        border,
+        margin,
+        block_margins_collapsed_with_children: CollapsedBlockMargins::zero(),
+    };
+    MathLayout {
+        fragment,
+        inline_size: row.inline_size + pbm.inline_sum(),
+        ascent: pbm.block_start + row.ascent,
+        descent: row.descent + pbm.block_end,
+    }
+}
+
+fn layout_token<'a>(
+    state: &mut LayoutState<'_, 'a, '_>,
+    token: &'a IndependentFormattingContext,
+) -> MathLayout {
+    let style = &token.style;
+    let cbis = state.containing_block.inline_size;
+    let padding = style.padding().percentages_relative_to(cbis);
+    let border = style.border_width();
+    let margin = style
+        .margin()
+        .percentages_relative_to(cbis)
+        .auto_is(Length::zero);
+    let pbm = &(&padding + &border) + &margin;
+
+    let (children, size, first_baseline) = match token.as_replaced() {
+        Ok(replaced) => {
+            // FIXME: implement https://drafts.csswg.org/css2/visudet.html#inline-replaced-width
+            // and https://drafts.csswg.org/css2/visudet.html#inline-replaced-height
+            let size = Vec2::zero();
+            (replaced.make_fragments(style, size.clone()), size, None)
+        },
+        Err(non_replaced) => {
+            let box_size = style.box_size();
+            let inline_size = box_size
+                .inline
+                .percentage_relative_to(cbis)
+                .auto_is(|| match &token.content_sizes {
+                    BoxContentSizes::Inline(sizes) => sizes.max_content,
+                    BoxContentSizes::NoneWereRequested => Length::zero(),
+                });
+            let block_size = box_size
+                .block
+                .maybe_percentage_relative_to(state.containing_block.block_size.non_auto());
+            let containing_block_for_children = ContainingBlock {
+                inline_size,
+                block_size,
+                mode: style.writing_mode(),
+            };
+            let independent_layout = non_replaced.layout(
+                state.layout_context,
+                &containing_block_for_children,
+                state.tree_rank,
+                state.absolutely_positioned_fragments,
+            );
+            let size = Vec2 {
+                inline: inline_size,
+                block: block_size.auto_is(|| independent_layout.content_block_size),
+            };
+            (
+                independent_layout.fragments,
+                size,
+                independent_layout.first_baseline,
+            )
+        },
+    };
+
+    // Without a first baseline, the bottom margin edge is aligned with the baseline.
+    let content_ascent = first_baseline.unwrap_or(size.block);
+    let ascent = pbm.block_start + content_ascent;
+    let descent = (size.block - content_ascent) + pbm.block_end;
+    let inline_size = size.inline + pbm.inline_sum();
+    let fragment = BoxFragment {
+        tag: token.tag,
+        style: style.clone(),
+        children,
+        content_rect: Rect {
+            start_corner: Vec2 {
+                inline: pbm.inline_start,
+                block: pbm.block_start,
+            },
+            size,
+        },
+        padding,
+        border,
+        margin,
+        block_margins_collapsed_with_children: CollapsedBlockMargins::zero(),
+    };
+    MathLayout {
+        fragment,
+        inline_size,
+        ascent,
+        descent,
+    }
+}
+
+/// https://w3c.github.io/mathml-core/#fraction-with-nonzero-line-thickness
+fn layout_fraction<'a>(
+    state: &mut LayoutState<'_, 'a, '_>,
+    style: &Arc<ComputedValues>,
+    children: &'a [Arc<MathBox>],
+) -> MathRowLayout {
+    if children.len() != 2 {
+        // Invalid markup is rendered like a row.
+        // FIXME: render it as an error, per https://w3c.github.io/mathml-core/#dfn-invalid
+        return layout_row(state, children);
+    }
+    let em = font_size(style);
+    let numerator = children[0].layout(state);
+    let denominator = children[1].layout(state);
+
+    let inline_size = numerator.inline_size.max(denominator.inline_size);
+    let thickness = em * FRACTION_RULE_THICKNESS;
+    let gap = em * FRACTION_GAP_MIN;
+    let rule_block_start = numerator.block_size() + gap;
+    let denominator_block_start = rule_block_start + thickness + gap;
+    let block_size = denominator_block_start + denominator.block_size();
+    // The middle of the fraction bar is on the math axis.
+    let ascent = rule_block_start + thickness / 2. + em * AXIS_HEIGHT;
+
+    let centered = |layout: &MathLayout| (inline_size - layout.inline_size) / 2.;
+    let numerator_start = Vec2 {
+        inline: centered(&numerator),
+        block: Length::zero(),
+    };
+    let denominator_start = Vec2 {
+        inline: centered(&denominator),
+        block: denominator_block_start,
+    };
+    let rule = Fragment::Rule(RuleFragment {
+        style: style.clone(),
+        rect: Rect {
+            start_corner: Vec2 {
+                inline: Length::zero(),
+                block: rule_block_start,
+            },
+            size: Vec2 {
+                inline: inline_size,
+                block: thickness,
+            },
+        },
+    });
+    MathRowLayout {
+        fragments: vec![
+            numerator.into_fragment_at(numerator_start),
+            rule,
+            denominator.into_fragment_at(denominator_start),
+        ],
+        inline_size,
+        ascent,
+        descent: block_size - ascent,
+    }
+}
+
+/// https://w3c.github.io/mathml-core/#base-with-subscript
+/// https://w3c.github.io/mathml-core/#base-with-superscript
+/// https://w3c.github.io/mathml-core/#base-with-subscript-and-superscript
+fn layout_scripts<'a>(
+    state: &mut LayoutState<'_, 'a, '_>,
+    style: &Arc<ComputedValues>,
+    children: &'a [Arc<MathBox>],
+    subscript_index: Option<usize>,
+    superscript_index: Option<usize>,
+) -> MathRowLayout {
+    let expected_len =
+        1 + subscript_index.is_some() as usize + superscript_index.is_some() as usize;
+    if children.len() != expected_len {
+        // FIXME: render it as an error, per https://w3c.github.io/mathml-core/#dfn-invalid
+        return layout_row(state, children);
+    }
+    // FIXME: scripts should be laid out with an increased `math-depth`,
+    // which scales their font size down.
+    let em = font_size(style);
+    let base = children[0].layout(state);
+    let subscript = subscript_index.map(|index| children[index].layout(state));
+    let superscript = superscript_index.map(|index| children[index].layout(state));
+
+    let mut sub_shift = subscript.as_ref().map_or(Length::zero(), |sub| {
+        (em * SUBSCRIPT_SHIFT_DOWN).max(sub.ascent - em * SUBSCRIPT_TOP_MAX)
+    });
+    let sup_shift = superscript.as_ref().map_or(Length::zero(), |sup| {
+        (em * SUPERSCRIPT_SHIFT_UP).max(sup.descent + em * SUPERSCRIPT_BOTTOM_MIN)
+    });
+    if let (Some(sub), Some(sup)) = (&subscript, &superscript) {
+        let gap = (sup_shift - sup.descent) + (sub_shift - sub.ascent);
+        let gap_min = em * SUB_SUPERSCRIPT_GAP_MIN;
+        if gap < gap_min {
+            sub_shift += gap_min - gap;
+        }
+    }
+
+    let mut ascent = base.ascent;
+    let mut descent = base.descent;
+    if let Some(sub) = &subscript {
+        ascent.max_assign(sub.ascent - sub_shift);
+        descent.max_assign(sub_shift + sub.descent);
+    }
+    if let Some(sup) = &superscript {
+        ascent.max_assign(sup_shift + sup.ascent);
+        descent.max_assign(sup.descent - sup_shift);
+    }
+
+    let scripts_inline_start = base.inline_size;
+    let scripts_inline_size = subscript
+        .as_ref()
+        .map_or(Length::zero(), |sub| sub.inline_size)
+        .max(
+            superscript
+                .as_ref()
+                .map_or(Length::zero(), |sup| sup.inline_size),
+        );
+    let base_start = Vec2 {
+        inline: Length::zero(),
+        block: ascent - base.ascent,
+    };
+    let mut fragments = vec![base.into_fragment_at(base_start)];
+    if let Some(sub) = subscript {
+        let start_corner = Vec2 {
+            inline: scripts_inline_start,
+            block: ascent + sub_shift - sub.ascent,
+        };
+        fragments.push(sub.into_fragment_at(start_corner));
+    }
+    if let Some(sup) = superscript {
+        let start_corner = Vec2 {
+            inline: scripts_inline_start,
+            block: ascent - sup_shift - sup.ascent,
+        };
+        fragments.push(sup.into_fragment_at(start_corner));
+    }
+    MathRowLayout {
+        fragments,
+        inline_size: scripts_inline_start + scripts_inline_size + em * SPACE_AFTER_SCRIPT,
+        ascent,
+        descent,
+    }
+}
+
+impl MallocSizeOf for MathFormattingContext {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        size_of_boxes(&self.children, ops)
+    }
+}
+
+impl MallocSizeOf for MathBox {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        match self {
+            MathBox::Token { contents, .. } => contents.size_of(ops),
+            MathBox::Layout { children, .. } => size_of_boxes(children, ops),
+        }
+    }
+}
@@ -12,11 +12,12 @@ use crate::style_ext::{ComputedValuesExt, Direction, DisplayInside, WritingMode}
 use crate::{ContainingBlock, DefiniteContainingBlock};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use servo_arc::Arc;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::{Length, LengthOrAuto, LengthPercentage, LengthPercentageOrAuto};
 use style::Zero;
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) struct AbsolutelyPositionedBox {
     pub contents: IndependentFormattingContext,
 }
@@ -48,6 +49,7 @@ pub(crate) enum AbsoluteBoxOffsets<NonStatic> {
 impl AbsolutelyPositionedBox {
     pub fn construct<'dom>(
         context: &LayoutContext,
+        tag: Option<OpaqueNode>,
         style: Arc<ComputedValues>,
         display_inside: DisplayInside,
         contents: Contents<impl NodeExt<'dom>>,
@@ -64,6 +66,7 @@ impl AbsolutelyPositionedBox {
         Self {
             contents: IndependentFormattingContext::construct(
                 context,
+                tag,
                 style,
                 display_inside,
                 contents,
@@ -318,6 +321,8 @@ impl<'a> AbsolutelyPositionedFragment<'a> {
                 crate::formatting_contexts::IndependentLayout {
                     fragments,
                     content_block_size: block_size,
+                    first_baseline: None,
+                    last_baseline: None,
                 }
             },
             Err(non_replaced) => {
@@ -373,6 +378,7 @@ impl<'a> AbsolutelyPositionedFragment<'a> {
         );
 
         Fragment::Box(BoxFragment {
+            tag: self.absolutely_positioned_box.contents.tag,
             style: style.clone(),
             children: independent_layout.fragments,
             content_rect,
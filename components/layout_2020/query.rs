@@ -5,6 +5,7 @@
 //! Utilities for querying the layout, as needed by the layout thread.
 
 use crate::context::LayoutContext;
+use crate::flow::FragmentTreeRoot;
 use app_units::Au;
 use euclid::default::{Point2D, Rect};
 use euclid::Size2D;
@@ -15,16 +16,26 @@ use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
 use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, StyleResponse};
-use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
+use script_layout_interface::wrapper_traits::{
+    LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
+};
 use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::UntrustedNodeAddress;
+use servo_arc::Arc as ServoArc;
+use servo_url::ServoUrl;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use style::dom::OpaqueNode;
-use style::properties::PropertyId;
+use style::context::{StyleContext, ThreadLocalStyleContext};
+use style::dom::{OpaqueNode, TElement};
+use style::properties::style_structs::Font;
+use style::properties::{
+    parse_one_declaration_into, Importance, PropertyDeclarationBlock, PropertyId,
+    SourcePropertyDeclaration,
+};
 use style::selector_parser::PseudoElement;
-use style_traits::CSSPixel;
-use webrender_api::units::LayoutPixel;
+use style::shared_lock::SharedRwLock;
+use style_traits::{CSSPixel, ParsingMode};
+use webrender_api::units::{LayoutPixel, LayoutSize};
 use webrender_api::ExternalScrollId;
 
 /// Mutable data belonging to the LayoutThread.
@@ -55,6 +66,9 @@ pub struct LayoutThreadData {
     /// A queued response for the resolved style property of an element.
     pub resolved_style_response: String,
 
+    /// A queued response for the resolved font style for canvas.
+    pub resolved_font_style_response: Option<ServoArc<Font>>,
+
     /// A queued response for the offset parent/rect of a node.
     pub offset_parent_response: OffsetParentResponse,
 
@@ -132,6 +146,13 @@ impl LayoutRPC for LayoutRPCImpl {
         ResolvedStyleResponse(rw_data.resolved_style_response.clone())
     }
 
+    /// Retrieves the resolved font style for canvas.
+    fn resolved_font_style(&self) -> Option<ServoArc<Font>> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.resolved_font_style_response.clone()
+    }
+
     fn offset_parent(&self) -> OffsetParentResponse {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -163,12 +184,26 @@ impl LayoutRPC for LayoutRPCImpl {
     }
 }
 
-pub fn process_content_box_request(_requested_node: OpaqueNode) -> Option<Rect<Au>> {
-    None
+pub fn process_content_box_request(
+    requested_node: OpaqueNode,
+    fragment_tree_root: Option<&FragmentTreeRoot>,
+    viewport_size: LayoutSize,
+) -> Option<Rect<Au>> {
+    process_content_boxes_request(requested_node, fragment_tree_root, viewport_size)
+        .into_iter()
+        .fold(None, |union, rect| {
+            Some(union.map_or(rect, |union: Rect<Au>| union.union(&rect)))
+        })
 }
 
-pub fn process_content_boxes_request(_requested_node: OpaqueNode) -> Vec<Rect<Au>> {
-    vec![]
+pub fn process_content_boxes_request(
+    requested_node: OpaqueNode,
+    fragment_tree_root: Option<&FragmentTreeRoot>,
+    viewport_size: LayoutSize,
+) -> Vec<Rect<Au>> {
+    fragment_tree_root.map_or(vec![], |root| {
+        root.border_boxes_for_node(requested_node, viewport_size)
+    })
 }
 
 pub fn process_node_geometry_request(_requested_node: OpaqueNode) -> Rect<i32> {
@@ -202,12 +237,80 @@ where
     "".to_owned()
 }
 
+/// Resolves the font property `value` of a canvas context against the style
+/// of its canvas element, as the `font` attribute requires.
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-font>
+pub fn process_resolved_font_style_request<'a, N>(
+    context: &LayoutContext,
+    node: N,
+    value: &str,
+    property: &PropertyId,
+    url_data: ServoUrl,
+    shared_lock: &SharedRwLock,
+) -> Option<ServoArc<Font>>
+where
+    N: LayoutNode,
+{
+    use style::stylist::RuleInclusion;
+    use style::traversal::resolve_style;
+
+    // 1. Parse the given font property value.
+    let quirks_mode = context.style_context.quirks_mode();
+    let mut declarations = SourcePropertyDeclaration::new();
+    if parse_one_declaration_into(
+        &mut declarations,
+        property.clone(),
+        value,
+        &url_data,
+        None,
+        ParsingMode::DEFAULT,
+        quirks_mode,
+    )
+    .is_err()
+    {
+        return None;
+    }
+    let mut block = PropertyDeclarationBlock::new();
+    block.extend(declarations.drain(), Importance::Normal);
+
+    // 2. Get the resolved style of the canvas element, which is the parent
+    // of the font.
+    let element = node.as_element().unwrap();
+    let parent_style = if element.get_data().is_some() {
+        node.to_threadsafe().as_element().unwrap().resolved_style()
+    } else {
+        let mut tlc = ThreadLocalStyleContext::new(&context.style_context);
+        let mut context = StyleContext {
+            shared: &context.style_context,
+            thread_local: &mut tlc,
+        };
+        let styles = resolve_style(&mut context, element, RuleInclusion::All, None);
+        styles.primary().clone()
+    };
+
+    // 3. Compute the parsed value against that style.
+    Some(
+        context
+            .style_context
+            .stylist
+            .compute_for_declarations::<N::ConcreteElement>(
+                &context.style_context.guards,
+                &*parent_style,
+                ServoArc::new(shared_lock.wrap(block)),
+            )
+            .clone_font(),
+    )
+}
+
 pub fn process_offset_parent_query(_requested_node: OpaqueNode) -> OffsetParentResponse {
     OffsetParentResponse::empty()
 }
 
-pub fn process_style_query<N: LayoutNode>(_requested_node: N) -> StyleResponse {
-    StyleResponse(None)
+pub fn process_style_query<N: LayoutNode>(requested_node: N) -> StyleResponse {
+    let element = requested_node.as_element().unwrap();
+    let data = element.borrow_data();
+
+    StyleResponse(data.map(|d| d.styles.primary().clone()))
 }
 
 // https://html.spec.whatwg.org/multipage/#the-innertext-idl-attribute
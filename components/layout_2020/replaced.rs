@@ -11,15 +11,15 @@ use std::sync::Arc;
 use style::properties::ComputedValues;
 use style::values::computed::Length;
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) struct ReplacedContent {
     pub kind: ReplacedContentKind,
     pub intrinsic_size: physical::Vec2<Length>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, MallocSizeOf)]
 pub(crate) enum ReplacedContentKind {
-    Image(Option<Arc<Image>>),
+    Image(#[ignore_malloc_size_of = "Owned by the image cache"] Option<Arc<Image>>),
 }
 
 impl ReplacedContent {
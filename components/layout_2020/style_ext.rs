@@ -41,6 +41,8 @@ pub(crate) enum DisplayOutside {
 pub(crate) enum DisplayInside {
     Flow,
     FlowRoot,
+    /// https://w3c.github.io/mathml-core/#new-display-math-value
+    MathML,
 }
 
 pub(crate) trait ComputedValuesExt {
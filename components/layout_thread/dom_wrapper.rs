@@ -46,6 +46,7 @@ use script::layout_exports::{
     TextTypeId,
 };
 use script::layout_exports::{Document, Element, Node, Text};
+use script::layout_exports::{HTMLSlotElement, LayoutHTMLSlotElementHelpers};
 use script::layout_exports::{LayoutCharacterDataHelpers, LayoutDocumentHelpers};
 use script::layout_exports::{
     LayoutDom, LayoutElementHelpers, LayoutNodeHelpers, LayoutShadowRootHelpers,
@@ -79,7 +80,7 @@ use style::applicable_declarations::ApplicableDeclarationBlock;
 use style::attr::AttrValue;
 use style::context::SharedStyleContext;
 use style::data::ElementData;
-use style::dom::{DomChildren, LayoutIterator, NodeInfo, OpaqueNode};
+use style::dom::{LayoutIterator, NodeInfo, OpaqueNode};
 use style::dom::{TDocument, TElement, TNode, TShadowRoot};
 use style::element_state::*;
 use style::font_metrics::ServoMetricsProvider;
@@ -153,6 +154,44 @@ impl<'ln> ServoLayoutNode<'ln> {
     fn script_type_id(&self) -> NodeTypeId {
         unsafe { self.node.type_id_for_layout() }
     }
+
+    /// The first child of this node in the flat tree, the nodes assigned to a
+    /// slot replace its fallback content.
+    fn flat_tree_first_child(&self) -> Option<Self> {
+        unsafe {
+            let slot = self.node.downcast::<HTMLSlotElement>();
+            if let Some(node) = slot.and_then(|slot| slot.first_assigned_node_for_layout()) {
+                return Some(self.new_with_this_lifetime(&node));
+            }
+        }
+        self.first_child()
+    }
+
+    /// The next sibling of this node in the flat tree.
+    fn flat_tree_next_sibling(&self) -> Option<Self> {
+        unsafe {
+            match self.node.assigned_slot_for_layout() {
+                Some(slot) => slot
+                    .assigned_node_after_for_layout(self.node)
+                    .map(|node| self.new_with_this_lifetime(&node)),
+                None => self.next_sibling(),
+            }
+        }
+    }
+}
+
+/// An iterator over the children of a node in the flat tree,
+/// <https://drafts.csswg.org/css-scoping/#flat-tree>
+pub struct FlatTreeChildren<'ln>(Option<ServoLayoutNode<'ln>>);
+
+impl<'ln> Iterator for FlatTreeChildren<'ln> {
+    type Item = ServoLayoutNode<'ln>;
+
+    fn next(&mut self) -> Option<ServoLayoutNode<'ln>> {
+        let node = self.0.take()?;
+        self.0 = node.flat_tree_next_sibling();
+        Some(node)
+    }
 }
 
 impl<'ln> NodeInfo for ServoLayoutNode<'ln> {
@@ -274,6 +313,9 @@ impl<'ln> TNode for ServoLayoutNode<'ln> {
     }
 
     fn traversal_parent(&self) -> Option<ServoLayoutElement<'ln>> {
+        if let Some(slot) = unsafe { self.node.assigned_slot_for_layout() } {
+            return Some(ServoLayoutElement::from_layout_js(slot.upcast()));
+        }
         let parent = self.parent_node()?;
         if let Some(shadow) = parent.as_shadow_root() {
             return Some(shadow.host());
@@ -482,7 +524,7 @@ impl<'le> fmt::Debug for ServoLayoutElement<'le> {
 
 impl<'le> TElement for ServoLayoutElement<'le> {
     type ConcreteNode = ServoLayoutNode<'le>;
-    type TraversalChildrenIterator = DomChildren<Self::ConcreteNode>;
+    type TraversalChildrenIterator = FlatTreeChildren<'le>;
 
     type FontMetricsProvider = ServoMetricsProvider;
 
@@ -491,11 +533,15 @@ impl<'le> TElement for ServoLayoutElement<'le> {
     }
 
     fn traversal_children(&self) -> LayoutIterator<Self::TraversalChildrenIterator> {
-        LayoutIterator(if let Some(shadow) = self.shadow_root() {
-            shadow.as_node().dom_children()
-        } else {
-            self.as_node().dom_children()
-        })
+        let first_child = match self.shadow_root() {
+            Some(shadow) => shadow.as_node().flat_tree_first_child(),
+            None => self.as_node().flat_tree_first_child(),
+        };
+        LayoutIterator(FlatTreeChildren(first_child))
+    }
+
+    fn inheritance_parent(&self) -> Option<Self> {
+        self.traversal_parent()
     }
 
     fn is_html_element(&self) -> bool {
@@ -783,8 +829,6 @@ impl<'le> ServoLayoutElement<'le> {
     }
 
     pub unsafe fn note_dirty_descendant(&self) {
-        use selectors::Element;
-
         let mut current = Some(*self);
         while let Some(el) = current {
             // FIXME(bholley): Ideally we'd have the invariant that any element
@@ -793,7 +837,7 @@ impl<'le> ServoLayoutElement<'le> {
             // we get that wrong.  I have in-flight patches to fix all this
             // stuff up, so we just always propagate this bit for now.
             el.set_dirty_descendants();
-            current = el.parent_element();
+            current = el.traversal_parent();
         }
     }
 }
@@ -961,6 +1005,8 @@ impl<'le> ::selectors::Element for ServoLayoutElement<'le> {
             NonTSPseudoClass::Indeterminate |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
             NonTSPseudoClass::Target => self
                 .element
                 .get_state_for_layout()
@@ -1050,14 +1096,14 @@ impl<'a> PartialEq for ServoThreadSafeLayoutNode<'a> {
 
 impl<'ln> DangerousThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
     unsafe fn dangerous_first_child(&self) -> Option<Self> {
-        self.get_jsmanaged()
-            .first_child_ref()
-            .map(|node| self.new_with_this_lifetime(&node))
+        self.node
+            .flat_tree_first_child()
+            .map(|node| self.new_with_this_lifetime(node.get_jsmanaged()))
     }
     unsafe fn dangerous_next_sibling(&self) -> Option<Self> {
-        self.get_jsmanaged()
-            .next_sibling_ref()
-            .map(|node| self.new_with_this_lifetime(&node))
+        self.node
+            .flat_tree_next_sibling()
+            .map(|node| self.new_with_this_lifetime(node.get_jsmanaged()))
     }
 }
 
@@ -1117,11 +1163,22 @@ impl<'ln> ThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
     }
 
     fn parent_style(&self) -> Arc<ComputedValues> {
-        let parent = self.node.parent_node().unwrap().as_element().unwrap();
+        let parent = self.node.traversal_parent().unwrap();
         let parent_data = parent.get_data().unwrap().borrow();
         parent_data.styles.primary().clone()
     }
 
+    fn parent_selected_style(&self) -> Arc<ComputedValues> {
+        let parent = self.node.traversal_parent().unwrap();
+        let parent_data = parent.get_data().unwrap().borrow();
+        parent_data
+            .styles
+            .pseudos
+            .get(&PseudoElement::Selection)
+            .unwrap_or(parent_data.styles.primary())
+            .clone()
+    }
+
     fn debug_id(self) -> usize {
         self.node.debug_id()
     }
@@ -57,7 +57,8 @@ use layout::query::{
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
 use layout::query::{
-    process_offset_parent_query, process_resolved_style_request, process_style_query,
+    process_offset_parent_query, process_resolved_font_style_request,
+    process_resolved_style_request, process_style_query,
 };
 use layout::sequential;
 use layout::traversal::{
@@ -78,6 +79,7 @@ use parking_lot::RwLock;
 use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use script_layout_interface::message::WebFontSource;
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType, Reflow};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
 use script_layout_interface::rpc::TextIndexResponse;
@@ -87,7 +89,6 @@ use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as Cons
 use script_traits::{DrawAPaintImageResult, IFrameSizeMsg, PaintWorkletError, WindowSizeType};
 use script_traits::{Painter, WebrenderIpcSender};
 use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
-use selectors::Element;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
 use servo_config::opts;
@@ -125,6 +126,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::computed::font::FamilyName;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -153,10 +155,10 @@ pub struct LayoutThread {
     pipeline_port: Receiver<LayoutControlMsg>,
 
     /// The port on which we receive messages from the font cache thread.
-    font_cache_receiver: Receiver<()>,
+    font_cache_receiver: Receiver<bool>,
 
     /// The channel on which the font cache can send messages to us.
-    font_cache_sender: IpcSender<()>,
+    font_cache_sender: IpcSender<bool>,
 
     /// A means of communication with the background hang monitor.
     background_hang_monitor: Box<dyn BackgroundHangMonitor>,
@@ -469,7 +471,7 @@ fn add_font_face_rules(
     guard: &SharedRwLockReadGuard,
     device: &Device,
     font_cache_thread: &FontCacheThread,
-    font_cache_sender: &IpcSender<()>,
+    font_cache_sender: &IpcSender<bool>,
     outstanding_web_fonts_counter: &Arc<AtomicUsize>,
     load_webfonts_synchronously: bool,
 ) {
@@ -595,6 +597,7 @@ impl LayoutThread {
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
                 resolved_style_response: String::new(),
+                resolved_font_style_response: None,
                 offset_parent_response: OffsetParentResponse::empty(),
                 style_response: StyleResponse(None),
                 scroll_offsets: HashMap::new(),
@@ -705,6 +708,9 @@ impl LayoutThread {
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
+            Msg::ObserveNextFramePresentation => LayoutHangAnnotation::ObserveNextFramePresentation,
+            Msg::UpdateScriptAnimation(..) => LayoutHangAnnotation::UpdateScriptAnimation,
+            Msg::LoadWebFont(..) => LayoutHangAnnotation::LoadWebFont,
         };
         self.background_hang_monitor
             .notify_activity(HangAnnotation::Layout(hang_annotation));
@@ -878,14 +884,54 @@ impl LayoutThread {
             Msg::SetNavigationStart(time) => {
                 self.paint_time_metrics.set_navigation_start(time);
             },
+            Msg::ObserveNextFramePresentation => {
+                self.paint_time_metrics.observe_next_frame_presentation();
+            },
             Msg::GetRunningAnimations(sender) => {
                 let _ = sender.send(self.running_animations.read().len());
             },
+            Msg::UpdateScriptAnimation(animation) => {
+                let _ = self.new_animations_sender.send(animation);
+            },
+            Msg::LoadWebFont(family, source, sender) => {
+                self.load_web_font(family, source, sender);
+            },
         }
 
         true
     }
 
+    /// Loads a font that script created, and lets script know whether it
+    /// loaded, as well as reflow with it if it did.
+    fn load_web_font(&self, family: FamilyName, source: WebFontSource, sender: IpcSender<bool>) {
+        let (font_cache_sender, font_cache_receiver) = ipc::channel().unwrap();
+        let script_chan = self.script_chan.clone();
+        let id = self.id;
+        ROUTER.add_route(
+            font_cache_receiver.to_opaque(),
+            Box::new(move |message| {
+                let loaded = message.to().unwrap_or(false);
+                if loaded {
+                    font_context::invalidate_font_caches();
+                }
+                let _ = sender.send(loaded);
+                if loaded {
+                    let _ = script_chan.send(ConstellationControlMsg::WebFontLoaded(id));
+                }
+            }),
+        );
+        match source {
+            WebFontSource::Sources(sources) => {
+                self.font_cache_thread
+                    .add_web_font(family, sources, font_cache_sender)
+            },
+            WebFontSource::Data(data) => {
+                self.font_cache_thread
+                    .add_web_font_data(family, data, font_cache_sender)
+            },
+        }
+    }
+
     fn collect_reports<'a, 'b>(
         &self,
         reports_chan: ReportsChan,
@@ -1304,6 +1350,9 @@ impl LayoutThread {
                         &QueryMsg::ResolvedStyleQuery(_, _, _) => {
                             rw_data.resolved_style_response = String::new();
                         },
+                        &QueryMsg::ResolvedFontStyleQuery(_, _, _) => {
+                            rw_data.resolved_font_style_response = None;
+                        },
                         &QueryMsg::OffsetParentQuery(_) => {
                             rw_data.offset_parent_response = OffsetParentResponse::empty();
                         },
@@ -1452,7 +1501,7 @@ impl LayoutThread {
             // Propagate the descendant bit up the ancestors. Do this before
             // the restyle calculation so that we can also do it for new
             // unstyled nodes, which the descendants bit helps us find.
-            if let Some(parent) = el.parent_element() {
+            if let Some(parent) = el.traversal_parent() {
                 unsafe { parent.note_dirty_descendant() };
             }
 
@@ -1635,6 +1684,19 @@ impl LayoutThread {
                     rw_data.resolved_style_response =
                         process_resolved_style_request(context, node, pseudo, property, root_flow);
                 },
+                &QueryMsg::ResolvedFontStyleQuery(node, ref property, ref value) => {
+                    let node = unsafe { ServoLayoutNode::new(&node) };
+                    let url = self.url.clone();
+                    let shared_lock = self.document_shared_lock.clone().unwrap();
+                    rw_data.resolved_font_style_response = process_resolved_font_style_request(
+                        context,
+                        node,
+                        value,
+                        property,
+                        url,
+                        &shared_lock,
+                    );
+                },
                 &QueryMsg::OffsetParentQuery(node) => {
                     rw_data.offset_parent_response = process_offset_parent_query(node, root_flow);
                 },
@@ -1742,7 +1804,7 @@ impl LayoutThread {
 
             let invalid_nodes = {
                 // Perform an abbreviated style recalc that operates without access to the DOM.
-                let animations = self.running_animations.read();
+                let mut animations = self.running_animations.write();
                 profile(
                     profile_time::ProfilerCategory::LayoutStyleRecalc,
                     self.profiler_metadata(),
@@ -1751,7 +1813,7 @@ impl LayoutThread {
                         animation::recalc_style_for_animations::<ServoLayoutElement>(
                             &layout_context,
                             FlowRef::deref_mut(&mut root_flow),
-                            &animations,
+                            &mut animations,
                         )
                     },
                 )
@@ -34,7 +34,10 @@ use gfx::font_context;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
+use layout::animation;
+use layout::context::malloc_size_of_persistent_local_context;
 use layout::context::LayoutContext;
+use layout::context::{RegisteredPainter, RegisteredPainters};
 use layout::display_list::DisplayListBuilder;
 use layout::query::{
     process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
@@ -42,8 +45,8 @@ use layout::query::{
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
 use layout::query::{
-    process_offset_parent_query, process_resolved_style_request, process_style_query,
-    process_text_index_request,
+    process_offset_parent_query, process_resolved_font_style_request,
+    process_resolved_style_request, process_style_query, process_text_index_request,
 };
 use layout::traversal::RecalcStyle;
 use layout::BoxTreeRoot;
@@ -57,9 +60,11 @@ use msg::constellation_msg::{
 use msg::constellation_msg::{LayoutHangAnnotation, MonitoredComponentType, PipelineId};
 use msg::constellation_msg::{MonitoredComponentId, TopLevelBrowsingContextId};
 use net_traits::image_cache::ImageCache;
-use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReportsChan};
+use parking_lot::RwLock;
+use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReporterRequest, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use script_layout_interface::message::WebFontSource;
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
 use script_layout_interface::rpc::TextIndexResponse;
@@ -68,7 +73,6 @@ use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as Cons
 use script_traits::{DrawAPaintImageResult, PaintWorkletError};
 use script_traits::{Painter, WebrenderIpcSender};
 use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
-use selectors::Element;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
 use servo_config::opts;
@@ -79,13 +83,13 @@ use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::process;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{Arc, Barrier, Mutex, MutexGuard, Once};
 use std::thread;
 use std::time::Duration;
 use style::animation::Animation;
 use style::context::{QuirksMode, RegisteredSpeculativePainter, RegisteredSpeculativePainters};
 use style::context::{SharedStyleContext, ThreadLocalStyleContextCreationInfo};
-use style::dom::{TDocument, TElement, TNode};
+use style::dom::{OpaqueNode, TDocument, TElement, TNode};
 use style::driver;
 use style::error_reporting::RustLogReporter;
 use style::global_style_data::{GLOBAL_STYLE_DATA, STYLE_THREAD_POOL};
@@ -102,6 +106,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::computed::font::FamilyName;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -130,10 +135,10 @@ pub struct LayoutThread {
     pipeline_port: Receiver<LayoutControlMsg>,
 
     /// The port on which we receive messages from the font cache thread.
-    font_cache_receiver: Receiver<()>,
+    font_cache_receiver: Receiver<bool>,
 
     /// The channel on which the font cache can send messages to us.
-    font_cache_sender: IpcSender<()>,
+    font_cache_sender: IpcSender<bool>,
 
     /// A means of communication with the background hang monitor.
     background_hang_monitor: Box<dyn BackgroundHangMonitor>,
@@ -162,7 +167,7 @@ pub struct LayoutThread {
     new_animations_sender: Sender<Animation>,
 
     /// Receives newly-discovered animations.
-    _new_animations_receiver: Receiver<Animation>,
+    new_animations_receiver: Receiver<Animation>,
 
     /// The number of Web fonts that have been requested but not yet loaded.
     outstanding_web_fonts: Arc<AtomicUsize>,
@@ -176,6 +181,12 @@ pub struct LayoutThread {
     /// The document-specific shared lock used for author-origin stylesheets
     document_shared_lock: Option<SharedRwLock>,
 
+    /// The list of currently-running animations.
+    running_animations: ServoArc<RwLock<FxHashMap<OpaqueNode, Vec<Animation>>>>,
+
+    /// The list of animations that have expired since the last style recalculation.
+    expired_animations: ServoArc<RwLock<FxHashMap<OpaqueNode, Vec<Animation>>>>,
+
     /// A counter for epoch messages
     epoch: Cell<Epoch>,
 
@@ -289,6 +300,7 @@ impl LayoutThreadFactory for LayoutThread {
                         relayout_event,
                     );
 
+                    register_thread_pool_reporter(&mem_profiler_chan);
                     let reporter_name = format!("layout-reporter-{}", id);
                     mem_profiler_chan.run_with_memory_reporting(
                         || {
@@ -304,6 +316,63 @@ impl LayoutThreadFactory for LayoutThread {
     }
 }
 
+/// Registers, once per process, the memory reporter of the font contexts that
+/// live in the thread-local storage of the style thread pool.
+fn register_thread_pool_reporter(mem_profiler_chan: &profile_mem::ProfilerChan) {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let (reporter_sender, reporter_receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(
+            reporter_receiver.to_opaque(),
+            Box::new(|message| {
+                let request: ReporterRequest = message.to().unwrap();
+                // Measuring waits for the threads of the pool, which must not
+                // block the router thread.
+                thread::Builder::new()
+                    .name("LayoutThreadPoolReporter".to_owned())
+                    .spawn(move || {
+                        request.reports_channel.send(vec![Report {
+                            path: path!["layout-thread-pool", "local-context"],
+                            kind: ReportKind::ExplicitJemallocHeapSize,
+                            size: malloc_size_of_thread_pool_local_contexts(),
+                        }]);
+                    })
+                    .expect("Thread spawning failed");
+            }),
+        );
+        mem_profiler_chan.send(profile_mem::ProfilerMsg::RegisterReporter(
+            format!("layout-thread-pool-reporter-{}", process::id()),
+            profile_mem::Reporter(reporter_sender),
+        ));
+    });
+}
+
+/// Measures the font context of each thread of the style thread pool, from that thread.
+fn malloc_size_of_thread_pool_local_contexts() -> usize {
+    let pool = STYLE_THREAD_POOL.pool();
+    let pool = match pool.as_ref() {
+        Some(pool) => pool,
+        None => return 0,
+    };
+    let num_threads = pool.current_num_threads();
+    // Every job waits for all the others, so each one runs on a different thread.
+    let barrier = Barrier::new(num_threads);
+    let size = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        for _ in 0..num_threads {
+            scope.spawn(|_| {
+                let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+                size.fetch_add(
+                    malloc_size_of_persistent_local_context(&mut ops),
+                    Ordering::Relaxed,
+                );
+                barrier.wait();
+            });
+        }
+    });
+    size.into_inner()
+}
+
 struct ScriptReflowResult {
     script_reflow: ScriptReflow,
     result: RefCell<Option<ReflowComplete>>,
@@ -395,7 +464,7 @@ fn add_font_face_rules(
     guard: &SharedRwLockReadGuard,
     device: &Device,
     font_cache_thread: &FontCacheThread,
-    font_cache_sender: &IpcSender<()>,
+    font_cache_sender: &IpcSender<bool>,
     outstanding_web_fonts_counter: &Arc<AtomicUsize>,
     load_webfonts_synchronously: bool,
 ) {
@@ -490,11 +559,13 @@ impl LayoutThread {
             font_cache_sender: ipc_font_cache_sender,
             generation: Cell::new(0),
             new_animations_sender: new_animations_sender,
-            _new_animations_receiver: new_animations_receiver,
+            new_animations_receiver: new_animations_receiver,
             outstanding_web_fonts: Arc::new(AtomicUsize::new(0)),
             box_tree_root: Default::default(),
             fragment_tree_root: Default::default(),
             document_shared_lock: None,
+            running_animations: ServoArc::new(RwLock::new(Default::default())),
+            expired_animations: ServoArc::new(RwLock::new(Default::default())),
             // Epoch starts at 1 because of the initial display list for epoch 0 that we send to WR
             epoch: Cell::new(Epoch(1)),
             viewport_size: Size2D::new(Au(0), Au(0)),
@@ -510,6 +581,7 @@ impl LayoutThread {
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
                 resolved_style_response: String::new(),
+                resolved_font_style_response: None,
                 offset_parent_response: OffsetParentResponse::empty(),
                 style_response: StyleResponse(None),
                 scroll_offsets: HashMap::new(),
@@ -559,8 +631,8 @@ impl LayoutThread {
                 options: GLOBAL_STYLE_DATA.options.clone(),
                 guards,
                 visited_styles_enabled: false,
-                running_animations: Default::default(),
-                expired_animations: Default::default(),
+                running_animations: self.running_animations.clone(),
+                expired_animations: self.expired_animations.clone(),
                 registered_speculative_painters: &self.registered_painters,
                 local_context_creation_data: Mutex::new(thread_local_style_context_creation_data),
                 timer: self.timer.clone(),
@@ -568,6 +640,12 @@ impl LayoutThread {
                 snapshot_map: snapshot_map,
             },
             font_cache_thread: Mutex::new(self.font_cache_thread.clone()),
+            // Negative values are meaningless, and would wrap around if cast as is.
+            parallel_box_construction_threshold: pref!(layout.parallel_box_construction.threshold)
+                .max(0) as usize,
+            parallel_box_construction_max_depth: pref!(layout.parallel_box_construction.max_depth)
+                .max(0) as usize,
+            registered_painters: &self.registered_painters,
         }
     }
 
@@ -595,6 +673,9 @@ impl LayoutThread {
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
+            Msg::ObserveNextFramePresentation => LayoutHangAnnotation::ObserveNextFramePresentation,
+            Msg::UpdateScriptAnimation(..) => LayoutHangAnnotation::UpdateScriptAnimation,
+            Msg::LoadWebFont(..) => LayoutHangAnnotation::LoadWebFont,
         };
         self.background_hang_monitor
             .notify_activity(HangAnnotation::Layout(hang_annotation));
@@ -738,7 +819,23 @@ impl LayoutThread {
             Msg::SetFinalUrl(final_url) => {
                 self.url = final_url;
             },
-            Msg::RegisterPaint(_name, _properties, _painter) => {},
+            Msg::RegisterPaint(name, mut properties, painter) => {
+                debug!("Registering the painter");
+                let properties = properties
+                    .drain(..)
+                    .filter_map(|name| {
+                        let id = PropertyId::parse_enabled_for_all_content(&*name).ok()?;
+                        Some((name.clone(), id))
+                    })
+                    .filter(|&(_, ref id)| !id.is_shorthand())
+                    .collect();
+                let registered_painter = RegisteredPainterImpl {
+                    name: name.clone(),
+                    properties,
+                    painter,
+                };
+                self.registered_painters.0.insert(name, registered_painter);
+            },
             Msg::PrepareToExit(response_chan) => {
                 self.prepare_to_exit(response_chan);
                 return false;
@@ -752,14 +849,54 @@ impl LayoutThread {
             Msg::SetNavigationStart(time) => {
                 self.paint_time_metrics.set_navigation_start(time);
             },
+            Msg::ObserveNextFramePresentation => {
+                self.paint_time_metrics.observe_next_frame_presentation();
+            },
             Msg::GetRunningAnimations(sender) => {
-                let _ = sender.send(0);
+                let _ = sender.send(self.running_animations.read().len());
+            },
+            Msg::UpdateScriptAnimation(animation) => {
+                let _ = self.new_animations_sender.send(animation);
+            },
+            Msg::LoadWebFont(family, source, sender) => {
+                self.load_web_font(family, source, sender);
             },
         }
 
         true
     }
 
+    /// Loads a font that script created, and lets script know whether it
+    /// loaded, as well as reflow with it if it did.
+    fn load_web_font(&self, family: FamilyName, source: WebFontSource, sender: IpcSender<bool>) {
+        let (font_cache_sender, font_cache_receiver) = ipc::channel().unwrap();
+        let script_chan = self.script_chan.clone();
+        let id = self.id;
+        ROUTER.add_route(
+            font_cache_receiver.to_opaque(),
+            Box::new(move |message| {
+                let loaded = message.to().unwrap_or(false);
+                if loaded {
+                    font_context::invalidate_font_caches();
+                }
+                let _ = sender.send(loaded);
+                if loaded {
+                    let _ = script_chan.send(ConstellationControlMsg::WebFontLoaded(id));
+                }
+            }),
+        );
+        match source {
+            WebFontSource::Sources(sources) => {
+                self.font_cache_thread
+                    .add_web_font(family, sources, font_cache_sender)
+            },
+            WebFontSource::Data(data) => {
+                self.font_cache_thread
+                    .add_web_font_data(family, data, font_cache_sender)
+            },
+        }
+    }
+
     fn collect_reports<'a, 'b>(
         &self,
         reports_chan: ReportsChan,
@@ -786,6 +923,27 @@ impl LayoutThread {
             size: self.stylist.size_of(&mut ops),
         });
 
+        reports.push(Report {
+            path: path![formatted_url, "layout-thread", "box-tree"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size: self.box_tree_root.borrow().size_of(&mut ops),
+        });
+
+        reports.push(Report {
+            path: path![formatted_url, "layout-thread", "fragment-tree"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size: self.fragment_tree_root.borrow().size_of(&mut ops),
+        });
+
+        // Font contexts, and the shaped text caches they own, live in
+        // thread-local storage. The ones of the style thread pool are shared
+        // by all documents, see `register_thread_pool_reporter`.
+        reports.push(Report {
+            path: path![formatted_url, "layout-thread", "local-context"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size: malloc_size_of_persistent_local_context(&mut ops),
+        });
+
         reports_chan.send(reports);
     }
 
@@ -914,6 +1072,9 @@ impl LayoutThread {
                         &QueryMsg::ResolvedStyleQuery(_, _, _) => {
                             rw_data.resolved_style_response = String::new();
                         },
+                        &QueryMsg::ResolvedFontStyleQuery(_, _, _) => {
+                            rw_data.resolved_font_style_response = None;
+                        },
                         &QueryMsg::OffsetParentQuery(_) => {
                             rw_data.offset_parent_response = OffsetParentResponse::empty();
                         },
@@ -1037,7 +1198,7 @@ impl LayoutThread {
             // Propagate the descendant bit up the ancestors. Do this before
             // the restyle calculation so that we can also do it for new
             // unstyled nodes, which the descendants bit helps us find.
-            if let Some(parent) = el.parent_element() {
+            if let Some(parent) = el.traversal_parent() {
                 unsafe { parent.note_dirty_descendant() };
             }
 
@@ -1082,6 +1243,7 @@ impl LayoutThread {
             driver::traverse_dom(&traversal, token, Some(rayon_pool));
 
             let root_node = document.root_element().unwrap().as_node();
+            self.update_animations(&rw_data.constellation_chan, traversal.context(), root_node);
             let box_tree =
                 rayon_pool.install(|| BoxTreeRoot::construct(traversal.context(), root_node));
             Some(box_tree)
@@ -1113,7 +1275,12 @@ impl LayoutThread {
 
         // Perform post-style recalculation layout passes.
         if let Some(root) = &*self.fragment_tree_root.borrow() {
-            self.perform_post_style_recalc_layout_passes(root, &data.reflow_goal, Some(&document));
+            self.perform_post_style_recalc_layout_passes(
+                root,
+                &data.reflow_goal,
+                Some(&document),
+                &layout_context,
+            );
         }
 
         self.first_reflow.set(false);
@@ -1129,10 +1296,18 @@ impl LayoutThread {
         match *reflow_goal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match querymsg {
                 &QueryMsg::ContentBoxQuery(node) => {
-                    rw_data.content_box_response = process_content_box_request(node);
+                    rw_data.content_box_response = process_content_box_request(
+                        node,
+                        self.fragment_tree_root.borrow().as_ref(),
+                        self.layout_viewport_size(),
+                    );
                 },
                 &QueryMsg::ContentBoxesQuery(node) => {
-                    rw_data.content_boxes_response = process_content_boxes_request(node);
+                    rw_data.content_boxes_response = process_content_boxes_request(
+                        node,
+                        self.fragment_tree_root.borrow().as_ref(),
+                        self.layout_viewport_size(),
+                    );
                 },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
@@ -1157,6 +1332,19 @@ impl LayoutThread {
                     rw_data.resolved_style_response =
                         process_resolved_style_request(context, node, pseudo, property);
                 },
+                &QueryMsg::ResolvedFontStyleQuery(node, ref property, ref value) => {
+                    let node = unsafe { ServoLayoutNode::new(&node) };
+                    let url = self.url.clone();
+                    let shared_lock = self.document_shared_lock.clone().unwrap();
+                    rw_data.resolved_font_style_response = process_resolved_font_style_request(
+                        context,
+                        node,
+                        value,
+                        property,
+                        url,
+                        &shared_lock,
+                    );
+                },
                 &QueryMsg::OffsetParentQuery(node) => {
                     rw_data.offset_parent_response = process_offset_parent_query(node);
                 },
@@ -1241,26 +1429,75 @@ impl LayoutThread {
             );
         }
 
+        // The animations advance when the elements they run on are restyled,
+        // which needs access to the DOM, so script reflows for them.
+        if !self.running_animations.read().is_empty() {
+            let _ = self
+                .script_chan
+                .send(ConstellationControlMsg::RestyleAnimatedElements(self.id));
+        }
+
         if let Some(root) = &*self.fragment_tree_root.borrow() {
             // Unwrap here should not panic since self.fragment_tree_root is only ever set to Some(_)
             // in handle_reflow() where self.document_shared_lock is as well.
             let author_shared_lock = self.document_shared_lock.clone().unwrap();
             let author_guard = author_shared_lock.read();
             let ua_or_user_guard = UA_STYLESHEETS.shared_lock.read();
-            let _guards = StylesheetGuards {
+            let guards = StylesheetGuards {
                 author: &author_guard,
                 ua_or_user: &ua_or_user_guard,
             };
+            let snapshots = SnapshotMap::new();
+            let layout_context = self.build_layout_context(guards, &snapshots);
 
-            self.perform_post_style_recalc_layout_passes(root, &ReflowGoal::TickAnimations, None);
+            self.perform_post_style_recalc_layout_passes(
+                root,
+                &ReflowGoal::TickAnimations,
+                None,
+                &layout_context,
+            );
         }
     }
 
+    /// Kicks off the animations that script sent, expires completed ones, and
+    /// applies the running ones to the style of the elements they run on.
+    fn update_animations(
+        &self,
+        constellation_chan: &IpcSender<ConstellationMsg>,
+        context: &LayoutContext,
+        root: ServoLayoutNode,
+    ) {
+        let mut running_animations = self.running_animations.write();
+        let mut expired_animations = self.expired_animations.write();
+        animation::update_animation_state(
+            constellation_chan,
+            &mut running_animations,
+            &mut expired_animations,
+            &self.new_animations_receiver,
+            self.id,
+            &self.timer,
+        );
+        animation::recalc_style_for_animations(
+            context,
+            root,
+            &mut running_animations,
+            &mut expired_animations,
+        );
+    }
+
+    fn layout_viewport_size(&self) -> webrender_api::units::LayoutSize {
+        webrender_api::units::LayoutSize::from_untyped(Size2D::new(
+            self.viewport_size.width.to_f32_px(),
+            self.viewport_size.height.to_f32_px(),
+        ))
+    }
+
     fn perform_post_style_recalc_layout_passes(
         &self,
         fragment_tree: &layout::FragmentTreeRoot,
         reflow_goal: &ReflowGoal,
         document: Option<&ServoLayoutDocument>,
+        layout_context: &LayoutContext,
     ) {
         if !reflow_goal.needs_display() {
             // Defer the paint step until the next ForDisplay.
@@ -1276,11 +1513,9 @@ impl LayoutThread {
             document.will_paint();
         }
 
-        let viewport_size = webrender_api::units::LayoutSize::from_untyped(Size2D::new(
-            self.viewport_size.width.to_f32_px(),
-            self.viewport_size.height.to_f32_px(),
-        ));
-        let mut display_list = DisplayListBuilder::new(self.id.to_webrender(), viewport_size);
+        let viewport_size = self.layout_viewport_size();
+        let mut display_list =
+            DisplayListBuilder::new(self.id.to_webrender(), layout_context, viewport_size);
         let is_contentful = fragment_tree.build_display_list(&mut display_list, viewport_size);
 
         debug!("Layout done!");
@@ -1453,6 +1688,8 @@ impl Painter for RegisteredPainterImpl {
     }
 }
 
+impl RegisteredPainter for RegisteredPainterImpl {}
+
 struct RegisteredPaintersImpl(FnvHashMap<Atom, RegisteredPainterImpl>);
 
 impl RegisteredSpeculativePainters for RegisteredPaintersImpl {
@@ -1462,3 +1699,11 @@ impl RegisteredSpeculativePainters for RegisteredPaintersImpl {
             .map(|painter| painter as &dyn RegisteredSpeculativePainter)
     }
 }
+
+impl RegisteredPainters for RegisteredPaintersImpl {
+    fn get(&self, name: &Atom) -> Option<&dyn RegisteredPainter> {
+        self.0
+            .get(&name)
+            .map(|painter| painter as &dyn RegisteredPainter)
+    }
+}
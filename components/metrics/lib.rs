@@ -255,6 +255,10 @@ pub struct PaintTimeMetrics {
     navigation_start: Option<u64>,
     first_paint: Cell<Option<u64>>,
     first_contentful_paint: Cell<Option<u64>>,
+    /// Whether script asked to be told when the next frame gets painted.
+    frame_presentation_requested: Cell<bool>,
+    /// The frame whose paint time script is waiting for.
+    pending_frame_presentation: Cell<Option<Epoch>>,
     pipeline_id: PipelineId,
     time_profiler_chan: ProfilerChan,
     constellation_chan: IpcSender<LayoutMsg>,
@@ -275,6 +279,8 @@ impl PaintTimeMetrics {
             navigation_start: None,
             first_paint: Cell::new(None),
             first_contentful_paint: Cell::new(None),
+            frame_presentation_requested: Cell::new(false),
+            pending_frame_presentation: Cell::new(None),
             pipeline_id,
             time_profiler_chan,
             constellation_chan,
@@ -302,6 +308,12 @@ impl PaintTimeMetrics {
         );
     }
 
+    /// Makes the next frame observed by `maybe_observe_paint_time` notify script
+    /// once it has been painted, regardless of the paint metrics.
+    pub fn observe_next_frame_presentation(&self) {
+        self.frame_presentation_requested.set(true);
+    }
+
     pub fn maybe_observe_paint_time<T>(
         &self,
         profiler_metadata_factory: &T,
@@ -310,18 +322,27 @@ impl PaintTimeMetrics {
     ) where
         T: ProfilerMetadataFactory,
     {
-        if self.first_paint.get().is_some() && self.first_contentful_paint.get().is_some() {
-            // If we already set all paint metrics, we just bail out.
-            return;
+        let observe_frame_presentation = self.frame_presentation_requested.replace(false);
+        if observe_frame_presentation {
+            self.pending_frame_presentation.set(Some(epoch));
         }
 
-        self.pending_metrics.borrow_mut().insert(
-            epoch,
-            (
-                profiler_metadata_factory.new_metadata(),
-                display_list_is_contentful,
-            ),
-        );
+        // If we already set all paint metrics, or if the frame can't set any of
+        // the remaining ones because it isn't contentful, there is no metric to
+        // observe.
+        let observe_paint_metrics = self.first_paint.get().is_none() ||
+            (self.first_contentful_paint.get().is_none() && display_list_is_contentful);
+        if observe_paint_metrics {
+            self.pending_metrics.borrow_mut().insert(
+                epoch,
+                (
+                    profiler_metadata_factory.new_metadata(),
+                    display_list_is_contentful,
+                ),
+            );
+        } else if !observe_frame_presentation {
+            return;
+        }
 
         // Send the pending metric information to the compositor thread.
         // The compositor will record the current time after painting the
@@ -333,6 +354,21 @@ impl PaintTimeMetrics {
     }
 
     pub fn maybe_set_metric(&self, epoch: Epoch, paint_time: u64) {
+        // Frames are painted in order, so the frame script is waiting for has
+        // been presented at the latest along with this one.
+        if let Some(pending_epoch) = self.pending_frame_presentation.get() {
+            if pending_epoch <= epoch {
+                self.pending_frame_presentation.set(None);
+                let msg = ConstellationControlMsg::FramePresented(self.pipeline_id, paint_time);
+                if let Err(e) = self.script_chan.send(msg) {
+                    warn!(
+                        "Sending frame presentation to script thread failed ({}).",
+                        e
+                    );
+                }
+            }
+        }
+
         if self.first_paint.get().is_some() && self.first_contentful_paint.get().is_some() ||
             self.navigation_start.is_none()
         {
@@ -343,17 +379,21 @@ impl PaintTimeMetrics {
 
         if let Some(pending_metric) = self.pending_metrics.borrow_mut().remove(&epoch) {
             let profiler_metadata = pending_metric.0;
-            set_metric(
-                self,
-                profiler_metadata.clone(),
-                ProgressiveWebMetricType::FirstPaint,
-                ProfilerCategory::TimeToFirstPaint,
-                &self.first_paint,
-                Some(paint_time),
-                &self.url,
-            );
+            // Each metric is only reported once, even if several frames were
+            // pending when the first one got painted.
+            if self.first_paint.get().is_none() {
+                set_metric(
+                    self,
+                    profiler_metadata.clone(),
+                    ProgressiveWebMetricType::FirstPaint,
+                    ProfilerCategory::TimeToFirstPaint,
+                    &self.first_paint,
+                    Some(paint_time),
+                    &self.url,
+                );
+            }
 
-            if pending_metric.1 {
+            if pending_metric.1 && self.first_contentful_paint.get().is_none() {
                 set_metric(
                     self,
                     profiler_metadata,
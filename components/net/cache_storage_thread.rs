@@ -0,0 +1,447 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::resource_thread;
+use http::header::VARY;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use net_traits::cache_storage_thread::{CacheQueryOptions, CacheStorageError};
+use net_traits::cache_storage_thread::{CacheStorageThreadMsg, CachedRequest, CachedResponse};
+use servo_url::ImmutableOrigin;
+use std::borrow::ToOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::thread;
+use uuid::Uuid;
+
+pub const QUOTA_SIZE_LIMIT: usize = 50 * 1024 * 1024;
+
+/// The directory of the config dir the caches are persisted to.
+const CACHE_STORAGE_DIR: &str = "cache_storage";
+
+/// The file of `CACHE_STORAGE_DIR` listing the caches and their entries,
+/// without the bodies of their responses.
+const CACHE_STORAGE_INDEX: &str = "index.json";
+
+/// An entry of a cache. The body of its response is persisted in a file of its own,
+/// so that storing or removing an entry does not rewrite the bodies of the others.
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    request: CachedRequest,
+    /// The response, whose body is kept in `body`.
+    response: CachedResponse,
+    #[serde(skip)]
+    body: Vec<u8>,
+    /// The name of the file the body is persisted to.
+    body_file: String,
+}
+
+impl Entry {
+    fn new(request: CachedRequest, mut response: CachedResponse) -> Entry {
+        let body = mem::replace(&mut response.body, vec![]);
+        Entry {
+            request: request,
+            response: response,
+            body: body,
+            body_file: Uuid::new_v4().to_simple().to_string(),
+        }
+    }
+
+    fn to_cached(&self) -> (CachedRequest, CachedResponse) {
+        let mut response = self.response.clone();
+        response.body = self.body.clone();
+        (self.request.clone(), response)
+    }
+
+    fn matches(&self, request: &CachedRequest, options: &CacheQueryOptions) -> bool {
+        request_matches_cached_item(request, &self.request, &self.response, options)
+    }
+
+    fn read_body(&mut self, dir: &Path) {
+        match fs::read(dir.join(&self.body_file)) {
+            Ok(body) => self.body = body,
+            Err(e) => warn!("Could not read cached body {}: {}", self.body_file, e),
+        }
+    }
+
+    fn write_body(&self, dir: Option<&Path>) {
+        if let Some(dir) = dir {
+            if let Err(e) = fs::write(dir.join(&self.body_file), &self.body) {
+                warn!("Could not write cached body {}: {}", self.body_file, e);
+            }
+        }
+    }
+
+    fn remove_body(&self, dir: Option<&Path>) {
+        if let Some(dir) = dir {
+            if let Err(e) = fs::remove_file(dir.join(&self.body_file)) {
+                warn!("Could not remove cached body {}: {}", self.body_file, e);
+            }
+        }
+    }
+}
+
+/// The entries of a cache, in the order they were stored.
+type Entries = Vec<Entry>;
+
+pub trait CacheStorageThreadFactory {
+    fn new(config_dir: Option<PathBuf>) -> Self;
+}
+
+impl CacheStorageThreadFactory for IpcSender<CacheStorageThreadMsg> {
+    /// Create a cache storage thread
+    fn new(config_dir: Option<PathBuf>) -> IpcSender<CacheStorageThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+        thread::Builder::new()
+            .name("CacheStorageManager".to_owned())
+            .spawn(move || {
+                CacheStorageManager::new(port, config_dir).start();
+            })
+            .expect("Thread spawning failed");
+        chan
+    }
+}
+
+struct CacheStorageManager {
+    port: IpcReceiver<CacheStorageThreadMsg>,
+    /// The caches of each tuple origin, along with their names, in the order they were created.
+    caches: HashMap<String, Vec<(String, Entries)>>,
+    /// The directory the caches are persisted to, if any.
+    storage_dir: Option<PathBuf>,
+}
+
+impl CacheStorageManager {
+    fn new(
+        port: IpcReceiver<CacheStorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+    ) -> CacheStorageManager {
+        let mut caches: HashMap<String, Vec<(String, Entries)>> = HashMap::new();
+        let storage_dir = config_dir.map(|config_dir| config_dir.join(CACHE_STORAGE_DIR));
+        if let Some(ref storage_dir) = storage_dir {
+            if let Err(e) = fs::create_dir_all(storage_dir) {
+                warn!("Could not create {}: {}", storage_dir.display(), e);
+            }
+            resource_thread::read_json_from_file(&mut caches, storage_dir, CACHE_STORAGE_INDEX);
+            for entry in caches
+                .values_mut()
+                .flat_map(|caches| caches.iter_mut())
+                .flat_map(|&mut (_, ref mut entries)| entries.iter_mut())
+            {
+                entry.read_body(storage_dir);
+            }
+        }
+        CacheStorageManager {
+            port: port,
+            caches: caches,
+            storage_dir: storage_dir,
+        }
+    }
+}
+
+impl CacheStorageManager {
+    fn start(&mut self) {
+        // The thread exits once every resource thread sender has been dropped,
+        // caches are saved eagerly so there is nothing to clean up.
+        while let Ok(msg) = self.port.recv() {
+            match msg {
+                CacheStorageThreadMsg::Has(sender, origin, name) => self.has(sender, origin, name),
+                CacheStorageThreadMsg::Open(sender, origin, name) => {
+                    if self.open(sender, origin, name) {
+                        self.save_index()
+                    }
+                },
+                CacheStorageThreadMsg::Delete(sender, origin, name) => {
+                    if self.delete(sender, origin, name) {
+                        self.save_index()
+                    }
+                },
+                CacheStorageThreadMsg::Keys(sender, origin) => self.keys(sender, origin),
+                CacheStorageThreadMsg::Usage(sender, origin) => {
+                    let origin = self.origin_as_string(origin);
+                    sender.send(self.usage(&origin)).unwrap()
+                },
+                CacheStorageThreadMsg::MatchAll(sender, origin, name, request, options) => {
+                    self.match_all(sender, origin, name, request, options)
+                },
+                CacheStorageThreadMsg::Put(sender, origin, name, entries) => {
+                    if self.put(sender, origin, name, entries) {
+                        self.save_index()
+                    }
+                },
+                CacheStorageThreadMsg::Remove(sender, origin, name, request, options) => {
+                    if self.remove(sender, origin, name, request, options) {
+                        self.save_index()
+                    }
+                },
+            }
+        }
+    }
+
+    /// Saves the list of caches and entries, the bodies are saved as entries are stored.
+    fn save_index(&self) {
+        if let Some(ref storage_dir) = self.storage_dir {
+            resource_thread::write_json_to_file(&self.caches, storage_dir, CACHE_STORAGE_INDEX);
+        }
+    }
+
+    fn find_cache(&self, origin: &str, name: &str) -> Option<&Entries> {
+        self.caches.get(origin).and_then(|caches| {
+            caches
+                .iter()
+                .find(|&&(ref cache_name, _)| cache_name == name)
+                .map(|&(_, ref entries)| entries)
+        })
+    }
+
+    fn find_cache_mut(&mut self, origin: &str, name: &str) -> Option<&mut Entries> {
+        self.caches.get_mut(origin).and_then(|caches| {
+            caches
+                .iter_mut()
+                .find(|&&mut (ref cache_name, _)| cache_name == name)
+                .map(|&mut (_, ref mut entries)| entries)
+        })
+    }
+
+    fn has(&self, sender: IpcSender<bool>, origin: ImmutableOrigin, name: String) {
+        let origin = self.origin_as_string(origin);
+        sender
+            .send(self.find_cache(&origin, &name).is_some())
+            .unwrap();
+    }
+
+    /// Returns whether a cache was created.
+    fn open(&mut self, sender: IpcSender<()>, origin: ImmutableOrigin, name: String) -> bool {
+        let origin = self.origin_as_string(origin);
+        let caches = self.caches.entry(origin).or_insert_with(Vec::new);
+        let created = !caches
+            .iter()
+            .any(|&(ref cache_name, _)| *cache_name == name);
+        if created {
+            caches.push((name, vec![]));
+        }
+        sender.send(()).unwrap();
+        created
+    }
+
+    /// Returns whether a cache was deleted.
+    fn delete(&mut self, sender: IpcSender<bool>, origin: ImmutableOrigin, name: String) -> bool {
+        let origin = self.origin_as_string(origin);
+        let storage_dir = self.storage_dir.clone();
+        let deleted = match self.caches.get_mut(&origin) {
+            Some(caches) => match caches
+                .iter()
+                .position(|&(ref cache_name, _)| *cache_name == name)
+            {
+                Some(index) => {
+                    let (_, entries) = caches.remove(index);
+                    for entry in entries {
+                        entry.remove_body(storage_dir.as_ref().map(|dir| &**dir));
+                    }
+                    true
+                },
+                None => false,
+            },
+            None => false,
+        };
+        sender.send(deleted).unwrap();
+        deleted
+    }
+
+    fn keys(&self, sender: IpcSender<Vec<String>>, origin: ImmutableOrigin) {
+        let origin = self.origin_as_string(origin);
+        let names = self.caches.get(&origin).map_or(vec![], |caches| {
+            caches.iter().map(|&(ref name, _)| name.clone()).collect()
+        });
+        sender.send(names).unwrap();
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#query-cache>
+    fn match_all(
+        &self,
+        sender: IpcSender<Vec<(CachedRequest, CachedResponse)>>,
+        origin: ImmutableOrigin,
+        name: Option<String>,
+        request: Option<CachedRequest>,
+        options: CacheQueryOptions,
+    ) {
+        let origin = self.origin_as_string(origin);
+        let caches: Vec<&Entries> = match name {
+            Some(name) => self.find_cache(&origin, &name).into_iter().collect(),
+            None => self.caches.get(&origin).map_or(vec![], |caches| {
+                caches.iter().map(|&(_, ref entries)| entries).collect()
+            }),
+        };
+        let matches = caches
+            .into_iter()
+            .flat_map(|entries| entries.iter())
+            .filter(|entry| {
+                request
+                    .as_ref()
+                    .map_or(true, |request| entry.matches(request, &options))
+            })
+            .map(Entry::to_cached)
+            .collect();
+        sender.send(matches).unwrap();
+    }
+
+    /// The size of the bodies of the responses stored in the caches of the origin.
+    fn usage(&self, origin: &str) -> usize {
+        self.caches.get(origin).map_or(0, |caches| {
+            caches
+                .iter()
+                .flat_map(|&(_, ref entries)| entries.iter())
+                .map(|entry| entry.body.len())
+                .sum()
+        })
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#batch-cache-operations>
+    ///
+    /// Returns whether the cache changed.
+    fn put(
+        &mut self,
+        sender: IpcSender<Result<(), CacheStorageError>>,
+        origin: ImmutableOrigin,
+        name: String,
+        new_entries: Vec<(CachedRequest, CachedResponse)>,
+    ) -> bool {
+        let origin = self.origin_as_string(origin);
+        let current_size = self.usage(&origin);
+        let storage_dir = self.storage_dir.clone();
+        let entries = match self.find_cache_mut(&origin, &name) {
+            Some(entries) => entries,
+            None => {
+                sender.send(Err(CacheStorageError::NotFound)).unwrap();
+                return false;
+            },
+        };
+
+        // The replaced entries are only removed once the quota is checked,
+        // so that none of the new entries is stored if it would be exceeded.
+        let mut replaced = vec![false; entries.len()];
+        let mut added: Vec<Entry> = vec![];
+        for (request, response) in new_entries {
+            let options = CacheQueryOptions::default();
+            for (replaced, entry) in replaced.iter_mut().zip(entries.iter()) {
+                *replaced = *replaced || entry.matches(&request, &options);
+            }
+            added.retain(|entry| !entry.matches(&request, &options));
+            added.push(Entry::new(request, response));
+        }
+        let old_size: usize = entries
+            .iter()
+            .zip(replaced.iter())
+            .filter(|&(_, replaced)| *replaced)
+            .map(|(entry, _)| entry.body.len())
+            .sum();
+        let new_size: usize = added.iter().map(|entry| entry.body.len()).sum();
+        if current_size - old_size + new_size > QUOTA_SIZE_LIMIT {
+            sender.send(Err(CacheStorageError::QuotaExceeded)).unwrap();
+            return false;
+        }
+
+        let storage_dir = storage_dir.as_ref().map(|dir| &**dir);
+        let mut replaced = replaced.into_iter();
+        entries.retain(|entry| {
+            let replaced = replaced.next().unwrap_or(false);
+            if replaced {
+                entry.remove_body(storage_dir);
+            }
+            !replaced
+        });
+        for entry in added {
+            entry.write_body(storage_dir);
+            entries.push(entry);
+        }
+        sender.send(Ok(())).unwrap();
+        true
+    }
+
+    /// Returns whether any entry was removed.
+    fn remove(
+        &mut self,
+        sender: IpcSender<bool>,
+        origin: ImmutableOrigin,
+        name: String,
+        request: CachedRequest,
+        options: CacheQueryOptions,
+    ) -> bool {
+        let origin = self.origin_as_string(origin);
+        let storage_dir = self.storage_dir.clone();
+        let removed = match self.find_cache_mut(&origin, &name) {
+            Some(entries) => {
+                let length = entries.len();
+                entries.retain(|entry| {
+                    let matches = entry.matches(&request, &options);
+                    if matches {
+                        entry.remove_body(storage_dir.as_ref().map(|dir| &**dir));
+                    }
+                    !matches
+                });
+                entries.len() != length
+            },
+            None => false,
+        };
+        sender.send(removed).unwrap();
+        removed
+    }
+
+    /// Opaque origins all serialize to "null", nothing is stored for them since
+    /// the methods of a `CacheStorage` of an opaque origin reject with a `SecurityError`.
+    fn origin_as_string(&self, origin: ImmutableOrigin) -> String {
+        origin.ascii_serialization()
+    }
+}
+
+/// <https://w3c.github.io/ServiceWorker/#request-matches-cached-item-algorithm>
+fn request_matches_cached_item(
+    request: &CachedRequest,
+    cached_request: &CachedRequest,
+    cached_response: &CachedResponse,
+    options: &CacheQueryOptions,
+) -> bool {
+    // Step 1.
+    if !options.ignore_method && request.method != "GET" {
+        return false;
+    }
+
+    // Steps 2-5.
+    let mut query_url = request.url.clone();
+    let mut cached_url = cached_request.url.clone();
+    query_url.set_fragment(None);
+    cached_url.set_fragment(None);
+    if options.ignore_search {
+        query_url.as_mut_url().set_query(None);
+        cached_url.as_mut_url().set_query(None);
+    }
+    if query_url != cached_url {
+        return false;
+    }
+
+    // Step 6.
+    if options.ignore_vary {
+        return true;
+    }
+
+    // Step 7.
+    let vary = cached_response.headers.get_all(VARY);
+    for field_value in vary.iter() {
+        let field_value = match field_value.to_str() {
+            Ok(field_value) => field_value,
+            Err(_) => return false,
+        };
+        for field_name in field_value.split(',').map(str::trim) {
+            if field_name == "*" {
+                return false;
+            }
+            if request.headers.get(field_name) != cached_request.headers.get(field_name) {
+                return false;
+            }
+        }
+    }
+
+    // Step 8.
+    true
+}
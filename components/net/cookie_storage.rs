@@ -6,8 +6,11 @@
 //! http://tools.ietf.org/html/rfc6265
 
 use crate::cookie::Cookie;
+use hyper_serde::Serde;
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::PipelineId;
 use net_traits::pub_domains::reg_suffix;
-use net_traits::CookieSource;
+use net_traits::{CookieChange, CookieSource};
 use servo_url::ServoUrl;
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -18,6 +21,8 @@ pub struct CookieStorage {
     version: u32,
     cookies_map: HashMap<String, Vec<Cookie>>,
     max_per_host: usize,
+    #[serde(skip)]
+    change_listeners: HashMap<PipelineId, (ServoUrl, IpcSender<CookieChange>)>,
 }
 
 impl CookieStorage {
@@ -26,6 +31,28 @@ impl CookieStorage {
             version: 1,
             cookies_map: HashMap::new(),
             max_per_host: max_cookies,
+            change_listeners: HashMap::new(),
+        }
+    }
+
+    pub fn add_change_listener(
+        &mut self,
+        pipeline_id: PipelineId,
+        url: ServoUrl,
+        sender: IpcSender<CookieChange>,
+    ) {
+        self.change_listeners.insert(pipeline_id, (url, sender));
+    }
+
+    pub fn remove_change_listener(&mut self, pipeline_id: PipelineId) {
+        self.change_listeners.remove(&pipeline_id);
+    }
+
+    /// Removes a cookie on behalf of a non-HTTP API, and reports the deletion
+    /// to the change listeners.
+    pub fn delete(&mut self, cookie: &Cookie, url: &ServoUrl) {
+        if let Ok(Some(cookie)) = self.remove(cookie, url, CookieSource::NonHTTP) {
+            notify_change_listeners(&self.change_listeners, &cookie, true);
         }
     }
 
@@ -126,6 +153,9 @@ impl CookieStorage {
                 return;
             }
         }
+        // Storing an expired cookie deletes it as far as non-HTTP APIs are concerned.
+        let deleted = is_cookie_expired(&cookie);
+        notify_change_listeners(&self.change_listeners, &cookie, deleted);
         cookies.push(cookie);
     }
 
@@ -218,6 +248,25 @@ fn is_cookie_expired(cookie: &Cookie) -> bool {
     }
 }
 
+/// Reports the change of a cookie to the listeners of the URLs it is visible to.
+fn notify_change_listeners(
+    listeners: &HashMap<PipelineId, (ServoUrl, IpcSender<CookieChange>)>,
+    cookie: &Cookie,
+    deleted: bool,
+) {
+    for &(ref url, ref sender) in listeners.values() {
+        if !cookie.appropriate_for_url(url, CookieSource::NonHTTP) {
+            continue;
+        }
+        let change = Serde(cookie.cookie.clone());
+        let _ = sender.send(if deleted {
+            CookieChange::Deleted(change)
+        } else {
+            CookieChange::Changed(change)
+        });
+    }
+}
+
 fn evict_one_cookie(is_secure_cookie: bool, cookies: &mut Vec<Cookie>) -> bool {
     // Remove non-secure cookie with oldest access time
     let oldest_accessed: Option<(usize, Tm)> = get_oldest_accessed(false, cookies);
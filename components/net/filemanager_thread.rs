@@ -157,8 +157,8 @@ impl FileManager {
             FileManagerThreadMsg::PromoteMemory(id, blob_buf, set_valid, origin) => {
                 self.promote_memory(id, blob_buf, set_valid, origin);
             },
-            FileManagerThreadMsg::AddSlicedURLEntry(id, rel_pos, sender, origin) => {
-                self.store.add_sliced_url_entry(id, rel_pos, sender, origin);
+            FileManagerThreadMsg::AddBlobURLEntry(id, rel_pos, sender, origin) => {
+                self.store.add_blob_url_entry(id, rel_pos, sender, origin);
             },
             FileManagerThreadMsg::DecRef(id, origin, sender) => {
                 let _ = sender.send(self.store.dec_ref(&id, &origin));
@@ -233,7 +233,7 @@ impl FileManagerStore {
         }
     }
 
-    fn add_sliced_url_entry(
+    fn add_blob_url_entry(
         &self,
         parent_id: Uuid,
         rel_pos: RelativePos,
@@ -248,14 +248,13 @@ impl FileManagerStore {
                     FileStoreEntry {
                         origin: origin_in,
                         file_impl: FileImpl::Sliced(parent_id, rel_pos),
-                        refs: AtomicUsize::new(1),
-                        // Valid here since AddSlicedURLEntry implies URL creation
-                        // from a BlobImpl::Sliced
+                        // Only the Blob URL refers to this entry,
+                        // so it is removed once the URL is revoked
+                        refs: AtomicUsize::new(0),
                         is_valid_url: AtomicBool::new(true),
                     },
                 );
 
-                // We assume that the returned id will be held by BlobImpl::File
                 let _ = sender.send(Ok(new_id));
             },
             Err(e) => {
@@ -35,20 +35,25 @@ use http::uri::Authority;
 use http::{HeaderMap, Request as HyperRequest};
 use hyper::{Body, Client, Method, Response as HyperResponse, StatusCode};
 use hyper_serde::Serde;
+use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::{HistoryStateId, PipelineId};
 use net_traits::quality::{quality_to_value, Quality, QualityItem};
 use net_traits::request::Origin::Origin as SpecificOrigin;
+use net_traits::request::KEEP_ALIVE_QUOTA;
+use net_traits::request::{BodyChunkRequest, BodyChunkResponse};
 use net_traits::request::{CacheMode, CredentialsMode, Destination, Origin};
 use net_traits::request::{RedirectMode, Referrer, Request, RequestBuilder, RequestMode};
 use net_traits::request::{ResponseTainting, ServiceWorkersMode};
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
 use net_traits::{CookieSource, FetchMetadata, NetworkError, ReferrerPolicy};
+use net_traits::{CustomResponseMediator, ServiceWorkerResponse};
 use net_traits::{
     RedirectEndValue, RedirectStartValue, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue,
 };
 use openssl::ssl::SslConnectorBuilder;
 use servo_arc::Arc;
 use servo_url::{ImmutableOrigin, ServoUrl};
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::iter::FromIterator;
@@ -56,6 +61,7 @@ use std::mem;
 use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::{Condvar, Mutex, RwLock};
+use std::thread;
 use std::time::{Duration, SystemTime};
 use time::{self, Tm};
 use tokio::prelude::{future, Future, Stream};
@@ -86,6 +92,9 @@ pub struct HttpState {
     pub http_cache_state: Mutex<HashMap<CacheKey, Arc<(Mutex<HttpCacheEntryState>, Condvar)>>>,
     pub auth_cache: RwLock<AuthCache>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    /// The total size of the bodies of the inflight keepalive requests of
+    /// each pipeline.
+    pub keep_alive_bytes: Mutex<HashMap<Option<PipelineId>, u64>>,
     pub client: Client<Connector, Body>,
 }
 
@@ -98,11 +107,53 @@ impl HttpState {
             history_states: RwLock::new(HashMap::new()),
             http_cache: RwLock::new(HttpCache::new()),
             http_cache_state: Mutex::new(HashMap::new()),
+            keep_alive_bytes: Mutex::new(HashMap::new()),
             client: create_http_client(ssl_connector_builder, HANDLE.lock().unwrap().executor()),
         }
     }
 }
 
+/// A share of the keepalive quota of a pipeline, given back when dropped.
+struct KeepAliveReservation<'a> {
+    state: &'a HttpState,
+    pipeline_id: Option<PipelineId>,
+    bytes: u64,
+}
+
+impl<'a> KeepAliveReservation<'a> {
+    /// Returns `None` if the inflight keepalive requests of the pipeline
+    /// leave less than `bytes` of the quota.
+    fn new(
+        state: &'a HttpState,
+        pipeline_id: Option<PipelineId>,
+        bytes: u64,
+    ) -> Option<KeepAliveReservation<'a>> {
+        let mut keep_alive_bytes = state.keep_alive_bytes.lock().unwrap();
+        let inflight_bytes = keep_alive_bytes.get(&pipeline_id).cloned().unwrap_or(0);
+        if inflight_bytes + bytes > KEEP_ALIVE_QUOTA {
+            return None;
+        }
+        keep_alive_bytes.insert(pipeline_id, inflight_bytes + bytes);
+        Some(KeepAliveReservation {
+            state,
+            pipeline_id,
+            bytes,
+        })
+    }
+}
+
+impl<'a> Drop for KeepAliveReservation<'a> {
+    fn drop(&mut self) {
+        let mut keep_alive_bytes = self.state.keep_alive_bytes.lock().unwrap();
+        if let Entry::Occupied(mut entry) = keep_alive_bytes.entry(self.pipeline_id) {
+            *entry.get_mut() -= self.bytes;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
 fn precise_time_ms() -> u64 {
     time::precise_time_ns() / (1000 * 1000)
 }
@@ -365,6 +416,7 @@ fn obtain_response(
     method: &Method,
     request_headers: &HeaderMap,
     data: &Option<Vec<u8>>,
+    body_stream: &Option<IpcSender<BodyChunkRequest>>,
     load_data_method: &Method,
     pipeline_id: &Option<PipelineId>,
     iters: u32,
@@ -387,11 +439,18 @@ fn obtain_response(
     // https://tools.ietf.org/html/rfc7231#section-6.4
     let is_redirected_request = iters != 1;
     let request_body;
-    match data {
-        &Some(ref d) if !is_redirected_request => {
+    let mut streamed_body = None;
+    match (data, body_stream) {
+        (&Some(ref d), _) if !is_redirected_request => {
             headers.typed_insert(ContentLength(d.len() as u64));
             request_body = d.clone();
         },
+        // The length of a streamed body is not known up front, so it is sent
+        // with the chunked transfer coding rather than a Content-Length.
+        (_, &Some(ref body_stream)) if !is_redirected_request => {
+            request_body = vec![];
+            streamed_body = Some(body_stream.clone());
+        },
         _ => {
             if *load_data_method != Method::GET && *load_data_method != Method::HEAD {
                 headers.typed_insert(ContentLength(0))
@@ -400,11 +459,13 @@ fn obtain_response(
         },
     }
 
-    context
-        .timing
-        .lock()
-        .unwrap()
-        .set_attribute(ResourceAttribute::DomainLookupStart);
+    // The host is resolved by the connector of the client, which doesn't tell
+    // us when it's done, so the lookup is considered to be immediate.
+    {
+        let mut timing = context.timing.lock().unwrap();
+        timing.set_attribute(ResourceAttribute::DomainLookupStart);
+        timing.set_attribute(ResourceAttribute::DomainLookupEnd);
+    }
 
     // TODO(#21261) connect_start: set if a persistent connection is *not* used and the last non-redirected
     // fetch passes the timing allow check
@@ -413,7 +474,17 @@ fn obtain_response(
         .timing
         .lock()
         .unwrap()
-        .set_attribute(ResourceAttribute::ConnectStart(connect_start));
+        .set_attribute(ResourceAttribute::ConnectStart);
+
+    // Devtools is only shown the bodies whose bytes are known up front.
+    let devtools_body = match streamed_body {
+        Some(_) => None,
+        None => Some(request_body.clone()),
+    };
+    let hyper_body = match streamed_body {
+        Some(body_stream) => stream_request_body(body_stream),
+        None => request_body.into(),
+    };
 
     // https://url.spec.whatwg.org/#percent-encoded-bytes
     let request = HyperRequest::builder()
@@ -426,7 +497,7 @@ fn obtain_response(
                 .replace("{", "%7B")
                 .replace("}", "%7D"),
         )
-        .body(request_body.clone().into());
+        .body(hyper_body);
 
     // TODO: We currently don't know when the handhhake before the connection is done
     // so our best bet would be to set `secure_connection_start` here when we are currently
@@ -450,13 +521,14 @@ fn obtain_response(
         .timing
         .lock()
         .unwrap()
-        .set_attribute(ResourceAttribute::ConnectEnd(connect_end));
+        .set_attribute(ResourceAttribute::ConnectEnd);
 
     let request_id = request_id.map(|v| v.to_owned());
     let pipeline_id = pipeline_id.clone();
     let closure_url = url.clone();
     let method = method.clone();
     let send_start = precise_time_ms();
+    let timing = context.timing.clone();
 
     Box::new(
         client
@@ -464,7 +536,12 @@ fn obtain_response(
             .and_then(move |res| {
                 let send_end = precise_time_ms();
 
-                // TODO(#21271) response_start: immediately after receiving first byte of response
+                // TODO(#21271) response_start: immediately after receiving first byte of response,
+                // the headers are the closest we get to it.
+                timing
+                    .lock()
+                    .unwrap()
+                    .set_attribute(ResourceAttribute::ResponseStart);
 
                 let msg = if let Some(request_id) = request_id {
                     if let Some(pipeline_id) = pipeline_id {
@@ -473,7 +550,7 @@ fn obtain_response(
                             closure_url,
                             method.clone(),
                             headers,
-                            Some(request_body.clone()),
+                            devtools_body,
                             pipeline_id,
                             time::now(),
                             connect_end - connect_start,
@@ -498,6 +575,112 @@ fn obtain_response(
     )
 }
 
+/// Creates the body of a hyper request from a `ReadableStream` in script,
+/// asking for each chunk only once hyper is ready to send it, so that the
+/// stream is never read faster than the body is transmitted.
+fn stream_request_body(body_stream: IpcSender<BodyChunkRequest>) -> Body {
+    let (mut sender, body) = Body::channel();
+    thread::Builder::new()
+        .name("RequestBodyStream".to_owned())
+        .spawn(move || {
+            let (chunk_sender, chunk_receiver) = match ipc::channel() {
+                Ok(channel) => channel,
+                Err(_) => return sender.abort(),
+            };
+            if body_stream
+                .send(BodyChunkRequest::Connect(chunk_sender))
+                .is_err()
+            {
+                return sender.abort();
+            }
+            loop {
+                // The receiving end of the body is dropped if the request
+                // fails or is cancelled, in which case the stream is told to
+                // stop.
+                if future::poll_fn(|| sender.poll_ready()).wait().is_err() {
+                    let _ = body_stream.send(BodyChunkRequest::Done);
+                    return;
+                }
+                if body_stream.send(BodyChunkRequest::Chunk).is_err() {
+                    return sender.abort();
+                }
+                match chunk_receiver.recv() {
+                    Ok(BodyChunkResponse::Chunk(bytes)) => {
+                        if sender.send_data(bytes.into()).is_err() {
+                            let _ = body_stream.send(BodyChunkRequest::Done);
+                            return;
+                        }
+                    },
+                    // Dropping the sender ends the body.
+                    Ok(BodyChunkResponse::Done) => return,
+                    Ok(BodyChunkResponse::Error) | Err(_) => return sender.abort(),
+                }
+            }
+        })
+        .expect("Thread spawning failed");
+    body
+}
+
+/// [Handle fetch](https://w3c.github.io/ServiceWorker/#handle-fetch)
+///
+/// Asks the service worker manager whether a service worker wants to respond to `request`,
+/// blocking until it has decided. `None` means the request should go to the network.
+fn handle_fetch(request: &Request, context: &FetchContext) -> Option<Response> {
+    if !pref!(dom.serviceworker.enabled) {
+        return None;
+    }
+    let swmanager_chan = context.swmanager_chan.as_ref()?;
+    let (response_chan, response_port) = ipc::channel().ok()?;
+    let mediator = CustomResponseMediator {
+        response_chan: response_chan,
+        load_url: request.current_url(),
+        request: request_builder_for_fetch_event(request),
+    };
+    if swmanager_chan.send(mediator).is_err() {
+        return None;
+    }
+    match response_port.recv() {
+        Ok(ServiceWorkerResponse::Custom(custom_response)) => {
+            let mut response = Response::new(
+                request.current_url(),
+                ResourceFetchTiming::new(request.timing_type()),
+            );
+            let (status, status_text) = custom_response.raw_status;
+            response.raw_status = Some((status.as_u16(), status_text.clone().into_bytes()));
+            response.status = Some((status, status_text));
+            response.headers = custom_response.headers;
+            *response.body.lock().unwrap() = ResponseBody::Done(custom_response.body);
+            Some(response)
+        },
+        Ok(ServiceWorkerResponse::NetworkError) => Some(Response::network_error(
+            NetworkError::Internal("Service worker failed to respond".into()),
+        )),
+        Ok(ServiceWorkerResponse::Fallback) | Err(_) => None,
+    }
+}
+
+/// The request as seen by a service worker, in a form that can be sent across threads.
+fn request_builder_for_fetch_event(request: &Request) -> RequestBuilder {
+    let origin = match request.origin {
+        SpecificOrigin(ref origin) => origin.clone(),
+        Origin::Client => ImmutableOrigin::new_opaque(),
+    };
+    RequestBuilder::new(request.current_url())
+        .method(request.method.clone())
+        .headers(request.headers.clone())
+        .body(request.body.clone())
+        .destination(request.destination)
+        .mode(request.mode.clone())
+        .credentials_mode(request.credentials_mode)
+        .origin(origin)
+        .referrer(Some(request.referrer.clone()))
+        .referrer_policy(request.referrer_policy)
+        .pipeline_id(request.pipeline_id)
+        .redirect_mode(request.redirect_mode)
+        .integrity_metadata(request.integrity_metadata.clone())
+        .service_workers_mode(ServiceWorkersMode::None)
+}
+
 /// [HTTP fetch](https://fetch.spec.whatwg.org#http-fetch)
 pub fn http_fetch(
     request: &mut Request,
@@ -519,8 +702,8 @@ pub fn http_fetch(
 
     // Step 3
     if request.service_workers_mode == ServiceWorkersMode::All {
-        // TODO: Substep 1
-        // Set response to the result of invoking handle fetch for request.
+        // Substep 1
+        response = handle_fetch(request, context);
 
         // Substep 2
         if let Some(ref res) = response {
@@ -783,13 +966,14 @@ pub fn http_redirect_fetch(
         return Response::network_error(NetworkError::Internal("Credentials check failed".into()));
     }
 
-    // Step 9
+    // Step 9, where a streamed body has a null source, and cannot be sent
+    // again.
     if response
         .actual_response()
         .status
         .as_ref()
         .map_or(true, |s| s.0 != StatusCode::SEE_OTHER) &&
-        request.body.as_ref().map_or(false, |b| b.is_empty())
+        (request.body.as_ref().map_or(false, |b| b.is_empty()) || request.body_stream.is_some())
     {
         return Response::network_error(NetworkError::Internal("Request body is not done".into()));
     }
@@ -812,6 +996,7 @@ pub fn http_redirect_fetch(
     {
         request.method = Method::GET;
         request.body = None;
+        request.body_stream = None;
     }
 
     // Step 12
@@ -903,6 +1088,8 @@ fn http_network_or_cache_fetch(
     };
 
     let content_length_value = match http_request.body {
+        // Step 5.6, where a streamed body has a null length.
+        None if http_request.body_stream.is_some() => None,
         None => match http_request.method {
             // Step 5.5
             Method::POST | Method::PUT => Some(0),
@@ -918,11 +1105,29 @@ fn http_network_or_cache_fetch(
         http_request
             .headers
             .typed_insert(ContentLength(content_length_value));
-        if http_request.keep_alive {
-            // Step 5.8 TODO: needs request's client object
-        }
     }
 
+    // Step 5.8, with the pipeline standing in for the fetch group of the
+    // request's client. The request body has been sent by the time this
+    // function returns, which gives the reservation back.
+    let _keep_alive_reservation = match content_length_value {
+        Some(content_length_value) if http_request.keep_alive => {
+            match KeepAliveReservation::new(
+                &context.state,
+                http_request.pipeline_id,
+                content_length_value,
+            ) {
+                Some(reservation) => Some(reservation),
+                None => {
+                    return Response::network_error(NetworkError::Internal(
+                        "Keepalive request quota exceeded".into(),
+                    ));
+                },
+            }
+        },
+        _ => None,
+    };
+
     // Step 5.9
     match http_request.referrer {
         Referrer::NoReferrer => (),
@@ -1292,8 +1497,10 @@ fn http_network_or_cache_fetch(
         // TODO: Spec says requires testing on multiple WWW-Authenticate headers
 
         // Substep 2
-        if http_request.body.is_some() {
-            // TODO Implement body source
+        if http_request.body_stream.is_some() {
+            return Response::network_error(NetworkError::Internal(
+                "The streamed request body cannot be sent again".into(),
+            ));
         }
 
         // Substep 3
@@ -1421,6 +1628,7 @@ fn http_network_fetch(
         &request.method,
         &request.headers,
         &request.body,
+        &request.body_stream,
         &request.method,
         &request.pipeline_id,
         request.redirect_count + 1,
@@ -1443,11 +1651,13 @@ fn http_network_fetch(
         }
     }
 
+    // The header is a comma-separated list, which may be split across several headers.
     let header_strings: Vec<&str> = res
         .headers()
         .get_all("Timing-Allow-Origin")
         .iter()
-        .map(|header_value| header_value.to_str().unwrap_or(""))
+        .flat_map(|header_value| header_value.to_str().unwrap_or("").split(','))
+        .map(str::trim)
         .collect();
     let wildcard_present = header_strings.iter().any(|header_str| *header_str == "*");
     // The spec: https://www.w3.org/TR/resource-timing-2/#sec-timing-allow-origin
@@ -1471,6 +1681,16 @@ fn http_network_fetch(
         context.timing.lock().unwrap().mark_timing_check_failed();
     }
 
+    // The body is decoded as it's received, so the size of a content-encoded
+    // body is only known from its Content-Length.
+    let encoded_body_size = if res.headers().contains_key(header::CONTENT_ENCODING) {
+        res.headers()
+            .typed_get::<ContentLength>()
+            .map(|length| length.0)
+    } else {
+        None
+    };
+
     let timing = context.timing.lock().unwrap().clone();
     let mut response = Response::new(url.clone(), timing);
 
@@ -1558,11 +1778,16 @@ fn http_network_fetch(
                     ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
                     _ => vec![],
                 };
+                let decoded_body_size = completed_body.len() as u64;
                 *body = ResponseBody::Done(completed_body);
-                timing_ptr2
-                    .lock()
-                    .unwrap()
-                    .set_attribute(ResourceAttribute::ResponseEnd);
+                {
+                    let mut timing = timing_ptr2.lock().unwrap();
+                    timing.set_response_sizes(
+                        encoded_body_size.unwrap_or(decoded_body_size),
+                        decoded_body_size,
+                    );
+                    timing.set_attribute(ResourceAttribute::ResponseEnd);
+                }
                 let _ = done_sender2.send(Data::Done);
                 future::ok(())
             })
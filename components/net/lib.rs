@@ -19,6 +19,7 @@ extern crate serde;
 #[macro_use]
 extern crate servo_config;
 
+mod cache_storage_thread;
 pub mod connector;
 pub mod cookie;
 pub mod cookie_storage;
@@ -31,6 +32,8 @@ pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
 pub mod mime_classifier;
+mod permission_store_thread;
+mod quota_manager;
 pub mod resource_thread;
 mod storage_thread;
 pub mod subresource_integrity;
@@ -43,6 +46,7 @@ pub mod fetch {
 
 /// A module for re-exports of items used in unit tests.
 pub mod test {
+    pub use crate::cache_storage_thread::{CacheStorageThreadFactory, QUOTA_SIZE_LIMIT};
     pub use crate::hosts::{parse_hostsfile, replace_host_table};
     pub use crate::http_loader::HttpState;
 }
@@ -0,0 +1,146 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::resource_thread;
+use embedder_traits::{PermissionName, PermissionRequest};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use net_traits::permission_store_thread::PermissionStoreThreadMsg;
+use servo_url::ImmutableOrigin;
+use std::borrow::ToOwned;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::thread;
+
+pub trait PermissionStoreThreadFactory {
+    fn new(config_dir: Option<PathBuf>) -> Self;
+}
+
+impl PermissionStoreThreadFactory for IpcSender<PermissionStoreThreadMsg> {
+    /// Create a permission store thread
+    fn new(config_dir: Option<PathBuf>) -> IpcSender<PermissionStoreThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+        thread::Builder::new()
+            .name("PermissionStoreManager".to_owned())
+            .spawn(move || {
+                PermissionStoreManager::new(port, config_dir).start();
+            })
+            .expect("Thread spawning failed");
+        chan
+    }
+}
+
+struct PermissionStoreManager {
+    port: IpcReceiver<PermissionStoreThreadMsg>,
+    /// The answers of the user for each tuple origin.
+    permissions: HashMap<String, Vec<(PermissionName, PermissionRequest)>>,
+    /// The directory the answers are persisted to,
+    /// `None` for the private browsing store which only lives in memory.
+    config_dir: Option<PathBuf>,
+}
+
+impl PermissionStoreManager {
+    fn new(
+        port: IpcReceiver<PermissionStoreThreadMsg>,
+        config_dir: Option<PathBuf>,
+    ) -> PermissionStoreManager {
+        let mut permissions = HashMap::new();
+        if let Some(ref config_dir) = config_dir {
+            resource_thread::read_json_from_file(&mut permissions, config_dir, "permissions.json");
+        }
+        PermissionStoreManager {
+            port: port,
+            permissions: permissions,
+            config_dir: config_dir,
+        }
+    }
+}
+
+impl PermissionStoreManager {
+    fn start(&mut self) {
+        // The thread exits once every resource thread sender has been dropped,
+        // permissions are saved eagerly so there is nothing to clean up.
+        while let Ok(msg) = self.port.recv() {
+            match msg {
+                PermissionStoreThreadMsg::Get(sender, origin, name) => {
+                    self.get(sender, origin, name)
+                },
+                PermissionStoreThreadMsg::Set(origin, name, request) => {
+                    if self.set(origin, name, request) {
+                        self.save_state()
+                    }
+                },
+                PermissionStoreThreadMsg::Remove(origin, name) => {
+                    if self.remove(origin, name) {
+                        self.save_state()
+                    }
+                },
+            }
+        }
+    }
+
+    fn save_state(&self) {
+        if let Some(ref config_dir) = self.config_dir {
+            resource_thread::write_json_to_file(&self.permissions, config_dir, "permissions.json");
+        }
+    }
+
+    fn get(
+        &self,
+        sender: IpcSender<Option<PermissionRequest>>,
+        origin: ImmutableOrigin,
+        name: PermissionName,
+    ) {
+        let request = self
+            .origin_as_string(&origin)
+            .and_then(|origin| self.permissions.get(&origin))
+            .and_then(|permissions| {
+                permissions
+                    .iter()
+                    .find(|&&(stored_name, _)| stored_name == name)
+                    .map(|&(_, request)| request)
+            });
+        sender.send(request).unwrap();
+    }
+
+    /// Returns whether the store changed.
+    fn set(
+        &mut self,
+        origin: ImmutableOrigin,
+        name: PermissionName,
+        request: PermissionRequest,
+    ) -> bool {
+        let origin = match self.origin_as_string(&origin) {
+            Some(origin) => origin,
+            None => return false,
+        };
+        let permissions = self.permissions.entry(origin).or_insert_with(Vec::new);
+        permissions.retain(|&(stored_name, _)| stored_name != name);
+        permissions.push((name, request));
+        true
+    }
+
+    /// Returns whether the store changed.
+    fn remove(&mut self, origin: ImmutableOrigin, name: PermissionName) -> bool {
+        let origin = match self.origin_as_string(&origin) {
+            Some(origin) => origin,
+            None => return false,
+        };
+        match self.permissions.get_mut(&origin) {
+            Some(permissions) => {
+                permissions.retain(|&(stored_name, _)| stored_name != name);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Opaque origins all serialize to "null", they never share the answers of the user.
+    fn origin_as_string(&self, origin: &ImmutableOrigin) -> Option<String> {
+        if origin.is_tuple() {
+            Some(origin.ascii_serialization())
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::cache_storage_thread;
+use crate::resource_thread;
+use crate::storage_thread;
+use ipc_channel::ipc::{self, IpcSender};
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
+use net_traits::storage_thread::StorageEstimate;
+use servo_url::ServoUrl;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Accounts for the storage used by each origin across the storage endpoints,
+/// and keeps track of the origins whose storage is persistent,
+/// <https://storage.spec.whatwg.org/#usage-and-quota>
+pub struct QuotaManager {
+    cache_storage: IpcSender<CacheStorageThreadMsg>,
+    persistent_origins: HashSet<String>,
+    config_dir: Option<PathBuf>,
+}
+
+impl QuotaManager {
+    pub fn new(
+        cache_storage: IpcSender<CacheStorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+    ) -> QuotaManager {
+        let mut persistent_origins = HashSet::new();
+        if let Some(ref config_dir) = config_dir {
+            resource_thread::read_json_from_file(
+                &mut persistent_origins,
+                config_dir,
+                "persistent_origins.json",
+            );
+        }
+        QuotaManager {
+            cache_storage: cache_storage,
+            persistent_origins: persistent_origins,
+            config_dir: config_dir,
+        }
+    }
+
+    fn save_state(&self) {
+        if let Some(ref config_dir) = self.config_dir {
+            resource_thread::write_json_to_file(
+                &self.persistent_origins,
+                config_dir,
+                "persistent_origins.json",
+            );
+        }
+    }
+
+    /// <https://storage.spec.whatwg.org/#storage-usage>
+    ///
+    /// The usage of `localStorage` is owned by the storage thread, which
+    /// passes it in; the usage of the caches is asked to their thread.
+    pub fn estimate(&self, url: ServoUrl, local_storage_usage: usize) -> StorageEstimate {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cache_usage = match self
+            .cache_storage
+            .send(CacheStorageThreadMsg::Usage(sender, url.origin()))
+        {
+            Ok(()) => receiver.recv().unwrap_or(0),
+            Err(_) => 0,
+        };
+        StorageEstimate {
+            usage: (local_storage_usage + cache_usage) as u64,
+            quota: (storage_thread::QUOTA_SIZE_LIMIT + cache_storage_thread::QUOTA_SIZE_LIMIT)
+                as u64,
+        }
+    }
+
+    /// Whether the storage of the origin is persistent,
+    /// <https://storage.spec.whatwg.org/#bucket-mode>
+    pub fn persisted(&self, origin: &str) -> bool {
+        self.persistent_origins.contains(origin)
+    }
+
+    /// Makes the storage of the origin persistent. Nothing is ever evicted
+    /// under storage pressure, so this always succeeds.
+    pub fn persist(&mut self, origin: String) -> bool {
+        if self.persistent_origins.insert(origin) {
+            self.save_state();
+        }
+        true
+    }
+}
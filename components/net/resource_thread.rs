@@ -4,6 +4,7 @@
 
 //! A thread that takes a URL and streams back the binary data.
 
+use crate::cache_storage_thread::CacheStorageThreadFactory;
 use crate::connector::{create_http_client, create_ssl_connector_builder};
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
@@ -13,6 +14,7 @@ use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::permission_store_thread::PermissionStoreThreadFactory;
 use crate::storage_thread::StorageThreadFactory;
 use crate::websocket_loader;
 use crossbeam_channel::Sender;
@@ -22,6 +24,8 @@ use embedder_traits::EmbedderProxy;
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
+use net_traits::permission_store_thread::PermissionStoreThreadMsg;
 use net_traits::request::{Destination, RequestBuilder};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
@@ -67,10 +71,28 @@ pub fn new_resource_threads(
         config_dir.clone(),
         certificate_path,
     );
-    let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
+    let cache_storage: IpcSender<CacheStorageThreadMsg> =
+        CacheStorageThreadFactory::new(config_dir.clone());
+    let storage: IpcSender<StorageThreadMsg> =
+        StorageThreadFactory::new(config_dir.clone(), cache_storage.clone());
+    let permission_store: IpcSender<PermissionStoreThreadMsg> =
+        PermissionStoreThreadFactory::new(config_dir);
+    // The answers given while browsing privately are never written to disk.
+    let private_permission_store: IpcSender<PermissionStoreThreadMsg> =
+        PermissionStoreThreadFactory::new(None);
     (
-        ResourceThreads::new(public_core, storage.clone()),
-        ResourceThreads::new(private_core, storage),
+        ResourceThreads::new(
+            public_core,
+            storage.clone(),
+            cache_storage.clone(),
+            permission_store,
+        ),
+        ResourceThreads::new(
+            private_core,
+            storage,
+            cache_storage,
+            private_permission_store,
+        ),
     )
 }
 
@@ -148,6 +170,7 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
+        keep_alive_bytes: Mutex::new(HashMap::new()),
         client: create_http_client(
             create_ssl_connector_builder(&certs),
             HANDLE.lock().unwrap().executor(),
@@ -161,6 +184,7 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(HttpCache::new()),
         http_cache_state: Mutex::new(HashMap::new()),
+        keep_alive_bytes: Mutex::new(HashMap::new()),
         client: create_http_client(
             create_ssl_connector_builder(&certs),
             HANDLE.lock().unwrap().executor(),
@@ -274,6 +298,30 @@ impl ResourceChannelManager {
                     .clear_storage(&request);
                 return true;
             },
+            CoreResourceMsg::DeleteCookie(request, cookie) => {
+                let cookie = cookie::Cookie::new_wrapped(
+                    cookie.into_inner(),
+                    &request,
+                    CookieSource::NonHTTP,
+                );
+                if let Some(cookie) = cookie {
+                    http_state
+                        .cookie_jar
+                        .write()
+                        .unwrap()
+                        .delete(&cookie, &request);
+                }
+            },
+            CoreResourceMsg::AddCookieChangeListener(pipeline_id, url, sender) => http_state
+                .cookie_jar
+                .write()
+                .unwrap()
+                .add_change_listener(pipeline_id, url, sender),
+            CoreResourceMsg::RemoveCookieChangeListener(pipeline_id) => http_state
+                .cookie_jar
+                .write()
+                .unwrap()
+                .remove_change_listener(pipeline_id),
             CoreResourceMsg::FetchRedirect(req_init, res_init, sender, cancel_chan) => self
                 .resource_manager
                 .fetch(req_init, Some(res_init), sender, http_state, cancel_chan),
@@ -487,6 +535,15 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_chan.clone();
         let filemanager = self.filemanager.clone();
+        let swmanager_chan = self.swmanager_chan.clone();
+
+        // A keepalive request outlives the document that started it.
+        // https://fetch.spec.whatwg.org/#request-keepalive-flag
+        let cancel_chan = if request_builder.keep_alive {
+            None
+        } else {
+            cancel_chan
+        };
 
         let timing_type = match request_builder.destination {
             Destination::Document => ResourceTimingType::Navigation,
@@ -498,7 +555,6 @@ impl CoreResourceManager {
             // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
             // todo load context / mimesniff in fetch
             // todo referrer policy?
-            // todo service worker stuff
             let context = FetchContext {
                 state: http_state,
                 user_agent: ua,
@@ -506,6 +562,7 @@ impl CoreResourceManager {
                 filemanager: filemanager,
                 cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(cancel_chan))),
                 timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(request.timing_type()))),
+                swmanager_chan: swmanager_chan,
             };
 
             match res_init_ {
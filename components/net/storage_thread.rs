@@ -2,9 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::quota_manager::QuotaManager;
 use crate::resource_thread;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
-use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
+use net_traits::storage_thread::{StorageEstimate, StorageThreadMsg, StorageType};
 use servo_url::ServoUrl;
 use std::borrow::ToOwned;
 use std::collections::BTreeMap;
@@ -12,20 +14,23 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::thread;
 
-const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
+pub const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
 
 pub trait StorageThreadFactory {
-    fn new(config_dir: Option<PathBuf>) -> Self;
+    fn new(config_dir: Option<PathBuf>, cache_storage: IpcSender<CacheStorageThreadMsg>) -> Self;
 }
 
 impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
     /// Create a storage thread
-    fn new(config_dir: Option<PathBuf>) -> IpcSender<StorageThreadMsg> {
+    fn new(
+        config_dir: Option<PathBuf>,
+        cache_storage: IpcSender<CacheStorageThreadMsg>,
+    ) -> IpcSender<StorageThreadMsg> {
         let (chan, port) = ipc::channel().unwrap();
         thread::Builder::new()
             .name("StorageManager".to_owned())
             .spawn(move || {
-                StorageManager::new(port, config_dir).start();
+                StorageManager::new(port, config_dir, cache_storage).start();
             })
             .expect("Thread spawning failed");
         chan
@@ -36,11 +41,16 @@ struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
     session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
+    quota_manager: QuotaManager,
     config_dir: Option<PathBuf>,
 }
 
 impl StorageManager {
-    fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
+    fn new(
+        port: IpcReceiver<StorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+        cache_storage: IpcSender<CacheStorageThreadMsg>,
+    ) -> StorageManager {
         let mut local_data = HashMap::new();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
@@ -49,6 +59,7 @@ impl StorageManager {
             port: port,
             session_data: HashMap::new(),
             local_data: local_data,
+            quota_manager: QuotaManager::new(cache_storage, config_dir.clone()),
             config_dir: config_dir,
         }
     }
@@ -82,6 +93,15 @@ impl StorageManager {
                     self.clear(sender, url, storage_type);
                     self.save_state()
                 },
+                StorageThreadMsg::Estimate(sender, url) => self.estimate(sender, url),
+                StorageThreadMsg::Persisted(sender, url) => {
+                    let origin = self.origin_as_string(url);
+                    sender.send(self.quota_manager.persisted(&origin)).unwrap()
+                },
+                StorageThreadMsg::Persist(sender, url) => {
+                    let origin = self.origin_as_string(url);
+                    sender.send(self.quota_manager.persist(origin)).unwrap()
+                },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
                     let _ = sender.send(());
@@ -270,6 +290,17 @@ impl StorageManager {
             .unwrap();
     }
 
+    fn estimate(&self, sender: IpcSender<StorageEstimate>, url: ServoUrl) {
+        let origin = self.origin_as_string(url.clone());
+        let local_storage_usage = self
+            .select_data(StorageType::Local)
+            .get(&origin)
+            .map_or(0, |&(total, _)| total);
+        sender
+            .send(self.quota_manager.estimate(url, local_storage_usage))
+            .unwrap();
+    }
+
     fn origin_as_string(&self, url: ServoUrl) -> String {
         url.origin().ascii_serialization()
     }
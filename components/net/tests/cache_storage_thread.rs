@@ -0,0 +1,405 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, VARY};
+use hyper_serde::Serde;
+use ipc_channel::ipc::{self, IpcSender};
+use net::test::{CacheStorageThreadFactory, QUOTA_SIZE_LIMIT};
+use net_traits::cache_storage_thread::{CacheQueryOptions, CacheStorageError};
+use net_traits::cache_storage_thread::{CacheStorageThreadMsg, CachedRequest, CachedResponse};
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+fn origin(url: &str) -> ImmutableOrigin {
+    ServoUrl::parse(url).unwrap().origin()
+}
+
+fn request(url: &str, headers: HeaderMap) -> CachedRequest {
+    CachedRequest {
+        url: ServoUrl::parse(url).unwrap(),
+        method: "GET".to_owned(),
+        headers: Serde(headers),
+    }
+}
+
+fn response(headers: HeaderMap, body: Vec<u8>) -> CachedResponse {
+    CachedResponse {
+        url: None,
+        status: Some((200, b"OK".to_vec())),
+        headers: Serde(headers),
+        body: body,
+    }
+}
+
+fn accept_language(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static(value));
+    headers
+}
+
+fn vary(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(VARY, HeaderValue::from_static(value));
+    headers
+}
+
+fn open(thread: &IpcSender<CacheStorageThreadMsg>, origin: &ImmutableOrigin, name: &str) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(CacheStorageThreadMsg::Open(
+            sender,
+            origin.clone(),
+            name.to_owned(),
+        ))
+        .unwrap();
+    receiver.recv().unwrap();
+}
+
+fn put(
+    thread: &IpcSender<CacheStorageThreadMsg>,
+    origin: &ImmutableOrigin,
+    name: &str,
+    entries: Vec<(CachedRequest, CachedResponse)>,
+) -> Result<(), CacheStorageError> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(CacheStorageThreadMsg::Put(
+            sender,
+            origin.clone(),
+            name.to_owned(),
+            entries,
+        ))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn match_all(
+    thread: &IpcSender<CacheStorageThreadMsg>,
+    origin: &ImmutableOrigin,
+    name: Option<&str>,
+    request: Option<CachedRequest>,
+    options: CacheQueryOptions,
+) -> Vec<(CachedRequest, CachedResponse)> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(CacheStorageThreadMsg::MatchAll(
+            sender,
+            origin.clone(),
+            name.map(str::to_owned),
+            request,
+            options,
+        ))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn usage(thread: &IpcSender<CacheStorageThreadMsg>, origin: &ImmutableOrigin) -> usize {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(CacheStorageThreadMsg::Usage(sender, origin.clone()))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn keys(thread: &IpcSender<CacheStorageThreadMsg>, origin: &ImmutableOrigin) -> Vec<String> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(CacheStorageThreadMsg::Keys(sender, origin.clone()))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+#[test]
+fn test_match_uses_the_headers_listed_in_vary() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    let url = "https://example.com/page";
+    open(&thread, &origin, "v1");
+    put(
+        &thread,
+        &origin,
+        "v1",
+        vec![(
+            request(url, accept_language("en")),
+            response(vary("Accept-Language"), b"english".to_vec()),
+        )],
+    )
+    .unwrap();
+
+    let matches = match_all(
+        &thread,
+        &origin,
+        Some("v1"),
+        Some(request(url, accept_language("en"))),
+        CacheQueryOptions::default(),
+    );
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].1.body, b"english");
+
+    let matches = match_all(
+        &thread,
+        &origin,
+        Some("v1"),
+        Some(request(url, accept_language("fr"))),
+        CacheQueryOptions::default(),
+    );
+    assert!(matches.is_empty());
+
+    let matches = match_all(
+        &thread,
+        &origin,
+        Some("v1"),
+        Some(request(url, HeaderMap::new())),
+        CacheQueryOptions::default(),
+    );
+    assert!(matches.is_empty());
+}
+
+#[test]
+fn test_match_ignores_vary_when_asked_to() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    let url = "https://example.com/page";
+    open(&thread, &origin, "v1");
+    put(
+        &thread,
+        &origin,
+        "v1",
+        vec![(
+            request(url, accept_language("en")),
+            response(vary("Accept-Language"), vec![]),
+        )],
+    )
+    .unwrap();
+
+    let options = CacheQueryOptions {
+        ignore_vary: true,
+        ..CacheQueryOptions::default()
+    };
+    let matches = match_all(
+        &thread,
+        &origin,
+        Some("v1"),
+        Some(request(url, accept_language("fr"))),
+        options,
+    );
+    assert_eq!(matches.len(), 1);
+}
+
+#[test]
+fn test_vary_star_never_matches() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    let url = "https://example.com/page";
+    open(&thread, &origin, "v1");
+    put(
+        &thread,
+        &origin,
+        "v1",
+        vec![(
+            request(url, HeaderMap::new()),
+            response(vary("Accept-Encoding, *"), vec![]),
+        )],
+    )
+    .unwrap();
+
+    let matches = match_all(
+        &thread,
+        &origin,
+        Some("v1"),
+        Some(request(url, HeaderMap::new())),
+        CacheQueryOptions::default(),
+    );
+    assert!(matches.is_empty());
+}
+
+#[test]
+fn test_put_replaces_the_entries_matching_by_vary() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    let url = "https://example.com/page";
+    open(&thread, &origin, "v1");
+    let entry = |language, body: &[u8]| {
+        (
+            request(url, accept_language(language)),
+            response(vary("Accept-Language"), body.to_vec()),
+        )
+    };
+    put(&thread, &origin, "v1", vec![entry("en", b"english")]).unwrap();
+    put(&thread, &origin, "v1", vec![entry("fr", b"french")]).unwrap();
+    put(&thread, &origin, "v1", vec![entry("en", b"english, again")]).unwrap();
+
+    let bodies: Vec<_> = match_all(
+        &thread,
+        &origin,
+        Some("v1"),
+        None,
+        CacheQueryOptions::default(),
+    )
+    .into_iter()
+    .map(|(_, response)| response.body)
+    .collect();
+    assert_eq!(bodies, vec![b"french".to_vec(), b"english, again".to_vec()]);
+}
+
+#[test]
+fn test_put_within_quota() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    open(&thread, &origin, "v1");
+    let body = vec![0; QUOTA_SIZE_LIMIT / 2];
+    put(
+        &thread,
+        &origin,
+        "v1",
+        vec![
+            (
+                request("https://example.com/a", HeaderMap::new()),
+                response(HeaderMap::new(), body.clone()),
+            ),
+            (
+                request("https://example.com/b", HeaderMap::new()),
+                response(HeaderMap::new(), body),
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(usage(&thread, &origin), QUOTA_SIZE_LIMIT);
+}
+
+#[test]
+fn test_put_exceeding_quota_stores_nothing() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    open(&thread, &origin, "v1");
+    let body = vec![0; QUOTA_SIZE_LIMIT / 2];
+    put(
+        &thread,
+        &origin,
+        "v1",
+        vec![(
+            request("https://example.com/a", HeaderMap::new()),
+            response(HeaderMap::new(), body.clone()),
+        )],
+    )
+    .unwrap();
+
+    let result = put(
+        &thread,
+        &origin,
+        "v1",
+        vec![
+            (
+                request("https://example.com/b", HeaderMap::new()),
+                response(HeaderMap::new(), vec![0; 1]),
+            ),
+            (
+                request("https://example.com/c", HeaderMap::new()),
+                response(HeaderMap::new(), body),
+            ),
+        ],
+    );
+    match result {
+        Err(CacheStorageError::QuotaExceeded) => {},
+        result => panic!("Expected the quota to be exceeded, got {:?}", result),
+    }
+    assert_eq!(usage(&thread, &origin), QUOTA_SIZE_LIMIT / 2);
+    let matches = match_all(&thread, &origin, None, None, CacheQueryOptions::default());
+    assert_eq!(matches.len(), 1);
+}
+
+#[test]
+fn test_quota_counts_replaced_entries_once() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = origin("https://example.com");
+    open(&thread, &origin, "v1");
+    let entry = || {
+        (
+            request("https://example.com/a", HeaderMap::new()),
+            response(HeaderMap::new(), vec![0; QUOTA_SIZE_LIMIT * 3 / 4]),
+        )
+    };
+    put(&thread, &origin, "v1", vec![entry()]).unwrap();
+    put(&thread, &origin, "v1", vec![entry()]).unwrap();
+    assert_eq!(usage(&thread, &origin), QUOTA_SIZE_LIMIT * 3 / 4);
+}
+
+#[test]
+fn test_quota_is_per_origin() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let first = origin("https://example.com");
+    let second = origin("https://example.org");
+    let body = vec![0; QUOTA_SIZE_LIMIT * 3 / 4];
+    for origin in &[&first, &second] {
+        open(&thread, origin, "v1");
+        put(
+            &thread,
+            origin,
+            "v1",
+            vec![(
+                request("https://example.com/a", HeaderMap::new()),
+                response(HeaderMap::new(), body.clone()),
+            )],
+        )
+        .unwrap();
+    }
+    assert_eq!(usage(&thread, &first), body.len());
+    assert_eq!(usage(&thread, &second), body.len());
+}
+
+#[test]
+fn test_caches_are_keyed_by_origin() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let first = origin("https://example.com");
+    let other_port = origin("https://example.com:8443");
+    let other_scheme = origin("http://example.com");
+    open(&thread, &first, "v1");
+    put(
+        &thread,
+        &first,
+        "v1",
+        vec![(
+            request("https://example.com/a", HeaderMap::new()),
+            response(HeaderMap::new(), vec![1]),
+        )],
+    )
+    .unwrap();
+
+    assert_eq!(keys(&thread, &first), vec!["v1".to_owned()]);
+    for origin in &[&other_port, &other_scheme] {
+        assert!(keys(&thread, origin).is_empty());
+        let matches = match_all(
+            &thread,
+            origin,
+            Some("v1"),
+            Some(request("https://example.com/a", HeaderMap::new())),
+            CacheQueryOptions::default(),
+        );
+        assert!(matches.is_empty());
+    }
+
+    // The same origin, from another URL, shares the caches.
+    let same = origin("https://example.com/other/page");
+    assert_eq!(keys(&thread, &same), vec!["v1".to_owned()]);
+}
+
+#[test]
+fn test_put_into_a_cache_of_another_origin_is_not_found() {
+    let thread: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let first = origin("https://example.com");
+    let second = origin("https://example.org");
+    open(&thread, &first, "v1");
+    let result = put(
+        &thread,
+        &second,
+        "v1",
+        vec![(
+            request("https://example.org/a", HeaderMap::new()),
+            response(HeaderMap::new(), vec![]),
+        )],
+    );
+    match result {
+        Err(CacheStorageError::NotFound) => {},
+        result => panic!("Expected the cache not to be found, got {:?}", result),
+    }
+}
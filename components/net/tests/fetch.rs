@@ -30,6 +30,7 @@ use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
 use net::hsts::HstsEntry;
 use net::test::HttpState;
+use net_traits::request::KEEP_ALIVE_QUOTA;
 use net_traits::request::{Destination, Origin, RedirectMode, Referrer, Request, RequestMode};
 use net_traits::response::{CacheState, Response, ResponseBody, ResponseType};
 use net_traits::{
@@ -108,6 +109,35 @@ fn test_fetch_response_body_matches_const_message() {
     };
 }
 
+#[test]
+fn test_fetch_keep_alive_quota() {
+    let handler = move |_: HyperRequest<Body>, _: &mut HyperResponse<Body>| {};
+    let (server, url) = make_server(handler);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url.clone(), Some(origin.clone()), None);
+    request.referrer = Referrer::NoReferrer;
+    request.method = Method::POST;
+    request.keep_alive = true;
+    request.body = Some(vec![0; KEEP_ALIVE_QUOTA as usize]);
+    let fetch_response = fetch(&mut request, None);
+    assert!(!fetch_response.is_network_error());
+
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.method = Method::POST;
+    request.keep_alive = true;
+    request.body = Some(vec![0; KEEP_ALIVE_QUOTA as usize + 1]);
+    let fetch_response = fetch(&mut request, None);
+    let _ = server.close();
+    assert_eq!(
+        fetch_response.get_network_error(),
+        Some(&NetworkError::Internal(
+            "Keepalive request quota exceeded".into()
+        ))
+    );
+}
+
 #[test]
 fn test_fetch_aboutblank() {
     let url = ServoUrl::parse("about:blank").unwrap();
@@ -669,6 +699,7 @@ fn test_fetch_with_hsts() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        swmanager_chan: None,
     };
 
     {
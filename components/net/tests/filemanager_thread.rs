@@ -6,14 +6,15 @@ use crate::create_embedder_proxy;
 use embedder_traits::FilterPattern;
 use ipc_channel::ipc;
 use net::filemanager_thread::FileManager;
-use net_traits::blob_url_store::BlobURLStoreError;
+use net_traits::blob_url_store::{BlobBuf, BlobURLStoreError};
 use net_traits::filemanager_thread::{
-    FileManagerThreadError, FileManagerThreadMsg, ReadFileProgress,
+    FileManagerThreadError, FileManagerThreadMsg, ReadFileProgress, RelativePos,
 };
 use servo_config::set_pref;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
+use uuid::Uuid;
 
 #[test]
 fn test_filemanager() {
@@ -130,3 +131,112 @@ fn test_filemanager() {
         }
     }
 }
+
+fn read_blob_url(filemanager: &FileManager, id: Uuid, origin: &str) -> Result<Vec<u8>, ()> {
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::ReadFile(
+        tx,
+        id,
+        true,
+        origin.to_string(),
+    ));
+
+    let mut bytes = vec![];
+    loop {
+        match rx.recv().expect("Broken channel") {
+            Ok(ReadFileProgress::Meta(mut blob_buf)) => bytes.append(&mut blob_buf.bytes),
+            Ok(ReadFileProgress::Partial(mut bytes_in)) => bytes.append(&mut bytes_in),
+            Ok(ReadFileProgress::EOF) => return Ok(bytes),
+            Err(_) => return Err(()),
+        }
+    }
+}
+
+#[test]
+fn test_blob_url_entries() {
+    let filemanager = FileManager::new(create_embedder_proxy());
+    let origin = "http://test.com".to_string();
+
+    // A memory-based blob, which is not itself a valid Blob URL
+    let blob_id = Uuid::new_v4();
+    let blob_buf = BlobBuf {
+        filename: None,
+        type_string: "text/plain".to_string(),
+        size: 4,
+        bytes: b"test".to_vec(),
+    };
+    filemanager.handle(FileManagerThreadMsg::PromoteMemory(
+        blob_id,
+        blob_buf,
+        false,
+        origin.clone(),
+    ));
+
+    // Every URL created for the blob gets its own entry
+    let mut url_ids = vec![];
+    for _ in 0..2 {
+        let (tx, rx) = ipc::channel().unwrap();
+        filemanager.handle(FileManagerThreadMsg::AddBlobURLEntry(
+            blob_id,
+            RelativePos::full_range(),
+            tx,
+            origin.clone(),
+        ));
+        let id = rx
+            .recv()
+            .expect("Broken channel")
+            .expect("Adding a Blob URL entry failed");
+        assert_eq!(
+            read_blob_url(&filemanager, id, &origin),
+            Ok(b"test".to_vec())
+        );
+        url_ids.push(id);
+    }
+    assert_ne!(url_ids[0], url_ids[1]);
+
+    // Revoking one URL leaves the other one valid
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::RevokeBlobURL(
+        url_ids[0],
+        origin.clone(),
+        tx,
+    ));
+    assert!(rx.recv().expect("Broken channel").is_ok());
+    assert_eq!(read_blob_url(&filemanager, url_ids[0], &origin), Err(()));
+    assert_eq!(
+        read_blob_url(&filemanager, url_ids[1], &origin),
+        Ok(b"test".to_vec())
+    );
+
+    // The content outlives the blob as long as a URL refers to it
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::DecRef(blob_id, origin.clone(), tx));
+    assert!(rx.recv().expect("Broken channel").is_ok());
+    assert_eq!(
+        read_blob_url(&filemanager, url_ids[1], &origin),
+        Ok(b"test".to_vec())
+    );
+
+    // Revoking the last URL removes the content
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::RevokeBlobURL(
+        url_ids[1],
+        origin.clone(),
+        tx,
+    ));
+    assert!(rx.recv().expect("Broken channel").is_ok());
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::ReadFile(
+        tx,
+        blob_id,
+        false,
+        origin.clone(),
+    ));
+    match rx.recv().expect("Broken channel") {
+        Err(FileManagerThreadError::BlobURLStoreError(BlobURLStoreError::InvalidFileID)) => {},
+        other => panic!(
+            "Get unexpected response after revoking the URLs: {:?}",
+            other
+        ),
+    }
+}
@@ -24,12 +24,14 @@ use http::uri::Authority;
 use http::{Method, StatusCode};
 use hyper::body::Body;
 use hyper::{Request as HyperRequest, Response as HyperResponse};
+use ipc_channel::ipc;
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
 use net::http_loader::determine_request_referrer;
 use net::resource_thread::AuthCacheEntry;
 use net::test::replace_host_table;
+use net_traits::request::{BodyChunkRequest, BodyChunkResponse};
 use net_traits::request::{CredentialsMode, Destination, RequestBuilder, RequestMode};
 use net_traits::response::ResponseBody;
 use net_traits::{CookieSource, NetworkError, ReferrerPolicy};
@@ -39,6 +41,7 @@ use std::io::Write;
 use std::str;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
 use std::time::Duration;
 
 fn mock_origin() -> ImmutableOrigin {
@@ -547,6 +550,54 @@ fn test_load_doesnt_send_request_body_on_any_redirect() {
     assert!(response.to_actual().status.unwrap().0.is_success());
 }
 
+#[test]
+fn test_load_sends_streamed_request_body_chunked() {
+    let handler = move |request: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        assert_eq!(request.method(), Method::POST);
+        assert!(request.headers().get(header::CONTENT_LENGTH).is_none());
+        assert_eq!(
+            request.headers().get(header::TRANSFER_ENCODING),
+            Some(&HeaderValue::from_static("chunked"))
+        );
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    // Stands in for the script thread, answering each request for a chunk.
+    let (body_sender, body_receiver) = ipc::channel().unwrap();
+    thread::spawn(move || {
+        let mut chunks = vec![b"Body ".to_vec(), b"on POST!".to_vec()].into_iter();
+        let mut chunk_sender = None;
+        while let Ok(message) = body_receiver.recv() {
+            match message {
+                BodyChunkRequest::Connect(sender) => chunk_sender = Some(sender),
+                BodyChunkRequest::Chunk => {
+                    let sender = chunk_sender.as_ref().unwrap();
+                    let _ = sender.send(match chunks.next() {
+                        Some(chunk) => BodyChunkResponse::Chunk(chunk),
+                        None => BodyChunkResponse::Done,
+                    });
+                },
+                BodyChunkRequest::Done => break,
+            }
+        }
+    });
+
+    let mut request = RequestBuilder::new(url.clone())
+        .body_stream(Some(body_sender))
+        .method(Method::POST)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let response = fetch(&mut request, None);
+
+    let _ = server.close();
+
+    assert!(response.to_actual().status.unwrap().0.is_success());
+}
+
 #[test]
 fn test_load_doesnt_add_host_to_sts_list_when_url_is_http_even_if_sts_headers_are_present() {
     let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
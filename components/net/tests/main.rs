@@ -7,6 +7,7 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod cache_storage_thread;
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
@@ -99,6 +100,7 @@ fn new_fetch_context(
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        swmanager_chan: None,
     }
 }
 impl FetchTaskTarget for FetchResponseCollector {
@@ -0,0 +1,90 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::HeaderMap;
+use hyper_serde::Serde;
+use ipc_channel::ipc::IpcSender;
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+/// The request of an entry of a cache, with the parts that are used to match it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedRequest {
+    pub url: ServoUrl,
+    pub method: String,
+    pub headers: Serde<HeaderMap>,
+}
+
+/// The response of an entry of a cache.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedResponse {
+    pub url: Option<ServoUrl>,
+    pub status: Option<(u16, Vec<u8>)>,
+    pub headers: Serde<HeaderMap>,
+    pub body: Vec<u8>,
+}
+
+/// <https://w3c.github.io/ServiceWorker/#dictdef-cachequeryoptions>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct CacheQueryOptions {
+    pub ignore_search: bool,
+    pub ignore_method: bool,
+    pub ignore_vary: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CacheStorageError {
+    /// The cache does not exist, it may have been deleted.
+    NotFound,
+    /// Storing the entries would exceed the quota of the origin.
+    QuotaExceeded,
+}
+
+/// Request operations on the caches of a particular tuple origin,
+/// <https://w3c.github.io/ServiceWorker/#cache-objects>
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CacheStorageThreadMsg {
+    /// whether a cache with the given name exists
+    Has(IpcSender<bool>, ImmutableOrigin, String),
+
+    /// opens the cache with the given name, creating it if it does not exist
+    Open(IpcSender<()>, ImmutableOrigin, String),
+
+    /// deletes the cache with the given name, replying whether it existed
+    Delete(IpcSender<bool>, ImmutableOrigin, String),
+
+    /// gets the names of the caches, in the order they were created
+    Keys(IpcSender<Vec<String>>, ImmutableOrigin),
+
+    /// gets the number of bytes used by the caches
+    Usage(IpcSender<usize>, ImmutableOrigin),
+
+    /// gets the entries matching a request, from the cache with the given name
+    /// or from every cache in order, all of the entries if there is no request
+    MatchAll(
+        IpcSender<Vec<(CachedRequest, CachedResponse)>>,
+        ImmutableOrigin,
+        Option<String>,
+        Option<CachedRequest>,
+        CacheQueryOptions,
+    ),
+
+    /// stores entries in the cache with the given name, replacing the entries
+    /// that match their requests, either all of them are stored or none is
+    Put(
+        IpcSender<Result<(), CacheStorageError>>,
+        ImmutableOrigin,
+        String,
+        Vec<(CachedRequest, CachedResponse)>,
+    ),
+
+    /// removes the entries matching a request from the cache with the given name,
+    /// replying whether any were removed
+    Remove(
+        IpcSender<bool>,
+        ImmutableOrigin,
+        String,
+        CachedRequest,
+        CacheQueryOptions,
+    ),
+}
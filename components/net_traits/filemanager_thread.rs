@@ -132,9 +132,9 @@ pub enum FileManagerThreadMsg {
     /// Add an entry as promoted memory-based blob
     PromoteMemory(Uuid, BlobBuf, bool, FileOrigin),
 
-    /// Add a sliced entry pointing to the parent FileID, and send back the associated FileID
-    /// as part of a valid Blob URL
-    AddSlicedURLEntry(
+    /// Add an entry for a new Blob URL pointing to (a part of) the content of the FileID,
+    /// and send back the FileID of the URL, which stays valid until it is revoked
+    AddBlobURLEntry(
         Uuid,
         RelativePos,
         IpcSender<Result<Uuid, BlobURLStoreError>>,
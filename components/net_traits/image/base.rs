@@ -4,6 +4,7 @@
 
 use crate::image_cache::CorsStatus;
 use ipc_channel::ipc::IpcSharedMemory;
+use mime::Mime;
 use piston_image::{DynamicImage, ImageFormat};
 use pixels::PixelFormat;
 use std::fmt;
@@ -91,6 +92,18 @@ pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
     }
 }
 
+/// Whether images of the given MIME type can be decoded, ignoring its
+/// parameters.
+pub fn is_supported_image_type(mime_type: &Mime) -> bool {
+    if mime_type.type_() != mime::IMAGE {
+        return false;
+    }
+    match mime_type.subtype().as_str() {
+        "gif" | "jpeg" | "png" | "bmp" | "x-icon" | "vnd.microsoft.icon" => true,
+        _ => false,
+    }
+}
+
 fn is_gif(buffer: &[u8]) -> bool {
     buffer.starts_with(b"GIF87a") || buffer.starts_with(b"GIF89a")
 }
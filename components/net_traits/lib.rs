@@ -15,7 +15,9 @@ extern crate malloc_size_of_derive;
 #[macro_use]
 extern crate serde;
 
+use crate::cache_storage_thread::CacheStorageThreadMsg;
 use crate::filemanager_thread::FileManagerThreadMsg;
+use crate::permission_store_thread::PermissionStoreThreadMsg;
 use crate::request::{Request, RequestBuilder};
 use crate::response::{HttpsState, Response, ResponseInit};
 use crate::storage_thread::StorageThreadMsg;
@@ -29,15 +31,17 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
 use mime::Mime;
-use msg::constellation_msg::HistoryStateId;
+use msg::constellation_msg::{HistoryStateId, PipelineId};
 use servo_url::ServoUrl;
 use std::error::Error;
 use time::precise_time_ns;
 use webrender_api::ImageKey;
 
 pub mod blob_url_store;
+pub mod cache_storage_thread;
 pub mod filemanager_thread;
 pub mod image_cache;
+pub mod permission_store_thread;
 pub mod pub_domains;
 pub mod quality;
 pub mod request;
@@ -99,10 +103,23 @@ impl CustomResponse {
     }
 }
 
+/// The outcome of [handle fetch](https://w3c.github.io/ServiceWorker/#handle-fetch).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ServiceWorkerResponse {
+    /// No service worker responded, the request should go to the network.
+    Fallback,
+    /// A service worker responded with a synthesized response.
+    Custom(CustomResponse),
+    /// A service worker failed to provide a response.
+    NetworkError,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CustomResponseMediator {
-    pub response_chan: IpcSender<Option<CustomResponse>>,
+    pub response_chan: IpcSender<ServiceWorkerResponse>,
     pub load_url: ServoUrl,
+    /// The request that is being intercepted, exposed as `FetchEvent.request`.
+    pub request: RequestBuilder,
 }
 
 /// [Policies](https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states)
@@ -317,13 +334,22 @@ where
 pub struct ResourceThreads {
     core_thread: CoreResourceThread,
     storage_thread: IpcSender<StorageThreadMsg>,
+    cache_storage_thread: IpcSender<CacheStorageThreadMsg>,
+    permission_store_thread: IpcSender<PermissionStoreThreadMsg>,
 }
 
 impl ResourceThreads {
-    pub fn new(c: CoreResourceThread, s: IpcSender<StorageThreadMsg>) -> ResourceThreads {
+    pub fn new(
+        c: CoreResourceThread,
+        s: IpcSender<StorageThreadMsg>,
+        cs: IpcSender<CacheStorageThreadMsg>,
+        ps: IpcSender<PermissionStoreThreadMsg>,
+    ) -> ResourceThreads {
         ResourceThreads {
             core_thread: c,
             storage_thread: s,
+            cache_storage_thread: cs,
+            permission_store_thread: ps,
         }
     }
 }
@@ -348,6 +374,26 @@ impl IpcSend<StorageThreadMsg> for ResourceThreads {
     }
 }
 
+impl IpcSend<CacheStorageThreadMsg> for ResourceThreads {
+    fn send(&self, msg: CacheStorageThreadMsg) -> IpcSendResult {
+        self.cache_storage_thread.send(msg)
+    }
+
+    fn sender(&self) -> IpcSender<CacheStorageThreadMsg> {
+        self.cache_storage_thread.clone()
+    }
+}
+
+impl IpcSend<PermissionStoreThreadMsg> for ResourceThreads {
+    fn send(&self, msg: PermissionStoreThreadMsg) -> IpcSendResult {
+        self.permission_store_thread.send(msg)
+    }
+
+    fn sender(&self) -> IpcSender<PermissionStoreThreadMsg> {
+        self.permission_store_thread.clone()
+    }
+}
+
 // Ignore the sub-fields
 malloc_size_of_is_0!(ResourceThreads);
 
@@ -416,6 +462,14 @@ pub enum CoreResourceMsg {
         CookieSource,
     ),
     DeleteCookies(ServoUrl),
+    /// Remove the cookie matching the name, domain and path of the given one,
+    /// as the given originating URL would be allowed to from a non-HTTP API
+    DeleteCookie(ServoUrl, Serde<Cookie<'static>>),
+    /// Report the changes of the cookies visible to a URL from a non-HTTP API
+    /// until the listener of the pipeline is removed
+    AddCookieChangeListener(PipelineId, ServoUrl, IpcSender<CookieChange>),
+    /// Stop reporting the cookie changes to the listener of a pipeline
+    RemoveCookieChangeListener(PipelineId),
     /// Get a history state by a given history state id
     GetHistoryState(HistoryStateId, IpcSender<Option<Vec<u8>>>),
     /// Set a history state for a given history state id
@@ -462,6 +516,7 @@ pub struct ResourceCorsData {
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct ResourceFetchTiming {
     pub domain_lookup_start: u64,
+    pub domain_lookup_end: u64,
     pub timing_check_passed: bool,
     pub timing_type: ResourceTimingType,
     /// Number of redirects until final resource (currently limited to 20)
@@ -476,6 +531,12 @@ pub struct ResourceFetchTiming {
     pub connect_start: u64,
     pub connect_end: u64,
     pub start_time: u64,
+    /// The size of the response, headers included, as received from the network.
+    pub transfer_size: u64,
+    /// The size of the body of the response before removing its content coding.
+    pub encoded_body_size: u64,
+    /// The size of the body of the response after removing its content coding.
+    pub decoded_body_size: u64,
 }
 
 pub enum RedirectStartValue {
@@ -501,13 +562,14 @@ pub enum ResourceTimeValue {
 pub enum ResourceAttribute {
     RedirectCount(u16),
     DomainLookupStart,
+    DomainLookupEnd,
     RequestStart,
     ResponseStart,
     RedirectStart(RedirectStartValue),
     RedirectEnd(RedirectEndValue),
     FetchStart,
-    ConnectStart(u64),
-    ConnectEnd(u64),
+    ConnectStart,
+    ConnectEnd,
     SecureConnectionStart,
     ResponseEnd,
     StartTime(ResourceTimeValue),
@@ -527,6 +589,7 @@ impl ResourceFetchTiming {
             timing_type: timing_type,
             timing_check_passed: true,
             domain_lookup_start: 0,
+            domain_lookup_end: 0,
             redirect_count: 0,
             secure_connection_start: 0,
             request_start: 0,
@@ -538,11 +601,14 @@ impl ResourceFetchTiming {
             connect_end: 0,
             response_end: 0,
             start_time: 0,
+            transfer_size: 0,
+            encoded_body_size: 0,
+            decoded_body_size: 0,
         }
     }
 
-    // TODO currently this is being set with precise time ns when it should be time since
-    // time origin (as described in Performance::now)
+    // The timestamps are read from the monotonic clock, in nanoseconds, script
+    // makes them relative to the time origin of the global that uses them.
     pub fn set_attribute(&mut self, attribute: ResourceAttribute) {
         let should_attribute_always_be_updated = match attribute {
             ResourceAttribute::FetchStart |
@@ -555,6 +621,7 @@ impl ResourceFetchTiming {
         }
         match attribute {
             ResourceAttribute::DomainLookupStart => self.domain_lookup_start = precise_time_ns(),
+            ResourceAttribute::DomainLookupEnd => self.domain_lookup_end = precise_time_ns(),
             ResourceAttribute::RedirectCount(count) => self.redirect_count = count,
             ResourceAttribute::RequestStart => self.request_start = precise_time_ns(),
             ResourceAttribute::ResponseStart => self.response_start = precise_time_ns(),
@@ -571,8 +638,8 @@ impl ResourceFetchTiming {
                 RedirectEndValue::ResponseEnd => self.redirect_end = self.response_end,
             },
             ResourceAttribute::FetchStart => self.fetch_start = precise_time_ns(),
-            ResourceAttribute::ConnectStart(val) => self.connect_start = val,
-            ResourceAttribute::ConnectEnd(val) => self.connect_end = val,
+            ResourceAttribute::ConnectStart => self.connect_start = precise_time_ns(),
+            ResourceAttribute::ConnectEnd => self.connect_end = precise_time_ns(),
             ResourceAttribute::SecureConnectionStart => {
                 self.secure_connection_start = precise_time_ns()
             },
@@ -594,15 +661,33 @@ impl ResourceFetchTiming {
         }
     }
 
+    /// Records the sizes of a response received from the network, which are
+    /// not exposed when the timing allow check failed.
+    /// <https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-transfersize>
+    pub fn set_response_sizes(&mut self, encoded_body_size: u64, decoded_body_size: u64) {
+        if !self.timing_check_passed {
+            return;
+        }
+        // The size of the headers is not known once hyper parsed them, so the
+        // spec's estimate of 300 octets is used.
+        self.transfer_size = encoded_body_size + 300;
+        self.encoded_body_size = encoded_body_size;
+        self.decoded_body_size = decoded_body_size;
+    }
+
     pub fn mark_timing_check_failed(&mut self) {
         self.timing_check_passed = false;
         self.domain_lookup_start = 0;
+        self.domain_lookup_end = 0;
         self.redirect_count = 0;
         self.request_start = 0;
         self.response_start = 0;
         self.redirect_start = 0;
         self.connect_start = 0;
         self.connect_end = 0;
+        self.transfer_size = 0;
+        self.encoded_body_size = 0;
+        self.decoded_body_size = 0;
     }
 }
 
@@ -687,6 +772,15 @@ pub enum CookieSource {
     NonHTTP,
 }
 
+/// A change of the cookie storage, reported to the cookie change listeners
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum CookieChange {
+    /// A cookie was created, or replaced an existing one
+    Changed(Serde<Cookie<'static>>),
+    /// A cookie was removed, or replaced by an expired one
+    Deleted(Serde<Cookie<'static>>),
+}
+
 /// Network errors that have to be exported out of the loaders
 #[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
 pub enum NetworkError {
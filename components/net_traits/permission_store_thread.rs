@@ -0,0 +1,26 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::{PermissionName, PermissionRequest};
+use ipc_channel::ipc::IpcSender;
+use servo_url::ImmutableOrigin;
+
+/// Request operations on the answers of the user to the permission prompts
+/// of a particular origin, answers are never stored for opaque origins,
+/// <https://w3c.github.io/permissions/#permission-store>
+#[derive(Debug, Deserialize, Serialize)]
+pub enum PermissionStoreThreadMsg {
+    /// gets the answer of the user for the given permission, if any
+    Get(
+        IpcSender<Option<PermissionRequest>>,
+        ImmutableOrigin,
+        PermissionName,
+    ),
+
+    /// stores the answer of the user for the given permission
+    Set(ImmutableOrigin, PermissionName, PermissionRequest),
+
+    /// forgets the answer of the user for the given permission
+    Remove(ImmutableOrigin, PermissionName),
+}
@@ -7,9 +7,40 @@ use crate::ResourceTimingType;
 use content_security_policy::{self as csp, CspList};
 use http::HeaderMap;
 use hyper::Method;
+use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use servo_url::{ImmutableOrigin, ServoUrl};
 
+/// The maximum total size, in bytes, of the bodies of the inflight
+/// keepalive requests of a client.
+///
+/// <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>
+pub const KEEP_ALIVE_QUOTA: u64 = 64 * 1024;
+
+/// A message from the net component to the script thread that owns the
+/// `ReadableStream` of a streamed request body.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum BodyChunkRequest {
+    /// Connects the stream to the channel its chunks are sent on.
+    Connect(IpcSender<BodyChunkResponse>),
+    /// Asks for the next chunk of the body.
+    Chunk,
+    /// Tells the stream that no more chunks will be read, because the
+    /// request was done with its body or failed.
+    Done,
+}
+
+/// A message from the script thread answering a `BodyChunkRequest::Chunk`.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum BodyChunkResponse {
+    /// The next chunk of the body.
+    Chunk(Vec<u8>),
+    /// The stream closed, and the body is complete.
+    Done,
+    /// The stream errored, or gave a chunk that is not a `Uint8Array`.
+    Error,
+}
+
 /// An [initiator](https://fetch.spec.whatwg.org/#concept-request-initiator)
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
 pub enum Initiator {
@@ -130,6 +161,8 @@ pub struct RequestBuilder {
     pub headers: HeaderMap,
     pub unsafe_request: bool,
     pub body: Option<Vec<u8>>,
+    #[ignore_malloc_size_of = "channels are hard"]
+    pub body_stream: Option<IpcSender<BodyChunkRequest>>,
     pub service_workers_mode: ServiceWorkersMode,
     // TODO: client object
     pub destination: Destination,
@@ -155,6 +188,7 @@ pub struct RequestBuilder {
     pub url_list: Vec<ServoUrl>,
     pub parser_metadata: ParserMetadata,
     pub initiator: Initiator,
+    pub keep_alive: bool,
 }
 
 impl RequestBuilder {
@@ -165,6 +199,7 @@ impl RequestBuilder {
             headers: HeaderMap::new(),
             unsafe_request: false,
             body: None,
+            body_stream: None,
             service_workers_mode: ServiceWorkersMode::All,
             destination: Destination::None,
             synchronous: false,
@@ -183,6 +218,7 @@ impl RequestBuilder {
             parser_metadata: ParserMetadata::Default,
             initiator: Initiator::None,
             csp_list: None,
+            keep_alive: false,
         }
     }
 
@@ -211,6 +247,22 @@ impl RequestBuilder {
         self
     }
 
+    pub fn body_stream(
+        mut self,
+        body_stream: Option<IpcSender<BodyChunkRequest>>,
+    ) -> RequestBuilder {
+        self.body_stream = body_stream;
+        self
+    }
+
+    pub fn service_workers_mode(
+        mut self,
+        service_workers_mode: ServiceWorkersMode,
+    ) -> RequestBuilder {
+        self.service_workers_mode = service_workers_mode;
+        self
+    }
+
     pub fn destination(mut self, destination: Destination) -> RequestBuilder {
         self.destination = destination;
         self
@@ -276,6 +328,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn keep_alive(mut self, keep_alive: bool) -> RequestBuilder {
+        self.keep_alive = keep_alive;
+        self
+    }
+
     pub fn build(self) -> Request {
         let mut request = Request::new(
             self.url.clone(),
@@ -287,6 +344,7 @@ impl RequestBuilder {
         request.headers = self.headers;
         request.unsafe_request = self.unsafe_request;
         request.body = self.body;
+        request.body_stream = self.body_stream;
         request.service_workers_mode = self.service_workers_mode;
         request.destination = self.destination;
         request.synchronous = self.synchronous;
@@ -307,6 +365,7 @@ impl RequestBuilder {
         request.integrity_metadata = self.integrity_metadata;
         request.parser_metadata = self.parser_metadata;
         request.csp_list = self.csp_list;
+        request.keep_alive = self.keep_alive;
         request
     }
 }
@@ -329,6 +388,11 @@ pub struct Request {
     pub unsafe_request: bool,
     /// <https://fetch.spec.whatwg.org/#concept-request-body>
     pub body: Option<Vec<u8>>,
+    /// The body of the request when it is a `ReadableStream`, whose chunks
+    /// are requested from script as the body is transmitted. Such a body has
+    /// a null source, and cannot be sent again.
+    #[ignore_malloc_size_of = "channels are hard"]
+    pub body_stream: Option<IpcSender<BodyChunkRequest>>,
     // TODO: client object
     pub window: Window,
     // TODO: target browsing context
@@ -390,6 +454,7 @@ impl Request {
             headers: HeaderMap::new(),
             unsafe_request: false,
             body: None,
+            body_stream: None,
             window: Window::Client,
             keep_alive: false,
             service_workers_mode: ServiceWorkersMode::All,
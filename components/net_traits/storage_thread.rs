@@ -11,6 +11,15 @@ pub enum StorageType {
     Local,
 }
 
+/// <https://storage.spec.whatwg.org/#dictdef-storageestimate>
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct StorageEstimate {
+    /// The number of bytes used by the origin, across the storage endpoints.
+    pub usage: u64,
+    /// The number of bytes the origin is allowed to use.
+    pub quota: u64,
+}
+
 /// Request operations on the storage data associated with a particular url
 #[derive(Debug, Deserialize, Serialize)]
 pub enum StorageThreadMsg {
@@ -41,6 +50,15 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, ServoUrl, StorageType),
 
+    /// gets the usage and quota of the storage of the origin of the url
+    Estimate(IpcSender<StorageEstimate>, ServoUrl),
+
+    /// whether the storage of the origin of the url is persistent
+    Persisted(IpcSender<bool>, ServoUrl),
+
+    /// makes the storage of the origin of the url persistent, replying whether it is
+    Persist(IpcSender<bool>, ServoUrl),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use net_traits::image::base::detect_image_format;
+use net_traits::image::base::{detect_image_format, is_supported_image_type};
 
 #[test]
 fn test_supported_images() {
@@ -22,3 +22,30 @@ fn test_supported_images() {
     assert!(detect_image_format(&ico).is_ok());
     assert!(detect_image_format(&junk_format).is_err());
 }
+
+#[test]
+fn test_supported_image_types() {
+    let supported = [
+        "image/png",
+        "image/jpeg",
+        "image/gif",
+        "image/bmp",
+        "image/x-icon",
+    ];
+    for mime_type in &supported {
+        assert!(is_supported_image_type(&mime_type.parse().unwrap()));
+    }
+    assert!(is_supported_image_type(
+        &"image/png; foo=bar".parse().unwrap()
+    ));
+
+    let unsupported = [
+        "image/unknown",
+        "image/svg+xml",
+        "text/plain",
+        "application/png",
+    ];
+    for mime_type in &unsupported {
+        assert!(!is_supported_image_type(&mime_type.parse().unwrap()));
+    }
+}
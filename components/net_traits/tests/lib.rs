@@ -210,3 +210,27 @@ fn test_reset_start_time() {
         "failed to reset `start_time`"
     );
 }
+
+#[test]
+fn test_set_response_sizes_if_tao() {
+    let mut resource_timing: ResourceFetchTiming =
+        ResourceFetchTiming::new(ResourceTimingType::Resource);
+    resource_timing.set_response_sizes(10, 20);
+    assert_eq!(resource_timing.encoded_body_size, 10);
+    assert_eq!(resource_timing.decoded_body_size, 20);
+    assert!(
+        resource_timing.transfer_size > resource_timing.encoded_body_size,
+        "`transfer_size` should include the headers"
+    );
+}
+
+#[test]
+fn test_set_response_sizes_if_no_tao() {
+    let mut resource_timing: ResourceFetchTiming =
+        ResourceFetchTiming::new(ResourceTimingType::Resource);
+    resource_timing.mark_timing_check_failed();
+    resource_timing.set_response_sizes(10, 20);
+    assert_eq!(resource_timing.transfer_size, 0);
+    assert_eq!(resource_timing.encoded_body_size, 0);
+    assert_eq!(resource_timing.decoded_body_size, 0);
+}
@@ -126,7 +126,7 @@ fn run_package_data_algorithm<T: BodyOperations + DomObject>(
     }
 }
 
-fn run_text_data_algorithm(bytes: Vec<u8>) -> Fallible<FetchedData> {
+pub fn run_text_data_algorithm(bytes: Vec<u8>) -> Fallible<FetchedData> {
     Ok(FetchedData::Text(
         String::from_utf8_lossy(&bytes).into_owned(),
     ))
@@ -200,7 +200,7 @@ fn run_form_data_algorithm(
 }
 
 #[allow(unsafe_code)]
-fn run_array_buffer_data_algorithm(cx: JSContext, bytes: Vec<u8>) -> Fallible<FetchedData> {
+pub fn run_array_buffer_data_algorithm(cx: JSContext, bytes: Vec<u8>) -> Fallible<FetchedData> {
     rooted!(in(*cx) let mut array_buffer_ptr = ptr::null_mut::<JSObject>());
     let arraybuffer = unsafe {
         ArrayBuffer::create(
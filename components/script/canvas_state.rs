@@ -9,6 +9,7 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::Ca
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasLineJoin;
 use crate::dom::bindings::codegen::Bindings::ImageDataBinding::ImageDataMethods;
 use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrUnrestrictedDoubleSequence;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
@@ -20,19 +21,23 @@ use crate::dom::element::cors_setting_for_element;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::{CanvasContext, HTMLCanvasElement};
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::imagedata::ImageData;
-use crate::dom::node::{Node, NodeDamage};
+use crate::dom::node::{window_from_node, Node, NodeDamage};
 use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasContext};
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
+use crate::dom::path2d::Path2D;
 use crate::dom::textmetrics::TextMetrics;
 use crate::euclidext::Size2DExt;
 use crate::unpremultiplytable::UNPREMULTIPLY_TABLE;
+use canvas_traits::canvas::TextMetrics as CanvasTextMetrics;
 use canvas_traits::canvas::{Canvas2dMsg, CanvasId, CanvasMsg};
-use canvas_traits::canvas::{CompositionOrBlending, FillOrStrokeStyle, FillRule};
+use canvas_traits::canvas::{CompositionOrBlending, ConicGradientStyle, CornerRadii};
+use canvas_traits::canvas::{FillOrStrokeStyle, FillRule, FilterFunction};
 use canvas_traits::canvas::{LineCapStyle, LineJoinStyle, LinearGradientStyle};
 use canvas_traits::canvas::{RadialGradientStyle, RepetitionStyle};
 use cssparser::Color as CSSColor;
-use cssparser::{Parser, ParserInput, RGBA};
+use cssparser::{ParseError, Parser, ParserInput, Token, RGBA};
 use euclid::{
     default::{Point2D, Rect, Size2D, Transform2D},
     vec2,
@@ -49,11 +54,19 @@ use pixels::PixelFormat;
 use profile_traits::ipc as profiled_ipc;
 use script_traits::ScriptMsg;
 use serde_bytes::ByteBuf;
+use servo_arc::Arc as ServoArc;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::cell::Cell;
-use std::fmt;
+use std::fmt::{self, Write};
+use std::mem;
 use std::str::FromStr;
 use std::sync::Arc;
+use style::properties::longhands::font_variant_caps::computed_value::T as FontVariantCaps;
+use style::properties::style_structs::Font;
+use style::str::HTML_SPACE_CHARACTERS;
+use style::values::computed::font::{FontStyle, FontWeight};
+use style::values::specified::font::FontStretchKeyword;
+use style_traits::{CssWriter, ToCss};
 
 #[unrooted_must_root_lint::must_root]
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -76,11 +89,19 @@ pub(crate) struct CanvasContextState {
     line_cap: LineCapStyle,
     line_join: LineJoinStyle,
     miter_limit: f64,
+    line_dash: Vec<f64>,
+    line_dash_offset: f64,
     transform: Transform2D<f32>,
     shadow_offset_x: f64,
     shadow_offset_y: f64,
     shadow_blur: f64,
     shadow_color: RGBA,
+    /// The resolved `font` attribute, or `None` for the default
+    /// `10px sans-serif`.
+    #[ignore_malloc_size_of = "Arc"]
+    font_style: Option<ServoArc<Font>>,
+    /// The last value of the `filter` attribute that could be parsed.
+    filter: DOMString,
 }
 
 impl CanvasContextState {
@@ -96,15 +117,45 @@ impl CanvasContextState {
             line_cap: LineCapStyle::Butt,
             line_join: LineJoinStyle::Miter,
             miter_limit: 10.0,
+            line_dash: Vec::new(),
+            line_dash_offset: 0.0,
             transform: Transform2D::identity(),
             shadow_offset_x: 0.0,
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
             shadow_color: RGBA::transparent(),
+            font_style: None,
+            filter: DOMString::from("none"),
         }
     }
 }
 
+/// Converts the premultiplied BGRA pixels of a paint thread to unpremultiplied RGBA.
+pub(crate) fn unpremultiply_bgra_inplace(pixels: &mut [u8]) {
+    for chunk in pixels.chunks_mut(4) {
+        let b = chunk[0];
+        chunk[0] = UNPREMULTIPLY_TABLE[256 * (chunk[3] as usize) + chunk[2] as usize];
+        chunk[1] = UNPREMULTIPLY_TABLE[256 * (chunk[3] as usize) + chunk[1] as usize];
+        chunk[2] = UNPREMULTIPLY_TABLE[256 * (chunk[3] as usize) + b as usize];
+    }
+}
+
+/// Asks the constellation to create a new canvas paint thread.
+pub(crate) fn create_canvas_paint_thread(
+    global: &GlobalScope,
+    size: Size2D<u64>,
+) -> (IpcSender<CanvasMsg>, CanvasId) {
+    let (sender, receiver) = profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    debug!("Asking constellation to create new canvas thread.");
+    global
+        .script_to_constellation_chan()
+        .send(ScriptMsg::CreateCanvasPaintThread(size, sender))
+        .unwrap();
+    let paint_thread = receiver.recv().unwrap();
+    debug!("Done.");
+    paint_thread
+}
+
 #[unrooted_must_root_lint::must_root]
 #[derive(JSTraceable, MallocSizeOf)]
 pub(crate) struct CanvasState {
@@ -127,15 +178,17 @@ pub(crate) struct CanvasState {
 impl CanvasState {
     pub(crate) fn new(global: &GlobalScope, size: Size2D<u64>) -> CanvasState {
         debug!("Creating new canvas rendering context.");
-        let (sender, receiver) =
-            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
-        let script_to_constellation_chan = global.script_to_constellation_chan();
-        debug!("Asking constellation to create new canvas thread.");
-        script_to_constellation_chan
-            .send(ScriptMsg::CreateCanvasPaintThread(size, sender))
-            .unwrap();
-        let (ipc_renderer, canvas_id) = receiver.recv().unwrap();
-        debug!("Done.");
+        let (ipc_renderer, canvas_id) = create_canvas_paint_thread(global, size);
+        CanvasState::new_with_paint_thread(global, ipc_renderer, canvas_id)
+    }
+
+    /// Creates the state of a rendering context drawing into an existing paint thread,
+    /// as the one of the placeholder canvas element of an `OffscreenCanvas`.
+    pub(crate) fn new_with_paint_thread(
+        global: &GlobalScope,
+        ipc_renderer: IpcSender<CanvasMsg>,
+        canvas_id: CanvasId,
+    ) -> CanvasState {
         // Worklets always receive a unique origin. This messes with fetching
         // cached images in the case of paint worklets, since the image cache
         // is keyed on the origin requesting the image data.
@@ -224,6 +277,7 @@ impl CanvasState {
         match image {
             CanvasImageSource::HTMLCanvasElement(canvas) => canvas.origin_is_clean(),
             CanvasImageSource::OffscreenCanvas(canvas) => canvas.origin_is_clean(),
+            CanvasImageSource::ImageBitmap(bitmap) => bitmap.origin_is_clean(),
             CanvasImageSource::HTMLImageElement(image) => {
                 image.same_origin(GlobalScope::entry().origin())
             },
@@ -325,14 +379,7 @@ impl CanvasState {
         let (sender, receiver) = ipc::bytes_channel().unwrap();
         self.send_canvas_2d_msg(Canvas2dMsg::GetImageData(rect, canvas_size, sender));
         let mut pixels = receiver.recv().unwrap().to_vec();
-
-        for chunk in pixels.chunks_mut(4) {
-            let b = chunk[0];
-            chunk[0] = UNPREMULTIPLY_TABLE[256 * (chunk[3] as usize) + chunk[2] as usize];
-            chunk[1] = UNPREMULTIPLY_TABLE[256 * (chunk[3] as usize) + chunk[1] as usize];
-            chunk[2] = UNPREMULTIPLY_TABLE[256 * (chunk[3] as usize) + b as usize];
-        }
-
+        unpremultiply_bgra_inplace(&mut pixels);
         pixels
     }
 
@@ -377,6 +424,9 @@ impl CanvasState {
             CanvasImageSource::OffscreenCanvas(ref canvas) => {
                 self.draw_offscreen_canvas(&canvas, htmlcanvas, sx, sy, sw, sh, dx, dy, dw, dh)
             },
+            CanvasImageSource::ImageBitmap(ref bitmap) => {
+                self.draw_image_bitmap(&bitmap, htmlcanvas, sx, sy, sw, sh, dx, dy, dw, dh)
+            },
             CanvasImageSource::HTMLImageElement(ref image) => {
                 // https://html.spec.whatwg.org/multipage/#img-error
                 // If the image argument is an HTMLImageElement object that is in the broken state,
@@ -469,6 +519,50 @@ impl CanvasState {
         Ok(())
     }
 
+    fn draw_image_bitmap(
+        &self,
+        bitmap: &ImageBitmap,
+        htmlcanvas: Option<&HTMLCanvasElement>,
+        sx: f64,
+        sy: f64,
+        sw: Option<f64>,
+        sh: Option<f64>,
+        dx: f64,
+        dy: f64,
+        dw: Option<f64>,
+        dh: Option<f64>,
+    ) -> ErrorResult {
+        // https://html.spec.whatwg.org/multipage/#check-the-usability-of-the-image-argument
+        let image_data = bitmap
+            .premultiplied_bitmap_data()
+            .ok_or(Error::InvalidState)?;
+        let image_size = bitmap.get_size().to_f64();
+
+        let dw = dw.unwrap_or(image_size.width);
+        let dh = dh.unwrap_or(image_size.height);
+        let sw = sw.unwrap_or(image_size.width);
+        let sh = sh.unwrap_or(image_size.height);
+
+        // Establish the source and destination rectangles
+        let (source_rect, dest_rect) =
+            self.adjust_source_dest_rects(image_size, sx, sy, sw, sh, dx, dy, dw, dh);
+
+        if !is_rect_valid(source_rect) || !is_rect_valid(dest_rect) {
+            return Ok(());
+        }
+
+        let smoothing_enabled = self.state.borrow().image_smoothing_enabled;
+        self.send_canvas_2d_msg(Canvas2dMsg::DrawImage(
+            Some(ByteBuf::from(image_data)),
+            image_size,
+            dest_rect,
+            source_rect,
+            smoothing_enabled,
+        ));
+        self.mark_as_dirty(htmlcanvas);
+        Ok(())
+    }
+
     fn draw_html_canvas_element(
         &self,
         canvas: &HTMLCanvasElement,             // source canvas
@@ -515,6 +609,21 @@ impl CanvasState {
                         smoothing_enabled,
                     ));
                 },
+                CanvasContext::Placeholder(ref placeholder) => {
+                    placeholder
+                        .ipc_renderer
+                        .send(CanvasMsg::Canvas2d(
+                            Canvas2dMsg::DrawImageInOther(
+                                self.get_canvas_id(),
+                                image_size,
+                                dest_rect,
+                                source_rect,
+                                smoothing_enabled,
+                            ),
+                            placeholder.canvas_id,
+                        ))
+                        .unwrap();
+                },
                 _ => return Err(Error::InvalidState),
             }
         } else {
@@ -729,6 +838,57 @@ impl CanvasState {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-font
+    pub fn font(&self) -> DOMString {
+        match self.state.borrow().font_style {
+            Some(ref font_style) => {
+                let mut result = String::new();
+                serialize_font(font_style, &mut result).unwrap();
+                DOMString::from(result)
+            },
+            None => DOMString::from("10px sans-serif"),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-font
+    pub fn set_font(&self, canvas: Option<&HTMLCanvasElement>, value: DOMString) {
+        // FIXME: relative values are resolved against the style of the
+        // canvas element, so contexts without one keep the default font.
+        let canvas = match canvas {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        // CSS-wide keywords are not font values, and are ignored like values
+        // that cannot be parsed.
+        if ["inherit", "initial", "unset", "revert"]
+            .iter()
+            .any(|keyword| value.trim().eq_ignore_ascii_case(keyword))
+        {
+            return;
+        }
+        let node = canvas.upcast::<Node>();
+        let window = window_from_node(canvas);
+        let font_style = match window.resolved_font_style_query(node, value.to_string()) {
+            Some(font_style) => font_style,
+            None => return,
+        };
+        self.state.borrow_mut().font_style = Some(font_style.clone());
+        self.send_canvas_2d_msg(Canvas2dMsg::SetFont((*font_style).clone()));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    pub fn filter(&self) -> DOMString {
+        self.state.borrow().filter.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    pub fn set_filter(&self, value: DOMString) {
+        if let Ok(filters) = parse_filter(&value) {
+            self.state.borrow_mut().filter = value;
+            self.send_canvas_2d_msg(Canvas2dMsg::SetFilter(filters));
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-strokestyle
     pub fn stroke_style(&self) -> StringOrCanvasGradientOrCanvasPattern {
         match self.state.borrow().stroke_style {
@@ -877,6 +1037,20 @@ impl CanvasState {
         ))
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient
+    pub fn create_conic_gradient(
+        &self,
+        global: &GlobalScope,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> DomRoot<CanvasGradient> {
+        CanvasGradient::new(
+            global,
+            CanvasGradientStyle::Conic(ConicGradientStyle::new(*start_angle, *x, *y, Vec::new())),
+        )
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     pub fn create_pattern(
         &self,
@@ -910,6 +1084,12 @@ impl CanvasState {
                     .unwrap_or_else(|| vec![0; size.area() as usize * 4]);
                 (data, size)
             },
+            CanvasImageSource::ImageBitmap(ref bitmap) => {
+                let data = bitmap
+                    .premultiplied_bitmap_data()
+                    .ok_or(Error::InvalidState)?;
+                (data, bitmap.get_size())
+            },
             CanvasImageSource::CSSStyleValue(ref value) => value
                 .get_url(self.base_url.clone())
                 .and_then(|url| self.fetch_image_data(url, None))
@@ -999,11 +1179,30 @@ impl CanvasState {
     }
 
     // https://html.spec.whatwg.org/multipage/#textmetrics
-    pub fn measure_text(&self, global: &GlobalScope, _text: DOMString) -> DomRoot<TextMetrics> {
-        // FIXME: for now faking the implementation of MeasureText().
-        // See https://github.com/servo/servo/issues/5411#issuecomment-533776291
+    pub fn measure_text(&self, global: &GlobalScope, text: DOMString) -> DomRoot<TextMetrics> {
+        // Step 1.
+        let text = String::from(text).replace(HTML_SPACE_CHARACTERS, " ");
+
+        // Steps 2-3, using the font set through the font attribute.
+        let (sender, receiver) =
+            profiled_ipc::channel::<CanvasTextMetrics>(global.time_profiler_chan().clone())
+                .unwrap();
+        self.send_canvas_2d_msg(Canvas2dMsg::MeasureText(text, sender));
+        let metrics = receiver.recv().unwrap();
         TextMetrics::new(
-            global, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+            global,
+            metrics.width as f64,
+            metrics.actual_bounding_box_left as f64,
+            metrics.actual_bounding_box_right as f64,
+            metrics.font_bounding_box_ascent as f64,
+            metrics.font_bounding_box_descent as f64,
+            metrics.actual_bounding_box_ascent as f64,
+            metrics.actual_bounding_box_descent as f64,
+            metrics.em_height_ascent as f64,
+            metrics.em_height_descent as f64,
+            metrics.hanging_baseline as f64,
+            metrics.alphabetic_baseline as f64,
+            metrics.ideographic_baseline as f64,
         )
     }
 
@@ -1077,6 +1276,47 @@ impl CanvasState {
         self.send_canvas_2d_msg(Canvas2dMsg::SetMiterLimit(limit as f32))
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-getlinedash
+    pub fn line_dash(&self) -> Vec<f64> {
+        self.state.borrow().line_dash.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-setlinedash
+    pub fn set_line_dash(&self, mut segments: Vec<f64>) {
+        // Step 1.
+        if segments
+            .iter()
+            .any(|segment| !segment.is_finite() || *segment < 0.0)
+        {
+            return;
+        }
+
+        // Step 2.
+        if segments.len() % 2 == 1 {
+            segments.extend_from_slice(&segments.clone());
+        }
+
+        // Step 3.
+        let dashes = segments.iter().map(|segment| *segment as f32).collect();
+        self.state.borrow_mut().line_dash = segments;
+        self.send_canvas_2d_msg(Canvas2dMsg::SetLineDash(dashes))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-linedashoffset
+    pub fn line_dash_offset(&self) -> f64 {
+        self.state.borrow().line_dash_offset
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-linedashoffset
+    pub fn set_line_dash_offset(&self, offset: f64) {
+        if !offset.is_finite() {
+            return;
+        }
+
+        self.state.borrow_mut().line_dash_offset = offset;
+        self.send_canvas_2d_msg(Canvas2dMsg::SetLineDashOffset(offset as f32))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createimagedata
     pub fn create_image_data(
         &self,
@@ -1305,17 +1545,34 @@ impl CanvasState {
         self.send_canvas_2d_msg(Canvas2dMsg::Fill);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-fill
+    pub fn fill_(&self, path: &Path2D, _fill_rule: CanvasFillRule) {
+        // TODO: Process fill rule
+        self.send_canvas_2d_msg(Canvas2dMsg::FillPath(path.segments()));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke
     pub fn stroke(&self) {
         self.send_canvas_2d_msg(Canvas2dMsg::Stroke);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke
+    pub fn stroke_(&self, path: &Path2D) {
+        self.send_canvas_2d_msg(Canvas2dMsg::StrokePath(path.segments()));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-clip
     pub fn clip(&self, _fill_rule: CanvasFillRule) {
         // TODO: Process fill rule
         self.send_canvas_2d_msg(Canvas2dMsg::Clip);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-clip
+    pub fn clip_(&self, path: &Path2D, _fill_rule: CanvasFillRule) {
+        // TODO: Process fill rule
+        self.send_canvas_2d_msg(Canvas2dMsg::ClipPath(path.segments()));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath
     pub fn is_point_in_path(
         &self,
@@ -1338,6 +1595,35 @@ impl CanvasState {
         receiver.recv().unwrap()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath
+    pub fn is_point_in_path_(
+        &self,
+        global: &GlobalScope,
+        path: &Path2D,
+        x: f64,
+        y: f64,
+        fill_rule: CanvasFillRule,
+    ) -> bool {
+        if !(x.is_finite() && y.is_finite()) {
+            return false;
+        }
+
+        let fill_rule = match fill_rule {
+            CanvasFillRule::Nonzero => FillRule::Nonzero,
+            CanvasFillRule::Evenodd => FillRule::Evenodd,
+        };
+        let (sender, receiver) =
+            profiled_ipc::channel::<bool>(global.time_profiler_chan().clone()).unwrap();
+        self.send_canvas_2d_msg(Canvas2dMsg::IsPointInPathPath(
+            path.segments(),
+            x,
+            y,
+            fill_rule,
+            sender,
+        ));
+        receiver.recv().unwrap()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-scale
     pub fn scale(&self, x: f64, y: f64) {
         if !(x.is_finite() && y.is_finite()) {
@@ -1453,6 +1739,21 @@ impl CanvasState {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect
+    pub fn round_rect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: Option<UnrestrictedDoubleOrUnrestrictedDoubleSequence>,
+    ) -> ErrorResult {
+        if let Some((rect, radii)) = round_rect_corners(x, y, width, height, radii)? {
+            self.send_canvas_2d_msg(Canvas2dMsg::RoundRect(rect, radii));
+        }
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
     pub fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
         if !(cpx.is_finite() && cpy.is_finite() && x.is_finite() && y.is_finite()) {
@@ -1569,6 +1870,318 @@ pub fn parse_color(string: &str) -> Result<RGBA, ()> {
     }
 }
 
+/// Parses the value of the `filter` attribute into its filter functions,
+/// with lengths in pixels and angles in degrees. References to SVG filters
+/// are not supported, <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+fn parse_filter(string: &str) -> Result<Vec<FilterFunction>, ()> {
+    let mut input = ParserInput::new(string);
+    let mut parser = Parser::new(&mut input);
+    let start = parser.state();
+    if parser.expect_ident_matching("none").is_ok() && parser.is_exhausted() {
+        return Ok(vec![]);
+    }
+    parser.reset(&start);
+
+    let mut filters = vec![];
+    while !parser.is_exhausted() {
+        let name = match parser.next() {
+            Ok(&Token::Function(ref name)) => name.to_ascii_lowercase(),
+            _ => return Err(()),
+        };
+        let filter = parser
+            .parse_nested_block(|parser| parse_filter_function(&name, parser))
+            .map_err(|_: ParseError<()>| ())?;
+        filters.push(filter);
+    }
+    if filters.is_empty() {
+        return Err(());
+    }
+    Ok(filters)
+}
+
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+fn parse_filter_function<'i>(
+    name: &str,
+    parser: &mut Parser<'i, '_>,
+) -> Result<FilterFunction, ParseError<'i, ()>> {
+    let filter = match name {
+        "blur" => FilterFunction::Blur(parse_optional(parser, parse_filter_length, 0.)?),
+        "brightness" => FilterFunction::Brightness(parse_optional(parser, parse_factor, 1.)?),
+        "contrast" => FilterFunction::Contrast(parse_optional(parser, parse_factor, 1.)?),
+        "drop-shadow" => parse_drop_shadow(parser)?,
+        "grayscale" => FilterFunction::Grayscale(parse_optional(parser, parse_factor, 1.)?),
+        "hue-rotate" => FilterFunction::HueRotate(parse_optional(parser, parse_angle, 0.)?),
+        "invert" => FilterFunction::Invert(parse_optional(parser, parse_factor, 1.)?),
+        "opacity" => FilterFunction::Opacity(parse_optional(parser, parse_factor, 1.)?),
+        "saturate" => FilterFunction::Saturate(parse_optional(parser, parse_factor, 1.)?),
+        "sepia" => FilterFunction::Sepia(parse_optional(parser, parse_factor, 1.)?),
+        _ => return Err(parser.new_custom_error(())),
+    };
+    parser.expect_exhausted()?;
+    Ok(filter)
+}
+
+fn parse_optional<'i, 't, F>(
+    parser: &mut Parser<'i, 't>,
+    parse: F,
+    default: f32,
+) -> Result<f32, ParseError<'i, ()>>
+where
+    F: FnOnce(&mut Parser<'i, 't>) -> Result<f32, ParseError<'i, ()>>,
+{
+    if parser.is_exhausted() {
+        Ok(default)
+    } else {
+        parse(parser)
+    }
+}
+
+/// Parses a non-negative number or percentage.
+fn parse_factor<'i>(parser: &mut Parser<'i, '_>) -> Result<f32, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let value = match *parser.next()? {
+        Token::Number { value, .. } => value,
+        Token::Percentage { unit_value, .. } => unit_value,
+        ref token => return Err(location.new_unexpected_token_error(token.clone())),
+    };
+    if value < 0. {
+        return Err(location.new_custom_error(()));
+    }
+    Ok(value)
+}
+
+/// Parses an absolute length into pixels.
+fn parse_length<'i>(parser: &mut Parser<'i, '_>) -> Result<f32, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let (value, unit) = match *parser.next()? {
+        Token::Number { value, .. } if value == 0. => return Ok(0.),
+        Token::Dimension {
+            value, ref unit, ..
+        } => (value, unit.to_ascii_lowercase()),
+        ref token => return Err(location.new_unexpected_token_error(token.clone())),
+    };
+    let pixels_per_unit = match &*unit {
+        "px" => 1.,
+        "in" => 96.,
+        "cm" => 96. / 2.54,
+        "mm" => 96. / 25.4,
+        "q" => 96. / 101.6,
+        "pt" => 96. / 72.,
+        "pc" => 16.,
+        _ => return Err(location.new_custom_error(())),
+    };
+    Ok(value * pixels_per_unit)
+}
+
+/// Parses a non-negative length into pixels.
+fn parse_filter_length<'i>(parser: &mut Parser<'i, '_>) -> Result<f32, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let length = parse_length(parser)?;
+    if length < 0. {
+        return Err(location.new_custom_error(()));
+    }
+    Ok(length)
+}
+
+/// Parses an angle into degrees.
+fn parse_angle<'i>(parser: &mut Parser<'i, '_>) -> Result<f32, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let (value, unit) = match *parser.next()? {
+        Token::Number { value, .. } if value == 0. => return Ok(0.),
+        Token::Dimension {
+            value, ref unit, ..
+        } => (value, unit.to_ascii_lowercase()),
+        ref token => return Err(location.new_unexpected_token_error(token.clone())),
+    };
+    match &*unit {
+        "deg" => Ok(value),
+        "grad" => Ok(value * 0.9),
+        "rad" => Ok(value.to_degrees()),
+        "turn" => Ok(value * 360.),
+        _ => Err(location.new_custom_error(())),
+    }
+}
+
+/// Parses the arguments of `drop-shadow()`, an optional color before or
+/// after two offsets and an optional blur radius.
+fn parse_drop_shadow<'i>(
+    parser: &mut Parser<'i, '_>,
+) -> Result<FilterFunction, ParseError<'i, ()>> {
+    let mut color = None;
+    let mut color_after_lengths = false;
+    let mut lengths = vec![];
+    while !parser.is_exhausted() {
+        let state = parser.state();
+        if let Ok(length) = parse_length(parser) {
+            // The lengths cannot be on both sides of the color.
+            if lengths.len() == 3 || color_after_lengths {
+                return Err(parser.new_custom_error(()));
+            }
+            lengths.push(length);
+            continue;
+        }
+        parser.reset(&state);
+        if color.is_some() {
+            return Err(parser.new_custom_error(()));
+        }
+        color_after_lengths = !lengths.is_empty();
+        color = Some(match CSSColor::parse(parser)? {
+            CSSColor::RGBA(rgba) => rgba,
+            CSSColor::CurrentColor => RGBA::new(0, 0, 0, 255),
+        });
+    }
+    if lengths.len() < 2 || lengths.get(2).map_or(false, |blur| *blur < 0.) {
+        return Err(parser.new_custom_error(()));
+    }
+    Ok(FilterFunction::DropShadow {
+        offset_x: lengths[0],
+        offset_y: lengths[1],
+        blur: lengths.get(2).cloned().unwrap_or(0.),
+        color: color.unwrap_or_else(|| RGBA::new(0, 0, 0, 255)),
+    })
+}
+
+/// Serializes a computed font as the `font` shorthand, leaving out the
+/// longhands that have their initial value,
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-font>
+fn serialize_font<W>(style: &Font, dest: &mut W) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    let mut dest = CssWriter::new(dest);
+    if style.font_style != FontStyle::normal() {
+        style.font_style.to_css(&mut dest)?;
+        dest.write_char(' ')?;
+    }
+    if style.font_variant_caps != FontVariantCaps::Normal {
+        style.font_variant_caps.to_css(&mut dest)?;
+        dest.write_char(' ')?;
+    }
+    if style.font_weight != FontWeight::normal() {
+        style.font_weight.to_css(&mut dest)?;
+        dest.write_char(' ')?;
+    }
+    // Only the keyword values of font-stretch are allowed in the shorthand.
+    match FontStretchKeyword::from_percentage(((style.font_stretch.0).0).0) {
+        Some(FontStretchKeyword::Normal) | None => {},
+        Some(keyword) => {
+            keyword.to_css(&mut dest)?;
+            dest.write_char(' ')?;
+        },
+    }
+    style.font_size.to_css(&mut dest)?;
+    dest.write_char(' ')?;
+    style.font_family.to_css(&mut dest)
+}
+
+/// Validates the arguments of `roundRect()` and computes the rectangle and
+/// the radii of its corners, or returns `None` if nothing should be added
+/// to the path, <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+pub fn round_rect_corners(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    radii: Option<UnrestrictedDoubleOrUnrestrictedDoubleSequence>,
+) -> Fallible<Option<(Rect<f32>, CornerRadii)>> {
+    // Step 1.
+    if ![x, y, width, height].iter().all(|val| val.is_finite()) {
+        return Ok(None);
+    }
+
+    // Step 2.
+    let radii = match radii {
+        None => vec![0.0],
+        Some(UnrestrictedDoubleOrUnrestrictedDoubleSequence::UnrestrictedDouble(radius)) => {
+            vec![radius]
+        },
+        Some(UnrestrictedDoubleOrUnrestrictedDoubleSequence::UnrestrictedDoubleSequence(radii)) => {
+            radii
+        },
+    };
+
+    // Step 3.
+    if radii.is_empty() || radii.len() > 4 {
+        return Err(Error::Range(
+            "roundRect() expects between one and four radii".to_owned(),
+        ));
+    }
+
+    // Steps 4-5.
+    for radius in &radii {
+        if !radius.is_finite() {
+            return Ok(None);
+        }
+        if *radius < 0.0 {
+            return Err(Error::Range(
+                "roundRect() radii must not be negative".to_owned(),
+            ));
+        }
+    }
+
+    // Step 6.
+    let (mut upper_left, mut upper_right, mut lower_right, mut lower_left) = match radii[..] {
+        [all] => (all, all, all, all),
+        [upper_left_lower_right, upper_right_lower_left] => (
+            upper_left_lower_right,
+            upper_right_lower_left,
+            upper_left_lower_right,
+            upper_right_lower_left,
+        ),
+        [upper_left, upper_right_lower_left, lower_right] => (
+            upper_left,
+            upper_right_lower_left,
+            lower_right,
+            upper_right_lower_left,
+        ),
+        [upper_left, upper_right, lower_right, lower_left] => {
+            (upper_left, upper_right, lower_right, lower_left)
+        },
+        _ => unreachable!(),
+    };
+
+    // Step 7.
+    let scale = [
+        width.abs() / (upper_left + upper_right),
+        height.abs() / (upper_right + lower_right),
+        width.abs() / (lower_right + lower_left),
+        height.abs() / (upper_left + lower_left),
+    ]
+    .iter()
+    .fold(1.0f64, |scale, factor| scale.min(*factor));
+    upper_left *= scale;
+    upper_right *= scale;
+    lower_right *= scale;
+    lower_left *= scale;
+
+    // Rectangles with a negative size are drawn mirrored, so normalize them
+    // and swap the radii of the corners accordingly.
+    let mut rect = Rect::new(Point2D::new(x, y), Size2D::new(width, height));
+    if width < 0.0 {
+        rect.origin.x += width;
+        rect.size.width = -width;
+        mem::swap(&mut upper_left, &mut upper_right);
+        mem::swap(&mut lower_left, &mut lower_right);
+    }
+    if height < 0.0 {
+        rect.origin.y += height;
+        rect.size.height = -height;
+        mem::swap(&mut upper_left, &mut lower_left);
+        mem::swap(&mut upper_right, &mut lower_right);
+    }
+
+    let corner = |radius: f64| Size2D::new(radius as f32, radius as f32);
+    Ok(Some((
+        rect.to_f32(),
+        CornerRadii {
+            upper_left: corner(upper_left),
+            upper_right: corner(upper_right),
+            lower_right: corner(lower_right),
+            lower_left: corner(lower_left),
+        },
+    )))
+}
+
 // Used by drawImage to determine if a source or destination rectangle is valid
 // Origin coordinates and size cannot be negative. Size has to be greater than zero
 pub fn is_rect_valid(rect: Rect<f64>) -> bool {
@@ -0,0 +1,238 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The [Clear Key](https://w3c.github.io/encrypted-media/#clear-key) key
+//! system, whose keys are exchanged unencrypted as JSON Web Keys.
+//!
+//! Only `temporary` sessions are supported.
+
+use super::{Cdm, CdmError, KeyStatus, Message, MessageType};
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+
+/// The system ID of the Common PSSH box format, which carries the key IDs of
+/// `cenc` initialization data for Clear Key.
+/// <https://w3c.github.io/encrypted-media/format-registry/initdata/cenc.html#common-system>
+const COMMON_SYSTEM_ID: [u8; 16] = [
+    0x10, 0x77, 0xef, 0xec, 0xc0, 0xb2, 0x4d, 0x02, 0xac, 0xe3, 0x3c, 0x1e, 0x52, 0xe2, 0xfb, 0x4b,
+];
+
+/// The maximum length of a key ID in `keyids` and `webm` initialization data.
+const MAX_KEY_ID_LENGTH: usize = 512;
+
+struct Session {
+    /// The keys obtained by the session, by key ID.
+    keys: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Session {
+    fn statuses(&self, status: KeyStatus) -> Vec<(Vec<u8>, KeyStatus)> {
+        self.keys
+            .iter()
+            .map(|&(ref key_id, _)| (key_id.clone(), status))
+            .collect()
+    }
+}
+
+pub struct ClearKey {
+    sessions: HashMap<String, Session>,
+    next_session_id: u32,
+}
+
+impl ClearKey {
+    pub const KEY_SYSTEM: &'static str = "org.w3.clearkey";
+
+    pub fn new() -> ClearKey {
+        ClearKey {
+            sessions: HashMap::new(),
+            next_session_id: 0,
+        }
+    }
+
+    fn session_mut(&mut self, session_id: &str) -> Result<&mut Session, CdmError> {
+        self.sessions
+            .get_mut(session_id)
+            .ok_or(CdmError::UnknownSession)
+    }
+}
+
+impl Cdm for ClearKey {
+    fn supports_init_data_type(&self, init_data_type: &str) -> bool {
+        match init_data_type {
+            "cenc" | "keyids" | "webm" => true,
+            _ => false,
+        }
+    }
+
+    fn supports_persistent_license(&self) -> bool {
+        false
+    }
+
+    fn set_server_certificate(&mut self, _certificate: &[u8]) -> Result<bool, CdmError> {
+        // Clear Key has no use for a server certificate.
+        Ok(false)
+    }
+
+    // https://w3c.github.io/encrypted-media/#clear-key-request-format
+    fn generate_request(
+        &mut self,
+        init_data_type: &str,
+        init_data: &[u8],
+    ) -> Result<(String, Message), CdmError> {
+        let key_ids = key_ids(init_data_type, init_data)?;
+
+        self.next_session_id += 1;
+        let session_id = self.next_session_id.to_string();
+        self.sessions
+            .insert(session_id.clone(), Session { keys: vec![] });
+
+        let kids: Vec<String> = key_ids
+            .iter()
+            .map(|key_id| base64::encode_config(key_id, base64::URL_SAFE_NO_PAD))
+            .collect();
+        let request = json!({
+            "kids": kids,
+            "type": "temporary",
+        });
+        let message = Message {
+            message_type: MessageType::LicenseRequest,
+            data: request.to_string().into_bytes(),
+        };
+        Ok((session_id, message))
+    }
+
+    // https://w3c.github.io/encrypted-media/#clear-key-license-format
+    fn update(
+        &mut self,
+        session_id: &str,
+        response: &[u8],
+    ) -> Result<Vec<(Vec<u8>, KeyStatus)>, CdmError> {
+        let keys = parse_license(response)?;
+        let session = self.session_mut(session_id)?;
+        for (key_id, key) in keys {
+            session.keys.retain(|&(ref id, _)| *id != key_id);
+            session.keys.push((key_id, key));
+        }
+        Ok(session.statuses(KeyStatus::Usable))
+    }
+
+    fn remove(
+        &mut self,
+        session_id: &str,
+    ) -> Result<(Vec<(Vec<u8>, KeyStatus)>, Option<Message>), CdmError> {
+        let session = self.session_mut(session_id)?;
+        let statuses = session.statuses(KeyStatus::Released);
+        session.keys.clear();
+        // Temporary sessions have no license to release.
+        Ok((statuses, None))
+    }
+
+    fn close(&mut self, session_id: &str) {
+        self.sessions.remove(session_id);
+    }
+
+    fn key(&self, key_id: &[u8]) -> Option<&[u8]> {
+        self.sessions
+            .values()
+            .flat_map(|session| session.keys.iter())
+            .find(|&&(ref id, _)| &**id == key_id)
+            .map(|&(_, ref key)| &**key)
+    }
+}
+
+fn decode_base64url(value: &JsonValue) -> Option<Vec<u8>> {
+    base64::decode_config(value.as_str()?, base64::URL_SAFE_NO_PAD).ok()
+}
+
+/// Extracts the key IDs from initialization data.
+fn key_ids(init_data_type: &str, init_data: &[u8]) -> Result<Vec<Vec<u8>>, CdmError> {
+    let key_ids = match init_data_type {
+        // https://w3c.github.io/encrypted-media/format-registry/initdata/cenc.html
+        "cenc" => cenc_key_ids(init_data)?,
+        // https://w3c.github.io/encrypted-media/format-registry/initdata/keyids.html
+        "keyids" => {
+            let json: JsonValue =
+                serde_json::from_slice(init_data).map_err(|_| CdmError::InvalidData)?;
+            json.get("kids")
+                .and_then(JsonValue::as_array)
+                .ok_or(CdmError::InvalidData)?
+                .iter()
+                .map(|kid| decode_base64url(kid).ok_or(CdmError::InvalidData))
+                .collect::<Result<Vec<_>, _>>()?
+        },
+        // https://w3c.github.io/encrypted-media/format-registry/initdata/webm.html
+        "webm" => vec![init_data.to_vec()],
+        _ => return Err(CdmError::NotSupported),
+    };
+    if key_ids.is_empty() ||
+        key_ids
+            .iter()
+            .any(|key_id| key_id.is_empty() || key_id.len() > MAX_KEY_ID_LENGTH)
+    {
+        return Err(CdmError::InvalidData);
+    }
+    Ok(key_ids)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    (data[offset] as u32) << 24 |
+        (data[offset + 1] as u32) << 16 |
+        (data[offset + 2] as u32) << 8 |
+        data[offset + 3] as u32
+}
+
+/// Collects the key IDs of the Common PSSH boxes in `cenc` initialization
+/// data, ignoring the boxes of other key systems.
+fn cenc_key_ids(mut data: &[u8]) -> Result<Vec<Vec<u8>>, CdmError> {
+    let mut key_ids = vec![];
+    while !data.is_empty() {
+        if data.len() < 8 {
+            return Err(CdmError::InvalidData);
+        }
+        let size = read_u32(data, 0) as usize;
+        // A `pssh` box has at least a header, a version, flags and a system ID.
+        if size < 28 || size > data.len() || &data[4..8] != b"pssh" {
+            return Err(CdmError::InvalidData);
+        }
+        let pssh = &data[..size];
+        data = &data[size..];
+
+        // Key IDs only appear in version 1 boxes.
+        let version = pssh[8];
+        if pssh[12..28] != COMMON_SYSTEM_ID || version == 0 {
+            continue;
+        }
+        if size < 32 {
+            return Err(CdmError::InvalidData);
+        }
+        let count = read_u32(pssh, 28) as usize;
+        let kids = count
+            .checked_mul(16)
+            .and_then(|length| pssh[32..].get(..length))
+            .ok_or(CdmError::InvalidData)?;
+        key_ids.extend(kids.chunks(16).map(|kid| kid.to_vec()));
+    }
+    Ok(key_ids)
+}
+
+/// Parses a JSON Web Key Set of symmetric keys into key IDs and keys.
+fn parse_license(response: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, CdmError> {
+    let json: JsonValue = serde_json::from_slice(response).map_err(|_| CdmError::InvalidData)?;
+    json.get("keys")
+        .and_then(JsonValue::as_array)
+        .ok_or(CdmError::InvalidData)?
+        .iter()
+        .map(|jwk| {
+            if jwk.get("kty").and_then(JsonValue::as_str) != Some("oct") {
+                return Err(CdmError::InvalidData);
+            }
+            let key_id = jwk.get("kid").and_then(decode_base64url);
+            let key = jwk.get("k").and_then(decode_base64url);
+            match (key_id, key) {
+                (Some(key_id), Some(key)) => Ok((key_id, key)),
+                _ => Err(CdmError::InvalidData),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Content Decryption Modules, which implement the key systems of
+//! [Encrypted Media Extensions](https://w3c.github.io/encrypted-media/).
+//!
+//! A CDM handles the license exchange of the sessions of a `MediaKeys`
+//! object, and holds the keys those sessions obtain. The DOM objects only
+//! deal with the generic algorithms of the specification.
+
+mod clearkey;
+
+pub use self::clearkey::ClearKey;
+
+/// <https://w3c.github.io/encrypted-media/#dom-mediakeymessagetype>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageType {
+    LicenseRequest,
+    LicenseRenewal,
+    LicenseRelease,
+    IndividualizationRequest,
+}
+
+/// A message the CDM needs to send to the license server.
+#[derive(Debug)]
+pub struct Message {
+    pub message_type: MessageType,
+    pub data: Vec<u8>,
+}
+
+/// <https://w3c.github.io/encrypted-media/#dom-mediakeystatus>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyStatus {
+    Usable,
+    Expired,
+    Released,
+    OutputRestricted,
+    OutputDownscaled,
+    StatusPending,
+    InternalError,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CdmError {
+    /// The initialization data type is not supported by the key system.
+    NotSupported,
+    /// The initialization data or the license server response is malformed.
+    InvalidData,
+    /// There is no session with the given ID.
+    UnknownSession,
+}
+
+pub trait Cdm {
+    /// Whether this CDM supports the given
+    /// [initialization data type](https://w3c.github.io/encrypted-media/#initialization-data-type).
+    fn supports_init_data_type(&self, init_data_type: &str) -> bool;
+
+    /// Whether this CDM can create `persistent-license` sessions.
+    fn supports_persistent_license(&self) -> bool;
+
+    /// Provides the license server certificate, returning whether the CDM
+    /// makes use of it.
+    fn set_server_certificate(&mut self, certificate: &[u8]) -> Result<bool, CdmError>;
+
+    /// Creates a session from the given initialization data, returning its
+    /// ID and the license request to send.
+    fn generate_request(
+        &mut self,
+        init_data_type: &str,
+        init_data: &[u8],
+    ) -> Result<(String, Message), CdmError>;
+
+    /// Processes a license server response, returning the statuses of the
+    /// keys of the session.
+    fn update(
+        &mut self,
+        session_id: &str,
+        response: &[u8],
+    ) -> Result<Vec<(Vec<u8>, KeyStatus)>, CdmError>;
+
+    /// Releases the keys of a session, returning their new statuses and the
+    /// license release message to send, if any.
+    fn remove(
+        &mut self,
+        session_id: &str,
+    ) -> Result<(Vec<(Vec<u8>, KeyStatus)>, Option<Message>), CdmError>;
+
+    /// Closes a session, discarding its keys.
+    fn close(&mut self, session_id: &str);
+
+    /// The key with the given ID, if a session holds it.
+    fn key(&self, key_id: &[u8]) -> Option<&[u8]>;
+}
+
+/// Returns a CDM for the given key system, if it is supported.
+pub fn cdm_for_key_system(key_system: &str) -> Option<Box<dyn Cdm>> {
+    match key_system {
+        ClearKey::KEY_SYSTEM => Some(Box::new(ClearKey::new())),
+        _ => None,
+    }
+}
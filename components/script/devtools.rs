@@ -17,6 +17,7 @@ use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::node::{window_from_node, Node, ShadowIncluding};
 use crate::dom::window::Window;
+use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::script_thread::Documents;
 use devtools_traits::TimelineMarkerType;
 use devtools_traits::{AutoMargins, CachedConsoleMessage, CachedConsoleMessageTypes};
@@ -226,6 +227,25 @@ pub fn handle_get_cached_messages(
     reply.send(messages).unwrap();
 }
 
+/// Reply with the console messages logged by a worker so far,
+/// worker scopes do not report page errors to devtools yet.
+pub fn handle_get_cached_worker_messages(
+    worker: &WorkerGlobalScope,
+    message_types: CachedConsoleMessageTypes,
+    reply: IpcSender<Vec<CachedConsoleMessage>>,
+) {
+    let mut messages = Vec::new();
+    if message_types.contains(CachedConsoleMessageTypes::CONSOLE_API) {
+        messages.extend(
+            worker
+                .cached_console_messages()
+                .into_iter()
+                .map(CachedConsoleMessage::ConsoleAPI),
+        );
+    }
+    reply.send(messages).unwrap();
+}
+
 pub fn handle_modify_attribute(
     documents: &Documents,
     pipeline: PipelineId,
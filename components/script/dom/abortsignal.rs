@@ -0,0 +1,391 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::enter_realm;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::{self, AbortSignalMethods};
+use crate::dom::bindings::codegen::Bindings::EventListenerBinding::EventListener;
+use crate::dom::bindings::codegen::Bindings::EventTargetBinding::EventListenerOptions;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::weakref::WeakRef;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::response::Response;
+use crate::dom::scheduler::Scheduler;
+use crate::fetch::FetchCanceller;
+use crate::script_runtime::JSContext;
+use crate::timers::OneshotTimerCallback;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_SetPendingException;
+use js::rust::HandleValue;
+use script_traits::MsDuration;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+/// The steps to run when a signal is aborted,
+/// <https://dom.spec.whatwg.org/#abortsignal-abort-algorithms>
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum AbortAlgorithm {
+    /// <https://fetch.spec.whatwg.org/#abort-fetch>, which also terminates
+    /// the fetch in the net component.
+    Fetch {
+        #[ignore_malloc_size_of = "Rc"]
+        promise: Rc<Promise>,
+        response: Dom<Response>,
+        canceller: FetchCanceller,
+    },
+    /// Rejects the promise of a task posted with `scheduler.postTask()`,
+    /// which then never runs.
+    PostedTask { scheduler: Dom<Scheduler>, id: u64 },
+    /// Shuts down the `pipeTo()` from `source`, aborting its destination and
+    /// canceling `source` unless prevented.
+    PipeTo { source: Dom<ReadableStream> },
+    /// Removes an event listener that was added with this signal.
+    RemoveEventListener {
+        target: Dom<EventTarget>,
+        ty: Atom,
+        #[ignore_malloc_size_of = "Rc"]
+        listener: Rc<EventListener>,
+        capture: bool,
+    },
+}
+
+impl AbortAlgorithm {
+    #[allow(unrooted_must_root)]
+    fn run(self, cx: JSContext, reason: HandleValue) {
+        match self {
+            AbortAlgorithm::Fetch {
+                promise,
+                response,
+                mut canceller,
+            } => {
+                canceller.cancel();
+                let _ac = enter_realm(&*promise);
+                // Step 2.
+                if !promise.is_fulfilled() {
+                    promise.reject(cx, reason);
+                }
+                // Steps 3-5.
+                response.abort_body(cx, reason);
+            },
+            AbortAlgorithm::PostedTask { scheduler, id } => scheduler.abort_task(id, cx, reason),
+            AbortAlgorithm::PipeTo { source } => source.pipe_abort(reason),
+            AbortAlgorithm::RemoveEventListener {
+                target,
+                ty,
+                listener,
+                capture,
+            } => target.remove_event_listener(
+                DOMString::from(&*ty),
+                Some(listener),
+                EventListenerOptions { capture },
+            ),
+        }
+    }
+}
+
+// https://dom.spec.whatwg.org/#interface-AbortSignal
+#[dom_struct]
+pub struct AbortSignal {
+    eventtarget: EventTarget,
+    /// Undefined until the signal is aborted.
+    #[ignore_malloc_size_of = "mozjs"]
+    reason: Heap<JSVal>,
+    abort_algorithms: DomRefCell<Vec<AbortAlgorithm>>,
+    dependent: Cell<bool>,
+    source_signals: DomRefCell<Vec<Dom<AbortSignal>>>,
+    /// Weak, so that a long-lived source signal does not keep alive
+    /// every signal that ever depended on it.
+    dependent_signals: DomRefCell<Vec<WeakRef<AbortSignal>>>,
+}
+
+impl AbortSignal {
+    pub fn new_inherited() -> AbortSignal {
+        AbortSignal {
+            eventtarget: EventTarget::new_inherited(),
+            reason: Heap::default(),
+            abort_algorithms: DomRefCell::new(vec![]),
+            dependent: Cell::new(false),
+            source_signals: DomRefCell::new(vec![]),
+            dependent_signals: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<AbortSignal> {
+        reflect_dom_object(
+            Box::new(AbortSignal::new_inherited()),
+            global,
+            AbortSignalBinding::Wrap,
+        )
+    }
+
+    /// <https://dom.spec.whatwg.org/#create-a-dependent-abort-signal>
+    pub fn new_dependent(
+        global: &GlobalScope,
+        signals: &[DomRoot<AbortSignal>],
+    ) -> DomRoot<AbortSignal> {
+        // Step 1.
+        let result = AbortSignal::new(global);
+
+        // Step 2.
+        if let Some(signal) = signals.iter().find(|signal| signal.aborted()) {
+            result.reason.set(signal.reason.get());
+            return result;
+        }
+
+        // Step 3.
+        result.dependent.set(true);
+
+        // Step 4.
+        for signal in signals {
+            if !signal.dependent.get() {
+                result.add_source_signal(signal);
+            } else {
+                for source in signal.source_signals.borrow().iter() {
+                    result.add_source_signal(source);
+                }
+            }
+        }
+
+        // Step 5.
+        result
+    }
+
+    fn add_source_signal(&self, source: &AbortSignal) {
+        let mut source_signals = self.source_signals.borrow_mut();
+        if source_signals
+            .iter()
+            .any(|signal| ptr::eq(&**signal, source))
+        {
+            return;
+        }
+        source_signals.push(Dom::from_ref(source));
+        // Dependent signals are created by `new_dependent`, so `self` is never
+        // a `TaskSignal`, whose reflector has no slot for weak references.
+        let mut dependent_signals = source.dependent_signals.borrow_mut();
+        dependent_signals.retain(WeakRef::is_alive);
+        dependent_signals.push(WeakRef::new(self));
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-aborted>
+    pub fn aborted(&self) -> bool {
+        !self.reason.get().is_undefined()
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-abort-reason>
+    pub fn reason(&self) -> JSVal {
+        self.reason.get()
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-add>
+    pub fn add_abort_algorithm(&self, algorithm: AbortAlgorithm) {
+        if self.aborted() {
+            return;
+        }
+        self.abort_algorithms.borrow_mut().push(algorithm);
+    }
+
+    /// Removes the abort algorithm of a fetch, once its response has been fully received.
+    pub fn remove_fetch(&self, fetched: &Response) {
+        self.abort_algorithms
+            .borrow_mut()
+            .retain(|algorithm| match *algorithm {
+                AbortAlgorithm::Fetch { ref response, .. } => !ptr::eq(&**response, fetched),
+                _ => true,
+            });
+    }
+
+    /// Removes the abort algorithm of an event listener, once it has been
+    /// removed from its target otherwise.
+    pub fn remove_listener(
+        &self,
+        target: &EventTarget,
+        ty: &Atom,
+        listener: &Rc<EventListener>,
+        capture: bool,
+    ) {
+        self.abort_algorithms
+            .borrow_mut()
+            .retain(|algorithm| match *algorithm {
+                AbortAlgorithm::RemoveEventListener {
+                    target: ref added_to,
+                    ty: ref added_ty,
+                    listener: ref added,
+                    capture: added_capture,
+                } => {
+                    !ptr::eq(&**added_to, target) ||
+                        added_ty != ty ||
+                        added != listener ||
+                        added_capture != capture
+                },
+                _ => true,
+            });
+    }
+
+    /// Removes the abort algorithm of a posted task, once it ran.
+    /// <https://dom.spec.whatwg.org/#abortsignal-remove>
+    pub fn remove_posted_task(&self, scheduler: &Scheduler, id: u64) {
+        self.abort_algorithms
+            .borrow_mut()
+            .retain(|algorithm| match *algorithm {
+                AbortAlgorithm::PostedTask {
+                    scheduler: ref posted_to,
+                    id: posted_id,
+                } => !ptr::eq(&**posted_to, scheduler) || posted_id != id,
+                _ => true,
+            });
+    }
+
+    /// Removes the abort algorithm of a `pipeTo()`, once it finished.
+    pub fn remove_pipe(&self, piped: &ReadableStream) {
+        self.abort_algorithms
+            .borrow_mut()
+            .retain(|algorithm| match *algorithm {
+                AbortAlgorithm::PipeTo { ref source } => !ptr::eq(&**source, piped),
+                _ => true,
+            });
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-signal-abort>
+    #[allow(unsafe_code)]
+    pub fn signal_abort(&self, cx: JSContext, reason: HandleValue) {
+        // Step 1.
+        if self.aborted() {
+            return;
+        }
+
+        // Step 2.
+        if reason.is_undefined() {
+            rooted!(in(*cx) let mut error = UndefinedValue());
+            unsafe { Error::Abort.to_jsval(*cx, &self.global(), error.handle_mut()) };
+            self.reason.set(error.get());
+        } else {
+            self.reason.set(reason.get());
+        }
+
+        // Steps 3-4.
+        let dependent_signals: Vec<DomRoot<AbortSignal>> = self
+            .dependent_signals
+            .borrow()
+            .iter()
+            .filter_map(WeakRef::root)
+            .filter(|signal| !signal.aborted())
+            .collect();
+        for signal in &dependent_signals {
+            signal.reason.set(self.reason.get());
+        }
+
+        // Step 5.
+        self.run_abort_steps(cx);
+
+        // Step 6.
+        for signal in dependent_signals {
+            signal.run_abort_steps(cx);
+        }
+    }
+
+    /// <https://dom.spec.whatwg.org/#run-the-abort-steps>
+    #[allow(unrooted_must_root)]
+    fn run_abort_steps(&self, cx: JSContext) {
+        // Steps 1-2.
+        let algorithms = mem::replace(&mut *self.abort_algorithms.borrow_mut(), vec![]);
+        rooted!(in(*cx) let reason = self.reason.get());
+        for algorithm in algorithms {
+            algorithm.run(cx, reason.handle());
+        }
+
+        // Step 3.
+        self.upcast::<EventTarget>().fire_event(atom!("abort"));
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-abort
+    pub fn Abort(cx: JSContext, global: &GlobalScope, reason: HandleValue) -> DomRoot<AbortSignal> {
+        // Steps 1-2.
+        let signal = AbortSignal::new(global);
+        signal.signal_abort(cx, reason);
+        // Step 3.
+        signal
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-timeout
+    pub fn Timeout(global: &GlobalScope, milliseconds: u64) -> DomRoot<AbortSignal> {
+        // Step 1.
+        let signal = AbortSignal::new(global);
+
+        // Steps 2-3.
+        let callback = OneshotTimerCallback::AbortSignalTimeout(AbortSignalTimeoutCallback {
+            signal: Trusted::new(&*signal),
+        });
+        global.schedule_callback(callback, MsDuration::new(milliseconds));
+
+        // Step 4.
+        signal
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-any
+    pub fn Any(global: &GlobalScope, signals: Vec<DomRoot<AbortSignal>>) -> DomRoot<AbortSignal> {
+        AbortSignal::new_dependent(global, &signals)
+    }
+}
+
+impl AbortSignalMethods for AbortSignal {
+    // https://dom.spec.whatwg.org/#dom-abortsignal-aborted
+    fn Aborted(&self) -> bool {
+        self.aborted()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-reason
+    fn Reason(&self, _cx: JSContext) -> JSVal {
+        self.reason.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-throwifaborted
+    #[allow(unsafe_code)]
+    fn ThrowIfAborted(&self) -> ErrorResult {
+        if !self.aborted() {
+            return Ok(());
+        }
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let reason = self.reason.get());
+        unsafe { JS_SetPendingException(*cx, reason.handle()) };
+        Err(Error::JSFailed)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+}
+
+/// Aborts the signal returned by `AbortSignal.timeout()` once its time has elapsed.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct AbortSignalTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    signal: Trusted<AbortSignal>,
+}
+
+impl AbortSignalTimeoutCallback {
+    // https://dom.spec.whatwg.org/#dom-abortsignal-timeout
+    #[allow(unsafe_code)]
+    pub fn invoke(self) {
+        let signal = self.signal.root();
+        let global = signal.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*signal);
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        unsafe { Error::Timeout.to_jsval(*cx, &global, error.handle_mut()) };
+        signal.signal_abort(cx, error.handle());
+    }
+}
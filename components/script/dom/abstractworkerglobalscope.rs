@@ -5,6 +5,7 @@
 use crate::dom::abstractworker::WorkerScriptMsg;
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
 use crate::dom::dedicatedworkerglobalscope::{AutoWorkerReset, DedicatedWorkerScriptMsg};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::worker::TrustedWorkerAddress;
@@ -13,6 +14,7 @@ use crate::script_runtime::{CommonScriptMsg, ScriptChan, ScriptPort};
 use crate::task_queue::{QueuedTaskConversion, TaskQueue};
 use crossbeam_channel::{Receiver, Sender};
 use devtools_traits::DevtoolScriptControlMsg;
+use js::jsapi::JSContext;
 
 /// A ScriptChan that can be cloned freely and will silently send a TrustedWorkerAddress with
 /// common event loop messages. While this SendableWorkerScriptChan is alive, the associated
@@ -110,7 +112,11 @@ pub fn run_worker_event_loop<T, WorkerMsg, Event>(
     let event = select! {
         recv(task_queue.select()) -> msg => {
             task_queue.take_tasks(msg.unwrap());
-            worker_scope.from_worker_msg(task_queue.recv().unwrap())
+            match task_queue.recv() {
+                Ok(msg) => worker_scope.from_worker_msg(msg),
+                // Only a wake-up was received, as when the worker is closing.
+                Err(_) => return,
+            }
         },
         recv(devtools_port.unwrap_or(&crossbeam_channel::never())) -> msg =>
             worker_scope.from_devtools_msg(msg.unwrap()),
@@ -136,6 +142,10 @@ pub fn run_worker_event_loop<T, WorkerMsg, Event>(
     }
     // Step 3
     for event in sequential {
+        // The tasks of a terminated worker are discarded.
+        if scope.is_terminated() {
+            return;
+        }
         worker_scope.handle_event(event);
         // Step 6
         let _ar = match worker {
@@ -150,3 +160,13 @@ pub fn run_worker_event_loop<T, WorkerMsg, Event>(
         .upcast::<GlobalScope>()
         .perform_a_message_port_garbage_collection_checkpoint();
 }
+
+/// The interrupt callback of a worker runtime, set with `JS_AddInterruptCallback`.
+#[allow(unsafe_code)]
+pub unsafe extern "C" fn interrupt_callback(cx: *mut JSContext) -> bool {
+    let worker = DomRoot::downcast::<WorkerGlobalScope>(GlobalScope::from_context(cx))
+        .expect("global is not a worker scope");
+
+    // A false response causes the script to terminate
+    !worker.is_closing()
+}
@@ -0,0 +1,764 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationeffect::{AnimationEffect, Timing};
+use crate::dom::animationplaybackevent::AnimationPlaybackEvent;
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::{
+    self, AnimationMethods, AnimationPlayState,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::keyframeeffect::KeyframeEffect;
+use crate::dom::node::{Node, NodeDamage};
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use script_layout_interface::message::Msg;
+use script_traits::MsDuration;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
+use style::animation::{Animation as StyleAnimation, ScriptAnimationState};
+use style::stylesheets::keyframes_rule::KeyframesAnimation;
+
+// https://drafts.csswg.org/web-animations/#the-animation-interface
+#[dom_struct]
+pub struct Animation {
+    eventtarget: EventTarget,
+    /// The id layout knows the animation by.
+    layout_id: u64,
+    id: DomRefCell<DOMString>,
+    effect: MutNullableDom<AnimationEffect>,
+    timeline: Option<Dom<AnimationTimeline>>,
+    /// <https://drafts.csswg.org/web-animations/#animation-start-time>, in
+    /// milliseconds of the timeline.
+    start_time: Cell<Option<f64>>,
+    /// <https://drafts.csswg.org/web-animations/#animation-hold-time>
+    hold_time: Cell<Option<f64>>,
+    /// <https://drafts.csswg.org/web-animations/#previous-current-time>
+    previous_current_time: Cell<Option<f64>>,
+    playback_rate: Cell<f64>,
+    /// The ready promise, which is always resolved since the play and pause
+    /// tasks run as soon as they are scheduled.
+    #[ignore_malloc_size_of = "Rc"]
+    ready_promise: Rc<Promise>,
+    #[ignore_malloc_size_of = "Rc"]
+    finished_promise: DomRefCell<Rc<Promise>>,
+    /// Whether the finished promise was resolved, and the finish event
+    /// queued, since the animation last became finished.
+    finish_notified: Cell<bool>,
+    /// The element layout runs the animation on, if any.
+    layout_target: MutNullableDom<Element>,
+    /// The timer that updates the finished state when the animation reaches
+    /// its end.
+    finish_timer: DomRefCell<Option<OneshotTimerHandle>>,
+}
+
+impl Animation {
+    fn new_inherited(window: &Window, timeline: Option<&AnimationTimeline>) -> Animation {
+        let global = window.upcast::<GlobalScope>();
+        Animation {
+            eventtarget: EventTarget::new_inherited(),
+            layout_id: window.Document().next_animation_id(),
+            id: DomRefCell::new(DOMString::new()),
+            effect: MutNullableDom::new(None),
+            timeline: timeline.map(Dom::from_ref),
+            start_time: Cell::new(None),
+            hold_time: Cell::new(None),
+            previous_current_time: Cell::new(None),
+            playback_rate: Cell::new(1.),
+            ready_promise: Promise::new(global),
+            finished_promise: DomRefCell::new(Promise::new(global)),
+            finish_notified: Cell::new(false),
+            layout_target: MutNullableDom::new(None),
+            finish_timer: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<&AnimationTimeline>,
+    ) -> DomRoot<Animation> {
+        let animation = reflect_dom_object(
+            Box::new(Animation::new_inherited(window, timeline)),
+            window,
+            AnimationBinding::Wrap,
+        );
+        animation.ready_promise.resolve_native(&animation);
+        if let Some(effect) = effect {
+            animation.set_effect(Some(effect));
+        }
+        animation
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-animation
+    pub fn Constructor(
+        window: &Window,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<Option<&AnimationTimeline>>,
+    ) -> Fallible<DomRoot<Animation>> {
+        let document_timeline;
+        let timeline = match timeline {
+            Some(timeline) => timeline,
+            None => {
+                document_timeline = window.Document().timeline();
+                Some(document_timeline.upcast::<AnimationTimeline>())
+            },
+        };
+        Ok(Animation::new(window, effect, timeline))
+    }
+
+    fn document(&self) -> DomRoot<Document> {
+        self.global().as_window().Document()
+    }
+
+    fn timeline_time(&self) -> Option<f64> {
+        self.timeline
+            .as_ref()
+            .and_then(|timeline| timeline.current_time())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#associated-effect-end>
+    fn effect_end(&self) -> f64 {
+        self.effect
+            .get()
+            .map_or(0., |effect| effect.timing().end_time())
+    }
+
+    /// The id layout knows the animation by, which also orders the
+    /// animations by when they were created.
+    pub fn layout_id(&self) -> u64 {
+        self.layout_id
+    }
+
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate.get()
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#animation-current-time>
+    pub fn current_time(&self) -> Option<f64> {
+        if let Some(hold_time) = self.hold_time.get() {
+            return Some(hold_time);
+        }
+        let timeline_time = self.timeline_time()?;
+        let start_time = self.start_time.get()?;
+        Some((timeline_time - start_time) * self.playback_rate.get())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#play-states>
+    fn play_state(&self) -> AnimationPlayState {
+        let current_time = self.current_time();
+        if current_time.is_none() && self.start_time.get().is_none() {
+            return AnimationPlayState::Idle;
+        }
+        if self.start_time.get().is_none() {
+            return AnimationPlayState::Paused;
+        }
+        let rate = self.playback_rate.get();
+        match current_time {
+            Some(current_time)
+                if (rate > 0. && current_time >= self.effect_end()) ||
+                    (rate < 0. && current_time <= 0.) =>
+            {
+                AnimationPlayState::Finished
+            },
+            _ => AnimationPlayState::Running,
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#relevant-animations>
+    pub fn is_relevant(&self) -> bool {
+        match self.play_state() {
+            AnimationPlayState::Idle => false,
+            AnimationPlayState::Finished => self
+                .effect
+                .get()
+                .map_or(false, |effect| effect.is_in_effect()),
+            _ => true,
+        }
+    }
+
+    /// The target of the keyframe effect of the animation, if any.
+    pub fn target(&self) -> Option<DomRoot<Element>> {
+        self.effect
+            .get()
+            .and_then(DomRoot::downcast::<KeyframeEffect>)
+            .and_then(|effect| effect.target())
+    }
+
+    /// The element that layout runs the animation on, if any.
+    pub fn layout_target(&self) -> Option<DomRoot<Element>> {
+        self.layout_target.get()
+    }
+
+    /// Makes the pending play or pause task run, which happens right away
+    /// since the animation doesn't wait for anything to be ready.
+    ///
+    /// <https://drafts.csswg.org/web-animations/#playing-an-animation-section>
+    fn commit_pending_play(&self) {
+        let timeline_time = match self.timeline_time() {
+            Some(timeline_time) => timeline_time,
+            None => return,
+        };
+        if let Some(hold_time) = self.hold_time.get() {
+            let rate = self.playback_rate.get();
+            if rate == 0. {
+                self.start_time.set(Some(timeline_time));
+            } else {
+                self.start_time.set(Some(timeline_time - hold_time / rate));
+                self.hold_time.set(None);
+            }
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#play-an-animation>
+    fn play(&self, auto_rewind: bool) -> ErrorResult {
+        // Steps 1-6.
+        let current_time = self.current_time();
+        let end = self.effect_end();
+        let rate = self.playback_rate.get();
+        if rate > 0. && auto_rewind && current_time.map_or(true, |time| time < 0. || time >= end) {
+            self.hold_time.set(Some(0.));
+        } else if rate < 0. &&
+            auto_rewind &&
+            current_time.map_or(true, |time| time <= 0. || time > end)
+        {
+            if end.is_infinite() {
+                return Err(Error::InvalidState);
+            }
+            self.hold_time.set(Some(end));
+        } else if rate == 0. && current_time.is_none() {
+            self.hold_time.set(Some(0.));
+        }
+
+        // Steps 7-11.
+        if self.hold_time.get().is_some() {
+            self.start_time.set(None);
+        }
+        self.commit_pending_play();
+
+        // Step 12.
+        self.update_finished_state(false);
+        self.changed();
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#pause-an-animation>
+    fn pause(&self) -> ErrorResult {
+        // Step 2.
+        if self.play_state() == AnimationPlayState::Paused {
+            return Ok(());
+        }
+
+        // Steps 3-4.
+        if self.current_time().is_none() {
+            if self.playback_rate.get() >= 0. {
+                self.hold_time.set(Some(0.));
+            } else {
+                let end = self.effect_end();
+                if end.is_infinite() {
+                    return Err(Error::InvalidState);
+                }
+                self.hold_time.set(Some(end));
+            }
+        }
+
+        // The pending pause task.
+        self.hold_time.set(self.current_time());
+        self.start_time.set(None);
+
+        self.update_finished_state(false);
+        self.changed();
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#silently-set-the-current-time>
+    fn silently_set_current_time(&self, seek_time: Option<f64>) -> ErrorResult {
+        // Step 1.
+        let seek_time = match seek_time {
+            Some(seek_time) => seek_time,
+            None if self.current_time().is_some() => {
+                return Err(Error::Type(
+                    "The current time can't be set to null".to_owned(),
+                ));
+            },
+            None => return Ok(()),
+        };
+
+        // Step 2.
+        let rate = self.playback_rate.get();
+        let timeline_time = self.timeline_time();
+        match timeline_time {
+            Some(timeline_time)
+                if self.hold_time.get().is_none() &&
+                    self.start_time.get().is_some() &&
+                    rate != 0. =>
+            {
+                self.start_time.set(Some(timeline_time - seek_time / rate));
+            }
+            _ => self.hold_time.set(Some(seek_time)),
+        }
+
+        // Step 3.
+        if timeline_time.is_none() {
+            self.start_time.set(None);
+        }
+
+        // Step 4.
+        self.previous_current_time.set(None);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#set-the-playback-rate>
+    fn set_playback_rate(&self, rate: f64) {
+        let previous_time = self.current_time();
+        self.playback_rate.set(rate);
+        if previous_time.is_some() {
+            let _ = self.silently_set_current_time(previous_time);
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#update-an-animations-finished-state>
+    fn update_finished_state(&self, did_seek: bool) {
+        // Step 2.
+        let current_time = self.current_time();
+        let rate = self.playback_rate.get();
+        if let (Some(current_time), Some(_)) = (current_time, self.start_time.get()) {
+            let end = self.effect_end();
+            let previous_time = self.previous_current_time.get();
+            if rate > 0. && current_time >= end {
+                self.hold_time.set(Some(if did_seek {
+                    current_time
+                } else {
+                    previous_time.map_or(end, |time| time.max(end))
+                }));
+            } else if rate < 0. && current_time <= 0. {
+                self.hold_time.set(Some(if did_seek {
+                    current_time
+                } else {
+                    previous_time.map_or(0., |time| time.min(0.))
+                }));
+            } else if rate != 0. {
+                if let (true, Some(hold_time), Some(timeline_time)) =
+                    (did_seek, self.hold_time.get(), self.timeline_time())
+                {
+                    self.start_time.set(Some(timeline_time - hold_time / rate));
+                }
+                self.hold_time.set(None);
+            }
+        }
+
+        // Step 3.
+        self.previous_current_time.set(self.current_time());
+
+        // Steps 4-6.
+        if self.play_state() == AnimationPlayState::Finished {
+            if !self.finish_notified.get() {
+                self.finish_notified.set(true);
+                self.finished_promise
+                    .borrow()
+                    .resolve_native(&DomRoot::from_ref(self));
+                self.queue_event(atom!("finish"), self.current_time());
+            }
+        } else if self.finish_notified.get() {
+            self.finish_notified.set(false);
+            *self.finished_promise.borrow_mut() = Promise::new(&self.global());
+        }
+    }
+
+    /// Queues an `AnimationPlaybackEvent` at the animation.
+    fn queue_event(&self, type_: Atom, current_time: Option<f64>) {
+        let global = self.global();
+        let this = Trusted::new(self);
+        let timeline_time = self.timeline_time();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(fire_animation_playback_event: move || {
+                let animation = this.root();
+                let event = AnimationPlaybackEvent::new(
+                    animation.global().as_window(),
+                    type_,
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::NotCancelable,
+                    current_time.map(Finite::wrap),
+                    timeline_time.map(Finite::wrap),
+                );
+                event.upcast::<Event>().fire(animation.upcast());
+            }),
+            &global,
+        );
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#setting-the-associated-effect>
+    fn set_effect(&self, effect: Option<&AnimationEffect>) {
+        if let Some(effect) = effect {
+            // The effect leaves the animation it was associated with.
+            if let Some(animation) = effect.animation() {
+                if &*animation != self {
+                    animation.SetEffect(None);
+                }
+            }
+        }
+        if let Some(old_effect) = self.effect.get() {
+            old_effect.set_animation(None);
+        }
+        self.effect.set(effect);
+        if let Some(effect) = effect {
+            effect.set_animation(Some(self));
+        }
+        self.effect_changed();
+    }
+
+    /// Updates the animation after the timing or the keyframes of its
+    /// effect changed.
+    pub fn effect_changed(&self) {
+        self.update_finished_state(false);
+        self.changed();
+    }
+
+    /// Lets layout, the document and the finish timer know the animation
+    /// changed.
+    fn changed(&self) {
+        let document = self.document();
+        if self.is_relevant() {
+            document.add_animation(self);
+        } else {
+            document.remove_animation(self);
+        }
+        self.update_layout(&document);
+        self.schedule_finish_timer();
+    }
+
+    /// Sends the state of the animation to layout, which runs it until it
+    /// gets another one.
+    fn update_layout(&self, document: &Document) {
+        let window = document.window();
+        let effect = self
+            .effect
+            .get()
+            .and_then(DomRoot::downcast::<KeyframeEffect>);
+        let target = effect.as_ref().and_then(|effect| effect.target());
+        let current_time = self.current_time();
+
+        // The animation stops running on the element it ran on when it isn't
+        // running on it anymore.
+        if let Some(previous_target) = self.layout_target.get() {
+            let moved = match (&target, current_time) {
+                (Some(target), Some(_)) => *target != previous_target,
+                _ => true,
+            };
+            if moved {
+                let keyframes = KeyframesAnimation::from_keyframes(
+                    &[],
+                    None,
+                    &document.style_shared_lock().read(),
+                );
+                let state = ScriptAnimationState {
+                    timing: Timing::default().to_style(),
+                    current_time: 0.,
+                    playback_rate: 1.,
+                    paused: true,
+                    synced_at: None,
+                    expired: true,
+                    cascade_style: None,
+                };
+                self.send_to_layout(window, &previous_target, keyframes, state);
+                self.layout_target.set(None);
+            }
+        }
+
+        let (effect, target, current_time) = match (effect, target, current_time) {
+            (Some(effect), Some(target), Some(current_time)) => (effect, target, current_time),
+            _ => return,
+        };
+        let mut timing = effect.upcast::<AnimationEffect>().timing();
+        // Layout runs animations in seconds.
+        timing.delay /= 1000.;
+        timing.end_delay /= 1000.;
+        timing.duration /= 1000.;
+        let state = ScriptAnimationState {
+            timing,
+            current_time: current_time / 1000.,
+            playback_rate: self.playback_rate.get(),
+            paused: self.hold_time.get().is_some() || self.start_time.get().is_none(),
+            synced_at: None,
+            expired: false,
+            cascade_style: None,
+        };
+        let keyframes = effect.style_keyframes(document);
+        self.send_to_layout(window, &target, keyframes, state);
+        self.layout_target.set(Some(&target));
+    }
+
+    fn send_to_layout(
+        &self,
+        window: &Window,
+        target: &Element,
+        keyframes: KeyframesAnimation,
+        state: ScriptAnimationState,
+    ) {
+        let node = target.upcast::<Node>();
+        let animation = StyleAnimation::Script(node.to_opaque(), keyframes, self.layout_id, state);
+        if window
+            .layout_chan()
+            .send(Msg::UpdateScriptAnimation(animation))
+            .is_err()
+        {
+            warn!("Layout thread unavailable to update an animation");
+        }
+        node.dirty(NodeDamage::NodeStyleDamaged);
+    }
+
+    /// Schedules the update of the finished state for when the animation
+    /// reaches its end, or its start if it plays backwards.
+    fn schedule_finish_timer(&self) {
+        let global = self.global();
+        if let Some(handle) = self.finish_timer.borrow_mut().take() {
+            global.unschedule_callback(handle);
+        }
+        if self.play_state() != AnimationPlayState::Running {
+            return;
+        }
+        let current_time = match self.current_time() {
+            Some(current_time) => current_time,
+            None => return,
+        };
+        let rate = self.playback_rate.get();
+        let remaining = if rate > 0. {
+            (self.effect_end() - current_time) / rate
+        } else if rate < 0. {
+            current_time / -rate
+        } else {
+            return;
+        };
+        if !remaining.is_finite() {
+            return;
+        }
+        let callback = OneshotTimerCallback::AnimationFinish(AnimationFinishCallback {
+            animation: Trusted::new(self),
+        });
+        *self.finish_timer.borrow_mut() = Some(
+            global.schedule_callback(callback, MsDuration::new(remaining.max(0.).ceil() as u64)),
+        );
+    }
+}
+
+impl AnimationMethods for Animation {
+    // https://drafts.csswg.org/web-animations/#dom-animation-id
+    fn Id(&self) -> DOMString {
+        self.id.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-id
+    fn SetId(&self, id: DOMString) {
+        *self.id.borrow_mut() = id;
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-effect
+    fn GetEffect(&self) -> Option<DomRoot<AnimationEffect>> {
+        self.effect.get()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-effect
+    fn SetEffect(&self, effect: Option<&AnimationEffect>) {
+        if self.effect.get().as_deref() == effect {
+            return;
+        }
+        self.set_effect(effect);
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-timeline
+    fn GetTimeline(&self) -> Option<DomRoot<AnimationTimeline>> {
+        self.timeline
+            .as_ref()
+            .map(|timeline| DomRoot::from_ref(&**timeline))
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-starttime
+    fn GetStartTime(&self) -> Option<Finite<f64>> {
+        self.start_time.get().map(Finite::wrap)
+    }
+
+    // https://drafts.csswg.org/web-animations/#set-the-start-time
+    fn SetStartTime(&self, start_time: Option<Finite<f64>>) {
+        let start_time = start_time.map(|start_time| *start_time);
+        // Step 2.
+        if self.timeline_time().is_none() && start_time.is_some() {
+            self.hold_time.set(None);
+        }
+
+        // Steps 3-4.
+        let previous_time = self.current_time();
+        self.start_time.set(start_time);
+
+        // Step 5.
+        if start_time.is_some() {
+            if self.playback_rate.get() != 0. {
+                self.hold_time.set(None);
+            }
+        } else {
+            self.hold_time.set(previous_time);
+        }
+
+        // Step 7.
+        self.update_finished_state(true);
+        self.changed();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+
+    // https://drafts.csswg.org/web-animations/#set-the-current-time
+    fn SetCurrentTime(&self, current_time: Option<Finite<f64>>) -> ErrorResult {
+        self.silently_set_current_time(current_time.map(|current_time| *current_time))?;
+        self.update_finished_state(true);
+        self.changed();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-playbackrate
+    fn PlaybackRate(&self) -> Finite<f64> {
+        Finite::wrap(self.playback_rate.get())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-playbackrate
+    fn SetPlaybackRate(&self, rate: Finite<f64>) {
+        self.set_playback_rate(*rate);
+        self.changed();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-playstate
+    fn PlayState(&self) -> AnimationPlayState {
+        self.play_state()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-pending
+    fn Pending(&self) -> bool {
+        false
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-finished
+    fn Finished(&self) -> Rc<Promise> {
+        self.finished_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-onfinish
+    event_handler!(finish, GetOnfinish, SetOnfinish);
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-oncancel
+    event_handler!(cancel, GetOncancel, SetOncancel);
+
+    // https://drafts.csswg.org/web-animations/#cancel-an-animation
+    fn Cancel(&self) {
+        if self.play_state() != AnimationPlayState::Idle {
+            // Step 1.
+            self.finished_promise.borrow().reject_error(Error::Abort);
+            self.finish_notified.set(false);
+            *self.finished_promise.borrow_mut() = Promise::new(&self.global());
+            self.queue_event(atom!("cancel"), None);
+        }
+
+        // Steps 2-4.
+        self.hold_time.set(None);
+        self.start_time.set(None);
+        self.previous_current_time.set(None);
+        self.changed();
+    }
+
+    // https://drafts.csswg.org/web-animations/#finish-an-animation
+    fn Finish(&self) -> ErrorResult {
+        // Step 1.
+        let rate = self.playback_rate.get();
+        let end = self.effect_end();
+        if rate == 0. || (rate > 0. && end.is_infinite()) {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 3-4.
+        let limit = if rate > 0. { end } else { 0. };
+        self.silently_set_current_time(Some(limit))?;
+
+        // Step 5.
+        if let (None, Some(timeline_time)) = (self.start_time.get(), self.timeline_time()) {
+            self.start_time.set(Some(timeline_time - limit / rate));
+        }
+
+        // Step 8.
+        self.update_finished_state(true);
+        self.changed();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-play
+    fn Play(&self) -> ErrorResult {
+        self.play(true)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-pause
+    fn Pause(&self) -> ErrorResult {
+        self.pause()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-updateplaybackrate
+    fn UpdatePlaybackRate(&self, rate: Finite<f64>) {
+        self.set_playback_rate(*rate);
+        self.update_finished_state(false);
+        self.changed();
+    }
+
+    // https://drafts.csswg.org/web-animations/#reverse-an-animation
+    fn Reverse(&self) -> ErrorResult {
+        // Step 1.
+        if self.timeline_time().is_none() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 2-4.
+        let original_rate = self.playback_rate.get();
+        self.set_playback_rate(-original_rate);
+        let result = self.play(true);
+        if result.is_err() {
+            self.set_playback_rate(original_rate);
+        }
+        result
+    }
+}
+
+/// Updates the finished state of an animation once it reaches its end.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct AnimationFinishCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    animation: Trusted<Animation>,
+}
+
+impl AnimationFinishCallback {
+    pub fn invoke(self) {
+        let animation = self.animation.root();
+        animation.finish_timer.borrow_mut().take();
+        animation.update_finished_state(false);
+        animation.changed();
+    }
+}
@@ -0,0 +1,316 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animation::Animation;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::{
+    AnimationEffectMethods, ComputedEffectTiming, EffectTiming, FillMode, OptionalEffectTiming,
+    PlaybackDirection,
+};
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrString;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use cssparser::{Parser, ParserInput};
+use dom_struct::dom_struct;
+use servo_url::ServoUrl;
+use style::animation::ScriptAnimationTiming;
+use style::context::QuirksMode;
+use style::parser::{Parse, ParserContext};
+use style::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
+use style::stylesheets::{CssRuleType, Origin};
+use style::values::computed::TimingFunction;
+use style::values::generics::easing::TimingKeyword;
+use style::values::specified::TimingFunction as SpecifiedTimingFunction;
+use style_traits::ParsingMode;
+
+/// The timing of an animation effect, in milliseconds.
+///
+/// <https://drafts.csswg.org/web-animations/#the-effecttiming-dictionaries>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct Timing {
+    delay: f64,
+    end_delay: f64,
+    fill: FillMode,
+    iteration_start: f64,
+    iterations: f64,
+    /// None for `auto`.
+    duration: Option<f64>,
+    direction: PlaybackDirection,
+    easing: DOMString,
+}
+
+impl Default for Timing {
+    fn default() -> Timing {
+        Timing {
+            delay: 0.,
+            end_delay: 0.,
+            fill: FillMode::Auto,
+            iteration_start: 0.,
+            iterations: 1.,
+            duration: None,
+            direction: PlaybackDirection::Normal,
+            easing: DOMString::from("linear"),
+        }
+    }
+}
+
+impl Timing {
+    /// The timing of an effect that script gave a duration for, such as with
+    /// `element.animate(keyframes, 1000)`.
+    pub fn from_duration(duration: f64) -> Fallible<Timing> {
+        Ok(Timing {
+            duration: validate_duration(&UnrestrictedDoubleOrString::UnrestrictedDouble(duration))?,
+            ..Timing::default()
+        })
+    }
+
+    pub fn from_dictionary(timing: &EffectTiming) -> Fallible<Timing> {
+        let mut result = Timing::default();
+        result.update(&OptionalEffectTiming {
+            delay: Some(timing.delay),
+            endDelay: Some(timing.endDelay),
+            fill: Some(timing.fill),
+            iterationStart: Some(timing.iterationStart),
+            iterations: Some(timing.iterations),
+            duration: Some(timing.duration.clone()),
+            direction: Some(timing.direction),
+            easing: Some(timing.easing.clone()),
+        })?;
+        Ok(result)
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#update-the-timing-properties-of-an-animation-effect>
+    fn update(&mut self, timing: &OptionalEffectTiming) -> ErrorResult {
+        // Step 1. Nothing changes if any of the properties is invalid.
+        if let Some(iteration_start) = timing.iterationStart {
+            if *iteration_start < 0. {
+                return Err(Error::Type(
+                    "iterationStart must not be negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(iterations) = timing.iterations {
+            if iterations < 0. || iterations.is_nan() {
+                return Err(Error::Type(
+                    "iterations must be a positive number".to_owned(),
+                ));
+            }
+        }
+        let duration = match timing.duration {
+            Some(ref duration) => Some(validate_duration(duration)?),
+            None => None,
+        };
+        if let Some(ref easing) = timing.easing {
+            parse_easing(easing)?;
+        }
+
+        // Step 2.
+        if let Some(delay) = timing.delay {
+            self.delay = *delay;
+        }
+        if let Some(end_delay) = timing.endDelay {
+            self.end_delay = *end_delay;
+        }
+        if let Some(fill) = timing.fill {
+            self.fill = fill;
+        }
+        if let Some(iteration_start) = timing.iterationStart {
+            self.iteration_start = *iteration_start;
+        }
+        if let Some(iterations) = timing.iterations {
+            self.iterations = iterations;
+        }
+        if let Some(duration) = duration {
+            self.duration = duration;
+        }
+        if let Some(direction) = timing.direction {
+            self.direction = direction;
+        }
+        if let Some(ref easing) = timing.easing {
+            self.easing = easing.clone();
+        }
+        Ok(())
+    }
+
+    fn to_dictionary(&self) -> EffectTiming {
+        EffectTiming {
+            delay: Finite::wrap(self.delay),
+            endDelay: Finite::wrap(self.end_delay),
+            fill: self.fill,
+            iterationStart: Finite::wrap(self.iteration_start),
+            iterations: self.iterations,
+            duration: match self.duration {
+                Some(duration) => UnrestrictedDoubleOrString::UnrestrictedDouble(duration),
+                None => UnrestrictedDoubleOrString::String(DOMString::from("auto")),
+            },
+            direction: self.direction,
+            easing: self.easing.clone(),
+        }
+    }
+
+    /// The timing of the effect as layout runs it, in milliseconds.
+    pub fn to_style(&self) -> ScriptAnimationTiming {
+        // The `auto` fill mode is `none` for keyframe effects.
+        let (fill_backwards, fill_forwards) = match self.fill {
+            FillMode::None | FillMode::Auto => (false, false),
+            FillMode::Forwards => (false, true),
+            FillMode::Backwards => (true, false),
+            FillMode::Both => (true, true),
+        };
+        ScriptAnimationTiming {
+            delay: self.delay,
+            end_delay: self.end_delay,
+            fill_backwards,
+            fill_forwards,
+            iteration_start: self.iteration_start,
+            iterations: self.iterations,
+            // The `auto` duration is zero for keyframe effects.
+            duration: self.duration.unwrap_or(0.),
+            direction: match self.direction {
+                PlaybackDirection::Normal => AnimationDirection::Normal,
+                PlaybackDirection::Reverse => AnimationDirection::Reverse,
+                PlaybackDirection::Alternate => AnimationDirection::Alternate,
+                PlaybackDirection::Alternate_reverse => AnimationDirection::AlternateReverse,
+            },
+            // The easing was validated when it was set.
+            easing: parse_easing(&self.easing)
+                .unwrap_or(TimingFunction::Keyword(TimingKeyword::Linear)),
+        }
+    }
+}
+
+fn validate_duration(duration: &UnrestrictedDoubleOrString) -> Fallible<Option<f64>> {
+    match *duration {
+        UnrestrictedDoubleOrString::UnrestrictedDouble(duration) => {
+            if duration < 0. || duration.is_nan() {
+                return Err(Error::Type("duration must be a positive number".to_owned()));
+            }
+            Ok(Some(duration))
+        },
+        UnrestrictedDoubleOrString::String(ref duration) => {
+            if &**duration != "auto" {
+                return Err(Error::Type("duration must be a number or auto".to_owned()));
+            }
+            Ok(None)
+        },
+    }
+}
+
+/// Parses an easing function, such as `ease-in` or `steps(4)`.
+///
+/// <https://drafts.csswg.org/css-easing/#easing-functions>
+pub fn parse_easing(easing: &str) -> Fallible<TimingFunction> {
+    let mut input = ParserInput::new(easing);
+    let mut parser = Parser::new(&mut input);
+    let url = ServoUrl::parse("about:blank").unwrap();
+    let context = ParserContext::new(
+        Origin::Author,
+        &url,
+        Some(CssRuleType::Style),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+        None,
+    );
+    parser
+        .parse_entirely(|input| SpecifiedTimingFunction::parse(&context, input))
+        .map(|easing| easing.to_computed_value_without_context())
+        .map_err(|_| Error::Type(format!("{} is not a valid easing", easing)))
+}
+
+// https://drafts.csswg.org/web-animations/#the-animationeffect-interface
+#[dom_struct]
+pub struct AnimationEffect {
+    reflector_: Reflector,
+    timing: DomRefCell<Timing>,
+    /// <https://drafts.csswg.org/web-animations/#animation-associated-effect>
+    animation: MutNullableDom<Animation>,
+}
+
+impl AnimationEffect {
+    pub fn new_inherited(timing: Timing) -> AnimationEffect {
+        AnimationEffect {
+            reflector_: Reflector::new(),
+            timing: DomRefCell::new(timing),
+            animation: MutNullableDom::new(None),
+        }
+    }
+
+    pub fn timing(&self) -> ScriptAnimationTiming {
+        self.timing.borrow().to_style()
+    }
+
+    pub fn animation(&self) -> Option<DomRoot<Animation>> {
+        self.animation.get()
+    }
+
+    pub fn set_animation(&self, animation: Option<&Animation>) {
+        self.animation.set(animation);
+    }
+
+    /// Lets the animation of the effect, if any, know that the effect changed.
+    pub fn changed(&self) {
+        if let Some(animation) = self.animation.get() {
+            animation.effect_changed();
+        }
+    }
+
+    /// The local time of the effect, which is the current time of its
+    /// animation, and whether the animation plays backwards.
+    fn local_time(&self) -> Option<(f64, bool)> {
+        let animation = self.animation.get()?;
+        let local_time = animation.current_time()?;
+        Some((local_time, animation.playback_rate() < 0.))
+    }
+
+    /// Whether the effect applies at the current time of its animation.
+    ///
+    /// <https://drafts.csswg.org/web-animations/#in-effect>
+    pub fn is_in_effect(&self) -> bool {
+        self.local_time().map_or(false, |(local_time, backwards)| {
+            self.timing().progress(local_time, backwards).is_some()
+        })
+    }
+}
+
+impl AnimationEffectMethods for AnimationEffect {
+    // https://drafts.csswg.org/web-animations/#dom-animationeffect-gettiming
+    fn GetTiming(&self) -> EffectTiming {
+        self.timing.borrow().to_dictionary()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animationeffect-getcomputedtiming
+    fn GetComputedTiming(&self) -> ComputedEffectTiming {
+        let mut parent = self.timing.borrow().to_dictionary();
+        let timing = self.timing();
+        // The `auto` fill mode and duration resolve to their values for
+        // keyframe effects.
+        if parent.fill == FillMode::Auto {
+            parent.fill = FillMode::None;
+        }
+        parent.duration = UnrestrictedDoubleOrString::UnrestrictedDouble(timing.duration);
+
+        let local_time = self.local_time();
+        let progress =
+            local_time.and_then(|(local_time, backwards)| timing.progress(local_time, backwards));
+        ComputedEffectTiming {
+            parent,
+            endTime: Some(timing.end_time()),
+            activeDuration: Some(timing.active_duration()),
+            localTime: Some(local_time.map(|(local_time, _)| Finite::wrap(local_time))),
+            progress: Some(progress.map(|(progress, _)| Finite::wrap(progress))),
+            currentIteration: Some(progress.map(|(_, current_iteration)| current_iteration)),
+        }
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animationeffect-updatetiming
+    fn UpdateTiming(&self, timing: &OptionalEffectTiming) -> ErrorResult {
+        self.timing.borrow_mut().update(timing)?;
+        self.changed();
+        Ok(())
+    }
+}
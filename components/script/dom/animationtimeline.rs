@@ -0,0 +1,44 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AnimationTimelineBinding::AnimationTimelineMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::Dom;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+// https://drafts.csswg.org/web-animations/#the-animationtimeline-interface
+#[dom_struct]
+pub struct AnimationTimeline {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    /// The time relative to the time origin of the window that the timeline
+    /// is zero at, in milliseconds.
+    origin_time: f64,
+}
+
+impl AnimationTimeline {
+    pub fn new_inherited(window: &Window, origin_time: f64) -> AnimationTimeline {
+        AnimationTimeline {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            origin_time,
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations/#timeline-current-time>
+    pub fn current_time(&self) -> Option<f64> {
+        Some(*self.window.Performance().Now() - self.origin_time)
+    }
+}
+
+impl AnimationTimelineMethods for AnimationTimeline {
+    // https://drafts.csswg.org/web-animations/#dom-animationtimeline-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+}
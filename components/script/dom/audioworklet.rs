@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::audioworkletglobalscope::AudioWorkletProcessorId;
+use crate::dom::audioworkletnode::AudioWorkletNode;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AudioWorkletBinding;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::weakref::WeakRef;
+use crate::dom::window::Window;
+use crate::dom::worklet::{Worklet, WorkletExecutor};
+use crate::dom::workletglobalscope::WorkletGlobalScopeType;
+use crate::script_thread::ScriptThread;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// <https://webaudio.github.io/web-audio-api/#audioworklet>
+#[dom_struct]
+pub struct AudioWorklet {
+    worklet: Worklet,
+    /// <https://webaudio.github.io/web-audio-api/#node-name-to-parameter-descriptor-map>
+    processor_names: AudioWorkletProcessorNames,
+    /// The nodes whose processors run in this worklet. Weak, so that nodes
+    /// are collected even though their processors keep running.
+    nodes: DomRefCell<HashMap<AudioWorkletProcessorId, WeakRef<AudioWorkletNode>>>,
+}
+
+impl AudioWorklet {
+    fn new_inherited(window: &Window, sample_rate: f32) -> AudioWorklet {
+        let processor_names = AudioWorkletProcessorNames::default();
+        let global_type = WorkletGlobalScopeType::Audio(sample_rate, processor_names.clone());
+        AudioWorklet {
+            worklet: Worklet::new_inherited(window, global_type),
+            processor_names,
+            nodes: DomRefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn new(window: &Window, sample_rate: f32) -> DomRoot<AudioWorklet> {
+        debug!("Creating audio worklet.");
+        reflect_dom_object(
+            Box::new(AudioWorklet::new_inherited(window, sample_rate)),
+            window,
+            AudioWorkletBinding::Wrap,
+        )
+    }
+}
+
+impl AudioWorklet {
+    /// An executor of tasks in the global scopes of this worklet.
+    pub fn executor(&self) -> WorkletExecutor {
+        ScriptThread::worklet_thread_pool().executor(self.worklet.worklet_id())
+    }
+
+    /// Whether a processor was registered under `name` by the modules of this
+    /// worklet, <https://webaudio.github.io/web-audio-api/#node-name-to-parameter-descriptor-map>
+    pub fn has_processor(&self, name: &Atom) -> bool {
+        self.processor_names.contains(name)
+    }
+
+    pub fn add_node(&self, id: AudioWorkletProcessorId, node: &AudioWorkletNode) {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.retain(|_, node| node.is_alive());
+        nodes.insert(id, WeakRef::new(node));
+    }
+
+    /// The node whose processor is `id`, unless it was collected.
+    pub fn node(&self, id: AudioWorkletProcessorId) -> Option<DomRoot<AudioWorkletNode>> {
+        self.nodes.borrow().get(&id).and_then(WeakRef::root)
+    }
+}
+
+/// The names of the processors registered by the modules of an audio worklet.
+/// The worklet threads add to them when `registerProcessor()` is called, and
+/// the script thread reads them without waiting for the worklet threads.
+#[derive(Clone, Debug, Default)]
+pub struct AudioWorkletProcessorNames(Arc<Mutex<HashSet<Atom>>>);
+
+unsafe_no_jsmanaged_fields!(AudioWorkletProcessorNames);
+malloc_size_of_is_0!(AudioWorkletProcessorNames);
+
+impl AudioWorkletProcessorNames {
+    pub fn insert(&self, name: Atom) {
+        self.0.lock().unwrap().insert(name);
+    }
+
+    pub fn contains(&self, name: &Atom) -> bool {
+        self.0.lock().unwrap().contains(name)
+    }
+}
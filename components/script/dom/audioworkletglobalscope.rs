@@ -0,0 +1,353 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::audioworklet::AudioWorkletProcessorNames;
+use crate::dom::bindings::callback::CallbackContainer;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AudioWorkletGlobalScopeBinding;
+use crate::dom::bindings::codegen::Bindings::AudioWorkletGlobalScopeBinding::AudioWorkletGlobalScopeMethods;
+use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
+use crate::dom::bindings::conversions::get_property_jsval;
+use crate::dom::bindings::error::{report_pending_exception, Error, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::set_dictionary_property;
+use crate::dom::worklet::WorkletExecutor;
+use crate::dom::workletglobalscope::WorkletGlobalScope;
+use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::HandleValueArray;
+use js::jsapi::Heap;
+use js::jsapi::IsConstructor;
+use js::jsapi::JSAutoRealm;
+use js::jsapi::JSObject;
+use js::jsapi::JS_NewArrayObject;
+use js::jsapi::JS_NewPlainObject;
+use js::jsapi::JS_SetPrototype;
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{Call, Construct1};
+use js::rust::{MutableHandleObject, MutableHandleValue, Runtime};
+use js::typedarray::{CreateWith, Float32Array};
+use msg::constellation_msg::PipelineId;
+use servo_atoms::Atom;
+use servo_media::audio::block::{Block, FRAMES_PER_BLOCK_USIZE};
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+use std::ptr::null_mut;
+use std::rc::Rc;
+use uuid::Uuid;
+
+/// <https://webaudio.github.io/web-audio-api/#audioworkletglobalscope>
+#[dom_struct]
+pub struct AudioWorkletGlobalScope {
+    /// The worklet global for this object
+    worklet_global: WorkletGlobalScope,
+    /// <https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-samplerate>
+    sample_rate: f32,
+    /// The names of the processors registered by the modules of the worklet,
+    /// shared with its other global scopes and its `AudioWorklet`.
+    processor_names: AudioWorkletProcessorNames,
+    /// <https://webaudio.github.io/web-audio-api/#node-name-to-processor-constructor-map>
+    #[ignore_malloc_size_of = "mozjs"]
+    processor_constructors: DomRefCell<HashMap<Atom, Box<Heap<*mut JSObject>>>>,
+    /// The processors which this worklet thread constructed for audio worklet
+    /// nodes, which are `None` once they are errored.
+    #[ignore_malloc_size_of = "mozjs"]
+    processors: DomRefCell<HashMap<AudioWorkletProcessorId, Option<Box<Heap<JSVal>>>>>,
+}
+
+impl AudioWorkletGlobalScope {
+    #[allow(unsafe_code)]
+    pub fn new(
+        runtime: &Runtime,
+        pipeline_id: PipelineId,
+        base_url: ServoUrl,
+        executor: WorkletExecutor,
+        init: &WorkletGlobalScopeInit,
+        sample_rate: f32,
+        processor_names: AudioWorkletProcessorNames,
+    ) -> DomRoot<AudioWorkletGlobalScope> {
+        debug!(
+            "Creating audio worklet global scope for pipeline {}.",
+            pipeline_id
+        );
+        let global = Box::new(AudioWorkletGlobalScope {
+            worklet_global: WorkletGlobalScope::new_inherited(
+                pipeline_id,
+                base_url,
+                executor,
+                init,
+            ),
+            sample_rate,
+            processor_names,
+            processor_constructors: Default::default(),
+            processors: Default::default(),
+        });
+        unsafe { AudioWorkletGlobalScopeBinding::Wrap(JSContext::from_ptr(runtime.cx()), global) }
+    }
+
+    pub fn perform_a_worklet_task(&self, task: AudioWorkletTask) {
+        match task {
+            AudioWorkletTask::Process(init, block) => self.process(init, block),
+        }
+    }
+
+    /// Runs the processor of a node on a render quantum of its input. Each
+    /// worklet thread constructs the processor the first time it runs it.
+    /// <https://webaudio.github.io/web-audio-api/#rendering-loop>
+    #[allow(unsafe_code)]
+    fn process(&self, init: AudioWorkletProcessorInit, block: Block) {
+        let cx = self.worklet_global.get_cx();
+        let _ac = JSAutoRealm::new(*cx, self.worklet_global.reflector().get_jsobject().get());
+
+        rooted!(in(*cx) let mut processor = UndefinedValue());
+        let instance = self
+            .processors
+            .borrow()
+            .get(&init.id)
+            .map(|instance| instance.as_ref().map(|instance| instance.get()));
+        match instance {
+            Some(Some(instance)) => processor.set(instance),
+            Some(None) => return,
+            None => {
+                if !self.construct_processor(&init.name, processor.handle_mut()) {
+                    return self.processor_error(&init);
+                }
+                self.processors
+                    .borrow_mut()
+                    .insert(init.id, Some(Heap::boxed(processor.get())));
+            },
+        }
+
+        // A disconnected input has no channels.
+        let mut input = vec![];
+        if !block.is_silence() {
+            input.extend((0..block.chan_count()).map(|channel| block.data_chan(channel)));
+        }
+        let inputs = vec![input; init.number_of_inputs as usize];
+        let silence = [0.; FRAMES_PER_BLOCK_USIZE];
+        let outputs: Vec<_> = init
+            .output_channel_count
+            .iter()
+            .map(|&count| vec![&silence[..]; count as usize])
+            .collect();
+
+        rooted!(in(*cx) let processor_obj = processor.to_object());
+        rooted!(in(*cx) let mut process = UndefinedValue());
+        rooted!(in(*cx) let mut inputs_obj = null_mut::<JSObject>());
+        rooted!(in(*cx) let mut outputs_obj = null_mut::<JSObject>());
+        rooted!(in(*cx) let parameters_obj = unsafe { JS_NewPlainObject(*cx) });
+        rooted!(in(*cx) let mut result = UndefinedValue());
+        let ports_created = unsafe {
+            create_ports(cx, &inputs, inputs_obj.handle_mut()).is_ok() &&
+                create_ports(cx, &outputs, outputs_obj.handle_mut()).is_ok()
+        };
+        if !ports_created {
+            return self.processor_error(&init);
+        }
+        let args_slice = [
+            ObjectValue(inputs_obj.get()),
+            ObjectValue(outputs_obj.get()),
+            ObjectValue(parameters_obj.get()),
+        ];
+        let args = unsafe { HandleValueArray::from_rooted_slice(&args_slice) };
+        let called = unsafe {
+            let found =
+                get_property_jsval(*cx, processor_obj.handle(), "process", process.handle_mut());
+            found.is_ok() &&
+                Call(
+                    *cx,
+                    processor.handle(),
+                    process.handle(),
+                    &args,
+                    result.handle_mut(),
+                )
+        };
+        if !called {
+            return self.processor_error(&init);
+        }
+
+        // TODO: servo-media has no node which renders the outputs of the
+        // processor, so they are discarded, and the processor is kept alive
+        // whatever `process()` returns. Until then, audio worklets are behind
+        // the `dom.audioworklet.enabled` pref.
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#instantiation-of-AudioWorkletNode-and-AudioWorkletProcessor>
+    #[allow(unsafe_code)]
+    fn construct_processor(&self, name: &Atom, mut processor: MutableHandleValue) -> bool {
+        let cx = self.worklet_global.get_cx();
+        rooted!(in(*cx) let mut constructor = UndefinedValue());
+        match self.processor_constructors.borrow().get(name) {
+            Some(processor_ctor) => constructor.set(ObjectValue(processor_ctor.get())),
+            None => return false,
+        }
+        rooted!(in(*cx) let constructor_obj = constructor.to_object());
+
+        let args = HandleValueArray::new();
+        rooted!(in(*cx) let mut instance = null_mut::<JSObject>());
+        rooted!(in(*cx) let mut prototype = UndefinedValue());
+        unsafe {
+            if !Construct1(*cx, constructor.handle(), &args, instance.handle_mut()) {
+                return false;
+            }
+            // The bindings construct an `AudioWorkletProcessor` without looking
+            // at `new.target`, so the instance is given the prototype of the
+            // registered subclass here.
+            if get_property_jsval(
+                *cx,
+                constructor_obj.handle(),
+                "prototype",
+                prototype.handle_mut(),
+            )
+            .is_err() ||
+                !prototype.is_object()
+            {
+                return false;
+            }
+            rooted!(in(*cx) let prototype = prototype.to_object());
+            if !JS_SetPrototype(*cx, instance.handle().into(), prototype.handle().into()) {
+                return false;
+            }
+        }
+        processor.set(ObjectValue(instance.get()));
+        true
+    }
+
+    /// Marks the processor of a node as errored, and has `processorerror`
+    /// fired at the node.
+    #[allow(unsafe_code)]
+    fn processor_error(&self, init: &AudioWorkletProcessorInit) {
+        debug!("Audio worklet processor {} errored.", init.name);
+        unsafe {
+            report_pending_exception(*self.worklet_global.get_cx(), true);
+        }
+        self.processors.borrow_mut().insert(init.id, None);
+        let _ = init.error_sender.send(());
+    }
+}
+
+/// Creates the array of ports which `process()` takes as its inputs or
+/// outputs, each of which is an array of `Float32Array` channels.
+#[allow(unsafe_code)]
+unsafe fn create_ports(
+    cx: JSContext,
+    ports: &[Vec<&[f32]>],
+    mut rval: MutableHandleObject,
+) -> Result<(), ()> {
+    rval.set(JS_NewArrayObject(*cx, &HandleValueArray::new()));
+    for (index, channels) in ports.iter().enumerate() {
+        rooted!(in(*cx) let port = JS_NewArrayObject(*cx, &HandleValueArray::new()));
+        for (channel_index, data) in channels.iter().enumerate() {
+            rooted!(in(*cx) let mut channel = null_mut::<JSObject>());
+            Float32Array::create(*cx, CreateWith::Slice(data), channel.handle_mut())?;
+            rooted!(in(*cx) let channel = ObjectValue(channel.get()));
+            set_dictionary_property(
+                *cx,
+                port.handle(),
+                &channel_index.to_string(),
+                channel.handle(),
+            )?;
+        }
+        rooted!(in(*cx) let port = ObjectValue(port.get()));
+        set_dictionary_property(*cx, rval.handle(), &index.to_string(), port.handle())?;
+    }
+    Ok(())
+}
+
+/// A guid for the processors of audio worklet nodes.
+#[derive(Clone, Copy, Debug, Eq, Hash, JSTraceable, PartialEq)]
+pub struct AudioWorkletProcessorId(Uuid);
+
+malloc_size_of_is_0!(AudioWorkletProcessorId);
+
+impl AudioWorkletProcessorId {
+    pub fn new() -> AudioWorkletProcessorId {
+        AudioWorkletProcessorId(servo_rand::random_uuid())
+    }
+}
+
+/// What a worklet thread needs to construct and run the processor of an
+/// audio worklet node.
+#[derive(Clone)]
+pub struct AudioWorkletProcessorInit {
+    pub id: AudioWorkletProcessorId,
+    pub name: Atom,
+    pub number_of_inputs: u32,
+    pub output_channel_count: Vec<u32>,
+    /// Notifies the node that its processor errored.
+    pub error_sender: IpcSender<()>,
+}
+
+/// Tasks which can be performed by an audio worklet.
+pub enum AudioWorkletTask {
+    /// Runs the processor of a node on a render quantum of its input.
+    Process(AudioWorkletProcessorInit, Block),
+}
+
+impl AudioWorkletGlobalScopeMethods for AudioWorkletGlobalScope {
+    /// <https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-registerprocessor>
+    #[allow(unsafe_code)]
+    fn RegisterProcessor(&self, name: DOMString, processor_ctor: Rc<VoidFunction>) -> Fallible<()> {
+        let name = Atom::from(name);
+        let cx = self.worklet_global.get_cx();
+        rooted!(in(*cx) let processor_obj = processor_ctor.callback_holder().get());
+
+        debug!("Registering audio worklet processor {}.", name);
+
+        // Step 1.
+        if name.is_empty() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 2.
+        if self.processor_constructors.borrow().contains_key(&name) {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 3.
+        if unsafe { !IsConstructor(processor_obj.get()) } {
+            return Err(Error::Type(String::from("Not a constructor.")));
+        }
+
+        // Step 4-5.
+        rooted!(in(*cx) let mut prototype = UndefinedValue());
+        unsafe {
+            get_property_jsval(
+                *cx,
+                processor_obj.handle(),
+                "prototype",
+                prototype.handle_mut(),
+            )?;
+        }
+        if !prototype.is_object() {
+            return Err(Error::Type(String::from("Prototype is not an object.")));
+        }
+
+        // TODO: Step 6-7, the parameter descriptors are only needed
+        // to construct an AudioWorkletNode.
+
+        // Step 8.
+        debug!("Registering processor constructor {}.", name);
+        self.processor_constructors
+            .borrow_mut()
+            .insert(name.clone(), Heap::boxed(processor_obj.get()));
+
+        // Step 9, the name is shared with the AudioWorklet rather than queued
+        // in a task, since it does not run on the same thread as this scope.
+        // TODO: the parameter descriptors are not passed along yet.
+        self.processor_names.insert(name);
+
+        Ok(())
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-samplerate>
+    fn SampleRate(&self) -> Finite<f32> {
+        Finite::wrap(self.sample_rate)
+    }
+}
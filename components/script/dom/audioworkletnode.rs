@@ -0,0 +1,167 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::audionode::{AudioNode, MAX_CHANNEL_COUNT};
+use crate::dom::audioworkletglobalscope::{
+    AudioWorkletProcessorId, AudioWorkletProcessorInit, AudioWorkletTask,
+};
+use crate::dom::baseaudiocontext::BaseAudioContext;
+use crate::dom::bindings::codegen::Bindings::AudioNodeBinding::{
+    ChannelCountMode, ChannelInterpretation,
+};
+use crate::dom::bindings::codegen::Bindings::AudioWorkletNodeBinding::{
+    self, AudioWorkletNodeMethods, AudioWorkletNodeOptions,
+};
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContextMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use crate::dom::workletglobalscope::WorkletTask;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcReceiver};
+use ipc_channel::router::ROUTER;
+use servo_atoms::Atom;
+use servo_media::audio::block::Block;
+use servo_media::audio::node::AudioNodeInit;
+
+/// <https://webaudio.github.io/web-audio-api/#audioworkletnode>
+#[dom_struct]
+pub struct AudioWorkletNode {
+    node: AudioNode,
+}
+
+impl AudioWorkletNode {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        context: &BaseAudioContext,
+        name: DOMString,
+        options: &AudioWorkletNodeOptions,
+    ) -> Fallible<(AudioWorkletNode, AudioWorkletProcessorId, IpcReceiver<()>)> {
+        let name = Atom::from(name);
+        let audio_worklet = context.AudioWorklet();
+
+        // Step 1.
+        if !audio_worklet.has_processor(&name) {
+            return Err(Error::InvalidState);
+        }
+
+        // https://webaudio.github.io/web-audio-api/#configuring-channels-with-audioworkletnodeoptions
+        if options.numberOfInputs == 0 && options.numberOfOutputs == 0 {
+            return Err(Error::NotSupported);
+        }
+        // TODO: The node is rendered by a servo-media node with a single
+        // input and a single output.
+        if options.numberOfInputs > 1 || options.numberOfOutputs > 1 {
+            return Err(Error::NotSupported);
+        }
+        let node_options =
+            options
+                .parent
+                .unwrap_or(2, ChannelCountMode::Max, ChannelInterpretation::Speakers);
+        let output_channel_count = match options.outputChannelCount {
+            Some(ref counts) => {
+                if counts
+                    .iter()
+                    .any(|&count| count == 0 || count > MAX_CHANNEL_COUNT)
+                {
+                    return Err(Error::NotSupported);
+                }
+                if counts.len() != options.numberOfOutputs as usize {
+                    return Err(Error::IndexSize);
+                }
+                counts.clone()
+            },
+            None if options.numberOfInputs == 1 => {
+                vec![node_options.count; options.numberOfOutputs as usize]
+            },
+            None => vec![1; options.numberOfOutputs as usize],
+        };
+
+        // The processor runs on the input of each render quantum, which the
+        // audio rendering thread hands to the worklet thread pool.
+        let (error_sender, error_receiver) = ipc::channel().unwrap();
+        let id = AudioWorkletProcessorId::new();
+        let init = AudioWorkletProcessorInit {
+            id,
+            name,
+            number_of_inputs: options.numberOfInputs,
+            output_channel_count,
+            error_sender,
+        };
+        let executor = audio_worklet.executor();
+        let callback = move |block: Block| {
+            executor.schedule_a_worklet_task(WorkletTask::Audio(AudioWorkletTask::Process(
+                init.clone(),
+                block,
+            )));
+        };
+
+        // TODO: servo-media has no node which takes its output from the
+        // processor, so the node passes its input through.
+        let node = AudioNode::new_inherited(
+            AudioNodeInit::AnalyserNode(Box::new(callback)),
+            context,
+            node_options,
+            options.numberOfInputs,
+            options.numberOfOutputs,
+        )?;
+        Ok((AudioWorkletNode { node }, id, error_receiver))
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        context: &BaseAudioContext,
+        name: DOMString,
+        options: &AudioWorkletNodeOptions,
+    ) -> Fallible<DomRoot<AudioWorkletNode>> {
+        let (node, id, error_receiver) = AudioWorkletNode::new_inherited(context, name, options)?;
+        let object = reflect_dom_object(Box::new(node), window, AudioWorkletNodeBinding::Wrap);
+        let (source, canceller) = window
+            .task_manager()
+            .media_element_task_source_with_canceller();
+
+        // The processor, and so the route, lives as long as the audio context,
+        // so the route only knows the node by the id of its processor.
+        let audio_worklet = context.AudioWorklet();
+        audio_worklet.add_node(id, &object);
+        let audio_worklet = Trusted::new(&*audio_worklet);
+        ROUTER.add_route(
+            error_receiver.to_opaque(),
+            Box::new(move |_| {
+                let audio_worklet = audio_worklet.clone();
+                let _ = source.queue_with_canceller(
+                    task!(fire_processorerror: move || {
+                        if let Some(node) = audio_worklet.root().node(id) {
+                            node.upcast::<EventTarget>().fire_event(atom!("processorerror"));
+                        }
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        Ok(object)
+    }
+
+    /// https://webaudio.github.io/web-audio-api/#dom-audioworkletnode-audioworkletnode
+    pub fn Constructor(
+        window: &Window,
+        context: &BaseAudioContext,
+        name: DOMString,
+        options: &AudioWorkletNodeOptions,
+    ) -> Fallible<DomRoot<AudioWorkletNode>> {
+        AudioWorkletNode::new(window, context, name, options)
+    }
+}
+
+impl AudioWorkletNodeMethods for AudioWorkletNode {
+    // https://webaudio.github.io/web-audio-api/#dom-audioworkletnode-onprocessorerror
+    event_handler!(processorerror, GetOnprocessorerror, SetOnprocessorerror);
+}
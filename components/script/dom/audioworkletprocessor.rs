@@ -0,0 +1,36 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::audioworkletglobalscope::AudioWorkletGlobalScope;
+use crate::dom::bindings::codegen::Bindings::AudioWorkletProcessorBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use dom_struct::dom_struct;
+
+/// <https://webaudio.github.io/web-audio-api/#audioworkletprocessor>
+#[dom_struct]
+pub struct AudioWorkletProcessor {
+    reflector_: Reflector,
+}
+
+impl AudioWorkletProcessor {
+    fn new_inherited() -> AudioWorkletProcessor {
+        AudioWorkletProcessor {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &AudioWorkletGlobalScope) -> DomRoot<AudioWorkletProcessor> {
+        reflect_dom_object(
+            Box::new(AudioWorkletProcessor::new_inherited()),
+            global,
+            AudioWorkletProcessorBinding::Wrap,
+        )
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-audioworkletprocessor-audioworkletprocessor>
+    pub fn Constructor(global: &AudioWorkletGlobalScope) -> DomRoot<AudioWorkletProcessor> {
+        AudioWorkletProcessor::new(global)
+    }
+}
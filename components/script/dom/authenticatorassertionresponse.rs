@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{set_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAssertionResponseBinding::{
+    self, AuthenticatorAssertionResponseMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use std::ptr::NonNull;
+
+// https://w3c.github.io/webauthn/#authenticatorassertionresponse
+#[dom_struct]
+pub struct AuthenticatorAssertionResponse {
+    response: AuthenticatorResponse,
+    #[ignore_malloc_size_of = "mozjs"]
+    authenticator_data: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "mozjs"]
+    signature: Heap<*mut JSObject>,
+    /// Null when the authenticator didn't return the user handle.
+    #[ignore_malloc_size_of = "mozjs"]
+    user_handle: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorAssertionResponse {
+    fn new_inherited() -> AuthenticatorAssertionResponse {
+        AuthenticatorAssertionResponse {
+            response: AuthenticatorResponse::new_inherited(),
+            authenticator_data: Heap::default(),
+            signature: Heap::default(),
+            user_handle: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        client_data_json: &[u8],
+        authenticator_data: &[u8],
+        signature: &[u8],
+        user_handle: Option<&[u8]>,
+    ) -> DomRoot<AuthenticatorAssertionResponse> {
+        let response = reflect_dom_object(
+            Box::new(AuthenticatorAssertionResponse::new_inherited()),
+            global,
+            AuthenticatorAssertionResponseBinding::Wrap,
+        );
+        let cx = global.get_cx();
+        response.response.set_client_data_json(cx, client_data_json);
+        set_array_buffer(cx, &response.authenticator_data, authenticator_data);
+        set_array_buffer(cx, &response.signature, signature);
+        if let Some(user_handle) = user_handle {
+            set_array_buffer(cx, &response.user_handle, user_handle);
+        }
+        response
+    }
+}
+
+impl AuthenticatorAssertionResponseMethods for AuthenticatorAssertionResponse {
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-authenticatordata
+    #[allow(unsafe_code)]
+    fn AuthenticatorData(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.authenticator_data.get()) }
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-signature
+    #[allow(unsafe_code)]
+    fn Signature(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.signature.get()) }
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-userhandle
+    fn GetUserHandle(&self, _cx: JSContext) -> Option<NonNull<JSObject>> {
+        NonNull::new(self.user_handle.get())
+    }
+}
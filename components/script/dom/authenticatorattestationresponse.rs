@@ -0,0 +1,56 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{set_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAttestationResponseBinding::{
+    self, AuthenticatorAttestationResponseMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use std::ptr::NonNull;
+
+// https://w3c.github.io/webauthn/#authenticatorattestationresponse
+#[dom_struct]
+pub struct AuthenticatorAttestationResponse {
+    response: AuthenticatorResponse,
+    #[ignore_malloc_size_of = "mozjs"]
+    attestation_object: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorAttestationResponse {
+    fn new_inherited() -> AuthenticatorAttestationResponse {
+        AuthenticatorAttestationResponse {
+            response: AuthenticatorResponse::new_inherited(),
+            attestation_object: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        client_data_json: &[u8],
+        attestation_object: &[u8],
+    ) -> DomRoot<AuthenticatorAttestationResponse> {
+        let response = reflect_dom_object(
+            Box::new(AuthenticatorAttestationResponse::new_inherited()),
+            global,
+            AuthenticatorAttestationResponseBinding::Wrap,
+        );
+        let cx = global.get_cx();
+        response.response.set_client_data_json(cx, client_data_json);
+        set_array_buffer(cx, &response.attestation_object, attestation_object);
+        response
+    }
+}
+
+impl AuthenticatorAttestationResponseMethods for AuthenticatorAttestationResponse {
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-attestationobject
+    #[allow(unsafe_code)]
+    fn AttestationObject(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.attestation_object.get()) }
+    }
+}
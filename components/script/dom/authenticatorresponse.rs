@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AuthenticatorResponseBinding::AuthenticatorResponseMethods;
+use crate::dom::bindings::reflector::Reflector;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::typedarray::{ArrayBuffer, CreateWith};
+use std::ptr::{self, NonNull};
+
+// https://w3c.github.io/webauthn/#authenticatorresponse
+#[dom_struct]
+pub struct AuthenticatorResponse {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "mozjs"]
+    client_data_json: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorResponse {
+    pub fn new_inherited() -> AuthenticatorResponse {
+        AuthenticatorResponse {
+            reflector_: Reflector::new(),
+            client_data_json: Heap::default(),
+        }
+    }
+
+    /// Sets the client data the authenticator signed, once the response is
+    /// reflected.
+    pub fn set_client_data_json(&self, cx: JSContext, client_data_json: &[u8]) {
+        set_array_buffer(cx, &self.client_data_json, client_data_json);
+    }
+}
+
+/// Stores a new `ArrayBuffer` holding `bytes` in `heap`.
+#[allow(unsafe_code)]
+pub fn set_array_buffer(cx: JSContext, heap: &Heap<*mut JSObject>, bytes: &[u8]) {
+    rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+    unsafe {
+        assert!(
+            ArrayBuffer::create(*cx, CreateWith::Slice(bytes), array_buffer.handle_mut()).is_ok()
+        );
+    }
+    heap.set(array_buffer.get());
+}
+
+impl AuthenticatorResponseMethods for AuthenticatorResponse {
+    // https://w3c.github.io/webauthn/#dom-authenticatorresponse-clientdatajson
+    #[allow(unsafe_code)]
+    fn ClientDataJSON(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.client_data_json.get()) }
+    }
+}
@@ -9,6 +9,7 @@ use crate::dom::audiobuffersourcenode::AudioBufferSourceNode;
 use crate::dom::audiodestinationnode::AudioDestinationNode;
 use crate::dom::audiolistener::AudioListener;
 use crate::dom::audionode::MAX_CHANNEL_COUNT;
+use crate::dom::audioworklet::AudioWorklet;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AnalyserNodeBinding::AnalyserOptions;
@@ -42,6 +43,7 @@ use crate::dom::constantsourcenode::ConstantSourceNode;
 use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::gainnode::GainNode;
+use crate::dom::offlineaudiocontext::OfflineAudioContext;
 use crate::dom::oscillatornode::OscillatorNode;
 use crate::dom::pannernode::PannerNode;
 use crate::dom::promise::Promise;
@@ -86,6 +88,8 @@ pub struct BaseAudioContext {
     /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-destination
     destination: MutNullableDom<AudioDestinationNode>,
     listener: MutNullableDom<AudioListener>,
+    /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-audioworklet
+    audio_worklet: MutNullableDom<AudioWorklet>,
     /// Resume promises which are soon to be fulfilled by a queued task.
     #[ignore_malloc_size_of = "promises are hard"]
     in_flight_resume_promises_queue: DomRefCell<VecDeque<(Box<[Rc<Promise>]>, ErrorResult)>>,
@@ -103,6 +107,8 @@ pub struct BaseAudioContext {
     /// been "closed()".
     state: Cell<AudioContextState>,
     channel_count: u32,
+    /// Whether this is an OfflineAudioContext.
+    offline: bool,
 }
 
 impl BaseAudioContext {
@@ -111,10 +117,10 @@ impl BaseAudioContext {
         options: BaseAudioContextOptions,
         pipeline_id: PipelineId,
     ) -> BaseAudioContext {
-        let (sample_rate, channel_count) = match options {
-            BaseAudioContextOptions::AudioContext(ref opt) => (opt.sample_rate, 2),
+        let (sample_rate, channel_count, offline) = match options {
+            BaseAudioContextOptions::AudioContext(ref opt) => (opt.sample_rate, 2, false),
             BaseAudioContextOptions::OfflineAudioContext(ref opt) => {
-                (opt.sample_rate, opt.channels)
+                (opt.sample_rate, opt.channels, true)
             },
         };
 
@@ -127,12 +133,14 @@ impl BaseAudioContext {
                 .create_audio_context(&client_context_id, options.into()),
             destination: Default::default(),
             listener: Default::default(),
+            audio_worklet: Default::default(),
             in_flight_resume_promises_queue: Default::default(),
             pending_resume_promises: Default::default(),
             decode_resolvers: Default::default(),
             sample_rate,
             state: Cell::new(AudioContextState::Suspended),
             channel_count: channel_count.into(),
+            offline,
         };
 
         context
@@ -140,7 +148,7 @@ impl BaseAudioContext {
 
     /// Tells whether this is an OfflineAudioContext or not.
     pub fn is_offline(&self) -> bool {
-        false
+        self.offline
     }
 
     pub fn audio_context_impl(&self) -> Arc<Mutex<AudioContext>> {
@@ -292,6 +300,14 @@ impl BaseAudioContextMethods for BaseAudioContext {
             return promise;
         }
 
+        // https://webaudio.github.io/web-audio-api/#dom-offlineaudiocontext-resume
+        if let Some(context) = self.downcast::<OfflineAudioContext>() {
+            if !context.rendering_started() {
+                promise.reject_error(Error::InvalidState);
+                return promise;
+            }
+        }
+
         // Step 3.
         if self.state.get() == AudioContextState::Running {
             promise.resolve_native(&());
@@ -331,6 +347,14 @@ impl BaseAudioContextMethods for BaseAudioContext {
         self.listener.or_init(|| AudioListener::new(&window, self))
     }
 
+    /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-audioworklet
+    fn AudioWorklet(&self) -> DomRoot<AudioWorklet> {
+        let global = self.global();
+        let window = global.as_window();
+        self.audio_worklet
+            .or_init(|| AudioWorklet::new(&window, self.sample_rate))
+    }
+
     /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-onstatechange
     event_handler!(statechange, GetOnstatechange, SetOnstatechange);
 
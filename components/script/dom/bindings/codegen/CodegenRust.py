@@ -4250,9 +4250,10 @@ def getEnumValueName(value):
     # [0-9A-Za-z_] with '_'.
     if re.match("[^\x20-\x7E]", value):
         raise SyntaxError('Enum value "' + value + '" contains non-ASCII characters')
-    if re.match("^[0-9]", value):
-        raise SyntaxError('Enum value "' + value + '" starts with a digit')
     value = re.sub(r'[^0-9A-Za-z_]', '_', value)
+    # Values like "2d" aren't valid identifiers, so they are prefixed with "_".
+    if re.match("^[0-9]", value):
+        value = "_" + value
     if re.match("^_[A-Z]|__", value):
         raise SyntaxError('Enum value "' + value + '" is reserved by the C++ spec')
     if value == "_empty":
@@ -27,6 +27,7 @@ use js::rust::wrappers::JS_ErrorFromException;
 use js::rust::wrappers::JS_GetPendingException;
 use js::rust::wrappers::JS_SetPendingException;
 use js::rust::HandleObject;
+use js::rust::HandleValue;
 use js::rust::MutableHandleValue;
 use libc::c_uint;
 use std::slice::from_raw_parts;
@@ -87,6 +88,10 @@ pub enum Error {
     NotReadable,
     /// OperationError DOMException
     Operation,
+    /// NotAllowedError DOMException
+    NotAllowed,
+    /// DataError DOMException
+    Data,
 
     /// TypeError JavaScript Error
     Type(String),
@@ -140,6 +145,8 @@ pub fn throw_dom_exception(cx: SafeJSContext, global: &GlobalScope, result: Erro
         Error::InvalidModification => DOMErrorName::InvalidModificationError,
         Error::NotReadable => DOMErrorName::NotReadableError,
         Error::Operation => DOMErrorName::OperationError,
+        Error::NotAllowed => DOMErrorName::NotAllowedError,
+        Error::Data => DOMErrorName::DataError,
         Error::Type(message) => unsafe {
             assert!(!JS_IsExceptionPending(*cx));
             throw_type_error(*cx, &message);
@@ -226,6 +233,40 @@ impl ErrorInfo {
             column: 0,
         })
     }
+
+    /// Describes a value that was thrown, or that a promise was rejected with.
+    pub unsafe fn from_value(value: HandleValue, cx: *mut JSContext) -> ErrorInfo {
+        if value.is_object() {
+            rooted!(in(cx) let object = value.to_object());
+            ErrorInfo::from_native_error(cx, object.handle())
+                .or_else(|| ErrorInfo::from_dom_exception(object.handle(), cx))
+                .unwrap_or_else(|| ErrorInfo {
+                    message: format!("uncaught exception: unknown (can't convert to string)"),
+                    filename: String::new(),
+                    lineno: 0,
+                    column: 0,
+                })
+        } else {
+            match USVString::from_jsval(cx, value, ()) {
+                Ok(ConversionResult::Success(USVString(string))) => ErrorInfo {
+                    message: format!("uncaught exception: {}", string),
+                    filename: String::new(),
+                    lineno: 0,
+                    column: 0,
+                },
+                // Symbols can't be converted to strings.
+                _ => {
+                    JS_ClearPendingException(cx);
+                    ErrorInfo {
+                        message: format!("uncaught exception: unknown (can't convert to string)"),
+                        filename: String::new(),
+                        lineno: 0,
+                        column: 0,
+                    }
+                },
+            }
+        }
+    }
 }
 
 /// Report a pending exception, thereby clearing it.
@@ -245,29 +286,7 @@ pub unsafe fn report_pending_exception(cx: *mut JSContext, dispatch_event: bool)
     }
 
     JS_ClearPendingException(cx);
-    let error_info = if value.is_object() {
-        rooted!(in(cx) let object = value.to_object());
-        ErrorInfo::from_native_error(cx, object.handle())
-            .or_else(|| ErrorInfo::from_dom_exception(object.handle(), cx))
-            .unwrap_or_else(|| ErrorInfo {
-                message: format!("uncaught exception: unknown (can't convert to string)"),
-                filename: String::new(),
-                lineno: 0,
-                column: 0,
-            })
-    } else {
-        match USVString::from_jsval(cx, value.handle(), ()) {
-            Ok(ConversionResult::Success(USVString(string))) => ErrorInfo {
-                message: format!("uncaught exception: {}", string),
-                filename: String::new(),
-                lineno: 0,
-                column: 0,
-            },
-            _ => {
-                panic!("Uncaught exception: failed to stringify primitive");
-            },
-        }
-    };
+    let error_info = ErrorInfo::from_value(value.handle(), cx);
 
     error!(
         "Error at {}:{}:{} {}",
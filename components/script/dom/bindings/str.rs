@@ -3,8 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 //! The `ByteString` struct.
-use chrono::prelude::{Utc, Weekday};
-use chrono::{Datelike, TimeZone};
+use chrono::prelude::{NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono::{Datelike, TimeZone, Timelike};
 use cssparser::CowRcStr;
 use html5ever::{LocalName, Namespace};
 use regex::Regex;
@@ -347,8 +347,87 @@ impl DOMString {
     /// https://html.spec.whatwg.org/multipage/#best-representation-of-the-number-as-a-floating-point-number
     pub fn set_best_representation_of_the_floating_point_number(&mut self) {
         if let Ok(val) = parse_floating_point_number(&self.0) {
-            self.0 = val.to_string();
+            // TODO(#19773): need consider `min`, `max`, `step`, when they are implemented
+            self.0 = val.round().to_string();
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#rules-for-parsing-floating-point-number-values
+    pub fn to_floating_point_number(&self) -> Option<f64> {
+        parse_floating_point_number(&self.0).ok()
+    }
+
+    /// Converts a valid date string to the number of milliseconds elapsed
+    /// from midnight UTC on 1970-01-01.
+    /// https://html.spec.whatwg.org/multipage/#date-state-(type=date):concept-input-value-string-number
+    pub fn date_string_to_number(&self) -> Option<f64> {
+        let (year, month, day) = parse_date_string(&self.0).ok()?;
+        date_to_milliseconds(year, month, day)
+    }
+
+    /// Converts a number of milliseconds elapsed from midnight UTC on 1970-01-01
+    /// to a valid date string.
+    /// https://html.spec.whatwg.org/multipage/#date-state-(type=date):concept-input-value-number-string
+    pub fn from_date_number(value: f64) -> Option<DOMString> {
+        let datetime = milliseconds_to_datetime(value)?;
+        Some(DOMString::from(datetime.format("%Y-%m-%d").to_string()))
+    }
+
+    /// Converts a valid month string to the number of months between
+    /// January 1970 and that month.
+    /// https://html.spec.whatwg.org/multipage/#month-state-(type=month):concept-input-value-string-number
+    pub fn month_string_to_number(&self) -> Option<f64> {
+        let (year, month) = parse_month_string(&self.0).ok()?;
+        Some((year as f64 - 1970.0) * 12.0 + (month as f64 - 1.0))
+    }
+
+    /// Converts a valid week string to the number of milliseconds elapsed
+    /// from midnight UTC on 1970-01-01 to midnight UTC on the Monday of that week.
+    /// https://html.spec.whatwg.org/multipage/#week-state-(type=week):concept-input-value-string-number
+    pub fn week_string_to_number(&self) -> Option<f64> {
+        let (year, week) = parse_week_string(&self.0).ok()?;
+        let monday = NaiveDate::from_isoywd_opt(year as i32, week, Weekday::Mon)?;
+        Some(monday.and_hms(0, 0, 0).timestamp() as f64 * 1000.0)
+    }
+
+    /// Converts a valid time string to the number of milliseconds elapsed
+    /// from midnight.
+    /// https://html.spec.whatwg.org/multipage/#time-state-(type=time):concept-input-value-string-number
+    pub fn time_string_to_number(&self) -> Option<f64> {
+        if !self.is_valid_time_string() {
+            return None;
         }
+        let (hour, minute, second) = parse_time_component(&self.0).ok()?;
+        Some(time_to_milliseconds(hour, minute, second))
+    }
+
+    /// Converts a valid local date and time string to the number of
+    /// milliseconds elapsed from midnight on 1970-01-01, with no time zone
+    /// offset.
+    /// https://html.spec.whatwg.org/multipage/#local-date-and-time-state-(type=datetime-local):concept-input-value-string-number
+    pub fn local_date_and_time_string_to_number(&self) -> Option<f64> {
+        let ((year, month, day), (hour, minute, second)) =
+            parse_local_date_and_time_string(&self.0).ok()?;
+        Some(date_to_milliseconds(year, month, day)? + time_to_milliseconds(hour, minute, second))
+    }
+
+    /// Converts a number of milliseconds elapsed from midnight to a valid time string.
+    /// https://html.spec.whatwg.org/multipage/#time-state-(type=time):concept-input-value-number-string
+    pub fn from_time_number(value: f64) -> Option<DOMString> {
+        let datetime = milliseconds_to_datetime(value)?;
+        Some(DOMString::from(format_time(&datetime.time())))
+    }
+
+    /// Converts a number of milliseconds elapsed from midnight on 1970-01-01
+    /// to a valid normalized local date and time string.
+    /// https://html.spec.whatwg.org/multipage/#local-date-and-time-state-(type=datetime-local):concept-input-value-number-string
+    pub fn from_local_date_and_time_number(value: f64) -> Option<DOMString> {
+        let datetime = milliseconds_to_datetime(value)?;
+        Some(DOMString::from(format!(
+            "{}T{}",
+            datetime.format("%Y-%m-%d"),
+            format_time(&datetime.time())
+        )))
     }
 
     /// A valid normalized local date and time string should be "{date}T{time}"
@@ -690,6 +769,39 @@ fn parse_local_date_and_time_string(value: &str) -> Result<((u32, u32, u32), (u3
     Ok((date_tuple, time_tuple))
 }
 
+fn date_to_milliseconds(year: u32, month: u32, day: u32) -> Option<f64> {
+    let date = NaiveDate::from_ymd_opt(year as i32, month, day)?;
+    Some(date.and_hms(0, 0, 0).timestamp() as f64 * 1000.0)
+}
+
+fn time_to_milliseconds(hour: u32, minute: u32, second: f32) -> f64 {
+    ((hour * 3600 + minute * 60) as f64 * 1000.0) + (second as f64 * 1000.0).round()
+}
+
+fn milliseconds_to_datetime(value: f64) -> Option<NaiveDateTime> {
+    if !value.is_finite() {
+        return None;
+    }
+    let milliseconds = value.round() as i64;
+    NaiveDateTime::from_timestamp_opt(
+        milliseconds.div_euclid(1000),
+        (milliseconds.rem_euclid(1000) * 1_000_000) as u32,
+    )
+}
+
+/// Formats a time as the shortest valid time string, leaving out
+/// the seconds and milliseconds when they are zero.
+fn format_time(time: &NaiveTime) -> String {
+    let format = if time.nanosecond() != 0 {
+        "%H:%M:%S%.3f"
+    } else if time.second() != 0 {
+        "%H:%M:%S"
+    } else {
+        "%H:%M"
+    };
+    time.format(format).to_string()
+}
+
 fn max_day_in_month(year_num: u32, month_num: u32) -> Result<u32, ()> {
     match month_num {
         1 | 3 | 5 | 7 | 8 | 10 | 12 => Ok(31),
@@ -729,9 +841,8 @@ fn parse_floating_point_number(input: &str) -> Result<f64, ()> {
                 val.is_infinite() || val.is_nan() || input.ends_with(".") || input.starts_with("+")
             ) =>
         {
-            // TODO(#19773): need consider `min`, `max`, `step`, when they are implemented
-            Ok(val.round())
-        }
+            Ok(val)
+        },
         _ => Err(()),
     }
 }
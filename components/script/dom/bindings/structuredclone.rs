@@ -6,14 +6,23 @@
 //! (https://html.spec.whatwg.org/multipage/#safe-passing-of-structured-data).
 
 use crate::compartments::enter_realm;
+use crate::dom::bindings::codegen::Bindings::FileBinding::FileMethods;
 use crate::dom::bindings::conversions::{root_from_object, ToJSValConvertible};
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::transferable::Transferable;
 use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::file::File;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::messageport::MessagePort;
+use crate::dom::offscreencanvas::OffscreenCanvas;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::transformstream::TransformStream;
+use crate::dom::writablestream::WritableStream;
 use crate::script_runtime::JSContext as SafeJSContext;
 use js::glue::CopyJSStructuredCloneData;
 use js::glue::DeleteJSAutoStructuredCloneBuffer;
@@ -22,6 +31,7 @@ use js::glue::NewJSAutoStructuredCloneBuffer;
 use js::glue::WriteBytesToJSStructuredCloneData;
 use js::jsapi::CloneDataPolicy;
 use js::jsapi::HandleObject as RawHandleObject;
+use js::jsapi::JSAutoStructuredCloneBuffer;
 use js::jsapi::JSContext;
 use js::jsapi::MutableHandleObject as RawMutableHandleObject;
 use js::jsapi::StructuredCloneScope;
@@ -35,8 +45,8 @@ use js::jsval::UndefinedValue;
 use js::rust::wrappers::{JS_ReadStructuredClone, JS_WriteStructuredClone};
 use js::rust::{CustomAutoRooterGuard, HandleValue, MutableHandleValue};
 use msg::constellation_msg::MessagePortId;
-use script_traits::transferable::MessagePortImpl;
-use script_traits::StructuredSerializedData;
+use script_traits::transferable::{ImageBitmapImpl, MessagePortImpl, OffscreenCanvasImpl};
+use script_traits::{SharedMemoryRefs, StructuredSerializedData};
 use std::collections::HashMap;
 use std::os::raw;
 use std::ptr;
@@ -51,6 +61,12 @@ enum StructuredCloneTags {
     Min = 0xFFFF8000,
     DomBlob = 0xFFFF8001,
     MessagePort = 0xFFFF8002,
+    OffscreenCanvas = 0xFFFF8003,
+    DomFile = 0xFFFF8004,
+    ImageBitmap = 0xFFFF8005,
+    ReadableStream = 0xFFFF8006,
+    WritableStream = 0xFFFF8007,
+    TransformStream = 0xFFFF8008,
     Max = 0xFFFFFFFF,
 }
 
@@ -107,6 +123,9 @@ impl StructuredCloneWriter {
     unsafe fn write_str(&self, s: &str) {
         self.write_slice(s.as_bytes());
     }
+    unsafe fn write_u64(&self, v: u64) {
+        assert!(JS_WriteUint32Pair(self.w, (v >> 32) as u32, v as u32));
+    }
 }
 
 struct StructuredCloneReader {
@@ -128,22 +147,49 @@ impl StructuredCloneReader {
         let str_buffer = self.read_bytes();
         return String::from_utf8_unchecked(str_buffer);
     }
+    unsafe fn read_u64(&self) -> u64 {
+        let mut high: u32 = 0;
+        let mut low: u32 = 0;
+        assert!(JS_ReadUint32Pair(
+            self.r,
+            &mut high as *mut u32,
+            &mut low as *mut u32
+        ));
+        return ((high as u64) << 32) | low as u64;
+    }
 }
 
+/// Blobs and Files are serialized with a copy of their bytes, so that the
+/// clone does not depend on the file manager entry of the original, which
+/// may be revoked or belong to another origin by the time it is read.
 unsafe fn read_blob(
     cx: *mut JSContext,
+    tag: u32,
     r: *mut JSStructuredCloneReader,
     sc_holder: &mut StructuredDataHolder,
 ) -> *mut JSObject {
     let structured_reader = StructuredCloneReader { r: r };
     let blob_buffer = structured_reader.read_bytes();
     let type_str = structured_reader.read_str();
+    let file_metadata = if tag == StructuredCloneTags::DomFile as u32 {
+        let name = structured_reader.read_str();
+        let modified = structured_reader.read_u64() as i64;
+        Some((name, modified))
+    } else {
+        None
+    };
     let target_global = GlobalScope::from_context(cx);
-    let read_blob = Blob::new(
-        &target_global,
-        BlobImpl::new_from_bytes(blob_buffer),
-        type_str,
-    );
+    let blob_impl = BlobImpl::new_from_bytes(blob_buffer);
+    let read_blob = match file_metadata {
+        Some((name, modified)) => DomRoot::upcast::<Blob>(File::new(
+            &target_global,
+            blob_impl,
+            DOMString::from(name),
+            Some(modified),
+            &type_str,
+        )),
+        None => Blob::new(&target_global, blob_impl, type_str),
+    };
     let js_object = read_blob.reflector().get_jsobject().get();
     match sc_holder {
         StructuredDataHolder::Read { blob, .. } => {
@@ -157,13 +203,85 @@ unsafe fn read_blob(
 unsafe fn write_blob(blob: DomRoot<Blob>, w: *mut JSStructuredCloneWriter) -> Result<(), ()> {
     let structured_writer = StructuredCloneWriter { w: w };
     let blob_vec = blob.get_bytes()?;
+    let file = blob.downcast::<File>();
+    let tag = match file {
+        Some(_) => StructuredCloneTags::DomFile,
+        None => StructuredCloneTags::DomBlob,
+    };
+    assert!(JS_WriteUint32Pair(w, tag as u32, 0));
+    structured_writer.write_slice(&blob_vec);
+    structured_writer.write_str(&blob.type_string());
+    if let Some(file) = file {
+        structured_writer.write_str(file.name());
+        structured_writer.write_u64(file.LastModified() as u64);
+    }
+    return Ok(());
+}
+
+/// ImageBitmaps are serialized with a copy of their bitmap data,
+/// the origin-clean flag being stored as the data of their tag.
+unsafe fn read_image_bitmap(
+    cx: *mut JSContext,
+    r: *mut JSStructuredCloneReader,
+    origin_clean: u32,
+    sc_holder: &mut StructuredDataHolder,
+) -> *mut JSObject {
+    let structured_reader = StructuredCloneReader { r: r };
+    let mut width: u32 = 0;
+    let mut height: u32 = 0;
+    assert!(JS_ReadUint32Pair(
+        r,
+        &mut width as *mut u32,
+        &mut height as *mut u32
+    ));
+    let mut alpha_premultiplied: u32 = 0;
+    let mut unused: u32 = 0;
+    assert!(JS_ReadUint32Pair(
+        r,
+        &mut alpha_premultiplied as *mut u32,
+        &mut unused as *mut u32
+    ));
+    let bitmap_data = structured_reader.read_bytes();
+    let target_global = GlobalScope::from_context(cx);
+    let read_image_bitmap = ImageBitmap::new(
+        &target_global,
+        width,
+        height,
+        bitmap_data,
+        alpha_premultiplied != 0,
+        origin_clean != 0,
+    );
+    let js_object = read_image_bitmap.reflector().get_jsobject().get();
+    match sc_holder {
+        StructuredDataHolder::Read { image_bitmap, .. } => {
+            *image_bitmap = Some(read_image_bitmap);
+        },
+        _ => panic!("Unexpected variant of StructuredDataHolder"),
+    }
+    js_object
+}
+
+/// <https://html.spec.whatwg.org/multipage/#the-imagebitmap-interface:serialization-steps>
+unsafe fn write_image_bitmap(
+    image_bitmap: DomRoot<ImageBitmap>,
+    w: *mut JSStructuredCloneWriter,
+) -> Result<(), ()> {
+    let structured_writer = StructuredCloneWriter { w: w };
+    // Step 2, a closed bitmap can't be serialized.
+    let bitmap_data = image_bitmap.bitmap_data().ok_or(())?;
+    let size = image_bitmap.get_size();
     assert!(JS_WriteUint32Pair(
         w,
-        StructuredCloneTags::DomBlob as u32,
+        StructuredCloneTags::ImageBitmap as u32,
+        image_bitmap.origin_is_clean() as u32
+    ));
+    assert!(JS_WriteUint32Pair(w, size.width, size.height));
+    assert!(JS_WriteUint32Pair(
+        w,
+        image_bitmap.is_alpha_premultiplied() as u32,
         0
     ));
-    structured_writer.write_slice(&blob_vec);
-    structured_writer.write_str(&blob.type_string());
+    structured_writer.write_slice(&bitmap_data);
     return Ok(());
 }
 
@@ -171,7 +289,7 @@ unsafe extern "C" fn read_callback(
     cx: *mut JSContext,
     r: *mut JSStructuredCloneReader,
     tag: u32,
-    _data: u32,
+    data: u32,
     closure: *mut raw::c_void,
 ) -> *mut JSObject {
     assert!(
@@ -182,8 +300,11 @@ unsafe extern "C" fn read_callback(
         tag > StructuredCloneTags::Min as u32,
         "tag should be higher than StructuredCloneTags::Min"
     );
-    if tag == StructuredCloneTags::DomBlob as u32 {
-        return read_blob(cx, r, &mut *(closure as *mut StructuredDataHolder));
+    if tag == StructuredCloneTags::DomBlob as u32 || tag == StructuredCloneTags::DomFile as u32 {
+        return read_blob(cx, tag, r, &mut *(closure as *mut StructuredDataHolder));
+    }
+    if tag == StructuredCloneTags::ImageBitmap as u32 {
+        return read_image_bitmap(cx, r, data, &mut *(closure as *mut StructuredDataHolder));
     }
     return ptr::null_mut();
 }
@@ -197,6 +318,9 @@ unsafe extern "C" fn write_callback(
     if let Ok(blob) = root_from_object::<Blob>(*obj, cx) {
         return write_blob(blob, w).is_ok();
     }
+    if let Ok(image_bitmap) = root_from_object::<ImageBitmap>(*obj, cx) {
+        return write_image_bitmap(image_bitmap, w).is_ok();
+    }
     return false;
 }
 
@@ -209,9 +333,9 @@ unsafe extern "C" fn read_transfer_callback(
     closure: *mut raw::c_void,
     return_object: RawMutableHandleObject,
 ) -> bool {
+    let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+    let owner = GlobalScope::from_context(cx);
     if tag == StructuredCloneTags::MessagePort as u32 {
-        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
-        let owner = GlobalScope::from_context(cx);
         if let Ok(_) = <MessagePort as Transferable>::transfer_receive(
             &owner,
             &mut sc_holder,
@@ -221,6 +345,56 @@ unsafe extern "C" fn read_transfer_callback(
             return true;
         }
     }
+    if tag == StructuredCloneTags::OffscreenCanvas as u32 {
+        if let Ok(_) = <OffscreenCanvas as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
+    if tag == StructuredCloneTags::ImageBitmap as u32 {
+        if let Ok(_) = <ImageBitmap as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
+    if tag == StructuredCloneTags::ReadableStream as u32 {
+        if let Ok(_) = <ReadableStream as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
+    if tag == StructuredCloneTags::WritableStream as u32 {
+        if let Ok(_) = <WritableStream as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
+    if tag == StructuredCloneTags::TransformStream as u32 {
+        if let Ok(_) = <TransformStream as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
     false
 }
 
@@ -243,6 +417,51 @@ unsafe extern "C" fn write_transfer_callback(
             return true;
         }
     }
+    if let Ok(canvas) = root_from_object::<OffscreenCanvas>(*obj, cx) {
+        *tag = StructuredCloneTags::OffscreenCanvas as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = canvas.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
+    if let Ok(image_bitmap) = root_from_object::<ImageBitmap>(*obj, cx) {
+        *tag = StructuredCloneTags::ImageBitmap as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = image_bitmap.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
+    if let Ok(stream) = root_from_object::<ReadableStream>(*obj, cx) {
+        *tag = StructuredCloneTags::ReadableStream as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = stream.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
+    if let Ok(stream) = root_from_object::<WritableStream>(*obj, cx) {
+        *tag = StructuredCloneTags::WritableStream as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = stream.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
+    if let Ok(stream) = root_from_object::<TransformStream>(*obj, cx) {
+        *tag = StructuredCloneTags::TransformStream as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = stream.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
     false
 }
 
@@ -263,6 +482,21 @@ unsafe extern "C" fn can_transfer_callback(
     if let Ok(_port) = root_from_object::<MessagePort>(*obj, cx) {
         return true;
     }
+    if let Ok(_canvas) = root_from_object::<OffscreenCanvas>(*obj, cx) {
+        return true;
+    }
+    if let Ok(_image_bitmap) = root_from_object::<ImageBitmap>(*obj, cx) {
+        return true;
+    }
+    if let Ok(_stream) = root_from_object::<ReadableStream>(*obj, cx) {
+        return true;
+    }
+    if let Ok(_stream) = root_from_object::<WritableStream>(*obj, cx) {
+        return true;
+    }
+    if let Ok(_stream) = root_from_object::<TransformStream>(*obj, cx) {
+        return true;
+    }
     false
 }
 
@@ -284,6 +518,8 @@ pub enum StructuredDataHolder {
     Read {
         /// A deserialized blob, stored temporarily here to keep it rooted.
         blob: Option<DomRoot<Blob>>,
+        /// A deserialized image bitmap, stored temporarily here to keep it rooted.
+        image_bitmap: Option<DomRoot<ImageBitmap>>,
         /// A vec of transfer-received DOM ports,
         /// to be made available to script through a message event.
         message_ports: Option<Vec<DomRoot<MessagePort>>>,
@@ -291,10 +527,38 @@ pub enum StructuredDataHolder {
         /// used as part of the "transfer-receiving" steps of ports,
         /// to produce the DOM ports stored in `message_ports` above.
         port_impls: Option<HashMap<MessagePortId, MessagePortImpl>>,
+        /// The transferred offscreen canvases,
+        /// indexed by the extra data of their transfer.
+        offscreen_canvases: Option<Vec<OffscreenCanvasImpl>>,
+        /// The transferred image bitmaps,
+        /// indexed by the extra data of their transfer.
+        image_bitmaps: Option<Vec<ImageBitmapImpl>>,
+        /// The keys of the ports of the sides of the transferred transform streams,
+        /// indexed by the extra data of their transfer.
+        transform_streams: Option<Vec<(u64, u64)>>,
     },
-    /// A data holder into which transferred ports
+    /// A data holder into which transferred objects
     /// can be written as part of their transfer steps.
-    Write(Option<HashMap<MessagePortId, MessagePortImpl>>),
+    Write {
+        port_impls: Option<HashMap<MessagePortId, MessagePortImpl>>,
+        offscreen_canvases: Option<Vec<OffscreenCanvasImpl>>,
+        image_bitmaps: Option<Vec<ImageBitmapImpl>>,
+        transform_streams: Option<Vec<(u64, u64)>>,
+    },
+}
+
+/// The clone buffer of data serialized with shared memory,
+/// which holds references to the shared memory until it is deleted.
+struct SharedMemoryCloneBuffer(*mut JSAutoStructuredCloneBuffer);
+
+unsafe impl Send for SharedMemoryCloneBuffer {}
+
+impl Drop for SharedMemoryCloneBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            DeleteJSAutoStructuredCloneBuffer(self.0);
+        }
+    }
 }
 
 /// Writes a structured clone. Returns a `DataClone` error if that fails.
@@ -303,29 +567,55 @@ pub fn write(
     message: HandleValue,
     transfer: Option<CustomAutoRooterGuard<Vec<*mut JSObject>>>,
 ) -> Fallible<StructuredSerializedData> {
+    write_with_scope(cx, message, transfer, false)
+}
+
+/// Writes a structured clone that can contain shared memory, such as SharedArrayBuffers,
+/// to be read by another agent of the same agent cluster, running in this process.
+/// Returns a `DataClone` error if that fails.
+pub fn write_with_shared_memory(
+    cx: SafeJSContext,
+    message: HandleValue,
+    transfer: Option<CustomAutoRooterGuard<Vec<*mut JSObject>>>,
+) -> Fallible<StructuredSerializedData> {
+    write_with_scope(cx, message, transfer, true)
+}
+
+fn write_with_scope(
+    cx: SafeJSContext,
+    message: HandleValue,
+    transfer: Option<CustomAutoRooterGuard<Vec<*mut JSObject>>>,
+    shared_memory: bool,
+) -> Fallible<StructuredSerializedData> {
+    let scope = if shared_memory {
+        StructuredCloneScope::SameProcessDifferentThread
+    } else {
+        StructuredCloneScope::DifferentProcess
+    };
     unsafe {
         rooted!(in(*cx) let mut val = UndefinedValue());
         if let Some(transfer) = transfer {
             transfer.to_jsval(*cx, val.handle_mut());
         }
 
-        let mut sc_holder = StructuredDataHolder::Write(None);
+        let mut sc_holder = StructuredDataHolder::Write {
+            port_impls: None,
+            offscreen_canvases: None,
+            image_bitmaps: None,
+            transform_streams: None,
+        };
         let sc_holder_ptr = &mut sc_holder as *mut _;
 
-        let scbuf = NewJSAutoStructuredCloneBuffer(
-            StructuredCloneScope::DifferentProcess,
-            &STRUCTURED_CLONE_CALLBACKS,
-        );
+        let scbuf = NewJSAutoStructuredCloneBuffer(scope, &STRUCTURED_CLONE_CALLBACKS);
         let scdata = &mut ((*scbuf).data_);
         let policy = CloneDataPolicy {
-            // TODO: SAB?
-            sharedArrayBuffer_: false,
+            sharedArrayBuffer_: shared_memory,
         };
         let result = JS_WriteStructuredClone(
             *cx,
             message,
             scdata,
-            StructuredCloneScope::DifferentProcess,
+            scope,
             policy,
             &STRUCTURED_CLONE_CALLBACKS,
             sc_holder_ptr as *mut raw::c_void,
@@ -341,16 +631,37 @@ pub fn write(
         CopyJSStructuredCloneData(scdata, data.as_mut_ptr());
         data.set_len(nbytes);
 
-        DeleteJSAutoStructuredCloneBuffer(scbuf);
+        // The references to shared memory are held by the clone buffer,
+        // which is then kept alive until the data has been read.
+        let shared_memory = if shared_memory {
+            Some(SharedMemoryRefs(Box::new(SharedMemoryCloneBuffer(scbuf))))
+        } else {
+            DeleteJSAutoStructuredCloneBuffer(scbuf);
+            None
+        };
 
-        let mut port_impls = match sc_holder {
-            StructuredDataHolder::Write(port_impls) => port_impls,
+        let (port_impls, offscreen_canvases, image_bitmaps, transform_streams) = match sc_holder {
+            StructuredDataHolder::Write {
+                port_impls,
+                offscreen_canvases,
+                image_bitmaps,
+                transform_streams,
+            } => (
+                port_impls,
+                offscreen_canvases,
+                image_bitmaps,
+                transform_streams,
+            ),
             _ => panic!("Unexpected variant of StructuredDataHolder"),
         };
 
         let data = StructuredSerializedData {
             serialized: data,
-            ports: port_impls.take(),
+            ports: port_impls,
+            offscreen_canvases,
+            image_bitmaps,
+            transform_streams,
+            shared_memory,
         };
 
         Ok(data)
@@ -368,15 +679,23 @@ pub fn read(
     let _ac = enter_realm(&*global);
     let mut sc_holder = StructuredDataHolder::Read {
         blob: None,
+        image_bitmap: None,
         message_ports: None,
         port_impls: data.ports.take(),
+        offscreen_canvases: data.offscreen_canvases.take(),
+        image_bitmaps: data.image_bitmaps.take(),
+        transform_streams: data.transform_streams.take(),
     };
     let sc_holder_ptr = &mut sc_holder as *mut _;
+    // Shared memory can only be read from data kept in this process,
+    // reading it from any other data fails.
+    let scope = if data.shared_memory.is_some() {
+        StructuredCloneScope::SameProcessDifferentThread
+    } else {
+        StructuredCloneScope::DifferentProcess
+    };
     unsafe {
-        let scbuf = NewJSAutoStructuredCloneBuffer(
-            StructuredCloneScope::DifferentProcess,
-            &STRUCTURED_CLONE_CALLBACKS,
-        );
+        let scbuf = NewJSAutoStructuredCloneBuffer(scope, &STRUCTURED_CLONE_CALLBACKS);
         let scdata = &mut ((*scbuf).data_);
 
         WriteBytesToJSStructuredCloneData(
@@ -389,7 +708,7 @@ pub fn read(
             *cx,
             scdata,
             JS_STRUCTURED_CLONE_VERSION,
-            StructuredCloneScope::DifferentProcess,
+            scope,
             rval,
             &STRUCTURED_CLONE_CALLBACKS,
             sc_holder_ptr as *mut raw::c_void,
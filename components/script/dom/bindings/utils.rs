@@ -130,12 +130,17 @@ pub fn message_ports_to_frozen_array(
     message_ports: &[DomRoot<MessagePort>],
     cx: SafeJSContext,
 ) -> JSVal {
-    rooted!(in(*cx) let mut ports = UndefinedValue());
-    unsafe { message_ports.to_jsval(*cx, ports.handle_mut()) };
+    to_frozen_array(message_ports, cx)
+}
+
+/// Returns a JSVal representing a frozen array of values
+pub fn to_frozen_array<T: ToJSValConvertible>(convertibles: &[T], cx: SafeJSContext) -> JSVal {
+    rooted!(in(*cx) let mut array = UndefinedValue());
+    unsafe { convertibles.to_jsval(*cx, array.handle_mut()) };
 
-    rooted!(in(*cx) let obj = ports.to_object());
+    rooted!(in(*cx) let obj = array.to_object());
     unsafe { JS_FreezeObject(*cx, RawHandleObject::from(obj.handle())) };
-    *ports
+    *array
 }
 
 /// Returns the ProtoOrIfaceArray for the given global object.
@@ -2,23 +2,33 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::body::{run_array_buffer_data_algorithm, run_text_data_algorithm, FetchedData};
+use crate::compartments::{enter_realm, InCompartment};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BlobBinding;
 use crate::dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferOrArrayBufferViewOrBlobOrString;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
+use crate::task_source::{TaskSource, TaskSourceName};
 use dom_struct::dom_struct;
+use ipc_channel::router::ROUTER;
 use net_traits::blob_url_store::{get_blob_origin, BlobBuf};
-use net_traits::filemanager_thread::{FileManagerThreadMsg, ReadFileProgress, RelativePos};
+use net_traits::filemanager_thread::{
+    FileManagerResult, FileManagerThreadMsg, ReadFileProgress, RelativePos,
+};
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
 use std::mem;
-use std::ops::Index;
+use std::ops::{Index, Range};
 use std::path::PathBuf;
+use std::rc::Rc;
 use uuid::Uuid;
 
 /// File-based blob
@@ -164,30 +174,121 @@ impl Blob {
         }
     }
 
+    /// Where the content of the Blob can be read from: its bytes if they are
+    /// in this process, or the range of a file of the file manager.
+    fn content(&self) -> BlobContent {
+        match *self.blob_impl.borrow() {
+            BlobImpl::File(ref f) => match *f.cache.borrow() {
+                Some(ref bytes) => BlobContent::Bytes(bytes.clone()),
+                None => BlobContent::File(f.id.clone(), 0..f.size as usize),
+            },
+            BlobImpl::Memory(ref s) => BlobContent::Bytes(s.clone()),
+            BlobImpl::Sliced(ref parent, ref rel_pos) => match parent.content() {
+                BlobContent::Bytes(bytes) => {
+                    let range = rel_pos.to_abs_range(bytes.len());
+                    BlobContent::Bytes(bytes.index(range).to_vec())
+                },
+                BlobContent::File(id, range) => {
+                    BlobContent::File(id, rel_pos.to_abs_range(range.len()))
+                },
+            },
+        }
+    }
+
+    /// Reads the content of the Blob without blocking, the returned stream
+    /// getting a chunk enqueued for each chunk that the file manager reads.
+    fn read_into_stream(&self) -> DomRoot<ReadableStream> {
+        let global = self.global();
+        let (id, range) = match self.content() {
+            BlobContent::Bytes(bytes) => return ReadableStream::new_from_bytes(&global, bytes),
+            BlobContent::File(id, range) => (id, range),
+        };
+        let stream = ReadableStream::new_with_native_source(&global);
+        let trusted_stream = Trusted::new(&*stream);
+        let task_source = global.file_reading_task_source();
+        let canceller = global.task_canceller(TaskSourceName::FileReading);
+        read_file_async(&global, id, range, move |progress| {
+            let stream = trusted_stream.clone();
+            let _ = task_source.queue_with_canceller(
+                task!(enqueue_blob_chunk: move || {
+                    let stream = stream.root();
+                    match progress {
+                        Ok(Some(bytes)) => stream.enqueue_native(bytes),
+                        Ok(None) => stream.close_native(),
+                        Err(()) => stream.error_native(Error::NotReadable),
+                    }
+                }),
+                &canceller,
+            );
+        });
+        stream
+    }
+
+    /// Reads all the content of the Blob without blocking, then calls
+    /// `callback` with it from a task queued on the file reading task source.
+    pub fn read_all<F>(&self, callback: F)
+    where
+        F: FnOnce(Result<Vec<u8>, ()>) + Send + 'static,
+    {
+        let global = self.global();
+        let task_source = global.file_reading_task_source();
+        let canceller = global.task_canceller(TaskSourceName::FileReading);
+        let (id, range) = match self.content() {
+            BlobContent::Bytes(bytes) => {
+                let _ = task_source.queue_with_canceller(
+                    task!(read_blob: move || { callback(Ok(bytes)) }),
+                    &canceller,
+                );
+                return;
+            },
+            BlobContent::File(id, range) => (id, range),
+        };
+        let mut bytes = vec![];
+        let mut callback = Some(callback);
+        read_file_async(&global, id, range, move |progress| {
+            let result = match progress {
+                Ok(Some(mut chunk)) => return bytes.append(&mut chunk),
+                Ok(None) => Ok(mem::replace(&mut bytes, vec![])),
+                Err(()) => Err(()),
+            };
+            if let Some(callback) = callback.take() {
+                let _ = task_source.queue_with_canceller(
+                    task!(read_blob: move || { callback(result) }),
+                    &canceller,
+                );
+            }
+        });
+    }
+
     /// Get a copy of the type_string
     pub fn type_string(&self) -> String {
         self.type_string.clone()
     }
 
-    /// Get a FileID representing the Blob content,
-    /// used by URL.createObjectURL
+    /// Get a new FileID representing the Blob content,
+    /// used by URL.createObjectURL.
+    /// Every call adds a new entry to the Blob URL store,
+    /// which is valid until it is revoked.
     pub fn get_blob_url_id(&self) -> Uuid {
         let opt_sliced_parent = match *self.blob_impl.borrow() {
             BlobImpl::Sliced(ref parent, ref rel_pos) => {
-                Some((
-                    parent.promote(/* set_valid is */ false),
-                    rel_pos.clone(),
-                    parent.Size(),
-                ))
+                Some((DomRoot::from_ref(&**parent), rel_pos.clone()))
             },
             _ => None,
         };
+        let (blob, rel_pos) = opt_sliced_parent
+            .unwrap_or_else(|| (DomRoot::from_ref(self), RelativePos::full_range()));
 
-        match opt_sliced_parent {
-            Some((parent_id, rel_pos, size)) => {
-                self.create_sliced_url_id(&parent_id, &rel_pos, size)
-            },
-            None => self.promote(/* set_valid is */ true),
+        let parent_id = blob.promote(/* set_valid is */ false);
+
+        let origin = get_blob_origin(&self.global().get_url());
+        let (tx, rx) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        let msg = FileManagerThreadMsg::AddBlobURLEntry(parent_id, rel_pos, tx, origin);
+        self.send_to_file_manager(msg);
+        match rx.recv().expect("File manager thread is down") {
+            Ok(new_id) => new_id,
+            // Return a dummy id on error
+            Err(_) => Uuid::new_v4(),
         }
     }
 
@@ -251,42 +352,6 @@ impl Blob {
         id
     }
 
-    /// Get a FileID representing sliced parent-blob content
-    fn create_sliced_url_id(
-        &self,
-        parent_id: &Uuid,
-        rel_pos: &RelativePos,
-        parent_len: u64,
-    ) -> Uuid {
-        let origin = get_blob_origin(&self.global().get_url());
-
-        let (tx, rx) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
-        let msg = FileManagerThreadMsg::AddSlicedURLEntry(
-            parent_id.clone(),
-            rel_pos.clone(),
-            tx,
-            origin.clone(),
-        );
-        self.send_to_file_manager(msg);
-        match rx.recv().expect("File manager thread is down") {
-            Ok(new_id) => {
-                *self.blob_impl.borrow_mut() = BlobImpl::File(FileBlob {
-                    id: new_id.clone(),
-                    name: None,
-                    cache: DomRefCell::new(None),
-                    size: rel_pos.to_abs_range(parent_len as usize).len() as u64,
-                });
-
-                // Return the indirect id reference
-                new_id
-            },
-            Err(_) => {
-                // Return dummy id
-                Uuid::new_v4()
-            },
-        }
-    }
-
     /// Cleanups at the time of destruction/closing
     fn clean_up_file_resource(&self) {
         if let BlobImpl::File(ref f) = *self.blob_impl.borrow() {
@@ -339,6 +404,63 @@ fn read_file(global: &GlobalScope, id: Uuid) -> Result<Vec<u8>, ()> {
     }
 }
 
+/// Where the content of a Blob can be read from.
+enum BlobContent {
+    Bytes(Vec<u8>),
+    File(Uuid, Range<usize>),
+}
+
+/// Reads `range` of the file `id` of the file manager without blocking.
+/// `on_progress` is called on the IPC router thread with each chunk of the
+/// range, then with `None` once it is all read, or with an error.
+fn read_file_async<F>(global: &GlobalScope, id: Uuid, range: Range<usize>, mut on_progress: F)
+where
+    F: FnMut(Result<Option<Vec<u8>>, ()>) + Send + 'static,
+{
+    let (chan, recv) = match ipc_channel::ipc::channel() {
+        Ok(channel) => channel,
+        Err(_) => return on_progress(Err(())),
+    };
+    let origin = get_blob_origin(&global.get_url());
+    let check_url_validity = false;
+    let msg = FileManagerThreadMsg::ReadFile(chan, id, check_url_validity, origin);
+    let _ = global
+        .resource_threads()
+        .send(CoreResourceMsg::ToFileManager(msg));
+
+    // The position in the file of the next chunk.
+    let mut position = 0;
+    let mut done = false;
+    ROUTER.add_route(
+        recv.to_opaque(),
+        Box::new(move |message| {
+            if done {
+                return;
+            }
+            let chunk = match message.to::<FileManagerResult<ReadFileProgress>>() {
+                Ok(Ok(ReadFileProgress::Meta(blob_buf))) => blob_buf.bytes,
+                Ok(Ok(ReadFileProgress::Partial(bytes))) => bytes,
+                Ok(Ok(ReadFileProgress::EOF)) => {
+                    done = true;
+                    return on_progress(Ok(None));
+                },
+                _ => {
+                    done = true;
+                    return on_progress(Err(()));
+                },
+            };
+            let start = position;
+            position += chunk.len();
+            let chunk_range = range.start.max(start)..range.end.min(position);
+            if chunk_range.start < chunk_range.end {
+                on_progress(Ok(Some(
+                    chunk[chunk_range.start - start..chunk_range.end - start].to_vec(),
+                )));
+            }
+        }),
+    );
+}
+
 /// Extract bytes from BlobParts, used by Blob and File constructor
 /// <https://w3c.github.io/FileAPI/#constructorBlob>
 #[allow(unsafe_code)]
@@ -396,6 +518,46 @@ impl BlobMethods for Blob {
         let rel_pos = RelativePos::from_opts(start, end);
         Blob::new_sliced(self, rel_pos, content_type.unwrap_or(DOMString::from("")))
     }
+
+    // https://w3c.github.io/FileAPI/#stream-method-algo
+    fn Stream(&self) -> DomRoot<ReadableStream> {
+        self.read_into_stream()
+    }
+
+    // https://w3c.github.io/FileAPI/#text-method-algo
+    fn Text(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        self.read_all(move |bytes| {
+            let promise = trusted_promise.root();
+            match bytes.map(run_text_data_algorithm) {
+                Ok(Ok(FetchedData::Text(text))) => promise.resolve_native(&USVString(text)),
+                _ => promise.reject_error(Error::NotReadable),
+            }
+        });
+        promise
+    }
+
+    // https://w3c.github.io/FileAPI/#arraybuffer-method-algo
+    fn ArrayBuffer(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        self.read_all(move |bytes| {
+            let promise = trusted_promise.root();
+            let bytes = match bytes {
+                Ok(bytes) => bytes,
+                Err(()) => return promise.reject_error(Error::NotReadable),
+            };
+            let cx = promise.global().get_cx();
+            let _ac = enter_realm(&*promise);
+            match run_array_buffer_data_algorithm(cx, bytes) {
+                Ok(FetchedData::ArrayBuffer(buffer)) => promise.resolve_native(&buffer),
+                Ok(_) => unreachable!(),
+                Err(error) => promise.reject_error(error),
+            }
+        });
+        promise
+    }
 }
 
 /// Get the normalized, MIME-parsable type string
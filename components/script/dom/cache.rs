@@ -0,0 +1,636 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CacheBinding;
+use crate::dom::bindings::codegen::Bindings::CacheBinding::{CacheMethods, CacheQueryOptions};
+use crate::dom::bindings::codegen::Bindings::HeadersBinding::HeadersMethods;
+use crate::dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::{
+    ResponseMethods, ResponseType as DOMResponseType,
+};
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::headers::Guard;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::fetch;
+use crate::script_runtime::JSContext as SafeJSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use http::header::VARY;
+use http::Method;
+use hyper_serde::Serde;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use js::jsapi::JSContext;
+use js::rust::HandleValue;
+use js::typedarray::ArrayBuffer;
+use net_traits::cache_storage_thread::CacheQueryOptions as NetTraitsCacheQueryOptions;
+use net_traits::cache_storage_thread::{CacheStorageError, CacheStorageThreadMsg};
+use net_traits::cache_storage_thread::{CachedRequest, CachedResponse};
+use net_traits::request::{Origin, Request as NetTraitsRequest};
+use net_traits::IpcSend;
+use serde::{Deserialize, Serialize};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use std::cell::Cell;
+use std::rc::Rc;
+
+// https://w3c.github.io/ServiceWorker/#cache-interface
+#[dom_struct]
+pub struct Cache {
+    reflector_: Reflector,
+    name: DOMString,
+}
+
+impl Cache {
+    fn new_inherited(name: DOMString) -> Cache {
+        Cache {
+            reflector_: Reflector::new(),
+            name: name,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, name: DOMString) -> DomRoot<Cache> {
+        reflect_dom_object(
+            Box::new(Cache::new_inherited(name)),
+            global,
+            CacheBinding::Wrap,
+        )
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#query-cache>
+    fn query<F>(
+        &self,
+        promise: &Rc<Promise>,
+        request: Option<CachedRequest>,
+        options: &CacheQueryOptions,
+        settle: F,
+    ) where
+        F: FnOnce(&Promise, Vec<(CachedRequest, CachedResponse)>) + Send + 'static,
+    {
+        let global = self.global();
+        let origin = global.origin().immutable().clone();
+        let name = String::from(self.name.clone());
+        let options = net_query_options(options);
+        send_with_reply(
+            &global,
+            promise,
+            move |sender| {
+                CacheStorageThreadMsg::MatchAll(sender, origin, Some(name), request, options)
+            },
+            settle,
+        );
+    }
+}
+
+impl CacheMethods for Cache {
+    // https://w3c.github.io/ServiceWorker/#cache-match
+    fn Match(
+        &self,
+        request: RequestInfo,
+        options: &CacheQueryOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let request = match cached_request_from_request_info(&global, request) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.query(
+            &promise,
+            Some(request),
+            options,
+            resolve_with_first_response,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-matchall
+    fn MatchAll(
+        &self,
+        request: Option<RequestInfo>,
+        options: &CacheQueryOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let request = match request
+            .map(|request| cached_request_from_request_info(&global, request))
+            .transpose()
+        {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.query(&promise, request, options, |promise, entries| {
+            let global = promise.global();
+            let responses: Vec<_> = entries
+                .into_iter()
+                .map(|(_, response)| response_from_cached_response(&global, response))
+                .collect();
+            promise.resolve_native(&responses);
+        });
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-add
+    fn Add(&self, request: RequestInfo, comp: InCompartment) -> Rc<Promise> {
+        self.AddAll(vec![request], comp)
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-addAll
+    fn AddAll(&self, requests: Vec<RequestInfo>, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+
+        // Step 1-3.
+        let mut request_list = vec![];
+        for request in requests {
+            let request = match request {
+                RequestInfo::Request(request) => request,
+                RequestInfo::USVString(url) => {
+                    match Request::Constructor(
+                        &global,
+                        RequestInfo::USVString(url),
+                        RequestInit::empty(),
+                    ) {
+                        Ok(request) => request,
+                        Err(error) => {
+                            promise.reject_error(error);
+                            return promise;
+                        },
+                    }
+                },
+            };
+            let cached_request = cached_request_from_request(&request);
+            if let Err(error) = check_request_for_put(&cached_request) {
+                promise.reject_error(error);
+                return promise;
+            }
+            request_list.push((request, cached_request));
+        }
+
+        // Step 4-6.
+        let batch = BatchPut::new(self, &promise, request_list.len());
+        for (index, (request, cached_request)) in request_list.into_iter().enumerate() {
+            let response_promise = fetch::Fetch(
+                &global,
+                RequestInfo::Request(request),
+                RequestInit::empty(),
+                comp,
+            );
+            let handler = PromiseNativeHandler::new(
+                &global,
+                Some(Box::new(FetchedResponse {
+                    batch: batch.clone(),
+                    index: index,
+                    request: cached_request,
+                })),
+                Some(Box::new(BatchPutFailed {
+                    batch: batch.clone(),
+                })),
+            );
+            response_promise.append_native_handler(&handler);
+        }
+
+        // Step 7.
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-put
+    fn Put(&self, request: RequestInfo, response: &Response, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+
+        // Step 1-4.
+        let request = match cached_request_from_request_info(&global, request) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        if let Err(error) = check_request_for_put(&request) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        // Step 5-7.
+        if response.Status() == 206 || varies_on_everything(response) {
+            promise.reject_error(Error::Type(
+                "The response can not be stored in a cache".to_owned(),
+            ));
+            return promise;
+        }
+
+        // Step 8-9.
+        if response.BodyUsed() {
+            promise.reject_error(Error::Type("The response body is already used".to_owned()));
+            return promise;
+        }
+
+        // Step 10-13.
+        let batch = BatchPut::new(self, &promise, 1);
+        BatchPut::read_response(&batch, 0, request, response);
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-delete
+    fn Delete(
+        &self,
+        request: RequestInfo,
+        options: &CacheQueryOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let request = match cached_request_from_request_info(&global, request) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let origin = global.origin().immutable().clone();
+        let name = String::from(self.name.clone());
+        let options = net_query_options(options);
+        send_with_reply(
+            &global,
+            &promise,
+            move |sender| CacheStorageThreadMsg::Remove(sender, origin, name, request, options),
+            |promise, removed: bool| promise.resolve_native(&removed),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-keys
+    fn Keys(
+        &self,
+        request: Option<RequestInfo>,
+        options: &CacheQueryOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let request = match request
+            .map(|request| cached_request_from_request_info(&global, request))
+            .transpose()
+        {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.query(&promise, request, options, |promise, entries| {
+            let global = promise.global();
+            let requests: Vec<_> = entries
+                .into_iter()
+                .map(|(request, _)| request_from_cached_request(&global, request))
+                .collect();
+            promise.resolve_native(&requests);
+        });
+        promise
+    }
+}
+
+/// Sends a message to the cache storage thread without waiting for its reply,
+/// `settle` is called with the promise and the reply from a task queued on the
+/// DOM manipulation task source.
+pub fn send_with_reply<T, M, F>(global: &GlobalScope, promise: &Rc<Promise>, message: M, settle: F)
+where
+    T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+    M: FnOnce(IpcSender<T>) -> CacheStorageThreadMsg,
+    F: FnOnce(&Promise, T) + Send + 'static,
+{
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    let task_source = global.dom_manipulation_task_source();
+    let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+    let mut pending = Some((TrustedPromise::new(promise.clone()), settle));
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            let reply: T = match message.to() {
+                Ok(reply) => reply,
+                Err(_) => return,
+            };
+            if let Some((promise, settle)) = pending.take() {
+                let _ = task_source.queue_with_canceller(
+                    task!(settle_cache_promise: move || {
+                        settle(&promise.root(), reply);
+                    }),
+                    &canceller,
+                );
+            }
+        }),
+    );
+    let _ = global.resource_threads().sender().send(message(sender));
+}
+
+/// Resolves the promise of a match with the response of the first entry, if any.
+pub fn resolve_with_first_response(
+    promise: &Promise,
+    entries: Vec<(CachedRequest, CachedResponse)>,
+) {
+    match entries.into_iter().next() {
+        Some((_, response)) => {
+            promise.resolve_native(&response_from_cached_response(&promise.global(), response))
+        },
+        None => promise.resolve_native(&()),
+    }
+}
+
+pub fn net_query_options(options: &CacheQueryOptions) -> NetTraitsCacheQueryOptions {
+    NetTraitsCacheQueryOptions {
+        ignore_search: options.ignoreSearch,
+        ignore_method: options.ignoreMethod,
+        ignore_vary: options.ignoreVary,
+    }
+}
+
+/// The request that is matched against the entries of a cache,
+/// a string is parsed into a new request, as for the `Request` constructor.
+pub fn cached_request_from_request_info(
+    global: &GlobalScope,
+    request: RequestInfo,
+) -> Fallible<CachedRequest> {
+    let request = match request {
+        RequestInfo::Request(request) => request,
+        RequestInfo::USVString(url) => {
+            Request::Constructor(global, RequestInfo::USVString(url), RequestInit::empty())?
+        },
+    };
+    Ok(cached_request_from_request(&request))
+}
+
+fn cached_request_from_request(request: &Request) -> CachedRequest {
+    let request = request.get_request();
+    CachedRequest {
+        url: request.url(),
+        method: request.method.as_str().to_owned(),
+        headers: Serde(request.headers),
+    }
+}
+
+fn request_from_cached_request(global: &GlobalScope, request: CachedRequest) -> DomRoot<Request> {
+    let origin = Origin::Origin(global.origin().immutable().clone());
+    let mut net_request =
+        NetTraitsRequest::new(request.url, Some(origin), Some(global.pipeline_id()));
+    net_request.method = Method::from_bytes(request.method.as_bytes()).unwrap_or(Method::GET);
+    net_request.headers = request.headers.clone().into_inner();
+    let dom_request = Request::from_net_request(global, net_request);
+    dom_request
+        .Headers()
+        .set_headers(request.headers.into_inner());
+    dom_request.Headers().set_guard(Guard::Request);
+    dom_request
+}
+
+pub fn response_from_cached_response(
+    global: &GlobalScope,
+    response: CachedResponse,
+) -> DomRoot<Response> {
+    let dom_response = Response::new(global);
+    dom_response.set_type(DOMResponseType::Basic);
+    dom_response.set_headers(Some(response.headers));
+    dom_response.Headers().set_guard(Guard::Immutable);
+    dom_response.set_raw_status(response.status);
+    if let Some(url) = response.url {
+        dom_response.set_final_url(url);
+    }
+    dom_response.stream_chunk(&response.body);
+    dom_response.finish();
+    dom_response
+}
+
+/// <https://w3c.github.io/ServiceWorker/#cache-put> Step 3-4,
+/// only GET requests to http(s) URLs can be stored.
+fn check_request_for_put(request: &CachedRequest) -> ErrorResult {
+    let scheme = request.url.scheme();
+    if scheme != "http" && scheme != "https" {
+        return Err(Error::Type(format!(
+            "Requests with a {} URL can not be stored in a cache",
+            scheme
+        )));
+    }
+    if request.method != "GET" {
+        return Err(Error::Type(format!(
+            "{} requests can not be stored in a cache",
+            request.method
+        )));
+    }
+    Ok(())
+}
+
+/// Whether the response has a `Vary` header containing `*`,
+/// which would never match any request.
+fn varies_on_everything(response: &Response) -> bool {
+    response
+        .Headers()
+        .get_headers_list()
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|field_name| field_name.trim() == "*")
+}
+
+/// The entries that a put, add or addAll stores together in a cache,
+/// once the bodies of all of their responses have been read.
+///
+/// <https://w3c.github.io/ServiceWorker/#batch-cache-operations>
+#[derive(JSTraceable, MallocSizeOf)]
+struct BatchPut {
+    origin: ImmutableOrigin,
+    name: String,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    entries: DomRefCell<Vec<Option<(CachedRequest, CachedResponse)>>>,
+    pending: Cell<usize>,
+}
+
+impl BatchPut {
+    fn new(cache: &Cache, promise: &Rc<Promise>, count: usize) -> Rc<BatchPut> {
+        let batch = Rc::new(BatchPut {
+            origin: cache.global().origin().immutable().clone(),
+            name: String::from(cache.name.clone()),
+            promise: promise.clone(),
+            entries: DomRefCell::new(vec![None; count]),
+            pending: Cell::new(count),
+        });
+        if count == 0 {
+            batch.store();
+        }
+        batch
+    }
+
+    /// Reads the body of `response`, which is stored along with `request`.
+    fn read_response(
+        batch: &Rc<BatchPut>,
+        index: usize,
+        request: CachedRequest,
+        response: &Response,
+    ) {
+        let cached_response = CachedResponse {
+            url: ServoUrl::parse(&response.Url()).ok(),
+            status: Some((response.Status(), response.StatusText().to_vec())),
+            headers: Serde(response.Headers().get_headers_list()),
+            body: vec![],
+        };
+        let handler = PromiseNativeHandler::new(
+            &response.global(),
+            Some(Box::new(ResponseBodyRead {
+                batch: batch.clone(),
+                index: index,
+                request: request,
+                response: cached_response,
+            })),
+            Some(Box::new(BatchPutFailed {
+                batch: batch.clone(),
+            })),
+        );
+        response.ArrayBuffer().append_native_handler(&handler);
+    }
+
+    fn add_entry(&self, index: usize, request: CachedRequest, response: CachedResponse) {
+        self.entries.borrow_mut()[index] = Some((request, response));
+        self.pending.set(self.pending.get() - 1);
+        if self.pending.get() == 0 {
+            self.store();
+        }
+    }
+
+    fn store(&self) {
+        // One of the responses failed, and the whole batch was rejected.
+        if self.promise.is_fulfilled() {
+            return;
+        }
+        let entries = self.entries.borrow_mut().drain(..).flatten().collect();
+        let origin = self.origin.clone();
+        let name = self.name.clone();
+        send_with_reply(
+            &self.promise.global(),
+            &self.promise,
+            move |sender| CacheStorageThreadMsg::Put(sender, origin, name, entries),
+            |promise, result| match result {
+                Ok(()) => promise.resolve_native(&()),
+                Err(CacheStorageError::NotFound) => promise.reject_error(Error::NotFound),
+                Err(CacheStorageError::QuotaExceeded) => promise.reject_error(Error::QuotaExceeded),
+            },
+        );
+    }
+
+    fn fail(&self, error: Error) {
+        if !self.promise.is_fulfilled() {
+            self.promise.reject_error(error);
+        }
+    }
+}
+
+/// Checks the response fetched by add or addAll, then reads its body.
+#[derive(JSTraceable, MallocSizeOf)]
+struct FetchedResponse {
+    #[ignore_malloc_size_of = "Rc"]
+    batch: Rc<BatchPut>,
+    index: usize,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    request: CachedRequest,
+}
+
+impl Callback for FetchedResponse {
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        let response = match root_from_handlevalue::<Response>(v, cx) {
+            Ok(response) => response,
+            Err(()) => {
+                return self
+                    .batch
+                    .fail(Error::Type("Expected a response".to_owned()))
+            },
+        };
+        // https://w3c.github.io/ServiceWorker/#cache-addAll Step 5.6
+        let status = response.Status();
+        if response.Type() == DOMResponseType::Error ||
+            status < 200 ||
+            status > 299 ||
+            varies_on_everything(&response)
+        {
+            return self.batch.fail(Error::Type(format!(
+                "The response for {} can not be stored in a cache",
+                self.request.url
+            )));
+        }
+        BatchPut::read_response(&self.batch, self.index, self.request.clone(), &response);
+    }
+}
+
+/// Stores the body of a response in its entry, once it has been read.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ResponseBodyRead {
+    #[ignore_malloc_size_of = "Rc"]
+    batch: Rc<BatchPut>,
+    index: usize,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    request: CachedRequest,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    response: CachedResponse,
+}
+
+impl Callback for ResponseBodyRead {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        if !v.is_object() {
+            return self
+                .batch
+                .fail(Error::Type("Expected an ArrayBuffer".to_owned()));
+        }
+        let body = unsafe {
+            typedarray!(in(cx) let buffer: ArrayBuffer = v.to_object());
+            buffer.map(|buffer| buffer.to_vec())
+        };
+        match body {
+            Ok(body) => {
+                let mut response = self.response.clone();
+                response.body = body;
+                self.batch
+                    .add_entry(self.index, self.request.clone(), response);
+            },
+            Err(()) => self
+                .batch
+                .fail(Error::Type("Expected an ArrayBuffer".to_owned())),
+        }
+    }
+}
+
+/// Rejects a batch when fetching a response or reading its body failed.
+#[derive(JSTraceable, MallocSizeOf)]
+struct BatchPutFailed {
+    #[ignore_malloc_size_of = "Rc"]
+    batch: Rc<BatchPut>,
+}
+
+impl Callback for BatchPutFailed {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        if !self.batch.promise.is_fulfilled() {
+            let cx = unsafe { SafeJSContext::from_ptr(cx) };
+            self.batch.promise.reject(cx, v);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::CacheStorageBinding;
+use crate::dom::bindings::codegen::Bindings::CacheStorageBinding::{
+    CacheStorageMethods, MultiCacheQueryOptions,
+};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cache::{self, Cache};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
+use servo_url::ImmutableOrigin;
+use std::rc::Rc;
+
+// https://w3c.github.io/ServiceWorker/#cachestorage-interface
+#[dom_struct]
+pub struct CacheStorage {
+    reflector_: Reflector,
+}
+
+impl CacheStorage {
+    fn new_inherited() -> CacheStorage {
+        CacheStorage {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CacheStorage> {
+        reflect_dom_object(
+            Box::new(CacheStorage::new_inherited()),
+            global,
+            CacheStorageBinding::Wrap,
+        )
+    }
+
+    /// The origin whose caches are used, opaque origins have no caches.
+    fn origin(&self) -> Fallible<ImmutableOrigin> {
+        let origin = self.global().origin().immutable().clone();
+        if origin.is_tuple() {
+            Ok(origin)
+        } else {
+            Err(Error::Security)
+        }
+    }
+}
+
+impl CacheStorageMethods for CacheStorage {
+    // https://w3c.github.io/ServiceWorker/#cache-storage-match
+    fn Match(
+        &self,
+        request: RequestInfo,
+        options: &MultiCacheQueryOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let origin = match self.origin() {
+            Ok(origin) => origin,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let request = match cache::cached_request_from_request_info(&global, request) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Step 1-2, the caches are queried in the order they were created,
+        // a cache that does not exist matches nothing.
+        let name = options.cacheName.clone().map(String::from);
+        let query_options = cache::net_query_options(&options.parent);
+        cache::send_with_reply(
+            &global,
+            &promise,
+            move |sender| {
+                CacheStorageThreadMsg::MatchAll(sender, origin, name, Some(request), query_options)
+            },
+            cache::resolve_with_first_response,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-has
+    fn Has(&self, cache_name: DOMString, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let origin = match self.origin() {
+            Ok(origin) => origin,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let name = String::from(cache_name);
+        cache::send_with_reply(
+            &global,
+            &promise,
+            move |sender| CacheStorageThreadMsg::Has(sender, origin, name),
+            |promise, has: bool| promise.resolve_native(&has),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-open
+    fn Open(&self, cache_name: DOMString, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let origin = match self.origin() {
+            Ok(origin) => origin,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let name = String::from(cache_name);
+        let cache_name = name.clone();
+        cache::send_with_reply(
+            &global,
+            &promise,
+            move |sender| CacheStorageThreadMsg::Open(sender, origin, name),
+            move |promise, ()| {
+                let cache = Cache::new(&promise.global(), DOMString::from(cache_name));
+                promise.resolve_native(&cache);
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-delete
+    fn Delete(&self, cache_name: DOMString, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let origin = match self.origin() {
+            Ok(origin) => origin,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let name = String::from(cache_name);
+        cache::send_with_reply(
+            &global,
+            &promise,
+            move |sender| CacheStorageThreadMsg::Delete(sender, origin, name),
+            |promise, deleted: bool| promise.resolve_native(&deleted),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-keys
+    fn Keys(&self, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        let origin = match self.origin() {
+            Ok(origin) => origin,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        cache::send_with_reply(
+            &global,
+            &promise,
+            move |sender| CacheStorageThreadMsg::Keys(sender, origin),
+            |promise, names: Vec<String>| {
+                let names: Vec<DOMString> = names.into_iter().map(DOMString::from).collect();
+                promise.resolve_native(&names);
+            },
+        );
+        promise
+    }
+}
@@ -11,9 +11,8 @@ use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
-use canvas_traits::canvas::{
-    CanvasGradientStop, FillOrStrokeStyle, LinearGradientStyle, RadialGradientStyle,
-};
+use canvas_traits::canvas::{CanvasGradientStop, ConicGradientStyle, FillOrStrokeStyle};
+use canvas_traits::canvas::{LinearGradientStyle, RadialGradientStyle};
 use cssparser::Color as CSSColor;
 use cssparser::{Parser, ParserInput, RGBA};
 use dom_struct::dom_struct;
@@ -30,6 +29,7 @@ pub struct CanvasGradient {
 pub enum CanvasGradientStyle {
     Linear(LinearGradientStyle),
     Radial(RadialGradientStyle),
+    Conic(ConicGradientStyle),
 }
 
 impl CanvasGradient {
@@ -106,6 +106,14 @@ impl<'a> ToFillOrStrokeStyle for &'a CanvasGradient {
                     gradient_stops,
                 ))
             },
+            CanvasGradientStyle::Conic(ref gradient) => {
+                FillOrStrokeStyle::ConicGradient(ConicGradientStyle::new(
+                    gradient.start_angle,
+                    gradient.x,
+                    gradient.y,
+                    gradient_stops,
+                ))
+            },
         }
     }
 }
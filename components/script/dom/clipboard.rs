@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::clipboard_provider::ClipboardProvider;
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::ClipboardBinding::{self, ClipboardMethods};
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::PresentationStyle;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::clipboarditem::ClipboardItem;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use script_traits::ScriptToConstellationChan;
+use std::rc::Rc;
+
+/// The only type of data the system clipboard holds.
+const TEXT_PLAIN: &'static str = "text/plain";
+
+// https://w3c.github.io/clipboard-apis/#clipboard-interface
+#[dom_struct]
+pub struct Clipboard {
+    eventtarget: EventTarget,
+}
+
+impl Clipboard {
+    fn new_inherited() -> Clipboard {
+        Clipboard {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Clipboard> {
+        reflect_dom_object(
+            Box::new(Clipboard::new_inherited()),
+            global,
+            ClipboardBinding::Wrap,
+        )
+    }
+
+    /// The system clipboard, accessed through the embedder.
+    fn clipboard_provider(&self) -> ScriptToConstellationChan {
+        self.global().script_to_constellation_chan().clone()
+    }
+
+    // https://w3c.github.io/clipboard-apis/#check-clipboard-read-permission
+    // https://w3c.github.io/clipboard-apis/#check-clipboard-write-permission
+    /// Rejects the promise if the permission is not granted, and runs `steps`
+    /// otherwise, once the user answered the prompt if any.
+    fn check_permission<F>(&self, permission_name: PermissionName, promise: &Rc<Promise>, steps: F)
+    where
+        F: FnOnce(&Clipboard, &Promise) + Send + 'static,
+    {
+        let this = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        request_permission_to_use(permission_name, &self.global(), move |state| {
+            let promise = trusted_promise.root();
+            if state != PermissionState::Granted {
+                return promise.reject_error(Error::NotAllowed);
+            }
+            steps(&this.root(), &promise);
+        });
+    }
+
+    /// Steps 2.3-2.5 of `read()`.
+    fn read(&self, promise: &Promise) {
+        let global = self.global();
+        let text = self.clipboard_provider().clipboard_contents();
+        let items = if text.is_empty() {
+            vec![]
+        } else {
+            let blob = Blob::new(
+                &global,
+                BlobImpl::new_from_bytes(text.into_bytes()),
+                TEXT_PLAIN.to_owned(),
+            );
+            vec![ClipboardItem::new(
+                &global,
+                vec![(DOMString::from(TEXT_PLAIN), blob)],
+                PresentationStyle::Unspecified,
+            )]
+        };
+        promise.resolve_native(&items);
+    }
+
+    /// Steps 2.3-2.7 of `write()`.
+    fn write(&self, data: &[DomRoot<ClipboardItem>], promise: &Promise) {
+        // The system clipboard holds a single item.
+        if data.len() > 1 {
+            return promise.reject_error(Error::NotAllowed);
+        }
+
+        // Steps 2.3-2.5.
+        let mut text = String::new();
+        for (mime_type, blob) in data.iter().flat_map(|item| item.representations()) {
+            // TODO: Write the other mandatory data types,
+            // once the embedder can hold them on the system clipboard.
+            if &*mime_type != TEXT_PLAIN {
+                return promise.reject_error(Error::NotAllowed);
+            }
+            match blob.get_bytes() {
+                Ok(bytes) => text = String::from_utf8_lossy(&bytes).into_owned(),
+                Err(()) => return promise.reject_error(Error::NotReadable),
+            }
+        }
+
+        // Steps 2.6-2.7.
+        self.clipboard_provider().set_clipboard_contents(text);
+        promise.resolve_native(&());
+    }
+}
+
+impl ClipboardMethods for Clipboard {
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-read
+    fn Read(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 2.1-2.5.
+        self.check_permission(
+            PermissionName::Clipboard_read,
+            &promise,
+            |clipboard, promise| clipboard.read(promise),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-readtext
+    fn ReadText(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 2.1-2.5.
+        self.check_permission(
+            PermissionName::Clipboard_read,
+            &promise,
+            |clipboard, promise| {
+                let text = clipboard.clipboard_provider().clipboard_contents();
+                promise.resolve_native(&DOMString::from(text));
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-write
+    fn Write(&self, data: Vec<DomRoot<ClipboardItem>>, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 2.1-2.7.
+        let data: Vec<Trusted<ClipboardItem>> =
+            data.iter().map(|item| Trusted::new(&**item)).collect();
+        self.check_permission(
+            PermissionName::Clipboard_write,
+            &promise,
+            move |clipboard, promise| {
+                let data: Vec<DomRoot<ClipboardItem>> =
+                    data.iter().map(|item| item.root()).collect();
+                clipboard.write(&data, promise);
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-writetext
+    fn WriteText(&self, data: DOMString, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 2.1-2.6.
+        let text = String::from(data);
+        self.check_permission(
+            PermissionName::Clipboard_write,
+            &promise,
+            move |clipboard, promise| {
+                clipboard.clipboard_provider().set_clipboard_contents(text);
+                promise.resolve_native(&());
+            },
+        );
+        promise
+    }
+}
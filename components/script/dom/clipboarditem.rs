@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::{
+    self, ClipboardItemMethods, ClipboardItemOptions, PresentationStyle,
+};
+use crate::dom::bindings::codegen::UnionTypes::StringOrBlob;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::record::Record;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use std::rc::Rc;
+
+/// <https://w3c.github.io/clipboard-apis/#representation>
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+struct Representation {
+    mime_type: DOMString,
+    data: Dom<Blob>,
+}
+
+// https://w3c.github.io/clipboard-apis/#clipboard-item-interface
+#[dom_struct]
+pub struct ClipboardItem {
+    reflector_: Reflector,
+    representations: Vec<Representation>,
+    presentation_style: PresentationStyle,
+    #[ignore_malloc_size_of = "mozjs"]
+    types: Heap<JSVal>,
+}
+
+impl ClipboardItem {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        representations: &[(DOMString, DomRoot<Blob>)],
+        presentation_style: PresentationStyle,
+    ) -> ClipboardItem {
+        ClipboardItem {
+            reflector_: Reflector::new(),
+            representations: representations
+                .iter()
+                .map(|&(ref mime_type, ref data)| Representation {
+                    mime_type: mime_type.clone(),
+                    data: Dom::from_ref(&**data),
+                })
+                .collect(),
+            presentation_style: presentation_style,
+            types: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        representations: Vec<(DOMString, DomRoot<Blob>)>,
+        presentation_style: PresentationStyle,
+    ) -> DomRoot<ClipboardItem> {
+        let item = reflect_dom_object(
+            Box::new(ClipboardItem::new_inherited(
+                &representations,
+                presentation_style,
+            )),
+            global,
+            ClipboardItemBinding::Wrap,
+        );
+        let types: Vec<DOMString> = representations
+            .into_iter()
+            .map(|(mime_type, _)| mime_type)
+            .collect();
+        item.types.set(to_frozen_array(&types, global.get_cx()));
+        item
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-clipboarditem
+    pub fn Constructor(
+        global: &GlobalScope,
+        items: Record<DOMString, StringOrBlob>,
+        options: &ClipboardItemOptions,
+    ) -> Fallible<DomRoot<ClipboardItem>> {
+        // Step 1.
+        if items.is_empty() {
+            return Err(Error::Type("A ClipboardItem needs data".to_owned()));
+        }
+
+        // Steps 2-4.
+        let representations = items
+            .iter()
+            .map(|(mime_type, data)| {
+                let data = match *data {
+                    StringOrBlob::String(ref string) => Blob::new(
+                        global,
+                        BlobImpl::new_from_bytes(string.as_bytes().to_vec()),
+                        mime_type.to_string(),
+                    ),
+                    StringOrBlob::Blob(ref blob) => blob.clone(),
+                };
+                (mime_type.clone(), data)
+            })
+            .collect();
+        Ok(ClipboardItem::new(
+            global,
+            representations,
+            options.presentationStyle,
+        ))
+    }
+
+    /// The MIME types of the representations of the item, along with their data.
+    pub fn representations(&self) -> Vec<(DOMString, DomRoot<Blob>)> {
+        self.representations
+            .iter()
+            .map(|representation| {
+                (
+                    representation.mime_type.clone(),
+                    DomRoot::from_ref(&*representation.data),
+                )
+            })
+            .collect()
+    }
+}
+
+impl ClipboardItemMethods for ClipboardItem {
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-presentationstyle
+    fn PresentationStyle(&self) -> PresentationStyle {
+        self.presentation_style
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-types
+    fn Types(&self, _cx: JSContext) -> JSVal {
+        self.types.get()
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype
+    fn GetType(&self, type_: DOMString, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        match self
+            .representations
+            .iter()
+            .find(|representation| representation.mime_type == type_)
+        {
+            // Steps 3-4.
+            Some(representation) => {
+                promise.resolve_native(&DomRoot::from_ref(&*representation.data))
+            },
+            // Step 5.
+            None => promise.reject_error(Error::NotFound),
+        }
+        promise
+    }
+}
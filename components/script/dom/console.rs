@@ -6,8 +6,9 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
-use devtools_traits::{ConsoleMessage, LogLevel, ScriptToDevtoolsControlMsg};
+use devtools_traits::{ConsoleAPI, ConsoleMessage, LogLevel, ScriptToDevtoolsControlMsg};
 use std::io;
+use time::precise_time_ns;
 
 // https://developer.mozilla.org/en-US/docs/Web/API/Console
 pub struct Console(());
@@ -15,10 +16,13 @@ pub struct Console(());
 impl Console {
     fn send_to_devtools(global: &GlobalScope, level: LogLevel, message: DOMString) {
         if let Some(chan) = global.devtools_chan() {
-            let console_message = prepare_message(level, message);
-            let worker_id = global
-                .downcast::<WorkerGlobalScope>()
-                .map(|worker| worker.get_worker_id());
+            let worker = global.downcast::<WorkerGlobalScope>();
+            let console_message = prepare_message(worker, level, message);
+            // Workers keep their messages, for devtools clients that attach to them later.
+            if let Some(worker) = worker {
+                worker.cache_console_message(prepare_cached_message(&console_message));
+            }
+            let worker_id = worker.map(|worker| worker.get_worker_id());
             let devtools_message = ScriptToDevtoolsControlMsg::ConsoleAPI(
                 global.pipeline_id(),
                 console_message,
@@ -27,6 +31,20 @@ impl Console {
             chan.send(devtools_message).unwrap();
         }
     }
+
+    /// Print a message to stdout, prefixed with the worker and pipeline it comes from
+    /// in workers, so that the output of the many workers of a page can be told apart.
+    fn print(global: &GlobalScope, message: &str) {
+        match global.downcast::<WorkerGlobalScope>() {
+            Some(worker) => println!(
+                "[Worker {} of {}] {}",
+                *worker.get_url(),
+                global.pipeline_id(),
+                message
+            ),
+            None => println!("{}", message),
+        }
+    }
 }
 
 // In order to avoid interleaving the stdout output of the Console API methods
@@ -48,7 +66,7 @@ impl Console {
     pub fn Log(global: &GlobalScope, messages: Vec<DOMString>) {
         with_stderr_lock(move || {
             for message in messages {
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Log, message);
             }
         })
@@ -58,7 +76,7 @@ impl Console {
     pub fn Debug(global: &GlobalScope, messages: Vec<DOMString>) {
         with_stderr_lock(move || {
             for message in messages {
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Debug, message);
             }
         })
@@ -68,7 +86,7 @@ impl Console {
     pub fn Info(global: &GlobalScope, messages: Vec<DOMString>) {
         with_stderr_lock(move || {
             for message in messages {
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Info, message);
             }
         })
@@ -78,7 +96,7 @@ impl Console {
     pub fn Warn(global: &GlobalScope, messages: Vec<DOMString>) {
         with_stderr_lock(move || {
             for message in messages {
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Warn, message);
             }
         })
@@ -88,7 +106,7 @@ impl Console {
     pub fn Error(global: &GlobalScope, messages: Vec<DOMString>) {
         with_stderr_lock(move || {
             for message in messages {
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Error, message);
             }
         })
@@ -99,7 +117,7 @@ impl Console {
         with_stderr_lock(move || {
             if !condition {
                 let message = message.unwrap_or_else(|| DOMString::from("no message"));
-                println!("Assertion failed: {}", message);
+                Self::print(global, &format!("Assertion failed: {}", message));
                 Self::send_to_devtools(global, LogLevel::Error, message);
             }
         })
@@ -110,7 +128,7 @@ impl Console {
         with_stderr_lock(move || {
             if let Ok(()) = global.time(label.clone()) {
                 let message = DOMString::from(format!("{}: timer started", label));
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Log, message);
             }
         })
@@ -121,20 +139,48 @@ impl Console {
         with_stderr_lock(move || {
             if let Ok(delta) = global.time_end(&label) {
                 let message = DOMString::from(format!("{}: {}ms", label, delta));
-                println!("{}", message);
+                Self::print(global, &message);
                 Self::send_to_devtools(global, LogLevel::Log, message);
             };
         })
     }
 }
 
-fn prepare_message(log_level: LogLevel, message: DOMString) -> ConsoleMessage {
-    // TODO: Sending fake values for filename, lineNumber and columnNumber in LogMessage; adjust later
+fn prepare_message(
+    worker: Option<&WorkerGlobalScope>,
+    log_level: LogLevel,
+    message: DOMString,
+) -> ConsoleMessage {
+    // TODO: Sending fake values for lineNumber and columnNumber in LogMessage; adjust later
+    let filename = match worker {
+        Some(worker) => worker.get_url().to_string(),
+        None => "test".to_owned(),
+    };
     ConsoleMessage {
         message: String::from(message),
         logLevel: log_level,
-        filename: "test".to_owned(),
+        filename,
         lineNumber: 1,
         columnNumber: 1,
     }
 }
+
+fn prepare_cached_message(message: &ConsoleMessage) -> ConsoleAPI {
+    ConsoleAPI {
+        type_: "ConsoleAPI".to_owned(),
+        level: match message.logLevel {
+            LogLevel::Log => "log",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+        .to_owned(),
+        filename: message.filename.clone(),
+        lineNumber: message.lineNumber as u32,
+        functionName: String::new(),
+        timeStamp: precise_time_ns(),
+        private: false,
+        arguments: vec![message.message.clone()],
+    }
+}
@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CookieChangeEventBinding::{
+    self, CookieChangeEventInit, CookieChangeEventMethods,
+};
+use crate::dom::bindings::codegen::Bindings::CookieStoreBinding::CookieListItem;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use servo_atoms::Atom;
+
+// https://wicg.github.io/cookie-store/#cookiechangeevent
+#[dom_struct]
+pub struct CookieChangeEvent {
+    event: Event,
+    #[ignore_malloc_size_of = "mozjs"]
+    changed: Heap<JSVal>,
+    #[ignore_malloc_size_of = "mozjs"]
+    deleted: Heap<JSVal>,
+}
+
+impl CookieChangeEvent {
+    fn new_inherited() -> CookieChangeEvent {
+        CookieChangeEvent {
+            event: Event::new_inherited(),
+            changed: Heap::default(),
+            deleted: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        changed: &[CookieListItem],
+        deleted: &[CookieListItem],
+    ) -> DomRoot<CookieChangeEvent> {
+        let ev = reflect_dom_object(
+            Box::new(CookieChangeEvent::new_inherited()),
+            global,
+            CookieChangeEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev.changed.set(to_frozen_array(changed, global.get_cx()));
+        ev.deleted.set(to_frozen_array(deleted, global.get_cx()));
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &CookieChangeEventInit,
+    ) -> Fallible<DomRoot<CookieChangeEvent>> {
+        Ok(CookieChangeEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.changed,
+            &init.deleted,
+        ))
+    }
+}
+
+impl CookieChangeEventMethods for CookieChangeEvent {
+    // https://wicg.github.io/cookie-store/#dom-cookiechangeevent-changed
+    fn Changed(&self, _cx: JSContext) -> JSVal {
+        self.changed.get()
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiechangeevent-deleted
+    fn Deleted(&self, _cx: JSContext) -> JSVal {
+        self.deleted.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
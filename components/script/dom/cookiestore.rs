@@ -0,0 +1,437 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::CookieStoreBinding::{
+    self, CookieInit, CookieListItem, CookieSameSite, CookieStoreDeleteOptions,
+    CookieStoreGetOptions, CookieStoreMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cookiechangeevent::CookieChangeEvent;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::{TaskSource, TaskSourceName};
+use cookie::{Cookie, SameSite};
+use dom_struct::dom_struct;
+use hyper_serde::Serde;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::{CookieChange, CookieSource, CoreResourceMsg, IpcSend};
+use profile_traits::ipc as profiled_ipc;
+use servo_url::ServoUrl;
+use std::rc::Rc;
+use time::{self, Timespec};
+
+/// <https://wicg.github.io/cookie-store/#cookie-maximum-name-value-pair-size>
+const MAX_NAME_VALUE_PAIR_SIZE: usize = 4096;
+
+/// <https://wicg.github.io/cookie-store/#cookie-maximum-attribute-value-size>
+const MAX_ATTRIBUTE_VALUE_SIZE: usize = 1024;
+
+// https://wicg.github.io/cookie-store/#cookiestore
+#[dom_struct]
+pub struct CookieStore {
+    eventtarget: EventTarget,
+}
+
+impl CookieStore {
+    fn new_inherited() -> CookieStore {
+        CookieStore {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CookieStore> {
+        let store = reflect_dom_object(
+            Box::new(CookieStore::new_inherited()),
+            global,
+            CookieStoreBinding::Wrap,
+        );
+        // Change events are only exposed to windows.
+        if global.is::<Window>() {
+            store.add_change_listener();
+        }
+        store
+    }
+
+    /// Subscribes to the changes of the cookies visible to the document,
+    /// <https://wicg.github.io/cookie-store/#process-cookie-changes>
+    fn add_change_listener(&self) {
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let this = Trusted::new(self);
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let change: CookieChange = match message.to() {
+                    Ok(change) => change,
+                    Err(_) => return,
+                };
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(process_cookie_change: move || {
+                        this.root().dispatch_change(change);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let _ = global
+            .resource_threads()
+            .send(CoreResourceMsg::AddCookieChangeListener(
+                global.pipeline_id(),
+                global.get_url(),
+                sender,
+            ));
+    }
+
+    /// Stops the change events, when the window is torn down.
+    pub fn remove_change_listener(&self) {
+        let global = self.global();
+        let _ = global
+            .resource_threads()
+            .send(CoreResourceMsg::RemoveCookieChangeListener(
+                global.pipeline_id(),
+            ));
+    }
+
+    fn dispatch_change(&self, change: CookieChange) {
+        let (changed, deleted) = match change {
+            CookieChange::Changed(cookie) => (vec![cookie_list_item(&cookie)], vec![]),
+            CookieChange::Deleted(cookie) => (vec![], vec![cookie_list_item(&cookie)]),
+        };
+        let event = CookieChangeEvent::new(
+            &self.global(),
+            atom!("change"),
+            false,
+            false,
+            &changed,
+            &deleted,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// Creates the promise of a method, rejected with a `SecurityError` if
+    /// the origin of the global is opaque.
+    fn new_promise(&self, comp: InCompartment) -> (Rc<Promise>, bool) {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        if !global.origin().is_tuple() {
+            promise.reject_error(Error::Security);
+            return (promise, false);
+        }
+        (promise, true)
+    }
+
+    /// <https://wicg.github.io/cookie-store/#query-cookies>
+    fn query_cookies(&self, url: ServoUrl, name: Option<&str>) -> Vec<CookieListItem> {
+        let global = self.global();
+        let (sender, receiver) =
+            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let _ = global
+            .resource_threads()
+            .send(CoreResourceMsg::GetCookiesDataForUrl(
+                url,
+                sender,
+                CookieSource::NonHTTP,
+            ));
+        receiver
+            .recv()
+            .unwrap_or_default()
+            .iter()
+            .filter(|cookie| name.map_or(true, |name| cookie.name() == name))
+            .map(|cookie| cookie_list_item(cookie))
+            .collect()
+    }
+
+    /// Resolves the URL of the `get()` and `getAll()` options,
+    /// <https://wicg.github.io/cookie-store/#dom-cookiestore-get-options>
+    fn options_url(&self, options: &CookieStoreGetOptions) -> Fallible<ServoUrl> {
+        let global = self.global();
+        let url = match options.url {
+            Some(ref url) => url,
+            None => return Ok(global.get_url()),
+        };
+        let parsed = ServoUrl::parse_with_base(Some(&global.api_base_url()), url)
+            .map_err(|_| Error::Type("The url of the options is invalid".to_owned()))?;
+        if global.is::<Window>() && parsed != global.get_url() {
+            return Err(Error::Type(
+                "The url of the options must be the url of the document".to_owned(),
+            ));
+        }
+        if parsed.origin() != *global.origin().immutable() {
+            return Err(Error::Type(
+                "The url of the options must be same origin".to_owned(),
+            ));
+        }
+        Ok(parsed)
+    }
+
+    fn get(&self, comp: InCompartment, options: &CookieStoreGetOptions) -> Rc<Promise> {
+        let (promise, allowed) = self.new_promise(comp);
+        if !allowed {
+            return promise;
+        }
+        if options.name.is_none() && options.url.is_none() {
+            promise.reject_error(Error::Type("The options are empty".to_owned()));
+            return promise;
+        }
+        let url = match self.options_url(options) {
+            Ok(url) => url,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let name = options.name.as_ref().map(|name| &*name.0);
+        let cookie = self.query_cookies(url, name).into_iter().next();
+        promise.resolve_native(&cookie);
+        promise
+    }
+
+    fn get_all(&self, comp: InCompartment, options: &CookieStoreGetOptions) -> Rc<Promise> {
+        let (promise, allowed) = self.new_promise(comp);
+        if !allowed {
+            return promise;
+        }
+        let url = match self.options_url(options) {
+            Ok(url) => url,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let name = options.name.as_ref().map(|name| &*name.0);
+        promise.resolve_native(&self.query_cookies(url, name));
+        promise
+    }
+
+    fn set(&self, comp: InCompartment, init: &CookieInit) -> Rc<Promise> {
+        let (promise, allowed) = self.new_promise(comp);
+        if !allowed {
+            return promise;
+        }
+        let url = self.global().get_url();
+        match new_cookie(&url, init) {
+            Ok(cookie) => {
+                let _ = self
+                    .global()
+                    .resource_threads()
+                    .send(CoreResourceMsg::SetCookieForUrl(
+                        url,
+                        Serde(cookie),
+                        CookieSource::NonHTTP,
+                    ));
+                promise.resolve_native(&());
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    fn delete(&self, comp: InCompartment, options: &CookieStoreDeleteOptions) -> Rc<Promise> {
+        let (promise, allowed) = self.new_promise(comp);
+        if !allowed {
+            return promise;
+        }
+        let url = self.global().get_url();
+        let init = CookieInit {
+            name: options.name.clone(),
+            value: USVString::default(),
+            expires: None,
+            domain: options.domain.clone(),
+            path: options.path.clone(),
+            sameSite: CookieSameSite::Strict,
+            partitioned: options.partitioned,
+        };
+        match new_cookie(&url, &init) {
+            Ok(cookie) => {
+                let _ = self
+                    .global()
+                    .resource_threads()
+                    .send(CoreResourceMsg::DeleteCookie(url, Serde(cookie)));
+                promise.resolve_native(&());
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
+
+impl CookieStoreMethods for CookieStore {
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-get
+    fn Get(&self, name: USVString, comp: InCompartment) -> Rc<Promise> {
+        let options = CookieStoreGetOptions {
+            name: Some(name),
+            url: None,
+        };
+        self.get(comp, &options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-get-options
+    fn Get_(&self, options: &CookieStoreGetOptions, comp: InCompartment) -> Rc<Promise> {
+        self.get(comp, options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-getall
+    fn GetAll(&self, name: USVString, comp: InCompartment) -> Rc<Promise> {
+        let options = CookieStoreGetOptions {
+            name: Some(name),
+            url: None,
+        };
+        self.get_all(comp, &options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-getall-options
+    fn GetAll_(&self, options: &CookieStoreGetOptions, comp: InCompartment) -> Rc<Promise> {
+        self.get_all(comp, options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-set
+    fn Set(&self, name: USVString, value: USVString, comp: InCompartment) -> Rc<Promise> {
+        let init = CookieInit {
+            name,
+            value,
+            expires: None,
+            domain: None,
+            path: USVString("/".to_owned()),
+            sameSite: CookieSameSite::Strict,
+            partitioned: false,
+        };
+        self.set(comp, &init)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-set-options
+    fn Set_(&self, options: &CookieInit, comp: InCompartment) -> Rc<Promise> {
+        self.set(comp, options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-delete
+    fn Delete(&self, name: USVString, comp: InCompartment) -> Rc<Promise> {
+        let options = CookieStoreDeleteOptions {
+            name,
+            domain: None,
+            path: USVString("/".to_owned()),
+            partitioned: false,
+        };
+        self.delete(comp, &options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-delete-options
+    fn Delete_(&self, options: &CookieStoreDeleteOptions, comp: InCompartment) -> Rc<Promise> {
+        self.delete(comp, options)
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+}
+
+/// <https://wicg.github.io/cookie-store/#set-a-cookie>
+fn new_cookie(url: &ServoUrl, init: &CookieInit) -> Fallible<Cookie<'static>> {
+    let is_whitespace = |c: char| c == ' ' || c == '\t';
+    let name = init.name.0.trim_matches(is_whitespace);
+    let value = init.value.0.trim_matches(is_whitespace);
+
+    let is_forbidden = |c: char| c == ';' || c == '\x7f' || (c < ' ' && c != '\t');
+    if name.contains(is_forbidden) || value.contains(is_forbidden) {
+        return Err(Error::Type(
+            "The name or value contains a forbidden character".to_owned(),
+        ));
+    }
+
+    // Cookies without a name can't be parsed back out of the cookie strings
+    // they are sent to the net component as, so they are rejected altogether.
+    if name.is_empty() || name.contains('=') {
+        return Err(Error::Type("The name is empty or contains '='".to_owned()));
+    }
+
+    if name.len() + value.len() > MAX_NAME_VALUE_PAIR_SIZE {
+        return Err(Error::Type("The name and value are too long".to_owned()));
+    }
+
+    let mut cookie = Cookie::new(name.to_owned(), value.to_owned());
+
+    let is_host_prefixed = name.len() >= 7 && name[..7].eq_ignore_ascii_case("__Host-");
+    if let Some(ref domain) = init.domain {
+        if is_host_prefixed {
+            return Err(Error::Type(
+                "A __Host- cookie can't have a domain".to_owned(),
+            ));
+        }
+        let host = url.host_str().unwrap_or("");
+        if domain.starts_with('.') ||
+            (host != &**domain && !host.ends_with(&format!(".{}", &**domain)))
+        {
+            return Err(Error::Type(
+                "The domain doesn't match the current host".to_owned(),
+            ));
+        }
+        if domain.len() > MAX_ATTRIBUTE_VALUE_SIZE {
+            return Err(Error::Type("The domain is too long".to_owned()));
+        }
+        cookie.set_domain(domain.0.clone());
+    }
+
+    if let Some(expires) = init.expires {
+        let seconds = (*expires / 1000.).floor() as i64;
+        cookie.set_expires(time::at_utc(Timespec::new(seconds, 0)));
+    }
+
+    let path = &*init.path;
+    if !path.starts_with('/') {
+        return Err(Error::Type("The path must start with '/'".to_owned()));
+    }
+    if is_host_prefixed && path != "/" {
+        return Err(Error::Type(
+            "A __Host- cookie must have '/' as path".to_owned(),
+        ));
+    }
+    if path.len() > MAX_ATTRIBUTE_VALUE_SIZE {
+        return Err(Error::Type("The path is too long".to_owned()));
+    }
+    cookie.set_path(path.to_owned());
+
+    // Only secure contexts can use the API, so the cookie is always secure.
+    cookie.set_secure(true);
+    cookie.set_same_site(match init.sameSite {
+        CookieSameSite::Strict => SameSite::Strict,
+        CookieSameSite::Lax => SameSite::Lax,
+        CookieSameSite::None => SameSite::None,
+    });
+
+    Ok(cookie)
+}
+
+/// <https://wicg.github.io/cookie-store/#create-a-cookielistitem>
+fn cookie_list_item(cookie: &Cookie) -> CookieListItem {
+    CookieListItem {
+        name: Some(USVString(cookie.name().to_owned())),
+        value: Some(USVString(cookie.value().to_owned())),
+        domain: Some(cookie.domain().map(|domain| USVString(domain.to_owned()))),
+        path: cookie.path().map(|path| USVString(path.to_owned())),
+        expires: Some(
+            cookie
+                .expires()
+                .map(|expires| Finite::wrap(expires.to_timespec().sec as f64 * 1000.)),
+        ),
+        secure: Some(cookie.secure().unwrap_or(false)),
+        sameSite: Some(match cookie.same_site() {
+            Some(SameSite::Strict) => CookieSameSite::Strict,
+            Some(SameSite::Lax) => CookieSameSite::Lax,
+            _ => CookieSameSite::None,
+        }),
+        partitioned: Some(false),
+    }
+}
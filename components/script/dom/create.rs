@@ -62,6 +62,7 @@ use crate::dom::htmlprogresselement::HTMLProgressElement;
 use crate::dom::htmlquoteelement::HTMLQuoteElement;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
+use crate::dom::htmlslotelement::HTMLSlotElement;
 use crate::dom::htmlsourceelement::HTMLSourceElement;
 use crate::dom::htmlspanelement::HTMLSpanElement;
 use crate::dom::htmlstyleelement::HTMLStyleElement;
@@ -197,6 +198,12 @@ fn create_html_element(
         result.set_custom_element_state(CustomElementState::Undefined);
     }
 
+    // Step 7.4, keep the is value so the element can be upgraded once its
+    // customized built-in element is defined.
+    if let Some(is) = is {
+        result.set_is(is);
+    }
+
     result
 }
 
@@ -332,6 +339,7 @@ pub fn create_native_html_element(
         local_name!("script") => make!(HTMLScriptElement, creator),
         local_name!("section") => make!(HTMLElement),
         local_name!("select") => make!(HTMLSelectElement),
+        local_name!("slot") => make!(HTMLSlotElement),
         local_name!("small") => make!(HTMLElement),
         local_name!("source") => make!(HTMLSourceElement),
         // https://html.spec.whatwg.org/multipage/#other-elements,-attributes-and-apis:spacer
@@ -0,0 +1,92 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::CredentialsContainerBinding::{
+    self, CredentialCreationOptions, CredentialRequestOptions, CredentialsContainerMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::credential::Credential;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::publickeycredential::PublicKeyCredential;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+// https://w3c.github.io/webappsec-credential-management/#credentialscontainer
+#[dom_struct]
+pub struct CredentialsContainer {
+    reflector_: Reflector,
+}
+
+impl CredentialsContainer {
+    fn new_inherited() -> CredentialsContainer {
+        CredentialsContainer {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CredentialsContainer> {
+        reflect_dom_object(
+            Box::new(CredentialsContainer::new_inherited()),
+            global,
+            CredentialsContainerBinding::Wrap,
+        )
+    }
+}
+
+// Public key credentials are the only type of credentials that is supported.
+impl CredentialsContainerMethods for CredentialsContainer {
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-get
+    fn Get(
+        &self,
+        options: RootedTraceableBox<CredentialRequestOptions>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        match options.publicKey {
+            Some(ref options) => {
+                PublicKeyCredential::discover(global.as_window(), options, &promise)
+            },
+            None => promise.reject_error(Error::NotSupported),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-store
+    fn Store(&self, _credential: &Credential, comp: InCompartment) -> Rc<Promise> {
+        // Public key credentials can't be stored.
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        promise.reject_error(Error::NotSupported);
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-create
+    fn Create(
+        &self,
+        options: RootedTraceableBox<CredentialCreationOptions>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        match options.publicKey {
+            Some(ref options) => PublicKeyCredential::create(global.as_window(), options, &promise),
+            None => promise.reject_error(Error::NotSupported),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-preventsilentaccess
+    fn PreventSilentAccess(&self, comp: InCompartment) -> Rc<Promise> {
+        // Public key credentials are never used without the consent of the
+        // user, so there is nothing to prevent.
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        promise.resolve_native(&());
+        promise
+    }
+}
@@ -0,0 +1,212 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{
+    self, CryptoKeyMethods, KeyType, KeyUsage,
+};
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::{set_dictionary_property, to_frozen_array};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::subtlecrypto::{AlgorithmName, Curve, Sha};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject, JS_NewPlainObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::{HandleObject, MutableHandleObject};
+use js::typedarray::{CreateWith, Uint8Array};
+use openssl::pkey::{PKey, Private, Public};
+use std::ptr::{self, NonNull};
+
+/// The algorithm of a key, along with the parameters it was created with,
+/// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-algorithm>
+#[derive(Clone, Debug)]
+pub enum KeyAlgorithm {
+    /// <https://w3c.github.io/webcrypto/#AesKeyAlgorithm-dictionary>
+    Aes { name: AlgorithmName, length: u16 },
+    /// <https://w3c.github.io/webcrypto/#HmacKeyAlgorithm-dictionary>
+    Hmac { hash: Sha, length: u32 },
+    /// <https://w3c.github.io/webcrypto/#RsaHashedKeyAlgorithm-dictionary>
+    Rsa {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: Sha,
+    },
+    /// <https://w3c.github.io/webcrypto/#EcKeyAlgorithm-dictionary>
+    Ec { name: AlgorithmName, curve: Curve },
+    /// The base key of HKDF or PBKDF2, which has no parameters.
+    Kdf { name: AlgorithmName },
+}
+
+impl KeyAlgorithm {
+    pub fn name(&self) -> AlgorithmName {
+        match *self {
+            KeyAlgorithm::Aes { name, .. } => name,
+            KeyAlgorithm::Hmac { .. } => AlgorithmName::Hmac,
+            KeyAlgorithm::Rsa { name, .. } => name,
+            KeyAlgorithm::Ec { name, .. } => name,
+            KeyAlgorithm::Kdf { name } => name,
+        }
+    }
+
+    #[allow(unsafe_code)]
+    unsafe fn to_object(&self, cx: JSContext, mut rval: MutableHandleObject) {
+        rooted!(in(*cx) let object = JS_NewPlainObject(*cx));
+        set_string_property(cx, object.handle(), "name", self.name().as_str());
+        match *self {
+            KeyAlgorithm::Aes { length, .. } => {
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                length.to_jsval(*cx, value.handle_mut());
+                let _ = set_dictionary_property(*cx, object.handle(), "length", value.handle());
+            },
+            KeyAlgorithm::Hmac { hash, length } => {
+                set_hash_property(cx, object.handle(), hash);
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                length.to_jsval(*cx, value.handle_mut());
+                let _ = set_dictionary_property(*cx, object.handle(), "length", value.handle());
+            },
+            KeyAlgorithm::Rsa {
+                modulus_length,
+                ref public_exponent,
+                hash,
+                ..
+            } => {
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                modulus_length.to_jsval(*cx, value.handle_mut());
+                let _ =
+                    set_dictionary_property(*cx, object.handle(), "modulusLength", value.handle());
+                rooted!(in(*cx) let mut array = ptr::null_mut::<JSObject>());
+                let _ =
+                    Uint8Array::create(*cx, CreateWith::Slice(public_exponent), array.handle_mut());
+                rooted!(in(*cx) let value = ObjectValue(array.get()));
+                let _ =
+                    set_dictionary_property(*cx, object.handle(), "publicExponent", value.handle());
+                set_hash_property(cx, object.handle(), hash);
+            },
+            KeyAlgorithm::Ec { curve, .. } => {
+                set_string_property(cx, object.handle(), "namedCurve", curve.name());
+            },
+            KeyAlgorithm::Kdf { .. } => {},
+        }
+        rval.set(object.get());
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe fn set_string_property(cx: JSContext, object: HandleObject, name: &str, value: &str) {
+    rooted!(in(*cx) let mut string = UndefinedValue());
+    DOMString::from(value).to_jsval(*cx, string.handle_mut());
+    let _ = set_dictionary_property(*cx, object, name, string.handle());
+}
+
+/// Sets the `hash` member of a key algorithm, which is itself a `KeyAlgorithm`.
+#[allow(unsafe_code)]
+unsafe fn set_hash_property(cx: JSContext, object: HandleObject, hash: Sha) {
+    rooted!(in(*cx) let hash_object = JS_NewPlainObject(*cx));
+    set_string_property(cx, hash_object.handle(), "name", hash.name());
+    rooted!(in(*cx) let value = ObjectValue(hash_object.get()));
+    let _ = set_dictionary_property(*cx, object, "hash", value.handle());
+}
+
+/// The key material of a key,
+/// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-handle>
+#[derive(Clone)]
+pub enum Handle {
+    /// The bytes of an AES or HMAC key, or of the base key of HKDF and PBKDF2.
+    Secret(Vec<u8>),
+    Private(PKey<Private>),
+    Public(PKey<Public>),
+}
+
+/// The internal slots of a `CryptoKey`, which can be handed to the threads
+/// that run the operations of `SubtleCrypto`.
+#[derive(Clone)]
+pub struct KeyData {
+    pub key_type: KeyType,
+    pub extractable: bool,
+    pub algorithm: KeyAlgorithm,
+    pub usages: Vec<KeyUsage>,
+    pub handle: Handle,
+}
+
+unsafe_no_jsmanaged_fields!(KeyData);
+
+// https://w3c.github.io/webcrypto/#cryptokey-interface
+#[dom_struct]
+pub struct CryptoKey {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Defined in openssl"]
+    data: KeyData,
+    /// The cached `algorithm` object.
+    #[ignore_malloc_size_of = "mozjs"]
+    algorithm: Heap<*mut JSObject>,
+    /// The cached `usages` array.
+    #[ignore_malloc_size_of = "mozjs"]
+    usages: Heap<JSVal>,
+}
+
+impl CryptoKey {
+    fn new_inherited(data: KeyData) -> CryptoKey {
+        CryptoKey {
+            reflector_: Reflector::new(),
+            data,
+            algorithm: Heap::default(),
+            usages: Heap::default(),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(global: &GlobalScope, data: KeyData) -> DomRoot<CryptoKey> {
+        let key = reflect_dom_object(
+            Box::new(CryptoKey::new_inherited(data)),
+            global,
+            CryptoKeyBinding::Wrap,
+        );
+        let cx = global.get_cx();
+        rooted!(in(*cx) let mut algorithm = ptr::null_mut::<JSObject>());
+        unsafe { key.data.algorithm.to_object(cx, algorithm.handle_mut()) };
+        key.algorithm.set(algorithm.get());
+        key.usages.set(to_frozen_array(&key.data.usages, cx));
+        key
+    }
+
+    pub fn data(&self) -> &KeyData {
+        &self.data
+    }
+
+    pub fn algorithm_name(&self) -> AlgorithmName {
+        self.data.algorithm.name()
+    }
+
+    pub fn has_usage(&self, usage: KeyUsage) -> bool {
+        self.data.usages.contains(&usage)
+    }
+}
+
+impl CryptoKeyMethods for CryptoKey {
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-type
+    fn Type(&self) -> KeyType {
+        self.data.key_type
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-extractable
+    fn Extractable(&self) -> bool {
+        self.data.extractable
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm
+    #[allow(unsafe_code)]
+    fn Algorithm(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.algorithm.get()) }
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-usages
+    #[allow(unsafe_code)]
+    fn Usages(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.usages.get().to_object()) }
+    }
+}
@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::{
+    self, CSPViolationReportBodyMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::security_manager::CSPViolationReport;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/webappsec-csp/#cspviolationreportbody
+#[dom_struct]
+pub struct CSPViolationReportBody {
+    reportbody: ReportBody,
+    report: CSPViolationReport,
+}
+
+impl CSPViolationReportBody {
+    fn new_inherited(report: &CSPViolationReport) -> CSPViolationReportBody {
+        CSPViolationReportBody {
+            reportbody: ReportBody::new_inherited(),
+            report: report.clone(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        report: &CSPViolationReport,
+    ) -> DomRoot<CSPViolationReportBody> {
+        reflect_dom_object(
+            Box::new(CSPViolationReportBody::new_inherited(report)),
+            global,
+            CSPViolationReportBodyBinding::Wrap,
+        )
+    }
+}
+
+impl CSPViolationReportBodyMethods for CSPViolationReportBody {
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-documenturl
+    fn DocumentURL(&self) -> USVString {
+        USVString(self.report.document_url.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-referrer
+    fn GetReferrer(&self) -> Option<USVString> {
+        Some(USVString(self.report.referrer.clone()))
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-blockedurl
+    fn GetBlockedURL(&self) -> Option<USVString> {
+        Some(USVString(self.report.blocked_url.clone()))
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-effectivedirective
+    fn EffectiveDirective(&self) -> DOMString {
+        DOMString::from(self.report.effective_directive.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-originalpolicy
+    fn OriginalPolicy(&self) -> DOMString {
+        DOMString::from(self.report.original_policy.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-sourcefile
+    fn GetSourceFile(&self) -> Option<USVString> {
+        // TODO: the location of the script that caused the violation.
+        None
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-sample
+    fn GetSample(&self) -> Option<DOMString> {
+        Some(DOMString::from(self.report.sample.clone()))
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-disposition
+    fn Disposition(&self) -> SecurityPolicyViolationEventDisposition {
+        if self.report.report_only {
+            SecurityPolicyViolationEventDisposition::Report
+        } else {
+            SecurityPolicyViolationEventDisposition::Enforce
+        }
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-statuscode
+    fn StatusCode(&self) -> u16 {
+        // TODO: the status code of the document's response.
+        0
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-linenumber
+    fn GetLineNumber(&self) -> Option<u32> {
+        None
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-columnnumber
+    fn GetColumnNumber(&self) -> Option<u32> {
+        None
+    }
+}
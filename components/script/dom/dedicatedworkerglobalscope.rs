@@ -3,9 +3,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::enter_realm;
-use crate::devtools;
 use crate::dom::abstractworker::{SimpleWorkerErrorHandler, WorkerScriptMsg};
-use crate::dom::abstractworkerglobalscope::{run_worker_event_loop, WorkerEventLoopMethods};
+use crate::dom::abstractworkerglobalscope::{
+    interrupt_callback, run_worker_event_loop, WorkerEventLoopMethods,
+};
 use crate::dom::abstractworkerglobalscope::{SendableWorkerScriptChan, WorkerThreadWorkerChan};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DedicatedWorkerGlobalScopeBinding;
@@ -26,10 +27,10 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::worker::{TrustedWorkerAddress, Worker};
 use crate::dom::workerglobalscope::WorkerGlobalScope;
-use crate::fetch::load_whole_resource;
 use crate::script_runtime::ScriptThreadEventCategory::WorkerEvent;
 use crate::script_runtime::{
-    new_child_runtime, CommonScriptMsg, JSContext as SafeJSContext, Runtime, ScriptChan, ScriptPort,
+    new_child_runtime, CommonScriptMsg, ContextForRequestInterrupt, JSContext as SafeJSContext,
+    Runtime, ScriptChan, ScriptPort,
 };
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
 use crate::task_source::networking::NetworkingTaskSource;
@@ -40,14 +41,14 @@ use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcReceiver;
 use ipc_channel::router::ROUTER;
 use js::jsapi::JS_AddInterruptCallback;
-use js::jsapi::{Heap, JSContext, JSObject};
+use js::jsapi::JS_SetFutexCanWait;
+use js::jsapi::{Heap, JSObject};
 use js::jsval::UndefinedValue;
 use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue};
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
 use net_traits::image_cache::ImageCache;
 use net_traits::request::{CredentialsMode, Destination, ParserMetadata};
 use net_traits::request::{Referrer, RequestBuilder, RequestMode};
-use net_traits::IpcSend;
 use script_traits::{WorkerGlobalScopeInit, WorkerScriptLoadOrigin};
 use servo_rand::random;
 use servo_url::ServoUrl;
@@ -220,6 +221,7 @@ impl DedicatedWorkerGlobalScope {
         own_sender: Sender<DedicatedWorkerScriptMsg>,
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
+        termination_receiver: Receiver<()>,
         image_cache: Arc<dyn ImageCache>,
     ) -> DedicatedWorkerGlobalScope {
         DedicatedWorkerGlobalScope {
@@ -231,6 +233,7 @@ impl DedicatedWorkerGlobalScope {
                 runtime,
                 from_devtools_receiver,
                 Some(closing),
+                Some(termination_receiver),
             ),
             task_queue: TaskQueue::new(receiver, own_sender.clone()),
             own_sender: own_sender,
@@ -252,6 +255,7 @@ impl DedicatedWorkerGlobalScope {
         own_sender: Sender<DedicatedWorkerScriptMsg>,
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
+        termination_receiver: Receiver<()>,
         image_cache: Arc<dyn ImageCache>,
     ) -> DomRoot<DedicatedWorkerGlobalScope> {
         let cx = runtime.cx();
@@ -266,6 +270,7 @@ impl DedicatedWorkerGlobalScope {
             own_sender,
             receiver,
             closing,
+            termination_receiver,
             image_cache,
         ));
         unsafe { DedicatedWorkerGlobalScopeBinding::Wrap(SafeJSContext::from_ptr(cx), scope) }
@@ -284,7 +289,10 @@ impl DedicatedWorkerGlobalScope {
         worker_load_origin: WorkerScriptLoadOrigin,
         worker_name: String,
         worker_type: WorkerType,
+        credentials: CredentialsMode,
         closing: Arc<AtomicBool>,
+        termination_receiver: Receiver<()>,
+        context_for_interrupt: ContextForRequestInterrupt,
         image_cache: Arc<dyn ImageCache>,
     ) {
         let serialized_worker_url = worker_url.to_string();
@@ -317,7 +325,7 @@ impl DedicatedWorkerGlobalScope {
                 let request = RequestBuilder::new(worker_url.clone())
                     .destination(Destination::Worker)
                     .mode(RequestMode::SameOrigin)
-                    .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+                    .credentials_mode(credentials)
                     .parser_metadata(ParserMetadata::NotParserInserted)
                     .use_url_credentials(true)
                     .pipeline_id(pipeline_id)
@@ -346,6 +354,9 @@ impl DedicatedWorkerGlobalScope {
                     devtools_mpsc_chan,
                 );
 
+                // https://html.spec.whatwg.org/multipage/#run-a-worker step 6,
+                // a dedicated worker shares the cross-origin isolation of its owner.
+                let cross_origin_isolated = init.cross_origin_isolated;
                 let global = DedicatedWorkerGlobalScope::new(
                     init,
                     DOMString::from_string(worker_name),
@@ -357,18 +368,20 @@ impl DedicatedWorkerGlobalScope {
                     own_sender,
                     receiver,
                     closing,
+                    termination_receiver,
                     image_cache,
                 );
                 // FIXME(njn): workers currently don't have a unique ID suitable for using in reporter
                 // registration (#6631), so we instead use a random number and cross our fingers.
                 let scope = global.upcast::<WorkerGlobalScope>();
-                let global_scope = global.upcast::<GlobalScope>();
+                scope
+                    .upcast::<GlobalScope>()
+                    .set_cross_origin_isolated(cross_origin_isolated);
+                // Let the owner interrupt the worker while it is running,
+                // the guard is dropped before the global and its runtime.
+                let _context_guard = context_for_interrupt.set(*scope.get_cx());
 
-                let (metadata, bytes) = match load_whole_resource(
-                    request,
-                    &global_scope.resource_threads().sender(),
-                    &global_scope,
-                ) {
+                let script = match scope.fetch_worker_script(request) {
                     Err(_) => {
                         println!("error loading script {}", serialized_worker_url);
                         parent_sender
@@ -381,14 +394,14 @@ impl DedicatedWorkerGlobalScope {
                             .unwrap();
                         return;
                     },
-                    Ok((metadata, bytes)) => (metadata, bytes),
+                    Ok(script) => script,
                 };
-                scope.set_url(metadata.final_url);
-                let source = String::from_utf8_lossy(&bytes);
 
                 unsafe {
                     // Handle interrupt requests
                     JS_AddInterruptCallback(*scope.get_cx(), Some(interrupt_callback));
+                    // Unlike the main thread, workers can block on `Atomics.wait`.
+                    JS_SetFutexCanWait(*scope.get_cx());
                 }
 
                 if scope.is_closing() {
@@ -397,7 +410,7 @@ impl DedicatedWorkerGlobalScope {
 
                 {
                     let _ar = AutoWorkerReset::new(&global, worker.clone());
-                    scope.execute_script(DOMString::from(source));
+                    scope.run_worker_script(script);
                 }
 
                 let reporter_name = format!("dedicated-worker-reporter-{}", random::<u64>());
@@ -414,6 +427,7 @@ impl DedicatedWorkerGlobalScope {
                             while !scope.is_closing() {
                                 run_worker_event_loop(&*global, Some(&worker));
                             }
+                            scope.release_resources();
                         },
                         reporter_name,
                         parent_sender,
@@ -472,18 +486,9 @@ impl DedicatedWorkerGlobalScope {
 
     fn handle_mixed_message(&self, msg: MixedMessage) {
         match msg {
-            MixedMessage::FromDevtools(msg) => match msg {
-                DevtoolScriptControlMsg::EvaluateJS(_pipe_id, string, sender) => {
-                    devtools::handle_evaluate_js(self.upcast(), string, sender)
-                },
-                DevtoolScriptControlMsg::GetCachedMessages(pipe_id, message_types, sender) => {
-                    devtools::handle_get_cached_messages(pipe_id, message_types, sender)
-                },
-                DevtoolScriptControlMsg::WantsLiveNotifications(_pipe_id, bool_val) => {
-                    devtools::handle_wants_live_notifications(self.upcast(), bool_val)
-                },
-                _ => debug!("got an unusable devtools control message inside the worker!"),
-            },
+            MixedMessage::FromDevtools(msg) => self
+                .upcast::<WorkerGlobalScope>()
+                .handle_devtools_message(msg),
             MixedMessage::FromWorker(DedicatedWorkerScriptMsg::CommonWorker(
                 linked_worker,
                 msg,
@@ -541,7 +546,11 @@ impl DedicatedWorkerGlobalScope {
         message: HandleValue,
         transfer: CustomAutoRooterGuard<Vec<*mut JSObject>>,
     ) -> ErrorResult {
-        let data = structuredclone::write(cx, message, Some(transfer))?;
+        let data = if self.upcast::<GlobalScope>().is_cross_origin_isolated() {
+            structuredclone::write_with_shared_memory(cx, message, Some(transfer))?
+        } else {
+            structuredclone::write(cx, message, Some(transfer))?
+        };
         let worker = self.worker.borrow().as_ref().unwrap().clone();
         let global_scope = self.upcast::<GlobalScope>();
         let pipeline_id = global_scope.pipeline_id();
@@ -561,16 +570,6 @@ impl DedicatedWorkerGlobalScope {
     }
 }
 
-#[allow(unsafe_code)]
-unsafe extern "C" fn interrupt_callback(cx: *mut JSContext) -> bool {
-    let worker = DomRoot::downcast::<WorkerGlobalScope>(GlobalScope::from_context(cx))
-        .expect("global is not a worker scope");
-    assert!(worker.is::<DedicatedWorkerGlobalScope>());
-
-    // A false response causes the script to terminate
-    !worker.is_closing()
-}
-
 impl DedicatedWorkerGlobalScopeMethods for DedicatedWorkerGlobalScope {
     /// https://html.spec.whatwg.org/multipage/#dom-dedicatedworkerglobalscope-postmessage
     fn PostMessage(
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeprecationReportBodyBinding::{
+    self, DeprecationReportBodyMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::reporting::DeprecationReport;
+use dom_struct::dom_struct;
+
+// https://wicg.github.io/deprecation-reporting/#deprecationreportbody
+#[dom_struct]
+pub struct DeprecationReportBody {
+    reportbody: ReportBody,
+    id: DOMString,
+    message: DOMString,
+    source_file: Option<DOMString>,
+    line_number: Option<u32>,
+    column_number: Option<u32>,
+}
+
+impl DeprecationReportBody {
+    fn new_inherited(report: &DeprecationReport) -> DeprecationReportBody {
+        DeprecationReportBody {
+            reportbody: ReportBody::new_inherited(),
+            id: DOMString::from(report.id.clone()),
+            message: DOMString::from(report.message.clone()),
+            source_file: report.source_file.clone().map(DOMString::from),
+            line_number: report.line_number,
+            column_number: report.column_number,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, report: &DeprecationReport) -> DomRoot<DeprecationReportBody> {
+        reflect_dom_object(
+            Box::new(DeprecationReportBody::new_inherited(report)),
+            global,
+            DeprecationReportBodyBinding::Wrap,
+        )
+    }
+}
+
+impl DeprecationReportBodyMethods for DeprecationReportBody {
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-message
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-sourcefile
+    fn GetSourceFile(&self) -> Option<DOMString> {
+        self.source_file.clone()
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-linenumber
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.line_number
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-columnnumber
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.column_number
+    }
+}
@@ -5,27 +5,31 @@
 use crate::compartments::{AlreadyInCompartment, InCompartment};
 use crate::document_loader::{DocumentLoader, LoadType};
 use crate::dom::activation::{synthetic_click_activation, ActivationSource};
+use crate::dom::animation::Animation;
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEventBinding::BeforeUnloadEventMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding;
-use crate::dom::bindings::codegen::Bindings::DocumentBinding::ElementCreationOptions;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
-    DocumentMethods, DocumentReadyState,
+    DocumentMethods, DocumentReadyState, DocumentVisibilityState,
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     FrameRequestCallback, ScrollBehavior, WindowMethods,
 };
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use crate::dom::bindings::codegen::Bindings::XPathNSResolverBinding::XPathNSResolver;
 use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::StringOrElementCreationOptions;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::num::Finite;
@@ -33,11 +37,13 @@ use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, DomSlice, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::weakref::WeakRef;
 use crate::dom::bindings::xmlname::XMLName::InvalidXMLName;
 use crate::dom::bindings::xmlname::{
     namespace_from_domstring, validate_and_extract, xml_name_type,
 };
 use crate::dom::cdatasection::CDATASection;
+use crate::dom::characterdata::CharacterData;
 use crate::dom::closeevent::CloseEvent;
 use crate::dom::comment::Comment;
 use crate::dom::compositionevent::CompositionEvent;
@@ -46,6 +52,7 @@ use crate::dom::customelementregistry::CustomElementDefinition;
 use crate::dom::customevent::CustomEvent;
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
+use crate::dom::documenttimeline::DocumentTimeline;
 use crate::dom::documenttype::DocumentType;
 use crate::dom::domimplementation::DOMImplementation;
 use crate::dom::element::CustomElementCreationMode;
@@ -56,12 +63,15 @@ use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventDefault, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::focusevent::FocusEvent;
+use crate::dom::fontfaceset::FontFaceSet;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucanvascontext::GPUCanvasContext;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
+use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlembedelement::HTMLEmbedElement;
@@ -81,11 +91,13 @@ use crate::dom::node::{LayoutNodeHelpers, Node, NodeDamage, NodeFlags, ShadowInc
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::pointerevent::{PointerAttributes, PointerEvent, PointerType};
 use crate::dom::popstateevent::PopStateEvent;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::progressevent::ProgressEvent;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
+use crate::dom::selection::Selection;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::storageevent::StorageEvent;
@@ -102,6 +114,8 @@ use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
+use crate::dom::xpathexpression::XPathExpression;
+use crate::dom::xpathresult::XPathResult;
 use crate::fetch::FetchCanceller;
 use crate::script_runtime::JSContext;
 use crate::script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
@@ -109,7 +123,9 @@ use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::stylesheet_set::StylesheetSetRef;
 use crate::task::TaskBox;
 use crate::task_source::{TaskSource, TaskSourceName};
+use crate::textinput::{Direction, CMD_OR_CONTROL};
 use crate::timers::OneshotTimerCallback;
+use canvas_traits::canvas::CanvasId;
 use canvas_traits::webgl::{self, SwapChainId, WebGLContextId, WebGLMsg};
 use content_security_policy::{self as csp, CspList};
 use cookie::Cookie;
@@ -117,12 +133,12 @@ use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
 use embedder_traits::EmbedderMsg;
 use encoding_rs::{Encoding, UTF_8};
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use html5ever::{LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::{JSObject, JSRuntime};
-use keyboard_types::{Code, Key, KeyState};
+use keyboard_types::{Code, Key, KeyState, Modifiers, ShortcutMatcher};
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
     ProgressiveWebMetric,
@@ -142,9 +158,10 @@ use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataR
 use ref_filter_map::ref_filter_map;
 use ref_slice::ref_slice;
 use script_layout_interface::message::{Msg, ReflowGoal};
+use script_layout_interface::rpc::TextIndexResponse;
 use script_traits::{AnimationState, DocumentActivity, MouseButton, MouseEventType};
 use script_traits::{
-    MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
+    MsDuration, PenState, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
 };
 use servo_arc::Arc;
 use servo_atoms::Atom;
@@ -171,6 +188,7 @@ use style::stylesheet_set::DocumentStylesheetSet;
 use style::stylesheets::{Origin, OriginSet, Stylesheet};
 use url::Host;
 use uuid::Uuid;
+use webrender_api::ImageKey;
 
 /// The number of times we are allowed to see spurious `requestAnimationFrame()` calls before
 /// falling back to fake ones.
@@ -181,6 +199,11 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// The `pointerId` of the mouse pointer. Touch points and pens get the ids
+/// that follow it.
+/// <https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid>
+const MOUSE_POINTER_ID: i32 = 1;
+
 pub enum TouchEventResult {
     Processed(bool),
     Forwarded,
@@ -202,6 +225,16 @@ impl FireMouseEventType {
     }
 }
 
+/// Returns the value of the `button` attribute of a mouse event for `button`.
+/// <https://w3c.github.io/uievents/#dom-mouseevent-button>
+fn dom_mouse_button(button: MouseButton) -> i16 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    }
+}
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum IsHTMLDocument {
     HTMLDocument,
@@ -267,6 +300,8 @@ pub struct Document {
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
     ready_state: Cell<DocumentReadyState>,
+    /// <https://w3c.github.io/page-visibility/#dom-visibilitystate>
+    visibility_state: Cell<DocumentVisibilityState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
     /// The element that has most recently requested focus for itself.
@@ -318,9 +353,15 @@ pub struct Document {
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
-    /// Navigation Timing properties:
+    /// <https://w3c.github.io/pointerevents/#dfn-active-pointer>
+    active_pointers: ActivePointerList,
+    /// Set while the mouse events that are synthesized for a touch tap or a pen
+    /// contact are dispatched, to whether they are suppressed because the
+    /// `pointerdown` event of the contact was canceled.
+    /// <https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events>
+    synthesized_mouse_events: Cell<Option<bool>>,
+    /// Navigation Timing properties, read from the monotonic clock in nanoseconds:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
-    dom_loading: Cell<u64>,
     dom_interactive: Cell<u64>,
     dom_content_loaded_event_start: Cell<u64>,
     dom_content_loaded_event_end: Cell<u64>,
@@ -343,6 +384,10 @@ pub struct Document {
     /// <https://w3c.github.io/uievents/#event-type-dblclick>
     #[ignore_malloc_size_of = "Defined in std"]
     last_click_info: DomRefCell<Option<(Instant, Point2D<f32>)>>,
+    /// <https://w3c.github.io/selection-api/#dfn-selection>
+    selection: MutNullableDom<Selection>,
+    /// Whether the user is selecting text by dragging the mouse.
+    text_selection_in_progress: Cell<bool>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-destructive-writes-counter>
     ignore_destructive_writes_counter: Cell<u32>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-opens-during-unload-counter>
@@ -360,6 +405,11 @@ pub struct Document {
     dom_count: Cell<u32>,
     /// Entry node for fullscreen.
     fullscreen_element: MutNullableDom<Element>,
+    /// <https://w3c.github.io/pointerlock/#dfn-pointer-lock-target>
+    pointer_lock_element: MutNullableDom<Element>,
+    /// Where the mouse pointer was when it last moved, which is also where it
+    /// stays while it is locked.
+    last_mouse_move_point: Cell<Option<Point2D<f32>>>,
     /// Map from ID to set of form control elements that have that ID as
     /// their 'form' content attribute. Used to reset form controls
     /// whenever any element with the same ID as the form attribute
@@ -401,9 +451,25 @@ pub struct Document {
     media_controls: DomRefCell<HashMap<String, Dom<ShadowRoot>>>,
     /// List of all WebGL context IDs that need flushing.
     dirty_webgl_contexts: DomRefCell<HashMap<WebGLContextId, Dom<WebGLRenderingContext>>>,
+    /// List of all WebGPU contexts whose swap chain needs presenting.
+    dirty_webgpu_contexts: DomRefCell<HashMap<ImageKey, Dom<GPUCanvasContext>>>,
+    /// The canvases that transferred their control to an offscreen canvas,
+    /// by the id of the paint thread that they display.
+    /// Weak, so that an offscreen canvas committing frames does not keep
+    /// its placeholder alive.
+    offscreen_canvas_placeholders: DomRefCell<HashMap<CanvasId, WeakRef<HTMLCanvasElement>>>,
     /// https://html.spec.whatwg.org/multipage/#concept-document-csp-list
     #[ignore_malloc_size_of = "Defined in rust-content-security-policy"]
     csp_list: DomRefCell<Option<CspList>>,
+    /// <https://drafts.csswg.org/web-animations/#the-documents-default-timeline>
+    timeline: MutNullableDom<DocumentTimeline>,
+    /// The relevant animations that script created, in the order they were
+    /// created.
+    animations: DomRefCell<Vec<Dom<Animation>>>,
+    /// The id layout knows the next animation that script creates by.
+    next_animation_id: Cell<u64>,
+    /// <https://drafts.csswg.org/css-font-loading/#font-source>
+    fonts: MutNullableDom<FontFaceSet>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -820,7 +886,7 @@ impl Document {
                 x,
                 y,
                 global_scope.pipeline_id().root_scroll_id(),
-                ScrollBehavior::Instant,
+                ScrollBehavior::Auto,
                 target.as_deref(),
             );
         }
@@ -843,13 +909,11 @@ impl Document {
     // https://html.spec.whatwg.org/multipage/#current-document-readiness
     pub fn set_ready_state(&self, state: DocumentReadyState) {
         match state {
-            DocumentReadyState::Loading => {
-                update_with_current_time_ms(&self.dom_loading);
-            },
+            DocumentReadyState::Loading => {},
             DocumentReadyState::Complete => {
-                update_with_current_time_ms(&self.dom_complete);
+                update_with_current_time_ns(&self.dom_complete);
             },
-            DocumentReadyState::Interactive => update_with_current_time_ms(&self.dom_interactive),
+            DocumentReadyState::Interactive => update_with_current_time_ns(&self.dom_interactive),
         };
 
         self.ready_state.set(state);
@@ -858,6 +922,19 @@ impl Document {
             .fire_event(atom!("readystatechange"));
     }
 
+    /// <https://w3c.github.io/page-visibility/#update-the-visibility-state>
+    pub fn update_visibility_state(&self, visibility_state: DocumentVisibilityState) {
+        // Step 1.
+        if self.visibility_state.get() == visibility_state {
+            return;
+        }
+        // Step 2.
+        self.visibility_state.set(visibility_state);
+        // Step 5.
+        self.upcast::<EventTarget>()
+            .fire_bubbling_event(atom!("visibilitychange"));
+    }
+
     /// Return whether scripting is enabled or not
     pub fn is_scripting_enabled(&self) -> bool {
         self.scripting_enabled
@@ -952,7 +1029,7 @@ impl Document {
     pub fn handle_mouse_event(
         &self,
         js_runtime: *mut JSRuntime,
-        _button: MouseButton,
+        button: MouseButton,
         client_point: Point2D<f32>,
         mouse_event_type: MouseEventType,
         node_address: Option<UntrustedNodeAddress>,
@@ -966,19 +1043,52 @@ impl Document {
         };
         debug!("{}: at {:?}", mouse_event_type_string, client_point);
 
-        let el = node_address.and_then(|address| {
-            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+        let hit_node = node_address
+            .map(|address| unsafe { node::from_untrusted_node_address(js_runtime, address) });
+        let el = hit_node.as_ref().and_then(|node| {
             node.inclusive_ancestors(ShadowIncluding::No)
                 .filter_map(DomRoot::downcast::<Element>)
                 .next()
         });
-        let el = match el {
-            Some(el) => el,
-            None => return,
+        // https://w3c.github.io/pointerlock/#pointerlock-and-mouseevent
+        let (el, client_point) = match self.pointer_lock_element.get() {
+            Some(locked) => (
+                locked,
+                self.last_mouse_move_point.get().unwrap_or(client_point),
+            ),
+            None => match el {
+                Some(el) => (el, client_point),
+                None => return,
+            },
         };
 
         let node = el.upcast::<Node>();
         debug!("{} on {:?}", mouse_event_type_string, node.debug_str());
+
+        // https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events
+        let fire_mouse_event = match (&mouse_event_type, self.synthesized_mouse_events.get()) {
+            (MouseEventType::Click, _) => {
+                // The click event is never suppressed, and it ends the mouse
+                // events synthesized for a touch tap.
+                self.synthesized_mouse_events.set(None);
+                true
+            },
+            (_, Some(suppressed)) => !suppressed,
+            (_, None) => self.fire_mouse_button_pointer_event(
+                &mouse_event_type,
+                button,
+                &el,
+                client_point,
+                point_in_node,
+                pressed_mouse_buttons,
+            ),
+        };
+        if !fire_mouse_event {
+            self.window
+                .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+            return;
+        }
+
         // Prevent click event if form control element is disabled.
         if let MouseEventType::Click = mouse_event_type {
             if el.click_event_filter_by_disabled_state() {
@@ -1027,6 +1137,12 @@ impl Document {
 
                 let target = node.upcast();
                 event.fire(target);
+
+                if let MouseButton::Left = button {
+                    if !event.DefaultPrevented() {
+                        self.begin_text_selection(hit_node.as_deref(), point_in_node);
+                    }
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
@@ -1035,6 +1151,10 @@ impl Document {
 
                 let target = node.upcast();
                 event.fire(target);
+
+                if let MouseButton::Left = button {
+                    self.text_selection_in_progress.set(false);
+                }
             },
         }
 
@@ -1047,6 +1167,394 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
     }
 
+    /// Fires the pointer event for a mouse button being pressed or released,
+    /// and returns whether the corresponding mouse event should be fired.
+    /// <https://w3c.github.io/pointerevents/#the-button-property>
+    fn fire_mouse_button_pointer_event(
+        &self,
+        mouse_event_type: &MouseEventType,
+        button: MouseButton,
+        target: &Element,
+        client_point: Point2D<f32>,
+        point_in_node: Option<Point2D<f32>>,
+        pressed_mouse_buttons: u16,
+    ) -> bool {
+        let previous_buttons = self.active_pointers.mouse_pointer(|pointer| {
+            let previous_buttons = pointer.buttons;
+            pointer.set_mouse_buttons(pressed_mouse_buttons);
+            previous_buttons
+        });
+        let event_type = match *mouse_event_type {
+            MouseEventType::MouseDown if previous_buttons == 0 => "pointerdown",
+            MouseEventType::MouseUp if pressed_mouse_buttons == 0 => "pointerup",
+            // Pressing or releasing a button while another one is pressed is
+            // reported as a chorded button press.
+            _ => "pointermove",
+        };
+        let status = self.fire_pointer_event(
+            MOUSE_POINTER_ID,
+            event_type,
+            target,
+            client_point,
+            dom_mouse_button(button),
+            point_in_node,
+        );
+        let suppressed = self.active_pointers.mouse_pointer(|pointer| {
+            if event_type == "pointerdown" {
+                pointer.prevent_compat_mouse_events = status == EventStatus::Canceled;
+            }
+            let suppressed = pointer.prevent_compat_mouse_events;
+            if event_type == "pointerup" {
+                pointer.prevent_compat_mouse_events = false;
+            }
+            suppressed
+        });
+        if event_type == "pointerup" {
+            self.release_pointer_capture_implicitly(MOUSE_POINTER_ID, client_point);
+        }
+        !suppressed
+    }
+
+    /// Fires the pointer event for a touch point or a pen contact that the
+    /// embedder identifies with `source_id`. Returns `None` if no compatibility
+    /// mouse events should be fired for the contact, or whether they are
+    /// suppressed otherwise.
+    /// <https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events>
+    fn fire_contact_pointer_event(
+        &self,
+        pointer_type: PointerType,
+        event_type: TouchEventType,
+        source_id: i32,
+        target: &Element,
+        client_point: Point2D<f32>,
+        pen_state: Option<PenState>,
+    ) -> Option<bool> {
+        let (pressure, tilt_x, tilt_y) = match pen_state {
+            Some(state) => (state.pressure, state.tilt_x, state.tilt_y),
+            // https://w3c.github.io/pointerevents/#dom-pointerevent-pressure
+            None => (0.5, 0, 0),
+        };
+        let pointer_id = match event_type {
+            TouchEventType::Down => self.active_pointers.add_contact(pointer_type, source_id),
+            _ => self.active_pointers.find_contact(pointer_type, source_id)?,
+        };
+        let is_primary = self.active_pointers.with_pointer(pointer_id, |pointer| {
+            match event_type {
+                TouchEventType::Down | TouchEventType::Move => {
+                    pointer.buttons = MouseButton::Left as u16;
+                    pointer.attributes.pressure = pressure;
+                },
+                TouchEventType::Up | TouchEventType::Cancel => {
+                    pointer.buttons = 0;
+                    pointer.attributes.pressure = 0.0;
+                },
+            }
+            pointer.attributes.tilt_x = tilt_x;
+            pointer.attributes.tilt_y = tilt_y;
+            // https://w3c.github.io/pointerevents/#implicit-pointer-capture
+            if let (TouchEventType::Down, PointerType::Touch) = (event_type, pointer_type) {
+                pointer.pending_capture_target = Some(Dom::from_ref(target));
+            }
+            pointer.attributes.is_primary
+        })?;
+
+        let (event_name, button) = match event_type {
+            TouchEventType::Down => ("pointerdown", 0),
+            TouchEventType::Move => ("pointermove", -1),
+            TouchEventType::Up => ("pointerup", 0),
+            TouchEventType::Cancel => ("pointercancel", -1),
+        };
+        let status =
+            self.fire_pointer_event(pointer_id, event_name, target, client_point, button, None);
+        let suppressed = self.active_pointers.with_pointer(pointer_id, |pointer| {
+            if let TouchEventType::Down = event_type {
+                pointer.prevent_compat_mouse_events = status == EventStatus::Canceled;
+            }
+            pointer.prevent_compat_mouse_events
+        })?;
+
+        match event_type {
+            TouchEventType::Up | TouchEventType::Cancel => {
+                self.release_pointer_capture_implicitly(pointer_id, client_point);
+                self.active_pointers.remove(pointer_id);
+            },
+            TouchEventType::Down | TouchEventType::Move => {},
+        }
+
+        match event_type {
+            TouchEventType::Cancel => None,
+            _ if is_primary => Some(suppressed),
+            _ => None,
+        }
+    }
+
+    /// Fires a pointer event of the active pointer `pointer_id` at its pointer
+    /// capture target, or at `target` if the pointer isn't captured.
+    /// <https://w3c.github.io/pointerevents/#firing-events-using-the-pointerevent-interface>
+    fn fire_pointer_event(
+        &self,
+        pointer_id: i32,
+        event_type: &str,
+        target: &Element,
+        client_point: Point2D<f32>,
+        button: i16,
+        point_in_node: Option<Point2D<f32>>,
+    ) -> EventStatus {
+        // A pointer capture that is set while pointerdown is dispatched takes
+        // effect from the next pointer event on.
+        if event_type != "pointerdown" {
+            self.process_pending_pointer_capture(pointer_id, client_point);
+        }
+
+        let state = self.active_pointers.with_pointer(pointer_id, |pointer| {
+            let capture_target = pointer
+                .capture_target
+                .as_ref()
+                .map(|target| DomRoot::from_ref(&**target));
+            (pointer.attributes, pointer.buttons, capture_target)
+        });
+        let (attributes, buttons, capture_target) = match state {
+            Some(state) => state,
+            None => return EventStatus::NotCanceled,
+        };
+        let (target, point_in_node) = match capture_target {
+            Some(ref capture_target) => (&**capture_target, None),
+            None => (target, point_in_node),
+        };
+        self.dispatch_pointer_event(
+            event_type,
+            target.upcast(),
+            attributes,
+            client_point,
+            button,
+            buttons,
+            point_in_node,
+        )
+    }
+
+    /// Fires a pointerover or pointerout event of the mouse pointer at `target`.
+    fn fire_mouse_pointer_boundary_event(
+        &self,
+        event_type: &str,
+        target: &Element,
+        client_point: Point2D<f32>,
+    ) {
+        if self.synthesized_mouse_events.get().is_some() {
+            return;
+        }
+        let (attributes, buttons) = self
+            .active_pointers
+            .mouse_pointer(|pointer| (pointer.attributes, pointer.buttons));
+        self.dispatch_pointer_event(
+            event_type,
+            target.upcast(),
+            attributes,
+            client_point,
+            -1,
+            buttons,
+            None,
+        );
+    }
+
+    fn dispatch_pointer_event(
+        &self,
+        event_type: &str,
+        target: &EventTarget,
+        attributes: PointerAttributes,
+        client_point: Point2D<f32>,
+        button: i16,
+        buttons: u16,
+        point_in_node: Option<Point2D<f32>>,
+    ) -> EventStatus {
+        let cancelable = match event_type {
+            "pointercancel" | "gotpointercapture" | "lostpointercapture" => {
+                EventCancelable::NotCancelable
+            },
+            _ => EventCancelable::Cancelable,
+        };
+        let client_x = client_point.x as i32;
+        let client_y = client_point.y as i32;
+        let event = PointerEvent::new(
+            &self.window,
+            DOMString::from(event_type),
+            EventBubbles::Bubbles,
+            cancelable,
+            Some(&self.window),
+            0i32,
+            client_x,
+            client_y,
+            client_x,
+            client_y, // TODO: Get real screen coordinates?
+            false,
+            false,
+            false,
+            false,
+            button,
+            buttons,
+            None,
+            point_in_node,
+            attributes,
+        );
+        let event = event.upcast::<Event>();
+        event.set_trusted(true);
+        event.fire(target)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#process-pending-pointer-capture>
+    fn process_pending_pointer_capture(&self, pointer_id: i32, client_point: Point2D<f32>) {
+        let change = self.active_pointers.with_pointer(pointer_id, |pointer| {
+            let pending_target_is_connected = pointer
+                .pending_capture_target
+                .as_ref()
+                .map_or(true, |target| target.upcast::<Node>().is_connected());
+            if !pending_target_is_connected {
+                pointer.pending_capture_target = None;
+            }
+            if pointer.capture_target == pointer.pending_capture_target {
+                return None;
+            }
+            let old_target = pointer
+                .capture_target
+                .take()
+                .map(|target| DomRoot::from_ref(&*target));
+            pointer.capture_target = pointer
+                .pending_capture_target
+                .as_ref()
+                .map(|target| Dom::from_ref(&**target));
+            let new_target = pointer
+                .capture_target
+                .as_ref()
+                .map(|target| DomRoot::from_ref(&**target));
+            Some((old_target, new_target, pointer.attributes, pointer.buttons))
+        });
+        let (old_target, new_target, attributes, buttons) = match change {
+            Some(Some(change)) => change,
+            _ => return,
+        };
+
+        if let Some(old_target) = old_target {
+            // If the element lost the capture by being removed from the
+            // document, the event is fired at the document instead.
+            let target = if old_target.upcast::<Node>().is_connected() {
+                DomRoot::upcast::<EventTarget>(old_target)
+            } else {
+                DomRoot::from_ref(self.upcast::<EventTarget>())
+            };
+            self.dispatch_pointer_event(
+                "lostpointercapture",
+                &target,
+                attributes,
+                client_point,
+                -1,
+                buttons,
+                None,
+            );
+        }
+        if let Some(new_target) = new_target {
+            self.dispatch_pointer_event(
+                "gotpointercapture",
+                new_target.upcast(),
+                attributes,
+                client_point,
+                -1,
+                buttons,
+                None,
+            );
+        }
+    }
+
+    /// <https://w3c.github.io/pointerevents/#implicit-release-of-pointer-capture>
+    fn release_pointer_capture_implicitly(&self, pointer_id: i32, client_point: Point2D<f32>) {
+        self.active_pointers.with_pointer(pointer_id, |pointer| {
+            pointer.pending_capture_target = None;
+        });
+        self.process_pending_pointer_capture(pointer_id, client_point);
+    }
+
+    /// <https://w3c.github.io/pointerevents/#setting-pointer-capture>
+    pub fn set_pointer_capture(&self, element: &Element, pointer_id: i32) -> ErrorResult {
+        let is_connected = element.upcast::<Node>().is_connected();
+        self.active_pointers
+            .with_pointer(pointer_id, |pointer| {
+                if !is_connected {
+                    return Err(Error::InvalidState);
+                }
+                // Only a pointer with active buttons can be captured.
+                if pointer.buttons != 0 {
+                    pointer.pending_capture_target = Some(Dom::from_ref(element));
+                }
+                Ok(())
+            })
+            .unwrap_or(Err(Error::NotFound))
+    }
+
+    /// <https://w3c.github.io/pointerevents/#releasing-pointer-capture>
+    pub fn release_pointer_capture(&self, element: &Element, pointer_id: i32) -> ErrorResult {
+        self.active_pointers
+            .with_pointer(pointer_id, |pointer| {
+                if pointer.pending_capture_target.as_deref() == Some(element) {
+                    pointer.pending_capture_target = None;
+                }
+            })
+            .ok_or(Error::NotFound)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-haspointercapture>
+    pub fn has_pointer_capture(&self, element: &Element, pointer_id: i32) -> bool {
+        self.active_pointers
+            .with_pointer(pointer_id, |pointer| {
+                pointer.pending_capture_target.as_deref() == Some(element)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Returns the offset, in UTF-16 code units, of the boundary point in
+    /// the text node `node` that is closest to `point_in_node`.
+    fn text_offset_at_point(&self, node: &Node, point_in_node: Point2D<f32>) -> Option<u32> {
+        let text = node.downcast::<Text>()?;
+        let TextIndexResponse(index) = self.window.text_index_query(node, point_in_node);
+        let data = text.upcast::<CharacterData>().data();
+        let mut index = index?.min(data.len());
+        while !data.is_char_boundary(index) {
+            index -= 1;
+        }
+        Some(data[..index].encode_utf16().count() as u32)
+    }
+
+    /// Collapses the selection to the text under the mouse, and starts
+    /// extending it as the mouse moves.
+    fn begin_text_selection(&self, node: Option<&Node>, point_in_node: Option<Point2D<f32>>) {
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let offset = match (node, point_in_node) {
+            (Some(node), Some(point)) => self.text_offset_at_point(node, point),
+            _ => None,
+        };
+        match (node, offset) {
+            (Some(node), Some(offset)) => {
+                let _ = selection.Collapse(Some(node), offset);
+                self.text_selection_in_progress.set(true);
+            },
+            _ => selection.RemoveAllRanges(),
+        }
+    }
+
+    /// Moves the focus of the selection to the text under the mouse, while
+    /// the user is selecting text.
+    fn extend_text_selection(&self, node: Option<&Node>, point_in_node: Option<Point2D<f32>>) {
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        if let (Some(node), Some(point)) = (node, point_in_node) {
+            if let Some(offset) = self.text_offset_at_point(node, point) {
+                let _ = selection.Extend(node, offset);
+            }
+        }
+    }
+
     fn maybe_fire_dblclick(
         &self,
         click_pos: Point2D<f32>,
@@ -1114,10 +1622,20 @@ impl Document {
         event_name: FireMouseEventType,
         pressed_mouse_buttons: u16,
     ) {
+        let mouse_event = self.create_mouse_event(client_point, event_name, pressed_mouse_buttons);
+        mouse_event.upcast::<Event>().fire(target);
+    }
+
+    fn create_mouse_event(
+        &self,
+        client_point: Point2D<f32>,
+        event_name: FireMouseEventType,
+        pressed_mouse_buttons: u16,
+    ) -> DomRoot<MouseEvent> {
         let client_x = client_point.x.to_i32().unwrap_or(0);
         let client_y = client_point.y.to_i32().unwrap_or(0);
 
-        let mouse_event = MouseEvent::new(
+        MouseEvent::new(
             &self.window,
             DOMString::from(event_name.as_str()),
             EventBubbles::Bubbles,
@@ -1136,9 +1654,32 @@ impl Document {
             pressed_mouse_buttons,
             None,
             None,
+        )
+    }
+
+    /// Fires a `mousemove` event at the element that locked the pointer, after
+    /// the embedder reported that the locked pointer moved.
+    ///
+    /// <https://w3c.github.io/pointerlock/#pointerlock-and-mouseevent>
+    pub fn handle_mouse_movement_event(&self, movement: Vector2D<f32>, pressed_mouse_buttons: u16) {
+        let element = match self.pointer_lock_element.get() {
+            Some(element) => element,
+            None => return,
+        };
+        let client_point = self
+            .last_mouse_move_point
+            .get()
+            .unwrap_or_else(Point2D::zero);
+        let mouse_event = self.create_mouse_event(
+            client_point,
+            FireMouseEventType::Move,
+            pressed_mouse_buttons,
         );
-        let event = mouse_event.upcast::<Event>();
-        event.fire(target);
+        mouse_event.set_movement(
+            movement.x.round().to_i32().unwrap_or(0),
+            movement.y.round().to_i32().unwrap_or(0),
+        );
+        mouse_event.upcast::<Event>().fire(element.upcast());
     }
 
     #[allow(unsafe_code)]
@@ -1148,6 +1689,7 @@ impl Document {
         client_point: Option<Point2D<f32>>,
         prev_mouse_over_target: &MutNullableDom<Element>,
         node_address: Option<UntrustedNodeAddress>,
+        point_in_node: Option<Point2D<f32>>,
         pressed_mouse_buttons: u16,
     ) {
         let client_point = match client_point {
@@ -1160,8 +1702,26 @@ impl Document {
             Some(client_point) => client_point,
         };
 
-        let maybe_new_target = node_address.and_then(|address| {
-            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+        // The embedder reports how a locked pointer moves with movement events.
+        if self.pointer_lock_element.get().is_some() {
+            return;
+        }
+
+        // https://w3c.github.io/pointerlock/#dom-mouseevent-movementx
+        let movement = self
+            .last_mouse_move_point
+            .get()
+            .map_or(Vector2D::zero(), |last_point| {
+                Vector2D::new(
+                    client_point.x.to_i32().unwrap_or(0) - last_point.x.to_i32().unwrap_or(0),
+                    client_point.y.to_i32().unwrap_or(0) - last_point.y.to_i32().unwrap_or(0),
+                )
+            });
+        self.last_mouse_move_point.set(Some(client_point));
+
+        let hit_node = node_address
+            .map(|address| unsafe { node::from_untrusted_node_address(js_runtime, address) });
+        let maybe_new_target = hit_node.as_ref().and_then(|node| {
             node.inclusive_ancestors(ShadowIncluding::No)
                 .filter_map(DomRoot::downcast::<Element>)
                 .next()
@@ -1174,12 +1734,43 @@ impl Document {
             None => return,
         };
 
-        self.fire_mouse_event(
-            client_point,
-            new_target.upcast(),
-            FireMouseEventType::Move,
-            pressed_mouse_buttons,
-        );
+        let synthesized_mouse_events = self.synthesized_mouse_events.get();
+        if synthesized_mouse_events.is_none() {
+            self.active_pointers
+                .mouse_pointer(|pointer| pointer.set_mouse_buttons(pressed_mouse_buttons));
+            self.fire_pointer_event(
+                MOUSE_POINTER_ID,
+                "pointermove",
+                new_target,
+                client_point,
+                -1,
+                point_in_node,
+            );
+        }
+
+        // https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events
+        let suppressed = synthesized_mouse_events.unwrap_or_else(|| {
+            self.active_pointers
+                .mouse_pointer(|pointer| pointer.prevent_compat_mouse_events)
+        });
+        if !suppressed {
+            let mouse_event = self.create_mouse_event(
+                client_point,
+                FireMouseEventType::Move,
+                pressed_mouse_buttons,
+            );
+            mouse_event.set_movement(movement.x, movement.y);
+            mouse_event.upcast::<Event>().fire(new_target.upcast());
+        }
+
+        if self.text_selection_in_progress.get() {
+            if pressed_mouse_buttons & MouseButton::Left as u16 != 0 {
+                self.extend_text_selection(hit_node.as_deref(), point_in_node);
+            } else {
+                // The button was released outside of this document.
+                self.text_selection_in_progress.set(false);
+            }
+        }
 
         // Nothing more to do here, mousemove is sent,
         // and the element under the mouse hasn't changed.
@@ -1212,6 +1803,7 @@ impl Document {
             }
 
             // Remove hover state to old target and its parents
+            self.fire_mouse_pointer_boundary_event("pointerout", &old_target, client_point);
             self.fire_mouse_event(
                 client_point,
                 old_target.upcast(),
@@ -1236,6 +1828,7 @@ impl Document {
                 element.set_hover_state(true);
             }
 
+            self.fire_mouse_pointer_boundary_event("pointerover", new_target, client_point);
             self.fire_mouse_event(
                 client_point,
                 &new_target.upcast(),
@@ -1329,7 +1922,40 @@ impl Document {
             None => return TouchEventResult::Forwarded,
         };
 
-        let target = DomRoot::upcast::<EventTarget>(el);
+        let compat_mouse_events = self.fire_contact_pointer_event(
+            PointerType::Touch,
+            event_type,
+            identifier,
+            &el,
+            point,
+            None,
+        );
+        // The compositor synthesizes the mouse events for a tap right after
+        // the touch point is lifted.
+        if let TouchEventType::Up = event_type {
+            self.synthesized_mouse_events
+                .set(Some(compat_mouse_events.unwrap_or(true)));
+        }
+
+        // https://w3c.github.io/touch-events/#dom-touch-target
+        let target = match event_type {
+            TouchEventType::Down => DomRoot::upcast::<EventTarget>(el),
+            // A touch point keeps targeting the element it started on, even
+            // after it has moved out of it.
+            _ => {
+                let active_touch_points = self.active_touch_points.borrow();
+                match active_touch_points
+                    .iter()
+                    .find(|t| t.Identifier() == identifier)
+                {
+                    Some(t) => t.Target(),
+                    None => {
+                        warn!("Got a {} event for a non-active touch point", event_name);
+                        return TouchEventResult::Forwarded;
+                    },
+                }
+            },
+        };
         let window = &*self.window;
 
         let client_x = Finite::wrap(point.x as f64);
@@ -1343,37 +1969,27 @@ impl Document {
             client_x, client_y, page_x, page_y,
         );
 
-        match event_type {
-            TouchEventType::Down => {
+        {
+            let mut active_touch_points = self.active_touch_points.borrow_mut();
+            let index = active_touch_points
+                .iter()
+                .position(|t| t.Identifier() == identifier);
+            match (event_type, index) {
+                // Replace a touch point whose end we never saw.
+                (TouchEventType::Down, Some(i)) | (TouchEventType::Move, Some(i)) => {
+                    active_touch_points[i] = Dom::from_ref(&*touch);
+                },
                 // Add a new touch point
-                self.active_touch_points
-                    .borrow_mut()
-                    .push(Dom::from_ref(&*touch));
-            },
-            TouchEventType::Move => {
-                // Replace an existing touch point
-                let mut active_touch_points = self.active_touch_points.borrow_mut();
-                match active_touch_points
-                    .iter_mut()
-                    .find(|t| t.Identifier() == identifier)
-                {
-                    Some(t) => *t = Dom::from_ref(&*touch),
-                    None => warn!("Got a touchmove event for a non-active touch point"),
-                }
-            },
-            TouchEventType::Up | TouchEventType::Cancel => {
-                // Remove an existing touch point
-                let mut active_touch_points = self.active_touch_points.borrow_mut();
-                match active_touch_points
-                    .iter()
-                    .position(|t| t.Identifier() == identifier)
-                {
-                    Some(i) => {
-                        active_touch_points.swap_remove(i);
-                    },
-                    None => warn!("Got a touchend event for a non-active touch point"),
-                }
-            },
+                (TouchEventType::Down, None) => {
+                    active_touch_points.push(Dom::from_ref(&*touch));
+                },
+                // Remove an existing touch point, keeping the others in the
+                // order they touched the screen.
+                (TouchEventType::Up, Some(i)) | (TouchEventType::Cancel, Some(i)) => {
+                    active_touch_points.remove(i);
+                },
+                (_, None) => unreachable!("Only touchstart can add a touch point"),
+            }
         }
 
         rooted_vec!(let mut target_touches);
@@ -1410,6 +2026,84 @@ impl Document {
         }
     }
 
+    #[allow(unsafe_code)]
+    pub fn handle_pen_event(
+        &self,
+        js_runtime: *mut JSRuntime,
+        event_type: TouchEventType,
+        pen_id: TouchId,
+        point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>,
+        state: PenState,
+    ) {
+        let TouchId(identifier) = pen_id;
+
+        let el = node_address.and_then(|address| {
+            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+            node.inclusive_ancestors(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<Element>)
+                .next()
+        });
+        let el = match el {
+            Some(el) => el,
+            None => return,
+        };
+
+        let compat_mouse_events = self.fire_contact_pointer_event(
+            PointerType::Pen,
+            event_type,
+            identifier,
+            &el,
+            point,
+            Some(state),
+        );
+
+        // https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events
+        if let Some(suppressed) = compat_mouse_events {
+            self.synthesized_mouse_events.set(Some(suppressed));
+            let button = MouseButton::Left;
+            match event_type {
+                TouchEventType::Down => self.handle_mouse_event(
+                    js_runtime,
+                    button,
+                    point,
+                    MouseEventType::MouseDown,
+                    node_address,
+                    None,
+                    button as u16,
+                ),
+                TouchEventType::Move => {
+                    if !suppressed {
+                        self.fire_mouse_event(
+                            point,
+                            el.upcast(),
+                            FireMouseEventType::Move,
+                            button as u16,
+                        );
+                    }
+                },
+                TouchEventType::Up => {
+                    for mouse_event_type in vec![MouseEventType::MouseUp, MouseEventType::Click] {
+                        self.handle_mouse_event(
+                            js_runtime,
+                            button,
+                            point,
+                            mouse_event_type,
+                            node_address,
+                            None,
+                            0,
+                        );
+                    }
+                },
+                TouchEventType::Cancel => {},
+            }
+            self.synthesized_mouse_events.set(None);
+        }
+
+        self.window
+            .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+    }
+
     /// The entry point for all key processing for web content
     pub fn dispatch_key_event(&self, keyboard_event: ::keyboard_types::KeyboardEvent) {
         let focused = self.get_focused_element();
@@ -1473,6 +2167,10 @@ impl Document {
             let msg = EmbedderMsg::Keyboard(keyboard_event.clone());
             self.send_to_embedder(msg);
 
+            if keyboard_event.state == KeyState::Down {
+                self.handle_selection_shortcut(&keyboard_event);
+            }
+
             // This behavior is unspecced
             // We are supposed to dispatch synthetic click activation for Space and/or Return,
             // however *when* we do it is up to us.
@@ -1498,6 +2196,34 @@ impl Document {
         self.window.reflow(ReflowGoal::Full, ReflowReason::KeyEvent);
     }
 
+    /// Runs the keyboard shortcuts that act on the selection of the document.
+    /// Text controls handle these themselves, for their own selection.
+    fn handle_selection_shortcut(&self, keyboard_event: &::keyboard_types::KeyboardEvent) {
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        ShortcutMatcher::from_event(keyboard_event.clone())
+            .shortcut(CMD_OR_CONTROL, 'A', || {
+                if let Some(body) = self.GetBody() {
+                    let _ = selection.SelectAllChildren(body.upcast());
+                }
+            })
+            .shortcut(CMD_OR_CONTROL, 'C', || {
+                let text = selection.Stringifier();
+                if !text.is_empty() {
+                    self.send_to_embedder(EmbedderMsg::SetClipboardContents(text.into()));
+                }
+            })
+            .shortcut(Modifiers::SHIFT, Key::ArrowLeft, || {
+                extend_selection_by_one_character(&selection, Direction::Backward);
+            })
+            .shortcut(Modifiers::SHIFT, Key::ArrowRight, || {
+                extend_selection_by_one_character(&selection, Direction::Forward);
+            })
+            .otherwise(|| ());
+    }
+
     pub fn dispatch_composition_event(
         &self,
         composition_event: ::keyboard_types::CompositionEvent,
@@ -1789,7 +2515,7 @@ impl Document {
 
         // Servo measures when the top-level content (not iframes) is loaded.
         if (self.top_level_dom_complete.get() == 0) && loader.is_only_blocked_by_iframes() {
-            update_with_current_time_ms(&self.top_level_dom_complete);
+            update_with_current_time_ns(&self.top_level_dom_complete);
         }
 
         if loader.is_blocked() || loader.events_inhibited() {
@@ -1861,6 +2587,8 @@ impl Document {
         // TODO: Step 1, increase the event loop's termination nesting level by 1.
         // Step 2
         self.incr_ignore_opens_during_unload_counter();
+        // https://w3c.github.io/pointerlock/#exiting-pointer-lock
+        self.exit_pointer_lock();
         let document = Trusted::new(self);
         // Step 3-6
         if self.page_showing.get() {
@@ -1980,7 +2708,7 @@ impl Document {
                     event.set_trusted(true);
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventStart
-                    update_with_current_time_ms(&document.load_event_start);
+                    update_with_current_time_ns(&document.load_event_start);
 
                     debug!("About to dispatch load for {:?}", document.url());
                     // FIXME(nox): Why are errors silenced here?
@@ -1990,7 +2718,7 @@ impl Document {
                     );
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
-                    update_with_current_time_ms(&document.load_event_end);
+                    update_with_current_time_ns(&document.load_event_end);
 
                     window.reflow(ReflowGoal::Full, ReflowReason::DocumentLoaded);
 
@@ -2203,7 +2931,7 @@ impl Document {
             "Complete before DOMContentLoaded?"
         );
 
-        update_with_current_time_ms(&self.dom_content_loaded_event_start);
+        update_with_current_time_ns(&self.dom_content_loaded_event_start);
 
         // Step 4.1.
         let window = self.window();
@@ -2215,7 +2943,7 @@ impl Document {
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"));
-                update_with_current_time_ms(&document.dom_content_loaded_event_end);
+                update_with_current_time_ns(&document.dom_content_loaded_event_end);
                 }),
                 window.upcast(),
             )
@@ -2312,10 +3040,6 @@ impl Document {
             .find(|node| node.browsing_context_id() == Some(browsing_context_id))
     }
 
-    pub fn get_dom_loading(&self) -> u64 {
-        self.dom_loading.get()
-    }
-
     pub fn get_dom_interactive(&self) -> u64 {
         self.dom_interactive.get()
     }
@@ -2502,7 +3226,35 @@ impl Document {
             .or_insert_with(|| Dom::from_ref(context));
     }
 
+    pub fn add_dirty_webgpu_canvas(&self, context: &GPUCanvasContext) {
+        if let Some(image_key) = context.image_key() {
+            self.dirty_webgpu_contexts
+                .borrow_mut()
+                .entry(image_key)
+                .or_insert_with(|| Dom::from_ref(context));
+        }
+    }
+
+    pub fn add_offscreen_canvas_placeholder(&self, id: CanvasId, canvas: &HTMLCanvasElement) {
+        let mut placeholders = self.offscreen_canvas_placeholders.borrow_mut();
+        placeholders.retain(|_, canvas| canvas.is_alive());
+        placeholders.insert(id, WeakRef::new(canvas));
+    }
+
+    pub fn offscreen_canvas_placeholder(&self, id: CanvasId) -> Option<DomRoot<HTMLCanvasElement>> {
+        self.offscreen_canvas_placeholders
+            .borrow()
+            .get(&id)
+            .and_then(WeakRef::root)
+    }
+
     pub fn flush_dirty_canvases(&self) {
+        for (_, context) in self.dirty_webgpu_contexts.borrow_mut().drain() {
+            if context.onscreen() {
+                context.send_swap_chain_present();
+            }
+        }
+
         let dirty_context_ids: Vec<_> = self
             .dirty_webgl_contexts
             .borrow_mut()
@@ -2618,7 +3370,7 @@ impl LayoutDocumentHelpers for LayoutDom<Document> {
 // https://html.spec.whatwg.org/multipage/#is-a-registrable-domain-suffix-of-or-is-equal-to
 // The spec says to return a bool, we actually return an Option<Host> containing
 // the parsed host in the successful case, to avoid having to re-parse the host.
-fn get_registrable_domain_suffix_of_or_is_equal_to(
+pub fn get_registrable_domain_suffix_of_or_is_equal_to(
     host_suffix_string: &str,
     original_host: Host,
 ) -> Option<Host> {
@@ -2666,6 +3418,17 @@ fn get_registrable_domain_suffix_of_or_is_equal_to(
     Some(host)
 }
 
+/// The `is` value passed to `createElement()` or `createElementNS()`, a string
+/// in place of the options dictionary is ignored.
+fn is_value_from_options(options: &StringOrElementCreationOptions) -> Option<LocalName> {
+    match *options {
+        StringOrElementCreationOptions::String(_) => None,
+        StringOrElementCreationOptions::ElementCreationOptions(ref options) => {
+            options.is.as_ref().map(|is| LocalName::from(&**is))
+        },
+    }
+}
+
 /// <https://url.spec.whatwg.org/#network-scheme>
 fn url_has_network_scheme(url: &ServoUrl) -> bool {
     match url.scheme() {
@@ -2766,6 +3529,7 @@ impl Document {
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
             ready_state: Cell::new(ready_state),
+            visibility_state: Cell::new(DocumentVisibilityState::Visible),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             possibly_focused: Default::default(),
             focused: Default::default(),
@@ -2787,7 +3551,8 @@ impl Document {
             pending_restyles: DomRefCell::new(HashMap::new()),
             needs_paint: Cell::new(false),
             active_touch_points: DomRefCell::new(Vec::new()),
-            dom_loading: Cell::new(Default::default()),
+            active_pointers: ActivePointerList::new(),
+            synthesized_mouse_events: Cell::new(None),
             dom_interactive: Cell::new(Default::default()),
             dom_content_loaded_event_start: Cell::new(Default::default()),
             dom_content_loaded_event_end: Cell::new(Default::default()),
@@ -2803,11 +3568,15 @@ impl Document {
             referrer_policy: Cell::new(referrer_policy),
             target_element: MutNullableDom::new(None),
             last_click_info: DomRefCell::new(None),
+            selection: MutNullableDom::new(None),
+            text_selection_in_progress: Cell::new(false),
             ignore_destructive_writes_counter: Default::default(),
             ignore_opens_during_unload_counter: Default::default(),
             spurious_animation_frames: Cell::new(0),
             dom_count: Cell::new(1),
             fullscreen_element: MutNullableDom::new(None),
+            pointer_lock_element: MutNullableDom::new(None),
+            last_mouse_move_point: Cell::new(None),
             form_id_listener_map: Default::default(),
             interactive_time: DomRefCell::new(interactive_time),
             tti_window: DomRefCell::new(InteractiveWindow::new()),
@@ -2825,7 +3594,13 @@ impl Document {
             shadow_roots_styles_changed: Cell::new(false),
             media_controls: DomRefCell::new(HashMap::new()),
             dirty_webgl_contexts: DomRefCell::new(HashMap::new()),
+            dirty_webgpu_contexts: DomRefCell::new(HashMap::new()),
             csp_list: DomRefCell::new(None),
+            timeline: MutNullableDom::new(None),
+            animations: DomRefCell::new(vec![]),
+            next_animation_id: Cell::new(0),
+            offscreen_canvas_placeholders: DomRefCell::new(HashMap::new()),
+            fonts: MutNullableDom::new(None),
         }
     }
 
@@ -2837,6 +3612,56 @@ impl Document {
         ref_filter_map(self.csp_list.borrow(), Option::as_ref)
     }
 
+    /// <https://drafts.csswg.org/web-animations/#the-documents-default-timeline>
+    pub fn timeline(&self) -> DomRoot<DocumentTimeline> {
+        self.timeline
+            .or_init(|| DocumentTimeline::new(&self.window, 0.))
+    }
+
+    pub fn next_animation_id(&self) -> u64 {
+        let id = self.next_animation_id.get();
+        self.next_animation_id.set(id + 1);
+        id
+    }
+
+    /// Keeps track of an animation that became relevant.
+    pub fn add_animation(&self, animation: &Animation) {
+        let mut animations = self.animations.borrow_mut();
+        if animations.iter().any(|other| &**other == animation) {
+            return;
+        }
+        let index = animations
+            .iter()
+            .position(|other| other.layout_id() > animation.layout_id())
+            .unwrap_or(animations.len());
+        animations.insert(index, Dom::from_ref(animation));
+    }
+
+    pub fn remove_animation(&self, animation: &Animation) {
+        self.animations
+            .borrow_mut()
+            .retain(|other| &**other != animation);
+    }
+
+    /// Marks the elements that the animations script created run on as
+    /// needing a restyle, which is how layout advances these animations.
+    pub fn dirty_animated_elements(&self) {
+        for animation in self.animations.borrow().iter() {
+            if let Some(target) = animation.layout_target() {
+                target.upcast::<Node>().dirty(NodeDamage::NodeStyleDamaged);
+            }
+        }
+    }
+
+    /// The relevant animations that script created, in composite order.
+    pub fn animations(&self) -> Vec<DomRoot<Animation>> {
+        self.animations
+            .borrow()
+            .iter()
+            .map(|animation| DomRoot::from_ref(&**animation))
+            .collect()
+    }
+
     /// https://www.w3.org/TR/CSP/#should-block-inline
     pub fn should_elements_inline_type_behavior_be_blocked(
         &self,
@@ -2849,13 +3674,30 @@ impl Document {
                 .get_attribute(&ns!(), &local_name!("nonce"))
                 .map(|attr| Cow::Owned(attr.value().to_string())),
         };
-        // TODO: Instead of ignoring violations, report them.
-        self.get_csp_list()
-            .map(|c| {
-                c.should_elements_inline_type_behavior_be_blocked(&element, type_, source)
-                    .0
-            })
-            .unwrap_or(csp::CheckResult::Allowed)
+        let (result, violations) = match self.get_csp_list() {
+            Some(csp_list) => {
+                csp_list.should_elements_inline_type_behavior_be_blocked(&element, type_, source)
+            },
+            None => return csp::CheckResult::Allowed,
+        };
+        self.global().report_csp_violations(violations, Some(el));
+        result
+    }
+
+    /// The inline check for navigations to `javascript:` URLs.
+    /// https://www.w3.org/TR/CSP/#should-block-navigation-request
+    pub fn should_javascript_url_navigation_be_blocked(&self, url: &ServoUrl) -> bool {
+        let element = csp::Element { nonce: None };
+        let (result, violations) = match self.get_csp_list() {
+            Some(csp_list) => csp_list.should_elements_inline_type_behavior_be_blocked(
+                &element,
+                csp::InlineCheckType::Navigation,
+                url.as_str(),
+            ),
+            None => return false,
+        };
+        self.global().report_csp_violations(violations, None);
+        result == csp::CheckResult::Blocked
     }
 
     /// Prevent any JS or layout from running until the corresponding call to
@@ -3196,21 +4038,13 @@ impl Document {
             error = true;
         }
 
-        if pref!(dom.fullscreen.test) {
-            // For reftests we just take over the current window,
-            // and don't try to really enter fullscreen.
-            info!("Tests don't really enter fullscreen.");
-        } else {
-            // TODO fullscreen is supported
-            // TODO This algorithm is allowed to request fullscreen.
-            warn!("Fullscreen not supported yet");
-        }
-
         // Step 5 Parallel start
 
         let window = self.window();
         // Step 6
-        if !error {
+        // For reftests we just take over the current window,
+        // and don't try to really enter fullscreen.
+        if !error && !pref!(dom.fullscreen.test) {
             let event = EmbedderMsg::SetFullscreenState(true);
             self.send_to_embedder(event);
         }
@@ -3256,8 +4090,10 @@ impl Document {
 
         let window = self.window();
         // Step 8
-        let event = EmbedderMsg::SetFullscreenState(false);
-        self.send_to_embedder(event);
+        if !pref!(dom.fullscreen.test) {
+            let event = EmbedderMsg::SetFullscreenState(false);
+            self.send_to_embedder(event);
+        }
 
         // Step 9
         let trusted_element = Trusted::new(&*element);
@@ -3278,6 +4114,49 @@ impl Document {
         promise
     }
 
+    /// <https://w3c.github.io/pointerlock/#dom-element-requestpointerlock>
+    pub fn request_pointer_lock(&self, element: &Element) {
+        // TODO: Only lock the pointer in response to an engagement gesture,
+        // and honour the allow-pointer-lock sandboxing flag.
+        if !element.is_connected() || !self.is_fully_active() || !self.has_browsing_context {
+            self.queue_pointer_lock_event(atom!("pointerlockerror"));
+            return;
+        }
+
+        let previous = self.pointer_lock_element.get();
+        if previous.as_deref() == Some(element) {
+            return;
+        }
+        self.pointer_lock_element.set(Some(element));
+        if previous.is_none() {
+            self.send_to_embedder(EmbedderMsg::SetPointerLockState(true));
+        }
+        self.queue_pointer_lock_event(atom!("pointerlockchange"));
+    }
+
+    /// <https://w3c.github.io/pointerlock/#dom-document-exitpointerlock>
+    pub fn exit_pointer_lock(&self) {
+        if self.pointer_lock_element.get().is_none() {
+            return;
+        }
+        self.pointer_lock_element.set(None);
+        self.send_to_embedder(EmbedderMsg::SetPointerLockState(false));
+        self.queue_pointer_lock_event(atom!("pointerlockchange"));
+    }
+
+    fn queue_pointer_lock_event(&self, name: Atom) {
+        self.window
+            .task_manager()
+            .user_interaction_task_source()
+            .queue_event(
+                self.upcast(),
+                name,
+                EventBubbles::DoesNotBubble,
+                EventCancelable::NotCancelable,
+                &self.window,
+            );
+    }
+
     pub fn set_fullscreen_element(&self, element: Option<&Element>) {
         self.fullscreen_element.set(element);
     }
@@ -3624,7 +4503,7 @@ impl DocumentMethods for Document {
     fn CreateElement(
         &self,
         mut local_name: DOMString,
-        options: &ElementCreationOptions,
+        options: StringOrElementCreationOptions,
     ) -> Fallible<DomRoot<Element>> {
         if xml_name_type(&local_name) == InvalidXMLName {
             debug!("Not a valid element name");
@@ -3645,7 +4524,7 @@ impl DocumentMethods for Document {
         };
 
         let name = QualName::new(None, ns, LocalName::from(local_name));
-        let is = options.is.as_ref().map(|is| LocalName::from(&**is));
+        let is = is_value_from_options(&options);
         Ok(Element::create(
             name,
             is,
@@ -3660,11 +4539,11 @@ impl DocumentMethods for Document {
         &self,
         namespace: Option<DOMString>,
         qualified_name: DOMString,
-        options: &ElementCreationOptions,
+        options: StringOrElementCreationOptions,
     ) -> Fallible<DomRoot<Element>> {
         let (namespace, prefix, local_name) = validate_and_extract(namespace, &qualified_name)?;
         let name = QualName::new(prefix, namespace, local_name);
-        let is = options.is.as_ref().map(|is| LocalName::from(&**is));
+        let is = is_value_from_options(&options);
         Ok(Element::create(
             name,
             is,
@@ -4221,6 +5100,16 @@ impl DocumentMethods for Document {
         self.ready_state.get()
     }
 
+    // https://w3c.github.io/page-visibility/#dom-hidden
+    fn Hidden(&self) -> bool {
+        self.visibility_state.get() == DocumentVisibilityState::Hidden
+    }
+
+    // https://w3c.github.io/page-visibility/#dom-visibilitystate
+    fn VisibilityState(&self) -> DocumentVisibilityState {
+        self.visibility_state.get()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-defaultview
     fn GetDefaultView(&self) -> Option<DomRoot<Window>> {
         if self.has_browsing_context {
@@ -4404,6 +5293,13 @@ impl DocumentMethods for Document {
         SetOnreadystatechange
     );
 
+    // https://w3c.github.io/page-visibility/#dom-onvisibilitychange
+    event_handler!(
+        visibilitychange,
+        GetOnvisibilitychange,
+        SetOnvisibilitychange
+    );
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     fn ElementFromPoint(&self, x: Finite<f64>, y: Finite<f64>) -> Option<DomRoot<Element>> {
         self.document_or_shadow_root.element_from_point(
@@ -4540,7 +5436,7 @@ impl DocumentMethods for Document {
         // See https://github.com/whatwg/html/issues/4091
         let context = self.browsing_context().ok_or(Error::InvalidAccess)?;
         context
-            .open(url, target, features)
+            .open(url, target, features)?
             .ok_or(Error::InvalidAccess)
     }
 
@@ -4634,6 +5530,28 @@ impl DocumentMethods for Document {
         SetOnfullscreenchange
     );
 
+    // https://drafts.csswg.org/web-animations/#dom-document-timeline
+    fn Timeline(&self) -> DomRoot<DocumentTimeline> {
+        self.timeline()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-document-getanimations
+    fn GetAnimations(&self) -> Vec<DomRoot<Animation>> {
+        self.animations()
+            .into_iter()
+            .filter(|animation| {
+                animation
+                    .target()
+                    .map_or(false, |target| target.upcast::<Node>().is_connected())
+            })
+            .collect()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts
+    fn Fonts(&self) -> DomRoot<FontFaceSet> {
+        self.fonts.or_init(|| FontFaceSet::new(&self.global()))
+    }
+
     // https://fullscreen.spec.whatwg.org/#dom-document-fullscreenenabled
     fn FullscreenEnabled(&self) -> bool {
         self.get_allow_fullscreen()
@@ -4655,6 +5573,70 @@ impl DocumentMethods for Document {
         self.exit_fullscreen()
     }
 
+    // https://w3c.github.io/pointerlock/#dom-documentorshadowroot-pointerlockelement
+    fn GetPointerLockElement(&self) -> Option<DomRoot<Element>> {
+        // TODO ShadowRoot
+        self.pointer_lock_element.get()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-exitpointerlock
+    fn ExitPointerLock(&self) {
+        self.exit_pointer_lock()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-onpointerlockchange
+    event_handler!(
+        pointerlockchange,
+        GetOnpointerlockchange,
+        SetOnpointerlockchange
+    );
+
+    // https://w3c.github.io/pointerlock/#dom-document-onpointerlockerror
+    event_handler!(
+        pointerlockerror,
+        GetOnpointerlockerror,
+        SetOnpointerlockerror
+    );
+
+    // https://w3c.github.io/selection-api/#dom-document-getselection
+    fn GetSelection(&self) -> Option<DomRoot<Selection>> {
+        if self.has_browsing_context {
+            Some(self.selection.or_init(|| Selection::new(self)))
+        } else {
+            None
+        }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    fn CreateExpression(
+        &self,
+        expression: DOMString,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        XPathExpression::new(&self.window, &expression, resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-creatensresolver
+    fn CreateNSResolver(&self, node_resolver: &Node) -> DomRoot<Node> {
+        DomRoot::from_ref(node_resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+    fn Evaluate(
+        &self,
+        expression: DOMString,
+        context_node: &Node,
+        resolver: Option<Rc<XPathNSResolver>>,
+        type_: u16,
+        result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        XPathExpression::new(&self.window, &expression, resolver)?.Evaluate(
+            context_node,
+            type_,
+            result,
+        )
+    }
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -4666,11 +5648,34 @@ impl DocumentMethods for Document {
     }
 }
 
-fn update_with_current_time_ms(marker: &Cell<u64>) {
+/// Moves the focus of `selection` by one character within its text node.
+fn extend_selection_by_one_character(selection: &Selection, direction: Direction) {
+    let node = match selection.GetFocusNode() {
+        Some(node) => node,
+        None => return,
+    };
+    let offset = selection.FocusOffset();
+    let text = match node.downcast::<CharacterData>() {
+        Some(text) if node.is::<Text>() => text.data().to_string(),
+        _ => return,
+    };
+    let mut boundaries = vec![0];
+    boundaries.extend(text.chars().scan(0, |position, c| {
+        *position += c.len_utf16() as u32;
+        Some(*position)
+    }));
+    let new_offset = match direction {
+        Direction::Backward => boundaries.iter().rev().find(|&&b| b < offset),
+        Direction::Forward => boundaries.iter().find(|&&b| b > offset),
+    };
+    if let Some(&new_offset) = new_offset {
+        let _ = selection.Extend(&node, new_offset);
+    }
+}
+
+fn update_with_current_time_ns(marker: &Cell<u64>) {
     if marker.get() == 0 {
-        let time = time::get_time();
-        let current_time_ms = time.sec * 1000 + time.nsec as i64 / 1000000;
-        marker.set(current_time_ms as u64);
+        marker.set(time::precise_time_ns());
     }
 }
 
@@ -4755,6 +5760,137 @@ impl AnimationFrameCallback {
     }
 }
 
+/// <https://w3c.github.io/pointerevents/#dfn-active-pointer>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct ActivePointerList {
+    pointers: DomRefCell<Vec<ActivePointer>>,
+    /// The `pointerId` to give to the next touch point or pen contact.
+    next_pointer_id: Cell<i32>,
+}
+
+impl ActivePointerList {
+    fn new() -> Self {
+        Self {
+            pointers: DomRefCell::new(Vec::new()),
+            next_pointer_id: Cell::new(MOUSE_POINTER_ID + 1),
+        }
+    }
+
+    fn with_pointer<R>(
+        &self,
+        pointer_id: i32,
+        f: impl FnOnce(&mut ActivePointer) -> R,
+    ) -> Option<R> {
+        if pointer_id == MOUSE_POINTER_ID {
+            return Some(self.mouse_pointer(f));
+        }
+        let mut pointers = self.pointers.borrow_mut();
+        pointers
+            .iter_mut()
+            .find(|pointer| pointer.attributes.pointer_id == pointer_id)
+            .map(f)
+    }
+
+    /// The mouse pointer is always active, so it is created on first use.
+    fn mouse_pointer<R>(&self, f: impl FnOnce(&mut ActivePointer) -> R) -> R {
+        let mut pointers = self.pointers.borrow_mut();
+        let index = match pointers
+            .iter()
+            .position(|pointer| pointer.attributes.pointer_id == MOUSE_POINTER_ID)
+        {
+            Some(index) => index,
+            None => {
+                pointers.push(ActivePointer::new(
+                    MOUSE_POINTER_ID,
+                    PointerType::Mouse,
+                    true,
+                    None,
+                ));
+                pointers.len() - 1
+            },
+        };
+        f(&mut pointers[index])
+    }
+
+    fn add_contact(&self, pointer_type: PointerType, source_id: i32) -> i32 {
+        let mut pointers = self.pointers.borrow_mut();
+        // The embedder may reuse the identifier of a contact we never saw lifted.
+        pointers.retain(|pointer| !pointer.is_contact(pointer_type, source_id));
+        let is_primary = !pointers
+            .iter()
+            .any(|pointer| pointer.attributes.pointer_type == pointer_type);
+        let pointer_id = self.next_pointer_id.get();
+        self.next_pointer_id.set(pointer_id + 1);
+        pointers.push(ActivePointer::new(
+            pointer_id,
+            pointer_type,
+            is_primary,
+            Some(source_id),
+        ));
+        pointer_id
+    }
+
+    fn find_contact(&self, pointer_type: PointerType, source_id: i32) -> Option<i32> {
+        self.pointers
+            .borrow()
+            .iter()
+            .find(|pointer| pointer.is_contact(pointer_type, source_id))
+            .map(|pointer| pointer.attributes.pointer_id)
+    }
+
+    fn remove(&self, pointer_id: i32) {
+        self.pointers
+            .borrow_mut()
+            .retain(|pointer| pointer.attributes.pointer_id != pointer_id);
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct ActivePointer {
+    attributes: PointerAttributes,
+    /// The identifier the embedder gave to the touch point or pen contact.
+    source_id: Option<i32>,
+    /// Bitmask of the buttons that are pressed. A touch point or pen in
+    /// contact with the screen presses the primary button.
+    buttons: u16,
+    /// Whether the `pointerdown` event of the current press was canceled.
+    prevent_compat_mouse_events: bool,
+    /// <https://w3c.github.io/pointerevents/#dfn-pending-pointer-capture-target-override>
+    pending_capture_target: Option<Dom<Element>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pointer-capture-target-override>
+    capture_target: Option<Dom<Element>>,
+}
+
+impl ActivePointer {
+    fn new(
+        pointer_id: i32,
+        pointer_type: PointerType,
+        is_primary: bool,
+        source_id: Option<i32>,
+    ) -> Self {
+        Self {
+            attributes: PointerAttributes::new(pointer_id, pointer_type, is_primary),
+            source_id,
+            buttons: 0,
+            prevent_compat_mouse_events: false,
+            pending_capture_target: None,
+            capture_target: None,
+        }
+    }
+
+    fn is_contact(&self, pointer_type: PointerType, source_id: i32) -> bool {
+        self.attributes.pointer_type == pointer_type && self.source_id == Some(source_id)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-pointerevent-pressure>
+    fn set_mouse_buttons(&mut self, buttons: u16) {
+        self.buttons = buttons;
+        self.attributes.pressure = if buttons != 0 { 0.5 } else { 0.0 };
+    }
+}
+
 #[derive(Default, JSTraceable, MallocSizeOf)]
 #[unrooted_must_root_lint::must_root]
 struct PendingInOrderScriptVec {
@@ -5,20 +5,28 @@
 //! Element nodes.
 
 use crate::dom::activation::Activatable;
+use crate::dom::animation::Animation;
+use crate::dom::animationeffect::Timing;
 use crate::dom::attr::{Attr, AttrHelpersForLayout};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimatableBinding::GetAnimationsOptions;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::AnimationMethods;
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ScrollLogicalPosition;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ShadowRootInit;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
-use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrKeyframeAnimationOptions;
+use crate::dom::bindings::codegen::UnionTypes::{BooleanOrScrollIntoViewOptions, NodeOrString};
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -59,7 +67,9 @@ use crate::dom::htmllegendelement::HTMLLegendElement;
 use crate::dom::htmllinkelement::HTMLLinkElement;
 use crate::dom::htmlobjectelement::HTMLObjectElement;
 use crate::dom::htmloptgroupelement::HTMLOptGroupElement;
+use crate::dom::htmloutputelement::HTMLOutputElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
+use crate::dom::htmlslotelement::{find_a_slot, slottable_name_changed, HTMLSlotElement};
 use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::htmltablecellelement::{HTMLTableCellElement, HTMLTableCellElementLayoutHelpers};
 use crate::dom::htmltableelement::{HTMLTableElement, HTMLTableElementLayoutHelpers};
@@ -69,6 +79,7 @@ use crate::dom::htmltablesectionelement::{
 };
 use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
+use crate::dom::keyframeeffect::KeyframeEffect;
 use crate::dom::mutationobserver::{Mutation, MutationObserver};
 use crate::dom::namednodemap::NamedNodeMap;
 use crate::dom::node::{document_from_node, window_from_node};
@@ -83,9 +94,13 @@ use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::ReflowReason;
+use crate::dom::xmlserializer::serialize_xml;
+use crate::script_runtime::JSContext;
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
 use crate::task::TaskOnce;
+use app_units::Au;
+use content_security_policy as csp;
 use devtools_traits::AttrInfo;
 use dom_struct::dom_struct;
 use html5ever::serialize;
@@ -93,7 +108,7 @@ use html5ever::serialize::SerializeOpts;
 use html5ever::serialize::TraversalScope;
 use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
 use html5ever::{LocalName, Namespace, Prefix, QualName};
-use js::jsapi::Heap;
+use js::jsapi::{Heap, JSObject};
 use js::jsval::JSVal;
 use msg::constellation_msg::InputMethodType;
 use net_traits::request::CorsSettings;
@@ -135,8 +150,6 @@ use style::thread_state;
 use style::values::generics::NonNegative;
 use style::values::{computed, specified, CSSFloat};
 use style::CaseSensitivityExt;
-use xml5ever::serialize as xmlSerialize;
-use xml5ever::serialize::SerializeOpts as XmlSerializeOpts;
 use xml5ever::serialize::TraversalScope as XmlTraversalScope;
 use xml5ever::serialize::TraversalScope::ChildrenOnly as XmlChildrenOnly;
 use xml5ever::serialize::TraversalScope::IncludeNode as XmlIncludeNode;
@@ -436,7 +449,7 @@ impl Element {
         })
     }
 
-    fn shadow_root(&self) -> Option<DomRoot<ShadowRoot>> {
+    pub fn shadow_root(&self) -> Option<DomRoot<ShadowRoot>> {
         self.rare_data()
             .as_ref()?
             .shadow_root
@@ -451,7 +464,11 @@ impl Element {
     /// https://dom.spec.whatwg.org/#dom-element-attachshadow
     /// XXX This is not exposed to web content yet. It is meant to be used
     ///     for UA widgets only.
-    pub fn attach_shadow(&self, is_ua_widget: IsUserAgentWidget) -> Fallible<DomRoot<ShadowRoot>> {
+    pub fn attach_shadow(
+        &self,
+        is_ua_widget: IsUserAgentWidget,
+        mode: ShadowRootMode,
+    ) -> Fallible<DomRoot<ShadowRoot>> {
         // Step 1.
         if self.namespace != ns!(html) {
             return Err(Error::NotSupported);
@@ -488,7 +505,7 @@ impl Element {
         }
 
         // Steps 4, 5 and 6.
-        let shadow_root = ShadowRoot::new(self, &*self.node.owner_doc());
+        let shadow_root = ShadowRoot::new(self, &*self.node.owner_doc(), mode);
         self.ensure_rare_data().shadow_root = Some(Dom::from_ref(&*shadow_root));
         shadow_root
             .upcast::<Node>()
@@ -1204,18 +1221,7 @@ impl Element {
     }
 
     pub fn xmlSerialize(&self, traversal_scope: XmlTraversalScope) -> Fallible<DOMString> {
-        let mut writer = vec![];
-        match xmlSerialize::serialize(
-            &mut writer,
-            &self.upcast::<Node>(),
-            XmlSerializeOpts {
-                traversal_scope: traversal_scope,
-                ..Default::default()
-            },
-        ) {
-            Ok(()) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
-            Err(_) => panic!("Cannot serialize element"),
-        }
+        serialize_xml(self.upcast(), traversal_scope, true)
     }
 
     pub fn root_element(&self) -> DomRoot<Element> {
@@ -1743,6 +1749,95 @@ impl Element {
         win.scroll_node(node, x, y, behavior);
     }
 
+    // https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view
+    pub fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+    ) {
+        let node = self.upcast::<Node>();
+        let doc = node.owner_doc();
+        let win = match doc.GetDefaultView() {
+            None => return,
+            Some(win) => win,
+        };
+
+        // TODO: Layout does not create scrolling boxes for elements yet, so
+        // the viewport is the only ancestor scrolling box we scroll.
+        let rect = match node.bounding_content_box() {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        // Step 1.1: The element's bounding border box, outset by its scroll-margin.
+        let (margin_top, margin_right, margin_bottom, margin_left) = match self.style() {
+            Some(style) => {
+                let margin = style.get_margin();
+                (
+                    margin.scroll_margin_top.px(),
+                    margin.scroll_margin_right.px(),
+                    margin.scroll_margin_bottom.px(),
+                    margin.scroll_margin_left.px(),
+                )
+            },
+            None => (0., 0., 0., 0.),
+        };
+        let element_top = rect.origin.y.to_f32_px() - margin_top;
+        let element_bottom = rect.max_y().to_f32_px() + margin_bottom;
+        let element_left = rect.origin.x.to_f32_px() - margin_left;
+        let element_right = rect.max_x().to_f32_px() + margin_right;
+
+        // Step 1.2: The scrollport of the viewport, deflated by the scroll-padding
+        // of the root element.
+        let width = win.InnerWidth() as f32;
+        let height = win.InnerHeight() as f32;
+        let (padding_top, padding_right, padding_bottom, padding_left) =
+            match doc.GetDocumentElement().and_then(|root| root.style()) {
+                Some(style) => {
+                    let padding = style.get_padding();
+                    let resolve = |value: &computed::NonNegativeLengthPercentageOrAuto,
+                                   basis: f32| {
+                        value
+                            .to_used_value(Au::from_f32_px(basis))
+                            .map_or(0., |used| used.to_f32_px())
+                    };
+                    (
+                        resolve(&padding.scroll_padding_top, height),
+                        resolve(&padding.scroll_padding_right, width),
+                        resolve(&padding.scroll_padding_bottom, height),
+                        resolve(&padding.scroll_padding_left, width),
+                    )
+                },
+                None => (0., 0., 0., 0.),
+            };
+
+        // Steps 1.3-1.12
+        // FIXME: This assumes a horizontal writing mode with left-to-right
+        // direction, so that the block axis is vertical.
+        let y = scroll_position_for_alignment(
+            block,
+            win.ScrollY() as f32,
+            height,
+            padding_top,
+            padding_bottom,
+            element_top,
+            element_bottom,
+        );
+        let x = scroll_position_for_alignment(
+            inline,
+            win.ScrollX() as f32,
+            width,
+            padding_left,
+            padding_right,
+            element_left,
+            element_right,
+        );
+
+        // Steps 1.13-1.14
+        win.scroll(x as f64, y as f64, behavior);
+    }
+
     // https://w3c.github.io/DOM-Parsing/#parsing
     pub fn parse_fragment(&self, markup: DOMString) -> Fallible<DomRoot<DocumentFragment>> {
         // Steps 1-2.
@@ -1849,6 +1944,21 @@ impl ElementMethods for Element {
             .or_init(|| DOMTokenList::new(self, &local_name!("class")))
     }
 
+    // https://dom.spec.whatwg.org/#dom-element-slot
+    fn Slot(&self) -> DOMString {
+        self.get_string_attribute(&local_name!("slot"))
+    }
+
+    // https://dom.spec.whatwg.org/#dom-element-slot
+    fn SetSlot(&self, slot: DOMString) {
+        self.set_string_attribute(&local_name!("slot"), slot);
+    }
+
+    // https://dom.spec.whatwg.org/#dom-slotable-assignedslot
+    fn GetAssignedSlot(&self) -> Option<DomRoot<HTMLSlotElement>> {
+        find_a_slot(self.upcast(), true)
+    }
+
     // https://dom.spec.whatwg.org/#dom-element-attributes
     fn Attributes(&self) -> DomRoot<NamedNodeMap> {
         self.attr_list
@@ -2126,6 +2236,34 @@ impl ElementMethods for Element {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions) {
+        // Steps 1-3
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::Boolean(true) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::Start,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::Boolean(false) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::End,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+        };
+
+        // Step 4
+        if !self.has_css_layout_box() {
+            return;
+        }
+
+        // Step 5
+        self.scroll_into_view(behavior, block, inline);
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions) {
         // Step 1
@@ -2660,11 +2798,82 @@ impl ElementMethods for Element {
         doc.enter_fullscreen(self)
     }
 
+    // https://w3c.github.io/pointerlock/#dom-element-requestpointerlock
+    fn RequestPointerLock(&self) {
+        document_from_node(self).request_pointer_lock(self)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-setpointercapture
+    fn SetPointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        let doc = document_from_node(self);
+        doc.set_pointer_capture(self, pointer_id)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-releasepointercapture
+    fn ReleasePointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        let doc = document_from_node(self);
+        doc.release_pointer_capture(self, pointer_id)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-haspointercapture
+    fn HasPointerCapture(&self, pointer_id: i32) -> bool {
+        let doc = document_from_node(self);
+        doc.has_pointer_capture(self, pointer_id)
+    }
+
     // XXX Hidden under dom.shadowdom.enabled pref. Only exposed to be able
     //     to test partial Shadow DOM support for UA widgets.
     // https://dom.spec.whatwg.org/#dom-element-attachshadow
-    fn AttachShadow(&self) -> Fallible<DomRoot<ShadowRoot>> {
-        self.attach_shadow(IsUserAgentWidget::No)
+    fn AttachShadow(&self, init: &ShadowRootInit) -> Fallible<DomRoot<ShadowRoot>> {
+        self.attach_shadow(IsUserAgentWidget::No, init.mode)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-element-shadowroot
+    fn GetShadowRoot(&self) -> Option<DomRoot<ShadowRoot>> {
+        self.shadow_root()
+            .filter(|shadow_root| shadow_root.Mode() == ShadowRootMode::Open)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animatable-animate
+    fn Animate(
+        &self,
+        cx: JSContext,
+        keyframes: *mut JSObject,
+        options: UnrestrictedDoubleOrKeyframeAnimationOptions,
+    ) -> Fallible<DomRoot<Animation>> {
+        let (timing, id) = match options {
+            UnrestrictedDoubleOrKeyframeAnimationOptions::UnrestrictedDouble(duration) => {
+                (Timing::from_duration(duration)?, DOMString::new())
+            },
+            UnrestrictedDoubleOrKeyframeAnimationOptions::KeyframeAnimationOptions(ref options) => {
+                (
+                    Timing::from_dictionary(&options.parent.parent)?,
+                    options.id.clone(),
+                )
+            },
+        };
+        let window = window_from_node(self);
+        let effect = KeyframeEffect::create(cx, &window, Some(self), keyframes, timing)?;
+        let timeline = document_from_node(self).timeline();
+        let animation = Animation::new(&window, Some(effect.upcast()), Some(timeline.upcast()));
+        animation.SetId(id);
+        animation.Play()?;
+        Ok(animation)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animatable-getanimations
+    fn GetAnimations(&self, options: &GetAnimationsOptions) -> Vec<DomRoot<Animation>> {
+        document_from_node(self)
+            .animations()
+            .into_iter()
+            .filter(|animation| match animation.target() {
+                Some(ref target) if options.subtree => self
+                    .upcast::<Node>()
+                    .is_shadow_including_inclusive_ancestor_of(target.upcast()),
+                Some(ref target) => &**target == self,
+                None => false,
+            })
+            .collect()
     }
 }
 
@@ -2704,7 +2913,7 @@ impl VirtualMethods for Element {
                             _ => false,
                         };
 
-                        let block = if is_declaration {
+                        if is_declaration {
                             let mut value = AttrValue::String(String::new());
                             attr.swap_value(&mut value);
                             let (serialization, block) = match value {
@@ -2713,18 +2922,27 @@ impl VirtualMethods for Element {
                             };
                             let mut value = AttrValue::String(serialization);
                             attr.swap_value(&mut value);
-                            block
+                            Some(block)
+                        } else if doc.should_elements_inline_type_behavior_be_blocked(
+                            self,
+                            csp::InlineCheckType::StyleAttribute,
+                            &attr.value(),
+                        ) == csp::CheckResult::Blocked
+                        {
+                            // Style attributes set from markup, as opposed to
+                            // through CSSOM, are subject to the style-src directive.
+                            None
                         } else {
                             let win = window_from_node(self);
-                            Arc::new(doc.style_shared_lock().wrap(parse_style_attribute(
-                                &attr.value(),
-                                &doc.base_url(),
-                                win.css_error_reporter(),
-                                doc.quirks_mode(),
+                            Some(Arc::new(doc.style_shared_lock().wrap(
+                                parse_style_attribute(
+                                    &attr.value(),
+                                    &doc.base_url(),
+                                    win.css_error_reporter(),
+                                    doc.quirks_mode(),
+                                ),
                             )))
-                        };
-
-                        Some(block)
+                        }
                     },
                     AttributeMutation::Removed => None,
                 };
@@ -2771,6 +2989,12 @@ impl VirtualMethods for Element {
                     }
                 }
             },
+            &local_name!("slot") if attr.namespace() == &ns!() => {
+                // https://dom.spec.whatwg.org/#slotable-name
+                if !mutation.leaves_value_unchanged(attr) {
+                    slottable_name_changed(self);
+                }
+            },
             _ => {
                 // FIXME(emilio): This is pretty dubious, and should be done in
                 // the relevant super-classes.
@@ -2860,6 +3084,9 @@ impl VirtualMethods for Element {
         if fullscreen.as_deref() == Some(self) {
             doc.exit_fullscreen();
         }
+        if doc.GetPointerLockElement().as_deref() == Some(self) {
+            doc.exit_pointer_lock();
+        }
         if let Some(ref value) = *self.id_attribute.borrow() {
             doc.unregister_named_element(self, value.clone());
         }
@@ -3042,6 +3269,8 @@ impl<'a> SelectorsElement for DomRoot<Element> {
             NonTSPseudoClass::Indeterminate |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
             NonTSPseudoClass::Target => Element::state(self).contains(pseudo_class.state_flag()),
         }
     }
@@ -3177,6 +3406,18 @@ impl Element {
                 let element = self.downcast::<HTMLTextAreaElement>().unwrap();
                 Some(element as &dyn Validatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                HTMLElementTypeId::HTMLFieldSetElement,
+            )) => {
+                let element = self.downcast::<HTMLFieldSetElement>().unwrap();
+                Some(element as &dyn Validatable)
+            },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                HTMLElementTypeId::HTMLOutputElement,
+            )) => {
+                let element = self.downcast::<HTMLOutputElement>().unwrap();
+                Some(element as &dyn Validatable)
+            },
             _ => None,
         };
         element
@@ -3330,7 +3571,11 @@ impl Element {
     }
 
     pub fn set_disabled_state(&self, value: bool) {
-        self.set_state(ElementState::IN_DISABLED_STATE, value)
+        self.set_state(ElementState::IN_DISABLED_STATE, value);
+        // Disabled elements are barred from constraint validation.
+        if let Some(validatable) = self.as_maybe_validatable() {
+            validatable.validity_state().update_pseudo_classes();
+        }
     }
 
     pub fn read_write_state(&self) -> bool {
@@ -3457,6 +3702,15 @@ impl<'a> AttributeMutation<'a> {
             AttributeMutation::Removed => None,
         }
     }
+
+    /// Whether the value of the attribute, treating a missing attribute as the
+    /// empty string, is the same before and after the mutation.
+    pub fn leaves_value_unchanged(&self, attr: &Attr) -> bool {
+        match *self {
+            AttributeMutation::Set(Some(old_value)) => **old_value == **attr.value(),
+            AttributeMutation::Set(None) | AttributeMutation::Removed => attr.value().is_empty(),
+        }
+    }
 }
 
 /// A holder for an element's "tag name", which will be lazily
@@ -3497,6 +3751,19 @@ impl TagName {
     }
 }
 
+/// Fires a fullscreen event at `element`, or at `document` if the element was
+/// removed from it in the meantime.
+///
+/// <https://fullscreen.spec.whatwg.org/#run-the-fullscreen-steps>
+fn fire_fullscreen_event(element: &Element, document: &Document, name: Atom) {
+    let target = if element.is_connected() && *document_from_node(element) == *document {
+        element.upcast::<EventTarget>()
+    } else {
+        document.upcast::<EventTarget>()
+    };
+    target.fire_bubbling_event(name);
+}
+
 pub struct ElementPerformFullscreenEnter {
     element: Trusted<Element>,
     promise: TrustedPromise,
@@ -3526,15 +3793,24 @@ impl TaskOnce for ElementPerformFullscreenEnter {
 
         // Step 7.1
         if self.error || !element.fullscreen_element_ready_check() {
-            document
-                .upcast::<EventTarget>()
-                .fire_event(atom!("fullscreenerror"));
+            fire_fullscreen_event(&element, &document, atom!("fullscreenerror"));
             promise.reject_error(Error::Type(String::from("fullscreen is not connected")));
             return;
         }
 
-        // TODO Step 7.2-4
+        // Steps 7.2-7.4
+        let previous = document.GetFullscreenElement();
+        if previous.as_deref() == Some(&*element) {
+            promise.resolve_native(&());
+            return;
+        }
+
         // Step 7.5
+        // A document only keeps track of its topmost fullscreen element, so the
+        // previous one stops being fullscreen instead of staying in the top layer.
+        if let Some(previous) = previous {
+            previous.set_fullscreen_state(false);
+        }
         element.set_fullscreen_state(true);
         document.set_fullscreen_element(Some(&element));
         document
@@ -3542,9 +3818,7 @@ impl TaskOnce for ElementPerformFullscreenEnter {
             .reflow(ReflowGoal::Full, ReflowReason::ElementStateChanged);
 
         // Step 7.6
-        document
-            .upcast::<EventTarget>()
-            .fire_event(atom!("fullscreenchange"));
+        fire_fullscreen_event(&element, &document, atom!("fullscreenchange"));
 
         // Step 7.7
         promise.resolve_native(&());
@@ -3584,15 +3858,57 @@ impl TaskOnce for ElementPerformFullscreenExit {
         document.set_fullscreen_element(None);
 
         // Step 9.8
-        document
-            .upcast::<EventTarget>()
-            .fire_event(atom!("fullscreenchange"));
+        fire_fullscreen_event(&element, &document, atom!("fullscreenchange"));
 
         // Step 9.10
         self.promise.root().resolve_native(&());
     }
 }
 
+/// Returns the scroll position along one axis of a scrolling box that aligns
+/// the element edges `element_start` and `element_end` as requested, given the
+/// current `scroll_position`, the `size` of the scrollport and its scroll-padding.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+fn scroll_position_for_alignment(
+    alignment: ScrollLogicalPosition,
+    scroll_position: f32,
+    size: f32,
+    padding_start: f32,
+    padding_end: f32,
+    element_start: f32,
+    element_end: f32,
+) -> f32 {
+    let align_start = element_start - padding_start;
+    let align_end = element_end - size + padding_end;
+    match alignment {
+        ScrollLogicalPosition::Start => align_start,
+        ScrollLogicalPosition::End => align_end,
+        ScrollLogicalPosition::Center => (align_start + align_end) / 2.,
+        ScrollLogicalPosition::Nearest => {
+            let scrollport_start = scroll_position + padding_start;
+            let scrollport_end = scroll_position + size - padding_end;
+            let scrollport_size = scrollport_end - scrollport_start;
+            let element_size = element_end - element_start;
+            let before = element_start < scrollport_start;
+            let after = element_end > scrollport_end;
+            if before && after {
+                scroll_position
+            } else if (before && element_size < scrollport_size) ||
+                (after && element_size > scrollport_size)
+            {
+                align_start
+            } else if (before && element_size > scrollport_size) ||
+                (after && element_size < scrollport_size)
+            {
+                align_end
+            } else {
+                scroll_position
+            }
+        },
+    }
+}
+
 pub fn reflect_cross_origin_attribute(element: &Element) -> Option<DOMString> {
     let attr = element.get_attribute(&ns!(), &local_name!("crossorigin"));
 
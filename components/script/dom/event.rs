@@ -19,6 +19,7 @@ use crate::dom::document::Document;
 use crate::dom::eventtarget::{CompiledEventListener, EventTarget, ListenerPhase};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::node::Node;
+use crate::dom::performanceeventtiming::PerformanceEventTiming;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::window::Window;
 use crate::task::TaskOnce;
@@ -37,6 +38,7 @@ pub struct Event {
     type_: DomRefCell<Atom>,
     phase: Cell<EventPhase>,
     canceled: Cell<EventDefault>,
+    in_passive_listener: Cell<bool>,
     stop_propagation: Cell<bool>,
     stop_immediate: Cell<bool>,
     cancelable: Cell<bool>,
@@ -56,6 +58,7 @@ impl Event {
             type_: DomRefCell::new(atom!("")),
             phase: Cell::new(EventPhase::None),
             canceled: Cell::new(EventDefault::Allowed),
+            in_passive_listener: Cell::new(false),
             stop_propagation: Cell::new(false),
             stop_immediate: Cell::new(false),
             cancelable: Cell::new(false),
@@ -170,6 +173,9 @@ impl Event {
             return self.status();
         }
 
+        // https://w3c.github.io/event-timing/#sec-modifications-DOM
+        let event_timing = PerformanceEventTiming::start_measuring(self, target);
+
         // Step 3-4.
         let path = self.construct_event_path(&target);
         rooted_vec!(let event_path <- path.into_iter());
@@ -184,6 +190,10 @@ impl Event {
             }
         }
 
+        if let Some(event_timing) = event_timing {
+            event_timing.finish_measuring(self);
+        }
+
         // Step 10-12.
         self.clear_dispatching_flags();
 
@@ -191,6 +201,11 @@ impl Event {
         self.status()
     }
 
+    /// The time at which the event was created, read from the monotonic clock.
+    pub fn precise_time_ns(&self) -> u64 {
+        self.precise_time_ns
+    }
+
     pub fn status(&self) -> EventStatus {
         if self.DefaultPrevented() {
             EventStatus::Canceled
@@ -280,7 +295,8 @@ impl EventMethods for Event {
 
     // https://dom.spec.whatwg.org/#dom-event-preventdefault
     fn PreventDefault(&self) {
-        if self.cancelable.get() {
+        // https://dom.spec.whatwg.org/#set-the-canceled-flag
+        if self.cancelable.get() && !self.in_passive_listener.get() {
             self.canceled.set(EventDefault::Prevented)
         }
     }
@@ -561,13 +577,13 @@ fn inner_invoke(
     window: Option<&Window>,
     object: &EventTarget,
     event: &Event,
-    listeners: &[CompiledEventListener],
+    listeners: &[(CompiledEventListener, bool)],
 ) -> bool {
     // Step 1.
     let mut found = false;
 
     // Step 2.
-    for listener in listeners {
+    for (listener, passive) in listeners {
         // Steps 2.1 and 2.3-2.4 are not done because `listeners` contain only the
         // relevant ones for this invoke call during the dispatch algorithm.
 
@@ -580,8 +596,11 @@ fn inner_invoke(
         }
 
         // Step 2.6.
+        // Calls to preventDefault() from a passive listener are ignored.
+        event.in_passive_listener.set(*passive);
         let marker = TimelineMarker::start("DOMEvent".to_owned());
         listener.call_or_handle_event(object, event, ExceptionHandling::Report);
+        event.in_passive_listener.set(false);
         if let Some(window) = window {
             window.emit_timeline_marker(marker.end());
         }
@@ -182,10 +182,16 @@ impl EventSourceContext {
                 self.data.push_str(&self.value);
                 self.data.push('\n');
             },
-            "id" => mem::swap(&mut self.last_event_id, &mut self.value),
+            "id" => {
+                if !self.value.contains('\0') {
+                    mem::swap(&mut self.last_event_id, &mut self.value);
+                }
+            },
             "retry" => {
-                if let Ok(time) = u64::from_str(&self.value) {
-                    self.event_source.root().reconnection_time.set(time);
+                if self.value.bytes().all(|b| b.is_ascii_digit()) {
+                    if let Ok(time) = u64::from_str(&self.value) {
+                        self.event_source.root().reconnection_time.set(time);
+                    }
                 }
             },
             _ => (),
@@ -341,6 +347,12 @@ impl FetchResponseListener for EventSourceContext {
                     FetchMetadata::Unfiltered(m) => m,
                     FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
                 };
+                // https://html.spec.whatwg.org/multipage/#sse-processing-model
+                // Only a 200 response announces the connection, network errors
+                // reestablish it, and any other status fails it.
+                if meta.status.as_ref().map(|&(code, _)| code) != Some(200) {
+                    return self.fail_the_connection();
+                }
                 let mime = match meta.content_type {
                     None => return self.fail_the_connection(),
                     Some(ct) => <ContentType as Into<Mime>>::into(ct.into_inner()),
@@ -643,18 +655,20 @@ impl EventSourceTimeoutCallback {
         // Step 5.3
         if !event_source.last_event_id.borrow().is_empty() {
             //TODO(eijebong): Change this once typed header support custom values
-            request.headers.insert(
-                HeaderName::from_static("last-event-id"),
-                HeaderValue::from_str(&String::from(event_source.last_event_id.borrow().clone()))
-                    .unwrap(),
-            );
+            let last_event_id = String::from(event_source.last_event_id.borrow().clone());
+            if let Ok(value) = HeaderValue::from_str(&last_event_id) {
+                request
+                    .headers
+                    .insert(HeaderName::from_static("last-event-id"), value);
+            }
         }
-        // Step 5.4
+        // Step 5.4, the new fetch can be aborted by close() as the first one.
+        let cancel_receiver = event_source.canceller.borrow_mut().initialize();
         global
             .core_resource_thread()
             .send(CoreResourceMsg::Fetch(
                 request,
-                FetchChannels::ResponseMsg(self.action_sender, None),
+                FetchChannels::ResponseMsg(self.action_sender, Some(cancel_receiver)),
             ))
             .unwrap();
     }
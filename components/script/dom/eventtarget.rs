@@ -3,10 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::enter_realm;
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::{CallbackContainer, CallbackFunction, ExceptionHandling};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEventMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ErrorEventBinding::ErrorEventMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
@@ -24,13 +26,14 @@ use crate::dom::bindings::codegen::UnionTypes::EventOrString;
 use crate::dom::bindings::error::{report_pending_exception, Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::node::document_from_node;
+use crate::dom::node::{document_from_node, Node};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
@@ -40,6 +43,7 @@ use js::rust::wrappers::CompileFunction;
 use js::rust::{AutoObjectVectorWrapper, CompileOptionsWrapper};
 use libc::c_char;
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_url::ServoUrl;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::HashMap;
@@ -245,12 +249,30 @@ impl CompiledEventListener {
     }
 }
 
+#[unrooted_must_root_lint::must_root]
 #[derive(Clone, DenyPublicFields, JSTraceable, MallocSizeOf)]
 /// A listener in a collection of event listeners.
 struct EventListenerEntry {
     phase: ListenerPhase,
     listener: EventListenerType,
     once: bool,
+    passive: bool,
+    /// The signal whose abort removes this listener, if it was added with one.
+    signal: Option<Dom<AbortSignal>>,
+}
+
+impl EventListenerEntry {
+    /// Removes the abort algorithm of the signal of a listener that has
+    /// been removed otherwise, so that the signal no longer keeps it alive.
+    fn forget_signal(&self, target: &EventTarget, ty: &Atom) {
+        let listener = match self.listener {
+            EventListenerType::Additive(ref listener) => listener,
+            EventListenerType::Inline(_) => return,
+        };
+        if let Some(ref signal) = self.signal {
+            signal.remove_listener(target, ty, listener, self.phase == ListenerPhase::Capturing);
+        }
+    }
 }
 
 impl std::cmp::PartialEq for EventListenerEntry {
@@ -259,6 +281,7 @@ impl std::cmp::PartialEq for EventListenerEntry {
     }
 }
 
+#[unrooted_must_root_lint::must_root]
 #[derive(JSTraceable, MallocSizeOf)]
 /// A mix of potentially uncompiled and compiled event listeners of the same type.
 struct EventListeners(Vec<EventListenerEntry>);
@@ -300,13 +323,17 @@ impl EventListeners {
         phase: Option<ListenerPhase>,
         owner: &EventTarget,
         ty: &Atom,
-    ) -> Vec<CompiledEventListener> {
+    ) -> Vec<(CompiledEventListener, bool)> {
         self.0
             .iter_mut()
             .filter_map(|entry| {
                 if phase.is_none() || Some(entry.phase) == phase {
                     // Step 1.1-1.8, 2
-                    entry.listener.get_compiled_listener(owner, ty)
+                    let passive = entry.passive;
+                    entry
+                        .listener
+                        .get_compiled_listener(owner, ty)
+                        .map(|listener| (listener, passive))
                 } else {
                     None
                 }
@@ -354,7 +381,7 @@ impl EventTarget {
         &self,
         type_: &Atom,
         specific_phase: Option<ListenerPhase>,
-    ) -> Vec<CompiledEventListener> {
+    ) -> Vec<(CompiledEventListener, bool)> {
         self.handlers
             .borrow_mut()
             .get_mut(type_)
@@ -387,6 +414,7 @@ impl EventTarget {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#event-handler-attributes:event-handlers-11>
+    #[allow(unrooted_must_root)]
     fn set_inline_event_listener(&self, ty: Atom, listener: Option<InlineEventListener>) {
         let mut handlers = self.handlers.borrow_mut();
         let entries = match handlers.entry(ty) {
@@ -410,18 +438,23 @@ impl EventTarget {
                         phase: ListenerPhase::Bubbling,
                         listener: EventListenerType::Inline(listener),
                         once: false,
+                        passive: false,
+                        signal: None,
                     });
                 }
             },
         }
     }
 
+    #[allow(unrooted_must_root)]
     pub fn remove_listener_if_once(&self, ty: &Atom, listener: &Rc<EventListener>) {
         let mut handlers = self.handlers.borrow_mut();
 
         let listener = EventListenerType::Additive(listener.clone());
         for entries in handlers.get_mut(ty) {
-            entries.drain_filter(|e| e.listener == listener && e.once);
+            for removed in entries.drain_filter(|e| e.listener == listener && e.once) {
+                removed.forget_signal(self, ty);
+            }
         }
     }
 
@@ -659,7 +692,33 @@ impl EventTarget {
         event.fire(self);
         event
     }
+    /// <https://dom.spec.whatwg.org/#default-passive-value>
+    fn default_passive_value(&self, ty: &Atom) -> bool {
+        if !pref!(dom.event_listener.passive_by_default) {
+            return false;
+        }
+        match *ty {
+            atom!("touchstart") | atom!("touchmove") | atom!("wheel") | atom!("mousewheel") => {},
+            _ => return false,
+        }
+        if self.is::<Window>() || self.is::<Document>() {
+            return true;
+        }
+        let node = match self.downcast::<Node>() {
+            Some(node) => node,
+            None => return false,
+        };
+        let document = document_from_node(node);
+        document
+            .GetDocumentElement()
+            .map_or(false, |element| element.upcast::<Node>() == node) ||
+            document
+                .GetBody()
+                .map_or(false, |body| body.upcast::<Node>() == node)
+    }
+
     // https://dom.spec.whatwg.org/#dom-eventtarget-addeventlistener
+    #[allow(unrooted_must_root)]
     pub fn add_event_listener(
         &self,
         ty: DOMString,
@@ -670,8 +729,19 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        // A listener added with an aborted signal is never added.
+        if let Some(ref signal) = options.signal {
+            if signal.aborted() {
+                return;
+            }
+        }
+        let ty = Atom::from(ty);
+        let passive = options
+            .passive
+            .unwrap_or_else(|| self.default_passive_value(&ty));
+
         let mut handlers = self.handlers.borrow_mut();
-        let entry = match handlers.entry(Atom::from(ty)) {
+        let entry = match handlers.entry(ty.clone()) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -683,15 +753,32 @@ impl EventTarget {
         };
         let new_entry = EventListenerEntry {
             phase: phase,
-            listener: EventListenerType::Additive(listener),
+            listener: EventListenerType::Additive(listener.clone()),
             once: options.once,
+            passive,
+            signal: options
+                .signal
+                .as_ref()
+                .map(|signal| Dom::from_ref(&**signal)),
         };
-        if !entry.contains(&new_entry) {
-            entry.push(new_entry);
+        if entry.contains(&new_entry) {
+            return;
+        }
+        entry.push(new_entry);
+
+        // Aborting the signal removes the listener.
+        if let Some(signal) = options.signal {
+            signal.add_abort_algorithm(AbortAlgorithm::RemoveEventListener {
+                target: Dom::from_ref(self),
+                ty,
+                listener,
+                capture: options.parent.capture,
+            });
         }
     }
 
     // https://dom.spec.whatwg.org/#dom-eventtarget-removeeventlistener
+    #[allow(unrooted_must_root)]
     pub fn remove_event_listener(
         &self,
         ty: DOMString,
@@ -702,8 +789,9 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        let ty = Atom::from(ty);
         let mut handlers = self.handlers.borrow_mut();
-        let entry = handlers.get_mut(&Atom::from(ty));
+        let entry = handlers.get_mut(&ty);
         for entry in entry {
             let phase = if options.capture {
                 ListenerPhase::Capturing
@@ -714,9 +802,11 @@ impl EventTarget {
                 phase: phase,
                 listener: EventListenerType::Additive(listener.clone()),
                 once: false,
+                passive: false,
+                signal: None,
             };
             if let Some(position) = entry.iter().position(|e| *e == old_entry) {
-                entry.remove(position);
+                entry.remove(position).forget_signal(self, &ty);
             }
         }
     }
@@ -768,7 +858,9 @@ impl From<AddEventListenerOptionsOrBoolean> for AddEventListenerOptions {
             AddEventListenerOptionsOrBoolean::AddEventListenerOptions(options) => options,
             AddEventListenerOptionsOrBoolean::Boolean(capture) => Self {
                 parent: EventListenerOptions { capture },
+                passive: None,
                 once: false,
+                signal: None,
             },
         }
     }
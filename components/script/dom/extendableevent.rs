@@ -2,32 +2,43 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventBinding::{self, EventMethods};
 use crate::dom::bindings::codegen::Bindings::ExtendableEventBinding;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::event::Event;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
+use js::jsapi::JSContext as RawJSContext;
 use js::rust::HandleValue;
 use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
 
 // https://w3c.github.io/ServiceWorker/#extendable-event
 #[dom_struct]
 pub struct ExtendableEvent {
     event: Event,
-    extensions_allowed: bool,
+    /// https://w3c.github.io/ServiceWorker/#extendableevent-extend-lifetime-promises
+    #[ignore_malloc_size_of = "Rc"]
+    extend_lifetime_promises: DomRefCell<Vec<Rc<Promise>>>,
+    /// https://w3c.github.io/ServiceWorker/#extendableevent-pending-promises-count
+    pending_promises_count: Cell<usize>,
 }
 
 impl ExtendableEvent {
     pub fn new_inherited() -> ExtendableEvent {
         ExtendableEvent {
             event: Event::new_inherited(),
-            extensions_allowed: true,
+            extend_lifetime_promises: DomRefCell::new(vec![]),
+            pending_promises_count: Cell::new(0),
         }
     }
     pub fn new(
@@ -62,22 +73,84 @@ impl ExtendableEvent {
     }
 
     // https://w3c.github.io/ServiceWorker/#wait-until-method
-    pub fn WaitUntil(&self, _cx: JSContext, _val: HandleValue) -> ErrorResult {
+    pub fn WaitUntil(&self, cx: JSContext, val: HandleValue) -> ErrorResult {
         // Step 1
-        if !self.extensions_allowed {
+        if !self.IsTrusted() {
             return Err(Error::InvalidState);
         }
         // Step 2
-        // TODO add a extended_promises array to enqueue the `val`
+        let promise = Promise::new_resolved(&self.global(), cx, val)?;
+        self.add_lifetime_promise(&promise)
+    }
+
+    /// https://w3c.github.io/ServiceWorker/#extendableevent-active
+    fn is_active(&self) -> bool {
+        self.event.dispatching() || self.pending_promises_count.get() > 0
+    }
+
+    /// https://w3c.github.io/ServiceWorker/#extendableevent-add-lifetime-promise
+    pub fn add_lifetime_promise(&self, promise: &Promise) -> ErrorResult {
+        // Step 2
+        if !self.is_active() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        self.extend_lifetime_promises
+            .borrow_mut()
+            .push(promise.duplicate());
+        // Step 4
+        self.pending_promises_count
+            .set(self.pending_promises_count.get() + 1);
+        // The worker is kept alive until the promise settles.
+        let global = self.global();
+        if let Some(worker) = global.downcast::<ServiceWorkerGlobalScope>() {
+            worker.extend_lifetime();
+        }
+        // Step 5
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(LifetimePromiseSettled::new(self)),
+            Some(LifetimePromiseSettled::new(self)),
+        );
+        promise.append_native_handler(&handler);
         Ok(())
     }
 
+    fn lifetime_promise_settled(&self) {
+        self.pending_promises_count
+            .set(self.pending_promises_count.get() - 1);
+        if let Some(worker) = self.global().downcast::<ServiceWorkerGlobalScope>() {
+            worker.release_lifetime();
+        }
+    }
+
     // https://dom.spec.whatwg.org/#dom-event-istrusted
     pub fn IsTrusted(&self) -> bool {
         self.event.IsTrusted()
     }
 }
 
+/// Decrements the pending promises count of an event once one of its
+/// extend lifetime promises is fulfilled or rejected.
+#[derive(JSTraceable, MallocSizeOf)]
+struct LifetimePromiseSettled {
+    event: Dom<ExtendableEvent>,
+}
+
+impl LifetimePromiseSettled {
+    fn new(event: &ExtendableEvent) -> Box<dyn Callback> {
+        Box::new(LifetimePromiseSettled {
+            event: Dom::from_ref(event),
+        })
+    }
+}
+
+impl Callback for LifetimePromiseSettled {
+    fn callback(&self, _cx: *mut RawJSContext, _v: HandleValue) {
+        self.event.lifetime_promise_settled();
+    }
+}
+
 impl Default for ExtendableEventBinding::ExtendableEventInit {
     fn default() -> ExtendableEventBinding::ExtendableEventInit {
         ExtendableEventBinding::ExtendableEventInit {
@@ -0,0 +1,292 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding::FetchEventMethods;
+use crate::dom::bindings::codegen::Bindings::HeadersBinding::HeadersMethods;
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::{
+    ResponseMethods, ResponseType as DOMResponseType,
+};
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::extendableevent::ExtendableEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use dom_struct::dom_struct;
+use http::header::HeaderMap;
+use hyper::StatusCode;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::JSContext;
+use js::rust::HandleValue;
+use js::typedarray::ArrayBuffer;
+use net_traits::{CustomResponse, ServiceWorkerResponse};
+use servo_atoms::Atom;
+use std::cell::Cell;
+
+// https://w3c.github.io/ServiceWorker/#fetchevent-interface
+#[dom_struct]
+pub struct FetchEvent {
+    event: ExtendableEvent,
+    request: Dom<Request>,
+    client_id: DOMString,
+    resulting_client_id: DOMString,
+    is_reload: bool,
+    /// https://w3c.github.io/ServiceWorker/#fetchevent-respond-with-entered-flag
+    respond_with_entered: Cell<bool>,
+    /// Where the response to the intercepted request is sent,
+    /// `None` for events that were not dispatched by handle fetch,
+    /// or that have already been responded to.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    response_chan: DomRefCell<Option<IpcSender<ServiceWorkerResponse>>>,
+}
+
+impl FetchEvent {
+    fn new_inherited(
+        request: &Request,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+        is_reload: bool,
+    ) -> FetchEvent {
+        FetchEvent {
+            event: ExtendableEvent::new_inherited(),
+            request: Dom::from_ref(request),
+            client_id,
+            resulting_client_id,
+            is_reload,
+            respond_with_entered: Cell::new(false),
+            response_chan: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        request: &Request,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+        is_reload: bool,
+    ) -> DomRoot<FetchEvent> {
+        let ev = reflect_dom_object(
+            Box::new(FetchEvent::new_inherited(
+                request,
+                client_id,
+                resulting_client_id,
+                is_reload,
+            )),
+            global,
+            FetchEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    pub fn Constructor(
+        worker: &ServiceWorkerGlobalScope,
+        type_: DOMString,
+        init: &FetchEventBinding::FetchEventInit,
+    ) -> Fallible<DomRoot<FetchEvent>> {
+        Ok(FetchEvent::new(
+            worker.upcast(),
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.request,
+            init.clientId.clone(),
+            init.resultingClientId.clone(),
+            init.isReload,
+        ))
+    }
+
+    pub fn set_response_chan(&self, response_chan: IpcSender<ServiceWorkerResponse>) {
+        *self.response_chan.borrow_mut() = Some(response_chan);
+    }
+
+    fn respond(&self, response: ServiceWorkerResponse) {
+        if let Some(response_chan) = self.response_chan.borrow_mut().take() {
+            let _ = response_chan.send(response);
+        }
+    }
+
+    /// https://w3c.github.io/ServiceWorker/#handle-fetch Step 24.3.4-24.3.5
+    pub fn finish_dispatch(&self, status: EventStatus) {
+        // If `respondWith` was called, the response is sent once its promise settles.
+        if self.respond_with_entered.get() {
+            return;
+        }
+        match status {
+            EventStatus::Canceled => self.respond(ServiceWorkerResponse::NetworkError),
+            EventStatus::NotCanceled => self.respond(ServiceWorkerResponse::Fallback),
+        }
+    }
+
+    /// https://w3c.github.io/ServiceWorker/#fetch-event-respondwith Step 8.2
+    fn response_fulfilled(&self, cx: *mut JSContext, value: HandleValue) {
+        let response = match root_from_handlevalue::<Response>(value, cx) {
+            Ok(response) => response,
+            Err(()) => return self.respond(ServiceWorkerResponse::NetworkError),
+        };
+        if response.BodyUsed() || response.Type() == DOMResponseType::Error {
+            return self.respond(ServiceWorkerResponse::NetworkError);
+        }
+        let status = match StatusCode::from_u16(response.Status()) {
+            Ok(status) => status,
+            Err(_) => return self.respond(ServiceWorkerResponse::NetworkError),
+        };
+        let status_text = String::from_utf8_lossy(&response.StatusText()).into_owned();
+        let headers = response.Headers().get_headers_list();
+        // The response is sent once all of its body has been received.
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(Box::new(ResponseBodyRead {
+                event: Dom::from_ref(self),
+                headers,
+                status: (status, status_text),
+            })),
+            Some(Box::new(RespondWithHandler {
+                event: Dom::from_ref(self),
+                fulfilled: false,
+            })),
+        );
+        response.ArrayBuffer().append_native_handler(&handler);
+    }
+
+    /// https://w3c.github.io/ServiceWorker/#fetch-event-respondwith Step 8.1
+    fn response_rejected(&self) {
+        self.respond(ServiceWorkerResponse::NetworkError);
+    }
+}
+
+impl Drop for FetchEvent {
+    /// Handle fetch waits for a response to every event that it dispatches,
+    /// even one whose `respondWith` promise never settles.
+    fn drop(&mut self) {
+        self.respond(ServiceWorkerResponse::NetworkError);
+    }
+}
+
+impl FetchEventMethods for FetchEvent {
+    // https://w3c.github.io/ServiceWorker/#fetch-event-request
+    fn Request(&self) -> DomRoot<Request> {
+        DomRoot::from_ref(&*self.request)
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-clientid
+    fn ClientId(&self) -> DOMString {
+        self.client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-resultingclientid
+    fn ResultingClientId(&self) -> DOMString {
+        self.resulting_client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-isreload
+    fn IsReload(&self) -> bool {
+        self.is_reload
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-respondwith
+    fn RespondWith(&self, r: &Promise) -> ErrorResult {
+        let event = self.upcast::<Event>();
+        // Step 1
+        if !event.dispatching() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2
+        if self.respond_with_entered.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        self.upcast::<ExtendableEvent>().add_lifetime_promise(r)?;
+        // Step 4-5
+        event.StopPropagation();
+        event.StopImmediatePropagation();
+        // Step 6
+        self.respond_with_entered.set(true);
+        // Step 8
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(Box::new(RespondWithHandler {
+                event: Dom::from_ref(self),
+                fulfilled: true,
+            })),
+            Some(Box::new(RespondWithHandler {
+                event: Dom::from_ref(self),
+                fulfilled: false,
+            })),
+        );
+        r.append_native_handler(&handler);
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct RespondWithHandler {
+    event: Dom<FetchEvent>,
+    fulfilled: bool,
+}
+
+impl Callback for RespondWithHandler {
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        if self.fulfilled {
+            self.event.response_fulfilled(cx, v);
+        } else {
+            self.event.response_rejected();
+        }
+    }
+}
+
+/// Sends the response given to `respondWith`, once its body has been read.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ResponseBodyRead {
+    event: Dom<FetchEvent>,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    headers: HeaderMap,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    status: (StatusCode, String),
+}
+
+impl Callback for ResponseBodyRead {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        let body = if v.is_object() {
+            unsafe {
+                typedarray!(in(cx) let buffer: ArrayBuffer = v.to_object());
+                buffer.map(|buffer| buffer.to_vec())
+            }
+        } else {
+            Err(())
+        };
+        let response = match body {
+            Ok(body) => ServiceWorkerResponse::Custom(CustomResponse::new(
+                self.headers.clone(),
+                self.status.clone(),
+                body,
+            )),
+            Err(()) => ServiceWorkerResponse::NetworkError,
+        };
+        self.event.respond(response);
+    }
+}
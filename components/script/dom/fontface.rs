@@ -0,0 +1,496 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding::{
+    self, FontFaceDescriptors, FontFaceLoadStatus, FontFaceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrArrayBufferOrArrayBufferView;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::fontfaceset::FontFaceSet;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::{TaskSource, TaskSourceName};
+use cssparser::{Parser, ParserInput, SourceLocation, UnicodeRange};
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_layout_interface::message::{Msg, WebFontSource};
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::rc::Rc;
+use style::context::QuirksMode;
+use style::font_face::{FontDisplay, FontFaceRuleData, FontStretchRange, FontStyle};
+use style::font_face::{FontWeightRange, Source};
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::{CssRuleType, Origin};
+use style::values::computed::font::FamilyName;
+use style::values::specified::font::{
+    SpecifiedFontFeatureSettings, SpecifiedFontVariationSettings,
+};
+use style_traits::{ParseError, ParsingMode, ToCss};
+
+/// Parses `value` with the grammar of a `@font-face` descriptor, resolving
+/// urls against `url`.
+fn parse_descriptor<T, F>(value: &str, url: &ServoUrl, parse: F) -> Option<T>
+where
+    F: for<'i, 't> FnOnce(&ParserContext, &mut Parser<'i, 't>) -> Result<T, ParseError<'i>>,
+{
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+    let context = ParserContext::new(
+        Origin::Author,
+        url,
+        Some(CssRuleType::FontFace),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+        None,
+    );
+    parser.parse_entirely(|input| parse(&context, input)).ok()
+}
+
+/// Parses `value` as a `T`, and returns its serialization.
+fn normalize<T: Parse + ToCss>(value: &str) -> Option<DOMString> {
+    let url = ServoUrl::parse("about:blank").unwrap();
+    parse_descriptor(value, &url, T::parse).map(|value| DOMString::from(value.to_css_string()))
+}
+
+/// <https://drafts.csswg.org/css-fonts/#unicode-range-desc>
+fn normalize_unicode_range(value: &str) -> Option<DOMString> {
+    let url = ServoUrl::parse("about:blank").unwrap();
+    let ranges = parse_descriptor(value, &url, |_, input| {
+        input.parse_comma_separated(|input| UnicodeRange::parse(input).map_err(|e| e.into()))
+    })?;
+    let ranges: Vec<String> = ranges.iter().map(cssparser::ToCss::to_css_string).collect();
+    Some(DOMString::from(ranges.join(", ")))
+}
+
+/// <https://drafts.csswg.org/css-fonts/#font-family-desc>
+fn parse_family(value: &str) -> Option<FamilyName> {
+    let url = ServoUrl::parse("about:blank").unwrap();
+    parse_descriptor(value, &url, FamilyName::parse)
+}
+
+/// <https://drafts.csswg.org/css-fonts/#src-desc>
+fn parse_src(value: &str, base_url: &ServoUrl) -> Option<Vec<Source>> {
+    parse_descriptor(value, base_url, |context, input| {
+        input.parse_comma_separated(|input| Source::parse(context, input))
+    })
+}
+
+/// The descriptors a `FontFace` exposes as attributes, besides its family.
+#[derive(Clone, Copy)]
+enum Descriptor {
+    Style,
+    Weight,
+    Stretch,
+    UnicodeRange,
+    Variant,
+    FeatureSettings,
+    VariationSettings,
+    Display,
+}
+
+/// Parses the value of a descriptor and returns its serialization, or `None`
+/// if it doesn't match the descriptor's grammar.
+fn normalize_descriptor(descriptor: Descriptor, value: &str) -> Option<DOMString> {
+    match descriptor {
+        Descriptor::Style => normalize::<FontStyle>(value),
+        Descriptor::Weight => normalize::<FontWeightRange>(value),
+        Descriptor::Stretch => normalize::<FontStretchRange>(value),
+        Descriptor::UnicodeRange => normalize_unicode_range(value),
+        // TODO: Servo doesn't support the font-variant descriptor, so its
+        // value isn't validated.
+        Descriptor::Variant => Some(DOMString::from(value)),
+        Descriptor::FeatureSettings => normalize::<SpecifiedFontFeatureSettings>(value),
+        Descriptor::VariationSettings => normalize::<SpecifiedFontVariationSettings>(value),
+        Descriptor::Display => normalize::<FontDisplay>(value),
+    }
+}
+
+/// Where the data of a font face comes from.
+#[derive(JSTraceable, MallocSizeOf)]
+enum FontFaceSource {
+    /// A list of sources with the grammar of the `src` descriptor, and the
+    /// url to resolve them against.
+    Urls(DOMString, ServoUrl),
+    /// Font data given to the constructor, which is taken once it is sent to
+    /// the font cache.
+    Data(Option<Vec<u8>>),
+}
+
+// https://drafts.csswg.org/css-font-loading/#fontface-interface
+#[dom_struct]
+pub struct FontFace {
+    reflector_: Reflector,
+    family: DomRefCell<DOMString>,
+    style: DomRefCell<DOMString>,
+    weight: DomRefCell<DOMString>,
+    stretch: DomRefCell<DOMString>,
+    unicode_range: DomRefCell<DOMString>,
+    variant: DomRefCell<DOMString>,
+    feature_settings: DomRefCell<DOMString>,
+    variation_settings: DomRefCell<DOMString>,
+    display: DomRefCell<DOMString>,
+    status: Cell<FontFaceLoadStatus>,
+    #[ignore_malloc_size_of = "Rc"]
+    loaded: Rc<Promise>,
+    source: DomRefCell<FontFaceSource>,
+    /// The `FontFaceSet`s this font face was added to, which are notified
+    /// when it starts and finishes loading.
+    sets: DomRefCell<Vec<Dom<FontFaceSet>>>,
+}
+
+impl FontFace {
+    fn new_inherited(
+        global: &GlobalScope,
+        family: DOMString,
+        source: FontFaceSource,
+        descriptors: &FontFaceDescriptors,
+    ) -> FontFace {
+        FontFace {
+            reflector_: Reflector::new(),
+            family: DomRefCell::new(family),
+            style: DomRefCell::new(descriptors.style.clone()),
+            weight: DomRefCell::new(descriptors.weight.clone()),
+            stretch: DomRefCell::new(descriptors.stretch.clone()),
+            unicode_range: DomRefCell::new(descriptors.unicodeRange.clone()),
+            variant: DomRefCell::new(descriptors.variant.clone()),
+            feature_settings: DomRefCell::new(descriptors.featureSettings.clone()),
+            variation_settings: DomRefCell::new(descriptors.variationSettings.clone()),
+            display: DomRefCell::new(descriptors.display.clone()),
+            status: Cell::new(FontFaceLoadStatus::Unloaded),
+            loaded: Promise::new(global),
+            source: DomRefCell::new(source),
+            sets: DomRefCell::new(vec![]),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        family: DOMString,
+        source: FontFaceSource,
+        descriptors: &FontFaceDescriptors,
+    ) -> DomRoot<FontFace> {
+        let font_face = reflect_dom_object(
+            Box::new(FontFace::new_inherited(
+                window.upcast(),
+                family,
+                source,
+                descriptors,
+            )),
+            window,
+            FontFaceBinding::Wrap,
+        );
+        font_face.normalize();
+        font_face
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-fontface
+    #[allow(unsafe_code)]
+    pub fn Constructor(
+        window: &Window,
+        family: DOMString,
+        source: StringOrArrayBufferOrArrayBufferView,
+        descriptors: &FontFaceDescriptors,
+    ) -> Fallible<DomRoot<FontFace>> {
+        let source = match source {
+            StringOrArrayBufferOrArrayBufferView::String(src) => {
+                FontFaceSource::Urls(src, window.Document().base_url())
+            },
+            StringOrArrayBufferOrArrayBufferView::ArrayBuffer(mut buffer) => unsafe {
+                FontFaceSource::Data(Some(buffer.as_slice().to_vec()))
+            },
+            StringOrArrayBufferOrArrayBufferView::ArrayBufferView(mut view) => unsafe {
+                FontFaceSource::Data(Some(view.as_slice().to_vec()))
+            },
+        };
+        let font_face = FontFace::new(window, family, source, descriptors);
+        // Binary data is loaded right away, the url sources only when
+        // `load()` is called.
+        if font_face.status.get() == FontFaceLoadStatus::Unloaded {
+            if let FontFaceSource::Data(_) = *font_face.source.borrow() {
+                font_face.start_loading();
+            }
+        }
+        Ok(font_face)
+    }
+
+    /// Validates the family, the source and the descriptors given to the
+    /// constructor, and puts the font face in the error state if any of them
+    /// is invalid.
+    fn normalize(&self) {
+        let valid = self.normalize_family() &&
+            self.has_valid_source() &&
+            [
+                (&self.style, Descriptor::Style),
+                (&self.weight, Descriptor::Weight),
+                (&self.stretch, Descriptor::Stretch),
+                (&self.unicode_range, Descriptor::UnicodeRange),
+                (&self.variant, Descriptor::Variant),
+                (&self.feature_settings, Descriptor::FeatureSettings),
+                (&self.variation_settings, Descriptor::VariationSettings),
+                (&self.display, Descriptor::Display),
+            ]
+            .iter()
+            .all(|&(field, descriptor)| {
+                let value = normalize_descriptor(descriptor, &field.borrow());
+                match value {
+                    Some(value) => {
+                        *field.borrow_mut() = value;
+                        true
+                    },
+                    None => false,
+                }
+            });
+        if !valid {
+            self.status.set(FontFaceLoadStatus::Error);
+            self.loaded.reject_error(Error::Syntax);
+        }
+    }
+
+    fn normalize_family(&self) -> bool {
+        let family = match parse_family(&self.family.borrow()) {
+            Some(family) => family,
+            None => return false,
+        };
+        *self.family.borrow_mut() = DOMString::from(family.to_css_string());
+        true
+    }
+
+    fn has_valid_source(&self) -> bool {
+        match *self.source.borrow() {
+            FontFaceSource::Urls(ref src, ref base_url) => parse_src(src, base_url).is_some(),
+            FontFaceSource::Data(_) => true,
+        }
+    }
+
+    /// The sources to send to the font cache, or `None` if the string given
+    /// to the constructor doesn't match the grammar of the `src` descriptor.
+    fn sources(&self) -> Option<WebFontSource> {
+        match *self.source.borrow_mut() {
+            FontFaceSource::Urls(ref src, ref base_url) => {
+                let sources = parse_src(src, base_url)?;
+                let mut rule = FontFaceRuleData::empty(SourceLocation { line: 0, column: 0 });
+                rule.family = self.family_name();
+                rule.sources = Some(sources);
+                let font_face = rule.font_face()?;
+                Some(WebFontSource::Sources(font_face.effective_sources()))
+            },
+            FontFaceSource::Data(ref mut data) => {
+                Some(WebFontSource::Data(data.take().unwrap_or_default()))
+            },
+        }
+    }
+
+    /// The family name the font face is registered under.
+    pub fn family_name(&self) -> Option<FamilyName> {
+        parse_family(&self.family.borrow())
+    }
+
+    pub fn status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    pub fn add_to_set(&self, set: &FontFaceSet) {
+        self.sets.borrow_mut().push(Dom::from_ref(set));
+    }
+
+    pub fn remove_from_set(&self, set: &FontFaceSet) {
+        self.sets.borrow_mut().retain(|other| &**other != set);
+    }
+
+    fn sets(&self) -> Vec<DomRoot<FontFaceSet>> {
+        self.sets
+            .borrow()
+            .iter()
+            .map(|set| DomRoot::from_ref(&**set))
+            .collect()
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#font-face-load>
+    fn start_loading(&self) {
+        let family = self.family_name();
+        let source = self.sources();
+        let (family, source) = match (family, source) {
+            (Some(family), Some(source)) => (family, source),
+            _ => return,
+        };
+        self.status.set(FontFaceLoadStatus::Loading);
+        for set in self.sets() {
+            set.face_started_loading(self);
+        }
+
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let this = Trusted::new(self);
+        let task_source = global.networking_task_source();
+        let canceller = global.task_canceller(TaskSourceName::Networking);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let loaded = message.to().unwrap_or(false);
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(finish_loading_font_face: move || {
+                        this.root().finish_loading(loaded);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let _ = global
+            .as_window()
+            .layout_chan()
+            .send(Msg::LoadWebFont(family, source, sender));
+    }
+
+    fn finish_loading(&self, loaded: bool) {
+        if loaded {
+            self.status.set(FontFaceLoadStatus::Loaded);
+            self.loaded.resolve_native(&DomRoot::from_ref(self));
+        } else {
+            self.status.set(FontFaceLoadStatus::Error);
+            self.loaded.reject_error(Error::Network);
+        }
+        for set in self.sets() {
+            set.face_finished_loading(self);
+        }
+    }
+
+    fn set_descriptor(
+        &self,
+        field: &DomRefCell<DOMString>,
+        descriptor: Descriptor,
+        value: DOMString,
+    ) -> ErrorResult {
+        let value = normalize_descriptor(descriptor, &value).ok_or(Error::Syntax)?;
+        *field.borrow_mut() = value;
+        Ok(())
+    }
+}
+
+impl FontFaceMethods for FontFace {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn Family(&self) -> DOMString {
+        self.family.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn SetFamily(&self, value: DOMString) -> ErrorResult {
+        let family = parse_family(&value).ok_or(Error::Syntax)?;
+        *self.family.borrow_mut() = DOMString::from(family.to_css_string());
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn Style(&self) -> DOMString {
+        self.style.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn SetStyle(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.style, Descriptor::Style, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn Weight(&self) -> DOMString {
+        self.weight.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn SetWeight(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.weight, Descriptor::Weight, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn Stretch(&self) -> DOMString {
+        self.stretch.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn SetStretch(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.stretch, Descriptor::Stretch, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn UnicodeRange(&self) -> DOMString {
+        self.unicode_range.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn SetUnicodeRange(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.unicode_range, Descriptor::UnicodeRange, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variant
+    fn Variant(&self) -> DOMString {
+        self.variant.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variant
+    fn SetVariant(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.variant, Descriptor::Variant, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-featuresettings
+    fn FeatureSettings(&self) -> DOMString {
+        self.feature_settings.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-featuresettings
+    fn SetFeatureSettings(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.feature_settings, Descriptor::FeatureSettings, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variationsettings
+    fn VariationSettings(&self) -> DOMString {
+        self.variation_settings.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variationsettings
+    fn SetVariationSettings(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(
+            &self.variation_settings,
+            Descriptor::VariationSettings,
+            value,
+        )
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn Display(&self) -> DOMString {
+        self.display.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn SetDisplay(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor(&self.display, Descriptor::Display, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-status
+    fn Status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-load
+    fn Load(&self) -> Rc<Promise> {
+        if self.status.get() == FontFaceLoadStatus::Unloaded {
+            self.start_loading();
+        }
+        self.loaded.clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-loaded
+    fn Loaded(&self) -> Rc<Promise> {
+        self.loaded.clone()
+    }
+}
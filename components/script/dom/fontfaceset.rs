@@ -0,0 +1,331 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding::{
+    FontFaceLoadStatus, FontFaceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::FontFaceSetBinding::{
+    self, FontFaceSetLoadStatus, FontFaceSetMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::fontface::FontFace;
+use crate::dom::fontfacesetloadevent::FontFaceSetLoadEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::mem;
+use std::rc::Rc;
+use style::properties::{
+    parse_one_declaration_into, PropertyDeclaration, PropertyId, ShorthandId,
+    SourcePropertyDeclaration,
+};
+use style::values::computed::font::SingleFontFamily;
+use style::values::specified::font::FontFamily;
+use style_traits::ParsingMode;
+
+/// A call to `load()` that waits for its font faces to load.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PendingLoad {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    faces: Vec<Dom<FontFace>>,
+}
+
+// https://drafts.csswg.org/css-font-loading/#FontFaceSet-interface
+#[dom_struct]
+pub struct FontFaceSet {
+    eventtarget: EventTarget,
+    /// The font faces in the set, in insertion order.
+    faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#loadingfonts>
+    loading_fonts: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#loadedfonts>
+    loaded_fonts: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#failedfonts>
+    failed_fonts: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#fontfaceset-readypromise>
+    #[ignore_malloc_size_of = "Rc"]
+    ready: DomRefCell<Rc<Promise>>,
+    pending_loads: DomRefCell<Vec<PendingLoad>>,
+}
+
+impl FontFaceSet {
+    fn new_inherited(global: &GlobalScope) -> FontFaceSet {
+        FontFaceSet {
+            eventtarget: EventTarget::new_inherited(),
+            faces: DomRefCell::new(vec![]),
+            loading_fonts: DomRefCell::new(vec![]),
+            loaded_fonts: DomRefCell::new(vec![]),
+            failed_fonts: DomRefCell::new(vec![]),
+            ready: DomRefCell::new(Promise::new(global)),
+            pending_loads: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<FontFaceSet> {
+        let set = reflect_dom_object(
+            Box::new(FontFaceSet::new_inherited(global)),
+            global,
+            FontFaceSetBinding::Wrap,
+        );
+        set.ready.borrow().resolve_native(&set);
+        set
+    }
+
+    /// Called when a font face of the set starts loading.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#fontfaceset-pending-on-the-environment>
+    pub fn face_started_loading(&self, face: &FontFace) {
+        let was_empty = self.loading_fonts.borrow().is_empty();
+        self.loading_fonts.borrow_mut().push(Dom::from_ref(face));
+        if !was_empty {
+            return;
+        }
+        // https://drafts.csswg.org/css-font-loading/#switch-the-fontfaceset-to-loading
+        if self.ready.borrow().is_fulfilled() {
+            *self.ready.borrow_mut() = Promise::new(&self.global());
+        }
+        self.queue_load_event(atom!("loading"), vec![]);
+    }
+
+    /// Called when a font face of the set finishes loading, successfully or
+    /// not.
+    pub fn face_finished_loading(&self, face: &FontFace) {
+        let was_loading = self.remove_loading_font(face);
+        if was_loading {
+            if face.status() == FontFaceLoadStatus::Loaded {
+                self.loaded_fonts.borrow_mut().push(Dom::from_ref(face));
+            } else {
+                self.failed_fonts.borrow_mut().push(Dom::from_ref(face));
+            }
+        }
+        self.settle_pending_loads();
+        if was_loading && self.loading_fonts.borrow().is_empty() {
+            self.switch_to_loaded();
+        }
+    }
+
+    fn remove_loading_font(&self, face: &FontFace) -> bool {
+        let mut loading_fonts = self.loading_fonts.borrow_mut();
+        let len = loading_fonts.len();
+        loading_fonts.retain(|other| &**other != face);
+        loading_fonts.len() != len
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#switch-the-fontfaceset-to-loaded>
+    fn switch_to_loaded(&self) {
+        let loaded_fonts = mem::replace(&mut *self.loaded_fonts.borrow_mut(), vec![]);
+        let failed_fonts = mem::replace(&mut *self.failed_fonts.borrow_mut(), vec![]);
+        self.ready.borrow().resolve_native(&DomRoot::from_ref(self));
+        self.queue_load_event(atom!("loadingdone"), loaded_fonts);
+        if !failed_fonts.is_empty() {
+            self.queue_load_event(atom!("loadingerror"), failed_fonts);
+        }
+    }
+
+    /// Queues a `FontFaceSetLoadEvent` at the set.
+    fn queue_load_event(&self, type_: Atom, faces: Vec<Dom<FontFace>>) {
+        let global = self.global();
+        let this = Trusted::new(self);
+        let faces: Vec<_> = faces.iter().map(|face| Trusted::new(&**face)).collect();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(fire_font_face_set_load_event: move || {
+                let set = this.root();
+                let faces: Vec<_> = faces.iter().map(|face| face.root()).collect();
+                let event = FontFaceSetLoadEvent::new(&set.global(), type_, false, false, &faces);
+                event.upcast::<Event>().fire(set.upcast());
+            }),
+            &global,
+        );
+    }
+
+    /// Resolves the promises of the `load()` calls whose font faces all
+    /// loaded, and rejects those of which a font face failed to load.
+    fn settle_pending_loads(&self) {
+        let pending_loads = mem::replace(&mut *self.pending_loads.borrow_mut(), vec![]);
+        let mut still_pending = vec![];
+        for load in pending_loads {
+            let statuses: Vec<_> = load.faces.iter().map(|face| face.status()).collect();
+            if statuses.contains(&FontFaceLoadStatus::Error) {
+                load.promise.reject_error(Error::Network);
+            } else if statuses
+                .iter()
+                .all(|status| *status == FontFaceLoadStatus::Loaded)
+            {
+                let faces: Vec<_> = load
+                    .faces
+                    .iter()
+                    .map(|face| DomRoot::from_ref(&**face))
+                    .collect();
+                load.promise.resolve_native(&faces);
+            } else {
+                still_pending.push(load);
+            }
+        }
+        self.pending_loads.borrow_mut().extend(still_pending);
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#find-the-matching-font-faces>
+    fn matching_font_faces(&self, font: &str) -> Fallible<Vec<DomRoot<FontFace>>> {
+        let window = self.global();
+        let window = window.as_window();
+        let document = window.Document();
+        let mut declarations = SourcePropertyDeclaration::new();
+        parse_one_declaration_into(
+            &mut declarations,
+            PropertyId::Shorthand(ShorthandId::Font),
+            font,
+            &document.url(),
+            window.css_error_reporter(),
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+        )
+        .map_err(|_| Error::Syntax)?;
+        let mut families = vec![];
+        for declaration in declarations.drain() {
+            if let PropertyDeclaration::FontFamily(FontFamily::Values(ref list)) = declaration {
+                families.extend(list.iter().filter_map(|family| match *family {
+                    SingleFontFamily::FamilyName(ref name) => Some(name.name.clone()),
+                    SingleFontFamily::Generic(_) => None,
+                }));
+            }
+        }
+        Ok(self
+            .faces
+            .borrow()
+            .iter()
+            .filter(|face| {
+                face.family_name().map_or(false, |name| {
+                    families
+                        .iter()
+                        .any(|family| family.eq_ignore_ascii_case(&name.name))
+                })
+            })
+            .map(|face| DomRoot::from_ref(&**face))
+            .collect())
+    }
+
+    fn has(&self, face: &FontFace) -> bool {
+        self.faces.borrow().iter().any(|other| &**other == face)
+    }
+}
+
+impl FontFaceSetMethods for FontFaceSet {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-size
+    fn Size(&self) -> u32 {
+        self.faces.borrow().len() as u32
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-has
+    fn Has(&self, font: &FontFace) -> bool {
+        self.has(font)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-add
+    fn Add(&self, font: &FontFace) -> DomRoot<FontFaceSet> {
+        if !self.has(font) {
+            self.faces.borrow_mut().push(Dom::from_ref(font));
+            font.add_to_set(self);
+            if font.status() == FontFaceLoadStatus::Loading {
+                self.face_started_loading(font);
+            }
+        }
+        DomRoot::from_ref(self)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-delete
+    fn Delete(&self, font: &FontFace) -> bool {
+        if !self.has(font) {
+            return false;
+        }
+        self.faces.borrow_mut().retain(|other| &**other != font);
+        font.remove_from_set(self);
+        self.loaded_fonts
+            .borrow_mut()
+            .retain(|other| &**other != font);
+        self.failed_fonts
+            .borrow_mut()
+            .retain(|other| &**other != font);
+        if self.remove_loading_font(font) && self.loading_fonts.borrow().is_empty() {
+            self.switch_to_loaded();
+        }
+        true
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-clear
+    fn Clear(&self) {
+        let faces: Vec<_> = self
+            .faces
+            .borrow()
+            .iter()
+            .map(|face| DomRoot::from_ref(&**face))
+            .collect();
+        for face in faces {
+            self.Delete(&face);
+        }
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloading
+    event_handler!(loading, GetOnloading, SetOnloading);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloadingdone
+    event_handler!(loadingdone, GetOnloadingdone, SetOnloadingdone);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloadingerror
+    event_handler!(loadingerror, GetOnloadingerror, SetOnloadingerror);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-load
+    fn Load(&self, font: DOMString, _text: DOMString) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        let faces = match self.matching_font_faces(&font) {
+            Ok(faces) => faces,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        for face in &faces {
+            face.Load();
+        }
+        self.pending_loads.borrow_mut().push(PendingLoad {
+            promise: promise.clone(),
+            faces: faces.iter().map(|face| Dom::from_ref(&**face)).collect(),
+        });
+        self.settle_pending_loads();
+        promise
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-check
+    fn Check(&self, font: DOMString, _text: DOMString) -> Fallible<bool> {
+        let faces = self.matching_font_faces(&font)?;
+        Ok(faces
+            .iter()
+            .all(|face| face.status() == FontFaceLoadStatus::Loaded))
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-status
+    fn Status(&self) -> FontFaceSetLoadStatus {
+        if self.loading_fonts.borrow().is_empty() {
+            FontFaceSetLoadStatus::Loaded
+        } else {
+            FontFaceSetLoadStatus::Loading
+        }
+    }
+}
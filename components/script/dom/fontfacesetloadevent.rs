@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::FontFaceSetLoadEventBinding::{
+    self, FontFaceSetLoadEventInit, FontFaceSetLoadEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::event::Event;
+use crate::dom::fontface::FontFace;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use servo_atoms::Atom;
+
+// https://drafts.csswg.org/css-font-loading/#fontfacesetloadevent
+#[dom_struct]
+pub struct FontFaceSetLoadEvent {
+    event: Event,
+    #[ignore_malloc_size_of = "mozjs"]
+    fontfaces: Heap<JSVal>,
+}
+
+impl FontFaceSetLoadEvent {
+    fn new_inherited() -> FontFaceSetLoadEvent {
+        FontFaceSetLoadEvent {
+            event: Event::new_inherited(),
+            fontfaces: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        fontfaces: &[DomRoot<FontFace>],
+    ) -> DomRoot<FontFaceSetLoadEvent> {
+        let ev = reflect_dom_object(
+            Box::new(FontFaceSetLoadEvent::new_inherited()),
+            global,
+            FontFaceSetLoadEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev.fontfaces
+            .set(to_frozen_array(fontfaces, global.get_cx()));
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &FontFaceSetLoadEventInit,
+    ) -> Fallible<DomRoot<FontFaceSetLoadEvent>> {
+        Ok(FontFaceSetLoadEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.fontfaces,
+        ))
+    }
+}
+
+impl FontFaceSetLoadEventMethods for FontFaceSetLoadEvent {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesetloadevent-fontfaces
+    fn Fontfaces(&self, _cx: JSContext) -> JSVal {
+        self.fontfaces.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -185,10 +185,11 @@ impl FormData {
     // https://xhr.spec.whatwg.org/#create-an-entry
     // Steps 3-4.
     fn create_an_entry(&self, blob: &Blob, opt_filename: Option<USVString>) -> DomRoot<File> {
-        let name = match opt_filename {
-            Some(filename) => DOMString::from(filename.0),
-            None if blob.downcast::<File>().is_none() => DOMString::from("blob"),
-            None => DOMString::from(""),
+        let name = match (opt_filename, blob.downcast::<File>()) {
+            (Some(filename), _) => DOMString::from(filename.0),
+            // A file without a filename is the entry itself.
+            (None, Some(file)) => return DomRoot::from_ref(file),
+            (None, None) => DOMString::from("blob"),
         };
 
         let bytes = blob.get_bytes().unwrap_or(vec![]);
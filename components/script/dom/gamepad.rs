@@ -4,6 +4,8 @@
 
 use crate::dom::bindings::codegen::Bindings::GamepadBinding;
 use crate::dom::bindings::codegen::Bindings::GamepadBinding::GamepadMethods;
+use crate::dom::bindings::codegen::Bindings::GamepadButtonListBinding::GamepadButtonListMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -19,6 +21,7 @@ use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
 use js::jsapi::{Heap, JSObject};
 use js::typedarray::{CreateWith, Float64Array};
+use script_traits::{GamepadIndex, GamepadUpdateType};
 use std::cell::Cell;
 use std::ptr;
 use std::ptr::NonNull;
@@ -40,6 +43,8 @@ pub struct Gamepad {
     #[ignore_malloc_size_of = "Defined in rust-webvr"]
     hand: WebVRGamepadHand,
     display_id: u32,
+    /// The index chosen by the embedder, for the gamepads that are not VR controllers.
+    embedder_index: Option<GamepadIndex>,
 }
 
 impl Gamepad {
@@ -54,6 +59,7 @@ impl Gamepad {
         pose: Option<&VRPose>,
         hand: WebVRGamepadHand,
         display_id: u32,
+        embedder_index: Option<GamepadIndex>,
     ) -> Gamepad {
         Self {
             reflector_: Reflector::new(),
@@ -68,6 +74,7 @@ impl Gamepad {
             pose: pose.map(Dom::from_ref),
             hand: hand,
             display_id: display_id,
+            embedder_index: embedder_index,
         }
     }
 
@@ -93,6 +100,7 @@ impl Gamepad {
                 Some(&pose),
                 data.hand.clone(),
                 data.display_id,
+                None,
             )),
             global,
             GamepadBinding::Wrap,
@@ -107,6 +115,47 @@ impl Gamepad {
 
         gamepad
     }
+
+    /// A gamepad connected to the embedder, whose inputs are laid out as in the standard gamepad,
+    /// <https://w3c.github.io/gamepad/#dfn-a-new-gamepad>
+    #[allow(unsafe_code)]
+    pub fn new_from_embedder(
+        global: &GlobalScope,
+        embedder_index: GamepadIndex,
+        id: String,
+        axes: usize,
+        buttons: usize,
+    ) -> DomRoot<Gamepad> {
+        let buttons = GamepadButtonList::new_with_length(&global, buttons);
+
+        let gamepad = reflect_dom_object(
+            Box::new(Gamepad::new_inherited(
+                0,
+                id,
+                0,
+                true,
+                *global.performance().Now(),
+                "standard".into(),
+                &buttons,
+                None,
+                WebVRGamepadHand::Unknown,
+                0,
+                Some(embedder_index),
+            )),
+            global,
+            GamepadBinding::Wrap,
+        );
+
+        let cx = global.get_cx();
+        rooted!(in (*cx) let mut array = ptr::null_mut::<JSObject>());
+        unsafe {
+            let _ =
+                Float64Array::create(*cx, CreateWith::Slice(&vec![0.0; axes]), array.handle_mut());
+        }
+        gamepad.axes.set(array.get());
+
+        gamepad
+    }
 }
 
 impl GamepadMethods for Gamepad {
@@ -185,10 +234,36 @@ impl Gamepad {
         self.update_connected(state.connected);
     }
 
+    /// <https://w3c.github.io/gamepad/#dfn-update-gamepad-state>
+    #[allow(unsafe_code)]
+    pub fn update_from_embedder(&self, update: GamepadUpdateType) {
+        self.timestamp.set(*self.global().performance().Now());
+        match update {
+            GamepadUpdateType::Axis(index, value) => unsafe {
+                let cx = self.global().get_cx();
+                typedarray!(in(*cx) let axes: Float64Array = self.axes.get());
+                if let Ok(mut array) = axes {
+                    if let Some(axis) = array.as_mut_slice().get_mut(index) {
+                        *axis = value;
+                    }
+                }
+            },
+            GamepadUpdateType::Button(index, value) => {
+                if let Some(button) = self.buttons.Item(index as u32) {
+                    button.update_value(value);
+                }
+            },
+        }
+    }
+
     pub fn gamepad_id(&self) -> u32 {
         self.gamepad_id
     }
 
+    pub fn embedder_index(&self) -> Option<GamepadIndex> {
+        self.embedder_index
+    }
+
     pub fn update_connected(&self, connected: bool) {
         if self.connected.get() == connected {
             return;
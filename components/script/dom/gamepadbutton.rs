@@ -11,6 +11,10 @@ use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use std::cell::Cell;
 
+/// The value above which a button is pressed,
+/// <https://w3c.github.io/gamepad/#dfn-button-press-threshold>
+const BUTTON_PRESS_THRESHOLD: f64 = 0.5;
+
 #[dom_struct]
 pub struct GamepadButton {
     reflector_: Reflector,
@@ -60,4 +64,11 @@ impl GamepadButton {
         self.pressed.set(pressed);
         self.touched.set(touched);
     }
+
+    /// Updates the value of an analog or digital button, between 0.0 and 1.0.
+    pub fn update_value(&self, value: f64) {
+        self.value.set(value);
+        self.pressed.set(value >= BUTTON_PRESS_THRESHOLD);
+        self.touched.set(value > 0.0);
+    }
 }
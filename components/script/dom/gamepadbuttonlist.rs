@@ -41,6 +41,16 @@ impl GamepadButtonList {
         )
     }
 
+    pub fn new_with_length(global: &GlobalScope, length: usize) -> DomRoot<GamepadButtonList> {
+        rooted_vec!(let list <- (0..length).map(|_| GamepadButton::new(&global, false, false)));
+
+        reflect_dom_object(
+            Box::new(GamepadButtonList::new_inherited(list.r())),
+            global,
+            GamepadButtonListBinding::Wrap,
+        )
+    }
+
     pub fn sync_from_vr(&self, vr_buttons: &[WebVRGamepadButton]) {
         for (gp_btn, btn) in self.list.iter().zip(vr_buttons.iter()) {
             gp_btn.update(btn.pressed, btn.touched);
@@ -10,19 +10,22 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::gamepad::Gamepad;
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
+use script_traits::GamepadIndex;
 
 // https://www.w3.org/TR/gamepad/
 #[dom_struct]
 pub struct GamepadList {
     reflector_: Reflector,
-    list: DomRefCell<Vec<Dom<Gamepad>>>,
+    /// The gamepads by index. The index of a disconnected gamepad is null,
+    /// until another gamepad is connected and takes it.
+    list: DomRefCell<Vec<Option<Dom<Gamepad>>>>,
 }
 
 impl GamepadList {
     fn new_inherited(list: &[&Gamepad]) -> GamepadList {
         GamepadList {
             reflector_: Reflector::new(),
-            list: DomRefCell::new(list.iter().map(|g| Dom::from_ref(&**g)).collect()),
+            list: DomRefCell::new(list.iter().map(|g| Some(Dom::from_ref(&**g))).collect()),
         }
     }
 
@@ -40,14 +43,58 @@ impl GamepadList {
                 .list
                 .borrow()
                 .iter()
-                .any(|g| g.gamepad_id() == gamepad.gamepad_id())
+                .flatten()
+                .any(|g| g.embedder_index().is_none() && g.gamepad_id() == gamepad.gamepad_id())
             {
-                self.list.borrow_mut().push(Dom::from_ref(&*gamepad));
-                // Ensure that the gamepad has the correct index
-                gamepad.update_index(self.list.borrow().len() as i32 - 1);
+                self.add(gamepad);
             }
         }
     }
+
+    /// Gives `gamepad` the lowest unused index,
+    /// <https://w3c.github.io/gamepad/#dfn-selecting-an-unused-gamepad-index>
+    pub fn add(&self, gamepad: &Gamepad) {
+        let mut list = self.list.borrow_mut();
+        let index = match list.iter().position(|slot| slot.is_none()) {
+            Some(index) => {
+                list[index] = Some(Dom::from_ref(gamepad));
+                index
+            },
+            None => {
+                list.push(Some(Dom::from_ref(gamepad)));
+                list.len() - 1
+            },
+        };
+        gamepad.update_index(index as i32);
+    }
+
+    pub fn find_embedder_gamepad(&self, index: GamepadIndex) -> Option<DomRoot<Gamepad>> {
+        self.list
+            .borrow()
+            .iter()
+            .flatten()
+            .find(|gamepad| gamepad.embedder_index() == Some(index))
+            .map(|gamepad| DomRoot::from_ref(&**gamepad))
+    }
+
+    /// Removes a disconnected gamepad, leaving its index null so that the
+    /// indices of the other gamepads do not change.
+    pub fn remove_embedder_gamepad(&self, index: GamepadIndex) -> Option<DomRoot<Gamepad>> {
+        let mut list = self.list.borrow_mut();
+        let position = list.iter().position(|slot| {
+            slot.as_ref()
+                .map_or(false, |gamepad| gamepad.embedder_index() == Some(index))
+        })?;
+        let gamepad = list[position]
+            .as_ref()
+            .map(|gamepad| DomRoot::from_ref(&**gamepad));
+        list[position] = None;
+        // Only the indices up to the last connected gamepad are listed.
+        while let Some(None) = list.last() {
+            list.pop();
+        }
+        gamepad
+    }
 }
 
 impl GamepadListMethods for GamepadList {
@@ -61,6 +108,7 @@ impl GamepadListMethods for GamepadList {
         self.list
             .borrow()
             .get(index as usize)
+            .and_then(|slot| slot.as_ref())
             .map(|gamepad| DomRoot::from_ref(&**gamepad))
     }
 
@@ -3,41 +3,58 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSourceBinding::EventSourceMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowOrWorkerGlobalScopeBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use crate::dom::bindings::conversions::{root_from_object, root_from_object_static};
-use crate::dom::bindings::error::{report_pending_exception, ErrorInfo};
+use crate::dom::bindings::error::{report_pending_exception, Error, ErrorInfo, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::settings_stack::{entry_global, incumbent_global, AutoEntryScript};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::weakref::{DOMTracker, WeakRef};
+use crate::dom::broadcastchannel::BroadcastChannel;
 use crate::dom::crypto::Crypto;
-use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
+use crate::dom::dedicatedworkerglobalscope::{
+    DedicatedWorkerGlobalScope, DedicatedWorkerScriptMsg,
+};
+use crate::dom::element::Element;
 use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventsource::EventSource;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::messageport::MessagePort;
+use crate::dom::node::Node;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
+use crate::dom::report::Report as DOMReport;
+use crate::dom::reportingobserver::ReportingObserver;
+use crate::dom::scheduler::Scheduler;
+use crate::dom::sharedworkerglobalscope::SharedWorkerGlobalScope;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
 use crate::microtask::{Microtask, MicrotaskQueue};
+use crate::reporting::{self, DeprecationReport, InterventionReport, Report, ReportBody};
+use crate::script_module::ImportMap;
 use crate::script_runtime::{CommonScriptMsg, JSContext as SafeJSContext, ScriptChan, ScriptPort};
 use crate::script_thread::{MainThreadScriptChan, ScriptThread};
+use crate::security_manager::{CSPViolationReport, CSPViolationReporter};
 use crate::task::TaskCanceller;
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
@@ -45,48 +62,69 @@ use crate::task_source::TaskSource;
 use crate::task_source::TaskSourceName;
 use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle};
 use crate::timers::{OneshotTimers, TimerCallback};
-use content_security_policy::CspList;
+use content_security_policy::{self as csp, CspList};
+use crossbeam_channel::Sender;
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
+use js::conversions::ToJSValConvertible;
 use js::glue::{IsWrapper, UnwrapObjectDynamic};
-use js::jsapi::JSObject;
+use js::jsapi::{CompileUtf8, JSObject, JSScript, SetScriptPrivate};
 use js::jsapi::{CurrentGlobalOrNull, GetNonCCWObjectGlobal};
 use js::jsapi::{HandleObject, Heap};
 use js::jsapi::{JSAutoRealm, JSContext};
-use js::jsval::UndefinedValue;
+use js::jsval::{JSVal, UndefinedValue};
 use js::panic::maybe_resume_unwind;
-use js::rust::wrappers::EvaluateUtf8;
+use js::rust::wrappers::JS_ExecuteScript;
 use js::rust::{get_object_class, CompileOptionsWrapper, ParentRuntime, Runtime};
-use js::rust::{HandleValue, MutableHandleValue};
+use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue, MutableHandleValue};
 use js::{JSCLASS_IS_DOMJSCLASS, JSCLASS_IS_GLOBAL};
-use msg::constellation_msg::{MessagePortId, MessagePortRouterId, PipelineId};
+use msg::constellation_msg::{
+    BroadcastChannelRouterId, MessagePortId, MessagePortRouterId, PipelineId,
+};
+use net_traits::blob_url_store::get_blob_origin;
+use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::image_cache::ImageCache;
-use net_traits::{CoreResourceThread, IpcSend, ResourceThreads};
+use net_traits::{CoreResourceMsg, CoreResourceThread, IpcSend, ReferrerPolicy, ResourceThreads};
 use profile_traits::{mem as profile_mem, time as profile_time};
 use script_traits::transferable::MessagePortImpl;
 use script_traits::{
-    MessagePortMsg, MsDuration, PortMessageTask, ScriptMsg, ScriptToConstellationChan, TimerEvent,
+    BroadcastMsg, MessagePortMsg, MsDuration, PortMessageTask, ScriptMsg,
+    ScriptToConstellationChan, TimerEvent,
 };
 use script_traits::{TimerEventId, TimerSchedulerMsg, TimerSource};
+use servo_config::pref;
 use servo_url::{MutableOrigin, ServoUrl};
 use std::borrow::Cow;
-use std::cell::Cell;
+use std::cell::{Cell, Ref};
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
 use std::ffi::CString;
+use std::ptr;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use time::{get_time, Timespec};
+use uuid::Uuid;
+
+/// The number of reports kept for the `buffered` option of the reporting observers,
+/// <https://w3c.github.io/reporting/#windoworworkerglobalscope-reports>
+const MAX_REPORT_BUFFER_SIZE: usize = 100;
 
 #[derive(JSTraceable)]
-pub struct AutoCloseWorker(Arc<AtomicBool>);
+pub struct AutoCloseWorker {
+    /// https://html.spec.whatwg.org/multipage/#dom-workerglobalscope-closing
+    closing: Arc<AtomicBool>,
+    /// A sender to the event loop of the worker, to wake it up once closing.
+    sender: Sender<DedicatedWorkerScriptMsg>,
+}
 
 impl Drop for AutoCloseWorker {
     fn drop(&mut self) {
-        self.0.store(true, Ordering::SeqCst);
+        self.closing.store(true, Ordering::SeqCst);
+        // The worker may be waiting for its next task.
+        let _ = self.sender.send(DedicatedWorkerScriptMsg::WakeUp);
     }
 }
 
@@ -94,11 +132,26 @@ impl Drop for AutoCloseWorker {
 pub struct GlobalScope {
     eventtarget: EventTarget,
     crypto: MutNullableDom<Crypto>,
+    scheduler: MutNullableDom<Scheduler>,
     next_worker_id: Cell<WorkerId>,
 
     /// The message-port router id for this global, if it is managing ports.
     message_port_state: DomRefCell<MessagePortState>,
 
+    /// The broadcast channels state this global, if it is managing any.
+    broadcast_channel_state: DomRefCell<BroadcastChannelState>,
+
+    /// The ids of the Blob URLs created by this global,
+    /// which are revoked when it is torn down.
+    #[ignore_malloc_size_of = "Defined in uuid"]
+    blob_url_ids: DomRefCell<Vec<Uuid>>,
+
+    /// The MediaSource objects that object URLs created by this global
+    /// refer to, keyed by URL.
+    ///
+    /// <https://w3c.github.io/media-source/#dom-url-createobjecturl>
+    media_source_urls: DomRefCell<HashMap<String, Dom<MediaSource>>>,
+
     /// Pipeline id associated with this global.
     pipeline_id: PipelineId,
 
@@ -180,11 +233,43 @@ pub struct GlobalScope {
     #[ignore_malloc_size_of = "mozjs"]
     consumed_rejections: DomRefCell<Vec<Box<Heap<*mut JSObject>>>>,
 
+    /// The module records of the module scripts fetched by this global,
+    /// keyed by the final URL of their response.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#module-map>
+    #[ignore_malloc_size_of = "mozjs"]
+    module_map: DomRefCell<HashMap<ServoUrl, Box<Heap<*mut JSObject>>>>,
+
+    /// The final URL of the response of the module scripts whose request was redirected,
+    /// keyed by the requested URL, which is what their importers resolve to.
+    module_redirects: DomRefCell<HashMap<ServoUrl, ServoUrl>>,
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-global-import-map>
+    import_map: DomRefCell<ImportMap>,
+
+    /// <https://html.spec.whatwg.org/multipage/#import-maps-allowed>
+    import_maps_allowed: Cell<bool>,
+
     /// True if headless mode.
     is_headless: bool,
 
+    /// <https://html.spec.whatwg.org/multipage/#concept-settings-object-cross-origin-isolated-capability>
+    cross_origin_isolated: Cell<bool>,
+
     /// An optional string allowing the user agent to be set for testing.
     user_agent: Cow<'static, str>,
+
+    /// <https://w3c.github.io/reporting/#reportingobserver-registered-observer-list>
+    reporting_observers: DomRefCell<Vec<Dom<ReportingObserver>>>,
+
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-reports>
+    report_buffer: DomRefCell<VecDeque<Dom<DOMReport>>>,
+
+    /// Whether a task to invoke the reporting observers is already queued.
+    pending_reporting_observers_task: Cell<bool>,
+
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-endpoints>
+    reporting_endpoints: DomRefCell<HashMap<String, ServoUrl>>,
 }
 
 /// A wrapper for glue-code between the ipc router and the event-loop.
@@ -194,6 +279,13 @@ struct MessageListener {
     context: Trusted<GlobalScope>,
 }
 
+/// A wrapper between broadcasts coming in over IPC, and the event-loop.
+struct BroadcastListener {
+    canceller: TaskCanceller,
+    task_source: DOMManipulationTaskSource,
+    context: Trusted<GlobalScope>,
+}
+
 /// A wrapper between timer events coming in over IPC, and the event-loop.
 struct TimerListener {
     canceller: TaskCanceller,
@@ -225,6 +317,41 @@ pub enum MessagePortState {
     UnManaged,
 }
 
+/// State representing whether this global is currently managing broadcast channels.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum BroadcastChannelState {
+    /// The broadcast-channel router id for this global, and a map of channel-name
+    /// to the queue of managed channels of that name, in creation order.
+    Managed(
+        BroadcastChannelRouterId,
+        HashMap<DOMString, VecDeque<Dom<BroadcastChannel>>>,
+    ),
+    /// This global is not managing any broadcast channels at this time.
+    UnManaged,
+}
+
+impl BroadcastListener {
+    /// Handle a broadcast coming in over IPC,
+    /// by queueing the appropriate task on the relevant event-loop.
+    fn handle(&self, event: BroadcastMsg) {
+        let context = self.context.clone();
+
+        // Note: strictly speaking we should just queue the message event tasks,
+        // not queue a task that then queues more tasks.
+        // This however seems to be hard to avoid in the light of the IPC.
+        let _ = self.task_source.queue_with_canceller(
+            task!(broadcast_message_event: move || {
+                let global = context.root();
+                // Step 10 of https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage,
+                // For each BroadcastChannel object destination in destinations, queue a task.
+                global.broadcast_message_event(event, None);
+            }),
+            &self.canceller,
+        );
+    }
+}
+
 impl TimerListener {
     /// Handle a timer-event coming-in over IPC,
     /// by queuing the appropriate task on the relevant event-loop.
@@ -345,8 +472,12 @@ impl GlobalScope {
     ) -> Self {
         Self {
             message_port_state: DomRefCell::new(MessagePortState::UnManaged),
+            broadcast_channel_state: DomRefCell::new(BroadcastChannelState::UnManaged),
+            blob_url_ids: DomRefCell::new(vec![]),
+            media_source_urls: Default::default(),
             eventtarget: EventTarget::new_inherited(),
             crypto: Default::default(),
+            scheduler: Default::default(),
             next_worker_id: Cell::new(WorkerId(0)),
             pipeline_id,
             devtools_wants_updates: Default::default(),
@@ -366,8 +497,17 @@ impl GlobalScope {
             event_source_tracker: DOMTracker::new(),
             uncaught_rejections: Default::default(),
             consumed_rejections: Default::default(),
+            module_map: Default::default(),
+            module_redirects: Default::default(),
+            import_map: Default::default(),
+            import_maps_allowed: Cell::new(true),
             is_headless,
+            cross_origin_isolated: Default::default(),
             user_agent,
+            reporting_observers: Default::default(),
+            report_buffer: Default::default(),
+            pending_reporting_observers_task: Default::default(),
+            reporting_endpoints: Default::default(),
         }
     }
 
@@ -458,6 +598,71 @@ impl GlobalScope {
         *self.message_port_state.borrow_mut() = MessagePortState::UnManaged;
     }
 
+    /// Update our state to un-managed,
+    /// and tell the constellation to drop the sender to our broadcast router.
+    pub fn remove_broadcast_channel_router(&self) {
+        if let BroadcastChannelState::Managed(router_id, _channels) =
+            &*self.broadcast_channel_state.borrow()
+        {
+            let _ =
+                self.script_to_constellation_chan()
+                    .send(ScriptMsg::RemoveBroadcastChannelRouter(
+                        router_id.clone(),
+                        self.origin().immutable().clone(),
+                    ));
+        }
+        *self.broadcast_channel_state.borrow_mut() = BroadcastChannelState::UnManaged;
+    }
+
+    /// Keep track of a Blob URL created by this global.
+    pub fn track_blob_url(&self, id: Uuid) {
+        self.blob_url_ids.borrow_mut().push(id);
+    }
+
+    /// Stop keeping track of a Blob URL, once it has been revoked.
+    pub fn untrack_blob_url(&self, id: &Uuid) {
+        self.blob_url_ids
+            .borrow_mut()
+            .retain(|blob_url_id| blob_url_id != id);
+    }
+
+    /// Keep track of the MediaSource an object URL created by this global
+    /// refers to.
+    pub fn track_media_source_url(&self, url: String, media_source: &MediaSource) {
+        self.media_source_urls
+            .borrow_mut()
+            .insert(url, Dom::from_ref(media_source));
+    }
+
+    /// Stop keeping track of a MediaSource object URL, once it has been
+    /// revoked. Returns whether `url` was such a URL.
+    pub fn untrack_media_source_url(&self, url: &str) -> bool {
+        self.media_source_urls.borrow_mut().remove(url).is_some()
+    }
+
+    /// Returns the MediaSource that an object URL refers to, if any.
+    pub fn media_source_for_url(&self, url: &ServoUrl) -> Option<DomRoot<MediaSource>> {
+        self.media_source_urls
+            .borrow()
+            .get(url.as_str())
+            .map(|media_source| DomRoot::from_ref(&**media_source))
+    }
+
+    /// Revoke the Blob URLs created by this global,
+    /// <https://w3c.github.io/FileAPI/#lifeTime>
+    pub fn revoke_blob_urls(&self) {
+        let origin = get_blob_origin(&self.get_url());
+        self.media_source_urls.borrow_mut().clear();
+        // The acknowledgements are not waited for, the global is going away.
+        let (tx, _rx) = ipc::channel().unwrap();
+        for id in self.blob_url_ids.borrow_mut().drain(..) {
+            let msg = FileManagerThreadMsg::RevokeBlobURL(id, origin.clone(), tx.clone());
+            let _ = self
+                .resource_threads()
+                .send(CoreResourceMsg::ToFileManager(msg));
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#entangle>
     pub fn entangle_ports(&self, port1: MessagePortId, port2: MessagePortId) {
         if let MessagePortState::Managed(_id, message_ports) =
@@ -493,15 +698,21 @@ impl GlobalScope {
             .send(ScriptMsg::EntanglePorts(port1, port2));
     }
 
-    /// Remove all referrences to a port.
+    /// Remove all referrences to a port, whose entangled port doesn't exist anymore.
     pub fn remove_message_port(&self, port_id: &MessagePortId) {
         let is_empty = if let MessagePortState::Managed(_id, message_ports) =
             &mut *self.message_port_state.borrow_mut()
         {
-            match message_ports.remove(&port_id) {
+            let dom_port = match message_ports.remove(&port_id) {
                 None => panic!("remove_message_port called on a global not managing the port."),
-                Some(_) => message_ports.is_empty(),
+                Some(ManagedMessagePort::Pending(_port_impl, dom_port)) => dom_port,
+                Some(ManagedMessagePort::Added(_port_impl, dom_port)) => dom_port,
+            };
+            // A port still in use must no longer route messages through us.
+            if let Some(dom_port) = dom_port.root() {
+                dom_port.disentangle();
             }
+            message_ports.is_empty()
         } else {
             return warn!("remove_message_port called on a global not managing any ports.");
         };
@@ -564,17 +775,26 @@ impl GlobalScope {
 
     /// <https://html.spec.whatwg.org/multipage/#dom-messageport-close>
     pub fn close_message_port(&self, port_id: &MessagePortId) {
-        if let MessagePortState::Managed(_id, message_ports) =
+        let is_empty = if let MessagePortState::Managed(_id, message_ports) =
             &mut *self.message_port_state.borrow_mut()
         {
-            let port = match message_ports.get_mut(&port_id) {
+            let mut port = match message_ports.remove(&port_id) {
                 None => panic!("close_message_port called on an unknown port."),
                 Some(ManagedMessagePort::Pending(port_impl, _)) => port_impl,
                 Some(ManagedMessagePort::Added(port_impl, _)) => port_impl,
             };
             port.close();
+            // Disentangle the port: let the constellation drop it,
+            // and let the global managing the entangled port know about it.
+            let _ = self
+                .script_to_constellation_chan()
+                .send(ScriptMsg::RemoveMessagePort(port_id.clone()));
+            message_ports.is_empty()
         } else {
             return warn!("close_message_port called on a global not managing any ports.");
+        };
+        if is_empty {
+            self.remove_message_ports_router();
         }
     }
 
@@ -649,7 +869,18 @@ impl GlobalScope {
         if let Some((dom_port, PortMessageTask { origin, data })) = should_dispatch {
             // Substep 3-4
             rooted!(in(*self.get_cx()) let mut message_clone = UndefinedValue());
-            if let Ok(ports) = structuredclone::read(self, data, message_clone.handle_mut()) {
+            let result = structuredclone::read(self, data, message_clone.handle_mut());
+
+            // The port of a transferred stream hands its messages to the stream,
+            // rather than dispatching them to script.
+            if dom_port.has_cross_realm_transform() {
+                return match result {
+                    Ok(_) => dom_port.handle_cross_realm_message(message_clone.handle()),
+                    Err(()) => dom_port.handle_cross_realm_message_error(),
+                };
+            }
+
+            if let Ok(ports) = result {
                 // Substep 6
                 // Dispatch the event, using the dom message-port.
                 MessageEvent::dispatch_jsval(
@@ -737,6 +968,187 @@ impl GlobalScope {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+    pub fn schedule_broadcast(&self, msg: BroadcastMsg, channel: &BroadcastChannel) {
+        // First, broadcast locally.
+        self.broadcast_message_event(msg.clone(), Some(channel));
+
+        if let BroadcastChannelState::Managed(router_id, _) =
+            &*self.broadcast_channel_state.borrow()
+        {
+            // Second, broadcast to other globals via the constellation.
+            //
+            // Note: for globals in the same script-thread,
+            // we could skip the hop to the constellation.
+            let _ = self
+                .script_to_constellation_chan()
+                .send(ScriptMsg::ScheduleBroadcast(router_id.clone(), msg));
+        } else {
+            panic!("Attemps to broadcast a message via global not managing any channels.");
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+    pub fn broadcast_message_event(&self, event: BroadcastMsg, source: Option<&BroadcastChannel>) {
+        if let BroadcastChannelState::Managed(_, channels) = &*self.broadcast_channel_state.borrow()
+        {
+            // Step 7, a few preliminary steps.
+
+            // - Check the worker is not closing.
+            if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+                if worker.is_closing() {
+                    return;
+                }
+            }
+
+            // - Check the associated document is fully-active.
+            if let Some(window) = self.downcast::<Window>() {
+                if !window.Document().is_fully_active() {
+                    return;
+                }
+            }
+
+            // - Check for a case-sensitive match for the name of the channel.
+            let channel_name = DOMString::from_string(event.channel_name.clone());
+
+            if let Some(channels) = channels.get(&channel_name) {
+                channels
+                    .iter()
+                    .filter(|channel| {
+                        // Step 8.
+                        // Filter out the sender.
+                        match source {
+                            Some(source) => !ptr::eq(&***channel, source),
+                            None => true,
+                        }
+                    })
+                    .map(|channel| DomRoot::from_ref(&**channel))
+                    // Step 9, sort by creation order,
+                    // done by using a queue to store channels in creation order.
+                    .for_each(|channel| {
+                        let BroadcastMsg { data, origin, .. } = event.clone();
+
+                        // Step 10: Queue a task on the DOM manipulation task-source,
+                        // to fire the message event
+                        let channel = Trusted::new(&*channel);
+                        let global = Trusted::new(&*self);
+                        let _ = self.dom_manipulation_task_source().queue(
+                            task!(process_pending_broadcast_message: move || {
+                                let destination = channel.root();
+                                let global = global.root();
+
+                                // 10.1 Check for closed flag.
+                                if destination.closed() {
+                                    return;
+                                }
+
+                                rooted!(in(*global.get_cx()) let mut message = UndefinedValue());
+
+                                // Step 10.3 StructuredDeserialize(serialized, targetRealm).
+                                if let Ok(ports) = structuredclone::read(&global, data, message.handle_mut()) {
+                                    // Step 10.4, Fire an event named message at destination.
+                                    MessageEvent::dispatch_jsval(
+                                        &*destination.upcast(),
+                                        &global,
+                                        message.handle(),
+                                        Some(&origin.ascii_serialization()),
+                                        None,
+                                        ports,
+                                    );
+                                } else {
+                                    // Step 10.3, fire an event named messageerror at destination.
+                                    MessageEvent::dispatch_error(&*destination.upcast(), &global);
+                                }
+                            }),
+                            &self,
+                        );
+                    });
+            }
+        }
+    }
+
+    /// Start tracking a broadcast-channel.
+    pub fn track_broadcast_channel(&self, dom_channel: &BroadcastChannel) {
+        let mut current_state = self.broadcast_channel_state.borrow_mut();
+
+        if let BroadcastChannelState::UnManaged = &*current_state {
+            // Setup a route for IPC, for broadcasts from the constellation to our channels.
+            let (broadcast_control_sender, broadcast_control_receiver) =
+                ipc::channel().expect("ipc channel failure");
+            let context = Trusted::new(self);
+            let (task_source, canceller) = (
+                self.dom_manipulation_task_source(),
+                self.task_canceller(TaskSourceName::DOMManipulation),
+            );
+            let listener = BroadcastListener {
+                canceller,
+                task_source,
+                context,
+            };
+            ROUTER.add_route(
+                broadcast_control_receiver.to_opaque(),
+                Box::new(move |message| {
+                    let msg = message.to();
+                    match msg {
+                        Ok(msg) => listener.handle(msg),
+                        Err(err) => warn!("Error receiving a BroadcastMsg: {:?}", err),
+                    }
+                }),
+            );
+            let router_id = BroadcastChannelRouterId::new();
+            *current_state = BroadcastChannelState::Managed(router_id.clone(), HashMap::new());
+            let _ = self
+                .script_to_constellation_chan()
+                .send(ScriptMsg::NewBroadcastChannelRouter(
+                    router_id,
+                    broadcast_control_sender,
+                    self.origin().immutable().clone(),
+                ));
+        }
+
+        if let BroadcastChannelState::Managed(router_id, channels) = &mut *current_state {
+            let entry = channels.entry(dom_channel.Name()).or_insert_with(|| {
+                let _ = self.script_to_constellation_chan().send(
+                    ScriptMsg::NewBroadcastChannelNameInRouter(
+                        router_id.clone(),
+                        dom_channel.Name().to_string(),
+                        self.origin().immutable().clone(),
+                    ),
+                );
+                VecDeque::new()
+            });
+            entry.push_back(Dom::from_ref(dom_channel));
+        } else {
+            panic!("track_broadcast_channel should have first switched the state to managed.");
+        }
+    }
+
+    /// Stop tracking a broadcast-channel that was closed.
+    pub fn untrack_broadcast_channel(&self, dom_channel: &BroadcastChannel) {
+        if let BroadcastChannelState::Managed(router_id, channels) =
+            &mut *self.broadcast_channel_state.borrow_mut()
+        {
+            let name = dom_channel.Name();
+            let is_empty = match channels.get_mut(&name) {
+                Some(queue) => {
+                    queue.retain(|channel| !ptr::eq(&**channel, dom_channel));
+                    queue.is_empty()
+                },
+                None => false,
+            };
+            if is_empty {
+                channels.remove(&name);
+                let _ = self.script_to_constellation_chan().send(
+                    ScriptMsg::RemoveBroadcastChannelNameInRouter(
+                        router_id.clone(),
+                        name.to_string(),
+                        self.origin().immutable().clone(),
+                    ),
+                );
+            }
+        }
+    }
+
     /// Start tracking a message-port
     pub fn track_message_port(&self, dom_port: &MessagePort, port_impl: Option<MessagePortImpl>) {
         let mut current_state = self.message_port_state.borrow_mut();
@@ -814,10 +1226,23 @@ impl GlobalScope {
         }
     }
 
-    pub fn track_worker(&self, closing_worker: Arc<AtomicBool>) {
+    pub fn track_worker(
+        &self,
+        closing_worker: Arc<AtomicBool>,
+        worker_sender: Sender<DedicatedWorkerScriptMsg>,
+    ) {
         self.list_auto_close_worker
             .borrow_mut()
-            .push(AutoCloseWorker(closing_worker));
+            .push(AutoCloseWorker {
+                closing: closing_worker,
+                sender: worker_sender,
+            });
+    }
+
+    /// Close the dedicated workers created by this global,
+    /// which in turn close their own dedicated workers.
+    pub fn close_workers(&self) {
+        self.list_auto_close_worker.borrow_mut().clear();
     }
 
     pub fn track_event_source(&self, event_source: &EventSource) {
@@ -896,6 +1321,45 @@ impl GlobalScope {
         &self.uncaught_rejections
     }
 
+    /// Stores the module record of the module script that was requested from
+    /// `request_url` and whose response came from `final_url`.
+    pub fn set_module_map(&self, request_url: ServoUrl, final_url: ServoUrl, module: HandleObject) {
+        if request_url != final_url {
+            self.module_redirects
+                .borrow_mut()
+                .insert(request_url, final_url.clone());
+        }
+        self.module_map
+            .borrow_mut()
+            .insert(final_url, Heap::boxed(module.get()));
+    }
+
+    /// The module record of the module script whose response came from `url`,
+    /// or whose request to `url` was redirected.
+    pub fn get_module_map_entry(&self, url: &ServoUrl) -> Option<*mut JSObject> {
+        let redirects = self.module_redirects.borrow();
+        let url = redirects.get(url).unwrap_or(url);
+        self.module_map.borrow().get(url).map(|module| module.get())
+    }
+
+    pub fn import_map(&self) -> Ref<ImportMap> {
+        self.import_map.borrow()
+    }
+
+    pub fn import_maps_allowed(&self) -> bool {
+        self.import_maps_allowed.get()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#disallow-further-import-maps>
+    pub fn disallow_further_import_maps(&self) {
+        self.import_maps_allowed.set(false);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#register-an-import-map>
+    pub fn set_import_map(&self, import_map: ImportMap) {
+        *self.import_map.borrow_mut() = import_map;
+    }
+
     pub fn add_consumed_rejection(&self, rejection: HandleObject) {
         self.consumed_rejections
             .borrow_mut()
@@ -926,6 +1390,10 @@ impl GlobalScope {
         self.crypto.or_init(|| Crypto::new(self))
     }
 
+    pub fn scheduler(&self) -> DomRoot<Scheduler> {
+        self.scheduler.or_init(|| Scheduler::new(self))
+    }
+
     /// Get next worker id.
     pub fn get_next_worker_id(&self) -> WorkerId {
         let worker_id = self.next_worker_id.get();
@@ -1006,12 +1474,27 @@ impl GlobalScope {
         if let Some(worker) = self.downcast::<DedicatedWorkerGlobalScope>() {
             return worker.image_cache();
         }
+        if let Some(worker) = self.downcast::<SharedWorkerGlobalScope>() {
+            return worker.image_cache();
+        }
         if let Some(worker) = self.downcast::<PaintWorkletGlobalScope>() {
             return worker.image_cache();
         }
         unreachable!();
     }
 
+    /// The referrer policy of the environment settings object of this global,
+    /// used by requests that do not specify their own.
+    pub fn get_referrer_policy(&self) -> Option<ReferrerPolicy> {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.Document().get_referrer_policy();
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.get_referrer_policy();
+        }
+        None
+    }
+
     /// Get the [base url](https://html.spec.whatwg.org/multipage/#api-base-url)
     /// for this global scope.
     pub fn api_base_url(&self) -> ServoUrl {
@@ -1200,6 +1683,7 @@ impl GlobalScope {
             || {
                 let cx = self.get_cx();
                 let globalhandle = self.reflector().get_jsobject();
+                let url = ServoUrl::parse(filename).ok();
                 let filename = CString::new(filename).unwrap();
 
                 let _ac = JSAutoRealm::new(*cx, globalhandle.get());
@@ -1207,14 +1691,24 @@ impl GlobalScope {
                 let options = CompileOptionsWrapper::new(*cx, filename.as_ptr(), line_number);
 
                 debug!("evaluating Dom string");
+                rooted!(in(*cx) let mut script = ptr::null_mut::<JSScript>());
                 let result = unsafe {
-                    EvaluateUtf8(
+                    CompileUtf8(
                         *cx,
                         options.ptr,
                         code.as_ptr() as *const _,
                         code.len() as libc::size_t,
-                        rval,
-                    )
+                        script.handle_mut().into(),
+                    ) && {
+                        // Dynamic imports of the script are resolved against
+                        // its URL, which is found in its private value.
+                        if let Some(url) = url {
+                            rooted!(in(*cx) let mut private = UndefinedValue());
+                            url.as_str().to_jsval(*cx, private.handle_mut());
+                            SetScriptPrivate(script.get(), &*private);
+                        }
+                        JS_ExecuteScript(*cx, script.handle(), rval)
+                    }
                 };
 
                 if !result {
@@ -1440,6 +1934,18 @@ impl GlobalScope {
         unreachable!();
     }
 
+    /// Channel to send messages to the posted task task source of this
+    /// global scope.
+    pub fn posted_task_source(&self) -> PostedTaskSource {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.task_manager().posted_task_source();
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.posted_task_source();
+        }
+        unreachable!();
+    }
+
     pub fn is_headless(&self) -> bool {
         self.is_headless
     }
@@ -1448,6 +1954,40 @@ impl GlobalScope {
         self.user_agent.clone()
     }
 
+    /// Whether this global can share memory with the other agents of its agent cluster.
+    pub fn is_cross_origin_isolated(&self) -> bool {
+        self.cross_origin_isolated.get()
+    }
+
+    pub fn set_cross_origin_isolated(&self, cross_origin_isolated: bool) {
+        self.cross_origin_isolated.set(cross_origin_isolated);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#structured-cloning>
+    pub fn structured_clone(
+        &self,
+        cx: SafeJSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        let mut rooted = CustomAutoRooter::new(
+            options
+                .transfer
+                .iter()
+                .map(|js: &RootedTraceableBox<Heap<*mut JSObject>>| js.get())
+                .collect(),
+        );
+        let transfer = CustomAutoRooterGuard::new(*cx, &mut rooted);
+        let data = if self.is_cross_origin_isolated() {
+            structuredclone::write_with_shared_memory(cx, value, Some(transfer))?
+        } else {
+            structuredclone::write(cx, value, Some(transfer))?
+        };
+        rooted!(in(*cx) let mut clone = UndefinedValue());
+        structuredclone::read(self, data, clone.handle_mut()).map_err(|_| Error::DataClone)?;
+        Ok(clone.get())
+    }
+
     /// https://www.w3.org/TR/CSP/#get-csp-of-object
     pub fn get_csp_list(&self) -> Option<CspList> {
         if let Some(window) = self.downcast::<Window>() {
@@ -1456,6 +1996,154 @@ impl GlobalScope {
         // TODO: Worker and Worklet global scopes.
         None
     }
+
+    /// Queue a task to report each of the violations, at `element` if it is
+    /// connected and at the document or this global otherwise.
+    /// https://w3c.github.io/webappsec-csp/#report-violation
+    pub fn report_csp_violations(
+        &self,
+        violations: Vec<csp::Violation>,
+        element: Option<&Element>,
+    ) {
+        for violation in violations {
+            warn!("Content Security Policy violation: {:?}", violation);
+            let report_to = violation
+                .policy
+                .directive_set
+                .iter()
+                .find(|directive| directive.name == "report-to")
+                .and_then(|directive| directive.value.first().cloned());
+            let report = CSPViolationReport::new(self, violation);
+            if let Some(report_to) = report_to {
+                self.generate_report(ReportBody::CSPViolation(report.clone()), &report_to);
+            }
+            let target = match element {
+                Some(element) if element.upcast::<Node>().is_connected() => {
+                    DomRoot::from_ref(element.upcast::<EventTarget>())
+                },
+                _ => match self.downcast::<Window>() {
+                    Some(window) => DomRoot::from_ref(window.Document().upcast::<EventTarget>()),
+                    None => DomRoot::from_ref(self.upcast::<EventTarget>()),
+                },
+            };
+            let task = CSPViolationReporter::new(&target, report);
+            let _ = self.dom_manipulation_task_source().queue(task, self);
+        }
+    }
+
+    /// Reports the use of a deprecated feature of the web platform.
+    pub fn report_deprecation(&self, id: &str, message: &str) {
+        warn!("Deprecation: {}", message);
+        let body = ReportBody::Deprecation(DeprecationReport {
+            id: id.to_owned(),
+            message: message.to_owned(),
+            source_file: None,
+            line_number: None,
+            column_number: None,
+        });
+        self.generate_report(body, "default");
+    }
+
+    /// Reports that a request of the page was not honoured.
+    pub fn report_intervention(&self, id: &str, message: &str) {
+        warn!("Intervention: {}", message);
+        let body = ReportBody::Intervention(InterventionReport {
+            id: id.to_owned(),
+            message: message.to_owned(),
+            source_file: None,
+            line_number: None,
+            column_number: None,
+        });
+        self.generate_report(body, "default");
+    }
+
+    /// <https://w3c.github.io/reporting/#generate-and-queue-a-report>
+    fn generate_report(&self, body: ReportBody, destination: &str) {
+        if !pref!(dom.reporting.enabled) {
+            return;
+        }
+        let report = Report::new(self, body);
+        self.notify_reporting_observers(&DOMReport::new(self, &report));
+
+        // The report is delivered right away, instead of being queued in the
+        // reports of the user agent.
+        let endpoint = self.reporting_endpoints.borrow().get(destination).cloned();
+        if let Some(endpoint) = endpoint {
+            reporting::deliver_report(self, endpoint, &report);
+        }
+    }
+
+    /// <https://w3c.github.io/reporting/#notify-reporting-observers>
+    fn notify_reporting_observers(&self, report: &DOMReport) {
+        for observer in self.reporting_observers.borrow().iter() {
+            observer.queue_report(report);
+        }
+
+        let mut buffer = self.report_buffer.borrow_mut();
+        if buffer.len() == MAX_REPORT_BUFFER_SIZE {
+            buffer.pop_front();
+        }
+        buffer.push_back(Dom::from_ref(report));
+
+        // A single task invokes all the observers with the reports queued
+        // since the last one.
+        if self.pending_reporting_observers_task.get() {
+            return;
+        }
+        self.pending_reporting_observers_task.set(true);
+        let this = Trusted::new(self);
+        let _ = self.dom_manipulation_task_source().queue(
+            task!(invoke_reporting_observers: move || {
+                this.root().invoke_reporting_observers();
+            }),
+            self,
+        );
+    }
+
+    /// <https://w3c.github.io/reporting/#invoke-observers>
+    fn invoke_reporting_observers(&self) {
+        self.pending_reporting_observers_task.set(false);
+        // The observers are copied, since their callbacks can modify the list.
+        let observers: Vec<DomRoot<ReportingObserver>> = self
+            .reporting_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect();
+        for observer in observers {
+            observer.invoke();
+        }
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-reportingobserver-observe>
+    pub fn register_reporting_observer(&self, observer: &ReportingObserver) {
+        let mut observers = self.reporting_observers.borrow_mut();
+        if !observers.iter().any(|o| &**o == observer) {
+            observers.push(Dom::from_ref(observer));
+        }
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-reportingobserver-disconnect>
+    pub fn unregister_reporting_observer(&self, observer: &ReportingObserver) {
+        self.reporting_observers
+            .borrow_mut()
+            .retain(|o| &**o != observer);
+    }
+
+    /// The reports that were generated in this global, oldest first,
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-reports>
+    pub fn buffered_reports(&self) -> Vec<DomRoot<DOMReport>> {
+        self.report_buffer
+            .borrow()
+            .iter()
+            .map(|report| DomRoot::from_ref(&**report))
+            .collect()
+    }
+
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-endpoints>
+    pub fn set_reporting_endpoints(&self, endpoints: HashMap<String, ServoUrl>) {
+        *self.reporting_endpoints.borrow_mut() = endpoints;
+    }
 }
 
 fn timestamp_in_ms(time: Timespec) -> u64 {
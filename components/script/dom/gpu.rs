@@ -146,6 +146,7 @@ impl AsyncWGPUListener for GPU {
             WebGPUResponse::RequestAdapter(name, adapter) => {
                 let adapter = GPUAdapter::new(
                     &self.global(),
+                    self.wgpu_channel().unwrap(),
                     DOMString::from(name),
                     Heap::default(),
                     adapter,
@@ -24,11 +24,13 @@ use dom_struct::dom_struct;
 use js::jsapi::{Heap, JSObject};
 use std::ptr::NonNull;
 use std::rc::Rc;
-use webgpu::{wgpu, WebGPUAdapter, WebGPURequest, WebGPUResponse};
+use webgpu::{wgpu, WebGPU, WebGPUAdapter, WebGPURequest, WebGPUResponse};
 
 #[dom_struct]
 pub struct GPUAdapter {
     reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
     name: DOMString,
     #[ignore_malloc_size_of = "mozjs"]
     extensions: Heap<*mut JSObject>,
@@ -37,12 +39,14 @@ pub struct GPUAdapter {
 
 impl GPUAdapter {
     pub fn new_inherited(
+        channel: WebGPU,
         name: DOMString,
         extensions: Heap<*mut JSObject>,
         adapter: WebGPUAdapter,
     ) -> GPUAdapter {
         GPUAdapter {
             reflector_: Reflector::new(),
+            channel,
             name,
             extensions,
             adapter,
@@ -51,12 +55,15 @@ impl GPUAdapter {
 
     pub fn new(
         global: &GlobalScope,
+        channel: WebGPU,
         name: DOMString,
         extensions: Heap<*mut JSObject>,
         adapter: WebGPUAdapter,
     ) -> DomRoot<GPUAdapter> {
         reflect_dom_object(
-            Box::new(GPUAdapter::new_inherited(name, extensions, adapter)),
+            Box::new(GPUAdapter::new_inherited(
+                channel, name, extensions, adapter,
+            )),
             global,
             GPUAdapterBinding::Wrap,
         )
@@ -108,6 +115,7 @@ impl AsyncWGPUListener for GPUAdapter {
             WebGPUResponse::RequestDevice(device_id, _descriptor) => {
                 let device = GPUDevice::new(
                     &self.global(),
+                    self.channel.clone(),
                     &self,
                     Heap::default(),
                     Heap::default(),
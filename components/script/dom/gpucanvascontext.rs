@@ -0,0 +1,160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUCanvasContextBinding::{
+    self, GPUCanvasContextMethods, GPUSwapChainDescriptor,
+};
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::GPUTextureFormat;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpudevice::GPUDevice;
+use crate::dom::gpuswapchain::GPUSwapChain;
+use crate::dom::gputexture::convert_texture_format;
+use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::node::{document_from_node, Node, NodeDamage};
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use script_layout_interface::HTMLCanvasDataSource;
+use std::cell::Cell;
+use std::rc::Rc;
+use webgpu::wgpu::{Extent3d, TextureDescriptor, TextureDimension, TextureUsage};
+use webgpu::WebGPURequest;
+use webrender_api::ImageKey;
+
+#[dom_struct]
+pub struct GPUCanvasContext {
+    reflector_: Reflector,
+    canvas: Dom<HTMLCanvasElement>,
+    swap_chain: MutNullableDom<GPUSwapChain>,
+    /// The WebRender image which the swap chain presents into, if any.
+    #[ignore_malloc_size_of = "Defined in webrender"]
+    image_key: Cell<Option<ImageKey>>,
+}
+
+impl GPUCanvasContext {
+    fn new_inherited(canvas: &HTMLCanvasElement) -> GPUCanvasContext {
+        Self {
+            reflector_: Reflector::new(),
+            canvas: Dom::from_ref(canvas),
+            swap_chain: Default::default(),
+            image_key: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, canvas: &HTMLCanvasElement) -> DomRoot<GPUCanvasContext> {
+        reflect_dom_object(
+            Box::new(GPUCanvasContext::new_inherited(canvas)),
+            global,
+            GPUCanvasContextBinding::Wrap,
+        )
+    }
+}
+
+impl GPUCanvasContext {
+    pub fn image_key(&self) -> Option<ImageKey> {
+        self.image_key.get()
+    }
+
+    pub fn onscreen(&self) -> bool {
+        self.canvas.upcast::<Node>().is_connected()
+    }
+
+    /// Schedules the current texture of the swap chain to be presented once
+    /// the document is next reflowed.
+    pub fn mark_as_dirty(&self) {
+        self.canvas
+            .upcast::<Node>()
+            .dirty(NodeDamage::OtherNodeDamage);
+
+        let document = document_from_node(&*self.canvas);
+        document.add_dirty_webgpu_canvas(self);
+    }
+
+    /// Copies the current texture of the swap chain into the WebRender image
+    /// of the canvas.
+    pub fn send_swap_chain_present(&self) {
+        if let (Some(image_key), Some(swap_chain)) = (self.image_key.get(), self.swap_chain.get()) {
+            swap_chain.present(image_key);
+        }
+    }
+}
+
+pub trait LayoutCanvasWebGPUHelpers {
+    #[allow(unsafe_code)]
+    unsafe fn canvas_data_source(&self) -> HTMLCanvasDataSource;
+}
+
+impl LayoutCanvasWebGPUHelpers for LayoutDom<GPUCanvasContext> {
+    #[allow(unsafe_code)]
+    unsafe fn canvas_data_source(&self) -> HTMLCanvasDataSource {
+        match (*self.unsafe_get()).image_key.get() {
+            Some(image_key) => HTMLCanvasDataSource::WebGPU(image_key),
+            None => HTMLCanvasDataSource::Image(None),
+        }
+    }
+}
+
+impl GPUCanvasContextMethods for GPUCanvasContext {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucanvascontext-configureswapchain
+    fn ConfigureSwapChain(&self, descriptor: &GPUSwapChainDescriptor) -> DomRoot<GPUSwapChain> {
+        if let Some(swap_chain) = self.swap_chain.get() {
+            swap_chain.destroy(self.image_key.take());
+        }
+
+        let device = &descriptor.device;
+        let size = self.canvas.get_size();
+        let usage = TextureUsage::from_bits(descriptor.usage);
+        // Presentation reads back the texture, which only supports the
+        // format of the WebRender image.
+        let valid = descriptor.format == GPUTextureFormat::Bgra8unorm && usage.is_some();
+        let texture = device.create_texture(
+            TextureDescriptor {
+                size: Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth: 1,
+                },
+                array_layer_count: 1,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: convert_texture_format(descriptor.format),
+                usage: usage.unwrap_or(TextureUsage::empty()) | TextureUsage::COPY_SRC,
+            },
+            valid,
+        );
+
+        if texture.is_valid() {
+            let buffer_id = self.global().as_window().Navigator().create_buffer_id();
+            let (sender, receiver) = ipc::channel().unwrap();
+            device
+                .channel()
+                .0
+                .send(WebGPURequest::CreateSwapChain(
+                    sender,
+                    device.id(),
+                    buffer_id,
+                    size.width,
+                    size.height,
+                ))
+                .expect("Failed to create WebGPU SwapChain");
+            self.image_key.set(Some(receiver.recv().unwrap()));
+        }
+
+        let swap_chain = GPUSwapChain::new(&self.global(), device.channel(), self, &texture);
+        self.swap_chain.set(Some(&swap_chain));
+        swap_chain
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucanvascontext-getswapchainpreferredformat
+    fn GetSwapChainPreferredFormat(&self, _device: &GPUDevice) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        promise.resolve_native(&GPUTextureFormat::Bgra8unorm);
+        promise
+    }
+}
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUCommandBufferBinding::{
+    self, GPUCommandBufferMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use webgpu::WebGPUCommandBuffer;
+
+#[dom_struct]
+pub struct GPUCommandBuffer {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    command_buffer: WebGPUCommandBuffer,
+    /// Unset once the command buffer is submitted, since wgpu consumes it.
+    valid: Cell<bool>,
+}
+
+impl GPUCommandBuffer {
+    fn new_inherited(command_buffer: WebGPUCommandBuffer, valid: bool) -> GPUCommandBuffer {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            command_buffer,
+            valid: Cell::new(valid),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        command_buffer: WebGPUCommandBuffer,
+        valid: bool,
+    ) -> DomRoot<GPUCommandBuffer> {
+        reflect_dom_object(
+            Box::new(GPUCommandBuffer::new_inherited(command_buffer, valid)),
+            global,
+            GPUCommandBufferBinding::Wrap,
+        )
+    }
+}
+
+impl GPUCommandBuffer {
+    pub fn id(&self) -> WebGPUCommandBuffer {
+        self.command_buffer
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid.get()
+    }
+
+    pub fn mark_submitted(&self) {
+        self.valid.set(false);
+    }
+}
+
+impl GPUCommandBufferMethods for GPUCommandBuffer {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+}
@@ -0,0 +1,279 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUCommandBufferBinding::GPUCommandBufferDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUCommandEncoderBinding::{
+    self, GPUCommandEncoderMethods, GPUComputePassDescriptor,
+};
+use crate::dom::bindings::codegen::Bindings::GPURenderPassEncoderBinding::{
+    GPURenderPassDescriptor, GPUStoreOp,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::GPULoadOpOrDoubleSequenceOrGPUColorDict;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucommandbuffer::GPUCommandBuffer;
+use crate::dom::gpucomputepassencoder::GPUComputePassEncoder;
+use crate::dom::gpudevice::{GPUDevice, MAX_COLOR_ATTACHMENTS};
+use crate::dom::gpurenderpassencoder::{
+    color_from_components, color_from_dict, GPURenderPassEncoder,
+};
+use crate::dom::gputexture::is_color_renderable;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use std::cell::Cell;
+use webgpu::wgpu::{Color, LoadOp, StoreOp};
+use webgpu::{
+    ComputePassCommand, RenderPassColorAttachment, RenderPassCommand, WebGPU, WebGPUCommandBuffer,
+    WebGPUCommandEncoder, WebGPURequest,
+};
+
+/// <https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-state-slot>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum GPUCommandEncoderState {
+    Open,
+    EncodingComputePass,
+    EncodingRenderPass,
+    Closed,
+}
+
+#[dom_struct]
+pub struct GPUCommandEncoder {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    device: Dom<GPUDevice>,
+    label: DomRefCell<Option<DOMString>>,
+    encoder: WebGPUCommandEncoder,
+    state: Cell<GPUCommandEncoderState>,
+    valid: Cell<bool>,
+}
+
+impl GPUCommandEncoder {
+    fn new_inherited(
+        channel: WebGPU,
+        device: &GPUDevice,
+        encoder: WebGPUCommandEncoder,
+    ) -> GPUCommandEncoder {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            device: Dom::from_ref(device),
+            label: DomRefCell::new(None),
+            encoder,
+            state: Cell::new(GPUCommandEncoderState::Open),
+            valid: Cell::new(true),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        device: &GPUDevice,
+        encoder: WebGPUCommandEncoder,
+    ) -> DomRoot<GPUCommandEncoder> {
+        reflect_dom_object(
+            Box::new(GPUCommandEncoder::new_inherited(channel, device, encoder)),
+            global,
+            GPUCommandEncoderBinding::Wrap,
+        )
+    }
+}
+
+impl GPUCommandEncoder {
+    pub fn id(&self) -> WebGPUCommandEncoder {
+        self.encoder
+    }
+
+    /// Ends the compute pass being encoded, whose `commands` are replayed into
+    /// this encoder on the WGPU thread if both are valid.
+    pub fn end_compute_pass(&self, commands: Vec<ComputePassCommand>, valid: bool) {
+        debug_assert_eq!(
+            self.state.get(),
+            GPUCommandEncoderState::EncodingComputePass
+        );
+        self.state.set(GPUCommandEncoderState::Open);
+        if !valid {
+            self.valid.set(false);
+        }
+        if !self.valid.get() {
+            return;
+        }
+
+        let pass_id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_compute_pass_id();
+        self.channel
+            .0
+            .send(WebGPURequest::RunComputePass(
+                self.encoder,
+                pass_id,
+                commands,
+            ))
+            .expect("Failed to run WebGPU compute pass");
+    }
+
+    /// Ends the render pass being encoded, whose `commands` are replayed into
+    /// this encoder on the WGPU thread if both are valid.
+    pub fn end_render_pass(
+        &self,
+        color_attachments: Vec<RenderPassColorAttachment>,
+        commands: Vec<RenderPassCommand>,
+        valid: bool,
+    ) {
+        debug_assert_eq!(self.state.get(), GPUCommandEncoderState::EncodingRenderPass);
+        self.state.set(GPUCommandEncoderState::Open);
+        if !valid {
+            self.valid.set(false);
+        }
+        if !self.valid.get() {
+            return;
+        }
+
+        let pass_id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_render_pass_id();
+        self.channel
+            .0
+            .send(WebGPURequest::RunRenderPass(
+                self.encoder,
+                pass_id,
+                color_attachments,
+                commands,
+            ))
+            .expect("Failed to run WebGPU render pass");
+    }
+}
+
+impl GPUCommandEncoderMethods for GPUCommandEncoder {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-begincomputepass
+    fn BeginComputePass(
+        &self,
+        _descriptor: &GPUComputePassDescriptor,
+    ) -> DomRoot<GPUComputePassEncoder> {
+        // A pass can only be begun while no other pass is being encoded, the
+        // returned pass is otherwise invalid and invalidates this encoder.
+        let active = self.state.get() == GPUCommandEncoderState::Open;
+        if active {
+            self.state.set(GPUCommandEncoderState::EncodingComputePass);
+        } else {
+            self.valid.set(false);
+        }
+        GPUComputePassEncoder::new(&self.global(), self, active)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-beginrenderpass
+    fn BeginRenderPass(
+        &self,
+        descriptor: &GPURenderPassDescriptor,
+    ) -> DomRoot<GPURenderPassEncoder> {
+        let active = self.state.get() == GPUCommandEncoderState::Open;
+        if active {
+            self.state.set(GPUCommandEncoderState::EncodingRenderPass);
+        } else {
+            self.valid.set(false);
+        }
+
+        // All the attachments must be valid renderable views of the same size
+        // and sample count, and resolve targets must be single-sampled views
+        // of their multisampled attachment's size and format.
+        let attachments = &descriptor.colorAttachments;
+        let mut valid = !attachments.is_empty() && attachments.len() <= MAX_COLOR_ATTACHMENTS;
+        let size = attachments.first().map(|color| color.attachment.size());
+        let sample_count = attachments
+            .first()
+            .map_or(1, |color| color.attachment.sample_count());
+        let mut color_attachments = Vec::with_capacity(attachments.len());
+        let mut color_formats = Vec::with_capacity(attachments.len());
+        for color in attachments {
+            let view = &color.attachment;
+            valid &= view.is_valid() &&
+                view.is_output_attachment() &&
+                is_color_renderable(view.format()) &&
+                Some(view.size()) == size &&
+                view.sample_count() == sample_count;
+            if let Some(ref resolve_target) = color.resolveTarget {
+                valid &= resolve_target.is_valid() &&
+                    resolve_target.is_output_attachment() &&
+                    resolve_target.sample_count() == 1 &&
+                    sample_count > 1 &&
+                    resolve_target.size() == view.size() &&
+                    resolve_target.format() == view.format();
+            }
+            let (load_op, clear_color) = match color.loadValue {
+                GPULoadOpOrDoubleSequenceOrGPUColorDict::GPULoadOp(_) => {
+                    (LoadOp::Load, Color::TRANSPARENT)
+                },
+                GPULoadOpOrDoubleSequenceOrGPUColorDict::DoubleSequence(ref components) => {
+                    let color = color_from_components(components);
+                    valid &= color.is_some();
+                    (LoadOp::Clear, color.unwrap_or(Color::TRANSPARENT))
+                },
+                GPULoadOpOrDoubleSequenceOrGPUColorDict::GPUColorDict(ref color) => {
+                    (LoadOp::Clear, color_from_dict(color))
+                },
+            };
+            color_attachments.push(RenderPassColorAttachment {
+                attachment: view.id().0,
+                resolve_target: color.resolveTarget.as_ref().map(|view| view.id().0),
+                load_op,
+                store_op: match color.storeOp {
+                    GPUStoreOp::Store => StoreOp::Store,
+                    GPUStoreOp::Clear => StoreOp::Clear,
+                },
+                clear_color,
+            });
+            color_formats.push(view.format());
+        }
+
+        GPURenderPassEncoder::new(
+            &self.global(),
+            self,
+            color_attachments,
+            color_formats,
+            sample_count,
+            active,
+            valid,
+        )
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-finish
+    fn Finish(&self, _descriptor: &GPUCommandBufferDescriptor) -> DomRoot<GPUCommandBuffer> {
+        let valid = self.valid.get() && self.state.get() == GPUCommandEncoderState::Open;
+        self.state.set(GPUCommandEncoderState::Closed);
+        self.valid.set(false);
+
+        let command_buffer = if valid {
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CommandEncoderFinish(sender, self.encoder))
+                .expect("Failed to finish WebGPU CommandEncoder");
+            receiver.recv().unwrap()
+        } else {
+            self.device
+                .dispatch_validation_error("Invalid GPUCommandEncoder");
+            WebGPUCommandBuffer(self.encoder.0)
+        };
+
+        GPUCommandBuffer::new(&self.global(), command_buffer, valid)
+    }
+}
@@ -0,0 +1,107 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUComputePassEncoderBinding::{
+    self, GPUComputePassEncoderMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucommandencoder::GPUCommandEncoder;
+use crate::dom::gpucomputepipeline::GPUComputePipeline;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::mem;
+use webgpu::ComputePassCommand;
+
+#[dom_struct]
+pub struct GPUComputePassEncoder {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    encoder: Dom<GPUCommandEncoder>,
+    /// The commands recorded so far, which are sent to the WGPU thread once
+    /// the pass ends.
+    commands: DomRefCell<Vec<ComputePassCommand>>,
+    /// Whether this is the pass being encoded by its encoder.
+    active: Cell<bool>,
+    has_pipeline: Cell<bool>,
+    valid: Cell<bool>,
+}
+
+impl GPUComputePassEncoder {
+    fn new_inherited(encoder: &GPUCommandEncoder, active: bool) -> GPUComputePassEncoder {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            encoder: Dom::from_ref(encoder),
+            commands: DomRefCell::new(Vec::new()),
+            active: Cell::new(active),
+            has_pipeline: Cell::new(false),
+            valid: Cell::new(active),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        encoder: &GPUCommandEncoder,
+        active: bool,
+    ) -> DomRoot<GPUComputePassEncoder> {
+        reflect_dom_object(
+            Box::new(GPUComputePassEncoder::new_inherited(encoder, active)),
+            global,
+            GPUComputePassEncoderBinding::Wrap,
+        )
+    }
+}
+
+impl GPUComputePassEncoderMethods for GPUComputePassEncoder {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucomputepassencoder-setpipeline
+    fn SetPipeline(&self, pipeline: &GPUComputePipeline) {
+        if !self.active.get() {
+            return;
+        }
+        if !pipeline.is_valid() {
+            return self.valid.set(false);
+        }
+        self.has_pipeline.set(true);
+        self.commands
+            .borrow_mut()
+            .push(ComputePassCommand::SetPipeline(pipeline.id().0));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpucomputepassencoder-dispatch
+    fn Dispatch(&self, x: u32, y: u32, z: u32) {
+        if !self.active.get() {
+            return;
+        }
+        if !self.has_pipeline.get() {
+            return self.valid.set(false);
+        }
+        self.commands
+            .borrow_mut()
+            .push(ComputePassCommand::Dispatch(x, y, z));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-endpass
+    fn EndPass(&self) {
+        if !self.active.get() {
+            return;
+        }
+        self.active.set(false);
+        let commands = mem::replace(&mut *self.commands.borrow_mut(), Vec::new());
+        self.encoder.end_compute_pass(commands, self.valid.get());
+    }
+}
@@ -0,0 +1,67 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUComputePipelineBinding::{
+    self, GPUComputePipelineMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUComputePipeline;
+
+#[dom_struct]
+pub struct GPUComputePipeline {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    compute_pipeline: WebGPUComputePipeline,
+    valid: bool,
+}
+
+impl GPUComputePipeline {
+    fn new_inherited(compute_pipeline: WebGPUComputePipeline, valid: bool) -> GPUComputePipeline {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            compute_pipeline,
+            valid,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        compute_pipeline: WebGPUComputePipeline,
+        valid: bool,
+    ) -> DomRoot<GPUComputePipeline> {
+        reflect_dom_object(
+            Box::new(GPUComputePipeline::new_inherited(compute_pipeline, valid)),
+            global,
+            GPUComputePipelineBinding::Wrap,
+        )
+    }
+}
+
+impl GPUComputePipeline {
+    pub fn id(&self) -> WebGPUComputePipeline {
+        self.compute_pipeline
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+}
+
+impl GPUComputePipelineMethods for GPUComputePipeline {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+}
@@ -3,22 +3,89 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUAdapterBinding::GPULimits;
+use crate::dom::bindings::codegen::Bindings::GPUBindGroupLayoutBinding::{
+    GPUBindGroupLayoutBindings, GPUBindGroupLayoutDescriptor, GPUBindingType,
+};
+use crate::dom::bindings::codegen::Bindings::GPUCommandEncoderBinding::GPUCommandEncoderDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUComputePipelineBinding::GPUComputePipelineDescriptor;
 use crate::dom::bindings::codegen::Bindings::GPUDeviceBinding::{self, GPUDeviceMethods};
-use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::codegen::Bindings::GPUPipelineLayoutBinding::GPUPipelineLayoutDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPURenderBundleEncoderBinding::GPURenderBundleEncoderDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPURenderPipelineBinding::{
+    GPUBlendDescriptor, GPUBlendFactor, GPUBlendOperation, GPUCullMode, GPUFrontFace,
+    GPUIndexFormat, GPUPrimitiveTopology, GPURenderPipelineDescriptor,
+};
+use crate::dom::bindings::codegen::Bindings::GPUShaderModuleBinding::GPUShaderModuleDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUShaderStageBinding::GPUShaderStageConstants;
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::{
+    GPUTextureDescriptor, GPUTextureDimension,
+};
+use crate::dom::bindings::codegen::Bindings::GPUValidationErrorBinding::GPUErrorFilter;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnsignedLongSequenceOrGPUExtent3DDict;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::gpuadapter::GPUAdapter;
+use crate::dom::gpubindgrouplayout::GPUBindGroupLayout;
+use crate::dom::gpucommandencoder::GPUCommandEncoder;
+use crate::dom::gpucomputepipeline::GPUComputePipeline;
+use crate::dom::gpuoutofmemoryerror::GPUOutOfMemoryError;
+use crate::dom::gpupipelinelayout::GPUPipelineLayout;
+use crate::dom::gpuqueue::GPUQueue;
+use crate::dom::gpurenderbundleencoder::GPURenderBundleEncoder;
+use crate::dom::gpurenderpipeline::GPURenderPipeline;
+use crate::dom::gpushadermodule::GPUShaderModule;
+use crate::dom::gputexture::{convert_texture_format, is_color_renderable, GPUTexture};
+use crate::dom::gpuvalidationerror::GPUValidationError;
+use crate::dom::promise::Promise;
 use crate::script_runtime::JSContext as SafeJSContext;
 use dom_struct::dom_struct;
+use ipc_channel::ipc;
 use js::jsapi::{Heap, JSObject};
+use std::collections::HashSet;
 use std::ptr::NonNull;
-use webgpu::WebGPUDevice;
+use std::rc::Rc;
+use webgpu::wgpu::{
+    BindGroupLayoutBinding, BindingType, BlendDescriptor, BlendFactor, BlendOperation,
+    ColorStateDescriptor, ColorWrite, CullMode, Extent3d, FrontFace, IndexFormat,
+    PrimitiveTopology, RasterizationStateDescriptor, ShaderStage, TextureDescriptor,
+    TextureDimension, TextureUsage, TextureViewDimension,
+};
+use webgpu::{
+    ProgrammableStage, RenderPipelineDescriptor, WebGPU, WebGPUBindGroupLayout,
+    WebGPUComputePipeline, WebGPUDevice, WebGPUPipelineLayout, WebGPUQueue, WebGPURenderPipeline,
+    WebGPURequest, WebGPUTexture,
+};
+
+/// <https://gpuweb.github.io/gpuweb/#dom-gpulimits-maxcolorattachments>
+pub const MAX_COLOR_ATTACHMENTS: usize = 4;
+
+/// An error which a device reports to its innermost matching error scope,
+/// <https://gpuweb.github.io/gpuweb/#errors-and-debugging>
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf)]
+pub enum GPUError {
+    OutOfMemory,
+    Validation(DOMString),
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct ErrorScope {
+    filter: GPUErrorFilter,
+    /// The first error captured by this scope.
+    error: Option<GPUError>,
+}
 
 #[dom_struct]
 pub struct GPUDevice {
     eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
     adapter: Dom<GPUAdapter>,
     #[ignore_malloc_size_of = "mozjs"]
     extensions: Heap<*mut JSObject>,
@@ -26,10 +93,13 @@ pub struct GPUDevice {
     limits: Heap<*mut JSObject>,
     label: DomRefCell<Option<DOMString>>,
     device: WebGPUDevice,
+    default_queue: MutNullableDom<GPUQueue>,
+    error_scopes: DomRefCell<Vec<ErrorScope>>,
 }
 
 impl GPUDevice {
     fn new_inherited(
+        channel: WebGPU,
         adapter: &GPUAdapter,
         extensions: Heap<*mut JSObject>,
         limits: Heap<*mut JSObject>,
@@ -37,17 +107,21 @@ impl GPUDevice {
     ) -> GPUDevice {
         Self {
             eventtarget: EventTarget::new_inherited(),
+            channel,
             adapter: Dom::from_ref(adapter),
             extensions,
             limits,
             label: DomRefCell::new(None),
             device,
+            default_queue: Default::default(),
+            error_scopes: DomRefCell::new(Vec::new()),
         }
     }
 
     #[allow(unsafe_code)]
     pub fn new(
         global: &GlobalScope,
+        channel: WebGPU,
         adapter: &GPUAdapter,
         extensions: Heap<*mut JSObject>,
         limits: Heap<*mut JSObject>,
@@ -55,7 +129,7 @@ impl GPUDevice {
     ) -> DomRoot<GPUDevice> {
         reflect_dom_object(
             Box::new(GPUDevice::new_inherited(
-                adapter, extensions, limits, device,
+                channel, adapter, extensions, limits, device,
             )),
             global,
             GPUDeviceBinding::Wrap,
@@ -63,6 +137,79 @@ impl GPUDevice {
     }
 }
 
+impl GPUDevice {
+    /// Reports `error` to the innermost error scope whose filter matches it,
+    /// which only keeps the first error it captures.
+    pub fn dispatch_error(&self, error: GPUError) {
+        let filter = match error {
+            GPUError::OutOfMemory => GPUErrorFilter::Out_of_memory,
+            GPUError::Validation(_) => GPUErrorFilter::Validation,
+        };
+        let mut error_scopes = self.error_scopes.borrow_mut();
+        match error_scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.filter == filter)
+        {
+            Some(scope) => {
+                if scope.error.is_none() {
+                    scope.error = Some(error);
+                }
+            },
+            // TODO: Fire an uncapturederror event.
+            None => warn!("Uncaptured WebGPU error: {:?}", error),
+        }
+    }
+
+    pub fn dispatch_validation_error(&self, message: &str) {
+        self.dispatch_error(GPUError::Validation(DOMString::from(message)));
+    }
+
+    pub fn id(&self) -> WebGPUDevice {
+        self.device
+    }
+
+    pub fn channel(&self) -> WebGPU {
+        self.channel.clone()
+    }
+
+    /// Creates a texture, which is invalid unless `valid` is set and
+    /// `descriptor` passes validation.
+    pub fn create_texture(
+        &self,
+        descriptor: TextureDescriptor,
+        valid: bool,
+    ) -> DomRoot<GPUTexture> {
+        let valid = valid && validate_texture_descriptor(&descriptor);
+        let id = self.global().as_window().Navigator().create_texture_id();
+        let texture = if valid {
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CreateTexture(
+                    sender,
+                    self.device,
+                    id,
+                    descriptor.clone(),
+                ))
+                .expect("Failed to create WebGPU Texture");
+            receiver.recv().unwrap()
+        } else {
+            self.dispatch_validation_error("Invalid GPUTextureDescriptor");
+            WebGPUTexture(id)
+        };
+
+        GPUTexture::new(
+            &self.global(),
+            self.channel.clone(),
+            self,
+            texture,
+            descriptor,
+            valid,
+        )
+    }
+}
+
 impl GPUDeviceMethods for GPUDevice {
     /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-adapter
     fn Adapter(&self) -> DomRoot<GPUAdapter> {
@@ -88,4 +235,556 @@ impl GPUDeviceMethods for GPUDevice {
     fn SetLabel(&self, value: Option<DOMString>) {
         *self.label.borrow_mut() = value;
     }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createtexture
+    fn CreateTexture(&self, descriptor: &GPUTextureDescriptor) -> DomRoot<GPUTexture> {
+        let size = match descriptor.size {
+            UnsignedLongSequenceOrGPUExtent3DDict::UnsignedLongSequence(ref size) => {
+                match size.as_slice() {
+                    [width, height, depth] => Some(Extent3d {
+                        width: *width,
+                        height: *height,
+                        depth: *depth,
+                    }),
+                    _ => None,
+                }
+            },
+            UnsignedLongSequenceOrGPUExtent3DDict::GPUExtent3DDict(ref size) => Some(Extent3d {
+                width: size.width,
+                height: size.height,
+                depth: size.depth,
+            }),
+        };
+        let usage = TextureUsage::from_bits(descriptor.usage);
+        let wgpu_descriptor = TextureDescriptor {
+            size: size.unwrap_or(Extent3d {
+                width: 1,
+                height: 1,
+                depth: 1,
+            }),
+            array_layer_count: descriptor.arrayLayerCount,
+            mip_level_count: descriptor.mipLevelCount,
+            sample_count: descriptor.sampleCount,
+            dimension: match descriptor.dimension {
+                GPUTextureDimension::_1d => TextureDimension::D1,
+                GPUTextureDimension::_2d => TextureDimension::D2,
+                GPUTextureDimension::_3d => TextureDimension::D3,
+            },
+            format: convert_texture_format(descriptor.format),
+            usage: usage.unwrap_or(TextureUsage::empty()),
+        };
+        let valid = size.is_some() && usage.is_some();
+        self.create_texture(wgpu_descriptor, valid)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbindgrouplayout
+    fn CreateBindGroupLayout(
+        &self,
+        descriptor: &GPUBindGroupLayoutDescriptor,
+    ) -> DomRoot<GPUBindGroupLayout> {
+        let valid = validate_bind_group_layout(&descriptor.bindings);
+        let bindings = descriptor
+            .bindings
+            .iter()
+            .map(|binding| BindGroupLayoutBinding {
+                binding: binding.binding,
+                visibility: ShaderStage::from_bits_truncate(binding.visibility),
+                ty: match binding.type_ {
+                    GPUBindingType::Uniform_buffer => BindingType::UniformBuffer,
+                    GPUBindingType::Storage_buffer => BindingType::StorageBuffer,
+                    GPUBindingType::Readonly_storage_buffer => BindingType::ReadonlyStorageBuffer,
+                    GPUBindingType::Sampled_texture => BindingType::SampledTexture,
+                    GPUBindingType::Storage_texture => BindingType::StorageTexture,
+                    GPUBindingType::Sampler => BindingType::Sampler,
+                },
+                texture_dimension: TextureViewDimension::D2,
+                multisampled: binding.multisampled,
+                dynamic: binding.hasDynamicOffset,
+            })
+            .collect::<Vec<_>>();
+
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_bind_group_layout_id();
+        // Invalid layouts are only tracked on the content side,
+        // https://gpuweb.github.io/gpuweb/#invalid
+        let bind_group_layout = if valid {
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CreateBindGroupLayout(
+                    sender,
+                    self.device,
+                    id,
+                    bindings.clone(),
+                ))
+                .expect("Failed to create WebGPU BindGroupLayout");
+            receiver.recv().unwrap()
+        } else {
+            WebGPUBindGroupLayout(id)
+        };
+
+        if !valid {
+            self.dispatch_validation_error("Invalid GPUBindGroupLayoutDescriptor");
+        }
+        GPUBindGroupLayout::new(&self.global(), bind_group_layout, bindings, valid)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createpipelinelayout
+    fn CreatePipelineLayout(
+        &self,
+        descriptor: &GPUPipelineLayoutDescriptor,
+    ) -> DomRoot<GPUPipelineLayout> {
+        let limits = GPULimits::empty();
+        let mut dynamic_uniform_buffers = 0;
+        let mut dynamic_storage_buffers = 0;
+        let mut valid = descriptor.bindGroupLayouts.len() <= limits.maxBindGroups as usize;
+        for bind_group_layout in &descriptor.bindGroupLayouts {
+            valid &= bind_group_layout.is_valid();
+            for binding in bind_group_layout.bindings().iter().filter(|b| b.dynamic) {
+                match binding.ty {
+                    BindingType::UniformBuffer => dynamic_uniform_buffers += 1,
+                    BindingType::StorageBuffer | BindingType::ReadonlyStorageBuffer => {
+                        dynamic_storage_buffers += 1
+                    },
+                    _ => {},
+                }
+            }
+        }
+        valid &= dynamic_uniform_buffers <= limits.maxDynamicUniformBuffersPerPipelineLayout &&
+            dynamic_storage_buffers <= limits.maxDynamicStorageBuffersPerPipelineLayout;
+
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_pipeline_layout_id();
+        let pipeline_layout = if valid {
+            let bind_group_layouts = descriptor
+                .bindGroupLayouts
+                .iter()
+                .map(|bgl| bgl.id().0)
+                .collect::<Vec<_>>();
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CreatePipelineLayout(
+                    sender,
+                    self.device,
+                    id,
+                    bind_group_layouts,
+                ))
+                .expect("Failed to create WebGPU PipelineLayout");
+            receiver.recv().unwrap()
+        } else {
+            WebGPUPipelineLayout(id)
+        };
+
+        if !valid {
+            self.dispatch_validation_error("Invalid GPUPipelineLayoutDescriptor");
+        }
+        GPUPipelineLayout::new(&self.global(), pipeline_layout, valid)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createshadermodule
+    fn CreateShaderModule(
+        &self,
+        descriptor: RootedTraceableBox<GPUShaderModuleDescriptor>,
+    ) -> DomRoot<GPUShaderModule> {
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_shader_module_id();
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateShaderModule(
+                sender,
+                self.device,
+                id,
+                descriptor.code.to_vec(),
+            ))
+            .expect("Failed to create WebGPU ShaderModule");
+
+        let shader_module = receiver.recv().unwrap();
+        GPUShaderModule::new(&self.global(), shader_module)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createcomputepipeline
+    fn CreateComputePipeline(
+        &self,
+        descriptor: &GPUComputePipelineDescriptor,
+    ) -> DomRoot<GPUComputePipeline> {
+        let stage = &descriptor.computeStage;
+        // The entry point is passed to wgpu as a C string.
+        let valid = descriptor.parent.layout.is_valid() && !stage.entryPoint.contains('\0');
+
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_compute_pipeline_id();
+        let compute_pipeline = if valid {
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CreateComputePipeline(
+                    sender,
+                    self.device,
+                    id,
+                    descriptor.parent.layout.id().0,
+                    stage.module.id().0,
+                    String::from(stage.entryPoint.clone()),
+                ))
+                .expect("Failed to create WebGPU ComputePipeline");
+            receiver.recv().unwrap()
+        } else {
+            WebGPUComputePipeline(id)
+        };
+
+        if !valid {
+            self.dispatch_validation_error("Invalid GPUComputePipelineDescriptor");
+        }
+        GPUComputePipeline::new(&self.global(), compute_pipeline, valid)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createrenderpipeline
+    fn CreateRenderPipeline(
+        &self,
+        descriptor: &GPURenderPipelineDescriptor,
+    ) -> DomRoot<GPURenderPipeline> {
+        let color_states = descriptor
+            .colorStates
+            .iter()
+            .map(|color_state| {
+                Some(ColorStateDescriptor {
+                    format: convert_texture_format(color_state.format),
+                    alpha_blend: convert_blend_descriptor(&color_state.alphaBlend),
+                    color_blend: convert_blend_descriptor(&color_state.colorBlend),
+                    write_mask: ColorWrite::from_bits(color_state.writeMask)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>();
+        let color_formats = color_states.as_ref().map_or(vec![], |color_states| {
+            color_states.iter().map(|state| state.format).collect()
+        });
+        let sample_count = descriptor.sampleCount;
+        // The entry points are passed to wgpu as C strings.
+        let valid = descriptor.parent.layout.is_valid() &&
+            !descriptor.vertexStage.entryPoint.contains('\0') &&
+            descriptor
+                .fragmentStage
+                .as_ref()
+                .map_or(true, |stage| !stage.entryPoint.contains('\0')) &&
+            color_states.is_some() &&
+            !color_formats.is_empty() &&
+            color_formats.len() <= MAX_COLOR_ATTACHMENTS &&
+            color_formats
+                .iter()
+                .all(|format| is_color_renderable(*format)) &&
+            (sample_count == 1 || sample_count == 4) &&
+            (!descriptor.alphaToCoverageEnabled || sample_count > 1);
+
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_render_pipeline_id();
+        let render_pipeline = if valid {
+            let rasterization_state = &descriptor.rasterizationState;
+            let wgpu_descriptor = RenderPipelineDescriptor {
+                layout: descriptor.parent.layout.id().0,
+                vertex_stage: ProgrammableStage {
+                    module: descriptor.vertexStage.module.id().0,
+                    entry_point: String::from(descriptor.vertexStage.entryPoint.clone()),
+                },
+                fragment_stage: descriptor
+                    .fragmentStage
+                    .as_ref()
+                    .map(|stage| ProgrammableStage {
+                        module: stage.module.id().0,
+                        entry_point: String::from(stage.entryPoint.clone()),
+                    }),
+                primitive_topology: match descriptor.primitiveTopology {
+                    GPUPrimitiveTopology::Point_list => PrimitiveTopology::PointList,
+                    GPUPrimitiveTopology::Line_list => PrimitiveTopology::LineList,
+                    GPUPrimitiveTopology::Line_strip => PrimitiveTopology::LineStrip,
+                    GPUPrimitiveTopology::Triangle_list => PrimitiveTopology::TriangleList,
+                    GPUPrimitiveTopology::Triangle_strip => PrimitiveTopology::TriangleStrip,
+                },
+                rasterization_state: RasterizationStateDescriptor {
+                    front_face: match rasterization_state.frontFace {
+                        GPUFrontFace::Ccw => FrontFace::Ccw,
+                        GPUFrontFace::Cw => FrontFace::Cw,
+                    },
+                    cull_mode: match rasterization_state.cullMode {
+                        GPUCullMode::None => CullMode::None,
+                        GPUCullMode::Front => CullMode::Front,
+                        GPUCullMode::Back => CullMode::Back,
+                    },
+                    depth_bias: rasterization_state.depthBias,
+                    depth_bias_slope_scale: *rasterization_state.depthBiasSlopeScale,
+                    depth_bias_clamp: *rasterization_state.depthBiasClamp,
+                },
+                color_states: color_states.unwrap(),
+                index_format: match descriptor.vertexState.indexFormat {
+                    GPUIndexFormat::Uint16 => IndexFormat::Uint16,
+                    GPUIndexFormat::Uint32 => IndexFormat::Uint32,
+                },
+                sample_count,
+                sample_mask: descriptor.sampleMask,
+                alpha_to_coverage_enabled: descriptor.alphaToCoverageEnabled,
+            };
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CreateRenderPipeline(
+                    sender,
+                    self.device,
+                    id,
+                    wgpu_descriptor,
+                ))
+                .expect("Failed to create WebGPU RenderPipeline");
+            receiver.recv().unwrap()
+        } else {
+            self.dispatch_validation_error("Invalid GPURenderPipelineDescriptor");
+            WebGPURenderPipeline(id)
+        };
+
+        GPURenderPipeline::new(
+            &self.global(),
+            render_pipeline,
+            color_formats,
+            sample_count,
+            valid,
+        )
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createrenderbundleencoder
+    fn CreateRenderBundleEncoder(
+        &self,
+        descriptor: &GPURenderBundleEncoderDescriptor,
+    ) -> DomRoot<GPURenderBundleEncoder> {
+        let color_formats = descriptor
+            .colorFormats
+            .iter()
+            .map(|format| convert_texture_format(*format))
+            .collect::<Vec<_>>();
+        let sample_count = descriptor.sampleCount;
+        let valid = !color_formats.is_empty() &&
+            color_formats.len() <= MAX_COLOR_ATTACHMENTS &&
+            color_formats
+                .iter()
+                .all(|format| is_color_renderable(*format)) &&
+            (sample_count == 1 || sample_count == 4);
+        if !valid {
+            self.dispatch_validation_error("Invalid GPURenderBundleEncoderDescriptor");
+        }
+        GPURenderBundleEncoder::new(&self.global(), self, color_formats, sample_count, valid)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createcommandencoder
+    fn CreateCommandEncoder(
+        &self,
+        _descriptor: &GPUCommandEncoderDescriptor,
+    ) -> DomRoot<GPUCommandEncoder> {
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_command_encoder_id();
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateCommandEncoder(sender, self.device, id))
+            .expect("Failed to create WebGPU CommandEncoder");
+
+        let encoder = receiver.recv().unwrap();
+        GPUCommandEncoder::new(&self.global(), self.channel.clone(), self, encoder)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-getqueue
+    fn GetQueue(&self) -> DomRoot<GPUQueue> {
+        self.default_queue.or_init(|| {
+            GPUQueue::new(
+                &self.global(),
+                self.channel.clone(),
+                self,
+                WebGPUQueue(self.device.0),
+            )
+        })
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-pusherrorscope
+    fn PushErrorScope(&self, filter: GPUErrorFilter) {
+        self.error_scopes.borrow_mut().push(ErrorScope {
+            filter,
+            error: None,
+        });
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-poperrorscope
+    fn PopErrorScope(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let scope = self.error_scopes.borrow_mut().pop();
+        match scope {
+            None => promise.reject_error(Error::Operation),
+            Some(ErrorScope { error: None, .. }) => {
+                promise.resolve_native(&None::<DomRoot<GPUValidationError>>)
+            },
+            Some(ErrorScope {
+                error: Some(GPUError::OutOfMemory),
+                ..
+            }) => promise.resolve_native(&GPUOutOfMemoryError::new(&global)),
+            Some(ErrorScope {
+                error: Some(GPUError::Validation(message)),
+                ..
+            }) => promise.resolve_native(&GPUValidationError::new(&global, message)),
+        }
+        promise
+    }
+}
+
+/// Checks the bindings of a bind group layout descriptor against the
+/// default limits, <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbindgrouplayout>
+fn validate_bind_group_layout(bindings: &[GPUBindGroupLayoutBindings]) -> bool {
+    let limits = GPULimits::empty();
+    let all_stages = GPUShaderStageConstants::VERTEX |
+        GPUShaderStageConstants::FRAGMENT |
+        GPUShaderStageConstants::COMPUTE;
+
+    let mut binding_numbers = HashSet::new();
+    let mut dynamic_uniform_buffers = 0;
+    let mut dynamic_storage_buffers = 0;
+    // Per-stage counts of uniform buffers, storage buffers, sampled textures,
+    // storage textures and samplers, indexed by the stage bit.
+    let mut stage_counts = [[0u32; 5]; 3];
+
+    for binding in bindings {
+        if !binding_numbers.insert(binding.binding) {
+            return false;
+        }
+        if binding.visibility & !all_stages != 0 {
+            return false;
+        }
+
+        let kind = match binding.type_ {
+            GPUBindingType::Uniform_buffer => 0,
+            GPUBindingType::Storage_buffer | GPUBindingType::Readonly_storage_buffer => 1,
+            GPUBindingType::Sampled_texture => 2,
+            GPUBindingType::Storage_texture => 3,
+            GPUBindingType::Sampler => 4,
+        };
+
+        let writable = match binding.type_ {
+            GPUBindingType::Storage_buffer | GPUBindingType::Storage_texture => true,
+            _ => false,
+        };
+        if writable && binding.visibility & GPUShaderStageConstants::VERTEX != 0 {
+            return false;
+        }
+
+        if binding.hasDynamicOffset {
+            match kind {
+                0 => dynamic_uniform_buffers += 1,
+                1 => dynamic_storage_buffers += 1,
+                _ => return false,
+            }
+        }
+        if binding.multisampled && binding.type_ != GPUBindingType::Sampled_texture {
+            return false;
+        }
+
+        for (stage, counts) in stage_counts.iter_mut().enumerate() {
+            if binding.visibility & (1 << stage) != 0 {
+                counts[kind] += 1;
+            }
+        }
+    }
+
+    let max_per_stage = [
+        limits.maxUniformBuffersPerShaderStage,
+        limits.maxStorageBuffersPerShaderStage,
+        limits.maxSampledTexturesPerShaderStage,
+        limits.maxStorageTexturesPerShaderStage,
+        limits.maxSamplersPerShaderStage,
+    ];
+    let within_stage_limits = stage_counts.iter().all(|counts| {
+        counts
+            .iter()
+            .zip(max_per_stage.iter())
+            .all(|(count, max)| count <= max)
+    });
+
+    within_stage_limits &&
+        dynamic_uniform_buffers <= limits.maxDynamicUniformBuffersPerPipelineLayout &&
+        dynamic_storage_buffers <= limits.maxDynamicStorageBuffersPerPipelineLayout
+}
+
+fn convert_blend_descriptor(descriptor: &GPUBlendDescriptor) -> BlendDescriptor {
+    let convert_factor = |factor| match factor {
+        GPUBlendFactor::Zero => BlendFactor::Zero,
+        GPUBlendFactor::One => BlendFactor::One,
+        GPUBlendFactor::Src_color => BlendFactor::SrcColor,
+        GPUBlendFactor::One_minus_src_color => BlendFactor::OneMinusSrcColor,
+        GPUBlendFactor::Src_alpha => BlendFactor::SrcAlpha,
+        GPUBlendFactor::One_minus_src_alpha => BlendFactor::OneMinusSrcAlpha,
+        GPUBlendFactor::Dst_color => BlendFactor::DstColor,
+        GPUBlendFactor::One_minus_dst_color => BlendFactor::OneMinusDstColor,
+        GPUBlendFactor::Dst_alpha => BlendFactor::DstAlpha,
+        GPUBlendFactor::One_minus_dst_alpha => BlendFactor::OneMinusDstAlpha,
+        GPUBlendFactor::Src_alpha_saturated => BlendFactor::SrcAlphaSaturated,
+        GPUBlendFactor::Blend_color => BlendFactor::BlendColor,
+        GPUBlendFactor::One_minus_blend_color => BlendFactor::OneMinusBlendColor,
+    };
+    BlendDescriptor {
+        src_factor: convert_factor(descriptor.srcFactor),
+        dst_factor: convert_factor(descriptor.dstFactor),
+        operation: match descriptor.operation {
+            GPUBlendOperation::Add => BlendOperation::Add,
+            GPUBlendOperation::Subtract => BlendOperation::Subtract,
+            GPUBlendOperation::Reverse_subtract => BlendOperation::ReverseSubtract,
+            GPUBlendOperation::Min => BlendOperation::Min,
+            GPUBlendOperation::Max => BlendOperation::Max,
+        },
+    }
+}
+
+/// Checks the size, level and layer counts, sample count and usage of a
+/// texture descriptor, <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createtexture>
+fn validate_texture_descriptor(descriptor: &TextureDescriptor) -> bool {
+    let size = descriptor.size;
+    let max_dimension = match descriptor.dimension {
+        TextureDimension::D3 => size.width.max(size.height).max(size.depth),
+        _ => size.width.max(size.height),
+    };
+    // A full mipmap chain has a level per bit of the largest dimension.
+    let max_mip_level_count = 32 - max_dimension.leading_zeros();
+    let valid_dimension = match descriptor.dimension {
+        TextureDimension::D1 => size.height == 1 && size.depth == 1,
+        TextureDimension::D2 => size.depth == 1,
+        TextureDimension::D3 => descriptor.array_layer_count == 1,
+    };
+    let valid_sample_count = match descriptor.sample_count {
+        1 => true,
+        4 => {
+            descriptor.mip_level_count == 1 &&
+                descriptor.array_layer_count == 1 &&
+                descriptor.dimension == TextureDimension::D2
+        },
+        _ => false,
+    };
+    size.width > 0 &&
+        size.height > 0 &&
+        size.depth > 0 &&
+        descriptor.array_layer_count > 0 &&
+        descriptor.mip_level_count > 0 &&
+        descriptor.mip_level_count <= max_mip_level_count &&
+        !descriptor.usage.is_empty() &&
+        valid_dimension &&
+        valid_sample_count
 }
@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUOutOfMemoryErrorBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct GPUOutOfMemoryError {
+    reflector_: Reflector,
+}
+
+impl GPUOutOfMemoryError {
+    fn new_inherited() -> GPUOutOfMemoryError {
+        Self {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<GPUOutOfMemoryError> {
+        reflect_dom_object(
+            Box::new(GPUOutOfMemoryError::new_inherited()),
+            global,
+            GPUOutOfMemoryErrorBinding::Wrap,
+        )
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuoutofmemoryerror-gpuoutofmemoryerror
+    pub fn Constructor(global: &GlobalScope) -> DomRoot<GPUOutOfMemoryError> {
+        GPUOutOfMemoryError::new(global)
+    }
+}
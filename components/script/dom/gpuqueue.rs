@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUQueueBinding::{self, GPUQueueMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucommandbuffer::GPUCommandBuffer;
+use crate::dom::gpudevice::GPUDevice;
+use dom_struct::dom_struct;
+use std::collections::HashSet;
+use webgpu::{WebGPU, WebGPUQueue, WebGPURequest};
+
+#[dom_struct]
+pub struct GPUQueue {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    device: Dom<GPUDevice>,
+    label: DomRefCell<Option<DOMString>>,
+    queue: WebGPUQueue,
+}
+
+impl GPUQueue {
+    fn new_inherited(channel: WebGPU, device: &GPUDevice, queue: WebGPUQueue) -> GPUQueue {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            device: Dom::from_ref(device),
+            label: DomRefCell::new(None),
+            queue,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        device: &GPUDevice,
+        queue: WebGPUQueue,
+    ) -> DomRoot<GPUQueue> {
+        reflect_dom_object(
+            Box::new(GPUQueue::new_inherited(channel, device, queue)),
+            global,
+            GPUQueueBinding::Wrap,
+        )
+    }
+}
+
+impl GPUQueueMethods for GPUQueue {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuqueue-submit
+    fn Submit(&self, command_buffers: Vec<DomRoot<GPUCommandBuffer>>) {
+        // A command buffer can only be submitted once.
+        let mut ids = HashSet::new();
+        let valid = command_buffers
+            .iter()
+            .all(|command_buffer| command_buffer.is_valid() && ids.insert(command_buffer.id()));
+        if !valid {
+            return self
+                .device
+                .dispatch_validation_error("Invalid or already submitted GPUCommandBuffer");
+        }
+
+        for command_buffer in &command_buffers {
+            command_buffer.mark_submitted();
+        }
+        let command_buffers = command_buffers
+            .iter()
+            .map(|command_buffer| command_buffer.id().0)
+            .collect();
+        self.channel
+            .0
+            .send(WebGPURequest::Submit(self.queue, command_buffers))
+            .expect("Failed to submit WebGPU command buffers");
+    }
+}
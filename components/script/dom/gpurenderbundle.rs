@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPURenderBundleBinding::{
+    self, GPURenderBundleMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::wgpu::TextureFormat;
+use webgpu::RenderPassCommand;
+
+/// A render bundle, which wgpu doesn't support, so its commands are replayed
+/// into the render passes which execute it.
+#[dom_struct]
+pub struct GPURenderBundle {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    commands: Vec<RenderPassCommand>,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    color_formats: Vec<TextureFormat>,
+    sample_count: u32,
+    valid: bool,
+}
+
+impl GPURenderBundle {
+    fn new_inherited(
+        commands: Vec<RenderPassCommand>,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        valid: bool,
+    ) -> GPURenderBundle {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            commands,
+            color_formats,
+            sample_count,
+            valid,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        commands: Vec<RenderPassCommand>,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        valid: bool,
+    ) -> DomRoot<GPURenderBundle> {
+        reflect_dom_object(
+            Box::new(GPURenderBundle::new_inherited(
+                commands,
+                color_formats,
+                sample_count,
+                valid,
+            )),
+            global,
+            GPURenderBundleBinding::Wrap,
+        )
+    }
+}
+
+impl GPURenderBundle {
+    pub fn commands(&self) -> &[RenderPassCommand] {
+        &self.commands
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Whether this bundle can be executed by a pass with attachments of
+    /// `color_formats` and `sample_count`.
+    pub fn is_compatible(&self, color_formats: &[TextureFormat], sample_count: u32) -> bool {
+        self.color_formats == color_formats && self.sample_count == sample_count
+    }
+}
+
+impl GPURenderBundleMethods for GPURenderBundle {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+}
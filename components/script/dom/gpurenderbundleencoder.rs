@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPURenderBundleBinding::GPURenderBundleDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPURenderBundleEncoderBinding::{
+    self, GPURenderBundleEncoderMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpudevice::GPUDevice;
+use crate::dom::gpurenderbundle::GPURenderBundle;
+use crate::dom::gpurenderpipeline::GPURenderPipeline;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::mem;
+use webgpu::wgpu::TextureFormat;
+use webgpu::RenderPassCommand;
+
+#[dom_struct]
+pub struct GPURenderBundleEncoder {
+    reflector_: Reflector,
+    device: Dom<GPUDevice>,
+    label: DomRefCell<Option<DOMString>>,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    color_formats: Vec<TextureFormat>,
+    sample_count: u32,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    commands: DomRefCell<Vec<RenderPassCommand>>,
+    has_pipeline: Cell<bool>,
+    valid: Cell<bool>,
+    finished: Cell<bool>,
+}
+
+impl GPURenderBundleEncoder {
+    fn new_inherited(
+        device: &GPUDevice,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        valid: bool,
+    ) -> GPURenderBundleEncoder {
+        Self {
+            reflector_: Reflector::new(),
+            device: Dom::from_ref(device),
+            label: DomRefCell::new(None),
+            color_formats,
+            sample_count,
+            commands: DomRefCell::new(Vec::new()),
+            has_pipeline: Cell::new(false),
+            valid: Cell::new(valid),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        device: &GPUDevice,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        valid: bool,
+    ) -> DomRoot<GPURenderBundleEncoder> {
+        reflect_dom_object(
+            Box::new(GPURenderBundleEncoder::new_inherited(
+                device,
+                color_formats,
+                sample_count,
+                valid,
+            )),
+            global,
+            GPURenderBundleEncoderBinding::Wrap,
+        )
+    }
+}
+
+impl GPURenderBundleEncoderMethods for GPURenderBundleEncoder {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-setpipeline
+    fn SetPipeline(&self, pipeline: &GPURenderPipeline) {
+        if self.finished.get() {
+            return;
+        }
+        if !pipeline.is_valid() || !pipeline.is_compatible(&self.color_formats, self.sample_count) {
+            return self.valid.set(false);
+        }
+        self.has_pipeline.set(true);
+        self.commands
+            .borrow_mut()
+            .push(RenderPassCommand::SetPipeline(pipeline.id().0));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-draw
+    fn Draw(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        if self.finished.get() {
+            return;
+        }
+        if !self.has_pipeline.get() {
+            return self.valid.set(false);
+        }
+        self.commands.borrow_mut().push(RenderPassCommand::Draw(
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        ));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderbundleencoder-finish
+    fn Finish(&self, _descriptor: &GPURenderBundleDescriptor) -> DomRoot<GPURenderBundle> {
+        // An encoder can only be finished once.
+        let valid = self.valid.get() && !self.finished.get();
+        self.finished.set(true);
+        if !valid {
+            self.device
+                .dispatch_validation_error("Invalid GPURenderBundleEncoder");
+        }
+        let commands = mem::replace(&mut *self.commands.borrow_mut(), Vec::new());
+        GPURenderBundle::new(
+            &self.global(),
+            commands,
+            self.color_formats.clone(),
+            self.sample_count,
+            valid,
+        )
+    }
+}
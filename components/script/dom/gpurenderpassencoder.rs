@@ -0,0 +1,245 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPURenderPassEncoderBinding::{
+    self, GPUColorDict, GPURenderPassEncoderMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::DoubleSequenceOrGPUColorDict;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucommandencoder::GPUCommandEncoder;
+use crate::dom::gpurenderbundle::GPURenderBundle;
+use crate::dom::gpurenderpipeline::GPURenderPipeline;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::mem;
+use webgpu::wgpu::{Color, TextureFormat};
+use webgpu::{RenderPassColorAttachment, RenderPassCommand};
+
+#[dom_struct]
+pub struct GPURenderPassEncoder {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    encoder: Dom<GPUCommandEncoder>,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    color_attachments: DomRefCell<Vec<RenderPassColorAttachment>>,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    color_formats: Vec<TextureFormat>,
+    sample_count: u32,
+    /// The commands recorded so far, which are sent to the WGPU thread once
+    /// the pass ends.
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    commands: DomRefCell<Vec<RenderPassCommand>>,
+    /// Whether this is the pass being encoded by its encoder.
+    active: Cell<bool>,
+    has_pipeline: Cell<bool>,
+    valid: Cell<bool>,
+}
+
+impl GPURenderPassEncoder {
+    fn new_inherited(
+        encoder: &GPUCommandEncoder,
+        color_attachments: Vec<RenderPassColorAttachment>,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        active: bool,
+        valid: bool,
+    ) -> GPURenderPassEncoder {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            encoder: Dom::from_ref(encoder),
+            color_attachments: DomRefCell::new(color_attachments),
+            color_formats,
+            sample_count,
+            commands: DomRefCell::new(Vec::new()),
+            active: Cell::new(active),
+            has_pipeline: Cell::new(false),
+            valid: Cell::new(active && valid),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        encoder: &GPUCommandEncoder,
+        color_attachments: Vec<RenderPassColorAttachment>,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        active: bool,
+        valid: bool,
+    ) -> DomRoot<GPURenderPassEncoder> {
+        reflect_dom_object(
+            Box::new(GPURenderPassEncoder::new_inherited(
+                encoder,
+                color_attachments,
+                color_formats,
+                sample_count,
+                active,
+                valid,
+            )),
+            global,
+            GPURenderPassEncoderBinding::Wrap,
+        )
+    }
+}
+
+impl GPURenderPassEncoderMethods for GPURenderPassEncoder {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-setpipeline
+    fn SetPipeline(&self, pipeline: &GPURenderPipeline) {
+        if !self.active.get() {
+            return;
+        }
+        if !pipeline.is_valid() || !pipeline.is_compatible(&self.color_formats, self.sample_count) {
+            return self.valid.set(false);
+        }
+        self.has_pipeline.set(true);
+        self.commands
+            .borrow_mut()
+            .push(RenderPassCommand::SetPipeline(pipeline.id().0));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-draw
+    fn Draw(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        if !self.active.get() {
+            return;
+        }
+        if !self.has_pipeline.get() {
+            return self.valid.set(false);
+        }
+        self.commands.borrow_mut().push(RenderPassCommand::Draw(
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        ));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setviewport
+    fn SetViewport(
+        &self,
+        x: Finite<f32>,
+        y: Finite<f32>,
+        width: Finite<f32>,
+        height: Finite<f32>,
+        min_depth: Finite<f32>,
+        max_depth: Finite<f32>,
+    ) {
+        if !self.active.get() {
+            return;
+        }
+        let depth_range = 0.0..=1.0;
+        if *width < 0. ||
+            *height < 0. ||
+            !depth_range.contains(&*min_depth) ||
+            !depth_range.contains(&*max_depth)
+        {
+            return self.valid.set(false);
+        }
+        self.commands
+            .borrow_mut()
+            .push(RenderPassCommand::SetViewport(
+                *x, *y, *width, *height, *min_depth, *max_depth,
+            ));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setscissorrect
+    fn SetScissorRect(&self, x: u32, y: u32, width: u32, height: u32) {
+        if !self.active.get() {
+            return;
+        }
+        if width == 0 || height == 0 {
+            return self.valid.set(false);
+        }
+        self.commands
+            .borrow_mut()
+            .push(RenderPassCommand::SetScissorRect(x, y, width, height));
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setblendcolor
+    fn SetBlendColor(&self, color: DoubleSequenceOrGPUColorDict) {
+        if !self.active.get() {
+            return;
+        }
+        match convert_color(&color) {
+            Some(color) => self
+                .commands
+                .borrow_mut()
+                .push(RenderPassCommand::SetBlendColor(color)),
+            None => self.valid.set(false),
+        }
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-executebundles
+    fn ExecuteBundles(&self, bundles: Vec<DomRoot<GPURenderBundle>>) {
+        if !self.active.get() {
+            return;
+        }
+        let mut commands = self.commands.borrow_mut();
+        for bundle in &bundles {
+            if !bundle.is_valid() || !bundle.is_compatible(&self.color_formats, self.sample_count) {
+                return self.valid.set(false);
+            }
+            commands.extend_from_slice(bundle.commands());
+        }
+        // Executing bundles resets the state of the pass.
+        self.has_pipeline.set(false);
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-endpass
+    fn EndPass(&self) {
+        if !self.active.get() {
+            return;
+        }
+        self.active.set(false);
+        let color_attachments = mem::replace(&mut *self.color_attachments.borrow_mut(), Vec::new());
+        let commands = mem::replace(&mut *self.commands.borrow_mut(), Vec::new());
+        self.encoder
+            .end_render_pass(color_attachments, commands, self.valid.get());
+    }
+}
+
+/// Converts a `GPUColor`, whose sequences must have four components.
+pub fn convert_color(color: &DoubleSequenceOrGPUColorDict) -> Option<Color> {
+    match *color {
+        DoubleSequenceOrGPUColorDict::DoubleSequence(ref components) => {
+            color_from_components(components)
+        },
+        DoubleSequenceOrGPUColorDict::GPUColorDict(ref color) => Some(color_from_dict(color)),
+    }
+}
+
+pub fn color_from_components(components: &[Finite<f64>]) -> Option<Color> {
+    match components {
+        [r, g, b, a] => Some(Color {
+            r: **r,
+            g: **g,
+            b: **b,
+            a: **a,
+        }),
+        _ => None,
+    }
+}
+
+pub fn color_from_dict(color: &GPUColorDict) -> Color {
+    Color {
+        r: *color.r,
+        g: *color.g,
+        b: *color.b,
+        a: *color.a,
+    }
+}
@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPURenderPipelineBinding::{
+    self, GPURenderPipelineMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::wgpu::TextureFormat;
+use webgpu::WebGPURenderPipeline;
+
+#[dom_struct]
+pub struct GPURenderPipeline {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    render_pipeline: WebGPURenderPipeline,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    color_formats: Vec<TextureFormat>,
+    sample_count: u32,
+    valid: bool,
+}
+
+impl GPURenderPipeline {
+    fn new_inherited(
+        render_pipeline: WebGPURenderPipeline,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        valid: bool,
+    ) -> GPURenderPipeline {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            render_pipeline,
+            color_formats,
+            sample_count,
+            valid,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        render_pipeline: WebGPURenderPipeline,
+        color_formats: Vec<TextureFormat>,
+        sample_count: u32,
+        valid: bool,
+    ) -> DomRoot<GPURenderPipeline> {
+        reflect_dom_object(
+            Box::new(GPURenderPipeline::new_inherited(
+                render_pipeline,
+                color_formats,
+                sample_count,
+                valid,
+            )),
+            global,
+            GPURenderPipelineBinding::Wrap,
+        )
+    }
+}
+
+impl GPURenderPipeline {
+    pub fn id(&self) -> WebGPURenderPipeline {
+        self.render_pipeline
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Whether this pipeline can be used with attachments of `color_formats`
+    /// and `sample_count`.
+    pub fn is_compatible(&self, color_formats: &[TextureFormat], sample_count: u32) -> bool {
+        self.color_formats == color_formats && self.sample_count == sample_count
+    }
+}
+
+impl GPURenderPipelineMethods for GPURenderPipeline {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+}
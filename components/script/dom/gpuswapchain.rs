@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUSwapChainBinding::{self, GPUSwapChainMethods};
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::GPUTextureMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucanvascontext::GPUCanvasContext;
+use crate::dom::gputexture::GPUTexture;
+use dom_struct::dom_struct;
+use webgpu::{WebGPU, WebGPURequest};
+use webrender_api::ImageKey;
+
+#[dom_struct]
+pub struct GPUSwapChain {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    label: DomRefCell<Option<DOMString>>,
+    context: Dom<GPUCanvasContext>,
+    texture: Dom<GPUTexture>,
+}
+
+impl GPUSwapChain {
+    fn new_inherited(
+        channel: WebGPU,
+        context: &GPUCanvasContext,
+        texture: &GPUTexture,
+    ) -> GPUSwapChain {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            label: DomRefCell::new(None),
+            context: Dom::from_ref(context),
+            texture: Dom::from_ref(texture),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        context: &GPUCanvasContext,
+        texture: &GPUTexture,
+    ) -> DomRoot<GPUSwapChain> {
+        reflect_dom_object(
+            Box::new(GPUSwapChain::new_inherited(channel, context, texture)),
+            global,
+            GPUSwapChainBinding::Wrap,
+        )
+    }
+}
+
+impl GPUSwapChain {
+    /// Copies the texture of this swap chain into the WebRender image of
+    /// `image_key`.
+    pub fn present(&self, image_key: ImageKey) {
+        if !self.texture.is_valid() {
+            return;
+        }
+        let encoder_id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_command_encoder_id();
+        self.channel
+            .0
+            .send(WebGPURequest::SwapChainPresent(
+                image_key,
+                self.texture.id(),
+                encoder_id,
+            ))
+            .expect("Failed to present WebGPU SwapChain");
+    }
+
+    /// Destroys the texture of this swap chain, and the WebRender image of
+    /// `image_key` if any, when the swap chain is reconfigured.
+    pub fn destroy(&self, image_key: Option<ImageKey>) {
+        self.texture.Destroy();
+        if let Some(image_key) = image_key {
+            self.channel
+                .0
+                .send(WebGPURequest::DestroySwapChain(image_key))
+                .expect("Failed to destroy WebGPU SwapChain");
+        }
+    }
+}
+
+impl GPUSwapChainMethods for GPUSwapChain {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuswapchain-getcurrenttexture
+    fn GetCurrentTexture(&self) -> DomRoot<GPUTexture> {
+        self.context.mark_as_dirty();
+        DomRoot::from_ref(&*self.texture)
+    }
+}
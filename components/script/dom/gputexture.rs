@@ -0,0 +1,274 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::{
+    self, GPUTextureFormat, GPUTextureMethods,
+};
+use crate::dom::bindings::codegen::Bindings::GPUTextureViewBinding::{
+    GPUTextureAspect, GPUTextureViewDescriptor, GPUTextureViewDimension,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpudevice::GPUDevice;
+use crate::dom::gputextureview::GPUTextureView;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use std::cell::Cell;
+use webgpu::wgpu::{
+    Extent3d, TextureAspectFlags, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureViewDescriptor, TextureViewDimension,
+};
+use webgpu::{WebGPU, WebGPURequest, WebGPUTexture, WebGPUTextureView};
+
+#[dom_struct]
+pub struct GPUTexture {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    device: Dom<GPUDevice>,
+    label: DomRefCell<Option<DOMString>>,
+    texture: WebGPUTexture,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    descriptor: TextureDescriptor,
+    /// Unset once the texture is destroyed.
+    valid: Cell<bool>,
+}
+
+impl GPUTexture {
+    fn new_inherited(
+        channel: WebGPU,
+        device: &GPUDevice,
+        texture: WebGPUTexture,
+        descriptor: TextureDescriptor,
+        valid: bool,
+    ) -> GPUTexture {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            device: Dom::from_ref(device),
+            label: DomRefCell::new(None),
+            texture,
+            descriptor,
+            valid: Cell::new(valid),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        device: &GPUDevice,
+        texture: WebGPUTexture,
+        descriptor: TextureDescriptor,
+        valid: bool,
+    ) -> DomRoot<GPUTexture> {
+        reflect_dom_object(
+            Box::new(GPUTexture::new_inherited(
+                channel, device, texture, descriptor, valid,
+            )),
+            global,
+            GPUTextureBinding::Wrap,
+        )
+    }
+}
+
+impl GPUTexture {
+    pub fn id(&self) -> WebGPUTexture {
+        self.texture
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid.get()
+    }
+
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+}
+
+impl GPUTextureMethods for GPUTexture {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gputexture-createview
+    fn CreateView(&self, descriptor: &GPUTextureViewDescriptor) -> DomRoot<GPUTextureView> {
+        let texture = &self.descriptor;
+        // A count of 0 selects all the remaining levels or layers.
+        let level_count = match descriptor.mipLevelCount {
+            0 => texture
+                .mip_level_count
+                .saturating_sub(descriptor.baseMipLevel),
+            count => count,
+        };
+        let layer_count = match descriptor.arrayLayerCount {
+            0 => texture
+                .array_layer_count
+                .saturating_sub(descriptor.baseArrayLayer),
+            count => count,
+        };
+        let format = descriptor
+            .format
+            .map_or(texture.format, convert_texture_format);
+        let dimension = match descriptor.dimension {
+            Some(dimension) => convert_texture_view_dimension(dimension),
+            None => match texture.dimension {
+                TextureDimension::D1 => TextureViewDimension::D1,
+                TextureDimension::D2 if texture.array_layer_count > 1 => {
+                    TextureViewDimension::D2Array
+                },
+                TextureDimension::D2 => TextureViewDimension::D2,
+                TextureDimension::D3 => TextureViewDimension::D3,
+            },
+        };
+        let aspect = match descriptor.aspect {
+            GPUTextureAspect::All => match format {
+                TextureFormat::Depth32Float | TextureFormat::Depth24Plus => {
+                    TextureAspectFlags::DEPTH
+                },
+                TextureFormat::Depth24PlusStencil8 => {
+                    TextureAspectFlags::DEPTH | TextureAspectFlags::STENCIL
+                },
+                _ => TextureAspectFlags::COLOR,
+            },
+            GPUTextureAspect::Depth_only => TextureAspectFlags::DEPTH,
+            GPUTextureAspect::Stencil_only => TextureAspectFlags::STENCIL,
+        };
+
+        let valid = self.valid.get() &&
+            level_count > 0 &&
+            layer_count > 0 &&
+            descriptor.baseMipLevel + level_count <= texture.mip_level_count &&
+            descriptor.baseArrayLayer + layer_count <= texture.array_layer_count &&
+            format == texture.format;
+
+        let id = self
+            .global()
+            .as_window()
+            .Navigator()
+            .create_texture_view_id();
+        let texture_view = if valid {
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.channel
+                .0
+                .send(WebGPURequest::CreateTextureView(
+                    sender,
+                    self.texture,
+                    id,
+                    TextureViewDescriptor {
+                        format,
+                        dimension,
+                        aspect,
+                        base_mip_level: descriptor.baseMipLevel,
+                        level_count,
+                        base_array_layer: descriptor.baseArrayLayer,
+                        array_layer_count: layer_count,
+                    },
+                ))
+                .expect("Failed to create WebGPU TextureView");
+            receiver.recv().unwrap()
+        } else {
+            self.device
+                .dispatch_validation_error("Invalid GPUTextureViewDescriptor");
+            WebGPUTextureView(id)
+        };
+
+        // The size of the view is the one of its base level.
+        let size = Extent3d {
+            width: (texture.size.width >> descriptor.baseMipLevel).max(1),
+            height: (texture.size.height >> descriptor.baseMipLevel).max(1),
+            depth: texture.size.depth,
+        };
+        GPUTextureView::new(&self.global(), self, texture_view, format, size, valid)
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gputexture-destroy
+    fn Destroy(&self) {
+        if !self.valid.get() {
+            return;
+        }
+        self.valid.set(false);
+        self.channel
+            .0
+            .send(WebGPURequest::DestroyTexture(self.texture))
+            .expect("Failed to destroy WebGPU Texture");
+    }
+}
+
+pub fn convert_texture_format(format: GPUTextureFormat) -> TextureFormat {
+    match format {
+        GPUTextureFormat::R8unorm => TextureFormat::R8Unorm,
+        GPUTextureFormat::R8snorm => TextureFormat::R8Snorm,
+        GPUTextureFormat::R8uint => TextureFormat::R8Uint,
+        GPUTextureFormat::R8sint => TextureFormat::R8Sint,
+        GPUTextureFormat::R16uint => TextureFormat::R16Uint,
+        GPUTextureFormat::R16sint => TextureFormat::R16Sint,
+        GPUTextureFormat::R16float => TextureFormat::R16Float,
+        GPUTextureFormat::Rg8unorm => TextureFormat::Rg8Unorm,
+        GPUTextureFormat::Rg8snorm => TextureFormat::Rg8Snorm,
+        GPUTextureFormat::Rg8uint => TextureFormat::Rg8Uint,
+        GPUTextureFormat::Rg8sint => TextureFormat::Rg8Sint,
+        GPUTextureFormat::R32uint => TextureFormat::R32Uint,
+        GPUTextureFormat::R32sint => TextureFormat::R32Sint,
+        GPUTextureFormat::R32float => TextureFormat::R32Float,
+        GPUTextureFormat::Rg16uint => TextureFormat::Rg16Uint,
+        GPUTextureFormat::Rg16sint => TextureFormat::Rg16Sint,
+        GPUTextureFormat::Rg16float => TextureFormat::Rg16Float,
+        GPUTextureFormat::Rgba8unorm => TextureFormat::Rgba8Unorm,
+        GPUTextureFormat::Rgba8unorm_srgb => TextureFormat::Rgba8UnormSrgb,
+        GPUTextureFormat::Rgba8snorm => TextureFormat::Rgba8Snorm,
+        GPUTextureFormat::Rgba8uint => TextureFormat::Rgba8Uint,
+        GPUTextureFormat::Rgba8sint => TextureFormat::Rgba8Sint,
+        GPUTextureFormat::Bgra8unorm => TextureFormat::Bgra8Unorm,
+        GPUTextureFormat::Bgra8unorm_srgb => TextureFormat::Bgra8UnormSrgb,
+        GPUTextureFormat::Rgb10a2unorm => TextureFormat::Rgb10a2Unorm,
+        GPUTextureFormat::Rg11b10float => TextureFormat::Rg11b10Float,
+        GPUTextureFormat::Rg32uint => TextureFormat::Rg32Uint,
+        GPUTextureFormat::Rg32sint => TextureFormat::Rg32Sint,
+        GPUTextureFormat::Rg32float => TextureFormat::Rg32Float,
+        GPUTextureFormat::Rgba16uint => TextureFormat::Rgba16Uint,
+        GPUTextureFormat::Rgba16sint => TextureFormat::Rgba16Sint,
+        GPUTextureFormat::Rgba16float => TextureFormat::Rgba16Float,
+        GPUTextureFormat::Rgba32uint => TextureFormat::Rgba32Uint,
+        GPUTextureFormat::Rgba32sint => TextureFormat::Rgba32Sint,
+        GPUTextureFormat::Rgba32float => TextureFormat::Rgba32Float,
+        GPUTextureFormat::Depth32float => TextureFormat::Depth32Float,
+        GPUTextureFormat::Depth24plus => TextureFormat::Depth24Plus,
+        GPUTextureFormat::Depth24plus_stencil8 => TextureFormat::Depth24PlusStencil8,
+    }
+}
+
+/// Whether `format` can be used by color attachments.
+pub fn is_color_renderable(format: TextureFormat) -> bool {
+    match format {
+        TextureFormat::R8Snorm |
+        TextureFormat::Rg8Snorm |
+        TextureFormat::Rgba8Snorm |
+        TextureFormat::Rg11b10Float |
+        TextureFormat::Depth32Float |
+        TextureFormat::Depth24Plus |
+        TextureFormat::Depth24PlusStencil8 => false,
+        _ => true,
+    }
+}
+
+fn convert_texture_view_dimension(dimension: GPUTextureViewDimension) -> TextureViewDimension {
+    match dimension {
+        GPUTextureViewDimension::_1d => TextureViewDimension::D1,
+        GPUTextureViewDimension::_2d => TextureViewDimension::D2,
+        GPUTextureViewDimension::_2d_array => TextureViewDimension::D2Array,
+        GPUTextureViewDimension::Cube => TextureViewDimension::Cube,
+        GPUTextureViewDimension::Cube_array => TextureViewDimension::CubeArray,
+        GPUTextureViewDimension::_3d => TextureViewDimension::D3,
+    }
+}
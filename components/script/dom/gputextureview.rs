@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUTextureViewBinding::{self, GPUTextureViewMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gputexture::GPUTexture;
+use dom_struct::dom_struct;
+use webgpu::wgpu::{Extent3d, TextureFormat, TextureUsage};
+use webgpu::WebGPUTextureView;
+
+#[dom_struct]
+pub struct GPUTextureView {
+    reflector_: Reflector,
+    label: DomRefCell<Option<DOMString>>,
+    texture: Dom<GPUTexture>,
+    texture_view: WebGPUTextureView,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    format: TextureFormat,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    size: Extent3d,
+    valid: bool,
+}
+
+impl GPUTextureView {
+    fn new_inherited(
+        texture: &GPUTexture,
+        texture_view: WebGPUTextureView,
+        format: TextureFormat,
+        size: Extent3d,
+        valid: bool,
+    ) -> GPUTextureView {
+        Self {
+            reflector_: Reflector::new(),
+            label: DomRefCell::new(None),
+            texture: Dom::from_ref(texture),
+            texture_view,
+            format,
+            size,
+            valid,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        texture: &GPUTexture,
+        texture_view: WebGPUTextureView,
+        format: TextureFormat,
+        size: Extent3d,
+        valid: bool,
+    ) -> DomRoot<GPUTextureView> {
+        reflect_dom_object(
+            Box::new(GPUTextureView::new_inherited(
+                texture,
+                texture_view,
+                format,
+                size,
+                valid,
+            )),
+            global,
+            GPUTextureViewBinding::Wrap,
+        )
+    }
+}
+
+impl GPUTextureView {
+    pub fn id(&self) -> WebGPUTextureView {
+        self.texture_view
+    }
+
+    /// Views of destroyed textures are invalid.
+    pub fn is_valid(&self) -> bool {
+        self.valid && self.texture.is_valid()
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn size(&self) -> Extent3d {
+        self.size
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.texture.descriptor().sample_count
+    }
+
+    pub fn is_output_attachment(&self) -> bool {
+        self.texture
+            .descriptor()
+            .usage
+            .contains(TextureUsage::OUTPUT_ATTACHMENT)
+    }
+}
+
+impl GPUTextureViewMethods for GPUTextureView {
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn GetLabel(&self) -> Option<DOMString> {
+        self.label.borrow().clone()
+    }
+
+    /// https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label
+    fn SetLabel(&self, value: Option<DOMString>) {
+        *self.label.borrow_mut() = value;
+    }
+}
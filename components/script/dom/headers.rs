@@ -301,7 +301,7 @@ impl Iterable for Headers {
     }
 }
 
-fn is_cors_safelisted_request_content_type(value: &[u8]) -> bool {
+pub fn is_cors_safelisted_request_content_type(value: &[u8]) -> bool {
     let value_string = if let Ok(s) = str::from_utf8(value) {
         s
     } else {
@@ -2,12 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::HistoryBinding::ScrollRestoration;
 use crate::dom::bindings::codegen::Bindings::LocationBinding::LocationBinding::LocationMethods;
-use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationType;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::{DOMString, USVString};
@@ -19,6 +23,7 @@ use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::popstateevent::PopStateEvent;
 use crate::dom::window::Window;
 use crate::script_runtime::JSContext;
+use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use js::jsapi::Heap;
 use js::jsval::{JSVal, NullValue, UndefinedValue};
@@ -27,15 +32,26 @@ use msg::constellation_msg::{HistoryStateId, TraversalDirection};
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
 use profile_traits::ipc::channel;
-use script_traits::{ScriptMsg, StructuredSerializedData};
+use script_traits::{HistoryEntryReplacement, ScriptMsg, StructuredSerializedData};
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::collections::HashMap;
 
+#[derive(Clone, Copy)]
 enum PushOrReplace {
     Push,
     Replace,
 }
 
+/// What a session history entry of this document remembers for when it is
+/// traversed back to.
+/// <https://html.spec.whatwg.org/multipage/#persisted-user-state>
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+struct PersistedUserState {
+    scroll_restoration: ScrollRestoration,
+    scroll_position: (f64, f64),
+}
+
 // https://html.spec.whatwg.org/multipage/#the-history-interface
 #[dom_struct]
 pub struct History {
@@ -44,6 +60,12 @@ pub struct History {
     #[ignore_malloc_size_of = "mozjs"]
     state: Heap<JSVal>,
     state_id: Cell<Option<HistoryStateId>>,
+    /// The scroll restoration mode of the current session history entry.
+    scroll_restoration: Cell<ScrollRestoration>,
+    /// The persisted user state of the other session history entries of
+    /// this document, keyed by their history state and URL.
+    persisted_user_states:
+        DomRefCell<HashMap<(Option<HistoryStateId>, ServoUrl), PersistedUserState>>,
 }
 
 impl History {
@@ -55,6 +77,8 @@ impl History {
             window: Dom::from_ref(&window),
             state: state,
             state_id: Cell::new(None),
+            scroll_restoration: Cell::new(ScrollRestoration::Auto),
+            persisted_user_states: DomRefCell::new(HashMap::new()),
         }
     }
 
@@ -81,10 +105,33 @@ impl History {
         Ok(())
     }
 
+    /// Remembers the scroll position and scroll restoration mode of the
+    /// current session history entry, which is about to be left.
+    pub fn persist_user_state(&self) {
+        let key = (self.state_id.get(), self.window.Document().url());
+        let state = PersistedUserState {
+            scroll_restoration: self.scroll_restoration.get(),
+            scroll_position: (self.window.ScrollX() as f64, self.window.ScrollY() as f64),
+        };
+        self.persisted_user_states.borrow_mut().insert(key, state);
+    }
+
     // https://html.spec.whatwg.org/multipage/#history-traversal
     // Steps 5-16
     #[allow(unsafe_code)]
     pub fn activate_state(&self, state_id: Option<HistoryStateId>, url: ServoUrl) {
+        let navigation = self.window.navigation_api();
+        if let Some(ref navigation) = navigation {
+            let destination_entry = navigation.entry_for_traversal(state_id, &url);
+            navigation.fire_navigate_event(
+                NavigationType::Traverse,
+                &url,
+                destination_entry.as_deref(),
+            );
+        }
+
+        self.persist_user_state();
+
         // Steps 5
         let document = self.window.Document();
         let old_url = document.url().clone();
@@ -93,14 +140,17 @@ impl History {
         // Step 6
         let hash_changed = old_url.fragment() != url.fragment();
 
-        // Step 8
-        if let Some(fragment) = url.fragment() {
-            document.check_and_scroll_fragment(fragment);
-        }
-
         // Step 11
         let state_changed = state_id != self.state_id.get();
         self.state_id.set(state_id);
+        let persisted_user_state = self
+            .persisted_user_states
+            .borrow()
+            .get(&(state_id, url.clone()))
+            .cloned();
+        self.scroll_restoration.set(
+            persisted_user_state.map_or(ScrollRestoration::Auto, |state| state.scroll_restoration),
+        );
         let serialized_data = match state_id {
             Some(state_id) => {
                 let (tx, rx) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
@@ -119,6 +169,10 @@ impl History {
                 let data = StructuredSerializedData {
                     serialized: data,
                     ports: None,
+                    offscreen_canvases: None,
+                    image_bitmaps: None,
+                    transform_streams: None,
+                    shared_memory: None,
                 };
                 let global_scope = self.window.upcast::<GlobalScope>();
                 rooted!(in(*global_scope.get_cx()) let mut state = UndefinedValue());
@@ -132,7 +186,10 @@ impl History {
             },
         }
 
-        // TODO: Queue events on DOM Manipulation task source if non-blocking flag is set.
+        if let Some(navigation) = navigation {
+            navigation.commit_ongoing_navigation(NavigationType::Traverse);
+        }
+
         // Step 16.1
         if state_changed {
             PopStateEvent::dispatch_jsval(
@@ -142,19 +199,47 @@ impl History {
             );
         }
 
+        // Step 16.2: Restore the scroll position if the entry asks for it, and
+        // only fall back to scrolling to the fragment when there is none.
+        match persisted_user_state {
+            Some(state) => {
+                if let Some(fragment) = url.fragment() {
+                    document.set_target_element(document.find_fragment_node(fragment).as_deref());
+                }
+                if state.scroll_restoration == ScrollRestoration::Auto {
+                    let (x, y) = state.scroll_position;
+                    self.window.scroll(x, y, ScrollBehavior::Instant);
+                }
+            },
+            None => {
+                if let Some(fragment) = url.fragment() {
+                    document.check_and_scroll_fragment(fragment);
+                }
+            },
+        }
+
         // Step 16.3
         if hash_changed {
-            let event = HashChangeEvent::new(
-                &self.window,
-                atom!("hashchange"),
-                false,
-                false,
-                old_url.into_string(),
-                url.into_string(),
-            );
-            event
-                .upcast::<Event>()
-                .fire(self.window.upcast::<EventTarget>());
+            let window = Trusted::new(&*self.window);
+            let old_url = old_url.into_string();
+            let new_url = url.into_string();
+            let task = task!(hashchange_event: move || {
+                let window = window.root();
+                let event = HashChangeEvent::new(
+                    &window,
+                    atom!("hashchange"),
+                    false,
+                    false,
+                    old_url,
+                    new_url,
+                );
+                event.upcast::<Event>().fire(window.upcast::<EventTarget>());
+            });
+            let _ = self
+                .window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue(task, self.window.upcast::<GlobalScope>());
         }
     }
 
@@ -205,18 +290,8 @@ impl History {
                     Err(_) => return Err(Error::Security),
                 };
 
-                // Step 6.4
-                if new_url.scheme() != document_url.scheme() ||
-                    new_url.host() != document_url.host() ||
-                    new_url.port() != document_url.port() ||
-                    new_url.username() != document_url.username() ||
-                    new_url.password() != document_url.password()
-                {
-                    return Err(Error::Security);
-                }
-
-                // Step 6.5
-                if new_url.origin() != document_url.origin() {
+                // Steps 6.4-6.5
+                if !can_have_its_url_rewritten(&document_url, &new_url) {
                     return Err(Error::Security);
                 }
 
@@ -226,9 +301,57 @@ impl History {
             None => document.url(),
         };
 
+        let navigation = self.window.navigation_api();
+
         // Step 8
-        let state_id = match push_or_replace {
+        let state_id = self.add_or_replace_entry(&new_url, push_or_replace);
+
+        let _ = self.window.upcast::<GlobalScope>().resource_threads().send(
+            CoreResourceMsg::SetHistoryState(state_id, serialized_data.serialized.clone()),
+        );
+
+        // TODO: Step 9 Update current entry to represent a GET request
+        // https://github.com/servo/servo/issues/19156
+
+        // Step 10
+        document.set_url(new_url);
+
+        // Step 11
+        let global_scope = self.window.upcast::<GlobalScope>();
+        rooted!(in(*cx) let mut state = UndefinedValue());
+        if let Err(_) = structuredclone::read(&global_scope, serialized_data, state.handle_mut()) {
+            warn!("Error reading structuredclone data");
+        }
+
+        // Step 12
+        self.state.set(state.get());
+
+        // TODO: Step 13 Update Document's latest entry to current entry
+        // https://github.com/servo/servo/issues/19158
+
+        if let Some(navigation) = navigation {
+            let navigation_type = match push_or_replace {
+                PushOrReplace::Push => NavigationType::Push,
+                PushOrReplace::Replace => NavigationType::Replace,
+            };
+            navigation.update_entries_for_same_document_navigation(navigation_type, None);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a session history entry for `new_url` after the current one, or
+    /// replaces the current one with it, and returns its history state.
+    fn add_or_replace_entry(
+        &self,
+        new_url: &ServoUrl,
+        push_or_replace: PushOrReplace,
+    ) -> HistoryStateId {
+        match push_or_replace {
             PushOrReplace::Push => {
+                // The new entry inherits the scroll restoration mode of the
+                // current one, which is remembered as it is left.
+                self.persist_user_state();
                 let state_id = HistoryStateId::new();
                 self.state_id.set(Some(state_id));
                 let msg = ScriptMsg::PushHistoryState(state_id, new_url.clone());
@@ -256,35 +379,41 @@ impl History {
                     .send(msg);
                 state_id
             },
-        };
-
-        let _ = self.window.upcast::<GlobalScope>().resource_threads().send(
-            CoreResourceMsg::SetHistoryState(state_id, serialized_data.serialized.clone()),
-        );
-
-        // TODO: Step 9 Update current entry to represent a GET request
-        // https://github.com/servo/servo/issues/19156
-
-        // Step 10
-        document.set_url(new_url);
-
-        // Step 11
-        let global_scope = self.window.upcast::<GlobalScope>();
-        rooted!(in(*cx) let mut state = UndefinedValue());
-        if let Err(_) = structuredclone::read(&global_scope, serialized_data, state.handle_mut()) {
-            warn!("Error reading structuredclone data");
         }
+    }
 
-        // Step 12
-        self.state.set(state.get());
-
-        // TODO: Step 13 Update Document's latest entry to current entry
-        // https://github.com/servo/servo/issues/19158
+    /// Commits a navigation to `new_url` that was intercepted through the
+    /// navigation API, which changes the URL of the document and its session
+    /// history without loading anything, and leaves a null classic history
+    /// API state.
+    /// <https://html.spec.whatwg.org/multipage/#url-and-history-update-steps>
+    pub fn update_url_and_history(&self, new_url: ServoUrl, replace: HistoryEntryReplacement) {
+        let push_or_replace = match replace {
+            HistoryEntryReplacement::Enabled => PushOrReplace::Replace,
+            HistoryEntryReplacement::Disabled => PushOrReplace::Push,
+        };
+        self.add_or_replace_entry(&new_url, push_or_replace);
+        self.window.Document().set_url(new_url);
+        self.state.set(NullValue());
+    }
 
-        Ok(())
+    pub fn state_id(&self) -> Option<HistoryStateId> {
+        self.state_id.get()
     }
 }
 
+/// Returns whether the URL of a document can be changed from `document_url`
+/// to `target_url` without loading a new document.
+/// <https://html.spec.whatwg.org/multipage/#can-have-its-url-rewritten>
+pub fn can_have_its_url_rewritten(document_url: &ServoUrl, target_url: &ServoUrl) -> bool {
+    target_url.scheme() == document_url.scheme() &&
+        target_url.host() == document_url.host() &&
+        target_url.port() == document_url.port() &&
+        target_url.username() == document_url.username() &&
+        target_url.password() == document_url.password() &&
+        target_url.origin() == document_url.origin()
+}
+
 impl HistoryMethods for History {
     // https://html.spec.whatwg.org/multipage/#dom-history-state
     fn GetState(&self, _cx: JSContext) -> Fallible<JSVal> {
@@ -294,6 +423,23 @@ impl HistoryMethods for History {
         Ok(self.state.get())
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn GetScrollRestoration(&self) -> Fallible<ScrollRestoration> {
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        Ok(self.scroll_restoration.get())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn SetScrollRestoration(&self, value: ScrollRestoration) -> ErrorResult {
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        self.scroll_restoration.set(value);
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history-length
     fn GetLength(&self) -> Fallible<u32> {
         if !self.window.Document().is_fully_active() {
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::canvas_state::{create_canvas_paint_thread, unpremultiply_bgra_inplace};
 use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLCanvasElementBinding;
@@ -21,8 +22,10 @@ use crate::dom::canvasrenderingcontext2d::{
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucanvascontext::{GPUCanvasContext, LayoutCanvasWebGPUHelpers};
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage};
+use crate::dom::offscreencanvas::OffscreenCanvas;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::webgl2renderingcontext::WebGL2RenderingContext;
 use crate::dom::webglrenderingcontext::{
@@ -30,6 +33,8 @@ use crate::dom::webglrenderingcontext::{
 };
 use crate::euclidext::Size2DExt;
 use crate::script_runtime::JSContext;
+use crate::script_thread::ScriptThread;
+use crate::task_source::TaskSource;
 use base64;
 use canvas_traits::canvas::{CanvasId, CanvasMsg, FromScriptMsg};
 use canvas_traits::webgl::{GLContextAttributes, WebGLVersion};
@@ -39,10 +44,12 @@ use html5ever::{LocalName, Prefix};
 use image::png::PNGEncoder;
 use image::ColorType;
 use ipc_channel::ipc::IpcSharedMemory;
+use ipc_channel::router::ROUTER;
 use js::error::throw_type_error;
 use js::rust::HandleValue;
 use profile_traits::ipc;
 use script_layout_interface::{HTMLCanvasData, HTMLCanvasDataSource};
+use script_traits::transferable::OffscreenCanvasPlaceholder;
 use servo_config::pref;
 use std::cell::Ref;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
@@ -56,6 +63,11 @@ pub enum CanvasContext {
     Context2d(Dom<CanvasRenderingContext2D>),
     WebGL(Dom<WebGLRenderingContext>),
     WebGL2(Dom<WebGL2RenderingContext>),
+    WebGPU(Dom<GPUCanvasContext>),
+    /// Control of the canvas was transferred to an `OffscreenCanvas`,
+    /// which renders into the paint thread displayed by this element.
+    /// <https://html.spec.whatwg.org/multipage/#concept-canvas-placeholder>
+    Placeholder(OffscreenCanvasPlaceholder),
 }
 
 #[dom_struct]
@@ -100,6 +112,10 @@ impl HTMLCanvasElement {
                 },
                 CanvasContext::WebGL(ref context) => context.recreate(size),
                 CanvasContext::WebGL2(ref context) => context.recreate(size),
+                // The swap chain is sized when it is configured.
+                CanvasContext::WebGPU(_) => {},
+                // The size of the bitmap is controlled by the offscreen canvas.
+                CanvasContext::Placeholder(_) => {},
             }
         }
     }
@@ -138,6 +154,12 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<HTMLCanvasElement> {
                 Some(&CanvasContext::WebGL2(ref context)) => {
                     context.to_layout().canvas_data_source()
                 },
+                Some(&CanvasContext::WebGPU(ref context)) => {
+                    context.to_layout().canvas_data_source()
+                },
+                Some(&CanvasContext::Placeholder(ref placeholder)) => {
+                    HTMLCanvasDataSource::Image(Some(placeholder.ipc_renderer.clone()))
+                },
                 None => HTMLCanvasDataSource::Image(None),
             };
 
@@ -180,11 +202,10 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<HTMLCanvasElement> {
     fn get_canvas_id_for_layout(&self) -> CanvasId {
         unsafe {
             let canvas = &*self.unsafe_get();
-            if let &Some(CanvasContext::Context2d(ref context)) = canvas.context.borrow_for_layout()
-            {
-                context.to_layout().get_canvas_id()
-            } else {
-                CanvasId(0)
+            match canvas.context.borrow_for_layout() {
+                &Some(CanvasContext::Context2d(ref context)) => context.to_layout().get_canvas_id(),
+                &Some(CanvasContext::Placeholder(ref placeholder)) => placeholder.canvas_id,
+                _ => CanvasId(0),
             }
         }
     }
@@ -250,6 +271,21 @@ impl HTMLCanvasElement {
         Some(context)
     }
 
+    fn get_or_init_webgpu_context(&self) -> Option<DomRoot<GPUCanvasContext>> {
+        if !pref!(dom.webgpu.enabled) {
+            return None;
+        }
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                CanvasContext::WebGPU(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let context = GPUCanvasContext::new(&self.global(), self);
+        *self.context.borrow_mut() = Some(CanvasContext::WebGPU(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
     /// Gets the base WebGLRenderingContext for WebGL or WebGL 2, if exists.
     pub fn get_base_webgl_context(&self) -> Option<DomRoot<WebGLRenderingContext>> {
         match *self.context.borrow() {
@@ -299,6 +335,15 @@ impl HTMLCanvasElement {
 
                 Some(receiver.recv().unwrap())
             },
+            Some(&CanvasContext::Placeholder(ref placeholder)) => {
+                let (sender, receiver) =
+                    ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+                let msg =
+                    CanvasMsg::FromScript(FromScriptMsg::SendPixels(sender), placeholder.canvas_id);
+                placeholder.ipc_renderer.send(msg).unwrap();
+
+                Some(receiver.recv().unwrap())
+            },
             Some(&CanvasContext::WebGL(_)) => {
                 // TODO: add a method in WebGLRenderingContext to get the pixels.
                 return None;
@@ -307,6 +352,10 @@ impl HTMLCanvasElement {
                 // TODO: add a method in WebGL2RenderingContext to get the pixels.
                 return None;
             },
+            Some(&CanvasContext::WebGPU(_)) => {
+                // TODO: read back the pixels of the swap chain.
+                return None;
+            },
             None => None,
         };
 
@@ -333,8 +382,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         cx: JSContext,
         id: DOMString,
         options: HandleValue,
-    ) -> Option<RenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<RenderingContext>> {
+        if let Some(CanvasContext::Placeholder(_)) = *self.context.borrow() {
+            return Err(Error::InvalidState);
+        }
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(RenderingContext::CanvasRenderingContext2D),
@@ -344,8 +396,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
             "webgl2" | "experimental-webgl2" => self
                 .get_or_init_webgl2_context(cx, options)
                 .map(RenderingContext::WebGL2RenderingContext),
+            "gpupresent" => self
+                .get_or_init_webgpu_context()
+                .map(RenderingContext::GPUCanvasContext),
             _ => None,
-        }
+        })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-canvas-todataurl
@@ -382,6 +437,17 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
                     None => return Ok(USVString("data:,".into())),
                 }
             },
+            // TODO: read back the pixels of the swap chain.
+            Some(CanvasContext::WebGPU(_)) => return Ok(USVString("data:,".into())),
+            Some(CanvasContext::Placeholder(_)) => match self.fetch_all_data() {
+                // The offscreen canvas could have resized the bitmap since the last frame.
+                Some((Some(data), size)) if data.len() == size.area() as usize * 4 => {
+                    let mut data = data.to_vec();
+                    unpremultiply_bgra_inplace(&mut data);
+                    data
+                },
+                _ => return Ok(USVString("data:,".into())),
+            },
             None => {
                 // Each pixel is fully-transparent black.
                 vec![0; (self.Width() * self.Height() * 4) as usize]
@@ -401,6 +467,60 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         base64::encode_config_buf(&png, base64::STANDARD, &mut url);
         Ok(USVString(url))
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-canvas-transfercontroltooffscreen
+    fn TransferControlToOffscreen(&self) -> Fallible<DomRoot<OffscreenCanvas>> {
+        // Step 1.
+        if self.context.borrow().is_some() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2-3, the placeholder displays the bitmap of a paint thread that the
+        // offscreen canvas renders into, wherever it gets transferred, and that
+        // notifies this element whenever a new frame is committed.
+        let window = window_from_node(self);
+        let size = self.get_size().to_u64();
+        let (ipc_renderer, canvas_id) = create_canvas_paint_thread(window.upcast(), size);
+        let (commit_chan, commit_port) = ipc_channel::ipc::channel().unwrap();
+        // The route only knows the canvas by id, so that it does not keep the
+        // canvas and its document alive for as long as frames get committed.
+        let document = document_from_node(self);
+        document.add_offscreen_canvas_placeholder(canvas_id, self);
+        let pipeline_id = window.upcast::<GlobalScope>().pipeline_id();
+        let (task_source, canceller) = window
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        ROUTER.add_route(
+            commit_port.to_opaque(),
+            Box::new(move |_| {
+                let _ = task_source.queue_with_canceller(
+                    task!(update_canvas_placeholder: move || {
+                        let canvas = ScriptThread::find_document(pipeline_id)
+                            .and_then(|document| document.offscreen_canvas_placeholder(canvas_id));
+                        if let Some(canvas) = canvas {
+                            canvas.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        }
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let placeholder = OffscreenCanvasPlaceholder {
+            ipc_renderer,
+            canvas_id,
+            commit_chan,
+        };
+        *self.context.borrow_mut() = Some(CanvasContext::Placeholder(placeholder.clone()));
+
+        // Step 4.
+        Ok(OffscreenCanvas::new(
+            window.upcast(),
+            size.width,
+            size.height,
+            Some(self),
+            Some(placeholder),
+        ))
+    }
 }
 
 impl VirtualMethods for HTMLCanvasElement {
@@ -8,6 +8,7 @@ use crate::dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLButtonElementBinding::HTMLButtonElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormControlsCollectionBinding::HTMLFormControlsCollectionMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::HTMLFormElementMethods;
@@ -45,7 +46,6 @@ use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::node::{document_from_node, window_from_node};
 use crate::dom::node::{Node, NodeFlags, ShadowIncluding};
 use crate::dom::node::{UnbindContext, VecPreOrderInsertionHelper};
-use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
 use crate::task_source::TaskSource;
@@ -55,7 +55,6 @@ use headers::{ContentType, HeaderMapExt};
 use html5ever::{LocalName, Prefix};
 use hyper::Method;
 use mime::{self, Mime};
-use net_traits::http_percent_encode;
 use net_traits::request::Referrer;
 use script_traits::{HistoryEntryReplacement, LoadData, LoadOrigin};
 use servo_rand::random;
@@ -182,6 +181,16 @@ impl HTMLFormElementMethods for HTMLFormElement {
         self.reset(ResetFrom::FromForm);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-form-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.static_validation().is_ok()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-form-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.interactive_validation().is_ok()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-form-elements
     fn Elements(&self) -> DomRoot<HTMLFormControlsCollection> {
         #[derive(JSTraceable, MallocSizeOf)]
@@ -333,8 +342,6 @@ impl HTMLFormElement {
         // Step 6
         if submit_method_flag == SubmittedFrom::NotFromForm && !submitter.no_validate(self) {
             if self.interactive_validation().is_err() {
-                // TODO: Implement event handlers on all form control elements
-                self.upcast::<EventTarget>().fire_event(atom!("invalid"));
                 return;
             }
         }
@@ -581,13 +588,28 @@ impl HTMLFormElement {
     /// Interactively validate the constraints of form elements
     /// <https://html.spec.whatwg.org/multipage/#interactively-validate-the-constraints>
     fn interactive_validation(&self) -> Result<(), ()> {
-        // Step 1-3
-        let _unhandled_invalid_controls = match self.static_validation() {
+        // Step 1-2
+        let unhandled_invalid_controls = match self.static_validation() {
             Ok(()) => return Ok(()),
             Err(err) => err,
         };
-        // TODO: Report the problems with the constraints of at least one of
-        //       the elements given in unhandled invalid controls to the user
+
+        // Step 3
+        // Report the problem with the first unhandled control to the user,
+        // and focus it.
+        if let Some(field) = unhandled_invalid_controls.first() {
+            let target = field.as_event_target();
+            if let Some(validatable) = target
+                .downcast::<Element>()
+                .and_then(Element::as_maybe_validatable)
+            {
+                warn!("Validation error: {}", validatable.validation_message());
+            }
+            if let Some(html_elem) = target.downcast::<HTMLElement>() {
+                html_elem.Focus();
+            }
+        }
+
         // Step 4
         Err(())
     }
@@ -595,29 +617,15 @@ impl HTMLFormElement {
     /// Statitically validate the constraints of form elements
     /// <https://html.spec.whatwg.org/multipage/#statically-validate-the-constraints>
     fn static_validation(&self) -> Result<(), Vec<FormSubmittableElement>> {
-        let node = self.upcast::<Node>();
-        // FIXME(#3553): This is an incorrect way of getting controls owned by the
-        //               form, refactor this when html5ever's form owner PR lands
         // Step 1-3
-        let invalid_controls = node
-            .traverse_preorder(ShadowIncluding::No)
+        let invalid_controls = self
+            .controls
+            .borrow()
+            .iter()
             .filter_map(|field| {
-                if let Some(el) = field.downcast::<Element>() {
-                    if el.disabled_state() {
-                        None
-                    } else {
-                        let validatable = match el.as_maybe_validatable() {
-                            Some(v) => v,
-                            None => return None,
-                        };
-                        if !validatable.is_instance_validatable() {
-                            None
-                        } else if validatable.validate(ValidationFlags::empty()) {
-                            None
-                        } else {
-                            Some(FormSubmittableElement::from_element(&el))
-                        }
-                    }
+                let validatable = field.as_maybe_validatable()?;
+                if validatable.is_instance_validatable() && !validatable.satisfies_constraints() {
+                    Some(FormSubmittableElement::from_element(&field))
                 } else {
                     None
                 }
@@ -1312,7 +1320,6 @@ pub fn encode_multipart_form_data(
         if entry.name == "_charset_" && entry.ty == "hidden" {
             entry.value = FormDatumValue::String(DOMString::from(charset.clone()));
         }
-        // TODO: 3.2
 
         // Step 4
         // https://tools.ietf.org/html/rfc7578#section-4
@@ -1321,42 +1328,33 @@ pub fn encode_multipart_form_data(
         let mut boundary_bytes = format!("--{}\r\n", boundary).into_bytes();
         result.append(&mut boundary_bytes);
 
-        // TODO(eijebong): Everthing related to content-disposition it to redo once typed headers
-        // are capable of it.
+        // https://tools.ietf.org/html/rfc7578#section-4.2
+        result.extend_from_slice(b"Content-Disposition: form-data; name=\"");
+        result.append(&mut encode_multipart_header_value(
+            &normalize_line_breaks(&entry.name),
+            encoding,
+        ));
+        result.push(b'"');
+
         match entry.value {
             FormDatumValue::String(ref s) => {
-                let content_disposition = format!("form-data; name=\"{}\"", entry.name);
-                let mut bytes =
-                    format!("Content-Disposition: {}\r\n\r\n{}", content_disposition, s)
-                        .into_bytes();
-                result.append(&mut bytes);
+                result.extend_from_slice(b"\r\n\r\n");
+                result.extend_from_slice(&encoding.encode(&normalize_line_breaks(s)).0);
             },
             FormDatumValue::File(ref f) => {
-                let extra = if charset.to_lowercase() == "utf-8" {
-                    format!(
-                        "filename=\"{}\"",
-                        String::from_utf8(f.name().as_bytes().into()).unwrap()
-                    )
-                } else {
-                    format!(
-                        "filename*=\"{}\"''{}",
-                        charset,
-                        http_percent_encode(f.name().as_bytes())
-                    )
-                };
+                result.extend_from_slice(b"; filename=\"");
+                result.append(&mut encode_multipart_header_value(&f.name(), encoding));
+                result.push(b'"');
 
-                let content_disposition = format!("form-data; name=\"{}\"; {}", entry.name, extra);
                 // https://tools.ietf.org/html/rfc7578#section-4.4
-                let content_type: Mime = f
-                    .upcast::<Blob>()
-                    .Type()
-                    .parse()
-                    .unwrap_or(mime::TEXT_PLAIN);
-                let mut type_bytes = format!(
-                    "Content-Disposition: {}\r\ncontent-type: {}\r\n\r\n",
-                    content_disposition, content_type
-                )
-                .into_bytes();
+                let content_type = f.upcast::<Blob>().Type();
+                let content_type = if content_type.is_empty() {
+                    "application/octet-stream"
+                } else {
+                    &*content_type
+                };
+                let mut type_bytes =
+                    format!("\r\nContent-Type: {}\r\n\r\n", content_type).into_bytes();
                 result.append(&mut type_bytes);
 
                 let mut bytes = f.upcast::<Blob>().get_bytes().unwrap_or(vec![]);
@@ -1364,14 +1362,51 @@ pub fn encode_multipart_form_data(
                 result.append(&mut bytes);
             },
         }
+
+        result.extend_from_slice(b"\r\n");
     }
 
-    let mut boundary_bytes = format!("\r\n--{}--\r\n", boundary).into_bytes();
+    let mut boundary_bytes = format!("--{}--\r\n", boundary).into_bytes();
     result.append(&mut boundary_bytes);
 
     result
 }
 
+/// Replaces every CR not followed by a LF, and every LF not preceded by a CR,
+/// by a CRLF pair.
+fn normalize_line_breaks(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                result.push_str("\r\n");
+            },
+            '\n' => result.push_str("\r\n"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Encodes a name or filename of the Content-Disposition header of a part,
+/// escaping the bytes that would end its quoted string.
+fn encode_multipart_header_value(value: &str, encoding: &'static Encoding) -> Vec<u8> {
+    let mut result = vec![];
+    for &byte in encoding.encode(value).0.iter() {
+        match byte {
+            b'\n' => result.extend_from_slice(b"%0A"),
+            b'\r' => result.extend_from_slice(b"%0D"),
+            b'"' => result.extend_from_slice(b"%22"),
+            byte => result.push(byte),
+        }
+    }
+    result
+}
+
 // https://tools.ietf.org/html/rfc7578#section-4.1
 pub fn generate_boundary() -> String {
     let i1 = random::<u32>();
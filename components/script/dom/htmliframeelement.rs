@@ -141,8 +141,8 @@ impl HTMLIFrameElement {
             let window_proxy = self.GetContentWindow();
             if let Some(window_proxy) = window_proxy {
                 // Important re security. See https://github.com/servo/servo/issues/23373
-                // TODO: check according to https://w3c.github.io/webappsec-csp/#should-block-navigation-request
-                if ScriptThread::check_load_origin(&load_data.load_origin, &document.url().origin())
+                if ScriptThread::check_load_origin(&load_data.load_origin, &document.url().origin()) &&
+                    !document.should_javascript_url_navigation_be_blocked(&load_data.url)
                 {
                     ScriptThread::eval_js_url(&window_proxy.global(), &mut load_data);
                 }
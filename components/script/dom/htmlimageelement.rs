@@ -56,7 +56,7 @@ use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
 use mime::{self, Mime};
 use msg::constellation_msg::PipelineId;
-use net_traits::image::base::{Image, ImageMetadata};
+use net_traits::image::base::{is_supported_image_type, Image, ImageMetadata};
 use net_traits::image_cache::UsePlaceholder;
 use net_traits::image_cache::{CanRequestImages, CorsStatus, ImageCache, ImageOrMetadataAvailable};
 use net_traits::image_cache::{ImageResponder, ImageResponse, ImageState, PendingImageId};
@@ -611,13 +611,8 @@ impl HTMLImageElement {
 
             // Step 4.8
             if let Some(x) = element.get_attribute(&ns!(), &local_name!("type")) {
-                // TODO Handle unsupported mime type
-                let mime = x.value().parse::<Mime>();
-                match mime {
-                    Ok(m) => match m.type_() {
-                        mime::IMAGE => (),
-                        _ => continue,
-                    },
+                match x.value().parse::<Mime>() {
+                    Ok(ref mime) if is_supported_image_type(mime) => {},
                     _ => continue,
                 }
             }
@@ -648,16 +643,11 @@ impl HTMLImageElement {
         let document = document_from_node(self);
         let quirks_mode = document.quirks_mode();
         let document_url = &document.url();
-        // FIXME(emilio): This should do the same that we do for other media
-        // lists regarding the rule type and such, though it doesn't really
-        // matter right now...
-        //
-        // Also, ParsingMode::all() is wrong, and should be DEFAULT.
         let context = ParserContext::new(
             Origin::Author,
             document_url,
-            Some(CssRuleType::Style),
-            ParsingMode::all(),
+            Some(CssRuleType::Media),
+            ParsingMode::DEFAULT,
             quirks_mode,
             None,
             None,
@@ -1296,6 +1286,11 @@ impl HTMLImageElement {
         useMapElements.map(|mapElem| mapElem.get_area_elements())
     }
 
+    /// The decoded image of the current request, if it is available.
+    pub fn image(&self) -> Option<Arc<Image>> {
+        self.current_request.borrow().image.clone()
+    }
+
     pub fn same_origin(&self, origin: &MutableOrigin) -> bool {
         if let Some(ref image) = self.current_request.borrow().image {
             return image.cors_status == CorsStatus::Safe;
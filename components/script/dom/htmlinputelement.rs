@@ -16,6 +16,7 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::blob::BlobImpl;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
 use crate::dom::element::{
@@ -38,8 +39,8 @@ use crate::dom::node::{document_from_node, window_from_node};
 use crate::dom::node::{BindContext, Node, NodeDamage, UnbindContext};
 use crate::dom::nodelist::NodeList;
 use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
-use crate::dom::validation::Validatable;
-use crate::dom::validitystate::ValidationFlags;
+use crate::dom::validation::{is_barred_by_datalist_ancestor, Validatable};
+use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::textinput::KeyReaction::{
     DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction,
@@ -48,16 +49,18 @@ use crate::textinput::Lines::Single;
 use crate::textinput::{Direction, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes};
 use caseless::compatibility_caseless_match_str;
 use dom_struct::dom_struct;
-use embedder_traits::FilterPattern;
+use embedder_traits::{EmbedderMsg, FilterPattern};
 use html5ever::{LocalName, Prefix};
 use msg::constellation_msg::InputMethodType;
 use net_traits::blob_url_store::get_blob_origin;
 use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
+use regex::Regex;
 use script_layout_interface::rpc::TextIndexResponse;
 use script_traits::ScriptToConstellationChan;
 use servo_atoms::Atom;
+use servo_url::ServoUrl;
 use std::borrow::ToOwned;
 use std::cell::Cell;
 use std::ops::Range;
@@ -126,6 +129,50 @@ impl InputType {
         self.is_textual() || *self == InputType::Password
     }
 
+    // The types to which the pattern, maxlength and minlength attributes apply.
+    // https://html.spec.whatwg.org/multipage/#do-not-apply
+    fn has_length_constraints(&self) -> bool {
+        match *self {
+            InputType::Email |
+            InputType::Password |
+            InputType::Search |
+            InputType::Tel |
+            InputType::Text |
+            InputType::Url => true,
+
+            _ => false,
+        }
+    }
+
+    // The types to which the min, max and step attributes apply.
+    // https://html.spec.whatwg.org/multipage/#do-not-apply
+    fn has_range_constraints(&self) -> bool {
+        match *self {
+            InputType::Date |
+            InputType::DatetimeLocal |
+            InputType::Month |
+            InputType::Number |
+            InputType::Range |
+            InputType::Time |
+            InputType::Week => true,
+
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#attr-input-readonly
+    fn has_readonly(&self) -> bool {
+        self.has_length_constraints() || (self.has_range_constraints() && *self != InputType::Range)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#attr-input-required
+    fn has_required(&self) -> bool {
+        match *self {
+            InputType::Checkbox | InputType::File | InputType::Radio => true,
+            _ => self.has_readonly(),
+        }
+    }
+
     fn to_str(&self) -> &str {
         match *self {
             InputType::Button => "button",
@@ -231,9 +278,13 @@ pub struct HTMLInputElement {
     activation_state: DomRefCell<InputActivationState>,
     // https://html.spec.whatwg.org/multipage/#concept-input-value-dirty-flag
     value_dirty: Cell<bool>,
+    /// Whether the value was last changed by a user edit rather than by script,
+    /// which the tooLong and tooShort constraints depend on.
+    value_changed_by_user: Cell<bool>,
 
     filelist: MutNullableDom<FileList>,
     form_owner: MutNullableDom<HTMLFormElement>,
+    validity_state: MutNullableDom<ValidityState>,
 }
 
 #[derive(JSTraceable)]
@@ -301,8 +352,10 @@ impl HTMLInputElement {
             )),
             activation_state: DomRefCell::new(InputActivationState::new()),
             value_dirty: Cell::new(false),
+            value_changed_by_user: Cell::new(false),
             filelist: MutNullableDom::new(None),
             form_owner: Default::default(),
+            validity_state: Default::default(),
         }
     }
 
@@ -636,6 +689,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
             ValueMode::Value => {
                 // Step 3.
                 self.value_dirty.set(true);
+                self.value_changed_by_user.set(false);
 
                 // Step 4.
                 self.sanitize_value(&mut value);
@@ -666,6 +720,8 @@ impl HTMLInputElementMethods for HTMLInputElement {
             },
         }
 
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all());
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         Ok(())
     }
@@ -800,6 +856,36 @@ impl HTMLInputElementMethods for HTMLInputElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.is_instance_validatable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> DomRoot<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-textarea/input-select
     fn Select(&self) {
         self.selection().dom_select();
@@ -906,6 +992,17 @@ fn broadcast_radio_checked(broadcaster: &HTMLInputElement, group: Option<&Atom>)
     do_broadcast(doc.upcast(), broadcaster, owner.as_deref(), group)
 }
 
+// https://html.spec.whatwg.org/multipage/#valid-e-mail-address
+fn is_valid_email_address(value: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(?:\.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$"
+        )
+        .unwrap();
+    }
+    RE.is_match(value)
+}
+
 // https://html.spec.whatwg.org/multipage/#radio-button-group
 fn in_same_group(
     other: &HTMLInputElement,
@@ -957,30 +1054,38 @@ impl HTMLInputElement {
             },
 
             InputType::File => {
-                let mut datums = vec![];
+                if name.is_empty() {
+                    return vec![];
+                }
 
                 // Step 3.2-3.7
-                let name = self.Name();
-
-                match self.GetFiles() {
-                    Some(fl) => {
-                        for f in fl.iter_files() {
-                            datums.push(FormDatum {
-                                ty: ty.clone(),
-                                name: name.clone(),
-                                value: FormDatumValue::File(DomRoot::from_ref(&f)),
-                            });
-                        }
-                    },
-                    None => {
-                        datums.push(FormDatum {
-                            // XXX(izgzhen): Spec says 'application/octet-stream' as the type,
-                            // but this is _type_ of element rather than content right?
-                            ty: ty.clone(),
-                            name: name.clone(),
-                            value: FormDatumValue::String(DOMString::from("")),
-                        })
-                    },
+                let mut datums: Vec<FormDatum> = self
+                    .GetFiles()
+                    .iter()
+                    .flat_map(|fl| fl.iter_files())
+                    .map(|f| FormDatum {
+                        ty: ty.clone(),
+                        name: name.clone(),
+                        value: FormDatumValue::File(DomRoot::from_ref(&f)),
+                    })
+                    .collect();
+
+                // Without a selected file, the entry is an empty file with an
+                // empty name and a type of application/octet-stream.
+                if datums.is_empty() {
+                    let window = window_from_node(self);
+                    let file = File::new(
+                        window.upcast(),
+                        BlobImpl::new_from_bytes(vec![]),
+                        DOMString::new(),
+                        None,
+                        "application/octet-stream",
+                    );
+                    datums.push(FormDatum {
+                        ty: ty.clone(),
+                        name: name.clone(),
+                        value: FormDatumValue::File(file),
+                    });
                 }
 
                 return datums;
@@ -1024,6 +1129,8 @@ impl HTMLInputElement {
             broadcast_radio_checked(self, self.radio_group_name().as_ref());
         }
 
+        self.update_value_missing_state();
+
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         //TODO: dispatch change event
     }
@@ -1035,6 +1142,208 @@ impl HTMLInputElement {
         !(self.upcast::<Element>().disabled_state() || self.ReadOnly())
     }
 
+    // https://html.spec.whatwg.org/multipage/#radio-button-group
+    fn radio_group_members(&self) -> Vec<DomRoot<HTMLInputElement>> {
+        let group = self.radio_group_name();
+        let mut members = vec![DomRoot::from_ref(self)];
+        match group {
+            None | Some(atom!("")) => return members,
+            _ => {},
+        }
+        if !self.upcast::<Node>().is_connected() {
+            return members;
+        }
+
+        //TODO: if not in document, use root ancestor instead of document
+        let owner = self.form_owner();
+        let doc = document_from_node(self);
+        members.extend(
+            doc.upcast::<Node>()
+                .query_selector_iter(DOMString::from("input[type=radio]"))
+                .unwrap()
+                .filter_map(DomRoot::downcast::<HTMLInputElement>)
+                .filter(|r| in_same_group(&r, owner.as_deref(), group.as_ref()) && self != &**r),
+        );
+        members
+    }
+
+    // The checkedness and required attribute of a radio button affect
+    // whether the whole group is suffering from being missing.
+    fn update_value_missing_state(&self) {
+        if self.input_type() == InputType::Radio {
+            for radio in self.radio_group_members() {
+                radio
+                    .validity_state()
+                    .perform_validation_and_update(ValidationFlags::VALUE_MISSING);
+            }
+        } else {
+            self.validity_state()
+                .perform_validation_and_update(ValidationFlags::VALUE_MISSING);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-value-string-number
+    fn convert_string_to_number(&self, value: &DOMString) -> Option<f64> {
+        match self.input_type() {
+            InputType::Date => value.date_string_to_number(),
+            InputType::DatetimeLocal => value.local_date_and_time_string_to_number(),
+            InputType::Month => value.month_string_to_number(),
+            InputType::Number | InputType::Range => value.to_floating_point_number(),
+            InputType::Time => value.time_string_to_number(),
+            InputType::Week => value.week_string_to_number(),
+            _ => None,
+        }
+    }
+
+    fn attribute_as_number(&self, local_name: &LocalName) -> Option<f64> {
+        self.upcast::<Element>()
+            .get_attribute(&ns!(), local_name)
+            .and_then(|attr| self.convert_string_to_number(&DOMString::from(&**attr.value())))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-min
+    fn minimum(&self) -> Option<f64> {
+        self.attribute_as_number(&local_name!("min")).or_else(|| {
+            // https://html.spec.whatwg.org/multipage/#range-state-(type=range):concept-input-min-default
+            if self.input_type() == InputType::Range {
+                Some(0.0)
+            } else {
+                None
+            }
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-max
+    fn maximum(&self) -> Option<f64> {
+        self.attribute_as_number(&local_name!("max")).or_else(|| {
+            // https://html.spec.whatwg.org/multipage/#range-state-(type=range):concept-input-max-default
+            if self.input_type() == InputType::Range {
+                Some(100.0)
+            } else {
+                None
+            }
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-step-default
+    // https://html.spec.whatwg.org/multipage/#concept-input-step-scale
+    fn default_step_and_scale_factor(&self) -> (f64, f64) {
+        match self.input_type() {
+            InputType::Date => (1.0, 86_400_000.0),
+            InputType::DatetimeLocal | InputType::Time => (60.0, 1_000.0),
+            InputType::Week => (1.0, 604_800_000.0),
+            _ => (1.0, 1.0),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-step
+    fn allowed_value_step(&self) -> Option<f64> {
+        let (default_step, scale_factor) = self.default_step_and_scale_factor();
+        let step = match self
+            .upcast::<Element>()
+            .get_attribute(&ns!(), &local_name!("step"))
+        {
+            Some(ref attr) if attr.value().eq_ignore_ascii_case("any") => return None,
+            Some(ref attr) => DOMString::from(&**attr.value())
+                .to_floating_point_number()
+                .filter(|step| *step > 0.0)
+                .map_or(default_step, |step| match self.input_type() {
+                    // Steps for these types are a whole number of days, weeks or months.
+                    InputType::Date | InputType::Month | InputType::Week => step.round().max(1.0),
+                    _ => step,
+                }),
+            None => default_step,
+        };
+        Some(step * scale_factor)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-min-zero
+    fn step_base(&self) -> f64 {
+        self.attribute_as_number(&local_name!("min"))
+            .or_else(|| self.attribute_as_number(&local_name!("value")))
+            .unwrap_or_else(|| {
+                // https://html.spec.whatwg.org/multipage/#week-state-(type=week):concept-input-step-default-base
+                if self.input_type() == InputType::Week {
+                    -259_200_000.0
+                } else {
+                    0.0
+                }
+            })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-being-missing
+    fn suffers_from_being_missing(&self, value: &DOMString) -> bool {
+        match self.input_type() {
+            // https://html.spec.whatwg.org/multipage/#checkbox-state-(type=checkbox)%3Asuffering-from-being-missing
+            InputType::Checkbox => self.Required() && !self.Checked(),
+            // https://html.spec.whatwg.org/multipage/#radio-button-state-(type=radio)%3Asuffering-from-being-missing
+            InputType::Radio => {
+                let members = self.radio_group_members();
+                members.iter().any(|radio| radio.Required()) &&
+                    !members.iter().any(|radio| radio.Checked())
+            },
+            // https://html.spec.whatwg.org/multipage/#file-upload-state-(type=file)%3Asuffering-from-being-missing
+            InputType::File => {
+                self.Required() &&
+                    self.filelist
+                        .get()
+                        .map_or(true, |files| files.Length() == 0)
+            },
+            // https://html.spec.whatwg.org/multipage/#the-required-attribute%3Asuffering-from-being-missing
+            ty if ty.has_required() => self.Required() && value.is_empty(),
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-a-type-mismatch
+    fn suffers_from_type_mismatch(&self, value: &DOMString) -> bool {
+        if value.is_empty() {
+            return false;
+        }
+
+        match self.input_type() {
+            // https://html.spec.whatwg.org/multipage/#url-state-(type=url)%3Asuffering-from-a-type-mismatch
+            InputType::Url => ServoUrl::parse(value).is_err(),
+            // https://html.spec.whatwg.org/multipage/#e-mail-state-(type=email)%3Asuffering-from-a-type-mismatch
+            // https://html.spec.whatwg.org/multipage/#e-mail-state-(type=email)%3Asuffering-from-a-type-mismatch-2
+            InputType::Email => {
+                if self.Multiple() {
+                    !split_commas(value).all(is_valid_email_address)
+                } else {
+                    !is_valid_email_address(value)
+                }
+            },
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-a-pattern-mismatch
+    fn suffers_from_pattern_mismatch(&self, value: &DOMString) -> bool {
+        if value.is_empty() || !self.input_type().has_length_constraints() {
+            return false;
+        }
+
+        let pattern = match self
+            .upcast::<Element>()
+            .get_attribute(&ns!(), &local_name!("pattern"))
+        {
+            Some(attr) => String::from(&**attr.value()),
+            None => return false,
+        };
+        // https://html.spec.whatwg.org/multipage/#compiled-pattern-regular-expression
+        // Patterns that fail to compile are ignored.
+        let regex = match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) => regex,
+            Err(_) => return false,
+        };
+
+        if self.input_type() == InputType::Email && self.Multiple() {
+            !split_commas(value).all(|value| regex.is_match(value))
+        } else {
+            !regex.is_match(value)
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-input-element:concept-form-reset-control
     pub fn reset(&self) {
         match self.input_type() {
@@ -1047,6 +1356,9 @@ impl HTMLInputElement {
         }
         self.textinput.borrow_mut().set_content(self.DefaultValue());
         self.value_dirty.set(false);
+        self.value_changed_by_user.set(false);
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all());
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
@@ -1126,12 +1438,94 @@ impl HTMLInputElement {
         } else {
             let filelist = FileList::new(&window, files);
             self.filelist.set(Some(&filelist));
+            self.validity_state()
+                .perform_validation_and_update(ValidationFlags::VALUE_MISSING);
 
             target.fire_bubbling_event(atom!("input"));
             target.fire_bubbling_event(atom!("change"));
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#concept-input-value-number-string
+    fn convert_number_to_string(&self, value: f64) -> Option<DOMString> {
+        match self.input_type() {
+            InputType::Date => DOMString::from_date_number(value),
+            InputType::DatetimeLocal => DOMString::from_local_date_and_time_number(value),
+            InputType::Time => DOMString::from_time_number(value),
+            _ => None,
+        }
+    }
+
+    // Brings a number within the range of the element and onto the
+    // nearest allowed value step, the way a picker constrains its choices.
+    fn constrain_number(&self, value: f64) -> f64 {
+        let minimum = self.minimum();
+        let maximum = self.maximum();
+        let mut value = value;
+        if let Some(step) = self.allowed_value_step() {
+            let base = self.step_base();
+            value = base + ((value - base) / step).round() * step;
+            if maximum.map_or(false, |max| value > max) {
+                value -= step;
+            }
+            if minimum.map_or(false, |min| value < min) {
+                value += step;
+            }
+        }
+        if let Some(max) = maximum {
+            value = value.min(max);
+        }
+        if let Some(min) = minimum {
+            value = value.max(min);
+        }
+        value
+    }
+
+    // https://html.spec.whatwg.org/multipage/#show-the-picker,-if-applicable
+    fn show_picker(&self) {
+        let ty = match self.input_type().as_ime_type() {
+            Some(ty) => ty,
+            None => return,
+        };
+        let current_value = self.Value().to_string();
+
+        let window = window_from_node(self);
+        let (chan, recv) = ipc::channel(self.global().time_profiler_chan().clone())
+            .expect("Error initializing channel");
+        window.send_to_embedder(EmbedderMsg::ShowInputPicker(ty, current_value, chan));
+
+        // The picker was dismissed, or the embedder doesn't provide one.
+        let mut value = match recv.recv() {
+            Ok(Some(value)) => DOMString::from(value),
+            _ => return,
+        };
+
+        self.sanitize_value(&mut value);
+        if let Some(number) = self.convert_string_to_number(&value) {
+            let constrained = self.constrain_number(number);
+            if constrained != number {
+                if let Some(constrained) = self.convert_number_to_string(constrained) {
+                    value = constrained;
+                }
+            }
+        }
+        if value == self.Value() {
+            return;
+        }
+
+        self.textinput.borrow_mut().set_content(value);
+        self.value_dirty.set(true);
+        self.value_changed_by_user.set(true);
+        self.update_placeholder_shown_state();
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all());
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"));
+        target.fire_bubbling_event(atom!("change"));
+    }
+
     // https://html.spec.whatwg.org/multipage/#value-sanitization-algorithm
     fn sanitize_value(&self, value: &mut DOMString) {
         match self.input_type() {
@@ -1255,7 +1649,7 @@ impl HTMLInputElement {
             Some(ref button) => {
                 if button.is_instance_activatable() {
                     synthetic_click_activation(
-                        button.as_element(),
+                        button.upcast::<Element>(),
                         ctrl_key,
                         shift_key,
                         alt_key,
@@ -1497,6 +1891,12 @@ impl VirtualMethods for HTMLInputElement {
             },
             _ => {},
         }
+
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all());
+        if *attr.local_name() == local_name!("required") {
+            self.update_value_missing_state();
+        }
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -1524,6 +1924,8 @@ impl VirtualMethods for HTMLInputElement {
         }
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all());
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -1539,6 +1941,8 @@ impl VirtualMethods for HTMLInputElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all());
     }
 
     fn handle_event(&self, event: &Event) {
@@ -1593,7 +1997,10 @@ impl VirtualMethods for HTMLInputElement {
                     },
                     DispatchInput => {
                         self.value_dirty.set(true);
+                        self.value_changed_by_user.set(true);
                         self.update_placeholder_shown_state();
+                        self.validity_state()
+                            .perform_validation_and_update(ValidationFlags::all());
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -1634,6 +2041,10 @@ impl VirtualMethods for HTMLInputElement {
                         .textinput
                         .borrow_mut()
                         .handle_compositionend(compositionevent);
+                    self.value_dirty.set(true);
+                    self.value_changed_by_user.set(true);
+                    self.validity_state()
+                        .perform_validation_and_update(ValidationFlags::all());
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                 }
                 event.mark_as_handled();
@@ -1657,13 +2068,115 @@ impl FormControl for HTMLInputElement {
 }
 
 impl Validatable for HTMLInputElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state(&self) -> DomRoot<ValidityState> {
+        self.validity_state
+            .or_init(|| ValidityState::new(&window_from_node(self), self.upcast()))
+    }
+
     fn is_instance_validatable(&self) -> bool {
-        // https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation
-        true
+        // https://html.spec.whatwg.org/multipage/#hidden-state-(type=hidden)%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#button-state-(type=button)%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#reset-button-state-(type=reset)%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#enabling-and-disabling-form-controls%3A-the-disabled-attribute%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#the-readonly-attribute%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#the-datalist-element%3Abarred-from-constraint-validation
+        match self.input_type() {
+            InputType::Hidden | InputType::Button | InputType::Reset => false,
+            ty => {
+                !(self.upcast::<Element>().disabled_state() ||
+                    (ty.has_readonly() && self.ReadOnly()) ||
+                    is_barred_by_datalist_ancestor(self.upcast()))
+            },
+        }
     }
-    fn validate(&self, _validate_flags: ValidationFlags) -> bool {
-        // call stub methods defined in validityState.rs file here according to the flags set in validate_flags
-        true
+
+    fn perform_validation(&self, validate_flags: ValidationFlags) -> ValidationFlags {
+        let mut failed_flags = ValidationFlags::empty();
+        let value = self.Value();
+
+        if validate_flags.contains(ValidationFlags::VALUE_MISSING) &&
+            self.suffers_from_being_missing(&value)
+        {
+            failed_flags.insert(ValidationFlags::VALUE_MISSING);
+        }
+
+        if validate_flags.contains(ValidationFlags::TYPE_MISMATCH) &&
+            self.suffers_from_type_mismatch(&value)
+        {
+            failed_flags.insert(ValidationFlags::TYPE_MISMATCH);
+        }
+
+        if validate_flags.contains(ValidationFlags::PATTERN_MISMATCH) &&
+            self.suffers_from_pattern_mismatch(&value)
+        {
+            failed_flags.insert(ValidationFlags::PATTERN_MISMATCH);
+        }
+
+        // https://html.spec.whatwg.org/multipage/#suffering-from-being-too-long
+        // https://html.spec.whatwg.org/multipage/#suffering-from-being-too-short
+        if validate_flags.intersects(ValidationFlags::TOO_LONG | ValidationFlags::TOO_SHORT) &&
+            self.input_type().has_length_constraints() &&
+            self.value_dirty.get() &&
+            self.value_changed_by_user.get()
+        {
+            let value_len = value.encode_utf16().count() as i32;
+            let max_length = self.maxlength.get();
+            let min_length = self.minlength.get();
+
+            if validate_flags.contains(ValidationFlags::TOO_LONG) &&
+                max_length != DEFAULT_MAX_LENGTH &&
+                value_len > max_length
+            {
+                failed_flags.insert(ValidationFlags::TOO_LONG);
+            }
+
+            if validate_flags.contains(ValidationFlags::TOO_SHORT) &&
+                min_length != DEFAULT_MIN_LENGTH &&
+                value_len != 0 &&
+                value_len < min_length
+            {
+                failed_flags.insert(ValidationFlags::TOO_SHORT);
+            }
+        }
+
+        if validate_flags.intersects(
+            ValidationFlags::RANGE_UNDERFLOW |
+                ValidationFlags::RANGE_OVERFLOW |
+                ValidationFlags::STEP_MISMATCH,
+        ) && self.input_type().has_range_constraints()
+        {
+            if let Some(value_as_number) = self.convert_string_to_number(&value) {
+                // https://html.spec.whatwg.org/multipage/#the-min-and-max-attributes%3Asuffering-from-an-underflow
+                if validate_flags.contains(ValidationFlags::RANGE_UNDERFLOW) &&
+                    self.minimum().map_or(false, |min| value_as_number < min)
+                {
+                    failed_flags.insert(ValidationFlags::RANGE_UNDERFLOW);
+                }
+
+                // https://html.spec.whatwg.org/multipage/#the-min-and-max-attributes%3Asuffering-from-an-overflow
+                if validate_flags.contains(ValidationFlags::RANGE_OVERFLOW) &&
+                    self.maximum().map_or(false, |max| value_as_number > max)
+                {
+                    failed_flags.insert(ValidationFlags::RANGE_OVERFLOW);
+                }
+
+                // https://html.spec.whatwg.org/multipage/#the-step-attribute%3Asuffering-from-a-step-mismatch
+                if validate_flags.contains(ValidationFlags::STEP_MISMATCH) {
+                    if let Some(step) = self.allowed_value_step() {
+                        let steps = (value_as_number - self.step_base()) / step;
+                        if (steps - steps.round()).abs() > 1e-9 {
+                            failed_flags.insert(ValidationFlags::STEP_MISMATCH);
+                        }
+                    }
+                }
+            }
+        }
+
+        failed_flags
     }
 }
 
@@ -1678,11 +2191,16 @@ impl Activatable for HTMLInputElement {
             // https://html.spec.whatwg.org/multipage/#reset-button-state-%28type=reset%29:activation-behaviour-2
             // https://html.spec.whatwg.org/multipage/#checkbox-state-%28type=checkbox%29:activation-behaviour-2
             // https://html.spec.whatwg.org/multipage/#radio-button-state-%28type=radio%29:activation-behaviour-2
+            // https://html.spec.whatwg.org/multipage/#the-input-element:show-the-picker,-if-applicable
             InputType::Submit |
             InputType::Reset |
             InputType::File |
             InputType::Checkbox |
-            InputType::Radio => self.is_mutable(),
+            InputType::Radio |
+            InputType::Color |
+            InputType::Date |
+            InputType::Time |
+            InputType::DatetimeLocal => self.is_mutable(),
             _ => false,
         }
     }
@@ -1787,7 +2305,7 @@ impl Activatable for HTMLInputElement {
     }
 
     // https://html.spec.whatwg.org/multipage/#run-post-click-activation-steps
-    fn activation_behavior(&self, _event: &Event, _target: &EventTarget) {
+    fn activation_behavior(&self, event: &Event, _target: &EventTarget) {
         let ty = self.input_type();
         if self.activation_state.borrow().old_type != ty || !self.is_mutable() {
             // Type changed or input is immutable, abandon ship
@@ -1821,6 +2339,12 @@ impl Activatable for HTMLInputElement {
                 target.fire_bubbling_event(atom!("change"));
             },
             InputType::File => self.select_files(None),
+            // Only show a picker in response to the user, not to script calling click().
+            InputType::Color | InputType::Date | InputType::Time | InputType::DatetimeLocal
+                if event.IsTrusted() =>
+            {
+                self.show_picker()
+            },
             _ => (),
         }
     }
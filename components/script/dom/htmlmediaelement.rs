@@ -13,11 +13,18 @@ use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::CanPlayTyp
 use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementConstants;
 use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLSourceElementBinding::HTMLSourceElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTrackElementBinding::HTMLTrackElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::HTMLVideoElementMethods;
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConstants::*;
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorMethods;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
-use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, TextTrackMode};
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
+use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{
+    TextTrackKind, TextTrackMethods, TextTrackMode,
+};
+use crate::dom::bindings::codegen::Bindings::TextTrackCueListBinding::TextTrackCueListMethods;
+use crate::dom::bindings::codegen::Bindings::TextTrackListBinding::TextTrackListMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::InheritTypes::{ElementTypeId, HTMLElementTypeId};
 use crate::dom::bindings::codegen::InheritTypes::{HTMLMediaElementTypeId, NodeTypeId};
@@ -40,13 +47,18 @@ use crate::dom::element::{AttributeMutation, Element, ElementCreator};
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmldivelement::HTMLDivElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlsourceelement::HTMLSourceElement;
+use crate::dom::htmlspanelement::HTMLSpanElement;
 use crate::dom::htmlstyleelement::HTMLStyleElement;
+use crate::dom::htmltrackelement::HTMLTrackElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::mediaerror::MediaError;
 use crate::dom::mediafragmentparser::MediaFragmentParser;
+use crate::dom::mediakeys::MediaKeys;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage, UnbindContext};
 use crate::dom::performanceresourcetiming::InitiatorType;
@@ -60,6 +72,7 @@ use crate::dom::url::URL;
 use crate::dom::videotrack::VideoTrack;
 use crate::dom::videotracklist::VideoTrackList;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::dom::vttcue::VTTCue;
 use crate::fetch::{create_a_potential_CORS_request, FetchCanceller};
 use crate::microtask::{Microtask, MicrotaskRunnable};
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
@@ -328,6 +341,10 @@ pub struct HTMLMediaElement {
     in_flight_play_promises_queue: DomRefCell<VecDeque<(Box<[Rc<Promise>]>, ErrorResult)>>,
     #[ignore_malloc_size_of = "servo_media"]
     player: DomRefCell<Option<Arc<Mutex<dyn Player>>>>,
+    /// The player of the audio stream of the attached MediaSource, when it
+    /// is fed by a SourceBuffer of its own alongside a video one.
+    #[ignore_malloc_size_of = "servo_media"]
+    media_source_audio_player: DomRefCell<Option<Arc<Mutex<dyn Player>>>>,
     #[ignore_malloc_size_of = "Arc"]
     video_renderer: Arc<Mutex<MediaFrameRenderer>>,
     #[ignore_malloc_size_of = "Arc"]
@@ -372,6 +389,18 @@ pub struct HTMLMediaElement {
     /// the access to the "privileged" document.servoGetMediaControls(id) API by
     /// keeping a whitelist of media controls identifiers.
     media_controls_id: DomRefCell<Option<String>>,
+    /// The MediaSource attached to this element, if any.
+    /// https://w3c.github.io/media-source/#mediasource-attach
+    media_source: MutNullableDom<MediaSource>,
+    /// https://w3c.github.io/encrypted-media/#dom-htmlmediaelement-mediakeys
+    media_keys: MutNullableDom<MediaKeys>,
+    /// The current playback position the last time the time marches on
+    /// algorithm ran, if it ran since the position last jumped.
+    /// https://html.spec.whatwg.org/multipage/#time-marches-on
+    last_time_marches_on: Cell<Option<f64>>,
+    /// The element of the user agent shadow root rendering the active cues
+    /// of the showing text tracks.
+    cue_container: MutNullableDom<HTMLDivElement>,
 }
 
 /// <https://html.spec.whatwg.org/multipage/#dom-media-networkstate>
@@ -384,6 +413,15 @@ pub enum NetworkState {
     NoSource = HTMLMediaElementConstants::NETWORK_NO_SOURCE as u8,
 }
 
+/// The streams a MediaSource can push data to, each having a player.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum MediaSourceStream {
+    /// The stream of the main player of the media element.
+    Main,
+    /// An audio stream played along the main one.
+    Audio,
+}
+
 /// <https://html.spec.whatwg.org/multipage/#dom-media-readystate>
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq, PartialOrd)]
 #[repr(u8)]
@@ -416,6 +454,7 @@ impl HTMLMediaElement {
             pending_play_promises: Default::default(),
             in_flight_play_promises_queue: Default::default(),
             player: Default::default(),
+            media_source_audio_player: Default::default(),
             video_renderer: Arc::new(Mutex::new(MediaFrameRenderer::new(
                 document.window().get_webrender_api_sender(),
             ))),
@@ -436,6 +475,10 @@ impl HTMLMediaElement {
             current_fetch_context: DomRefCell::new(None),
             id: Cell::new(0),
             media_controls_id: DomRefCell::new(None),
+            media_source: Default::default(),
+            media_keys: Default::default(),
+            last_time_marches_on: Cell::new(None),
+            cue_container: Default::default(),
         }
     }
 
@@ -452,8 +495,19 @@ impl HTMLMediaElement {
         }
     }
 
+    /// The players of this element: the main one, and the one of the audio
+    /// stream of the attached MediaSource, if any.
+    fn players(&self) -> Vec<Arc<Mutex<dyn Player>>> {
+        self.player
+            .borrow()
+            .iter()
+            .chain(self.media_source_audio_player.borrow().iter())
+            .cloned()
+            .collect()
+    }
+
     fn play_media(&self) {
-        if let Some(ref player) = *self.player.borrow() {
+        for player in self.players() {
             if let Err(e) = player.lock().unwrap().set_rate(self.playbackRate.get()) {
                 warn!("Could not set the playback rate {:?}", e);
             }
@@ -480,16 +534,304 @@ impl HTMLMediaElement {
 
     /// https://html.spec.whatwg.org/multipage/#time-marches-on
     fn time_marches_on(&self) {
+        let window = window_from_node(self);
+        let task_source = window.task_manager().media_element_task_source();
+        let position = self.playback_position.get();
+        let normal_playback = !self.Paused() && !self.seeking.get();
+
+        // Steps 1-2, remembering the position of each cue in the text track
+        // cue order.
+        let mut current_cues = vec![];
+        let mut other_cues = vec![];
+        let text_tracks = self.TextTracks();
+        for track_index in 0..text_tracks.Length() as usize {
+            let track = text_tracks.item(track_index).unwrap();
+            if track.Mode() == TextTrackMode::Disabled {
+                continue;
+            }
+            let cues = track.get_cues();
+            for cue_index in 0..cues.Length() as usize {
+                let cue = cues.item(cue_index).unwrap();
+                let order = (track_index, cue_index);
+                if cue.start_time() <= position && cue.end_time() > position {
+                    current_cues.push((order, cue));
+                } else {
+                    other_cues.push((order, cue));
+                }
+            }
+        }
+
+        // Steps 3-4.
+        let last_time = self.last_time_marches_on.replace(Some(position));
+        let missed_cues: Vec<_> = match last_time {
+            Some(last_time) if normal_playback => other_cues
+                .iter()
+                .filter(|&&(_, ref cue)| {
+                    cue.start_time() >= last_time && cue.end_time() <= position
+                })
+                .cloned()
+                .collect(),
+            _ => vec![],
+        };
+
         // Step 6.
         if time::get_time() > self.next_timeupdate_event.get() {
-            let window = window_from_node(self);
-            window
-                .task_manager()
-                .media_element_task_source()
-                .queue_simple_event(self.upcast(), atom!("timeupdate"), &window);
+            task_source.queue_simple_event(self.upcast(), atom!("timeupdate"), &window);
             self.next_timeupdate_event
                 .set(time::get_time() + Duration::milliseconds(350));
         }
+
+        // Step 7.
+        if current_cues.iter().all(|&(_, ref cue)| cue.active()) &&
+            !other_cues.iter().any(|&(_, ref cue)| cue.active()) &&
+            missed_cues.is_empty()
+        {
+            return;
+        }
+
+        // Step 8.
+        if normal_playback &&
+            other_cues.iter().any(|&(order, ref cue)| {
+                cue.pause_on_exit() &&
+                    (cue.active() || missed_cues.iter().any(|&(missed, _)| missed == order))
+            })
+        {
+            self.internal_pause_steps();
+        }
+
+        // Steps 9-12.
+        let mut events = vec![];
+        for &(order, ref cue) in &missed_cues {
+            events.push((cue.start_time(), order, atom!("enter"), cue.clone()));
+        }
+        for &(order, ref cue) in &other_cues {
+            if cue.active() || missed_cues.iter().any(|&(missed, _)| missed == order) {
+                let time = cue.end_time().max(cue.start_time());
+                events.push((time, order, atom!("exit"), cue.clone()));
+            }
+        }
+        for &(order, ref cue) in &current_cues {
+            if !cue.active() {
+                events.push((cue.start_time(), order, atom!("enter"), cue.clone()));
+            }
+        }
+
+        // Step 13.
+        events.sort_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap()
+                .then(a.1.cmp(&b.1))
+                .then((a.2 == atom!("exit")).cmp(&(b.2 == atom!("exit"))))
+        });
+
+        // Step 14.
+        for &(_, _, ref name, ref cue) in &events {
+            task_source.queue_simple_event(cue.upcast(), name.clone(), &window);
+        }
+
+        // Step 15.
+        let mut affected_tracks: Vec<_> = events
+            .iter()
+            .map(|&(_, (track_index, _), _, _)| track_index)
+            .collect();
+        affected_tracks.sort();
+        affected_tracks.dedup();
+
+        // Step 16.
+        for track_index in affected_tracks {
+            let track = text_tracks.item(track_index).unwrap();
+            let track_element = self
+                .upcast::<Node>()
+                .children()
+                .filter_map(DomRoot::downcast::<HTMLTrackElement>)
+                .find(|track_element| track_element.Track() == track)
+                .map(|track_element| Trusted::new(&*track_element));
+            let track = Trusted::new(&*track);
+            let _ = task_source.queue(
+                task!(fire_cuechange_events: move || {
+                    track.root().upcast::<EventTarget>().fire_event(atom!("cuechange"));
+                    if let Some(track_element) = track_element {
+                        track_element.root().upcast::<EventTarget>().fire_event(atom!("cuechange"));
+                    }
+                }),
+                window.upcast(),
+            );
+        }
+
+        // Step 17.
+        for &(_, ref cue) in &current_cues {
+            cue.set_active(true);
+        }
+        for &(_, ref cue) in &other_cues {
+            cue.set_active(false);
+        }
+        for track_index in 0..text_tracks.Length() as usize {
+            text_tracks.item(track_index).unwrap().update_active_cues();
+        }
+
+        // Step 18.
+        self.update_text_track_rendering();
+    }
+
+    /// Runs the cue processing part of the time marches on algorithm after
+    /// the cues or the mode of a text track changed.
+    pub fn text_track_cues_changed(&self) {
+        self.last_time_marches_on.set(None);
+        self.time_marches_on();
+        // Time marches on leaves the rendering alone if no cue became active
+        // or inactive, but the showing tracks may have changed.
+        self.update_text_track_rendering();
+    }
+
+    /// <https://w3c.github.io/webvtt/#rules-for-updating-the-display-of-webvtt-text-tracks>
+    fn update_text_track_rendering(&self) {
+        let video = match self.downcast::<HTMLVideoElement>() {
+            Some(video) => video,
+            None => return,
+        };
+
+        // Steps 1-3, only rendering the cues of the showing subtitles and
+        // captions.
+        let mut cues = vec![];
+        let text_tracks = self.TextTracks();
+        for track_index in 0..text_tracks.Length() as usize {
+            let track = text_tracks.item(track_index).unwrap();
+            if track.Mode() != TextTrackMode::Showing ||
+                (track.Kind() != TextTrackKind::Subtitles &&
+                    track.Kind() != TextTrackKind::Captions)
+            {
+                continue;
+            }
+            let active_cues = track.GetActiveCues().unwrap();
+            for cue_index in 0..active_cues.Length() as usize {
+                let cue = active_cues.item(cue_index).unwrap();
+                if let Some(cue) = DomRoot::downcast::<VTTCue>(cue) {
+                    cues.push(cue);
+                }
+            }
+        }
+
+        let document = document_from_node(self);
+        let container = match self.cue_container.get() {
+            Some(container) => container,
+            None if cues.is_empty() => return,
+            None => {
+                let element = self.upcast::<Element>();
+                let shadow_root = match element.shadow_root() {
+                    Some(shadow_root) => shadow_root,
+                    None => element
+                        .attach_shadow(IsUserAgentWidget::Yes, ShadowRootMode::Closed)
+                        .unwrap(),
+                };
+                let container = HTMLDivElement::new(local_name!("div"), None, &document);
+                let shadow_root = shadow_root.upcast::<Node>();
+                if let Err(e) = shadow_root
+                    .InsertBefore(container.upcast(), shadow_root.GetFirstChild().as_deref())
+                {
+                    warn!("Could not render text track cues {:?}", e);
+                    return;
+                }
+                self.cue_container.set(Some(&container));
+                container
+            },
+        };
+
+        // Steps 4-10, sizing the container to the video and letting each cue
+        // box be positioned within it from the cue settings.
+        let height = video.VideoHeight();
+        container.upcast::<Element>().set_string_attribute(
+            &local_name!("style"),
+            DOMString::from(format!(
+                "position: absolute; width: {}px; height: {}px; overflow: hidden; \
+                 pointer-events: none; font: {}px sans-serif; line-height: 1.2;",
+                video.VideoWidth(),
+                height,
+                (height as f64 * 0.05).max(1.)
+            )),
+        );
+        container.upcast::<Node>().SetTextContent(None);
+        for cue in cues {
+            let cue_box = HTMLDivElement::new(local_name!("div"), None, &document);
+            cue_box
+                .upcast::<Element>()
+                .set_string_attribute(&local_name!("style"), DOMString::from(cue.cue_box_style()));
+            let background = HTMLSpanElement::new(local_name!("span"), None, &document);
+            background.upcast::<Element>().set_string_attribute(
+                &local_name!("style"),
+                DOMString::from(
+                    "background: rgba(0, 0, 0, 0.8); color: white; white-space: pre-line;",
+                ),
+            );
+            let cue_html = cue.cue_as_html(&document);
+            let _ = background.upcast::<Node>().AppendChild(cue_html.upcast());
+            let _ = cue_box.upcast::<Node>().AppendChild(background.upcast());
+            let _ = container.upcast::<Node>().AppendChild(cue_box.upcast());
+        }
+    }
+
+    /// Adds the text track of a `track` child to the list of text tracks.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#sourcing-out-of-band-text-tracks>
+    pub fn add_track_element(&self, track_element: &HTMLTrackElement) {
+        let track = track_element.Track();
+        let text_tracks = self.TextTracks();
+        if text_tracks.find(&track).is_some() {
+            return;
+        }
+        text_tracks.add(&track);
+        track.set_media_element(Some(self));
+
+        // https://html.spec.whatwg.org/multipage/#honor-user-preferences-for-automatic-text-track-selection
+        if !track_element.Default() || track.Mode() != TextTrackMode::Disabled {
+            return;
+        }
+        match track.Kind() {
+            TextTrackKind::Subtitles | TextTrackKind::Captions => {
+                let showing = (0..text_tracks.Length() as usize)
+                    .filter_map(|index| text_tracks.item(index))
+                    .any(|other| {
+                        other.Mode() == TextTrackMode::Showing &&
+                            (other.Kind() == TextTrackKind::Subtitles ||
+                                other.Kind() == TextTrackKind::Captions)
+                    });
+                if !showing {
+                    track.SetMode(TextTrackMode::Showing);
+                }
+            },
+            // There is no user preference for descriptions.
+            TextTrackKind::Descriptions => {},
+            TextTrackKind::Chapters | TextTrackKind::Metadata => {
+                track.SetMode(TextTrackMode::Hidden);
+            },
+        }
+    }
+
+    /// Removes the text track of a `track` child that was removed from this
+    /// element.
+    pub fn remove_track_element(&self, track_element: &HTMLTrackElement) {
+        let track = track_element.Track();
+        let text_tracks = self.TextTracks();
+        if let Some(index) = text_tracks.find(&track) {
+            text_tracks.remove(index);
+            track.set_media_element(None);
+            self.text_track_cues_changed();
+        }
+    }
+
+    /// Starts loading the text tracks of the `track` children that are no
+    /// longer disabled, and updates the active cues.
+    pub fn text_track_mode_changed(&self, track: &TextTrack) {
+        if track.Mode() != TextTrackMode::Disabled {
+            let track_element = self
+                .upcast::<Node>()
+                .children()
+                .filter_map(DomRoot::downcast::<HTMLTrackElement>)
+                .find(|track_element| &*track_element.Track() == track);
+            if let Some(track_element) = track_element {
+                track_element.start_track_processing();
+            }
+        }
+        self.text_track_cues_changed();
     }
 
     /// <https://html.spec.whatwg.org/multipage/#internal-pause-steps>
@@ -523,7 +865,7 @@ impl HTMLMediaElement {
                         // Step 2.3.2.
                         this.upcast::<EventTarget>().fire_event(atom!("pause"));
 
-                        if let Some(ref player) = *this.player.borrow() {
+                        for player in this.players() {
                             if let Err(e) = player.lock().unwrap().pause() {
                                 eprintln!("Could not pause player {:?}", e);
                             }
@@ -884,6 +1226,11 @@ impl HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#concept-media-load-resource
     fn resource_fetch_algorithm(&self, resource: Resource) {
+        if let Resource::Url(ref url) = resource {
+            self.media_source
+                .set(self.global().media_source_for_url(url).as_deref());
+        }
+
         if let Err(e) = self.setup_media_player(&resource) {
             eprintln!("Setup media player error {:?}", e);
             self.queue_dedicated_media_source_failure_steps();
@@ -900,6 +1247,15 @@ impl HTMLMediaElement {
         // Step 4.
         match resource {
             Resource::Url(url) => {
+                // https://w3c.github.io/media-source/#mediasource-attach
+                if let Some(media_source) = self.media_source.get() {
+                    if media_source.attach(self).is_err() {
+                        self.media_source.set(None);
+                        self.queue_dedicated_media_source_failure_steps();
+                    }
+                    return;
+                }
+
                 // Step 4.remote.1.
                 if self.Preload() == "none" && !self.autoplaying.get() {
                     // Step 4.remote.1.1.
@@ -1007,7 +1363,7 @@ impl HTMLMediaElement {
                     // Step 5.
                     this.upcast::<EventTarget>().fire_event(atom!("error"));
 
-                    if let Some(ref player) = *this.player.borrow() {
+                    for player in this.players() {
                         if let Err(e) = player.lock().unwrap().stop() {
                             eprintln!("Could not stop player {:?}", e);
                         }
@@ -1096,7 +1452,14 @@ impl HTMLMediaElement {
             }
 
             // Step 6.3.
-            // FIXME(nox): Detach MediaSource media provider object.
+            if let Some(media_source) = self.media_source.take() {
+                media_source.detach();
+            }
+            if let Some(player) = self.media_source_audio_player.borrow_mut().take() {
+                if let Err(e) = player.lock().unwrap().stop() {
+                    eprintln!("Could not stop player {:?}", e);
+                }
+            }
 
             // Step 6.4.
             self.AudioTracks().clear();
@@ -1125,6 +1488,7 @@ impl HTMLMediaElement {
             // Step 6.8.
             let queue_timeupdate_event = self.playback_position.get() != 0.;
             self.playback_position.set(0.);
+            self.last_time_marches_on.set(None);
             if queue_timeupdate_event {
                 task_source.queue_simple_event(self.upcast(), atom!("timeupdate"), &window);
             }
@@ -1232,6 +1596,7 @@ impl HTMLMediaElement {
         // Step 4.
         // The flag will be cleared when the media engine tells us the seek was done.
         self.seeking.set(true);
+        self.last_time_marches_on.set(None);
 
         // Step 5.
         // XXX(ferjm) The rest of the steps should be run in parallel, so seeking cancelation
@@ -1263,7 +1628,7 @@ impl HTMLMediaElement {
         task_source.queue_simple_event(self.upcast(), atom!("seeking"), &window);
 
         // Step 11.
-        if let Some(ref player) = *self.player.borrow() {
+        for player in self.players() {
             if let Err(e) = player.lock().unwrap().seek(time) {
                 eprintln!("Seek error {:?}", e);
             }
@@ -1326,6 +1691,7 @@ impl HTMLMediaElement {
                     return Err(());
                 }
             },
+            Resource::Url(_) if self.media_source.get().is_some() => StreamType::Stream,
             _ => StreamType::Seekable,
         };
 
@@ -1845,12 +2211,25 @@ impl HTMLMediaElement {
 
     fn render_controls(&self) {
         let element = self.htmlelement.upcast::<Element>();
-        if self.ready_state.get() < ReadyState::HaveMetadata || element.is_shadow_host() {
+        let shadow_root = element.shadow_root();
+        let showing_controls = shadow_root.as_ref().map_or(false, |shadow_root| {
+            shadow_root
+                .upcast::<Node>()
+                .children()
+                .any(|child| child.is::<HTMLScriptElement>())
+        });
+        if self.ready_state.get() < ReadyState::HaveMetadata || showing_controls {
             // Bail out if we have no metadata yet or
             // if we are already showing the controls.
             return;
         }
-        let shadow_root = element.attach_shadow(IsUserAgentWidget::Yes).unwrap();
+        // The shadow root may already exist to render text track cues.
+        let shadow_root = match shadow_root {
+            Some(shadow_root) => shadow_root,
+            None => element
+                .attach_shadow(IsUserAgentWidget::Yes, ShadowRootMode::Closed)
+                .unwrap(),
+        };
         let document = document_from_node(self);
         let script = HTMLScriptElement::new(
             local_name!("script"),
@@ -1939,8 +2318,108 @@ impl HTMLMediaElement {
         self.duration.set(duration);
     }
 
+    /// Pushes data appended to the attached MediaSource to the player of
+    /// `stream`.
+    pub fn push_media_source_data(&self, stream: MediaSourceStream, data: Vec<u8>) {
+        let player = match stream {
+            MediaSourceStream::Main => self.player.borrow().clone(),
+            MediaSourceStream::Audio => self.media_source_audio_player(),
+        };
+        if let Some(player) = player {
+            if let Err(e) = player.lock().unwrap().push_data(data) {
+                warn!("Could not push MediaSource data to player {:?}", e);
+            }
+        }
+    }
+
+    /// Returns the player of the audio stream of the attached MediaSource,
+    /// creating it if needed. Its events are ignored: the main player drives
+    /// the state of the element, and this one follows its playback.
+    fn media_source_audio_player(&self) -> Option<Arc<Mutex<dyn Player>>> {
+        if self.player.borrow().is_none() {
+            return None;
+        }
+        if let Some(ref player) = *self.media_source_audio_player.borrow() {
+            return Some(player.clone());
+        }
+
+        let window = window_from_node(self);
+        let (action_sender, action_receiver) = ipc::channel::<PlayerEvent>().unwrap();
+        let audio_renderer = self.audio_renderer.borrow().as_ref().map(|r| r.clone());
+        let pipeline_id = window
+            .pipeline_id()
+            .expect("Cannot create player outside of a pipeline");
+        let client_context_id =
+            ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
+        let player = ServoMedia::get().unwrap().create_player(
+            &client_context_id,
+            StreamType::Stream,
+            action_sender,
+            None,
+            audio_renderer,
+            Box::new(window.get_player_context()),
+        );
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: PlayerEvent = message.to().unwrap();
+                trace!("MediaSource audio player event {:?}", event);
+            }),
+        );
+
+        {
+            let mut player = player.lock().unwrap();
+            let _ = player.set_mute(self.muted.get());
+            if self.is_potentially_playing() {
+                if let Err(e) = player.set_rate(self.playbackRate.get()) {
+                    warn!("Could not set the playback rate {:?}", e);
+                }
+                if let Err(e) = player.play() {
+                    warn!("Could not play media {:?}", e);
+                }
+            }
+        }
+        *self.media_source_audio_player.borrow_mut() = Some(player.clone());
+        Some(player)
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    pub fn media_source_duration_changed(&self, duration: f64) {
+        // Step 6.
+        self.duration.set(duration);
+        let window = window_from_node(self);
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), atom!("durationchange"), &window);
+    }
+
+    /// Notifies the player that the attached MediaSource has ended.
+    pub fn media_source_end_of_stream(&self) {
+        for player in self.players() {
+            if let Err(e) = player.lock().unwrap().end_of_stream() {
+                warn!("Could not signal EOS to player {:?}", e);
+            }
+        }
+    }
+
+    /// The error steps of the [end of stream algorithm][eos], run when the
+    /// attached MediaSource ends with an error.
+    ///
+    /// [eos]: https://w3c.github.io/media-source/#end-of-stream-algorithm
+    pub fn media_source_error(&self, code: u16) {
+        if self.ready_state.get() == ReadyState::HaveNothing {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+        let window = window_from_node(self);
+        self.error.set(Some(&*MediaError::new(&window, code)));
+        self.network_state.set(NetworkState::Idle);
+        self.upcast::<EventTarget>().fire_event(atom!("error"));
+    }
+
     pub fn reset(&self) {
-        if let Some(ref player) = *self.player.borrow() {
+        for player in self.players() {
             if let Err(e) = player.lock().unwrap().stop() {
                 eprintln!("Could not stop player {:?}", e);
             }
@@ -2039,7 +2518,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             return;
         }
 
-        if let Some(ref player) = *self.player.borrow() {
+        for player in self.players() {
             let _ = player.lock().unwrap().set_mute(value);
         }
 
@@ -2247,7 +2726,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             self.playbackRate.set(*value);
             self.queue_ratechange_event();
             if self.is_potentially_playing() {
-                if let Some(ref player) = *self.player.borrow() {
+                for player in self.players() {
                     if let Err(e) = player.lock().unwrap().set_rate(*value) {
                         warn!("Could not set the playback rate {:?}", e);
                     }
@@ -2313,6 +2792,9 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-media-buffered
     fn Buffered(&self) -> DomRoot<TimeRanges> {
+        if let Some(media_source) = self.media_source.get() {
+            return TimeRanges::new(self.global().as_window(), media_source.buffered());
+        }
         let mut buffered = TimeRangesContainer::new();
         if let Some(ref player) = *self.player.borrow() {
             if let Ok(ranges) = player.lock().unwrap().buffered() {
@@ -2365,6 +2847,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
         );
         // Step 3 & 4
         self.TextTracks().add(&track);
+        track.set_media_element(Some(self));
         // Step 5
         DomRoot::from_ref(&track)
     }
@@ -2397,6 +2880,43 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
         Ok(())
     }
+
+    // https://w3c.github.io/encrypted-media/#dom-htmlmediaelement-mediakeys
+    fn GetMediaKeys(&self) -> Option<DomRoot<MediaKeys>> {
+        self.media_keys.get()
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-htmlmediaelement-setmediakeys
+    fn SetMediaKeys(&self, media_keys: Option<&MediaKeys>, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Step 1.
+        if self.media_keys.get().as_deref() == media_keys {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Step 5.1.
+        if let Some(media_keys) = media_keys {
+            if media_keys.in_use() {
+                promise.reject_error(Error::QuotaExceeded);
+                return promise;
+            }
+        }
+
+        // Step 5.2.
+        if let Some(old_media_keys) = self.media_keys.get() {
+            old_media_keys.set_in_use(false);
+        }
+
+        // Steps 5.3-5.4.
+        if let Some(media_keys) = media_keys {
+            media_keys.set_in_use(true);
+        }
+        self.media_keys.set(media_keys);
+        promise.resolve_native(&());
+        promise
+    }
 }
 
 impl VirtualMethods for HTMLMediaElement {
@@ -21,6 +21,8 @@ use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
 use crate::dom::node::{BindContext, Node, ShadowIncluding, UnbindContext};
 use crate::dom::text::Text;
+use crate::dom::validation::Validatable;
+use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
@@ -74,6 +76,16 @@ impl HTMLOptionElement {
 
     pub fn set_selectedness(&self, selected: bool) {
         self.selectedness.set(selected);
+        if let Some(select) = self
+            .upcast::<Node>()
+            .ancestors()
+            .filter_map(DomRoot::downcast::<HTMLSelectElement>)
+            .next()
+        {
+            select
+                .validity_state()
+                .perform_validation_and_update(ValidationFlags::VALUE_MISSING);
+        }
     }
 
     pub fn set_dirtiness(&self, dirtiness: bool) {
@@ -188,7 +200,7 @@ impl HTMLOptionElementMethods for HTMLOptionElement {
     // https://html.spec.whatwg.org/multipage/#dom-option-selected
     fn SetSelected(&self, selected: bool) {
         self.dirtiness.set(true);
-        self.selectedness.set(selected);
+        self.set_selectedness(selected);
         self.pick_if_selected_and_reset();
     }
 }
@@ -220,13 +232,13 @@ impl VirtualMethods for HTMLOptionElement {
                     AttributeMutation::Set(_) => {
                         // https://html.spec.whatwg.org/multipage/#concept-option-selectedness
                         if !self.dirtiness.get() {
-                            self.selectedness.set(true);
+                            self.set_selectedness(true);
                         }
                     },
                     AttributeMutation::Removed => {
                         // https://html.spec.whatwg.org/multipage/#concept-option-selectedness
                         if !self.dirtiness.get() {
-                            self.selectedness.set(false);
+                            self.set_selectedness(false);
                         }
                     },
                 }
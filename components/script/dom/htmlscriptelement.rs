@@ -2,12 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::compartments::enter_realm;
 use crate::document_loader::LoadType;
 use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLScriptElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLScriptElementBinding::HTMLScriptElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::error::{report_pending_exception, throw_dom_exception};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
@@ -27,6 +29,7 @@ use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::fetch::create_a_potential_CORS_request;
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::script_module::ImportMap;
 use content_security_policy as csp;
 use dom_struct::dom_struct;
 use encoding_rs::Encoding;
@@ -109,7 +112,7 @@ impl HTMLScriptElement {
 
 /// Supported script types as defined by
 /// <https://html.spec.whatwg.org/multipage/#javascript-mime-type>.
-static SCRIPT_JS_MIMES: StaticStringVec = &[
+pub static SCRIPT_JS_MIMES: StaticStringVec = &[
     "application/ecmascript",
     "application/javascript",
     "application/x-ecmascript",
@@ -132,6 +135,7 @@ static SCRIPT_JS_MIMES: StaticStringVec = &[
 pub enum ScriptType {
     Classic,
     Module,
+    ImportMap,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -560,11 +564,21 @@ impl HTMLScriptElement {
                         url.clone()
                     );
                 },
+                ScriptType::ImportMap => {
+                    // External import maps are not supported.
+                    self.queue_error_event();
+                    return;
+                },
             }
         } else {
             // Step 25.
             assert!(!text.is_empty());
 
+            if let ScriptType::ImportMap = script_type {
+                self.register_import_map(&text, base_url);
+                return;
+            }
+
             // Step 25-1.
             let result = Ok(ScriptOrigin::internal(
                 text.clone(),
@@ -729,6 +743,32 @@ impl HTMLScriptElement {
         );
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#register-an-import-map>
+    #[allow(unsafe_code)]
+    fn register_import_map(&self, text: &str, base_url: ServoUrl) {
+        let window = window_from_node(self);
+        let global = window.upcast::<GlobalScope>();
+
+        // Only a single import map is allowed, and only before the first
+        // module graph is fetched.
+        if !global.import_maps_allowed() {
+            warn!("ignoring import map in {}", base_url);
+            self.queue_error_event();
+            return;
+        }
+        global.disallow_further_import_maps();
+
+        match ImportMap::parse(text, &base_url) {
+            Ok(import_map) => global.set_import_map(import_map),
+            Err(error) => {
+                let cx = global.get_cx();
+                let _ac = enter_realm(global);
+                throw_dom_exception(cx, global, error);
+                unsafe { report_pending_exception(*cx, true) };
+            },
+        }
+    }
+
     pub fn queue_error_event(&self) {
         let window = window_from_node(self);
         window
@@ -793,6 +833,10 @@ impl HTMLScriptElement {
                     return Some(ScriptType::Module);
                 }
 
+                if ty.eq_ignore_ascii_case("importmap") {
+                    return Some(ScriptType::ImportMap);
+                }
+
                 if SCRIPT_JS_MIMES
                     .contains(&ty.to_ascii_lowercase().trim_matches(HTML_SPACE_CHARACTERS))
                 {
@@ -0,0 +1,386 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::attr::Attr;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLSlotElementBinding::{
+    self, AssignedNodesOptions, HTMLSlotElementMethods,
+};
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::mutationobserver::MutationObserver;
+use crate::dom::node::{Node, NodeDamage, ShadowIncluding};
+use crate::dom::shadowroot::ShadowRoot;
+use crate::dom::text::Text;
+use crate::dom::virtualmethods::VirtualMethods;
+use crate::script_thread::ScriptThread;
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Prefix};
+
+// https://html.spec.whatwg.org/multipage/#the-slot-element
+#[dom_struct]
+pub struct HTMLSlotElement {
+    htmlelement: HTMLElement,
+    /// <https://dom.spec.whatwg.org/#slot-assigned-nodes>
+    assigned_nodes: DomRefCell<Vec<Dom<Node>>>,
+}
+
+impl HTMLSlotElement {
+    fn new_inherited(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> HTMLSlotElement {
+        HTMLSlotElement {
+            htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
+            assigned_nodes: DomRefCell::new(vec![]),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> DomRoot<HTMLSlotElement> {
+        Node::reflect_node(
+            Box::new(HTMLSlotElement::new_inherited(local_name, prefix, document)),
+            document,
+            HTMLSlotElementBinding::Wrap,
+        )
+    }
+
+    /// <https://dom.spec.whatwg.org/#slot-name>
+    fn name(&self) -> DOMString {
+        self.upcast::<Element>()
+            .get_string_attribute(&local_name!("name"))
+    }
+
+    pub fn assigned_nodes(&self) -> Vec<DomRoot<Node>> {
+        self.assigned_nodes
+            .borrow()
+            .iter()
+            .map(|node| DomRoot::from_ref(&**node))
+            .collect()
+    }
+
+    /// <https://dom.spec.whatwg.org/#find-slotables>
+    fn find_slottables(&self) -> Vec<DomRoot<Node>> {
+        // Steps 1-2.
+        let root = match shadow_root_of(self.upcast()) {
+            Some(root) => root,
+            None => return vec![],
+        };
+
+        // Step 3.
+        let host = root.Host();
+
+        // Step 5, only named slot assignment is supported.
+        host.upcast::<Node>()
+            .children()
+            .filter(|slottable| {
+                is_slottable(slottable) &&
+                    find_a_slot(slottable, false).map_or(false, |slot| &*slot == self)
+            })
+            .collect()
+    }
+
+    /// <https://dom.spec.whatwg.org/#find-flattened-slotables>
+    fn find_flattened_slottables(&self) -> Vec<DomRoot<Node>> {
+        // Step 1.
+        let mut result = vec![];
+
+        // Step 2.
+        if shadow_root_of(self.upcast()).is_none() {
+            return result;
+        }
+
+        // Steps 3-4.
+        let mut slottables = self.assigned_nodes();
+        if slottables.is_empty() {
+            slottables = self
+                .upcast::<Node>()
+                .children()
+                .filter(|node| is_slottable(node))
+                .collect();
+        }
+
+        // Step 5.
+        for node in slottables {
+            match node.downcast::<HTMLSlotElement>() {
+                Some(slot) if shadow_root_of(&node).is_some() => {
+                    result.extend(slot.find_flattened_slottables());
+                },
+                _ => result.push(node),
+            }
+        }
+
+        // Step 6.
+        result
+    }
+
+    /// <https://dom.spec.whatwg.org/#assign-slotables>
+    pub fn assign_slottables(&self) {
+        // Step 1.
+        let slottables = self.find_slottables();
+
+        // Step 2.
+        let old_slottables = self.assigned_nodes();
+        if old_slottables == slottables {
+            return;
+        }
+        self.signal_a_slot_change();
+
+        // Nodes that are no longer assigned to any slot leave the flat tree.
+        for node in &old_slottables {
+            if !slottables.contains(node) &&
+                node.assigned_slot().map_or(false, |slot| &*slot == self)
+            {
+                node.set_assigned_slot(None);
+            }
+        }
+
+        // Steps 3-4.
+        *self.assigned_nodes.borrow_mut() = slottables
+            .iter()
+            .map(|node| Dom::from_ref(&**node))
+            .collect();
+        for node in &slottables {
+            node.set_assigned_slot(Some(self));
+        }
+
+        // The children of the slot in the flat tree changed.
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        for node in old_slottables.iter().chain(&slottables) {
+            node.dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
+    /// <https://dom.spec.whatwg.org/#signal-a-slot-change>
+    pub fn signal_a_slot_change(&self) {
+        // Step 1.
+        ScriptThread::add_signal_slot(self);
+
+        // Step 2.
+        MutationObserver::queue_mutation_observer_microtask();
+    }
+
+    /// Whether this slot is in a shadow tree and has no assigned nodes, in
+    /// which case its children are shown as fallback content.
+    pub fn shows_fallback_content(&self) -> bool {
+        self.assigned_nodes.borrow().is_empty() && shadow_root_of(self.upcast()).is_some()
+    }
+}
+
+impl HTMLSlotElementMethods for HTMLSlotElement {
+    // https://html.spec.whatwg.org/multipage/#dom-slot-name
+    make_getter!(Name, "name");
+
+    // https://html.spec.whatwg.org/multipage/#dom-slot-name
+    make_setter!(SetName, "name");
+
+    // https://html.spec.whatwg.org/multipage/#dom-slot-assignednodes
+    fn AssignedNodes(&self, options: &AssignedNodesOptions) -> Vec<DomRoot<Node>> {
+        if options.flatten {
+            self.find_flattened_slottables()
+        } else {
+            self.assigned_nodes()
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-slot-assignedelements
+    fn AssignedElements(&self, options: &AssignedNodesOptions) -> Vec<DomRoot<Element>> {
+        self.AssignedNodes(options)
+            .into_iter()
+            .filter_map(DomRoot::downcast::<Element>)
+            .collect()
+    }
+}
+
+impl VirtualMethods for HTMLSlotElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+        // https://dom.spec.whatwg.org/#slot-name
+        if attr.local_name() == &local_name!("name") &&
+            attr.namespace() == &ns!() &&
+            !mutation.leaves_value_unchanged(attr)
+        {
+            if let Some(root) = shadow_root_of(self.upcast()) {
+                assign_slottables_for_a_tree(root.upcast());
+            }
+        }
+    }
+}
+
+#[allow(unsafe_code)]
+pub trait LayoutHTMLSlotElementHelpers {
+    unsafe fn first_assigned_node_for_layout(&self) -> Option<LayoutDom<Node>>;
+    unsafe fn assigned_node_after_for_layout(
+        &self,
+        node: LayoutDom<Node>,
+    ) -> Option<LayoutDom<Node>>;
+}
+
+impl LayoutHTMLSlotElementHelpers for LayoutDom<HTMLSlotElement> {
+    #[inline]
+    #[allow(unsafe_code)]
+    unsafe fn first_assigned_node_for_layout(&self) -> Option<LayoutDom<Node>> {
+        (*self.unsafe_get())
+            .assigned_nodes
+            .borrow_for_layout()
+            .first()
+            .map(|node| node.to_layout())
+    }
+
+    #[inline]
+    #[allow(unsafe_code)]
+    unsafe fn assigned_node_after_for_layout(
+        &self,
+        node: LayoutDom<Node>,
+    ) -> Option<LayoutDom<Node>> {
+        let assigned_nodes = (*self.unsafe_get()).assigned_nodes.borrow_for_layout();
+        let index = assigned_nodes
+            .iter()
+            .position(|assigned_node| assigned_node.to_layout() == node)?;
+        assigned_nodes.get(index + 1).map(|node| node.to_layout())
+    }
+}
+
+/// <https://dom.spec.whatwg.org/#concept-slotable>
+pub fn is_slottable(node: &Node) -> bool {
+    node.is::<Element>() || node.is::<Text>()
+}
+
+/// The shadow root that is the root of `node`, if any.
+fn shadow_root_of(node: &Node) -> Option<DomRoot<ShadowRoot>> {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .last()
+        .and_then(DomRoot::downcast::<ShadowRoot>)
+}
+
+/// <https://dom.spec.whatwg.org/#find-a-slot>
+pub fn find_a_slot(slottable: &Node, open: bool) -> Option<DomRoot<HTMLSlotElement>> {
+    // Step 1.
+    let parent = slottable.GetParentNode()?;
+
+    // Step 2.
+    let shadow = parent.downcast::<Element>()?.shadow_root()?;
+
+    // Step 3.
+    if open && shadow.Mode() != ShadowRootMode::Open {
+        return None;
+    }
+
+    // Step 5, only named slot assignment is supported.
+    let name = slottable
+        .downcast::<Element>()
+        .map_or(DOMString::new(), |element| {
+            element.get_string_attribute(&local_name!("slot"))
+        });
+    shadow
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLSlotElement>)
+        .find(|slot| slot.name() == name)
+}
+
+/// <https://dom.spec.whatwg.org/#assign-a-slot>
+pub fn assign_a_slot(slottable: &Node) {
+    // Steps 1-2.
+    if let Some(slot) = find_a_slot(slottable, false) {
+        slot.assign_slottables();
+    }
+}
+
+/// <https://dom.spec.whatwg.org/#assign-slotables-for-a-tree>
+pub fn assign_slottables_for_a_tree(root: &Node) {
+    let slots: Vec<_> = root
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLSlotElement>)
+        .collect();
+    for slot in slots {
+        slot.assign_slottables();
+    }
+}
+
+/// The slot assignment steps of <https://dom.spec.whatwg.org/#concept-node-insert>,
+/// run once `node` has been inserted into `parent`.
+pub fn slottable_inserted(node: &Node, parent: &Node) {
+    // Step 7.4.
+    if parent
+        .downcast::<Element>()
+        .map_or(false, |parent| parent.is_shadow_host()) &&
+        is_slottable(node)
+    {
+        assign_a_slot(node);
+    }
+
+    // Step 7.5.
+    if let Some(slot) = parent.downcast::<HTMLSlotElement>() {
+        if slot.shows_fallback_content() {
+            slot.signal_a_slot_change();
+        }
+    }
+
+    // Step 7.6, slots can only be assigned nodes in a shadow tree.
+    if let Some(root) = shadow_root_of(node) {
+        if node
+            .traverse_preorder(ShadowIncluding::No)
+            .any(|node| node.is::<HTMLSlotElement>())
+        {
+            assign_slottables_for_a_tree(root.upcast());
+        }
+    }
+}
+
+/// The slot assignment steps of <https://dom.spec.whatwg.org/#concept-node-remove>,
+/// run once `node` has been removed from `parent`.
+pub fn slottable_removed(node: &Node, parent: &Node) {
+    // Reassign the slot that node was assigned to.
+    if let Some(slot) = node.assigned_slot() {
+        slot.assign_slottables();
+    }
+
+    // A slot showing its fallback content had its children change.
+    if let Some(slot) = parent.downcast::<HTMLSlotElement>() {
+        if slot.shows_fallback_content() {
+            slot.signal_a_slot_change();
+        }
+    }
+
+    // Slots removed along with node lose their assigned nodes.
+    if node
+        .traverse_preorder(ShadowIncluding::No)
+        .any(|node| node.is::<HTMLSlotElement>())
+    {
+        if let Some(root) = shadow_root_of(parent) {
+            assign_slottables_for_a_tree(root.upcast());
+        }
+        assign_slottables_for_a_tree(node);
+    }
+}
+
+/// The steps run when the `slot` attribute of `element` changes,
+/// <https://dom.spec.whatwg.org/#slotable-name>
+pub fn slottable_name_changed(element: &Element) {
+    let node = element.upcast::<Node>();
+
+    // Step 5.
+    if let Some(slot) = node.assigned_slot() {
+        slot.assign_slottables();
+    }
+
+    // Step 6.
+    assign_a_slot(node);
+}
@@ -2,23 +2,48 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::document_loader::LoadType;
+use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::HTMLTrackElementBinding::{
     self, HTMLTrackElementConstants, HTMLTrackElementMethods,
 };
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{
+    TextTrackKind, TextTrackMethods, TextTrackMode,
+};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::element::{cors_setting_for_element, AttributeMutation, Element};
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::Node;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::node::{document_from_node, window_from_node, BindContext, Node, UnbindContext};
+use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::texttrack::TextTrack;
+use crate::dom::texttrackcue::TextTrackCue;
+use crate::dom::virtualmethods::VirtualMethods;
+use crate::dom::vttcue::VTTCue;
+use crate::fetch::create_a_potential_CORS_request;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::task_source::TaskSource;
+use crate::webvtt;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::request::Destination;
+use net_traits::{FetchMetadata, FetchResponseListener, NetworkError};
+use net_traits::{ResourceFetchTiming, ResourceTimingType};
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 #[repr(u16)]
-#[allow(unused)]
 pub enum ReadyState {
     None = HTMLTrackElementConstants::NONE,
     Loading = HTMLTrackElementConstants::LOADING,
@@ -29,8 +54,11 @@ pub enum ReadyState {
 #[dom_struct]
 pub struct HTMLTrackElement {
     htmlelement: HTMLElement,
-    ready_state: ReadyState,
+    ready_state: Cell<ReadyState>,
     track: Dom<TextTrack>,
+    /// Incremented when the track URL changes, to ignore the responses of
+    /// previous fetches.
+    generation_id: Cell<u32>,
 }
 
 impl HTMLTrackElement {
@@ -42,8 +70,9 @@ impl HTMLTrackElement {
     ) -> HTMLTrackElement {
         HTMLTrackElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
-            ready_state: ReadyState::None,
+            ready_state: Cell::new(ReadyState::None),
             track: Dom::from_ref(&track),
+            generation_id: Cell::new(0),
         }
     }
 
@@ -68,6 +97,123 @@ impl HTMLTrackElement {
             HTMLTrackElementBinding::Wrap,
         )
     }
+
+    fn parent_media_element(&self) -> Option<DomRoot<HTMLMediaElement>> {
+        self.upcast::<Node>()
+            .GetParentNode()
+            .and_then(DomRoot::downcast::<HTMLMediaElement>)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#start-the-track-processing-model>
+    pub fn start_track_processing(&self) {
+        // Steps 1-4.
+        if self.ready_state.get() != ReadyState::None ||
+            self.track.Mode() == TextTrackMode::Disabled
+        {
+            return;
+        }
+        let media_element = match self.parent_media_element() {
+            Some(media_element) => media_element,
+            None => return,
+        };
+
+        // Step 7.
+        self.ready_state.set(ReadyState::Loading);
+
+        // Steps 8-10.
+        let document = document_from_node(self);
+        let src = self
+            .upcast::<Element>()
+            .get_string_attribute(&local_name!("src"));
+        let url = match document.base_url().join(&src) {
+            Ok(url) if !src.is_empty() => url,
+            _ => {
+                self.queue_load_result(false);
+                return;
+            },
+        };
+
+        // Step 11.
+        let request = create_a_potential_CORS_request(
+            url.clone(),
+            Destination::Track,
+            cors_setting_for_element(media_element.upcast()),
+            None,
+        )
+        .origin(document.origin().immutable().clone())
+        .pipeline_id(Some(document.global().pipeline_id()));
+
+        let context = Arc::new(Mutex::new(TrackContext {
+            elem: Trusted::new(self),
+            generation_id: self.generation_id.get(),
+            status_ok: true,
+            data: vec![],
+            resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+            url,
+        }));
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = document
+            .window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let listener = NetworkListener {
+            context,
+            task_source,
+            canceller: Some(canceller),
+        };
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                listener.notify_fetch(message.to().unwrap());
+            }),
+        );
+        document.fetch_async(LoadType::Media, request, action_sender);
+    }
+
+    /// Parses the fetched track resource and adds its cues to the track.
+    fn process_track_data(&self, data: Option<Vec<u8>>) {
+        // WebVTT files are always encoded as UTF-8.
+        let cues = match data.and_then(|data| webvtt::parse(&String::from_utf8_lossy(&data))) {
+            Some(cues) => cues,
+            None => {
+                self.queue_load_result(false);
+                return;
+            },
+        };
+        let window = window_from_node(self);
+        let cues: Vec<_> = cues
+            .into_iter()
+            .map(|cue| DomRoot::upcast::<TextTrackCue>(VTTCue::new_from_parsed(&window, cue)))
+            .collect();
+        self.track.add_cues(&cues);
+        self.queue_load_result(true);
+    }
+
+    /// Sets the readiness state once the track resource is processed, and
+    /// queues a task to fire a `load` or an `error` event.
+    fn queue_load_result(&self, loaded: bool) {
+        let (ready_state, event) = if loaded {
+            (ReadyState::Loaded, atom!("load"))
+        } else {
+            (ReadyState::Error, atom!("error"))
+        };
+        self.ready_state.set(ready_state);
+        let window = window_from_node(self);
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), event, &window);
+    }
+
+    fn kind(&self) -> TextTrackKind {
+        match &*self.Kind() {
+            "captions" => TextTrackKind::Captions,
+            "descriptions" => TextTrackKind::Descriptions,
+            "chapters" => TextTrackKind::Chapters,
+            "metadata" => TextTrackKind::Metadata,
+            _ => TextTrackKind::Subtitles,
+        }
+    }
 }
 
 impl HTMLTrackElementMethods for HTMLTrackElement {
@@ -125,7 +271,7 @@ impl HTMLTrackElementMethods for HTMLTrackElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-track-readystate
     fn ReadyState(&self) -> u16 {
-        self.ready_state as u16
+        self.ready_state.get() as u16
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-track-track
@@ -133,3 +279,122 @@ impl HTMLTrackElementMethods for HTMLTrackElement {
         DomRoot::from_ref(&*self.track)
     }
 }
+
+impl VirtualMethods for HTMLTrackElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+
+        match attr.local_name() {
+            &local_name!("kind") => self.track.set_kind(self.kind()),
+            &local_name!("label") => self.track.set_label(self.Label()),
+            &local_name!("srclang") => self.track.set_language(self.Srclang()),
+            // https://html.spec.whatwg.org/multipage/#sourcing-out-of-band-text-tracks:attr-track-src
+            &local_name!("src") => {
+                self.generation_id.set(self.generation_id.get() + 1);
+                if self.ready_state.get() != ReadyState::None {
+                    self.track.clear_cues();
+                    self.ready_state.set(ReadyState::None);
+                }
+                self.start_track_processing();
+            },
+            _ => {},
+        }
+    }
+
+    fn bind_to_tree(&self, context: &BindContext) {
+        self.super_type().unwrap().bind_to_tree(context);
+
+        if let Some(media_element) = self.parent_media_element() {
+            media_element.add_track_element(self);
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        if let Some(media_element) = context.parent.downcast::<HTMLMediaElement>() {
+            media_element.remove_track_element(self);
+        }
+    }
+}
+
+struct TrackContext {
+    /// The element that initiated the request.
+    elem: Trusted<HTMLTrackElement>,
+    /// The generation of the track URL this request fetches.
+    generation_id: u32,
+    /// Whether the response has an ok status.
+    status_ok: bool,
+    /// The body of the response.
+    data: Vec<u8>,
+    /// Timing data for this resource.
+    resource_timing: ResourceFetchTiming,
+    /// The URL of the track resource.
+    url: ServoUrl,
+}
+
+impl FetchResponseListener for TrackContext {
+    fn process_request_body(&mut self) {}
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>) {
+        let metadata = metadata.ok().map(|meta| match meta {
+            FetchMetadata::Unfiltered(m) => m,
+            FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+        });
+        self.status_ok = metadata.as_ref().map_or(false, |m| {
+            m.status.as_ref().map_or(true, |s| s.0 >= 200 && s.0 < 300)
+        });
+    }
+
+    fn process_response_chunk(&mut self, mut payload: Vec<u8>) {
+        if self.status_ok {
+            self.data.append(&mut payload);
+        }
+    }
+
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let elem = self.elem.root();
+        document_from_node(&*elem).finish_load(LoadType::Media);
+        if self.generation_id != elem.generation_id.get() {
+            return;
+        }
+        let data = if response.is_ok() && self.status_ok {
+            Some(std::mem::replace(&mut self.data, vec![]))
+        } else {
+            None
+        };
+        elem.process_track_data(data);
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for TrackContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (
+            InitiatorType::LocalName("track".to_owned()),
+            self.url.clone(),
+        )
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        document_from_node(&*self.elem.root()).global()
+    }
+}
+
+impl PreInvoke for TrackContext {}
@@ -2,12 +2,27 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use webgpu::wgpu::{AdapterId, Backend, DeviceId, IdentityManager, SurfaceId};
+use webgpu::wgpu::{
+    AdapterId, Backend, BindGroupLayoutId, BufferId, CommandEncoderId, ComputePassId,
+    ComputePipelineId, DeviceId, IdentityManager, PipelineLayoutId, RenderPassId, RenderPipelineId,
+    ShaderModuleId, SurfaceId, TextureId, TextureViewId,
+};
 
 #[derive(Debug)]
 pub struct IdentityHub {
     adapters: IdentityManager<AdapterId>,
     devices: IdentityManager<DeviceId>,
+    bind_group_layouts: IdentityManager<BindGroupLayoutId>,
+    pipeline_layouts: IdentityManager<PipelineLayoutId>,
+    shader_modules: IdentityManager<ShaderModuleId>,
+    compute_pipelines: IdentityManager<ComputePipelineId>,
+    command_encoders: IdentityManager<CommandEncoderId>,
+    compute_passes: IdentityManager<ComputePassId>,
+    render_pipelines: IdentityManager<RenderPipelineId>,
+    render_passes: IdentityManager<RenderPassId>,
+    textures: IdentityManager<TextureId>,
+    texture_views: IdentityManager<TextureViewId>,
+    buffers: IdentityManager<BufferId>,
 }
 
 impl IdentityHub {
@@ -15,6 +30,17 @@ impl IdentityHub {
         IdentityHub {
             adapters: IdentityManager::new(backend),
             devices: IdentityManager::new(backend),
+            bind_group_layouts: IdentityManager::new(backend),
+            pipeline_layouts: IdentityManager::new(backend),
+            shader_modules: IdentityManager::new(backend),
+            compute_pipelines: IdentityManager::new(backend),
+            command_encoders: IdentityManager::new(backend),
+            compute_passes: IdentityManager::new(backend),
+            render_pipelines: IdentityManager::new(backend),
+            render_passes: IdentityManager::new(backend),
+            textures: IdentityManager::new(backend),
+            texture_views: IdentityManager::new(backend),
+            buffers: IdentityManager::new(backend),
         }
     }
 }
@@ -48,4 +74,48 @@ impl Identities {
     pub fn create_device_id(&mut self) -> DeviceId {
         self.hub.devices.alloc()
     }
+
+    pub fn create_bind_group_layout_id(&mut self) -> BindGroupLayoutId {
+        self.hub.bind_group_layouts.alloc()
+    }
+
+    pub fn create_pipeline_layout_id(&mut self) -> PipelineLayoutId {
+        self.hub.pipeline_layouts.alloc()
+    }
+
+    pub fn create_shader_module_id(&mut self) -> ShaderModuleId {
+        self.hub.shader_modules.alloc()
+    }
+
+    pub fn create_compute_pipeline_id(&mut self) -> ComputePipelineId {
+        self.hub.compute_pipelines.alloc()
+    }
+
+    pub fn create_command_encoder_id(&mut self) -> CommandEncoderId {
+        self.hub.command_encoders.alloc()
+    }
+
+    pub fn create_compute_pass_id(&mut self) -> ComputePassId {
+        self.hub.compute_passes.alloc()
+    }
+
+    pub fn create_render_pipeline_id(&mut self) -> RenderPipelineId {
+        self.hub.render_pipelines.alloc()
+    }
+
+    pub fn create_render_pass_id(&mut self) -> RenderPassId {
+        self.hub.render_passes.alloc()
+    }
+
+    pub fn create_texture_id(&mut self) -> TextureId {
+        self.hub.textures.alloc()
+    }
+
+    pub fn create_texture_view_id(&mut self) -> TextureViewId {
+        self.hub.texture_views.alloc()
+    }
+
+    pub fn create_buffer_id(&mut self) -> BufferId {
+        self.hub.buffers.alloc()
+    }
 }
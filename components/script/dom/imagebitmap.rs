@@ -0,0 +1,538 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
+    ImageBitmapMethods, ImageBitmapOptions, ImageBitmapSource, ImageOrientation, PremultiplyAlpha,
+    ResizeQuality,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
+use crate::dom::blob::Blob;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crate::unpremultiplytable::UNPREMULTIPLY_TABLE;
+use dom_struct::dom_struct;
+use euclid::default::{Point2D, Rect, Size2D};
+use image::imageops::{self, FilterType};
+use image::{ImageBuffer, Rgba};
+use js::jsapi::MutableHandleObject;
+use net_traits::image::base::{load_from_memory, Image};
+use net_traits::image_cache::CorsStatus;
+use pixels::PixelFormat;
+use script_traits::transferable::ImageBitmapImpl;
+use std::rc::Rc;
+use std::thread;
+
+#[dom_struct]
+pub struct ImageBitmap {
+    reflector_: Reflector,
+    width: u32,
+    height: u32,
+    /// The bitmap data, as BGRA pixels, or `None` once the bitmap has been closed.
+    bitmap_data: DomRefCell<Option<Vec<u8>>>,
+    /// Whether the colors of the bitmap data are premultiplied by their alpha,
+    /// which they are unless the bitmap was created with `premultiplyAlpha: "none"`.
+    alpha_premultiplied: bool,
+    /// https://html.spec.whatwg.org/multipage/#concept-canvas-origin-clean
+    origin_clean: bool,
+}
+
+impl ImageBitmap {
+    fn new_inherited(
+        width: u32,
+        height: u32,
+        bitmap_data: Vec<u8>,
+        alpha_premultiplied: bool,
+        origin_clean: bool,
+    ) -> ImageBitmap {
+        ImageBitmap {
+            reflector_: Reflector::new(),
+            width,
+            height,
+            bitmap_data: DomRefCell::new(Some(bitmap_data)),
+            alpha_premultiplied,
+            origin_clean,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        width: u32,
+        height: u32,
+        bitmap_data: Vec<u8>,
+        alpha_premultiplied: bool,
+        origin_clean: bool,
+    ) -> DomRoot<ImageBitmap> {
+        reflect_dom_object(
+            Box::new(ImageBitmap::new_inherited(
+                width,
+                height,
+                bitmap_data,
+                alpha_premultiplied,
+                origin_clean,
+            )),
+            global,
+            ImageBitmapBinding::Wrap,
+        )
+    }
+
+    pub fn get_size(&self) -> Size2D<u32> {
+        Size2D::new(self.width, self.height)
+    }
+
+    /// The bitmap data, or `None` if the bitmap has been closed.
+    pub fn bitmap_data(&self) -> Option<Vec<u8>> {
+        self.bitmap_data.borrow().clone()
+    }
+
+    pub fn is_alpha_premultiplied(&self) -> bool {
+        self.alpha_premultiplied
+    }
+
+    /// The bitmap data as premultiplied BGRA pixels, which is what drawing
+    /// expects, or `None` if the bitmap has been closed.
+    pub fn premultiplied_bitmap_data(&self) -> Option<Vec<u8>> {
+        let mut data = self.bitmap_data()?;
+        if !self.alpha_premultiplied {
+            pixels::rgba8_premultiply_inplace(&mut data);
+        }
+        Some(data)
+    }
+
+    pub fn origin_is_clean(&self) -> bool {
+        self.origin_clean
+    }
+
+    fn is_detached(&self) -> bool {
+        self.bitmap_data.borrow().is_none()
+    }
+}
+
+impl ImageBitmapMethods for ImageBitmap {
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-width
+    fn Width(&self) -> u32 {
+        if self.is_detached() {
+            return 0;
+        }
+        self.width
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-height
+    fn Height(&self) -> u32 {
+        if self.is_detached() {
+            return 0;
+        }
+        self.height
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-close
+    fn Close(&self) {
+        *self.bitmap_data.borrow_mut() = None;
+    }
+}
+
+impl Transferable for ImageBitmap {
+    /// <https://html.spec.whatwg.org/multipage/#the-imagebitmap-interface:transfer-steps>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        let image_bitmaps = match sc_holder {
+            StructuredDataHolder::Write { image_bitmaps, .. } => image_bitmaps,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+
+        // Steps 1-2, the bitmap data of this bitmap is unset,
+        // which also detaches it.
+        let bitmap_data = self.bitmap_data.borrow_mut().take().ok_or(())?;
+        let bitmap_impl = ImageBitmapImpl {
+            width: self.width,
+            height: self.height,
+            bitmap_data,
+            alpha_premultiplied: self.alpha_premultiplied,
+            origin_clean: self.origin_clean,
+        };
+
+        // The transferred bitmap is stored at the index returned as its extra data.
+        let image_bitmaps = image_bitmaps.get_or_insert_with(Vec::new);
+        image_bitmaps.push(bitmap_impl);
+        Ok((image_bitmaps.len() - 1) as u64)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-imagebitmap-interface:transfer-receiving-steps>
+    fn transfer_receive(
+        owner: &DomRoot<GlobalScope>,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        let image_bitmaps = match sc_holder {
+            StructuredDataHolder::Read { image_bitmaps, .. } => image_bitmaps,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+        let bitmap_impl = image_bitmaps
+            .as_ref()
+            .and_then(|bitmaps| bitmaps.get(extra_data as usize))
+            .ok_or(())?
+            .clone();
+
+        // Steps 1-2.
+        let image_bitmap = ImageBitmap::new(
+            owner,
+            bitmap_impl.width,
+            bitmap_impl.height,
+            bitmap_impl.bitmap_data,
+            bitmap_impl.alpha_premultiplied,
+            bitmap_impl.origin_clean,
+        );
+        return_object.set(image_bitmap.reflector().rootable().get());
+        Ok(())
+    }
+}
+
+/// The largest number of pixels of a bitmap created by `createImageBitmap()`,
+/// so that script can't make us allocate without bound.
+const MAX_BITMAP_AREA: usize = 1 << 26;
+
+/// The BGRA pixels of the source of a bitmap, or of the bitmap itself.
+struct Pixels {
+    data: Vec<u8>,
+    size: Size2D<u32>,
+    /// Whether the colors are premultiplied by their alpha.
+    alpha_premultiplied: bool,
+}
+
+/// The options of `createImageBitmap()` that apply to the pixels of its
+/// source, so that they can be applied off the script thread.
+struct Formatting {
+    crop: Option<Rect<i64>>,
+    resize_width: Option<u32>,
+    resize_height: Option<u32>,
+    resize_quality: ResizeQuality,
+    flip_y: bool,
+    premultiply_alpha: bool,
+}
+
+/// <https://html.spec.whatwg.org/multipage/#dom-createimagebitmap>
+pub fn create_image_bitmap(
+    global: &GlobalScope,
+    image: ImageBitmapSource,
+    crop: Option<(i32, i32, i32, i32)>,
+    options: &ImageBitmapOptions,
+    comp: InCompartment,
+) -> Rc<Promise> {
+    let promise = Promise::new_in_current_compartment(global, comp);
+    // Steps 1-2.
+    let formatting = match Formatting::new(crop, options) {
+        Ok(formatting) => formatting,
+        Err(error) => {
+            promise.reject_error(error);
+            return promise;
+        },
+    };
+
+    // Steps 3-6.
+    if let ImageBitmapSource::Blob(ref blob) = image {
+        decode_blob(blob, formatting, &promise);
+        return promise;
+    }
+    let result = pixels_from_source(global, image)
+        .and_then(|(pixels, origin_clean)| Ok((formatting.apply(pixels)?, origin_clean)));
+    match result {
+        Ok((pixels, origin_clean)) => resolve_with_bitmap(&promise, pixels, origin_clean),
+        Err(error) => promise.reject_error(error),
+    }
+    promise
+}
+
+fn resolve_with_bitmap(promise: &Promise, pixels: Pixels, origin_clean: bool) {
+    let image_bitmap = ImageBitmap::new(
+        &promise.global(),
+        pixels.size.width,
+        pixels.size.height,
+        pixels.data,
+        pixels.alpha_premultiplied,
+        origin_clean,
+    );
+    promise.resolve_native(&image_bitmap);
+}
+
+/// Reads the image of `blob`, then decodes and formats it on a thread of its
+/// own, and settles `promise` with the resulting bitmap.
+fn decode_blob(blob: &Blob, formatting: Formatting, promise: &Rc<Promise>) {
+    let trusted_promise = TrustedPromise::new(promise.clone());
+    blob.read_all(move |bytes| {
+        let promise = trusted_promise.root();
+        let bytes = match bytes {
+            Ok(bytes) => bytes,
+            Err(()) => return promise.reject_error(Error::InvalidState),
+        };
+        let global = promise.global();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_promise = TrustedPromise::new(promise);
+        thread::Builder::new()
+            .name("ImageBitmap decoder".to_owned())
+            .spawn(move || {
+                let result = load_from_memory(&bytes, CorsStatus::Safe)
+                    .ok_or(Error::InvalidState)
+                    .and_then(|image| image_pixels(&image))
+                    .and_then(|pixels| formatting.apply(pixels));
+                let _ = task_source.queue_with_canceller(
+                    task!(resolve_decoded_image_bitmap: move || {
+                        let promise = trusted_promise.root();
+                        match result {
+                            Ok(pixels) => resolve_with_bitmap(&promise, pixels, true),
+                            Err(error) => promise.reject_error(error),
+                        }
+                    }),
+                    &canceller,
+                );
+            })
+            .expect("Thread spawning failed");
+    });
+}
+
+/// Steps 3-6 of `createImageBitmap()` for the sources whose pixels are
+/// already decoded, returning them and the origin-clean flag of the bitmap.
+fn pixels_from_source(global: &GlobalScope, image: ImageBitmapSource) -> Fallible<(Pixels, bool)> {
+    match image {
+        ImageBitmapSource::HTMLImageElement(ref image) => {
+            let data = image.image().ok_or(Error::InvalidState)?;
+            let origin_clean = image.same_origin(global.origin());
+            Ok((image_pixels(&data)?, origin_clean))
+        },
+        ImageBitmapSource::HTMLCanvasElement(ref canvas) => {
+            let (data, size) = canvas.fetch_all_data().ok_or(Error::InvalidState)?;
+            let data = data
+                .map(|data| data.to_vec())
+                .unwrap_or_else(|| vec![0; size.area() as usize * 4]);
+            let pixels = Pixels {
+                data,
+                size,
+                alpha_premultiplied: true,
+            };
+            Ok((pixels, canvas.origin_is_clean()))
+        },
+        ImageBitmapSource::OffscreenCanvas(ref canvas) => {
+            let (data, size) = canvas.fetch_all_data().ok_or(Error::InvalidState)?;
+            let data = data
+                .map(|data| data.to_vec())
+                .unwrap_or_else(|| vec![0; size.area() as usize * 4]);
+            let pixels = Pixels {
+                data,
+                size,
+                alpha_premultiplied: true,
+            };
+            Ok((pixels, canvas.origin_is_clean()))
+        },
+        ImageBitmapSource::ImageBitmap(ref bitmap) => {
+            let pixels = Pixels {
+                data: bitmap.bitmap_data().ok_or(Error::InvalidState)?,
+                size: bitmap.get_size(),
+                alpha_premultiplied: bitmap.is_alpha_premultiplied(),
+            };
+            Ok((pixels, bitmap.origin_is_clean()))
+        },
+        ImageBitmapSource::ImageData(ref image_data) => {
+            let mut data = image_data.to_shared_memory().to_vec();
+            pixels::rgba8_byte_swap_colors_inplace(&mut data);
+            let pixels = Pixels {
+                data,
+                size: image_data.get_size(),
+                alpha_premultiplied: false,
+            };
+            Ok((pixels, true))
+        },
+        ImageBitmapSource::Blob(_) => unreachable!("Blobs are decoded off the script thread"),
+        ImageBitmapSource::CSSStyleValue(_) => Err(Error::Type(
+            "CSSStyleValue is not a valid image bitmap source".to_owned(),
+        )),
+    }
+}
+
+/// The pixels of a decoded image.
+fn image_pixels(image: &Image) -> Fallible<Pixels> {
+    let data = match image.format {
+        PixelFormat::BGRA8 => image.bytes.to_vec(),
+        PixelFormat::RGB8 => image
+            .bytes
+            .chunks(3)
+            .flat_map(|rgb| vec![rgb[2], rgb[1], rgb[0], 0xff])
+            .collect(),
+        _ => return Err(Error::InvalidState),
+    };
+    Ok(Pixels {
+        data,
+        size: Size2D::new(image.width, image.height),
+        alpha_premultiplied: false,
+    })
+}
+
+/// The length of the data of a bitmap of `size`, or an InvalidStateError if
+/// it has more than `MAX_BITMAP_AREA` pixels.
+fn bitmap_length(size: Size2D<u32>) -> Fallible<usize> {
+    (size.width as usize)
+        .checked_mul(size.height as usize)
+        .filter(|&area| area <= MAX_BITMAP_AREA)
+        .map(|area| area * 4)
+        .ok_or(Error::InvalidState)
+}
+
+/// Divides the colors of premultiplied pixels by their alpha.
+fn unpremultiply_inplace(data: &mut [u8]) {
+    for pixel in data.chunks_mut(4) {
+        let alpha = pixel[3] as usize;
+        for color in &mut pixel[..3] {
+            *color = UNPREMULTIPLY_TABLE[256 * alpha + *color as usize];
+        }
+    }
+}
+
+impl Formatting {
+    /// Steps 1-2 of `createImageBitmap()`.
+    fn new(
+        crop: Option<(i32, i32, i32, i32)>,
+        options: &ImageBitmapOptions,
+    ) -> Fallible<Formatting> {
+        // Step 1.
+        let crop = match crop {
+            Some((_, _, 0, _)) | Some((_, _, _, 0)) => {
+                return Err(Error::Range("The crop rect must not be empty".to_owned()));
+            },
+            Some((sx, sy, sw, sh)) => {
+                // https://html.spec.whatwg.org/multipage/#cropped-to-the-source-rectangle-with-formatting
+                // Steps 2-3, negative sizes extend the rect to the left and top.
+                let (sx, sw) = if sw < 0 {
+                    (sx as i64 + sw as i64, -(sw as i64))
+                } else {
+                    (sx as i64, sw as i64)
+                };
+                let (sy, sh) = if sh < 0 {
+                    (sy as i64 + sh as i64, -(sh as i64))
+                } else {
+                    (sy as i64, sh as i64)
+                };
+                Some(Rect::new(Point2D::new(sx, sy), Size2D::new(sw, sh)))
+            },
+            None => None,
+        };
+
+        // Step 2.
+        if options.resizeWidth == Some(0) || options.resizeHeight == Some(0) {
+            return Err(Error::InvalidState);
+        }
+
+        Ok(Formatting {
+            crop,
+            resize_width: options.resizeWidth,
+            resize_height: options.resizeHeight,
+            resize_quality: options.resizeQuality,
+            flip_y: options.imageOrientation == ImageOrientation::FlipY,
+            premultiply_alpha: options.premultiplyAlpha != PremultiplyAlpha::None,
+        })
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#cropped-to-the-source-rectangle-with-formatting>
+    ///
+    /// Bitmaps are premultiplied unless `premultiplyAlpha` is `"none"`, as
+    /// that is what drawing them expects. No color space conversion is done,
+    /// so the `colorSpaceConversion` option doesn't change the resulting pixels.
+    fn apply(&self, pixels: Pixels) -> Fallible<Pixels> {
+        let Pixels {
+            data,
+            size,
+            alpha_premultiplied,
+        } = pixels;
+        if size.width == 0 || size.height == 0 {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 1.
+        let input_rect = Rect::from_size(size.to_i64());
+        let source_rect = self.crop.unwrap_or(input_rect);
+        let source_size = source_rect.size.to_u32();
+
+        // Steps 4-6.
+        let scaled = |length: u32, numerator: u32, denominator: u32| {
+            (length as f64 * numerator as f64 / denominator as f64)
+                .ceil()
+                .min(u32::max_value() as f64) as u32
+        };
+        let output_size = match (self.resize_width, self.resize_height) {
+            (Some(width), Some(height)) => Size2D::new(width, height),
+            (Some(width), None) => {
+                Size2D::new(width, scaled(source_size.height, width, source_size.width))
+            },
+            (None, Some(height)) => Size2D::new(
+                scaled(source_size.width, height, source_size.height),
+                height,
+            ),
+            (None, None) => source_size,
+        };
+        bitmap_length(output_size)?;
+
+        // Step 7, the parts of the source rect outside of the input are transparent black.
+        let mut data = if source_rect == input_rect {
+            data
+        } else {
+            let mut cropped = vec![0; bitmap_length(source_size)?];
+            if let Some(visible) = source_rect.intersection(&input_rect) {
+                let row_length = visible.size.width as usize * 4;
+                for y in visible.origin.y..visible.max_y() {
+                    let src = ((y * size.width as i64 + visible.origin.x) * 4) as usize;
+                    let dst = (((y - source_rect.origin.y) * source_size.width as i64 +
+                        visible.origin.x -
+                        source_rect.origin.x) *
+                        4) as usize;
+                    cropped[dst..dst + row_length].copy_from_slice(&data[src..src + row_length]);
+                }
+            }
+            cropped
+        };
+
+        if self.premultiply_alpha && !alpha_premultiplied {
+            pixels::rgba8_premultiply_inplace(&mut data);
+        } else if !self.premultiply_alpha && alpha_premultiplied {
+            unpremultiply_inplace(&mut data);
+        }
+
+        // Step 8.
+        let mut data = if output_size != source_size {
+            let filter = match self.resize_quality {
+                ResizeQuality::Pixelated => FilterType::Nearest,
+                ResizeQuality::Low => FilterType::Triangle,
+                ResizeQuality::Medium => FilterType::CatmullRom,
+                ResizeQuality::High => FilterType::Lanczos3,
+            };
+            let buffer: ImageBuffer<Rgba<u8>, Vec<u8>> =
+                ImageBuffer::from_raw(source_size.width, source_size.height, data).unwrap();
+            imageops::resize(&buffer, output_size.width, output_size.height, filter).into_raw()
+        } else {
+            data
+        };
+
+        // Step 9.
+        if self.flip_y {
+            let row_length = output_size.width as usize * 4;
+            let rows = output_size.height as usize;
+            for y in 0..rows / 2 {
+                let (top, bottom) = data.split_at_mut((rows - 1 - y) * row_length);
+                top[y * row_length..(y + 1) * row_length]
+                    .swap_with_slice(&mut bottom[..row_length]);
+            }
+        }
+
+        Ok(Pixels {
+            data,
+            size: output_size,
+            alpha_premultiplied: self.premultiply_alpha,
+        })
+    }
+}
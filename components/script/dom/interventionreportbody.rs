@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::InterventionReportBodyBinding::{
+    self, InterventionReportBodyMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::reporting::InterventionReport;
+use dom_struct::dom_struct;
+
+// https://wicg.github.io/intervention-reporting/#interventionreportbody
+#[dom_struct]
+pub struct InterventionReportBody {
+    reportbody: ReportBody,
+    id: DOMString,
+    message: DOMString,
+    source_file: Option<DOMString>,
+    line_number: Option<u32>,
+    column_number: Option<u32>,
+}
+
+impl InterventionReportBody {
+    fn new_inherited(report: &InterventionReport) -> InterventionReportBody {
+        InterventionReportBody {
+            reportbody: ReportBody::new_inherited(),
+            id: DOMString::from(report.id.clone()),
+            message: DOMString::from(report.message.clone()),
+            source_file: report.source_file.clone().map(DOMString::from),
+            line_number: report.line_number,
+            column_number: report.column_number,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        report: &InterventionReport,
+    ) -> DomRoot<InterventionReportBody> {
+        reflect_dom_object(
+            Box::new(InterventionReportBody::new_inherited(report)),
+            global,
+            InterventionReportBodyBinding::Wrap,
+        )
+    }
+}
+
+impl InterventionReportBodyMethods for InterventionReportBody {
+    // https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-message
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+
+    // https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-sourcefile
+    fn GetSourceFile(&self) -> Option<DOMString> {
+        self.source_file.clone()
+    }
+
+    // https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-linenumber
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.line_number
+    }
+
+    // https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-columnnumber
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.column_number
+    }
+}
@@ -0,0 +1,509 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationeffect::{parse_easing, AnimationEffect, Timing};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::AnimationEffectMethods;
+use crate::dom::bindings::codegen::Bindings::KeyframeEffectBinding::{
+    self, CompositeOperation, KeyframeEffectMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrKeyframeEffectOptions;
+use crate::dom::bindings::conversions::StringificationBehavior;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::record::Record;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::bindings::utils::set_dictionary_property;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use cssparser::SourceLocation;
+use dom_struct::dom_struct;
+use js::conversions::{ConversionResult, FromJSValConvertible, ToJSValConvertible};
+use js::jsapi::{Heap, JSContext as RawJSContext, JSObject, JS_NewPlainObject};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::JS_IsArrayObject;
+use js::rust::{HandleObject, HandleValue};
+use servo_arc::Arc;
+use std::ptr::NonNull;
+use style::properties::{
+    parse_one_declaration_into, Importance, PropertyDeclarationBlock, PropertyId,
+    SourcePropertyDeclaration,
+};
+use style::stylesheets::keyframes_rule::{Keyframe as StyleKeyframe, KeyframePercentage};
+use style::stylesheets::keyframes_rule::{KeyframeSelector, KeyframesAnimation};
+use style_traits::ParsingMode;
+
+/// The value a keyframe object gives to one of its properties, which is a
+/// list of values, one per keyframe, in the property-indexed form.
+///
+/// <https://drafts.csswg.org/web-animations/#processing-a-keyframes-argument>
+enum KeyframeValue {
+    Single(DOMString),
+    List(Vec<DOMString>),
+}
+
+impl FromJSValConvertible for KeyframeValue {
+    type Config = ();
+
+    #[allow(unsafe_code)]
+    unsafe fn from_jsval(
+        cx: *mut RawJSContext,
+        value: HandleValue,
+        _: (),
+    ) -> Result<ConversionResult<KeyframeValue>, ()> {
+        if value.is_object() {
+            let mut is_array = false;
+            if !JS_IsArrayObject(cx, value, &mut is_array) {
+                return Err(());
+            }
+            if is_array {
+                return Ok(
+                    match Vec::<DOMString>::from_jsval(cx, value, StringificationBehavior::Default)?
+                    {
+                        ConversionResult::Success(values) => {
+                            ConversionResult::Success(KeyframeValue::List(values))
+                        },
+                        ConversionResult::Failure(message) => ConversionResult::Failure(message),
+                    },
+                );
+            }
+        }
+        Ok(
+            match DOMString::from_jsval(cx, value, StringificationBehavior::Default)? {
+                ConversionResult::Success(value) => {
+                    ConversionResult::Success(KeyframeValue::Single(value))
+                },
+                ConversionResult::Failure(message) => ConversionResult::Failure(message),
+            },
+        )
+    }
+}
+
+/// <https://drafts.csswg.org/web-animations/#keyframe>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+struct Keyframe {
+    offset: Option<f64>,
+    computed_offset: f64,
+    easing: DOMString,
+    /// The values of the properties the keyframe sets, by their IDL
+    /// attribute names, such as `backgroundColor`.
+    values: Vec<(DOMString, DOMString)>,
+}
+
+impl Keyframe {
+    fn new(offset: Option<f64>, computed_offset: f64) -> Keyframe {
+        Keyframe {
+            offset,
+            computed_offset,
+            easing: DOMString::from("linear"),
+            values: vec![],
+        }
+    }
+}
+
+/// Parses the offset of a keyframe, which is null when it's computed.
+fn parse_offset(offset: &DOMString) -> Fallible<Option<f64>> {
+    if &**offset == "null" || &**offset == "undefined" {
+        return Ok(None);
+    }
+    match offset.trim().parse::<f64>() {
+        Ok(offset) if offset >= 0. && offset <= 1. => Ok(Some(offset)),
+        _ => Err(Error::Type(format!(
+            "{} is not an offset between 0 and 1",
+            offset
+        ))),
+    }
+}
+
+/// The CSS property an IDL attribute name of a keyframe object stands for,
+/// such as `background-color` for `backgroundColor`.
+///
+/// <https://drafts.csswg.org/web-animations/#idl-attribute-name-to-animation-property-name>
+fn property_id(name: &str) -> Option<PropertyId> {
+    if name == "cssFloat" {
+        return PropertyId::parse_enabled_for_all_content("float").ok();
+    }
+    // Properties are only known by their IDL attribute names.
+    if name == "float" || name.contains('-') {
+        return None;
+    }
+    let mut css_name = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            css_name.push('-');
+            css_name.push(c.to_ascii_lowercase());
+        } else {
+            css_name.push(c);
+        }
+    }
+    PropertyId::parse_enabled_for_all_content(&css_name).ok()
+}
+
+/// <https://drafts.csswg.org/web-animations/#compute-missing-keyframe-offsets>
+fn compute_missing_offsets(keyframes: &mut [Keyframe]) {
+    let count = keyframes.len();
+    if count == 0 {
+        return;
+    }
+    let mut offsets: Vec<Option<f64>> = keyframes.iter().map(|keyframe| keyframe.offset).collect();
+    if count > 1 && offsets[0].is_none() {
+        offsets[0] = Some(0.);
+    }
+    if offsets[count - 1].is_none() {
+        offsets[count - 1] = Some(1.);
+    }
+    // The keyframes without an offset are spaced evenly between the ones
+    // around them.
+    let mut previous = 0;
+    for index in 1..count {
+        if let Some(offset) = offsets[index] {
+            let start = offsets[previous].unwrap_or(0.);
+            let steps = (index - previous) as f64;
+            for step in previous + 1..index {
+                offsets[step] = Some(start + (offset - start) * (step - previous) as f64 / steps);
+            }
+            previous = index;
+        }
+    }
+    for (keyframe, offset) in keyframes.iter_mut().zip(offsets) {
+        keyframe.computed_offset = offset.unwrap_or(1.);
+    }
+}
+
+/// <https://drafts.csswg.org/web-animations/#processing-a-keyframes-argument>
+#[allow(unsafe_code)]
+fn process_keyframes(cx: JSContext, keyframes: *mut JSObject) -> Fallible<Vec<Keyframe>> {
+    // Step 1.
+    if keyframes.is_null() {
+        return Ok(vec![]);
+    }
+
+    rooted!(in(*cx) let value = ObjectValue(keyframes));
+    let mut is_array = false;
+    if !unsafe { JS_IsArrayObject(*cx, value.handle(), &mut is_array) } {
+        return Err(Error::JSFailed);
+    }
+
+    let mut result = if is_array {
+        // Step 5, the array of keyframes form.
+        let objects = match unsafe {
+            Vec::<Record<DOMString, KeyframeValue>>::from_jsval(*cx, value.handle(), ())
+        } {
+            Ok(ConversionResult::Success(objects)) => objects,
+            Ok(ConversionResult::Failure(message)) => return Err(Error::Type(message.into())),
+            Err(()) => return Err(Error::JSFailed),
+        };
+        let mut result = vec![];
+        for object in objects {
+            let mut keyframe = Keyframe::new(None, 0.);
+            for (name, value) in object.iter() {
+                let value = match *value {
+                    KeyframeValue::Single(ref value) => value.clone(),
+                    KeyframeValue::List(ref values) => DOMString::from(
+                        values
+                            .iter()
+                            .map(|value| &**value)
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    ),
+                };
+                match &**name {
+                    "offset" => keyframe.offset = parse_offset(&value)?,
+                    "easing" => keyframe.easing = value,
+                    // Keyframes only replace the underlying values.
+                    "composite" => {},
+                    _ => keyframe.values.push((name.clone(), value)),
+                }
+            }
+            result.push(keyframe);
+        }
+        result
+    } else {
+        // Step 6, the property-indexed form.
+        let object = match unsafe {
+            Record::<DOMString, KeyframeValue>::from_jsval(*cx, value.handle(), ())
+        } {
+            Ok(ConversionResult::Success(object)) => object,
+            Ok(ConversionResult::Failure(message)) => return Err(Error::Type(message.into())),
+            Err(()) => return Err(Error::JSFailed),
+        };
+        let mut result: Vec<Keyframe> = vec![];
+        let mut offsets = vec![];
+        let mut easings = vec![];
+        for (name, value) in object.iter() {
+            let values = match *value {
+                KeyframeValue::Single(ref value) => vec![value.clone()],
+                KeyframeValue::List(ref values) => values.clone(),
+            };
+            match &**name {
+                "offset" => {
+                    offsets = values
+                        .iter()
+                        .map(parse_offset)
+                        .collect::<Fallible<Vec<_>>>()?
+                },
+                "easing" => easings = values,
+                "composite" => {},
+                _ => {
+                    // The values of a property are spaced evenly.
+                    let count = values.len();
+                    for (index, value) in values.into_iter().enumerate() {
+                        let computed_offset = if count == 1 {
+                            1.
+                        } else {
+                            index as f64 / (count - 1) as f64
+                        };
+                        match result
+                            .iter_mut()
+                            .find(|keyframe| keyframe.computed_offset == computed_offset)
+                        {
+                            Some(keyframe) => keyframe.values.push((name.clone(), value)),
+                            None => {
+                                let mut keyframe = Keyframe::new(None, computed_offset);
+                                keyframe.values.push((name.clone(), value));
+                                result.push(keyframe);
+                            },
+                        }
+                    }
+                },
+            }
+        }
+        result.sort_by(|a, b| a.computed_offset.partial_cmp(&b.computed_offset).unwrap());
+        for (keyframe, offset) in result.iter_mut().zip(offsets) {
+            if let Some(offset) = offset {
+                keyframe.offset = Some(offset);
+                keyframe.computed_offset = offset;
+            }
+        }
+        // The easings repeat when there are fewer of them than keyframes.
+        if !easings.is_empty() {
+            for (index, keyframe) in result.iter_mut().enumerate() {
+                keyframe.easing = easings[index % easings.len()].clone();
+            }
+        }
+        result
+    };
+
+    // Step 7. The offsets that were given must be loosely sorted.
+    let mut previous_offset = 0.;
+    for offset in result.iter().filter_map(|keyframe| keyframe.offset) {
+        if offset < previous_offset {
+            return Err(Error::Type(
+                "Keyframe offsets must be loosely sorted".to_owned(),
+            ));
+        }
+        previous_offset = offset;
+    }
+
+    // Step 8.
+    for keyframe in &result {
+        parse_easing(&keyframe.easing)?;
+    }
+
+    if is_array {
+        compute_missing_offsets(&mut result);
+    }
+    Ok(result)
+}
+
+/// Sets a property of a keyframe object that `getKeyframes()` returns.
+#[allow(unsafe_code)]
+fn set_property<T: ToJSValConvertible>(
+    cx: JSContext,
+    object: HandleObject,
+    name: &str,
+    value: &T,
+) -> ErrorResult {
+    rooted!(in(*cx) let mut jsval = UndefinedValue());
+    unsafe { value.to_jsval(*cx, jsval.handle_mut()) };
+    set_dictionary_property(*cx, object, name, jsval.handle()).map_err(|()| Error::JSFailed)
+}
+
+// https://drafts.csswg.org/web-animations/#the-keyframeeffect-interface
+#[dom_struct]
+pub struct KeyframeEffect {
+    animationeffect: AnimationEffect,
+    target: MutNullableDom<Element>,
+    keyframes: DomRefCell<Vec<Keyframe>>,
+}
+
+impl KeyframeEffect {
+    fn new_inherited(
+        target: Option<&Element>,
+        keyframes: Vec<Keyframe>,
+        timing: Timing,
+    ) -> KeyframeEffect {
+        KeyframeEffect {
+            animationeffect: AnimationEffect::new_inherited(timing),
+            target: MutNullableDom::new(target),
+            keyframes: DomRefCell::new(keyframes),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        target: Option<&Element>,
+        keyframes: Vec<Keyframe>,
+        timing: Timing,
+    ) -> DomRoot<KeyframeEffect> {
+        reflect_dom_object(
+            Box::new(KeyframeEffect::new_inherited(target, keyframes, timing)),
+            window,
+            KeyframeEffectBinding::Wrap,
+        )
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-keyframeeffect
+    pub fn Constructor(
+        cx: JSContext,
+        window: &Window,
+        target: Option<&Element>,
+        keyframes: *mut JSObject,
+        options: UnrestrictedDoubleOrKeyframeEffectOptions,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let timing = match options {
+            UnrestrictedDoubleOrKeyframeEffectOptions::UnrestrictedDouble(duration) => {
+                Timing::from_duration(duration)?
+            },
+            UnrestrictedDoubleOrKeyframeEffectOptions::KeyframeEffectOptions(ref options) => {
+                Timing::from_dictionary(&options.parent)?
+            },
+        };
+        KeyframeEffect::create(cx, window, target, keyframes, timing)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-keyframeeffect-source
+    pub fn Constructor_(
+        window: &Window,
+        source: &KeyframeEffect,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let timing = Timing::from_dictionary(&source.upcast::<AnimationEffect>().GetTiming())?;
+        Ok(KeyframeEffect::new(
+            window,
+            source.target.get().as_deref(),
+            source.keyframes.borrow().clone(),
+            timing,
+        ))
+    }
+
+    /// Creates an effect from the keyframes argument of its constructor, or
+    /// of `element.animate()`.
+    pub fn create(
+        cx: JSContext,
+        window: &Window,
+        target: Option<&Element>,
+        keyframes: *mut JSObject,
+        timing: Timing,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let keyframes = process_keyframes(cx, keyframes)?;
+        Ok(KeyframeEffect::new(window, target, keyframes, timing))
+    }
+
+    pub fn target(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    /// The keyframes of the effect as layout runs them, with the property
+    /// values that parse.
+    pub fn style_keyframes(&self, document: &Document) -> KeyframesAnimation {
+        let window = document.window();
+        let lock = document.style_shared_lock();
+        let url = document.base_url();
+        let keyframes: Vec<_> = self
+            .keyframes
+            .borrow()
+            .iter()
+            .map(|keyframe| {
+                let mut block = PropertyDeclarationBlock::new();
+                for &(ref name, ref value) in &keyframe.values {
+                    let id = match property_id(name) {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    let mut declarations = SourcePropertyDeclaration::new();
+                    let result = parse_one_declaration_into(
+                        &mut declarations,
+                        id,
+                        value,
+                        &url,
+                        window.css_error_reporter(),
+                        ParsingMode::DEFAULT,
+                        document.quirks_mode(),
+                    );
+                    if result.is_ok() {
+                        block.extend(declarations.drain(), Importance::Normal);
+                    }
+                }
+                Arc::new(lock.wrap(StyleKeyframe {
+                    selector: KeyframeSelector::new(vec![KeyframePercentage::new(
+                        keyframe.computed_offset as f32,
+                    )]),
+                    block: Arc::new(lock.wrap(block)),
+                    source_location: SourceLocation { line: 0, column: 0 },
+                }))
+            })
+            .collect();
+        KeyframesAnimation::from_keyframes(&keyframes, None, &lock.read())
+    }
+}
+
+impl KeyframeEffectMethods for KeyframeEffect {
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-target
+    fn GetTarget(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-target
+    fn SetTarget(&self, target: Option<&Element>) {
+        self.target.set(target);
+        self.upcast::<AnimationEffect>().changed();
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-composite
+    fn Composite(&self) -> CompositeOperation {
+        CompositeOperation::Replace
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-getkeyframes
+    #[allow(unsafe_code)]
+    fn GetKeyframes(&self, cx: JSContext) -> Fallible<Vec<NonNull<JSObject>>> {
+        // The objects stay rooted until they are all created.
+        let mut objects = RootedTraceableBox::new(Vec::<Box<Heap<*mut JSObject>>>::new());
+        for keyframe in self.keyframes.borrow().iter() {
+            rooted!(in(*cx) let object = unsafe { JS_NewPlainObject(*cx) });
+            if object.is_null() {
+                return Err(Error::JSFailed);
+            }
+            set_property(cx, object.handle(), "offset", &keyframe.offset)?;
+            set_property(
+                cx,
+                object.handle(),
+                "computedOffset",
+                &keyframe.computed_offset,
+            )?;
+            set_property(cx, object.handle(), "easing", &keyframe.easing)?;
+            set_property(cx, object.handle(), "composite", &DOMString::from("auto"))?;
+            for &(ref name, ref value) in &keyframe.values {
+                set_property(cx, object.handle(), name, value)?;
+            }
+            objects.push(Heap::boxed(object.get()));
+        }
+        Ok(objects
+            .iter()
+            .map(|object| NonNull::new(object.get()).unwrap())
+            .collect())
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-keyframeeffect-setkeyframes
+    fn SetKeyframes(&self, cx: JSContext, keyframes: *mut JSObject) -> ErrorResult {
+        *self.keyframes.borrow_mut() = process_keyframes(cx, keyframes)?;
+        self.upcast::<AnimationEffect>().changed();
+        Ok(())
+    }
+}
@@ -3,23 +3,35 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::MediaDeviceInfoBinding::MediaDeviceKind;
 use crate::dom::bindings::codegen::Bindings::MediaDevicesBinding::MediaStreamConstraints;
 use crate::dom::bindings::codegen::Bindings::MediaDevicesBinding::{self, MediaDevicesMethods};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
 use crate::dom::bindings::codegen::UnionTypes::BooleanOrMediaTrackConstraints;
 use crate::dom::bindings::codegen::UnionTypes::ClampedUnsignedLongOrConstrainULongRange as ConstrainULong;
 use crate::dom::bindings::codegen::UnionTypes::DoubleOrConstrainDoubleRange as ConstrainDouble;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::mediadeviceinfo::MediaDeviceInfo;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::mediastreamtrack::MediaStreamTrack;
+use crate::dom::permissions::{get_descriptor_permission_state, request_permission_to_use};
 use crate::dom::promise::Promise;
 use dom_struct::dom_struct;
+use openssl::sha::Sha256;
 use servo_media::streams::capture::{Constrain, ConstrainRange, MediaTrackConstraintSet};
+use servo_media::streams::device_monitor::MediaDeviceKind as ServoMediaDeviceKind;
 use servo_media::streams::MediaStreamType;
 use servo_media::ServoMedia;
+use servo_url::ImmutableOrigin;
 use std::rc::Rc;
 
 #[dom_struct]
@@ -41,6 +53,38 @@ impl MediaDevices {
             MediaDevicesBinding::Wrap,
         )
     }
+
+    /// Steps 8.5-8.7 of `getUserMedia()`, once the user allowed access to the devices.
+    fn create_stream(
+        &self,
+        p: &Promise,
+        audio: Option<MediaTrackConstraintSet>,
+        video: Option<MediaTrackConstraintSet>,
+    ) {
+        let global = self.global();
+        let media = ServoMedia::get().unwrap();
+        let stream = MediaStream::new(&global);
+        if let Some(constraints) = audio {
+            match media.create_audioinput_stream(constraints) {
+                Some(audio) => {
+                    let track = MediaStreamTrack::new(&global, audio, MediaStreamType::Audio);
+                    stream.add_track(&track);
+                },
+                None => return p.reject_error(Error::NotFound),
+            }
+        }
+        if let Some(constraints) = video {
+            match media.create_videoinput_stream(constraints) {
+                Some(video) => {
+                    let track = MediaStreamTrack::new(&global, video, MediaStreamType::Video);
+                    stream.add_track(&track);
+                },
+                None => return p.reject_error(Error::NotFound),
+            }
+        }
+
+        p.resolve_native(&stream);
+    }
 }
 
 impl MediaDevicesMethods for MediaDevices {
@@ -51,27 +95,122 @@ impl MediaDevicesMethods for MediaDevices {
         constraints: &MediaStreamConstraints,
         comp: InCompartment,
     ) -> Rc<Promise> {
-        let p = Promise::new_in_current_compartment(&self.global(), comp);
-        let media = ServoMedia::get().unwrap();
-        let stream = MediaStream::new(&self.global());
-        if let Some(constraints) = convert_constraints(&constraints.audio) {
-            if let Some(audio) = media.create_audioinput_stream(constraints) {
-                let track = MediaStreamTrack::new(&self.global(), audio, MediaStreamType::Audio);
-                stream.add_track(&track);
-            }
+        let global = self.global();
+        let p = Promise::new_in_current_compartment(&global, comp);
+        let audio = convert_constraints(&constraints.audio);
+        let video = convert_constraints(&constraints.video);
+
+        // Step 3.
+        if audio.is_none() && video.is_none() {
+            p.reject_error(Error::Type(
+                "At least one of audio and video must be requested".to_owned(),
+            ));
+            return p;
+        }
+
+        // Steps 8.2-8.4, the user is asked for each kind of requested device.
+        let mut permissions = vec![];
+        if audio.is_some() {
+            permissions.push(PermissionName::Microphone);
+        }
+        if video.is_some() {
+            permissions.push(PermissionName::Camera);
         }
-        if let Some(constraints) = convert_constraints(&constraints.video) {
-            if let Some(video) = media.create_videoinput_stream(constraints) {
-                let track = MediaStreamTrack::new(&self.global(), video, MediaStreamType::Video);
-                stream.add_track(&track);
+        let this = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(p.clone());
+        request_permissions_to_use(&global, permissions, move |granted| {
+            let p = trusted_promise.root();
+            if !granted {
+                return p.reject_error(Error::NotAllowed);
             }
+
+            // Steps 8.5-8.7.
+            this.root().create_stream(&p, audio, video);
+        });
+        p
+    }
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-enumeratedevices
+    fn EnumerateDevices(&self, comp: InCompartment) -> Rc<Promise> {
+        // Step 1.
+        let global = self.global();
+        let p = Promise::new_in_current_compartment(&global, comp);
+
+        // Step 2.5, device information is only exposed once the user
+        // allowed access to a device of the same kind.
+        let can_expose_device_info = |kind: ServoMediaDeviceKind| {
+            let name = match kind {
+                ServoMediaDeviceKind::VideoInput => PermissionName::Camera,
+                ServoMediaDeviceKind::AudioInput | ServoMediaDeviceKind::AudioOutput => {
+                    PermissionName::Microphone
+                },
+            };
+            get_descriptor_permission_state(name, Some(&global)) == PermissionState::Granted
+        };
+        let origin = global.origin().immutable();
+        let media = ServoMedia::get().unwrap();
+        let mut listed_kinds = vec![];
+        let mut devices: Vec<DomRoot<MediaDeviceInfo>> = vec![];
+        for device in media
+            .get_device_monitor()
+            .enumerate_devices()
+            .unwrap_or_default()
+        {
+            let kind: MediaDeviceKind = device.kind.into();
+            if !can_expose_device_info(device.kind) {
+                // Without permission, a single device of each kind is
+                // listed, without any identifying information.
+                if listed_kinds.contains(&kind) {
+                    continue;
+                }
+                listed_kinds.push(kind);
+                devices.push(MediaDeviceInfo::new(
+                    &global,
+                    DOMString::new(),
+                    kind,
+                    DOMString::new(),
+                    DOMString::new(),
+                ));
+                continue;
+            }
+            // The media backend does not report which devices belong to
+            // the same physical device, so devices are grouped by label.
+            let group_id = origin_bound_id(origin, &device.label);
+            devices.push(MediaDeviceInfo::new(
+                &global,
+                origin_bound_id(origin, &device.device_id),
+                kind,
+                DOMString::from(device.label),
+                group_id,
+            ));
         }
 
-        p.resolve_native(&stream);
+        // Step 3.
+        p.resolve_native(&devices);
         p
     }
 }
 
+/// Returns an identifier for `id` that is stable for `origin` but cannot be
+/// correlated across origins.
+///
+/// https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-deviceid
+fn origin_bound_id(origin: &ImmutableOrigin, id: &str) -> DOMString {
+    lazy_static! {
+        /// Per-process salt, so that identifiers cannot be matched against
+        /// the raw device identifiers of the media backend.
+        static ref SALT: [u8; 16] = servo_rand::random();
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(&*SALT);
+    hasher.update(format!("{:?}", origin).as_bytes());
+    hasher.update(id.as_bytes());
+    DOMString::from(base64::encode_config(
+        &hasher.finish(),
+        base64::URL_SAFE_NO_PAD,
+    ))
+}
+
 fn convert_constraints(js: &BooleanOrMediaTrackConstraints) -> Option<MediaTrackConstraintSet> {
     match js {
         BooleanOrMediaTrackConstraints::Boolean(false) => None,
@@ -127,3 +266,25 @@ fn convert_cdouble(js: &ConstrainDouble) -> Option<Constrain<f64>> {
         },
     }
 }
+
+/// Asks the user for each of the permissions in turn, `callback` is run with
+/// whether all of them are granted.
+fn request_permissions_to_use<F>(
+    global: &GlobalScope,
+    mut permissions: Vec<PermissionName>,
+    callback: F,
+) where
+    F: FnOnce(bool) + Send + 'static,
+{
+    if permissions.is_empty() {
+        return callback(true);
+    }
+    let name = permissions.remove(0);
+    let trusted_global = Trusted::new(global);
+    request_permission_to_use(name, global, move |state| {
+        if state != PermissionState::Granted {
+            return callback(false);
+        }
+        request_permissions_to_use(&trusted_global.root(), permissions, callback);
+    });
+}
@@ -0,0 +1,90 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::cdm::{Message, MessageType};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::MediaKeyMessageEventBinding::{
+    self, MediaKeyMessageEventMethods, MediaKeyMessageType,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::typedarray::{ArrayBuffer, CreateWith};
+use std::ptr::{self, NonNull};
+
+// https://w3c.github.io/encrypted-media/#mediakeymessageevent
+#[dom_struct]
+pub struct MediaKeyMessageEvent {
+    event: Event,
+    message_type: MediaKeyMessageType,
+    #[ignore_malloc_size_of = "mozjs"]
+    message: Heap<*mut JSObject>,
+}
+
+impl MediaKeyMessageEvent {
+    fn new_inherited(message_type: MediaKeyMessageType) -> MediaKeyMessageEvent {
+        MediaKeyMessageEvent {
+            event: Event::new_inherited(),
+            message_type,
+            message: Heap::default(),
+        }
+    }
+
+    /// Creates a `message` event carrying a message from the CDM.
+    #[allow(unsafe_code)]
+    pub fn new(window: &Window, message: Message) -> DomRoot<MediaKeyMessageEvent> {
+        let message_type = match message.message_type {
+            MessageType::LicenseRequest => MediaKeyMessageType::License_request,
+            MessageType::LicenseRenewal => MediaKeyMessageType::License_renewal,
+            MessageType::LicenseRelease => MediaKeyMessageType::License_release,
+            MessageType::IndividualizationRequest => MediaKeyMessageType::Individualization_request,
+        };
+        let event = reflect_dom_object(
+            Box::new(MediaKeyMessageEvent::new_inherited(message_type)),
+            window,
+            MediaKeyMessageEventBinding::Wrap,
+        );
+        event.upcast::<Event>().init_event(
+            atom!("message"),
+            bool::from(EventBubbles::DoesNotBubble),
+            bool::from(EventCancelable::NotCancelable),
+        );
+
+        let cx = window.get_cx();
+        rooted!(in (*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+        unsafe {
+            assert!(ArrayBuffer::create(
+                *cx,
+                CreateWith::Slice(&message.data),
+                array_buffer.handle_mut()
+            )
+            .is_ok());
+        }
+        event.message.set(array_buffer.get());
+        event
+    }
+}
+
+impl MediaKeyMessageEventMethods for MediaKeyMessageEvent {
+    // https://w3c.github.io/encrypted-media/#dom-mediakeymessageevent-messagetype
+    fn MessageType(&self) -> MediaKeyMessageType {
+        self.message_type
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeymessageevent-message
+    #[allow(unsafe_code)]
+    fn Message(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.message.get()) }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
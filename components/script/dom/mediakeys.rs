@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::cdm::Cdm;
+use crate::compartments::InCompartment;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MediaKeysBinding::{
+    self, MediaKeySessionType, MediaKeysMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::mediakeysession::MediaKeySession;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::cell::{Cell, RefMut};
+use std::rc::Rc;
+
+/// Copies the bytes of a `BufferSource`.
+pub fn buffer_source_bytes(source: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+// https://w3c.github.io/encrypted-media/#mediakeys-interface
+#[dom_struct]
+pub struct MediaKeys {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Trait object"]
+    cdm: DomRefCell<Box<dyn Cdm>>,
+    /// The session types allowed by the configuration these MediaKeys were
+    /// created with.
+    session_types: Vec<MediaKeySessionType>,
+    /// Whether a media element uses these MediaKeys.
+    in_use: Cell<bool>,
+}
+
+impl MediaKeys {
+    fn new_inherited(cdm: Box<dyn Cdm>, session_types: Vec<MediaKeySessionType>) -> MediaKeys {
+        MediaKeys {
+            reflector_: Reflector::new(),
+            cdm: DomRefCell::new(cdm),
+            session_types,
+            in_use: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        cdm: Box<dyn Cdm>,
+        session_types: Vec<MediaKeySessionType>,
+    ) -> DomRoot<MediaKeys> {
+        reflect_dom_object(
+            Box::new(MediaKeys::new_inherited(cdm, session_types)),
+            window,
+            MediaKeysBinding::Wrap,
+        )
+    }
+
+    pub fn cdm(&self) -> RefMut<Box<dyn Cdm>> {
+        self.cdm.borrow_mut()
+    }
+
+    pub fn in_use(&self) -> bool {
+        self.in_use.get()
+    }
+
+    pub fn set_in_use(&self, in_use: bool) {
+        self.in_use.set(in_use);
+    }
+}
+
+impl MediaKeysMethods for MediaKeys {
+    // https://w3c.github.io/encrypted-media/#dom-mediakeys-createsession
+    fn CreateSession(
+        &self,
+        session_type: MediaKeySessionType,
+    ) -> Fallible<DomRoot<MediaKeySession>> {
+        // Steps 1-2.
+        if !self.session_types.contains(&session_type) {
+            return Err(Error::NotSupported);
+        }
+        // Steps 3-11.
+        Ok(MediaKeySession::new(
+            self.global().as_window(),
+            self,
+            session_type,
+        ))
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeys-setservercertificate
+    fn SetServerCertificate(
+        &self,
+        server_certificate: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Step 2.
+        let certificate = buffer_source_bytes(&server_certificate);
+        if certificate.is_empty() {
+            promise.reject_error(Error::Type("The server certificate is empty".to_owned()));
+            return promise;
+        }
+
+        // Steps 1 and 3-6.
+        match self.cdm().set_server_certificate(&certificate) {
+            Ok(used) => promise.resolve_native(&used),
+            Err(_) => {
+                promise.reject_error(Error::Type("The server certificate is invalid".to_owned()))
+            },
+        }
+        promise
+    }
+}
@@ -0,0 +1,320 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::cdm::{CdmError, KeyStatus, Message};
+use crate::compartments::InCompartment;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MediaKeySessionBinding::{
+    self, MediaKeySessionMethods,
+};
+use crate::dom::bindings::codegen::Bindings::MediaKeysBinding::MediaKeySessionType;
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mediakeymessageevent::MediaKeyMessageEvent;
+use crate::dom::mediakeys::{buffer_source_bytes, MediaKeys};
+use crate::dom::mediakeystatusmap::MediaKeyStatusMap;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::f64;
+use std::rc::Rc;
+
+// https://w3c.github.io/encrypted-media/#mediakeysession-interface
+#[dom_struct]
+pub struct MediaKeySession {
+    eventtarget: EventTarget,
+    media_keys: Dom<MediaKeys>,
+    session_type: MediaKeySessionType,
+    session_id: DomRefCell<DOMString>,
+    #[ignore_malloc_size_of = "Rc"]
+    closed: Rc<Promise>,
+    key_statuses: Dom<MediaKeyStatusMap>,
+    /// <https://w3c.github.io/encrypted-media/#dfn-uninitialized>
+    uninitialized: Cell<bool>,
+    /// <https://w3c.github.io/encrypted-media/#dfn-callable>
+    callable: Cell<bool>,
+    /// <https://w3c.github.io/encrypted-media/#dfn-closing-or-closed>
+    closing_or_closed: Cell<bool>,
+}
+
+impl MediaKeySession {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        media_keys: &MediaKeys,
+        session_type: MediaKeySessionType,
+        closed: Rc<Promise>,
+        key_statuses: &MediaKeyStatusMap,
+    ) -> MediaKeySession {
+        MediaKeySession {
+            eventtarget: EventTarget::new_inherited(),
+            media_keys: Dom::from_ref(media_keys),
+            session_type,
+            session_id: DomRefCell::new(DOMString::new()),
+            closed,
+            key_statuses: Dom::from_ref(key_statuses),
+            uninitialized: Cell::new(true),
+            callable: Cell::new(false),
+            closing_or_closed: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        media_keys: &MediaKeys,
+        session_type: MediaKeySessionType,
+    ) -> DomRoot<MediaKeySession> {
+        let closed = Promise::new(window.upcast());
+        let key_statuses = MediaKeyStatusMap::new(window);
+        reflect_dom_object(
+            Box::new(MediaKeySession::new_inherited(
+                media_keys,
+                session_type,
+                closed,
+                &key_statuses,
+            )),
+            window,
+            MediaKeySessionBinding::Wrap,
+        )
+    }
+
+    /// Queues a task to fire a `message` event carrying a message from the
+    /// CDM.
+    fn queue_message(&self, message: Message) {
+        let this = Trusted::new(self);
+        let window = self.global();
+        let window = window.as_window();
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(fire_media_key_message_event: move || {
+                let this = this.root();
+                let event = MediaKeyMessageEvent::new(this.global().as_window(), message);
+                event.upcast::<Event>().fire(this.upcast());
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// <https://w3c.github.io/encrypted-media/#update-key-statuses>
+    fn update_key_statuses(&self, statuses: Vec<(Vec<u8>, KeyStatus)>) {
+        // Steps 1-3.
+        self.key_statuses.set_statuses(statuses);
+        // Step 4.
+        let window = self.global();
+        let window = window.as_window();
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), atom!("keystatuseschange"), window);
+    }
+
+    /// <https://w3c.github.io/encrypted-media/#session-closed>
+    fn session_closed(&self) {
+        // Steps 3-4.
+        self.update_key_statuses(vec![]);
+        // Step 5.
+        self.closing_or_closed.set(true);
+        // Step 6.
+        self.closed.resolve_native(&());
+    }
+
+    fn cdm_error(error: CdmError) -> Error {
+        match error {
+            CdmError::NotSupported => Error::NotSupported,
+            CdmError::InvalidData => {
+                Error::Type("The data is invalid for the key system".to_owned())
+            },
+            CdmError::UnknownSession => Error::InvalidState,
+        }
+    }
+}
+
+impl MediaKeySessionMethods for MediaKeySession {
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-sessionid
+    fn SessionId(&self) -> DOMString {
+        self.session_id.borrow().clone()
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-expiration
+    fn Expiration(&self) -> f64 {
+        // None of the supported key systems expire their keys.
+        f64::NAN
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-closed
+    fn Closed(&self) -> Rc<Promise> {
+        self.closed.clone()
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-keystatuses
+    fn KeyStatuses(&self) -> DomRoot<MediaKeyStatusMap> {
+        DomRoot::from_ref(&*self.key_statuses)
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-onkeystatuseschange
+    event_handler!(
+        keystatuseschange,
+        GetOnkeystatuseschange,
+        SetOnkeystatuseschange
+    );
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-generaterequest
+    fn GenerateRequest(
+        &self,
+        init_data_type: DOMString,
+        init_data: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 1-3.
+        if self.closing_or_closed.get() || !self.uninitialized.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+        self.uninitialized.set(false);
+
+        // Steps 4-5.
+        let init_data = buffer_source_bytes(&init_data);
+        if init_data_type.is_empty() || init_data.is_empty() {
+            promise.reject_error(Error::Type(
+                "The initialization data and its type must not be empty".to_owned(),
+            ));
+            return promise;
+        }
+
+        // Step 7.
+        let result = self
+            .media_keys
+            .cdm()
+            .generate_request(&init_data_type, &init_data);
+        match result {
+            Ok((session_id, message)) => {
+                // Steps 10.10.1-10.10.4.
+                *self.session_id.borrow_mut() = DOMString::from(session_id);
+                self.callable.set(true);
+                self.queue_message(message);
+                promise.resolve_native(&());
+            },
+            Err(error) => promise.reject_error(MediaKeySession::cdm_error(error)),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-load
+    fn Load(&self, session_id: DOMString, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 1-3.
+        if self.closing_or_closed.get() || !self.uninitialized.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+        self.uninitialized.set(false);
+
+        // Steps 4-5.
+        if session_id.is_empty() || self.session_type != MediaKeySessionType::Persistent_license {
+            promise.reject_error(Error::Type(
+                "Only persistent-license sessions can be loaded".to_owned(),
+            ));
+            return promise;
+        }
+
+        // No key system supports persistent-license sessions yet, so
+        // createSession() never returns one.
+        promise.reject_error(Error::NotSupported);
+        promise
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-update
+    fn Update(&self, response: ArrayBufferViewOrArrayBuffer, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 1-2.
+        if self.closing_or_closed.get() || !self.callable.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 3.
+        let response = buffer_source_bytes(&response);
+        if response.is_empty() {
+            promise.reject_error(Error::Type("The response is empty".to_owned()));
+            return promise;
+        }
+
+        // Step 6.
+        let result = self
+            .media_keys
+            .cdm()
+            .update(&self.session_id.borrow(), &response);
+        match result {
+            Ok(statuses) => {
+                self.update_key_statuses(statuses);
+                promise.resolve_native(&());
+            },
+            Err(error) => promise.reject_error(MediaKeySession::cdm_error(error)),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-close
+    fn Close(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Step 2.
+        if self.closing_or_closed.get() {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Step 3.
+        if !self.callable.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Steps 4-6.
+        self.closing_or_closed.set(true);
+        self.media_keys.cdm().close(&self.session_id.borrow());
+        self.session_closed();
+        promise.resolve_native(&());
+        promise
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysession-remove
+    fn Remove(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Steps 1-2.
+        if self.closing_or_closed.get() || !self.callable.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 4.
+        let result = self.media_keys.cdm().remove(&self.session_id.borrow());
+        match result {
+            Ok((statuses, message)) => {
+                self.update_key_statuses(statuses);
+                if let Some(message) = message {
+                    self.queue_message(message);
+                }
+                promise.resolve_native(&());
+            },
+            Err(error) => promise.reject_error(MediaKeySession::cdm_error(error)),
+        }
+        promise
+    }
+}
@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::cdm::KeyStatus;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MediaKeyStatusMapBinding::{
+    self, MediaKeyStatus, MediaKeyStatusMapMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::mediakeys::buffer_source_bytes;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsval::{JSVal, UndefinedValue};
+
+// https://w3c.github.io/encrypted-media/#mediakeystatusmap-interface
+#[dom_struct]
+pub struct MediaKeyStatusMap {
+    reflector_: Reflector,
+    statuses: DomRefCell<Vec<(Vec<u8>, MediaKeyStatus)>>,
+}
+
+impl MediaKeyStatusMap {
+    fn new_inherited() -> MediaKeyStatusMap {
+        MediaKeyStatusMap {
+            reflector_: Reflector::new(),
+            statuses: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<MediaKeyStatusMap> {
+        reflect_dom_object(
+            Box::new(MediaKeyStatusMap::new_inherited()),
+            window,
+            MediaKeyStatusMapBinding::Wrap,
+        )
+    }
+
+    /// Replaces the statuses with the ones reported by the CDM.
+    pub fn set_statuses(&self, statuses: Vec<(Vec<u8>, KeyStatus)>) {
+        *self.statuses.borrow_mut() = statuses
+            .into_iter()
+            .map(|(key_id, status)| {
+                let status = match status {
+                    KeyStatus::Usable => MediaKeyStatus::Usable,
+                    KeyStatus::Expired => MediaKeyStatus::Expired,
+                    KeyStatus::Released => MediaKeyStatus::Released,
+                    KeyStatus::OutputRestricted => MediaKeyStatus::Output_restricted,
+                    KeyStatus::OutputDownscaled => MediaKeyStatus::Output_downscaled,
+                    KeyStatus::StatusPending => MediaKeyStatus::Status_pending,
+                    KeyStatus::InternalError => MediaKeyStatus::Internal_error,
+                };
+                (key_id, status)
+            })
+            .collect();
+    }
+
+    fn status(&self, key_id: &[u8]) -> Option<MediaKeyStatus> {
+        self.statuses
+            .borrow()
+            .iter()
+            .find(|&&(ref id, _)| &**id == key_id)
+            .map(|&(_, status)| status)
+    }
+}
+
+impl MediaKeyStatusMapMethods for MediaKeyStatusMap {
+    // https://w3c.github.io/encrypted-media/#dom-mediakeystatusmap-size
+    fn Size(&self) -> u32 {
+        self.statuses.borrow().len() as u32
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeystatusmap-has
+    fn Has(&self, key_id: ArrayBufferViewOrArrayBuffer) -> bool {
+        self.status(&buffer_source_bytes(&key_id)).is_some()
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeystatusmap-get
+    #[allow(unsafe_code)]
+    fn Get(&self, cx: JSContext, key_id: ArrayBufferViewOrArrayBuffer) -> JSVal {
+        match self.status(&buffer_source_bytes(&key_id)) {
+            Some(status) => unsafe {
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                status.to_jsval(*cx, value.handle_mut());
+                value.get()
+            },
+            None => UndefinedValue(),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::cdm::{self, Cdm};
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::MediaKeySystemAccessBinding::{
+    self, MediaKeySystemAccessMethods, MediaKeySystemConfiguration, MediaKeySystemMediaCapability,
+    MediaKeysRequirement,
+};
+use crate::dom::bindings::codegen::Bindings::MediaKeysBinding::MediaKeySessionType;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::mediakeys::MediaKeys;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_media::{ServoMedia, SupportsMediaType};
+use std::rc::Rc;
+
+/// <https://w3c.github.io/encrypted-media/#dom-mediakeysystemmediacapability>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+struct Capability {
+    content_type: DOMString,
+    robustness: DOMString,
+}
+
+/// A configuration accepted by `requestMediaKeySystemAccess()`, with the
+/// unsupported values of the candidate configuration left out.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct SupportedConfiguration {
+    label: DOMString,
+    init_data_types: Vec<DOMString>,
+    audio_capabilities: Vec<Capability>,
+    video_capabilities: Vec<Capability>,
+    session_types: Vec<MediaKeySessionType>,
+}
+
+/// <https://w3c.github.io/encrypted-media/#get-supported-capabilities-for-audio-video-type>
+///
+/// Returns `None` if none of the requested capabilities are supported.
+fn supported_capabilities(requested: &[MediaKeySystemMediaCapability]) -> Option<Vec<Capability>> {
+    let supported: Vec<_> = requested
+        .iter()
+        .filter(|capability| {
+            // The supported key systems have no robustness levels.
+            !capability.contentType.is_empty() &&
+                capability.robustness.is_empty() &&
+                ServoMedia::get()
+                    .unwrap()
+                    .can_play_type(&capability.contentType) !=
+                    SupportsMediaType::No
+        })
+        .map(|capability| Capability {
+            content_type: capability.contentType.clone(),
+            robustness: capability.robustness.clone(),
+        })
+        .collect();
+    if supported.is_empty() {
+        None
+    } else {
+        Some(supported)
+    }
+}
+
+/// <https://w3c.github.io/encrypted-media/#get-supported-configuration-and-consent>
+///
+/// The supported key systems need neither a distinctive identifier nor
+/// user consent.
+fn supported_configuration(
+    cdm: &dyn Cdm,
+    candidate: &MediaKeySystemConfiguration,
+) -> Option<SupportedConfiguration> {
+    // Step 3.
+    let init_data_types: Vec<_> = candidate
+        .initDataTypes
+        .iter()
+        .filter(|init_data_type| cdm.supports_init_data_type(init_data_type))
+        .cloned()
+        .collect();
+    if !candidate.initDataTypes.is_empty() && init_data_types.is_empty() {
+        return None;
+    }
+
+    // Step 4.
+    if candidate.distinctiveIdentifier == MediaKeysRequirement::Required {
+        return None;
+    }
+
+    // Step 5.
+    if candidate.persistentState == MediaKeysRequirement::Required &&
+        !cdm.supports_persistent_license()
+    {
+        return None;
+    }
+
+    // Steps 6-7.
+    let session_types = match candidate.sessionTypes {
+        None => vec![MediaKeySessionType::Temporary],
+        Some(ref session_types) => {
+            let mut supported = vec![];
+            for session_type in session_types {
+                match &**session_type {
+                    "temporary" => supported.push(MediaKeySessionType::Temporary),
+                    "persistent-license"
+                        if cdm.supports_persistent_license() &&
+                            candidate.persistentState != MediaKeysRequirement::Not_allowed =>
+                    {
+                        supported.push(MediaKeySessionType::Persistent_license)
+                    },
+                    _ => return None,
+                }
+            }
+            supported
+        },
+    };
+
+    // Steps 8-10.
+    if candidate.audioCapabilities.is_empty() && candidate.videoCapabilities.is_empty() {
+        return None;
+    }
+    let video_capabilities = if candidate.videoCapabilities.is_empty() {
+        vec![]
+    } else {
+        supported_capabilities(&candidate.videoCapabilities)?
+    };
+    let audio_capabilities = if candidate.audioCapabilities.is_empty() {
+        vec![]
+    } else {
+        supported_capabilities(&candidate.audioCapabilities)?
+    };
+
+    Some(SupportedConfiguration {
+        label: candidate.label.clone(),
+        init_data_types,
+        audio_capabilities,
+        video_capabilities,
+        session_types,
+    })
+}
+
+/// <https://w3c.github.io/encrypted-media/#navigator-extension-requestmediakeysystemaccess>
+pub fn request_media_key_system_access(
+    window: &Window,
+    key_system: DOMString,
+    supported_configurations: Vec<MediaKeySystemConfiguration>,
+    comp: InCompartment,
+) -> Rc<Promise> {
+    let promise = Promise::new_in_current_compartment(window.upcast(), comp);
+
+    // Steps 1-2.
+    if key_system.is_empty() || supported_configurations.is_empty() {
+        promise.reject_error(Error::Type(
+            "A key system and at least one configuration are required".to_owned(),
+        ));
+        return promise;
+    }
+
+    // Steps 6.1-6.2.
+    let cdm = match cdm::cdm_for_key_system(&key_system) {
+        Some(cdm) => cdm,
+        None => {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        },
+    };
+
+    // Steps 6.3-6.4.
+    let configuration = supported_configurations
+        .iter()
+        .filter_map(|candidate| supported_configuration(&*cdm, candidate))
+        .next();
+    match configuration {
+        Some(configuration) => {
+            let access = MediaKeySystemAccess::new(window, key_system, configuration);
+            promise.resolve_native(&access);
+        },
+        None => promise.reject_error(Error::NotSupported),
+    }
+    promise
+}
+
+// https://w3c.github.io/encrypted-media/#mediakeysystemaccess-interface
+#[dom_struct]
+pub struct MediaKeySystemAccess {
+    reflector_: Reflector,
+    key_system: DOMString,
+    configuration: SupportedConfiguration,
+}
+
+impl MediaKeySystemAccess {
+    fn new_inherited(
+        key_system: DOMString,
+        configuration: SupportedConfiguration,
+    ) -> MediaKeySystemAccess {
+        MediaKeySystemAccess {
+            reflector_: Reflector::new(),
+            key_system,
+            configuration,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        key_system: DOMString,
+        configuration: SupportedConfiguration,
+    ) -> DomRoot<MediaKeySystemAccess> {
+        reflect_dom_object(
+            Box::new(MediaKeySystemAccess::new_inherited(
+                key_system,
+                configuration,
+            )),
+            window,
+            MediaKeySystemAccessBinding::Wrap,
+        )
+    }
+}
+
+impl MediaKeySystemAccessMethods for MediaKeySystemAccess {
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysystemaccess-keysystem
+    fn KeySystem(&self) -> DOMString {
+        self.key_system.clone()
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysystemaccess-getconfiguration
+    fn GetConfiguration(&self) -> MediaKeySystemConfiguration {
+        let configuration = &self.configuration;
+        let capabilities = |capabilities: &[Capability]| {
+            capabilities
+                .iter()
+                .map(|capability| MediaKeySystemMediaCapability {
+                    contentType: capability.content_type.clone(),
+                    robustness: capability.robustness.clone(),
+                })
+                .collect()
+        };
+        let session_types = configuration
+            .session_types
+            .iter()
+            .map(|session_type| DOMString::from(session_type.as_str()))
+            .collect();
+        MediaKeySystemConfiguration {
+            label: configuration.label.clone(),
+            initDataTypes: configuration.init_data_types.clone(),
+            audioCapabilities: capabilities(&configuration.audio_capabilities),
+            videoCapabilities: capabilities(&configuration.video_capabilities),
+            // The supported key systems use neither.
+            distinctiveIdentifier: MediaKeysRequirement::Not_allowed,
+            persistentState: MediaKeysRequirement::Not_allowed,
+            sessionTypes: Some(session_types),
+        }
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-mediakeysystemaccess-createmediakeys
+    fn CreateMediaKeys(&self, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        // Steps 2.1-2.9.
+        let cdm = cdm::cdm_for_key_system(&self.key_system).expect("Key system went away");
+        let media_keys = MediaKeys::new(
+            global.as_window(),
+            cdm,
+            self.configuration.session_types.clone(),
+        );
+        // Step 2.10.
+        promise.resolve_native(&media_keys);
+        promise
+    }
+}
@@ -28,16 +28,19 @@ pub struct MediaQueryList {
     eventtarget: EventTarget,
     document: Dom<Document>,
     media_query_list: MediaList,
-    last_match_state: Cell<Option<bool>>,
+    /// Whether the media queries matched when they were last evaluated, so
+    /// that only actual changes are reported.
+    last_match_state: Cell<bool>,
 }
 
 impl MediaQueryList {
     fn new_inherited(document: &Document, media_query_list: MediaList) -> MediaQueryList {
+        let matches = media_query_list.evaluate(&document.device(), document.quirks_mode());
         MediaQueryList {
             eventtarget: EventTarget::new_inherited(),
             document: Dom::from_ref(document),
             media_query_list: media_query_list,
-            last_match_state: Cell::new(None),
+            last_match_state: Cell::new(matches),
         }
     }
 
@@ -53,19 +56,11 @@ impl MediaQueryList {
 impl MediaQueryList {
     pub fn evaluate_changes(&self) -> MediaQueryListMatchState {
         let matches = self.evaluate();
-
-        let result = if let Some(old_matches) = self.last_match_state.get() {
-            if old_matches == matches {
-                MediaQueryListMatchState::Same(matches)
-            } else {
-                MediaQueryListMatchState::Changed(matches)
-            }
+        if self.last_match_state.replace(matches) == matches {
+            MediaQueryListMatchState::Same(matches)
         } else {
             MediaQueryListMatchState::Changed(matches)
-        };
-
-        self.last_match_state.set(Some(matches));
-        result
+        }
     }
 
     pub fn evaluate(&self) -> bool {
@@ -82,10 +77,7 @@ impl MediaQueryListMethods for MediaQueryList {
 
     // https://drafts.csswg.org/cssom-view/#dom-mediaquerylist-matches
     fn Matches(&self) -> bool {
-        match self.last_match_state.get() {
-            None => self.evaluate(),
-            Some(state) => state,
-        }
+        self.evaluate()
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-mediaquerylist-addlistener
@@ -95,7 +87,9 @@ impl MediaQueryListMethods for MediaQueryList {
             listener,
             AddEventListenerOptions {
                 parent: EventListenerOptions { capture: false },
+                passive: None,
                 once: false,
+                signal: None,
             },
         );
     }
@@ -0,0 +1,374 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConstants::{
+    MEDIA_ERR_DECODE, MEDIA_ERR_NETWORK,
+};
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{
+    self, EndOfStreamError, MediaSourceMethods, ReadyState,
+};
+use crate::dom::bindings::codegen::Bindings::SourceBufferBinding::SourceBufferMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::sourcebuffer::{SourceBuffer, SourceBufferKind};
+use crate::dom::sourcebufferlist::SourceBufferList;
+use crate::dom::timeranges::TimeRangesContainer;
+use crate::dom::window::Window;
+use crate::media_segment_parser::ByteStreamFormat;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use servo_media::{ServoMedia, SupportsMediaType};
+use std::cell::Cell;
+use std::f64;
+
+// https://w3c.github.io/media-source/#mediasource
+#[dom_struct]
+pub struct MediaSource {
+    eventtarget: EventTarget,
+    source_buffers: Dom<SourceBufferList>,
+    active_source_buffers: Dom<SourceBufferList>,
+    ready_state: Cell<ReadyState>,
+    duration: Cell<f64>,
+    /// The media element this MediaSource is attached to, if any.
+    media_element: MutNullableDom<HTMLMediaElement>,
+}
+
+impl MediaSource {
+    fn new_inherited(
+        source_buffers: &SourceBufferList,
+        active_source_buffers: &SourceBufferList,
+    ) -> MediaSource {
+        MediaSource {
+            eventtarget: EventTarget::new_inherited(),
+            source_buffers: Dom::from_ref(source_buffers),
+            active_source_buffers: Dom::from_ref(active_source_buffers),
+            ready_state: Cell::new(ReadyState::Closed),
+            duration: Cell::new(f64::NAN),
+            media_element: Default::default(),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<MediaSource> {
+        let source_buffers = SourceBufferList::new(window);
+        let active_source_buffers = SourceBufferList::new(window);
+        reflect_dom_object(
+            Box::new(MediaSource::new_inherited(
+                &source_buffers,
+                &active_source_buffers,
+            )),
+            window,
+            MediaSourceBinding::Wrap,
+        )
+    }
+
+    pub fn Constructor(window: &Window) -> Fallible<DomRoot<MediaSource>> {
+        Ok(MediaSource::new(window))
+    }
+
+    pub fn ready_state(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    pub fn media_element(&self) -> Option<DomRoot<HTMLMediaElement>> {
+        self.media_element.get()
+    }
+
+    fn queue_simple_event(&self, target: &EventTarget, name: Atom) {
+        let window = self.global();
+        let window = window.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(target, name, window);
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    ///
+    /// Fails if the MediaSource is already attached to a media element.
+    pub fn attach(&self, media_element: &HTMLMediaElement) -> Result<(), ()> {
+        if self.ready_state.get() != ReadyState::Closed {
+            return Err(());
+        }
+        self.media_element.set(Some(media_element));
+        self.ready_state.set(ReadyState::Open);
+        self.queue_simple_event(self.upcast(), atom!("sourceopen"));
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-detach>
+    pub fn detach(&self) {
+        self.media_element.set(None);
+        // Steps 1-2.
+        self.ready_state.set(ReadyState::Closed);
+        self.duration.set(f64::NAN);
+
+        // Step 3.
+        for source_buffer in self.active_source_buffers.buffers() {
+            self.active_source_buffers.remove(&source_buffer);
+        }
+        self.queue_simple_event(
+            self.active_source_buffers.upcast(),
+            atom!("removesourcebuffer"),
+        );
+
+        // Step 4.
+        for source_buffer in self.source_buffers.buffers() {
+            self.source_buffers.remove(&source_buffer);
+            source_buffer.detach();
+        }
+        self.queue_simple_event(self.source_buffers.upcast(), atom!("removesourcebuffer"));
+
+        // Step 5.
+        self.queue_simple_event(self.upcast(), atom!("sourceclose"));
+    }
+
+    /// Moves an ended MediaSource back to the open state, as appending to or
+    /// removing from one of its SourceBuffers does.
+    pub fn reopen_if_ended(&self) {
+        if self.ready_state.get() == ReadyState::Ended {
+            self.ready_state.set(ReadyState::Open);
+            self.queue_simple_event(self.upcast(), atom!("sourceopen"));
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#htmlmediaelement-extensions-buffered>
+    ///
+    /// The buffered ranges of the media element, where all the active
+    /// SourceBuffers have data.
+    pub fn buffered(&self) -> TimeRangesContainer {
+        let buffers = self.active_source_buffers.buffers();
+        let mut buffered = match buffers.first() {
+            Some(buffer) => buffer.buffered_ranges(),
+            None => return TimeRangesContainer::new(),
+        };
+        for buffer in &buffers[1..] {
+            buffered.intersect(&buffer.buffered_ranges());
+        }
+        buffered
+    }
+
+    /// Whether a SourceBuffer carrying video was added to this MediaSource.
+    pub fn has_video_source_buffer(&self) -> bool {
+        self.source_buffers
+            .buffers()
+            .iter()
+            .any(|buffer| buffer.kind() == SourceBufferKind::Video)
+    }
+
+    /// The highest presentation timestamp buffered by any SourceBuffer.
+    fn highest_buffered_end(&self) -> f64 {
+        self.source_buffers
+            .buffers()
+            .iter()
+            .map(|buffer| buffer.highest_end_timestamp())
+            .fold(0., f64::max)
+    }
+
+    fn any_updating(&self) -> bool {
+        self.source_buffers
+            .buffers()
+            .iter()
+            .any(|buffer| buffer.Updating())
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-init-segment-received>
+    pub fn initialization_segment_received(
+        &self,
+        source_buffer: &SourceBuffer,
+        duration: Option<f64>,
+    ) {
+        // Step 1.
+        if self.duration.get().is_nan() {
+            self.duration_change(duration.unwrap_or(f64::INFINITY));
+        }
+
+        // Step 5.
+        if !self.active_source_buffers.contains(source_buffer) {
+            self.active_source_buffers.add(source_buffer);
+            self.queue_simple_event(
+                self.active_source_buffers.upcast(),
+                atom!("addsourcebuffer"),
+            );
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    fn duration_change(&self, new_duration: f64) {
+        // Step 1.
+        if self.duration.get() == new_duration {
+            return;
+        }
+        // Steps 5-6.
+        self.duration.set(new_duration);
+        // Step 7.
+        if let Some(media_element) = self.media_element.get() {
+            media_element.media_source_duration_changed(new_duration);
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub fn end_of_stream(&self, error: Option<EndOfStreamError>) {
+        // Steps 1-2.
+        self.ready_state.set(ReadyState::Ended);
+        self.queue_simple_event(self.upcast(), atom!("sourceended"));
+
+        // Step 3.
+        let media_element = match self.media_element.get() {
+            Some(media_element) => media_element,
+            None => return,
+        };
+        match error {
+            None => {
+                self.duration_change(self.highest_buffered_end());
+                media_element.media_source_end_of_stream();
+            },
+            Some(EndOfStreamError::Network) => media_element.media_source_error(MEDIA_ERR_NETWORK),
+            Some(EndOfStreamError::Decode) => media_element.media_source_error(MEDIA_ERR_DECODE),
+        }
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-istypesupported
+    pub fn IsTypeSupported(_window: &Window, type_: DOMString) -> bool {
+        if ByteStreamFormat::from_mime_type(&type_).is_none() {
+            return false;
+        }
+        match ServoMedia::get().unwrap().can_play_type(&type_) {
+            SupportsMediaType::No => false,
+            SupportsMediaType::Maybe | SupportsMediaType::Probably => true,
+        }
+    }
+}
+
+impl MediaSourceMethods for MediaSource {
+    // https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers
+    fn SourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers
+    fn ActiveSourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.active_source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-readystate
+    fn ReadyState(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn Duration(&self) -> f64 {
+        if self.ready_state.get() == ReadyState::Closed {
+            return f64::NAN;
+        }
+        self.duration.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn SetDuration(&self, value: f64) -> ErrorResult {
+        // Step 1.
+        if value < 0. || value.is_nan() {
+            return Err(Error::Type(
+                "The duration must be a positive number".to_owned(),
+            ));
+        }
+        // Steps 2-3.
+        if self.ready_state.get() != ReadyState::Open || self.any_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4, https://w3c.github.io/media-source/#duration-change-algorithm
+        // Step 2.
+        if value < self.highest_buffered_end() {
+            return Err(Error::InvalidState);
+        }
+        self.duration_change(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-addsourcebuffer
+    fn AddSourceBuffer(&self, type_: DOMString) -> Fallible<DomRoot<SourceBuffer>> {
+        // Step 1.
+        if type_.is_empty() {
+            return Err(Error::Type("The type must not be empty".to_owned()));
+        }
+        // Step 2.
+        let global = self.global();
+        let window = global.as_window();
+        if !MediaSource::IsTypeSupported(window, type_.clone()) {
+            return Err(Error::NotSupported);
+        }
+        let format = ByteStreamFormat::from_mime_type(&type_).unwrap();
+        let kind = SourceBufferKind::from_mime_type(&type_);
+        // Step 3. Each SourceBuffer feeds a stream of the media player, which
+        // supports one video and one audio stream.
+        if self
+            .source_buffers
+            .buffers()
+            .iter()
+            .any(|buffer| buffer.kind() == kind)
+        {
+            return Err(Error::QuotaExceeded);
+        }
+        // Step 4.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Steps 5-6.
+        let source_buffer = SourceBuffer::new(window, self, format, kind);
+        // Step 7.
+        self.source_buffers.add(&source_buffer);
+        self.queue_simple_event(self.source_buffers.upcast(), atom!("addsourcebuffer"));
+        // Step 8.
+        Ok(source_buffer)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-removesourcebuffer
+    fn RemoveSourceBuffer(&self, source_buffer: &SourceBuffer) -> ErrorResult {
+        // Step 1.
+        if !self.source_buffers.contains(source_buffer) {
+            return Err(Error::NotFound);
+        }
+        // Step 2.
+        if source_buffer.Updating() {
+            source_buffer.abort_buffer_append();
+        }
+        // Steps 3-8.
+        if self.active_source_buffers.contains(source_buffer) {
+            self.active_source_buffers.remove(source_buffer);
+            self.queue_simple_event(
+                self.active_source_buffers.upcast(),
+                atom!("removesourcebuffer"),
+            );
+        }
+        // Steps 9-11.
+        self.source_buffers.remove(source_buffer);
+        self.queue_simple_event(self.source_buffers.upcast(), atom!("removesourcebuffer"));
+        source_buffer.detach();
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-endofstream
+    fn EndOfStream(&self, error: Option<EndOfStreamError>) -> ErrorResult {
+        // Steps 1-2.
+        if self.ready_state.get() != ReadyState::Open || self.any_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        self.end_of_stream(error);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceopen
+    event_handler!(sourceopen, GetOnsourceopen, SetOnsourceopen);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceended
+    event_handler!(sourceended, GetOnsourceended, SetOnsourceended);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceclose
+    event_handler!(sourceclose, GetOnsourceclose, SetOnsourceclose);
+}
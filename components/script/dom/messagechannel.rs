@@ -22,20 +22,8 @@ impl MessageChannel {
     pub fn Constructor(global: &GlobalScope) -> Fallible<DomRoot<MessageChannel>> {
         let incumbent = GlobalScope::incumbent().ok_or(Error::InvalidState)?;
 
-        // Step 1
-        let port1 = MessagePort::new(&incumbent);
-
-        // Step 2
-        let port2 = MessagePort::new(&incumbent);
-
-        incumbent.track_message_port(&*port1, None);
-        incumbent.track_message_port(&*port2, None);
-
-        // Step 3
-        incumbent.entangle_ports(
-            port1.message_port_id().clone(),
-            port2.message_port_id().clone(),
-        );
+        // Steps 1-3
+        let (port1, port2) = MessagePort::new_entangled(&incumbent);
 
         // Steps 4-6
         let channel = reflect_dom_object(
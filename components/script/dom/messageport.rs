@@ -2,24 +2,38 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use crate::compartments::enter_realm;
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use crate::dom::bindings::codegen::Bindings::MessagePortBinding::{
     MessagePortMethods, PostMessageOptions, Wrap,
 };
-use crate::dom::bindings::conversions::root_from_object;
+use crate::dom::bindings::conversions::{
+    root_from_object, ConversionResult, FromJSValConvertible, StringificationBehavior,
+    ToJSValConvertible,
+};
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone::{self, StructuredDataHolder};
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::transferable::Transferable;
+use crate::dom::bindings::utils::{get_dictionary_property, set_dictionary_property};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestreamdefaultcontroller::ReadableStreamDefaultController;
+use crate::dom::writablestreamdefaultcontroller::WritableStreamDefaultController;
 use crate::script_runtime::JSContext as SafeJSContext;
+use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
-use js::jsapi::Heap;
+use js::jsapi::{Heap, JSContext, JS_NewPlainObject};
 use js::jsapi::{JSObject, MutableHandleObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
 use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue};
 use msg::constellation_msg::{MessagePortId, MessagePortIndex, PipelineNamespaceId};
 use script_traits::PortMessageTask;
@@ -29,6 +43,23 @@ use std::convert::TryInto;
 use std::num::NonZeroU32;
 use std::rc::Rc;
 
+/// The stream whose chunks a port carries from, or to, the realm of its
+/// entangled port, <https://streams.spec.whatwg.org/#transferrable-streams>.
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum CrossRealmTransform {
+    /// A readable stream, into which the chunks posted by the entangled port
+    /// are enqueued.
+    Readable(Dom<ReadableStreamDefaultController>),
+    /// A writable stream, whose chunks are posted to the entangled port.
+    Writable {
+        controller: Dom<WritableStreamDefaultController>,
+        /// Resolved once the entangled port pulls, if writes wait for it.
+        #[ignore_malloc_size_of = "Rc"]
+        backpressure_promise: Option<Rc<Promise>>,
+    },
+}
+
 #[dom_struct]
 /// The MessagePort used in the DOM.
 pub struct MessagePort {
@@ -36,6 +67,9 @@ pub struct MessagePort {
     message_port_id: MessagePortId,
     entangled_port: RefCell<Option<MessagePortId>>,
     detached: Cell<bool>,
+    /// Set for the port of a transferred stream, whose messages are handled
+    /// by the stream rather than dispatched as events.
+    cross_realm_transform: DomRefCell<Option<CrossRealmTransform>>,
 }
 
 impl MessagePort {
@@ -44,6 +78,7 @@ impl MessagePort {
             eventtarget: EventTarget::new_inherited(),
             entangled_port: RefCell::new(None),
             detached: Cell::new(false),
+            cross_realm_transform: DomRefCell::new(None),
             message_port_id,
         }
     }
@@ -54,6 +89,19 @@ impl MessagePort {
         reflect_dom_object(Box::new(MessagePort::new_inherited(port_id)), owner, Wrap)
     }
 
+    /// Creates two entangled ports, as `new MessageChannel()` does.
+    pub fn new_entangled(owner: &GlobalScope) -> (DomRoot<MessagePort>, DomRoot<MessagePort>) {
+        let port1 = MessagePort::new(owner);
+        let port2 = MessagePort::new(owner);
+        owner.track_message_port(&*port1, None);
+        owner.track_message_port(&*port2, None);
+        owner.entangle_ports(
+            port1.message_port_id().clone(),
+            port2.message_port_id().clone(),
+        );
+        (port1, port2)
+    }
+
     /// Create a new port for an incoming transfer-received one.
     fn new_transferred(
         owner: &GlobalScope,
@@ -66,6 +114,7 @@ impl MessagePort {
                 eventtarget: EventTarget::new_inherited(),
                 detached: Cell::new(false),
                 entangled_port: RefCell::new(entangled_port),
+                cross_realm_transform: DomRefCell::new(None),
             }),
             owner,
             Wrap,
@@ -77,6 +126,14 @@ impl MessagePort {
         *self.entangled_port.borrow_mut() = Some(other_id);
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#disentangle>
+    /// Called once the entangled port is gone, along with the implementation
+    /// backing this port, which can then no longer be used.
+    pub fn disentangle(&self) {
+        *self.entangled_port.borrow_mut() = None;
+        self.detached.set(true);
+    }
+
     pub fn message_port_id(&self) -> &MessagePortId {
         &self.message_port_id
     }
@@ -162,7 +219,7 @@ impl Transferable for MessagePort {
         }
 
         let port_impls = match sc_holder {
-            StructuredDataHolder::Write(port_impls) => port_impls,
+            StructuredDataHolder::Write { port_impls, .. } => port_impls,
             _ => panic!("Unexpected variant of StructuredDataHolder"),
         };
 
@@ -204,12 +261,35 @@ impl Transferable for MessagePort {
         extra_data: u64,
         return_object: MutableHandleObject,
     ) -> Result<(), ()> {
-        let (message_ports, port_impls) = match sc_holder {
-            StructuredDataHolder::Read {
-                message_ports,
-                port_impls,
-                ..
-            } => (message_ports, port_impls),
+        let transferred_port = MessagePort::receive_transferred(owner, sc_holder, extra_data);
+
+        return_object.set(transferred_port.reflector().rootable().get());
+
+        let message_ports = match sc_holder {
+            StructuredDataHolder::Read { message_ports, .. } => message_ports,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+
+        // Store the DOM port where it will be passed along to script in the message-event.
+        message_ports
+            .get_or_insert_with(Vec::new)
+            .push(transferred_port);
+
+        Ok(())
+    }
+}
+
+impl MessagePort {
+    /// Creates the port transfer-received at the key `extra_data`.
+    /// Unlike `transfer_receive`, this does not pass the port along to script,
+    /// which lets transferred streams receive the ports they are built on.
+    pub fn receive_transferred(
+        owner: &GlobalScope,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+    ) -> DomRoot<MessagePort> {
+        let port_impls = match sc_holder {
+            StructuredDataHolder::Read { port_impls, .. } => port_impls,
             _ => panic!("Unexpected variant of StructuredDataHolder"),
         };
 
@@ -236,35 +316,292 @@ impl Transferable for MessagePort {
         };
 
         // 2. Get the transferred object from its storage, using the key.
-        // Assign the transfer-received port-impl, and total number of transferred ports.
-        let (ports_len, port_impl) = if let Some(ports) = port_impls.as_mut() {
-            let ports_len = ports.len();
+        let port_impl = if let Some(ports) = port_impls.as_mut() {
             let port_impl = ports.remove(&id).expect("Transferred port to be stored");
             if ports.is_empty() {
                 *port_impls = None;
             }
-            (ports_len, port_impl)
+            port_impl
         } else {
             panic!("A messageport was transfer-received, yet the SC holder does not have any port impls");
         };
 
         let transferred_port =
-            MessagePort::new_transferred(&**owner, id.clone(), port_impl.entangled_port_id());
+            MessagePort::new_transferred(owner, id.clone(), port_impl.entangled_port_id());
         owner.track_message_port(&transferred_port, Some(port_impl));
+        transferred_port
+    }
 
-        return_object.set(transferred_port.reflector().rootable().get());
+    /// Sets up the port of a transferred stream, whose messages are then
+    /// handled by `handle_cross_realm_message`, and enables its port message
+    /// queue.
+    #[allow(unrooted_must_root)]
+    pub fn set_cross_realm_transform(&self, transform: CrossRealmTransform) {
+        *self.cross_realm_transform.borrow_mut() = Some(transform);
+        self.global().start_message_port(self.message_port_id());
+    }
 
-        // Store the DOM port where it will be passed along to script in the message-event.
-        if let Some(ports) = message_ports.as_mut() {
-            ports.push(transferred_port);
-        } else {
-            let mut ports = Vec::with_capacity(ports_len);
-            ports.push(transferred_port);
-            *message_ports = Some(ports);
+    pub fn has_cross_realm_transform(&self) -> bool {
+        self.cross_realm_transform.borrow().is_some()
+    }
+
+    /// The readable stream controller of the port of a transferred stream,
+    /// or None if the port carries the chunks of a writable stream.
+    fn cross_realm_readable(&self) -> Option<DomRoot<ReadableStreamDefaultController>> {
+        match *self.cross_realm_transform.borrow() {
+            Some(CrossRealmTransform::Readable(ref controller)) => {
+                Some(DomRoot::from_ref(&**controller))
+            },
+            _ => None,
         }
+    }
+
+    fn cross_realm_writable(&self) -> Option<DomRoot<WritableStreamDefaultController>> {
+        match *self.cross_realm_transform.borrow() {
+            Some(CrossRealmTransform::Writable { ref controller, .. }) => {
+                Some(DomRoot::from_ref(&**controller))
+            },
+            _ => None,
+        }
+    }
 
+    /// The message handler set up by
+    /// <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformreadable>
+    /// and <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformwritable>.
+    #[allow(unsafe_code)]
+    pub fn handle_cross_realm_message(&self, message: HandleValue) {
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*global);
+
+        // Steps 1-4.
+        if !message.is_object() {
+            return;
+        }
+        rooted!(in(*cx) let data = message.to_object());
+        rooted!(in(*cx) let mut type_value = UndefinedValue());
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        if get_dictionary_property(*cx, data.handle(), "type", type_value.handle_mut()).is_err() ||
+            get_dictionary_property(*cx, data.handle(), "value", value.handle_mut()).is_err()
+        {
+            return;
+        }
+        let type_ = match unsafe {
+            DOMString::from_jsval(*cx, type_value.handle(), StringificationBehavior::Default)
+        } {
+            Ok(ConversionResult::Success(type_)) => type_,
+            _ => return,
+        };
+
+        // Steps 5-7.
+        if let Some(controller) = self.cross_realm_readable() {
+            match &*type_ {
+                "chunk" => {
+                    let _ = controller.enqueue(cx, value.handle());
+                },
+                "close" => {
+                    if controller.can_close_or_enqueue() {
+                        controller.close();
+                    }
+                    self.disentangle_cross_realm();
+                },
+                "error" => {
+                    controller.error(value.handle());
+                    self.disentangle_cross_realm();
+                },
+                _ => {},
+            }
+        } else if let Some(controller) = self.cross_realm_writable() {
+            match &*type_ {
+                "pull" => self.resolve_backpressure_promise(),
+                "error" => {
+                    controller.error_if_needed(value.handle());
+                    self.resolve_backpressure_promise();
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// The messageerror handler set up by
+    /// <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformreadable>
+    /// and <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformwritable>.
+    #[allow(unsafe_code)]
+    pub fn handle_cross_realm_message_error(&self) {
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*global);
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        unsafe { Error::DataClone.to_jsval(*cx, &global, error.handle_mut()) };
+        let _ = self.pack_and_post_message("error", error.handle());
+        if let Some(controller) = self.cross_realm_readable() {
+            controller.error(error.handle());
+        } else if let Some(controller) = self.cross_realm_writable() {
+            controller.error_if_needed(error.handle());
+        }
+        self.disentangle_cross_realm();
+    }
+
+    fn resolve_backpressure_promise(&self) {
+        let promise = match *self.cross_realm_transform.borrow_mut() {
+            Some(CrossRealmTransform::Writable {
+                ref mut backpressure_promise,
+                ..
+            }) => backpressure_promise.take(),
+            _ => None,
+        };
+        if let Some(promise) = promise {
+            promise.resolve_native(&());
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#abstract-opdef-packandpostmessage>
+    #[allow(unsafe_code)]
+    fn pack_and_post_message(&self, type_: &str, value: HandleValue) -> ErrorResult {
+        if self.detached.get() {
+            return Ok(());
+        }
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*global);
+
+        // Steps 1-3.
+        rooted!(in(*cx) let message = unsafe { JS_NewPlainObject(*cx) });
+        rooted!(in(*cx) let mut type_value = UndefinedValue());
+        unsafe { type_.to_jsval(*cx, type_value.handle_mut()) };
+        let _ = set_dictionary_property(*cx, message.handle(), "type", type_value.handle());
+        let _ = set_dictionary_property(*cx, message.handle(), "value", value);
+
+        // Steps 4-6, the message port post message steps, without a transfer
+        // list.
+        rooted!(in(*cx) let message_value = ObjectValue(message.get()));
+        let data = structuredclone::write(cx, message_value.handle(), None)?;
+        let task = PortMessageTask {
+            origin: global.origin().immutable().clone(),
+            data,
+        };
+        global.post_messageport_msg(self.message_port_id().clone(), task);
         Ok(())
     }
+
+    /// <https://streams.spec.whatwg.org/#abstract-opdef-packandpostmessagehandlingerror>
+    /// Settles `result` with the outcome of posting the message, and returns
+    /// whether it was posted.
+    #[allow(unsafe_code)]
+    fn pack_and_post_message_handling_error(
+        &self,
+        type_: &str,
+        value: HandleValue,
+        result: &Promise,
+    ) -> bool {
+        let error = match self.pack_and_post_message(type_, value) {
+            Ok(()) => {
+                result.resolve_native(&());
+                return true;
+            },
+            Err(error) => error,
+        };
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*global);
+        rooted!(in(*cx) let mut error_value = UndefinedValue());
+        unsafe { error.to_jsval(*cx, &global, error_value.handle_mut()) };
+        let _ = self.pack_and_post_message("error", error_value.handle());
+        result.reject(cx, error_value.handle());
+        false
+    }
+
+    /// Disentangles the port of a transferred stream. This is done in a task,
+    /// so that the messages the port already posted reach the entangled port
+    /// first.
+    fn disentangle_cross_realm(&self) {
+        let global = self.global();
+        let this = Trusted::new(self);
+        let _ = global.port_message_queue().queue(
+            task!(disentangle_cross_realm_port: move || {
+                this.root().Close();
+            }),
+            &global,
+        );
+    }
+
+    /// The pull algorithm of a transferred readable stream.
+    pub fn cross_realm_pull(&self) -> Rc<Promise> {
+        let global = self.global();
+        let cx = global.get_cx();
+        rooted!(in(*cx) let undefined = UndefinedValue());
+        let _ = self.pack_and_post_message("pull", undefined.handle());
+        let promise = Promise::new(&global);
+        promise.resolve_native(&());
+        promise
+    }
+
+    /// The cancel algorithm of a transferred readable stream, and the abort
+    /// algorithm of a transferred writable stream.
+    pub fn cross_realm_cancel(&self, reason: HandleValue) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        self.pack_and_post_message_handling_error("error", reason, &promise);
+        self.disentangle_cross_realm();
+        promise
+    }
+
+    /// The write algorithm of a transferred writable stream, which posts
+    /// `chunk` once the entangled port pulls.
+    pub fn cross_realm_write(&self, chunk: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+        let backpressure_promise = match *self.cross_realm_transform.borrow_mut() {
+            Some(CrossRealmTransform::Writable {
+                ref mut backpressure_promise,
+                ..
+            }) => backpressure_promise
+                .get_or_insert_with(|| {
+                    let promise = Promise::new(&global);
+                    promise.resolve_native(&());
+                    promise
+                })
+                .clone(),
+            _ => unreachable!("Only the port of a writable stream is written to."),
+        };
+        let result = Promise::new(&global);
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(CrossRealmWrite {
+                port: Dom::from_ref(self),
+                chunk: Heap::boxed(chunk.get()),
+                result: result.clone(),
+            })),
+            None,
+        );
+        backpressure_promise.append_native_handler(&handler);
+        result
+    }
+
+    /// Step 8.2 of <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformwritable>.
+    fn cross_realm_write_steps(&self, chunk: HandleValue, result: &Promise) {
+        let backpressure_promise = Promise::new(&self.global());
+        if let Some(CrossRealmTransform::Writable {
+            backpressure_promise: ref mut promise,
+            ..
+        }) = *self.cross_realm_transform.borrow_mut()
+        {
+            *promise = Some(backpressure_promise);
+        }
+        if !self.pack_and_post_message_handling_error("chunk", chunk, result) {
+            self.disentangle_cross_realm();
+        }
+    }
+
+    /// The close algorithm of a transferred writable stream.
+    pub fn cross_realm_close(&self) -> Rc<Promise> {
+        let global = self.global();
+        let cx = global.get_cx();
+        rooted!(in(*cx) let undefined = UndefinedValue());
+        let _ = self.pack_and_post_message("close", undefined.handle());
+        self.disentangle_cross_realm();
+        let promise = Promise::new(&global);
+        promise.resolve_native(&());
+        promise
+    }
 }
 
 impl MessagePortMethods for MessagePort {
@@ -315,7 +652,10 @@ impl MessagePortMethods for MessagePort {
         if self.detached.get() {
             return;
         }
+        // Step 1
         self.detached.set(true);
+        // Step 2
+        *self.entangled_port.borrow_mut() = None;
         self.global().close_message_port(self.message_port_id());
     }
 
@@ -341,3 +681,22 @@ impl MessagePortMethods for MessagePort {
     /// <https://html.spec.whatwg.org/multipage/#handler-messageport-onmessageerror>
     event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
 }
+
+/// Posts a chunk written to a transferred writable stream, once the
+/// entangled port pulls.
+#[derive(JSTraceable, MallocSizeOf)]
+struct CrossRealmWrite {
+    port: Dom<MessagePort>,
+    #[ignore_malloc_size_of = "mozjs"]
+    chunk: Box<Heap<JSVal>>,
+    #[ignore_malloc_size_of = "Rc"]
+    result: Rc<Promise>,
+}
+
+impl Callback for CrossRealmWrite {
+    fn callback(&self, cx: *mut JSContext, _v: HandleValue) {
+        rooted!(in(cx) let chunk = self.chunk.get());
+        self.port
+            .cross_realm_write_steps(chunk.handle(), &self.result);
+    }
+}
@@ -209,10 +209,16 @@ pub mod types {
     include!(concat!(env!("OUT_DIR"), "/InterfaceTypes.rs"));
 }
 
+pub mod abortcontroller;
+pub mod abortsignal;
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
 pub mod activation;
 pub mod analysernode;
+pub mod animation;
+pub mod animationeffect;
+pub mod animationplaybackevent;
+pub mod animationtimeline;
 pub mod attr;
 pub mod audiobuffer;
 pub mod audiobuffersourcenode;
@@ -224,6 +230,13 @@ pub mod audioparam;
 pub mod audioscheduledsourcenode;
 pub mod audiotrack;
 pub mod audiotracklist;
+pub mod audioworklet;
+pub mod audioworkletglobalscope;
+pub mod audioworkletnode;
+pub mod audioworkletprocessor;
+pub mod authenticatorassertionresponse;
+pub mod authenticatorattestationresponse;
+pub mod authenticatorresponse;
 pub mod baseaudiocontext;
 pub mod beforeunloadevent;
 pub mod bindings;
@@ -239,6 +252,9 @@ pub mod bluetoothremotegattdescriptor;
 pub mod bluetoothremotegattserver;
 pub mod bluetoothremotegattservice;
 pub mod bluetoothuuid;
+pub mod broadcastchannel;
+pub mod cache;
+pub mod cachestorage;
 pub mod canvasgradient;
 pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
@@ -247,13 +263,21 @@ pub mod channelmergernode;
 pub mod channelsplitternode;
 pub mod characterdata;
 pub mod client;
+pub mod clipboard;
+pub mod clipboarditem;
 pub mod closeevent;
 pub mod comment;
 pub mod compositionevent;
 pub mod console;
 pub mod constantsourcenode;
+pub mod cookiechangeevent;
+pub mod cookiestore;
 mod create;
+pub mod credential;
+pub mod credentialscontainer;
 pub mod crypto;
+pub mod cryptokey;
+pub mod cspviolationreportbody;
 pub mod css;
 pub mod cssconditionrule;
 pub mod cssfontfacerule;
@@ -274,11 +298,13 @@ pub mod cssviewportrule;
 pub mod customelementregistry;
 pub mod customevent;
 pub mod dedicatedworkerglobalscope;
+pub mod deprecationreportbody;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
 pub mod document;
 pub mod documentfragment;
 pub mod documentorshadowroot;
+pub mod documenttimeline;
 pub mod documenttype;
 pub mod domexception;
 pub mod domimplementation;
@@ -301,11 +327,15 @@ pub mod eventtarget;
 pub mod extendableevent;
 pub mod extendablemessageevent;
 pub mod fakexrdevice;
+pub mod fetchevent;
 pub mod file;
 pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
 pub mod focusevent;
+pub mod fontface;
+pub mod fontfaceset;
+pub mod fontfacesetloadevent;
 pub mod formdata;
 pub mod formdataevent;
 pub mod gainnode;
@@ -317,7 +347,28 @@ pub mod gamepadlist;
 pub mod globalscope;
 pub mod gpu;
 pub mod gpuadapter;
+pub mod gpubindgrouplayout;
+pub mod gpucanvascontext;
+pub mod gpucolorwrite;
+pub mod gpucommandbuffer;
+pub mod gpucommandencoder;
+pub mod gpucomputepassencoder;
+pub mod gpucomputepipeline;
 pub mod gpudevice;
+pub mod gpuoutofmemoryerror;
+pub mod gpupipelinelayout;
+pub mod gpuqueue;
+pub mod gpurenderbundle;
+pub mod gpurenderbundleencoder;
+pub mod gpurenderpassencoder;
+pub mod gpurenderpipeline;
+pub mod gpushadermodule;
+pub mod gpushaderstage;
+pub mod gpuswapchain;
+pub mod gputexture;
+pub mod gputextureusage;
+pub mod gputextureview;
+pub mod gpuvalidationerror;
 pub mod hashchangeevent;
 pub mod headers;
 pub mod history;
@@ -375,6 +426,7 @@ pub mod htmlprogresselement;
 pub mod htmlquoteelement;
 pub mod htmlscriptelement;
 pub mod htmlselectelement;
+pub mod htmlslotelement;
 pub mod htmlsourceelement;
 pub mod htmlspanelement;
 pub mod htmlstyleelement;
@@ -393,19 +445,29 @@ pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
 pub mod identityhub;
+pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
+pub mod interventionreportbody;
 pub mod keyboardevent;
+pub mod keyframeeffect;
 pub mod location;
+pub mod mediadeviceinfo;
 pub mod mediadevices;
 pub mod mediaelementaudiosourcenode;
 pub mod mediaerror;
 pub mod mediafragmentparser;
+pub mod mediakeymessageevent;
+pub mod mediakeys;
+pub mod mediakeysession;
+pub mod mediakeystatusmap;
+pub mod mediakeysystemaccess;
 pub mod medialist;
 pub mod mediametadata;
 pub mod mediaquerylist;
 pub mod mediaquerylistevent;
 pub mod mediasession;
+pub mod mediasource;
 pub mod mediastream;
 pub mod mediastreamtrack;
 pub mod messagechannel;
@@ -417,12 +479,18 @@ pub mod mouseevent;
 pub mod mutationobserver;
 pub mod mutationrecord;
 pub mod namednodemap;
+pub mod navigateevent;
+pub mod navigation;
+pub mod navigationcurrententrychangeevent;
+pub mod navigationdestination;
+pub mod navigationhistoryentry;
 pub mod navigationpreloadmanager;
 pub mod navigator;
 pub mod navigatorinfo;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
+pub mod notification;
 pub mod offlineaudiocompletionevent;
 pub mod offlineaudiocontext;
 pub mod offscreencanvas;
@@ -433,8 +501,15 @@ pub mod paintrenderingcontext2d;
 pub mod paintsize;
 pub mod paintworkletglobalscope;
 pub mod pannernode;
+pub mod path2d;
+pub mod paymentaddress;
+pub mod paymentrequest;
+pub mod paymentrequestupdateevent;
+pub mod paymentresponse;
 pub mod performance;
 pub mod performanceentry;
+pub mod performanceeventtiming;
+pub mod performancelongtasktiming;
 pub mod performancemark;
 pub mod performancemeasure;
 pub mod performancenavigation;
@@ -447,38 +522,70 @@ pub mod permissions;
 pub mod permissionstatus;
 pub mod plugin;
 pub mod pluginarray;
+pub mod pointerevent;
 pub mod popstateevent;
 pub mod processinginstruction;
 pub mod progressevent;
 pub mod promise;
 pub mod promisenativehandler;
 pub mod promiserejectionevent;
+pub mod publickeycredential;
 pub mod radionodelist;
 pub mod range;
 pub mod raredata;
+pub mod readablebytestreamcontroller;
+pub mod readablestream;
+pub mod readablestreambyobreader;
+pub mod readablestreambyobrequest;
+pub mod readablestreamdefaultcontroller;
+pub mod readablestreamdefaultreader;
+pub mod report;
+pub mod reportbody;
+pub mod reportingobserver;
 pub mod request;
 pub mod response;
+pub mod rtcdatachannel;
+pub mod rtcdatachannelevent;
 pub mod rtcicecandidate;
 pub mod rtcpeerconnection;
 pub mod rtcpeerconnectioniceevent;
 pub mod rtcsessiondescription;
 pub mod rtctrackevent;
+pub mod scheduler;
 pub mod screen;
+pub mod screenorientation;
+pub mod securitypolicyviolationevent;
+pub mod selection;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servoparser;
 pub mod shadowroot;
+pub mod sharedworker;
+pub mod sharedworkerglobalscope;
+pub mod sourcebuffer;
+pub mod sourcebufferlist;
+pub mod speechsynthesis;
+pub mod speechsynthesiserrorevent;
+pub mod speechsynthesisevent;
+pub mod speechsynthesisutterance;
+pub mod speechsynthesisvoice;
 pub mod stereopannernode;
 pub mod storage;
 pub mod storageevent;
+pub mod storagemanager;
 pub mod stylepropertymapreadonly;
 pub mod stylesheet;
 pub mod stylesheetlist;
+pub mod subtlecrypto;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
+pub mod taskattributiontiming;
+pub mod taskcontroller;
+pub mod taskprioritychangeevent;
+pub mod tasksignal;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingpairiterable;
@@ -500,6 +607,8 @@ pub mod touch;
 pub mod touchevent;
 pub mod touchlist;
 pub mod trackevent;
+pub mod transformstream;
+pub mod transformstreamdefaultcontroller;
 pub mod transitionevent;
 pub mod treewalker;
 pub mod uievent;
@@ -521,6 +630,7 @@ pub mod vrfieldofview;
 pub mod vrframedata;
 pub mod vrpose;
 pub mod vrstageparameters;
+pub mod vttcue;
 pub mod webgl_extensions;
 pub use self::webgl_extensions::ext::*;
 pub mod webgl2renderingcontext;
@@ -552,16 +662,27 @@ pub mod workerlocation;
 pub mod workernavigator;
 pub mod worklet;
 pub mod workletglobalscope;
+pub mod writablestream;
+pub mod writablestreamdefaultcontroller;
+pub mod writablestreamdefaultwriter;
 pub mod xmldocument;
 pub mod xmlhttprequest;
 pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
 pub mod xmlserializer;
+pub mod xpathevaluator;
+pub mod xpathexpression;
+pub mod xpathresult;
 pub mod xr;
+pub mod xranchor;
 pub mod xrframe;
+pub mod xrhittestresult;
+pub mod xrhittestsource;
 pub mod xrinputsource;
 pub mod xrinputsourcearray;
 pub mod xrinputsourceevent;
+pub mod xrinputsourceschangeevent;
+pub mod xrlayer;
 pub mod xrpose;
 pub mod xrreferencespace;
 pub mod xrrenderstate;
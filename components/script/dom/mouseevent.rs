@@ -35,6 +35,8 @@ pub struct MouseEvent {
     y: Cell<i32>,
     offset_x: Cell<i32>,
     offset_y: Cell<i32>,
+    movement_x: Cell<i32>,
+    movement_y: Cell<i32>,
     ctrl_key: Cell<bool>,
     shift_key: Cell<bool>,
     alt_key: Cell<bool>,
@@ -59,6 +61,8 @@ impl MouseEvent {
             y: Cell::new(0),
             offset_x: Cell::new(0),
             offset_y: Cell::new(0),
+            movement_x: Cell::new(0),
+            movement_y: Cell::new(0),
             ctrl_key: Cell::new(false),
             shift_key: Cell::new(false),
             alt_key: Cell::new(false),
@@ -116,14 +120,25 @@ impl MouseEvent {
             button,
             related_target,
         );
-        ev.buttons.set(buttons);
-        ev.point_in_target.set(point_in_target);
-        // TODO: Set proper values in https://github.com/servo/servo/issues/24415
-        ev.page_x.set(client_x);
-        ev.page_y.set(client_y);
+        ev.set_buttons_and_point_in_target(buttons, point_in_target);
         ev
     }
 
+    /// Sets the state of a user agent generated mouse event that `initMouseEvent`
+    /// can't express. Subclasses such as `PointerEvent` use this after initializing
+    /// their mouse event part.
+    pub fn set_buttons_and_point_in_target(
+        &self,
+        buttons: u16,
+        point_in_target: Option<Point2D<f32>>,
+    ) {
+        self.buttons.set(buttons);
+        self.point_in_target.set(point_in_target);
+        // TODO: Set proper values in https://github.com/servo/servo/issues/24415
+        self.page_x.set(self.client_x.get());
+        self.page_y.set(self.client_y.get());
+    }
+
     pub fn Constructor(
         window: &Window,
         type_: DOMString,
@@ -151,12 +166,19 @@ impl MouseEvent {
             init.relatedTarget.as_deref(),
             None,
         );
+        event.set_movement(init.movementX, init.movementY);
         Ok(event)
     }
 
     pub fn point_in_target(&self) -> Option<Point2D<f32>> {
         self.point_in_target.get()
     }
+
+    /// <https://w3c.github.io/pointerlock/#extensions-to-the-mouseevent-interface>
+    pub fn set_movement(&self, movement_x: i32, movement_y: i32) {
+        self.movement_x.set(movement_x);
+        self.movement_y.set(movement_y);
+    }
 }
 
 impl MouseEventMethods for MouseEvent {
@@ -282,6 +304,16 @@ impl MouseEventMethods for MouseEvent {
         self.buttons.get()
     }
 
+    // https://w3c.github.io/pointerlock/#dom-mouseevent-movementx
+    fn MovementX(&self) -> i32 {
+        self.movement_x.get()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-mouseevent-movementy
+    fn MovementY(&self) -> i32 {
+        self.movement_y.get()
+    }
+
     // https://w3c.github.io/uievents/#widl-MouseEvent-relatedTarget
     fn GetRelatedTarget(&self) -> Option<DomRoot<EventTarget>> {
         self.related_target.get()
@@ -0,0 +1,220 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::abortsignal::AbortSignal;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::NavigateEventBinding;
+use crate::dom::bindings::codegen::Bindings::NavigateEventBinding::NavigateEventMethods;
+use crate::dom::bindings::codegen::Bindings::NavigateEventBinding::NavigationInterceptHandler;
+use crate::dom::bindings::codegen::Bindings::NavigateEventBinding::NavigationInterceptOptions;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationType;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::event::Event;
+use crate::dom::formdata::FormData;
+use crate::dom::navigationdestination::NavigationDestination;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
+
+// https://html.spec.whatwg.org/multipage/#navigateevent
+#[dom_struct]
+pub struct NavigateEvent {
+    event: Event,
+    navigation_type: NavigationType,
+    destination: Dom<NavigationDestination>,
+    can_intercept: bool,
+    user_initiated: bool,
+    hash_change: bool,
+    signal: Dom<AbortSignal>,
+    form_data: Option<Dom<FormData>>,
+    download_request: Option<DOMString>,
+    #[ignore_malloc_size_of = "mozjs"]
+    info: Heap<JSVal>,
+    /// Whether `intercept()` was called during the dispatch of the event.
+    intercepted: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#navigateevent-navigation-handler-list>
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    navigation_handler_list: DomRefCell<Vec<Rc<NavigationInterceptHandler>>>,
+}
+
+impl NavigateEvent {
+    fn new_inherited(
+        navigation_type: NavigationType,
+        destination: &NavigationDestination,
+        can_intercept: bool,
+        user_initiated: bool,
+        hash_change: bool,
+        signal: &AbortSignal,
+        form_data: Option<&FormData>,
+        download_request: Option<DOMString>,
+    ) -> NavigateEvent {
+        NavigateEvent {
+            event: Event::new_inherited(),
+            navigation_type,
+            destination: Dom::from_ref(destination),
+            can_intercept,
+            user_initiated,
+            hash_change,
+            signal: Dom::from_ref(signal),
+            form_data: form_data.map(Dom::from_ref),
+            download_request,
+            info: Heap::default(),
+            intercepted: Cell::new(false),
+            navigation_handler_list: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        navigation_type: NavigationType,
+        destination: &NavigationDestination,
+        can_intercept: bool,
+        user_initiated: bool,
+        hash_change: bool,
+        signal: &AbortSignal,
+        form_data: Option<&FormData>,
+        download_request: Option<DOMString>,
+        info: HandleValue,
+    ) -> DomRoot<NavigateEvent> {
+        let ev = reflect_dom_object(
+            Box::new(NavigateEvent::new_inherited(
+                navigation_type,
+                destination,
+                can_intercept,
+                user_initiated,
+                hash_change,
+                signal,
+                form_data,
+                download_request,
+            )),
+            window,
+            NavigateEventBinding::Wrap,
+        );
+        ev.info.set(info.get());
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: RootedTraceableBox<NavigateEventBinding::NavigateEventInit>,
+    ) -> Fallible<DomRoot<NavigateEvent>> {
+        Ok(NavigateEvent::new(
+            window,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            init.navigationType,
+            &init.destination,
+            init.canIntercept,
+            init.userInitiated,
+            init.hashChange,
+            &init.signal,
+            init.formData.as_deref(),
+            init.downloadRequest.clone(),
+            init.info.handle(),
+        ))
+    }
+
+    pub fn was_intercepted(&self) -> bool {
+        self.intercepted.get()
+    }
+
+    pub fn navigation_handlers(&self) -> Vec<Rc<NavigationInterceptHandler>> {
+        self.navigation_handler_list.borrow().clone()
+    }
+}
+
+impl NavigateEventMethods for NavigateEvent {
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-navigationtype
+    fn NavigationType(&self) -> NavigationType {
+        self.navigation_type
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-destination
+    fn Destination(&self) -> DomRoot<NavigationDestination> {
+        DomRoot::from_ref(&*self.destination)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-canintercept
+    fn CanIntercept(&self) -> bool {
+        self.can_intercept
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-userinitiated
+    fn UserInitiated(&self) -> bool {
+        self.user_initiated
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-hashchange
+    fn HashChange(&self) -> bool {
+        self.hash_change
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-signal
+    fn Signal(&self) -> DomRoot<AbortSignal> {
+        DomRoot::from_ref(&*self.signal)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-formdata
+    fn GetFormData(&self) -> Option<DomRoot<FormData>> {
+        self.form_data
+            .as_ref()
+            .map(|form_data| DomRoot::from_ref(&**form_data))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-downloadrequest
+    fn GetDownloadRequest(&self) -> Option<DOMString> {
+        self.download_request.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-info
+    fn Info(&self, _cx: JSContext) -> JSVal {
+        self.info.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigateevent-intercept
+    fn Intercept(&self, options: &NavigationInterceptOptions) -> ErrorResult {
+        // Step 1: Perform shared checks.
+        if !self.IsTrusted() || !self.can_intercept {
+            return Err(Error::Security);
+        }
+        let event = self.upcast::<Event>();
+        if !event.dispatching() || event.DefaultPrevented() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        if let Some(ref handler) = options.handler {
+            self.navigation_handler_list
+                .borrow_mut()
+                .push(handler.clone());
+        }
+
+        // Step 4.
+        self.intercepted.set(true);
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
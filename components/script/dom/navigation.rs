@@ -0,0 +1,909 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::enter_realm;
+use crate::dom::abortsignal::AbortSignal;
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::NavigateEventBinding::NavigateEventMethods;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationHistoryBehavior;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationMethods;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationNavigateOptions;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationOptions;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationReloadOptions;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationResult;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationType;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationUpdateCurrentEntryOptions;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::errorevent::ErrorEvent;
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::history::can_have_its_url_rewritten;
+use crate::dom::navigateevent::NavigateEvent;
+use crate::dom::navigationcurrententrychangeevent::NavigationCurrentEntryChangeEvent;
+use crate::dom::navigationdestination::NavigationDestination;
+use crate::dom::navigationhistoryentry::NavigationHistoryEntry;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsapi::JSContext as RawJSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::{JS_ClearPendingException, JS_GetPendingException};
+use js::rust::HandleValue;
+use msg::constellation_msg::HistoryStateId;
+use net_traits::request::Referrer;
+use script_traits::{HistoryEntryReplacement, LoadData, LoadOrigin, StructuredSerializedData};
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use url::Position;
+use uuid::Uuid;
+
+/// The outcome of firing a navigate event.
+#[derive(Clone, Copy, PartialEq)]
+pub enum NavigateEventOutcome {
+    /// The navigation goes ahead as it would without the navigation API.
+    Continue,
+    /// The navigation was canceled, or aborted by another one.
+    Canceled,
+    /// The navigation was intercepted, so it only changes the URL of the
+    /// document, and is finished by the navigation handlers of the event.
+    Intercepted,
+}
+
+/// A navigation started by one of the methods of the navigation API, whose
+/// promises are settled as the navigation makes progress.
+/// <https://html.spec.whatwg.org/multipage/#navigation-api-method-tracker>
+#[derive(JSTraceable, MallocSizeOf)]
+struct ApiMethodTracker {
+    #[ignore_malloc_size_of = "mozjs"]
+    info: Box<Heap<JSVal>>,
+    serialized_state: Option<Vec<u8>>,
+    #[ignore_malloc_size_of = "Rc"]
+    committed: Rc<Promise>,
+    #[ignore_malloc_size_of = "Rc"]
+    finished: Rc<Promise>,
+}
+
+impl ApiMethodTracker {
+    fn result(&self) -> NavigationResult {
+        NavigationResult {
+            committed: Some(self.committed.clone()),
+            finished: Some(self.finished.clone()),
+        }
+    }
+}
+
+// https://html.spec.whatwg.org/multipage/#navigation-interface
+//
+// Only the session history entries of the current document are known to
+// the navigation API, so the entry list starts over with every new document.
+#[dom_struct]
+pub struct Navigation {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+    /// <https://html.spec.whatwg.org/multipage/#navigation-entry-list>
+    entry_list: DomRefCell<Vec<Dom<NavigationHistoryEntry>>>,
+    /// <https://html.spec.whatwg.org/multipage/#navigation-current-entry-index>
+    current_entry_index: Cell<usize>,
+    /// <https://html.spec.whatwg.org/multipage/#ongoing-navigate-event>
+    ongoing_navigate_event: MutNullableDom<NavigateEvent>,
+    /// Identifies the ongoing navigation, so that the navigation handlers of
+    /// a navigation that has since finished or been aborted are ignored.
+    ongoing_navigation_id: Cell<u64>,
+    /// The number of promises returned by the navigation handlers of the
+    /// ongoing navigation that haven't been fulfilled yet.
+    pending_handler_count: Cell<usize>,
+    /// <https://html.spec.whatwg.org/multipage/#ongoing-api-method-tracker>
+    ongoing_api_method_tracker: DomRefCell<Option<ApiMethodTracker>>,
+    /// <https://html.spec.whatwg.org/multipage/#upcoming-non-traverse-api-method-tracker>
+    upcoming_non_traverse_api_method_tracker: DomRefCell<Option<ApiMethodTracker>>,
+    /// <https://html.spec.whatwg.org/multipage/#upcoming-traverse-api-method-trackers>
+    upcoming_traverse_api_method_trackers: DomRefCell<HashMap<DOMString, ApiMethodTracker>>,
+}
+
+impl Navigation {
+    fn new_inherited(window: &Window) -> Navigation {
+        Navigation {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+            entry_list: DomRefCell::new(vec![]),
+            current_entry_index: Cell::new(0),
+            ongoing_navigate_event: Default::default(),
+            ongoing_navigation_id: Cell::new(0),
+            pending_handler_count: Cell::new(0),
+            ongoing_api_method_tracker: DomRefCell::new(None),
+            upcoming_non_traverse_api_method_tracker: DomRefCell::new(None),
+            upcoming_traverse_api_method_trackers: DomRefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<Navigation> {
+        let navigation = reflect_dom_object(
+            Box::new(Navigation::new_inherited(window)),
+            window,
+            NavigationBinding::Wrap,
+        );
+        let entry = navigation.new_entry(None, None);
+        navigation
+            .entry_list
+            .borrow_mut()
+            .push(Dom::from_ref(&*entry));
+        navigation
+    }
+
+    /// Creates an entry for the current session history entry of the document.
+    fn new_entry(
+        &self,
+        key: Option<DOMString>,
+        state: Option<Vec<u8>>,
+    ) -> DomRoot<NavigationHistoryEntry> {
+        let new_uuid = || DOMString::from(Uuid::new_v4().to_string());
+        NavigationHistoryEntry::new(
+            self,
+            self.window.get_url(),
+            key.unwrap_or_else(new_uuid),
+            new_uuid(),
+            self.window.History().state_id(),
+            state,
+        )
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#has-entries-and-events-disabled>
+    fn has_entries_and_events_disabled(&self) -> bool {
+        let document = self.window.Document();
+        !document.is_fully_active() || !document.origin().is_tuple()
+    }
+
+    fn current_entry(&self) -> DomRoot<NavigationHistoryEntry> {
+        DomRoot::from_ref(&*self.entry_list.borrow()[self.current_entry_index.get()])
+    }
+
+    pub fn index_of(&self, entry: &NavigationHistoryEntry) -> Option<usize> {
+        self.entry_list
+            .borrow()
+            .iter()
+            .position(|other| &**other == entry)
+    }
+
+    /// Finds the entry for the session history entry with the given classic
+    /// history API state and URL, picking the one closest to the current
+    /// entry if there are several.
+    fn find_entry(&self, state_id: Option<HistoryStateId>, url: &ServoUrl) -> Option<usize> {
+        let current = self.current_entry_index.get() as isize;
+        self.entry_list
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.history_state_id() == state_id && entry.url() == url)
+            .min_by_key(|(index, _)| (*index as isize - current).abs())
+            .map(|(index, _)| index)
+    }
+
+    fn replace_current_entry(&self, state: Option<Vec<u8>>) -> DomRoot<NavigationHistoryEntry> {
+        let old_entry = self.current_entry();
+        let entry = self.new_entry(Some(old_entry.key().clone()), state);
+        self.entry_list.borrow_mut()[self.current_entry_index.get()] = Dom::from_ref(&*entry);
+        old_entry
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#update-the-navigation-api-entries-for-a-same-document-navigation>
+    pub fn update_entries_for_same_document_navigation(
+        &self,
+        navigation_type: NavigationType,
+        state: Option<Vec<u8>>,
+    ) {
+        // Step 1.
+        if self.has_entries_and_events_disabled() {
+            return;
+        }
+
+        // Steps 2-6.
+        let old_current_entry = self.current_entry();
+        let disposed_entries = match navigation_type {
+            NavigationType::Traverse => {
+                let state_id = self.window.History().state_id();
+                match self.find_entry(state_id, &self.window.get_url()) {
+                    Some(index) => {
+                        self.current_entry_index.set(index);
+                        vec![]
+                    },
+                    // The entry was added before the navigation API was set
+                    // up, so it isn't known yet.
+                    None => vec![self.replace_current_entry(state)],
+                }
+            },
+            NavigationType::Push => {
+                let entry = self.new_entry(None, state);
+                let index = self.current_entry_index.get() + 1;
+                let mut entry_list = self.entry_list.borrow_mut();
+                let disposed_entries = entry_list
+                    .drain(index..)
+                    .map(|entry| DomRoot::from_ref(&*entry))
+                    .collect();
+                entry_list.push(Dom::from_ref(&*entry));
+                self.current_entry_index.set(index);
+                disposed_entries
+            },
+            NavigationType::Replace => vec![self.replace_current_entry(state)],
+            NavigationType::Reload => {
+                if state.is_some() {
+                    old_current_entry.set_state(state);
+                }
+                vec![]
+            },
+        };
+
+        // Step 8.
+        self.fire_current_entry_change(Some(navigation_type), &old_current_entry);
+
+        // Step 9.
+        for entry in disposed_entries {
+            entry.upcast::<EventTarget>().fire_event(atom!("dispose"));
+        }
+    }
+
+    fn fire_current_entry_change(
+        &self,
+        navigation_type: Option<NavigationType>,
+        from: &NavigationHistoryEntry,
+    ) {
+        let event = NavigationCurrentEntryChangeEvent::new(
+            &self.window,
+            atom!("currententrychange"),
+            false,
+            false,
+            navigation_type,
+            from,
+        );
+        event.upcast::<Event>().fire(self.upcast::<EventTarget>());
+    }
+
+    fn fire_navigate_error(&self, error: HandleValue) {
+        let event = ErrorEvent::new(
+            self.window.upcast::<GlobalScope>(),
+            atom!("navigateerror"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            DOMString::new(),
+            DOMString::new(),
+            0,
+            0,
+            error,
+        );
+        event.upcast::<Event>().fire(self.upcast::<EventTarget>());
+    }
+
+    /// Fires a navigate event for a navigation of the document to `url`, which
+    /// goes to `destination_entry` if it is a traversal. Navigations that go
+    /// ahead within the document must be committed with
+    /// `commit_ongoing_navigation` once the URL of the document is updated.
+    /// <https://html.spec.whatwg.org/multipage/#inner-navigate-event-firing-algorithm>
+    pub fn fire_navigate_event(
+        &self,
+        navigation_type: NavigationType,
+        url: &ServoUrl,
+        destination_entry: Option<&NavigationHistoryEntry>,
+    ) -> NavigateEventOutcome {
+        // Step 1.
+        if self.has_entries_and_events_disabled() {
+            self.upcoming_non_traverse_api_method_tracker
+                .borrow_mut()
+                .take();
+            return NavigateEventOutcome::Continue;
+        }
+
+        self.abort_ongoing_navigation();
+
+        // Steps 2-5.
+        let tracker = match (navigation_type, destination_entry) {
+            (NavigationType::Traverse, Some(entry)) => self
+                .upcoming_traverse_api_method_trackers
+                .borrow_mut()
+                .remove(entry.key()),
+            (NavigationType::Traverse, None) => None,
+            _ => self
+                .upcoming_non_traverse_api_method_tracker
+                .borrow_mut()
+                .take(),
+        };
+
+        // Steps 6-18.
+        let document_url = self.window.get_url();
+        let hash_change = url.fragment().is_some() &&
+            url.as_url()[..Position::AfterQuery] ==
+                document_url.as_url()[..Position::AfterQuery] &&
+            url.fragment() != document_url.fragment();
+        let same_document = destination_entry.is_some() || hash_change;
+        let can_intercept = can_have_its_url_rewritten(&document_url, url) &&
+            (navigation_type != NavigationType::Traverse || same_document);
+        let state = match destination_entry {
+            Some(entry) => entry.state(),
+            None => tracker
+                .as_ref()
+                .and_then(|tracker| tracker.serialized_state.clone()),
+        };
+        let destination = NavigationDestination::new(
+            &self.window,
+            url.clone(),
+            destination_entry,
+            state,
+            same_document,
+        );
+        let signal = AbortSignal::new(self.window.upcast::<GlobalScope>());
+        let cx = self.window.get_cx();
+        rooted!(in(*cx) let mut info = UndefinedValue());
+        if let Some(ref tracker) = tracker {
+            info.set(tracker.info.get());
+        }
+        let event = NavigateEvent::new(
+            &self.window,
+            atom!("navigate"),
+            false,
+            navigation_type != NavigationType::Traverse,
+            navigation_type,
+            &destination,
+            can_intercept,
+            false,
+            hash_change,
+            &signal,
+            None,
+            None,
+            info.handle(),
+        );
+
+        // Steps 27-28.
+        self.ongoing_navigate_event.set(Some(&event));
+        self.ongoing_navigation_id
+            .set(self.ongoing_navigation_id.get() + 1);
+        *self.ongoing_api_method_tracker.borrow_mut() = tracker;
+
+        // Step 31.
+        let status = event.upcast::<Event>().fire(self.upcast::<EventTarget>());
+
+        // A listener started another navigation, which aborted this one.
+        if self
+            .ongoing_navigate_event
+            .get()
+            .map_or(true, |ongoing| &*ongoing != &*event)
+        {
+            return NavigateEventOutcome::Canceled;
+        }
+
+        // Step 32.
+        if status == EventStatus::Canceled {
+            self.abort_ongoing_navigation();
+            return NavigateEventOutcome::Canceled;
+        }
+
+        if event.was_intercepted() {
+            return NavigateEventOutcome::Intercepted;
+        }
+        if !same_document {
+            // The document is about to be replaced, so there is nothing
+            // left to finish.
+            self.ongoing_navigate_event.set(None);
+            self.ongoing_api_method_tracker.borrow_mut().take();
+        }
+        NavigateEventOutcome::Continue
+    }
+
+    /// Finishes firing the navigate event of a same-document navigation once
+    /// the URL of the document has been updated, which runs its navigation
+    /// handlers if it was intercepted.
+    /// <https://html.spec.whatwg.org/multipage/#inner-navigate-event-firing-algorithm>
+    /// Step 33.
+    #[allow(unsafe_code)]
+    pub fn commit_ongoing_navigation(&self, navigation_type: NavigationType) {
+        let state = self
+            .ongoing_api_method_tracker
+            .borrow()
+            .as_ref()
+            .and_then(|tracker| tracker.serialized_state.clone());
+        self.update_entries_for_same_document_navigation(navigation_type, state);
+
+        let event = match self.ongoing_navigate_event.get() {
+            Some(event) => event,
+            None => return,
+        };
+        let id = self.ongoing_navigation_id.get();
+        let global = self.global();
+        let _ac = enter_realm(self);
+        if let Some(ref tracker) = *self.ongoing_api_method_tracker.borrow() {
+            tracker.committed.resolve_native(&self.current_entry());
+        }
+
+        let cx = global.get_cx();
+        let promises: Vec<Rc<Promise>> = event
+            .navigation_handlers()
+            .iter()
+            .map(|handler| match handler.Call__(ExceptionHandling::Rethrow) {
+                Ok(promise) => promise,
+                Err(_) => {
+                    let promise = Promise::new(&global);
+                    rooted!(in(*cx) let mut error = UndefinedValue());
+                    unsafe {
+                        JS_GetPendingException(*cx, error.handle_mut());
+                        JS_ClearPendingException(*cx);
+                    }
+                    promise.reject(cx, error.handle());
+                    promise
+                },
+            })
+            .collect();
+
+        // One of the handlers started another navigation.
+        if self.ongoing_navigation_id.get() != id {
+            return;
+        }
+
+        if promises.is_empty() {
+            let this = Trusted::new(self);
+            let task = task!(navigation_handlers_fulfilled: move || {
+                this.root().finish_ongoing_navigation(id, None);
+            });
+            let _ = self
+                .window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue(task, self.window.upcast());
+            return;
+        }
+        self.pending_handler_count.set(promises.len());
+        for promise in promises {
+            let handler = PromiseNativeHandler::new(
+                &global,
+                Some(NavigationHandlerSettled::new(self, id, true)),
+                Some(NavigationHandlerSettled::new(self, id, false)),
+            );
+            promise.append_native_handler(&handler);
+        }
+    }
+
+    fn navigation_handler_fulfilled(&self, id: u64) {
+        if self.ongoing_navigation_id.get() != id {
+            return;
+        }
+        let pending_handler_count = self.pending_handler_count.get() - 1;
+        self.pending_handler_count.set(pending_handler_count);
+        if pending_handler_count == 0 {
+            self.finish_ongoing_navigation(id, None);
+        }
+    }
+
+    /// Fires navigatesuccess or navigateerror, depending on whether the
+    /// navigation handlers of the ongoing navigation were all fulfilled.
+    fn finish_ongoing_navigation(&self, id: u64, error: Option<HandleValue>) {
+        if self.ongoing_navigation_id.get() != id {
+            return;
+        }
+        let event = match self.ongoing_navigate_event.get() {
+            Some(event) => event,
+            None => return,
+        };
+        self.ongoing_navigate_event.set(None);
+        self.ongoing_navigation_id.set(id + 1);
+        let tracker = self.ongoing_api_method_tracker.borrow_mut().take();
+
+        let _ac = enter_realm(self);
+        match error {
+            None => {
+                self.upcast::<EventTarget>()
+                    .fire_event(atom!("navigatesuccess"));
+                if let Some(tracker) = tracker {
+                    tracker.finished.resolve_native(&self.current_entry());
+                }
+            },
+            Some(error) => {
+                let cx = self.window.get_cx();
+                event.Signal().signal_abort(cx, error);
+                self.fire_navigate_error(error);
+                if let Some(tracker) = tracker {
+                    tracker.finished.reject(cx, error);
+                }
+            },
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#abort-the-ongoing-navigation>
+    fn abort_ongoing_navigation(&self) {
+        // Steps 1-2.
+        let event = match self.ongoing_navigate_event.get() {
+            Some(event) => event,
+            None => return,
+        };
+        self.ongoing_navigate_event.set(None);
+        self.ongoing_navigation_id
+            .set(self.ongoing_navigation_id.get() + 1);
+        let tracker = self.ongoing_api_method_tracker.borrow_mut().take();
+
+        // Step 4.
+        let _ac = enter_realm(self);
+        let cx = self.window.get_cx();
+        let signal = event.Signal();
+        signal.signal_abort(cx, HandleValue::undefined());
+        rooted!(in(*cx) let error = signal.reason());
+
+        // Steps 6-7.
+        self.fire_navigate_error(error.handle());
+
+        // Step 8.
+        if let Some(tracker) = tracker {
+            tracker.committed.reject(cx, error.handle());
+            tracker.finished.reject(cx, error.handle());
+        }
+    }
+
+    fn new_api_method_tracker(
+        &self,
+        info: HandleValue,
+        serialized_state: Option<Vec<u8>>,
+    ) -> ApiMethodTracker {
+        let global = self.global();
+        ApiMethodTracker {
+            info: Heap::boxed(info.get()),
+            serialized_state,
+            committed: Promise::new(&global),
+            finished: Promise::new(&global),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#early-error-result>
+    fn early_error_result(&self, error: Error) -> NavigationResult {
+        let global = self.global();
+        let committed = Promise::new(&global);
+        committed.reject_error(error.clone());
+        let finished = Promise::new(&global);
+        finished.reject_error(error);
+        NavigationResult {
+            committed: Some(committed),
+            finished: Some(finished),
+        }
+    }
+
+    /// Navigates the document to `url` on behalf of `navigate()` or
+    /// `reload()`, tracking the navigation with a new API method tracker.
+    /// <https://html.spec.whatwg.org/multipage/#maybe-set-the-upcoming-non-traverse-api-method-tracker>
+    fn navigate_with_tracker(
+        &self,
+        url: ServoUrl,
+        replacement: HistoryEntryReplacement,
+        reload_triggered: bool,
+        info: HandleValue,
+        serialized_state: Option<Vec<u8>>,
+    ) -> NavigationResult {
+        let tracker = self.new_api_method_tracker(info, serialized_state);
+        let result = tracker.result();
+        *self.upcoming_non_traverse_api_method_tracker.borrow_mut() = Some(tracker);
+
+        let document = self.window.Document();
+        let load_data = LoadData::new(
+            LoadOrigin::Script(document.origin().immutable().clone()),
+            url,
+            Some(self.window.upcast::<GlobalScope>().pipeline_id()),
+            Some(Referrer::ReferrerUrl(document.url())),
+            document.get_referrer_policy(),
+        );
+        self.window
+            .load_url(replacement, reload_triggered, load_data);
+
+        // The navigation didn't get as far as firing a navigate event.
+        if self
+            .upcoming_non_traverse_api_method_tracker
+            .borrow_mut()
+            .take()
+            .is_some()
+        {
+            return self.early_error_result(Error::Abort);
+        }
+        result
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#performing-a-navigation-api-traversal>
+    fn perform_traversal(&self, key: DOMString, info: HandleValue) -> NavigationResult {
+        // Step 3.
+        if self.has_entries_and_events_disabled() {
+            return self.early_error_result(Error::InvalidState);
+        }
+
+        // Steps 4-5.
+        let current_entry = self.current_entry();
+        if *current_entry.key() == key {
+            let global = self.global();
+            let committed = Promise::new(&global);
+            committed.resolve_native(&current_entry);
+            let finished = Promise::new(&global);
+            finished.resolve_native(&current_entry);
+            return NavigationResult {
+                committed: Some(committed),
+                finished: Some(finished),
+            };
+        }
+
+        // Step 6.
+        if let Some(tracker) = self
+            .upcoming_traverse_api_method_trackers
+            .borrow()
+            .get(&key)
+        {
+            return tracker.result();
+        }
+
+        let index = match self
+            .entry_list
+            .borrow()
+            .iter()
+            .position(|entry| *entry.key() == key)
+        {
+            Some(index) => index,
+            None => return self.early_error_result(Error::InvalidState),
+        };
+
+        // Steps 7-11.
+        let tracker = self.new_api_method_tracker(info, None);
+        let result = tracker.result();
+        self.upcoming_traverse_api_method_trackers
+            .borrow_mut()
+            .insert(key.clone(), tracker);
+        let delta = index as i32 - self.current_entry_index.get() as i32;
+        if let Err(error) = self.window.History().Go(delta) {
+            self.upcoming_traverse_api_method_trackers
+                .borrow_mut()
+                .remove(&key);
+            return self.early_error_result(error);
+        }
+        result
+    }
+
+    /// The entry a traversal to the session history entry with the given
+    /// classic history API state and URL goes to, if it is known.
+    pub fn entry_for_traversal(
+        &self,
+        state_id: Option<HistoryStateId>,
+        url: &ServoUrl,
+    ) -> Option<DomRoot<NavigationHistoryEntry>> {
+        self.find_entry(state_id, url)
+            .map(|index| DomRoot::from_ref(&*self.entry_list.borrow()[index]))
+    }
+}
+
+impl NavigationMethods for Navigation {
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-entries
+    fn Entries(&self) -> Vec<DomRoot<NavigationHistoryEntry>> {
+        if self.has_entries_and_events_disabled() {
+            return vec![];
+        }
+        self.entry_list
+            .borrow()
+            .iter()
+            .map(|entry| DomRoot::from_ref(&**entry))
+            .collect()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-currententry
+    fn GetCurrentEntry(&self) -> Option<DomRoot<NavigationHistoryEntry>> {
+        if self.has_entries_and_events_disabled() {
+            return None;
+        }
+        Some(self.current_entry())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-updatecurrententry
+    fn UpdateCurrentEntry(
+        &self,
+        options: RootedTraceableBox<NavigationUpdateCurrentEntryOptions>,
+    ) -> ErrorResult {
+        // Steps 1-2.
+        if self.has_entries_and_events_disabled() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        let state = serialize_state(self.window.get_cx(), options.state.handle())?;
+
+        // Steps 4-5.
+        let current_entry = self.current_entry();
+        current_entry.set_state(state);
+        self.fire_current_entry_change(None, &current_entry);
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-cangoback
+    fn CanGoBack(&self) -> bool {
+        !self.has_entries_and_events_disabled() && self.current_entry_index.get() > 0
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-cangoforward
+    fn CanGoForward(&self) -> bool {
+        !self.has_entries_and_events_disabled() &&
+            self.current_entry_index.get() + 1 < self.entry_list.borrow().len()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-navigate
+    fn Navigate(
+        &self,
+        url: USVString,
+        options: RootedTraceableBox<NavigationNavigateOptions>,
+    ) -> NavigationResult {
+        // Steps 1-2.
+        let document_url = self.window.get_url();
+        let url = match document_url.join(&url.0) {
+            Ok(url) => url,
+            Err(_) => return self.early_error_result(Error::Syntax),
+        };
+
+        // Step 5.
+        let state = match serialize_state(self.window.get_cx(), options.state.handle()) {
+            Ok(state) => state,
+            Err(error) => return self.early_error_result(error),
+        };
+
+        // Steps 3 and 6.
+        if self.has_entries_and_events_disabled() {
+            return self.early_error_result(Error::InvalidState);
+        }
+
+        // Steps 7-11.
+        let replacement = match options.history {
+            NavigationHistoryBehavior::Replace => HistoryEntryReplacement::Enabled,
+            NavigationHistoryBehavior::Auto if url == document_url => {
+                HistoryEntryReplacement::Enabled
+            },
+            _ => HistoryEntryReplacement::Disabled,
+        };
+        self.navigate_with_tracker(url, replacement, false, options.parent.info.handle(), state)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-reload
+    fn Reload(&self, options: RootedTraceableBox<NavigationReloadOptions>) -> NavigationResult {
+        // Steps 1-3.
+        let state = if options.state.get().is_undefined() {
+            self.current_entry().state()
+        } else {
+            match serialize_state(self.window.get_cx(), options.state.handle()) {
+                Ok(state) => state,
+                Err(error) => return self.early_error_result(error),
+            }
+        };
+
+        // Step 4.
+        if self.has_entries_and_events_disabled() {
+            return self.early_error_result(Error::InvalidState);
+        }
+
+        // Steps 5-7.
+        self.navigate_with_tracker(
+            self.window.get_url(),
+            HistoryEntryReplacement::Enabled,
+            true,
+            options.parent.info.handle(),
+            state,
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-traverseto
+    fn TraverseTo(
+        &self,
+        key: DOMString,
+        options: RootedTraceableBox<NavigationOptions>,
+    ) -> NavigationResult {
+        self.perform_traversal(key, options.info.handle())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-back
+    fn Back(&self, options: RootedTraceableBox<NavigationOptions>) -> NavigationResult {
+        if !self.CanGoBack() {
+            return self.early_error_result(Error::InvalidState);
+        }
+        let index = self.current_entry_index.get() - 1;
+        let key = self.entry_list.borrow()[index].key().clone();
+        self.perform_traversal(key, options.info.handle())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigation-forward
+    fn Forward(&self, options: RootedTraceableBox<NavigationOptions>) -> NavigationResult {
+        if !self.CanGoForward() {
+            return self.early_error_result(Error::InvalidState);
+        }
+        let index = self.current_entry_index.get() + 1;
+        let key = self.entry_list.borrow()[index].key().clone();
+        self.perform_traversal(key, options.info.handle())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#handler-navigation-onnavigate
+    event_handler!(navigate, GetOnnavigate, SetOnnavigate);
+
+    // https://html.spec.whatwg.org/multipage/#handler-navigation-onnavigatesuccess
+    event_handler!(navigatesuccess, GetOnnavigatesuccess, SetOnnavigatesuccess);
+
+    // https://html.spec.whatwg.org/multipage/#handler-navigation-onnavigateerror
+    event_handler!(navigateerror, GetOnnavigateerror, SetOnnavigateerror);
+
+    // https://html.spec.whatwg.org/multipage/#handler-navigation-oncurrententrychange
+    event_handler!(
+        currententrychange,
+        GetOncurrententrychange,
+        SetOncurrententrychange
+    );
+}
+
+/// Serializes the navigation API state of an entry, which is left out if it
+/// is undefined.
+fn serialize_state(cx: JSContext, state: HandleValue) -> Fallible<Option<Vec<u8>>> {
+    if state.is_undefined() {
+        return Ok(None);
+    }
+    Ok(Some(structuredclone::write(cx, state, None)?.serialized))
+}
+
+/// Deserializes the navigation API state of an entry, which is undefined if
+/// it has none.
+pub fn deserialize_state(
+    cx: JSContext,
+    global: &GlobalScope,
+    state: Option<&Vec<u8>>,
+) -> Fallible<JSVal> {
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    if let Some(state) = state {
+        let data = StructuredSerializedData {
+            serialized: state.clone(),
+            ports: None,
+            offscreen_canvases: None,
+            image_bitmaps: None,
+            transform_streams: None,
+            shared_memory: None,
+        };
+        if structuredclone::read(global, data, value.handle_mut()).is_err() {
+            return Err(Error::DataClone);
+        }
+    }
+    Ok(value.get())
+}
+
+/// Counts down the navigation handlers of a navigation that are still
+/// pending as their promises are fulfilled, and fails the navigation as
+/// soon as one of them is rejected.
+#[derive(JSTraceable, MallocSizeOf)]
+struct NavigationHandlerSettled {
+    navigation: Dom<Navigation>,
+    id: u64,
+    fulfilled: bool,
+}
+
+impl NavigationHandlerSettled {
+    fn new(navigation: &Navigation, id: u64, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(NavigationHandlerSettled {
+            navigation: Dom::from_ref(navigation),
+            id,
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for NavigationHandlerSettled {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        if self.fulfilled {
+            self.navigation.navigation_handler_fulfilled(self.id);
+        } else {
+            self.navigation.finish_ongoing_navigation(self.id, Some(v));
+        }
+    }
+}
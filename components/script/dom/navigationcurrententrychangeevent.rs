@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationType;
+use crate::dom::bindings::codegen::Bindings::NavigationCurrentEntryChangeEventBinding;
+use crate::dom::bindings::codegen::Bindings::NavigationCurrentEntryChangeEventBinding::NavigationCurrentEntryChangeEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::navigationhistoryentry::NavigationHistoryEntry;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+// https://html.spec.whatwg.org/multipage/#navigationcurrententrychangeevent
+#[dom_struct]
+pub struct NavigationCurrentEntryChangeEvent {
+    event: Event,
+    navigation_type: Option<NavigationType>,
+    from: Dom<NavigationHistoryEntry>,
+}
+
+impl NavigationCurrentEntryChangeEvent {
+    fn new_inherited(
+        navigation_type: Option<NavigationType>,
+        from: &NavigationHistoryEntry,
+    ) -> NavigationCurrentEntryChangeEvent {
+        NavigationCurrentEntryChangeEvent {
+            event: Event::new_inherited(),
+            navigation_type,
+            from: Dom::from_ref(from),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        navigation_type: Option<NavigationType>,
+        from: &NavigationHistoryEntry,
+    ) -> DomRoot<NavigationCurrentEntryChangeEvent> {
+        let ev = reflect_dom_object(
+            Box::new(NavigationCurrentEntryChangeEvent::new_inherited(
+                navigation_type,
+                from,
+            )),
+            window,
+            NavigationCurrentEntryChangeEventBinding::Wrap,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &NavigationCurrentEntryChangeEventBinding::NavigationCurrentEntryChangeEventInit,
+    ) -> Fallible<DomRoot<NavigationCurrentEntryChangeEvent>> {
+        Ok(NavigationCurrentEntryChangeEvent::new(
+            window,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            init.navigationType,
+            &init.from,
+        ))
+    }
+}
+
+impl NavigationCurrentEntryChangeEventMethods for NavigationCurrentEntryChangeEvent {
+    // https://html.spec.whatwg.org/multipage/#dom-navigationcurrententrychangeevent-navigationtype
+    fn GetNavigationType(&self) -> Option<NavigationType> {
+        self.navigation_type
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationcurrententrychangeevent-from
+    fn From(&self) -> DomRoot<NavigationHistoryEntry> {
+        DomRoot::from_ref(&*self.from)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
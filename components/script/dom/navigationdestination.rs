@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::NavigationDestinationBinding;
+use crate::dom::bindings::codegen::Bindings::NavigationDestinationBinding::NavigationDestinationMethods;
+use crate::dom::bindings::codegen::Bindings::NavigationHistoryEntryBinding::NavigationHistoryEntryMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::navigation::deserialize_state;
+use crate::dom::navigationhistoryentry::NavigationHistoryEntry;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use servo_url::ServoUrl;
+
+// https://html.spec.whatwg.org/multipage/#navigationdestination
+#[dom_struct]
+pub struct NavigationDestination {
+    reflector_: Reflector,
+    url: ServoUrl,
+    /// The key, id and index of the entry a traversal goes to, which are
+    /// empty and -1 for other navigations.
+    key: DOMString,
+    id: DOMString,
+    index: i64,
+    same_document: bool,
+    state: Option<Vec<u8>>,
+}
+
+impl NavigationDestination {
+    fn new_inherited(
+        url: ServoUrl,
+        entry: Option<&NavigationHistoryEntry>,
+        state: Option<Vec<u8>>,
+        same_document: bool,
+    ) -> NavigationDestination {
+        NavigationDestination {
+            reflector_: Reflector::new(),
+            url,
+            key: entry.map_or_else(DOMString::new, |entry| entry.key().clone()),
+            id: entry.map_or_else(DOMString::new, |entry| entry.id().clone()),
+            index: entry.map_or(-1, |entry| entry.Index()),
+            same_document,
+            state,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        url: ServoUrl,
+        entry: Option<&NavigationHistoryEntry>,
+        state: Option<Vec<u8>>,
+        same_document: bool,
+    ) -> DomRoot<NavigationDestination> {
+        reflect_dom_object(
+            Box::new(NavigationDestination::new_inherited(
+                url,
+                entry,
+                state,
+                same_document,
+            )),
+            window,
+            NavigationDestinationBinding::Wrap,
+        )
+    }
+
+    pub fn url(&self) -> &ServoUrl {
+        &self.url
+    }
+}
+
+impl NavigationDestinationMethods for NavigationDestination {
+    // https://html.spec.whatwg.org/multipage/#dom-navigationdestination-url
+    fn Url(&self) -> USVString {
+        USVString(self.url.to_string())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationdestination-key
+    fn Key(&self) -> DOMString {
+        self.key.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationdestination-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationdestination-index
+    fn Index(&self) -> i64 {
+        self.index
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationdestination-samedocument
+    fn SameDocument(&self) -> bool {
+        self.same_document
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationdestination-getstate
+    fn GetState(&self, cx: JSContext) -> Fallible<JSVal> {
+        deserialize_state(cx, &self.global(), self.state.as_ref())
+    }
+}
@@ -0,0 +1,139 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::NavigationHistoryEntryBinding;
+use crate::dom::bindings::codegen::Bindings::NavigationHistoryEntryBinding::NavigationHistoryEntryMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::navigation::{deserialize_state, Navigation};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use msg::constellation_msg::HistoryStateId;
+use servo_url::ServoUrl;
+
+// https://html.spec.whatwg.org/multipage/#navigationhistoryentry
+#[dom_struct]
+pub struct NavigationHistoryEntry {
+    eventtarget: EventTarget,
+    navigation: Dom<Navigation>,
+    url: ServoUrl,
+    key: DOMString,
+    id: DOMString,
+    /// The classic history API state of the session history entry, which
+    /// identifies it together with its URL.
+    history_state_id: Option<HistoryStateId>,
+    /// The serialized navigation API state of the session history entry.
+    state: DomRefCell<Option<Vec<u8>>>,
+}
+
+impl NavigationHistoryEntry {
+    fn new_inherited(
+        navigation: &Navigation,
+        url: ServoUrl,
+        key: DOMString,
+        id: DOMString,
+        history_state_id: Option<HistoryStateId>,
+        state: Option<Vec<u8>>,
+    ) -> NavigationHistoryEntry {
+        NavigationHistoryEntry {
+            eventtarget: EventTarget::new_inherited(),
+            navigation: Dom::from_ref(navigation),
+            url,
+            key,
+            id,
+            history_state_id,
+            state: DomRefCell::new(state),
+        }
+    }
+
+    pub fn new(
+        navigation: &Navigation,
+        url: ServoUrl,
+        key: DOMString,
+        id: DOMString,
+        history_state_id: Option<HistoryStateId>,
+        state: Option<Vec<u8>>,
+    ) -> DomRoot<NavigationHistoryEntry> {
+        reflect_dom_object(
+            Box::new(NavigationHistoryEntry::new_inherited(
+                navigation,
+                url,
+                key,
+                id,
+                history_state_id,
+                state,
+            )),
+            &*navigation.global(),
+            NavigationHistoryEntryBinding::Wrap,
+        )
+    }
+
+    pub fn url(&self) -> &ServoUrl {
+        &self.url
+    }
+
+    pub fn key(&self) -> &DOMString {
+        &self.key
+    }
+
+    pub fn id(&self) -> &DOMString {
+        &self.id
+    }
+
+    pub fn history_state_id(&self) -> Option<HistoryStateId> {
+        self.history_state_id
+    }
+
+    pub fn state(&self) -> Option<Vec<u8>> {
+        self.state.borrow().clone()
+    }
+
+    pub fn set_state(&self, state: Option<Vec<u8>>) {
+        *self.state.borrow_mut() = state;
+    }
+}
+
+impl NavigationHistoryEntryMethods for NavigationHistoryEntry {
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-url
+    fn GetUrl(&self) -> Option<USVString> {
+        Some(USVString(self.url.to_string()))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-key
+    fn Key(&self) -> DOMString {
+        self.key.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-index
+    fn Index(&self) -> i64 {
+        self.navigation
+            .index_of(self)
+            .map_or(-1, |index| index as i64)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-samedocument
+    fn SameDocument(&self) -> bool {
+        // Only the entries of the current document are tracked.
+        true
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-getstate
+    fn GetState(&self, cx: JSContext) -> Fallible<JSVal> {
+        deserialize_state(cx, &self.global(), self.state.borrow().as_ref())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-ondispose
+    event_handler!(dispose, GetOndispose, SetOndispose);
+}
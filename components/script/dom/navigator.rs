@@ -3,30 +3,61 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::MediaKeySystemAccessBinding::MediaKeySystemConfiguration;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::clipboard::Clipboard;
+use crate::dom::credentialscontainer::CredentialsContainer;
+use crate::dom::gamepad::Gamepad;
+use crate::dom::gamepadevent::GamepadEventType;
 use crate::dom::gamepadlist::GamepadList;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::gpu::GPU;
+use crate::dom::headers::is_cors_safelisted_request_content_type;
 use crate::dom::identityhub::Identities;
 use crate::dom::mediadevices::MediaDevices;
+use crate::dom::mediakeysystemaccess;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
+use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::promise::Promise;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::window::Window;
+use crate::dom::xmlhttprequest::Extractable;
 use crate::dom::xr::XR;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use dom_struct::dom_struct;
-use std::cell::RefCell;
+use http::header::{self, HeaderMap, HeaderValue};
+use hyper::Method;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::request::KEEP_ALIVE_QUOTA;
+use net_traits::request::{CredentialsMode, Referrer, RequestBuilder, RequestMode};
+use net_traits::{CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseListener};
+use net_traits::{NetworkError, ResourceFetchTiming, ResourceTimingType};
+use script_traits::GamepadEvent;
+use servo_config::pref;
+use servo_url::ServoUrl;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
-use webgpu::wgpu::{AdapterId, DeviceId};
+use std::sync::{Arc, Mutex};
+use webgpu::wgpu::{
+    AdapterId, BindGroupLayoutId, BufferId, CommandEncoderId, ComputePassId, ComputePipelineId,
+    DeviceId, PipelineLayoutId, RenderPassId, RenderPipelineId, ShaderModuleId, TextureId,
+    TextureViewId,
+};
 
 #[dom_struct]
 pub struct Navigator {
@@ -41,8 +72,14 @@ pub struct Navigator {
     permissions: MutNullableDom<Permissions>,
     mediasession: MutNullableDom<MediaSession>,
     gpu: MutNullableDom<GPU>,
+    clipboard: MutNullableDom<Clipboard>,
+    storage: MutNullableDom<StorageManager>,
+    credentials: MutNullableDom<CredentialsContainer>,
     #[ignore_malloc_size_of = "Defined in wgpu"]
     gpu_id_hub: RefCell<Identities>,
+    /// The total size of the bodies of the beacons that are still being
+    /// sent, which count towards the keepalive quota.
+    beacon_bytes: Cell<u64>,
 }
 
 impl Navigator {
@@ -59,7 +96,11 @@ impl Navigator {
             permissions: Default::default(),
             mediasession: Default::default(),
             gpu: Default::default(),
+            clipboard: Default::default(),
+            storage: Default::default(),
+            credentials: Default::default(),
             gpu_id_hub: RefCell::new(Identities::new()),
+            beacon_bytes: Cell::new(0),
         }
     }
 
@@ -80,6 +121,82 @@ impl Navigator {
     pub fn create_device_id(&self) -> DeviceId {
         self.gpu_id_hub.borrow_mut().create_device_id()
     }
+
+    pub fn create_bind_group_layout_id(&self) -> BindGroupLayoutId {
+        self.gpu_id_hub.borrow_mut().create_bind_group_layout_id()
+    }
+
+    pub fn create_pipeline_layout_id(&self) -> PipelineLayoutId {
+        self.gpu_id_hub.borrow_mut().create_pipeline_layout_id()
+    }
+
+    pub fn create_shader_module_id(&self) -> ShaderModuleId {
+        self.gpu_id_hub.borrow_mut().create_shader_module_id()
+    }
+
+    pub fn create_compute_pipeline_id(&self) -> ComputePipelineId {
+        self.gpu_id_hub.borrow_mut().create_compute_pipeline_id()
+    }
+
+    pub fn create_command_encoder_id(&self) -> CommandEncoderId {
+        self.gpu_id_hub.borrow_mut().create_command_encoder_id()
+    }
+
+    pub fn create_compute_pass_id(&self) -> ComputePassId {
+        self.gpu_id_hub.borrow_mut().create_compute_pass_id()
+    }
+
+    pub fn create_render_pipeline_id(&self) -> RenderPipelineId {
+        self.gpu_id_hub.borrow_mut().create_render_pipeline_id()
+    }
+
+    pub fn create_render_pass_id(&self) -> RenderPassId {
+        self.gpu_id_hub.borrow_mut().create_render_pass_id()
+    }
+
+    pub fn create_texture_id(&self) -> TextureId {
+        self.gpu_id_hub.borrow_mut().create_texture_id()
+    }
+
+    pub fn create_texture_view_id(&self) -> TextureViewId {
+        self.gpu_id_hub.borrow_mut().create_texture_view_id()
+    }
+
+    pub fn create_buffer_id(&self) -> BufferId {
+        self.gpu_id_hub.borrow_mut().create_buffer_id()
+    }
+
+    /// Updates the gamepads with an event from the embedder,
+    /// <https://w3c.github.io/gamepad/#receiving-inputs>
+    pub fn handle_gamepad_event(&self, event: GamepadEvent) {
+        if !pref!(dom.gamepad.enabled) {
+            return;
+        }
+        let gamepads = self
+            .gamepads
+            .or_init(|| GamepadList::new(&self.global(), &[]));
+        match event {
+            GamepadEvent::Connected(index, name, axes, buttons) => {
+                if gamepads.find_embedder_gamepad(index).is_some() {
+                    return;
+                }
+                let gamepad =
+                    Gamepad::new_from_embedder(&self.global(), index, name, axes, buttons);
+                gamepads.add(&gamepad);
+                gamepad.notify_event(GamepadEventType::Connected);
+            },
+            GamepadEvent::Disconnected(index) => {
+                if let Some(gamepad) = gamepads.remove_embedder_gamepad(index) {
+                    gamepad.update_connected(false);
+                }
+            },
+            GamepadEvent::Updated(index, update) => {
+                if let Some(gamepad) = gamepads.find_embedder_gamepad(index) {
+                    gamepad.update_from_embedder(update);
+                }
+            },
+        }
+    }
 }
 
 impl NavigatorMethods for Navigator {
@@ -143,6 +260,11 @@ impl NavigatorMethods for Navigator {
         navigatorinfo::Language()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-hardwareconcurrency
+    fn HardwareConcurrency(&self) -> u64 {
+        navigatorinfo::HardwareConcurrency()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-navigator-plugins
     fn Plugins(&self) -> DomRoot<PluginArray> {
         self.plugins.or_init(|| PluginArray::new(&self.global()))
@@ -178,7 +300,6 @@ impl NavigatorMethods for Navigator {
 
         let vr_gamepads = self.Xr().get_gamepads();
         root.add_if_not_exists(&vr_gamepads);
-        // TODO: Add not VR related gamepads
         root
     }
     // https://w3c.github.io/permissions/#navigator-and-workernavigator-extension
@@ -229,4 +350,174 @@ impl NavigatorMethods for Navigator {
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
+
+    // https://w3c.github.io/clipboard-apis/#dom-navigator-clipboard
+    fn Clipboard(&self) -> DomRoot<Clipboard> {
+        self.clipboard.or_init(|| Clipboard::new(&self.global()))
+    }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-navigator-credentials
+    fn Credentials(&self) -> DomRoot<CredentialsContainer> {
+        self.credentials
+            .or_init(|| CredentialsContainer::new(&self.global()))
+    }
+
+    // https://w3c.github.io/encrypted-media/#dom-navigator-requestmediakeysystemaccess
+    fn RequestMediaKeySystemAccess(
+        &self,
+        key_system: DOMString,
+        supported_configurations: Vec<MediaKeySystemConfiguration>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        mediakeysystemaccess::request_media_key_system_access(
+            self.global().as_window(),
+            key_system,
+            supported_configurations,
+            comp,
+        )
+    }
+
+    // https://w3c.github.io/beacon/#sec-sendBeacon-method
+    fn SendBeacon(&self, url: USVString, data: Option<BodyInit>) -> Fallible<bool> {
+        let global = self.global();
+        let window = global.as_window();
+
+        // Steps 1-2.
+        let url = match ServoUrl::parse_with_base(Some(&global.api_base_url()), &url) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::Type("Invalid beacon URL".to_owned())),
+        };
+
+        // Step 3.
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::Type(
+                "Beacons can only be sent over HTTP(S)".to_owned(),
+            ));
+        }
+
+        // Steps 5-6.
+        let mut mode = RequestMode::NoCors;
+        let mut headers = HeaderMap::new();
+        let (body, bytes) = match data {
+            Some(data) => {
+                let (body, content_type) = data.extract();
+                // Step 6.4.
+                let bytes = body.len() as u64;
+                if self.beacon_bytes.get() + bytes > KEEP_ALIVE_QUOTA {
+                    return Ok(false);
+                }
+                // Steps 6.5-6.6.
+                if let Some(content_type) = content_type {
+                    if !is_cors_safelisted_request_content_type(content_type.as_bytes()) {
+                        mode = RequestMode::CorsMode;
+                    }
+                    if let Ok(value) = HeaderValue::from_str(&content_type) {
+                        headers.insert(header::CONTENT_TYPE, value);
+                    }
+                }
+                (Some(body), bytes)
+            },
+            None => (None, 0),
+        };
+
+        // Step 7.
+        let document = window.Document();
+        let request = RequestBuilder::new(url.clone())
+            .method(Method::POST)
+            .headers(headers)
+            .body(body)
+            .mode(mode)
+            .credentials_mode(CredentialsMode::Include)
+            .keep_alive(true)
+            .origin(document.origin().immutable().clone())
+            .referrer(Some(Referrer::ReferrerUrl(document.url())))
+            .referrer_policy(document.get_referrer_policy())
+            .pipeline_id(Some(global.pipeline_id()));
+
+        self.beacon_bytes.set(self.beacon_bytes.get() + bytes);
+        let context = Arc::new(Mutex::new(BeaconContext {
+            navigator: Trusted::new(self),
+            bytes,
+            resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+            url,
+        }));
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = window
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let listener = NetworkListener {
+            context,
+            task_source,
+            canceller: Some(canceller),
+        };
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                listener.notify_fetch(message.to().unwrap());
+            }),
+        );
+        // The beacon is not cancelled when the document is unloaded.
+        global
+            .core_resource_thread()
+            .send(CoreResourceMsg::Fetch(
+                request,
+                FetchChannels::ResponseMsg(action_sender, None),
+            ))
+            .unwrap();
+
+        // Step 8.
+        Ok(true)
+    }
+}
+
+/// Gives the keepalive quota used by a beacon back once it is sent.
+struct BeaconContext {
+    navigator: Trusted<Navigator>,
+    /// The size of the body of the beacon.
+    bytes: u64,
+    resource_timing: ResourceFetchTiming,
+    url: ServoUrl,
 }
+
+impl FetchResponseListener for BeaconContext {
+    fn process_request_body(&mut self) {}
+    fn process_request_eof(&mut self) {}
+    fn process_response(&mut self, _metadata: Result<FetchMetadata, NetworkError>) {}
+    fn process_response_chunk(&mut self, _chunk: Vec<u8>) {}
+
+    fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
+        let navigator = self.navigator.root();
+        navigator
+            .beacon_bytes
+            .set(navigator.beacon_bytes.get() - self.bytes);
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for BeaconContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (InitiatorType::Beacon, self.url.clone())
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.navigator.root().global()
+    }
+}
+
+impl PreInvoke for BeaconContext {}
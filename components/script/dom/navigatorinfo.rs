@@ -3,7 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::str::DOMString;
+use servo_config::pref;
 use std::borrow::Cow;
+use std::cmp;
 
 pub fn Product() -> DOMString {
     DOMString::from("Gecko")
@@ -64,3 +66,16 @@ pub fn AppVersion() -> DOMString {
 pub fn Language() -> DOMString {
     DOMString::from("en-US")
 }
+
+/// The number of logical processors, capped by the
+/// `dom.navigator.hardware_concurrency` pref unless it is 0.
+pub fn HardwareConcurrency() -> u64 {
+    let cpus = num_cpus::get() as u64;
+    let cap = pref!(dom.navigator.hardware_concurrency);
+    let concurrency = if cap > 0 {
+        cmp::min(cpus, cap as u64)
+    } else {
+        cpus
+    };
+    cmp::max(concurrency, 1)
+}
@@ -47,6 +47,7 @@ use crate::dom::htmlinputelement::{HTMLInputElement, LayoutHTMLInputElementHelpe
 use crate::dom::htmllinkelement::HTMLLinkElement;
 use crate::dom::htmlmediaelement::{HTMLMediaElement, LayoutHTMLMediaElementHelpers};
 use crate::dom::htmlmetaelement::HTMLMetaElement;
+use crate::dom::htmlslotelement::{slottable_inserted, slottable_removed, HTMLSlotElement};
 use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
 use crate::dom::mutationobserver::{Mutation, MutationObserver, RegisteredObserver};
@@ -668,7 +669,7 @@ impl Node {
         parent.ancestors().any(|ancestor| &*ancestor == self)
     }
 
-    fn is_shadow_including_inclusive_ancestor_of(&self, node: &Node) -> bool {
+    pub fn is_shadow_including_inclusive_ancestor_of(&self, node: &Node) -> bool {
         node.inclusive_ancestors(ShadowIncluding::Yes)
             .any(|ancestor| &*ancestor == self)
     }
@@ -957,6 +958,26 @@ impl Node {
         self.owner_doc.set(Some(document));
     }
 
+    /// The base URL of this node: the document base URL, adjusted by the
+    /// `xml:base` attributes of the node and its ancestors in XML documents.
+    pub fn base_url(&self) -> ServoUrl {
+        let document = self.owner_doc();
+        let base_url = document.base_url();
+        if document.is_html_document() {
+            return base_url;
+        }
+        let elements: Vec<_> = self
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .collect();
+        elements.iter().rev().fold(base_url, |base_url, element| {
+            match element.get_attribute(&ns!(xml), &local_name!("base")) {
+                Some(attr) => base_url.join(&attr.value()).unwrap_or(base_url),
+                None => base_url,
+            }
+        })
+    }
+
     pub fn containing_shadow_root(&self) -> Option<DomRoot<ShadowRoot>> {
         self.rare_data()
             .as_ref()?
@@ -969,6 +990,30 @@ impl Node {
         self.ensure_rare_data().containing_shadow_root = shadow_root.map(Dom::from_ref);
     }
 
+    pub fn assigned_slot(&self) -> Option<DomRoot<HTMLSlotElement>> {
+        self.rare_data()
+            .as_ref()?
+            .assigned_slot
+            .as_ref()
+            .map(|slot| DomRoot::from_ref(&**slot))
+    }
+
+    pub fn set_assigned_slot(&self, slot: Option<&HTMLSlotElement>) {
+        self.ensure_rare_data().assigned_slot = slot.map(Dom::from_ref);
+    }
+
+    /// Sets the part of this text node's data that is painted as selected.
+    pub fn set_selected_range(&self, range: Option<Range<usize>>) {
+        let selected_range = self
+            .rare_data()
+            .as_ref()
+            .and_then(|rare_data| rare_data.selected_range.clone());
+        if selected_range != range {
+            self.ensure_rare_data().selected_range = range;
+            self.dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
     pub fn is_in_html_doc(&self) -> bool {
         self.owner_doc().is_html_document()
     }
@@ -1215,6 +1260,7 @@ pub trait LayoutNodeHelpers {
 
     unsafe fn owner_doc_for_layout(&self) -> LayoutDom<Document>;
     unsafe fn containing_shadow_root_for_layout(&self) -> Option<LayoutDom<ShadowRoot>>;
+    unsafe fn assigned_slot_for_layout(&self) -> Option<LayoutDom<HTMLSlotElement>>;
 
     unsafe fn is_element_for_layout(&self) -> bool;
     unsafe fn get_flag(&self, flag: NodeFlags) -> bool;
@@ -1308,6 +1354,17 @@ impl LayoutNodeHelpers for LayoutDom<Node> {
             .map(|sr| sr.to_layout())
     }
 
+    #[inline]
+    #[allow(unsafe_code)]
+    unsafe fn assigned_slot_for_layout(&self) -> Option<LayoutDom<HTMLSlotElement>> {
+        (*self.unsafe_get())
+            .rare_data_for_layout()
+            .as_ref()?
+            .assigned_slot
+            .as_ref()
+            .map(|slot| slot.to_layout())
+    }
+
     #[inline]
     #[allow(unsafe_code)]
     unsafe fn get_flag(&self, flag: NodeFlags) -> bool {
@@ -1375,6 +1432,16 @@ impl LayoutNodeHelpers for LayoutDom<Node> {
 
     #[allow(unsafe_code)]
     fn selection(&self) -> Option<Range<usize>> {
+        if self.downcast::<Text>().is_some() {
+            return unsafe {
+                (*self.unsafe_get())
+                    .rare_data_for_layout()
+                    .as_ref()?
+                    .selected_range
+                    .clone()
+            };
+        }
+
         if let Some(area) = self.downcast::<HTMLTextAreaElement>() {
             return unsafe { area.selection_for_layout() };
         }
@@ -1931,6 +1998,8 @@ impl Node {
         for kid in new_nodes {
             // Step 7.1.
             parent.add_child(*kid, child);
+            // Steps 7.4-7.6.
+            slottable_inserted(*kid, parent);
             // Step 7.7.
             for descendant in kid
                 .traverse_preorder(ShadowIncluding::Yes)
@@ -2061,6 +2130,8 @@ impl Node {
         let old_next_sibling = node.GetNextSibling();
         // Steps 9-10 are handled in unbind_from_tree.
         parent.remove_child(node, cached_index);
+        // Slot assignment.
+        slottable_removed(node, parent);
         // Step 11. transient registered observers
         // Step 12.
         if let SuppressObserver::Unsuppressed = suppress_observers {
@@ -2293,7 +2364,7 @@ impl NodeMethods for Node {
 
     // https://dom.spec.whatwg.org/#dom-node-baseuri
     fn BaseURI(&self) -> USVString {
-        USVString(String::from(self.owner_doc().base_url().as_str()))
+        USVString(String::from(self.base_url().as_str()))
     }
 
     // https://dom.spec.whatwg.org/#dom-node-ownerdocument
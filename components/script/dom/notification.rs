@@ -0,0 +1,349 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::{
+    self, NotificationDirection, NotificationMethods, NotificationOptions, NotificationPermission,
+    NotificationPermissionCallback,
+};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, request_permission_to_use};
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use dom_struct::dom_struct;
+use embedder_traits::EmbedderMsg;
+use embedder_traits::Notification as EmbedderNotification;
+use js::conversions::{ConversionResult, FromJSValConvertible};
+use js::jsapi::JSContext as RawJSContext;
+use js::rust::HandleValue;
+use msg::constellation_msg::NotificationId;
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::rc::Rc;
+
+// https://notifications.spec.whatwg.org/#notification
+#[dom_struct]
+pub struct Notification {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    notification: EmbedderNotification,
+    dir: NotificationDirection,
+    lang: DOMString,
+    /// Whether the notification is in the list of notifications,
+    /// <https://notifications.spec.whatwg.org/#list-of-notifications>
+    shown: Cell<bool>,
+}
+
+impl Notification {
+    fn new_inherited(
+        notification: EmbedderNotification,
+        options: &NotificationOptions,
+    ) -> Notification {
+        Notification {
+            eventtarget: EventTarget::new_inherited(),
+            notification,
+            dir: options.dir,
+            lang: options.lang.clone(),
+            shown: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        notification: EmbedderNotification,
+        options: &NotificationOptions,
+    ) -> DomRoot<Notification> {
+        reflect_dom_object(
+            Box::new(Notification::new_inherited(notification, options)),
+            global,
+            NotificationBinding::Wrap,
+        )
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-notification
+    pub fn Constructor(
+        global: &GlobalScope,
+        title: DOMString,
+        options: &NotificationOptions,
+    ) -> Fallible<DomRoot<Notification>> {
+        // Steps 1-3 do not apply, the interface is not exposed to service workers
+        // and actions are not supported.
+
+        // Step 4.
+        let notification = Notification::new(
+            global,
+            create_notification(global, title, options)?,
+            options,
+        );
+
+        // Step 5.
+        notification.show();
+
+        // Step 6.
+        Ok(notification)
+    }
+
+    /// <https://notifications.spec.whatwg.org/#show-steps>
+    fn show(&self) {
+        let global = self.global();
+        let window = global.as_window();
+        let task_source = window.dom_manipulation_task_source();
+
+        // Step 2.
+        if current_permission(&global) != NotificationPermission::Granted {
+            task_source.queue_simple_event(self.upcast(), atom!("error"), window);
+            return;
+        }
+
+        // Steps 4-6, the embedder replaces any displayed notification with the same tag.
+        self.shown.set(true);
+        window.send_to_embedder(EmbedderMsg::ShowNotification(self.notification.clone()));
+
+        // Step 7.
+        task_source.queue_simple_event(self.upcast(), atom!("show"), window);
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-permission
+    pub fn Permission(global: &GlobalScope) -> NotificationPermission {
+        current_permission(global)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-requestpermission
+    pub fn RequestPermission(
+        global: &GlobalScope,
+        deprecated_callback: Option<Rc<NotificationPermissionCallback>>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        // Step 2.
+        let promise = Promise::new_in_current_compartment(global, comp);
+
+        // Step 3.3.1, the callback is invoked once the promise is resolved.
+        if let Some(callback) = deprecated_callback {
+            global.report_deprecation(
+                "NotificationPermissionCallback",
+                "The callback argument of Notification.requestPermission() is deprecated, \
+                 use the returned promise instead.",
+            );
+            let handler = PromiseNativeHandler::new(
+                global,
+                Some(PermissionCallbackHandler::new(global, callback)),
+                None,
+            );
+            promise.append_native_handler(&handler);
+        }
+
+        // Steps 3.1-3.2, 3.3.2.
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        request_permission_to_use(PermissionName::Notifications, global, move |state| {
+            let permission = match state {
+                PermissionState::Granted => NotificationPermission::Granted,
+                PermissionState::Denied => NotificationPermission::Denied,
+                PermissionState::Prompt => NotificationPermission::Default,
+            };
+            trusted_promise.root().resolve_native(&permission);
+        });
+
+        // Step 4.
+        promise
+    }
+}
+
+impl NotificationMethods for Notification {
+    // https://notifications.spec.whatwg.org/#dom-notification-onclick
+    event_handler!(click, GetOnclick, SetOnclick);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onshow
+    event_handler!(show, GetOnshow, SetOnshow);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-title
+    fn Title(&self) -> DOMString {
+        DOMString::from(self.notification.title.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-dir
+    fn Dir(&self) -> NotificationDirection {
+        self.dir
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-lang
+    fn Lang(&self) -> DOMString {
+        self.lang.clone()
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-body
+    fn Body(&self) -> DOMString {
+        DOMString::from(self.notification.body.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-tag
+    fn Tag(&self) -> DOMString {
+        DOMString::from(self.notification.tag.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-image
+    fn Image(&self) -> USVString {
+        serialize_url(&self.notification.image)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-icon
+    fn Icon(&self) -> USVString {
+        serialize_url(&self.notification.icon)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-badge
+    fn Badge(&self) -> USVString {
+        serialize_url(&self.notification.badge)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-timestamp
+    fn Timestamp(&self) -> u64 {
+        self.notification.timestamp
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-renotify
+    fn Renotify(&self) -> bool {
+        self.notification.renotify
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-silent
+    fn Silent(&self) -> bool {
+        self.notification.silent
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-requireinteraction
+    fn RequireInteraction(&self) -> bool {
+        self.notification.require_interaction
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-close
+    fn Close(&self) {
+        // https://notifications.spec.whatwg.org/#close-steps
+        // Steps 1-2.
+        if !self.shown.replace(false) {
+            return;
+        }
+
+        // Step 3.
+        let global = self.global();
+        let window = global.as_window();
+        window.send_to_embedder(EmbedderMsg::CloseNotification(self.notification.id));
+
+        // Step 4.
+        window.dom_manipulation_task_source().queue_simple_event(
+            self.upcast(),
+            atom!("close"),
+            window,
+        );
+    }
+}
+
+/// <https://notifications.spec.whatwg.org/#create-a-notification>
+pub fn create_notification(
+    global: &GlobalScope,
+    title: DOMString,
+    options: &NotificationOptions,
+) -> Fallible<EmbedderNotification> {
+    // Step 3.
+    if options.renotify && options.tag.is_empty() {
+        return Err(Error::Type(
+            "A notification without a tag cannot be renotified".to_owned(),
+        ));
+    }
+
+    // Steps 10-14, urls that fail to parse are ignored.
+    let base_url = global.api_base_url();
+    let parse_url = |url: &Option<USVString>| {
+        url.as_ref()
+            .and_then(|url| ServoUrl::parse_with_base(Some(&base_url), &url.0).ok())
+    };
+
+    // Steps 15-16.
+    let timestamp = options.timestamp.unwrap_or_else(|| {
+        let time = time::get_time();
+        (time.sec * 1000 + (time.nsec / 1000000) as i64) as u64
+    });
+
+    // Steps 6-9 and 17-19.
+    Ok(EmbedderNotification {
+        id: NotificationId::new(),
+        title: String::from(title),
+        body: String::from(options.body.clone()),
+        tag: String::from(options.tag.clone()),
+        image: parse_url(&options.image),
+        icon: parse_url(&options.icon),
+        badge: parse_url(&options.badge),
+        timestamp,
+        renotify: options.renotify,
+        silent: options.silent,
+        require_interaction: options.requireInteraction,
+    })
+}
+
+/// The notifications permission of the global,
+/// <https://notifications.spec.whatwg.org/#permission-model>
+pub fn current_permission(global: &GlobalScope) -> NotificationPermission {
+    match get_descriptor_permission_state(PermissionName::Notifications, Some(global)) {
+        PermissionState::Granted => NotificationPermission::Granted,
+        PermissionState::Denied => NotificationPermission::Denied,
+        PermissionState::Prompt => NotificationPermission::Default,
+    }
+}
+
+fn serialize_url(url: &Option<ServoUrl>) -> USVString {
+    USVString(
+        url.as_ref()
+            .map_or(String::new(), |url| url.as_str().to_owned()),
+    )
+}
+
+/// Invokes the deprecated callback of `Notification.requestPermission()`
+/// with the permission its promise is resolved with.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PermissionCallbackHandler {
+    global: Dom<GlobalScope>,
+    #[ignore_malloc_size_of = "Rc"]
+    callback: Rc<NotificationPermissionCallback>,
+}
+
+impl PermissionCallbackHandler {
+    fn new(
+        global: &GlobalScope,
+        callback: Rc<NotificationPermissionCallback>,
+    ) -> Box<dyn Callback> {
+        Box::new(PermissionCallbackHandler {
+            global: Dom::from_ref(global),
+            callback,
+        })
+    }
+}
+
+impl Callback for PermissionCallbackHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut RawJSContext, v: HandleValue) {
+        let permission = match unsafe { NotificationPermission::from_jsval(cx, v, ()) } {
+            Ok(ConversionResult::Success(permission)) => permission,
+            _ => return,
+        };
+        let _ = self
+            .callback
+            .Call_(&*self.global, permission, ExceptionHandling::Report);
+    }
+}
@@ -7,6 +7,7 @@ use crate::dom::audiobuffer::{AudioBuffer, MAX_SAMPLE_RATE, MIN_SAMPLE_RATE};
 use crate::dom::audionode::MAX_CHANNEL_COUNT;
 use crate::dom::baseaudiocontext::{BaseAudioContext, BaseAudioContextOptions};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::AudioContextState;
 use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContextBinding::BaseAudioContextMethods;
 use crate::dom::bindings::codegen::Bindings::OfflineAudioContextBinding;
 use crate::dom::bindings::codegen::Bindings::OfflineAudioContextBinding::OfflineAudioContextMethods;
@@ -114,6 +115,22 @@ impl OfflineAudioContext {
     ) -> Fallible<DomRoot<OfflineAudioContext>> {
         OfflineAudioContext::new(window, number_of_channels, length, *sample_rate)
     }
+
+    /// https://webaudio.github.io/web-audio-api/#dom-offlineaudiocontext-renderingstarted-slot
+    pub fn rendering_started(&self) -> bool {
+        self.rendering_started.get()
+    }
+
+    /// Sets the state attribute of the context, and fires a `statechange`
+    /// event at it.
+    fn set_state(&self, state: AudioContextState) {
+        self.context.set_state_attribute(state);
+        let window = DomRoot::downcast::<Window>(self.global()).unwrap();
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), atom!("statechange"), &window);
+    }
 }
 
 impl OfflineAudioContextMethods for OfflineAudioContext {
@@ -128,10 +145,12 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
     // https://webaudio.github.io/web-audio-api/#dom-offlineaudiocontext-startrendering
     fn StartRendering(&self, comp: InCompartment) -> Rc<Promise> {
         let promise = Promise::new_in_current_compartment(&self.global(), comp);
-        if self.rendering_started.get() {
+        // Step 2.
+        if self.rendering_started.get() || self.context.State() == AudioContextState::Closed {
             promise.reject_error(Error::InvalidState);
             return promise;
         }
+        // Step 3.
         self.rendering_started.set(true);
 
         *self.pending_rendering_promise.borrow_mut() = Some(promise.clone());
@@ -170,9 +189,11 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
                             .chunks(this.length as usize)
                             .map(|channel| channel.to_vec())
                             .collect();
-                        // it can end up being empty if the task failed
-                        if processed_audio.len() != this.length as usize {
-                            processed_audio.resize(this.length as usize, Vec::new())
+                        // it can end up being short or empty if the task
+                        // failed, in which case the rest is silence
+                        processed_audio.resize(this.channel_count as usize, Vec::new());
+                        for channel in processed_audio.iter_mut() {
+                            channel.resize(this.length as usize, 0.);
                         }
                         let buffer = AudioBuffer::new(
                             &this.global().as_window(),
@@ -180,6 +201,8 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
                             this.length,
                             *this.context.SampleRate(),
                             Some(processed_audio.as_slice()));
+                        // Rendering the graph again isn't possible.
+                        this.set_state(AudioContextState::Closed);
                         (*this.pending_rendering_promise.borrow_mut()).take().unwrap().resolve_native(&buffer);
                         let global = &this.global();
                         let window = global.as_window();
@@ -203,8 +226,11 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
             .resume()
             .is_err()
         {
+            self.pending_rendering_promise.borrow_mut().take();
             promise.reject_error(Error::Type("Could not start offline rendering".to_owned()));
+            return promise;
         }
+        self.set_state(AudioContextState::Running);
 
         promise
     }
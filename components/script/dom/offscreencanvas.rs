@@ -2,29 +2,41 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::compartments::InCompartment;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::OffscreenCanvasBinding::{
-    OffscreenCanvasMethods, OffscreenRenderingContext, Wrap as OffscreenCanvasWrap,
+    ImageEncodeOptions, OffscreenCanvasMethods, OffscreenRenderingContext,
+    Wrap as OffscreenCanvasWrap,
 };
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
+use crate::dom::blob::{Blob, BlobImpl};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::offscreencanvasrenderingcontext2d::OffscreenCanvasRenderingContext2D;
+use crate::dom::promise::Promise;
 use crate::script_runtime::JSContext;
 use canvas_traits::canvas::{CanvasMsg, FromScriptMsg};
 use dom_struct::dom_struct;
-use euclid::default::Size2D;
+use euclid::default::{Rect, Size2D};
+use image::png::PNGEncoder;
+use image::ColorType;
 use ipc_channel::ipc::IpcSharedMemory;
+use js::jsapi::MutableHandleObject;
 use js::rust::HandleValue;
 use profile_traits::ipc;
 use ref_filter_map;
+use script_traits::transferable::{OffscreenCanvasImpl, OffscreenCanvasPlaceholder};
 use std::cell::Cell;
 use std::cell::Ref;
+use std::rc::Rc;
 
 #[unrooted_must_root_lint::must_root]
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -41,6 +53,11 @@ pub struct OffscreenCanvas {
     height: Cell<u64>,
     context: DomRefCell<Option<OffscreenCanvasContext>>,
     placeholder: Option<Dom<HTMLCanvasElement>>,
+    /// The paint thread of the placeholder canvas element, which is possibly
+    /// in another event-loop, if control of it was transferred to this canvas.
+    placeholder_link: DomRefCell<Option<OffscreenCanvasPlaceholder>>,
+    /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-detached>
+    detached: Cell<bool>,
 }
 
 impl OffscreenCanvas {
@@ -48,6 +65,7 @@ impl OffscreenCanvas {
         width: u64,
         height: u64,
         placeholder: Option<&HTMLCanvasElement>,
+        placeholder_link: Option<OffscreenCanvasPlaceholder>,
     ) -> OffscreenCanvas {
         OffscreenCanvas {
             eventtarget: EventTarget::new_inherited(),
@@ -55,6 +73,8 @@ impl OffscreenCanvas {
             height: Cell::new(height),
             context: DomRefCell::new(None),
             placeholder: placeholder.map(Dom::from_ref),
+            placeholder_link: DomRefCell::new(placeholder_link),
+            detached: Cell::new(false),
         }
     }
 
@@ -63,9 +83,15 @@ impl OffscreenCanvas {
         width: u64,
         height: u64,
         placeholder: Option<&HTMLCanvasElement>,
+        placeholder_link: Option<OffscreenCanvasPlaceholder>,
     ) -> DomRoot<OffscreenCanvas> {
         reflect_dom_object(
-            Box::new(OffscreenCanvas::new_inherited(width, height, placeholder)),
+            Box::new(OffscreenCanvas::new_inherited(
+                width,
+                height,
+                placeholder,
+                placeholder_link,
+            )),
             global,
             OffscreenCanvasWrap,
         )
@@ -76,7 +102,7 @@ impl OffscreenCanvas {
         width: u64,
         height: u64,
     ) -> Fallible<DomRoot<OffscreenCanvas>> {
-        let offscreencanvas = OffscreenCanvas::new(global, width, height, None);
+        let offscreencanvas = OffscreenCanvas::new(global, width, height, None, None);
         Ok(offscreencanvas)
     }
 
@@ -93,6 +119,19 @@ impl OffscreenCanvas {
         }
     }
 
+    pub fn placeholder_link(&self) -> Option<OffscreenCanvasPlaceholder> {
+        self.placeholder_link.borrow().clone()
+    }
+
+    /// Notifies the placeholder canvas element, if any, that the bitmap it
+    /// displays was updated with a new frame.
+    /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-placeholder>
+    pub fn commit(&self) {
+        if let Some(ref placeholder) = *self.placeholder_link.borrow() {
+            let _ = placeholder.commit_chan.send(());
+        }
+    }
+
     pub fn context(&self) -> Option<Ref<OffscreenCanvasContext>> {
         ref_filter_map::ref_filter_map(self.context.borrow(), |ctx| ctx.as_ref())
     }
@@ -148,8 +187,14 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
         _cx: JSContext,
         id: DOMString,
         _options: HandleValue,
-    ) -> Option<OffscreenRenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<OffscreenRenderingContext>> {
+        // Step 2.
+        if self.detached.get() {
+            return Err(Error::InvalidState);
+        }
+        // TODO: WebGL contexts need a window, so a canvas whose control was
+        // transferred to a worker can only be drawn with a 2d context.
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(OffscreenRenderingContext::OffscreenCanvasRenderingContext2D),
@@ -160,7 +205,7 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
                 .get_or_init_webgl2_context(cx, options)
                 .map(OffscreenRenderingContext::WebGL2RenderingContext),*/
             _ => None,
-        }
+        })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
@@ -198,4 +243,147 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
             }
         }
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-transfertoimagebitmap
+    fn TransferToImageBitmap(&self) -> Fallible<DomRoot<ImageBitmap>> {
+        // Step 2.
+        if self.context.borrow().is_none() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        let size = self.get_size().to_u32();
+        let data = self
+            .fetch_all_data()
+            .and_then(|(data, _)| data)
+            .map(|data| data.to_vec())
+            .unwrap_or_else(|| vec![0; size.area() as usize * 4]);
+        let image = ImageBitmap::new(
+            &self.global(),
+            size.width,
+            size.height,
+            data,
+            true,
+            self.origin_is_clean(),
+        );
+
+        // Step 4, the paint thread replaces the bitmap with a new transparent
+        // black one, which also resets the state of the rendering context.
+        if let Some(canvas_context) = self.context() {
+            match &*canvas_context {
+                OffscreenCanvasContext::OffscreenContext2d(rendering_context) => {
+                    rendering_context.set_canvas_bitmap_dimensions(self.get_size());
+                },
+            }
+        }
+
+        // Step 5.
+        Ok(image)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-converttoblob
+    fn ConvertToBlob(&self, _options: &ImageEncodeOptions, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+
+        // Step 2.
+        if !self.origin_is_clean() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        // Step 3.
+        if self.Width() == 0 || self.Height() == 0 {
+            promise.reject_error(Error::IndexSize);
+            return promise;
+        }
+
+        // Step 4.
+        let size = self.get_size().to_u32();
+        let file = match *self.context.borrow() {
+            Some(OffscreenCanvasContext::OffscreenContext2d(ref context)) => {
+                context.get_rect(Rect::from_size(size))
+            },
+            // Each pixel is fully-transparent black.
+            None => vec![0; size.area() as usize * 4],
+        };
+
+        // Step 6.
+        // FIXME: Only handle image/png for now, and encode synchronously.
+        let mut png = Vec::new();
+        PNGEncoder::new(&mut png)
+            .encode(&file, size.width, size.height, ColorType::RGBA(8))
+            .unwrap();
+        let blob = Blob::new(
+            &global,
+            BlobImpl::new_from_bytes(png),
+            "image/png".to_owned(),
+        );
+        promise.resolve_native(&blob);
+        promise
+    }
+}
+
+impl Transferable for OffscreenCanvas {
+    /// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-steps>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        // Step 1.
+        if self.detached.get() || self.context.borrow().is_some() {
+            return Err(());
+        }
+
+        let offscreen_canvases = match sc_holder {
+            StructuredDataHolder::Write {
+                offscreen_canvases, ..
+            } => offscreen_canvases,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+
+        // Step 2.
+        self.detached.set(true);
+
+        // Step 3-5, the bitmap of this canvas is unset,
+        // and its placeholder is now linked to the new canvas.
+        let canvas_impl = OffscreenCanvasImpl {
+            width: self.width.replace(0),
+            height: self.height.replace(0),
+            placeholder: self.placeholder_link.borrow_mut().take(),
+        };
+
+        // The transferred canvas is stored at the index returned as its extra data.
+        let offscreen_canvases = offscreen_canvases.get_or_insert_with(Vec::new);
+        offscreen_canvases.push(canvas_impl);
+        Ok((offscreen_canvases.len() - 1) as u64)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-receiving-steps>
+    fn transfer_receive(
+        owner: &DomRoot<GlobalScope>,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        let offscreen_canvases = match sc_holder {
+            StructuredDataHolder::Read {
+                offscreen_canvases, ..
+            } => offscreen_canvases,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+        let canvas_impl = offscreen_canvases
+            .as_ref()
+            .and_then(|canvases| canvases.get(extra_data as usize))
+            .ok_or(())?
+            .clone();
+
+        // Step 1-4.
+        let canvas = OffscreenCanvas::new(
+            owner,
+            canvas_impl.width,
+            canvas_impl.height,
+            None,
+            canvas_impl.placeholder,
+        );
+        return_object.set(canvas.reflector().rootable().get());
+        Ok(())
+    }
 }
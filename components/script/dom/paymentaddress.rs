@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PaymentAddressBinding::{self, PaymentAddressMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use embedder_traits::payment;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+
+// https://w3c.github.io/payment-request/#paymentaddress-interface
+#[dom_struct]
+pub struct PaymentAddress {
+    reflector_: Reflector,
+    country: DOMString,
+    #[ignore_malloc_size_of = "mozjs"]
+    address_line: Heap<JSVal>,
+    region: DOMString,
+    city: DOMString,
+    dependent_locality: DOMString,
+    postal_code: DOMString,
+    sorting_code: DOMString,
+    organization: DOMString,
+    recipient: DOMString,
+    phone: DOMString,
+}
+
+impl PaymentAddress {
+    fn new_inherited(address: &payment::PaymentAddress) -> PaymentAddress {
+        PaymentAddress {
+            reflector_: Reflector::new(),
+            country: DOMString::from(&*address.country),
+            address_line: Heap::default(),
+            region: DOMString::from(&*address.region),
+            city: DOMString::from(&*address.city),
+            dependent_locality: DOMString::from(&*address.dependent_locality),
+            postal_code: DOMString::from(&*address.postal_code),
+            sorting_code: DOMString::from(&*address.sorting_code),
+            organization: DOMString::from(&*address.organization),
+            recipient: DOMString::from(&*address.recipient),
+            phone: DOMString::from(&*address.phone),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, address: &payment::PaymentAddress) -> DomRoot<PaymentAddress> {
+        let payment_address = reflect_dom_object(
+            Box::new(PaymentAddress::new_inherited(address)),
+            global,
+            PaymentAddressBinding::Wrap,
+        );
+        let address_line: Vec<DOMString> = address
+            .address_line
+            .iter()
+            .map(|line| DOMString::from(&**line))
+            .collect();
+        payment_address
+            .address_line
+            .set(to_frozen_array(&address_line, global.get_cx()));
+        payment_address
+    }
+}
+
+impl PaymentAddressMethods for PaymentAddress {
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-country
+    fn Country(&self) -> DOMString {
+        self.country.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-addressline
+    fn AddressLine(&self, _cx: JSContext) -> JSVal {
+        self.address_line.get()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-region
+    fn Region(&self) -> DOMString {
+        self.region.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-city
+    fn City(&self) -> DOMString {
+        self.city.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-dependentlocality
+    fn DependentLocality(&self) -> DOMString {
+        self.dependent_locality.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-postalcode
+    fn PostalCode(&self) -> DOMString {
+        self.postal_code.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-sortingcode
+    fn SortingCode(&self) -> DOMString {
+        self.sorting_code.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-organization
+    fn Organization(&self) -> DOMString {
+        self.organization.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-recipient
+    fn Recipient(&self) -> DOMString {
+        self.recipient.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentaddress-phone
+    fn Phone(&self) -> DOMString {
+        self.phone.clone()
+    }
+}
@@ -0,0 +1,801 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::PaymentRequestBinding::{
+    self, PaymentCurrencyAmount, PaymentDetailsInit, PaymentDetailsModifier, PaymentDetailsUpdate,
+    PaymentItem, PaymentMethodData, PaymentOptions, PaymentRequestMethods, PaymentShippingOption,
+    PaymentShippingType,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::conversions::ConversionResult;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::paymentaddress::PaymentAddress;
+use crate::dom::paymentrequestupdateevent::PaymentRequestUpdateEvent;
+use crate::dom::paymentresponse::PaymentResponse;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use embedder_traits::payment::{self, PaymentDetails, PaymentMethod, PaymentResponseData};
+use embedder_traits::payment::{PaymentSheet, PaymentSheetEvent};
+use embedder_traits::EmbedderMsg;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::jsapi::JSContext as RawJSContext;
+use js::jsapi::{Heap, JSObject, JS_Stringify};
+use js::jsval::ObjectValue;
+use js::rust::{HandleObject, HandleValue};
+use profile_traits::ipc as profiled_ipc;
+use servo_atoms::Atom;
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::os::raw::c_void;
+use std::rc::Rc;
+use std::slice;
+use uuid::Uuid;
+
+/// <https://w3c.github.io/payment-request/#dfn-state>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+enum PaymentRequestState {
+    Created,
+    /// The payment sheet is shown to the user.
+    Interactive,
+    Closed,
+}
+
+// https://w3c.github.io/payment-request/#paymentrequest-interface
+#[dom_struct]
+pub struct PaymentRequest {
+    eventtarget: EventTarget,
+    id: DOMString,
+    /// The payment methods the page accepts, with their data serialized to JSON,
+    /// <https://w3c.github.io/payment-request/#dfn-serializedmethoddata>
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    serialized_method_data: Vec<PaymentMethod>,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    details: DomRefCell<PaymentDetails>,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    options: payment::PaymentOptions,
+    /// The payment methods the embedder supports, known once the payment
+    /// sheet is shown.
+    supported_methods: DomRefCell<Vec<String>>,
+    state: Cell<PaymentRequestState>,
+    /// Whether the page is updating the details after the user changed the
+    /// shipping address or option.
+    updating: Cell<bool>,
+    shipping_address: MutNullableDom<PaymentAddress>,
+    shipping_option: DomRefCell<Option<DOMString>>,
+    #[ignore_malloc_size_of = "promises are hard"]
+    accept_promise: DomRefCell<Option<Rc<Promise>>>,
+}
+
+impl PaymentRequest {
+    fn new_inherited(
+        id: DOMString,
+        serialized_method_data: Vec<PaymentMethod>,
+        details: PaymentDetails,
+        options: payment::PaymentOptions,
+        shipping_option: Option<DOMString>,
+    ) -> PaymentRequest {
+        PaymentRequest {
+            eventtarget: EventTarget::new_inherited(),
+            id,
+            serialized_method_data,
+            details: DomRefCell::new(details),
+            options,
+            supported_methods: DomRefCell::new(Vec::new()),
+            state: Cell::new(PaymentRequestState::Created),
+            updating: Cell::new(false),
+            shipping_address: Default::default(),
+            shipping_option: DomRefCell::new(shipping_option),
+            accept_promise: DomRefCell::new(None),
+        }
+    }
+
+    // https://w3c.github.io/payment-request/#constructor
+    pub fn Constructor(
+        window: &Window,
+        method_data: Vec<RootedTraceableBox<PaymentMethodData>>,
+        details: RootedTraceableBox<PaymentDetailsInit>,
+        options: &PaymentOptions,
+    ) -> Fallible<DomRoot<PaymentRequest>> {
+        let global = window.upcast::<GlobalScope>();
+        let cx = global.get_cx();
+
+        // Step 3.
+        let id = details
+            .id
+            .clone()
+            .unwrap_or_else(|| DOMString::from(Uuid::new_v4().to_string()));
+
+        // Steps 4-5.
+        if method_data.is_empty() {
+            return Err(Error::Type(
+                "At least one payment method is required".to_owned(),
+            ));
+        }
+        let mut serialized_method_data = Vec::with_capacity(method_data.len());
+        for method in &method_data {
+            validate_payment_method_identifier(&method.supportedMethods)?;
+            serialized_method_data.push(PaymentMethod {
+                supported_method: method.supportedMethods.to_string(),
+                data: serialize_data(cx, method.data.as_ref().map(|data| &**data))?,
+            });
+        }
+
+        // Steps 6-7.
+        let total = total_item(&details.total)?;
+        let display_items = payment_items(details.parent.displayItems.as_ref())?;
+
+        // Step 8. The shipping options are ignored when the page doesn't
+        // request a shipping address.
+        let (shipping_options, selected_shipping_option) = match details.parent.shippingOptions {
+            Some(ref given_options) if options.requestShipping => shipping_options(given_options)?,
+            _ => (Vec::new(), None),
+        };
+
+        // Step 9.
+        let modifiers = details_modifiers(cx, details.parent.modifiers.as_ref())?;
+
+        // Steps 11-16.
+        let shipping_type = if options.requestShipping {
+            Some(match options.shippingType {
+                PaymentShippingType::Shipping => payment::PaymentShippingType::Shipping,
+                PaymentShippingType::Delivery => payment::PaymentShippingType::Delivery,
+                PaymentShippingType::Pickup => payment::PaymentShippingType::Pickup,
+            })
+        } else {
+            None
+        };
+        let options = payment::PaymentOptions {
+            request_payer_name: options.requestPayerName,
+            request_payer_email: options.requestPayerEmail,
+            request_payer_phone: options.requestPayerPhone,
+            shipping_type,
+        };
+        let details = PaymentDetails {
+            total,
+            display_items,
+            shipping_options,
+            modifiers,
+            error: None,
+        };
+        Ok(reflect_dom_object(
+            Box::new(PaymentRequest::new_inherited(
+                id,
+                serialized_method_data,
+                details,
+                options,
+                selected_shipping_option.map(DOMString::from),
+            )),
+            window,
+            PaymentRequestBinding::Wrap,
+        ))
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.state.get() == PaymentRequestState::Interactive
+    }
+
+    pub fn is_updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    /// Waits for the page to settle `details_promise` with the new details of
+    /// the payment, and shows them on the payment sheet,
+    /// <https://w3c.github.io/payment-request/#update-a-paymentrequest-s-details-algorithm>
+    pub fn update_details(&self, details_promise: &Promise) {
+        self.updating.set(true);
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(DetailsPromiseSettled::new(self, true)),
+            Some(DetailsPromiseSettled::new(self, false)),
+        );
+        details_promise.append_native_handler(&handler);
+    }
+
+    fn details_promise_fulfilled(&self, cx: JSContext, value: HandleValue) {
+        if !self.is_interactive() {
+            return;
+        }
+        let details = match PaymentDetailsUpdate::new(cx, value) {
+            Ok(ConversionResult::Success(details)) => details,
+            Ok(ConversionResult::Failure(error)) => {
+                return self.abort_update(Error::Type(error.into_owned()));
+            },
+            Err(()) => return self.abort_update(Error::JSFailed),
+        };
+        if let Err(error) = self.apply_details_update(cx, &details) {
+            return self.abort_update(error);
+        }
+        self.updating.set(false);
+        self.update_payment_sheet();
+    }
+
+    /// Validates `update` and replaces the details of the payment with it,
+    /// <https://w3c.github.io/payment-request/#update-a-paymentrequest-s-details-algorithm>
+    /// steps 8.2-8.3.
+    fn apply_details_update(&self, cx: JSContext, update: &PaymentDetailsUpdate) -> Fallible<()> {
+        let total = match update.total {
+            Some(ref total) => Some(total_item(total)?),
+            None => None,
+        };
+        let display_items = match update.parent.displayItems {
+            Some(ref items) => Some(payment_items(Some(items))?),
+            None => None,
+        };
+        let shipping_options = match update.parent.shippingOptions {
+            Some(ref options) if self.options.shipping_type.is_some() => {
+                Some(shipping_options(options)?)
+            },
+            _ => None,
+        };
+        let modifiers = match update.parent.modifiers {
+            Some(ref modifiers) => Some(details_modifiers(cx, Some(modifiers))?),
+            None => None,
+        };
+
+        let mut details = self.details.borrow_mut();
+        if let Some(total) = total {
+            details.total = total;
+        }
+        if let Some(display_items) = display_items {
+            details.display_items = display_items;
+        }
+        if let Some((shipping_options, selected_shipping_option)) = shipping_options {
+            details.shipping_options = shipping_options;
+            *self.shipping_option.borrow_mut() = selected_shipping_option.map(DOMString::from);
+        }
+        if let Some(modifiers) = modifiers {
+            details.modifiers = modifiers;
+        }
+        details.error = update.error.as_ref().map(|error| error.to_string());
+        Ok(())
+    }
+
+    /// Closes the payment sheet when the page failed to update the details of
+    /// the payment,
+    /// <https://w3c.github.io/payment-request/#abort-the-update>
+    fn abort_update(&self, error: Error) {
+        if !self.is_interactive() {
+            return;
+        }
+        self.close_payment_sheet();
+        self.state.set(PaymentRequestState::Closed);
+        self.updating.set(false);
+        let accept_promise = self.accept_promise.borrow_mut().take();
+        if let Some(accept_promise) = accept_promise {
+            accept_promise.reject_error(error);
+        }
+    }
+
+    /// Asks the embedder to close the payment sheet, which it can't do once
+    /// the payment is processed.
+    fn close_payment_sheet(&self) -> bool {
+        let global = self.global();
+        let (sender, receiver) =
+            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        global
+            .as_window()
+            .send_to_embedder(EmbedderMsg::AbortPayment(self.id.to_string(), sender));
+        receiver.recv().unwrap_or(false)
+    }
+
+    /// The details of the payment, with the modifiers of the payment methods
+    /// that the user can't pay with left out.
+    fn payment_sheet_details(&self) -> PaymentDetails {
+        let mut details = self.details.borrow().clone();
+        let supported_methods = self.supported_methods.borrow();
+        details
+            .modifiers
+            .retain(|modifier| supported_methods.contains(&modifier.supported_method));
+        details
+    }
+
+    fn update_payment_sheet(&self) {
+        self.global()
+            .as_window()
+            .send_to_embedder(EmbedderMsg::UpdatePaymentSheet(
+                self.id.to_string(),
+                self.payment_sheet_details(),
+            ));
+    }
+
+    /// Sends the payment sheet to the embedder, and listens to what the user
+    /// does with it.
+    fn show_payment_sheet(&self, window: &Window, methods: Vec<PaymentMethod>) {
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let this = Trusted::new(self);
+        let (task_source, canceller) = window
+            .task_manager()
+            .user_interaction_task_source_with_canceller();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: PaymentSheetEvent = match message.to() {
+                    Ok(event) => event,
+                    Err(_) => return,
+                };
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_payment_sheet_event: move || {
+                        this.root().handle_payment_sheet_event(event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let sheet = PaymentSheet {
+            id: self.id.to_string(),
+            methods,
+            details: self.payment_sheet_details(),
+            options: self.options.clone(),
+        };
+        window.send_to_embedder(EmbedderMsg::ShowPaymentSheet(sheet, sender));
+    }
+
+    fn handle_payment_sheet_event(&self, event: PaymentSheetEvent) {
+        if !self.is_interactive() {
+            return;
+        }
+        match event {
+            PaymentSheetEvent::ShippingAddressChange(address) => {
+                self.shipping_address_changed(address)
+            },
+            PaymentSheetEvent::ShippingOptionChange(option) => self.shipping_option_changed(option),
+            PaymentSheetEvent::Accept(data) => self.accept(data),
+            PaymentSheetEvent::Abort => self.user_aborted(),
+        }
+    }
+
+    /// <https://w3c.github.io/payment-request/#shipping-address-changed-algorithm>
+    fn shipping_address_changed(&self, mut address: payment::PaymentAddress) {
+        // The page only learns where to ship until the user accepts to pay.
+        address.organization.clear();
+        address.phone.clear();
+        address.recipient.clear();
+        address.address_line.clear();
+        self.shipping_address
+            .set(Some(&PaymentAddress::new(&self.global(), &address)));
+        self.fire_update_event(atom!("shippingaddresschange"));
+    }
+
+    /// <https://w3c.github.io/payment-request/#shipping-option-changed-algorithm>
+    fn shipping_option_changed(&self, option: String) {
+        *self.shipping_option.borrow_mut() = Some(DOMString::from(option));
+        self.fire_update_event(atom!("shippingoptionchange"));
+    }
+
+    /// <https://w3c.github.io/payment-request/#dfn-paymentrequest-updated-algorithm>
+    fn fire_update_event(&self, name: Atom) {
+        let event = PaymentRequestUpdateEvent::new(&self.global(), name, false, false);
+        event.upcast::<Event>().fire(self.upcast());
+        // The payment sheet goes on with the current details when the page
+        // doesn't update them.
+        if !event.wait_for_update() {
+            self.update_payment_sheet();
+        }
+    }
+
+    /// <https://w3c.github.io/payment-request/#user-accepts-the-payment-request-algorithm>
+    fn accept(&self, mut data: PaymentResponseData) {
+        // Steps 2-3.
+        if self.updating.get() {
+            return;
+        }
+        let request_shipping = self.options.shipping_type.is_some();
+        if request_shipping &&
+            (self.shipping_address.get().is_none() || self.shipping_option.borrow().is_none())
+        {
+            return;
+        }
+
+        // Steps 4-11.
+        self.state.set(PaymentRequestState::Closed);
+        let global = self.global();
+        let shipping_address = match data.shipping_address.take() {
+            Some(ref address) if request_shipping => Some(PaymentAddress::new(&global, address)),
+            _ => None,
+        };
+        data.shipping_option = if request_shipping {
+            self.shipping_option
+                .borrow()
+                .as_ref()
+                .map(|option| option.to_string())
+        } else {
+            None
+        };
+        if !self.options.request_payer_name {
+            data.payer_name = None;
+        }
+        if !self.options.request_payer_email {
+            data.payer_email = None;
+        }
+        if !self.options.request_payer_phone {
+            data.payer_phone = None;
+        }
+        let response = PaymentResponse::new(
+            &global,
+            self.id.clone(),
+            &data,
+            shipping_address.as_ref().map(|address| &**address),
+        );
+
+        // Step 12.
+        let accept_promise = self.accept_promise.borrow_mut().take();
+        if let Some(accept_promise) = accept_promise {
+            accept_promise.resolve_native(&response);
+        }
+    }
+
+    /// <https://w3c.github.io/payment-request/#user-aborts-the-payment-request-algorithm>
+    fn user_aborted(&self) {
+        self.state.set(PaymentRequestState::Closed);
+        self.updating.set(false);
+        let accept_promise = self.accept_promise.borrow_mut().take();
+        if let Some(accept_promise) = accept_promise {
+            accept_promise.reject_error(Error::Abort);
+        }
+    }
+}
+
+impl PaymentRequestMethods for PaymentRequest {
+    // https://w3c.github.io/payment-request/#show-method
+    fn Show(&self, details_promise: Option<&Promise>, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let window = global.as_window();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+
+        // Steps 3-5.
+        if !window.Document().is_fully_active() {
+            promise.reject_error(Error::Abort);
+            return promise;
+        }
+        if self.state.get() != PaymentRequestState::Created {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Steps 7-8.
+        self.state.set(PaymentRequestState::Interactive);
+        *self.accept_promise.borrow_mut() = Some(promise.clone());
+
+        // Steps 9-12. Only the methods that the embedder supports are shown.
+        let supported_methods = supported_payment_methods(window);
+        let methods: Vec<PaymentMethod> = self
+            .serialized_method_data
+            .iter()
+            .filter(|method| supported_methods.contains(&method.supported_method))
+            .cloned()
+            .collect();
+        if methods.is_empty() {
+            self.state.set(PaymentRequestState::Closed);
+            self.accept_promise.borrow_mut().take();
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        }
+        *self.supported_methods.borrow_mut() = supported_methods;
+
+        // Steps 13-14.
+        self.show_payment_sheet(window, methods);
+        if let Some(details_promise) = details_promise {
+            self.update_details(details_promise);
+        }
+        promise
+    }
+
+    // https://w3c.github.io/payment-request/#abort-method
+    fn Abort(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Step 2.
+        if !self.is_interactive() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 4.
+        if !self.close_payment_sheet() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+        self.state.set(PaymentRequestState::Closed);
+        self.updating.set(false);
+        let accept_promise = self.accept_promise.borrow_mut().take();
+        if let Some(accept_promise) = accept_promise {
+            accept_promise.reject_error(Error::Abort);
+        }
+        promise.resolve_native(&());
+        promise
+    }
+
+    // https://w3c.github.io/payment-request/#canmakepayment-method
+    fn CanMakePayment(&self, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+
+        // Step 1.
+        if self.state.get() != PaymentRequestState::Created {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Steps 3-5.
+        let supported_methods = supported_payment_methods(global.as_window());
+        let can_make_payment = self
+            .serialized_method_data
+            .iter()
+            .any(|method| supported_methods.contains(&method.supported_method));
+        promise.resolve_native(&can_make_payment);
+        promise
+    }
+
+    // https://w3c.github.io/payment-request/#id-attribute
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#shippingaddress-attribute
+    fn GetShippingAddress(&self) -> Option<DomRoot<PaymentAddress>> {
+        self.shipping_address.get()
+    }
+
+    // https://w3c.github.io/payment-request/#shippingoption-attribute
+    fn GetShippingOption(&self) -> Option<DOMString> {
+        self.shipping_option.borrow().clone()
+    }
+
+    // https://w3c.github.io/payment-request/#shippingtype-attribute
+    fn GetShippingType(&self) -> Option<PaymentShippingType> {
+        self.options
+            .shipping_type
+            .map(|shipping_type| match shipping_type {
+                payment::PaymentShippingType::Shipping => PaymentShippingType::Shipping,
+                payment::PaymentShippingType::Delivery => PaymentShippingType::Delivery,
+                payment::PaymentShippingType::Pickup => PaymentShippingType::Pickup,
+            })
+    }
+
+    // https://w3c.github.io/payment-request/#onshippingaddresschange-attribute
+    event_handler!(
+        shippingaddresschange,
+        GetOnshippingaddresschange,
+        SetOnshippingaddresschange
+    );
+
+    // https://w3c.github.io/payment-request/#onshippingoptionchange-attribute
+    event_handler!(
+        shippingoptionchange,
+        GetOnshippingoptionchange,
+        SetOnshippingoptionchange
+    );
+}
+
+/// Updates the details of a payment request once the page settles the
+/// promise it passed to `show()` or `updateWith()`.
+#[derive(JSTraceable, MallocSizeOf)]
+struct DetailsPromiseSettled {
+    request: Dom<PaymentRequest>,
+    fulfilled: bool,
+}
+
+impl DetailsPromiseSettled {
+    fn new(request: &PaymentRequest, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(DetailsPromiseSettled {
+            request: Dom::from_ref(request),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for DetailsPromiseSettled {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut RawJSContext, v: HandleValue) {
+        if self.fulfilled {
+            let cx = unsafe { JSContext::from_ptr(cx) };
+            self.request.details_promise_fulfilled(cx, v);
+        } else {
+            self.request.abort_update(Error::Abort);
+        }
+    }
+}
+
+/// The identifiers of the payment methods that the embedder supports.
+fn supported_payment_methods(window: &Window) -> Vec<String> {
+    let global = window.upcast::<GlobalScope>();
+    let (sender, receiver) = profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    window.send_to_embedder(EmbedderMsg::GetSupportedPaymentMethods(sender));
+    receiver.recv().unwrap_or_default()
+}
+
+/// Throws a `RangeError` unless `identifier` is an https URL or a standardized
+/// payment method identifier, such as "basic-card",
+/// <https://w3c.github.io/payment-method-id/#validity>
+pub fn validate_payment_method_identifier(identifier: &str) -> Fallible<()> {
+    let valid = match ServoUrl::parse(identifier) {
+        Ok(url) => url.scheme() == "https" && url.username().is_empty() && url.password().is_none(),
+        Err(_) => identifier.split('-').all(|part| {
+            part.bytes()
+                .next()
+                .map_or(false, |byte| byte.is_ascii_lowercase()) &&
+                part.bytes()
+                    .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit())
+        }),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Range(format!(
+            "{} is not a valid payment method identifier",
+            identifier
+        )))
+    }
+}
+
+/// The JSON serialization of the data of a payment method.
+#[allow(unsafe_code)]
+fn serialize_data(cx: JSContext, data: Option<&Heap<*mut JSObject>>) -> Fallible<Option<String>> {
+    let data = match data {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    rooted!(in(*cx) let mut value = ObjectValue(data.get()));
+    let mut json: Vec<u16> = Vec::new();
+    let stringified = unsafe {
+        JS_Stringify(
+            *cx,
+            value.handle_mut().into(),
+            HandleObject::null().into(),
+            HandleValue::null().into(),
+            Some(write_json),
+            &mut json as *mut Vec<u16> as *mut c_void,
+        )
+    };
+    if !stringified {
+        return Err(Error::JSFailed);
+    }
+    Ok(Some(String::from_utf16_lossy(&json)))
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn write_json(string: *const u16, length: u32, data: *mut c_void) -> bool {
+    let json = &mut *(data as *mut Vec<u16>);
+    json.extend_from_slice(slice::from_raw_parts(string, length as usize));
+    true
+}
+
+/// <https://w3c.github.io/payment-request/#dfn-check-and-canonicalize-amount>
+pub fn check_and_canonicalize_amount(
+    amount: &PaymentCurrencyAmount,
+) -> Fallible<payment::PaymentCurrencyAmount> {
+    if amount.currency.len() != 3 ||
+        !amount
+            .currency
+            .bytes()
+            .all(|byte| byte.is_ascii_alphabetic())
+    {
+        return Err(Error::Range(format!(
+            "{} is not a well-formed currency code",
+            amount.currency
+        )));
+    }
+    if !is_valid_decimal_monetary_value(&amount.value) {
+        return Err(Error::Type(format!(
+            "{} is not a valid decimal monetary value",
+            amount.value
+        )));
+    }
+    Ok(payment::PaymentCurrencyAmount {
+        currency: amount.currency.to_ascii_uppercase(),
+        value: amount.value.to_string(),
+    })
+}
+
+/// <https://w3c.github.io/payment-request/#dfn-valid-decimal-monetary-value>
+pub fn is_valid_decimal_monetary_value(value: &str) -> bool {
+    let value = if value.starts_with('-') {
+        &value[1..]
+    } else {
+        value
+    };
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    let mut parts = value.splitn(2, '.');
+    parts.next().map_or(false, is_digits) && parts.next().map_or(true, is_digits)
+}
+
+fn payment_item(item: &PaymentItem) -> Fallible<payment::PaymentItem> {
+    Ok(payment::PaymentItem {
+        label: item.label.to_string(),
+        amount: check_and_canonicalize_amount(&item.amount)?,
+        pending: item.pending,
+    })
+}
+
+fn payment_items(items: Option<&Vec<PaymentItem>>) -> Fallible<Vec<payment::PaymentItem>> {
+    items
+        .map_or(&[][..], |items| &items[..])
+        .iter()
+        .map(payment_item)
+        .collect()
+}
+
+/// <https://w3c.github.io/payment-request/#dfn-check-and-canonicalize-total-amount>
+pub fn total_item(total: &PaymentItem) -> Fallible<payment::PaymentItem> {
+    let total = payment_item(total)?;
+    if total.amount.value.starts_with('-') {
+        return Err(Error::Type(
+            "The total amount must not be negative".to_owned(),
+        ));
+    }
+    Ok(total)
+}
+
+/// The shipping options of the details of a payment, with the id of the
+/// selected one, which is the last one that claims to be.
+pub fn shipping_options(
+    options: &[PaymentShippingOption],
+) -> Fallible<(Vec<payment::PaymentShippingOption>, Option<String>)> {
+    let mut shipping_options: Vec<payment::PaymentShippingOption> =
+        Vec::with_capacity(options.len());
+    let mut selected_shipping_option = None;
+    for option in options {
+        let amount = check_and_canonicalize_amount(&option.amount)?;
+        if shipping_options
+            .iter()
+            .any(|shipping_option| shipping_option.id == *option.id)
+        {
+            return Err(Error::Type(format!(
+                "There are several shipping options with the id {}",
+                option.id
+            )));
+        }
+        if option.selected {
+            selected_shipping_option = Some(option.id.to_string());
+        }
+        shipping_options.push(payment::PaymentShippingOption {
+            id: option.id.to_string(),
+            label: option.label.to_string(),
+            amount,
+            selected: option.selected,
+        });
+    }
+    Ok((shipping_options, selected_shipping_option))
+}
+
+fn details_modifiers(
+    cx: JSContext,
+    modifiers: Option<&Vec<RootedTraceableBox<PaymentDetailsModifier>>>,
+) -> Fallible<Vec<payment::PaymentDetailsModifier>> {
+    modifiers
+        .map_or(&[][..], |modifiers| &modifiers[..])
+        .iter()
+        .map(|modifier| {
+            validate_payment_method_identifier(&modifier.supportedMethods)?;
+            let total = match modifier.total {
+                Some(ref total) => Some(total_item(total)?),
+                None => None,
+            };
+            Ok(payment::PaymentDetailsModifier {
+                supported_method: modifier.supportedMethods.to_string(),
+                total,
+                additional_display_items: payment_items(modifier.additionalDisplayItems.as_ref())?,
+                data: serialize_data(cx, modifier.data.as_ref().map(|data| &**data))?,
+            })
+        })
+        .collect()
+}
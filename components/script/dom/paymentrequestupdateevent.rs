@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PaymentRequestUpdateEventBinding::{
+    self, PaymentRequestUpdateEventInit, PaymentRequestUpdateEventMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::paymentrequest::PaymentRequest;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::cell::Cell;
+
+// https://w3c.github.io/payment-request/#paymentrequestupdateevent-interface
+#[dom_struct]
+pub struct PaymentRequestUpdateEvent {
+    event: Event,
+    /// Whether `updateWith()` was called,
+    /// <https://w3c.github.io/payment-request/#dfn-waitforupdate>
+    wait_for_update: Cell<bool>,
+}
+
+impl PaymentRequestUpdateEvent {
+    fn new_inherited() -> PaymentRequestUpdateEvent {
+        PaymentRequestUpdateEvent {
+            event: Event::new_inherited(),
+            wait_for_update: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+    ) -> DomRoot<PaymentRequestUpdateEvent> {
+        let ev = reflect_dom_object(
+            Box::new(PaymentRequestUpdateEvent::new_inherited()),
+            global,
+            PaymentRequestUpdateEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &PaymentRequestUpdateEventInit,
+    ) -> Fallible<DomRoot<PaymentRequestUpdateEvent>> {
+        Ok(PaymentRequestUpdateEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+        ))
+    }
+
+    pub fn wait_for_update(&self) -> bool {
+        self.wait_for_update.get()
+    }
+}
+
+impl PaymentRequestUpdateEventMethods for PaymentRequestUpdateEvent {
+    // https://w3c.github.io/payment-request/#dom-paymentrequestupdateevent-updatewith
+    fn UpdateWith(&self, details_promise: &Promise) -> ErrorResult {
+        // Steps 1-2.
+        if !self.event.IsTrusted() || self.wait_for_update.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 3-4.
+        let target = self.event.GetTarget();
+        let request = match target
+            .as_ref()
+            .and_then(|target| target.downcast::<PaymentRequest>())
+        {
+            Some(request) if request.is_interactive() && !request.is_updating() => request,
+            _ => return Err(Error::InvalidState),
+        };
+
+        // Steps 5-8.
+        self.event.StopPropagation();
+        self.event.StopImmediatePropagation();
+        self.wait_for_update.set(true);
+        request.update_details(details_promise);
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
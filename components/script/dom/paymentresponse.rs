@@ -0,0 +1,180 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::PaymentResponseBinding::{
+    self, PaymentComplete, PaymentResponseMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::paymentaddress::PaymentAddress;
+use crate::dom::promise::Promise;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use embedder_traits::payment::{self, PaymentResponseData};
+use embedder_traits::EmbedderMsg;
+use js::jsapi::{Heap, JSObject, JS_ClearPendingException, JS_NewPlainObject};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::JS_ParseJSON;
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+// https://w3c.github.io/payment-request/#paymentresponse-interface
+#[dom_struct]
+pub struct PaymentResponse {
+    eventtarget: EventTarget,
+    request_id: DOMString,
+    method_name: DOMString,
+    #[ignore_malloc_size_of = "mozjs"]
+    details: Heap<*mut JSObject>,
+    shipping_address: Option<Dom<PaymentAddress>>,
+    shipping_option: Option<DOMString>,
+    payer_name: Option<DOMString>,
+    payer_email: Option<DOMString>,
+    payer_phone: Option<DOMString>,
+    /// Whether `complete()` was called.
+    complete: Cell<bool>,
+}
+
+impl PaymentResponse {
+    fn new_inherited(
+        request_id: DOMString,
+        data: &PaymentResponseData,
+        shipping_address: Option<&PaymentAddress>,
+    ) -> PaymentResponse {
+        PaymentResponse {
+            eventtarget: EventTarget::new_inherited(),
+            request_id,
+            method_name: DOMString::from(&*data.method_name),
+            details: Heap::default(),
+            shipping_address: shipping_address.map(Dom::from_ref),
+            shipping_option: data.shipping_option.as_ref().map(|o| DOMString::from(&**o)),
+            payer_name: data
+                .payer_name
+                .as_ref()
+                .map(|name| DOMString::from(&**name)),
+            payer_email: data
+                .payer_email
+                .as_ref()
+                .map(|email| DOMString::from(&**email)),
+            payer_phone: data
+                .payer_phone
+                .as_ref()
+                .map(|phone| DOMString::from(&**phone)),
+            complete: Cell::new(false),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        request_id: DOMString,
+        data: &PaymentResponseData,
+        shipping_address: Option<&PaymentAddress>,
+    ) -> DomRoot<PaymentResponse> {
+        let response = reflect_dom_object(
+            Box::new(PaymentResponse::new_inherited(
+                request_id,
+                data,
+                shipping_address,
+            )),
+            global,
+            PaymentResponseBinding::Wrap,
+        );
+        let cx = global.get_cx();
+        let json: Vec<u16> = data.details.encode_utf16().collect();
+        rooted!(in(*cx) let mut details = UndefinedValue());
+        unsafe {
+            // The details are an object, even when the payment method
+            // returned something else.
+            let parsed = JS_ParseJSON(*cx, json.as_ptr(), json.len() as u32, details.handle_mut());
+            if !parsed {
+                JS_ClearPendingException(*cx);
+            }
+            if parsed && details.is_object() {
+                response.details.set(details.to_object());
+            } else {
+                response.details.set(JS_NewPlainObject(*cx));
+            }
+        }
+        response
+    }
+}
+
+impl PaymentResponseMethods for PaymentResponse {
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-requestid
+    fn RequestId(&self) -> DOMString {
+        self.request_id.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-methodname
+    fn MethodName(&self) -> DOMString {
+        self.method_name.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-details
+    #[allow(unsafe_code)]
+    fn Details(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.details.get()) }
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-shippingaddress
+    fn GetShippingAddress(&self) -> Option<DomRoot<PaymentAddress>> {
+        self.shipping_address
+            .as_ref()
+            .map(|address| DomRoot::from_ref(&**address))
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-shippingoption
+    fn GetShippingOption(&self) -> Option<DOMString> {
+        self.shipping_option.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-payername
+    fn GetPayerName(&self) -> Option<DOMString> {
+        self.payer_name.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-payeremail
+    fn GetPayerEmail(&self) -> Option<DOMString> {
+        self.payer_email.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-payerphone
+    fn GetPayerPhone(&self) -> Option<DOMString> {
+        self.payer_phone.clone()
+    }
+
+    // https://w3c.github.io/payment-request/#dom-paymentresponse-complete
+    fn Complete(&self, result: PaymentComplete, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        // Step 1.
+        if self.complete.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Steps 3-5.
+        self.complete.set(true);
+        let result = match result {
+            PaymentComplete::Fail => payment::PaymentComplete::Fail,
+            PaymentComplete::Success => payment::PaymentComplete::Success,
+            PaymentComplete::Unknown => payment::PaymentComplete::Unknown,
+        };
+        global
+            .as_window()
+            .send_to_embedder(EmbedderMsg::CompletePayment(
+                self.request_id.to_string(),
+                result,
+            ));
+        promise.resolve_native(&());
+        promise
+    }
+}
@@ -3,31 +3,38 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::KeyboardEventBinding::KeyboardEventMethods;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceEntryList as DOMPerformanceEntryList;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
     DOMHighResTimeStamp, PerformanceMethods,
 };
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding::PointerEventMethods;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceeventtiming::{PerformanceEventTiming, MIN_DURATION_THRESHOLD};
 use crate::dom::performancemark::PerformanceMark;
 use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigation::PerformanceNavigation;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
 use crate::dom::performanceobserver::PerformanceObserver as DOMPerformanceObserver;
+use crate::dom::pointerevent::PointerEvent;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use metrics::ToMs;
+use servo_rand::random;
 use std::cell::Cell;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "navigationStart",
@@ -133,6 +140,8 @@ impl IntoIterator for PerformanceEntryList {
 struct PerformanceObserver {
     observer: DomRoot<DOMPerformanceObserver>,
     entry_types: Vec<DOMString>,
+    /// https://w3c.github.io/event-timing/#sec-modifications-perf-timeline
+    duration_threshold: f64,
 }
 
 #[dom_struct]
@@ -142,6 +151,9 @@ pub struct Performance {
     observers: DomRefCell<Vec<PerformanceObserver>>,
     pending_notification_observers_task: Cell<bool>,
     navigation_start_precise: u64,
+    /// https://w3c.github.io/hr-time/#dfn-time-origin
+    /// The time origin, in milliseconds relative to the Unix epoch.
+    time_origin: f64,
     /// https://w3c.github.io/performance-timeline/#dfn-maxbuffersize
     /// The max-size of the buffer, set to 0 once the pipeline exits.
     /// TODO: have one max-size per entry type.
@@ -149,6 +161,23 @@ pub struct Performance {
     resource_timing_buffer_current_size: Cell<usize>,
     resource_timing_buffer_pending_full_event: Cell<bool>,
     resource_timing_secondary_entries: DomRefCell<VecDeque<DomRoot<PerformanceEntry>>>,
+    /// https://w3c.github.io/event-timing/#pending-event-entries
+    /// The entries of the events dispatched since the last rendering update.
+    pending_event_entries: DomRefCell<Vec<Dom<PerformanceEventTiming>>>,
+    /// The entries of the events whose effects wait for a frame to be painted.
+    event_entries_awaiting_frame: DomRefCell<Vec<Dom<PerformanceEventTiming>>>,
+    /// https://w3c.github.io/event-timing/#pending-key-downs
+    pending_key_downs: DomRefCell<HashMap<u32, Dom<PerformanceEventTiming>>>,
+    /// https://w3c.github.io/event-timing/#pending-pointer-downs
+    pending_pointer_downs: DomRefCell<HashMap<i32, Dom<PerformanceEventTiming>>>,
+    /// The interaction of the last pointerup, that the click following it shares.
+    last_pointer_interaction_id: Cell<u64>,
+    /// https://w3c.github.io/event-timing/#user-interaction-value
+    user_interaction_value: Cell<u64>,
+    /// https://w3c.github.io/event-timing/#window-interactioncount
+    interaction_count: Cell<u64>,
+    /// https://w3c.github.io/event-timing/#has-dispatched-input-event
+    has_dispatched_input_event: Cell<bool>,
 }
 
 impl Performance {
@@ -159,10 +188,20 @@ impl Performance {
             observers: DomRefCell::new(Vec::new()),
             pending_notification_observers_task: Cell::new(false),
             navigation_start_precise,
+            time_origin: time_origin_from_precise(navigation_start_precise),
             resource_timing_buffer_size_limit: Cell::new(250),
             resource_timing_buffer_current_size: Cell::new(0),
             resource_timing_buffer_pending_full_event: Cell::new(false),
             resource_timing_secondary_entries: DomRefCell::new(VecDeque::new()),
+            pending_event_entries: DomRefCell::new(Vec::new()),
+            event_entries_awaiting_frame: DomRefCell::new(Vec::new()),
+            pending_key_downs: DomRefCell::new(HashMap::new()),
+            pending_pointer_downs: DomRefCell::new(HashMap::new()),
+            last_pointer_interaction_id: Cell::new(0),
+            // Interaction ids start from a random value between 100 and 10000.
+            user_interaction_value: Cell::new(100 + random::<u64>() % 9901),
+            interaction_count: Cell::new(0),
+            has_dispatched_input_event: Cell::new(false),
         }
     }
 
@@ -190,6 +229,7 @@ impl Performance {
         observer: &DOMPerformanceObserver,
         entry_types: Vec<DOMString>,
         buffered: bool,
+        duration_threshold: f64,
     ) {
         if buffered {
             let buffer = self.buffer.borrow();
@@ -205,11 +245,15 @@ impl Performance {
         match observers.iter().position(|o| *o.observer == *observer) {
             // If the observer is already in the list, we only update the observed
             // entry types.
-            Some(p) => observers[p].entry_types = entry_types,
+            Some(p) => {
+                observers[p].entry_types = entry_types;
+                observers[p].duration_threshold = duration_threshold;
+            },
             // Otherwise, we create and insert the new PerformanceObserver.
             None => observers.push(PerformanceObserver {
                 observer: DomRoot::from_ref(observer),
                 entry_types,
+                duration_threshold,
             }),
         };
     }
@@ -248,6 +292,7 @@ impl Performance {
             .borrow()
             .iter()
             .filter(|o| o.entry_types.contains(entry.entry_type()))
+            .filter(|o| entry.entry_type() != "event" || entry.duration() >= o.duration_threshold)
         {
             o.observer.queue_entry(entry);
         }
@@ -310,6 +355,188 @@ impl Performance {
         (time::precise_time_ns() - self.navigation_start_precise).to_ms()
     }
 
+    /// Converts a timestamp read from the monotonic clock, in nanoseconds, into
+    /// milliseconds relative to the time origin. Zero stands for an event that
+    /// didn't happen, and stays zero.
+    pub fn time_since_origin(&self, precise_time_ns: u64) -> f64 {
+        if precise_time_ns == 0 {
+            return 0.;
+        }
+        precise_time_ns
+            .saturating_sub(self.navigation_start_precise)
+            .to_ms()
+    }
+
+    /// Adds the entry of an event whose dispatch just ended to the ones waiting
+    /// for the next rendering update, once its interaction is known,
+    /// <https://w3c.github.io/event-timing/#finalize-event-timing>
+    pub fn add_pending_event_entry(&self, entry: &PerformanceEventTiming, event: &Event) {
+        self.compute_interaction_id(entry, event);
+        self.pending_event_entries
+            .borrow_mut()
+            .push(Dom::from_ref(entry));
+    }
+
+    /// Makes the pending event entries wait for the next frame to be painted.
+    /// Returns whether there were any.
+    pub fn observe_event_presentation(&self) -> bool {
+        let mut pending_entries = self.pending_event_entries.borrow_mut();
+        if pending_entries.is_empty() {
+            return false;
+        }
+        self.event_entries_awaiting_frame
+            .borrow_mut()
+            .extend(pending_entries.drain(..));
+        true
+    }
+
+    /// Reports the pending event entries whose effects no frame is going to
+    /// present, at the end of the rendering update.
+    pub fn report_pending_event_entries(&self) {
+        let entries = take_event_entries(&self.pending_event_entries);
+        self.report_event_entries(entries, time::precise_time_ns());
+    }
+
+    /// Reports the event entries that waited for the frame painted at `paint_time`.
+    pub fn report_presented_event_entries(&self, paint_time: u64) {
+        let entries = take_event_entries(&self.event_entries_awaiting_frame);
+        self.report_event_entries(entries, paint_time);
+    }
+
+    /// <https://w3c.github.io/event-timing/#dispatch-pending>
+    fn report_event_entries(
+        &self,
+        entries: Vec<DomRoot<PerformanceEventTiming>>,
+        presentation_time: u64,
+    ) {
+        let presentation_time = self.time_since_origin(presentation_time);
+        for entry in entries {
+            entry.set_presentation_time(presentation_time);
+
+            if !self.has_dispatched_input_event.get() && entry.is_first_input_candidate() {
+                self.has_dispatched_input_event.set(true);
+                let first_input = PerformanceEventTiming::new_first_input(&self.global(), &entry);
+                self.queue_entry(first_input.upcast::<PerformanceEntry>(), true);
+            }
+
+            if !entry.awaiting_interaction() {
+                self.queue_event_entry(&entry);
+            }
+        }
+    }
+
+    fn queue_event_entry(&self, entry: &PerformanceEventTiming) {
+        let entry = entry.upcast::<PerformanceEntry>();
+        if entry.duration() >= MIN_DURATION_THRESHOLD {
+            // Event entries are only available to observers, not from the timeline.
+            self.queue_entry(entry, false);
+        }
+    }
+
+    /// <https://w3c.github.io/event-timing/#sec-computing-interactionid>
+    fn compute_interaction_id(&self, entry: &PerformanceEventTiming, event: &Event) {
+        match &*event.type_() {
+            "keydown" => {
+                let event = match event.downcast::<KeyboardEvent>() {
+                    Some(event) => event,
+                    None => return,
+                };
+                // The keys pressed during a composition are part of the
+                // interaction of the composition events.
+                if event.IsComposing() {
+                    return;
+                }
+                entry.set_awaiting_interaction(true);
+                let previous_keydown = self
+                    .pending_key_downs
+                    .borrow_mut()
+                    .insert(event.KeyCode(), Dom::from_ref(entry));
+                // A key that is held down repeats its keydown, each of which is
+                // an interaction of its own.
+                if let Some(keydown) = previous_keydown {
+                    let interaction_id = self.new_interaction_id();
+                    self.end_interaction(&keydown, interaction_id);
+                }
+            },
+            "keyup" => {
+                let event = match event.downcast::<KeyboardEvent>() {
+                    Some(event) => event,
+                    None => return,
+                };
+                let keydown = self.pending_key_downs.borrow_mut().remove(&event.KeyCode());
+                if let Some(keydown) = keydown {
+                    let interaction_id = self.new_interaction_id();
+                    self.end_interaction(&keydown, interaction_id);
+                    entry.set_interaction_id(interaction_id);
+                }
+            },
+            "pointerdown" => {
+                let event = match event.downcast::<PointerEvent>() {
+                    Some(event) => event,
+                    None => return,
+                };
+                entry.set_awaiting_interaction(true);
+                let previous_pointerdown = self
+                    .pending_pointer_downs
+                    .borrow_mut()
+                    .insert(event.PointerId(), Dom::from_ref(entry));
+                if let Some(pointerdown) = previous_pointerdown {
+                    self.end_interaction(&pointerdown, 0);
+                }
+            },
+            type_ @ "pointercancel" | type_ @ "pointerup" => {
+                let event = match event.downcast::<PointerEvent>() {
+                    Some(event) => event,
+                    None => return,
+                };
+                let pointerdown = self
+                    .pending_pointer_downs
+                    .borrow_mut()
+                    .remove(&event.PointerId());
+                let pointerdown = match pointerdown {
+                    Some(pointerdown) => pointerdown,
+                    None => return,
+                };
+                // A canceled pointer turned into a scroll or another gesture of
+                // the user agent, which isn't an interaction with the page.
+                if type_ == "pointercancel" {
+                    return self.end_interaction(&pointerdown, 0);
+                }
+                let interaction_id = self.new_interaction_id();
+                self.end_interaction(&pointerdown, interaction_id);
+                entry.set_interaction_id(interaction_id);
+                self.last_pointer_interaction_id.set(interaction_id);
+            },
+            "click" => {
+                // The click that follows a pointerup is part of its interaction.
+                let interaction_id = match self.last_pointer_interaction_id.replace(0) {
+                    0 => self.new_interaction_id(),
+                    interaction_id => interaction_id,
+                };
+                entry.set_interaction_id(interaction_id);
+            },
+            _ => {},
+        }
+    }
+
+    /// Gives its interaction id to an entry that waited for the end of its
+    /// interaction, and queues it if it's already been presented.
+    fn end_interaction(&self, entry: &PerformanceEventTiming, interaction_id: u64) {
+        entry.set_interaction_id(interaction_id);
+        entry.set_awaiting_interaction(false);
+        if entry.presented() {
+            self.queue_event_entry(entry);
+        }
+    }
+
+    /// <https://w3c.github.io/event-timing/#increase-interaction-count>
+    fn new_interaction_id(&self) -> u64 {
+        self.user_interaction_value
+            .set(self.user_interaction_value.get() + 7);
+        self.interaction_count.set(self.interaction_count.get() + 1);
+        self.user_interaction_value.get()
+    }
+
     fn can_add_resource_timing_entry(&self) -> bool {
         self.resource_timing_buffer_current_size.get() <=
             self.resource_timing_buffer_size_limit.get()
@@ -372,6 +599,25 @@ impl Performance {
     }
 }
 
+fn take_event_entries(
+    entries: &DomRefCell<Vec<Dom<PerformanceEventTiming>>>,
+) -> Vec<DomRoot<PerformanceEventTiming>> {
+    entries
+        .borrow_mut()
+        .drain(..)
+        .map(|entry| DomRoot::from_ref(&*entry))
+        .collect()
+}
+
+/// Converts a time origin from the monotonic clock used by `navigation_start_precise`
+/// into milliseconds relative to the Unix epoch, so that the time origins of globals
+/// created at different times, on different threads, can be compared.
+fn time_origin_from_precise(navigation_start_precise: u64) -> f64 {
+    let now = time::get_time();
+    let now_ms = now.sec as f64 * 1000. + now.nsec as f64 / 1_000_000.;
+    now_ms - (time::precise_time_ns() - navigation_start_precise).to_ms()
+}
+
 impl PerformanceMethods for Performance {
     // FIXME(avada): this should be deprecated in the future, but some sites still use it
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/NavigationTiming/Overview.html#performance-timing-attribute
@@ -387,6 +633,11 @@ impl PerformanceMethods for Performance {
         unreachable!("Are we trying to expose Performance.timing in workers?");
     }
 
+    // https://w3c.github.io/event-timing/#dom-performance-interactioncount
+    fn InteractionCount(&self) -> u64 {
+        self.interaction_count.get()
+    }
+
     // https://w3c.github.io/navigation-timing/#dom-performance-navigation
     fn Navigation(&self) -> DomRoot<PerformanceNavigation> {
         PerformanceNavigation::new(&self.global())
@@ -399,7 +650,7 @@ impl PerformanceMethods for Performance {
 
     // https://www.w3.org/TR/hr-time-2/#dom-performance-timeorigin
     fn TimeOrigin(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.navigation_start_precise as f64)
+        Finite::wrap(self.time_origin)
     }
 
     // https://www.w3.org/TR/performance-timeline-2/#dom-performance-getentries
@@ -0,0 +1,258 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceEventTimingBinding::{
+    self, PerformanceEventTimingMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// The duration under which event entries are not reported to observers that
+/// don't ask for another one.
+pub const DEFAULT_DURATION_THRESHOLD: f64 = 104.;
+
+/// The duration under which event entries are never reported.
+pub const MIN_DURATION_THRESHOLD: f64 = 16.;
+
+/// The types of the events whose dispatch is measured,
+/// <https://w3c.github.io/event-timing/#sec-events-exposed>
+const MEASURED_EVENT_TYPES: &'static [&'static str] = &[
+    "auxclick",
+    "beforeinput",
+    "click",
+    "compositionend",
+    "compositionstart",
+    "compositionupdate",
+    "contextmenu",
+    "dblclick",
+    "dragend",
+    "dragenter",
+    "dragleave",
+    "dragover",
+    "dragstart",
+    "drop",
+    "gotpointercapture",
+    "input",
+    "keydown",
+    "keypress",
+    "keyup",
+    "lostpointercapture",
+    "mousedown",
+    "mouseenter",
+    "mouseleave",
+    "mouseout",
+    "mouseover",
+    "mouseup",
+    "pointercancel",
+    "pointerdown",
+    "pointerenter",
+    "pointerleave",
+    "pointerout",
+    "pointerover",
+    "pointerup",
+    "touchcancel",
+    "touchend",
+    "touchstart",
+];
+
+/// The types of the events that can be the first input of a page. A pointerdown
+/// only is one when a pointerup follows it, but the mousedown fired along with
+/// it is reported instead.
+const FIRST_INPUT_TYPES: &'static [&'static str] = &["keydown", "mousedown", "click"];
+
+// https://w3c.github.io/event-timing/#sec-performance-event-timing
+#[dom_struct]
+pub struct PerformanceEventTiming {
+    entry: PerformanceEntry,
+    processing_start: f64,
+    processing_end: Cell<f64>,
+    cancelable: bool,
+    target: Option<Dom<Node>>,
+    interaction_id: Cell<u64>,
+    /// Whether the entry waits for the end of its interaction before being queued.
+    awaiting_interaction: Cell<bool>,
+    /// Whether the duration of the entry is known.
+    presented: Cell<bool>,
+}
+
+impl PerformanceEventTiming {
+    fn new_inherited(
+        name: DOMString,
+        entry_type: DOMString,
+        start_time: f64,
+        processing_start: f64,
+        cancelable: bool,
+        target: Option<&Node>,
+    ) -> PerformanceEventTiming {
+        PerformanceEventTiming {
+            entry: PerformanceEntry::new_inherited(name, entry_type, start_time, 0.),
+            processing_start,
+            processing_end: Cell::new(processing_start),
+            cancelable,
+            target: target.map(Dom::from_ref),
+            interaction_id: Cell::new(0),
+            awaiting_interaction: Cell::new(false),
+            presented: Cell::new(false),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        start_time: f64,
+        processing_start: f64,
+        cancelable: bool,
+        target: Option<&Node>,
+    ) -> DomRoot<PerformanceEventTiming> {
+        reflect_dom_object(
+            Box::new(PerformanceEventTiming::new_inherited(
+                name,
+                DOMString::from("event"),
+                start_time,
+                processing_start,
+                cancelable,
+                target,
+            )),
+            global,
+            PerformanceEventTimingBinding::Wrap,
+        )
+    }
+
+    /// Creates the `first-input` entry of the page from the entry of its first
+    /// input event.
+    #[allow(unrooted_must_root)]
+    pub fn new_first_input(
+        global: &GlobalScope,
+        entry: &PerformanceEventTiming,
+    ) -> DomRoot<PerformanceEventTiming> {
+        let first_input = PerformanceEventTiming::new_inherited(
+            entry.entry.name().clone(),
+            DOMString::from("first-input"),
+            entry.entry.start_time(),
+            entry.processing_start,
+            entry.cancelable,
+            entry.target.as_ref().map(|target| &**target),
+        );
+        first_input.entry.set_duration(entry.entry.duration());
+        first_input.processing_end.set(entry.processing_end.get());
+        first_input.interaction_id.set(entry.interaction_id.get());
+        reflect_dom_object(
+            Box::new(first_input),
+            global,
+            PerformanceEventTimingBinding::Wrap,
+        )
+    }
+
+    /// Starts measuring the dispatch of `event` to `target` if it's a trusted
+    /// input event dispatched to a node of a window,
+    /// <https://w3c.github.io/event-timing/#initialize-event-timing>
+    pub fn start_measuring(
+        event: &Event,
+        target: &EventTarget,
+    ) -> Option<DomRoot<PerformanceEventTiming>> {
+        if !event.IsTrusted() || !MEASURED_EVENT_TYPES.contains(&&*event.type_()) {
+            return None;
+        }
+        let node = target.downcast::<Node>()?;
+        let global = target.global();
+        let window = global.downcast::<Window>()?;
+        let performance = window.Performance();
+        Some(PerformanceEventTiming::new(
+            &global,
+            DOMString::from(&*event.type_()),
+            performance.time_since_origin(event.precise_time_ns()),
+            *performance.Now(),
+            event.Cancelable(),
+            Some(node),
+        ))
+    }
+
+    /// Records the end of the dispatch of `event`, and waits for its effects to
+    /// be presented,
+    /// <https://w3c.github.io/event-timing/#finalize-event-timing>
+    pub fn finish_measuring(&self, event: &Event) {
+        let global = self.global();
+        let performance = match global.downcast::<Window>() {
+            Some(window) => window.Performance(),
+            None => return,
+        };
+        self.processing_end.set(*performance.Now());
+        performance.add_pending_event_entry(self, event);
+    }
+
+    /// Whether the entry can be the `first-input` entry of the page.
+    pub fn is_first_input_candidate(&self) -> bool {
+        FIRST_INPUT_TYPES.contains(&&**self.entry.name())
+    }
+
+    pub fn set_interaction_id(&self, interaction_id: u64) {
+        self.interaction_id.set(interaction_id);
+    }
+
+    pub fn awaiting_interaction(&self) -> bool {
+        self.awaiting_interaction.get()
+    }
+
+    pub fn set_awaiting_interaction(&self, awaiting_interaction: bool) {
+        self.awaiting_interaction.set(awaiting_interaction);
+    }
+
+    pub fn presented(&self) -> bool {
+        self.presented.get()
+    }
+
+    /// Sets the duration of the entry from the time at which the effects of the
+    /// event were presented, rounded to 8ms to limit the precision of the timing.
+    pub fn set_presentation_time(&self, presentation_time: f64) {
+        let duration = (presentation_time - self.entry.start_time()).max(0.);
+        self.entry.set_duration((duration / 8.).round() * 8.);
+        self.presented.set(true);
+    }
+}
+
+impl PerformanceEventTimingMethods for PerformanceEventTiming {
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingstart
+    fn ProcessingStart(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.processing_start)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingend
+    fn ProcessingEnd(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.processing_end.get())
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-cancelable
+    fn Cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-target
+    fn GetTarget(&self) -> Option<DomRoot<Node>> {
+        self.target
+            .as_ref()
+            .filter(|target| target.is_connected())
+            .map(|target| DomRoot::from_ref(&**target))
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-interactionid
+    fn InteractionId(&self) -> u64 {
+        self.interaction_id.get()
+    }
+}
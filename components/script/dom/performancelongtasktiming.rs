@@ -0,0 +1,149 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding::{
+    self, PerformanceLongTaskTimingMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use msg::constellation_msg::BrowsingContextId;
+
+// https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming
+#[dom_struct]
+pub struct PerformanceLongTaskTiming {
+    entry: PerformanceEntry,
+    #[ignore_malloc_size_of = "mozjs"]
+    attribution: Heap<JSVal>,
+}
+
+impl PerformanceLongTaskTiming {
+    fn new_inherited(name: DOMString, start_time: f64, duration: f64) -> PerformanceLongTaskTiming {
+        PerformanceLongTaskTiming {
+            entry: PerformanceEntry::new_inherited(
+                name,
+                DOMString::from("longtask"),
+                start_time,
+                duration,
+            ),
+            attribution: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> DomRoot<PerformanceLongTaskTiming> {
+        let entry = reflect_dom_object(
+            Box::new(PerformanceLongTaskTiming::new_inherited(
+                name, start_time, duration,
+            )),
+            global,
+            PerformanceLongTaskTimingBinding::Wrap,
+        );
+        entry.attribution.set(to_frozen_array(
+            &[DomRoot::from_ref(attribution)],
+            global.get_cx(),
+        ));
+        entry
+    }
+
+    /// Queues an entry for a task of this event loop that ran from `start` to
+    /// `end`, on behalf of the `culprit` document if it's known, to the
+    /// observers of `destination`,
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    pub fn report(destination: &Document, culprit: Option<&Document>, start: u64, end: u64) {
+        let destination_contexts = browsing_context_ancestors(destination);
+        let destination_id = match destination_contexts.first() {
+            Some(id) => *id,
+            None => return,
+        };
+
+        let mut name = "unknown";
+        let mut container = None;
+        if let Some(culprit) = culprit {
+            let culprit_contexts = browsing_context_ancestors(culprit);
+            let same_origin = culprit.origin().same_origin(destination.origin());
+            if culprit_contexts.first() == Some(&destination_id) {
+                name = "self";
+            } else if let Some(position) =
+                culprit_contexts.iter().position(|id| *id == destination_id)
+            {
+                name = if same_origin {
+                    "same-origin-descendant"
+                } else {
+                    "cross-origin-descendant"
+                };
+                // The frame of the destination document that contains the culprit.
+                container = destination.find_iframe(culprit_contexts[position - 1]);
+            } else if culprit_contexts
+                .first()
+                .map_or(false, |id| destination_contexts.contains(id))
+            {
+                name = if same_origin {
+                    "same-origin-ancestor"
+                } else {
+                    "cross-origin-ancestor"
+                };
+            } else if same_origin {
+                name = "same-origin";
+            } else {
+                name = "cross-origin-unreachable";
+            }
+        }
+
+        let window = destination.window();
+        let global = window.upcast::<GlobalScope>();
+        let performance = window.Performance();
+        let attribution = TaskAttributionTiming::new(
+            global,
+            container.as_ref().map(|iframe| iframe.upcast::<Element>()),
+        );
+        let start_time = performance.time_since_origin(start);
+        let entry = PerformanceLongTaskTiming::new(
+            global,
+            DOMString::from(name),
+            start_time,
+            performance.time_since_origin(end) - start_time,
+            &attribution,
+        );
+        // Long tasks are only available to observers, not from the timeline.
+        performance.queue_entry(entry.upcast::<PerformanceEntry>(), false);
+    }
+}
+
+/// The browsing context of a document, followed by its ancestors.
+fn browsing_context_ancestors(document: &Document) -> Vec<BrowsingContextId> {
+    let mut ids = vec![];
+    if let Some(window_proxy) = document.browsing_context() {
+        let mut current = Some(&*window_proxy);
+        while let Some(window_proxy) = current {
+            ids.push(window_proxy.browsing_context_id());
+            current = window_proxy.parent();
+        }
+    }
+    ids
+}
+
+impl PerformanceLongTaskTimingMethods for PerformanceLongTaskTiming {
+    // https://w3c.github.io/longtasks/#dom-performancelongtasktiming-attribution
+    fn Attribution(&self, _cx: JSContext) -> JSVal {
+        self.attribution.get()
+    }
+}
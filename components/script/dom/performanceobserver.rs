@@ -16,6 +16,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performance::PerformanceEntryList;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceeventtiming::{DEFAULT_DURATION_THRESHOLD, MIN_DURATION_THRESHOLD};
 use crate::dom::performanceobserverentrylist::PerformanceObserverEntryList;
 use dom_struct::dom_struct;
 use std::rc::Rc;
@@ -28,7 +29,10 @@ const VALID_ENTRY_TYPES: &'static [&'static str] = &[
     "navigation", // Navigation Timing API
     // "frame", //TODO Frame Timing API
     // "server", XXX Server Timing API
-    "paint", // Paint Timing API
+    "paint",       // Paint Timing API
+    "longtask",    // Long Tasks API
+    "event",       // Event Timing API
+    "first-input", // Event Timing API
 ];
 
 #[dom_struct]
@@ -119,10 +123,20 @@ impl PerformanceObserverMethods for PerformanceObserver {
             return Err(Error::Type("entryTypes cannot be empty".to_string()));
         }
 
+        // https://w3c.github.io/event-timing/#sec-modifications-perf-timeline
+        let duration_threshold = options
+            .durationThreshold
+            .map_or(DEFAULT_DURATION_THRESHOLD, |threshold| {
+                (*threshold).max(MIN_DURATION_THRESHOLD)
+            });
+
         // step 3-4-5
-        self.global()
-            .performance()
-            .add_observer(self, entry_types, options.buffered);
+        self.global().performance().add_observer(
+            self,
+            entry_types,
+            options.buffered,
+            duration_threshold,
+        );
 
         Ok(())
     }
@@ -11,6 +11,7 @@ use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::performance::Performance;
 use crate::dom::performanceentry::PerformanceEntry;
 use dom_struct::dom_struct;
 use net_traits::ResourceFetchTiming;
@@ -23,13 +24,14 @@ use servo_url::ServoUrl;
 // TODO Cross origin resources MUST BE INCLUDED as PerformanceResourceTiming objects
 // https://w3c.github.io/resource-timing/#sec-cross-origin-resources
 
-// TODO CSS, Beacon
 #[derive(Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum InitiatorType {
     LocalName(String),
+    Css,
     Navigation,
     XMLHttpRequest,
     Fetch,
+    Beacon,
     Other,
 }
 
@@ -57,83 +59,51 @@ pub struct PerformanceResourceTiming {
 
 // TODO(#21269): next_hop
 // TODO(#21264): worker_start
-// TODO(#21258): fetch_start
-// TODO(#21259): domain_lookup_start
-// TODO(#21260): domain_lookup_end
-// TODO(#21261): connect_start
-// TODO(#21262): connect_end
 impl PerformanceResourceTiming {
+    /// Builds an entry from the timing of a fetch, whose timestamps are made
+    /// relative to the time origin of `performance`.
     pub fn new_inherited(
         url: ServoUrl,
         initiator_type: InitiatorType,
         next_hop: Option<DOMString>,
-        fetch_start: f64,
+        resource_timing: &ResourceFetchTiming,
+        performance: &Performance,
     ) -> PerformanceResourceTiming {
-        let entry_type = if initiator_type == InitiatorType::Navigation {
-            DOMString::from("navigation")
+        let time = |precise_time_ns| performance.time_since_origin(precise_time_ns);
+        // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming
+        // The navigation entry starts at the time origin.
+        let (entry_type, start_time) = if initiator_type == InitiatorType::Navigation {
+            (DOMString::from("navigation"), 0.)
         } else {
-            DOMString::from("resource")
+            (
+                DOMString::from("resource"),
+                time(resource_timing.start_time),
+            )
         };
         PerformanceResourceTiming {
             entry: PerformanceEntry::new_inherited(
                 DOMString::from(url.into_string()),
                 entry_type,
-                0.,
-                0.,
-            ),
-            initiator_type: initiator_type,
-            next_hop: next_hop,
-            worker_start: 0.,
-            redirect_start: 0.,
-            redirect_end: 0.,
-            fetch_start: fetch_start,
-            domain_lookup_end: 0.,
-            domain_lookup_start: 0.,
-            connect_start: 0.,
-            connect_end: 0.,
-            secure_connection_start: 0.,
-            request_start: 0.,
-            response_start: 0.,
-            response_end: 0.,
-            transfer_size: 0,
-            encoded_body_size: 0,
-            decoded_body_size: 0,
-        }
-    }
-
-    //TODO fetch start should be in RFT
-    #[allow(unrooted_must_root)]
-    fn from_resource_timing(
-        url: ServoUrl,
-        initiator_type: InitiatorType,
-        next_hop: Option<DOMString>,
-        resource_timing: &ResourceFetchTiming,
-    ) -> PerformanceResourceTiming {
-        PerformanceResourceTiming {
-            entry: PerformanceEntry::new_inherited(
-                DOMString::from(url.into_string()),
-                DOMString::from("resource"),
-                resource_timing.start_time as f64,
-                resource_timing.response_end as f64 - resource_timing.start_time as f64,
+                start_time,
+                time(resource_timing.response_end) - start_time,
             ),
             initiator_type: initiator_type,
             next_hop: next_hop,
             worker_start: 0.,
-            redirect_start: resource_timing.redirect_start as f64,
-            redirect_end: resource_timing.redirect_end as f64,
-            fetch_start: resource_timing.fetch_start as f64,
-            domain_lookup_start: resource_timing.domain_lookup_start as f64,
-            //TODO (#21260)
-            domain_lookup_end: 0.,
-            connect_start: resource_timing.connect_start as f64,
-            connect_end: resource_timing.connect_end as f64,
-            secure_connection_start: resource_timing.secure_connection_start as f64,
-            request_start: resource_timing.request_start as f64,
-            response_start: resource_timing.response_start as f64,
-            response_end: resource_timing.response_end as f64,
-            transfer_size: 0,
-            encoded_body_size: 0,
-            decoded_body_size: 0,
+            redirect_start: time(resource_timing.redirect_start),
+            redirect_end: time(resource_timing.redirect_end),
+            fetch_start: time(resource_timing.fetch_start),
+            domain_lookup_start: time(resource_timing.domain_lookup_start),
+            domain_lookup_end: time(resource_timing.domain_lookup_end),
+            connect_start: time(resource_timing.connect_start),
+            connect_end: time(resource_timing.connect_end),
+            secure_connection_start: time(resource_timing.secure_connection_start),
+            request_start: time(resource_timing.request_start),
+            response_start: time(resource_timing.response_start),
+            response_end: time(resource_timing.response_end),
+            transfer_size: resource_timing.transfer_size,
+            encoded_body_size: resource_timing.encoded_body_size,
+            decoded_body_size: resource_timing.decoded_body_size,
         }
     }
 
@@ -145,11 +115,12 @@ impl PerformanceResourceTiming {
         resource_timing: &ResourceFetchTiming,
     ) -> DomRoot<PerformanceResourceTiming> {
         reflect_dom_object(
-            Box::new(PerformanceResourceTiming::from_resource_timing(
+            Box::new(PerformanceResourceTiming::new_inherited(
                 url,
                 initiator_type,
                 next_hop,
                 resource_timing,
+                &global.performance(),
             )),
             global,
             PerformanceResourceTimingBinding::Wrap,
@@ -163,9 +134,11 @@ impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
     fn InitiatorType(&self) -> DOMString {
         match self.initiator_type {
             InitiatorType::LocalName(ref n) => DOMString::from(n.clone()),
+            InitiatorType::Css => DOMString::from("css"),
             InitiatorType::Navigation => DOMString::from("navigation"),
             InitiatorType::XMLHttpRequest => DOMString::from("xmlhttprequest"),
             InitiatorType::Fetch => DOMString::from("fetch"),
+            InitiatorType::Beacon => DOMString::from("beacon"),
             InitiatorType::Other => DOMString::from("other"),
         }
     }
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::{AlreadyInCompartment, InCompartment};
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionDescriptor;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionStatusMethods;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
@@ -10,28 +11,31 @@ use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::PermissionsBinding::{self, PermissionsMethods};
 use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bluetooth::Bluetooth;
 use crate::dom::bluetoothpermissionresult::BluetoothPermissionResult;
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
 use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
 use dom_struct::dom_struct;
+use embedder_traits::PermissionName as EmbedderPermissionName;
+use embedder_traits::{EmbedderMsg, PermissionPrompt, PermissionRequest};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
 use js::conversions::ConversionResult;
 use js::jsapi::JSObject;
 use js::jsval::{ObjectValue, UndefinedValue};
+use net_traits::permission_store_thread::PermissionStoreThreadMsg;
+use net_traits::IpcSend;
+use script_traits::ScriptMsg;
 use servo_config::pref;
 use std::rc::Rc;
-#[cfg(target_os = "linux")]
-use tinyfiledialogs::{self, MessageBoxIcon, YesNo};
-
-#[cfg(target_os = "linux")]
-const DIALOG_TITLE: &'static str = "Permission request dialog";
-const NONSECURE_DIALOG_MESSAGE: &'static str = "feature is only safe to use in secure context,\
- but servo can't guarantee\n that the current context is secure. Do you want to proceed and grant permission?";
-const REQUEST_DIALOG_MESSAGE: &'static str = "Do you want to grant permission for";
 
 pub trait PermissionAlgorithm {
     type Descriptor;
@@ -65,12 +69,16 @@ enum Operation {
 #[dom_struct]
 pub struct Permissions {
     reflector_: Reflector,
+    /// The permission status objects returned by `query()` and `request()`,
+    /// which are updated when the state of their permission changes.
+    statuses: DomRefCell<Vec<Dom<PermissionStatus>>>,
 }
 
 impl Permissions {
     pub fn new_inherited() -> Permissions {
         Permissions {
             reflector_: Reflector::new(),
+            statuses: DomRefCell::new(vec![]),
         }
     }
 
@@ -143,12 +151,11 @@ impl Permissions {
 
                     &Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .as_window()
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name.to_string());
+                        store_permission_state(
+                            root_desc.name,
+                            PermissionState::Prompt,
+                            &self.global(),
+                        );
 
                         // (Revoke) Step 4.
                         Bluetooth::permission_revoke(&bluetooth_desc, &result)
@@ -158,30 +165,27 @@ impl Permissions {
             _ => {
                 match &op {
                     &Operation::Request => {
-                        // (Request) Step 6.
+                        // (Request) Steps 6-8, the default algorithm always resolves
+                        // the promise, once the user answered the prompt if any.
                         Permissions::permission_request(cx, &p, &root_desc, &status);
-
-                        // (Request) Step 7. The default algorithm always resolve
-
-                        // (Request) Step 8.
-                        p.resolve_native(&status);
+                        self.track_status(&status);
                     },
                     &Operation::Query => {
                         // (Query) Step 6.
                         Permissions::permission_query(cx, &p, &root_desc, &status);
 
                         // (Query) Step 7.
+                        self.track_status(&status);
                         p.resolve_native(&status);
                     },
 
                     &Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .as_window()
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name.to_string());
+                        store_permission_state(
+                            root_desc.name,
+                            PermissionState::Prompt,
+                            &self.global(),
+                        );
 
                         // (Revoke) Step 4.
                         Permissions::permission_revoke(&root_desc, &status);
@@ -197,6 +201,31 @@ impl Permissions {
             _ => p,
         }
     }
+
+    fn track_status(&self, status: &PermissionStatus) {
+        self.statuses.borrow_mut().push(Dom::from_ref(status));
+    }
+
+    /// Updates the permission status objects of a permission whose state changed,
+    /// firing `change` at those whose state is different.
+    pub fn permission_state_changed(&self, permission_name: EmbedderPermissionName) {
+        let statuses: Vec<DomRoot<PermissionStatus>> = self
+            .statuses
+            .borrow()
+            .iter()
+            .filter(|status| embedder_permission_name(status.get_query()) == permission_name)
+            .map(|status| DomRoot::from_ref(&**status))
+            .collect();
+        let global = self.global();
+        for status in statuses {
+            let state = stored_permission_state(status.get_query(), &global)
+                .unwrap_or_else(|| initial_permission_state(status.get_query()));
+            if status.State() != state {
+                status.set_state(state);
+                status.upcast::<EventTarget>().fire_event(atom!("change"));
+            }
+        }
+    }
 }
 
 impl PermissionsMethods for Permissions {
@@ -259,29 +288,19 @@ impl PermissionAlgorithm for Permissions {
         match status.State() {
             // Step 3.
             PermissionState::Prompt => {
-                let perm_name = status.get_query();
-
-                let globalscope = GlobalScope::current().expect("No current global object");
-
-                // https://w3c.github.io/permissions/#request-permission-to-use (Step 3 - 4)
-                let state = prompt_user(
-                    &format!("{} {} ?", REQUEST_DIALOG_MESSAGE, perm_name.clone()),
-                    globalscope.is_headless(),
-                );
-
-                globalscope
-                    .as_window()
-                    .permission_state_invocation_results()
-                    .borrow_mut()
-                    .insert(perm_name.to_string(), state);
+                let trusted_status = Trusted::new(status);
+                let trusted_promise = TrustedPromise::new(promise.clone());
+                request_permission_to_use(status.get_query(), &status.global(), move |state| {
+                    // Step 4.
+                    let status = trusted_status.root();
+                    status.set_state(state);
+                    trusted_promise.root().resolve_native(&status);
+                });
             },
 
             // Step 2.
-            _ => return,
+            _ => promise.resolve_native(status),
         }
-
-        // Step 4.
-        Permissions::permission_query(cx, promise, descriptor, status);
     }
 
     fn permission_revoke(_descriptor: &PermissionDescriptor, _status: &PermissionStatus) {}
@@ -298,71 +317,173 @@ pub fn get_descriptor_permission_state(
         None => GlobalScope::current().expect("No current global object"),
     };
 
-    // Step 2.
-    // TODO: The `is the environment settings object a non-secure context` check is missing.
-    // The current solution is a workaround with a message box to warn about this,
-    // if the feature is not allowed in non-secure contexcts,
-    // and let the user decide to grant the permission or not.
-    let state = if allowed_in_nonsecure_contexts(&permission_name) {
-        PermissionState::Prompt
+    // Step 3, the answers of the user are kept in the permission store of the origin.
+    if let Some(state) = stored_permission_state(permission_name, &settings) {
+        return state;
+    }
+
+    // Steps 2 and 4.
+    // TODO: The `is the environment settings object a non-secure context` check is missing,
+    // the user is warned about it when prompted instead, see `request_permission_to_use`.
+    initial_permission_state(permission_name)
+}
+
+/// <https://w3c.github.io/permissions/#request-permission-to-use>
+///
+/// The prompt does not block the script thread, `callback` is run in a task
+/// of the DOM manipulation task source once the state of the permission is known.
+pub fn request_permission_to_use<F>(
+    permission_name: PermissionName,
+    global: &GlobalScope,
+    callback: F,
+) where
+    F: FnOnce(PermissionState) + Send + 'static,
+{
+    let task_source = global.dom_manipulation_task_source();
+    let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+
+    // Steps 1-2.
+    let current_state = get_descriptor_permission_state(permission_name, Some(global));
+    if current_state != PermissionState::Prompt {
+        let _ = task_source.queue_with_canceller(
+            task!(permission_state_known: move || {
+                callback(current_state);
+            }),
+            &canceller,
+        );
+        return;
+    }
+
+    // Step 3. Features that are not allowed in non-secure contexts warn the user,
+    // as whether the context is secure is not checked.
+    let name = embedder_permission_name(permission_name);
+    let prompt = if allowed_in_nonsecure_contexts(&permission_name) {
+        PermissionPrompt::Request(name)
     } else {
-        if pref!(dom.permissions.testing.allowed_in_nonsecure_contexts) {
-            PermissionState::Granted
-        } else {
-            settings
-                .as_window()
-                .permission_state_invocation_results()
-                .borrow_mut()
-                .remove(&permission_name.to_string());
-
-            prompt_user(
-                &format!("The {} {}", permission_name, NONSECURE_DIALOG_MESSAGE),
-                settings.is_headless(),
-            )
-        }
+        PermissionPrompt::Insecure(name)
     };
-
-    // Step 3.
-    if let Some(prev_result) = settings
-        .as_window()
-        .permission_state_invocation_results()
-        .borrow()
-        .get(&permission_name.to_string())
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    let msg = EmbedderMsg::PromptPermission(prompt, sender);
+    if let Err(e) = global
+        .script_to_constellation_chan()
+        .send(ScriptMsg::ForwardToEmbedder(msg))
     {
-        return prev_result.clone();
+        // The permission is denied if the embedder cannot be reached.
+        warn!("Failed to send permission prompt to the embedder: {:?}", e);
+        let _ = task_source.queue_with_canceller(
+            task!(permission_prompt_failed: move || {
+                callback(PermissionState::Denied);
+            }),
+            &canceller,
+        );
+        return;
     }
 
-    // Store the invocation result
-    settings
-        .as_window()
-        .permission_state_invocation_results()
-        .borrow_mut()
-        .insert(permission_name.to_string(), state);
-
-    // Step 4.
-    state
+    let mut pending = Some((callback, Trusted::new(global)));
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            let (callback, global) = match pending.take() {
+                Some(pending) => pending,
+                None => return,
+            };
+            let state = match message.to() {
+                Ok(PermissionRequest::Granted) => PermissionState::Granted,
+                Ok(PermissionRequest::Denied) => PermissionState::Denied,
+                Err(e) => {
+                    warn!(
+                        "Failed to receive permission state from the embedder: {:?}",
+                        e
+                    );
+                    PermissionState::Denied
+                },
+            };
+            let _ = task_source.queue_with_canceller(
+                task!(permission_prompt_answered: move || {
+                    // Step 4.
+                    store_permission_state(permission_name, state, &global.root());
+
+                    // Step 5.
+                    callback(state);
+                }),
+                &canceller,
+            );
+        }),
+    );
 }
 
-#[cfg(target_os = "linux")]
-fn prompt_user(message: &str, headless: bool) -> PermissionState {
-    if headless {
-        return PermissionState::Denied;
-    }
-    match tinyfiledialogs::message_box_yes_no(
-        DIALOG_TITLE,
-        message,
-        MessageBoxIcon::Question,
-        YesNo::No,
-    ) {
-        YesNo::Yes => PermissionState::Granted,
-        YesNo::No => PermissionState::Denied,
+/// The state of a permission that the user has not been prompted for.
+fn initial_permission_state(permission_name: PermissionName) -> PermissionState {
+    if !allowed_in_nonsecure_contexts(&permission_name) &&
+        pref!(dom.permissions.testing.allowed_in_nonsecure_contexts)
+    {
+        PermissionState::Granted
+    } else {
+        PermissionState::Prompt
     }
 }
 
-#[cfg(not(target_os = "linux"))]
-fn prompt_user(_message: &str, _headless: bool) -> PermissionState {
-    // TODO popup only supported on linux
-    PermissionState::Denied
+/// The answer of the user for a permission of the origin of the global, if any,
+/// <https://w3c.github.io/permissions/#permission-store>
+fn stored_permission_state(
+    permission_name: PermissionName,
+    global: &GlobalScope,
+) -> Option<PermissionState> {
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    global
+        .resource_threads()
+        .send(PermissionStoreThreadMsg::Get(
+            sender,
+            global.origin().immutable().clone(),
+            embedder_permission_name(permission_name),
+        ))
+        .unwrap();
+    receiver.recv().unwrap().map(|request| match request {
+        PermissionRequest::Granted => PermissionState::Granted,
+        PermissionRequest::Denied => PermissionState::Denied,
+    })
+}
+
+/// Stores the answer of the user for a permission of the origin of the global,
+/// forgetting it if the state is `prompt`, and notifies the same-origin documents.
+fn store_permission_state(
+    permission_name: PermissionName,
+    state: PermissionState,
+    global: &GlobalScope,
+) {
+    let origin = global.origin().immutable().clone();
+    let name = embedder_permission_name(permission_name);
+    let msg = match state {
+        PermissionState::Granted => {
+            PermissionStoreThreadMsg::Set(origin.clone(), name, PermissionRequest::Granted)
+        },
+        PermissionState::Denied => {
+            PermissionStoreThreadMsg::Set(origin.clone(), name, PermissionRequest::Denied)
+        },
+        PermissionState::Prompt => PermissionStoreThreadMsg::Remove(origin.clone(), name),
+    };
+    global.resource_threads().send(msg).unwrap();
+    let _ = global
+        .script_to_constellation_chan()
+        .send(ScriptMsg::BroadcastPermissionChange(origin, name));
+}
+
+fn embedder_permission_name(permission_name: PermissionName) -> EmbedderPermissionName {
+    match permission_name {
+        PermissionName::Geolocation => EmbedderPermissionName::Geolocation,
+        PermissionName::Notifications => EmbedderPermissionName::Notifications,
+        PermissionName::Push => EmbedderPermissionName::Push,
+        PermissionName::Midi => EmbedderPermissionName::Midi,
+        PermissionName::Camera => EmbedderPermissionName::Camera,
+        PermissionName::Microphone => EmbedderPermissionName::Microphone,
+        PermissionName::Speaker => EmbedderPermissionName::Speaker,
+        PermissionName::Device_info => EmbedderPermissionName::DeviceInfo,
+        PermissionName::Background_sync => EmbedderPermissionName::BackgroundSync,
+        PermissionName::Bluetooth => EmbedderPermissionName::Bluetooth,
+        PermissionName::Persistent_storage => EmbedderPermissionName::PersistentStorage,
+        PermissionName::Clipboard_read => EmbedderPermissionName::ClipboardRead,
+        PermissionName::Clipboard_write => EmbedderPermissionName::ClipboardWrite,
+    }
 }
 
 // https://w3c.github.io/permissions/#allowed-in-non-secure-contexts
@@ -390,5 +511,9 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/clipboard-apis/#dom-permissionname-clipboard-read
+        PermissionName::Clipboard_read => false,
+        // https://w3c.github.io/clipboard-apis/#dom-permissionname-clipboard-write
+        PermissionName::Clipboard_write => false,
     }
 }
@@ -0,0 +1,266 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding;
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding::PointerEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mouseevent::MouseEvent;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use euclid::default::Point2D;
+use std::cell::Cell;
+
+/// The kind of device that generated a pointer event.
+/// <https://w3c.github.io/pointerevents/#dom-pointerevent-pointertype>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum PointerType {
+    Mouse,
+    Pen,
+    Touch,
+}
+
+impl PointerType {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            PointerType::Mouse => "mouse",
+            PointerType::Pen => "pen",
+            PointerType::Touch => "touch",
+        }
+    }
+}
+
+/// The attributes a pointer event has on top of those of a mouse event.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+pub struct PointerAttributes {
+    pub pointer_id: i32,
+    pub width: f64,
+    pub height: f64,
+    pub pressure: f32,
+    pub tangential_pressure: f32,
+    pub tilt_x: i32,
+    pub tilt_y: i32,
+    pub twist: i32,
+    pub pointer_type: PointerType,
+    pub is_primary: bool,
+}
+
+impl PointerAttributes {
+    /// The attributes of a pointer without contact geometry, pressure or tilt.
+    pub fn new(pointer_id: i32, pointer_type: PointerType, is_primary: bool) -> PointerAttributes {
+        PointerAttributes {
+            pointer_id,
+            width: 1.0,
+            height: 1.0,
+            pressure: 0.0,
+            tangential_pressure: 0.0,
+            tilt_x: 0,
+            tilt_y: 0,
+            twist: 0,
+            pointer_type,
+            is_primary,
+        }
+    }
+}
+
+#[dom_struct]
+pub struct PointerEvent {
+    mouseevent: MouseEvent,
+    pointer_id: Cell<i32>,
+    width: Cell<Finite<f64>>,
+    height: Cell<Finite<f64>>,
+    pressure: Cell<Finite<f32>>,
+    tangential_pressure: Cell<Finite<f32>>,
+    tilt_x: Cell<i32>,
+    tilt_y: Cell<i32>,
+    twist: Cell<i32>,
+    pointer_type: DomRefCell<DOMString>,
+    is_primary: Cell<bool>,
+}
+
+impl PointerEvent {
+    fn new_inherited() -> PointerEvent {
+        PointerEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            pointer_id: Cell::new(0),
+            width: Cell::new(Finite::wrap(1.0)),
+            height: Cell::new(Finite::wrap(1.0)),
+            pressure: Cell::new(Finite::wrap(0.0)),
+            tangential_pressure: Cell::new(Finite::wrap(0.0)),
+            tilt_x: Cell::new(0),
+            tilt_y: Cell::new(0),
+            twist: Cell::new(0),
+            pointer_type: DomRefCell::new(DOMString::new()),
+            is_primary: Cell::new(false),
+        }
+    }
+
+    pub fn new_uninitialized(window: &Window) -> DomRoot<PointerEvent> {
+        reflect_dom_object(
+            Box::new(PointerEvent::new_inherited()),
+            window,
+            PointerEventBinding::Wrap,
+        )
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        buttons: u16,
+        related_target: Option<&EventTarget>,
+        point_in_target: Option<Point2D<f32>>,
+        attributes: PointerAttributes,
+    ) -> DomRoot<PointerEvent> {
+        let ev = PointerEvent::new_uninitialized(window);
+        ev.upcast::<MouseEvent>().InitMouseEvent(
+            type_,
+            bool::from(can_bubble),
+            bool::from(cancelable),
+            view,
+            detail,
+            screen_x,
+            screen_y,
+            client_x,
+            client_y,
+            ctrl_key,
+            alt_key,
+            shift_key,
+            meta_key,
+            button,
+            related_target,
+        );
+        ev.upcast::<MouseEvent>()
+            .set_buttons_and_point_in_target(buttons, point_in_target);
+        ev.pointer_id.set(attributes.pointer_id);
+        ev.width.set(Finite::wrap(attributes.width));
+        ev.height.set(Finite::wrap(attributes.height));
+        ev.pressure.set(Finite::wrap(attributes.pressure));
+        ev.tangential_pressure
+            .set(Finite::wrap(attributes.tangential_pressure));
+        ev.tilt_x.set(attributes.tilt_x);
+        ev.tilt_y.set(attributes.tilt_y);
+        ev.twist.set(attributes.twist);
+        *ev.pointer_type.borrow_mut() = DOMString::from(attributes.pointer_type.as_str());
+        ev.is_primary.set(attributes.is_primary);
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &PointerEventBinding::PointerEventInit,
+    ) -> Fallible<DomRoot<PointerEvent>> {
+        let mouse_init = &init.parent;
+        let event = PointerEvent::new_uninitialized(window);
+        event.upcast::<MouseEvent>().InitMouseEvent(
+            type_,
+            mouse_init.parent.parent.parent.bubbles,
+            mouse_init.parent.parent.parent.cancelable,
+            mouse_init.parent.parent.view.as_deref(),
+            mouse_init.parent.parent.detail,
+            mouse_init.screenX,
+            mouse_init.screenY,
+            mouse_init.clientX,
+            mouse_init.clientY,
+            mouse_init.parent.ctrlKey,
+            mouse_init.parent.altKey,
+            mouse_init.parent.shiftKey,
+            mouse_init.parent.metaKey,
+            mouse_init.button,
+            mouse_init.relatedTarget.as_deref(),
+        );
+        let mouse_event = event.upcast::<MouseEvent>();
+        mouse_event.set_buttons_and_point_in_target(mouse_init.buttons, None);
+        mouse_event.set_movement(mouse_init.movementX, mouse_init.movementY);
+        event.pointer_id.set(init.pointerId);
+        event.width.set(init.width);
+        event.height.set(init.height);
+        event.pressure.set(init.pressure);
+        event.tangential_pressure.set(init.tangentialPressure);
+        event.tilt_x.set(init.tiltX);
+        event.tilt_y.set(init.tiltY);
+        event.twist.set(init.twist);
+        *event.pointer_type.borrow_mut() = init.pointerType.clone();
+        event.is_primary.set(init.isPrimary);
+        Ok(event)
+    }
+}
+
+impl PointerEventMethods for PointerEvent {
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid
+    fn PointerId(&self) -> i32 {
+        self.pointer_id.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-width
+    fn Width(&self) -> Finite<f64> {
+        self.width.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-height
+    fn Height(&self) -> Finite<f64> {
+        self.height.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pressure
+    fn Pressure(&self) -> Finite<f32> {
+        self.pressure.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tangentialpressure
+    fn TangentialPressure(&self) -> Finite<f32> {
+        self.tangential_pressure.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tiltx
+    fn TiltX(&self) -> i32 {
+        self.tilt_x.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tilty
+    fn TiltY(&self) -> i32 {
+        self.tilt_y.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-twist
+    fn Twist(&self) -> i32 {
+        self.twist.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointertype
+    fn PointerType(&self) -> DOMString {
+        self.pointer_type.borrow().clone()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-isprimary
+    fn IsPrimary(&self) -> bool {
+        self.is_primary.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
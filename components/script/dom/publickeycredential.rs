@@ -0,0 +1,467 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::{enter_realm, InCompartment};
+use crate::dom::authenticatorassertionresponse::AuthenticatorAssertionResponse;
+use crate::dom::authenticatorattestationresponse::AuthenticatorAttestationResponse;
+use crate::dom::authenticatorresponse::{set_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::{
+    self, AttestationConveyancePreference, AuthenticationExtensionsClientOutputs,
+    AuthenticatorAttachment, PublicKeyCredentialCreationOptions, PublicKeyCredentialMethods,
+    PublicKeyCredentialRequestOptions, UserVerificationRequirement,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::credential::Credential;
+use crate::dom::document::get_registrable_domain_suffix_of_or_is_equal_to;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::webauthn::{self, AuthenticatorError, AuthenticatorRequest};
+use embedder_traits::webauthn::{AuthenticatorResponse as Response, GetAssertionRequest};
+use embedder_traits::webauthn::{MakeCredentialRequest, MakeCredentialResponse};
+use embedder_traits::webauthn::{RelyingParty, User};
+use embedder_traits::EmbedderMsg;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::jsapi::{Heap, JSObject};
+use openssl::sha::sha256;
+use profile_traits::ipc as profiled_ipc;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use url::Host;
+
+/// The COSE identifiers of ECDSA with SHA-256 and RSASSA-PKCS1-v1_5 with
+/// SHA-256, the algorithms credentials are created with when the relying
+/// party doesn't choose any.
+const ES256: i32 = -7;
+const RS256: i32 = -257;
+
+/// The flag of the authenticator data telling that it includes attested
+/// credential data.
+const ATTESTED_CREDENTIAL_DATA_FLAG: u8 = 0x40;
+
+// https://w3c.github.io/webauthn/#iface-pkcredential
+#[dom_struct]
+pub struct PublicKeyCredential {
+    credential: Credential,
+    #[ignore_malloc_size_of = "mozjs"]
+    raw_id: Heap<*mut JSObject>,
+    response: Dom<AuthenticatorResponse>,
+}
+
+impl PublicKeyCredential {
+    fn new_inherited(raw_id: &[u8], response: &AuthenticatorResponse) -> PublicKeyCredential {
+        let id = base64::encode_config(raw_id, base64::URL_SAFE_NO_PAD);
+        PublicKeyCredential {
+            credential: Credential::new_inherited(USVString(id), DOMString::from("public-key")),
+            raw_id: Heap::default(),
+            response: Dom::from_ref(response),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        raw_id: &[u8],
+        response: &AuthenticatorResponse,
+    ) -> DomRoot<PublicKeyCredential> {
+        let credential = reflect_dom_object(
+            Box::new(PublicKeyCredential::new_inherited(raw_id, response)),
+            global,
+            PublicKeyCredentialBinding::Wrap,
+        );
+        set_array_buffer(global.get_cx(), &credential.raw_id, raw_id);
+        credential
+    }
+
+    /// Creates a new credential with an authenticator of the embedder, and
+    /// resolves `promise` with it,
+    /// <https://w3c.github.io/webauthn/#sctn-createCredential>
+    pub fn create(
+        window: &Window,
+        options: &PublicKeyCredentialCreationOptions,
+        promise: &Rc<Promise>,
+    ) {
+        // Step 5.
+        let user_id = buffer_source_to_vec(&options.user.id);
+        if user_id.is_empty() || user_id.len() > 64 {
+            return promise.reject_error(Error::Type(
+                "The user id must be between 1 and 64 bytes long".to_owned(),
+            ));
+        }
+
+        // Steps 2 and 6-8.
+        let rp_id = match relying_party_id(window, options.rp.id.as_ref().map(|id| &**id)) {
+            Ok(rp_id) => rp_id,
+            Err(error) => return promise.reject_error(error),
+        };
+
+        // Steps 9-10. The type of the parameters can only be "public-key".
+        let algorithms = if options.pubKeyCredParams.is_empty() {
+            vec![ES256, RS256]
+        } else {
+            options
+                .pubKeyCredParams
+                .iter()
+                .map(|parameters| parameters.alg)
+                .collect()
+        };
+
+        // Steps 13-15.
+        let client_data_json = collected_client_data(
+            window,
+            "webauthn.create",
+            &buffer_source_to_vec(&options.challenge),
+        );
+
+        // Steps 18-20.
+        let selection = options.authenticatorSelection.as_ref();
+        let request = MakeCredentialRequest {
+            client_data_hash: sha256(client_data_json.as_bytes()).to_vec(),
+            rp: RelyingParty {
+                id: rp_id,
+                name: options.rp.parent.name.to_string(),
+            },
+            user: User {
+                id: user_id,
+                name: options.user.parent.name.to_string(),
+                display_name: options.user.displayName.to_string(),
+            },
+            algorithms,
+            exclude_list: options
+                .excludeCredentials
+                .iter()
+                .map(|descriptor| buffer_source_to_vec(&descriptor.id))
+                .collect(),
+            resident_key: selection.map_or(false, |selection| selection.requireResidentKey),
+            user_verification: selection.map_or(false, |selection| {
+                selection.userVerification == UserVerificationRequirement::Required
+            }),
+            attachment: selection
+                .and_then(|selection| selection.authenticatorAttachment)
+                .map(|attachment| match attachment {
+                    AuthenticatorAttachment::Platform => {
+                        webauthn::AuthenticatorAttachment::Platform
+                    },
+                    AuthenticatorAttachment::Cross_platform => {
+                        webauthn::AuthenticatorAttachment::CrossPlatform
+                    },
+                }),
+        };
+        let anonymize = options.attestation == AttestationConveyancePreference::None;
+        run_request(
+            window,
+            AuthenticatorRequest::MakeCredential(request),
+            client_data_json,
+            anonymize,
+            promise,
+        );
+    }
+
+    /// Asks an authenticator of the embedder to sign the challenge of
+    /// `options` with an existing credential, and resolves `promise` with it,
+    /// <https://w3c.github.io/webauthn/#sctn-discover-from-external-source>
+    pub fn discover(
+        window: &Window,
+        options: &PublicKeyCredentialRequestOptions,
+        promise: &Rc<Promise>,
+    ) {
+        // Steps 2 and 5-7.
+        let rp_id = match relying_party_id(window, options.rpId.as_ref().map(|id| &**id)) {
+            Ok(rp_id) => rp_id,
+            Err(error) => return promise.reject_error(error),
+        };
+
+        // Steps 9-11.
+        let client_data_json = collected_client_data(
+            window,
+            "webauthn.get",
+            &buffer_source_to_vec(&options.challenge),
+        );
+
+        // Steps 14-17.
+        let request = GetAssertionRequest {
+            rp_id,
+            client_data_hash: sha256(client_data_json.as_bytes()).to_vec(),
+            allow_list: options
+                .allowCredentials
+                .iter()
+                .map(|descriptor| buffer_source_to_vec(&descriptor.id))
+                .collect(),
+            user_verification: options.userVerification == UserVerificationRequirement::Required,
+        };
+        run_request(
+            window,
+            AuthenticatorRequest::GetAssertion(request),
+            client_data_json,
+            false,
+            promise,
+        );
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-isuserverifyingplatformauthenticatoravailable
+    pub fn IsUserVerifyingPlatformAuthenticatorAvailable(
+        global: &GlobalScope,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(global, comp);
+        let (sender, receiver) =
+            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        global.as_window().send_to_embedder(
+            EmbedderMsg::IsUserVerifyingPlatformAuthenticatorAvailable(sender),
+        );
+        promise.resolve_native(&receiver.recv().unwrap_or(false));
+        promise
+    }
+}
+
+impl PublicKeyCredentialMethods for PublicKeyCredential {
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-rawid
+    #[allow(unsafe_code)]
+    fn RawId(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.raw_id.get()) }
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-response
+    fn Response(&self) -> DomRoot<AuthenticatorResponse> {
+        DomRoot::from_ref(&*self.response)
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-getclientextensionresults
+    fn GetClientExtensionResults(&self) -> AuthenticationExtensionsClientOutputs {
+        // No extension is supported.
+        AuthenticationExtensionsClientOutputs::empty()
+    }
+}
+
+fn buffer_source_to_vec(buffer_source: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *buffer_source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+/// The id of the relying party a credential is created or used for, which is
+/// the effective domain of the document unless the page asks for one of its
+/// registrable suffixes.
+fn relying_party_id(window: &Window, rp_id: Option<&str>) -> Fallible<String> {
+    // Credentials can't be used by documents that are embedded in
+    // documents of other origins.
+    if !is_same_origin_with_ancestors(window) {
+        return Err(Error::NotAllowed);
+    }
+    let origin = window.upcast::<GlobalScope>().origin();
+    if !origin.is_tuple() {
+        return Err(Error::NotAllowed);
+    }
+    let effective_domain = match origin.effective_domain() {
+        Some(domain @ Host::Domain(_)) => domain,
+        _ => return Err(Error::Security),
+    };
+    match rp_id {
+        Some(rp_id) => get_registrable_domain_suffix_of_or_is_equal_to(rp_id, effective_domain)
+            .map(|host| host.to_string())
+            .ok_or(Error::Security),
+        None => Ok(effective_domain.to_string()),
+    }
+}
+
+fn is_same_origin_with_ancestors(window: &Window) -> bool {
+    let origin = window.upcast::<GlobalScope>().origin();
+    let window_proxy = window.window_proxy();
+    let mut current = window_proxy.parent();
+    while let Some(ancestor) = current {
+        // The documents of the ancestors of other origins aren't available.
+        match ancestor.document() {
+            Some(document) if document.origin().same_origin(origin) => {},
+            _ => return false,
+        }
+        current = ancestor.parent();
+    }
+    true
+}
+
+/// The JSON serialization of the client data of a request, which lists its
+/// members in the order of the specification,
+/// <https://w3c.github.io/webauthn/#clientdatajson-serialization>
+fn collected_client_data(window: &Window, type_: &str, challenge: &[u8]) -> String {
+    let origin = window.upcast::<GlobalScope>().origin().immutable();
+    let challenge = base64::encode_config(challenge, base64::URL_SAFE_NO_PAD);
+    format!(
+        "{{\"type\":{},\"challenge\":{},\"origin\":{},\"crossOrigin\":false}}",
+        serde_json::to_string(type_).unwrap(),
+        serde_json::to_string(&challenge).unwrap(),
+        serde_json::to_string(&origin.ascii_serialization()).unwrap(),
+    )
+}
+
+/// Sends `request` to the authenticators of the embedder, and settles
+/// `promise` with the credential they return.
+fn run_request(
+    window: &Window,
+    request: AuthenticatorRequest,
+    client_data_json: String,
+    anonymize: bool,
+    promise: &Rc<Promise>,
+) {
+    let global = window.upcast::<GlobalScope>();
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    let trusted_promise = TrustedPromise::new(promise.clone());
+    let task_source = global.dom_manipulation_task_source();
+    let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+    let mut trusted_promise = Some(trusted_promise);
+    let mut client_data_json = Some(client_data_json);
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            let (trusted_promise, client_data_json) =
+                match (trusted_promise.take(), client_data_json.take()) {
+                    (Some(trusted_promise), Some(client_data_json)) => {
+                        (trusted_promise, client_data_json)
+                    },
+                    _ => return,
+                };
+            let result = message.to().unwrap_or(Err(AuthenticatorError::NotAllowed));
+            let _ = task_source.queue_with_canceller(
+                task!(settle_credential_promise: move || {
+                    let promise = trusted_promise.root();
+                    settle(&promise, result, client_data_json.as_bytes(), anonymize);
+                }),
+                &canceller,
+            );
+        }),
+    );
+    window.send_to_embedder(EmbedderMsg::Authenticate(request, sender));
+}
+
+fn settle(
+    promise: &Promise,
+    result: Result<Response, AuthenticatorError>,
+    client_data_json: &[u8],
+    anonymize: bool,
+) {
+    let global = promise.global();
+    let _ac = enter_realm(promise);
+    match result {
+        Ok(Response::MakeCredential(mut response)) => {
+            let credential_id = match attested_credential_id(&response.authenticator_data) {
+                Some(credential_id) => credential_id.to_vec(),
+                None => return promise.reject_error(Error::NotAllowed),
+            };
+            if anonymize {
+                anonymize_attestation(&mut response);
+            }
+            let attestation = AuthenticatorAttestationResponse::new(
+                &global,
+                client_data_json,
+                &attestation_object(&response),
+            );
+            promise.resolve_native(&PublicKeyCredential::new(
+                &global,
+                &credential_id,
+                attestation.upcast(),
+            ));
+        },
+        Ok(Response::GetAssertion(response)) => {
+            let assertion = AuthenticatorAssertionResponse::new(
+                &global,
+                client_data_json,
+                &response.authenticator_data,
+                &response.signature,
+                response
+                    .user_handle
+                    .as_ref()
+                    .map(|user_handle| &**user_handle),
+            );
+            promise.resolve_native(&PublicKeyCredential::new(
+                &global,
+                &response.credential_id,
+                assertion.upcast(),
+            ));
+        },
+        // Any other failure looks like the user didn't consent, so that the
+        // page can't tell which credentials the authenticators hold.
+        Err(AuthenticatorError::InvalidState) => promise.reject_error(Error::InvalidState),
+        Err(AuthenticatorError::NotSupported) => promise.reject_error(Error::NotSupported),
+        Err(AuthenticatorError::NotAllowed) | Err(AuthenticatorError::Unknown) => {
+            promise.reject_error(Error::NotAllowed)
+        },
+    }
+}
+
+/// The id of the credential in the attested credential data of
+/// `authenticator_data`, which follows the hash of the relying party id, the
+/// flags, the signature counter and the AAGUID of the authenticator,
+/// <https://w3c.github.io/webauthn/#sctn-attested-credential-data>
+pub fn attested_credential_id(authenticator_data: &[u8]) -> Option<&[u8]> {
+    if authenticator_data.get(32)? & ATTESTED_CREDENTIAL_DATA_FLAG == 0 {
+        return None;
+    }
+    let length = u16::from_be_bytes([*authenticator_data.get(53)?, *authenticator_data.get(54)?]);
+    authenticator_data.get(55..55 + length as usize)
+}
+
+/// Replaces the attestation of a new credential with a "none" attestation,
+/// when the relying party isn't interested in it,
+/// <https://w3c.github.io/webauthn/#dom-attestationconveyancepreference-none>
+pub fn anonymize_attestation(response: &mut MakeCredentialResponse) {
+    if response.format == "none" {
+        return;
+    }
+    response.format = "none".to_owned();
+    // An empty CBOR map.
+    response.attestation_statement = vec![0xa0];
+    // The AAGUID could identify the model of the authenticator.
+    for byte in response.authenticator_data[37..53].iter_mut() {
+        *byte = 0;
+    }
+}
+
+/// The CBOR encoding of the attestation object of a new credential, with
+/// its keys in the canonical CTAP2 order,
+/// <https://w3c.github.io/webauthn/#sctn-attestation>
+pub fn attestation_object(response: &MakeCredentialResponse) -> Vec<u8> {
+    // A map of three entries.
+    let mut object = vec![0xa3];
+    cbor_text(&mut object, "fmt");
+    cbor_text(&mut object, &response.format);
+    cbor_text(&mut object, "attStmt");
+    object.extend_from_slice(&response.attestation_statement);
+    cbor_text(&mut object, "authData");
+    cbor_header(&mut object, 2, response.authenticator_data.len());
+    object.extend_from_slice(&response.authenticator_data);
+    object
+}
+
+fn cbor_text(output: &mut Vec<u8>, text: &str) {
+    cbor_header(output, 3, text.len());
+    output.extend_from_slice(text.as_bytes());
+}
+
+/// Writes the header of a CBOR data item of the given major type, whose
+/// argument is `length`.
+fn cbor_header(output: &mut Vec<u8>, major_type: u8, length: usize) {
+    let major_type = major_type << 5;
+    if length < 24 {
+        output.push(major_type | length as u8);
+    } else if length <= u8::max_value() as usize {
+        output.push(major_type | 24);
+        output.push(length as u8);
+    } else if length <= u16::max_value() as usize {
+        output.push(major_type | 25);
+        output.extend_from_slice(&(length as u16).to_be_bytes());
+    } else {
+        output.push(major_type | 26);
+        output.extend_from_slice(&(length as u32).to_be_bytes());
+    }
+}
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeConstants;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
@@ -24,6 +25,7 @@ use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::element::Element;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::node::{Node, ShadowIncluding, UnbindContext};
+use crate::dom::selection::Selection;
 use crate::dom::text::Text;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
@@ -37,6 +39,8 @@ pub struct Range {
     reflector_: Reflector,
     start: BoundaryPoint,
     end: BoundaryPoint,
+    /// The selections whose range this is.
+    associated_selections: DomRefCell<Vec<Dom<Selection>>>,
 }
 
 impl Range {
@@ -50,6 +54,7 @@ impl Range {
             reflector_: Reflector::new(),
             start: BoundaryPoint::new(start_container, start_offset),
             end: BoundaryPoint::new(end_container, end_offset),
+            associated_selections: DomRefCell::new(vec![]),
         }
     }
 
@@ -89,7 +94,7 @@ impl Range {
     }
 
     // https://dom.spec.whatwg.org/#contained
-    fn contains(&self, node: &Node) -> bool {
+    pub fn contains(&self, node: &Node) -> bool {
         match (
             bp_position(node, 0, &self.StartContainer(), self.StartOffset()),
             bp_position(node, node.len(), &self.EndContainer(), self.EndOffset()),
@@ -173,6 +178,7 @@ impl Range {
             }
         }
         self.start.set(node, offset);
+        self.report_change();
     }
 
     // https://dom.spec.whatwg.org/#concept-range-bp-set
@@ -188,6 +194,27 @@ impl Range {
             }
         }
         self.end.set(node, offset);
+        self.report_change();
+    }
+
+    pub fn associate_selection(&self, selection: &Selection) {
+        let mut selections = self.associated_selections.borrow_mut();
+        if !selections.iter().any(|s| &**s == selection) {
+            selections.push(Dom::from_ref(selection));
+        }
+    }
+
+    pub fn disassociate_selection(&self, selection: &Selection) {
+        self.associated_selections
+            .borrow_mut()
+            .retain(|s| &**s != selection);
+    }
+
+    /// Lets the selections whose range this is know that a boundary point moved.
+    fn report_change(&self) {
+        for selection in self.associated_selections.borrow().iter() {
+            selection.queue_selectionchange_task();
+        }
     }
 
     // https://dom.spec.whatwg.org/#dom-range-comparepointnode-offset
@@ -1057,7 +1084,7 @@ impl PartialEq for BoundaryPoint {
 }
 
 // https://dom.spec.whatwg.org/#concept-range-bp-position
-fn bp_position(a_node: &Node, a_offset: u32, b_node: &Node, b_offset: u32) -> Option<Ordering> {
+pub fn bp_position(a_node: &Node, a_offset: u32, b_node: &Node, b_offset: u32) -> Option<Ordering> {
     if a_node as *const Node == b_node as *const Node {
         // Step 1.
         return Some(a_offset.cmp(&b_offset));
@@ -1141,9 +1168,11 @@ impl WeakRangeVec {
                 }
                 if &range.start.node == child {
                     range.start.set(context.parent, offset);
+                    range.report_change();
                 }
                 if &range.end.node == child {
                     range.end.set(context.parent, offset);
+                    range.report_change();
                 }
             });
 
@@ -1168,9 +1197,11 @@ impl WeakRangeVec {
                 }
                 if &range.start.node == node {
                     range.start.set(sibling, range.StartOffset() + length);
+                    range.report_change();
                 }
                 if &range.end.node == node {
                     range.end.set(sibling, range.EndOffset() + length);
+                    range.report_change();
                 }
             });
 
@@ -1211,9 +1242,11 @@ impl WeakRangeVec {
 
                 if move_start {
                     range.start.set(child, new_offset);
+                    range.report_change();
                 }
                 if move_end {
                     range.end.set(child, new_offset);
+                    range.report_change();
                 }
             });
         }
@@ -1272,9 +1305,11 @@ impl WeakRangeVec {
 
                 if move_start {
                     range.start.set(sibling, start_offset - offset);
+                    range.report_change();
                 }
                 if move_end {
                     range.end.set(sibling, end_offset - offset);
+                    range.report_change();
                 }
             });
         }
@@ -1288,9 +1323,11 @@ impl WeakRangeVec {
                 let range = entry.root().unwrap();
                 if &range.start.node == node && offset == range.StartOffset() {
                     range.start.set_offset(offset + 1);
+                    range.report_change();
                 }
                 if &range.end.node == node && offset == range.EndOffset() {
                     range.end.set_offset(offset + 1);
+                    range.report_change();
                 }
             });
         }
@@ -1303,10 +1340,12 @@ impl WeakRangeVec {
                 let start_offset = range.StartOffset();
                 if &range.start.node == node && start_offset > offset {
                     range.start.set_offset(f(start_offset));
+                    range.report_change();
                 }
                 let end_offset = range.EndOffset();
                 if &range.end.node == node && end_offset > offset {
                     range.end.set_offset(f(end_offset));
+                    range.report_change();
                 }
             });
         }
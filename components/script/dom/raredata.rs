@@ -6,9 +6,11 @@ use crate::dom::bindings::root::Dom;
 use crate::dom::customelementregistry::{
     CustomElementDefinition, CustomElementReaction, CustomElementState,
 };
+use crate::dom::htmlslotelement::HTMLSlotElement;
 use crate::dom::mutationobserver::RegisteredObserver;
 use crate::dom::node::UniqueId;
 use crate::dom::shadowroot::ShadowRoot;
+use std::ops::Range;
 use std::rc::Rc;
 
 //XXX(ferjm) Ideally merge NodeRareData and ElementRareData so they share
@@ -25,6 +27,10 @@ pub struct NodeRareData {
     pub mutation_observers: Vec<RegisteredObserver>,
     /// Lazily-generated Unique Id for this node.
     pub unique_id: Option<UniqueId>,
+    /// <https://dom.spec.whatwg.org/#slotable-assigned-slot>
+    pub assigned_slot: Option<Dom<HTMLSlotElement>>,
+    /// The part of a text node's data that is in the document's selection, in UTF-8 bytes.
+    pub selected_range: Option<Range<usize>>,
 }
 
 #[derive(Default, JSTraceable, MallocSizeOf)]
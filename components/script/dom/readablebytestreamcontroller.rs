@@ -0,0 +1,1238 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::CallbackContainer;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableByteStreamControllerBinding::{
+    self, ReadableByteStreamControllerMethods,
+};
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::UnderlyingSource;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestream::{ReadableStream, ReadableStreamState};
+use crate::dom::readablestreambyobrequest::ReadableStreamBYOBRequest;
+use crate::dom::readablestreamdefaultcontroller::{
+    invoke_method, promise_from_invoke, CancelAlgorithmSettled, SourceAlgorithms,
+};
+use crate::dom::readablestreamdefaultreader::{ReadRequest, ReadableStreamDefaultReader};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{
+    Heap, IsDetachedArrayBufferObject, JSContext as RawJSContext, JSObject,
+    JS_GetArrayBufferViewByteLength, JS_GetArrayBufferViewByteOffset, JS_GetArrayBufferViewType,
+    NewArrayBufferWithContents, Type,
+};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    JS_ClearPendingException, JS_GetArrayBufferViewBuffer, JS_NewDataView,
+    JS_NewFloat32ArrayWithBuffer, JS_NewFloat64ArrayWithBuffer, JS_NewInt16ArrayWithBuffer,
+    JS_NewInt32ArrayWithBuffer, JS_NewInt8ArrayWithBuffer, JS_NewUint16ArrayWithBuffer,
+    JS_NewUint32ArrayWithBuffer, JS_NewUint8ArrayWithBuffer, JS_NewUint8ClampedArrayWithBuffer,
+    JS_SetPendingException, StealArrayBufferContents,
+};
+use js::rust::{CustomAutoRooterGuard, HandleObject, HandleValue, MutableHandleValue};
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use std::cell::Cell;
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::ptr;
+use std::rc::Rc;
+
+/// The constructor of an ArrayBufferView, which a pull-into descriptor
+/// uses to create the view it fills.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum ViewType {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    DataView,
+}
+
+impl ViewType {
+    #[allow(unsafe_code)]
+    pub fn of(view: *mut JSObject) -> ViewType {
+        match unsafe { JS_GetArrayBufferViewType(view) } {
+            Type::Int8 => ViewType::Int8,
+            Type::Uint8 => ViewType::Uint8,
+            Type::Uint8Clamped => ViewType::Uint8Clamped,
+            Type::Int16 => ViewType::Int16,
+            Type::Uint16 => ViewType::Uint16,
+            Type::Int32 => ViewType::Int32,
+            Type::Uint32 => ViewType::Uint32,
+            Type::Float32 => ViewType::Float32,
+            Type::Float64 => ViewType::Float64,
+            _ => ViewType::DataView,
+        }
+    }
+
+    /// <https://tc39.es/ecma262/#table-the-typedarray-constructors>
+    pub fn element_size(self) -> usize {
+        match self {
+            ViewType::Int8 | ViewType::Uint8 | ViewType::Uint8Clamped | ViewType::DataView => 1,
+            ViewType::Int16 | ViewType::Uint16 => 2,
+            ViewType::Int32 | ViewType::Uint32 | ViewType::Float32 => 4,
+            ViewType::Float64 => 8,
+        }
+    }
+}
+
+/// The `[[ViewedArrayBuffer]]` of an ArrayBufferView.
+#[allow(unsafe_code)]
+pub fn view_buffer(cx: JSContext, view: HandleObject) -> *mut JSObject {
+    let mut is_shared = false;
+    unsafe { JS_GetArrayBufferViewBuffer(*cx, view, &mut is_shared) }
+}
+
+/// The `[[ByteOffset]]` of an ArrayBufferView.
+#[allow(unsafe_code)]
+pub fn view_byte_offset(view: *mut JSObject) -> usize {
+    unsafe { JS_GetArrayBufferViewByteOffset(view) as usize }
+}
+
+/// The `[[ByteLength]]` of an ArrayBufferView.
+#[allow(unsafe_code)]
+pub fn view_byte_length(view: *mut JSObject) -> usize {
+    unsafe { JS_GetArrayBufferViewByteLength(view) as usize }
+}
+
+/// The `[[ArrayBufferByteLength]]` of an ArrayBuffer, which is 0 once it
+/// is detached.
+pub fn buffer_byte_length(cx: JSContext, buffer: *mut JSObject) -> usize {
+    typedarray!(in(*cx) let buffer: ArrayBuffer = buffer);
+    buffer.map_or(0, |buffer| buffer.len())
+}
+
+/// <https://tc39.es/ecma262/#sec-isdetachedbuffer>
+#[allow(unsafe_code)]
+pub fn is_detached(buffer: *mut JSObject) -> bool {
+    unsafe { IsDetachedArrayBufferObject(buffer) }
+}
+
+/// <https://streams.spec.whatwg.org/#transfer-array-buffer>, which moves the
+/// contents of `buffer` to a new ArrayBuffer without copying them, and
+/// detaches `buffer`.
+#[allow(unsafe_code)]
+pub fn transfer_array_buffer(cx: JSContext, buffer: HandleObject) -> Fallible<*mut JSObject> {
+    // Step 2.
+    if is_detached(buffer.get()) {
+        return Err(Error::Type("The buffer is detached".to_owned()));
+    }
+
+    // Steps 3-6.
+    let length = buffer_byte_length(cx, buffer.get());
+    unsafe {
+        let contents = StealArrayBufferContents(*cx, buffer);
+        if contents.is_null() {
+            JS_ClearPendingException(*cx);
+            return Err(Error::Type("The buffer cannot be transferred".to_owned()));
+        }
+        Ok(NewArrayBufferWithContents(*cx, length, contents))
+    }
+}
+
+/// Creates an ArrayBuffer whose contents are a copy of `length` bytes of
+/// `buffer` from `byte_offset`, <https://tc39.es/ecma262/#sec-clonearraybuffer>.
+#[allow(unsafe_code)]
+fn clone_array_buffer(
+    cx: JSContext,
+    buffer: *mut JSObject,
+    byte_offset: usize,
+    length: usize,
+) -> *mut JSObject {
+    typedarray!(in(*cx) let source: ArrayBuffer = buffer);
+    let source = source.expect("Only ArrayBuffers are cloned.");
+    rooted!(in(*cx) let mut clone = ptr::null_mut::<JSObject>());
+    unsafe {
+        let bytes = &source.as_slice()[byte_offset..byte_offset + length];
+        assert!(ArrayBuffer::create(*cx, CreateWith::Slice(bytes), clone.handle_mut()).is_ok());
+    }
+    clone.get()
+}
+
+/// Copies `count` bytes from the ArrayBuffer `from` at `from_index` to the
+/// distinct ArrayBuffer `to` at `to_index`,
+/// <https://tc39.es/ecma262/#sec-copydatablockbytes>.
+#[allow(unsafe_code)]
+fn copy_data_block_bytes(
+    cx: JSContext,
+    to: *mut JSObject,
+    to_index: usize,
+    from: *mut JSObject,
+    from_index: usize,
+    count: usize,
+) {
+    typedarray!(in(*cx) let from: ArrayBuffer = from);
+    typedarray!(in(*cx) let to: ArrayBuffer = to);
+    if let (Ok(from), Ok(mut to)) = (from, to) {
+        unsafe {
+            to.as_mut_slice()[to_index..to_index + count]
+                .copy_from_slice(&from.as_slice()[from_index..from_index + count]);
+        }
+    }
+}
+
+/// Creates a view of type `view_type` on `length` elements of `buffer`
+/// from `byte_offset`.
+#[allow(unsafe_code)]
+pub fn construct_view(
+    cx: JSContext,
+    view_type: ViewType,
+    buffer: HandleObject,
+    byte_offset: usize,
+    length: usize,
+) -> *mut JSObject {
+    let (byte_offset, length) = (byte_offset as u32, length as i32);
+    let view = unsafe {
+        match view_type {
+            ViewType::Int8 => JS_NewInt8ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Uint8 => JS_NewUint8ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Uint8Clamped => {
+                JS_NewUint8ClampedArrayWithBuffer(*cx, buffer, byte_offset, length)
+            },
+            ViewType::Int16 => JS_NewInt16ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Uint16 => JS_NewUint16ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Int32 => JS_NewInt32ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Uint32 => JS_NewUint32ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Float32 => JS_NewFloat32ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::Float64 => JS_NewFloat64ArrayWithBuffer(*cx, buffer, byte_offset, length),
+            ViewType::DataView => JS_NewDataView(*cx, buffer, byte_offset, length),
+        }
+    };
+    assert!(!view.is_null());
+    view
+}
+
+/// <https://streams.spec.whatwg.org/#readable-byte-stream-queue-entry>
+#[derive(JSTraceable, MallocSizeOf)]
+struct ByteQueueEntry {
+    #[ignore_malloc_size_of = "mozjs"]
+    buffer: Box<Heap<*mut JSObject>>,
+    byte_offset: usize,
+    byte_length: usize,
+}
+
+/// The kind of reader whose read a pull-into descriptor fulfills.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum ReaderType {
+    Default,
+    Byob,
+}
+
+/// <https://streams.spec.whatwg.org/#pull-into-descriptor>
+#[derive(JSTraceable, MallocSizeOf)]
+struct PullIntoDescriptor {
+    #[ignore_malloc_size_of = "mozjs"]
+    buffer: Box<Heap<*mut JSObject>>,
+    buffer_byte_length: usize,
+    byte_offset: usize,
+    byte_length: usize,
+    bytes_filled: usize,
+    element_size: usize,
+    view_type: ViewType,
+    reader_type: ReaderType,
+}
+
+/// The promises that a controller reacts to.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+enum SourceStep {
+    Start,
+    Pull,
+}
+
+// https://streams.spec.whatwg.org/#rbs-controller-class
+#[dom_struct]
+pub struct ReadableByteStreamController {
+    reflector_: Reflector,
+    stream: Dom<ReadableStream>,
+    /// The `this` of the underlying source methods, or null.
+    #[ignore_malloc_size_of = "mozjs"]
+    underlying_source: Heap<*mut JSObject>,
+    algorithms: DomRefCell<SourceAlgorithms>,
+    auto_allocate_chunk_size: Option<u64>,
+    byob_request: MutNullableDom<ReadableStreamBYOBRequest>,
+    pending_pull_intos: DomRefCell<VecDeque<PullIntoDescriptor>>,
+    queue: DomRefCell<VecDeque<ByteQueueEntry>>,
+    queue_total_size: Cell<usize>,
+    strategy_hwm: f64,
+    started: Cell<bool>,
+    close_requested: Cell<bool>,
+    pull_again: Cell<bool>,
+    pulling: Cell<bool>,
+}
+
+impl ReadableByteStreamController {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        stream: &ReadableStream,
+        algorithms: SourceAlgorithms,
+        strategy_hwm: f64,
+        auto_allocate_chunk_size: Option<u64>,
+    ) -> ReadableByteStreamController {
+        ReadableByteStreamController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            underlying_source: Heap::default(),
+            algorithms: DomRefCell::new(algorithms),
+            auto_allocate_chunk_size,
+            byob_request: Default::default(),
+            pending_pull_intos: DomRefCell::new(VecDeque::new()),
+            queue: DomRefCell::new(VecDeque::new()),
+            queue_total_size: Cell::new(0),
+            strategy_hwm,
+            started: Cell::new(false),
+            close_requested: Cell::new(false),
+            pull_again: Cell::new(false),
+            pulling: Cell::new(false),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn new(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        algorithms: SourceAlgorithms,
+        strategy_hwm: f64,
+        auto_allocate_chunk_size: Option<u64>,
+    ) -> DomRoot<ReadableByteStreamController> {
+        reflect_dom_object(
+            Box::new(ReadableByteStreamController::new_inherited(
+                stream,
+                algorithms,
+                strategy_hwm,
+                auto_allocate_chunk_size,
+            )),
+            global,
+            ReadableByteStreamControllerBinding::Wrap,
+        )
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-readable-byte-stream-controller-from-underlying-source>
+    #[allow(unsafe_code)]
+    pub fn set_up_from_underlying_source(
+        cx: JSContext,
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        underlying_source: HandleObject,
+        source_dict: &UnderlyingSource,
+        strategy_hwm: f64,
+    ) -> ErrorResult {
+        // Step 2 of https://streams.spec.whatwg.org/#set-up-readable-byte-stream-controller
+        let auto_allocate_chunk_size = source_dict.autoAllocateChunkSize;
+        if auto_allocate_chunk_size == Some(0) {
+            return Err(Error::Type(
+                "autoAllocateChunkSize must be greater than 0".to_owned(),
+            ));
+        }
+
+        // Steps 1-7.
+        let algorithms = SourceAlgorithms::Js {
+            pull: source_dict.pull.clone(),
+            cancel: source_dict.cancel.clone(),
+        };
+        let controller = ReadableByteStreamController::new(
+            global,
+            stream,
+            algorithms,
+            strategy_hwm,
+            auto_allocate_chunk_size,
+        );
+        controller.underlying_source.set(underlying_source.get());
+        stream.set_byte_controller(&controller);
+
+        // Steps 14-17 of https://streams.spec.whatwg.org/#set-up-readable-byte-stream-controller
+        rooted!(in(*cx) let mut start_result = UndefinedValue());
+        if let Some(ref start) = source_dict.start {
+            if !controller.invoke(&**start, None, start_result.handle_mut()) {
+                unsafe { JS_SetPendingException(*cx, start_result.handle()) };
+                return Err(Error::JSFailed);
+            }
+        }
+        let start_promise = Promise::new_resolved(global, cx, start_result.handle())?;
+        controller.react(&start_promise, SourceStep::Start);
+        Ok(())
+    }
+
+    /// Sets up the controller of a stream whose bytes are enqueued by Rust
+    /// code, which has no start, pull or cancel algorithms.
+    pub fn set_up_native(global: &GlobalScope, stream: &ReadableStream) {
+        let controller =
+            ReadableByteStreamController::new(global, stream, SourceAlgorithms::None, 0., None);
+        controller.started.set(true);
+        stream.set_byte_controller(&controller);
+    }
+
+    /// Invokes `method` of the underlying source, with either `argument` or
+    /// the controller as its only argument.
+    fn invoke<T: CallbackContainer>(
+        &self,
+        method: &T,
+        argument: Option<HandleValue>,
+        rval: MutableHandleValue,
+    ) -> bool {
+        let arguments = [match argument {
+            Some(argument) => argument.get(),
+            None => ObjectValue(self.reflector().get_jsobject().get()),
+        }];
+        invoke_method(self.underlying_source.get(), method, &arguments, rval)
+    }
+
+    fn react(&self, promise: &Promise, step: SourceStep) {
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(SourceAlgorithmSettled::new(self, step, true)),
+            Some(SourceAlgorithmSettled::new(self, step, false)),
+        );
+        promise.append_native_handler(&handler);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-call-pull-if-needed>
+    fn call_pull_if_needed(&self) {
+        // Steps 1-2.
+        if !self.should_call_pull() {
+            return;
+        }
+
+        // Step 3.
+        if self.pulling.get() {
+            self.pull_again.set(true);
+            return;
+        }
+
+        // Steps 5-8.
+        self.pulling.set(true);
+        let global = self.global();
+        let cx = global.get_cx();
+        let pull = match *self.algorithms.borrow() {
+            SourceAlgorithms::Js { ref pull, .. } => pull.clone(),
+            _ => None,
+        };
+        rooted!(in(*cx) let mut pull_result = UndefinedValue());
+        let succeeded = pull.map_or(true, |pull| {
+            self.invoke(&*pull, None, pull_result.handle_mut())
+        });
+        let pull_promise = promise_from_invoke(&global, succeeded, pull_result.handle());
+        self.react(&pull_promise, SourceStep::Pull);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-should-call-pull>
+    fn should_call_pull(&self) -> bool {
+        // Steps 2-4.
+        if !self.can_close_or_enqueue() || !self.started.get() {
+            return false;
+        }
+        // Pulling from a source without a pull algorithm does nothing.
+        match *self.algorithms.borrow() {
+            SourceAlgorithms::Js { pull: Some(_), .. } => {},
+            _ => return false,
+        }
+
+        // Steps 5-6.
+        if self.stream.num_read_requests() > 0 || self.stream.num_read_into_requests() > 0 {
+            return true;
+        }
+
+        // Steps 7-9.
+        self.desired_size().map_or(false, |size| size > 0.)
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-clear-algorithms>
+    fn clear_algorithms(&self) {
+        *self.algorithms.borrow_mut() = SourceAlgorithms::None;
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-clear-pending-pull-intos>
+    fn clear_pending_pull_intos(&self) {
+        self.invalidate_byob_request();
+        self.pending_pull_intos.borrow_mut().clear();
+    }
+
+    /// <https://streams.spec.whatwg.org/#reset-queue>
+    fn reset_queue(&self) {
+        self.queue.borrow_mut().clear();
+        self.queue_total_size.set(0);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-invalidate-byob-request>
+    fn invalidate_byob_request(&self) {
+        if let Some(byob_request) = self.byob_request.get() {
+            byob_request.invalidate();
+            self.byob_request.set(None);
+        }
+    }
+
+    pub fn can_close_or_enqueue(&self) -> bool {
+        !self.close_requested.get() && self.stream.state() == ReadableStreamState::Readable
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-get-desired-size>
+    pub fn desired_size(&self) -> Option<f64> {
+        match self.stream.state() {
+            ReadableStreamState::Errored => None,
+            ReadableStreamState::Closed => Some(0.),
+            ReadableStreamState::Readable => {
+                Some(self.strategy_hwm - self.queue_total_size.get() as f64)
+            },
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-close>
+    #[allow(unsafe_code)]
+    pub fn close(&self) -> ErrorResult {
+        // Step 2.
+        if !self.can_close_or_enqueue() {
+            return Ok(());
+        }
+
+        // Step 3.
+        if self.queue_total_size.get() > 0 {
+            self.close_requested.set(true);
+            return Ok(());
+        }
+
+        // Step 4.
+        let partially_filled = self
+            .pending_pull_intos
+            .borrow()
+            .front()
+            .map_or(false, |first| first.bytes_filled % first.element_size != 0);
+        if partially_filled {
+            let error = Error::Type("The stream closed in the middle of an element".to_owned());
+            let global = self.global();
+            let cx = global.get_cx();
+            rooted!(in(*cx) let mut error_value = UndefinedValue());
+            unsafe { error.to_jsval(*cx, &global, error_value.handle_mut()) };
+            self.error(error_value.handle());
+            unsafe { JS_SetPendingException(*cx, error_value.handle()) };
+            return Err(Error::JSFailed);
+        }
+
+        // Steps 5-6.
+        self.clear_algorithms();
+        self.stream.close();
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-enqueue>
+    fn enqueue(&self, cx: JSContext, chunk: HandleObject) -> ErrorResult {
+        // Step 2.
+        if !self.can_close_or_enqueue() {
+            return Ok(());
+        }
+
+        // Steps 3-7.
+        rooted!(in(*cx) let buffer = view_buffer(cx, chunk));
+        let byte_offset = view_byte_offset(chunk.get());
+        let byte_length = view_byte_length(chunk.get());
+        rooted!(in(*cx) let transferred_buffer = transfer_array_buffer(cx, buffer.handle())?);
+        self.enqueue_transferred(cx, transferred_buffer.handle(), byte_offset, byte_length)
+    }
+
+    /// Steps 8-11 of <https://streams.spec.whatwg.org/#readable-byte-stream-controller-enqueue>,
+    /// which enqueue `byte_length` bytes of the buffer `transferred_buffer`,
+    /// that no script holds, from `byte_offset`.
+    pub fn enqueue_transferred(
+        &self,
+        cx: JSContext,
+        transferred_buffer: HandleObject,
+        byte_offset: usize,
+        byte_length: usize,
+    ) -> ErrorResult {
+        // Step 8.
+        let first_buffer = self
+            .pending_pull_intos
+            .borrow()
+            .front()
+            .map(|first| first.buffer.get());
+        if let Some(first_buffer) = first_buffer {
+            rooted!(in(*cx) let first_buffer = first_buffer);
+            if is_detached(first_buffer.get()) {
+                return Err(Error::Type(
+                    "The buffer of the BYOB request is detached".to_owned(),
+                ));
+            }
+            self.invalidate_byob_request();
+            rooted!(in(*cx) let transferred = transfer_array_buffer(cx, first_buffer.handle())?);
+            if let Some(first) = self.pending_pull_intos.borrow_mut().front_mut() {
+                first.buffer.set(transferred.get());
+            }
+        }
+
+        // Steps 9-11.
+        if self.stream.has_default_reader() && self.stream.num_read_requests() > 0 {
+            assert!(self.queue.borrow().is_empty());
+            if !self.pending_pull_intos.borrow().is_empty() {
+                self.shift_pending_pull_into();
+            }
+            rooted!(in(*cx) let view = construct_view(
+                cx,
+                ViewType::Uint8,
+                transferred_buffer,
+                byte_offset,
+                byte_length,
+            ));
+            rooted!(in(*cx) let chunk = ObjectValue(view.get()));
+            self.stream.fulfill_read_request(chunk.handle(), false);
+        } else if self.stream.has_byob_reader() {
+            self.enqueue_chunk_to_queue(transferred_buffer.get(), byte_offset, byte_length);
+            self.process_pull_into_descriptors_using_queue();
+        } else {
+            self.enqueue_chunk_to_queue(transferred_buffer.get(), byte_offset, byte_length);
+        }
+
+        // Step 12.
+        self.call_pull_if_needed();
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-enqueue-chunk-to-queue>
+    fn enqueue_chunk_to_queue(
+        &self,
+        buffer: *mut JSObject,
+        byte_offset: usize,
+        byte_length: usize,
+    ) {
+        self.queue.borrow_mut().push_back(ByteQueueEntry {
+            buffer: Heap::boxed(buffer),
+            byte_offset,
+            byte_length,
+        });
+        self.queue_total_size
+            .set(self.queue_total_size.get() + byte_length);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-error>
+    pub fn error(&self, error: HandleValue) {
+        // Step 2.
+        if self.stream.state() != ReadableStreamState::Readable {
+            return;
+        }
+
+        // Steps 3-6.
+        self.clear_pending_pull_intos();
+        self.reset_queue();
+        self.clear_algorithms();
+        self.stream.error(error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-fill-pull-into-descriptor-from-queue>
+    fn fill_pull_into_descriptor_from_queue(&self, descriptor: &mut PullIntoDescriptor) -> bool {
+        let cx = self.global().get_cx();
+
+        // Steps 1-7.
+        let element_size = descriptor.element_size;
+        let current_aligned_bytes =
+            descriptor.bytes_filled - descriptor.bytes_filled % element_size;
+        let max_bytes_to_copy = min(
+            self.queue_total_size.get(),
+            descriptor.byte_length - descriptor.bytes_filled,
+        );
+        let max_bytes_filled = descriptor.bytes_filled + max_bytes_to_copy;
+        let max_aligned_bytes = max_bytes_filled - max_bytes_filled % element_size;
+        let mut total_bytes_to_copy_remaining = max_bytes_to_copy;
+        let mut ready = false;
+        if max_aligned_bytes > current_aligned_bytes {
+            total_bytes_to_copy_remaining = max_aligned_bytes - descriptor.bytes_filled;
+            ready = true;
+        }
+
+        // Step 9.
+        let mut queue = self.queue.borrow_mut();
+        while total_bytes_to_copy_remaining > 0 {
+            let head_consumed = {
+                let head = queue
+                    .front_mut()
+                    .expect("The queue holds the bytes to copy.");
+                let bytes_to_copy = min(total_bytes_to_copy_remaining, head.byte_length);
+                copy_data_block_bytes(
+                    cx,
+                    descriptor.buffer.get(),
+                    descriptor.byte_offset + descriptor.bytes_filled,
+                    head.buffer.get(),
+                    head.byte_offset,
+                    bytes_to_copy,
+                );
+                head.byte_offset += bytes_to_copy;
+                head.byte_length -= bytes_to_copy;
+                self.queue_total_size
+                    .set(self.queue_total_size.get() - bytes_to_copy);
+                descriptor.bytes_filled += bytes_to_copy;
+                total_bytes_to_copy_remaining -= bytes_to_copy;
+                head.byte_length == 0
+            };
+            if head_consumed {
+                queue.pop_front();
+            }
+        }
+
+        // Step 10.
+        if !ready {
+            assert_eq!(self.queue_total_size.get(), 0);
+            assert!(descriptor.bytes_filled < element_size);
+        }
+        ready
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-handle-queue-drain>
+    fn handle_queue_drain(&self) {
+        if self.queue_total_size.get() == 0 && self.close_requested.get() {
+            self.clear_algorithms();
+            self.stream.close();
+        } else {
+            self.call_pull_if_needed();
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-process-pull-into-descriptors-using-queue>
+    fn process_pull_into_descriptors_using_queue(&self) {
+        while self.queue_total_size.get() > 0 {
+            let ready = {
+                let mut pending_pull_intos = self.pending_pull_intos.borrow_mut();
+                match pending_pull_intos.front_mut() {
+                    Some(first) => self.fill_pull_into_descriptor_from_queue(first),
+                    None => return,
+                }
+            };
+            if ready {
+                let descriptor = self.shift_pending_pull_into();
+                self.commit_pull_into_descriptor(descriptor);
+            }
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-shift-pending-pull-into>
+    fn shift_pending_pull_into(&self) -> PullIntoDescriptor {
+        assert!(self.byob_request.get().is_none());
+        self.pending_pull_intos
+            .borrow_mut()
+            .pop_front()
+            .expect("There is a pending pull-into descriptor to shift.")
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-convert-pull-into-descriptor>
+    fn convert_pull_into_descriptor(&self, descriptor: &PullIntoDescriptor) -> *mut JSObject {
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let buffer = descriptor.buffer.get());
+        rooted!(in(*cx) let buffer = transfer_array_buffer(cx, buffer.handle())
+            .expect("The buffer of a pull-into descriptor is never detached."));
+        construct_view(
+            cx,
+            descriptor.view_type,
+            buffer.handle(),
+            descriptor.byte_offset,
+            descriptor.bytes_filled / descriptor.element_size,
+        )
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-commit-pull-into-descriptor>
+    fn commit_pull_into_descriptor(&self, descriptor: PullIntoDescriptor) {
+        let cx = self.global().get_cx();
+
+        // Step 3.
+        let done = self.stream.state() == ReadableStreamState::Closed;
+
+        // Steps 4-6.
+        rooted!(in(*cx) let view = self.convert_pull_into_descriptor(&descriptor));
+        rooted!(in(*cx) let chunk = ObjectValue(view.get()));
+        match descriptor.reader_type {
+            ReaderType::Default => self.stream.fulfill_read_request(chunk.handle(), done),
+            ReaderType::Byob => self.stream.fulfill_read_into_request(chunk.handle(), done),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-pull-into>
+    #[allow(unsafe_code)]
+    pub fn pull_into(&self, cx: JSContext, view: HandleObject, promise: &Rc<Promise>) {
+        // Steps 2-5.
+        let view_type = ViewType::of(view.get());
+        let element_size = view_type.element_size();
+        let byte_offset = view_byte_offset(view.get());
+        let byte_length = view_byte_length(view.get());
+
+        // Steps 6-7.
+        rooted!(in(*cx) let buffer = view_buffer(cx, view));
+        let buffer = match transfer_array_buffer(cx, buffer.handle()) {
+            Ok(buffer) => buffer,
+            Err(error) => return promise.reject_error(error),
+        };
+        rooted!(in(*cx) let buffer = buffer);
+
+        // Step 8.
+        let mut descriptor = PullIntoDescriptor {
+            buffer: Heap::boxed(buffer.get()),
+            buffer_byte_length: buffer_byte_length(cx, buffer.get()),
+            byte_offset,
+            byte_length,
+            bytes_filled: 0,
+            element_size,
+            view_type,
+            reader_type: ReaderType::Byob,
+        };
+
+        // Step 9.
+        if !self.pending_pull_intos.borrow().is_empty() {
+            self.pending_pull_intos.borrow_mut().push_back(descriptor);
+            self.stream.add_read_into_request(promise.clone());
+            return;
+        }
+
+        // Step 10.
+        if self.stream.state() == ReadableStreamState::Closed {
+            rooted!(in(*cx) let empty_view = construct_view(
+                cx,
+                view_type,
+                buffer.handle(),
+                byte_offset,
+                0,
+            ));
+            rooted!(in(*cx) let chunk = ObjectValue(empty_view.get()));
+            return ReadableStreamDefaultReader::resolve_read_request(
+                promise,
+                chunk.handle(),
+                true,
+            );
+        }
+
+        // Step 11.
+        if self.queue_total_size.get() > 0 {
+            if self.fill_pull_into_descriptor_from_queue(&mut descriptor) {
+                rooted!(in(*cx) let filled_view = self.convert_pull_into_descriptor(&descriptor));
+                rooted!(in(*cx) let chunk = ObjectValue(filled_view.get()));
+                self.handle_queue_drain();
+                return ReadableStreamDefaultReader::resolve_read_request(
+                    promise,
+                    chunk.handle(),
+                    false,
+                );
+            }
+            if self.close_requested.get() {
+                let global = self.global();
+                rooted!(in(*cx) let mut error = UndefinedValue());
+                unsafe {
+                    Error::Type("The stream is closing".to_owned()).to_jsval(
+                        *cx,
+                        &global,
+                        error.handle_mut(),
+                    );
+                }
+                self.error(error.handle());
+                return promise.reject(cx, error.handle());
+            }
+        }
+
+        // Steps 12-14.
+        self.pending_pull_intos.borrow_mut().push_back(descriptor);
+        self.stream.add_read_into_request(promise.clone());
+        self.call_pull_if_needed();
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-respond>
+    pub fn respond(&self, bytes_written: u64) -> ErrorResult {
+        let cx = self.global().get_cx();
+        let bytes_written = bytes_written as usize;
+
+        // Steps 1-4.
+        let (buffer, bytes_filled, byte_length) = match self.pending_pull_intos.borrow().front() {
+            Some(first) => (first.buffer.get(), first.bytes_filled, first.byte_length),
+            None => return Err(Error::Type("There is no pending read".to_owned())),
+        };
+        if self.stream.state() == ReadableStreamState::Closed {
+            if bytes_written != 0 {
+                return Err(Error::Type(
+                    "A closed stream can only respond with 0 bytes".to_owned(),
+                ));
+            }
+        } else {
+            if bytes_written == 0 {
+                return Err(Error::Type(
+                    "A readable stream cannot respond with 0 bytes".to_owned(),
+                ));
+            }
+            if bytes_filled + bytes_written > byte_length {
+                return Err(Error::Range(
+                    "More bytes were written than the view holds".to_owned(),
+                ));
+            }
+        }
+
+        // Step 5.
+        rooted!(in(*cx) let buffer = buffer);
+        rooted!(in(*cx) let transferred = transfer_array_buffer(cx, buffer.handle())?);
+        if let Some(first) = self.pending_pull_intos.borrow_mut().front_mut() {
+            first.buffer.set(transferred.get());
+        }
+
+        // Step 6.
+        self.respond_internal(bytes_written);
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-respond-with-new-view>
+    pub fn respond_with_new_view(&self, cx: JSContext, view: HandleObject) -> ErrorResult {
+        let view_byte_offset = view_byte_offset(view.get());
+        let view_byte_length = view_byte_length(view.get());
+        rooted!(in(*cx) let view_buffer = view_buffer(cx, view));
+
+        // Steps 1-3.
+        let (byte_offset, bytes_filled, byte_length, buffer_length) =
+            match self.pending_pull_intos.borrow().front() {
+                Some(first) => (
+                    first.byte_offset,
+                    first.bytes_filled,
+                    first.byte_length,
+                    first.buffer_byte_length,
+                ),
+                None => return Err(Error::Type("There is no pending read".to_owned())),
+            };
+
+        // Steps 4-5.
+        if self.stream.state() == ReadableStreamState::Closed {
+            if view_byte_length != 0 {
+                return Err(Error::Type(
+                    "A closed stream can only respond with an empty view".to_owned(),
+                ));
+            }
+        } else if view_byte_length == 0 {
+            return Err(Error::Type(
+                "A readable stream cannot respond with an empty view".to_owned(),
+            ));
+        }
+
+        // Steps 6-8.
+        if byte_offset + bytes_filled != view_byte_offset {
+            return Err(Error::Range(
+                "The view does not start where the read left off".to_owned(),
+            ));
+        }
+        if buffer_length != buffer_byte_length(cx, view_buffer.get()) {
+            return Err(Error::Range(
+                "The view is not on a buffer of the requested length".to_owned(),
+            ));
+        }
+        if bytes_filled + view_byte_length > byte_length {
+            return Err(Error::Range(
+                "The view holds more bytes than were requested".to_owned(),
+            ));
+        }
+
+        // Steps 9-11.
+        rooted!(in(*cx) let transferred = transfer_array_buffer(cx, view_buffer.handle())?);
+        if let Some(first) = self.pending_pull_intos.borrow_mut().front_mut() {
+            first.buffer.set(transferred.get());
+        }
+        self.respond_internal(view_byte_length);
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-respond-internal>
+    fn respond_internal(&self, bytes_written: usize) {
+        // Step 2.
+        self.invalidate_byob_request();
+
+        // Steps 3-4.
+        if self.stream.state() == ReadableStreamState::Closed {
+            self.respond_in_closed_state();
+        } else {
+            self.respond_in_readable_state(bytes_written);
+        }
+
+        // Step 5.
+        self.call_pull_if_needed();
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-respond-in-closed-state>
+    fn respond_in_closed_state(&self) {
+        if !self.stream.has_byob_reader() {
+            return;
+        }
+        while self.stream.num_read_into_requests() > 0 {
+            let descriptor = self.shift_pending_pull_into();
+            self.commit_pull_into_descriptor(descriptor);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-byte-stream-controller-respond-in-readable-state>
+    fn respond_in_readable_state(&self, bytes_written: usize) {
+        // Steps 1-3.
+        let filled_an_element = match self.pending_pull_intos.borrow_mut().front_mut() {
+            Some(first) => {
+                assert!(first.bytes_filled + bytes_written <= first.byte_length);
+                first.bytes_filled += bytes_written;
+                first.bytes_filled >= first.element_size
+            },
+            None => return,
+        };
+        if !filled_an_element {
+            return;
+        }
+
+        // Steps 4-7.
+        let mut descriptor = self.shift_pending_pull_into();
+        let remainder_size = descriptor.bytes_filled % descriptor.element_size;
+        if remainder_size > 0 {
+            let cx = self.global().get_cx();
+            let end = descriptor.byte_offset + descriptor.bytes_filled;
+            rooted!(in(*cx) let remainder = clone_array_buffer(
+                cx,
+                descriptor.buffer.get(),
+                end - remainder_size,
+                remainder_size,
+            ));
+            self.enqueue_chunk_to_queue(remainder.get(), 0, remainder_size);
+        }
+
+        // Steps 8-10.
+        descriptor.bytes_filled -= remainder_size;
+        self.commit_pull_into_descriptor(descriptor);
+        self.process_pull_into_descriptors_using_queue();
+    }
+
+    /// Dequeues all the bytes of a stream whose chunks are enqueued by Rust
+    /// code, as `ReadableStream::enqueue_native` does. The stream is closed
+    /// if closing it was requested.
+    #[allow(unsafe_code)]
+    pub fn take_queued_bytes(&self) -> Vec<u8> {
+        let entries = {
+            let mut queue = self.queue.borrow_mut();
+            queue.drain(..).collect::<Vec<_>>()
+        };
+        self.queue_total_size.set(0);
+        let cx = self.global().get_cx();
+        let mut bytes = vec![];
+        for entry in entries {
+            typedarray!(in(*cx) let buffer: ArrayBuffer = entry.buffer.get());
+            if let Ok(buffer) = buffer {
+                let end = entry.byte_offset + entry.byte_length;
+                bytes.extend_from_slice(unsafe { &buffer.as_slice()[entry.byte_offset..end] });
+            }
+        }
+        if self.close_requested.get() && self.stream.state() == ReadableStreamState::Readable {
+            self.clear_algorithms();
+            self.stream.close();
+        }
+        bytes
+    }
+
+    /// <https://streams.spec.whatwg.org/#rbs-controller-private-pull>
+    #[allow(unrooted_must_root, unsafe_code)]
+    pub fn pull_steps(&self, read_request: ReadRequest) {
+        let global = self.global();
+        let cx = global.get_cx();
+
+        // Step 3.
+        let entry = self.queue.borrow_mut().pop_front();
+        if let Some(entry) = entry {
+            // https://streams.spec.whatwg.org/#readable-byte-stream-controller-fill-read-request-from-queue
+            self.queue_total_size
+                .set(self.queue_total_size.get() - entry.byte_length);
+            self.handle_queue_drain();
+            rooted!(in(*cx) let buffer = entry.buffer.get());
+            rooted!(in(*cx) let view = construct_view(
+                cx,
+                ViewType::Uint8,
+                buffer.handle(),
+                entry.byte_offset,
+                entry.byte_length,
+            ));
+            rooted!(in(*cx) let chunk = ObjectValue(view.get()));
+            return read_request.chunk_steps(chunk.handle());
+        }
+
+        // Step 5.
+        if let Some(auto_allocate_chunk_size) = self.auto_allocate_chunk_size {
+            let size = auto_allocate_chunk_size as usize;
+            rooted!(in(*cx) let mut buffer = ptr::null_mut::<JSObject>());
+            let created = unsafe {
+                ArrayBuffer::create(*cx, CreateWith::Length(size as u32), buffer.handle_mut())
+            };
+            if created.is_err() {
+                unsafe { JS_ClearPendingException(*cx) };
+                rooted!(in(*cx) let mut error = UndefinedValue());
+                unsafe {
+                    Error::Range("The chunk cannot be allocated".to_owned()).to_jsval(
+                        *cx,
+                        &global,
+                        error.handle_mut(),
+                    );
+                }
+                return read_request.error_steps(error.handle());
+            }
+            self.pending_pull_intos
+                .borrow_mut()
+                .push_back(PullIntoDescriptor {
+                    buffer: Heap::boxed(buffer.get()),
+                    buffer_byte_length: size,
+                    byte_offset: 0,
+                    byte_length: size,
+                    bytes_filled: 0,
+                    element_size: 1,
+                    view_type: ViewType::Uint8,
+                    reader_type: ReaderType::Default,
+                });
+        }
+
+        // Steps 6-7.
+        self.stream.add_read_request(read_request);
+        self.call_pull_if_needed();
+    }
+
+    /// <https://streams.spec.whatwg.org/#rbs-controller-private-cancel>
+    pub fn cancel_steps(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+
+        // Steps 1-2.
+        self.clear_pending_pull_intos();
+        self.reset_queue();
+
+        // Steps 3-5.
+        let cancel = match *self.algorithms.borrow() {
+            SourceAlgorithms::Js { ref cancel, .. } => cancel.clone(),
+            _ => None,
+        };
+        self.clear_algorithms();
+        rooted!(in(*cx) let mut cancel_result = UndefinedValue());
+        let succeeded = cancel.map_or(true, |cancel| {
+            self.invoke(&*cancel, Some(reason), cancel_result.handle_mut())
+        });
+        let cancel_promise = promise_from_invoke(&global, succeeded, cancel_result.handle());
+
+        // https://streams.spec.whatwg.org/#readable-stream-cancel step 7.
+        let promise = Promise::new(&global);
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(CancelAlgorithmSettled::new(&promise, true)),
+            Some(CancelAlgorithmSettled::new(&promise, false)),
+        );
+        cancel_promise.append_native_handler(&handler);
+        promise
+    }
+
+    fn source_algorithm_settled(&self, step: SourceStep, fulfilled: bool, value: HandleValue) {
+        if !fulfilled {
+            return self.error(value);
+        }
+        match step {
+            SourceStep::Start => self.started.set(true),
+            SourceStep::Pull => {
+                self.pulling.set(false);
+                if !self.pull_again.replace(false) {
+                    return;
+                }
+            },
+        }
+        self.call_pull_if_needed();
+    }
+}
+
+impl ReadableByteStreamControllerMethods for ReadableByteStreamController {
+    // https://streams.spec.whatwg.org/#rbs-controller-byob-request
+    fn GetByobRequest(&self) -> Option<DomRoot<ReadableStreamBYOBRequest>> {
+        // https://streams.spec.whatwg.org/#abstract-opdef-readablebytestreamcontrollergetbyobrequest
+        if self.byob_request.get().is_none() {
+            let (buffer, byte_offset, byte_length) = match self.pending_pull_intos.borrow().front()
+            {
+                Some(first) => (
+                    first.buffer.get(),
+                    first.byte_offset + first.bytes_filled,
+                    first.byte_length - first.bytes_filled,
+                ),
+                None => return None,
+            };
+            let global = self.global();
+            let cx = global.get_cx();
+            rooted!(in(*cx) let buffer = buffer);
+            rooted!(in(*cx) let view = construct_view(
+                cx,
+                ViewType::Uint8,
+                buffer.handle(),
+                byte_offset,
+                byte_length,
+            ));
+            let byob_request = ReadableStreamBYOBRequest::new(&global, self, view.handle());
+            self.byob_request.set(Some(&byob_request));
+        }
+        self.byob_request.get()
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-desired-size
+    fn GetDesiredSize(&self) -> Option<f64> {
+        self.desired_size()
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-close
+    fn Close(&self) -> ErrorResult {
+        if !self.can_close_or_enqueue() {
+            return Err(Error::Type("The stream cannot be closed".to_owned()));
+        }
+        self.close()
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-enqueue
+    #[allow(unsafe_code)]
+    fn Enqueue(&self, chunk: CustomAutoRooterGuard<ArrayBufferView>) -> ErrorResult {
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let chunk = unsafe { *chunk.underlying_object() });
+
+        // Steps 1-2.
+        if view_byte_length(chunk.get()) == 0 {
+            return Err(Error::Type("The chunk is empty".to_owned()));
+        }
+        rooted!(in(*cx) let buffer = view_buffer(cx, chunk.handle()));
+        if buffer_byte_length(cx, buffer.get()) == 0 {
+            return Err(Error::Type("The buffer of the chunk is empty".to_owned()));
+        }
+
+        // Steps 3-4.
+        if !self.can_close_or_enqueue() {
+            return Err(Error::Type("The stream cannot be enqueued into".to_owned()));
+        }
+
+        // Step 5.
+        self.enqueue(cx, chunk.handle())
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-error
+    fn Error(&self, _cx: JSContext, e: HandleValue) {
+        self.error(e);
+    }
+}
+
+/// Pulls from the underlying source once its start or pull algorithm
+/// settles, or errors the stream if it rejects.
+#[derive(JSTraceable, MallocSizeOf)]
+struct SourceAlgorithmSettled {
+    controller: Dom<ReadableByteStreamController>,
+    step: SourceStep,
+    fulfilled: bool,
+}
+
+impl SourceAlgorithmSettled {
+    fn new(
+        controller: &ReadableByteStreamController,
+        step: SourceStep,
+        fulfilled: bool,
+    ) -> Box<dyn Callback> {
+        Box::new(SourceAlgorithmSettled {
+            controller: Dom::from_ref(controller),
+            step,
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for SourceAlgorithmSettled {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        self.controller
+            .source_algorithm_settled(self.step, self.fulfilled, v);
+    }
+}
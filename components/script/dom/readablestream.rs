@@ -0,0 +1,1031 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::enter_realm;
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::{
+    self, QueuingStrategy, QueuingStrategySize, ReadableStreamGetReaderOptions,
+    ReadableStreamMethods, ReadableStreamReaderMode, ReadableWritablePair, StreamPipeOptions,
+    UnderlyingSource,
+};
+use crate::dom::bindings::codegen::Bindings::ReadableStreamDefaultReaderBinding::ReadableStreamDefaultReaderMethods;
+use crate::dom::bindings::codegen::Bindings::WritableStreamDefaultWriterBinding::WritableStreamDefaultWriterMethods;
+use crate::dom::bindings::codegen::UnionTypes::ReadableStreamDefaultReaderOrReadableStreamBYOBReader;
+use crate::dom::bindings::conversions::ConversionResult;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageport::{CrossRealmTransform, MessagePort};
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablebytestreamcontroller::ReadableByteStreamController;
+use crate::dom::readablestreambyobreader::ReadableStreamBYOBReader;
+use crate::dom::readablestreamdefaultcontroller::{
+    ReadableStreamDefaultController, SourceAlgorithms,
+};
+use crate::dom::readablestreamdefaultreader::{ReadRequest, ReadableStreamDefaultReader};
+use crate::dom::writablestream::{WritableStream, WritableStreamState};
+use crate::dom::writablestreamdefaultwriter::WritableStreamDefaultWriter;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::{Heap, JSContext as RawJSContext, JSObject, MutableHandleObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::{HandleObject, HandleValue};
+use js::typedarray::{ArrayBuffer, CreateWith};
+use net_traits::request::BodyChunkResponse;
+use std::cell::Cell;
+use std::ptr;
+use std::rc::Rc;
+
+/// <https://streams.spec.whatwg.org/#validate-and-normalize-high-water-mark>
+pub fn extract_high_water_mark(strategy: &QueuingStrategy, default: f64) -> Fallible<f64> {
+    let high_water_mark = strategy.highWaterMark.unwrap_or(default);
+    if high_water_mark.is_nan() || high_water_mark < 0. {
+        return Err(Error::Range(
+            "The high water mark must be a non-negative number".to_owned(),
+        ));
+    }
+    Ok(high_water_mark)
+}
+
+/// <https://streams.spec.whatwg.org/#readablestream-state>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum ReadableStreamState {
+    Readable,
+    Closed,
+    Errored,
+}
+
+// https://streams.spec.whatwg.org/#rs-class
+#[dom_struct]
+pub struct ReadableStream {
+    reflector_: Reflector,
+    state: Cell<ReadableStreamState>,
+    /// Undefined until the stream is errored.
+    #[ignore_malloc_size_of = "mozjs"]
+    stored_error: Heap<JSVal>,
+    disturbed: Cell<bool>,
+    /// The controller of a stream that is not a byte stream.
+    controller: MutNullableDom<ReadableStreamDefaultController>,
+    /// The controller of a byte stream.
+    byte_controller: MutNullableDom<ReadableByteStreamController>,
+    reader: MutNullableDom<ReadableStreamDefaultReader>,
+    byob_reader: MutNullableDom<ReadableStreamBYOBReader>,
+    /// The `pipeTo()` from this stream, while it runs.
+    pipe: DomRefCell<Option<Pipe>>,
+}
+
+impl ReadableStream {
+    fn new_inherited() -> ReadableStream {
+        ReadableStream {
+            reflector_: Reflector::new(),
+            state: Cell::new(ReadableStreamState::Readable),
+            stored_error: Heap::default(),
+            disturbed: Cell::new(false),
+            controller: Default::default(),
+            byte_controller: Default::default(),
+            reader: Default::default(),
+            byob_reader: Default::default(),
+            pipe: DomRefCell::new(None),
+        }
+    }
+
+    fn new(global: &GlobalScope) -> DomRoot<ReadableStream> {
+        reflect_dom_object(
+            Box::new(ReadableStream::new_inherited()),
+            global,
+            ReadableStreamBinding::Wrap,
+        )
+    }
+
+    // https://streams.spec.whatwg.org/#rs-constructor
+    pub fn Constructor(
+        cx: JSContext,
+        global: &GlobalScope,
+        underlying_source: Option<*mut JSObject>,
+        strategy: &QueuingStrategy,
+    ) -> Fallible<DomRoot<ReadableStream>> {
+        // Step 1.
+        rooted!(in(*cx) let source = underlying_source.unwrap_or(ptr::null_mut()));
+        rooted!(in(*cx) let source_value = if source.is_null() {
+            UndefinedValue()
+        } else {
+            ObjectValue(source.get())
+        });
+
+        // Step 2.
+        let source_dict = match UnderlyingSource::new(cx, source_value.handle()) {
+            Ok(ConversionResult::Success(dict)) => dict,
+            Ok(ConversionResult::Failure(error)) => {
+                return Err(Error::Type(error.into_owned()));
+            },
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        // Step 4.
+        if source_dict.type_.is_some() {
+            // Step 4.1.
+            if strategy.size.is_some() {
+                return Err(Error::Range(
+                    "A byte stream cannot have a size algorithm".to_owned(),
+                ));
+            }
+
+            // Steps 4.2-4.3.
+            let high_water_mark = extract_high_water_mark(strategy, 0.)?;
+            let stream = ReadableStream::new(global);
+            ReadableByteStreamController::set_up_from_underlying_source(
+                cx,
+                global,
+                &stream,
+                source.handle(),
+                &source_dict,
+                high_water_mark,
+            )?;
+            return Ok(stream);
+        }
+
+        // Steps 5.1-5.2.
+        let high_water_mark = extract_high_water_mark(strategy, 1.)?;
+
+        // Step 5.3.
+        let stream = ReadableStream::new(global);
+        let algorithms = SourceAlgorithms::Js {
+            pull: source_dict.pull.clone(),
+            cancel: source_dict.cancel.clone(),
+        };
+        ReadableStreamDefaultController::set_up(
+            cx,
+            global,
+            &stream,
+            source.handle(),
+            source_dict.start.clone(),
+            algorithms,
+            strategy.size.clone(),
+            high_water_mark,
+        )?;
+        Ok(stream)
+    }
+
+    /// Creates a stream whose algorithms are implemented natively, which
+    /// starts once `start_promise` settles,
+    /// <https://streams.spec.whatwg.org/#create-readable-stream>
+    #[allow(unrooted_must_root)]
+    pub fn new_with_algorithms(
+        global: &GlobalScope,
+        start_promise: &Promise,
+        algorithms: SourceAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        high_water_mark: f64,
+    ) -> DomRoot<ReadableStream> {
+        let stream = ReadableStream::new(global);
+        ReadableStreamDefaultController::set_up_with_start_promise(
+            global,
+            &stream,
+            start_promise,
+            algorithms,
+            size,
+            high_water_mark,
+        );
+        stream
+    }
+
+    /// Creates a stream whose chunks are posted to `port` from another realm,
+    /// <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformreadable>
+    pub fn new_cross_realm(global: &GlobalScope, port: &MessagePort) -> DomRoot<ReadableStream> {
+        // Steps 6 and 9-10.
+        let start_promise = Promise::new(global);
+        start_promise.resolve_native(&());
+        let stream = ReadableStream::new_with_algorithms(
+            global,
+            &start_promise,
+            SourceAlgorithms::CrossRealm {
+                port: Dom::from_ref(port),
+            },
+            None,
+            0.,
+        );
+
+        // Steps 3-5.
+        port.set_cross_realm_transform(CrossRealmTransform::Readable(Dom::from_ref(
+            &*stream.controller(),
+        )));
+        stream
+    }
+
+    /// Creates a byte stream whose bytes are enqueued by Rust code, through
+    /// `enqueue_native`, `close_native` and `error_native`, so that a BYOB
+    /// reader can read them straight into its own buffers.
+    pub fn new_with_native_source(global: &GlobalScope) -> DomRoot<ReadableStream> {
+        let stream = ReadableStream::new(global);
+        ReadableByteStreamController::set_up_native(global, &stream);
+        stream
+    }
+
+    /// Creates a closed stream whose only chunk is a `Uint8Array` of `bytes`,
+    /// or which has no chunk at all if `bytes` is empty.
+    pub fn new_from_bytes(global: &GlobalScope, bytes: Vec<u8>) -> DomRoot<ReadableStream> {
+        let stream = ReadableStream::new_with_native_source(global);
+        if !bytes.is_empty() {
+            stream.enqueue_native(bytes);
+        }
+        stream.close_native();
+        stream
+    }
+
+    /// Enqueues `bytes` into a stream created with `new_with_native_source`.
+    #[allow(unsafe_code)]
+    pub fn enqueue_native(&self, bytes: Vec<u8>) {
+        let controller = self.byte_controller();
+        if !controller.can_close_or_enqueue() {
+            return;
+        }
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*global);
+        rooted!(in(*cx) let mut buffer = ptr::null_mut::<JSObject>());
+        unsafe {
+            assert!(
+                ArrayBuffer::create(*cx, CreateWith::Slice(&bytes), buffer.handle_mut()).is_ok()
+            );
+        }
+        // No script holds the buffer, so it does not need to be transferred,
+        // and the buffers of the pending reads cannot have been detached.
+        let _ = controller.enqueue_transferred(cx, buffer.handle(), 0, bytes.len());
+    }
+
+    /// Closes a stream created with `new_with_native_source`, once all its
+    /// bytes have been enqueued.
+    pub fn close_native(&self) {
+        let controller = self.byte_controller();
+        if controller.can_close_or_enqueue() {
+            // Only a BYOB reader can leave a partially filled element, and
+            // the views of the reads of native streams are filled whole.
+            let _ = controller.close();
+        }
+    }
+
+    /// Errors a stream created with `new_with_native_source` with `error`.
+    #[allow(unsafe_code)]
+    pub fn error_native(&self, error: Error) {
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*global);
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        unsafe {
+            error.to_jsval(*cx, &global, value.handle_mut());
+        }
+        self.error_native_with_value(value.handle());
+    }
+
+    /// Errors a stream created with `new_with_native_source` with the JS
+    /// value `error`.
+    pub fn error_native_with_value(&self, error: HandleValue) {
+        self.byte_controller().error(error);
+    }
+
+    /// Reads all the chunks enqueued so far into a stream created with
+    /// `new_with_native_source`, which disturbs it.
+    pub fn take_native_bytes(&self) -> Vec<u8> {
+        self.disturbed.set(true);
+        self.byte_controller().take_queued_bytes()
+    }
+
+    /// Reads the next chunk of the body of a request being uploaded, which
+    /// is sent to the net component with `sender`.
+    pub fn read_upload_chunk(&self, sender: IpcSender<BodyChunkResponse>) {
+        self.read(ReadRequest::Upload {
+            source: Dom::from_ref(self),
+            sender,
+        });
+    }
+
+    /// Cancels the body of a request being uploaded, once the net component
+    /// reads no more of it.
+    pub fn cancel_upload(&self) {
+        let cx = self.global().get_cx();
+        let _ac = enter_realm(self);
+        rooted!(in(*cx) let reason = UndefinedValue());
+        self.cancel(cx, reason.handle());
+    }
+
+    pub fn state(&self) -> ReadableStreamState {
+        self.state.get()
+    }
+
+    pub fn stored_error(&self) -> JSVal {
+        self.stored_error.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#is-readable-stream-disturbed>
+    pub fn is_disturbed(&self) -> bool {
+        self.disturbed.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#is-readable-stream-locked>
+    pub fn is_locked(&self) -> bool {
+        self.reader.get().is_some() || self.byob_reader.get().is_some()
+    }
+
+    /// Whether the controller of the stream is a `ReadableByteStreamController`.
+    pub fn is_byte_stream(&self) -> bool {
+        self.byte_controller.get().is_some()
+    }
+
+    /// The controller of a stream that is not a byte stream.
+    pub fn controller(&self) -> DomRoot<ReadableStreamDefaultController> {
+        self.controller
+            .get()
+            .expect("A stream that is not a byte stream has a default controller.")
+    }
+
+    pub fn set_controller(&self, controller: &ReadableStreamDefaultController) {
+        self.controller.set(Some(controller));
+    }
+
+    fn byte_controller(&self) -> DomRoot<ReadableByteStreamController> {
+        self.byte_controller
+            .get()
+            .expect("A byte stream has a byte controller.")
+    }
+
+    pub fn set_byte_controller(&self, controller: &ReadableByteStreamController) {
+        self.byte_controller.set(Some(controller));
+    }
+
+    pub fn set_reader(&self, reader: Option<&ReadableStreamDefaultReader>) {
+        self.reader.set(reader);
+    }
+
+    pub fn set_byob_reader(&self, reader: Option<&ReadableStreamBYOBReader>) {
+        self.byob_reader.set(reader);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-has-default-reader>
+    pub fn has_default_reader(&self) -> bool {
+        self.reader.get().is_some()
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-has-byob-reader>
+    pub fn has_byob_reader(&self) -> bool {
+        self.byob_reader.get().is_some()
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-get-num-read-requests>
+    pub fn num_read_requests(&self) -> usize {
+        self.reader
+            .get()
+            .map_or(0, |reader| reader.num_read_requests())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-get-num-read-into-requests>
+    pub fn num_read_into_requests(&self) -> usize {
+        self.byob_reader
+            .get()
+            .map_or(0, |reader| reader.num_read_into_requests())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-add-read-into-request>
+    pub fn add_read_into_request(&self, promise: Rc<Promise>) {
+        self.byob_reader
+            .get()
+            .expect("Only a stream locked to a BYOB reader has read-into requests.")
+            .add_read_into_request(promise);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-fulfill-read-into-request>
+    pub fn fulfill_read_into_request(&self, chunk: HandleValue, done: bool) {
+        self.byob_reader
+            .get()
+            .expect("Only a stream locked to a BYOB reader has read-into requests.")
+            .fulfill_read_into_request(chunk, done);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-add-read-request>
+    #[allow(unrooted_must_root)]
+    pub fn add_read_request(&self, read_request: ReadRequest) {
+        self.reader
+            .get()
+            .expect("Only a locked stream has read requests.")
+            .add_read_request(read_request);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-fulfill-read-request>
+    pub fn fulfill_read_request(&self, chunk: HandleValue, done: bool) {
+        self.reader
+            .get()
+            .expect("Only a locked stream has read requests.")
+            .fulfill_read_request(chunk, done);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-close>
+    pub fn close(&self) {
+        assert_eq!(self.state.get(), ReadableStreamState::Readable);
+        self.state.set(ReadableStreamState::Closed);
+        if let Some(reader) = self.reader.get() {
+            reader.close();
+        }
+        if let Some(reader) = self.byob_reader.get() {
+            reader.close();
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-error>
+    pub fn error(&self, error: HandleValue) {
+        assert_eq!(self.state.get(), ReadableStreamState::Readable);
+        self.state.set(ReadableStreamState::Errored);
+        self.stored_error.set(error.get());
+        if let Some(reader) = self.reader.get() {
+            reader.error(error);
+        }
+        if let Some(reader) = self.byob_reader.get() {
+            reader.error(error);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-reader-read>
+    #[allow(unrooted_must_root)]
+    pub fn read(&self, read_request: ReadRequest) {
+        self.disturbed.set(true);
+        match self.state.get() {
+            ReadableStreamState::Closed => read_request.close_steps(),
+            ReadableStreamState::Errored => {
+                let cx = self.global().get_cx();
+                rooted!(in(*cx) let error = self.stored_error.get());
+                read_request.error_steps(error.handle());
+            },
+            ReadableStreamState::Readable => match self.byte_controller.get() {
+                Some(controller) => controller.pull_steps(read_request),
+                None => self.controller().pull_steps(read_request),
+            },
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-byob-reader-read>
+    pub fn read_into(&self, cx: JSContext, view: HandleObject, promise: &Rc<Promise>) {
+        self.disturbed.set(true);
+        if self.state.get() == ReadableStreamState::Errored {
+            rooted!(in(*cx) let error = self.stored_error.get());
+            return promise.reject(cx, error.handle());
+        }
+        self.byte_controller().pull_into(cx, view, promise);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-cancel>
+    pub fn cancel(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        // Step 1.
+        self.disturbed.set(true);
+
+        // Steps 2-3.
+        match self.state.get() {
+            ReadableStreamState::Closed => {
+                let promise = Promise::new(&self.global());
+                promise.resolve_native(&());
+                return promise;
+            },
+            ReadableStreamState::Errored => {
+                let promise = Promise::new(&self.global());
+                rooted!(in(*cx) let error = self.stored_error.get());
+                promise.reject(cx, error.handle());
+                return promise;
+            },
+            ReadableStreamState::Readable => {},
+        }
+
+        // Step 4.
+        self.close();
+
+        // Step 5.
+        if let Some(reader) = self.byob_reader.get() {
+            reader.cancel_read_into_requests();
+        }
+
+        // Steps 6-7.
+        match self.byte_controller.get() {
+            Some(controller) => controller.cancel_steps(cx, reason),
+            None => self.controller().cancel_steps(cx, reason),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-pipe-to>
+    #[allow(unrooted_must_root)]
+    pub fn pipe_to(
+        &self,
+        dest: &WritableStream,
+        prevent_close: bool,
+        prevent_abort: bool,
+        prevent_cancel: bool,
+        signal: Option<&AbortSignal>,
+    ) -> Rc<Promise> {
+        let global = self.global();
+
+        // Steps 1-7.
+        let reader =
+            ReadableStreamDefaultReader::new(&global, self).expect("The source is not locked.");
+        let writer = WritableStreamDefaultWriter::new(&global, dest)
+            .expect("The destination is not locked.");
+
+        // Step 8.
+        self.disturbed.set(true);
+
+        // Steps 9-10.
+        let promise = Promise::new(&global);
+        *self.pipe.borrow_mut() = Some(Pipe {
+            dest: Dom::from_ref(dest),
+            reader: Dom::from_ref(&*reader),
+            writer: Dom::from_ref(&*writer),
+            prevent_close,
+            prevent_abort,
+            prevent_cancel,
+            signal: signal.map(Dom::from_ref),
+            promise: promise.clone(),
+            shutting_down: false,
+            actions: ShutdownActions::default(),
+            error: None,
+            pending_actions: 0,
+            pending_write: None,
+        });
+
+        // Step 11.
+        if let Some(signal) = signal {
+            if signal.aborted() {
+                let cx = global.get_cx();
+                rooted!(in(*cx) let reason = signal.reason());
+                self.pipe_abort(reason.handle());
+                return promise;
+            }
+            signal.add_abort_algorithm(AbortAlgorithm::PipeTo {
+                source: Dom::from_ref(self),
+            });
+        }
+
+        // Step 14.
+        self.pipe_react(&reader.Closed(), PipeStep::StateChanged);
+        self.pipe_react(&writer.Closed(), PipeStep::StateChanged);
+        self.pipe_check_state();
+        self.pipe_wait_for_writer();
+        promise
+    }
+
+    fn pipe_react(&self, promise: &Promise, step: PipeStep) {
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(PipeReaction::new(self, step, true)),
+            Some(PipeReaction::new(self, step, false)),
+        );
+        promise.append_native_handler(&handler);
+    }
+
+    fn pipe_reaction(&self, step: PipeStep, fulfilled: bool, value: HandleValue) {
+        match step {
+            PipeStep::WriterReady if fulfilled => self.pipe_read(),
+            PipeStep::WriterReady | PipeStep::StateChanged => self.pipe_check_state(),
+            PipeStep::PendingWriteSettled => self.pipe_run_shutdown_actions(),
+            PipeStep::ActionSettled => self.pipe_action_settled(fulfilled, value),
+        }
+    }
+
+    /// Waits until the destination is ready for another chunk, which applies
+    /// its backpressure to the source.
+    fn pipe_wait_for_writer(&self) {
+        let writer = match *self.pipe.borrow() {
+            Some(ref pipe) if !pipe.shutting_down => DomRoot::from_ref(&*pipe.writer),
+            _ => return,
+        };
+        self.pipe_react(&writer.Ready(), PipeStep::WriterReady);
+    }
+
+    fn pipe_read(&self) {
+        match *self.pipe.borrow() {
+            Some(ref pipe) if !pipe.shutting_down => {},
+            _ => return,
+        }
+        self.read(ReadRequest::Pipe {
+            source: Dom::from_ref(self),
+        });
+    }
+
+    /// The chunk steps of the read requests of a `pipeTo()`, which write
+    /// `chunk` to the destination.
+    pub fn pipe_chunk(&self, chunk: HandleValue) {
+        let writer = match *self.pipe.borrow() {
+            Some(ref pipe) => DomRoot::from_ref(&*pipe.writer),
+            None => return,
+        };
+        let write = writer.write(self.global().get_cx(), chunk);
+        if let Some(ref mut pipe) = *self.pipe.borrow_mut() {
+            pipe.pending_write = Some(write);
+        }
+        self.pipe_wait_for_writer();
+    }
+
+    /// Shuts down a `pipeTo()` if its source or destination closed or
+    /// errored, checking the conditions in the order of
+    /// <https://streams.spec.whatwg.org/#readable-stream-pipe-to> step 14.
+    #[allow(unsafe_code)]
+    pub fn pipe_check_state(&self) {
+        let (dest, prevent_close, prevent_abort, prevent_cancel) = match *self.pipe.borrow() {
+            Some(ref pipe) if !pipe.shutting_down => (
+                DomRoot::from_ref(&*pipe.dest),
+                pipe.prevent_close,
+                pipe.prevent_abort,
+                pipe.prevent_cancel,
+            ),
+            _ => return,
+        };
+        let global = self.global();
+        let cx = global.get_cx();
+
+        // Errors must be propagated forward.
+        if self.state.get() == ReadableStreamState::Errored {
+            rooted!(in(*cx) let error = self.stored_error.get());
+            let actions = ShutdownActions {
+                abort_dest: !prevent_abort,
+                ..ShutdownActions::default()
+            };
+            return self.pipe_shutdown(actions, Some(error.handle()));
+        }
+
+        // Errors must be propagated backward.
+        if dest.state() == WritableStreamState::Errored {
+            rooted!(in(*cx) let error = dest.stored_error());
+            let actions = ShutdownActions {
+                cancel_source: !prevent_cancel,
+                ..ShutdownActions::default()
+            };
+            return self.pipe_shutdown(actions, Some(error.handle()));
+        }
+
+        // Closing must be propagated forward.
+        if self.state.get() == ReadableStreamState::Closed {
+            let actions = ShutdownActions {
+                close_dest: !prevent_close,
+                ..ShutdownActions::default()
+            };
+            return self.pipe_shutdown(actions, None);
+        }
+
+        // Closing must be propagated backward.
+        if dest.close_queued_or_in_flight() || dest.state() == WritableStreamState::Closed {
+            rooted!(in(*cx) let mut error = UndefinedValue());
+            unsafe {
+                Error::Type("The destination is closed".to_owned()).to_jsval(
+                    *cx,
+                    &global,
+                    error.handle_mut(),
+                );
+            }
+            let actions = ShutdownActions {
+                cancel_source: !prevent_cancel,
+                ..ShutdownActions::default()
+            };
+            self.pipe_shutdown(actions, Some(error.handle()));
+        }
+    }
+
+    /// The abort algorithm of the signal of a `pipeTo()`.
+    pub fn pipe_abort(&self, reason: HandleValue) {
+        let actions = match *self.pipe.borrow() {
+            Some(ref pipe) => ShutdownActions {
+                abort_dest: !pipe.prevent_abort &&
+                    pipe.dest.state() == WritableStreamState::Writable,
+                cancel_source: !pipe.prevent_cancel &&
+                    self.state.get() == ReadableStreamState::Readable,
+                close_dest: false,
+            },
+            None => return,
+        };
+        self.pipe_shutdown(actions, Some(reason));
+    }
+
+    /// <https://streams.spec.whatwg.org/#rs-pipeTo-shutdown-with-action>
+    fn pipe_shutdown(&self, actions: ShutdownActions, error: Option<HandleValue>) {
+        let (dest, pending_write) = {
+            let mut state = self.pipe.borrow_mut();
+            let pipe = match *state {
+                Some(ref mut pipe) if !pipe.shutting_down => pipe,
+                _ => return,
+            };
+
+            // Step 2.
+            pipe.shutting_down = true;
+            pipe.actions = actions;
+            pipe.error = error.map(|error| Heap::boxed(error.get()));
+            (DomRoot::from_ref(&*pipe.dest), pipe.pending_write.clone())
+        };
+
+        // Step 3.
+        if dest.state() == WritableStreamState::Writable && !dest.close_queued_or_in_flight() {
+            if let Some(pending_write) = pending_write {
+                if !pending_write.is_fulfilled() {
+                    return self.pipe_react(&pending_write, PipeStep::PendingWriteSettled);
+                }
+            }
+        }
+
+        // Step 4.
+        self.pipe_run_shutdown_actions();
+    }
+
+    /// Runs the shutdown actions of a `pipeTo()`, and finalizes it once all
+    /// of them fulfilled.
+    fn pipe_run_shutdown_actions(&self) {
+        let global = self.global();
+        let cx = global.get_cx();
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        let (dest, writer, actions, has_error) = match *self.pipe.borrow() {
+            Some(ref pipe) => {
+                if let Some(ref pipe_error) = pipe.error {
+                    error.set(pipe_error.get());
+                }
+                (
+                    DomRoot::from_ref(&*pipe.dest),
+                    DomRoot::from_ref(&*pipe.writer),
+                    pipe.actions,
+                    pipe.error.is_some(),
+                )
+            },
+            None => return,
+        };
+        let mut promises = vec![];
+        if actions.abort_dest {
+            promises.push(dest.abort(cx, error.handle()));
+        }
+        if actions.cancel_source {
+            promises.push(self.cancel(cx, error.handle()));
+        }
+        if actions.close_dest {
+            promises.push(writer.close_with_error_propagation());
+        }
+        if promises.is_empty() {
+            return self.pipe_finalize(if has_error {
+                Some(error.handle())
+            } else {
+                None
+            });
+        }
+        if let Some(ref mut pipe) = *self.pipe.borrow_mut() {
+            pipe.pending_actions = promises.len();
+        }
+        for promise in promises {
+            self.pipe_react(&promise, PipeStep::ActionSettled);
+        }
+    }
+
+    /// Steps 5-6 of <https://streams.spec.whatwg.org/#rs-pipeTo-shutdown-with-action>.
+    fn pipe_action_settled(&self, fulfilled: bool, value: HandleValue) {
+        if !fulfilled {
+            return self.pipe_finalize(Some(value));
+        }
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        let has_error = match *self.pipe.borrow_mut() {
+            Some(ref mut pipe) => {
+                pipe.pending_actions -= 1;
+                if pipe.pending_actions > 0 {
+                    return;
+                }
+                if let Some(ref pipe_error) = pipe.error {
+                    error.set(pipe_error.get());
+                }
+                pipe.error.is_some()
+            },
+            None => return,
+        };
+        self.pipe_finalize(if has_error {
+            Some(error.handle())
+        } else {
+            None
+        });
+    }
+
+    /// <https://streams.spec.whatwg.org/#rs-pipeTo-finalize>
+    #[allow(unrooted_must_root)]
+    fn pipe_finalize(&self, error: Option<HandleValue>) {
+        let pipe = match self.pipe.borrow_mut().take() {
+            Some(pipe) => pipe,
+            None => return,
+        };
+
+        // Steps 1-2.
+        pipe.writer.release();
+        pipe.reader.release();
+
+        // Step 3.
+        if let Some(ref signal) = pipe.signal {
+            signal.remove_pipe(self);
+        }
+
+        // Steps 4-5.
+        match error {
+            Some(error) => pipe.promise.reject(self.global().get_cx(), error),
+            None => pipe.promise.resolve_native(&()),
+        }
+    }
+}
+
+impl ReadableStreamMethods for ReadableStream {
+    // https://streams.spec.whatwg.org/#rs-locked
+    fn Locked(&self) -> bool {
+        self.is_locked()
+    }
+
+    // https://streams.spec.whatwg.org/#rs-cancel
+    fn Cancel(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        if self.is_locked() {
+            let promise = Promise::new(&self.global());
+            promise.reject_error(Error::Type("The stream is locked".to_owned()));
+            return promise;
+        }
+        self.cancel(cx, reason)
+    }
+
+    // https://streams.spec.whatwg.org/#rs-get-reader
+    fn GetReader(
+        &self,
+        options: &ReadableStreamGetReaderOptions,
+    ) -> Fallible<ReadableStreamDefaultReaderOrReadableStreamBYOBReader> {
+        let global = self.global();
+        Ok(match options.mode {
+            None => {
+                ReadableStreamDefaultReaderOrReadableStreamBYOBReader::ReadableStreamDefaultReader(
+                    ReadableStreamDefaultReader::new(&global, self)?,
+                )
+            },
+            Some(ReadableStreamReaderMode::Byob) => {
+                ReadableStreamDefaultReaderOrReadableStreamBYOBReader::ReadableStreamBYOBReader(
+                    ReadableStreamBYOBReader::new(&global, self)?,
+                )
+            },
+        })
+    }
+
+    // https://streams.spec.whatwg.org/#rs-pipe-through
+    fn PipeThrough(
+        &self,
+        transform: &ReadableWritablePair,
+        options: &StreamPipeOptions,
+    ) -> Fallible<DomRoot<ReadableStream>> {
+        // Step 1.
+        if self.is_locked() {
+            return Err(Error::Type("The stream is locked".to_owned()));
+        }
+
+        // Step 2.
+        if transform.writable.is_locked() {
+            return Err(Error::Type("The writable stream is locked".to_owned()));
+        }
+
+        // Steps 3-5.
+        self.pipe_to(
+            &transform.writable,
+            options.preventClose,
+            options.preventAbort,
+            options.preventCancel,
+            options.signal.as_deref(),
+        );
+
+        // Step 6.
+        Ok(DomRoot::from_ref(&*transform.readable))
+    }
+
+    // https://streams.spec.whatwg.org/#rs-pipe-to
+    fn PipeTo(&self, destination: &WritableStream, options: &StreamPipeOptions) -> Rc<Promise> {
+        // Step 1.
+        if self.is_locked() {
+            let promise = Promise::new(&self.global());
+            promise.reject_error(Error::Type("The stream is locked".to_owned()));
+            return promise;
+        }
+
+        // Step 2.
+        if destination.is_locked() {
+            let promise = Promise::new(&self.global());
+            promise.reject_error(Error::Type("The destination is locked".to_owned()));
+            return promise;
+        }
+
+        // Steps 3-4.
+        self.pipe_to(
+            destination,
+            options.preventClose,
+            options.preventAbort,
+            options.preventCancel,
+            options.signal.as_deref(),
+        )
+    }
+}
+
+impl Transferable for ReadableStream {
+    /// <https://streams.spec.whatwg.org/#rs-transfer>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        // Step 1.
+        if self.is_locked() {
+            return Err(());
+        }
+
+        // Steps 2-3.
+        let global = self.global();
+        let (port1, port2) = MessagePort::new_entangled(&global);
+
+        // Steps 4-5.
+        let writable = WritableStream::new_cross_realm(&global, &port1);
+
+        // Steps 6-7.
+        self.pipe_to(&writable, false, false, false, None);
+
+        // Step 8.
+        port2.transfer(sc_holder)
+    }
+
+    /// <https://streams.spec.whatwg.org/#rs-transfer>
+    fn transfer_receive(
+        owner: &DomRoot<GlobalScope>,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        // Steps 1-2.
+        let port = MessagePort::receive_transferred(owner, sc_holder, extra_data);
+
+        // Step 3.
+        let stream = ReadableStream::new_cross_realm(owner, &port);
+        return_object.set(stream.reflector().rootable().get());
+        Ok(())
+    }
+}
+
+/// The actions to perform when a `pipeTo()` shuts down, before it finalizes.
+#[derive(Clone, Copy, Default, JSTraceable, MallocSizeOf)]
+struct ShutdownActions {
+    abort_dest: bool,
+    cancel_source: bool,
+    close_dest: bool,
+}
+
+/// The state of a running `pipeTo()`.
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+struct Pipe {
+    dest: Dom<WritableStream>,
+    reader: Dom<ReadableStreamDefaultReader>,
+    writer: Dom<WritableStreamDefaultWriter>,
+    prevent_close: bool,
+    prevent_abort: bool,
+    prevent_cancel: bool,
+    signal: Option<Dom<AbortSignal>>,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    shutting_down: bool,
+    actions: ShutdownActions,
+    /// The error to finalize with, if any.
+    #[ignore_malloc_size_of = "mozjs"]
+    error: Option<Box<Heap<JSVal>>>,
+    /// The number of shutdown actions that have not settled yet.
+    pending_actions: usize,
+    /// The last write to the destination, which shutting down waits for.
+    #[ignore_malloc_size_of = "Rc"]
+    pending_write: Option<Rc<Promise>>,
+}
+
+/// The promises that a `pipeTo()` reacts to.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+enum PipeStep {
+    /// The destination is ready for another chunk.
+    WriterReady,
+    /// The source or the destination closed or errored.
+    StateChanged,
+    /// The last write before shutting down settled.
+    PendingWriteSettled,
+    /// A shutdown action settled.
+    ActionSettled,
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct PipeReaction {
+    source: Dom<ReadableStream>,
+    step: PipeStep,
+    fulfilled: bool,
+}
+
+impl PipeReaction {
+    fn new(source: &ReadableStream, step: PipeStep, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(PipeReaction {
+            source: Dom::from_ref(source),
+            step,
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for PipeReaction {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        self.source.pipe_reaction(self.step, self.fulfilled, v);
+    }
+}
@@ -0,0 +1,232 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBYOBReaderBinding::{
+    self, ReadableStreamBYOBReaderMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablebytestreamcontroller::{
+    buffer_byte_length, is_detached, view_buffer, view_byte_length,
+};
+use crate::dom::readablestream::{ReadableStream, ReadableStreamState};
+use crate::dom::readablestreamdefaultreader::ReadableStreamDefaultReader;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsval::UndefinedValue;
+use js::rust::{CustomAutoRooterGuard, HandleValue};
+use js::typedarray::ArrayBufferView;
+use std::collections::VecDeque;
+use std::mem;
+use std::rc::Rc;
+
+// https://streams.spec.whatwg.org/#byob-reader-class
+#[dom_struct]
+pub struct ReadableStreamBYOBReader {
+    reflector_: Reflector,
+    /// Unset once the reader releases its lock.
+    stream: MutNullableDom<ReadableStream>,
+    #[ignore_malloc_size_of = "Rc"]
+    closed_promise: DomRefCell<Rc<Promise>>,
+    /// The promises of the pending `read(view)` calls,
+    /// <https://streams.spec.whatwg.org/#read-into-request>.
+    #[ignore_malloc_size_of = "Rc"]
+    read_into_requests: DomRefCell<VecDeque<Rc<Promise>>>,
+}
+
+impl ReadableStreamBYOBReader {
+    fn new_inherited(closed_promise: Rc<Promise>) -> ReadableStreamBYOBReader {
+        ReadableStreamBYOBReader {
+            reflector_: Reflector::new(),
+            stream: Default::default(),
+            closed_promise: DomRefCell::new(closed_promise),
+            read_into_requests: DomRefCell::new(VecDeque::new()),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-readable-stream-byob-reader>
+    pub fn new(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+    ) -> Fallible<DomRoot<ReadableStreamBYOBReader>> {
+        // Step 1.
+        if stream.is_locked() {
+            return Err(Error::Type("The stream is already locked".to_owned()));
+        }
+
+        // Step 2.
+        if !stream.is_byte_stream() {
+            return Err(Error::Type("The stream is not a byte stream".to_owned()));
+        }
+
+        // Step 3, https://streams.spec.whatwg.org/#readable-stream-reader-generic-initialize
+        let cx = global.get_cx();
+        let closed_promise = Promise::new(global);
+        match stream.state() {
+            ReadableStreamState::Readable => {},
+            ReadableStreamState::Closed => closed_promise.resolve_native(&()),
+            ReadableStreamState::Errored => {
+                rooted!(in(*cx) let error = stream.stored_error());
+                closed_promise.reject(cx, error.handle());
+            },
+        }
+        let reader = reflect_dom_object(
+            Box::new(ReadableStreamBYOBReader::new_inherited(closed_promise)),
+            global,
+            ReadableStreamBYOBReaderBinding::Wrap,
+        );
+        reader.stream.set(Some(stream));
+        stream.set_byob_reader(Some(&reader));
+        Ok(reader)
+    }
+
+    // https://streams.spec.whatwg.org/#byob-reader-constructor
+    pub fn Constructor(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+    ) -> Fallible<DomRoot<ReadableStreamBYOBReader>> {
+        ReadableStreamBYOBReader::new(global, stream)
+    }
+
+    pub fn num_read_into_requests(&self) -> usize {
+        self.read_into_requests.borrow().len()
+    }
+
+    pub fn add_read_into_request(&self, promise: Rc<Promise>) {
+        self.read_into_requests.borrow_mut().push_back(promise);
+    }
+
+    /// Resolves the first pending read with `chunk`.
+    pub fn fulfill_read_into_request(&self, chunk: HandleValue, done: bool) {
+        let promise = self
+            .read_into_requests
+            .borrow_mut()
+            .pop_front()
+            .expect("There is a read-into request to fulfill.");
+        ReadableStreamDefaultReader::resolve_read_request(&promise, chunk, done);
+    }
+
+    /// Resolves the closed promise once the stream closes. The pending reads
+    /// are fulfilled by the controller, with the bytes they were given.
+    pub fn close(&self) {
+        self.closed_promise.borrow().resolve_native(&());
+    }
+
+    /// Resolves the pending reads with `done` once the stream is cancelled,
+    /// step 5 of <https://streams.spec.whatwg.org/#readable-stream-cancel>.
+    pub fn cancel_read_into_requests(&self) {
+        let read_into_requests =
+            mem::replace(&mut *self.read_into_requests.borrow_mut(), VecDeque::new());
+        if read_into_requests.is_empty() {
+            return;
+        }
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let undefined = UndefinedValue());
+        for promise in read_into_requests {
+            ReadableStreamDefaultReader::resolve_read_request(&promise, undefined.handle(), true);
+        }
+    }
+
+    /// Rejects the pending reads and the closed promise once the stream
+    /// errors.
+    pub fn error(&self, error: HandleValue) {
+        let cx = self.global().get_cx();
+        self.closed_promise.borrow().reject(cx, error);
+        let read_into_requests =
+            mem::replace(&mut *self.read_into_requests.borrow_mut(), VecDeque::new());
+        for promise in read_into_requests {
+            promise.reject(cx, error);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-reader-generic-release>
+    fn release(&self) {
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => return,
+        };
+        let error = Error::Type("The reader released its lock".to_owned());
+        if stream.state() == ReadableStreamState::Readable {
+            self.closed_promise.borrow().reject_error(error);
+        } else {
+            let closed_promise = Promise::new(&self.global());
+            closed_promise.reject_error(error);
+            *self.closed_promise.borrow_mut() = closed_promise;
+        }
+        stream.set_byob_reader(None);
+        self.stream.set(None);
+    }
+}
+
+impl ReadableStreamBYOBReaderMethods for ReadableStreamBYOBReader {
+    // https://streams.spec.whatwg.org/#generic-reader-closed
+    fn Closed(&self) -> Rc<Promise> {
+        self.closed_promise.borrow().clone()
+    }
+
+    // https://streams.spec.whatwg.org/#generic-reader-cancel
+    fn Cancel(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        match self.stream.get() {
+            Some(stream) => stream.cancel(cx, reason),
+            None => {
+                let promise = Promise::new(&self.global());
+                promise.reject_error(Error::Type("The reader has no stream".to_owned()));
+                promise
+            },
+        }
+    }
+
+    // https://streams.spec.whatwg.org/#byob-reader-read
+    #[allow(unsafe_code)]
+    fn Read(&self, view: CustomAutoRooterGuard<ArrayBufferView>) -> Rc<Promise> {
+        let global = self.global();
+        let cx = global.get_cx();
+        let promise = Promise::new(&global);
+        rooted!(in(*cx) let view = unsafe { *view.underlying_object() });
+        rooted!(in(*cx) let buffer = view_buffer(cx, view.handle()));
+
+        // Steps 1-3.
+        let error = if view_byte_length(view.get()) == 0 {
+            Some("The view is empty")
+        } else if is_detached(buffer.get()) {
+            Some("The buffer of the view is detached")
+        } else if buffer_byte_length(cx, buffer.get()) == 0 {
+            Some("The buffer of the view is empty")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            promise.reject_error(Error::Type(error.to_owned()));
+            return promise;
+        }
+
+        // Steps 4-6.
+        match self.stream.get() {
+            Some(stream) => stream.read_into(cx, view.handle(), &promise),
+            None => promise.reject_error(Error::Type("The reader has no stream".to_owned())),
+        }
+        promise
+    }
+
+    // https://streams.spec.whatwg.org/#byob-reader-release-lock
+    fn ReleaseLock(&self) -> ErrorResult {
+        // Step 2.
+        if self.stream.get().is_none() {
+            return Ok(());
+        }
+
+        // Step 3.
+        if !self.read_into_requests.borrow().is_empty() {
+            return Err(Error::Type("The reader has pending reads".to_owned()));
+        }
+
+        // Step 4.
+        self.release();
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBYOBRequestBinding::{
+    self, ReadableStreamBYOBRequestMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::readablebytestreamcontroller::{
+    is_detached, view_buffer, ReadableByteStreamController,
+};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::rust::{CustomAutoRooterGuard, HandleObject};
+use js::typedarray::ArrayBufferView;
+use std::ptr::{self, NonNull};
+
+// https://streams.spec.whatwg.org/#rs-byob-request-class
+#[dom_struct]
+pub struct ReadableStreamBYOBRequest {
+    reflector_: Reflector,
+    /// Unset once the request is invalidated.
+    controller: MutNullableDom<ReadableByteStreamController>,
+    /// Null once the request is invalidated.
+    #[ignore_malloc_size_of = "mozjs"]
+    view: Heap<*mut JSObject>,
+}
+
+impl ReadableStreamBYOBRequest {
+    fn new_inherited(controller: &ReadableByteStreamController) -> ReadableStreamBYOBRequest {
+        ReadableStreamBYOBRequest {
+            reflector_: Reflector::new(),
+            controller: MutNullableDom::new(Some(controller)),
+            view: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        controller: &ReadableByteStreamController,
+        view: HandleObject,
+    ) -> DomRoot<ReadableStreamBYOBRequest> {
+        let request = reflect_dom_object(
+            Box::new(ReadableStreamBYOBRequest::new_inherited(controller)),
+            global,
+            ReadableStreamBYOBRequestBinding::Wrap,
+        );
+        request.view.set(view.get());
+        request
+    }
+
+    /// Steps 2-3 of <https://streams.spec.whatwg.org/#readable-byte-stream-controller-invalidate-byob-request>.
+    pub fn invalidate(&self) {
+        self.controller.set(None);
+        self.view.set(ptr::null_mut());
+    }
+
+    /// Returns the controller of a request that is still valid, and whose
+    /// view was not detached.
+    fn valid_controller(&self, cx: JSContext) -> Fallible<DomRoot<ReadableByteStreamController>> {
+        let controller = match self.controller.get() {
+            Some(controller) => controller,
+            None => return Err(Error::Type("The request was invalidated".to_owned())),
+        };
+        rooted!(in(*cx) let view = self.view.get());
+        if is_detached(view_buffer(cx, view.handle())) {
+            return Err(Error::Type(
+                "The view of the request is detached".to_owned(),
+            ));
+        }
+        Ok(controller)
+    }
+}
+
+impl ReadableStreamBYOBRequestMethods for ReadableStreamBYOBRequest {
+    // https://streams.spec.whatwg.org/#rs-byob-request-view
+    fn GetView(&self, _cx: JSContext) -> Option<NonNull<JSObject>> {
+        NonNull::new(self.view.get())
+    }
+
+    // https://streams.spec.whatwg.org/#rs-byob-request-respond
+    fn Respond(&self, bytes_written: u64) -> ErrorResult {
+        let controller = self.valid_controller(self.global().get_cx())?;
+        controller.respond(bytes_written)
+    }
+
+    // https://streams.spec.whatwg.org/#rs-byob-request-respond-with-new-view
+    #[allow(unsafe_code)]
+    fn RespondWithNewView(&self, view: CustomAutoRooterGuard<ArrayBufferView>) -> ErrorResult {
+        let cx = self.global().get_cx();
+        let controller = match self.controller.get() {
+            Some(controller) => controller,
+            None => return Err(Error::Type("The request was invalidated".to_owned())),
+        };
+        rooted!(in(*cx) let view = unsafe { *view.underlying_object() });
+        if is_detached(view_buffer(cx, view.handle())) {
+            return Err(Error::Type("The view is detached".to_owned()));
+        }
+        controller.respond_with_new_view(cx, view.handle())
+    }
+}
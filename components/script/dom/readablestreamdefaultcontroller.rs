@@ -0,0 +1,642 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::{CallSetup, CallbackContainer, ExceptionHandling};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::{
+    QueuingStrategySize, UnderlyingSourceCancelCallback, UnderlyingSourcePullCallback,
+    UnderlyingSourceStartCallback,
+};
+use crate::dom::bindings::codegen::Bindings::ReadableStreamDefaultControllerBinding::{
+    self, ReadableStreamDefaultControllerMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageport::MessagePort;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestream::{ReadableStream, ReadableStreamState};
+use crate::dom::readablestreamdefaultreader::ReadRequest;
+use crate::dom::transformstream::TransformStream;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{HandleValueArray, Heap, JSContext as RawJSContext, JSObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    Call, JS_ClearPendingException, JS_GetPendingException, JS_SetPendingException,
+};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// The pull and cancel algorithms of a controller, which come from its
+/// underlying source.
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum SourceAlgorithms {
+    /// The methods of the underlying source object of the constructor.
+    Js {
+        #[ignore_malloc_size_of = "Rc"]
+        pull: Option<Rc<UnderlyingSourcePullCallback>>,
+        #[ignore_malloc_size_of = "Rc"]
+        cancel: Option<Rc<UnderlyingSourceCancelCallback>>,
+    },
+    /// The readable side of a TransformStream.
+    Transform { stream: Dom<TransformStream> },
+    /// A transfer-received stream, whose chunks are posted to `port`.
+    CrossRealm { port: Dom<MessagePort> },
+    /// A native source, whose chunks are enqueued by Rust code, or cleared
+    /// algorithms.
+    None,
+}
+
+/// Calls `method` of an underlying source, sink or transformer object, with
+/// the object as `this`. Returns false, with the exception in `rval`, if the
+/// method threw.
+#[allow(unsafe_code)]
+pub fn invoke_method<T: CallbackContainer>(
+    this: *mut JSObject,
+    method: &T,
+    arguments: &[JSVal],
+    mut rval: MutableHandleValue,
+) -> bool {
+    let s = CallSetup::new(method, ExceptionHandling::Rethrow);
+    let cx = s.get_context();
+    rooted!(in(*cx) let this = ObjectValue(this));
+    rooted!(in(*cx) let function = ObjectValue(method.callback()));
+    rooted!(in(*cx) let mut result = UndefinedValue());
+    let succeeded = unsafe {
+        let arguments = HandleValueArray::from_rooted_slice(arguments);
+        Call(
+            *cx,
+            this.handle(),
+            function.handle(),
+            &arguments,
+            result.handle_mut(),
+        ) || {
+            JS_GetPendingException(*cx, result.handle_mut());
+            JS_ClearPendingException(*cx);
+            false
+        }
+    };
+    rval.set(result.get());
+    succeeded
+}
+
+/// Returns a promise settled with the outcome of `invoke_method`.
+pub fn promise_from_invoke(
+    global: &GlobalScope,
+    succeeded: bool,
+    result: HandleValue,
+) -> Rc<Promise> {
+    let cx = global.get_cx();
+    let promise = if succeeded {
+        Promise::new_resolved(global, cx, result)
+    } else {
+        Promise::new_rejected(global, cx, result)
+    };
+    promise.expect("Creating a settled promise does not fail.")
+}
+
+/// A chunk in the queue of a controller, with the size that the queuing
+/// strategy gave it.
+#[derive(JSTraceable, MallocSizeOf)]
+struct QueueEntry {
+    #[ignore_malloc_size_of = "mozjs"]
+    value: Box<Heap<JSVal>>,
+    size: f64,
+}
+
+// https://streams.spec.whatwg.org/#rs-default-controller-class
+#[dom_struct]
+pub struct ReadableStreamDefaultController {
+    reflector_: Reflector,
+    stream: Dom<ReadableStream>,
+    /// The `this` of the underlying source methods, or null.
+    #[ignore_malloc_size_of = "mozjs"]
+    underlying_source: Heap<*mut JSObject>,
+    /// <https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-pullalgorithm>
+    /// and <https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-cancelalgorithm>
+    algorithms: DomRefCell<SourceAlgorithms>,
+    /// <https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-strategysizealgorithm>
+    #[ignore_malloc_size_of = "Rc"]
+    strategy_size_algorithm: DomRefCell<Option<Rc<QueuingStrategySize>>>,
+    strategy_hwm: f64,
+    queue: DomRefCell<VecDeque<QueueEntry>>,
+    queue_total_size: Cell<f64>,
+    started: Cell<bool>,
+    close_requested: Cell<bool>,
+    pull_again: Cell<bool>,
+    pulling: Cell<bool>,
+}
+
+impl ReadableStreamDefaultController {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        stream: &ReadableStream,
+        algorithms: SourceAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) -> ReadableStreamDefaultController {
+        ReadableStreamDefaultController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            underlying_source: Heap::default(),
+            algorithms: DomRefCell::new(algorithms),
+            strategy_size_algorithm: DomRefCell::new(size),
+            strategy_hwm,
+            queue: DomRefCell::new(VecDeque::new()),
+            queue_total_size: Cell::new(0.),
+            started: Cell::new(false),
+            close_requested: Cell::new(false),
+            pull_again: Cell::new(false),
+            pulling: Cell::new(false),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn new(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        algorithms: SourceAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) -> DomRoot<ReadableStreamDefaultController> {
+        reflect_dom_object(
+            Box::new(ReadableStreamDefaultController::new_inherited(
+                stream,
+                algorithms,
+                size,
+                strategy_hwm,
+            )),
+            global,
+            ReadableStreamDefaultControllerBinding::Wrap,
+        )
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-readable-stream-default-controller>
+    #[allow(unrooted_must_root, unsafe_code)]
+    pub fn set_up(
+        cx: JSContext,
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        underlying_source: HandleObject,
+        start: Option<Rc<UnderlyingSourceStartCallback>>,
+        algorithms: SourceAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) -> ErrorResult {
+        // Steps 1-8.
+        let controller =
+            ReadableStreamDefaultController::new(global, stream, algorithms, size, strategy_hwm);
+        controller.underlying_source.set(underlying_source.get());
+
+        // Step 9.
+        stream.set_controller(&controller);
+
+        // Step 10.
+        rooted!(in(*cx) let mut start_result = UndefinedValue());
+        if let Some(start) = start {
+            if !controller.invoke(&*start, None, start_result.handle_mut()) {
+                unsafe { JS_SetPendingException(*cx, start_result.handle()) };
+                return Err(Error::JSFailed);
+            }
+        }
+
+        // Steps 11-13.
+        let start_promise = Promise::new_resolved(global, cx, start_result.handle())?;
+        controller.start_when_settled(&start_promise);
+        Ok(())
+    }
+
+    /// Sets up the controller of a stream whose algorithms are implemented
+    /// natively, which starts once `start_promise` settles.
+    #[allow(unrooted_must_root)]
+    pub fn set_up_with_start_promise(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        start_promise: &Promise,
+        algorithms: SourceAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) {
+        let controller =
+            ReadableStreamDefaultController::new(global, stream, algorithms, size, strategy_hwm);
+        stream.set_controller(&controller);
+        controller.start_when_settled(start_promise);
+    }
+
+    fn start_when_settled(&self, start_promise: &Promise) {
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(StartAlgorithmSettled::new(self, true)),
+            Some(StartAlgorithmSettled::new(self, false)),
+        );
+        start_promise.append_native_handler(&handler);
+    }
+
+    /// Invokes `method` of the underlying source, with either `argument` or
+    /// the controller as its only argument.
+    fn invoke<T: CallbackContainer>(
+        &self,
+        method: &T,
+        argument: Option<HandleValue>,
+        rval: MutableHandleValue,
+    ) -> bool {
+        let arguments = [match argument {
+            Some(argument) => argument.get(),
+            None => ObjectValue(self.reflector().get_jsobject().get()),
+        }];
+        invoke_method(self.underlying_source.get(), method, &arguments, rval)
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-call-pull-if-needed>
+    fn call_pull_if_needed(&self) {
+        // Steps 1-2.
+        if !self.should_call_pull() {
+            return;
+        }
+
+        // Step 3.
+        if self.pulling.get() {
+            self.pull_again.set(true);
+            return;
+        }
+
+        // Step 5.
+        self.pulling.set(true);
+
+        // Step 6.
+        let global = self.global();
+        let pull_promise = self.run_pull_algorithm();
+
+        // Steps 7-8.
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(PullAlgorithmSettled::new(self, true)),
+            Some(PullAlgorithmSettled::new(self, false)),
+        );
+        pull_promise.append_native_handler(&handler);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-pullalgorithm>
+    fn run_pull_algorithm(&self) -> Rc<Promise> {
+        let global = self.global();
+        let cx = global.get_cx();
+        let (pull, transform, port) = match *self.algorithms.borrow() {
+            SourceAlgorithms::Js { ref pull, .. } => (pull.clone(), None, None),
+            SourceAlgorithms::Transform { ref stream } => {
+                (None, Some(DomRoot::from_ref(&**stream)), None)
+            },
+            SourceAlgorithms::CrossRealm { ref port } => {
+                (None, None, Some(DomRoot::from_ref(&**port)))
+            },
+            SourceAlgorithms::None => (None, None, None),
+        };
+        if let Some(transform) = transform {
+            return transform.source_pull_algorithm();
+        }
+        if let Some(port) = port {
+            return port.cross_realm_pull();
+        }
+        rooted!(in(*cx) let mut pull_result = UndefinedValue());
+        let succeeded = pull.map_or(true, |pull| {
+            self.invoke(&*pull, None, pull_result.handle_mut())
+        });
+        promise_from_invoke(&global, succeeded, pull_result.handle())
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-should-call-pull>
+    fn should_call_pull(&self) -> bool {
+        if !self.can_close_or_enqueue() || !self.started.get() {
+            return false;
+        }
+        // Pulling from a source without a pull algorithm does nothing.
+        match *self.algorithms.borrow() {
+            SourceAlgorithms::Js { pull: None, .. } | SourceAlgorithms::None => return false,
+            _ => {},
+        }
+        if self.stream.is_locked() && self.stream.num_read_requests() > 0 {
+            return true;
+        }
+        self.desired_size().map_or(false, |size| size > 0.)
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-clear-algorithms>
+    fn clear_algorithms(&self) {
+        *self.algorithms.borrow_mut() = SourceAlgorithms::None;
+        self.strategy_size_algorithm.borrow_mut().take();
+    }
+
+    /// <https://streams.spec.whatwg.org/#reset-queue>
+    fn reset_queue(&self) {
+        self.queue.borrow_mut().clear();
+        self.queue_total_size.set(0.);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-can-close-or-enqueue>
+    pub fn can_close_or_enqueue(&self) -> bool {
+        !self.close_requested.get() && self.stream.state() == ReadableStreamState::Readable
+    }
+
+    /// <https://streams.spec.whatwg.org/#rs-default-controller-has-backpressure>
+    pub fn has_backpressure(&self) -> bool {
+        !self.should_call_pull()
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-get-desired-size>
+    pub fn desired_size(&self) -> Option<f64> {
+        match self.stream.state() {
+            ReadableStreamState::Errored => None,
+            ReadableStreamState::Closed => Some(0.),
+            ReadableStreamState::Readable => Some(self.strategy_hwm - self.queue_total_size.get()),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-close>
+    pub fn close(&self) {
+        // Steps 1-3.
+        self.close_requested.set(true);
+
+        // Step 4.
+        if self.queue.borrow().is_empty() {
+            self.clear_algorithms();
+            self.stream.close();
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-enqueue>
+    #[allow(unsafe_code)]
+    pub fn enqueue(&self, cx: JSContext, chunk: HandleValue) -> ErrorResult {
+        // Step 1.
+        if !self.can_close_or_enqueue() {
+            return Ok(());
+        }
+
+        // Step 3.
+        if self.stream.is_locked() && self.stream.num_read_requests() > 0 {
+            self.stream.fulfill_read_request(chunk, false);
+        } else {
+            // Step 4.1.
+            let size_algorithm = self.strategy_size_algorithm.borrow().clone();
+            let size = match size_algorithm {
+                Some(size_algorithm) => {
+                    match size_algorithm.Call__(chunk, ExceptionHandling::Rethrow) {
+                        Ok(size) => size,
+                        // Step 4.2.
+                        Err(_) => {
+                            rooted!(in(*cx) let mut error = UndefinedValue());
+                            unsafe {
+                                JS_GetPendingException(*cx, error.handle_mut());
+                                JS_ClearPendingException(*cx);
+                            }
+                            return Err(self.error_and_rethrow(cx, error.handle()));
+                        },
+                    }
+                },
+                None => 1.,
+            };
+
+            // Steps 4.3-4.4, https://streams.spec.whatwg.org/#enqueue-value-with-size
+            if !size.is_finite() || size < 0. {
+                let error = Error::Range("The chunk size must be a non-negative number".to_owned());
+                rooted!(in(*cx) let mut error_value = UndefinedValue());
+                unsafe { error.to_jsval(*cx, &self.global(), error_value.handle_mut()) };
+                return Err(self.error_and_rethrow(cx, error_value.handle()));
+            }
+            self.queue.borrow_mut().push_back(QueueEntry {
+                value: Heap::boxed(chunk.get()),
+                size,
+            });
+            self.queue_total_size
+                .set(self.queue_total_size.get() + size);
+        }
+
+        // Step 5.
+        self.call_pull_if_needed();
+        Ok(())
+    }
+
+    /// Errors the stream with `error`, and sets it as the pending exception
+    /// so that the caller rethrows it.
+    #[allow(unsafe_code)]
+    fn error_and_rethrow(&self, cx: JSContext, error: HandleValue) -> Error {
+        self.error(error);
+        unsafe { JS_SetPendingException(*cx, error) };
+        Error::JSFailed
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-error>
+    pub fn error(&self, error: HandleValue) {
+        // Step 2.
+        if self.stream.state() != ReadableStreamState::Readable {
+            return;
+        }
+
+        // Steps 3-5.
+        self.reset_queue();
+        self.clear_algorithms();
+        self.stream.error(error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#rs-default-controller-private-pull>
+    #[allow(unrooted_must_root)]
+    pub fn pull_steps(&self, read_request: ReadRequest) {
+        // Step 2.
+        let entry = self.queue.borrow_mut().pop_front();
+        let entry = match entry {
+            Some(entry) => entry,
+            // Step 3.
+            None => {
+                self.stream.add_read_request(read_request);
+                self.call_pull_if_needed();
+                return;
+            },
+        };
+        let total_size = self.queue_total_size.get() - entry.size;
+        self.queue_total_size
+            .set(if total_size < 0. { 0. } else { total_size });
+
+        // Steps 2.2-2.3.
+        if self.close_requested.get() && self.queue.borrow().is_empty() {
+            self.clear_algorithms();
+            self.stream.close();
+        } else {
+            self.call_pull_if_needed();
+        }
+
+        // Step 2.4.
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let chunk = entry.value.get());
+        read_request.chunk_steps(chunk.handle());
+    }
+
+    /// <https://streams.spec.whatwg.org/#rs-default-controller-private-cancel>
+    pub fn cancel_steps(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+
+        // Step 1.
+        self.reset_queue();
+
+        // Steps 2-3.
+        let (cancel, transform, port) = match *self.algorithms.borrow() {
+            SourceAlgorithms::Js { ref cancel, .. } => (cancel.clone(), None, None),
+            SourceAlgorithms::Transform { ref stream } => {
+                (None, Some(DomRoot::from_ref(&**stream)), None)
+            },
+            SourceAlgorithms::CrossRealm { ref port } => {
+                (None, None, Some(DomRoot::from_ref(&**port)))
+            },
+            SourceAlgorithms::None => (None, None, None),
+        };
+        self.clear_algorithms();
+        let cancel_promise = match (transform, port) {
+            (Some(transform), _) => transform.source_cancel_algorithm(reason),
+            (None, Some(port)) => port.cross_realm_cancel(reason),
+            (None, None) => {
+                rooted!(in(*cx) let mut cancel_result = UndefinedValue());
+                let succeeded = cancel.map_or(true, |cancel| {
+                    self.invoke(&*cancel, Some(reason), cancel_result.handle_mut())
+                });
+                promise_from_invoke(&global, succeeded, cancel_result.handle())
+            },
+        };
+
+        // https://streams.spec.whatwg.org/#readable-stream-cancel step 6.
+        let promise = Promise::new(&global);
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(CancelAlgorithmSettled::new(&promise, true)),
+            Some(CancelAlgorithmSettled::new(&promise, false)),
+        );
+        cancel_promise.append_native_handler(&handler);
+        promise
+    }
+
+    fn start_algorithm_settled(&self, fulfilled: bool, value: HandleValue) {
+        if !fulfilled {
+            return self.error(value);
+        }
+        self.started.set(true);
+        self.call_pull_if_needed();
+    }
+
+    fn pull_algorithm_settled(&self, fulfilled: bool, value: HandleValue) {
+        if !fulfilled {
+            return self.error(value);
+        }
+        self.pulling.set(false);
+        if self.pull_again.replace(false) {
+            self.call_pull_if_needed();
+        }
+    }
+}
+
+impl ReadableStreamDefaultControllerMethods for ReadableStreamDefaultController {
+    // https://streams.spec.whatwg.org/#rs-default-controller-desired-size
+    fn GetDesiredSize(&self) -> Option<f64> {
+        self.desired_size()
+    }
+
+    // https://streams.spec.whatwg.org/#rs-default-controller-close
+    fn Close(&self) -> ErrorResult {
+        if !self.can_close_or_enqueue() {
+            return Err(Error::Type("The stream cannot be closed".to_owned()));
+        }
+        self.close();
+        Ok(())
+    }
+
+    // https://streams.spec.whatwg.org/#rs-default-controller-enqueue
+    fn Enqueue(&self, cx: JSContext, chunk: HandleValue) -> ErrorResult {
+        if !self.can_close_or_enqueue() {
+            return Err(Error::Type("The stream cannot be enqueued into".to_owned()));
+        }
+        self.enqueue(cx, chunk)
+    }
+
+    // https://streams.spec.whatwg.org/#rs-default-controller-error
+    fn Error(&self, _cx: JSContext, error: HandleValue) {
+        self.error(error);
+    }
+}
+
+/// Starts pulling from the underlying source once its start algorithm
+/// settles, or errors the stream if it rejects.
+#[derive(JSTraceable, MallocSizeOf)]
+struct StartAlgorithmSettled {
+    controller: Dom<ReadableStreamDefaultController>,
+    fulfilled: bool,
+}
+
+impl StartAlgorithmSettled {
+    fn new(controller: &ReadableStreamDefaultController, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(StartAlgorithmSettled {
+            controller: Dom::from_ref(controller),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for StartAlgorithmSettled {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        self.controller.start_algorithm_settled(self.fulfilled, v);
+    }
+}
+
+/// Pulls again from the underlying source if a pull was requested while its
+/// pull algorithm ran, or errors the stream if it rejects.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PullAlgorithmSettled {
+    controller: Dom<ReadableStreamDefaultController>,
+    fulfilled: bool,
+}
+
+impl PullAlgorithmSettled {
+    fn new(controller: &ReadableStreamDefaultController, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(PullAlgorithmSettled {
+            controller: Dom::from_ref(controller),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for PullAlgorithmSettled {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        self.controller.pull_algorithm_settled(self.fulfilled, v);
+    }
+}
+
+/// Settles the promise returned by `cancel()` once the cancel algorithm of
+/// the underlying source settles.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct CancelAlgorithmSettled {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    fulfilled: bool,
+}
+
+impl CancelAlgorithmSettled {
+    pub fn new(promise: &Rc<Promise>, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(CancelAlgorithmSettled {
+            promise: promise.clone(),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for CancelAlgorithmSettled {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut RawJSContext, v: HandleValue) {
+        if self.fulfilled {
+            self.promise.resolve_native(&());
+        } else {
+            let cx = unsafe { JSContext::from_ptr(cx) };
+            self.promise.reject(cx, v);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::enter_realm;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamDefaultReaderBinding::{
+    self, ReadableStreamDefaultReaderMethods, ReadableStreamReadResult,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::{ReadableStream, ReadableStreamState};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::conversions::ToJSValConvertible;
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
+use js::typedarray::Uint8Array;
+use net_traits::request::BodyChunkResponse;
+use std::collections::VecDeque;
+use std::mem;
+use std::rc::Rc;
+
+/// <https://streams.spec.whatwg.org/#read-request>
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum ReadRequest {
+    /// A `read()` call, whose promise is resolved with a read result.
+    Promise {
+        #[ignore_malloc_size_of = "Rc"]
+        promise: Rc<Promise>,
+    },
+    /// A read of the source stream of a `pipeTo()`.
+    Pipe { source: Dom<ReadableStream> },
+    /// A read of the body of a request being uploaded, whose chunk is sent
+    /// to the net component.
+    Upload {
+        source: Dom<ReadableStream>,
+        #[ignore_malloc_size_of = "channels are hard"]
+        sender: IpcSender<BodyChunkResponse>,
+    },
+}
+
+impl ReadRequest {
+    /// <https://streams.spec.whatwg.org/#read-request-chunk-steps>
+    pub fn chunk_steps(&self, chunk: HandleValue) {
+        match *self {
+            ReadRequest::Promise { ref promise } => {
+                ReadableStreamDefaultReader::resolve_read_request(promise, chunk, false)
+            },
+            ReadRequest::Pipe { ref source } => source.pipe_chunk(chunk),
+            ReadRequest::Upload {
+                ref source,
+                ref sender,
+            } => {
+                // Only the bytes of a `Uint8Array` can be sent, any other
+                // chunk fails the request.
+                let cx = source.global().get_cx();
+                let bytes = if chunk.is_object() {
+                    typedarray!(in(*cx) let array: Uint8Array = chunk.to_object());
+                    array.map(|array| array.to_vec()).ok()
+                } else {
+                    None
+                };
+                let _ = sender.send(match bytes {
+                    Some(bytes) => BodyChunkResponse::Chunk(bytes),
+                    None => BodyChunkResponse::Error,
+                });
+            },
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#read-request-close-steps>
+    pub fn close_steps(&self) {
+        match *self {
+            ReadRequest::Promise { ref promise } => {
+                let cx = promise.global().get_cx();
+                rooted!(in(*cx) let undefined = UndefinedValue());
+                ReadableStreamDefaultReader::resolve_read_request(
+                    promise,
+                    undefined.handle(),
+                    true,
+                );
+            },
+            ReadRequest::Pipe { ref source } => source.pipe_check_state(),
+            ReadRequest::Upload { ref sender, .. } => {
+                let _ = sender.send(BodyChunkResponse::Done);
+            },
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#read-request-error-steps>
+    pub fn error_steps(&self, error: HandleValue) {
+        match *self {
+            ReadRequest::Promise { ref promise } => {
+                promise.reject(promise.global().get_cx(), error)
+            },
+            ReadRequest::Pipe { ref source } => source.pipe_check_state(),
+            ReadRequest::Upload { ref sender, .. } => {
+                let _ = sender.send(BodyChunkResponse::Error);
+            },
+        }
+    }
+}
+
+// https://streams.spec.whatwg.org/#default-reader-class
+#[dom_struct]
+pub struct ReadableStreamDefaultReader {
+    reflector_: Reflector,
+    /// Unset once the reader releases its lock.
+    stream: MutNullableDom<ReadableStream>,
+    #[ignore_malloc_size_of = "Rc"]
+    closed_promise: DomRefCell<Rc<Promise>>,
+    read_requests: DomRefCell<VecDeque<ReadRequest>>,
+}
+
+impl ReadableStreamDefaultReader {
+    fn new_inherited(closed_promise: Rc<Promise>) -> ReadableStreamDefaultReader {
+        ReadableStreamDefaultReader {
+            reflector_: Reflector::new(),
+            stream: Default::default(),
+            closed_promise: DomRefCell::new(closed_promise),
+            read_requests: DomRefCell::new(VecDeque::new()),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-readable-stream-default-reader>
+    pub fn new(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+    ) -> Fallible<DomRoot<ReadableStreamDefaultReader>> {
+        // Step 1.
+        if stream.is_locked() {
+            return Err(Error::Type("The stream is already locked".to_owned()));
+        }
+
+        // Step 2, https://streams.spec.whatwg.org/#readable-stream-reader-generic-initialize
+        let cx = global.get_cx();
+        let closed_promise = Promise::new(global);
+        match stream.state() {
+            ReadableStreamState::Readable => {},
+            ReadableStreamState::Closed => closed_promise.resolve_native(&()),
+            ReadableStreamState::Errored => {
+                rooted!(in(*cx) let error = stream.stored_error());
+                closed_promise.reject(cx, error.handle());
+            },
+        }
+        let reader = reflect_dom_object(
+            Box::new(ReadableStreamDefaultReader::new_inherited(closed_promise)),
+            global,
+            ReadableStreamDefaultReaderBinding::Wrap,
+        );
+        reader.stream.set(Some(stream));
+        stream.set_reader(Some(&reader));
+        Ok(reader)
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-constructor
+    pub fn Constructor(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+    ) -> Fallible<DomRoot<ReadableStreamDefaultReader>> {
+        ReadableStreamDefaultReader::new(global, stream)
+    }
+
+    pub fn num_read_requests(&self) -> usize {
+        self.read_requests.borrow().len()
+    }
+
+    pub fn add_read_request(&self, read_request: ReadRequest) {
+        self.read_requests.borrow_mut().push_back(read_request);
+    }
+
+    /// Runs the chunk steps, or the close steps if `done`, of the first
+    /// pending read request.
+    pub fn fulfill_read_request(&self, chunk: HandleValue, done: bool) {
+        let read_request = self
+            .read_requests
+            .borrow_mut()
+            .pop_front()
+            .expect("There is a read request to fulfill.");
+        if done {
+            read_request.close_steps();
+        } else {
+            read_request.chunk_steps(chunk);
+        }
+    }
+
+    /// Resolves `promise` with a `{ value, done }` read result.
+    #[allow(unsafe_code)]
+    pub fn resolve_read_request(promise: &Promise, chunk: HandleValue, done: bool) {
+        let global = promise.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(&*promise);
+        let mut result = ReadableStreamReadResult::empty();
+        result.value.set(chunk.get());
+        result.done = done;
+        rooted!(in(*cx) let mut result_value = UndefinedValue());
+        unsafe {
+            result.to_jsval(*cx, result_value.handle_mut());
+        }
+        promise.resolve(cx, result_value.handle());
+    }
+
+    /// Runs the close steps of the pending read requests, and resolves the
+    /// closed promise, once the stream closes.
+    pub fn close(&self) {
+        self.closed_promise.borrow().resolve_native(&());
+        let read_requests = mem::replace(&mut *self.read_requests.borrow_mut(), VecDeque::new());
+        for read_request in read_requests {
+            read_request.close_steps();
+        }
+    }
+
+    /// Runs the error steps of the pending read requests, and rejects the
+    /// closed promise, once the stream errors.
+    pub fn error(&self, error: HandleValue) {
+        let cx = self.global().get_cx();
+        self.closed_promise.borrow().reject(cx, error);
+        let read_requests = mem::replace(&mut *self.read_requests.borrow_mut(), VecDeque::new());
+        for read_request in read_requests {
+            read_request.error_steps(error);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-reader-release>,
+    /// which also errors the read requests of a `pipeTo()` that were still
+    /// pending when it finished.
+    #[allow(unsafe_code)]
+    pub fn release(&self) {
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => return,
+        };
+        let error = Error::Type("The reader released its lock".to_owned());
+        if stream.state() == ReadableStreamState::Readable {
+            self.closed_promise.borrow().reject_error(error);
+        } else {
+            let closed_promise = Promise::new(&self.global());
+            closed_promise.reject_error(error);
+            *self.closed_promise.borrow_mut() = closed_promise;
+        }
+        stream.set_reader(None);
+        self.stream.set(None);
+
+        let read_requests = mem::replace(&mut *self.read_requests.borrow_mut(), VecDeque::new());
+        if read_requests.is_empty() {
+            return;
+        }
+        let global = self.global();
+        let cx = global.get_cx();
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        unsafe {
+            Error::Type("The reader released its lock".to_owned()).to_jsval(
+                *cx,
+                &global,
+                error.handle_mut(),
+            );
+        }
+        for read_request in read_requests {
+            read_request.error_steps(error.handle());
+        }
+    }
+}
+
+impl ReadableStreamDefaultReaderMethods for ReadableStreamDefaultReader {
+    // https://streams.spec.whatwg.org/#default-reader-closed
+    fn Closed(&self) -> Rc<Promise> {
+        self.closed_promise.borrow().clone()
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-cancel
+    fn Cancel(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        match self.stream.get() {
+            Some(stream) => stream.cancel(cx, reason),
+            None => {
+                let promise = Promise::new(&self.global());
+                promise.reject_error(Error::Type("The reader has no stream".to_owned()));
+                promise
+            },
+        }
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-read
+    fn Read(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        match self.stream.get() {
+            Some(stream) => stream.read(ReadRequest::Promise {
+                promise: promise.clone(),
+            }),
+            None => promise.reject_error(Error::Type("The reader has no stream".to_owned())),
+        }
+        promise
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-release-lock
+    fn ReleaseLock(&self) -> ErrorResult {
+        // Step 2.
+        if self.stream.get().is_none() {
+            return Ok(());
+        }
+
+        // Step 3.
+        if !self.read_requests.borrow().is_empty() {
+            return Err(Error::Type("The reader has pending reads".to_owned()));
+        }
+
+        // Step 4.
+        self.release();
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ReportBinding::{self, ReportMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::reporting;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/reporting/#report
+#[dom_struct]
+pub struct Report {
+    reflector_: Reflector,
+    type_: DOMString,
+    url: DOMString,
+    body: Dom<ReportBody>,
+}
+
+impl Report {
+    fn new_inherited(report: &reporting::Report, body: &ReportBody) -> Report {
+        Report {
+            reflector_: Reflector::new(),
+            type_: DOMString::from(report.body.type_()),
+            url: DOMString::from(report.url.clone()),
+            body: Dom::from_ref(body),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, report: &reporting::Report) -> DomRoot<Report> {
+        let body = ReportBody::new(global, &report.body);
+        reflect_dom_object(
+            Box::new(Report::new_inherited(report, &body)),
+            global,
+            ReportBinding::Wrap,
+        )
+    }
+
+    pub fn type_(&self) -> &str {
+        &self.type_
+    }
+}
+
+impl ReportMethods for Report {
+    // https://w3c.github.io/reporting/#dom-report-type
+    fn Type(&self) -> DOMString {
+        self.type_.clone()
+    }
+
+    // https://w3c.github.io/reporting/#dom-report-url
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    // https://w3c.github.io/reporting/#dom-report-body
+    fn GetBody(&self) -> Option<DomRoot<ReportBody>> {
+        Some(DomRoot::from_ref(&*self.body))
+    }
+}
@@ -0,0 +1,42 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::cspviolationreportbody::CSPViolationReportBody;
+use crate::dom::deprecationreportbody::DeprecationReportBody;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::interventionreportbody::InterventionReportBody;
+use crate::reporting;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/reporting/#reportbody
+#[dom_struct]
+pub struct ReportBody {
+    reflector_: Reflector,
+}
+
+impl ReportBody {
+    pub fn new_inherited() -> ReportBody {
+        ReportBody {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    /// Creates the body of the given type of report.
+    pub fn new(global: &GlobalScope, body: &reporting::ReportBody) -> DomRoot<ReportBody> {
+        match *body {
+            reporting::ReportBody::Deprecation(ref report) => {
+                DomRoot::upcast(DeprecationReportBody::new(global, report))
+            },
+            reporting::ReportBody::Intervention(ref report) => {
+                DomRoot::upcast(InterventionReportBody::new(global, report))
+            },
+            reporting::ReportBody::CSPViolation(ref report) => {
+                DomRoot::upcast(CSPViolationReportBody::new(global, report))
+            },
+        }
+    }
+}
@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::{
+    self, ReportingObserverCallback, ReportingObserverMethods, ReportingObserverOptions,
+};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::report::Report;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::rc::Rc;
+
+// https://w3c.github.io/reporting/#reportingobserver
+#[dom_struct]
+pub struct ReportingObserver {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<ReportingObserverCallback>,
+    types: Option<Vec<DOMString>>,
+    buffered: Cell<bool>,
+    /// <https://w3c.github.io/reporting/#reportingobserver-report-queue>
+    report_queue: DomRefCell<Vec<Dom<Report>>>,
+}
+
+impl ReportingObserver {
+    fn new_inherited(
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> ReportingObserver {
+        ReportingObserver {
+            reflector_: Reflector::new(),
+            callback,
+            types: options.types.clone(),
+            buffered: Cell::new(options.buffered),
+            report_queue: DomRefCell::new(Vec::new()),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> DomRoot<ReportingObserver> {
+        reflect_dom_object(
+            Box::new(ReportingObserver::new_inherited(callback, options)),
+            global,
+            ReportingObserverBinding::Wrap,
+        )
+    }
+
+    // https://w3c.github.io/reporting/#dom-reportingobserver-reportingobserver
+    pub fn Constructor(
+        global: &GlobalScope,
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> DomRoot<ReportingObserver> {
+        ReportingObserver::new(global, callback, options)
+    }
+
+    /// Queues a report for the next invocation of the callback, unless the
+    /// observer is not interested in its type,
+    /// <https://w3c.github.io/reporting/#add-report>
+    pub fn queue_report(&self, report: &Report) {
+        if let Some(ref types) = self.types {
+            if !types.is_empty() && !types.iter().any(|type_| &**type_ == report.type_()) {
+                return;
+            }
+        }
+        self.report_queue.borrow_mut().push(Dom::from_ref(report));
+    }
+
+    /// Invokes the callback with the reports queued since the last call,
+    /// <https://w3c.github.io/reporting/#invoke-observers>
+    pub fn invoke(&self) {
+        let reports = self.take_reports();
+        if reports.is_empty() {
+            return;
+        }
+        let _ = self
+            .callback
+            .Call__(reports, self, ExceptionHandling::Report);
+    }
+
+    fn take_reports(&self) -> Vec<DomRoot<Report>> {
+        self.report_queue
+            .borrow_mut()
+            .drain(..)
+            .map(|report| DomRoot::from_ref(&*report))
+            .collect()
+    }
+}
+
+impl ReportingObserverMethods for ReportingObserver {
+    // https://w3c.github.io/reporting/#dom-reportingobserver-observe
+    fn Observe(&self) {
+        // Step 1.
+        let global = self.global();
+        global.register_reporting_observer(self);
+
+        // Steps 2-3.
+        if !self.buffered.get() {
+            return;
+        }
+        self.buffered.set(false);
+
+        // Step 4.
+        for report in global.buffered_reports() {
+            self.queue_report(&report);
+        }
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(invoke_reporting_observer: move || {
+                this.root().invoke();
+            }),
+            &global,
+        );
+    }
+
+    // https://w3c.github.io/reporting/#dom-reportingobserver-disconnect
+    fn Disconnect(&self) {
+        self.global().unregister_reporting_observer(self);
+    }
+
+    // https://w3c.github.io/reporting/#dom-reportingobserver-takerecords
+    fn TakeRecords(&self) -> Vec<DomRoot<Report>> {
+        self.take_reports()
+    }
+}
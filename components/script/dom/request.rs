@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::body::{consume_body, BodyOperations, BodyType};
+use crate::dom::abortsignal::AbortSignal;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use crate::dom::bindings::codegen::Bindings::RequestBinding;
@@ -15,6 +16,7 @@ use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMode;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestRedirect;
+use crate::dom::bindings::codegen::UnionTypes::BodyInitOrReadableStream;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
@@ -23,6 +25,7 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::{Guard, Headers};
 use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
 use crate::dom::xmlhttprequest::Extractable;
 use dom_struct::dom_struct;
 use http::method::InvalidMethod;
@@ -50,6 +53,10 @@ pub struct Request {
     mime_type: DomRefCell<Vec<u8>>,
     #[ignore_malloc_size_of = "Rc"]
     body_promise: DomRefCell<Option<(Rc<Promise>, BodyType)>>,
+    signal: MutNullableDom<AbortSignal>,
+    /// The body of the request when it is a `ReadableStream`, which is read
+    /// as the request is sent rather than extracted up front.
+    body_stream: MutNullableDom<ReadableStream>,
 }
 
 impl Request {
@@ -61,6 +68,8 @@ impl Request {
             headers: Default::default(),
             mime_type: DomRefCell::new("".to_string().into_bytes()),
             body_promise: DomRefCell::new(None),
+            signal: Default::default(),
+            body_stream: Default::default(),
         }
     }
 
@@ -297,6 +306,19 @@ impl Request {
         let r = Request::from_net_request(global, request);
         r.headers.or_init(|| Headers::for_request(&r.global()));
 
+        // The signal of the request follows the signal of the input request,
+        // or the signal of init if it has one.
+        let mut input_signal = match input {
+            RequestInfo::Request(ref input_request) => Some(input_request.Signal()),
+            RequestInfo::USVString(_) => None,
+        };
+        if let Some(ref init_signal) = init.signal {
+            input_signal = init_signal.clone();
+        }
+        let signals: Vec<DomRoot<AbortSignal>> = input_signal.into_iter().collect();
+        r.signal
+            .set(Some(&AbortSignal::new_dependent(global, &signals)));
+
         // Step 27
         let mut headers_copy = r.Headers();
 
@@ -364,16 +386,20 @@ impl Request {
         r.request.borrow_mut().headers = r.Headers().get_headers_list();
 
         // Step 32
-        let mut input_body = if let RequestInfo::Request(ref input_request) = input {
-            let input_request_request = input_request.request.borrow();
-            input_request_request.body.clone()
-        } else {
-            None
-        };
+        let (mut input_body, mut input_body_stream) =
+            if let RequestInfo::Request(ref input_request) = input {
+                let input_request_request = input_request.request.borrow();
+                (
+                    input_request_request.body.clone(),
+                    input_request.body_stream.get(),
+                )
+            } else {
+                (None, None)
+            };
 
         // Step 33
         if let Some(init_body_option) = init.body.as_ref() {
-            if init_body_option.is_some() || input_body.is_some() {
+            if init_body_option.is_some() || input_body.is_some() || input_body_stream.is_some() {
                 let req = r.request.borrow();
                 let req_method = &req.method;
                 match *req_method {
@@ -395,9 +421,43 @@ impl Request {
         // Step 34
         if let Some(Some(ref init_body)) = init.body {
             // Step 34.2
-            let extracted_body_tmp = init_body.extract();
-            input_body = Some(extracted_body_tmp.0);
-            let content_type = extracted_body_tmp.1;
+            let extracted_body_tmp = match *init_body {
+                BodyInitOrReadableStream::ReadableStream(ref stream) => {
+                    if stream.is_disturbed() || stream.is_locked() {
+                        return Err(Error::Type(
+                            "The body stream is disturbed or locked".to_string(),
+                        ));
+                    }
+                    if init.duplex.is_none() {
+                        return Err(Error::Type(
+                            "A stream body requires duplex to be 'half'".to_string(),
+                        ));
+                    }
+                    input_body_stream = Some(DomRoot::from_ref(&**stream));
+                    None
+                },
+                BodyInitOrReadableStream::String(ref s) => Some(s.extract()),
+                BodyInitOrReadableStream::URLSearchParams(ref usp) => Some(usp.extract()),
+                BodyInitOrReadableStream::Blob(ref b) => Some(b.extract()),
+                BodyInitOrReadableStream::FormData(ref formdata) => Some(formdata.extract()),
+                BodyInitOrReadableStream::ArrayBuffer(ref typedarray) => {
+                    Some((typedarray.to_vec(), None))
+                },
+                BodyInitOrReadableStream::ArrayBufferView(ref typedarray) => {
+                    Some((typedarray.to_vec(), None))
+                },
+            };
+            let content_type = match extracted_body_tmp {
+                Some((bytes, content_type)) => {
+                    input_body = Some(bytes);
+                    input_body_stream = None;
+                    content_type
+                },
+                None => {
+                    input_body = None;
+                    None
+                },
+            };
 
             // Step 34.3
             if let Some(contents) = content_type {
@@ -414,8 +474,31 @@ impl Request {
             }
         }
 
+        // A stream body has a null source, so it can only be sent once, and
+        // only in a request that is preflighted.
+        if input_body_stream.is_some() {
+            let mut request = r.request.borrow_mut();
+            if request.mode != NetTraitsRequestMode::SameOrigin &&
+                request.mode != NetTraitsRequestMode::CorsMode
+            {
+                return Err(Error::Type(
+                    "A stream body requires the mode to be 'same-origin' or 'cors'".to_string(),
+                ));
+            }
+            request.use_cors_preflight = true;
+        }
+
         // Step 35
         r.request.borrow_mut().body = input_body;
+        // The stream body of the input is now read through this request, so
+        // the input cannot be used again.
+        if let RequestInfo::Request(ref input_request) = input {
+            let init_body_is_null = init.body.as_ref().map_or(true, |body| body.is_none());
+            if init_body_is_null && input_body_stream.is_some() {
+                input_request.body_used.set(true);
+            }
+        }
+        r.body_stream.set(input_body_stream.as_deref());
 
         // Step 36
         let extracted_mime_type = r.Headers().extract_mime_type();
@@ -430,14 +513,22 @@ impl Request {
 
     // https://fetch.spec.whatwg.org/#concept-body-locked
     fn locked(&self) -> bool {
-        // TODO: ReadableStream is unimplemented. Just return false
-        // for now.
-        false
+        self.body_stream
+            .get()
+            .map_or(false, |stream| stream.is_locked())
+    }
+
+    /// The `ReadableStream` body of the request, if it has one.
+    pub fn body_stream(&self) -> Option<DomRoot<ReadableStream>> {
+        self.body_stream.get()
     }
 }
 
 impl Request {
-    fn from_net_request(global: &GlobalScope, net_request: NetTraitsRequest) -> DomRoot<Request> {
+    pub fn from_net_request(
+        global: &GlobalScope,
+        net_request: NetTraitsRequest,
+    ) -> DomRoot<Request> {
         let r = Request::new(global, net_request.current_url());
         *r.request.borrow_mut() = net_request;
         r
@@ -462,6 +553,10 @@ impl Request {
             .Headers()
             .fill(Some(HeadersInit::Headers(r.Headers())))?;
         r_clone.Headers().set_guard(headers_guard);
+        r_clone.signal.set(Some(&AbortSignal::new_dependent(
+            &r.global(),
+            &[r.Signal()],
+        )));
         Ok(r_clone)
     }
 
@@ -516,16 +611,18 @@ fn includes_credentials(input: &ServoUrl) -> bool {
     !input.username().is_empty() || input.password().is_some()
 }
 
-// TODO: `Readable Stream` object is not implemented in Servo yet.
 // https://fetch.spec.whatwg.org/#concept-body-disturbed
-fn request_is_disturbed(_input: &Request) -> bool {
-    false
+// Only a stream body is tracked, a stream body handed on to another request
+// counts as disturbed.
+fn request_is_disturbed(input: &Request) -> bool {
+    input.body_stream.get().map_or(false, |stream| {
+        input.body_used.get() || stream.is_disturbed()
+    })
 }
 
-// TODO: `Readable Stream` object is not implemented in Servo yet.
 // https://fetch.spec.whatwg.org/#concept-body-locked
-fn request_is_locked(_input: &Request) -> bool {
-    false
+fn request_is_locked(input: &Request) -> bool {
+    input.locked()
 }
 
 impl RequestMethods for Request {
@@ -602,6 +699,11 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    // https://fetch.spec.whatwg.org/#dom-request-signal
+    fn Signal(&self) -> DomRoot<AbortSignal> {
+        self.signal.or_init(|| AbortSignal::new(&self.global()))
+    }
+
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
         self.body_used.get()
@@ -616,6 +718,12 @@ impl RequestMethods for Request {
         if request_is_disturbed(self) {
             return Err(Error::Type("Request is disturbed".to_string()));
         }
+        // TODO: A stream body would be teed.
+        if self.body_stream.get().is_some() {
+            return Err(Error::Type(
+                "Cannot clone a request with a stream body".to_string(),
+            ));
+        }
 
         // Step 2
         Request::clone_from(self)
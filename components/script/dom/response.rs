@@ -18,12 +18,16 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::{is_obs_text, is_vchar};
 use crate::dom::headers::{Guard, Headers};
 use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
 use crate::dom::xmlhttprequest::Extractable;
-use crate::script_runtime::StreamConsumer;
+use crate::script_runtime::{JSContext, StreamConsumer};
 use dom_struct::dom_struct;
 use http::header::HeaderMap as HyperHeaders;
 use hyper::StatusCode;
 use hyper_serde::Serde;
+use js::jsapi::{Heap, JSErrNum};
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 use net_traits::response::ResponseBody as NetTraitsResponseBody;
 use servo_url::ServoUrl;
 use std::cell::{Cell, Ref};
@@ -49,8 +53,16 @@ pub struct Response {
     body: DomRefCell<NetTraitsResponseBody>,
     #[ignore_malloc_size_of = "Rc"]
     body_promise: DomRefCell<Option<(Rc<Promise>, BodyType)>>,
+    /// The reason the fetch filling the body was aborted with, undefined if it was not.
+    #[ignore_malloc_size_of = "mozjs"]
+    body_abort_reason: Heap<JSVal>,
     #[ignore_malloc_size_of = "StreamConsumer"]
     stream_consumer: DomRefCell<Option<StreamConsumer>>,
+    /// False for a null body, as that of a network error or of a response
+    /// constructed without one.
+    has_body: Cell<bool>,
+    /// Created when script first gets `body`, and then fed as the body arrives.
+    body_stream: MutNullableDom<ReadableStream>,
 }
 
 impl Response {
@@ -67,7 +79,10 @@ impl Response {
             url_list: DomRefCell::new(vec![]),
             body: DomRefCell::new(NetTraitsResponseBody::Empty),
             body_promise: DomRefCell::new(None),
+            body_abort_reason: Heap::default(),
             stream_consumer: DomRefCell::new(None),
+            has_body: Cell::new(false),
+            body_stream: Default::default(),
         }
     }
 
@@ -131,6 +146,7 @@ impl Response {
             // Step 7.3
             let (extracted_body, content_type) = body.extract();
             *r.body.borrow_mut() = NetTraitsResponseBody::Done(extracted_body);
+            r.has_body.set(true);
 
             // Step 7.4
             if let Some(content_type_contents) = content_type {
@@ -214,9 +230,43 @@ impl Response {
 
     // https://fetch.spec.whatwg.org/#concept-body-locked
     fn locked(&self) -> bool {
-        // TODO: ReadableStream is unimplemented. Just return false
-        // for now.
-        false
+        self.body_stream
+            .get()
+            .map_or(false, |stream| stream.is_locked())
+    }
+
+    /// Creates the stream of `body` from what has been received of the body
+    /// so far, the rest is enqueued as it arrives. The body received so far
+    /// is moved into the stream, which then is the only buffer of the body.
+    fn new_body_stream(&self) -> DomRoot<ReadableStream> {
+        let global = self.global();
+        let stream = ReadableStream::new_with_native_source(&global);
+        if !self.body_abort_reason.get().is_undefined() {
+            let cx = global.get_cx();
+            rooted!(in(*cx) let reason = self.body_abort_reason.get());
+            stream.error_native_with_value(reason.handle());
+            return stream;
+        }
+        let body = mem::replace(&mut *self.body.borrow_mut(), NetTraitsResponseBody::Empty);
+        match body {
+            NetTraitsResponseBody::Receiving(bytes) => stream.enqueue_native(bytes),
+            // The whole body is kept as well, as a clone of this response
+            // copies it.
+            NetTraitsResponseBody::Done(bytes) => {
+                if !bytes.is_empty() {
+                    stream.enqueue_native(bytes.clone());
+                }
+                stream.close_native();
+                *self.body.borrow_mut() = NetTraitsResponseBody::Done(bytes);
+            },
+            // The body was consumed, or the fetch has not received any of it yet.
+            NetTraitsResponseBody::Empty => {
+                if self.body_used.get() {
+                    stream.close_native();
+                }
+            },
+        }
+        stream
     }
 }
 
@@ -228,6 +278,21 @@ impl BodyOperations for Response {
     fn set_body_promise(&self, p: &Rc<Promise>, body_type: BodyType) {
         assert!(self.body_promise.borrow().is_none());
         self.body_used.set(true);
+        if !self.body_abort_reason.get().is_undefined() {
+            let cx = self.global().get_cx();
+            rooted!(in(*cx) let reason = self.body_abort_reason.get());
+            return p.reject(cx, reason.handle());
+        }
+        // The body received so far is in the stream of `body` if script got
+        // it, the rest of it is then buffered here until the fetch ends.
+        if let Some(stream) = self.body_stream.get() {
+            let bytes = stream.take_native_bytes();
+            let mut body = self.body.borrow_mut();
+            *body = match *body {
+                NetTraitsResponseBody::Done(_) => NetTraitsResponseBody::Done(bytes),
+                _ => NetTraitsResponseBody::Receiving(bytes),
+            };
+        }
         *self.body_promise.borrow_mut() = Some((p.clone(), body_type));
     }
 
@@ -353,6 +418,7 @@ impl ResponseMethods for Response {
         if *self.body.borrow() != NetTraitsResponseBody::Empty {
             *new_response.body.borrow_mut() = self.body.borrow().clone();
         }
+        new_response.has_body.set(self.has_body.get());
 
         // Step 3
         // TODO: This step relies on promises, which are still unimplemented.
@@ -361,9 +427,20 @@ impl ResponseMethods for Response {
         Ok(new_response)
     }
 
+    // https://fetch.spec.whatwg.org/#dom-body-body
+    fn GetBody(&self) -> Option<DomRoot<ReadableStream>> {
+        if !self.has_body.get() {
+            return None;
+        }
+        Some(self.body_stream.or_init(|| self.new_body_stream()))
+    }
+
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
-        self.body_used.get()
+        self.body_used.get() ||
+            self.body_stream
+                .get()
+                .map_or(false, |stream| stream.is_disturbed())
     }
 
     // https://fetch.spec.whatwg.org/#dom-body-text
@@ -399,6 +476,12 @@ fn serialize_without_fragment(url: &ServoUrl) -> &str {
 impl Response {
     pub fn set_type(&self, new_response_type: DOMResponseType) {
         *self.response_type.borrow_mut() = new_response_type;
+        self.has_body.set(match new_response_type {
+            DOMResponseType::Basic | DOMResponseType::Cors | DOMResponseType::Default => true,
+            DOMResponseType::Error | DOMResponseType::Opaque | DOMResponseType::Opaqueredirect => {
+                false
+            },
+        });
         self.set_response_members_by_type(new_response_type);
     }
 
@@ -445,18 +528,69 @@ impl Response {
         }
     }
 
-    pub fn set_stream_consumer(&self, sc: Option<StreamConsumer>) {
-        *self.stream_consumer.borrow_mut() = sc;
+    /// Hand the body to a WebAssembly stream consumer, which consumes what
+    /// has been received at once and the rest of it as it arrives.
+    pub fn set_stream_consumer(&self, sc: StreamConsumer) {
+        self.body_used.set(true);
+        let body = mem::replace(&mut *self.body.borrow_mut(), NetTraitsResponseBody::Empty);
+        let done = match body {
+            NetTraitsResponseBody::Done(_) => true,
+            _ => false,
+        };
+        let received = match self.body_stream.get() {
+            Some(stream) => stream.take_native_bytes(),
+            None => match body {
+                NetTraitsResponseBody::Receiving(bytes) | NetTraitsResponseBody::Done(bytes) => {
+                    bytes
+                },
+                NetTraitsResponseBody::Empty => vec![],
+            },
+        };
+        sc.consume_chunk(&received);
+        if done {
+            return sc.stream_end();
+        }
+        *self.stream_consumer.borrow_mut() = Some(sc);
+    }
+
+    /// Hands `chunk` to whatever reads the body, only buffering it here
+    /// when the body is consumed at once or script has not got `body` yet.
+    pub fn stream_chunk(&self, chunk: &[u8]) {
+        if let Some(stream_consumer) = self.stream_consumer.borrow().as_ref() {
+            return stream_consumer.consume_chunk(chunk);
+        }
+        if self.body_promise.borrow().is_none() {
+            if let Some(stream) = self.body_stream.get() {
+                return stream.enqueue_native(chunk.to_vec());
+            }
+        }
+        let mut body = self.body.borrow_mut();
+        match *body {
+            NetTraitsResponseBody::Receiving(ref mut bytes) => bytes.extend_from_slice(chunk),
+            NetTraitsResponseBody::Empty => {
+                *body = NetTraitsResponseBody::Receiving(chunk.to_vec());
+            },
+            NetTraitsResponseBody::Done(_) => {},
+        }
     }
 
-    pub fn stream_chunk(&self, stream: &[u8]) {
-        if let Some(stream_consumer) = self.stream_consumer.borrow_mut().as_ref() {
-            stream_consumer.consume_chunk(stream);
+    /// Errors the stream consumer, or the stream of `body`, reading the body
+    /// when the fetch fails.
+    pub fn error_stream(&self) {
+        if let Some(stream_consumer) = self.stream_consumer.borrow_mut().take() {
+            stream_consumer.stream_error(JSErrNum::JSMSG_WASM_STREAM_ERROR as usize);
+        }
+        if let Some(stream) = self.body_stream.get() {
+            stream.error_native(Error::Type("Network error occurred".to_owned()));
         }
     }
 
     #[allow(unrooted_must_root)]
-    pub fn finish(&self, body: Vec<u8>) {
+    pub fn finish(&self) {
+        let body = match mem::replace(&mut *self.body.borrow_mut(), NetTraitsResponseBody::Empty) {
+            NetTraitsResponseBody::Receiving(bytes) | NetTraitsResponseBody::Done(bytes) => bytes,
+            NetTraitsResponseBody::Empty => vec![],
+        };
         *self.body.borrow_mut() = NetTraitsResponseBody::Done(body);
         if let Some((p, body_type)) = self.body_promise.borrow_mut().take() {
             consume_body_with_promise(self, body_type, &p);
@@ -464,5 +598,24 @@ impl Response {
         if let Some(stream_consumer) = self.stream_consumer.borrow_mut().take() {
             stream_consumer.stream_end();
         }
+        if let Some(stream) = self.body_stream.get() {
+            stream.close_native();
+        }
+    }
+
+    /// Errors the body when the fetch filling it is aborted, reading it then
+    /// rejects with the abort reason, <https://fetch.spec.whatwg.org/#abort-fetch>
+    pub fn abort_body(&self, cx: JSContext, reason: HandleValue) {
+        if let NetTraitsResponseBody::Done(_) = *self.body.borrow() {
+            return;
+        }
+        self.body_abort_reason.set(reason.get());
+        if let Some((p, _)) = self.body_promise.borrow_mut().take() {
+            p.reject(cx, reason);
+        }
+        if let Some(stream) = self.body_stream.get() {
+            stream.error_native_with_value(reason);
+        }
+        self.error_stream();
     }
 }
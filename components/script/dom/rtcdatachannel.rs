@@ -0,0 +1,372 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::{
+    self, RTCDataChannelInit, RTCDataChannelMethods, RTCDataChannelState,
+};
+use crate::dom::bindings::codegen::Bindings::WebSocketBinding::BinaryType;
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::USVString;
+use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageevent::MessageEvent;
+use crate::dom::rtcpeerconnection::RTCPeerConnection;
+use dom_struct::dom_struct;
+use js::jsapi::{JSAutoRealm, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::CustomAutoRooterGuard;
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use servo_atoms::Atom;
+use servo_media::webrtc::{
+    DataChannelId, DataChannelInit, DataChannelMessage, DataChannelState, WebRtcError,
+};
+use std::cell::Cell;
+use std::ptr;
+
+// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel
+#[dom_struct]
+pub struct RTCDataChannel {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "defined in servo-media"]
+    servo_media_id: DataChannelId,
+    peer_connection: Dom<RTCPeerConnection>,
+    label: USVString,
+    ordered: bool,
+    max_packet_life_time: Option<u16>,
+    max_retransmits: Option<u16>,
+    protocol: USVString,
+    negotiated: bool,
+    id: Option<u16>,
+    ready_state: Cell<RTCDataChannelState>,
+    binary_type: Cell<BinaryType>,
+}
+
+impl RTCDataChannel {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        peer_connection: &RTCPeerConnection,
+        label: USVString,
+        options: &RTCDataChannelInit,
+        servo_media_id: DataChannelId,
+    ) -> RTCDataChannel {
+        RTCDataChannel {
+            eventtarget: EventTarget::new_inherited(),
+            servo_media_id,
+            peer_connection: Dom::from_ref(peer_connection),
+            label,
+            ordered: options.ordered,
+            max_packet_life_time: options.maxPacketLifeTime,
+            max_retransmits: options.maxRetransmits,
+            protocol: options.protocol.clone(),
+            negotiated: options.negotiated,
+            id: options.id,
+            ready_state: Cell::new(RTCDataChannelState::Connecting),
+            binary_type: Cell::new(BinaryType::Blob),
+        }
+    }
+
+    /// Creates a data channel, asking the backend for a new one unless
+    /// `servo_media_id` identifies a channel announced by the remote peer.
+    pub fn new(
+        global: &GlobalScope,
+        peer_connection: &RTCPeerConnection,
+        label: USVString,
+        options: &RTCDataChannelInit,
+        servo_media_id: Option<DataChannelId>,
+    ) -> Fallible<DomRoot<RTCDataChannel>> {
+        let servo_media_id = match servo_media_id {
+            Some(id) => id,
+            None => {
+                let mut init: DataChannelInit = options.into();
+                init.label = label.to_string();
+                peer_connection
+                    .get_webrtc_controller()
+                    .borrow()
+                    .as_ref()
+                    .unwrap()
+                    .create_data_channel(init)
+                    .map_err(|_| Error::Operation)?
+            },
+        };
+
+        let channel = reflect_dom_object(
+            Box::new(RTCDataChannel::new_inherited(
+                peer_connection,
+                label,
+                options,
+                servo_media_id,
+            )),
+            global,
+            RTCDataChannelBinding::Wrap,
+        );
+        peer_connection.register_data_channel(servo_media_id, &channel);
+        Ok(channel)
+    }
+
+    fn fire_simple_event(&self, name: Atom) {
+        let event = Event::new(
+            &self.global(),
+            name,
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-open>
+    pub fn on_open(&self) {
+        // Step 1.
+        if self.ready_state.get() == RTCDataChannelState::Closed {
+            return;
+        }
+
+        // Steps 2-4.
+        self.ready_state.set(RTCDataChannelState::Open);
+        self.fire_simple_event(atom!("open"));
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-closed>
+    pub fn on_close(&self) {
+        // Steps 2-3.
+        self.ready_state.set(RTCDataChannelState::Closed);
+        self.peer_connection
+            .unregister_data_channel(&self.servo_media_id);
+
+        // Step 5.
+        self.fire_simple_event(atom!("close"));
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#transport-independent-data-channel-closure>
+    pub fn on_error(&self, error: WebRtcError) {
+        match error {
+            WebRtcError::Backend(message) => warn!("Data channel error: {}", message),
+        }
+        self.fire_simple_event(atom!("error"));
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#receiving-messages-on-a-data-channel>
+    #[allow(unsafe_code)]
+    pub fn on_message(&self, channel_message: DataChannelMessage) {
+        // Step 2.
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return;
+        }
+
+        // Steps 3-6.
+        let global = self.global();
+        // global.get_cx() returns a valid `JSContext` pointer, so this is safe.
+        unsafe {
+            let cx = global.get_cx();
+            let _ac = JSAutoRealm::new(*cx, self.reflector().get_jsobject().get());
+            rooted!(in(*cx) let mut message = UndefinedValue());
+            match channel_message {
+                DataChannelMessage::Text(text) => text.to_jsval(*cx, message.handle_mut()),
+                DataChannelMessage::Binary(data) => match self.binary_type.get() {
+                    BinaryType::Blob => {
+                        let blob =
+                            Blob::new(&global, BlobImpl::new_from_bytes(data), "".to_owned());
+                        blob.to_jsval(*cx, message.handle_mut());
+                    },
+                    BinaryType::Arraybuffer => {
+                        rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+                        assert!(ArrayBuffer::create(
+                            *cx,
+                            CreateWith::Slice(&data),
+                            array_buffer.handle_mut()
+                        )
+                        .is_ok());
+
+                        (*array_buffer).to_jsval(*cx, message.handle_mut());
+                    },
+                },
+            }
+            MessageEvent::dispatch_jsval(
+                self.upcast(),
+                &global,
+                message.handle(),
+                Some(&global.origin().immutable().ascii_serialization()),
+                None,
+                vec![],
+            );
+        }
+    }
+
+    pub fn on_state_change(&self, state: DataChannelState) {
+        let state: RTCDataChannelState = state.into();
+        if self.ready_state.replace(state) == state {
+            return;
+        }
+        if state == RTCDataChannelState::Closing {
+            self.fire_simple_event(atom!("closing"));
+        }
+    }
+
+    /// Sets the ready state without firing events, used when the
+    /// peer connection is closed.
+    pub fn set_closed(&self) {
+        self.ready_state.set(RTCDataChannelState::Closed);
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#dom-datachannel-send>
+    fn send(&self, message: DataChannelMessage) -> Fallible<()> {
+        // Step 2.
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 3-4.
+        self.peer_connection
+            .get_webrtc_controller()
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .send_data_channel_message(&self.servo_media_id, message);
+        Ok(())
+    }
+}
+
+impl RTCDataChannelMethods for RTCDataChannel {
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onopen
+    event_handler!(open, GetOnopen, SetOnopen);
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onbufferedamountlow
+    event_handler!(
+        bufferedamountlow,
+        GetOnbufferedamountlow,
+        SetOnbufferedamountlow
+    );
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onclosing
+    event_handler!(closing, GetOnclosing, SetOnclosing);
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-label
+    fn Label(&self) -> USVString {
+        self.label.clone()
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-ordered
+    fn Ordered(&self) -> bool {
+        self.ordered
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-maxpacketlifetime
+    fn GetMaxPacketLifeTime(&self) -> Option<u16> {
+        self.max_packet_life_time
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-maxretransmits
+    fn GetMaxRetransmits(&self) -> Option<u16> {
+        self.max_retransmits
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-protocol
+    fn Protocol(&self) -> USVString {
+        self.protocol.clone()
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-negotiated
+    fn Negotiated(&self) -> bool {
+        self.negotiated
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-id
+    fn GetId(&self) -> Option<u16> {
+        self.id
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-readystate
+    fn ReadyState(&self) -> RTCDataChannelState {
+        self.ready_state.get()
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-close
+    fn Close(&self) {
+        // Step 2.
+        match self.ready_state.get() {
+            RTCDataChannelState::Closing | RTCDataChannelState::Closed => return,
+            _ => {},
+        }
+
+        // Steps 3-4, the backend announces the channel as closed once done.
+        self.ready_state.set(RTCDataChannelState::Closing);
+        self.peer_connection
+            .get_webrtc_controller()
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .close_data_channel(&self.servo_media_id);
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-binarytype
+    fn BinaryType(&self) -> BinaryType {
+        self.binary_type.get()
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-datachannel-binarytype
+    fn SetBinaryType(&self, binary_type: BinaryType) {
+        self.binary_type.set(binary_type)
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-send
+    fn Send(&self, data: USVString) -> Fallible<()> {
+        self.send(DataChannelMessage::Text(data.0))
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-send-blob
+    fn Send_(&self, data: &Blob) -> Fallible<()> {
+        self.send(DataChannelMessage::Binary(
+            data.get_bytes().unwrap_or(vec![]),
+        ))
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-send-arraybuffer
+    fn Send__(&self, data: CustomAutoRooterGuard<ArrayBuffer>) -> Fallible<()> {
+        self.send(DataChannelMessage::Binary(data.to_vec()))
+    }
+
+    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-send-arraybufferview
+    fn Send___(&self, data: CustomAutoRooterGuard<ArrayBufferView>) -> Fallible<()> {
+        self.send(DataChannelMessage::Binary(data.to_vec()))
+    }
+}
+
+impl<'a> From<&'a RTCDataChannelInit> for DataChannelInit {
+    fn from(init: &'a RTCDataChannelInit) -> DataChannelInit {
+        DataChannelInit {
+            label: String::new(),
+            id: init.id,
+            max_packet_life_time: init.maxPacketLifeTime,
+            max_retransmits: init.maxRetransmits,
+            negotiated: init.negotiated,
+            ordered: init.ordered,
+            protocol: init.protocol.to_string(),
+        }
+    }
+}
+
+impl From<DataChannelState> for RTCDataChannelState {
+    fn from(state: DataChannelState) -> RTCDataChannelState {
+        match state {
+            DataChannelState::Connecting => RTCDataChannelState::Connecting,
+            DataChannelState::Open => RTCDataChannelState::Open,
+            DataChannelState::Closing => RTCDataChannelState::Closing,
+            DataChannelState::Closed => RTCDataChannelState::Closed,
+        }
+    }
+}
@@ -2,8 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::compartments::InCompartment;
+use crate::compartments::{enter_realm, InCompartment};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChannelInit;
 use crate::dom::bindings::codegen::Bindings::RTCIceCandidateBinding::RTCIceCandidateInit;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::RTCPeerConnectionMethods;
@@ -21,13 +22,16 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::USVString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::mediastreamtrack::MediaStreamTrack;
 use crate::dom::promise::Promise;
+use crate::dom::rtcdatachannel::RTCDataChannel;
+use crate::dom::rtcdatachannelevent::RTCDataChannelEvent;
 use crate::dom::rtcicecandidate::RTCIceCandidate;
 use crate::dom::rtcpeerconnectioniceevent::RTCPeerConnectionIceEvent;
 use crate::dom::rtcsessiondescription::RTCSessionDescription;
@@ -41,12 +45,14 @@ use dom_struct::dom_struct;
 use servo_media::streams::registry::MediaStreamId;
 use servo_media::streams::MediaStreamType;
 use servo_media::webrtc::{
-    BundlePolicy, GatheringState, IceCandidate, IceConnectionState, SdpType, SessionDescription,
-    SignalingState, WebRtcController, WebRtcSignaller,
+    BundlePolicy, DataChannelEvent, DataChannelId, GatheringState, IceCandidate,
+    IceConnectionState, SdpType, SessionDescription, SignalingState, WebRtcController,
+    WebRtcSignaller,
 };
 use servo_media::ServoMedia;
 
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 #[dom_struct]
@@ -67,6 +73,8 @@ pub struct RTCPeerConnection {
     gathering_state: Cell<RTCIceGatheringState>,
     ice_connection_state: Cell<RTCIceConnectionState>,
     signaling_state: Cell<RTCSignalingState>,
+    #[ignore_malloc_size_of = "defined in servo-media"]
+    data_channels: DomRefCell<HashMap<DataChannelId, Dom<RTCDataChannel>>>,
 }
 
 struct RTCSignaller {
@@ -143,6 +151,23 @@ impl WebRtcSignaller for RTCSignaller {
         );
     }
 
+    fn on_data_channel_event(
+        &self,
+        channel: DataChannelId,
+        event: DataChannelEvent,
+        _: &WebRtcController,
+    ) {
+        let this = self.trusted.clone();
+        let _ = self.task_source.queue_with_canceller(
+            task!(on_data_channel_event: move || {
+                let this = this.root();
+                let _ac = enter_realm(&*this);
+                this.on_data_channel_event(channel, event);
+            }),
+            &self.canceller,
+        );
+    }
+
     fn close(&self) {
         // do nothing
     }
@@ -162,6 +187,7 @@ impl RTCPeerConnection {
             gathering_state: Cell::new(RTCIceGatheringState::New),
             ice_connection_state: Cell::new(RTCIceConnectionState::New),
             signaling_state: Cell::new(RTCSignalingState::Stable),
+            data_channels: DomRefCell::new(HashMap::new()),
         }
     }
 
@@ -251,6 +277,74 @@ impl RTCPeerConnection {
         event.upcast::<Event>().fire(self.upcast());
     }
 
+    fn on_data_channel_event(&self, channel_id: DataChannelId, event: DataChannelEvent) {
+        if self.closed.get() {
+            return;
+        }
+
+        if let DataChannelEvent::NewChannel = event {
+            // https://w3c.github.io/webrtc-pc/#announce-datachannel-open
+            // XXX the label and options of channels opened by the remote peer
+            // are not exposed by servo-media yet.
+            let channel = match RTCDataChannel::new(
+                &self.global(),
+                self,
+                USVString::from("".to_owned()),
+                &RTCDataChannelInit::empty(),
+                Some(channel_id),
+            ) {
+                Ok(channel) => channel,
+                Err(_) => return,
+            };
+            let event = RTCDataChannelEvent::new(
+                &self.global(),
+                atom!("datachannel"),
+                false,
+                false,
+                &channel,
+            );
+            event.upcast::<Event>().fire(self.upcast());
+            return;
+        }
+
+        let channel = match self.data_channels.borrow().get(&channel_id) {
+            Some(channel) => DomRoot::from_ref(&**channel),
+            None => {
+                return warn!(
+                    "Got an event for an unregistered data channel {:?}",
+                    channel_id
+                );
+            },
+        };
+        match event {
+            DataChannelEvent::Open => channel.on_open(),
+            DataChannelEvent::Close => channel.on_close(),
+            DataChannelEvent::Error(error) => channel.on_error(error),
+            DataChannelEvent::OnMessage(message) => channel.on_message(message),
+            DataChannelEvent::StateChange(state) => channel.on_state_change(state),
+            DataChannelEvent::NewChannel => unreachable!(),
+        }
+    }
+
+    pub fn get_webrtc_controller(&self) -> &DomRefCell<Option<WebRtcController>> {
+        &self.controller
+    }
+
+    pub fn register_data_channel(&self, id: DataChannelId, channel: &RTCDataChannel) {
+        if self
+            .data_channels
+            .borrow_mut()
+            .insert(id, Dom::from_ref(channel))
+            .is_some()
+        {
+            warn!("Data channel already registered {:?}", id);
+        }
+    }
+
+    pub fn unregister_data_channel(&self, id: &DataChannelId) {
+        self.data_channels.borrow_mut().remove(id);
+    }
+
     fn on_add_stream(&self, id: MediaStreamId, ty: MediaStreamType) {
         if self.closed.get() {
             return;
@@ -452,6 +546,9 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         SetOnsignalingstatechange
     );
 
+    /// https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-ondatachannel
+    event_handler!(datachannel, GetOndatachannel, SetOndatachannel);
+
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-addicecandidate
     fn AddIceCandidate(&self, candidate: &RTCIceCandidateInit, comp: InCompartment) -> Rc<Promise> {
         let p = Promise::new_in_current_compartment(&self.global(), comp);
@@ -606,6 +703,42 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         }
     }
 
+    /// https://www.w3.org/TR/webrtc/#dom-peerconnection-createdatachannel
+    fn CreateDataChannel(
+        &self,
+        label: USVString,
+        init: &RTCDataChannelInit,
+    ) -> Fallible<DomRoot<RTCDataChannel>> {
+        // Step 2.
+        if self.closed.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 5 and 8.
+        if label.0.len() > 65535 || init.protocol.0.len() > 65535 {
+            return Err(Error::Type(
+                "label and protocol must not be longer than 65535 bytes".to_owned(),
+            ));
+        }
+
+        // Step 12.
+        if init.maxPacketLifeTime.is_some() && init.maxRetransmits.is_some() {
+            return Err(Error::Type(
+                "maxPacketLifeTime and maxRetransmits cannot both be set".to_owned(),
+            ));
+        }
+
+        // Step 14.
+        if init.negotiated && init.id.is_none() {
+            return Err(Error::Type(
+                "negotiated data channels need an id".to_owned(),
+            ));
+        }
+
+        // Steps 15-19 are handled by the backend.
+        RTCDataChannel::new(&self.global(), self, label, init, None)
+    }
+
     /// https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-icegatheringstate
     fn IceGatheringState(&self) -> RTCIceGatheringState {
         self.gathering_state.get()
@@ -637,7 +770,11 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         self.controller.borrow_mut().as_ref().unwrap().quit();
 
         // Step 6-10
-        // (no current support for data channels, transports, etc)
+        // (no current support for transports, etc)
+        let channels: Vec<_> = self.data_channels.borrow_mut().drain().collect();
+        for (_, channel) in channels {
+            channel.set_closed();
+        }
 
         // Step 11
         self.ice_connection_state.set(RTCIceConnectionState::Closed);
@@ -0,0 +1,304 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::{enter_realm, InCompartment};
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::{
+    self, SchedulerMethods, SchedulerPostTaskCallback, SchedulerPostTaskOptions, TaskPriority,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::tasksignal::TaskSignal;
+use crate::script_runtime::JSContext;
+use crate::timers::OneshotTimerCallback;
+use dom_struct::dom_struct;
+use js::jsapi::{JS_ClearPendingException, JS_GetPendingException};
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
+use script_traits::MsDuration;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A task that was posted with `postTask()`, or the continuation of a call
+/// to `yield()`, and that didn't run yet.
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+struct PostedTask {
+    id: u64,
+    /// None for the continuation of `yield()`, which only resolves the promise.
+    #[ignore_malloc_size_of = "Rc"]
+    callback: Option<Rc<SchedulerPostTaskCallback>>,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    /// None when the task follows the priority of its signal, which is
+    /// then a `TaskSignal`.
+    priority: Option<TaskPriority>,
+    signal: Option<Dom<AbortSignal>>,
+    /// Whether the delay the task was posted with elapsed.
+    ready: bool,
+}
+
+impl PostedTask {
+    fn priority(&self) -> TaskPriority {
+        self.priority.unwrap_or_else(|| {
+            self.signal
+                .as_ref()
+                .and_then(|signal| signal.downcast::<TaskSignal>())
+                .map_or(TaskPriority::User_visible, |signal| signal.priority())
+        })
+    }
+
+    /// The continuations of a priority run before the tasks of the same
+    /// priority.
+    fn effective_priority(&self) -> u8 {
+        let priority = match self.priority() {
+            TaskPriority::Background => 0,
+            TaskPriority::User_visible => 2,
+            TaskPriority::User_blocking => 4,
+        };
+        if self.callback.is_none() {
+            priority + 1
+        } else {
+            priority
+        }
+    }
+}
+
+// https://wicg.github.io/scheduling-apis/#scheduler
+#[dom_struct]
+pub struct Scheduler {
+    reflector_: Reflector,
+    /// The posted tasks that didn't run yet, in the order they were posted.
+    /// Each of them has a task queued on the posted task task source with
+    /// its priority, which runs the task that should run first when it
+    /// runs, since the priority of a task changes with the one of its signal.
+    tasks: DomRefCell<Vec<PostedTask>>,
+    next_task_id: Cell<u64>,
+    /// The priority of the posted task that is running, if any, which the
+    /// continuation of `yield()` inherits.
+    current_priority: Cell<Option<TaskPriority>>,
+}
+
+impl Scheduler {
+    fn new_inherited() -> Scheduler {
+        Scheduler {
+            reflector_: Reflector::new(),
+            tasks: DomRefCell::new(vec![]),
+            next_task_id: Cell::new(0),
+            current_priority: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Scheduler> {
+        reflect_dom_object(
+            Box::new(Scheduler::new_inherited()),
+            global,
+            SchedulerBinding::Wrap,
+        )
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#schedule-a-posttask-task>
+    #[allow(unrooted_must_root)]
+    fn schedule_task(
+        &self,
+        callback: Option<Rc<SchedulerPostTaskCallback>>,
+        promise: &Rc<Promise>,
+        priority: Option<TaskPriority>,
+        signal: Option<&AbortSignal>,
+        delay: u64,
+    ) {
+        let id = self.next_task_id.get();
+        self.next_task_id.set(id + 1);
+
+        if let Some(signal) = signal {
+            signal.add_abort_algorithm(AbortAlgorithm::PostedTask {
+                scheduler: Dom::from_ref(self),
+                id,
+            });
+        }
+
+        let task = PostedTask {
+            id,
+            callback,
+            promise: promise.clone(),
+            priority,
+            signal: signal.map(Dom::from_ref),
+            ready: delay == 0,
+        };
+        let priority = task.priority();
+        self.tasks.borrow_mut().push(task);
+
+        if delay == 0 {
+            self.queue_posted_task(priority);
+        } else {
+            let callback = OneshotTimerCallback::PostedTaskDelay(PostedTaskDelayCallback {
+                scheduler: Trusted::new(self),
+                id,
+            });
+            self.global()
+                .schedule_callback(callback, MsDuration::new(delay));
+        }
+    }
+
+    fn queue_posted_task(&self, priority: TaskPriority) {
+        let global = self.global();
+        let this = Trusted::new(self);
+        let _ = global.posted_task_source().queue(
+            task!(run_posted_task: move || {
+                this.root().run_next_task();
+            }),
+            priority,
+            &global,
+        );
+    }
+
+    fn task_ready(&self, id: u64) {
+        let priority = {
+            let mut tasks = self.tasks.borrow_mut();
+            let task = match tasks.iter_mut().find(|task| task.id == id) {
+                Some(task) => task,
+                // The task was aborted while it was delayed.
+                None => return,
+            };
+            task.ready = true;
+            task.priority()
+        };
+        self.queue_posted_task(priority);
+    }
+
+    /// Runs the ready task with the highest effective priority, and the
+    /// first posted among those.
+    #[allow(unrooted_must_root, unsafe_code)]
+    fn run_next_task(&self) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            let mut next: Option<(usize, u8)> = None;
+            for (index, task) in tasks.iter().enumerate() {
+                if !task.ready {
+                    continue;
+                }
+                let effective_priority = task.effective_priority();
+                if next.map_or(true, |(_, priority)| effective_priority > priority) {
+                    next = Some((index, effective_priority));
+                }
+            }
+            match next {
+                Some((index, _)) => tasks.remove(index),
+                // The task this was queued for was aborted, or already
+                // ran in place of a task of a lower priority.
+                None => return,
+            }
+        };
+
+        if let Some(ref signal) = task.signal {
+            signal.remove_posted_task(self, task.id);
+        }
+
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(self);
+        let previous_priority = self.current_priority.replace(Some(task.priority()));
+        match task.callback {
+            Some(ref callback) => match callback.Call__(ExceptionHandling::Rethrow) {
+                Ok(result) => {
+                    rooted!(in(*cx) let result = result);
+                    task.promise.resolve(cx, result.handle());
+                },
+                Err(_) => {
+                    rooted!(in(*cx) let mut error = UndefinedValue());
+                    unsafe {
+                        JS_GetPendingException(*cx, error.handle_mut());
+                        JS_ClearPendingException(*cx);
+                    }
+                    task.promise.reject(cx, error.handle());
+                },
+            },
+            None => task.promise.resolve_native(&()),
+        }
+        self.current_priority.set(previous_priority);
+    }
+
+    /// The abort algorithm of a posted task, which rejects its promise and
+    /// removes it from the tasks to run.
+    #[allow(unrooted_must_root)]
+    pub fn abort_task(&self, id: u64, cx: JSContext, reason: HandleValue) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            match tasks.iter().position(|task| task.id == id) {
+                Some(index) => tasks.remove(index),
+                None => return,
+            }
+        };
+        let _ac = enter_realm(&*task.promise);
+        task.promise.reject(cx, reason);
+    }
+}
+
+impl SchedulerMethods for Scheduler {
+    // https://wicg.github.io/scheduling-apis/#dom-scheduler-posttask
+    fn PostTask(
+        &self,
+        callback: Rc<SchedulerPostTaskCallback>,
+        options: &SchedulerPostTaskOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // A task posted with an aborted signal never runs.
+        let signal = options.signal.as_ref().map(|signal| &**signal);
+        if let Some(signal) = signal {
+            if signal.aborted() {
+                let cx = self.global().get_cx();
+                rooted!(in(*cx) let reason = signal.reason());
+                promise.reject(cx, reason.handle());
+                return promise;
+            }
+        }
+
+        // A task without a priority follows the one of its signal, if that's
+        // a TaskSignal.
+        let task_signal = signal.and_then(|signal| signal.downcast::<TaskSignal>());
+        let priority = match (options.priority, task_signal) {
+            (Some(priority), _) => Some(priority),
+            (None, Some(_)) => None,
+            (None, None) => Some(TaskPriority::User_visible),
+        };
+
+        self.schedule_task(Some(callback), &promise, priority, signal, options.delay);
+        promise
+    }
+
+    // https://wicg.github.io/scheduling-apis/#dom-scheduler-yield
+    fn Yield(&self, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        // The continuation inherits the priority of the posted task that
+        // yields, if any.
+        let priority = self
+            .current_priority
+            .get()
+            .unwrap_or(TaskPriority::User_visible);
+        self.schedule_task(None, &promise, Some(priority), None, 0);
+        promise
+    }
+}
+
+/// Queues a posted task once the delay it was posted with elapsed.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct PostedTaskDelayCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    scheduler: Trusted<Scheduler>,
+    id: u64,
+}
+
+impl PostedTaskDelayCallback {
+    pub fn invoke(self) {
+        self.scheduler.root().task_ready(self.id);
+    }
+}
@@ -8,10 +8,12 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::screenorientation::ScreenOrientation;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use embedder_traits::{ScreenOrientationState, ScreenOrientationType};
 use euclid::Size2D;
 use profile_traits::ipc;
 use script_traits::ScriptMsg;
@@ -22,6 +24,7 @@ use webrender_api::units::DeviceIntSize;
 pub struct Screen {
     reflector_: Reflector,
     window: Dom<Window>,
+    orientation: MutNullableDom<ScreenOrientation>,
 }
 
 impl Screen {
@@ -29,6 +32,7 @@ impl Screen {
         Screen {
             reflector_: Reflector::new(),
             window: Dom::from_ref(&window),
+            orientation: Default::default(),
         }
     }
 
@@ -65,6 +69,42 @@ impl Screen {
         let screen = recv.recv().unwrap_or(Size2D::zero());
         (screen.to_f32() / dpr).to_u32()
     }
+
+    /// The orientation reported by the embedder, or the one implied by the
+    /// shape of the screen if it never reported any.
+    fn screen_orientation(&self) -> ScreenOrientationState {
+        let (send, recv) = ipc::channel::<Option<ScreenOrientationState>>(
+            self.global().time_profiler_chan().clone(),
+        )
+        .unwrap();
+        self.window
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::GetScreenOrientation(send))
+            .unwrap();
+        recv.recv()
+            .ok()
+            .and_then(|orientation| orientation)
+            .unwrap_or_else(|| {
+                let size = self.screen_size();
+                let orientation_type = if size.width >= size.height {
+                    ScreenOrientationType::LandscapePrimary
+                } else {
+                    ScreenOrientationType::PortraitPrimary
+                };
+                ScreenOrientationState {
+                    orientation_type,
+                    angle: 0,
+                }
+            })
+    }
+
+    /// Called when the embedder reports that the screen was rotated.
+    pub fn orientation_changed(&self, orientation: ScreenOrientationState) {
+        if let Some(screen_orientation) = self.orientation.get() {
+            screen_orientation.set_orientation(orientation);
+        }
+    }
 }
 
 impl ScreenMethods for Screen {
@@ -97,4 +137,10 @@ impl ScreenMethods for Screen {
     fn PixelDepth(&self) -> u32 {
         24
     }
+
+    // https://w3c.github.io/screen-orientation/#dom-screen-orientation
+    fn Orientation(&self) -> DomRoot<ScreenOrientation> {
+        self.orientation
+            .or_init(|| ScreenOrientation::new(&self.window, self.screen_orientation()))
+    }
 }
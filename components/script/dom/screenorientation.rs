@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::ScreenOrientationBinding::{
+    self, OrientationLockType, OrientationType, ScreenOrientationMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::{
+    EmbedderMsg, ScreenOrientationLock, ScreenOrientationState, ScreenOrientationType,
+};
+use profile_traits::ipc;
+use std::cell::Cell;
+use std::rc::Rc;
+
+// https://w3c.github.io/screen-orientation/#screenorientation-interface
+#[dom_struct]
+pub struct ScreenOrientation {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    orientation: Cell<ScreenOrientationState>,
+    /// The lock requested by the last `lock()` call, with the promise that is
+    /// resolved once the screen satisfies it.
+    #[ignore_malloc_size_of = "promises are hard"]
+    pending_lock: DomRefCell<Option<(ScreenOrientationLock, Rc<Promise>)>>,
+}
+
+impl ScreenOrientation {
+    fn new_inherited(window: &Window, orientation: ScreenOrientationState) -> ScreenOrientation {
+        ScreenOrientation {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+            orientation: Cell::new(orientation),
+            pending_lock: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(window: &Window, orientation: ScreenOrientationState) -> DomRoot<ScreenOrientation> {
+        reflect_dom_object(
+            Box::new(ScreenOrientation::new_inherited(window, orientation)),
+            window,
+            ScreenOrientationBinding::Wrap,
+        )
+    }
+
+    /// <https://w3c.github.io/screen-orientation/#dfn-screen-orientation-change-steps>
+    pub fn set_orientation(&self, orientation: ScreenOrientationState) {
+        if self.orientation.get() == orientation {
+            return;
+        }
+        self.orientation.set(orientation);
+        self.upcast::<EventTarget>().fire_event(atom!("change"));
+
+        let satisfied = match *self.pending_lock.borrow() {
+            Some((lock, _)) => lock.allows(&orientation),
+            None => false,
+        };
+        if satisfied {
+            if let Some((_, promise)) = self.pending_lock.borrow_mut().take() {
+                promise.resolve_native(&());
+            }
+        }
+    }
+
+    /// Rejects the promise of a pending `lock()` call with an `AbortError`.
+    fn abort_pending_lock(&self) {
+        if let Some((_, promise)) = self.pending_lock.borrow_mut().take() {
+            promise.reject_error(Error::Abort);
+        }
+    }
+}
+
+impl ScreenOrientationMethods for ScreenOrientation {
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-lock
+    fn Lock(&self, orientation: OrientationLockType, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+
+        // Step 1.
+        let document = self.window.Document();
+        if !document.is_fully_active() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 3.
+        if document.Hidden() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        let lock = match orientation {
+            OrientationLockType::Any => ScreenOrientationLock::Any,
+            OrientationLockType::Natural => ScreenOrientationLock::Natural,
+            OrientationLockType::Landscape => ScreenOrientationLock::Landscape,
+            OrientationLockType::Portrait => ScreenOrientationLock::Portrait,
+            OrientationLockType::Portrait_primary => ScreenOrientationLock::PortraitPrimary,
+            OrientationLockType::Portrait_secondary => ScreenOrientationLock::PortraitSecondary,
+            OrientationLockType::Landscape_primary => ScreenOrientationLock::LandscapePrimary,
+            OrientationLockType::Landscape_secondary => ScreenOrientationLock::LandscapeSecondary,
+        };
+
+        // Step 5.
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.window
+            .send_to_embedder(EmbedderMsg::LockScreenOrientation(lock, sender));
+        if !receiver.recv().unwrap_or(false) {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        }
+
+        // Step 6.
+        self.abort_pending_lock();
+
+        // Steps 7-8.
+        if lock.allows(&self.orientation.get()) {
+            promise.resolve_native(&());
+        } else {
+            *self.pending_lock.borrow_mut() = Some((lock, promise.clone()));
+        }
+        promise
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-unlock
+    fn Unlock(&self) {
+        self.abort_pending_lock();
+        self.window
+            .send_to_embedder(EmbedderMsg::UnlockScreenOrientation);
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-type
+    fn Type(&self) -> OrientationType {
+        match self.orientation.get().orientation_type {
+            ScreenOrientationType::PortraitPrimary => OrientationType::Portrait_primary,
+            ScreenOrientationType::PortraitSecondary => OrientationType::Portrait_secondary,
+            ScreenOrientationType::LandscapePrimary => OrientationType::Landscape_primary,
+            ScreenOrientationType::LandscapeSecondary => OrientationType::Landscape_secondary,
+        }
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-angle
+    fn Angle(&self) -> u16 {
+        self.orientation.get().angle
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+}
@@ -0,0 +1,485 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::range::{bp_position, Range};
+use crate::dom::text::Text;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum Direction {
+    Forwards,
+    Backwards,
+    Directionless,
+}
+
+// https://w3c.github.io/selection-api/#selection-interface
+#[dom_struct]
+pub struct Selection {
+    reflector_: Reflector,
+    document: Dom<Document>,
+    range: MutNullableDom<Range>,
+    direction: Cell<Direction>,
+    /// Whether a selectionchange event is already queued for this selection.
+    task_queued: Cell<bool>,
+    /// The text nodes that layout currently paints as selected.
+    painted_text: DomRefCell<Vec<Dom<Text>>>,
+}
+
+impl Selection {
+    fn new_inherited(document: &Document) -> Selection {
+        Selection {
+            reflector_: Reflector::new(),
+            document: Dom::from_ref(document),
+            range: MutNullableDom::new(None),
+            direction: Cell::new(Direction::Directionless),
+            task_queued: Cell::new(false),
+            painted_text: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(document: &Document) -> DomRoot<Selection> {
+        reflect_dom_object(
+            Box::new(Selection::new_inherited(document)),
+            document.window(),
+            SelectionBinding::Wrap,
+        )
+    }
+
+    fn set_range(&self, range: Option<&Range>) {
+        if let Some(old_range) = self.range.get() {
+            if range.map_or(false, |range| &*old_range == range) {
+                return;
+            }
+            old_range.disassociate_selection(self);
+        }
+        if let Some(range) = range {
+            range.associate_selection(self);
+        }
+        self.range.set(range);
+        self.queue_selectionchange_task();
+    }
+
+    /// Whether `node` is in the document associated with this selection,
+    /// which is the only place the range of a selection can be.
+    fn is_in_document(&self, node: &Node) -> bool {
+        node.inclusive_ancestors(ShadowIncluding::No)
+            .last()
+            .map_or(false, |root| {
+                root.downcast::<Document>()
+                    .map_or(false, |document| document == &*self.document)
+            })
+    }
+
+    fn new_range(&self, start: (&Node, u32), end: (&Node, u32)) -> DomRoot<Range> {
+        Range::new(&self.document, start.0, start.1, end.0, end.1)
+    }
+
+    // https://w3c.github.io/selection-api/#selectionchange-event
+    pub fn queue_selectionchange_task(&self) {
+        if self.task_queued.get() {
+            return;
+        }
+        self.task_queued.set(true);
+
+        let window = self.document.window();
+        let this = Trusted::new(self);
+        // FIXME(nox): Why are errors silenced here?
+        let _ = window.task_manager().user_interaction_task_source().queue(
+            task!(selectionchange_task_steps: move || {
+                let this = this.root();
+                this.task_queued.set(false);
+                this.update_painted_text();
+                this.document.upcast::<EventTarget>().fire_event(atom!("selectionchange"));
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// Tells layout which part of each text node is in the selection's range,
+    /// so that it gets painted as selected.
+    fn update_painted_text(&self) {
+        let mut painted_text = self.painted_text.borrow_mut();
+        for text in painted_text.drain(..) {
+            text.upcast::<Node>().set_selected_range(None);
+        }
+
+        let range = match self.range.get() {
+            Some(ref range) if !range.Collapsed() => range.clone(),
+            _ => return,
+        };
+        let start_container = range.StartContainer();
+        let end_container = range.EndContainer();
+
+        let common_ancestor = range.CommonAncestorContainer();
+        for node in common_ancestor.traverse_preorder(ShadowIncluding::No) {
+            let text = match node.downcast::<Text>() {
+                Some(text) => text,
+                None => continue,
+            };
+            let is_start = node == start_container;
+            let is_end = node == end_container;
+            if !is_start && !is_end && !range.contains(&node) {
+                continue;
+            }
+
+            let data = text.upcast::<CharacterData>().Data();
+            let start = if is_start { range.StartOffset() } else { 0 };
+            let end = if is_end {
+                range.EndOffset()
+            } else {
+                node.len()
+            };
+            if start >= end {
+                continue;
+            }
+            node.set_selected_range(Some(
+                utf16_offset_to_utf8(&data, start)..utf16_offset_to_utf8(&data, end),
+            ));
+            painted_text.push(Dom::from_ref(text));
+        }
+    }
+
+    fn anchor(&self) -> Option<(DomRoot<Node>, u32)> {
+        self.range.get().map(|range| match self.direction.get() {
+            Direction::Backwards => (range.EndContainer(), range.EndOffset()),
+            _ => (range.StartContainer(), range.StartOffset()),
+        })
+    }
+
+    fn focus(&self) -> Option<(DomRoot<Node>, u32)> {
+        self.range.get().map(|range| match self.direction.get() {
+            Direction::Backwards => (range.StartContainer(), range.StartOffset()),
+            _ => (range.EndContainer(), range.EndOffset()),
+        })
+    }
+}
+
+impl SelectionMethods for Selection {
+    // https://w3c.github.io/selection-api/#dom-selection-anchornode
+    fn GetAnchorNode(&self) -> Option<DomRoot<Node>> {
+        self.anchor().map(|(node, _)| node)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-anchoroffset
+    fn AnchorOffset(&self) -> u32 {
+        self.anchor().map_or(0, |(_, offset)| offset)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-focusnode
+    fn GetFocusNode(&self) -> Option<DomRoot<Node>> {
+        self.focus().map(|(node, _)| node)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-focusoffset
+    fn FocusOffset(&self) -> u32 {
+        self.focus().map_or(0, |(_, offset)| offset)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-iscollapsed
+    fn IsCollapsed(&self) -> bool {
+        self.range.get().map_or(true, |range| range.Collapsed())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-rangecount
+    fn RangeCount(&self) -> u32 {
+        if self.range.get().is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-type
+    fn Type(&self) -> DOMString {
+        match self.range.get() {
+            None => DOMString::from("None"),
+            Some(ref range) if range.Collapsed() => DOMString::from("Caret"),
+            Some(_) => DOMString::from("Range"),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-getrangeat
+    fn GetRangeAt(&self, index: u32) -> Fallible<DomRoot<Range>> {
+        match self.range.get() {
+            Some(range) if index == 0 => Ok(range),
+            _ => Err(Error::IndexSize),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-addrange
+    fn AddRange(&self, range: &Range) {
+        // Step 1.
+        if !self.is_in_document(&range.StartContainer()) {
+            return;
+        }
+
+        // Step 2.
+        if self.range.get().is_some() {
+            return;
+        }
+
+        // Step 3.
+        self.set_range(Some(range));
+        self.direction.set(Direction::Forwards);
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-removerange
+    fn RemoveRange(&self, range: &Range) -> ErrorResult {
+        match self.range.get() {
+            Some(ref own_range) if &**own_range == range => {
+                self.set_range(None);
+                Ok(())
+            },
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-removeallranges
+    fn RemoveAllRanges(&self) {
+        self.set_range(None);
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-empty
+    fn Empty(&self) {
+        self.RemoveAllRanges();
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapse
+    fn Collapse(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
+        // Step 1.
+        let node = match node {
+            Some(node) => node,
+            None => {
+                self.RemoveAllRanges();
+                return Ok(());
+            },
+        };
+
+        // Step 2.
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        if offset > node.len() {
+            return Err(Error::IndexSize);
+        }
+
+        // Step 3.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+
+        // Steps 4-5.
+        let range = self.new_range((node, offset), (node, offset));
+        self.set_range(Some(&range));
+        self.direction.set(Direction::Directionless);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-setposition
+    fn SetPosition(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
+        self.Collapse(node, offset)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapsetostart
+    fn CollapseToStart(&self) -> ErrorResult {
+        let range = self.range.get().ok_or(Error::InvalidState)?;
+        let start = range.StartContainer();
+        let new_range =
+            self.new_range((&start, range.StartOffset()), (&start, range.StartOffset()));
+        self.set_range(Some(&new_range));
+        self.direction.set(Direction::Directionless);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapsetoend
+    fn CollapseToEnd(&self) -> ErrorResult {
+        let range = self.range.get().ok_or(Error::InvalidState)?;
+        let end = range.EndContainer();
+        let new_range = self.new_range((&end, range.EndOffset()), (&end, range.EndOffset()));
+        self.set_range(Some(&new_range));
+        self.direction.set(Direction::Directionless);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-extend
+    fn Extend(&self, node: &Node, offset: u32) -> ErrorResult {
+        // Step 1.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+
+        // Step 2.
+        let range = self.range.get().ok_or(Error::InvalidState)?;
+
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        if offset > node.len() {
+            return Err(Error::IndexSize);
+        }
+
+        // Steps 3-5.
+        let (anchor, anchor_offset) = self.anchor().unwrap();
+        let range_root = range
+            .StartContainer()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .last()
+            .unwrap();
+        let node_root = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .last()
+            .unwrap();
+
+        // Steps 6-8.
+        let focus_is_before_anchor =
+            bp_position(node, offset, &anchor, anchor_offset) == Some(Ordering::Less);
+        let new_range = if range_root != node_root {
+            self.new_range((node, offset), (node, offset))
+        } else if focus_is_before_anchor {
+            self.new_range((node, offset), (&anchor, anchor_offset))
+        } else {
+            self.new_range((&anchor, anchor_offset), (node, offset))
+        };
+
+        // Steps 9-10.
+        self.set_range(Some(&new_range));
+        self.direction.set(if focus_is_before_anchor {
+            Direction::Backwards
+        } else {
+            Direction::Forwards
+        });
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-setbaseandextent
+    fn SetBaseAndExtent(
+        &self,
+        anchor_node: &Node,
+        anchor_offset: u32,
+        focus_node: &Node,
+        focus_offset: u32,
+    ) -> ErrorResult {
+        // Step 1.
+        if anchor_node.is_doctype() || focus_node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        if anchor_offset > anchor_node.len() || focus_offset > focus_node.len() {
+            return Err(Error::IndexSize);
+        }
+
+        // Step 2.
+        if !self.is_in_document(anchor_node) || !self.is_in_document(focus_node) {
+            return Ok(());
+        }
+
+        // Steps 3-6.
+        let focus_is_before_anchor =
+            bp_position(focus_node, focus_offset, anchor_node, anchor_offset) ==
+                Some(Ordering::Less);
+        let new_range = if focus_is_before_anchor {
+            self.new_range((focus_node, focus_offset), (anchor_node, anchor_offset))
+        } else {
+            self.new_range((anchor_node, anchor_offset), (focus_node, focus_offset))
+        };
+
+        // Steps 7-8.
+        self.set_range(Some(&new_range));
+        self.direction.set(if focus_is_before_anchor {
+            Direction::Backwards
+        } else {
+            Direction::Forwards
+        });
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-selectallchildren
+    fn SelectAllChildren(&self, node: &Node) -> ErrorResult {
+        // Step 1.
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+
+        // Step 2.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+
+        // Steps 3-6.
+        let child_count = node.children_count();
+        let new_range = self.new_range((node, 0), (node, child_count));
+        self.set_range(Some(&new_range));
+        self.direction.set(Direction::Forwards);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-deletefromdocument
+    fn DeleteFromDocument(&self) -> ErrorResult {
+        match self.range.get() {
+            Some(range) => range.DeleteContents(),
+            None => Ok(()),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-containsnode
+    fn ContainsNode(&self, node: &Node, allow_partial_containment: bool) -> bool {
+        if !self.is_in_document(node) {
+            return false;
+        }
+        let range = match self.range.get() {
+            Some(range) => range,
+            None => return false,
+        };
+
+        let start_before = |node: &Node, offset: u32| {
+            bp_position(&range.StartContainer(), range.StartOffset(), node, offset) !=
+                Some(Ordering::Greater)
+        };
+        let end_after = |node: &Node, offset: u32| {
+            bp_position(&range.EndContainer(), range.EndOffset(), node, offset) !=
+                Some(Ordering::Less)
+        };
+        if allow_partial_containment {
+            start_before(node, node.len()) && end_after(node, 0)
+        } else {
+            start_before(node, 0) && end_after(node, node.len())
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-stringifier
+    fn Stringifier(&self) -> DOMString {
+        self.range
+            .get()
+            .map_or_else(DOMString::new, |range| range.Stringifier())
+    }
+}
+
+/// Converts an offset in UTF-16 code units to one in UTF-8 bytes.
+fn utf16_offset_to_utf8(data: &str, offset: u32) -> usize {
+    let mut code_units = 0;
+    for (index, c) in data.char_indices() {
+        if code_units >= offset as usize {
+            return index;
+        }
+        code_units += c.len_utf16();
+    }
+    data.len()
+}
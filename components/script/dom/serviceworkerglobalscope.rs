@@ -3,23 +3,27 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::enter_realm;
-use crate::devtools;
 use crate::dom::abstractworker::WorkerScriptMsg;
 use crate::dom::abstractworkerglobalscope::{run_worker_event_loop, WorkerEventLoopMethods};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding::ServiceWorkerGlobalScopeMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::{DomRoot, RootCollection, ThreadLocalStackRoots};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom, RootCollection, ThreadLocalStackRoots};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone;
+use crate::dom::cookiestore::CookieStore;
 use crate::dom::dedicatedworkerglobalscope::AutoWorkerReset;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::extendableevent::ExtendableEvent;
 use crate::dom::extendablemessageevent::ExtendableMessageEvent;
+use crate::dom::fetchevent::FetchEvent;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::headers::Guard;
 use crate::dom::messageevent::MessageEvent;
+use crate::dom::request::Request;
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::fetch::load_whole_resource;
@@ -36,12 +40,14 @@ use ipc_channel::router::ROUTER;
 use js::jsapi::{JSContext, JS_AddInterruptCallback};
 use js::jsval::UndefinedValue;
 use msg::constellation_msg::PipelineId;
+use net_traits::request::ServiceWorkersMode;
 use net_traits::request::{CredentialsMode, Destination, ParserMetadata, Referrer, RequestBuilder};
 use net_traits::{CustomResponseMediator, IpcSend};
 use script_traits::{ScopeThings, ServiceWorkerMsg, WorkerGlobalScopeInit, WorkerScriptLoadOrigin};
 use servo_config::pref;
 use servo_rand::random;
 use servo_url::ServoUrl;
+use std::cell::Cell;
 use std::thread;
 use std::time::{Duration, Instant};
 use style::thread_state::{self, ThreadState};
@@ -158,10 +164,20 @@ pub struct ServiceWorkerGlobalScope {
     #[ignore_malloc_size_of = "Defined in std"]
     time_out_port: Receiver<Instant>,
 
+    /// Whether the time-out message has been received, the worker keeps running
+    /// past it while the lifetime of some event is extended.
+    timed_out: Cell<bool>,
+
+    /// The number of extend lifetime promises, across all events, that have not settled yet.
+    /// https://w3c.github.io/ServiceWorker/#extendableevent-pending-promises-count
+    pending_lifetime_promises: Cell<usize>,
+
     #[ignore_malloc_size_of = "Defined in std"]
     swmanager_sender: IpcSender<ServiceWorkerMsg>,
 
     scope_url: ServoUrl,
+
+    cookie_store: MutNullableDom<CookieStore>,
 }
 
 impl WorkerEventLoopMethods for ServiceWorkerGlobalScope {
@@ -210,12 +226,16 @@ impl ServiceWorkerGlobalScope {
                 runtime,
                 from_devtools_receiver,
                 None,
+                None,
             ),
             task_queue: TaskQueue::new(receiver, own_sender.clone()),
             own_sender: own_sender,
             time_out_port,
+            timed_out: Cell::new(false),
+            pending_lifetime_promises: Cell::new(0),
             swmanager_sender: swmanager_sender,
             scope_url: scope_url,
+            cookie_store: Default::default(),
         }
     }
 
@@ -286,6 +306,7 @@ impl ServiceWorkerGlobalScope {
 
                 let request = RequestBuilder::new(script_url.clone())
                     .destination(Destination::ServiceWorker)
+                    .service_workers_mode(ServiceWorkersMode::None)
                     .credentials_mode(CredentialsMode::Include)
                     .parser_metadata(ParserMetadata::NotParserInserted)
                     .use_url_credentials(true)
@@ -355,6 +376,16 @@ impl ServiceWorkerGlobalScope {
                             while !scope.is_closing() || !global.has_timed_out() {
                                 run_worker_event_loop(&*global, None);
                             }
+                            // Tell the constellation to drop the sender to our
+                            // message-port router, and to disentangle our ports.
+                            scope.upcast::<GlobalScope>().remove_message_ports_router();
+                            // Tell the constellation to drop the sender to our
+                            // broadcast router, if there is any.
+                            scope
+                                .upcast::<GlobalScope>()
+                                .remove_broadcast_channel_router();
+                            // Revoke the Blob URLs created by this worker.
+                            scope.upcast::<GlobalScope>().revoke_blob_urls();
                         },
                         reporter_name,
                         scope.script_chan(),
@@ -367,18 +398,8 @@ impl ServiceWorkerGlobalScope {
     fn handle_mixed_message(&self, msg: MixedMessage) -> bool {
         match msg {
             MixedMessage::FromDevtools(msg) => {
-                match msg {
-                    DevtoolScriptControlMsg::EvaluateJS(_pipe_id, string, sender) => {
-                        devtools::handle_evaluate_js(self.upcast(), string, sender)
-                    },
-                    DevtoolScriptControlMsg::GetCachedMessages(pipe_id, message_types, sender) => {
-                        devtools::handle_get_cached_messages(pipe_id, message_types, sender)
-                    },
-                    DevtoolScriptControlMsg::WantsLiveNotifications(_pipe_id, bool_val) => {
-                        devtools::handle_wants_live_notifications(self.upcast(), bool_val)
-                    },
-                    _ => debug!("got an unusable devtools control message inside the worker!"),
-                }
+                self.upcast::<WorkerGlobalScope>()
+                    .handle_devtools_message(msg);
                 true
             },
             MixedMessage::FromServiceWorker(msg) => {
@@ -392,6 +413,11 @@ impl ServiceWorkerGlobalScope {
         // Note: this should be included in the `select` inside `run_worker_event_loop`,
         // otherwise a block on the select can prevent the timeout.
         if self.time_out_port.try_recv().is_ok() {
+            self.timed_out.set(true);
+        }
+        // Events whose lifetime was extended keep the worker alive.
+        // https://w3c.github.io/ServiceWorker/#service-worker-lifetime
+        if self.timed_out.get() && self.pending_lifetime_promises.get() == 0 {
             let _ = self
                 .swmanager_sender
                 .send(ServiceWorkerMsg::Timeout(self.scope_url.clone()));
@@ -424,13 +450,7 @@ impl ServiceWorkerGlobalScope {
             CommonWorker(WorkerScriptMsg::Common(msg)) => {
                 self.upcast::<WorkerGlobalScope>().process_event(msg);
             },
-            Response(mediator) => {
-                // TODO XXXcreativcoder This will eventually use a FetchEvent interface to fire event
-                // when we have the Request and Response dom api's implemented
-                // https://w3c.github.io/ServiceWorker/#fetchevent-interface
-                self.upcast::<EventTarget>().fire_event(atom!("fetch"));
-                let _ = mediator.response_chan.send(None);
-            },
+            Response(mediator) => self.dispatch_fetch(mediator),
             WakeUp => {},
         }
     }
@@ -441,6 +461,46 @@ impl ServiceWorkerGlobalScope {
         })
     }
 
+    /// Keep the worker running until a matching call to `release_lifetime`.
+    pub fn extend_lifetime(&self) {
+        self.pending_lifetime_promises
+            .set(self.pending_lifetime_promises.get() + 1);
+    }
+
+    pub fn release_lifetime(&self) {
+        self.pending_lifetime_promises
+            .set(self.pending_lifetime_promises.get() - 1);
+    }
+
+    /// https://w3c.github.io/ServiceWorker/#handle-fetch Step 24
+    fn dispatch_fetch(&self, mediator: CustomResponseMediator) {
+        let CustomResponseMediator {
+            response_chan,
+            request,
+            ..
+        } = mediator;
+        let global = self.upcast::<GlobalScope>();
+        let _ac = enter_realm(self);
+        let request = request.build();
+        let headers = request.headers.clone();
+        let request = Request::from_net_request(global, request);
+        request.Headers().set_headers(headers);
+        request.Headers().set_guard(Guard::Immutable);
+        let event = FetchEvent::new(
+            global,
+            atom!("fetch"),
+            false,
+            true,
+            &request,
+            DOMString::new(),
+            DOMString::new(),
+            false,
+        );
+        event.set_response_chan(response_chan);
+        let status = event.upcast::<Event>().fire(self.upcast());
+        event.finish_dispatch(status);
+    }
+
     fn dispatch_activate(&self) {
         let event = ExtendableEvent::new(self, atom!("activate"), false, false);
         let event = (&*event).upcast::<Event>();
@@ -459,9 +519,18 @@ unsafe extern "C" fn interrupt_callback(cx: *mut JSContext) -> bool {
 }
 
 impl ServiceWorkerGlobalScopeMethods for ServiceWorkerGlobalScope {
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onfetch
+    event_handler!(fetch, GetOnfetch, SetOnfetch);
+
     // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onmessage
     event_handler!(message, GetOnmessage, SetOnmessage);
 
     // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onmessageerror
     event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+
+    // https://wicg.github.io/cookie-store/#dom-serviceworkerglobalscope-cookiestore
+    fn CookieStore(&self) -> DomRoot<CookieStore> {
+        self.cookie_store
+            .or_init(|| CookieStore::new(self.upcast::<GlobalScope>()))
+    }
 }
@@ -2,24 +2,33 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::compartments::InCompartment;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::{
+    NotificationOptions, NotificationPermission,
+};
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerBinding::ServiceWorkerState;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::ServiceWorkerUpdateViaCache;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::{
     ServiceWorkerRegistrationMethods, Wrap,
 };
+use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
-use crate::dom::bindings::str::{ByteString, USVString};
+use crate::dom::bindings::str::{ByteString, DOMString, USVString};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::navigationpreloadmanager::NavigationPreloadManager;
+use crate::dom::notification;
+use crate::dom::promise::Promise;
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::workerglobalscope::prepare_workerscope_init;
 use dom_struct::dom_struct;
-use script_traits::{ScopeThings, WorkerScriptLoadOrigin};
+use embedder_traits::EmbedderMsg;
+use script_traits::{ScopeThings, ScriptMsg, WorkerScriptLoadOrigin};
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::rc::Rc;
 
 #[dom_struct]
 pub struct ServiceWorkerRegistration {
@@ -183,4 +192,50 @@ impl ServiceWorkerRegistrationMethods for ServiceWorkerRegistration {
         self.navigation_preload
             .or_init(|| NavigationPreloadManager::new(&self.global(), &self))
     }
+
+    // https://notifications.spec.whatwg.org/#dom-serviceworkerregistration-shownotification
+    fn ShowNotification(
+        &self,
+        title: DOMString,
+        options: &NotificationOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+
+        // Step 3.
+        if self.active.is_none() {
+            promise.reject_error(Error::Type(
+                "The registration does not have an active worker".to_owned(),
+            ));
+            return promise;
+        }
+
+        // Step 4.
+        let notification = match notification::create_notification(&global, title, options) {
+            Ok(notification) => notification,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Step 5.1.
+        if notification::current_permission(&global) != NotificationPermission::Granted {
+            promise.reject_error(Error::Type(
+                "The notifications permission is not granted".to_owned(),
+            ));
+            return promise;
+        }
+
+        // Steps 5.2-5.4.
+        let msg = EmbedderMsg::ShowNotification(notification);
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::ForwardToEmbedder(msg));
+        promise.resolve_native(&());
+
+        // Step 6.
+        promise
+    }
 }
@@ -24,10 +24,11 @@ use html5ever::serialize::TraversalScope::IncludeNode;
 use html5ever::serialize::{AttrRef, Serialize, Serializer};
 use html5ever::tokenizer::{Tokenizer as HtmlTokenizer, TokenizerOpts, TokenizerResult};
 use html5ever::tree_builder::{Tracer as HtmlTracer, TreeBuilder, TreeBuilderOpts};
-use html5ever::QualName;
+use html5ever::{LocalName, QualName};
 use js::jsapi::JSTracer;
 use servo_url::ServoUrl;
 use std::io;
+use style::attr::AttrValue;
 
 #[derive(JSTraceable, MallocSizeOf)]
 #[unrooted_must_root_lint::must_root]
@@ -121,7 +122,7 @@ unsafe impl JSTraceable for HtmlTokenizer<TreeBuilder<Dom<Node>, Sink>> {
 
 fn start_element<S: Serializer>(node: &Element, serializer: &mut S) -> io::Result<()> {
     let name = QualName::new(None, node.namespace().clone(), node.local_name().clone());
-    let attrs = node
+    let mut attrs = node
         .attrs()
         .iter()
         .map(|attr| {
@@ -130,6 +131,14 @@ fn start_element<S: Serializer>(node: &Element, serializer: &mut S) -> io::Resul
             (qname, value)
         })
         .collect::<Vec<_>>();
+    // https://html.spec.whatwg.org/multipage/#serialising-html-fragments
+    let is_name = LocalName::from("is");
+    if let Some(is) = node.get_is() {
+        if !node.has_attribute(&is_name) {
+            let qname = QualName::new(None, ns!(), is_name);
+            attrs.insert(0, (qname, AttrValue::String(is.to_string())));
+        }
+    }
     let attr_refs = attrs.iter().map(|&(ref qname, ref value)| {
         let ar: AttrRef = (&qname, &**value);
         ar
@@ -34,6 +34,7 @@ use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::vtable_for;
 use crate::network_listener::PreInvoke;
+use crate::reporting;
 use crate::script_thread::ScriptThread;
 use content_security_policy::{self as csp, CspList};
 use dom_struct::dom_struct;
@@ -762,6 +763,12 @@ impl FetchResponseListener for ParserContext {
             Some(csp_list)
         });
 
+        // https://w3c.github.io/reporting/#document-configuration
+        let reporting_endpoints = metadata.as_ref().and_then(|m| {
+            let headers = m.headers.as_ref()?;
+            Some(reporting::parse_reporting_endpoints(headers, &m.final_url))
+        });
+
         let parser = match ScriptThread::page_headers_available(&self.id, metadata) {
             Some(parser) => parser,
             None => return,
@@ -771,6 +778,13 @@ impl FetchResponseListener for ParserContext {
         }
 
         parser.document.set_csp_list(csp_list);
+        if let Some(reporting_endpoints) = reporting_endpoints {
+            parser
+                .document
+                .window()
+                .upcast::<GlobalScope>()
+                .set_reporting_endpoints(reporting_endpoints);
+        }
 
         self.parser = Some(Trusted::new(&*parser));
 
@@ -905,9 +919,8 @@ impl FetchResponseListener for ParserContext {
 
         let document = &parser.document;
 
-        //TODO nav_start and nav_start_precise
         let performance_entry =
-            PerformanceNavigationTiming::new(&document.global(), 0, 0, &document);
+            PerformanceNavigationTiming::new(&document.global(), &document, &self.resource_timing);
         document
             .global()
             .performance()
@@ -1235,7 +1248,9 @@ fn create_element_for_token(
 
     // Step 8.
     for attr in attrs {
-        element.set_attribute_from_parser(attr.name, attr.value, None);
+        // Keep the prefix of namespaced attributes, like `xlink:href`.
+        let prefix = attr.name.prefix.clone();
+        element.set_attribute_from_parser(attr.name, attr.value, prefix);
     }
 
     // Step 9.
@@ -0,0 +1,224 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::SharedWorkerBinding;
+use crate::dom::bindings::codegen::Bindings::SharedWorkerBinding::SharedWorkerMethods;
+use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
+use crate::dom::bindings::codegen::UnionTypes::StringOrWorkerOptions;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::USVString;
+use crate::dom::bindings::structuredclone;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageport::MessagePort;
+use crate::dom::sharedworkerglobalscope::SharedWorkerGlobalScope;
+use crate::dom::workerglobalscope::prepare_workerscope_init;
+use crate::task_source::{TaskSource, TaskSourceName};
+use devtools_traits::{DevtoolsPageInfo, ScriptToDevtoolsControlMsg};
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcReceiver};
+use ipc_channel::router::ROUTER;
+use js::jsval::UndefinedValue;
+use js::rust::{CustomAutoRooter, CustomAutoRooterGuard};
+use msg::constellation_msg::SharedWorkerId;
+use net_traits::request::CredentialsMode;
+use script_traits::{ScriptMsg, SharedWorkerControlMsg, SharedWorkerKey, WorkerScriptLoadOrigin};
+use servo_url::ServoUrl;
+
+// https://html.spec.whatwg.org/multipage/#sharedworker
+#[dom_struct]
+pub struct SharedWorker {
+    eventtarget: EventTarget,
+    port: Dom<MessagePort>,
+}
+
+impl SharedWorker {
+    fn new_inherited(port: &MessagePort) -> SharedWorker {
+        SharedWorker {
+            eventtarget: EventTarget::new_inherited(),
+            port: Dom::from_ref(port),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, port: &MessagePort) -> DomRoot<SharedWorker> {
+        reflect_dom_object(
+            Box::new(SharedWorker::new_inherited(port)),
+            global,
+            SharedWorkerBinding::Wrap,
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-sharedworker
+    pub fn Constructor(
+        global: &GlobalScope,
+        script_url: USVString,
+        options: StringOrWorkerOptions,
+    ) -> Fallible<DomRoot<SharedWorker>> {
+        // Step 1.
+        let (name, worker_type, credentials) = match options {
+            StringOrWorkerOptions::String(name) => (name, WorkerType::Classic, None),
+            StringOrWorkerOptions::WorkerOptions(options) => {
+                (options.name, options.type_, Some(options.credentials))
+            },
+        };
+        // The credentials option is only used by module workers,
+        // classic worker scripts are fetched with same-origin credentials.
+        let credentials = match (worker_type, credentials) {
+            (WorkerType::Module, Some(credentials)) => credentials.into(),
+            _ => CredentialsMode::CredentialsSameOrigin,
+        };
+
+        // Step 5-6.
+        let worker_url = match global.api_base_url().join(&script_url) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::Syntax),
+        };
+
+        // Step 7-9.
+        let outside_port = MessagePort::new(global);
+        let inside_port = MessagePort::new(global);
+        global.track_message_port(&*outside_port, None);
+        global.track_message_port(&*inside_port, None);
+        global.entangle_ports(
+            outside_port.message_port_id().clone(),
+            inside_port.message_port_id().clone(),
+        );
+        let worker = SharedWorker::new(global, &outside_port);
+
+        // The inside port is transferred to the worker along with the connect request,
+        // and delivered to it in a `connect` event.
+        let cx = global.get_cx();
+        rooted!(in(*cx) let message = UndefinedValue());
+        let mut transfer =
+            CustomAutoRooter::new(vec![inside_port.reflector().get_jsobject().get()]);
+        let transfer = CustomAutoRooterGuard::new(*cx, &mut transfer);
+        let data = structuredclone::write(cx, message.handle(), Some(transfer))?;
+
+        // Step 11, the constellation keeps track of the running shared workers,
+        // and connects to a matching one if there is any.
+        let key = SharedWorkerKey {
+            origin: global.origin().immutable().clone(),
+            name: String::from(name),
+            url: worker_url.clone(),
+        };
+        let id = SharedWorkerId::new();
+        let (control_sender, control_receiver) = ipc::channel().unwrap();
+        let (response_sender, response_receiver) = ipc::channel().unwrap();
+
+        // The constellation replies whether this constructor is responsible for
+        // running a new worker, which is then done in a task.
+        let trusted_worker = Trusted::new(&*worker);
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let mut new_worker = Some((
+            key.clone(),
+            id,
+            control_receiver,
+            worker_url,
+            worker_type,
+            credentials,
+        ));
+        ROUTER.add_route(
+            response_receiver.to_opaque(),
+            Box::new(move |message| {
+                let is_new: bool = message.to().unwrap_or(false);
+                let (key, id, control_receiver, worker_url, worker_type, credentials) =
+                    match new_worker.take() {
+                        Some(new_worker) if is_new => new_worker,
+                        _ => return,
+                    };
+                let worker = trusted_worker.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(run_shared_worker: move || {
+                        let worker = worker.root();
+                        SharedWorker::run_new_worker(
+                            &worker.global(),
+                            key,
+                            id,
+                            control_receiver,
+                            worker_url,
+                            worker_type,
+                            credentials,
+                        );
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::ConnectToSharedWorker(
+                key,
+                id,
+                data,
+                control_sender,
+                response_sender,
+            ))
+            .unwrap();
+
+        Ok(worker)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-sharedworker> step 11.4,
+    /// run a new worker, which the constellation has registered under `key` and `id`.
+    fn run_new_worker(
+        global: &GlobalScope,
+        key: SharedWorkerKey,
+        id: SharedWorkerId,
+        control_receiver: IpcReceiver<SharedWorkerControlMsg>,
+        worker_url: ServoUrl,
+        worker_type: WorkerType,
+        credentials: CredentialsMode,
+    ) {
+        let worker_load_origin = WorkerScriptLoadOrigin {
+            referrer_url: None,
+            referrer_policy: global.get_referrer_policy(),
+            pipeline_id: Some(global.pipeline_id()),
+        };
+
+        let (devtools_sender, devtools_receiver) = ipc::channel().unwrap();
+        let worker_id = global.get_next_worker_id();
+        if let Some(ref chan) = global.devtools_chan() {
+            let pipeline_id = global.pipeline_id();
+            let title = format!("SharedWorker for {}", worker_url);
+            let page_info = DevtoolsPageInfo {
+                title: title,
+                url: worker_url.clone(),
+            };
+            let _ = chan.send(ScriptToDevtoolsControlMsg::NewGlobal(
+                (pipeline_id, Some(worker_id)),
+                devtools_sender.clone(),
+                page_info,
+            ));
+        }
+
+        let init = prepare_workerscope_init(global, Some(devtools_sender));
+
+        SharedWorkerGlobalScope::run_worker_scope(
+            init,
+            worker_url,
+            devtools_receiver,
+            key,
+            id,
+            control_receiver,
+            worker_load_origin,
+            worker_type,
+            credentials,
+            global.image_cache(),
+        );
+    }
+}
+
+impl SharedWorkerMethods for SharedWorker {
+    // https://html.spec.whatwg.org/multipage/#dom-sharedworker-port
+    fn Port(&self) -> DomRoot<MessagePort> {
+        DomRoot::from_ref(&*self.port)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#handler-abstractworker-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+}
@@ -0,0 +1,494 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::enter_realm;
+use crate::dom::abstractworker::WorkerScriptMsg;
+use crate::dom::abstractworkerglobalscope::{
+    interrupt_callback, run_worker_event_loop, WorkerEventLoopMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SharedWorkerGlobalScopeBinding;
+use crate::dom::bindings::codegen::Bindings::SharedWorkerGlobalScopeBinding::SharedWorkerGlobalScopeMethods;
+use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
+use crate::dom::bindings::codegen::UnionTypes::WindowProxyOrMessagePortOrServiceWorker;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{DomRoot, RootCollection, ThreadLocalStackRoots};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone;
+use crate::dom::dedicatedworkerglobalscope::AutoWorkerReset;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageevent::MessageEvent;
+use crate::dom::worker::TrustedWorkerAddress;
+use crate::dom::workerglobalscope::WorkerGlobalScope;
+use crate::script_runtime::{
+    new_rt_and_cx, CommonScriptMsg, JSContext as SafeJSContext, Runtime, ScriptChan, ScriptPort,
+};
+use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
+use crate::task_source::TaskSourceName;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use devtools_traits::DevtoolScriptControlMsg;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcReceiver;
+use ipc_channel::router::ROUTER;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::JS_AddInterruptCallback;
+use js::jsval::UndefinedValue;
+use msg::constellation_msg::{PipelineId, SharedWorkerId};
+use net_traits::image_cache::ImageCache;
+use net_traits::request::{CredentialsMode, Destination, ParserMetadata};
+use net_traits::request::{Referrer, RequestBuilder, RequestMode};
+use script_traits::{ScriptMsg, SharedWorkerControlMsg, SharedWorkerKey, StructuredSerializedData};
+use script_traits::{WorkerGlobalScopeInit, WorkerScriptLoadOrigin};
+use servo_rand::random;
+use servo_url::ServoUrl;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use style::thread_state::{self, ThreadState};
+
+/// Messages used to control the shared worker event loop
+pub enum SharedWorkerScriptMsg {
+    /// Message common to all workers
+    CommonWorker(WorkerScriptMsg),
+    /// Message from the constellation, connecting a new `SharedWorker`
+    /// or terminating the worker.
+    Control(SharedWorkerControlMsg),
+    /// Wake-up call from the task queue.
+    WakeUp,
+}
+
+impl QueuedTaskConversion for SharedWorkerScriptMsg {
+    fn task_source_name(&self) -> Option<&TaskSourceName> {
+        let script_msg = match self {
+            SharedWorkerScriptMsg::CommonWorker(WorkerScriptMsg::Common(script_msg)) => script_msg,
+            _ => return None,
+        };
+        match script_msg {
+            CommonScriptMsg::Task(_category, _boxed, _pipeline_id, task_source) => {
+                Some(&task_source)
+            },
+            _ => None,
+        }
+    }
+
+    fn pipeline_id(&self) -> Option<PipelineId> {
+        // Workers always return None, since the pipeline_id is only used to check for document activity,
+        // and this check does not apply to worker event-loops.
+        None
+    }
+
+    fn into_queued_task(self) -> Option<QueuedTask> {
+        let script_msg = match self {
+            SharedWorkerScriptMsg::CommonWorker(WorkerScriptMsg::Common(script_msg)) => script_msg,
+            _ => return None,
+        };
+        let (category, boxed, pipeline_id, task_source) = match script_msg {
+            CommonScriptMsg::Task(category, boxed, pipeline_id, task_source) => {
+                (category, boxed, pipeline_id, task_source)
+            },
+            _ => return None,
+        };
+        Some((None, category, boxed, pipeline_id, task_source))
+    }
+
+    fn from_queued_task(queued_task: QueuedTask) -> Self {
+        let (_worker, category, boxed, pipeline_id, task_source) = queued_task;
+        let script_msg = CommonScriptMsg::Task(category, boxed, pipeline_id, task_source);
+        SharedWorkerScriptMsg::CommonWorker(WorkerScriptMsg::Common(script_msg))
+    }
+
+    fn inactive_msg() -> Self {
+        // Inactive is only relevant in the context of a browsing-context event-loop.
+        panic!("Workers should never receive messages marked as inactive");
+    }
+
+    fn wake_up_msg() -> Self {
+        SharedWorkerScriptMsg::WakeUp
+    }
+
+    fn is_wake_up(&self) -> bool {
+        match self {
+            SharedWorkerScriptMsg::WakeUp => true,
+            _ => false,
+        }
+    }
+}
+
+pub enum MixedMessage {
+    FromSharedWorker(SharedWorkerScriptMsg),
+    FromDevtools(DevtoolScriptControlMsg),
+}
+
+#[derive(Clone, JSTraceable)]
+pub struct SharedWorkerChan {
+    pub sender: Sender<SharedWorkerScriptMsg>,
+}
+
+impl ScriptChan for SharedWorkerChan {
+    fn send(&self, msg: CommonScriptMsg) -> Result<(), ()> {
+        self.sender
+            .send(SharedWorkerScriptMsg::CommonWorker(
+                WorkerScriptMsg::Common(msg),
+            ))
+            .map_err(|_| ())
+    }
+
+    fn clone(&self) -> Box<dyn ScriptChan + Send> {
+        Box::new(SharedWorkerChan {
+            sender: self.sender.clone(),
+        })
+    }
+}
+
+impl ScriptPort for Receiver<SharedWorkerScriptMsg> {
+    fn recv(&self) -> Result<CommonScriptMsg, ()> {
+        match self.recv() {
+            Ok(SharedWorkerScriptMsg::CommonWorker(WorkerScriptMsg::Common(script_msg))) => {
+                Ok(script_msg)
+            },
+            Err(_) => Err(()),
+            Ok(_) => panic!("unexpected worker event message!"),
+        }
+    }
+}
+
+unsafe_no_jsmanaged_fields!(TaskQueue<SharedWorkerScriptMsg>);
+
+// https://html.spec.whatwg.org/multipage/#sharedworkerglobalscope
+#[dom_struct]
+pub struct SharedWorkerGlobalScope {
+    workerglobalscope: WorkerGlobalScope,
+    #[ignore_malloc_size_of = "Defined in std"]
+    task_queue: TaskQueue<SharedWorkerScriptMsg>,
+    #[ignore_malloc_size_of = "Defined in std"]
+    own_sender: Sender<SharedWorkerScriptMsg>,
+    #[ignore_malloc_size_of = "Defined in script_traits"]
+    /// The key the constellation knows this worker by.
+    key: SharedWorkerKey,
+    /// Tells this worker apart from other workers with the same key.
+    id: SharedWorkerId,
+    #[ignore_malloc_size_of = "Arc"]
+    image_cache: Arc<dyn ImageCache>,
+}
+
+impl WorkerEventLoopMethods for SharedWorkerGlobalScope {
+    type WorkerMsg = SharedWorkerScriptMsg;
+    type Event = MixedMessage;
+
+    fn task_queue(&self) -> &TaskQueue<SharedWorkerScriptMsg> {
+        &self.task_queue
+    }
+
+    fn handle_event(&self, event: MixedMessage) {
+        self.handle_mixed_message(event);
+    }
+
+    fn handle_worker_post_event(&self, _worker: &TrustedWorkerAddress) -> Option<AutoWorkerReset> {
+        None
+    }
+
+    fn from_worker_msg(&self, msg: SharedWorkerScriptMsg) -> MixedMessage {
+        MixedMessage::FromSharedWorker(msg)
+    }
+
+    fn from_devtools_msg(&self, msg: DevtoolScriptControlMsg) -> MixedMessage {
+        MixedMessage::FromDevtools(msg)
+    }
+}
+
+impl SharedWorkerGlobalScope {
+    fn new_inherited(
+        init: WorkerGlobalScopeInit,
+        worker_type: WorkerType,
+        worker_url: ServoUrl,
+        from_devtools_receiver: Receiver<DevtoolScriptControlMsg>,
+        runtime: Runtime,
+        own_sender: Sender<SharedWorkerScriptMsg>,
+        receiver: Receiver<SharedWorkerScriptMsg>,
+        closing: Arc<AtomicBool>,
+        key: SharedWorkerKey,
+        id: SharedWorkerId,
+        image_cache: Arc<dyn ImageCache>,
+    ) -> SharedWorkerGlobalScope {
+        SharedWorkerGlobalScope {
+            workerglobalscope: WorkerGlobalScope::new_inherited(
+                init,
+                DOMString::from(key.name.clone()),
+                worker_type,
+                worker_url,
+                runtime,
+                from_devtools_receiver,
+                Some(closing),
+                None,
+            ),
+            task_queue: TaskQueue::new(receiver, own_sender.clone()),
+            own_sender,
+            key,
+            id,
+            image_cache,
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        init: WorkerGlobalScopeInit,
+        worker_type: WorkerType,
+        worker_url: ServoUrl,
+        from_devtools_receiver: Receiver<DevtoolScriptControlMsg>,
+        runtime: Runtime,
+        own_sender: Sender<SharedWorkerScriptMsg>,
+        receiver: Receiver<SharedWorkerScriptMsg>,
+        closing: Arc<AtomicBool>,
+        key: SharedWorkerKey,
+        id: SharedWorkerId,
+        image_cache: Arc<dyn ImageCache>,
+    ) -> DomRoot<SharedWorkerGlobalScope> {
+        let cx = runtime.cx();
+        let scope = Box::new(SharedWorkerGlobalScope::new_inherited(
+            init,
+            worker_type,
+            worker_url,
+            from_devtools_receiver,
+            runtime,
+            own_sender,
+            receiver,
+            closing,
+            key,
+            id,
+            image_cache,
+        ));
+        unsafe { SharedWorkerGlobalScopeBinding::Wrap(SafeJSContext::from_ptr(cx), scope) }
+    }
+
+    #[allow(unsafe_code)]
+    // https://html.spec.whatwg.org/multipage/#run-a-worker
+    pub fn run_worker_scope(
+        init: WorkerGlobalScopeInit,
+        worker_url: ServoUrl,
+        from_devtools_receiver: IpcReceiver<DevtoolScriptControlMsg>,
+        key: SharedWorkerKey,
+        id: SharedWorkerId,
+        control_receiver: IpcReceiver<SharedWorkerControlMsg>,
+        worker_load_origin: WorkerScriptLoadOrigin,
+        worker_type: WorkerType,
+        credentials: CredentialsMode,
+        image_cache: Arc<dyn ImageCache>,
+    ) {
+        let serialized_worker_url = worker_url.to_string();
+        let name = format!("SharedWorker for {}", serialized_worker_url);
+        let origin = key.origin.clone();
+        let (own_sender, receiver) = unbounded();
+        let closing = Arc::new(AtomicBool::new(false));
+
+        // Connections and termination requests are sent by the constellation,
+        // a termination sets the closing flag right away so that running script is interrupted.
+        let control_sender = own_sender.clone();
+        let control_closing = closing.clone();
+        ROUTER.add_route(
+            control_receiver.to_opaque(),
+            Box::new(move |message| {
+                let msg: SharedWorkerControlMsg = match message.to() {
+                    Ok(msg) => msg,
+                    Err(_) => return,
+                };
+                if let SharedWorkerControlMsg::Terminate = msg {
+                    control_closing.store(true, Ordering::SeqCst);
+                }
+                let _ = control_sender.send(SharedWorkerScriptMsg::Control(msg));
+            }),
+        );
+
+        thread::Builder::new()
+            .name(name)
+            .spawn(move || {
+                thread_state::initialize(ThreadState::SCRIPT | ThreadState::IN_WORKER);
+
+                let roots = RootCollection::new();
+                let _stack_roots = ThreadLocalStackRoots::new(&roots);
+
+                let WorkerScriptLoadOrigin {
+                    referrer_url,
+                    referrer_policy,
+                    pipeline_id,
+                } = worker_load_origin;
+
+                let referrer = referrer_url.map(|referrer_url| Referrer::ReferrerUrl(referrer_url));
+
+                let request = RequestBuilder::new(worker_url.clone())
+                    .destination(Destination::SharedWorker)
+                    .mode(RequestMode::SameOrigin)
+                    .credentials_mode(credentials)
+                    .parser_metadata(ParserMetadata::NotParserInserted)
+                    .use_url_credentials(true)
+                    .pipeline_id(pipeline_id)
+                    .referrer(referrer)
+                    .referrer_policy(referrer_policy)
+                    .origin(origin);
+
+                // A shared worker can outlive the document that started it,
+                // so it does not share its runtime with it.
+                let runtime = new_rt_and_cx(None);
+
+                let (devtools_mpsc_chan, devtools_mpsc_port) = unbounded();
+                ROUTER.route_ipc_receiver_to_crossbeam_sender(
+                    from_devtools_receiver,
+                    devtools_mpsc_chan,
+                );
+
+                let global = SharedWorkerGlobalScope::new(
+                    init,
+                    worker_type,
+                    worker_url,
+                    devtools_mpsc_port,
+                    runtime,
+                    own_sender,
+                    receiver,
+                    closing,
+                    key,
+                    id,
+                    image_cache,
+                );
+                let scope = global.upcast::<WorkerGlobalScope>();
+
+                let script = match scope.fetch_worker_script(request) {
+                    Err(_) => {
+                        warn!(
+                            "Error loading shared worker script {}",
+                            serialized_worker_url
+                        );
+                        global.notify_closed();
+                        return;
+                    },
+                    Ok(script) => script,
+                };
+
+                unsafe {
+                    // Handle interrupt requests
+                    JS_AddInterruptCallback(*scope.get_cx(), Some(interrupt_callback));
+                }
+
+                if !scope.is_closing() {
+                    scope.run_worker_script(script);
+                }
+
+                let reporter_name = format!("shared-worker-reporter-{}", random::<u64>());
+                scope
+                    .upcast::<GlobalScope>()
+                    .mem_profiler_chan()
+                    .run_with_memory_reporting(
+                        || {
+                            // Step 29, Run the responsible event loop specified
+                            // by inside settings until it is destroyed.
+                            // The worker processing model remains on this step
+                            // until the event loop is destroyed,
+                            // which happens after the closing flag is set to true.
+                            while !scope.is_closing() {
+                                run_worker_event_loop(&*global, None);
+                            }
+                            scope.release_resources();
+                        },
+                        reporter_name,
+                        global.script_chan(),
+                        CommonScriptMsg::CollectReports,
+                    );
+                global.notify_closed();
+            })
+            .expect("Thread spawning failed");
+    }
+
+    pub fn image_cache(&self) -> Arc<dyn ImageCache> {
+        self.image_cache.clone()
+    }
+
+    pub fn script_chan(&self) -> Box<dyn ScriptChan + Send> {
+        Box::new(SharedWorkerChan {
+            sender: self.own_sender.clone(),
+        })
+    }
+
+    pub fn new_script_pair(&self) -> (Box<dyn ScriptChan + Send>, Box<dyn ScriptPort + Send>) {
+        let (tx, rx) = unbounded();
+        (Box::new(SharedWorkerChan { sender: tx }), Box::new(rx))
+    }
+
+    /// Let the constellation know that this worker should not receive new connections.
+    fn notify_closed(&self) {
+        let _ = self
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::SharedWorkerClosed(self.key.clone(), self.id));
+    }
+
+    fn handle_mixed_message(&self, msg: MixedMessage) {
+        match msg {
+            MixedMessage::FromDevtools(msg) => self
+                .upcast::<WorkerGlobalScope>()
+                .handle_devtools_message(msg),
+            MixedMessage::FromSharedWorker(msg) => self.handle_script_event(msg),
+        }
+    }
+
+    fn handle_script_event(&self, msg: SharedWorkerScriptMsg) {
+        match msg {
+            SharedWorkerScriptMsg::CommonWorker(WorkerScriptMsg::Common(msg)) => {
+                self.upcast::<WorkerGlobalScope>().process_event(msg);
+            },
+            SharedWorkerScriptMsg::CommonWorker(WorkerScriptMsg::DOMMessage { .. }) => {
+                debug!("shared worker global scopes do not receive messages directly");
+            },
+            SharedWorkerScriptMsg::Control(SharedWorkerControlMsg::Connect(data)) => {
+                self.dispatch_connect(data);
+            },
+            SharedWorkerScriptMsg::Control(SharedWorkerControlMsg::Terminate) => {
+                self.upcast::<WorkerGlobalScope>().close();
+            },
+            SharedWorkerScriptMsg::WakeUp => {},
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#dom-sharedworker step 11.2
+    #[allow(unsafe_code)]
+    fn dispatch_connect(&self, data: StructuredSerializedData) {
+        let scope = self.upcast::<WorkerGlobalScope>();
+        let _ac = enter_realm(self);
+        rooted!(in(*scope.get_cx()) let mut message = UndefinedValue());
+        let ports = match structuredclone::read(scope.upcast(), data, message.handle_mut()) {
+            Ok(ports) => ports,
+            Err(()) => return warn!("Failed to receive the port of a connecting SharedWorker."),
+        };
+        let source = ports
+            .first()
+            .map(|port| WindowProxyOrMessagePortOrServiceWorker::MessagePort(port.clone()));
+        // The data of a connect event is the empty string.
+        rooted!(in(*scope.get_cx()) let mut data = UndefinedValue());
+        unsafe { DOMString::new().to_jsval(*scope.get_cx(), data.handle_mut()) };
+        let event = MessageEvent::new(
+            scope.upcast(),
+            atom!("connect"),
+            false,
+            false,
+            data.handle(),
+            DOMString::new(),
+            source.as_ref(),
+            DOMString::new(),
+            ports,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+}
+
+impl SharedWorkerGlobalScopeMethods for SharedWorkerGlobalScope {
+    // https://html.spec.whatwg.org/multipage/#dom-sharedworkerglobalscope-name
+    fn Name(&self) -> DOMString {
+        self.upcast::<WorkerGlobalScope>().worker_name()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-sharedworkerglobalscope-close
+    fn Close(&self) {
+        self.upcast::<WorkerGlobalScope>().close();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#handler-sharedworkerglobalscope-onconnect
+    event_handler!(connect, GetOnconnect, SetOnconnect);
+}
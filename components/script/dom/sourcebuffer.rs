@@ -0,0 +1,534 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{
+    EndOfStreamError, MediaSourceMethods, ReadyState,
+};
+use crate::dom::bindings::codegen::Bindings::SourceBufferBinding::{
+    self, AppendMode, SourceBufferMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlmediaelement::MediaSourceStream;
+use crate::dom::mediasource::MediaSource;
+use crate::dom::timeranges::{TimeRanges, TimeRangesContainer};
+use crate::dom::window::Window;
+use crate::media_segment_parser::{ByteStreamFormat, ParseError, Segment, SegmentParser};
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::f64;
+
+/// The kind of media a SourceBuffer carries, from the type it is created
+/// with.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum SourceBufferKind {
+    Audio,
+    Video,
+}
+
+impl SourceBufferKind {
+    pub fn from_mime_type(mime_type: &str) -> SourceBufferKind {
+        if mime_type.to_ascii_lowercase().starts_with("audio/") {
+            SourceBufferKind::Audio
+        } else {
+            SourceBufferKind::Video
+        }
+    }
+}
+
+// https://w3c.github.io/media-source/#sourcebuffer
+#[dom_struct]
+pub struct SourceBuffer {
+    eventtarget: EventTarget,
+    /// The parent MediaSource, until this SourceBuffer is removed from it.
+    media_source: MutNullableDom<MediaSource>,
+    kind: SourceBufferKind,
+    /// The stream of the media player this SourceBuffer feeds, chosen on its
+    /// first append.
+    stream: Cell<Option<MediaSourceStream>>,
+    mode: Cell<AppendMode>,
+    updating: Cell<bool>,
+    timestamp_offset: Cell<f64>,
+    append_window_start: Cell<f64>,
+    append_window_end: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#sourcebuffer-input-buffer>
+    input_buffer: DomRefCell<Vec<u8>>,
+    parser: DomRefCell<SegmentParser>,
+    buffered: DomRefCell<TimeRangesContainer>,
+    /// <https://w3c.github.io/media-source/#sourcebuffer-group-start-timestamp>
+    group_start_timestamp: Cell<Option<f64>>,
+    /// <https://w3c.github.io/media-source/#sourcebuffer-group-end-timestamp>
+    group_end_timestamp: Cell<f64>,
+    /// The highest presentation timestamp that has been buffered.
+    highest_end_timestamp: Cell<f64>,
+    /// Whether the range removal algorithm is running.
+    removing: Cell<bool>,
+    /// Incremented when an append is aborted, so that its pending task is
+    /// ignored.
+    append_generation: Cell<u32>,
+}
+
+impl SourceBuffer {
+    fn new_inherited(
+        media_source: &MediaSource,
+        format: ByteStreamFormat,
+        kind: SourceBufferKind,
+    ) -> SourceBuffer {
+        SourceBuffer {
+            eventtarget: EventTarget::new_inherited(),
+            media_source: MutNullableDom::new(Some(media_source)),
+            kind,
+            stream: Cell::new(None),
+            mode: Cell::new(AppendMode::Segments),
+            updating: Cell::new(false),
+            timestamp_offset: Cell::new(0.),
+            append_window_start: Cell::new(0.),
+            append_window_end: Cell::new(f64::INFINITY),
+            input_buffer: DomRefCell::new(vec![]),
+            parser: DomRefCell::new(SegmentParser::new(format)),
+            buffered: DomRefCell::new(TimeRangesContainer::new()),
+            group_start_timestamp: Cell::new(None),
+            group_end_timestamp: Cell::new(0.),
+            highest_end_timestamp: Cell::new(0.),
+            removing: Cell::new(false),
+            append_generation: Cell::new(0),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        media_source: &MediaSource,
+        format: ByteStreamFormat,
+        kind: SourceBufferKind,
+    ) -> DomRoot<SourceBuffer> {
+        reflect_dom_object(
+            Box::new(SourceBuffer::new_inherited(media_source, format, kind)),
+            window,
+            SourceBufferBinding::Wrap,
+        )
+    }
+
+    /// Marks this SourceBuffer as removed from its parent MediaSource.
+    pub fn detach(&self) {
+        self.media_source.set(None);
+    }
+
+    pub fn kind(&self) -> SourceBufferKind {
+        self.kind
+    }
+
+    /// The stream of the media player this SourceBuffer feeds. Audio gets a
+    /// stream of its own when a video SourceBuffer feeds the main one.
+    fn player_stream(&self, media_source: &MediaSource) -> MediaSourceStream {
+        if let Some(stream) = self.stream.get() {
+            return stream;
+        }
+        let stream =
+            if self.kind == SourceBufferKind::Audio && media_source.has_video_source_buffer() {
+                MediaSourceStream::Audio
+            } else {
+                MediaSourceStream::Main
+            };
+        self.stream.set(Some(stream));
+        stream
+    }
+
+    pub fn buffered_ranges(&self) -> TimeRangesContainer {
+        self.buffered.borrow().clone()
+    }
+
+    pub fn highest_end_timestamp(&self) -> f64 {
+        self.highest_end_timestamp.get()
+    }
+
+    fn queue_simple_event(&self, name: Atom) {
+        let window = self.global();
+        let window = window.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name, window);
+    }
+
+    /// The parent MediaSource, or an `InvalidStateError` if this SourceBuffer
+    /// has been removed from it.
+    fn parent_media_source(&self) -> Fallible<DomRoot<MediaSource>> {
+        self.media_source.get().ok_or(Error::InvalidState)
+    }
+
+    /// The checks shared by the attribute setters that affect appends.
+    fn check_not_updating(&self) -> Fallible<DomRoot<MediaSource>> {
+        let media_source = self.parent_media_source()?;
+        if self.updating.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(media_source)
+    }
+
+    /// Aborts the running buffer append algorithm, as `abort()` and
+    /// `removeSourceBuffer()` do.
+    pub fn abort_buffer_append(&self) {
+        self.append_generation.set(self.append_generation.get() + 1);
+        self.updating.set(false);
+        self.queue_simple_event(atom!("abort"));
+        self.queue_simple_event(atom!("updateend"));
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-reset-parser-state>
+    fn reset_parser_state(&self) {
+        if self.mode.get() == AppendMode::Sequence {
+            self.group_start_timestamp
+                .set(Some(self.group_end_timestamp.get()));
+        }
+        self.input_buffer.borrow_mut().clear();
+        self.parser.borrow_mut().reset();
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-prepare-append>
+    fn prepare_append(&self) -> ErrorResult {
+        // Steps 1-2.
+        let media_source = self.check_not_updating()?;
+        // Step 3.
+        if let Some(media_element) = media_source.media_element() {
+            if media_element.GetError().is_some() {
+                return Err(Error::InvalidState);
+            }
+        }
+        // Step 4.
+        media_source.reopen_if_ended();
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-buffer-append>
+    fn buffer_append(&self, generation: u32) {
+        if generation != self.append_generation.get() {
+            return;
+        }
+        let media_source = match self.media_source.get() {
+            Some(media_source) => media_source,
+            None => return,
+        };
+
+        // Step 1.
+        let result = {
+            let mut input_buffer = self.input_buffer.borrow_mut();
+            self.parser.borrow_mut().parse(&mut input_buffer)
+        };
+        let segments = match result {
+            Ok(segments) => segments,
+            Err(error) => {
+                // Step 2.
+                debug!("Invalid data appended to a SourceBuffer: {:?}", error);
+                return self.append_error(&media_source);
+            },
+        };
+        let media_element = media_source.media_element();
+        let stream = self.player_stream(&media_source);
+        for segment in segments {
+            let data = match segment {
+                Segment::Initialization { duration, data } => {
+                    media_source.initialization_segment_received(self, duration);
+                    data
+                },
+                Segment::Media {
+                    start,
+                    end,
+                    mut data,
+                } => match self.coded_frame_processing(start, end, &mut data) {
+                    Ok(true) => data,
+                    Ok(false) => continue,
+                    Err(error) => {
+                        debug!("Could not offset a media segment: {:?}", error);
+                        return self.append_error(&media_source);
+                    },
+                },
+            };
+            if let Some(ref media_element) = media_element {
+                media_element.push_media_source_data(stream, data);
+            }
+        }
+
+        // Steps 3-5.
+        self.updating.set(false);
+        self.queue_simple_event(atom!("update"));
+        self.queue_simple_event(atom!("updateend"));
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-append-error>
+    fn append_error(&self, media_source: &MediaSource) {
+        // Step 1.
+        self.reset_parser_state();
+        // Steps 2-4.
+        self.updating.set(false);
+        self.queue_simple_event(atom!("error"));
+        self.queue_simple_event(atom!("updateend"));
+        // Step 5.
+        media_source.end_of_stream(Some(EndOfStreamError::Decode));
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-coded-frame-processing>
+    ///
+    /// Timestamps are handled per media segment rather than per coded frame:
+    /// the timestamp offset is written into the timestamps of the segment,
+    /// and a segment wholly outside the append window is dropped. The frames
+    /// of a segment overlapping the edges of the window are all kept.
+    /// Returns whether the segment is to be pushed to the player.
+    fn coded_frame_processing(
+        &self,
+        start: f64,
+        end: f64,
+        data: &mut Vec<u8>,
+    ) -> Result<bool, ParseError> {
+        if self.mode.get() == AppendMode::Sequence {
+            if let Some(group_start_timestamp) = self.group_start_timestamp.take() {
+                self.timestamp_offset.set(group_start_timestamp - start);
+            }
+        }
+        let timestamp_offset = self.timestamp_offset.get();
+        let (start, end) = (start + timestamp_offset, end + timestamp_offset);
+        self.group_end_timestamp.set(end);
+
+        // Only the part of the segment within the append window is buffered.
+        let start = start.max(self.append_window_start.get());
+        let end = end.min(self.append_window_end.get());
+        if start >= end {
+            return Ok(false);
+        }
+        self.parser
+            .borrow()
+            .apply_timestamp_offset(data, timestamp_offset)?;
+        let _ = self.buffered.borrow_mut().add(start, end);
+        if end > self.highest_end_timestamp.get() {
+            self.highest_end_timestamp.set(end);
+        }
+        Ok(true)
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-range-removal>
+    ///
+    /// Data already handed to the player is not evicted from it.
+    fn range_removal(&self, start: f64, end: f64) {
+        // Steps 3-4.
+        self.updating.set(true);
+        self.removing.set(true);
+        self.queue_simple_event(atom!("updatestart"));
+
+        // Step 5.
+        let this = Trusted::new(self);
+        let window = self.global();
+        let window = window.as_window();
+        let _ = window.task_manager().media_element_task_source().queue(
+            task!(source_buffer_range_removal: move || {
+                let this = this.root();
+                // Step 6.
+                let highest_end = {
+                    let mut buffered = this.buffered.borrow_mut();
+                    buffered.remove(start, end);
+                    match buffered.len() {
+                        0 => 0.,
+                        len => buffered.end(len - 1).unwrap(),
+                    }
+                };
+                this.highest_end_timestamp.set(highest_end);
+                // Steps 7-9.
+                this.updating.set(false);
+                this.removing.set(false);
+                this.queue_simple_event(atom!("update"));
+                this.queue_simple_event(atom!("updateend"));
+            }),
+            window.upcast(),
+        );
+    }
+}
+
+impl SourceBufferMethods for SourceBuffer {
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-mode
+    fn Mode(&self) -> AppendMode {
+        self.mode.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-mode
+    fn SetMode(&self, mode: AppendMode) -> ErrorResult {
+        // Steps 1-2.
+        let media_source = self.check_not_updating()?;
+        // Step 4.
+        media_source.reopen_if_ended();
+        // Step 6.
+        if mode == AppendMode::Sequence {
+            self.group_start_timestamp
+                .set(Some(self.group_end_timestamp.get()));
+        }
+        // Step 7.
+        self.mode.set(mode);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-updating
+    fn Updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-buffered
+    fn GetBuffered(&self) -> Fallible<DomRoot<TimeRanges>> {
+        self.parent_media_source()?;
+        Ok(TimeRanges::new(
+            self.global().as_window(),
+            self.buffered_ranges(),
+        ))
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn TimestampOffset(&self) -> Finite<f64> {
+        Finite::wrap(self.timestamp_offset.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn SetTimestampOffset(&self, value: Finite<f64>) -> ErrorResult {
+        // Steps 1-3.
+        let media_source = self.check_not_updating()?;
+        // Step 4.
+        media_source.reopen_if_ended();
+        // Step 6.
+        if self.mode.get() == AppendMode::Sequence {
+            self.group_start_timestamp.set(Some(*value));
+        }
+        // Step 7.
+        self.timestamp_offset.set(*value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart
+    fn AppendWindowStart(&self) -> Finite<f64> {
+        Finite::wrap(self.append_window_start.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart
+    fn SetAppendWindowStart(&self, value: Finite<f64>) -> ErrorResult {
+        // Steps 1-2.
+        self.check_not_updating()?;
+        // Step 3.
+        if *value < 0. || *value >= self.append_window_end.get() {
+            return Err(Error::Type(
+                "appendWindowStart must be between 0 and appendWindowEnd".to_owned(),
+            ));
+        }
+        // Step 4.
+        self.append_window_start.set(*value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend
+    fn AppendWindowEnd(&self) -> f64 {
+        self.append_window_end.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend
+    fn SetAppendWindowEnd(&self, value: f64) -> ErrorResult {
+        // Steps 1-2.
+        self.check_not_updating()?;
+        // Steps 3-4.
+        if value.is_nan() || value <= self.append_window_start.get() {
+            return Err(Error::Type(
+                "appendWindowEnd must be greater than appendWindowStart".to_owned(),
+            ));
+        }
+        // Step 5.
+        self.append_window_end.set(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendbuffer
+    fn AppendBuffer(&self, data: ArrayBufferViewOrArrayBuffer) -> ErrorResult {
+        // Step 1.
+        self.prepare_append()?;
+        // Step 2.
+        let bytes = match data {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+        };
+        self.input_buffer.borrow_mut().extend(bytes);
+        // Steps 3-4.
+        self.updating.set(true);
+        self.queue_simple_event(atom!("updatestart"));
+
+        // Step 5.
+        let this = Trusted::new(self);
+        let generation = self.append_generation.get();
+        let window = self.global();
+        let window = window.as_window();
+        let _ = window.task_manager().media_element_task_source().queue(
+            task!(source_buffer_append: move || {
+                this.root().buffer_append(generation);
+            }),
+            window.upcast(),
+        );
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-abort
+    fn Abort(&self) -> ErrorResult {
+        // Steps 1-3.
+        let media_source = self.parent_media_source()?;
+        if media_source.ready_state() != ReadyState::Open || self.removing.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4.
+        if self.updating.get() {
+            self.abort_buffer_append();
+        }
+        // Step 5.
+        self.reset_parser_state();
+        // Steps 6-7.
+        self.append_window_start.set(0.);
+        self.append_window_end.set(f64::INFINITY);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-remove
+    fn Remove(&self, start: Finite<f64>, end: f64) -> ErrorResult {
+        // Steps 1-2.
+        let media_source = self.check_not_updating()?;
+        // Steps 3-4.
+        let duration = media_source.Duration();
+        if duration.is_nan() || *start < 0. || *start > duration {
+            return Err(Error::Type(
+                "start must be between 0 and the duration".to_owned(),
+            ));
+        }
+        // Step 5.
+        if end.is_nan() || end <= *start {
+            return Err(Error::Type("end must be greater than start".to_owned()));
+        }
+        // Step 6.
+        media_source.reopen_if_ended();
+        // Step 7.
+        self.range_removal(*start, end);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdatestart
+    event_handler!(updatestart, GetOnupdatestart, SetOnupdatestart);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdate
+    event_handler!(update, GetOnupdate, SetOnupdate);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdateend
+    event_handler!(updateend, GetOnupdateend, SetOnupdateend);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+}
@@ -0,0 +1,400 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisBinding::{
+    self, SpeechSynthesisMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::SpeechSynthesisErrorCode;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::speechsynthesiserrorevent::SpeechSynthesisErrorEvent;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::speechsynthesisvoice::SpeechSynthesisVoice;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use embedder_traits::speech::{SpeechBoundary, SpeechEvent, SpeechRequest, SpeechSynthesisError};
+use embedder_traits::EmbedderMsg;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use profile_traits::ipc as profiled_ipc;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use time::precise_time_ns;
+use uuid::Uuid;
+
+// https://wicg.github.io/speech-api/#speechsynthesis
+#[dom_struct]
+pub struct SpeechSynthesis {
+    eventtarget: EventTarget,
+    /// The utterances to speak, the first of which is being spoken if
+    /// `speaking_id` is set.
+    queue: DomRefCell<VecDeque<Dom<SpeechSynthesisUtterance>>>,
+    /// The id of the utterance the speech synthesizer of the embedder is
+    /// speaking.
+    speaking_id: DomRefCell<Option<String>>,
+    /// When the speech synthesizer started speaking the utterance, in
+    /// nanoseconds, or 0 if it didn't start yet.
+    start_time: Cell<u64>,
+    paused: Cell<bool>,
+    /// The voices of the speech synthesizer, once they were asked for.
+    voices: DomRefCell<Option<Vec<Dom<SpeechSynthesisVoice>>>>,
+}
+
+impl SpeechSynthesis {
+    fn new_inherited() -> SpeechSynthesis {
+        SpeechSynthesis {
+            eventtarget: EventTarget::new_inherited(),
+            queue: DomRefCell::new(VecDeque::new()),
+            speaking_id: DomRefCell::new(None),
+            start_time: Cell::new(0),
+            paused: Cell::new(false),
+            voices: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<SpeechSynthesis> {
+        reflect_dom_object(
+            Box::new(SpeechSynthesis::new_inherited()),
+            window,
+            SpeechSynthesisBinding::Wrap,
+        )
+    }
+
+    /// Stops speaking when the document is unloaded, without firing any
+    /// event.
+    pub fn stop(&self) {
+        self.queue.borrow_mut().clear();
+        if let Some(id) = self.speaking_id.borrow_mut().take() {
+            self.send_request(SpeechRequest::Cancel(id));
+        }
+    }
+
+    fn send_request(&self, request: SpeechRequest) {
+        self.global()
+            .as_window()
+            .send_to_embedder(EmbedderMsg::Speech(request));
+    }
+
+    /// The time elapsed since the speech synthesizer started speaking the
+    /// current utterance, in seconds.
+    fn elapsed_time(&self) -> Finite<f32> {
+        let start_time = self.start_time.get();
+        if start_time == 0 {
+            return Finite::wrap(0.);
+        }
+        Finite::wrap((precise_time_ns() - start_time) as f32 / 1_000_000_000.)
+    }
+
+    /// Asks the speech synthesizer to speak the first utterance of the
+    /// queue, if any.
+    fn speak_next(&self) {
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+        let id = Uuid::new_v4().to_simple().to_string();
+        *self.speaking_id.borrow_mut() = Some(id.clone());
+        self.start_time.set(0);
+
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let this = Trusted::new(self);
+        let (task_source, canceller) = global
+            .as_window()
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        let speaking_id = id.clone();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: SpeechEvent = match message.to() {
+                    Ok(event) => event,
+                    Err(_) => return,
+                };
+                let this = this.clone();
+                let id = speaking_id.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_speech_event: move || {
+                        this.root().handle_speech_event(id, event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        self.send_request(SpeechRequest::Speak(utterance.speech_utterance(id), sender));
+    }
+
+    fn handle_speech_event(&self, id: String, event: SpeechEvent) {
+        if self.speaking_id.borrow().as_ref() != Some(&id) {
+            return;
+        }
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+        match event {
+            SpeechEvent::Start => {
+                self.start_time.set(precise_time_ns());
+                self.fire_event(&utterance, atom!("start"), 0, 0, DOMString::new());
+            },
+            SpeechEvent::Boundary {
+                boundary,
+                char_index,
+                char_length,
+            } => {
+                let name = match boundary {
+                    SpeechBoundary::Word => "word",
+                    SpeechBoundary::Sentence => "sentence",
+                };
+                self.fire_event(
+                    &utterance,
+                    atom!("boundary"),
+                    char_index,
+                    char_length,
+                    DOMString::from(name),
+                );
+            },
+            SpeechEvent::Mark { name, char_index } => {
+                self.fire_event(
+                    &utterance,
+                    atom!("mark"),
+                    char_index,
+                    0,
+                    DOMString::from(name),
+                );
+            },
+            SpeechEvent::Pause => {
+                self.fire_event(&utterance, atom!("pause"), 0, 0, DOMString::new())
+            },
+            SpeechEvent::Resume => {
+                self.fire_event(&utterance, atom!("resume"), 0, 0, DOMString::new())
+            },
+            SpeechEvent::End => {
+                let elapsed_time = self.elapsed_time();
+                self.finish_utterance();
+                let event = SpeechSynthesisEvent::new(
+                    &self.global(),
+                    atom!("end"),
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::NotCancelable,
+                    &utterance,
+                    0,
+                    0,
+                    elapsed_time,
+                    DOMString::new(),
+                );
+                event.upcast::<Event>().fire(utterance.upcast());
+                self.speak_next_if_idle();
+            },
+            SpeechEvent::Error(error) => {
+                let elapsed_time = self.elapsed_time();
+                self.finish_utterance();
+                self.fire_error_event(&utterance, elapsed_time, error_code(error));
+                self.speak_next_if_idle();
+            },
+        }
+    }
+
+    /// Removes the utterance that was spoken from the queue.
+    fn finish_utterance(&self) {
+        self.queue.borrow_mut().pop_front();
+        *self.speaking_id.borrow_mut() = None;
+    }
+
+    /// Speaks the next utterance, unless the page paused the speech or
+    /// already spoke another utterance while an event was dispatched.
+    fn speak_next_if_idle(&self) {
+        if self.speaking_id.borrow().is_none() && !self.paused.get() {
+            self.speak_next();
+        }
+    }
+
+    fn fire_event(
+        &self,
+        utterance: &SpeechSynthesisUtterance,
+        type_: Atom,
+        char_index: u32,
+        char_length: u32,
+        name: DOMString,
+    ) {
+        let event = SpeechSynthesisEvent::new(
+            &self.global(),
+            type_,
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            utterance,
+            char_index,
+            char_length,
+            self.elapsed_time(),
+            name,
+        );
+        event.upcast::<Event>().fire(utterance.upcast());
+    }
+
+    fn fire_error_event(
+        &self,
+        utterance: &SpeechSynthesisUtterance,
+        elapsed_time: Finite<f32>,
+        error: SpeechSynthesisErrorCode,
+    ) {
+        let event = SpeechSynthesisErrorEvent::new(
+            &self.global(),
+            atom!("error"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            utterance,
+            0,
+            elapsed_time,
+            error,
+        );
+        event.upcast::<Event>().fire(utterance.upcast());
+    }
+}
+
+impl SpeechSynthesisMethods for SpeechSynthesis {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-pending
+    fn Pending(&self) -> bool {
+        let speaking = self.speaking_id.borrow().is_some();
+        self.queue.borrow().len() > speaking as usize
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-speaking
+    fn Speaking(&self) -> bool {
+        self.speaking_id.borrow().is_some()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-paused
+    fn Paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-onvoiceschanged
+    event_handler!(voiceschanged, GetOnvoiceschanged, SetOnvoiceschanged);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-speak
+    fn Speak(&self, utterance: &SpeechSynthesisUtterance) {
+        self.queue.borrow_mut().push_back(Dom::from_ref(utterance));
+        self.speak_next_if_idle();
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-cancel
+    fn Cancel(&self) {
+        let utterances: Vec<_> = self
+            .queue
+            .borrow_mut()
+            .drain(..)
+            .map(|utterance| Trusted::new(&*utterance))
+            .collect();
+        let interrupted = match self.speaking_id.borrow_mut().take() {
+            Some(id) => {
+                self.send_request(SpeechRequest::Cancel(id));
+                true
+            },
+            None => false,
+        };
+        if utterances.is_empty() {
+            return;
+        }
+
+        // The utterance that was being spoken is interrupted, and the others
+        // are canceled before they began being spoken.
+        let elapsed_time = self.elapsed_time();
+        let this = Trusted::new(self);
+        let global = self.global();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(fire_speech_cancel_errors: move || {
+                let this = this.root();
+                for (index, utterance) in utterances.iter().enumerate() {
+                    if index == 0 && interrupted {
+                        this.fire_error_event(
+                            &utterance.root(),
+                            elapsed_time,
+                            SpeechSynthesisErrorCode::Interrupted,
+                        );
+                    } else {
+                        this.fire_error_event(
+                            &utterance.root(),
+                            Finite::wrap(0.),
+                            SpeechSynthesisErrorCode::Canceled,
+                        );
+                    }
+                }
+            }),
+            &global,
+        );
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-pause
+    fn Pause(&self) {
+        if self.paused.get() {
+            return;
+        }
+        self.paused.set(true);
+        if let Some(id) = self.speaking_id.borrow().clone() {
+            self.send_request(SpeechRequest::Pause(id));
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-resume
+    fn Resume(&self) {
+        if !self.paused.get() {
+            return;
+        }
+        self.paused.set(false);
+        match self.speaking_id.borrow().clone() {
+            Some(id) => self.send_request(SpeechRequest::Resume(id)),
+            None => self.speak_next(),
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-getvoices
+    fn GetVoices(&self) -> Vec<DomRoot<SpeechSynthesisVoice>> {
+        let global = self.global();
+        let mut voices = self.voices.borrow_mut();
+        let voices = voices.get_or_insert_with(|| {
+            let (sender, receiver) =
+                profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+            self.send_request(SpeechRequest::GetVoices(sender));
+            receiver
+                .recv()
+                .unwrap_or_default()
+                .iter()
+                .map(|voice| Dom::from_ref(&*SpeechSynthesisVoice::new(&global, voice)))
+                .collect()
+        });
+        voices
+            .iter()
+            .map(|voice| DomRoot::from_ref(&**voice))
+            .collect()
+    }
+}
+
+fn error_code(error: SpeechSynthesisError) -> SpeechSynthesisErrorCode {
+    match error {
+        SpeechSynthesisError::Interrupted => SpeechSynthesisErrorCode::Interrupted,
+        SpeechSynthesisError::AudioBusy => SpeechSynthesisErrorCode::Audio_busy,
+        SpeechSynthesisError::AudioHardware => SpeechSynthesisErrorCode::Audio_hardware,
+        SpeechSynthesisError::Network => SpeechSynthesisErrorCode::Network,
+        SpeechSynthesisError::SynthesisUnavailable => {
+            SpeechSynthesisErrorCode::Synthesis_unavailable
+        },
+        SpeechSynthesisError::SynthesisFailed => SpeechSynthesisErrorCode::Synthesis_failed,
+        SpeechSynthesisError::LanguageUnavailable => SpeechSynthesisErrorCode::Language_unavailable,
+        SpeechSynthesisError::VoiceUnavailable => SpeechSynthesisErrorCode::Voice_unavailable,
+        SpeechSynthesisError::TextTooLong => SpeechSynthesisErrorCode::Text_too_long,
+        SpeechSynthesisError::InvalidArgument => SpeechSynthesisErrorCode::Invalid_argument,
+        SpeechSynthesisError::NotAllowed => SpeechSynthesisErrorCode::Not_allowed,
+    }
+}
@@ -0,0 +1,117 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::{
+    self, SpeechSynthesisErrorCode, SpeechSynthesisErrorEventInit, SpeechSynthesisErrorEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+// https://wicg.github.io/speech-api/#speechsynthesiserrorevent
+#[dom_struct]
+pub struct SpeechSynthesisErrorEvent {
+    event: SpeechSynthesisEvent,
+    error: SpeechSynthesisErrorCode,
+}
+
+impl SpeechSynthesisErrorEvent {
+    fn new_inherited(
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        elapsed_time: Finite<f32>,
+        error: SpeechSynthesisErrorCode,
+    ) -> SpeechSynthesisErrorEvent {
+        SpeechSynthesisErrorEvent {
+            event: SpeechSynthesisEvent::new_inherited(
+                utterance,
+                char_index,
+                0,
+                elapsed_time,
+                DOMString::new(),
+            ),
+            error,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: EventBubbles,
+        cancelable: EventCancelable,
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        elapsed_time: Finite<f32>,
+        error: SpeechSynthesisErrorCode,
+    ) -> DomRoot<SpeechSynthesisErrorEvent> {
+        let ev = reflect_dom_object(
+            Box::new(SpeechSynthesisErrorEvent::new_inherited(
+                utterance,
+                char_index,
+                elapsed_time,
+                error,
+            )),
+            global,
+            SpeechSynthesisErrorEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bool::from(bubbles), bool::from(cancelable));
+        }
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &SpeechSynthesisErrorEventInit,
+    ) -> Fallible<DomRoot<SpeechSynthesisErrorEvent>> {
+        let ev = reflect_dom_object(
+            Box::new(SpeechSynthesisErrorEvent {
+                event: SpeechSynthesisEvent::new_inherited(
+                    &init.parent.utterance,
+                    init.parent.charIndex,
+                    init.parent.charLength,
+                    init.parent.elapsedTime,
+                    init.parent.name.clone(),
+                ),
+                error: init.error,
+            }),
+            &window.global(),
+            SpeechSynthesisErrorEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(
+                Atom::from(type_),
+                init.parent.parent.bubbles,
+                init.parent.parent.cancelable,
+            );
+        }
+        Ok(ev)
+    }
+}
+
+impl SpeechSynthesisErrorEventMethods for SpeechSynthesisErrorEvent {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesiserrorevent-error
+    fn Error(&self) -> SpeechSynthesisErrorCode {
+        self.error
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::StorageManagerBinding::{
+    self, StorageEstimate, StorageManagerMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::storage_thread::StorageThreadMsg;
+use net_traits::IpcSend;
+use profile_traits::ipc;
+use std::rc::Rc;
+
+// https://storage.spec.whatwg.org/#storagemanager
+#[dom_struct]
+pub struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<StorageManager> {
+        reflect_dom_object(
+            Box::new(StorageManager::new_inherited()),
+            global,
+            StorageManagerBinding::Wrap,
+        )
+    }
+
+    fn get_storage_thread(&self) -> IpcSender<StorageThreadMsg> {
+        self.global().resource_threads().sender()
+    }
+
+    /// Creates the promise of a method, rejected if the global has no storage
+    /// shelf because its origin is opaque,
+    /// <https://storage.spec.whatwg.org/#obtain-a-local-storage-shelf>
+    fn new_promise(&self, comp: InCompartment) -> (Rc<Promise>, bool) {
+        let global = self.global();
+        let promise = Promise::new_in_current_compartment(&global, comp);
+        if !global.origin().is_tuple() {
+            promise.reject_error(Error::Type(
+                "Storage is not available to an opaque origin".to_owned(),
+            ));
+            return (promise, false);
+        }
+        (promise, true)
+    }
+
+    fn persisted(&self) -> bool {
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Persisted(sender, global.get_url()))
+            .unwrap();
+        receiver.recv().unwrap()
+    }
+
+    /// Steps 4.2-4.5 of `persist()`, the storage stays best-effort
+    /// if the permission is not granted.
+    fn persist(&self, permission: PermissionState) -> bool {
+        if permission != PermissionState::Granted {
+            return self.persisted();
+        }
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Persist(sender, global.get_url()))
+            .unwrap();
+        receiver.recv().unwrap()
+    }
+}
+
+impl StorageManagerMethods for StorageManager {
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persisted
+    fn Persisted(&self, comp: InCompartment) -> Rc<Promise> {
+        // Steps 1-3.
+        let (promise, has_shelf) = self.new_promise(comp);
+        if !has_shelf {
+            return promise;
+        }
+
+        // Step 4.
+        promise.resolve_native(&self.persisted());
+        promise
+    }
+
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persist
+    fn Persist(&self, comp: InCompartment) -> Rc<Promise> {
+        // Steps 1-3.
+        let (promise, has_shelf) = self.new_promise(comp);
+        if !has_shelf {
+            return promise;
+        }
+
+        // Step 4.1.
+        let this = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        request_permission_to_use(
+            PermissionName::Persistent_storage,
+            &self.global(),
+            move |permission| {
+                // Steps 4.2-4.5.
+                let persisted = this.root().persist(permission);
+
+                // Step 4.6.
+                trusted_promise.root().resolve_native(&persisted);
+            },
+        );
+        promise
+    }
+
+    // https://storage.spec.whatwg.org/#dom-storagemanager-estimate
+    fn Estimate(&self, comp: InCompartment) -> Rc<Promise> {
+        // Steps 1-3.
+        let (promise, has_shelf) = self.new_promise(comp);
+        if !has_shelf {
+            return promise;
+        }
+
+        // Steps 4-5.
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Estimate(sender, global.get_url()))
+            .unwrap();
+        let estimate = receiver.recv().unwrap();
+
+        // Step 5.1.
+        promise.resolve_native(&StorageEstimate {
+            usage: Some(estimate.usage),
+            quota: Some(estimate.quota),
+        });
+        promise
+    }
+}
@@ -0,0 +1,2326 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::{enter_realm, InCompartment};
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::CryptoKeyMethods;
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{KeyType, KeyUsage};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    self, AesCbcParams, AesCtrParams, AesDerivedKeyParams, AesGcmParams, AesKeyGenParams,
+    Algorithm, CryptoKeyPair, EcKeyGenParams, EcKeyImportParams, EcdhKeyDeriveParams, EcdsaParams,
+    HkdfParams, HmacImportParams, HmacKeyGenParams, JsonWebKey, KeyFormat, Pbkdf2Params,
+    RsaHashedImportParams, RsaHashedKeyGenParams, RsaOaepParams, RsaPssParams, SubtleCryptoMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::{
+    ArrayBufferViewOrArrayBuffer, ArrayBufferViewOrArrayBufferOrJsonWebKey, ObjectOrString,
+};
+use crate::dom::bindings::conversions::{
+    ConversionResult, FromJSValConvertible, ToJSValConvertible,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::bindings::utils::set_dictionary_property;
+use crate::dom::cryptokey::{CryptoKey, Handle, KeyAlgorithm, KeyData};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use js::jsapi::{JSObject, JS_NewPlainObject};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::typedarray::{ArrayBuffer, CreateWith};
+use openssl::aes::{self, AesKey};
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::derive::Deriver;
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use openssl::ecdsa::EcdsaSig;
+use openssl::encrypt::{Decrypter, Encrypter};
+use openssl::error::ErrorStack;
+use openssl::hash::{self, MessageDigest};
+use openssl::memcmp;
+use openssl::nid::Nid;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::pkey::{HasPublic, PKey, Private, Public};
+use openssl::rand::rand_bytes;
+use openssl::rsa::{Padding, Rsa};
+use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+use openssl::symm::{self, Cipher};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::ptr;
+use std::rc::Rc;
+
+/// An operation that runs off the script thread, once its arguments have
+/// been checked.
+type Job = Box<dyn FnOnce() -> Fallible<Output> + Send>;
+
+lazy_static! {
+    /// The threads that the operations of every `SubtleCrypto` of the process
+    /// run on. Key generation can take long, so the operations are queued
+    /// rather than each spawning a thread of its own.
+    static ref JOB_POOL: ThreadPool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .thread_name(|index| format!("SubtleCrypto#{}", index))
+        .build()
+        .expect("Failed to create the SubtleCrypto thread pool");
+}
+
+/// The value the promise of an operation is resolved with.
+enum Output {
+    Bytes(Vec<u8>),
+    Bool(bool),
+    Jwk(Jwk),
+    Key(KeyData),
+    KeyPair(KeyData, KeyData),
+}
+
+impl Output {
+    #[allow(unsafe_code)]
+    fn resolve(self, global: &GlobalScope, promise: &Promise) {
+        let cx = global.get_cx();
+        let _ac = enter_realm(promise);
+        match self {
+            Output::Bytes(bytes) => {
+                rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+                let created = unsafe {
+                    ArrayBuffer::create(*cx, CreateWith::Slice(&bytes), array_buffer.handle_mut())
+                };
+                if created.is_err() {
+                    return promise.reject_error(Error::JSFailed);
+                }
+                rooted!(in(*cx) let value = ObjectValue(array_buffer.get()));
+                promise.resolve(cx, value.handle());
+            },
+            Output::Bool(result) => promise.resolve_native(&result),
+            Output::Jwk(jwk) => promise.resolve_native(&jwk.to_dictionary()),
+            Output::Key(key) => promise.resolve_native(&CryptoKey::new(global, key)),
+            Output::KeyPair(public_key, private_key) => promise.resolve_native(&CryptoKeyPair {
+                publicKey: Some(CryptoKey::new(global, public_key)),
+                privateKey: Some(CryptoKey::new(global, private_key)),
+            }),
+        }
+    }
+}
+
+// https://w3c.github.io/webcrypto/#subtlecrypto-interface
+#[dom_struct]
+pub struct SubtleCrypto {
+    reflector_: Reflector,
+}
+
+impl SubtleCrypto {
+    fn new_inherited() -> SubtleCrypto {
+        SubtleCrypto {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<SubtleCrypto> {
+        reflect_dom_object(
+            Box::new(SubtleCrypto::new_inherited()),
+            global,
+            SubtleCryptoBinding::Wrap,
+        )
+    }
+
+    /// Runs `job` on the thread pool, and settles `promise` with its result,
+    /// or rejects it right away if the arguments were invalid.
+    fn run_job(&self, promise: &Rc<Promise>, job: Fallible<Job>) {
+        let job = match job {
+            Ok(job) => job,
+            Err(error) => return promise.reject_error(error),
+        };
+        let global = self.global();
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        JOB_POOL.spawn(move || {
+            let result = job();
+            let _ = task_source.queue_with_canceller(
+                task!(settle_subtle_crypto_promise: move || {
+                    let promise = trusted_promise.root();
+                    match result {
+                        Ok(output) => output.resolve(&promise.global(), &promise),
+                        Err(error) => promise.reject_error(error),
+                    }
+                }),
+                &canceller,
+            );
+        });
+    }
+}
+
+impl SubtleCryptoMethods for SubtleCrypto {
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-encrypt
+    fn Encrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = cipher_job(cx, &algorithm, key, &data, KeyUsage::Encrypt);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-decrypt
+    fn Decrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = cipher_job(cx, &algorithm, key, &data, KeyUsage::Decrypt);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-sign
+    fn Sign(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = signature_job(cx, &algorithm, key, None, &data);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-verify
+    fn Verify(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        signature: ArrayBufferViewOrArrayBuffer,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = signature_job(cx, &algorithm, key, Some(&signature), &data);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-digest
+    fn Digest(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        // Steps 1-2.
+        let data = buffer_source_to_vec(&data);
+        // Steps 3-4.
+        let job = normalize_hash(cx, &algorithm).map(|hash| -> Job {
+            Box::new(move || {
+                hash::hash(hash.message_digest(), &data)
+                    .map(|digest| Output::Bytes(digest.to_vec()))
+                    .map_err(operation_error)
+            })
+        });
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-generatekey
+    fn GenerateKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        usages: Vec<KeyUsage>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        // Steps 1-4.
+        let job = KeyGenParams::normalize(cx, &algorithm)
+            .map(|params| -> Job { Box::new(move || params.generate(extractable, &usages)) });
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-derivekey
+    fn DeriveKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        derived_key_type: ObjectOrString,
+        extractable: bool,
+        usages: Vec<KeyUsage>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = derive_key_job(
+            cx,
+            &algorithm,
+            base_key,
+            &derived_key_type,
+            extractable,
+            usages,
+        );
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-derivebits
+    fn DeriveBits(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        length: Option<u32>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = derive_bits_job(cx, &algorithm, base_key, length);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-importkey
+    fn ImportKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key_data: ArrayBufferViewOrArrayBufferOrJsonWebKey,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        usages: Vec<KeyUsage>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = import_key_job(cx, format, &key_data, &algorithm, extractable, usages);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-exportkey
+    fn ExportKey(&self, format: KeyFormat, key: &CryptoKey, comp: InCompartment) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = check_exportable(format, key).map(|()| -> Job {
+            let key = key.data().clone();
+            Box::new(move || export_key(format, &key).map(KeyMaterial::into_output))
+        });
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-wrapkey
+    fn WrapKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key: &CryptoKey,
+        wrapping_key: &CryptoKey,
+        wrap_algorithm: ObjectOrString,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = wrap_key_job(cx, format, key, wrapping_key, &wrap_algorithm);
+        self.run_job(&promise, job);
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-subtlecrypto-unwrapkey
+    fn UnwrapKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        wrapped_key: ArrayBufferViewOrArrayBuffer,
+        unwrapping_key: &CryptoKey,
+        unwrap_algorithm: ObjectOrString,
+        unwrapped_key_algorithm: ObjectOrString,
+        extractable: bool,
+        usages: Vec<KeyUsage>,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_compartment(&self.global(), comp);
+        let job = unwrap_key_job(
+            cx,
+            format,
+            &wrapped_key,
+            unwrapping_key,
+            &unwrap_algorithm,
+            &unwrapped_key_algorithm,
+            extractable,
+            usages,
+        );
+        self.run_job(&promise, job);
+        promise
+    }
+}
+
+fn cipher_job(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    key: &CryptoKey,
+    data: &ArrayBufferViewOrArrayBuffer,
+    usage: KeyUsage,
+) -> Fallible<Job> {
+    // Steps 1-2.
+    let data = buffer_source_to_vec(data);
+    // Steps 3-4.
+    let params = CipherParams::normalize(cx, algorithm, false)?;
+    // Steps 8-9.
+    check_key(key, params.name(), usage)?;
+    let key = key.data().clone();
+    Ok(Box::new(move || {
+        let result = if usage == KeyUsage::Encrypt {
+            params.encrypt(&key, &data)
+        } else {
+            params.decrypt(&key, &data)
+        };
+        result.map(Output::Bytes)
+    }))
+}
+
+fn signature_job(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    key: &CryptoKey,
+    signature: Option<&ArrayBufferViewOrArrayBuffer>,
+    data: &ArrayBufferViewOrArrayBuffer,
+) -> Fallible<Job> {
+    // Steps 1-3.
+    let signature = signature.map(buffer_source_to_vec);
+    let data = buffer_source_to_vec(data);
+    // Steps 4-5.
+    let params = SignatureParams::normalize(cx, algorithm)?;
+    // Steps 9-10.
+    let usage = if signature.is_some() {
+        KeyUsage::Verify
+    } else {
+        KeyUsage::Sign
+    };
+    check_key(key, params.name(), usage)?;
+    let key = key.data().clone();
+    Ok(Box::new(move || match signature {
+        Some(signature) => params.verify(&key, &signature, &data).map(Output::Bool),
+        None => params.sign(&key, &data).map(Output::Bytes),
+    }))
+}
+
+fn derive_key_job(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    base_key: &CryptoKey,
+    derived_key_type: &ObjectOrString,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<Job> {
+    // Steps 2-3.
+    let params = DeriveParams::normalize(cx, algorithm)?;
+    // Steps 4-7.
+    let import_params = ImportParams::normalize(cx, derived_key_type)?;
+    let length = get_key_length(cx, derived_key_type)?;
+    // Steps 11-12.
+    check_key(base_key, params.name(), KeyUsage::DeriveKey)?;
+    let base_key = base_key.data().clone();
+    Ok(Box::new(move || {
+        // Steps 13-14.
+        let secret = params.derive_bits(&base_key, length)?;
+        // Steps 15-16.
+        let key = import_params.import(
+            KeyFormat::Raw,
+            &KeyMaterial::Bytes(secret),
+            extractable,
+            &usages,
+        )?;
+        usable_key(key)
+    }))
+}
+
+fn derive_bits_job(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    base_key: &CryptoKey,
+    length: Option<u32>,
+) -> Fallible<Job> {
+    // Steps 2-3.
+    let params = DeriveParams::normalize(cx, algorithm)?;
+    // Steps 7-8.
+    check_key(base_key, params.name(), KeyUsage::DeriveBits)?;
+    let base_key = base_key.data().clone();
+    Ok(Box::new(move || {
+        params.derive_bits(&base_key, length).map(Output::Bytes)
+    }))
+}
+
+fn import_key_job(
+    cx: JSContext,
+    format: KeyFormat,
+    key_data: &ArrayBufferViewOrArrayBufferOrJsonWebKey,
+    algorithm: &ObjectOrString,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<Job> {
+    // Steps 2-3.
+    let params = ImportParams::normalize(cx, algorithm)?;
+    // Step 4.
+    let material = match (format, key_data) {
+        (KeyFormat::Jwk, &ArrayBufferViewOrArrayBufferOrJsonWebKey::JsonWebKey(ref jwk)) => {
+            KeyMaterial::Jwk(Jwk::from_dictionary(jwk))
+        },
+        (KeyFormat::Jwk, _) => {
+            return Err(Error::Type("A JSON Web Key is required".to_owned()));
+        },
+        (_, &ArrayBufferViewOrArrayBufferOrJsonWebKey::ArrayBufferView(ref view)) => {
+            KeyMaterial::Bytes(view.to_vec())
+        },
+        (_, &ArrayBufferViewOrArrayBufferOrJsonWebKey::ArrayBuffer(ref buffer)) => {
+            KeyMaterial::Bytes(buffer.to_vec())
+        },
+        (_, &ArrayBufferViewOrArrayBufferOrJsonWebKey::JsonWebKey(_)) => {
+            return Err(Error::Type("A buffer source is required".to_owned()));
+        },
+    };
+    Ok(Box::new(move || {
+        // Steps 8-11.
+        let key = params.import(format, &material, extractable, &usages)?;
+        usable_key(key)
+    }))
+}
+
+fn wrap_key_job(
+    cx: JSContext,
+    format: KeyFormat,
+    key: &CryptoKey,
+    wrapping_key: &CryptoKey,
+    algorithm: &ObjectOrString,
+) -> Fallible<Job> {
+    // Steps 2-4.
+    let params = CipherParams::normalize(cx, algorithm, true)?;
+    // Steps 8-9.
+    check_key(wrapping_key, params.name(), KeyUsage::WrapKey)?;
+    // Steps 10-11.
+    check_exportable(format, key)?;
+    let key = key.data().clone();
+    let wrapping_key = wrapping_key.data().clone();
+    Ok(Box::new(move || {
+        // Steps 12-13.
+        let bytes = match export_key(format, &key)? {
+            KeyMaterial::Bytes(bytes) => bytes,
+            KeyMaterial::Jwk(jwk) => serde_json::to_vec(&jwk).map_err(operation_error)?,
+        };
+        // Steps 14-15.
+        params.encrypt(&wrapping_key, &bytes).map(Output::Bytes)
+    }))
+}
+
+fn unwrap_key_job(
+    cx: JSContext,
+    format: KeyFormat,
+    wrapped_key: &ArrayBufferViewOrArrayBuffer,
+    unwrapping_key: &CryptoKey,
+    unwrap_algorithm: &ObjectOrString,
+    unwrapped_key_algorithm: &ObjectOrString,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+) -> Fallible<Job> {
+    // Steps 2-3.
+    let wrapped_key = buffer_source_to_vec(wrapped_key);
+    // Steps 4-6.
+    let params = CipherParams::normalize(cx, unwrap_algorithm, true)?;
+    // Steps 7-8.
+    let import_params = ImportParams::normalize(cx, unwrapped_key_algorithm)?;
+    // Steps 12-13.
+    check_key(unwrapping_key, params.name(), KeyUsage::UnwrapKey)?;
+    let unwrapping_key = unwrapping_key.data().clone();
+    Ok(Box::new(move || {
+        // Steps 14-15.
+        let bytes = params.decrypt(&unwrapping_key, &wrapped_key)?;
+        let material = if format == KeyFormat::Jwk {
+            KeyMaterial::Jwk(serde_json::from_slice(&bytes).map_err(data_error)?)
+        } else {
+            KeyMaterial::Bytes(bytes)
+        };
+        // Steps 16-18.
+        let key = import_params.import(format, &material, extractable, &usages)?;
+        usable_key(key)
+    }))
+}
+
+/// Checks that `key` can be used for an operation of the algorithm `name`.
+fn check_key(key: &CryptoKey, name: AlgorithmName, usage: KeyUsage) -> Fallible<()> {
+    if key.algorithm_name() != name || !key.has_usage(usage) {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(())
+}
+
+/// Checks that `key` can be exported in `format`.
+fn check_exportable(format: KeyFormat, key: &CryptoKey) -> Fallible<()> {
+    let supported = match key.algorithm_name() {
+        AlgorithmName::RsassaPkcs1V1_5 | AlgorithmName::RsaPss | AlgorithmName::RsaOaep => {
+            format != KeyFormat::Raw
+        },
+        AlgorithmName::Ecdsa | AlgorithmName::Ecdh => true,
+        AlgorithmName::AesCtr |
+        AlgorithmName::AesCbc |
+        AlgorithmName::AesGcm |
+        AlgorithmName::AesKw |
+        AlgorithmName::Hmac => format == KeyFormat::Raw || format == KeyFormat::Jwk,
+        _ => false,
+    };
+    if !supported {
+        return Err(Error::NotSupported);
+    }
+    if !key.Extractable() {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(())
+}
+
+/// Secret and private keys can't be created without usages.
+fn usable_key(key: KeyData) -> Fallible<Output> {
+    if key.key_type != KeyType::Public && key.usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(Output::Key(key))
+}
+
+fn buffer_source_to_vec(buffer_source: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *buffer_source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+fn operation_error<E>(_: E) -> Error {
+    Error::Operation
+}
+
+fn data_error<E>(_: E) -> Error {
+    Error::Data
+}
+
+/// The algorithms of the specification that are supported,
+/// <https://w3c.github.io/webcrypto/#algorithm-overview>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlgorithmName {
+    RsassaPkcs1V1_5,
+    RsaPss,
+    RsaOaep,
+    Ecdsa,
+    Ecdh,
+    AesCtr,
+    AesCbc,
+    AesGcm,
+    AesKw,
+    Hmac,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+    Hkdf,
+    Pbkdf2,
+}
+
+const ALGORITHM_NAMES: &'static [(&'static str, AlgorithmName)] = &[
+    ("RSASSA-PKCS1-v1_5", AlgorithmName::RsassaPkcs1V1_5),
+    ("RSA-PSS", AlgorithmName::RsaPss),
+    ("RSA-OAEP", AlgorithmName::RsaOaep),
+    ("ECDSA", AlgorithmName::Ecdsa),
+    ("ECDH", AlgorithmName::Ecdh),
+    ("AES-CTR", AlgorithmName::AesCtr),
+    ("AES-CBC", AlgorithmName::AesCbc),
+    ("AES-GCM", AlgorithmName::AesGcm),
+    ("AES-KW", AlgorithmName::AesKw),
+    ("HMAC", AlgorithmName::Hmac),
+    ("SHA-1", AlgorithmName::Sha1),
+    ("SHA-256", AlgorithmName::Sha256),
+    ("SHA-384", AlgorithmName::Sha384),
+    ("SHA-512", AlgorithmName::Sha512),
+    ("HKDF", AlgorithmName::Hkdf),
+    ("PBKDF2", AlgorithmName::Pbkdf2),
+];
+
+impl AlgorithmName {
+    /// Algorithm names are matched case-insensitively.
+    fn from_name(name: &str) -> Option<AlgorithmName> {
+        ALGORITHM_NAMES
+            .iter()
+            .find(|&&(known_name, _)| known_name.eq_ignore_ascii_case(name))
+            .map(|&(_, algorithm)| algorithm)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        ALGORITHM_NAMES
+            .iter()
+            .find(|&&(_, algorithm)| algorithm == self)
+            .map(|&(name, _)| name)
+            .unwrap()
+    }
+
+    /// The usages the public key and the private or secret key of the
+    /// algorithm can have.
+    fn usages(self) -> (&'static [KeyUsage], &'static [KeyUsage]) {
+        match self {
+            AlgorithmName::RsassaPkcs1V1_5 | AlgorithmName::RsaPss | AlgorithmName::Ecdsa => {
+                (&[KeyUsage::Verify], &[KeyUsage::Sign])
+            },
+            AlgorithmName::RsaOaep => (
+                &[KeyUsage::Encrypt, KeyUsage::WrapKey],
+                &[KeyUsage::Decrypt, KeyUsage::UnwrapKey],
+            ),
+            AlgorithmName::Ecdh | AlgorithmName::Hkdf | AlgorithmName::Pbkdf2 => {
+                (&[], &[KeyUsage::DeriveKey, KeyUsage::DeriveBits])
+            },
+            AlgorithmName::AesCtr | AlgorithmName::AesCbc | AlgorithmName::AesGcm => (
+                &[],
+                &[
+                    KeyUsage::Encrypt,
+                    KeyUsage::Decrypt,
+                    KeyUsage::WrapKey,
+                    KeyUsage::UnwrapKey,
+                ],
+            ),
+            AlgorithmName::AesKw => (&[], &[KeyUsage::WrapKey, KeyUsage::UnwrapKey]),
+            AlgorithmName::Hmac => (&[], &[KeyUsage::Sign, KeyUsage::Verify]),
+            AlgorithmName::Sha1 |
+            AlgorithmName::Sha256 |
+            AlgorithmName::Sha384 |
+            AlgorithmName::Sha512 => (&[], &[]),
+        }
+    }
+}
+
+/// The hash functions that can be used by the other algorithms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sha {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Sha {
+    pub fn name(self) -> &'static str {
+        match self {
+            Sha::Sha1 => "SHA-1",
+            Sha::Sha256 => "SHA-256",
+            Sha::Sha384 => "SHA-384",
+            Sha::Sha512 => "SHA-512",
+        }
+    }
+
+    fn message_digest(self) -> MessageDigest {
+        match self {
+            Sha::Sha1 => MessageDigest::sha1(),
+            Sha::Sha256 => MessageDigest::sha256(),
+            Sha::Sha384 => MessageDigest::sha384(),
+            Sha::Sha512 => MessageDigest::sha512(),
+        }
+    }
+
+    /// The size of the blocks of the function, in bits, which is the default
+    /// length of HMAC keys.
+    fn block_size(self) -> u32 {
+        match self {
+            Sha::Sha1 | Sha::Sha256 => 512,
+            Sha::Sha384 | Sha::Sha512 => 1024,
+        }
+    }
+
+    fn output_len(self) -> usize {
+        self.message_digest().size()
+    }
+
+    /// The suffix of the JSON Web Algorithms that use this function.
+    fn jwa_suffix(self) -> &'static str {
+        match self {
+            Sha::Sha1 => "1",
+            Sha::Sha256 => "256",
+            Sha::Sha384 => "384",
+            Sha::Sha512 => "512",
+        }
+    }
+}
+
+/// The elliptic curves that can be used by ECDSA and ECDH.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    P256,
+    P384,
+}
+
+impl Curve {
+    fn from_name(name: &str) -> Option<Curve> {
+        match name {
+            "P-256" => Some(Curve::P256),
+            "P-384" => Some(Curve::P384),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Curve::P256 => "P-256",
+            Curve::P384 => "P-384",
+        }
+    }
+
+    fn nid(self) -> Nid {
+        match self {
+            Curve::P256 => Nid::X9_62_PRIME256V1,
+            Curve::P384 => Nid::SECP384R1,
+        }
+    }
+
+    fn group(self) -> Result<EcGroup, ErrorStack> {
+        EcGroup::from_curve_name(self.nid())
+    }
+
+    /// The size of the coordinates of the points of the curve, in bytes.
+    fn field_len(self) -> usize {
+        match self {
+            Curve::P256 => 32,
+            Curve::P384 => 48,
+        }
+    }
+
+    fn of_key<T>(pkey: &PKey<T>) -> Option<Curve> {
+        let nid = pkey.ec_key().ok()?.group().curve_name()?;
+        [Curve::P256, Curve::P384]
+            .iter()
+            .cloned()
+            .find(|curve| curve.nid() == nid)
+    }
+}
+
+/// Converts `algorithm` to the dictionary of the parameters of an operation,
+/// <https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm>
+#[allow(unsafe_code)]
+fn convert_params<T>(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<T>
+where
+    T: FromJSValConvertible<Config = ()>,
+{
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    match *algorithm {
+        ObjectOrString::Object(ref object) => value.set(ObjectValue(object.get())),
+        // A name on its own is the same as an `Algorithm` dictionary.
+        ObjectOrString::String(ref name) => unsafe {
+            rooted!(in(*cx) let object = JS_NewPlainObject(*cx));
+            rooted!(in(*cx) let mut name_value = UndefinedValue());
+            name.to_jsval(*cx, name_value.handle_mut());
+            set_dictionary_property(*cx, object.handle(), "name", name_value.handle())
+                .map_err(|_| Error::JSFailed)?;
+            value.set(ObjectValue(object.get()));
+        },
+    }
+    match unsafe { T::from_jsval(*cx, value.handle(), ()) } {
+        Ok(ConversionResult::Success(params)) => Ok(params),
+        Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+/// The name of `algorithm`, if it's one of the `supported` algorithms of an
+/// operation.
+fn normalize_name(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    supported: &[AlgorithmName],
+) -> Fallible<AlgorithmName> {
+    let params: Algorithm = convert_params(cx, algorithm)?;
+    AlgorithmName::from_name(&params.name)
+        .filter(|name| supported.contains(name))
+        .ok_or(Error::NotSupported)
+}
+
+/// Normalizes `algorithm` for the "digest" operation.
+fn normalize_hash(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<Sha> {
+    let supported = [
+        AlgorithmName::Sha1,
+        AlgorithmName::Sha256,
+        AlgorithmName::Sha384,
+        AlgorithmName::Sha512,
+    ];
+    Ok(match normalize_name(cx, algorithm, &supported)? {
+        AlgorithmName::Sha1 => Sha::Sha1,
+        AlgorithmName::Sha256 => Sha::Sha256,
+        AlgorithmName::Sha384 => Sha::Sha384,
+        _ => Sha::Sha512,
+    })
+}
+
+/// The parameters of the "encrypt" and "decrypt" operations, which are also
+/// those of "wrapKey" and "unwrapKey".
+pub enum CipherParams {
+    RsaOaep {
+        label: Option<Vec<u8>>,
+    },
+    AesCtr {
+        counter: Vec<u8>,
+        length: u8,
+    },
+    AesCbc {
+        iv: Vec<u8>,
+    },
+    AesGcm {
+        iv: Vec<u8>,
+        additional_data: Option<Vec<u8>>,
+        tag_length: u8,
+    },
+    AesKw,
+}
+
+impl CipherParams {
+    /// Normalizes `algorithm` for the "encrypt" and "decrypt" operations, or
+    /// for "wrapKey" and "unwrapKey" when `wrap` is true, which AES-KW only
+    /// supports.
+    fn normalize(cx: JSContext, algorithm: &ObjectOrString, wrap: bool) -> Fallible<CipherParams> {
+        let mut supported = vec![
+            AlgorithmName::RsaOaep,
+            AlgorithmName::AesCtr,
+            AlgorithmName::AesCbc,
+            AlgorithmName::AesGcm,
+        ];
+        if wrap {
+            supported.push(AlgorithmName::AesKw);
+        }
+        Ok(match normalize_name(cx, algorithm, &supported)? {
+            AlgorithmName::RsaOaep => {
+                let params: RootedTraceableBox<RsaOaepParams> = convert_params(cx, algorithm)?;
+                CipherParams::RsaOaep {
+                    label: params.label.as_ref().map(buffer_source_to_vec),
+                }
+            },
+            AlgorithmName::AesCtr => {
+                let params: RootedTraceableBox<AesCtrParams> = convert_params(cx, algorithm)?;
+                CipherParams::AesCtr {
+                    counter: buffer_source_to_vec(&params.counter),
+                    length: params.length,
+                }
+            },
+            AlgorithmName::AesCbc => {
+                let params: RootedTraceableBox<AesCbcParams> = convert_params(cx, algorithm)?;
+                CipherParams::AesCbc {
+                    iv: buffer_source_to_vec(&params.iv),
+                }
+            },
+            AlgorithmName::AesGcm => {
+                let params: RootedTraceableBox<AesGcmParams> = convert_params(cx, algorithm)?;
+                CipherParams::AesGcm {
+                    iv: buffer_source_to_vec(&params.iv),
+                    additional_data: params.additionalData.as_ref().map(buffer_source_to_vec),
+                    tag_length: params.tagLength.unwrap_or(128),
+                }
+            },
+            _ => CipherParams::AesKw,
+        })
+    }
+
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            CipherParams::RsaOaep { .. } => AlgorithmName::RsaOaep,
+            CipherParams::AesCtr { .. } => AlgorithmName::AesCtr,
+            CipherParams::AesCbc { .. } => AlgorithmName::AesCbc,
+            CipherParams::AesGcm { .. } => AlgorithmName::AesGcm,
+            CipherParams::AesKw => AlgorithmName::AesKw,
+        }
+    }
+
+    pub fn encrypt(&self, key: &KeyData, data: &[u8]) -> Fallible<Vec<u8>> {
+        match (self, &key.algorithm, &key.handle) {
+            (
+                &CipherParams::RsaOaep { ref label },
+                &KeyAlgorithm::Rsa { hash, .. },
+                &Handle::Public(ref public_key),
+            ) => rsa_oaep_encrypt(public_key, hash, label.as_ref(), data).map_err(operation_error),
+            (
+                &CipherParams::AesCtr {
+                    ref counter,
+                    length,
+                },
+                _,
+                &Handle::Secret(ref secret),
+            ) => aes_ctr(secret, counter, length, data),
+            (&CipherParams::AesCbc { ref iv }, _, &Handle::Secret(ref secret)) => {
+                if iv.len() != 16 {
+                    return Err(Error::Operation);
+                }
+                symm::encrypt(aes_cbc_cipher(secret)?, secret, Some(iv), data)
+                    .map_err(operation_error)
+            },
+            (
+                &CipherParams::AesGcm {
+                    ref iv,
+                    ref additional_data,
+                    tag_length,
+                },
+                _,
+                &Handle::Secret(ref secret),
+            ) => {
+                let mut tag = vec![0; aes_gcm_tag_len(tag_length)?];
+                let aad = additional_data.as_ref().map_or(&[][..], |aad| &**aad);
+                let mut encrypted = symm::encrypt_aead(
+                    aes_gcm_cipher(secret)?,
+                    secret,
+                    Some(iv),
+                    aad,
+                    data,
+                    &mut tag,
+                )
+                .map_err(operation_error)?;
+                encrypted.extend(tag);
+                Ok(encrypted)
+            },
+            (&CipherParams::AesKw, _, &Handle::Secret(ref secret)) => {
+                // The data must be made of at least two 64-bit blocks.
+                if data.len() % 8 != 0 || data.len() < 16 {
+                    return Err(Error::Operation);
+                }
+                let key = AesKey::new_encrypt(secret).map_err(operation_error)?;
+                let mut wrapped = vec![0; data.len() + 8];
+                aes::wrap_key(&key, None, &mut wrapped, data).map_err(operation_error)?;
+                Ok(wrapped)
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    pub fn decrypt(&self, key: &KeyData, data: &[u8]) -> Fallible<Vec<u8>> {
+        match (self, &key.algorithm, &key.handle) {
+            (
+                &CipherParams::RsaOaep { ref label },
+                &KeyAlgorithm::Rsa { hash, .. },
+                &Handle::Private(ref private_key),
+            ) => rsa_oaep_decrypt(private_key, hash, label.as_ref(), data).map_err(operation_error),
+            // Encryption and decryption are the same operation with AES-CTR.
+            (
+                &CipherParams::AesCtr {
+                    ref counter,
+                    length,
+                },
+                _,
+                &Handle::Secret(ref secret),
+            ) => aes_ctr(secret, counter, length, data),
+            (&CipherParams::AesCbc { ref iv }, _, &Handle::Secret(ref secret)) => {
+                if iv.len() != 16 {
+                    return Err(Error::Operation);
+                }
+                symm::decrypt(aes_cbc_cipher(secret)?, secret, Some(iv), data)
+                    .map_err(operation_error)
+            },
+            (
+                &CipherParams::AesGcm {
+                    ref iv,
+                    ref additional_data,
+                    tag_length,
+                },
+                _,
+                &Handle::Secret(ref secret),
+            ) => {
+                let tag_len = aes_gcm_tag_len(tag_length)?;
+                if data.len() < tag_len {
+                    return Err(Error::Operation);
+                }
+                let (encrypted, tag) = data.split_at(data.len() - tag_len);
+                let aad = additional_data.as_ref().map_or(&[][..], |aad| &**aad);
+                symm::decrypt_aead(
+                    aes_gcm_cipher(secret)?,
+                    secret,
+                    Some(iv),
+                    aad,
+                    encrypted,
+                    tag,
+                )
+                .map_err(operation_error)
+            },
+            (&CipherParams::AesKw, _, &Handle::Secret(ref secret)) => {
+                if data.len() % 8 != 0 || data.len() < 24 {
+                    return Err(Error::Operation);
+                }
+                let key = AesKey::new_decrypt(secret).map_err(operation_error)?;
+                let mut unwrapped = vec![0; data.len() - 8];
+                aes::unwrap_key(&key, None, &mut unwrapped, data).map_err(operation_error)?;
+                Ok(unwrapped)
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+}
+
+fn rsa_oaep_encrypt(
+    public_key: &PKey<Public>,
+    hash: Sha,
+    label: Option<&Vec<u8>>,
+    data: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut encrypter = Encrypter::new(public_key)?;
+    encrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+    encrypter.set_rsa_oaep_md(hash.message_digest())?;
+    encrypter.set_rsa_mgf1_md(hash.message_digest())?;
+    if let Some(label) = label {
+        encrypter.set_rsa_oaep_label(label)?;
+    }
+    let mut encrypted = vec![0; encrypter.encrypt_len(data)?];
+    let length = encrypter.encrypt(data, &mut encrypted)?;
+    encrypted.truncate(length);
+    Ok(encrypted)
+}
+
+fn rsa_oaep_decrypt(
+    private_key: &PKey<Private>,
+    hash: Sha,
+    label: Option<&Vec<u8>>,
+    data: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut decrypter = Decrypter::new(private_key)?;
+    decrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+    decrypter.set_rsa_oaep_md(hash.message_digest())?;
+    decrypter.set_rsa_mgf1_md(hash.message_digest())?;
+    if let Some(label) = label {
+        decrypter.set_rsa_oaep_label(label)?;
+    }
+    let mut decrypted = vec![0; decrypter.decrypt_len(data)?];
+    let length = decrypter.decrypt(data, &mut decrypted)?;
+    decrypted.truncate(length);
+    Ok(decrypted)
+}
+
+/// AES in counter mode, where only the rightmost `length` bits of the counter
+/// block are incremented,
+/// <https://w3c.github.io/webcrypto/#aes-ctr-operations>
+fn aes_ctr(key: &[u8], counter: &[u8], length: u8, data: &[u8]) -> Fallible<Vec<u8>> {
+    if counter.len() != 16 || length == 0 || length > 128 {
+        return Err(Error::Operation);
+    }
+    let cipher = match key.len() {
+        16 => Cipher::aes_128_ecb(),
+        24 => Cipher::aes_192_ecb(),
+        32 => Cipher::aes_256_ecb(),
+        _ => return Err(Error::Operation),
+    };
+    let blocks = (data.len() + 15) / 16;
+    // The counter must not wrap around.
+    if length < 128 && blocks as u128 > 1 << length {
+        return Err(Error::Operation);
+    }
+    let mask = if length == 128 {
+        !0
+    } else {
+        (1u128 << length) - 1
+    };
+    let mut block = [0; 16];
+    block.copy_from_slice(counter);
+    let mut counter = u128::from_be_bytes(block);
+    let mut counter_blocks = Vec::with_capacity(blocks * 16);
+    for _ in 0..blocks {
+        counter_blocks.extend_from_slice(&counter.to_be_bytes());
+        counter = (counter & !mask) | (counter.wrapping_add(1) & mask);
+    }
+    let key_stream = symm::encrypt(cipher, key, None, &counter_blocks).map_err(operation_error)?;
+    Ok(data
+        .iter()
+        .zip(key_stream)
+        .map(|(byte, key_byte)| byte ^ key_byte)
+        .collect())
+}
+
+fn aes_cbc_cipher(key: &[u8]) -> Fallible<Cipher> {
+    match key.len() {
+        16 => Ok(Cipher::aes_128_cbc()),
+        24 => Ok(Cipher::aes_192_cbc()),
+        32 => Ok(Cipher::aes_256_cbc()),
+        _ => Err(Error::Operation),
+    }
+}
+
+fn aes_gcm_cipher(key: &[u8]) -> Fallible<Cipher> {
+    match key.len() {
+        16 => Ok(Cipher::aes_128_gcm()),
+        24 => Ok(Cipher::aes_192_gcm()),
+        32 => Ok(Cipher::aes_256_gcm()),
+        _ => Err(Error::Operation),
+    }
+}
+
+/// The length of the authentication tag of AES-GCM, in bytes.
+fn aes_gcm_tag_len(tag_length: u8) -> Fallible<usize> {
+    match tag_length {
+        32 | 64 | 96 | 104 | 112 | 120 | 128 => Ok(tag_length as usize / 8),
+        _ => Err(Error::Operation),
+    }
+}
+
+/// The parameters of the "sign" and "verify" operations.
+pub enum SignatureParams {
+    RsassaPkcs1V1_5,
+    RsaPss { salt_length: u32 },
+    Ecdsa { hash: Sha },
+    Hmac,
+}
+
+impl SignatureParams {
+    fn normalize(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<SignatureParams> {
+        let supported = [
+            AlgorithmName::RsassaPkcs1V1_5,
+            AlgorithmName::RsaPss,
+            AlgorithmName::Ecdsa,
+            AlgorithmName::Hmac,
+        ];
+        Ok(match normalize_name(cx, algorithm, &supported)? {
+            AlgorithmName::RsassaPkcs1V1_5 => SignatureParams::RsassaPkcs1V1_5,
+            AlgorithmName::RsaPss => {
+                let params: RsaPssParams = convert_params(cx, algorithm)?;
+                SignatureParams::RsaPss {
+                    salt_length: params.saltLength,
+                }
+            },
+            AlgorithmName::Ecdsa => {
+                let params: RootedTraceableBox<EcdsaParams> = convert_params(cx, algorithm)?;
+                SignatureParams::Ecdsa {
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            _ => SignatureParams::Hmac,
+        })
+    }
+
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            SignatureParams::RsassaPkcs1V1_5 => AlgorithmName::RsassaPkcs1V1_5,
+            SignatureParams::RsaPss { .. } => AlgorithmName::RsaPss,
+            SignatureParams::Ecdsa { .. } => AlgorithmName::Ecdsa,
+            SignatureParams::Hmac => AlgorithmName::Hmac,
+        }
+    }
+
+    pub fn sign(&self, key: &KeyData, data: &[u8]) -> Fallible<Vec<u8>> {
+        let signature = match (self, &key.algorithm, &key.handle) {
+            (
+                &SignatureParams::Hmac,
+                &KeyAlgorithm::Hmac { hash, .. },
+                &Handle::Secret(ref secret),
+            ) => hmac(hash, secret, data),
+            (
+                &SignatureParams::Ecdsa { hash },
+                &KeyAlgorithm::Ec { curve, .. },
+                &Handle::Private(ref private_key),
+            ) => ecdsa_sign(private_key, hash, curve, data),
+            (_, &KeyAlgorithm::Rsa { hash, .. }, &Handle::Private(ref private_key)) => {
+                let mut signer =
+                    Signer::new(hash.message_digest(), private_key).map_err(operation_error)?;
+                if let SignatureParams::RsaPss { salt_length } = *self {
+                    signer
+                        .set_rsa_padding(Padding::PKCS1_PSS)
+                        .and_then(|_| {
+                            signer.set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_length as i32))
+                        })
+                        .and_then(|_| signer.set_rsa_mgf1_md(hash.message_digest()))
+                        .map_err(operation_error)?;
+                }
+                signer.update(data).and_then(|_| signer.sign_to_vec())
+            },
+            _ => return Err(Error::InvalidAccess),
+        };
+        signature.map_err(operation_error)
+    }
+
+    pub fn verify(&self, key: &KeyData, signature: &[u8], data: &[u8]) -> Fallible<bool> {
+        let verified = match (self, &key.algorithm, &key.handle) {
+            (
+                &SignatureParams::Hmac,
+                &KeyAlgorithm::Hmac { hash, .. },
+                &Handle::Secret(ref secret),
+            ) => hmac(hash, secret, data).map(|expected| {
+                expected.len() == signature.len() && memcmp::eq(&expected, signature)
+            }),
+            (
+                &SignatureParams::Ecdsa { hash },
+                &KeyAlgorithm::Ec { curve, .. },
+                &Handle::Public(ref public_key),
+            ) => ecdsa_verify(public_key, hash, curve, signature, data),
+            (_, &KeyAlgorithm::Rsa { hash, .. }, &Handle::Public(ref public_key)) => {
+                let mut verifier =
+                    Verifier::new(hash.message_digest(), public_key).map_err(operation_error)?;
+                if let SignatureParams::RsaPss { salt_length } = *self {
+                    verifier
+                        .set_rsa_padding(Padding::PKCS1_PSS)
+                        .and_then(|_| {
+                            verifier.set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_length as i32))
+                        })
+                        .and_then(|_| verifier.set_rsa_mgf1_md(hash.message_digest()))
+                        .map_err(operation_error)?;
+                }
+                // Malformed signatures are invalid ones.
+                verifier
+                    .update(data)
+                    .map(|_| verifier.verify(signature).unwrap_or(false))
+            },
+            _ => return Err(Error::InvalidAccess),
+        };
+        verified.map_err(operation_error)
+    }
+}
+
+fn hmac(hash: Sha, key: &[u8], data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(hash.message_digest(), &key)?;
+    signer.update(data)?;
+    signer.sign_to_vec()
+}
+
+/// Signs `data` with ECDSA, in the format of the specification, which is the
+/// concatenation of the integers r and s of the signature,
+/// <https://w3c.github.io/webcrypto/#ecdsa-operations>
+fn ecdsa_sign(
+    private_key: &PKey<Private>,
+    hash: Sha,
+    curve: Curve,
+    data: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut signer = Signer::new(hash.message_digest(), private_key)?;
+    signer.update(data)?;
+    let signature = EcdsaSig::from_der(&signer.sign_to_vec()?)?;
+    let mut raw_signature = padded_bytes(signature.r(), curve.field_len());
+    raw_signature.extend(padded_bytes(signature.s(), curve.field_len()));
+    Ok(raw_signature)
+}
+
+fn ecdsa_verify(
+    public_key: &PKey<Public>,
+    hash: Sha,
+    curve: Curve,
+    signature: &[u8],
+    data: &[u8],
+) -> Result<bool, ErrorStack> {
+    let length = curve.field_len();
+    if signature.len() != 2 * length {
+        return Ok(false);
+    }
+    let r = BigNum::from_slice(&signature[..length])?;
+    let s = BigNum::from_slice(&signature[length..])?;
+    let signature = EcdsaSig::from_private_components(r, s)?.to_der()?;
+    let mut verifier = Verifier::new(hash.message_digest(), public_key)?;
+    verifier.update(data)?;
+    Ok(verifier.verify(&signature).unwrap_or(false))
+}
+
+/// The big-endian bytes of `number`, padded with zeros to `length` bytes.
+fn padded_bytes(number: &BigNumRef, length: usize) -> Vec<u8> {
+    let bytes = number.to_vec();
+    let mut padded = vec![0; length.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+/// The parameters of the "deriveBits" operation, which is also used by
+/// "deriveKey".
+enum DeriveParams {
+    Ecdh {
+        public_key: KeyData,
+    },
+    Hkdf {
+        hash: Sha,
+        salt: Vec<u8>,
+        info: Vec<u8>,
+    },
+    Pbkdf2 {
+        hash: Sha,
+        salt: Vec<u8>,
+        iterations: u32,
+    },
+}
+
+impl DeriveParams {
+    fn normalize(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<DeriveParams> {
+        let supported = [
+            AlgorithmName::Ecdh,
+            AlgorithmName::Hkdf,
+            AlgorithmName::Pbkdf2,
+        ];
+        Ok(match normalize_name(cx, algorithm, &supported)? {
+            AlgorithmName::Ecdh => {
+                let params: EcdhKeyDeriveParams = convert_params(cx, algorithm)?;
+                DeriveParams::Ecdh {
+                    public_key: params.public.data().clone(),
+                }
+            },
+            AlgorithmName::Hkdf => {
+                let params: RootedTraceableBox<HkdfParams> = convert_params(cx, algorithm)?;
+                DeriveParams::Hkdf {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    salt: buffer_source_to_vec(&params.salt),
+                    info: buffer_source_to_vec(&params.info),
+                }
+            },
+            _ => {
+                let params: RootedTraceableBox<Pbkdf2Params> = convert_params(cx, algorithm)?;
+                DeriveParams::Pbkdf2 {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    salt: buffer_source_to_vec(&params.salt),
+                    iterations: params.iterations,
+                }
+            },
+        })
+    }
+
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            DeriveParams::Ecdh { .. } => AlgorithmName::Ecdh,
+            DeriveParams::Hkdf { .. } => AlgorithmName::Hkdf,
+            DeriveParams::Pbkdf2 { .. } => AlgorithmName::Pbkdf2,
+        }
+    }
+
+    /// Derives `length` bits from `key`, all the bits of the shared secret
+    /// of ECDH when it's null.
+    fn derive_bits(&self, key: &KeyData, length: Option<u32>) -> Fallible<Vec<u8>> {
+        match (self, &key.algorithm, &key.handle) {
+            (
+                &DeriveParams::Ecdh { ref public_key },
+                &KeyAlgorithm::Ec { curve, .. },
+                &Handle::Private(ref private_key),
+            ) => {
+                let public = match (&public_key.algorithm, &public_key.handle) {
+                    (
+                        &KeyAlgorithm::Ec {
+                            name,
+                            curve: public_curve,
+                        },
+                        &Handle::Public(ref public),
+                    ) if name == AlgorithmName::Ecdh && public_curve == curve => public,
+                    _ => return Err(Error::InvalidAccess),
+                };
+                let secret = Deriver::new(private_key)
+                    .and_then(|mut deriver| {
+                        deriver.set_peer(public)?;
+                        deriver.derive_to_vec()
+                    })
+                    .map_err(operation_error)?;
+                truncate_bits(secret, length)
+            },
+            (
+                &DeriveParams::Hkdf {
+                    hash,
+                    ref salt,
+                    ref info,
+                },
+                _,
+                &Handle::Secret(ref secret),
+            ) => hkdf(hash, secret, salt, info, byte_length(length)?),
+            (
+                &DeriveParams::Pbkdf2 {
+                    hash,
+                    ref salt,
+                    iterations,
+                },
+                _,
+                &Handle::Secret(ref secret),
+            ) => {
+                let mut derived = vec![0; byte_length(length)?];
+                if iterations == 0 {
+                    return Err(Error::Operation);
+                }
+                pbkdf2_hmac(
+                    secret,
+                    salt,
+                    iterations as usize,
+                    hash.message_digest(),
+                    &mut derived,
+                )
+                .map_err(operation_error)?;
+                Ok(derived)
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+}
+
+/// The largest length in bits of the keys generated and the bits derived, so
+/// that script can't make us allocate without bound. No algorithm has any use
+/// for keys this long.
+const MAX_LENGTH: u32 = 1 << 20;
+
+/// The number of bytes to derive with HKDF and PBKDF2, which require a length
+/// that is a multiple of 8.
+fn byte_length(length: Option<u32>) -> Fallible<usize> {
+    match length {
+        Some(length) if length % 8 == 0 && length <= MAX_LENGTH => Ok(length as usize / 8),
+        _ => Err(Error::Operation),
+    }
+}
+
+/// Keeps the first `length` bits of `bits`, if any.
+fn truncate_bits(mut bits: Vec<u8>, length: Option<u32>) -> Fallible<Vec<u8>> {
+    let length = match length {
+        Some(length) => length as usize,
+        None => return Ok(bits),
+    };
+    if length > bits.len() * 8 {
+        return Err(Error::Operation);
+    }
+    bits.truncate((length + 7) / 8);
+    if length % 8 != 0 {
+        if let Some(last) = bits.last_mut() {
+            *last &= 0xff << (8 - length % 8);
+        }
+    }
+    Ok(bits)
+}
+
+/// <https://tools.ietf.org/html/rfc5869>
+fn hkdf(hash: Sha, key: &[u8], salt: &[u8], info: &[u8], length: usize) -> Fallible<Vec<u8>> {
+    let hash_len = hash.output_len();
+    if length > 255 * hash_len {
+        return Err(Error::Operation);
+    }
+    // Extract, with a salt made of zeros if there is none.
+    let zeros = vec![0; hash_len];
+    let salt = if salt.is_empty() { &zeros[..] } else { salt };
+    let pseudorandom_key = hmac(hash, salt, key).map_err(operation_error)?;
+    // Expand.
+    let mut derived = Vec::with_capacity(length + hash_len);
+    let mut block = vec![];
+    let mut counter = 1u8;
+    while derived.len() < length {
+        block.extend_from_slice(info);
+        block.push(counter);
+        block = hmac(hash, &pseudorandom_key, &block).map_err(operation_error)?;
+        derived.extend_from_slice(&block);
+        counter = counter.wrapping_add(1);
+    }
+    derived.truncate(length);
+    Ok(derived)
+}
+
+/// The length of the keys derived with `algorithm` by "deriveKey",
+/// <https://w3c.github.io/webcrypto/#dfn-get-key-length>
+fn get_key_length(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<Option<u32>> {
+    let supported = [
+        AlgorithmName::AesCtr,
+        AlgorithmName::AesCbc,
+        AlgorithmName::AesGcm,
+        AlgorithmName::AesKw,
+        AlgorithmName::Hmac,
+        AlgorithmName::Hkdf,
+        AlgorithmName::Pbkdf2,
+    ];
+    match normalize_name(cx, algorithm, &supported)? {
+        AlgorithmName::Hmac => {
+            let params: RootedTraceableBox<HmacImportParams> = convert_params(cx, algorithm)?;
+            let hash = normalize_hash(cx, &params.hash)?;
+            match params.length {
+                Some(0) => Err(Error::Type("The length of a key can't be 0".to_owned())),
+                Some(length) => Ok(Some(length)),
+                None => Ok(Some(hash.block_size())),
+            }
+        },
+        AlgorithmName::Hkdf | AlgorithmName::Pbkdf2 => Ok(None),
+        _ => {
+            let params: AesDerivedKeyParams = convert_params(cx, algorithm)?;
+            match params.length {
+                128 | 192 | 256 => Ok(Some(params.length as u32)),
+                _ => Err(Error::Operation),
+            }
+        },
+    }
+}
+
+/// The parameters of the "generateKey" operation.
+enum KeyGenParams {
+    Rsa {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: Sha,
+    },
+    Ec {
+        name: AlgorithmName,
+        curve: Curve,
+    },
+    Aes {
+        name: AlgorithmName,
+        length: u16,
+    },
+    Hmac {
+        hash: Sha,
+        length: Option<u32>,
+    },
+}
+
+impl KeyGenParams {
+    fn normalize(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<KeyGenParams> {
+        let supported = [
+            AlgorithmName::RsassaPkcs1V1_5,
+            AlgorithmName::RsaPss,
+            AlgorithmName::RsaOaep,
+            AlgorithmName::Ecdsa,
+            AlgorithmName::Ecdh,
+            AlgorithmName::AesCtr,
+            AlgorithmName::AesCbc,
+            AlgorithmName::AesGcm,
+            AlgorithmName::AesKw,
+            AlgorithmName::Hmac,
+        ];
+        Ok(match normalize_name(cx, algorithm, &supported)? {
+            name @ AlgorithmName::RsassaPkcs1V1_5 |
+            name @ AlgorithmName::RsaPss |
+            name @ AlgorithmName::RsaOaep => {
+                let params: RootedTraceableBox<RsaHashedKeyGenParams> =
+                    convert_params(cx, algorithm)?;
+                KeyGenParams::Rsa {
+                    name,
+                    modulus_length: params.parent.modulusLength,
+                    public_exponent: buffer_source_to_vec(&params.parent.publicExponent),
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            name @ AlgorithmName::Ecdsa | name @ AlgorithmName::Ecdh => {
+                let params: EcKeyGenParams = convert_params(cx, algorithm)?;
+                KeyGenParams::Ec {
+                    name,
+                    curve: Curve::from_name(&params.namedCurve).ok_or(Error::NotSupported)?,
+                }
+            },
+            AlgorithmName::Hmac => {
+                let params: RootedTraceableBox<HmacKeyGenParams> = convert_params(cx, algorithm)?;
+                KeyGenParams::Hmac {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    length: params.length,
+                }
+            },
+            name => {
+                let params: AesKeyGenParams = convert_params(cx, algorithm)?;
+                KeyGenParams::Aes {
+                    name,
+                    length: params.length,
+                }
+            },
+        })
+    }
+
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            KeyGenParams::Rsa { name, .. } => name,
+            KeyGenParams::Ec { name, .. } => name,
+            KeyGenParams::Aes { name, .. } => name,
+            KeyGenParams::Hmac { .. } => AlgorithmName::Hmac,
+        }
+    }
+
+    fn generate(&self, extractable: bool, usages: &[KeyUsage]) -> Fallible<Output> {
+        let (public_usages, private_usages) = self.name().usages();
+        if usages
+            .iter()
+            .any(|usage| !public_usages.contains(usage) && !private_usages.contains(usage))
+        {
+            return Err(Error::Syntax);
+        }
+        match *self {
+            KeyGenParams::Rsa {
+                name,
+                modulus_length,
+                ref public_exponent,
+                hash,
+            } => {
+                let private_key = BigNum::from_slice(public_exponent)
+                    .and_then(|exponent| Rsa::generate_with_e(modulus_length, &exponent))
+                    .and_then(PKey::from_rsa)
+                    .map_err(operation_error)?;
+                let algorithm = KeyAlgorithm::Rsa {
+                    name,
+                    modulus_length,
+                    public_exponent: public_exponent.clone(),
+                    hash,
+                };
+                key_pair(algorithm, private_key, extractable, usages)
+            },
+            KeyGenParams::Ec { name, curve } => {
+                let private_key = curve
+                    .group()
+                    .and_then(|group| EcKey::generate(&group))
+                    .and_then(PKey::from_ec_key)
+                    .map_err(operation_error)?;
+                key_pair(
+                    KeyAlgorithm::Ec { name, curve },
+                    private_key,
+                    extractable,
+                    usages,
+                )
+            },
+            KeyGenParams::Aes { name, length } => {
+                if length != 128 && length != 192 && length != 256 {
+                    return Err(Error::Operation);
+                }
+                let secret = random_bytes(length as usize / 8)?;
+                usable_key(secret_key(
+                    KeyAlgorithm::Aes { name, length },
+                    secret,
+                    extractable,
+                    usages,
+                ))
+            },
+            KeyGenParams::Hmac { hash, length } => {
+                let length = match length {
+                    Some(0) => return Err(Error::Operation),
+                    Some(length) if length > MAX_LENGTH => return Err(Error::Operation),
+                    Some(length) => length,
+                    None => hash.block_size(),
+                };
+                let secret = random_bytes((length as usize + 7) / 8)?;
+                usable_key(secret_key(
+                    KeyAlgorithm::Hmac { hash, length },
+                    secret,
+                    extractable,
+                    usages,
+                ))
+            },
+        }
+    }
+}
+
+fn random_bytes(length: usize) -> Fallible<Vec<u8>> {
+    let mut bytes = vec![0; length];
+    rand_bytes(&mut bytes).map_err(operation_error)?;
+    Ok(bytes)
+}
+
+fn secret_key(
+    algorithm: KeyAlgorithm,
+    secret: Vec<u8>,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> KeyData {
+    KeyData {
+        key_type: KeyType::Secret,
+        extractable,
+        algorithm,
+        usages: usages.to_vec(),
+        handle: Handle::Secret(secret),
+    }
+}
+
+/// The key pair of a generated private key, whose usages are split between
+/// the public and the private key. The public key is always extractable.
+fn key_pair(
+    algorithm: KeyAlgorithm,
+    private_key: PKey<Private>,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Output> {
+    let (public_usages, private_usages) = algorithm.name().usages();
+    let private_usages: Vec<_> = usages
+        .iter()
+        .cloned()
+        .filter(|usage| private_usages.contains(usage))
+        .collect();
+    if private_usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    let public_key = private_key
+        .public_key_to_der()
+        .and_then(|der| PKey::public_key_from_der(&der))
+        .map_err(operation_error)?;
+    let public = KeyData {
+        key_type: KeyType::Public,
+        extractable: true,
+        algorithm: algorithm.clone(),
+        usages: usages
+            .iter()
+            .cloned()
+            .filter(|usage| public_usages.contains(usage))
+            .collect(),
+        handle: Handle::Public(public_key),
+    };
+    let private = KeyData {
+        key_type: KeyType::Private,
+        extractable,
+        algorithm,
+        usages: private_usages,
+        handle: Handle::Private(private_key),
+    };
+    Ok(Output::KeyPair(public, private))
+}
+
+/// The data a key is imported from, or exported to.
+pub enum KeyMaterial {
+    Bytes(Vec<u8>),
+    Jwk(Jwk),
+}
+
+impl KeyMaterial {
+    fn into_output(self) -> Output {
+        match self {
+            KeyMaterial::Bytes(bytes) => Output::Bytes(bytes),
+            KeyMaterial::Jwk(jwk) => Output::Jwk(jwk),
+        }
+    }
+}
+
+/// The parameters of the "importKey" operation, which is also used by
+/// "deriveKey" and "unwrapKey".
+pub enum ImportParams {
+    Rsa { name: AlgorithmName, hash: Sha },
+    Ec { name: AlgorithmName, curve: Curve },
+    Aes { name: AlgorithmName },
+    Hmac { hash: Sha, length: Option<u32> },
+    Kdf { name: AlgorithmName },
+}
+
+impl ImportParams {
+    fn normalize(cx: JSContext, algorithm: &ObjectOrString) -> Fallible<ImportParams> {
+        let supported = [
+            AlgorithmName::RsassaPkcs1V1_5,
+            AlgorithmName::RsaPss,
+            AlgorithmName::RsaOaep,
+            AlgorithmName::Ecdsa,
+            AlgorithmName::Ecdh,
+            AlgorithmName::AesCtr,
+            AlgorithmName::AesCbc,
+            AlgorithmName::AesGcm,
+            AlgorithmName::AesKw,
+            AlgorithmName::Hmac,
+            AlgorithmName::Hkdf,
+            AlgorithmName::Pbkdf2,
+        ];
+        Ok(match normalize_name(cx, algorithm, &supported)? {
+            name @ AlgorithmName::RsassaPkcs1V1_5 |
+            name @ AlgorithmName::RsaPss |
+            name @ AlgorithmName::RsaOaep => {
+                let params: RootedTraceableBox<RsaHashedImportParams> =
+                    convert_params(cx, algorithm)?;
+                ImportParams::Rsa {
+                    name,
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            name @ AlgorithmName::Ecdsa | name @ AlgorithmName::Ecdh => {
+                let params: EcKeyImportParams = convert_params(cx, algorithm)?;
+                ImportParams::Ec {
+                    name,
+                    curve: Curve::from_name(&params.namedCurve).ok_or(Error::NotSupported)?,
+                }
+            },
+            AlgorithmName::Hmac => {
+                let params: RootedTraceableBox<HmacImportParams> = convert_params(cx, algorithm)?;
+                ImportParams::Hmac {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    length: params.length,
+                }
+            },
+            name @ AlgorithmName::Hkdf | name @ AlgorithmName::Pbkdf2 => ImportParams::Kdf { name },
+            name => ImportParams::Aes { name },
+        })
+    }
+
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            ImportParams::Rsa { name, .. } => name,
+            ImportParams::Ec { name, .. } => name,
+            ImportParams::Aes { name } => name,
+            ImportParams::Hmac { .. } => AlgorithmName::Hmac,
+            ImportParams::Kdf { name } => name,
+        }
+    }
+
+    pub fn import(
+        &self,
+        format: KeyFormat,
+        material: &KeyMaterial,
+        extractable: bool,
+        usages: &[KeyUsage],
+    ) -> Fallible<KeyData> {
+        let name = self.name();
+        // The usages are checked against the type of the key before parsing it.
+        let key_type = match (self, format, material) {
+            (&ImportParams::Rsa { .. }, KeyFormat::Jwk, &KeyMaterial::Jwk(ref jwk)) |
+            (&ImportParams::Ec { .. }, KeyFormat::Jwk, &KeyMaterial::Jwk(ref jwk)) => {
+                if jwk.d.is_some() {
+                    KeyType::Private
+                } else {
+                    KeyType::Public
+                }
+            },
+            (&ImportParams::Rsa { .. }, KeyFormat::Pkcs8, _) |
+            (&ImportParams::Ec { .. }, KeyFormat::Pkcs8, _) => KeyType::Private,
+            (&ImportParams::Rsa { .. }, _, _) | (&ImportParams::Ec { .. }, _, _) => KeyType::Public,
+            _ => KeyType::Secret,
+        };
+        let (public_usages, private_usages) = name.usages();
+        let allowed_usages = if key_type == KeyType::Public {
+            public_usages
+        } else {
+            private_usages
+        };
+        if usages.iter().any(|usage| !allowed_usages.contains(usage)) {
+            return Err(Error::Syntax);
+        }
+        if let KeyMaterial::Jwk(ref jwk) = *material {
+            jwk.check(name, extractable, usages)?;
+        }
+
+        let (algorithm, handle) = match *self {
+            ImportParams::Aes { name } => {
+                let secret = secret_bytes(format, material)?;
+                let length = match secret.len() {
+                    16 | 24 | 32 => secret.len() as u16 * 8,
+                    _ => return Err(Error::Data),
+                };
+                (KeyAlgorithm::Aes { name, length }, Handle::Secret(secret))
+            },
+            ImportParams::Hmac { hash, length } => {
+                let secret = secret_bytes(format, material)?;
+                let secret_length = secret.len() as u32 * 8;
+                let length = match length {
+                    _ if secret.is_empty() => return Err(Error::Data),
+                    None => secret_length,
+                    Some(length) if length <= secret_length && length > secret_length - 8 => length,
+                    Some(_) => return Err(Error::Data),
+                };
+                (KeyAlgorithm::Hmac { hash, length }, Handle::Secret(secret))
+            },
+            ImportParams::Kdf { name } => {
+                if format != KeyFormat::Raw {
+                    return Err(Error::NotSupported);
+                }
+                if extractable {
+                    return Err(Error::Syntax);
+                }
+                let secret = secret_bytes(format, material)?;
+                (KeyAlgorithm::Kdf { name }, Handle::Secret(secret))
+            },
+            ImportParams::Rsa { name, hash } => {
+                let handle = match (format, material) {
+                    (KeyFormat::Spki, &KeyMaterial::Bytes(ref der)) => {
+                        Handle::Public(PKey::public_key_from_der(der).map_err(data_error)?)
+                    },
+                    (KeyFormat::Pkcs8, &KeyMaterial::Bytes(ref der)) => {
+                        Handle::Private(PKey::private_key_from_pkcs8(der).map_err(data_error)?)
+                    },
+                    (KeyFormat::Jwk, &KeyMaterial::Jwk(ref jwk)) => jwk.rsa_handle()?,
+                    _ => return Err(Error::NotSupported),
+                };
+                let (modulus_length, public_exponent) = match handle {
+                    Handle::Private(ref private_key) => rsa_parameters(private_key),
+                    Handle::Public(ref public_key) => rsa_parameters(public_key),
+                    Handle::Secret(_) => None,
+                }
+                .ok_or(Error::Data)?;
+                let algorithm = KeyAlgorithm::Rsa {
+                    name,
+                    modulus_length,
+                    public_exponent,
+                    hash,
+                };
+                (algorithm, handle)
+            },
+            ImportParams::Ec { name, curve } => {
+                let handle = match (format, material) {
+                    (KeyFormat::Raw, &KeyMaterial::Bytes(ref point)) => {
+                        Handle::Public(ec_public_key(curve, point).map_err(data_error)?)
+                    },
+                    (KeyFormat::Spki, &KeyMaterial::Bytes(ref der)) => {
+                        Handle::Public(PKey::public_key_from_der(der).map_err(data_error)?)
+                    },
+                    (KeyFormat::Pkcs8, &KeyMaterial::Bytes(ref der)) => {
+                        Handle::Private(PKey::private_key_from_pkcs8(der).map_err(data_error)?)
+                    },
+                    (KeyFormat::Jwk, &KeyMaterial::Jwk(ref jwk)) => jwk.ec_handle(curve)?,
+                    _ => return Err(Error::NotSupported),
+                };
+                let key_curve = match handle {
+                    Handle::Private(ref private_key) => Curve::of_key(private_key),
+                    Handle::Public(ref public_key) => Curve::of_key(public_key),
+                    Handle::Secret(_) => None,
+                };
+                if key_curve != Some(curve) {
+                    return Err(Error::Data);
+                }
+                (KeyAlgorithm::Ec { name, curve }, handle)
+            },
+        };
+
+        if let KeyMaterial::Jwk(ref jwk) = *material {
+            if let (Some(expected), Some(alg)) = (jwa_name(&algorithm), jwk.alg.as_ref()) {
+                if expected != *alg {
+                    return Err(Error::Data);
+                }
+            }
+        }
+        Ok(KeyData {
+            key_type,
+            extractable,
+            algorithm,
+            usages: usages.to_vec(),
+            handle,
+        })
+    }
+}
+
+/// The bytes of a secret key imported in `format`.
+fn secret_bytes(format: KeyFormat, material: &KeyMaterial) -> Fallible<Vec<u8>> {
+    match (format, material) {
+        (KeyFormat::Raw, &KeyMaterial::Bytes(ref bytes)) => Ok(bytes.clone()),
+        (KeyFormat::Jwk, &KeyMaterial::Jwk(ref jwk)) => base64_decode(&jwk.k),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// The modulus length, in bits, and the public exponent of an RSA key.
+fn rsa_parameters<T: HasPublic>(pkey: &PKey<T>) -> Option<(u32, Vec<u8>)> {
+    let rsa = pkey.rsa().ok()?;
+    Some((rsa.n().num_bits() as u32, rsa.e().to_vec()))
+}
+
+/// Imports the uncompressed point of an elliptic curve public key.
+fn ec_public_key(curve: Curve, point: &[u8]) -> Result<PKey<Public>, ErrorStack> {
+    let group = curve.group()?;
+    let mut context = BigNumContext::new()?;
+    let point = EcPoint::from_bytes(&group, point, &mut context)?;
+    PKey::from_ec_key(EcKey::from_public_key(&group, &point)?)
+}
+
+fn export_key(format: KeyFormat, key: &KeyData) -> Fallible<KeyMaterial> {
+    let bytes = match (format, &key.handle) {
+        (KeyFormat::Jwk, _) => return Jwk::export(key).map(KeyMaterial::Jwk),
+        (KeyFormat::Raw, &Handle::Secret(ref secret)) => Ok(secret.clone()),
+        (KeyFormat::Raw, &Handle::Public(ref public_key)) => {
+            public_key.ec_key().and_then(|ec_key| {
+                let mut context = BigNumContext::new()?;
+                ec_key.public_key().to_bytes(
+                    ec_key.group(),
+                    PointConversionForm::UNCOMPRESSED,
+                    &mut context,
+                )
+            })
+        },
+        (KeyFormat::Spki, &Handle::Public(ref public_key)) => public_key.public_key_to_der(),
+        (KeyFormat::Pkcs8, &Handle::Private(ref private_key)) => private_key.private_key_to_pkcs8(),
+        _ => return Err(Error::InvalidAccess),
+    };
+    bytes.map(KeyMaterial::Bytes).map_err(operation_error)
+}
+
+/// The name of the JSON Web Algorithm matching a key, if there is one.
+fn jwa_name(algorithm: &KeyAlgorithm) -> Option<String> {
+    match *algorithm {
+        KeyAlgorithm::Aes { name, length } => {
+            let mode = match name {
+                AlgorithmName::AesCtr => "CTR",
+                AlgorithmName::AesCbc => "CBC",
+                AlgorithmName::AesGcm => "GCM",
+                _ => "KW",
+            };
+            Some(format!("A{}{}", length, mode))
+        },
+        KeyAlgorithm::Hmac { hash, .. } => Some(format!("HS{}", hash.jwa_suffix())),
+        KeyAlgorithm::Rsa { name, hash, .. } => Some(match name {
+            AlgorithmName::RsaOaep if hash == Sha::Sha1 => "RSA-OAEP".to_owned(),
+            AlgorithmName::RsaOaep => format!("RSA-OAEP-{}", hash.jwa_suffix()),
+            AlgorithmName::RsaPss => format!("PS{}", hash.jwa_suffix()),
+            _ => format!("RS{}", hash.jwa_suffix()),
+        }),
+        KeyAlgorithm::Ec {
+            name: AlgorithmName::Ecdsa,
+            curve,
+        } => Some(match curve {
+            Curve::P256 => "ES256".to_owned(),
+            Curve::P384 => "ES384".to_owned(),
+        }),
+        KeyAlgorithm::Ec { .. } | KeyAlgorithm::Kdf { .. } => None,
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> Option<String> {
+    Some(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+}
+
+fn base64_decode(value: &Option<String>) -> Fallible<Vec<u8>> {
+    let value = value.as_ref().ok_or(Error::Data)?;
+    base64::decode_config(value, base64::URL_SAFE_NO_PAD).map_err(data_error)
+}
+
+fn base64_bignum(value: &Option<String>) -> Fallible<BigNum> {
+    BigNum::from_slice(&base64_decode(value)?).map_err(data_error)
+}
+
+/// A JSON Web Key, <https://tools.ietf.org/html/rfc7517>, which unlike the
+/// `JsonWebKey` dictionary can be serialized when it is wrapped, and be sent
+/// to the threads that run the operations.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Jwk {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kty: Option<String>,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    use_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_ops: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ext: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    d: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    p: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    q: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dq: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qi: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    k: Option<String>,
+}
+
+impl Jwk {
+    fn from_dictionary(jwk: &JsonWebKey) -> Jwk {
+        let string = |value: &Option<DOMString>| value.as_ref().map(|value| value.to_string());
+        Jwk {
+            kty: string(&jwk.kty),
+            use_: string(&jwk.use_),
+            key_ops: jwk
+                .key_ops
+                .as_ref()
+                .map(|key_ops| key_ops.iter().map(|key_op| key_op.to_string()).collect()),
+            alg: string(&jwk.alg),
+            ext: jwk.ext,
+            crv: string(&jwk.crv),
+            x: string(&jwk.x),
+            y: string(&jwk.y),
+            d: string(&jwk.d),
+            n: string(&jwk.n),
+            e: string(&jwk.e),
+            p: string(&jwk.p),
+            q: string(&jwk.q),
+            dp: string(&jwk.dp),
+            dq: string(&jwk.dq),
+            qi: string(&jwk.qi),
+            k: string(&jwk.k),
+        }
+    }
+
+    fn to_dictionary(&self) -> JsonWebKey {
+        let string = |value: &Option<String>| value.as_ref().map(|value| DOMString::from(&**value));
+        JsonWebKey {
+            kty: string(&self.kty),
+            use_: string(&self.use_),
+            key_ops: self.key_ops.as_ref().map(|key_ops| {
+                key_ops
+                    .iter()
+                    .map(|key_op| DOMString::from(&**key_op))
+                    .collect()
+            }),
+            alg: string(&self.alg),
+            ext: self.ext,
+            crv: string(&self.crv),
+            x: string(&self.x),
+            y: string(&self.y),
+            d: string(&self.d),
+            n: string(&self.n),
+            e: string(&self.e),
+            p: string(&self.p),
+            q: string(&self.q),
+            dp: string(&self.dp),
+            dq: string(&self.dq),
+            qi: string(&self.qi),
+            k: string(&self.k),
+        }
+    }
+
+    /// Checks the members of a key imported for the algorithm `name` that
+    /// don't depend on its type.
+    fn check(&self, name: AlgorithmName, extractable: bool, usages: &[KeyUsage]) -> Fallible<()> {
+        let (kty, use_) = match name {
+            AlgorithmName::RsassaPkcs1V1_5 | AlgorithmName::RsaPss => ("RSA", "sig"),
+            AlgorithmName::RsaOaep => ("RSA", "enc"),
+            AlgorithmName::Ecdsa => ("EC", "sig"),
+            AlgorithmName::Ecdh => ("EC", "enc"),
+            AlgorithmName::Hmac => ("oct", "sig"),
+            _ => ("oct", "enc"),
+        };
+        if self.kty.as_ref().map(|kty| &**kty) != Some(kty) {
+            return Err(Error::Data);
+        }
+        if !usages.is_empty() && self.use_.as_ref().map_or(false, |value| value != use_) {
+            return Err(Error::Data);
+        }
+        if let Some(ref key_ops) = self.key_ops {
+            if usages
+                .iter()
+                .any(|usage| !key_ops.iter().any(|key_op| key_op == usage.as_str()))
+            {
+                return Err(Error::Data);
+            }
+        }
+        if self.ext == Some(false) && extractable {
+            return Err(Error::Data);
+        }
+        Ok(())
+    }
+
+    fn rsa_handle(&self) -> Fallible<Handle> {
+        let n = base64_bignum(&self.n)?;
+        let e = base64_bignum(&self.e)?;
+        if self.d.is_none() {
+            return Rsa::from_public_components(n, e)
+                .and_then(PKey::from_rsa)
+                .map(Handle::Public)
+                .map_err(data_error);
+        }
+        Rsa::from_private_components(
+            n,
+            e,
+            base64_bignum(&self.d)?,
+            base64_bignum(&self.p)?,
+            base64_bignum(&self.q)?,
+            base64_bignum(&self.dp)?,
+            base64_bignum(&self.dq)?,
+            base64_bignum(&self.qi)?,
+        )
+        .and_then(|rsa| {
+            rsa.check_key()?;
+            PKey::from_rsa(rsa)
+        })
+        .map(Handle::Private)
+        .map_err(data_error)
+    }
+
+    fn ec_handle(&self, curve: Curve) -> Fallible<Handle> {
+        if self.crv.as_ref().map(|crv| &**crv) != Some(curve.name()) {
+            return Err(Error::Data);
+        }
+        let group = curve.group().map_err(data_error)?;
+        let x = base64_bignum(&self.x)?;
+        let y = base64_bignum(&self.y)?;
+        let public_key =
+            EcKey::from_public_key_affine_coordinates(&group, &x, &y).map_err(data_error)?;
+        if self.d.is_none() {
+            return PKey::from_ec_key(public_key)
+                .map(Handle::Public)
+                .map_err(data_error);
+        }
+        let d = base64_bignum(&self.d)?;
+        EcKey::from_private_components(&group, &d, public_key.public_key())
+            .and_then(|private_key| {
+                private_key.check_key()?;
+                PKey::from_ec_key(private_key)
+            })
+            .map(Handle::Private)
+            .map_err(data_error)
+    }
+
+    fn export(key: &KeyData) -> Fallible<Jwk> {
+        let mut jwk = Jwk {
+            key_ops: Some(
+                key.usages
+                    .iter()
+                    .map(|usage| usage.as_str().to_owned())
+                    .collect(),
+            ),
+            ext: Some(key.extractable),
+            alg: jwa_name(&key.algorithm),
+            ..Default::default()
+        };
+        match key.handle {
+            Handle::Secret(ref secret) => {
+                jwk.kty = Some("oct".to_owned());
+                jwk.k = base64_encode(secret);
+            },
+            Handle::Public(ref public_key) => {
+                jwk.set_public_members(public_key, &key.algorithm)
+                    .map_err(operation_error)?;
+            },
+            Handle::Private(ref private_key) => {
+                jwk.set_public_members(private_key, &key.algorithm)
+                    .and_then(|_| jwk.set_private_members(private_key, &key.algorithm))
+                    .map_err(operation_error)?;
+            },
+        }
+        Ok(jwk)
+    }
+
+    fn set_public_members<T: HasPublic>(
+        &mut self,
+        pkey: &PKey<T>,
+        algorithm: &KeyAlgorithm,
+    ) -> Result<(), ErrorStack> {
+        match *algorithm {
+            KeyAlgorithm::Rsa { .. } => {
+                let rsa = pkey.rsa()?;
+                self.kty = Some("RSA".to_owned());
+                self.n = base64_encode(&rsa.n().to_vec());
+                self.e = base64_encode(&rsa.e().to_vec());
+            },
+            KeyAlgorithm::Ec { curve, .. } => {
+                let ec_key = pkey.ec_key()?;
+                let mut context = BigNumContext::new()?;
+                let mut x = BigNum::new()?;
+                let mut y = BigNum::new()?;
+                ec_key.public_key().affine_coordinates_gfp(
+                    ec_key.group(),
+                    &mut x,
+                    &mut y,
+                    &mut context,
+                )?;
+                self.kty = Some("EC".to_owned());
+                self.crv = Some(curve.name().to_owned());
+                self.x = base64_encode(&padded_bytes(&x, curve.field_len()));
+                self.y = base64_encode(&padded_bytes(&y, curve.field_len()));
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn set_private_members(
+        &mut self,
+        private_key: &PKey<Private>,
+        algorithm: &KeyAlgorithm,
+    ) -> Result<(), ErrorStack> {
+        match *algorithm {
+            KeyAlgorithm::Rsa { .. } => {
+                let rsa = private_key.rsa()?;
+                let encode = |number: Option<&BigNumRef>| {
+                    number.and_then(|number| base64_encode(&number.to_vec()))
+                };
+                self.d = base64_encode(&rsa.d().to_vec());
+                self.p = encode(rsa.p());
+                self.q = encode(rsa.q());
+                self.dp = encode(rsa.dmp1());
+                self.dq = encode(rsa.dmq1());
+                self.qi = encode(rsa.iqmp());
+            },
+            KeyAlgorithm::Ec { curve, .. } => {
+                let ec_key = private_key.ec_key()?;
+                self.d = base64_encode(&padded_bytes(ec_key.private_key(), curve.field_len()));
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
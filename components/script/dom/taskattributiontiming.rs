@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::TaskAttributionTimingBinding::{
+    self, TaskAttributionTimingMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/longtasks/#sec-TaskAttributionTiming
+#[dom_struct]
+pub struct TaskAttributionTiming {
+    entry: PerformanceEntry,
+    container_type: DOMString,
+    container_src: DOMString,
+    container_id: DOMString,
+    container_name: DOMString,
+}
+
+impl TaskAttributionTiming {
+    fn new_inherited(container: Option<&Element>) -> TaskAttributionTiming {
+        let (container_type, container_src, container_id, container_name) = match container {
+            Some(element) => (
+                DOMString::from(&**element.local_name()),
+                element.get_string_attribute(&local_name!("src")),
+                element.get_string_attribute(&local_name!("id")),
+                element.get_string_attribute(&local_name!("name")),
+            ),
+            None => (
+                DOMString::from("window"),
+                DOMString::new(),
+                DOMString::new(),
+                DOMString::new(),
+            ),
+        };
+        TaskAttributionTiming {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("unknown"),
+                DOMString::from("taskattribution"),
+                0.,
+                0.,
+            ),
+            container_type,
+            container_src,
+            container_id,
+            container_name,
+        }
+    }
+
+    /// Attributes a task to the given frame container, or to the window when
+    /// there is none.
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        container: Option<&Element>,
+    ) -> DomRoot<TaskAttributionTiming> {
+        reflect_dom_object(
+            Box::new(TaskAttributionTiming::new_inherited(container)),
+            global,
+            TaskAttributionTimingBinding::Wrap,
+        )
+    }
+}
+
+impl TaskAttributionTimingMethods for TaskAttributionTiming {
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containertype
+    fn ContainerType(&self) -> DOMString {
+        self.container_type.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containersrc
+    fn ContainerSrc(&self) -> DOMString {
+        self.container_src.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containerid
+    fn ContainerId(&self) -> DOMString {
+        self.container_id.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containername
+    fn ContainerName(&self) -> DOMString {
+        self.container_name.clone()
+    }
+}
@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::abortcontroller::AbortController;
+use crate::dom::bindings::codegen::Bindings::AbortControllerBinding::AbortControllerMethods;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::TaskPriority;
+use crate::dom::bindings::codegen::Bindings::TaskControllerBinding::{
+    self, TaskControllerInit, TaskControllerMethods,
+};
+use crate::dom::bindings::error::{ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::tasksignal::TaskSignal;
+use dom_struct::dom_struct;
+
+// https://wicg.github.io/scheduling-apis/#taskcontroller
+#[dom_struct]
+pub struct TaskController {
+    abortcontroller: AbortController,
+}
+
+impl TaskController {
+    fn new_inherited(signal: &TaskSignal) -> TaskController {
+        TaskController {
+            abortcontroller: AbortController::new_inherited(signal.upcast()),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, priority: TaskPriority) -> DomRoot<TaskController> {
+        reflect_dom_object(
+            Box::new(TaskController::new_inherited(&TaskSignal::new(
+                global, priority,
+            ))),
+            global,
+            TaskControllerBinding::Wrap,
+        )
+    }
+
+    // https://wicg.github.io/scheduling-apis/#dom-taskcontroller-taskcontroller
+    pub fn Constructor(
+        global: &GlobalScope,
+        init: &TaskControllerInit,
+    ) -> Fallible<DomRoot<TaskController>> {
+        Ok(TaskController::new(global, init.priority))
+    }
+}
+
+impl TaskControllerMethods for TaskController {
+    // https://wicg.github.io/scheduling-apis/#dom-taskcontroller-setpriority
+    fn SetPriority(&self, priority: TaskPriority) -> ErrorResult {
+        let signal = self.upcast::<AbortController>().Signal();
+        signal
+            .downcast::<TaskSignal>()
+            .expect("The signal of a TaskController is a TaskSignal")
+            .signal_priority_change(priority)
+    }
+}
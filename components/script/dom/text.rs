@@ -13,6 +13,7 @@ use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
+use crate::dom::htmlslotelement::{find_a_slot, HTMLSlotElement};
 use crate::dom::node::Node;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
@@ -100,4 +101,9 @@ impl TextMethods for Text {
         }
         DOMString::from(text)
     }
+
+    // https://dom.spec.whatwg.org/#dom-slotable-assignedslot
+    fn GetAssignedSlot(&self) -> Option<DomRoot<HTMLSlotElement>> {
+        find_a_slot(self.upcast(), true)
+    }
 }
@@ -2,14 +2,17 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{
     self, TextTrackKind, TextTrackMethods, TextTrackMode,
 };
+use crate::dom::bindings::codegen::Bindings::TextTrackCueListBinding::TextTrackCueListMethods;
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::texttrackcue::TextTrackCue;
 use crate::dom::texttrackcuelist::TextTrackCueList;
 use crate::dom::window::Window;
@@ -19,12 +22,15 @@ use std::cell::Cell;
 #[dom_struct]
 pub struct TextTrack {
     eventtarget: EventTarget,
-    kind: TextTrackKind,
-    label: String,
-    language: String,
+    kind: Cell<TextTrackKind>,
+    label: DomRefCell<String>,
+    language: DomRefCell<String>,
     id: String,
     mode: Cell<TextTrackMode>,
     cue_list: MutNullableDom<TextTrackCueList>,
+    active_cue_list: MutNullableDom<TextTrackCueList>,
+    /// The media element whose list of text tracks contains this track.
+    media_element: MutNullableDom<HTMLMediaElement>,
 }
 
 impl TextTrack {
@@ -37,12 +43,14 @@ impl TextTrack {
     ) -> TextTrack {
         TextTrack {
             eventtarget: EventTarget::new_inherited(),
-            kind: kind,
-            label: label.into(),
-            language: language.into(),
+            kind: Cell::new(kind),
+            label: DomRefCell::new(label.into()),
+            language: DomRefCell::new(language.into()),
             id: id.into(),
             mode: Cell::new(mode),
             cue_list: Default::default(),
+            active_cue_list: Default::default(),
+            media_element: Default::default(),
         }
     }
 
@@ -69,22 +77,83 @@ impl TextTrack {
     pub fn id(&self) -> &str {
         &self.id
     }
+
+    pub fn set_kind(&self, kind: TextTrackKind) {
+        self.kind.set(kind);
+    }
+
+    pub fn set_label(&self, label: DOMString) {
+        *self.label.borrow_mut() = label.into();
+    }
+
+    pub fn set_language(&self, language: DOMString) {
+        *self.language.borrow_mut() = language.into();
+    }
+
+    pub fn set_media_element(&self, media_element: Option<&HTMLMediaElement>) {
+        self.media_element.set(media_element);
+    }
+
+    /// Adds the cues parsed from the resource of the corresponding `track`
+    /// element.
+    pub fn add_cues(&self, cues: &[DomRoot<TextTrackCue>]) {
+        let cue_list = self.get_cues();
+        for cue in cues {
+            cue_list.add(cue);
+            cue.set_track(Some(self));
+        }
+        if let Some(media_element) = self.media_element.get() {
+            media_element.text_track_cues_changed();
+        }
+    }
+
+    /// Empties the list of cues after the resource of the corresponding
+    /// `track` element changed.
+    pub fn clear_cues(&self) {
+        let cues = self.get_cues();
+        for index in 0..cues.Length() as usize {
+            let cue = cues.item(index).unwrap();
+            cue.set_track(None);
+            cue.set_active(false);
+        }
+        cues.set_cues(&[]);
+        self.update_active_cues();
+        if let Some(media_element) = self.media_element.get() {
+            media_element.text_track_cues_changed();
+        }
+    }
+
+    /// Updates the active cues to the cues whose active flag is set.
+    pub fn update_active_cues(&self) {
+        let cues = self.get_cues();
+        let active_cues: Vec<_> = (0..cues.Length() as usize)
+            .filter_map(|index| cues.item(index))
+            .filter(|cue| cue.active())
+            .collect();
+        let active_cues: Vec<_> = active_cues.iter().map(|cue| &**cue).collect();
+        self.get_active_cues().set_cues(&active_cues);
+    }
+
+    fn get_active_cues(&self) -> DomRoot<TextTrackCueList> {
+        self.active_cue_list
+            .or_init(|| TextTrackCueList::new(&self.global().as_window(), &[]))
+    }
 }
 
 impl TextTrackMethods for TextTrack {
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-kind
     fn Kind(&self) -> TextTrackKind {
-        self.kind
+        self.kind.get()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-label
     fn Label(&self) -> DOMString {
-        DOMString::from(self.label.clone())
+        DOMString::from(self.label.borrow().clone())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-language
     fn Language(&self) -> DOMString {
-        DOMString::from(self.language.clone())
+        DOMString::from(self.language.borrow().clone())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-id
@@ -99,7 +168,20 @@ impl TextTrackMethods for TextTrack {
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-mode
     fn SetMode(&self, value: TextTrackMode) {
-        self.mode.set(value)
+        if self.mode.get() == value {
+            return;
+        }
+        self.mode.set(value);
+        if value == TextTrackMode::Disabled {
+            let cues = self.get_cues();
+            for index in 0..cues.Length() as usize {
+                cues.item(index).unwrap().set_active(false);
+            }
+            self.update_active_cues();
+        }
+        if let Some(media_element) = self.media_element.get() {
+            media_element.text_track_mode_changed(self);
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-cues
@@ -112,9 +194,10 @@ impl TextTrackMethods for TextTrack {
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-activecues
     fn GetActiveCues(&self) -> Option<DomRoot<TextTrackCueList>> {
-        // XXX implement active cues logic
-        //      https://github.com/servo/servo/issues/22314
-        Some(TextTrackCueList::new(&self.global().as_window(), &[]))
+        match self.Mode() {
+            TextTrackMode::Disabled => None,
+            _ => Some(self.get_active_cues()),
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-addcue
@@ -131,6 +214,10 @@ impl TextTrackMethods for TextTrack {
         }
         // Step 4
         self.get_cues().add(cue);
+        cue.set_track(Some(self));
+        if let Some(media_element) = self.media_element.get() {
+            media_element.text_track_cues_changed();
+        }
         Ok(())
     }
 
@@ -144,6 +231,14 @@ impl TextTrackMethods for TextTrack {
         }?;
         // Step 2
         cues.remove(index);
+        cue.set_track(None);
+        if cue.active() {
+            cue.set_active(false);
+            self.update_active_cues();
+        }
+        if let Some(media_element) = self.media_element.get() {
+            media_element.text_track_cues_changed();
+        }
         Ok(())
     }
 
@@ -55,10 +55,25 @@ impl TextTrackCueList {
     pub fn add(&self, cue: &TextTrackCue) {
         // Only add a cue if it does not exist in the list
         if self.find(cue).is_none() {
-            self.dom_cues.borrow_mut().push(Dom::from_ref(cue));
+            // Keep the cues in text track cue order.
+            // https://html.spec.whatwg.org/multipage/#text-track-cue-order
+            let mut cues = self.dom_cues.borrow_mut();
+            let index = cues
+                .iter()
+                .position(|other| {
+                    other.start_time() > cue.start_time() ||
+                        (other.start_time() == cue.start_time() &&
+                            other.end_time() < cue.end_time())
+                })
+                .unwrap_or(cues.len());
+            cues.insert(index, Dom::from_ref(cue));
         }
     }
 
+    pub fn set_cues(&self, cues: &[&TextTrackCue]) {
+        *self.dom_cues.borrow_mut() = cues.iter().map(|cue| Dom::from_ref(&**cue)).collect();
+    }
+
     pub fn remove(&self, idx: usize) {
         self.dom_cues.borrow_mut().remove(idx);
     }
@@ -97,13 +97,31 @@ impl TextTrackList {
         }
     }
 
-    // FIXME(#22314, dlrobertson) allow TextTracks to be
-    // removed from the TextTrackList.
-    #[allow(dead_code)]
     pub fn remove(&self, idx: usize) {
-        self.dom_tracks.borrow_mut().remove(idx);
-        self.upcast::<EventTarget>()
-            .fire_event(atom!("removetrack"));
+        let track = Trusted::new(&*self.dom_tracks.borrow_mut().remove(idx));
+
+        let this = Trusted::new(self);
+        let (source, canceller) = &self
+            .global()
+            .as_window()
+            .task_manager()
+            .media_element_task_source_with_canceller();
+
+        let _ = source.queue_with_canceller(
+            task!(track_event_queue: move || {
+                let this = this.root();
+                let event = TrackEvent::new(
+                    &this.global(),
+                    atom!("removetrack"),
+                    false,
+                    false,
+                    &Some(VideoTrackOrAudioTrackOrTextTrack::TextTrack(track.root())),
+                );
+
+                event.upcast::<Event>().fire(this.upcast::<EventTarget>());
+            }),
+            &canceller,
+        );
     }
 }
 
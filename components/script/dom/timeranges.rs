@@ -10,6 +10,7 @@ use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use std::f64;
 use std::fmt;
 
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -121,6 +122,41 @@ impl TimeRangesContainer {
 
         Ok(())
     }
+
+    /// Removes the interval [start, end) from the ranges, splitting any range
+    /// that contains it.
+    pub fn remove(&mut self, start: f64, end: f64) {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + 1);
+        for range in self.ranges.drain(..) {
+            if range.end <= start || range.start >= end {
+                ranges.push(range);
+                continue;
+            }
+            if range.start < start {
+                ranges.push(TimeRange {
+                    start: range.start,
+                    end: start,
+                });
+            }
+            if range.end > end {
+                ranges.push(TimeRange {
+                    start: end,
+                    end: range.end,
+                });
+            }
+        }
+        self.ranges = ranges;
+    }
+
+    /// Keeps only the parts of the ranges that are also in `other`.
+    pub fn intersect(&mut self, other: &TimeRangesContainer) {
+        let mut gap_start = f64::NEG_INFINITY;
+        for range in &other.ranges {
+            self.remove(gap_start, range.start);
+            gap_start = range.end;
+        }
+        self.remove(gap_start, f64::INFINITY);
+    }
 }
 
 #[dom_struct]
@@ -0,0 +1,483 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::QueuingStrategy;
+use crate::dom::bindings::codegen::Bindings::TransformStreamBinding::{
+    self, TransformStreamMethods, Transformer,
+};
+use crate::dom::bindings::conversions::ConversionResult;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageport::MessagePort;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestream::{extract_high_water_mark, ReadableStream, ReadableStreamState};
+use crate::dom::readablestreamdefaultcontroller::SourceAlgorithms;
+use crate::dom::transformstreamdefaultcontroller::TransformStreamDefaultController;
+use crate::dom::writablestream::{WritableStream, WritableStreamState};
+use crate::dom::writablestreamdefaultcontroller::{
+    SinkAlgorithms, WritableStreamDefaultController,
+};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext as RawJSContext, JSObject, MutableHandleObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::JS_SetPendingException;
+use js::rust::HandleValue;
+use std::cell::Cell;
+use std::ptr;
+use std::rc::Rc;
+
+// https://streams.spec.whatwg.org/#ts-class
+#[dom_struct]
+pub struct TransformStream {
+    reflector_: Reflector,
+    backpressure: Cell<bool>,
+    /// Resolved, and replaced, whenever the backpressure changes.
+    #[ignore_malloc_size_of = "Rc"]
+    backpressure_change_promise: DomRefCell<Option<Rc<Promise>>>,
+    controller: MutNullableDom<TransformStreamDefaultController>,
+    readable: MutNullableDom<ReadableStream>,
+    writable: MutNullableDom<WritableStream>,
+}
+
+impl TransformStream {
+    fn new_inherited() -> TransformStream {
+        TransformStream {
+            reflector_: Reflector::new(),
+            backpressure: Cell::new(false),
+            backpressure_change_promise: DomRefCell::new(None),
+            controller: Default::default(),
+            readable: Default::default(),
+            writable: Default::default(),
+        }
+    }
+
+    // https://streams.spec.whatwg.org/#ts-constructor
+    #[allow(unsafe_code)]
+    pub fn Constructor(
+        cx: JSContext,
+        global: &GlobalScope,
+        transformer: Option<*mut JSObject>,
+        writable_strategy: &QueuingStrategy,
+        readable_strategy: &QueuingStrategy,
+    ) -> Fallible<DomRoot<TransformStream>> {
+        // Step 1.
+        rooted!(in(*cx) let transformer = transformer.unwrap_or(ptr::null_mut()));
+        rooted!(in(*cx) let transformer_value = if transformer.is_null() {
+            UndefinedValue()
+        } else {
+            ObjectValue(transformer.get())
+        });
+
+        // Step 2.
+        let transformer_dict = match Transformer::new(cx, transformer_value.handle()) {
+            Ok(ConversionResult::Success(dict)) => dict,
+            Ok(ConversionResult::Failure(error)) => {
+                return Err(Error::Type(error.into_owned()));
+            },
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        // Steps 3-4.
+        if !transformer_dict.readableType.get().is_undefined() {
+            return Err(Error::Range("Invalid readable type".to_owned()));
+        }
+        if !transformer_dict.writableType.get().is_undefined() {
+            return Err(Error::Range("Invalid writable type".to_owned()));
+        }
+
+        // Steps 5-8.
+        let readable_high_water_mark = extract_high_water_mark(readable_strategy, 0.)?;
+        let writable_high_water_mark = extract_high_water_mark(writable_strategy, 1.)?;
+
+        // Steps 9-10.
+        let start_promise = Promise::new(global);
+        let stream = reflect_dom_object(
+            Box::new(TransformStream::new_inherited()),
+            global,
+            TransformStreamBinding::Wrap,
+        );
+        stream.initialize(
+            &start_promise,
+            writable_high_water_mark,
+            writable_strategy,
+            readable_high_water_mark,
+            readable_strategy,
+        );
+
+        // Step 11.
+        let controller = TransformStreamDefaultController::new(
+            global,
+            &stream,
+            transformer.handle(),
+            &transformer_dict,
+        );
+        stream.controller.set(Some(&controller));
+
+        // Steps 12-13.
+        rooted!(in(*cx) let mut start_result = UndefinedValue());
+        if let Some(ref start) = transformer_dict.start {
+            if !controller.invoke_start(&**start, start_result.handle_mut()) {
+                unsafe { JS_SetPendingException(*cx, start_result.handle()) };
+                return Err(Error::JSFailed);
+            }
+        }
+        start_promise.resolve(cx, start_result.handle());
+        Ok(stream)
+    }
+
+    /// <https://streams.spec.whatwg.org/#initialize-transform-stream>
+    fn initialize(
+        &self,
+        start_promise: &Promise,
+        writable_high_water_mark: f64,
+        writable_strategy: &QueuingStrategy,
+        readable_high_water_mark: f64,
+        readable_strategy: &QueuingStrategy,
+    ) {
+        let global = self.global();
+
+        // Steps 1-5.
+        let writable = WritableStream::new(&global);
+        WritableStreamDefaultController::set_up_with_start_promise(
+            &global,
+            &writable,
+            start_promise,
+            SinkAlgorithms::Transform {
+                stream: Dom::from_ref(self),
+            },
+            writable_strategy.size.clone(),
+            writable_high_water_mark,
+        );
+        self.writable.set(Some(&writable));
+
+        // Steps 6-9.
+        let readable = ReadableStream::new_with_algorithms(
+            &global,
+            start_promise,
+            SourceAlgorithms::Transform {
+                stream: Dom::from_ref(self),
+            },
+            readable_strategy.size.clone(),
+            readable_high_water_mark,
+        );
+        self.readable.set(Some(&readable));
+
+        // Steps 10-11.
+        self.set_backpressure(true);
+    }
+
+    /// Creates a transform stream out of the transfer-received sides of
+    /// another one, which has no controller of its own.
+    fn new_from_sides(
+        global: &GlobalScope,
+        readable: &ReadableStream,
+        writable: &WritableStream,
+    ) -> DomRoot<TransformStream> {
+        let stream = reflect_dom_object(
+            Box::new(TransformStream::new_inherited()),
+            global,
+            TransformStreamBinding::Wrap,
+        );
+        stream.readable.set(Some(readable));
+        stream.writable.set(Some(writable));
+        stream
+    }
+
+    pub fn readable(&self) -> DomRoot<ReadableStream> {
+        self.readable
+            .get()
+            .expect("A transform stream always has a readable side.")
+    }
+
+    pub fn writable(&self) -> DomRoot<WritableStream> {
+        self.writable
+            .get()
+            .expect("A transform stream always has a writable side.")
+    }
+
+    fn controller(&self) -> DomRoot<TransformStreamDefaultController> {
+        self.controller
+            .get()
+            .expect("A transform stream always has a controller once it is set up.")
+    }
+
+    pub fn backpressure(&self) -> bool {
+        self.backpressure.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-set-backpressure>
+    pub fn set_backpressure(&self, backpressure: bool) {
+        let promise = Promise::new(&self.global());
+        if let Some(previous) = self
+            .backpressure_change_promise
+            .borrow_mut()
+            .replace(promise)
+        {
+            previous.resolve_native(&());
+        }
+        self.backpressure.set(backpressure);
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-error>
+    pub fn error(&self, error: HandleValue) {
+        self.readable().controller().error(error);
+        self.error_writable_and_unblock_write(error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-error-writable-and-unblock-write>
+    pub fn error_writable_and_unblock_write(&self, error: HandleValue) {
+        self.controller().clear_algorithms();
+        let writable = self.writable();
+        if writable.state() == WritableStreamState::Writable {
+            writable.controller().error(error);
+        }
+        if self.backpressure.get() {
+            self.set_backpressure(false);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-write-algorithm>
+    pub fn sink_write_algorithm(&self, chunk: HandleValue) -> Rc<Promise> {
+        // Steps 1-3.
+        if self.backpressure.get() {
+            let global = self.global();
+            let result = Promise::new(&global);
+            let backpressure_change_promise = self
+                .backpressure_change_promise
+                .borrow()
+                .clone()
+                .expect("A transform stream with backpressure has a change promise.");
+            let handler = PromiseNativeHandler::new(
+                &global,
+                Some(TransformReaction::new(
+                    self,
+                    TransformStep::BackpressureChanged,
+                    &result,
+                    chunk,
+                    true,
+                )),
+                None,
+            );
+            backpressure_change_promise.append_native_handler(&handler);
+            return result;
+        }
+
+        // Step 4.
+        self.controller().perform_transform(chunk)
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-abort-algorithm>
+    pub fn sink_abort_algorithm(&self, reason: HandleValue) -> Rc<Promise> {
+        self.error(reason);
+        let promise = Promise::new(&self.global());
+        promise.resolve_native(&());
+        promise
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-close-algorithm>
+    pub fn sink_close_algorithm(&self) -> Rc<Promise> {
+        let global = self.global();
+        let controller = self.controller();
+        let flush_promise = controller.run_flush_algorithm();
+        controller.clear_algorithms();
+        let result = Promise::new(&global);
+        let cx = global.get_cx();
+        rooted!(in(*cx) let undefined = UndefinedValue());
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(TransformReaction::new(
+                self,
+                TransformStep::Flushed,
+                &result,
+                undefined.handle(),
+                true,
+            )),
+            Some(TransformReaction::new(
+                self,
+                TransformStep::Flushed,
+                &result,
+                undefined.handle(),
+                false,
+            )),
+        );
+        flush_promise.append_native_handler(&handler);
+        result
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-source-pull>
+    pub fn source_pull_algorithm(&self) -> Rc<Promise> {
+        self.set_backpressure(false);
+        self.backpressure_change_promise
+            .borrow()
+            .clone()
+            .expect("Setting the backpressure creates a change promise.")
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-source-cancel>
+    pub fn source_cancel_algorithm(&self, reason: HandleValue) -> Rc<Promise> {
+        self.error_writable_and_unblock_write(reason);
+        let promise = Promise::new(&self.global());
+        promise.resolve_native(&());
+        promise
+    }
+
+    fn reaction(
+        &self,
+        step: TransformStep,
+        result: &Promise,
+        chunk: HandleValue,
+        fulfilled: bool,
+        value: HandleValue,
+    ) {
+        let cx = self.global().get_cx();
+        match step {
+            // Step 3.3 of https://streams.spec.whatwg.org/#transform-stream-default-sink-write-algorithm
+            TransformStep::BackpressureChanged => {
+                let writable = self.writable();
+                if writable.state() == WritableStreamState::Erroring {
+                    rooted!(in(*cx) let error = writable.stored_error());
+                    return result.reject(cx, error.handle());
+                }
+                self.controller().perform_transform_into(chunk, result);
+            },
+            // Step 5 of https://streams.spec.whatwg.org/#transform-stream-default-sink-close-algorithm
+            TransformStep::Flushed => {
+                let readable = self.readable();
+                if !fulfilled {
+                    self.error(value);
+                }
+                if readable.state() == ReadableStreamState::Errored {
+                    rooted!(in(*cx) let error = readable.stored_error());
+                    return result.reject(cx, error.handle());
+                }
+                let controller = readable.controller();
+                if controller.can_close_or_enqueue() {
+                    controller.close();
+                }
+                result.resolve_native(&());
+            },
+        }
+    }
+}
+
+impl Transferable for TransformStream {
+    /// <https://streams.spec.whatwg.org/#ts-transfer>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        // Steps 1-4.
+        let readable = self.readable();
+        let writable = self.writable();
+        if readable.is_locked() || writable.is_locked() {
+            return Err(());
+        }
+
+        // Steps 5-6.
+        let readable_key = readable.transfer(sc_holder)?;
+        let writable_key = writable.transfer(sc_holder)?;
+
+        // The keys of both sides are stored at the index returned as the extra data.
+        let transform_streams = match sc_holder {
+            StructuredDataHolder::Write {
+                transform_streams, ..
+            } => transform_streams,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+        let transform_streams = transform_streams.get_or_insert_with(Vec::new);
+        transform_streams.push((readable_key, writable_key));
+        Ok((transform_streams.len() - 1) as u64)
+    }
+
+    /// <https://streams.spec.whatwg.org/#ts-transfer>
+    fn transfer_receive(
+        owner: &DomRoot<GlobalScope>,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        let (readable_key, writable_key) = match sc_holder {
+            StructuredDataHolder::Read {
+                transform_streams, ..
+            } => *transform_streams
+                .as_ref()
+                .and_then(|streams| streams.get(extra_data as usize))
+                .ok_or(())?,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+
+        // Steps 1-6.
+        let readable_port = MessagePort::receive_transferred(owner, sc_holder, readable_key);
+        let readable = ReadableStream::new_cross_realm(owner, &readable_port);
+        let writable_port = MessagePort::receive_transferred(owner, sc_holder, writable_key);
+        let writable = WritableStream::new_cross_realm(owner, &writable_port);
+        let stream = TransformStream::new_from_sides(owner, &readable, &writable);
+        return_object.set(stream.reflector().rootable().get());
+        Ok(())
+    }
+}
+
+impl TransformStreamMethods for TransformStream {
+    // https://streams.spec.whatwg.org/#ts-readable
+    fn Readable(&self) -> DomRoot<ReadableStream> {
+        self.readable()
+    }
+
+    // https://streams.spec.whatwg.org/#ts-writable
+    fn Writable(&self) -> DomRoot<WritableStream> {
+        self.writable()
+    }
+}
+
+/// The promises that the sink algorithms of a transform stream react to.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+enum TransformStep {
+    /// The backpressure changed while a write waited for it.
+    BackpressureChanged,
+    /// The flush algorithm settled.
+    Flushed,
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct TransformReaction {
+    stream: Dom<TransformStream>,
+    step: TransformStep,
+    /// The promise returned by the sink algorithm.
+    #[ignore_malloc_size_of = "Rc"]
+    result: Rc<Promise>,
+    /// The chunk that a write waits to transform.
+    #[ignore_malloc_size_of = "mozjs"]
+    chunk: Box<Heap<JSVal>>,
+    fulfilled: bool,
+}
+
+impl TransformReaction {
+    fn new(
+        stream: &TransformStream,
+        step: TransformStep,
+        result: &Rc<Promise>,
+        chunk: HandleValue,
+        fulfilled: bool,
+    ) -> Box<dyn Callback> {
+        Box::new(TransformReaction {
+            stream: Dom::from_ref(stream),
+            step,
+            result: result.clone(),
+            chunk: Heap::boxed(chunk.get()),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for TransformReaction {
+    fn callback(&self, cx: *mut RawJSContext, v: HandleValue) {
+        rooted!(in(cx) let chunk = self.chunk.get());
+        self.stream
+            .reaction(self.step, &self.result, chunk.handle(), self.fulfilled, v);
+    }
+}
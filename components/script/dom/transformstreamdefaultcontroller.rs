@@ -0,0 +1,296 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::CallbackContainer;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::TransformStreamBinding::{
+    Transformer, TransformerFlushCallback, TransformerTransformCallback,
+};
+use crate::dom::bindings::codegen::Bindings::TransformStreamDefaultControllerBinding::{
+    self, TransformStreamDefaultControllerMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestreamdefaultcontroller::{invoke_method, promise_from_invoke};
+use crate::dom::transformstream::TransformStream;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext as RawJSContext, JSObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    JS_ClearPendingException, JS_GetPendingException, JS_SetPendingException,
+};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use std::rc::Rc;
+
+/// The transform and flush algorithms of a controller, which come from its
+/// transformer.
+#[derive(JSTraceable, MallocSizeOf)]
+struct TransformerAlgorithms {
+    #[ignore_malloc_size_of = "Rc"]
+    transform: Option<Rc<TransformerTransformCallback>>,
+    #[ignore_malloc_size_of = "Rc"]
+    flush: Option<Rc<TransformerFlushCallback>>,
+}
+
+// https://streams.spec.whatwg.org/#ts-default-controller-class
+#[dom_struct]
+pub struct TransformStreamDefaultController {
+    reflector_: Reflector,
+    stream: Dom<TransformStream>,
+    /// The `this` of the transformer methods, or null.
+    #[ignore_malloc_size_of = "mozjs"]
+    transformer: Heap<*mut JSObject>,
+    /// None once the algorithms are cleared.
+    algorithms: DomRefCell<Option<TransformerAlgorithms>>,
+}
+
+impl TransformStreamDefaultController {
+    fn new_inherited(
+        stream: &TransformStream,
+        algorithms: TransformerAlgorithms,
+    ) -> TransformStreamDefaultController {
+        TransformStreamDefaultController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            transformer: Heap::default(),
+            algorithms: DomRefCell::new(Some(algorithms)),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-transform-stream-default-controller-from-transformer>
+    pub fn new(
+        global: &GlobalScope,
+        stream: &TransformStream,
+        transformer: HandleObject,
+        transformer_dict: &Transformer,
+    ) -> DomRoot<TransformStreamDefaultController> {
+        let algorithms = TransformerAlgorithms {
+            transform: transformer_dict.transform.clone(),
+            flush: transformer_dict.flush.clone(),
+        };
+        let controller = reflect_dom_object(
+            Box::new(TransformStreamDefaultController::new_inherited(
+                stream, algorithms,
+            )),
+            global,
+            TransformStreamDefaultControllerBinding::Wrap,
+        );
+        controller.transformer.set(transformer.get());
+        controller
+    }
+
+    fn invoke<T: CallbackContainer>(
+        &self,
+        method: &T,
+        arguments: &[JSVal],
+        rval: MutableHandleValue,
+    ) -> bool {
+        invoke_method(self.transformer.get(), method, arguments, rval)
+    }
+
+    /// Invokes the start method of the transformer. Returns false, with the
+    /// exception in `rval`, if it threw.
+    pub fn invoke_start<T: CallbackContainer>(&self, start: &T, rval: MutableHandleValue) -> bool {
+        let arguments = [ObjectValue(self.reflector().get_jsobject().get())];
+        self.invoke(start, &arguments, rval)
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-clear-algorithms>
+    pub fn clear_algorithms(&self) {
+        self.algorithms.borrow_mut().take();
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-enqueue>
+    #[allow(unsafe_code)]
+    fn enqueue(&self, cx: JSContext, chunk: HandleValue) -> ErrorResult {
+        // Steps 1-3.
+        let readable = self.stream.readable();
+        let readable_controller = readable.controller();
+        if !readable_controller.can_close_or_enqueue() {
+            return Err(Error::Type(
+                "The readable side cannot be enqueued into".to_owned(),
+            ));
+        }
+
+        // Steps 4-5.
+        if readable_controller.enqueue(cx, chunk).is_err() {
+            rooted!(in(*cx) let mut error = UndefinedValue());
+            unsafe {
+                JS_GetPendingException(*cx, error.handle_mut());
+                JS_ClearPendingException(*cx);
+            }
+            self.stream.error_writable_and_unblock_write(error.handle());
+            rooted!(in(*cx) let stored_error = readable.stored_error());
+            unsafe { JS_SetPendingException(*cx, stored_error.handle()) };
+            return Err(Error::JSFailed);
+        }
+
+        // Steps 6-7.
+        if readable_controller.has_backpressure() && !self.stream.backpressure() {
+            self.stream.set_backpressure(true);
+        }
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-perform-transform>
+    pub fn perform_transform(&self, chunk: HandleValue) -> Rc<Promise> {
+        let result = Promise::new(&self.global());
+        self.perform_transform_into(chunk, &result);
+        result
+    }
+
+    /// Performs the transform of `chunk`, and settles `result` once it
+    /// settles.
+    pub fn perform_transform_into(&self, chunk: HandleValue, result: &Rc<Promise>) {
+        let global = self.global();
+        let cx = global.get_cx();
+        let transform = match *self.algorithms.borrow() {
+            Some(ref algorithms) => algorithms.transform.clone(),
+            None => None,
+        };
+        rooted!(in(*cx) let mut transform_result = UndefinedValue());
+        let succeeded = match transform {
+            Some(transform) => {
+                let arguments = [
+                    chunk.get(),
+                    ObjectValue(self.reflector().get_jsobject().get()),
+                ];
+                self.invoke(&*transform, &arguments, transform_result.handle_mut())
+            },
+            // Without a transform method, chunks pass through unchanged.
+            None => self.enqueue_or_take_exception(cx, chunk, transform_result.handle_mut()),
+        };
+        let transform_promise = promise_from_invoke(&global, succeeded, transform_result.handle());
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(TransformAlgorithmSettled::new(self, result, true)),
+            Some(TransformAlgorithmSettled::new(self, result, false)),
+        );
+        transform_promise.append_native_handler(&handler);
+    }
+
+    /// Enqueues `chunk`, returning false with the exception in `rval` if it
+    /// threw.
+    #[allow(unsafe_code)]
+    fn enqueue_or_take_exception(
+        &self,
+        cx: JSContext,
+        chunk: HandleValue,
+        rval: MutableHandleValue,
+    ) -> bool {
+        match self.enqueue(cx, chunk) {
+            Ok(()) => true,
+            Err(Error::JSFailed) => {
+                unsafe {
+                    JS_GetPendingException(*cx, rval);
+                    JS_ClearPendingException(*cx);
+                }
+                false
+            },
+            Err(error) => {
+                unsafe { error.to_jsval(*cx, &self.global(), rval) };
+                false
+            },
+        }
+    }
+
+    /// Runs the flush algorithm of the transformer, if any.
+    pub fn run_flush_algorithm(&self) -> Rc<Promise> {
+        let global = self.global();
+        let cx = global.get_cx();
+        let flush = match *self.algorithms.borrow() {
+            Some(ref algorithms) => algorithms.flush.clone(),
+            None => None,
+        };
+        rooted!(in(*cx) let mut flush_result = UndefinedValue());
+        let succeeded = flush.map_or(true, |flush| {
+            let arguments = [ObjectValue(self.reflector().get_jsobject().get())];
+            self.invoke(&*flush, &arguments, flush_result.handle_mut())
+        });
+        promise_from_invoke(&global, succeeded, flush_result.handle())
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-terminate>
+    #[allow(unsafe_code)]
+    fn terminate(&self) {
+        let readable_controller = self.stream.readable().controller();
+        if readable_controller.can_close_or_enqueue() {
+            readable_controller.close();
+        }
+        let global = self.global();
+        let cx = global.get_cx();
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        unsafe {
+            Error::Type("The transform stream was terminated".to_owned()).to_jsval(
+                *cx,
+                &global,
+                error.handle_mut(),
+            );
+        }
+        self.stream.error_writable_and_unblock_write(error.handle());
+    }
+}
+
+impl TransformStreamDefaultControllerMethods for TransformStreamDefaultController {
+    // https://streams.spec.whatwg.org/#ts-default-controller-desired-size
+    fn GetDesiredSize(&self) -> Option<f64> {
+        self.stream.readable().controller().desired_size()
+    }
+
+    // https://streams.spec.whatwg.org/#ts-default-controller-enqueue
+    fn Enqueue(&self, cx: JSContext, chunk: HandleValue) -> ErrorResult {
+        self.enqueue(cx, chunk)
+    }
+
+    // https://streams.spec.whatwg.org/#ts-default-controller-error
+    fn Error(&self, _cx: JSContext, reason: HandleValue) {
+        self.stream.error(reason);
+    }
+
+    // https://streams.spec.whatwg.org/#ts-default-controller-terminate
+    fn Terminate(&self) {
+        self.terminate();
+    }
+}
+
+/// Settles the promise of a write to a transform stream once the transform
+/// algorithm settles, erroring the stream if it rejects.
+#[derive(JSTraceable, MallocSizeOf)]
+struct TransformAlgorithmSettled {
+    controller: Dom<TransformStreamDefaultController>,
+    #[ignore_malloc_size_of = "Rc"]
+    result: Rc<Promise>,
+    fulfilled: bool,
+}
+
+impl TransformAlgorithmSettled {
+    fn new(
+        controller: &TransformStreamDefaultController,
+        result: &Rc<Promise>,
+        fulfilled: bool,
+    ) -> Box<dyn Callback> {
+        Box::new(TransformAlgorithmSettled {
+            controller: Dom::from_ref(controller),
+            result: result.clone(),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for TransformAlgorithmSettled {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut RawJSContext, v: HandleValue) {
+        if self.fulfilled {
+            return self.result.resolve_native(&());
+        }
+        self.controller.stream.error(v);
+        let cx = unsafe { JSContext::from_ptr(cx) };
+        self.result.reject(cx, v);
+    }
+}
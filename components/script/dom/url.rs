@@ -10,6 +10,7 @@ use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::blob::Blob;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::urlhelper::UrlHelper;
 use crate::dom::urlsearchparams::URLSearchParams;
 use dom_struct::dom_struct;
@@ -117,12 +118,25 @@ impl URL {
         let origin = get_blob_origin(&global.get_url());
 
         let id = blob.get_blob_url_id();
+        global.track_blob_url(id);
 
         DOMString::from(URL::unicode_serialization_blob_url(&origin, &id))
     }
 
+    // https://w3c.github.io/media-source/#dom-url-createobjecturl
+    pub fn CreateObjectURL_(global: &GlobalScope, media_source: &MediaSource) -> DOMString {
+        let origin = get_blob_origin(&global.get_url());
+        let url = URL::unicode_serialization_blob_url(&origin, &Uuid::new_v4());
+        global.track_media_source_url(url.clone(), media_source);
+        DOMString::from(url)
+    }
+
     // https://w3c.github.io/FileAPI/#dfn-revokeObjectURL
     pub fn RevokeObjectURL(global: &GlobalScope, url: DOMString) {
+        if global.untrack_media_source_url(&url) {
+            return;
+        }
+
         // If the value provided for the url argument is not a Blob URL OR
         // if the value provided for the url argument does not have an entry in the Blob URL Store,
         // this method call does nothing. User agents may display a message on the error console.
@@ -137,6 +151,7 @@ impl URL {
                     let _ = resource_threads.send(CoreResourceMsg::ToFileManager(msg));
 
                     let _ = rx.recv().unwrap();
+                    global.untrack_blob_url(&id);
                 }
             }
         }
@@ -154,7 +169,7 @@ impl URL {
         result.push('/');
 
         // Step 5
-        result.push_str(&id.to_simple().to_string());
+        result.push_str(&id.to_hyphenated().to_string());
 
         result
     }
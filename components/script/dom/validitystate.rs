@@ -2,32 +2,21 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ValidityStateBinding;
 use crate::dom::bindings::codegen::Bindings::ValidityStateBinding::ValidityStateMethods;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::element::Element;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use std::cell::Cell;
+use style::element_state::ElementState;
 
 // https://html.spec.whatwg.org/multipage/#validity-states
-#[derive(JSTraceable, MallocSizeOf)]
-#[allow(dead_code)]
-pub enum ValidityStatus {
-    ValueMissing,
-    TypeMismatch,
-    PatternMismatch,
-    TooLong,
-    TooShort,
-    RangeUnderflow,
-    RangeOverflow,
-    StepMismatch,
-    BadInput,
-    CustomError,
-    Valid,
-}
-
 bitflags! {
+    #[derive(JSTraceable, MallocSizeOf)]
     pub struct ValidationFlags: u32 {
         const VALUE_MISSING    = 0b0000000001;
         const TYPE_MISMATCH    = 0b0000000010;
@@ -47,7 +36,10 @@ bitflags! {
 pub struct ValidityState {
     reflector_: Reflector,
     element: Dom<Element>,
-    state: ValidityStatus,
+    /// <https://html.spec.whatwg.org/multipage/#custom-validity-error-message>
+    custom_error_message: DomRefCell<DOMString>,
+    /// The validity states the element is currently suffering from.
+    invalid_flags: Cell<ValidationFlags>,
 }
 
 impl ValidityState {
@@ -55,7 +47,8 @@ impl ValidityState {
         ValidityState {
             reflector_: Reflector::new(),
             element: Dom::from_ref(element),
-            state: ValidityStatus::Valid,
+            custom_error_message: DomRefCell::new(DOMString::new()),
+            invalid_flags: Cell::new(ValidationFlags::empty()),
         }
     }
 
@@ -66,61 +59,116 @@ impl ValidityState {
             ValidityStateBinding::Wrap,
         )
     }
+
+    pub fn custom_error_message(&self) -> DOMString {
+        self.custom_error_message.borrow().clone()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity>
+    pub fn set_custom_error_message(&self, error: DOMString) {
+        *self.custom_error_message.borrow_mut() = error;
+        self.perform_validation_and_update(ValidationFlags::CUSTOM_ERROR);
+    }
+
+    pub fn invalid_flags(&self) -> ValidationFlags {
+        self.invalid_flags.get()
+    }
+
+    /// Checks the constraints in `update_flags` again, keeping the result of
+    /// the other ones, and updates the `:valid` and `:invalid` state.
+    pub fn perform_validation_and_update(&self, update_flags: ValidationFlags) {
+        let mut invalid_flags = self.invalid_flags.get();
+        invalid_flags.remove(update_flags);
+
+        if let Some(validatable) = self.element.as_maybe_validatable() {
+            invalid_flags.insert(validatable.perform_validation(update_flags));
+        }
+
+        // https://html.spec.whatwg.org/multipage/#suffering-from-a-custom-error
+        if update_flags.contains(ValidationFlags::CUSTOM_ERROR) &&
+            !self.custom_error_message.borrow().is_empty()
+        {
+            invalid_flags.insert(ValidationFlags::CUSTOM_ERROR);
+        }
+
+        self.invalid_flags.set(invalid_flags);
+        self.update_pseudo_classes();
+    }
+
+    /// Updates the `:valid` and `:invalid` state, which only apply to
+    /// candidates for constraint validation.
+    pub fn update_pseudo_classes(&self) {
+        let is_candidate = self
+            .element
+            .as_maybe_validatable()
+            .map_or(false, |validatable| validatable.is_instance_validatable());
+        let is_valid = self.invalid_flags.get().is_empty();
+        self.element
+            .set_state(ElementState::IN_VALID_STATE, is_candidate && is_valid);
+        self.element
+            .set_state(ElementState::IN_INVALID_STATE, is_candidate && !is_valid);
+    }
 }
 
 impl ValidityStateMethods for ValidityState {
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valuemissing
     fn ValueMissing(&self) -> bool {
-        false
+        self.invalid_flags()
+            .contains(ValidationFlags::VALUE_MISSING)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-typemismatch
     fn TypeMismatch(&self) -> bool {
-        false
+        self.invalid_flags()
+            .contains(ValidationFlags::TYPE_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-patternmismatch
     fn PatternMismatch(&self) -> bool {
-        false
+        self.invalid_flags()
+            .contains(ValidationFlags::PATTERN_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-toolong
     fn TooLong(&self) -> bool {
-        false
+        self.invalid_flags().contains(ValidationFlags::TOO_LONG)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-tooshort
     fn TooShort(&self) -> bool {
-        false
+        self.invalid_flags().contains(ValidationFlags::TOO_SHORT)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeunderflow
     fn RangeUnderflow(&self) -> bool {
-        false
+        self.invalid_flags()
+            .contains(ValidationFlags::RANGE_UNDERFLOW)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeoverflow
     fn RangeOverflow(&self) -> bool {
-        false
+        self.invalid_flags()
+            .contains(ValidationFlags::RANGE_OVERFLOW)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-stepmismatch
     fn StepMismatch(&self) -> bool {
-        false
+        self.invalid_flags()
+            .contains(ValidationFlags::STEP_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-badinput
     fn BadInput(&self) -> bool {
-        false
+        self.invalid_flags().contains(ValidationFlags::BAD_INPUT)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-customerror
     fn CustomError(&self) -> bool {
-        false
+        self.invalid_flags().contains(ValidationFlags::CUSTOM_ERROR)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valid
     fn Valid(&self) -> bool {
-        false
+        self.invalid_flags().is_empty()
     }
 }
@@ -0,0 +1,432 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::VTTCueBinding::{
+    self, AlignSetting, AutoKeyword, DirectionSetting, LineAlignSetting, PositionAlignSetting,
+    VTTCueMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrAutoKeyword;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::Node;
+use crate::dom::processinginstruction::ProcessingInstruction;
+use crate::dom::text::Text;
+use crate::dom::texttrackcue::TextTrackCue;
+use crate::dom::window::Window;
+use crate::webvtt::{self, CueElementKind, CueNode};
+use dom_struct::dom_struct;
+use html5ever::QualName;
+use std::cell::Cell;
+
+/// The height of a line of cue text, in ems.
+const LINE_HEIGHT: f64 = 1.2;
+
+// https://w3c.github.io/webvtt/#the-vttcue-interface
+#[dom_struct]
+pub struct VTTCue {
+    texttrackcue: TextTrackCue,
+    vertical: Cell<DirectionSetting>,
+    snap_to_lines: Cell<bool>,
+    /// `None` stands for `auto`.
+    line: Cell<Option<f64>>,
+    line_align: Cell<LineAlignSetting>,
+    /// `None` stands for `auto`.
+    position: Cell<Option<f64>>,
+    position_align: Cell<PositionAlignSetting>,
+    size: Cell<f64>,
+    align: Cell<AlignSetting>,
+    text: DomRefCell<DOMString>,
+}
+
+impl VTTCue {
+    fn new_inherited(start_time: f64, end_time: f64, text: DOMString) -> VTTCue {
+        VTTCue {
+            texttrackcue: TextTrackCue::new_inherited(DOMString::new(), start_time, end_time),
+            vertical: Cell::new(DirectionSetting::_empty),
+            snap_to_lines: Cell::new(true),
+            line: Cell::new(None),
+            line_align: Cell::new(LineAlignSetting::Start),
+            position: Cell::new(None),
+            position_align: Cell::new(PositionAlignSetting::Auto),
+            size: Cell::new(100.),
+            align: Cell::new(AlignSetting::Center),
+            text: DomRefCell::new(text),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        start_time: f64,
+        end_time: f64,
+        text: DOMString,
+    ) -> DomRoot<VTTCue> {
+        reflect_dom_object(
+            Box::new(VTTCue::new_inherited(start_time, end_time, text)),
+            window,
+            VTTCueBinding::Wrap,
+        )
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-vttcue
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        start_time: Finite<f64>,
+        end_time: Finite<f64>,
+        text: DOMString,
+    ) -> DomRoot<VTTCue> {
+        VTTCue::new(window, *start_time, *end_time, text)
+    }
+
+    /// Creates a cue parsed from a WebVTT file.
+    pub fn new_from_parsed(window: &Window, cue: webvtt::Cue) -> DomRoot<VTTCue> {
+        let this = VTTCue::new(
+            window,
+            cue.start_time,
+            cue.end_time,
+            DOMString::from(cue.text),
+        );
+        this.upcast::<TextTrackCue>().SetId(DOMString::from(cue.id));
+
+        let settings = cue.settings;
+        this.vertical.set(match settings.writing_direction {
+            webvtt::WritingDirection::Horizontal => DirectionSetting::_empty,
+            webvtt::WritingDirection::VerticalGrowingLeft => DirectionSetting::Rl,
+            webvtt::WritingDirection::VerticalGrowingRight => DirectionSetting::Lr,
+        });
+        this.snap_to_lines.set(settings.snap_to_lines);
+        this.line.set(settings.line);
+        this.line_align.set(match settings.line_alignment {
+            webvtt::LineAlignment::Start => LineAlignSetting::Start,
+            webvtt::LineAlignment::Center => LineAlignSetting::Center,
+            webvtt::LineAlignment::End => LineAlignSetting::End,
+        });
+        this.position.set(settings.position);
+        this.position_align.set(match settings.position_alignment {
+            webvtt::PositionAlignment::LineLeft => PositionAlignSetting::Line_left,
+            webvtt::PositionAlignment::Center => PositionAlignSetting::Center,
+            webvtt::PositionAlignment::LineRight => PositionAlignSetting::Line_right,
+            webvtt::PositionAlignment::Auto => PositionAlignSetting::Auto,
+        });
+        this.size.set(settings.size);
+        this.align.set(match settings.text_alignment {
+            webvtt::TextAlignment::Start => AlignSetting::Start,
+            webvtt::TextAlignment::Center => AlignSetting::Center,
+            webvtt::TextAlignment::End => AlignSetting::End,
+            webvtt::TextAlignment::Left => AlignSetting::Left,
+            webvtt::TextAlignment::Right => AlignSetting::Right,
+        });
+        this
+    }
+
+    /// <https://w3c.github.io/webvtt/#webvtt-cue-text-dom-construction-rules>
+    pub fn cue_as_html(&self, document: &Document) -> DomRoot<DocumentFragment> {
+        let fragment = DocumentFragment::new(document);
+        let nodes = webvtt::parse_cue_text(&self.text.borrow());
+        append_cue_nodes(fragment.upcast(), &nodes, document);
+        fragment
+    }
+
+    /// The CSS declarations positioning the box of this cue within the
+    /// video's rendering area.
+    ///
+    /// <https://w3c.github.io/webvtt/#apply-webvtt-cue-settings>
+    pub fn cue_box_style(&self) -> String {
+        let align = self.align.get();
+
+        // Steps 1-3.
+        let (writing_mode, line_start, line_end, inline_start, inline_size, line_shift) =
+            match self.vertical.get() {
+                DirectionSetting::_empty => (
+                    "horizontal-tb",
+                    "top",
+                    "bottom",
+                    "left",
+                    "width",
+                    "translateY(-",
+                ),
+                DirectionSetting::Rl => (
+                    "vertical-rl",
+                    "right",
+                    "left",
+                    "top",
+                    "height",
+                    "translateX(",
+                ),
+                DirectionSetting::Lr => (
+                    "vertical-lr",
+                    "left",
+                    "right",
+                    "top",
+                    "height",
+                    "translateX(-",
+                ),
+            };
+
+        // https://w3c.github.io/webvtt/#cue-computed-position
+        let position = match (self.position.get(), align) {
+            (Some(position), _) => position,
+            (None, AlignSetting::Left) => 0.,
+            (None, AlignSetting::Right) => 100.,
+            (None, _) => 50.,
+        };
+
+        // https://w3c.github.io/webvtt/#cue-computed-position-alignment
+        let position_align = match (self.position_align.get(), align) {
+            (PositionAlignSetting::Auto, AlignSetting::Left) |
+            (PositionAlignSetting::Auto, AlignSetting::Start) => PositionAlignSetting::Line_left,
+            (PositionAlignSetting::Auto, AlignSetting::Right) |
+            (PositionAlignSetting::Auto, AlignSetting::End) => PositionAlignSetting::Line_right,
+            (PositionAlignSetting::Auto, AlignSetting::Center) => PositionAlignSetting::Center,
+            (position_align, _) => position_align,
+        };
+
+        // Steps 4-5.
+        let maximum_size = match position_align {
+            PositionAlignSetting::Line_left => 100. - position,
+            PositionAlignSetting::Line_right => position,
+            _ => position.min(100. - position) * 2.,
+        };
+        let size = self.size.get().min(maximum_size);
+
+        // Step 6.
+        let offset = match position_align {
+            PositionAlignSetting::Line_left => position,
+            PositionAlignSetting::Line_right => position - size,
+            _ => position - size / 2.,
+        };
+        let text_align = match align {
+            AlignSetting::Start => "start",
+            AlignSetting::Center => "center",
+            AlignSetting::End => "end",
+            AlignSetting::Left => "left",
+            AlignSetting::Right => "right",
+        };
+        let mut style = format!(
+            "position: absolute; writing-mode: {}; {}: {}%; {}: {}%; text-align: {};",
+            writing_mode, inline_start, offset, inline_size, size, text_align
+        );
+
+        // Step 7.
+        let line = match (self.line.get(), self.snap_to_lines.get()) {
+            // The last line of the rendering area.
+            (None, true) => format!(" {}: 0;", line_end),
+            (Some(line), true) if line >= 0. => {
+                format!(" {}: {}em;", line_start, line * LINE_HEIGHT)
+            },
+            (Some(line), true) => format!(" {}: {}em;", line_end, (-line - 1.) * LINE_HEIGHT),
+            (line, false) => {
+                let shift = match self.line_align.get() {
+                    LineAlignSetting::Start => 0,
+                    LineAlignSetting::Center => 50,
+                    LineAlignSetting::End => 100,
+                };
+                format!(
+                    " {}: {}%; transform: {}{}%);",
+                    line_start,
+                    line.unwrap_or(100.),
+                    line_shift,
+                    shift
+                )
+            },
+        };
+        style.push_str(&line);
+        style
+    }
+}
+
+/// Appends the DOM nodes built from parsed cue text to `parent`.
+fn append_cue_nodes(parent: &Node, nodes: &[CueNode], document: &Document) {
+    for node in nodes {
+        let child = match *node {
+            CueNode::Text(ref text) => {
+                DomRoot::upcast::<Node>(Text::new(DOMString::from(text.as_str()), document))
+            },
+            CueNode::Timestamp(time) => {
+                let data = format!(
+                    "{:02}:{:02}:{:02}.{:03}",
+                    (time / 3600.) as u64,
+                    (time / 60.) as u64 % 60,
+                    time as u64 % 60,
+                    ((time * 1000.).round() as u64) % 1000
+                );
+                DomRoot::upcast::<Node>(ProcessingInstruction::new(
+                    DOMString::from("timestamp"),
+                    DOMString::from(data),
+                    document,
+                ))
+            },
+            CueNode::Element {
+                kind,
+                ref classes,
+                ref annotation,
+                ref children,
+            } => {
+                let name = match kind {
+                    CueElementKind::Class | CueElementKind::Voice | CueElementKind::Language => {
+                        local_name!("span")
+                    },
+                    CueElementKind::Italic => local_name!("i"),
+                    CueElementKind::Bold => local_name!("b"),
+                    CueElementKind::Underline => local_name!("u"),
+                    CueElementKind::Ruby => local_name!("ruby"),
+                    CueElementKind::RubyText => local_name!("rt"),
+                };
+                let element = Element::create(
+                    QualName::new(None, ns!(html), name),
+                    None,
+                    document,
+                    ElementCreator::ScriptCreated,
+                    CustomElementCreationMode::Synchronous,
+                );
+                if !classes.is_empty() {
+                    element.set_tokenlist_attribute(
+                        &local_name!("class"),
+                        DOMString::from(classes.join(" ")),
+                    );
+                }
+                if let Some(ref annotation) = *annotation {
+                    let attribute = match kind {
+                        CueElementKind::Voice => local_name!("title"),
+                        _ => local_name!("lang"),
+                    };
+                    element.set_string_attribute(&attribute, DOMString::from(annotation.as_str()));
+                }
+                append_cue_nodes(element.upcast(), children, document);
+                DomRoot::upcast::<Node>(element)
+            },
+        };
+        parent
+            .AppendChild(&child)
+            .expect("Appending to a new node should not fail");
+    }
+}
+
+impl VTTCueMethods for VTTCue {
+    // https://w3c.github.io/webvtt/#dom-vttcue-vertical
+    fn Vertical(&self) -> DirectionSetting {
+        self.vertical.get()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-vertical
+    fn SetVertical(&self, value: DirectionSetting) {
+        self.vertical.set(value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-snaptolines
+    fn SnapToLines(&self) -> bool {
+        self.snap_to_lines.get()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-snaptolines
+    fn SetSnapToLines(&self, value: bool) {
+        self.snap_to_lines.set(value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-line
+    fn Line(&self) -> DoubleOrAutoKeyword {
+        match self.line.get() {
+            Some(line) => DoubleOrAutoKeyword::Double(Finite::wrap(line)),
+            None => DoubleOrAutoKeyword::AutoKeyword(AutoKeyword::Auto),
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-line
+    fn SetLine(&self, value: DoubleOrAutoKeyword) {
+        self.line.set(match value {
+            DoubleOrAutoKeyword::Double(line) => Some(*line),
+            DoubleOrAutoKeyword::AutoKeyword(_) => None,
+        });
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-linealign
+    fn LineAlign(&self) -> LineAlignSetting {
+        self.line_align.get()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-linealign
+    fn SetLineAlign(&self, value: LineAlignSetting) {
+        self.line_align.set(value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-position
+    fn Position(&self) -> DoubleOrAutoKeyword {
+        match self.position.get() {
+            Some(position) => DoubleOrAutoKeyword::Double(Finite::wrap(position)),
+            None => DoubleOrAutoKeyword::AutoKeyword(AutoKeyword::Auto),
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-position
+    fn SetPosition(&self, value: DoubleOrAutoKeyword) -> ErrorResult {
+        let position = match value {
+            DoubleOrAutoKeyword::Double(position) if *position < 0. || *position > 100. => {
+                return Err(Error::IndexSize);
+            },
+            DoubleOrAutoKeyword::Double(position) => Some(*position),
+            DoubleOrAutoKeyword::AutoKeyword(_) => None,
+        };
+        self.position.set(position);
+        Ok(())
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-positionalign
+    fn PositionAlign(&self) -> PositionAlignSetting {
+        self.position_align.get()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-positionalign
+    fn SetPositionAlign(&self, value: PositionAlignSetting) {
+        self.position_align.set(value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-size
+    fn Size(&self) -> Finite<f64> {
+        Finite::wrap(self.size.get())
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-size
+    fn SetSize(&self, value: Finite<f64>) -> ErrorResult {
+        if *value < 0. || *value > 100. {
+            return Err(Error::IndexSize);
+        }
+        self.size.set(*value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-align
+    fn Align(&self) -> AlignSetting {
+        self.align.get()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-align
+    fn SetAlign(&self, value: AlignSetting) {
+        self.align.set(value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-text
+    fn Text(&self) -> DOMString {
+        self.text.borrow().clone()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-text
+    fn SetText(&self, value: DOMString) {
+        *self.text.borrow_mut() = value;
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-getcueashtml
+    fn GetCueAsHTML(&self) -> DomRoot<DocumentFragment> {
+        self.cue_as_html(&self.global().as_window().Document())
+    }
+}
@@ -18,6 +18,7 @@ use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::webgl_validations::types::TexImageTarget;
 use crate::dom::webglactiveinfo::WebGLActiveInfo;
 use crate::dom::webglbuffer::WebGLBuffer;
 use crate::dom::webglframebuffer::WebGLFramebuffer;
@@ -39,19 +40,23 @@ use crate::js::conversions::ToJSValConvertible;
 use crate::script_runtime::JSContext;
 use canvas_traits::webgl::WebGLError::*;
 use canvas_traits::webgl::{
-    webgl_channel, GLContextAttributes, WebGLCommand, WebGLResult, WebGLVersion,
+    webgl_channel, GLContextAttributes, TexDataType, TexFormat, WebGLCommand, WebGLResult,
+    WebGLVersion,
 };
 use dom_struct::dom_struct;
 use euclid::default::{Point2D, Rect, Size2D};
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcSharedMemory};
 use js::jsapi::{JSObject, Type};
-use js::jsval::{BooleanValue, DoubleValue, Int32Value, JSVal, NullValue, UInt32Value};
+use js::jsval::{
+    BooleanValue, DoubleValue, Int32Value, JSVal, NullValue, ObjectValue, UInt32Value,
+    UndefinedValue,
+};
 use js::rust::CustomAutoRooterGuard;
-use js::typedarray::ArrayBufferView;
+use js::typedarray::{ArrayBufferView, CreateWith, Uint32Array};
 use script_layout_interface::HTMLCanvasDataSource;
 use std::cell::Cell;
 use std::cmp;
-use std::ptr::NonNull;
+use std::ptr::{self, NonNull};
 
 #[dom_struct]
 pub struct WebGL2RenderingContext {
@@ -66,6 +71,8 @@ pub struct WebGL2RenderingContext {
     bound_pixel_unpack_buffer: MutNullableDom<WebGLBuffer>,
     bound_transform_feedback_buffer: MutNullableDom<WebGLBuffer>,
     bound_uniform_buffer: MutNullableDom<WebGLBuffer>,
+    indexed_uniform_buffer_bindings: Box<[IndexedBinding]>,
+    indexed_transform_feedback_buffer_bindings: Box<[IndexedBinding]>,
     current_transform_feedback: MutNullableDom<WebGLTransformFeedback>,
     texture_pack_row_length: Cell<usize>,
     texture_pack_skip_pixels: Cell<usize>,
@@ -82,6 +89,45 @@ fn typedarray_elem_size(typeid: Type) -> usize {
     }
 }
 
+/// The format and type of the data that the sized internal formats accepted
+/// by texture storage hold.
+fn sized_internal_format_info(internal_format: u32) -> Option<(TexFormat, TexDataType)> {
+    Some(match internal_format {
+        constants::RGBA8 => (TexFormat::RGBA, TexDataType::UnsignedByte),
+        constants::RGB8 => (TexFormat::RGB, TexDataType::UnsignedByte),
+        constants::RGBA4 => (TexFormat::RGBA, TexDataType::UnsignedShort4444),
+        constants::RGB5_A1 => (TexFormat::RGBA, TexDataType::UnsignedShort5551),
+        constants::RGB565 => (TexFormat::RGB, TexDataType::UnsignedShort565),
+        constants::RGBA32F => (TexFormat::RGBA, TexDataType::Float),
+        constants::RGB32F => (TexFormat::RGB, TexDataType::Float),
+        _ => return None,
+    })
+}
+
+/// The number of levels of a complete mipmap chain whose base level is
+/// `size` texels large.
+fn mipmap_level_count(size: u32) -> u32 {
+    32 - size.leading_zeros()
+}
+
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+struct IndexedBinding {
+    buffer: MutNullableDom<WebGLBuffer>,
+    start: Cell<i64>,
+    size: Cell<i64>,
+}
+
+impl IndexedBinding {
+    fn new() -> IndexedBinding {
+        IndexedBinding {
+            buffer: MutNullableDom::new(None),
+            start: Cell::new(0),
+            size: Cell::new(0),
+        }
+    }
+}
+
 struct ReadPixelsAllowedFormats<'a> {
     array_types: &'a [Type],
     channels: usize,
@@ -106,6 +152,15 @@ impl WebGL2RenderingContext {
             .map(|_| Default::default())
             .collect::<Vec<_>>()
             .into();
+        let indexed_uniform_buffer_bindings = (0..base.limits().max_uniform_buffer_bindings)
+            .map(|_| IndexedBinding::new())
+            .collect::<Vec<_>>()
+            .into();
+        let indexed_transform_feedback_buffer_bindings =
+            (0..base.limits().max_transform_feedback_separate_attribs)
+                .map(|_| IndexedBinding::new())
+                .collect::<Vec<_>>()
+                .into();
 
         Some(WebGL2RenderingContext {
             reflector_: Reflector::new(),
@@ -119,6 +174,8 @@ impl WebGL2RenderingContext {
             bound_pixel_unpack_buffer: MutNullableDom::new(None),
             bound_transform_feedback_buffer: MutNullableDom::new(None),
             bound_uniform_buffer: MutNullableDom::new(None),
+            indexed_uniform_buffer_bindings,
+            indexed_transform_feedback_buffer_bindings,
             current_transform_feedback: MutNullableDom::new(None),
             texture_pack_row_length: Cell::new(0),
             texture_pack_skip_pixels: Cell::new(0),
@@ -160,6 +217,61 @@ impl WebGL2RenderingContext {
         }
     }
 
+    /// The texture bound to the active unit for `target`, which must be one
+    /// of the three-dimensional texture targets.
+    fn bound_3d_texture(
+        &self,
+        target: u32,
+    ) -> WebGLResult<(TexImageTarget, DomRoot<WebGLTexture>)> {
+        let image_target = match TexImageTarget::from_gl_constant(target) {
+            Some(image_target) if image_target.is_3d() => image_target,
+            _ => return Err(InvalidEnum),
+        };
+        let texture = self
+            .base
+            .textures()
+            .active_texture_for_image_target(image_target)
+            .ok_or(InvalidOperation)?;
+        Ok((image_target, texture))
+    }
+
+    /// Validates the level and the size of an image of a three-dimensional
+    /// texture against the limits of the context.
+    fn validate_3d_texture_size(
+        &self,
+        target: TexImageTarget,
+        level: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) -> WebGLResult<()> {
+        if level < 0 || width < 0 || height < 0 || depth < 0 {
+            return Err(InvalidValue);
+        }
+        let limits = self.base.limits();
+        let (max_size, max_depth) = match target {
+            TexImageTarget::Texture2DArray => (
+                limits.max_tex_size.checked_shr(level as u32).unwrap_or(0),
+                limits.max_array_texture_layers,
+            ),
+            _ => {
+                let max_size = limits
+                    .max_3d_texture_size
+                    .checked_shr(level as u32)
+                    .unwrap_or(0);
+                (max_size, max_size)
+            },
+        };
+        if max_size == 0 ||
+            width as u32 > max_size ||
+            height as u32 > max_size ||
+            depth as u32 > max_depth
+        {
+            return Err(InvalidValue);
+        }
+        Ok(())
+    }
+
     fn unbind_from(&self, slot: &MutNullableDom<WebGLBuffer>, buffer: &WebGLBuffer) {
         if slot.get().map_or(false, |b| buffer == &*b) {
             buffer.decrement_attached_counter();
@@ -167,6 +279,75 @@ impl WebGL2RenderingContext {
         }
     }
 
+    fn unbind_from_indexed(&self, binding: &IndexedBinding, buffer: &WebGLBuffer) {
+        if binding.buffer.get().map_or(false, |b| buffer == &*b) {
+            buffer.decrement_attached_counter();
+            binding.buffer.set(None);
+            binding.start.set(0);
+            binding.size.set(0);
+        }
+    }
+
+    fn bind_buffer_indexed(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<&WebGLBuffer>,
+        start: i64,
+        size: i64,
+    ) -> WebGLResult<()> {
+        let (generic_slot, bindings) = match target {
+            constants::TRANSFORM_FEEDBACK_BUFFER => (
+                &self.bound_transform_feedback_buffer,
+                &self.indexed_transform_feedback_buffer_bindings,
+            ),
+            constants::UNIFORM_BUFFER => (
+                &self.bound_uniform_buffer,
+                &self.indexed_uniform_buffer_bindings,
+            ),
+            _ => return Err(InvalidEnum),
+        };
+        let binding = bindings.get(index as usize).ok_or(InvalidValue)?;
+
+        if let Some(buffer) = buffer {
+            self.base.validate_ownership(buffer)?;
+            if buffer.is_marked_for_deletion() {
+                return Err(InvalidOperation);
+            }
+            buffer.set_target_maybe(target)?;
+        }
+
+        if target == constants::TRANSFORM_FEEDBACK_BUFFER &&
+            self.current_transform_feedback
+                .get()
+                .map_or(false, |tf| tf.is_active())
+        {
+            return Err(InvalidOperation);
+        }
+
+        self.base.send_command(match buffer {
+            Some(buffer) if size != 0 => {
+                WebGLCommand::BindBufferRange(target, index, Some(buffer.id()), start, size)
+            },
+            _ => WebGLCommand::BindBufferBase(target, index, buffer.map(|b| b.id())),
+        });
+
+        for slot in &[generic_slot, &binding.buffer] {
+            if let Some(old) = slot.get() {
+                old.decrement_attached_counter();
+            }
+            slot.set(buffer);
+        }
+        binding.start.set(start);
+        binding.size.set(size);
+        if let Some(buffer) = buffer {
+            buffer.increment_attached_counter();
+            buffer.increment_attached_counter();
+        }
+
+        Ok(())
+    }
+
     fn calc_read_pixel_formats(
         &self,
         pixel_type: u32,
@@ -443,12 +624,68 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             constants::TRANSFORM_FEEDBACK_BINDING => unsafe {
                 optional_root_object_to_js_or_null!(*cx, self.current_transform_feedback.get())
             },
+            constants::MAX_UNIFORM_BUFFER_BINDINGS => {
+                Int32Value(self.base.limits().max_uniform_buffer_bindings as i32)
+            },
+            constants::UNIFORM_BUFFER_OFFSET_ALIGNMENT => {
+                Int32Value(self.base.limits().uniform_buffer_offset_alignment as i32)
+            },
+            constants::MAX_3D_TEXTURE_SIZE => {
+                Int32Value(self.base.limits().max_3d_texture_size as i32)
+            },
+            constants::MAX_ARRAY_TEXTURE_LAYERS => {
+                Int32Value(self.base.limits().max_array_texture_layers as i32)
+            },
+            constants::MAX_VERTEX_OUTPUT_COMPONENTS => {
+                Int32Value(self.base.limits().max_vertex_output_vectors as i32 * 4)
+            },
+            constants::MAX_FRAGMENT_INPUT_COMPONENTS => {
+                Int32Value(self.base.limits().max_fragment_input_vectors as i32 * 4)
+            },
+            constants::MIN_PROGRAM_TEXEL_OFFSET => {
+                Int32Value(self.base.limits().min_program_texel_offset)
+            },
+            constants::MAX_PROGRAM_TEXEL_OFFSET => {
+                Int32Value(self.base.limits().max_program_texel_offset as i32)
+            },
+            constants::TEXTURE_BINDING_3D => unsafe {
+                let texture = self
+                    .base
+                    .textures()
+                    .active_texture_slot(constants::TEXTURE_3D)
+                    .unwrap()
+                    .get();
+                optional_root_object_to_js_or_null!(*cx, texture)
+            },
+            constants::TEXTURE_BINDING_2D_ARRAY => unsafe {
+                let texture = self
+                    .base
+                    .textures()
+                    .active_texture_slot(constants::TEXTURE_2D_ARRAY)
+                    .unwrap()
+                    .get();
+                optional_root_object_to_js_or_null!(*cx, texture)
+            },
             _ => self.base.GetParameter(cx, parameter),
         }
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     fn GetTexParameter(&self, cx: JSContext, target: u32, pname: u32) -> JSVal {
+        if pname == constants::TEXTURE_IMMUTABLE_FORMAT {
+            let texture_slot = handle_potential_webgl_error!(
+                self.base,
+                self.base.textures().active_texture_slot(target),
+                return NullValue()
+            );
+            return match texture_slot.get() {
+                Some(texture) => BooleanValue(texture.is_immutable()),
+                None => {
+                    self.base.webgl_error(InvalidOperation);
+                    NullValue()
+                },
+            };
+        }
         self.base.GetTexParameter(cx, target, pname)
     }
 
@@ -979,6 +1216,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         self.unbind_from(&self.bound_pixel_unpack_buffer, &buffer);
         self.unbind_from(&self.bound_transform_feedback_buffer, &buffer);
         self.unbind_from(&self.bound_uniform_buffer, &buffer);
+        for binding in self.indexed_uniform_buffer_bindings.iter() {
+            self.unbind_from_indexed(binding, &buffer);
+        }
+        for binding in self.indexed_transform_feedback_buffer_bindings.iter() {
+            self.unbind_from_indexed(binding, &buffer);
+        }
         buffer.mark_for_deletion(false);
     }
 
@@ -1073,6 +1316,9 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             constants::TRANSFORM_FEEDBACK_BUFFER_MODE => {
                 Int32Value(program.transform_feedback_buffer_mode())
             },
+            constants::ACTIVE_UNIFORM_BLOCKS => {
+                Int32Value(program.active_uniform_blocks().len() as i32)
+            },
             _ => self.base.GetProgramParameter(cx, program, param_id),
         }
     }
@@ -1671,6 +1917,374 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             .TexSubImage2D_(target, level, xoffset, yoffset, format, data_type, source)
     }
 
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexImage3D(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        border: i32,
+        format: u32,
+        data_type: u32,
+        src_data: CustomAutoRooterGuard<Option<ArrayBufferView>>,
+    ) {
+        let (image_target, texture) =
+            handle_potential_webgl_error!(self.base, self.bound_3d_texture(target), return);
+        if texture.is_immutable() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_3d_texture_size(image_target, level, width, height, depth),
+            return
+        );
+        if border != 0 {
+            return self.base.webgl_error(InvalidValue);
+        }
+
+        let tex_format = match TexFormat::from_gl_constant(format) {
+            Some(tex_format) if !tex_format.is_compressed() => tex_format,
+            _ => return self.base.webgl_error(InvalidEnum),
+        };
+        let tex_data_type = match TexDataType::from_gl_constant(data_type) {
+            Some(tex_data_type) => tex_data_type,
+            None => return self.base.webgl_error(InvalidEnum),
+        };
+
+        // Unsized internal formats must be equal to the format, and sized ones
+        // must hold data of the given format and type.
+        let internal_format = internal_format as u32;
+        let is_compatible = match sized_internal_format_info(internal_format) {
+            Some(info) => info == (tex_format, tex_data_type),
+            None => internal_format == format,
+        };
+        if !is_compatible {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        if src_data.is_some() && self.base.texture_unpacking_flips_or_premultiplies() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        // The images of each layer are laid out one after the other, so their
+        // rows are validated as those of one image `depth` times higher.
+        let unpacking_alignment = self.base.texture_unpacking_alignment();
+        let expected_byte_length = match self.base.validate_tex_image_2d_data(
+            width as u32,
+            height as u32 * depth as u32,
+            tex_format,
+            tex_data_type,
+            unpacking_alignment,
+            &*src_data,
+        ) {
+            Ok(byte_length) => byte_length as usize,
+            Err(()) => return,
+        };
+
+        let data = match *src_data {
+            None => IpcSharedMemory::from_bytes(&vec![0u8; expected_byte_length]),
+            Some(ref data) => IpcSharedMemory::from_bytes(unsafe { data.as_slice() }),
+        };
+        if data.len() < expected_byte_length {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        handle_potential_webgl_error!(
+            self.base,
+            texture.initialize(
+                image_target,
+                width as u32,
+                height as u32,
+                depth as u32,
+                tex_format,
+                level as u32,
+                Some(tex_data_type),
+            ),
+            return
+        );
+
+        self.base.send_command(WebGLCommand::TexImage3D {
+            target,
+            level: level as u32,
+            internal_format,
+            width: width as u32,
+            height: height as u32,
+            depth: depth as u32,
+            format,
+            data_type,
+            unpacking_alignment,
+            data: data.into(),
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexSubImage3D(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        data_type: u32,
+        src_data: CustomAutoRooterGuard<Option<ArrayBufferView>>,
+        src_offset: u32,
+    ) {
+        let (image_target, texture) =
+            handle_potential_webgl_error!(self.base, self.bound_3d_texture(target), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_3d_texture_size(image_target, level, width, height, depth),
+            return
+        );
+        let image_info = match texture.image_info_for_target(&image_target, level as u32) {
+            Some(info) => info,
+            None => return self.base.webgl_error(InvalidOperation),
+        };
+
+        if xoffset < 0 ||
+            yoffset < 0 ||
+            zoffset < 0 ||
+            xoffset as u32 + width as u32 > image_info.width() ||
+            yoffset as u32 + height as u32 > image_info.height() ||
+            zoffset as u32 + depth as u32 > image_info.depth()
+        {
+            return self.base.webgl_error(InvalidValue);
+        }
+
+        let tex_format = match TexFormat::from_gl_constant(format) {
+            Some(tex_format) => tex_format,
+            None => return self.base.webgl_error(InvalidEnum),
+        };
+        let tex_data_type = match TexDataType::from_gl_constant(data_type) {
+            Some(tex_data_type) => tex_data_type,
+            None => return self.base.webgl_error(InvalidEnum),
+        };
+        if tex_format != image_info.internal_format() ||
+            Some(tex_data_type) != image_info.data_type()
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        let src_bytes = match *src_data {
+            Some(ref data) => unsafe { data.as_slice() },
+            None => return self.base.webgl_error(InvalidValue),
+        };
+        if self.base.texture_unpacking_flips_or_premultiplies() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        let unpacking_alignment = self.base.texture_unpacking_alignment();
+        let expected_byte_length = match self.base.validate_tex_image_2d_data(
+            width as u32,
+            height as u32 * depth as u32,
+            tex_format,
+            tex_data_type,
+            unpacking_alignment,
+            &*src_data,
+        ) {
+            Ok(byte_length) => byte_length as usize,
+            Err(()) => return,
+        };
+
+        let src_byte_offset = src_offset as usize * tex_data_type.element_size() as usize;
+        if src_byte_offset > src_bytes.len() {
+            return self.base.webgl_error(InvalidValue);
+        }
+        if src_bytes.len() - src_byte_offset < expected_byte_length {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        self.base.send_command(WebGLCommand::TexSubImage3D {
+            target,
+            level: level as u32,
+            xoffset,
+            yoffset,
+            zoffset,
+            width: width as u32,
+            height: height as u32,
+            depth: depth as u32,
+            format,
+            data_type,
+            unpacking_alignment,
+            data: IpcSharedMemory::from_bytes(&src_bytes[src_byte_offset..]).into(),
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn CopyTexSubImage3D(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        handle_potential_webgl_error!(self.base, self.base.validate_framebuffer(), return);
+        let (image_target, texture) =
+            handle_potential_webgl_error!(self.base, self.bound_3d_texture(target), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_3d_texture_size(image_target, level, width, height, 0),
+            return
+        );
+        let image_info = match texture.image_info_for_target(&image_target, level as u32) {
+            Some(info) => info,
+            None => return self.base.webgl_error(InvalidOperation),
+        };
+
+        if xoffset < 0 ||
+            yoffset < 0 ||
+            zoffset < 0 ||
+            xoffset as u32 + width as u32 > image_info.width() ||
+            yoffset as u32 + height as u32 > image_info.height() ||
+            zoffset as u32 >= image_info.depth()
+        {
+            return self.base.webgl_error(InvalidValue);
+        }
+
+        self.base.send_command(WebGLCommand::CopyTexSubImage3D(
+            target, level, xoffset, yoffset, zoffset, x, y, width, height,
+        ));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexStorage2D(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        let image_target = match target {
+            constants::TEXTURE_2D => TexImageTarget::Texture2D,
+            constants::TEXTURE_CUBE_MAP => TexImageTarget::CubeMapPositiveX,
+            _ => return self.base.webgl_error(InvalidEnum),
+        };
+        let texture = match self
+            .base
+            .textures()
+            .active_texture_for_image_target(image_target)
+        {
+            Some(texture) => texture,
+            None => return self.base.webgl_error(InvalidOperation),
+        };
+        let (format, data_type) = match sized_internal_format_info(internal_format) {
+            Some(info) => info,
+            None => return self.base.webgl_error(InvalidEnum),
+        };
+
+        if levels < 1 || width < 1 || height < 1 {
+            return self.base.webgl_error(InvalidValue);
+        }
+        let limits = self.base.limits();
+        let max_size = if image_target.is_cubic() {
+            if width != height {
+                return self.base.webgl_error(InvalidValue);
+            }
+            limits.max_cube_map_tex_size
+        } else {
+            limits.max_tex_size
+        };
+        if width as u32 > max_size || height as u32 > max_size {
+            return self.base.webgl_error(InvalidValue);
+        }
+        if levels as u32 > mipmap_level_count(cmp::max(width, height) as u32) {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        handle_potential_webgl_error!(
+            self.base,
+            texture.initialize_storage(
+                target,
+                levels as u32,
+                width as u32,
+                height as u32,
+                1,
+                format,
+                data_type,
+            ),
+            return
+        );
+
+        self.base.send_command(WebGLCommand::TexStorage2D(
+            target,
+            levels as u32,
+            internal_format,
+            width as u32,
+            height as u32,
+        ));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexStorage3D(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) {
+        let (image_target, texture) =
+            handle_potential_webgl_error!(self.base, self.bound_3d_texture(target), return);
+        let (format, data_type) = match sized_internal_format_info(internal_format) {
+            Some(info) => info,
+            None => return self.base.webgl_error(InvalidEnum),
+        };
+
+        if levels < 1 || width < 1 || height < 1 || depth < 1 {
+            return self.base.webgl_error(InvalidValue);
+        }
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_3d_texture_size(image_target, 0, width, height, depth),
+            return
+        );
+        // The layers of an array texture do not shrink along its levels.
+        let largest_size = match image_target {
+            TexImageTarget::Texture2DArray => cmp::max(width, height),
+            _ => cmp::max(cmp::max(width, height), depth),
+        };
+        if levels as u32 > mipmap_level_count(largest_size as u32) {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        handle_potential_webgl_error!(
+            self.base,
+            texture.initialize_storage(
+                target,
+                levels as u32,
+                width as u32,
+                height as u32,
+                depth as u32,
+                format,
+                data_type,
+            ),
+            return
+        );
+
+        self.base.send_command(WebGLCommand::TexStorage3D(
+            target,
+            levels as u32,
+            internal_format,
+            width as u32,
+            height as u32,
+            depth as u32,
+        ));
+    }
+
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     fn TexParameterf(&self, target: u32, name: u32, value: f32) {
         self.base.TexParameterf(target, name, value)
@@ -2304,6 +2918,221 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             DOMString::from(name),
         ))
     }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferBase(&self, target: u32, index: u32, buffer: Option<&WebGLBuffer>) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.bind_buffer_indexed(target, index, buffer, 0, 0)
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferRange(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<&WebGLBuffer>,
+        offset: i64,
+        size: i64,
+    ) {
+        let alignment = match target {
+            constants::TRANSFORM_FEEDBACK_BUFFER => 4,
+            constants::UNIFORM_BUFFER => self.base.limits().uniform_buffer_offset_alignment as i64,
+            _ => return self.base.webgl_error(InvalidEnum),
+        };
+        if offset < 0 || offset % alignment != 0 {
+            return self.base.webgl_error(InvalidValue);
+        }
+        if buffer.is_some() && size <= 0 {
+            return self.base.webgl_error(InvalidValue);
+        }
+
+        handle_potential_webgl_error!(
+            self.base,
+            self.bind_buffer_indexed(target, index, buffer, offset, size)
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    #[allow(unsafe_code)]
+    fn GetIndexedParameter(&self, cx: JSContext, target: u32, index: u32) -> JSVal {
+        let bindings = match target {
+            constants::TRANSFORM_FEEDBACK_BUFFER_BINDING |
+            constants::TRANSFORM_FEEDBACK_BUFFER_SIZE |
+            constants::TRANSFORM_FEEDBACK_BUFFER_START => {
+                &self.indexed_transform_feedback_buffer_bindings
+            },
+            constants::UNIFORM_BUFFER_BINDING |
+            constants::UNIFORM_BUFFER_SIZE |
+            constants::UNIFORM_BUFFER_START => &self.indexed_uniform_buffer_bindings,
+            _ => {
+                self.base.webgl_error(InvalidEnum);
+                return NullValue();
+            },
+        };
+
+        let binding = match bindings.get(index as usize) {
+            Some(binding) => binding,
+            None => {
+                self.base.webgl_error(InvalidValue);
+                return NullValue();
+            },
+        };
+
+        match target {
+            constants::TRANSFORM_FEEDBACK_BUFFER_BINDING | constants::UNIFORM_BUFFER_BINDING => unsafe {
+                optional_root_object_to_js_or_null!(*cx, binding.buffer.get())
+            },
+            constants::TRANSFORM_FEEDBACK_BUFFER_START | constants::UNIFORM_BUFFER_START => {
+                DoubleValue(binding.start.get() as f64)
+            },
+            constants::TRANSFORM_FEEDBACK_BUFFER_SIZE | constants::UNIFORM_BUFFER_SIZE => {
+                DoubleValue(binding.size.get() as f64)
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetUniformIndices(&self, program: &WebGLProgram, names: Vec<DOMString>) -> Option<Vec<u32>> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return None
+        );
+        let indices = handle_potential_webgl_error!(
+            self.base,
+            program.get_uniform_indices(names),
+            return None
+        );
+        Some(indices)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    #[allow(unsafe_code)]
+    fn GetActiveUniforms(
+        &self,
+        cx: JSContext,
+        program: &WebGLProgram,
+        indices: Vec<u32>,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return NullValue()
+        );
+        let values = handle_potential_webgl_error!(
+            self.base,
+            program.get_active_uniforms(indices, pname),
+            return NullValue()
+        );
+
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        match pname {
+            constants::UNIFORM_SIZE |
+            constants::UNIFORM_TYPE |
+            constants::UNIFORM_ARRAY_STRIDE |
+            constants::UNIFORM_MATRIX_STRIDE => unsafe {
+                values.to_jsval(*cx, rval.handle_mut());
+            },
+            constants::UNIFORM_OFFSET | constants::UNIFORM_BLOCK_INDEX => unsafe {
+                let values = values.iter().map(|&v| v as u32).collect::<Vec<_>>();
+                values.to_jsval(*cx, rval.handle_mut());
+            },
+            constants::UNIFORM_IS_ROW_MAJOR => unsafe {
+                let values = values.iter().map(|&v| v != 0).collect::<Vec<_>>();
+                values.to_jsval(*cx, rval.handle_mut());
+            },
+            _ => unreachable!(),
+        }
+        rval.get()
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetUniformBlockIndex(&self, program: &WebGLProgram, block_name: DOMString) -> u32 {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return constants::INVALID_INDEX
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            program.get_uniform_block_index(block_name),
+            constants::INVALID_INDEX
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    #[allow(unsafe_code)]
+    fn GetActiveUniformBlockParameter(
+        &self,
+        cx: JSContext,
+        program: &WebGLProgram,
+        block_index: u32,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return NullValue()
+        );
+        let values = handle_potential_webgl_error!(
+            self.base,
+            program.get_active_uniform_block_parameter(block_index, pname),
+            return NullValue()
+        );
+        match pname {
+            constants::UNIFORM_BLOCK_BINDING |
+            constants::UNIFORM_BLOCK_DATA_SIZE |
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORMS => {
+                assert!(values.len() == 1);
+                UInt32Value(values[0] as u32)
+            },
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES => unsafe {
+                let values = values.iter().map(|&v| v as u32).collect::<Vec<_>>();
+                rooted!(in(*cx) let mut result = ptr::null_mut::<JSObject>());
+                let _ = Uint32Array::create(*cx, CreateWith::Slice(&values), result.handle_mut())
+                    .unwrap();
+                ObjectValue(result.get())
+            },
+            constants::UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER |
+            constants::UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER => {
+                assert!(values.len() == 1);
+                BooleanValue(values[0] != 0)
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetActiveUniformBlockName(
+        &self,
+        program: &WebGLProgram,
+        block_index: u32,
+    ) -> Option<DOMString> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return None
+        );
+        let name = handle_potential_webgl_error!(
+            self.base,
+            program.get_active_uniform_block_name(block_index),
+            return None
+        );
+        Some(DOMString::from(name))
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn UniformBlockBinding(&self, program: &WebGLProgram, block_index: u32, block_binding: u32) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(program), return);
+        handle_potential_webgl_error!(
+            self.base,
+            program.bind_uniform_block(block_index, block_binding)
+        );
+    }
 }
 
 impl LayoutCanvasWebGLRenderingContextHelpers for LayoutDom<WebGL2RenderingContext> {
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants;
 use canvas_traits::gl_enums;
 
@@ -14,14 +15,27 @@ gl_enums! {
         CubeMapNegativeY = WebGLRenderingContextConstants::TEXTURE_CUBE_MAP_NEGATIVE_Y,
         CubeMapPositiveZ = WebGLRenderingContextConstants::TEXTURE_CUBE_MAP_POSITIVE_Z,
         CubeMapNegativeZ = WebGLRenderingContextConstants::TEXTURE_CUBE_MAP_NEGATIVE_Z,
+        Texture3D = WebGL2RenderingContextConstants::TEXTURE_3D,
+        Texture2DArray = WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY,
     }
 }
 
 impl TexImageTarget {
     pub fn is_cubic(&self) -> bool {
         match *self {
-            TexImageTarget::Texture2D => false,
+            TexImageTarget::Texture2D |
+            TexImageTarget::Texture3D |
+            TexImageTarget::Texture2DArray => false,
             _ => true,
         }
     }
+
+    /// Whether this target takes three-dimensional images, through
+    /// `texImage3D` and `texStorage3D`.
+    pub fn is_3d(&self) -> bool {
+        match *self {
+            TexImageTarget::Texture3D | TexImageTarget::Texture2DArray => true,
+            _ => false,
+        }
+    }
 }
@@ -5,6 +5,7 @@
 use crate::dom::bindings::codegen::Bindings::ANGLEInstancedArraysBinding::ANGLEInstancedArraysConstants;
 use crate::dom::bindings::codegen::Bindings::EXTBlendMinmaxBinding::EXTBlendMinmaxConstants;
 use crate::dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectConstants;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants2;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::TexImageSource;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
@@ -216,7 +217,7 @@ impl WebGLRenderingContext {
                 capabilities: Default::default(),
                 default_vao: Default::default(),
                 current_vao: Default::default(),
-                textures: Textures::new(max_combined_texture_image_units),
+                textures: Textures::new(max_combined_texture_image_units, webgl_version),
                 api_type: ctx_data.api_type,
             }
         })
@@ -255,6 +256,18 @@ impl WebGLRenderingContext {
         &self.limits
     }
 
+    pub fn texture_unpacking_alignment(&self) -> u32 {
+        self.texture_unpacking_alignment.get()
+    }
+
+    /// Whether `UNPACK_FLIP_Y_WEBGL` or `UNPACK_PREMULTIPLY_ALPHA_WEBGL` is
+    /// set, which uploads of three-dimensional images do not support.
+    pub fn texture_unpacking_flips_or_premultiplies(&self) -> bool {
+        self.texture_unpacking_settings
+            .get()
+            .intersects(TextureUnpacking::FLIP_Y_AXIS | TextureUnpacking::PREMULTIPLY_ALPHA)
+    }
+
     pub fn current_vao(&self) -> DomRoot<WebGLVertexArrayObjectOES> {
         self.current_vao.or_init(|| {
             DomRoot::from_ref(
@@ -621,7 +634,7 @@ impl WebGLRenderingContext {
     }
 
     // TODO(emilio): Move this logic to a validator.
-    fn validate_tex_image_2d_data(
+    pub fn validate_tex_image_2d_data(
         &self,
         width: u32,
         height: u32,
@@ -684,6 +697,11 @@ impl WebGLRenderingContext {
         unpacking_alignment: u32,
         pixels: TexPixels,
     ) {
+        // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+        if texture.is_immutable() {
+            return self.webgl_error(InvalidOperation);
+        }
+
         // TexImage2D depth is always equal to 1.
         handle_potential_webgl_error!(
             self,
@@ -4284,12 +4302,15 @@ capabilities! {
 pub struct Textures {
     active_unit: Cell<u32>,
     units: Box<[TextureUnit]>,
+    /// The 3D and 2D array texture targets only exist in WebGL 2.
+    webgl_version: WebGLVersion,
 }
 
 impl Textures {
-    fn new(max_combined_textures: u32) -> Self {
+    fn new(max_combined_textures: u32, webgl_version: WebGLVersion) -> Self {
         Self {
             active_unit: Default::default(),
+            webgl_version,
             units: (0..max_combined_textures)
                 .map(|_| Default::default())
                 .collect::<Vec<_>>()
@@ -4310,11 +4331,14 @@ impl Textures {
         Ok(())
     }
 
-    fn active_texture_slot(&self, target: u32) -> WebGLResult<&MutNullableDom<WebGLTexture>> {
+    pub fn active_texture_slot(&self, target: u32) -> WebGLResult<&MutNullableDom<WebGLTexture>> {
         let active_unit = self.active_unit();
+        let is_webgl2 = self.webgl_version == WebGLVersion::WebGL2;
         match target {
             constants::TEXTURE_2D => Ok(&active_unit.tex_2d),
             constants::TEXTURE_CUBE_MAP => Ok(&active_unit.tex_cube_map),
+            constants2::TEXTURE_3D if is_webgl2 => Ok(&active_unit.tex_3d),
+            constants2::TEXTURE_2D_ARRAY if is_webgl2 => Ok(&active_unit.tex_2d_array),
             _ => Err(InvalidEnum),
         }
     }
@@ -4332,6 +4356,8 @@ impl Textures {
             TexImageTarget::CubeMapNegativeY |
             TexImageTarget::CubeMapPositiveZ |
             TexImageTarget::CubeMapNegativeZ => active_unit.tex_cube_map.get(),
+            TexImageTarget::Texture3D => active_unit.tex_3d.get(),
+            TexImageTarget::Texture2DArray => active_unit.tex_2d_array.get(),
         }
     }
 
@@ -4352,6 +4378,8 @@ impl Textures {
 struct TextureUnit {
     tex_2d: MutNullableDom<WebGLTexture>,
     tex_cube_map: MutNullableDom<WebGLTexture>,
+    tex_3d: MutNullableDom<WebGLTexture>,
+    tex_2d_array: MutNullableDom<WebGLTexture>,
 }
 
 impl TextureUnit {
@@ -4359,6 +4387,8 @@ impl TextureUnit {
         let fields = [
             (&self.tex_2d, constants::TEXTURE_2D),
             (&self.tex_cube_map, constants::TEXTURE_CUBE_MAP),
+            (&self.tex_3d, constants2::TEXTURE_3D),
+            (&self.tex_2d_array, constants2::TEXTURE_2D_ARRAY),
         ];
         for &(slot, target) in &fields {
             if slot.get().map_or(false, |t| texture == &*t) {
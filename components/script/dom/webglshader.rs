@@ -116,6 +116,10 @@ impl WebGLShader {
             MaxCombinedTextureImageUnits: limits.max_combined_texture_image_units as c_int,
             MaxTextureImageUnits: limits.max_texture_image_units as c_int,
             MaxFragmentUniformVectors: limits.max_fragment_uniform_vectors as c_int,
+            MaxVertexOutputVectors: limits.max_vertex_output_vectors as c_int,
+            MaxFragmentInputVectors: limits.max_fragment_input_vectors as c_int,
+            MinProgramTexelOffset: limits.min_program_texel_offset as c_int,
+            MaxProgramTexelOffset: limits.max_program_texel_offset as c_int,
             OES_standard_derivatives: ext.is_enabled::<OESStandardDerivatives>() as c_int,
             EXT_shader_texture_lod: ext.is_enabled::<EXTShaderTextureLod>() as c_int,
             FragmentPrecisionHigh: 1,
@@ -6,6 +6,7 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EXTTextureFilterAnisotropicBinding::EXTTextureFilterAnisotropicConstants;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants2;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::codegen::Bindings::WebGLTextureBinding;
 use crate::dom::bindings::inheritance::Castable;
@@ -51,6 +52,9 @@ pub struct WebGLTexture {
     attached_to_dom: Cell<bool>,
     /// Framebuffer that this texture is attached to.
     attached_framebuffer: MutNullableDom<WebGLFramebuffer>,
+    /// True once the storage of this texture was specified with `texStorage2D`
+    /// or `texStorage3D`.
+    immutable: Cell<bool>,
 }
 
 impl WebGLTexture {
@@ -67,6 +71,7 @@ impl WebGLTexture {
             image_info_array: DomRefCell::new([None; MAX_LEVEL_COUNT * MAX_FACE_COUNT]),
             attached_to_dom: Cell::new(false),
             attached_framebuffer: Default::default(),
+            immutable: Cell::new(false),
         }
     }
 
@@ -106,7 +111,7 @@ impl WebGLTexture {
         } else {
             // This is the first time binding
             let face_count = match target {
-                constants::TEXTURE_2D => 1,
+                constants::TEXTURE_2D | constants2::TEXTURE_3D | constants2::TEXTURE_2D_ARRAY => 1,
                 constants::TEXTURE_CUBE_MAP => 6,
                 _ => return Err(WebGLError::InvalidEnum),
             };
@@ -149,6 +154,52 @@ impl WebGLTexture {
         Ok(())
     }
 
+    /// <https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6>
+    pub fn is_immutable(&self) -> bool {
+        self.immutable.get()
+    }
+
+    /// Specifies all `levels` of an immutable texture at once, as
+    /// `texStorage2D` and `texStorage3D` do.
+    pub fn initialize_storage(
+        &self,
+        target: u32,
+        levels: u32,
+        width: u32,
+        height: u32,
+        depth: u32,
+        internal_format: TexFormat,
+        data_type: TexDataType,
+    ) -> WebGLResult<()> {
+        if self.immutable.get() {
+            return Err(WebGLError::InvalidOperation);
+        }
+        let (mut width, mut height, mut depth) = (width, height, depth);
+        for level in 0..levels {
+            let image_info = ImageInfo {
+                width,
+                height,
+                depth,
+                internal_format,
+                data_type: Some(data_type),
+            };
+            self.set_image_infos_at_level(level, image_info);
+            width = cmp::max(width / 2, 1);
+            height = cmp::max(height / 2, 1);
+            // The layers of an array texture are not halved.
+            if target != constants2::TEXTURE_2D_ARRAY {
+                depth = cmp::max(depth / 2, 1);
+            }
+        }
+        self.immutable.set(true);
+
+        if let Some(fb) = self.attached_framebuffer.get() {
+            fb.update_status();
+        }
+
+        Ok(())
+    }
+
     pub fn generate_mipmap(&self) -> WebGLResult<()> {
         let target = match self.target.get() {
             Some(target) => target,
@@ -368,6 +419,8 @@ impl WebGLTexture {
     fn face_index_for_target(&self, target: &TexImageTarget) -> u8 {
         match *target {
             TexImageTarget::Texture2D => 0,
+            TexImageTarget::Texture3D => 0,
+            TexImageTarget::Texture2DArray => 0,
             TexImageTarget::CubeMapPositiveX => 0,
             TexImageTarget::CubeMapNegativeX => 1,
             TexImageTarget::CubeMapPositiveY => 2,
@@ -442,6 +495,10 @@ impl ImageInfo {
         self.height
     }
 
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
     pub fn internal_format(&self) -> TexFormat {
         self.internal_format
     }
@@ -20,8 +20,6 @@ use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
-use crate::script_runtime::CommonScriptMsg;
-use crate::script_runtime::ScriptThreadEventCategory::WebSocketEvent;
 use crate::task::{TaskCanceller, TaskOnce};
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::TaskSource;
@@ -280,19 +278,14 @@ impl WebSocket {
         if !self.clearing_buffer.get() && self.ready_state.get() == WebSocketRequestState::Open {
             self.clearing_buffer.set(true);
 
-            let task = Box::new(BufferedAmountTask { address: address });
-
-            let pipeline_id = self.global().pipeline_id();
-            self.global()
-                .script_chan()
-                // TODO: Use a dedicated `websocket-task-source` task source instead.
-                .send(CommonScriptMsg::Task(
-                    WebSocketEvent,
-                    task,
-                    Some(pipeline_id),
-                    WebsocketTaskSource::NAME,
-                ))
-                .unwrap();
+            let task = BufferedAmountTask { address: address };
+
+            // In a worker, the task is routed through the script channel
+            // of the worker rather than the one of a window.
+            let global = self.global();
+            let _ = global
+                .websocket_task_source()
+                .queue_with_canceller(task, &global.task_canceller(WebsocketTaskSource::NAME));
         }
 
         Ok(true)
@@ -431,9 +424,6 @@ impl WebSocketMethods for WebSocket {
                 self.ready_state.set(WebSocketRequestState::Closing);
 
                 let address = Trusted::new(self);
-                // TODO: use a dedicated task source,
-                // https://html.spec.whatwg.org/multipage/#websocket-task-source
-                // When making the switch, also update the task_canceller call.
                 let task_source = self.global().websocket_task_source();
                 fail_the_websocket_connection(
                     address,
@@ -8,13 +8,17 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
 use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
+    ImageBitmapOptions, ImageBitmapSource,
+};
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryListBinding::MediaQueryListMethods;
-use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
+use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationType;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     self, FrameRequestCallback, WindowMethods, WindowPostMessageOptions,
 };
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
+use crate::dom::bindings::codegen::Bindings::WindowOrWorkerGlobalScopeBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
@@ -28,6 +32,8 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
 use crate::dom::bindings::weakref::DOMTracker;
 use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::cachestorage::CacheStorage;
+use crate::dom::cookiestore::CookieStore;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
@@ -38,15 +44,20 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
+use crate::dom::imagebitmap::create_image_bitmap;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
 use crate::dom::messageevent::MessageEvent;
+use crate::dom::navigation::{NavigateEventOutcome, Navigation};
 use crate::dom::navigator::Navigator;
 use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::screen::Screen;
+use crate::dom::selection::Selection;
+use crate::dom::speechsynthesis::SpeechSynthesis;
 use crate::dom::storage::Storage;
 use crate::dom::testrunner::TestRunner;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
@@ -110,6 +121,7 @@ use script_traits::{
 };
 use script_traits::{TimerSchedulerMsg, WebrenderIpcSender, WindowSizeData, WindowSizeType};
 use selectors::attr::CaseSensitivity;
+use servo_config::pref;
 use servo_geometry::{f32_rect_to_au_rect, MaxRect};
 use servo_url::{Host, ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
@@ -125,18 +137,20 @@ use std::mem;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use style::computed_values::scroll_behavior;
 use style::dom::OpaqueNode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
 use style::media_queries;
 use style::parser::ParserContext as CssParserContext;
-use style::properties::{ComputedValues, PropertyId};
+use style::properties::style_structs::Font;
+use style::properties::{ComputedValues, PropertyId, ShorthandId};
 use style::selector_parser::PseudoElement;
 use style::str::HTML_SPACE_CHARACTERS;
 use style::stylesheets::CssRuleType;
 use style_traits::{CSSPixel, DevicePixel, ParsingMode};
 use url::Position;
 use webgpu::WebGPU;
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPixel};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPixel, LayoutPoint};
 use webrender_api::{DocumentId, ExternalScrollId};
 use webvr_traits::WebVRMsg;
 
@@ -170,6 +184,7 @@ pub enum ReflowReason {
     IFrameLoadEvent,
     MissingExplicitReflow,
     ElementStateChanged,
+    AnimationTick,
 }
 
 #[dom_struct]
@@ -187,8 +202,12 @@ pub struct Window {
     document: MutNullableDom<Document>,
     location: MutNullableDom<Location>,
     history: MutNullableDom<History>,
+    navigation: MutNullableDom<Navigation>,
     custom_element_registry: MutNullableDom<CustomElementRegistry>,
     performance: MutNullableDom<Performance>,
+    caches: MutNullableDom<CacheStorage>,
+    cookie_store: MutNullableDom<CookieStore>,
+    speech_synthesis: MutNullableDom<SpeechSynthesis>,
     navigation_start: Cell<u64>,
     navigation_start_precise: Cell<u64>,
     screen: MutNullableDom<Screen>,
@@ -258,9 +277,17 @@ pub struct Window {
     /// A list of scroll offsets for each scrollable element.
     scroll_offsets: DomRefCell<HashMap<OpaqueNode, Vector2D<f32, LayoutPixel>>>,
 
+    /// The smooth scrolls in progress in the compositor, with the element
+    /// being scrolled, or `None` for the viewport.
+    smooth_scrolls: DomRefCell<HashMap<ExternalScrollId, Option<Dom<Element>>>>,
+
     /// All the MediaQueryLists we need to update
     media_query_lists: DOMTracker<MediaQueryList>,
 
+    /// The MediaQueryLists with change listeners, which are kept alive so
+    /// that they can report their changes.
+    listened_media_query_lists: DomRefCell<Vec<Dom<MediaQueryList>>>,
+
     test_runner: MutNullableDom<TestRunner>,
 
     /// A handle for communicating messages to the WebGL thread, if available.
@@ -278,9 +305,6 @@ pub struct Window {
     #[ignore_malloc_size_of = "defined in webxr"]
     webxr_registry: webxr_api::Registry,
 
-    /// A map for storing the previous permission state read results.
-    permission_state_invocation_results: DomRefCell<HashMap<String, PermissionState>>,
-
     /// All of the elements that have an outstanding image request that was
     /// initiated by layout during a reflow. They are stored in the script thread
     /// to ensure that the element can be marked dirty when the image data becomes
@@ -483,12 +507,6 @@ impl Window {
         Worklet::new(self, WorkletGlobalScopeType::Paint)
     }
 
-    pub fn permission_state_invocation_results(
-        &self,
-    ) -> &DomRefCell<HashMap<String, PermissionState>> {
-        &self.permission_state_invocation_results
-    }
-
     pub fn pending_image_notification(&self, response: PendingImageResponse) {
         //XXXjdm could be more efficient to send the responses to the layout thread,
         //       rather than making the layout thread talk to the image cache to
@@ -646,7 +664,7 @@ impl WindowMethods for Window {
         url: USVString,
         target: DOMString,
         features: DOMString,
-    ) -> Option<DomRoot<WindowProxy>> {
+    ) -> Fallible<Option<DomRoot<WindowProxy>>> {
         self.window_proxy().open(url, target, features)
     }
 
@@ -763,6 +781,11 @@ impl WindowMethods for Window {
         self.history.or_init(|| History::new(self))
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigation
+    fn Navigation(&self) -> DomRoot<Navigation> {
+        self.navigation.or_init(|| Navigation::new(self))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-customelements
     fn CustomElements(&self) -> DomRoot<CustomElementRegistry> {
         self.custom_element_registry
@@ -791,6 +814,11 @@ impl WindowMethods for Window {
         self.upcast::<GlobalScope>().crypto()
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.upcast::<GlobalScope>().scheduler()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-frameelement
     fn GetFrameElement(&self) -> Option<DomRoot<Element>> {
         // Steps 1-3.
@@ -923,6 +951,23 @@ impl WindowMethods for Window {
         })
     }
 
+    // https://w3c.github.io/ServiceWorker/#self-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.caches
+            .or_init(|| CacheStorage::new(self.upcast::<GlobalScope>()))
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-window-cookiestore
+    fn CookieStore(&self) -> DomRoot<CookieStore> {
+        self.cookie_store
+            .or_init(|| CookieStore::new(self.upcast::<GlobalScope>()))
+    }
+
+    // https://wicg.github.io/speech-api/#dom-window-speechsynthesis
+    fn SpeechSynthesis(&self) -> DomRoot<SpeechSynthesis> {
+        self.speech_synthesis.or_init(|| SpeechSynthesis::new(self))
+    }
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!();
 
@@ -1163,7 +1208,9 @@ impl WindowMethods for Window {
     // https://drafts.csswg.org/cssom-view/#dom-window-resizeto
     fn ResizeTo(&self, width: i32, height: i32) {
         // Step 1
-        //TODO determine if this operation is allowed
+        if !self.can_move_or_resize() {
+            return;
+        }
         let dpr = self.device_pixel_ratio();
         let size = Size2D::new(width, height).to_f32() * dpr;
         self.send_to_embedder(EmbedderMsg::ResizeTo(size.to_i32()));
@@ -1182,7 +1229,9 @@ impl WindowMethods for Window {
     // https://drafts.csswg.org/cssom-view/#dom-window-moveto
     fn MoveTo(&self, x: i32, y: i32) {
         // Step 1
-        //TODO determine if this operation is allowed
+        if !self.can_move_or_resize() {
+            return;
+        }
         let dpr = self.device_pixel_ratio();
         let point = Point2D::new(x, y).to_f32() * dpr;
         let msg = EmbedderMsg::MoveTo(point.to_i32());
@@ -1235,6 +1284,11 @@ impl WindowMethods for Window {
         *self.status.borrow_mut() = status
     }
 
+    // https://w3c.github.io/selection-api/#dom-window-getselection
+    fn GetSelection(&self) -> Option<DomRoot<Selection>> {
+        self.document.get().and_then(|d| d.GetSelection())
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-window-matchmedia
     fn MatchMedia(&self, query: DOMString) -> DomRoot<MediaQueryList> {
         let mut input = ParserInput::new(&query);
@@ -1266,6 +1320,41 @@ impl WindowMethods for Window {
         fetch::Fetch(&self.upcast(), input, init, comp)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap(
+        &self,
+        image: ImageBitmapSource,
+        options: &ImageBitmapOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        create_image_bitmap(self.upcast(), image, None, options, comp)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap_(
+        &self,
+        image: ImageBitmapSource,
+        sx: i32,
+        sy: i32,
+        sw: i32,
+        sh: i32,
+        options: &ImageBitmapOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        create_image_bitmap(self.upcast(), image, Some((sx, sy, sw, sh)), options, comp)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        self.upcast::<GlobalScope>()
+            .structured_clone(cx, value, options)
+    }
+
     fn TestRunner(&self) -> DomRoot<TestRunner> {
         self.test_runner.or_init(|| TestRunner::new(self.upcast()))
     }
@@ -1295,6 +1384,11 @@ impl WindowMethods for Window {
     fn Origin(&self) -> USVString {
         USVString(self.origin().immutable().ascii_serialization())
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-crossoriginisolated
+    fn CrossOriginIsolated(&self) -> bool {
+        self.upcast::<GlobalScope>().is_cross_origin_isolated()
+    }
 }
 
 impl Window {
@@ -1375,6 +1469,26 @@ impl Window {
         // Tell the constellation to drop the sender to our message-port router, if there is any.
         self.upcast::<GlobalScope>().remove_message_ports_router();
 
+        // Tell the constellation to drop the sender to our broadcast router, if there is any.
+        self.upcast::<GlobalScope>()
+            .remove_broadcast_channel_router();
+
+        // Close the dedicated workers created by this window.
+        self.upcast::<GlobalScope>().close_workers();
+
+        // Revoke the Blob URLs created by this window.
+        self.upcast::<GlobalScope>().revoke_blob_urls();
+
+        // Stop listening to the cookie changes.
+        if let Some(cookie_store) = self.cookie_store.get() {
+            cookie_store.remove_change_listener();
+        }
+
+        // Stop speaking the utterances of this window.
+        if let Some(speech_synthesis) = self.speech_synthesis.get() {
+            speech_synthesis.stop();
+        }
+
         // Clean up any active promises
         // https://github.com/servo/servo/issues/15318
         if let Some(custom_elements) = self.custom_element_registry.get() {
@@ -1447,25 +1561,22 @@ impl Window {
         };
 
         // Step 10
-        //TODO handling ongoing smooth scrolling
-        if x == self.ScrollX() as f64 && y == self.ScrollY() as f64 {
+        let global_scope = self.upcast::<GlobalScope>();
+        let scroll_id = global_scope.pipeline_id().root_scroll_id();
+        if x == self.ScrollX() as f64 &&
+            y == self.ScrollY() as f64 &&
+            !self.smooth_scrolls.borrow().contains_key(&scroll_id)
+        {
             return;
         }
 
         //TODO Step 11
         //let document = self.Document();
         // Step 12
-        let global_scope = self.upcast::<GlobalScope>();
         let x = x.to_f32().unwrap_or(0.0f32);
         let y = y.to_f32().unwrap_or(0.0f32);
         self.update_viewport_for_scroll(x, y);
-        self.perform_a_scroll(
-            x,
-            y,
-            global_scope.pipeline_id().root_scroll_id(),
-            behavior,
-            None,
-        );
+        self.perform_a_scroll(x, y, scroll_id, behavior, None);
     }
 
     /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
@@ -1474,18 +1585,100 @@ impl Window {
         x: f32,
         y: f32,
         scroll_id: ExternalScrollId,
-        _behavior: ScrollBehavior,
-        _element: Option<&Element>,
+        behavior: ScrollBehavior,
+        element: Option<&Element>,
     ) {
+        // The element whose scrolling box is scrolled, or `None` for the viewport.
+        let global_scope = self.upcast::<GlobalScope>();
+        let scrolling_element = if scroll_id == global_scope.pipeline_id().root_scroll_id() {
+            None
+        } else {
+            element
+        };
+
         // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
-        self.layout_chan
-            .send(Msg::UpdateScrollStateFromScript(ScrollState {
-                scroll_id,
-                scroll_offset: Vector2D::new(-x, -y),
-            }))
-            .unwrap();
+        // Step 2: The scroll is smooth if requested, or if the behavior is auto
+        // and the scrolling box (the root element, for the viewport) asks for it.
+        let smooth = match behavior {
+            ScrollBehavior::Smooth => true,
+            ScrollBehavior::Instant => false,
+            ScrollBehavior::Auto => scrolling_element
+                .map(DomRoot::from_ref)
+                .or_else(|| self.Document().GetDocumentElement())
+                .and_then(|element| element.style())
+                .map_or(false, |style| {
+                    style.get_box().clone_scroll_behavior() == scroll_behavior::T::Smooth
+                }),
+        };
+        let position = LayoutPoint::new(x, y);
+        if smooth {
+            // The compositor animates the scroll, reports the final scroll
+            // position back to layout and tells us once it is done. A smooth
+            // scroll of the same box that is still in progress is replaced.
+            self.smooth_scrolls
+                .borrow_mut()
+                .insert(scroll_id, scrolling_element.map(Dom::from_ref));
+            self.send_to_constellation(ScriptMsg::SmoothScroll(scroll_id, position));
+            return;
+        }
+
+        // An instant scroll interrupts any smooth scroll of the same box, which
+        // the compositor has to stop before moving the box to its new position.
+        let interrupted = self
+            .smooth_scrolls
+            .borrow_mut()
+            .remove(&scroll_id)
+            .is_some();
+        if interrupted {
+            self.send_to_constellation(ScriptMsg::StopSmoothScroll(scroll_id, position));
+        } else {
+            self.layout_chan
+                .send(Msg::UpdateScrollStateFromScript(ScrollState {
+                    scroll_id,
+                    scroll_offset: Vector2D::new(-x, -y),
+                }))
+                .unwrap();
+        }
+
+        let this = Trusted::new(self);
+        let element = scrolling_element.map(Trusted::new);
+        let task = task!(fire_scrollend_event: move || {
+            let element = element.map(|element| element.root());
+            this.root().fire_scrollend_event(element.as_deref());
+        });
+        let _ = self
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task, global_scope);
+    }
+
+    /// Called once the compositor has finished a smooth scroll of the given
+    /// scroll node, either because it reached its destination or because it
+    /// was interrupted by the user.
+    pub fn handle_smooth_scroll_ended(&self, scroll_id: ExternalScrollId) {
+        let element = match self.smooth_scrolls.borrow_mut().remove(&scroll_id) {
+            Some(element) => element.as_deref().map(DomRoot::from_ref),
+            None => return,
+        };
+        self.fire_scrollend_event(element.as_deref());
+    }
+
+    /// Fires a `scrollend` event at the element whose scrolling box finished
+    /// scrolling, or at the document if the viewport did.
+    /// <https://drafts.csswg.org/cssom-view/#scrolling-events>
+    fn fire_scrollend_event(&self, element: Option<&Element>) {
+        match element {
+            Some(element) => {
+                element
+                    .upcast::<EventTarget>()
+                    .fire_event(atom!("scrollend"));
+            },
+            None => {
+                self.Document()
+                    .upcast::<EventTarget>()
+                    .fire_bubbling_event(atom!("scrollend"));
+            },
+        }
     }
 
     pub fn update_viewport_for_scroll(&self, x: f32, y: f32) {
@@ -1594,6 +1787,18 @@ impl Window {
             dom_count: self.Document().dom_count(),
         };
 
+        // The effects of the events dispatched since the last reflow are
+        // presented along with the frame built by this one.
+        let observe_event_presentation = needs_display &&
+            self.performance.get().map_or(false, |performance| {
+                performance.observe_event_presentation()
+            });
+        if observe_event_presentation {
+            self.layout_chan
+                .send(Msg::ObserveNextFramePresentation)
+                .expect("Layout thread disconnected.");
+        }
+
         self.layout_chan
             .send(Msg::Reflow(reflow))
             .expect("Layout thread disconnected.");
@@ -1806,7 +2011,7 @@ impl Window {
             y_.to_f32().unwrap_or(0.0f32),
             scroll_id,
             behavior,
-            None,
+            node.downcast::<Element>(),
         );
     }
 
@@ -1823,6 +2028,24 @@ impl Window {
         DOMString::from(resolved)
     }
 
+    /// Resolves the `font` shorthand `value` against the style of `node`,
+    /// for the `font` attribute of canvas contexts.
+    pub fn resolved_font_style_query(
+        &self,
+        node: &Node,
+        value: String,
+    ) -> Option<servo_arc::Arc<Font>> {
+        let id = PropertyId::Shorthand(ShorthandId::Font);
+        if !self.layout_reflow(QueryMsg::ResolvedFontStyleQuery(
+            node.to_trusted_node_address(),
+            id,
+            value,
+        )) {
+            return None;
+        }
+        self.layout_rpc.resolved_font_style()
+    }
+
     pub fn inner_window_dimensions_query(
         &self,
         browsing_context: BrowsingContextId,
@@ -1914,6 +2137,17 @@ impl Window {
         load_data: LoadData,
     ) {
         let doc = self.Document();
+        let navigation = if load_data.url.scheme() != "javascript" {
+            self.navigation_api()
+        } else {
+            None
+        };
+        let navigation_type = match replace {
+            _ if force_reload => NavigationType::Reload,
+            HistoryEntryReplacement::Enabled => NavigationType::Replace,
+            HistoryEntryReplacement::Disabled => NavigationType::Push,
+        };
+
         // TODO: Important re security. See https://github.com/servo/servo/issues/23373
         // Step 3: check that the source browsing-context is "allowed to navigate" this window.
         if !force_reload &&
@@ -1922,10 +2156,18 @@ impl Window {
         {
             // Step 6
             if let Some(fragment) = load_data.url.fragment() {
+                if let Some(ref navigation) = navigation {
+                    let outcome =
+                        navigation.fire_navigate_event(navigation_type, &load_data.url, None);
+                    if outcome == NavigateEventOutcome::Canceled {
+                        return;
+                    }
+                }
                 self.send_to_constellation(ScriptMsg::NavigatedToFragment(
                     load_data.url.clone(),
                     replace,
                 ));
+                self.History().persist_user_state();
                 doc.check_and_scroll_fragment(fragment);
                 let this = Trusted::new(self);
                 let old_url = doc.url().into_string();
@@ -1953,10 +2195,28 @@ impl Window {
                     TaskSourceName::DOMManipulation,
                 ));
                 doc.set_url(load_data.url.clone());
+                if let Some(navigation) = navigation {
+                    navigation.commit_ongoing_navigation(navigation_type);
+                }
                 return;
             }
         }
 
+        if let Some(navigation) = navigation {
+            match navigation.fire_navigate_event(navigation_type, &load_data.url, None) {
+                NavigateEventOutcome::Continue => {},
+                NavigateEventOutcome::Canceled => return,
+                NavigateEventOutcome::Intercepted => {
+                    if navigation_type != NavigationType::Reload {
+                        self.History()
+                            .update_url_and_history(load_data.url.clone(), replace);
+                    }
+                    navigation.commit_ongoing_navigation(navigation_type);
+                    return;
+                },
+            }
+        }
+
         let pipeline_id = self.upcast::<GlobalScope>().pipeline_id();
 
         // Step 4 and 5
@@ -1983,6 +2243,15 @@ impl Window {
         };
     }
 
+    /// The navigation API of this window, if it is enabled.
+    pub fn navigation_api(&self) -> Option<DomRoot<Navigation>> {
+        if pref!(dom.navigation.enabled) {
+            Some(self.Navigation())
+        } else {
+            None
+        }
+    }
+
     pub fn handle_fire_timer(&self, timer_id: TimerEventId) {
         self.upcast::<GlobalScope>().fire_timer(timer_id);
         self.reflow(ReflowGoal::Full, ReflowReason::Timer);
@@ -2128,16 +2397,59 @@ impl Window {
         self.parent_info.is_none()
     }
 
+    /// Whether script is allowed to move or resize the window: only a
+    /// top-level auxiliary browsing context that was opened by script,
+    /// and whose session history contains a single entry, can be.
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-resizeto>
+    fn can_move_or_resize(&self) -> bool {
+        if !self.is_top_level() {
+            return false;
+        }
+        let is_auxiliary = self
+            .undiscarded_window_proxy()
+            .map_or(false, |proxy| proxy.is_auxiliary());
+        if !is_auxiliary {
+            return false;
+        }
+        self.History()
+            .GetLength()
+            .map_or(false, |length| length == 1)
+    }
+
     /// Evaluate media query lists and report changes
     /// <https://drafts.csswg.org/cssom-view/#evaluate-media-queries-and-report-changes>
     pub fn evaluate_media_queries_and_report_changes(&self) {
+        self.report_media_query_changes();
+        self.Document().react_to_environment_changes();
+    }
+
+    /// Like `evaluate_media_queries_and_report_changes`, but only reacts to
+    /// the environment changes if any media query list changed.
+    pub fn evaluate_media_queries_if_changed(&self) {
+        if self.report_media_query_changes() {
+            self.Document().react_to_environment_changes();
+        }
+    }
+
+    /// Fires a change event at the media query lists whose match changed,
+    /// and returns whether there were any.
+    fn report_media_query_changes(&self) -> bool {
         rooted_vec!(let mut mql_list);
+        let mut listened = self.listened_media_query_lists.borrow_mut();
+        listened.clear();
         self.media_query_lists.for_each(|mql| {
             if let MediaQueryListMatchState::Changed(_) = mql.evaluate_changes() {
                 // Recording list of changed Media Queries
                 mql_list.push(Dom::from_ref(&*mql));
             }
+            if mql
+                .upcast::<EventTarget>()
+                .has_listeners_for(&atom!("change"))
+            {
+                listened.push(Dom::from_ref(&*mql));
+            }
         });
+        drop(listened);
         // Sending change events for all changed Media Queries
         for mql in mql_list.iter() {
             let event = MediaQueryListEvent::new(
@@ -2150,7 +2462,7 @@ impl Window {
             );
             event.upcast::<Event>().fire(mql.upcast::<EventTarget>());
         }
-        self.Document().react_to_environment_changes();
+        !mql_list.is_empty()
     }
 
     /// Slow down/speed up timers based on visibility.
@@ -2166,6 +2478,14 @@ impl Window {
         self.unminified_js_dir.borrow().clone()
     }
 
+    /// Reports the entries of the events dispatched since the last rendering
+    /// update that no frame is going to present.
+    pub fn report_pending_event_timings(&self) {
+        if let Some(performance) = self.performance.get() {
+            performance.report_pending_event_entries();
+        }
+    }
+
     pub fn set_navigation_start(&self) {
         let current_time = time::get_time();
         let now = (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64;
@@ -2260,10 +2580,14 @@ impl Window {
             navigator: Default::default(),
             location: Default::default(),
             history: Default::default(),
+            navigation: Default::default(),
             custom_element_registry: Default::default(),
             window_proxy: Default::default(),
             document: Default::default(),
             performance: Default::default(),
+            caches: Default::default(),
+            cookie_store: Default::default(),
+            speech_synthesis: Default::default(),
             navigation_start: Cell::new(navigation_start),
             navigation_start_precise: Cell::new(navigation_start_precise),
             screen: Default::default(),
@@ -2289,13 +2613,14 @@ impl Window {
             webdriver_script_chan: Default::default(),
             error_reporter,
             scroll_offsets: Default::default(),
+            smooth_scrolls: Default::default(),
             media_query_lists: DOMTracker::new(),
+            listened_media_query_lists: Default::default(),
             test_runner: Default::default(),
             webgl_chan,
             webgpu,
             webvr_chan,
             webxr_registry,
-            permission_state_invocation_results: Default::default(),
             pending_layout_images: Default::default(),
             unminified_js_dir: Default::default(),
             test_worklet: Default::default(),
@@ -2359,6 +2684,7 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::NodeScrollGeometryQuery(_n) => "\tNodeScrollGeometryQuery",
             &QueryMsg::NodeScrollIdQuery(_n) => "\tNodeScrollIdQuery",
             &QueryMsg::ResolvedStyleQuery(_, _, _) => "\tResolvedStyleQuery",
+            &QueryMsg::ResolvedFontStyleQuery(..) => "\tResolvedFontStyleQuery",
             &QueryMsg::OffsetParentQuery(_n) => "\tOffsetParentQuery",
             &QueryMsg::StyleQuery(_n) => "\tStyleQuery",
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
@@ -2388,6 +2714,7 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
         ReflowReason::IFrameLoadEvent => "\tIFrameLoadEvent",
         ReflowReason::MissingExplicitReflow => "\tMissingExplicitReflow",
         ReflowReason::ElementStateChanged => "\tElementStateChanged",
+        ReflowReason::AnimationTick => "\tAnimationTick",
     });
 
     println!("{}", debug_msg);
@@ -5,7 +5,7 @@
 use crate::compartments::enter_realm;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::conversions::{root_from_handleobject, ToJSValConvertible};
-use crate::dom::bindings::error::{throw_dom_exception, Error};
+use crate::dom::bindings::error::{throw_dom_exception, Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::proxyhandler::fill_property_descriptor;
 use crate::dom::bindings::reflector::{DomObject, Reflector};
@@ -21,7 +21,7 @@ use crate::dom::window::Window;
 use crate::script_runtime::JSContext as SafeJSContext;
 use crate::script_thread::ScriptThread;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, WindowFeatures};
 use indexmap::map::IndexMap;
 use ipc_channel::ipc;
 use js::glue::{CreateWrapperProxyHandler, ProxyTraps};
@@ -413,7 +413,12 @@ impl WindowProxy {
         url: USVString,
         target: DOMString,
         features: DOMString,
-    ) -> Option<DomRoot<WindowProxy>> {
+    ) -> Fallible<Option<DomRoot<WindowProxy>>> {
+        let existing_document = self
+            .currently_active
+            .get()
+            .and_then(|id| ScriptThread::find_document(id))
+            .unwrap();
         // Step 4.
         let non_empty_target = match target.as_ref() {
             "" => DOMString::from("_blank"),
@@ -428,30 +433,34 @@ impl WindowProxy {
         } else {
             parse_open_feature_boolean(&tokenized_features, "noopener")
         };
+        // Step 14.1, parsing the url before a browsing context is chosen,
+        // so that no browsing context is created if it fails.
+        let url = if url.is_empty() {
+            None
+        } else {
+            match existing_document.url().join(&url) {
+                Ok(url) => Some(url),
+                Err(_) => return Err(Error::Syntax),
+            }
+        };
         // Step 10, 11
         let (chosen, new) = match self.choose_browsing_context(non_empty_target, noopener) {
             (Some(chosen), new) => (chosen, new),
-            (None, _) => return None,
+            (None, _) => return Ok(None),
         };
-        // TODO Step 12, set up browsing context features.
         let target_document = match chosen.document() {
             Some(target_document) => target_document,
-            None => return None,
+            None => return Ok(None),
         };
         let target_window = target_document.window();
+        // Step 12
+        if new {
+            let features = browsing_context_features(&tokenized_features, target_window);
+            target_window.send_to_embedder(EmbedderMsg::SetWindowFeatures(features));
+        }
         // Step 13, and 14.4, will have happened elsewhere,
         // since we've created a new browsing context and loaded it with about:blank.
-        if !url.is_empty() {
-            let existing_document = self
-                .currently_active
-                .get()
-                .and_then(|id| ScriptThread::find_document(id))
-                .unwrap();
-            // Step 14.1
-            let url = match existing_document.url().join(&url) {
-                Ok(url) => url,
-                Err(_) => return None, // TODO: throw a  "SyntaxError" DOMException.
-            };
+        if let Some(url) = url {
             // Step 14.3
             let referrer = if noreferrer {
                 Referrer::NoReferrer
@@ -477,10 +486,10 @@ impl WindowProxy {
         }
         if noopener {
             // Step 15 (Dis-owning has been done in create_auxiliary_browsing_context).
-            return None;
+            return Ok(None);
         }
         // Step 17.
-        return target_document.browsing_context();
+        Ok(target_document.browsing_context())
     }
 
     // https://html.spec.whatwg.org/multipage/#the-rules-for-choosing-a-browsing-context-given-a-browsing-context-name
@@ -740,6 +749,79 @@ fn parse_open_feature_boolean(tokenized_features: &IndexMap<String, String>, nam
     return false;
 }
 
+// https://html.spec.whatwg.org/multipage/#window-feature-is-set
+fn is_open_feature_set(
+    tokenized_features: &IndexMap<String, String>,
+    name: &str,
+    default_value: bool,
+) -> bool {
+    if tokenized_features.contains_key(name) {
+        return parse_open_feature_boolean(tokenized_features, name);
+    }
+    default_value
+}
+
+// https://html.spec.whatwg.org/multipage/#popup-window-is-requested
+fn is_popup_window_requested(tokenized_features: &IndexMap<String, String>) -> bool {
+    // Step 1
+    if tokenized_features.is_empty() {
+        return false;
+    }
+    // Step 2
+    if tokenized_features.contains_key("popup") {
+        return parse_open_feature_boolean(tokenized_features, "popup");
+    }
+    // Step 3-5
+    let location = is_open_feature_set(tokenized_features, "location", false);
+    let toolbar = is_open_feature_set(tokenized_features, "toolbar", false);
+    if !location && !toolbar {
+        return true;
+    }
+    // Step 6
+    if !is_open_feature_set(tokenized_features, "menubar", false) {
+        return true;
+    }
+    // Step 7
+    if !is_open_feature_set(tokenized_features, "resizable", true) {
+        return true;
+    }
+    // Step 8
+    if !is_open_feature_set(tokenized_features, "scrollbars", false) {
+        return true;
+    }
+    // Step 9
+    if !is_open_feature_set(tokenized_features, "status", false) {
+        return true;
+    }
+    // Step 10
+    false
+}
+
+// https://html.spec.whatwg.org/multipage/#set-up-browsing-context-features
+fn browsing_context_features(
+    tokenized_features: &IndexMap<String, String>,
+    window: &Window,
+) -> WindowFeatures {
+    let dpr = window.device_pixel_ratio().get();
+    let parse_feature = |name: &str, min: i32| {
+        tokenized_features
+            .get(name)
+            .and_then(|value| parse_integer(value.chars()).ok())
+            .map(|value| (value.max(min) as f32 * dpr) as i32)
+    };
+    WindowFeatures {
+        popup: is_popup_window_requested(tokenized_features),
+        // Step 3-4
+        left: parse_feature("left", i32::min_value()),
+        // Step 5-6
+        top: parse_feature("top", i32::min_value()),
+        // Step 7-8, with a minimum of 100 as allowed for clamping.
+        width: parse_feature("width", 100),
+        // Step 9-10
+        height: parse_feature("height", 100),
+    }
+}
+
 // This is only called from extern functions,
 // there's no use using the lifetimed handles here.
 // https://html.spec.whatwg.org/multipage/#accessing-other-browsing-contexts
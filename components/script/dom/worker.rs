@@ -5,9 +5,12 @@
 use crate::compartments::enter_realm;
 use crate::dom::abstractworker::SimpleWorkerErrorHandler;
 use crate::dom::abstractworker::WorkerScriptMsg;
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::MessagePortBinding::PostMessageOptions;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding;
-use crate::dom::bindings::codegen::Bindings::WorkerBinding::{WorkerMethods, WorkerOptions};
+use crate::dom::bindings::codegen::Bindings::WorkerBinding::{
+    WorkerMethods, WorkerOptions, WorkerType,
+};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
@@ -23,15 +26,16 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::workerglobalscope::prepare_workerscope_init;
-use crate::script_runtime::JSContext;
+use crate::script_runtime::{ContextForRequestInterrupt, JSContext};
 use crate::task::TaskOnce;
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{DevtoolsPageInfo, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
 use ipc_channel::ipc;
-use js::jsapi::{Heap, JSObject, JS_RequestInterruptCallback};
+use js::jsapi::{Heap, JSObject};
 use js::jsval::UndefinedValue;
 use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue};
+use net_traits::request::CredentialsMode;
 use script_traits::{StructuredSerializedData, WorkerScriptLoadOrigin};
 use std::cell::Cell;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -50,15 +54,29 @@ pub struct Worker {
     #[ignore_malloc_size_of = "Arc"]
     closing: Arc<AtomicBool>,
     terminated: Cell<bool>,
+    #[ignore_malloc_size_of = "Defined in std"]
+    /// Dropped when the worker is terminated, which disconnects
+    /// the termination receiver of its DedicatedWorkerGlobalScope.
+    termination_sender: DomRefCell<Option<Sender<()>>>,
+    #[ignore_malloc_size_of = "Arc"]
+    /// The JS context of the worker, interrupted when it is terminated.
+    context_for_interrupt: ContextForRequestInterrupt,
 }
 
 impl Worker {
-    fn new_inherited(sender: Sender<DedicatedWorkerScriptMsg>, closing: Arc<AtomicBool>) -> Worker {
+    fn new_inherited(
+        sender: Sender<DedicatedWorkerScriptMsg>,
+        closing: Arc<AtomicBool>,
+        termination_sender: Sender<()>,
+        context_for_interrupt: ContextForRequestInterrupt,
+    ) -> Worker {
         Worker {
             eventtarget: EventTarget::new_inherited(),
             sender: sender,
             closing: closing,
             terminated: Cell::new(false),
+            termination_sender: DomRefCell::new(Some(termination_sender)),
+            context_for_interrupt,
         }
     }
 
@@ -66,9 +84,16 @@ impl Worker {
         global: &GlobalScope,
         sender: Sender<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
+        termination_sender: Sender<()>,
+        context_for_interrupt: ContextForRequestInterrupt,
     ) -> DomRoot<Worker> {
         reflect_dom_object(
-            Box::new(Worker::new_inherited(sender, closing)),
+            Box::new(Worker::new_inherited(
+                sender,
+                closing,
+                termination_sender,
+                context_for_interrupt,
+            )),
             global,
             WorkerBinding::Wrap,
         )
@@ -89,13 +114,21 @@ impl Worker {
 
         let (sender, receiver) = unbounded();
         let closing = Arc::new(AtomicBool::new(false));
-        let worker = Worker::new(global, sender.clone(), closing.clone());
-        global.track_worker(closing.clone());
+        let (termination_sender, termination_receiver) = unbounded();
+        let context_for_interrupt = ContextForRequestInterrupt::default();
+        let worker = Worker::new(
+            global,
+            sender.clone(),
+            closing.clone(),
+            termination_sender,
+            context_for_interrupt.clone(),
+        );
+        global.track_worker(closing.clone(), sender.clone());
         let worker_ref = Trusted::new(&*worker);
 
         let worker_load_origin = WorkerScriptLoadOrigin {
             referrer_url: None,
-            referrer_policy: None,
+            referrer_policy: global.get_referrer_policy(),
             pipeline_id: Some(global.pipeline_id()),
         };
 
@@ -115,6 +148,13 @@ impl Worker {
             ));
         }
 
+        // The credentials option is only used by module workers,
+        // classic worker scripts are fetched with same-origin credentials.
+        let credentials = match worker_options.type_ {
+            WorkerType::Classic => CredentialsMode::CredentialsSameOrigin,
+            WorkerType::Module => worker_options.credentials.into(),
+        };
+
         let init = prepare_workerscope_init(global, Some(devtools_sender));
 
         DedicatedWorkerGlobalScope::run_worker_scope(
@@ -128,7 +168,10 @@ impl Worker {
             worker_load_origin,
             String::from(&*worker_options.name),
             worker_options.type_,
+            credentials,
             closing,
+            termination_receiver,
+            context_for_interrupt,
             global.image_cache(),
         );
 
@@ -181,7 +224,11 @@ impl Worker {
         message: HandleValue,
         transfer: CustomAutoRooterGuard<Vec<*mut JSObject>>,
     ) -> ErrorResult {
-        let data = structuredclone::write(cx, message, Some(transfer))?;
+        let data = if self.global().is_cross_origin_isolated() {
+            structuredclone::write_with_shared_memory(cx, message, Some(transfer))?
+        } else {
+            structuredclone::write(cx, message, Some(transfer))?
+        };
         let address = Trusted::new(self);
 
         // NOTE: step 9 of https://html.spec.whatwg.org/multipage/#dom-messageport-postmessage
@@ -226,7 +273,6 @@ impl WorkerMethods for Worker {
         self.post_message_impl(cx, message, guard)
     }
 
-    #[allow(unsafe_code)]
     // https://html.spec.whatwg.org/multipage/#terminate-a-worker
     fn Terminate(&self) {
         // Step 1
@@ -234,12 +280,17 @@ impl WorkerMethods for Worker {
             return;
         }
 
-        // Step 2
+        // Step 2, the worker discards its queued tasks,
+        // and stops waiting for any load in progress.
         self.terminated.set(true);
+        self.termination_sender.borrow_mut().take();
 
         // Step 3
-        let cx = self.global().get_cx();
-        unsafe { JS_RequestInterruptCallback(*cx) };
+        self.context_for_interrupt.request_interrupt();
+
+        // The worker may be waiting for its next task,
+        // it closes its own dedicated workers once it stops running.
+        let _ = self.sender.send(DedicatedWorkerScriptMsg::WakeUp);
     }
 
     // https://html.spec.whatwg.org/multipage/#handler-worker-onmessage
@@ -3,8 +3,13 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::InCompartment;
+use crate::devtools;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
+    ImageBitmapOptions, ImageBitmapSource,
+};
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
+use crate::dom::bindings::codegen::Bindings::WindowOrWorkerGlobalScopeBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
@@ -15,16 +20,21 @@ use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::settings_stack::AutoEntryScript;
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::cachestorage::CacheStorage;
 use crate::dom::crypto::Crypto;
 use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::imagebitmap::create_image_bitmap;
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::dom::sharedworkerglobalscope::SharedWorkerGlobalScope;
 use crate::dom::window::{base64_atob, base64_btoa};
 use crate::dom::workerlocation::WorkerLocation;
 use crate::dom::workernavigator::WorkerNavigator;
-use crate::fetch;
+use crate::fetch::{self, WholeResourceLoad};
+use crate::script_module::{fetch_module_worker_script_graph, run_module_script};
 use crate::script_runtime::JSContext;
 use crate::script_runtime::{get_reports, CommonScriptMsg, Runtime, ScriptChan, ScriptPort};
 use crate::task::TaskCanceller;
@@ -33,32 +43,54 @@ use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::timers::{IsInterval, TimerCallback};
-use crossbeam_channel::Receiver;
-use devtools_traits::{DevtoolScriptControlMsg, WorkerId};
+use crossbeam_channel::{Receiver, TryRecvError};
+use devtools_traits::{ConsoleAPI, DevtoolScriptControlMsg, WorkerId};
 use dom_struct::dom_struct;
+use headers::HeaderMapExt;
+use headers::ReferrerPolicy as ReferrerPolicyHeader;
+use http::header::{HeaderValue, ETAG, LAST_MODIFIED};
 use ipc_channel::ipc::IpcSender;
-use js::jsapi::JSAutoRealm;
-use js::jsval::UndefinedValue;
+use js::jsapi::{CompileUtf8, Heap, JSAutoRealm, JSScript};
+use js::jsval::{JSVal, UndefinedValue};
 use js::panic::maybe_resume_unwind;
-use js::rust::{HandleValue, ParentRuntime};
+use js::rust::wrappers::JS_ExecuteScript;
+use js::rust::{CompileOptionsWrapper, HandleValue, MutableHandle, ParentRuntime};
 use msg::constellation_msg::{PipelineId, PipelineNamespace};
 use net_traits::request::{
     CredentialsMode, Destination, ParserMetadata, RequestBuilder as NetRequestInit,
 };
-use net_traits::IpcSend;
+use net_traits::{IpcSend, Metadata, ReferrerPolicy};
 use script_traits::WorkerGlobalScopeInit;
 use servo_url::{MutableOrigin, ServoUrl};
-use std::cell::Ref;
+use std::cell::{Cell, Ref};
+use std::collections::{HashMap, VecDeque};
 use std::default::Default;
+use std::ffi::CString;
+use std::ops::Deref;
+use std::ptr;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use time::precise_time_ns;
 
+/// The number of console messages a worker keeps for devtools clients,
+/// the oldest ones are dropped first.
+const MAX_CACHED_CONSOLE_MESSAGES: usize = 1000;
+
+/// The main script of a worker, once fetched.
+pub enum WorkerScript {
+    /// The source of a classic script.
+    Classic(String),
+    /// A module script, whose graph is in the module map of the worker,
+    /// identified by the URL it was requested from.
+    Module(ServoUrl),
+}
+
 pub fn prepare_workerscope_init(
     global: &GlobalScope,
     devtools_sender: Option<IpcSender<DevtoolScriptControlMsg>>,
@@ -76,11 +108,51 @@ pub fn prepare_workerscope_init(
         origin: global.origin().immutable().clone(),
         is_headless: global.is_headless(),
         user_agent: global.get_user_agent(),
+        cross_origin_isolated: global.is_cross_origin_isolated(),
+        navigation_start_precise: precise_time_ns(),
     };
 
     init
 }
 
+/// The validators of the response to an imported script, which tell whether
+/// the script changed since it was compiled,
+/// <https://httpwg.org/specs/rfc7232.html#validators>
+#[derive(JSTraceable, MallocSizeOf, PartialEq)]
+struct ScriptValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl ScriptValidators {
+    /// `None` if the response has no validators, its script then cannot be reused.
+    fn from_metadata(metadata: &Metadata) -> Option<ScriptValidators> {
+        let headers = metadata.headers.as_ref()?;
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        match (header(ETAG), header(LAST_MODIFIED)) {
+            (None, None) => None,
+            (etag, last_modified) => Some(ScriptValidators {
+                etag,
+                last_modified,
+            }),
+        }
+    }
+}
+
+/// A script compiled by `importScripts`, which later imports of the same URL
+/// reuse as long as the validators of its response do not change.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ImportedScript {
+    validators: ScriptValidators,
+    #[ignore_malloc_size_of = "mozjs"]
+    script: Box<Heap<*mut JSScript>>,
+}
+
 // https://html.spec.whatwg.org/multipage/#the-workerglobalscope-common-interface
 #[dom_struct]
 pub struct WorkerGlobalScope {
@@ -91,8 +163,15 @@ pub struct WorkerGlobalScope {
 
     worker_id: WorkerId,
     worker_url: DomRefCell<ServoUrl>,
+    /// The referrer policy of the worker, from the response to its main script.
+    referrer_policy: Cell<Option<ReferrerPolicy>>,
+    /// The scripts compiled by `importScripts`, keyed by the URL of their response.
+    imported_scripts: DomRefCell<HashMap<ServoUrl, ImportedScript>>,
     #[ignore_malloc_size_of = "Arc"]
     closing: Option<Arc<AtomicBool>>,
+    #[ignore_malloc_size_of = "Defined in std"]
+    /// Disconnected once the worker has been terminated by its owner.
+    termination_receiver: Option<Receiver<()>>,
     #[ignore_malloc_size_of = "Defined in js"]
     runtime: Runtime,
     location: MutNullableDom<WorkerLocation>,
@@ -110,6 +189,11 @@ pub struct WorkerGlobalScope {
 
     navigation_start_precise: u64,
     performance: MutNullableDom<Performance>,
+    caches: MutNullableDom<CacheStorage>,
+    #[ignore_malloc_size_of = "Defined in devtools_traits"]
+    /// The latest console messages of the worker, kept for devtools clients
+    /// that attach to the worker after they were logged.
+    console_messages: DomRefCell<VecDeque<ConsoleAPI>>,
 }
 
 impl WorkerGlobalScope {
@@ -121,6 +205,7 @@ impl WorkerGlobalScope {
         runtime: Runtime,
         from_devtools_receiver: Receiver<DevtoolScriptControlMsg>,
         closing: Option<Arc<AtomicBool>>,
+        termination_receiver: Option<Receiver<()>>,
     ) -> Self {
         // Install a pipeline-namespace in the current thread.
         PipelineNamespace::auto_install();
@@ -142,14 +227,19 @@ impl WorkerGlobalScope {
             worker_name,
             worker_type,
             worker_url: DomRefCell::new(worker_url),
+            referrer_policy: Cell::new(None),
+            imported_scripts: Default::default(),
             closing,
+            termination_receiver,
             runtime,
             location: Default::default(),
             navigator: Default::default(),
             from_devtools_sender: init.from_devtools_sender,
             from_devtools_receiver,
-            navigation_start_precise: precise_time_ns(),
+            navigation_start_precise: init.navigation_start_precise,
             performance: Default::default(),
+            caches: Default::default(),
+            console_messages: Default::default(),
         }
     }
 
@@ -178,6 +268,25 @@ impl WorkerGlobalScope {
         }
     }
 
+    /// Whether the worker has been terminated by its owner,
+    /// <https://html.spec.whatwg.org/multipage/#terminate-a-worker>
+    pub fn is_terminated(&self) -> bool {
+        match self.termination_receiver {
+            Some(ref receiver) => receiver.try_recv() == Err(TryRecvError::Disconnected),
+            None => false,
+        }
+    }
+
+    /// A receiver that is disconnected once the worker has been terminated,
+    /// with which blocking operations can stop waiting.
+    pub fn termination_receiver(&self) -> Option<&Receiver<()>> {
+        self.termination_receiver.as_ref()
+    }
+
+    pub fn worker_name(&self) -> DOMString {
+        self.worker_name.clone()
+    }
+
     pub fn get_url(&self) -> Ref<ServoUrl> {
         self.worker_url.borrow()
     }
@@ -186,6 +295,75 @@ impl WorkerGlobalScope {
         *self.worker_url.borrow_mut() = url;
     }
 
+    pub fn get_referrer_policy(&self) -> Option<ReferrerPolicy> {
+        self.referrer_policy.get()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#run-a-worker Step 12.4
+    ///
+    /// Initializes the referrer policy of the worker from the response to its main script.
+    fn set_referrer_policy_from_response(&self, metadata: &Metadata) {
+        let referrer_policy = metadata
+            .headers
+            .as_ref()
+            .map(Deref::deref)
+            .and_then(|headers| headers.typed_get::<ReferrerPolicyHeader>())
+            .map(ReferrerPolicy::from);
+        self.referrer_policy.set(referrer_policy);
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#run-a-worker Step 12
+    ///
+    /// Fetches the main script of the worker according to its type, and sets the URL
+    /// of the worker to the URL of the response.
+    pub fn fetch_worker_script(&self, request: NetRequestInit) -> Result<WorkerScript, ()> {
+        let global_scope = self.upcast::<GlobalScope>();
+        match self.worker_type {
+            WorkerType::Classic => {
+                let (metadata, bytes) = fetch::load_whole_resource(
+                    request,
+                    &global_scope.resource_threads().sender(),
+                    global_scope,
+                )
+                .map_err(|_| ())?;
+                self.set_referrer_policy_from_response(&metadata);
+                self.set_url(metadata.final_url);
+                Ok(WorkerScript::Classic(
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                ))
+            },
+            WorkerType::Module => {
+                let url = request.url.clone();
+                let final_url = fetch_module_worker_script_graph(global_scope, request)?;
+                self.set_url(final_url);
+                Ok(WorkerScript::Module(url))
+            },
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#run-a-worker Step 24
+    pub fn run_worker_script(&self, script: WorkerScript) {
+        match script {
+            WorkerScript::Classic(source) => self.execute_script(DOMString::from(source)),
+            WorkerScript::Module(url) => {
+                // A failure was already reported as an exception of the module script.
+                let _ = run_module_script(self.upcast(), &url);
+            },
+        }
+    }
+
+    pub fn cache_console_message(&self, message: ConsoleAPI) {
+        let mut messages = self.console_messages.borrow_mut();
+        if messages.len() == MAX_CACHED_CONSOLE_MESSAGES {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+    }
+
+    pub fn cached_console_messages(&self) -> Vec<ConsoleAPI> {
+        self.console_messages.borrow().iter().cloned().collect()
+    }
+
     pub fn get_worker_id(&self) -> WorkerId {
         self.worker_id.clone()
     }
@@ -216,8 +394,30 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
     // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-onerror
     error_event_handler!(error, GetOnerror, SetOnerror);
 
+    // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-onrejectionhandled
+    event_handler!(
+        rejectionhandled,
+        GetOnrejectionhandled,
+        SetOnrejectionhandled
+    );
+
+    // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-onunhandledrejection
+    event_handler!(
+        unhandledrejection,
+        GetOnunhandledrejection,
+        SetOnunhandledrejection
+    );
+
     // https://html.spec.whatwg.org/multipage/#dom-workerglobalscope-importscripts
+    #[allow(unsafe_code)]
     fn ImportScripts(&self, url_strings: Vec<DOMString>) -> ErrorResult {
+        // Module workers use import statements instead.
+        if self.worker_type == WorkerType::Module {
+            return Err(Error::Type(
+                "importScripts() is not supported in module workers".to_owned(),
+            ));
+        }
+
         let mut urls = Vec::with_capacity(url_strings.len());
         for url in url_strings {
             let url = self.worker_url.borrow().join(&url);
@@ -227,43 +427,53 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
             };
         }
 
-        rooted!(in(self.runtime.cx()) let mut rval = UndefinedValue());
-        for url in urls {
-            let global_scope = self.upcast::<GlobalScope>();
-            let request = NetRequestInit::new(url.clone())
-                .destination(Destination::Script)
-                .credentials_mode(CredentialsMode::Include)
-                .parser_metadata(ParserMetadata::NotParserInserted)
-                .use_url_credentials(true)
-                .origin(global_scope.origin().immutable().clone())
-                .pipeline_id(Some(self.upcast::<GlobalScope>().pipeline_id()))
-                .referrer_policy(None);
-
-            let (url, source) = match fetch::load_whole_resource(
-                request,
-                &global_scope.resource_threads().sender(),
-                &global_scope,
-            ) {
-                Err(_) => return Err(Error::Network),
-                Ok((metadata, bytes)) => (metadata.final_url, String::from_utf8(bytes).unwrap()),
-            };
+        // The scripts are all fetched in parallel, and run in order once they
+        // have all been fetched. Unchanged responses are served by the HTTP
+        // cache, and the scripts compiled for them are reused.
+        let global_scope = self.upcast::<GlobalScope>();
+        let loads: Vec<_> = urls
+            .into_iter()
+            .map(|url| {
+                let request = NetRequestInit::new(url)
+                    .destination(Destination::Script)
+                    .credentials_mode(CredentialsMode::Include)
+                    .parser_metadata(ParserMetadata::NotParserInserted)
+                    .use_url_credentials(true)
+                    .origin(global_scope.origin().immutable().clone())
+                    .pipeline_id(Some(global_scope.pipeline_id()))
+                    .referrer_policy(self.get_referrer_policy());
+                WholeResourceLoad::start(request, &global_scope.resource_threads().sender())
+            })
+            .collect();
+        let mut responses = Vec::with_capacity(loads.len());
+        for load in loads {
+            let (metadata, bytes) = load.finish(global_scope).map_err(|_| Error::Network)?;
+            // https://html.spec.whatwg.org/multipage/#fetch-a-classic-worker-imported-script
+            let is_ok = metadata
+                .status
+                .as_ref()
+                .map_or(true, |&(code, _)| code >= 200 && code < 300);
+            if !is_ok {
+                return Err(Error::Network);
+            }
+            responses.push((metadata, bytes));
+        }
+
+        let cx = self.get_cx();
+        let _ac = JSAutoRealm::new(*cx, self.reflector().get_jsobject().get());
+        let _aes = AutoEntryScript::new(global_scope);
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        for (metadata, bytes) in responses {
+            rooted!(in(*cx) let mut script = ptr::null_mut::<JSScript>());
+            self.compile_imported_script(cx, &metadata, &bytes, script.handle_mut())?;
 
-            let result = self.runtime.evaluate_script(
-                self.reflector().get_jsobject(),
-                &source,
-                url.as_str(),
-                1,
-                rval.handle_mut(),
-            );
+            let result = unsafe { JS_ExecuteScript(*cx, script.handle(), rval.handle_mut()) };
 
             maybe_resume_unwind();
 
-            match result {
-                Ok(_) => (),
-                Err(_) => {
-                    println!("evaluate_script failed");
-                    return Err(Error::JSFailed);
-                },
+            if !result {
+                debug!("importScripts() failed to run {}", metadata.final_url);
+                return Err(Error::JSFailed);
             }
         }
 
@@ -280,6 +490,11 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         self.upcast::<GlobalScope>().crypto()
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.upcast::<GlobalScope>().scheduler()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
@@ -352,6 +567,41 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         fetch::Fetch(self.upcast(), input, init, comp)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap(
+        &self,
+        image: ImageBitmapSource,
+        options: &ImageBitmapOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        create_image_bitmap(self.upcast(), image, None, options, comp)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap_(
+        &self,
+        image: ImageBitmapSource,
+        sx: i32,
+        sy: i32,
+        sw: i32,
+        sh: i32,
+        options: &ImageBitmapOptions,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        create_image_bitmap(self.upcast(), image, Some((sx, sy, sw, sh)), options, comp)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        self.upcast::<GlobalScope>()
+            .structured_clone(cx, value, options)
+    }
+
     // https://w3c.github.io/hr-time/#the-performance-attribute
     fn Performance(&self) -> DomRoot<Performance> {
         self.performance.or_init(|| {
@@ -360,6 +610,12 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         })
     }
 
+    // https://w3c.github.io/ServiceWorker/#self-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.caches
+            .or_init(|| CacheStorage::new(self.upcast::<GlobalScope>()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-origin
     fn Origin(&self) -> USVString {
         USVString(
@@ -369,9 +625,72 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
                 .ascii_serialization(),
         )
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-crossoriginisolated
+    fn CrossOriginIsolated(&self) -> bool {
+        self.upcast::<GlobalScope>().is_cross_origin_isolated()
+    }
 }
 
 impl WorkerGlobalScope {
+    /// Compiles the script of the response to an import, or reuses the one
+    /// compiled for a previous import of the same URL if the validators of
+    /// the response did not change.
+    #[allow(unsafe_code)]
+    fn compile_imported_script(
+        &self,
+        cx: JSContext,
+        metadata: &Metadata,
+        bytes: &[u8],
+        mut script: MutableHandle<*mut JSScript>,
+    ) -> ErrorResult {
+        let url = &metadata.final_url;
+        let validators = ScriptValidators::from_metadata(metadata);
+        if let Some(imported) = self.imported_scripts.borrow().get(url) {
+            if Some(&imported.validators) == validators.as_ref() {
+                script.set(imported.script.get());
+                return Ok(());
+            }
+        }
+
+        let source = String::from_utf8_lossy(bytes);
+        let filename = CString::new(url.as_str()).unwrap();
+        let options = CompileOptionsWrapper::new(*cx, filename.as_ptr(), 1);
+        rooted!(in(*cx) let mut compiled = ptr::null_mut::<JSScript>());
+        let succeeded = unsafe {
+            CompileUtf8(
+                *cx,
+                options.ptr,
+                source.as_ptr() as *const _,
+                source.len() as libc::size_t,
+                compiled.handle_mut().into(),
+            )
+        };
+        if !succeeded {
+            return Err(Error::JSFailed);
+        }
+        script.set(compiled.get());
+
+        // A changed script, or one that cannot be validated, invalidates the
+        // script compiled for a previous import.
+        let mut imported_scripts = self.imported_scripts.borrow_mut();
+        match validators {
+            Some(validators) => {
+                imported_scripts.insert(
+                    url.clone(),
+                    ImportedScript {
+                        validators,
+                        script: Heap::boxed(compiled.get()),
+                    },
+                );
+            },
+            None => {
+                imported_scripts.remove(url);
+            },
+        }
+        Ok(())
+    }
+
     #[allow(unsafe_code)]
     pub fn execute_script(&self, source: DOMString) {
         let _aes = AutoEntryScript::new(self.upcast());
@@ -406,12 +725,15 @@ impl WorkerGlobalScope {
     pub fn script_chan(&self) -> Box<dyn ScriptChan + Send> {
         let dedicated = self.downcast::<DedicatedWorkerGlobalScope>();
         let service_worker = self.downcast::<ServiceWorkerGlobalScope>();
+        let shared_worker = self.downcast::<SharedWorkerGlobalScope>();
         if let Some(dedicated) = dedicated {
             return dedicated.script_chan();
         } else if let Some(service_worker) = service_worker {
             return service_worker.script_chan();
+        } else if let Some(shared_worker) = shared_worker {
+            return shared_worker.script_chan();
         } else {
-            panic!("need to implement a sender for SharedWorker")
+            unreachable!()
         }
     }
 
@@ -435,6 +757,10 @@ impl WorkerGlobalScope {
         PortMessageQueue(self.script_chan(), self.pipeline_id())
     }
 
+    pub fn posted_task_source(&self) -> PostedTaskSource {
+        PostedTaskSource(self.script_chan(), self.pipeline_id())
+    }
+
     pub fn timer_task_source(&self) -> TimerTaskSource {
         TimerTaskSource(self.script_chan(), self.pipeline_id())
     }
@@ -449,10 +775,13 @@ impl WorkerGlobalScope {
 
     pub fn new_script_pair(&self) -> (Box<dyn ScriptChan + Send>, Box<dyn ScriptPort + Send>) {
         let dedicated = self.downcast::<DedicatedWorkerGlobalScope>();
+        let shared_worker = self.downcast::<SharedWorkerGlobalScope>();
         if let Some(dedicated) = dedicated {
             return dedicated.new_script_pair();
+        } else if let Some(shared_worker) = shared_worker {
+            return shared_worker.new_script_pair();
         } else {
-            panic!("need to implement a sender for SharedWorker/ServiceWorker")
+            panic!("need to implement a sender for ServiceWorker")
         }
     }
 
@@ -468,9 +797,44 @@ impl WorkerGlobalScope {
         }
     }
 
+    /// Handle a message from the devtools, which all kinds of workers support alike.
+    pub fn handle_devtools_message(&self, msg: DevtoolScriptControlMsg) {
+        match msg {
+            DevtoolScriptControlMsg::EvaluateJS(_pipe_id, string, sender) => {
+                devtools::handle_evaluate_js(self.upcast(), string, sender)
+            },
+            DevtoolScriptControlMsg::GetCachedMessages(_pipe_id, message_types, sender) => {
+                devtools::handle_get_cached_worker_messages(self.upcast(), message_types, sender)
+            },
+            DevtoolScriptControlMsg::WantsLiveNotifications(_pipe_id, bool_val) => {
+                devtools::handle_wants_live_notifications(self.upcast(), bool_val)
+            },
+            _ => debug!("got an unusable devtools control message inside the worker!"),
+        }
+    }
+
+    /// Release what the worker holds outside of its own thread,
+    /// once its event loop has been destroyed.
+    pub fn release_resources(&self) {
+        let global = self.upcast::<GlobalScope>();
+        // Tell the constellation to drop the sender to our
+        // message-port router, and to disentangle our ports.
+        global.remove_message_ports_router();
+        // Tell the constellation to drop the sender to our
+        // broadcast router, if there is any.
+        global.remove_broadcast_channel_router();
+        // Close the dedicated workers created by this worker.
+        global.close_workers();
+        // Revoke the Blob URLs created by this worker.
+        global.revoke_blob_urls();
+    }
+
     pub fn close(&self) {
         if let Some(ref closing) = self.closing {
             closing.store(true, Ordering::SeqCst);
         }
+        // The tasks of the event sources would be discarded from now on,
+        // so their connections are aborted rather than reestablished.
+        self.upcast::<GlobalScope>().close_event_sources();
     }
 }
@@ -10,6 +10,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::gpu::GPU;
 use crate::dom::navigatorinfo;
 use crate::dom::permissions::Permissions;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use dom_struct::dom_struct;
 
@@ -19,6 +20,7 @@ pub struct WorkerNavigator {
     reflector_: Reflector,
     permissions: MutNullableDom<Permissions>,
     gpu: MutNullableDom<GPU>,
+    storage: MutNullableDom<StorageManager>,
 }
 
 impl WorkerNavigator {
@@ -27,6 +29,7 @@ impl WorkerNavigator {
             reflector_: Reflector::new(),
             permissions: Default::default(),
             gpu: Default::default(),
+            storage: Default::default(),
         }
     }
 
@@ -95,6 +98,11 @@ impl WorkerNavigatorMethods for WorkerNavigator {
         navigatorinfo::Language()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-hardwareconcurrency
+    fn HardwareConcurrency(&self) -> u64 {
+        navigatorinfo::HardwareConcurrency()
+    }
+
     // https://w3c.github.io/permissions/#navigator-and-workernavigator-extension
     fn Permissions(&self) -> DomRoot<Permissions> {
         self.permissions
@@ -105,4 +113,9 @@ impl WorkerNavigatorMethods for WorkerNavigator {
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
 }
@@ -81,7 +81,7 @@ pub struct Worklet {
 }
 
 impl Worklet {
-    fn new_inherited(window: &Window, global_type: WorkletGlobalScopeType) -> Worklet {
+    pub fn new_inherited(window: &Window, global_type: WorkletGlobalScopeType) -> Worklet {
         Worklet {
             reflector: Reflector::new(),
             window: Dom::from_ref(window),
@@ -105,7 +105,7 @@ impl Worklet {
 
     #[allow(dead_code)]
     pub fn worklet_global_scope_type(&self) -> WorkletGlobalScopeType {
-        self.global_type
+        self.global_type.clone()
     }
 }
 
@@ -141,7 +141,7 @@ impl WorkletMethods for Worklet {
         pool.fetch_and_invoke_a_worklet_script(
             global.pipeline_id(),
             self.worklet_id,
-            self.global_type,
+            self.global_type.clone(),
             self.window.origin().immutable().clone(),
             global.api_base_url(),
             module_url_record,
@@ -315,7 +315,7 @@ impl WorkletThreadPool {
             let _ = sender.send(WorkletControl::FetchAndInvokeAWorkletScript {
                 pipeline_id: pipeline_id,
                 worklet_id: worklet_id,
-                global_type: global_type,
+                global_type: global_type.clone(),
                 origin: origin.clone(),
                 base_url: base_url.clone(),
                 script_url: script_url.clone(),
@@ -346,6 +346,12 @@ impl WorkletThreadPool {
         receiver.recv().expect("Test worklet has died?")
     }
 
+    /// An executor of tasks for the given worklet, for tasks which are
+    /// scheduled from outside of the worklet thread pool.
+    pub(crate) fn executor(&self, worklet_id: WorkletId) -> WorkletExecutor {
+        WorkletExecutor::new(worklet_id, self.primary_sender.clone())
+    }
+
     fn wake_threads(&self) {
         // If any of the threads are blocked waiting on data, wake them up.
         let _ = self.cold_backup_sender.send(WorkletData::WakeUp);
@@ -2,6 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::audioworklet::AudioWorkletProcessorNames;
+use crate::dom::audioworkletglobalscope::AudioWorkletGlobalScope;
+use crate::dom::audioworkletglobalscope::AudioWorkletTask;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
@@ -129,6 +132,10 @@ impl WorkletGlobalScope {
                 Some(global) => global.perform_a_worklet_task(task),
                 None => warn!("This is not a paint worklet."),
             },
+            WorkletTask::Audio(task) => match self.downcast::<AudioWorkletGlobalScope>() {
+                Some(global) => global.perform_a_worklet_task(task),
+                None => warn!("This is not an audio worklet."),
+            },
         }
     }
 }
@@ -159,12 +166,15 @@ pub struct WorkletGlobalScopeInit {
 }
 
 /// <https://drafts.css-houdini.org/worklets/#worklet-global-scope-type>
-#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf)]
 pub enum WorkletGlobalScopeType {
     /// A servo-specific testing worklet
     Test,
     /// A paint worklet
     Paint,
+    /// An audio worklet, for an audio context with the given sample rate,
+    /// and the names of the processors its modules register
+    Audio(f32, AudioWorkletProcessorNames),
 }
 
 impl WorkletGlobalScopeType {
@@ -192,6 +202,17 @@ impl WorkletGlobalScopeType {
                 executor,
                 init,
             )),
+            WorkletGlobalScopeType::Audio(sample_rate, ref processor_names) => {
+                DomRoot::upcast(AudioWorkletGlobalScope::new(
+                    runtime,
+                    pipeline_id,
+                    base_url,
+                    executor,
+                    init,
+                    sample_rate,
+                    processor_names.clone(),
+                ))
+            },
         }
     }
 }
@@ -200,4 +221,5 @@ impl WorkletGlobalScopeType {
 pub enum WorkletTask {
     Test(TestWorkletTask),
     Paint(PaintWorkletTask),
+    Audio(AudioWorkletTask),
 }
@@ -0,0 +1,598 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::QueuingStrategy;
+use crate::dom::bindings::codegen::Bindings::WritableStreamBinding::{
+    self, UnderlyingSink, WritableStreamMethods,
+};
+use crate::dom::bindings::conversions::ConversionResult;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageport::{CrossRealmTransform, MessagePort};
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestream::{extract_high_water_mark, ReadableStream};
+use crate::dom::writablestreamdefaultcontroller::{
+    SinkAlgorithms, WritableStreamDefaultController,
+};
+use crate::dom::writablestreamdefaultwriter::WritableStreamDefaultWriter;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext as RawJSContext, JSObject, MutableHandleObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::HandleValue;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+/// <https://streams.spec.whatwg.org/#writablestream-state>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum WritableStreamState {
+    Writable,
+    Closed,
+    Erroring,
+    Errored,
+}
+
+/// <https://streams.spec.whatwg.org/#pending-abort-request>
+#[derive(JSTraceable, MallocSizeOf)]
+struct PendingAbortRequest {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    #[ignore_malloc_size_of = "mozjs"]
+    reason: Box<Heap<JSVal>>,
+    was_already_erroring: bool,
+}
+
+// https://streams.spec.whatwg.org/#ws-class
+#[dom_struct]
+pub struct WritableStream {
+    reflector_: Reflector,
+    state: Cell<WritableStreamState>,
+    /// Undefined until the stream starts erroring.
+    #[ignore_malloc_size_of = "mozjs"]
+    stored_error: Heap<JSVal>,
+    backpressure: Cell<bool>,
+    controller: MutNullableDom<WritableStreamDefaultController>,
+    writer: MutNullableDom<WritableStreamDefaultWriter>,
+    #[ignore_malloc_size_of = "Rc"]
+    write_requests: DomRefCell<VecDeque<Rc<Promise>>>,
+    #[ignore_malloc_size_of = "Rc"]
+    in_flight_write_request: DomRefCell<Option<Rc<Promise>>>,
+    #[ignore_malloc_size_of = "Rc"]
+    close_request: DomRefCell<Option<Rc<Promise>>>,
+    #[ignore_malloc_size_of = "Rc"]
+    in_flight_close_request: DomRefCell<Option<Rc<Promise>>>,
+    pending_abort_request: DomRefCell<Option<PendingAbortRequest>>,
+}
+
+impl WritableStream {
+    fn new_inherited() -> WritableStream {
+        WritableStream {
+            reflector_: Reflector::new(),
+            state: Cell::new(WritableStreamState::Writable),
+            stored_error: Heap::default(),
+            backpressure: Cell::new(false),
+            controller: Default::default(),
+            writer: Default::default(),
+            write_requests: DomRefCell::new(VecDeque::new()),
+            in_flight_write_request: DomRefCell::new(None),
+            close_request: DomRefCell::new(None),
+            in_flight_close_request: DomRefCell::new(None),
+            pending_abort_request: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<WritableStream> {
+        reflect_dom_object(
+            Box::new(WritableStream::new_inherited()),
+            global,
+            WritableStreamBinding::Wrap,
+        )
+    }
+
+    /// Creates a stream whose chunks are posted to `port`, for another realm,
+    /// <https://streams.spec.whatwg.org/#abstract-opdef-setupcrossrealmtransformwritable>
+    pub fn new_cross_realm(global: &GlobalScope, port: &MessagePort) -> DomRoot<WritableStream> {
+        // Steps 1, 7 and 11-12.
+        let stream = WritableStream::new(global);
+        let start_promise = Promise::new(global);
+        start_promise.resolve_native(&());
+        WritableStreamDefaultController::set_up_with_start_promise(
+            global,
+            &stream,
+            &start_promise,
+            SinkAlgorithms::CrossRealm {
+                port: Dom::from_ref(port),
+            },
+            None,
+            1.,
+        );
+
+        // Steps 2-6.
+        port.set_cross_realm_transform(CrossRealmTransform::Writable {
+            controller: Dom::from_ref(&*stream.controller()),
+            backpressure_promise: None,
+        });
+        stream
+    }
+
+    // https://streams.spec.whatwg.org/#ws-constructor
+    pub fn Constructor(
+        cx: JSContext,
+        global: &GlobalScope,
+        underlying_sink: Option<*mut JSObject>,
+        strategy: &QueuingStrategy,
+    ) -> Fallible<DomRoot<WritableStream>> {
+        // Step 1.
+        rooted!(in(*cx) let sink = underlying_sink.unwrap_or(ptr::null_mut()));
+        rooted!(in(*cx) let sink_value = if sink.is_null() {
+            UndefinedValue()
+        } else {
+            ObjectValue(sink.get())
+        });
+
+        // Step 2.
+        let sink_dict = match UnderlyingSink::new(cx, sink_value.handle()) {
+            Ok(ConversionResult::Success(dict)) => dict,
+            Ok(ConversionResult::Failure(error)) => {
+                return Err(Error::Type(error.into_owned()));
+            },
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        // Step 3.
+        if !sink_dict.type_.get().is_undefined() {
+            return Err(Error::Range(
+                "Invalid type for an underlying sink".to_owned(),
+            ));
+        }
+
+        // Steps 4-6.
+        let stream = WritableStream::new(global);
+        let high_water_mark = extract_high_water_mark(strategy, 1.)?;
+
+        // Step 7.
+        WritableStreamDefaultController::set_up_from_underlying_sink(
+            cx,
+            global,
+            &stream,
+            sink.handle(),
+            &sink_dict,
+            strategy.size.clone(),
+            high_water_mark,
+        )?;
+        Ok(stream)
+    }
+
+    pub fn state(&self) -> WritableStreamState {
+        self.state.get()
+    }
+
+    pub fn stored_error(&self) -> JSVal {
+        self.stored_error.get()
+    }
+
+    pub fn backpressure(&self) -> bool {
+        self.backpressure.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#is-writable-stream-locked>
+    pub fn is_locked(&self) -> bool {
+        self.writer.get().is_some()
+    }
+
+    pub fn controller(&self) -> DomRoot<WritableStreamDefaultController> {
+        self.controller
+            .get()
+            .expect("A stream always has a controller once it is set up.")
+    }
+
+    pub fn set_controller(&self, controller: &WritableStreamDefaultController) {
+        self.controller.set(Some(controller));
+    }
+
+    pub fn writer(&self) -> Option<DomRoot<WritableStreamDefaultWriter>> {
+        self.writer.get()
+    }
+
+    pub fn set_writer(&self, writer: Option<&WritableStreamDefaultWriter>) {
+        self.writer.set(writer);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-abort>
+    pub fn abort(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Steps 1-3.
+        let state = self.state.get();
+        if state == WritableStreamState::Closed || state == WritableStreamState::Errored {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Step 4.
+        if let Some(ref request) = *self.pending_abort_request.borrow() {
+            return request.promise.clone();
+        }
+
+        // Steps 6-7.
+        let was_already_erroring = state == WritableStreamState::Erroring;
+        rooted!(in(*cx) let mut reason = reason.get());
+        if was_already_erroring {
+            reason.set(UndefinedValue());
+        }
+
+        // Steps 8-9.
+        *self.pending_abort_request.borrow_mut() = Some(PendingAbortRequest {
+            promise: promise.clone(),
+            reason: Heap::boxed(reason.get()),
+            was_already_erroring,
+        });
+
+        // Step 10.
+        if !was_already_erroring {
+            self.start_erroring(reason.handle());
+        }
+
+        // Step 11.
+        promise
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-close>
+    pub fn close(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Steps 1-2.
+        let state = self.state.get();
+        if state == WritableStreamState::Closed || state == WritableStreamState::Errored {
+            promise.reject_error(Error::Type("The stream is closed".to_owned()));
+            return promise;
+        }
+
+        // Steps 5-6.
+        *self.close_request.borrow_mut() = Some(promise.clone());
+
+        // Steps 7-8.
+        if let Some(writer) = self.writer.get() {
+            if self.backpressure.get() && state == WritableStreamState::Writable {
+                writer.resolve_ready_promise();
+            }
+        }
+
+        // Steps 9-10.
+        self.controller().close();
+        promise
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-add-write-request>
+    pub fn add_write_request(&self) -> Rc<Promise> {
+        assert!(self.is_locked());
+        assert_eq!(self.state.get(), WritableStreamState::Writable);
+        let promise = Promise::new(&self.global());
+        self.write_requests.borrow_mut().push_back(promise.clone());
+        promise
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-deal-with-rejection>
+    pub fn deal_with_rejection(&self, error: HandleValue) {
+        if self.state.get() == WritableStreamState::Writable {
+            return self.start_erroring(error);
+        }
+        assert_eq!(self.state.get(), WritableStreamState::Erroring);
+        self.finish_erroring();
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-start-erroring>
+    pub fn start_erroring(&self, reason: HandleValue) {
+        // Steps 1-2.
+        assert!(self.stored_error.get().is_undefined());
+        assert_eq!(self.state.get(), WritableStreamState::Writable);
+
+        // Step 5.
+        self.state.set(WritableStreamState::Erroring);
+        self.stored_error.set(reason.get());
+
+        // Step 6.
+        if let Some(writer) = self.writer.get() {
+            writer.ensure_ready_promise_rejected(reason);
+        }
+
+        // Step 7.
+        if !self.has_operation_marked_in_flight() && self.controller().started() {
+            self.finish_erroring();
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-finish-erroring>
+    pub fn finish_erroring(&self) {
+        // Steps 1-3.
+        assert_eq!(self.state.get(), WritableStreamState::Erroring);
+        assert!(!self.has_operation_marked_in_flight());
+        self.state.set(WritableStreamState::Errored);
+
+        // Step 4.
+        let controller = self.controller();
+        controller.error_steps();
+
+        // Steps 5-7.
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let stored_error = self.stored_error.get());
+        let write_requests = mem::replace(&mut *self.write_requests.borrow_mut(), VecDeque::new());
+        for request in write_requests {
+            request.reject(cx, stored_error.handle());
+        }
+
+        // Steps 8-10.
+        let request = match self.pending_abort_request.borrow_mut().take() {
+            Some(request) => request,
+            None => return self.reject_close_and_closed_promise_if_needed(),
+        };
+
+        // Step 11.
+        if request.was_already_erroring {
+            request.promise.reject(cx, stored_error.handle());
+            return self.reject_close_and_closed_promise_if_needed();
+        }
+
+        // Steps 12-14.
+        rooted!(in(*cx) let reason = request.reason.get());
+        let abort_promise = controller.abort_steps(cx, reason.handle());
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(AbortStepsSettled::new(self, &request.promise, true)),
+            Some(AbortStepsSettled::new(self, &request.promise, false)),
+        );
+        abort_promise.append_native_handler(&handler);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-finish-in-flight-write>
+    pub fn finish_in_flight_write(&self) {
+        let request = self
+            .in_flight_write_request
+            .borrow_mut()
+            .take()
+            .expect("There is a write in flight.");
+        request.resolve_native(&());
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-finish-in-flight-write-with-error>
+    pub fn finish_in_flight_write_with_error(&self, error: HandleValue) {
+        let request = self
+            .in_flight_write_request
+            .borrow_mut()
+            .take()
+            .expect("There is a write in flight.");
+        request.reject(self.global().get_cx(), error);
+        self.deal_with_rejection(error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-finish-in-flight-close>
+    pub fn finish_in_flight_close(&self) {
+        // Steps 1-3.
+        let request = self
+            .in_flight_close_request
+            .borrow_mut()
+            .take()
+            .expect("There is a close in flight.");
+        request.resolve_native(&());
+
+        // Steps 4-5.
+        if self.state.get() == WritableStreamState::Erroring {
+            self.stored_error.set(UndefinedValue());
+            if let Some(request) = self.pending_abort_request.borrow_mut().take() {
+                request.promise.resolve_native(&());
+            }
+        }
+
+        // Steps 6-7.
+        self.state.set(WritableStreamState::Closed);
+        if let Some(writer) = self.writer.get() {
+            writer.resolve_closed_promise();
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-finish-in-flight-close-with-error>
+    pub fn finish_in_flight_close_with_error(&self, error: HandleValue) {
+        let cx = self.global().get_cx();
+
+        // Steps 1-3.
+        let request = self
+            .in_flight_close_request
+            .borrow_mut()
+            .take()
+            .expect("There is a close in flight.");
+        request.reject(cx, error);
+
+        // Step 5.
+        if let Some(request) = self.pending_abort_request.borrow_mut().take() {
+            request.promise.reject(cx, error);
+        }
+
+        // Step 6.
+        self.deal_with_rejection(error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-close-queued-or-in-flight>
+    pub fn close_queued_or_in_flight(&self) -> bool {
+        self.close_request.borrow().is_some() || self.in_flight_close_request.borrow().is_some()
+    }
+
+    pub fn has_write_in_flight(&self) -> bool {
+        self.in_flight_write_request.borrow().is_some()
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-has-operation-marked-in-flight>
+    fn has_operation_marked_in_flight(&self) -> bool {
+        self.in_flight_write_request.borrow().is_some() ||
+            self.in_flight_close_request.borrow().is_some()
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-mark-close-request-in-flight>
+    pub fn mark_close_request_in_flight(&self) {
+        let request = self.close_request.borrow_mut().take();
+        assert!(request.is_some());
+        *self.in_flight_close_request.borrow_mut() = request;
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-mark-first-write-request-in-flight>
+    pub fn mark_first_write_request_in_flight(&self) {
+        let request = self.write_requests.borrow_mut().pop_front();
+        assert!(request.is_some());
+        *self.in_flight_write_request.borrow_mut() = request;
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-reject-close-and-closed-promise-if-needed>
+    fn reject_close_and_closed_promise_if_needed(&self) {
+        assert_eq!(self.state.get(), WritableStreamState::Errored);
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let stored_error = self.stored_error.get());
+
+        // Step 2.
+        if let Some(request) = self.close_request.borrow_mut().take() {
+            request.reject(cx, stored_error.handle());
+        }
+
+        // Steps 3-4.
+        if let Some(writer) = self.writer.get() {
+            writer.reject_closed_promise(stored_error.handle());
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-update-backpressure>
+    pub fn update_backpressure(&self, backpressure: bool) {
+        assert_eq!(self.state.get(), WritableStreamState::Writable);
+        assert!(!self.close_queued_or_in_flight());
+        if let Some(writer) = self.writer.get() {
+            if backpressure != self.backpressure.get() {
+                if backpressure {
+                    writer.reset_ready_promise();
+                } else {
+                    writer.resolve_ready_promise();
+                }
+            }
+        }
+        self.backpressure.set(backpressure);
+    }
+
+    fn abort_steps_settled(&self, promise: &Promise, fulfilled: bool, value: HandleValue) {
+        if fulfilled {
+            promise.resolve_native(&());
+        } else {
+            promise.reject(self.global().get_cx(), value);
+        }
+        self.reject_close_and_closed_promise_if_needed();
+    }
+}
+
+impl Transferable for WritableStream {
+    /// <https://streams.spec.whatwg.org/#ws-transfer>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        // Step 1.
+        if self.is_locked() {
+            return Err(());
+        }
+
+        // Steps 2-3.
+        let global = self.global();
+        let (port1, port2) = MessagePort::new_entangled(&global);
+
+        // Steps 4-5.
+        let readable = ReadableStream::new_cross_realm(&global, &port1);
+
+        // Steps 6-7.
+        readable.pipe_to(self, false, false, false, None);
+
+        // Step 8.
+        port2.transfer(sc_holder)
+    }
+
+    /// <https://streams.spec.whatwg.org/#ws-transfer>
+    fn transfer_receive(
+        owner: &DomRoot<GlobalScope>,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        // Steps 1-2.
+        let port = MessagePort::receive_transferred(owner, sc_holder, extra_data);
+
+        // Step 3.
+        let stream = WritableStream::new_cross_realm(owner, &port);
+        return_object.set(stream.reflector().rootable().get());
+        Ok(())
+    }
+}
+
+impl WritableStreamMethods for WritableStream {
+    // https://streams.spec.whatwg.org/#ws-locked
+    fn Locked(&self) -> bool {
+        self.is_locked()
+    }
+
+    // https://streams.spec.whatwg.org/#ws-abort
+    fn Abort(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        if self.is_locked() {
+            let promise = Promise::new(&self.global());
+            promise.reject_error(Error::Type("The stream is locked".to_owned()));
+            return promise;
+        }
+        self.abort(cx, reason)
+    }
+
+    // https://streams.spec.whatwg.org/#ws-close
+    fn Close(&self) -> Rc<Promise> {
+        if self.is_locked() {
+            let promise = Promise::new(&self.global());
+            promise.reject_error(Error::Type("The stream is locked".to_owned()));
+            return promise;
+        }
+        if self.close_queued_or_in_flight() {
+            let promise = Promise::new(&self.global());
+            promise.reject_error(Error::Type("The stream is already closing".to_owned()));
+            return promise;
+        }
+        self.close()
+    }
+
+    // https://streams.spec.whatwg.org/#ws-get-writer
+    fn GetWriter(&self) -> Fallible<DomRoot<WritableStreamDefaultWriter>> {
+        WritableStreamDefaultWriter::new(&self.global(), self)
+    }
+}
+
+/// Settles the promise of a pending abort request once the abort algorithm
+/// of the underlying sink settles.
+#[derive(JSTraceable, MallocSizeOf)]
+struct AbortStepsSettled {
+    stream: Dom<WritableStream>,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    fulfilled: bool,
+}
+
+impl AbortStepsSettled {
+    fn new(stream: &WritableStream, promise: &Rc<Promise>, fulfilled: bool) -> Box<dyn Callback> {
+        Box::new(AbortStepsSettled {
+            stream: Dom::from_ref(stream),
+            promise: promise.clone(),
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for AbortStepsSettled {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        self.stream
+            .abort_steps_settled(&self.promise, self.fulfilled, v);
+    }
+}
@@ -0,0 +1,526 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::{CallbackContainer, ExceptionHandling};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::QueuingStrategySize;
+use crate::dom::bindings::codegen::Bindings::WritableStreamBinding::{
+    UnderlyingSink, UnderlyingSinkAbortCallback, UnderlyingSinkCloseCallback,
+    UnderlyingSinkWriteCallback,
+};
+use crate::dom::bindings::codegen::Bindings::WritableStreamDefaultControllerBinding::{
+    self, WritableStreamDefaultControllerMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageport::MessagePort;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestreamdefaultcontroller::{invoke_method, promise_from_invoke};
+use crate::dom::transformstream::TransformStream;
+use crate::dom::writablestream::{WritableStream, WritableStreamState};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext as RawJSContext, JSObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    JS_ClearPendingException, JS_GetPendingException, JS_SetPendingException,
+};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// The write, close and abort algorithms of a controller, which come from
+/// its underlying sink.
+#[unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum SinkAlgorithms {
+    /// The methods of the underlying sink object of the constructor.
+    Js {
+        #[ignore_malloc_size_of = "Rc"]
+        write: Option<Rc<UnderlyingSinkWriteCallback>>,
+        #[ignore_malloc_size_of = "Rc"]
+        close: Option<Rc<UnderlyingSinkCloseCallback>>,
+        #[ignore_malloc_size_of = "Rc"]
+        abort: Option<Rc<UnderlyingSinkAbortCallback>>,
+    },
+    /// The writable side of a TransformStream.
+    Transform { stream: Dom<TransformStream> },
+    /// A transfer-received stream, whose chunks are posted to `port`.
+    CrossRealm { port: Dom<MessagePort> },
+    /// Cleared algorithms.
+    None,
+}
+
+/// An entry in the queue of a controller.
+#[derive(JSTraceable, MallocSizeOf)]
+enum QueueEntry {
+    /// A chunk, with the size that the queuing strategy gave it.
+    Chunk {
+        #[ignore_malloc_size_of = "mozjs"]
+        value: Box<Heap<JSVal>>,
+        size: f64,
+    },
+    /// <https://streams.spec.whatwg.org/#close-sentinel>
+    Close,
+}
+
+/// The promises that a controller reacts to.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+enum SinkStep {
+    Start,
+    Write,
+    Close,
+}
+
+// https://streams.spec.whatwg.org/#ws-default-controller-class
+#[dom_struct]
+pub struct WritableStreamDefaultController {
+    reflector_: Reflector,
+    stream: Dom<WritableStream>,
+    /// The `this` of the underlying sink methods, or null.
+    #[ignore_malloc_size_of = "mozjs"]
+    underlying_sink: Heap<*mut JSObject>,
+    algorithms: DomRefCell<SinkAlgorithms>,
+    /// <https://streams.spec.whatwg.org/#writablestreamdefaultcontroller-strategysizealgorithm>
+    #[ignore_malloc_size_of = "Rc"]
+    strategy_size_algorithm: DomRefCell<Option<Rc<QueuingStrategySize>>>,
+    strategy_hwm: f64,
+    queue: DomRefCell<VecDeque<QueueEntry>>,
+    queue_total_size: Cell<f64>,
+    started: Cell<bool>,
+}
+
+impl WritableStreamDefaultController {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        stream: &WritableStream,
+        algorithms: SinkAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) -> WritableStreamDefaultController {
+        WritableStreamDefaultController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            underlying_sink: Heap::default(),
+            algorithms: DomRefCell::new(algorithms),
+            strategy_size_algorithm: DomRefCell::new(size),
+            strategy_hwm,
+            queue: DomRefCell::new(VecDeque::new()),
+            queue_total_size: Cell::new(0.),
+            started: Cell::new(false),
+        }
+    }
+
+    /// Steps 1-14 of <https://streams.spec.whatwg.org/#set-up-writable-stream-default-controller>.
+    #[allow(unrooted_must_root)]
+    fn new(
+        global: &GlobalScope,
+        stream: &WritableStream,
+        algorithms: SinkAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) -> DomRoot<WritableStreamDefaultController> {
+        let controller = reflect_dom_object(
+            Box::new(WritableStreamDefaultController::new_inherited(
+                stream,
+                algorithms,
+                size,
+                strategy_hwm,
+            )),
+            global,
+            WritableStreamDefaultControllerBinding::Wrap,
+        );
+        stream.set_controller(&controller);
+        stream.update_backpressure(controller.get_backpressure());
+        controller
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-writable-stream-default-controller-from-underlying-sink>
+    #[allow(unsafe_code)]
+    pub fn set_up_from_underlying_sink(
+        cx: JSContext,
+        global: &GlobalScope,
+        stream: &WritableStream,
+        underlying_sink: HandleObject,
+        sink_dict: &UnderlyingSink,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) -> ErrorResult {
+        // Steps 1-6.
+        let algorithms = SinkAlgorithms::Js {
+            write: sink_dict.write.clone(),
+            close: sink_dict.close.clone(),
+            abort: sink_dict.abort.clone(),
+        };
+        let controller =
+            WritableStreamDefaultController::new(global, stream, algorithms, size, strategy_hwm);
+        controller.underlying_sink.set(underlying_sink.get());
+
+        // Step 15 of https://streams.spec.whatwg.org/#set-up-writable-stream-default-controller
+        rooted!(in(*cx) let mut start_result = UndefinedValue());
+        if let Some(ref start) = sink_dict.start {
+            let arguments = [ObjectValue(controller.reflector().get_jsobject().get())];
+            if !controller.invoke(&**start, &arguments, start_result.handle_mut()) {
+                unsafe { JS_SetPendingException(*cx, start_result.handle()) };
+                return Err(Error::JSFailed);
+            }
+        }
+
+        // Steps 16-18.
+        let start_promise = Promise::new_resolved(global, cx, start_result.handle())?;
+        controller.react(&start_promise, SinkStep::Start);
+        Ok(())
+    }
+
+    /// Sets up the controller of a stream whose algorithms are implemented
+    /// natively, which starts once `start_promise` settles.
+    #[allow(unrooted_must_root)]
+    pub fn set_up_with_start_promise(
+        global: &GlobalScope,
+        stream: &WritableStream,
+        start_promise: &Promise,
+        algorithms: SinkAlgorithms,
+        size: Option<Rc<QueuingStrategySize>>,
+        strategy_hwm: f64,
+    ) {
+        let controller =
+            WritableStreamDefaultController::new(global, stream, algorithms, size, strategy_hwm);
+        controller.react(start_promise, SinkStep::Start);
+    }
+
+    fn invoke<T: CallbackContainer>(
+        &self,
+        method: &T,
+        arguments: &[JSVal],
+        rval: MutableHandleValue,
+    ) -> bool {
+        invoke_method(self.underlying_sink.get(), method, arguments, rval)
+    }
+
+    fn react(&self, promise: &Promise, step: SinkStep) {
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(SinkAlgorithmSettled::new(self, step, true)),
+            Some(SinkAlgorithmSettled::new(self, step, false)),
+        );
+        promise.append_native_handler(&handler);
+    }
+
+    pub fn started(&self) -> bool {
+        self.started.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-clear-algorithms>
+    fn clear_algorithms(&self) {
+        *self.algorithms.borrow_mut() = SinkAlgorithms::None;
+        self.strategy_size_algorithm.borrow_mut().take();
+    }
+
+    /// <https://streams.spec.whatwg.org/#ws-default-controller-private-error>
+    pub fn error_steps(&self) {
+        self.queue.borrow_mut().clear();
+        self.queue_total_size.set(0.);
+    }
+
+    /// <https://streams.spec.whatwg.org/#ws-default-controller-private-abort>
+    pub fn abort_steps(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+        let (abort, transform, port) = match *self.algorithms.borrow() {
+            SinkAlgorithms::Js { ref abort, .. } => (abort.clone(), None, None),
+            SinkAlgorithms::Transform { ref stream } => {
+                (None, Some(DomRoot::from_ref(&**stream)), None)
+            },
+            SinkAlgorithms::CrossRealm { ref port } => {
+                (None, None, Some(DomRoot::from_ref(&**port)))
+            },
+            SinkAlgorithms::None => (None, None, None),
+        };
+        let promise = match (transform, port) {
+            (Some(transform), _) => transform.sink_abort_algorithm(reason),
+            (None, Some(port)) => port.cross_realm_cancel(reason),
+            (None, None) => {
+                rooted!(in(*cx) let mut result = UndefinedValue());
+                let succeeded = abort.map_or(true, |abort| {
+                    self.invoke(&*abort, &[reason.get()], result.handle_mut())
+                });
+                promise_from_invoke(&global, succeeded, result.handle())
+            },
+        };
+        self.clear_algorithms();
+        promise
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-close>
+    pub fn close(&self) {
+        self.queue.borrow_mut().push_back(QueueEntry::Close);
+        self.advance_queue_if_needed();
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-get-chunk-size>
+    #[allow(unsafe_code)]
+    pub fn get_chunk_size(&self, cx: JSContext, chunk: HandleValue) -> f64 {
+        let size_algorithm = match self.strategy_size_algorithm.borrow().clone() {
+            Some(size_algorithm) => size_algorithm,
+            None => return 1.,
+        };
+        match size_algorithm.Call__(chunk, ExceptionHandling::Rethrow) {
+            Ok(size) => size,
+            Err(_) => {
+                rooted!(in(*cx) let mut error = UndefinedValue());
+                unsafe {
+                    JS_GetPendingException(*cx, error.handle_mut());
+                    JS_ClearPendingException(*cx);
+                }
+                self.error_if_needed(error.handle());
+                1.
+            },
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-get-desired-size>
+    pub fn desired_size(&self) -> f64 {
+        self.strategy_hwm - self.queue_total_size.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-write>
+    #[allow(unsafe_code)]
+    pub fn write(&self, cx: JSContext, chunk: HandleValue, chunk_size: f64) {
+        // Steps 1-2, https://streams.spec.whatwg.org/#enqueue-value-with-size
+        if !chunk_size.is_finite() || chunk_size < 0. {
+            let error = Error::Range("The chunk size must be a non-negative number".to_owned());
+            rooted!(in(*cx) let mut error_value = UndefinedValue());
+            unsafe { error.to_jsval(*cx, &self.global(), error_value.handle_mut()) };
+            return self.error_if_needed(error_value.handle());
+        }
+        self.queue.borrow_mut().push_back(QueueEntry::Chunk {
+            value: Heap::boxed(chunk.get()),
+            size: chunk_size,
+        });
+        self.queue_total_size
+            .set(self.queue_total_size.get() + chunk_size);
+
+        // Step 4.
+        if !self.stream.close_queued_or_in_flight() &&
+            self.stream.state() == WritableStreamState::Writable
+        {
+            self.stream.update_backpressure(self.get_backpressure());
+        }
+
+        // Step 5.
+        self.advance_queue_if_needed();
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-advance-queue-if-needed>
+    fn advance_queue_if_needed(&self) {
+        // Steps 2-3.
+        if !self.started.get() || self.stream.has_write_in_flight() {
+            return;
+        }
+
+        // Steps 4-6.
+        let state = self.stream.state();
+        assert!(state != WritableStreamState::Closed && state != WritableStreamState::Errored);
+        if state == WritableStreamState::Erroring {
+            return self.stream.finish_erroring();
+        }
+
+        // Steps 7-9.
+        let cx = self.global().get_cx();
+        rooted!(in(*cx) let mut chunk = UndefinedValue());
+        let is_close = match self.queue.borrow().front() {
+            None => return,
+            Some(&QueueEntry::Close) => true,
+            Some(&QueueEntry::Chunk { ref value, .. }) => {
+                chunk.set(value.get());
+                false
+            },
+        };
+        if is_close {
+            self.process_close();
+        } else {
+            self.process_write(chunk.handle());
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#dequeue-value>
+    fn dequeue(&self) {
+        if let Some(QueueEntry::Chunk { size, .. }) = self.queue.borrow_mut().pop_front() {
+            // Rounding errors can make the total size slightly negative.
+            self.queue_total_size
+                .set((self.queue_total_size.get() - size).max(0.));
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-error-if-needed>
+    pub fn error_if_needed(&self, error: HandleValue) {
+        if self.stream.state() == WritableStreamState::Writable {
+            self.error(error);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-process-close>
+    fn process_close(&self) {
+        // Steps 2-4.
+        self.stream.mark_close_request_in_flight();
+        self.dequeue();
+        assert!(self.queue.borrow().is_empty());
+
+        // Step 5.
+        let global = self.global();
+        let cx = global.get_cx();
+        let (close, transform, port) = match *self.algorithms.borrow() {
+            SinkAlgorithms::Js { ref close, .. } => (close.clone(), None, None),
+            SinkAlgorithms::Transform { ref stream } => {
+                (None, Some(DomRoot::from_ref(&**stream)), None)
+            },
+            SinkAlgorithms::CrossRealm { ref port } => {
+                (None, None, Some(DomRoot::from_ref(&**port)))
+            },
+            SinkAlgorithms::None => (None, None, None),
+        };
+        let close_promise = match (transform, port) {
+            (Some(transform), _) => transform.sink_close_algorithm(),
+            (None, Some(port)) => port.cross_realm_close(),
+            (None, None) => {
+                rooted!(in(*cx) let mut result = UndefinedValue());
+                let succeeded =
+                    close.map_or(true, |close| self.invoke(&*close, &[], result.handle_mut()));
+                promise_from_invoke(&global, succeeded, result.handle())
+            },
+        };
+
+        // Steps 6-8.
+        self.clear_algorithms();
+        self.react(&close_promise, SinkStep::Close);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-process-write>
+    fn process_write(&self, chunk: HandleValue) {
+        // Step 2.
+        self.stream.mark_first_write_request_in_flight();
+
+        // Step 3.
+        let global = self.global();
+        let cx = global.get_cx();
+        let (write, transform, port) = match *self.algorithms.borrow() {
+            SinkAlgorithms::Js { ref write, .. } => (write.clone(), None, None),
+            SinkAlgorithms::Transform { ref stream } => {
+                (None, Some(DomRoot::from_ref(&**stream)), None)
+            },
+            SinkAlgorithms::CrossRealm { ref port } => {
+                (None, None, Some(DomRoot::from_ref(&**port)))
+            },
+            SinkAlgorithms::None => (None, None, None),
+        };
+        let write_promise = match (transform, port) {
+            (Some(transform), _) => transform.sink_write_algorithm(chunk),
+            (None, Some(port)) => port.cross_realm_write(chunk),
+            (None, None) => {
+                rooted!(in(*cx) let mut result = UndefinedValue());
+                let arguments = [
+                    chunk.get(),
+                    ObjectValue(self.reflector().get_jsobject().get()),
+                ];
+                let succeeded = write.map_or(true, |write| {
+                    self.invoke(&*write, &arguments, result.handle_mut())
+                });
+                promise_from_invoke(&global, succeeded, result.handle())
+            },
+        };
+
+        // Steps 4-5.
+        self.react(&write_promise, SinkStep::Write);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-get-backpressure>
+    fn get_backpressure(&self) -> bool {
+        self.desired_size() <= 0.
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-controller-error>
+    pub fn error(&self, error: HandleValue) {
+        assert_eq!(self.stream.state(), WritableStreamState::Writable);
+        self.clear_algorithms();
+        self.stream.start_erroring(error);
+    }
+
+    fn sink_algorithm_settled(&self, step: SinkStep, fulfilled: bool, value: HandleValue) {
+        match step {
+            // Steps 17-18 of https://streams.spec.whatwg.org/#set-up-writable-stream-default-controller
+            SinkStep::Start => {
+                self.started.set(true);
+                if fulfilled {
+                    self.advance_queue_if_needed();
+                } else {
+                    self.stream.deal_with_rejection(value);
+                }
+            },
+            // Step 4 of https://streams.spec.whatwg.org/#writable-stream-default-controller-process-write
+            SinkStep::Write if fulfilled => {
+                self.stream.finish_in_flight_write();
+                self.dequeue();
+                if !self.stream.close_queued_or_in_flight() &&
+                    self.stream.state() == WritableStreamState::Writable
+                {
+                    self.stream.update_backpressure(self.get_backpressure());
+                }
+                self.advance_queue_if_needed();
+            },
+            // Step 5 of https://streams.spec.whatwg.org/#writable-stream-default-controller-process-write
+            SinkStep::Write => {
+                if self.stream.state() == WritableStreamState::Writable {
+                    self.clear_algorithms();
+                }
+                self.stream.finish_in_flight_write_with_error(value);
+            },
+            // Steps 7-8 of https://streams.spec.whatwg.org/#writable-stream-default-controller-process-close
+            SinkStep::Close if fulfilled => self.stream.finish_in_flight_close(),
+            SinkStep::Close => self.stream.finish_in_flight_close_with_error(value),
+        }
+    }
+}
+
+impl WritableStreamDefaultControllerMethods for WritableStreamDefaultController {
+    // https://streams.spec.whatwg.org/#ws-default-controller-error
+    fn Error(&self, _cx: JSContext, e: HandleValue) {
+        if self.stream.state() != WritableStreamState::Writable {
+            return;
+        }
+        self.error(e);
+    }
+}
+
+/// Advances the queue of a controller once its start, write or close
+/// algorithm settles, or errors its stream if it rejects.
+#[derive(JSTraceable, MallocSizeOf)]
+struct SinkAlgorithmSettled {
+    controller: Dom<WritableStreamDefaultController>,
+    step: SinkStep,
+    fulfilled: bool,
+}
+
+impl SinkAlgorithmSettled {
+    fn new(
+        controller: &WritableStreamDefaultController,
+        step: SinkStep,
+        fulfilled: bool,
+    ) -> Box<dyn Callback> {
+        Box::new(SinkAlgorithmSettled {
+            controller: Dom::from_ref(controller),
+            step,
+            fulfilled,
+        })
+    }
+}
+
+impl Callback for SinkAlgorithmSettled {
+    fn callback(&self, _cx: *mut RawJSContext, v: HandleValue) {
+        self.controller
+            .sink_algorithm_settled(self.step, self.fulfilled, v);
+    }
+}
@@ -0,0 +1,292 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WritableStreamDefaultWriterBinding::{
+    self, WritableStreamDefaultWriterMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::writablestream::{WritableStream, WritableStreamState};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::rust::HandleValue;
+use std::rc::Rc;
+
+// https://streams.spec.whatwg.org/#default-writer-class
+#[dom_struct]
+pub struct WritableStreamDefaultWriter {
+    reflector_: Reflector,
+    /// Unset once the writer releases its lock.
+    stream: MutNullableDom<WritableStream>,
+    #[ignore_malloc_size_of = "Rc"]
+    ready_promise: DomRefCell<Rc<Promise>>,
+    #[ignore_malloc_size_of = "Rc"]
+    closed_promise: DomRefCell<Rc<Promise>>,
+}
+
+impl WritableStreamDefaultWriter {
+    fn new_inherited(
+        ready_promise: Rc<Promise>,
+        closed_promise: Rc<Promise>,
+    ) -> WritableStreamDefaultWriter {
+        WritableStreamDefaultWriter {
+            reflector_: Reflector::new(),
+            stream: Default::default(),
+            ready_promise: DomRefCell::new(ready_promise),
+            closed_promise: DomRefCell::new(closed_promise),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-writable-stream-default-writer>
+    pub fn new(
+        global: &GlobalScope,
+        stream: &WritableStream,
+    ) -> Fallible<DomRoot<WritableStreamDefaultWriter>> {
+        // Step 1.
+        if stream.is_locked() {
+            return Err(Error::Type("The stream is already locked".to_owned()));
+        }
+
+        // Steps 4-8.
+        let cx = global.get_cx();
+        let ready_promise = Promise::new(global);
+        let closed_promise = Promise::new(global);
+        rooted!(in(*cx) let stored_error = stream.stored_error());
+        match stream.state() {
+            WritableStreamState::Writable => {
+                if stream.close_queued_or_in_flight() || !stream.backpressure() {
+                    ready_promise.resolve_native(&());
+                }
+            },
+            WritableStreamState::Closed => {
+                ready_promise.resolve_native(&());
+                closed_promise.resolve_native(&());
+            },
+            WritableStreamState::Erroring => ready_promise.reject(cx, stored_error.handle()),
+            WritableStreamState::Errored => {
+                ready_promise.reject(cx, stored_error.handle());
+                closed_promise.reject(cx, stored_error.handle());
+            },
+        }
+
+        // Steps 2-3.
+        let writer = reflect_dom_object(
+            Box::new(WritableStreamDefaultWriter::new_inherited(
+                ready_promise,
+                closed_promise,
+            )),
+            global,
+            WritableStreamDefaultWriterBinding::Wrap,
+        );
+        writer.stream.set(Some(stream));
+        stream.set_writer(Some(&writer));
+        Ok(writer)
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-constructor
+    pub fn Constructor(
+        global: &GlobalScope,
+        stream: &WritableStream,
+    ) -> Fallible<DomRoot<WritableStreamDefaultWriter>> {
+        WritableStreamDefaultWriter::new(global, stream)
+    }
+
+    pub fn resolve_ready_promise(&self) {
+        self.ready_promise.borrow().resolve_native(&());
+    }
+
+    /// Replaces the ready promise with a pending one, once the stream applies
+    /// backpressure.
+    pub fn reset_ready_promise(&self) {
+        *self.ready_promise.borrow_mut() = Promise::new(&self.global());
+    }
+
+    pub fn resolve_closed_promise(&self) {
+        self.closed_promise.borrow().resolve_native(&());
+    }
+
+    pub fn reject_closed_promise(&self, error: HandleValue) {
+        self.closed_promise
+            .borrow()
+            .reject(self.global().get_cx(), error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-writer-ensure-ready-promise-rejected>
+    pub fn ensure_ready_promise_rejected(&self, error: HandleValue) {
+        let cx = self.global().get_cx();
+        if self.ready_promise.borrow().is_fulfilled() {
+            self.reset_ready_promise();
+        }
+        self.ready_promise.borrow().reject(cx, error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-writer-ensure-closed-promise-rejected>
+    fn ensure_closed_promise_rejected(&self, error: Error) {
+        if self.closed_promise.borrow().is_fulfilled() {
+            *self.closed_promise.borrow_mut() = Promise::new(&self.global());
+        }
+        self.closed_promise.borrow().reject_error(error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-writer-close-with-error-propagation>
+    pub fn close_with_error_propagation(&self) -> Rc<Promise> {
+        let stream = self
+            .stream
+            .get()
+            .expect("A writer closing its stream has a stream.");
+        let state = stream.state();
+
+        // Step 4.
+        if stream.close_queued_or_in_flight() || state == WritableStreamState::Closed {
+            let promise = Promise::new(&self.global());
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Step 5.
+        if state == WritableStreamState::Errored {
+            let promise = Promise::new(&self.global());
+            let cx = self.global().get_cx();
+            rooted!(in(*cx) let stored_error = stream.stored_error());
+            promise.reject(cx, stored_error.handle());
+            return promise;
+        }
+
+        // Step 7.
+        stream.close()
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-writer-release>
+    pub fn release(&self) {
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => return,
+        };
+
+        // Steps 3-5.
+        let released_error = || Error::Type("The writer released its lock".to_owned());
+        if self.ready_promise.borrow().is_fulfilled() {
+            self.reset_ready_promise();
+        }
+        self.ready_promise.borrow().reject_error(released_error());
+        self.ensure_closed_promise_rejected(released_error());
+
+        // Steps 6-7.
+        stream.set_writer(None);
+        self.stream.set(None);
+    }
+
+    /// <https://streams.spec.whatwg.org/#writable-stream-default-writer-write>
+    pub fn write(&self, cx: JSContext, chunk: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => {
+                promise.reject_error(Error::Type("The writer has no stream".to_owned()));
+                return promise;
+            },
+        };
+
+        // Steps 3-4.
+        let controller = stream.controller();
+        let chunk_size = controller.get_chunk_size(cx, chunk);
+
+        // Step 5.
+        if self.stream.get().as_deref() != Some(&*stream) {
+            promise.reject_error(Error::Type("The writer released its lock".to_owned()));
+            return promise;
+        }
+
+        // Steps 6-7.
+        let state = stream.state();
+        rooted!(in(*cx) let stored_error = stream.stored_error());
+        if state == WritableStreamState::Errored {
+            promise.reject(cx, stored_error.handle());
+            return promise;
+        }
+
+        // Step 8.
+        if stream.close_queued_or_in_flight() || state == WritableStreamState::Closed {
+            promise.reject_error(Error::Type("The stream is closing or closed".to_owned()));
+            return promise;
+        }
+
+        // Step 9.
+        if state == WritableStreamState::Erroring {
+            promise.reject(cx, stored_error.handle());
+            return promise;
+        }
+
+        // Steps 11-13.
+        let promise = stream.add_write_request();
+        controller.write(cx, chunk, chunk_size);
+        promise
+    }
+}
+
+impl WritableStreamDefaultWriterMethods for WritableStreamDefaultWriter {
+    // https://streams.spec.whatwg.org/#default-writer-closed
+    fn Closed(&self) -> Rc<Promise> {
+        self.closed_promise.borrow().clone()
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-desired-size
+    fn GetDesiredSize(&self) -> Fallible<Option<f64>> {
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => return Err(Error::Type("The writer has no stream".to_owned())),
+        };
+        Ok(match stream.state() {
+            WritableStreamState::Errored | WritableStreamState::Erroring => None,
+            WritableStreamState::Closed => Some(0.),
+            WritableStreamState::Writable => Some(stream.controller().desired_size()),
+        })
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.borrow().clone()
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-abort
+    fn Abort(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        match self.stream.get() {
+            Some(stream) => stream.abort(cx, reason),
+            None => {
+                let promise = Promise::new(&self.global());
+                promise.reject_error(Error::Type("The writer has no stream".to_owned()));
+                promise
+            },
+        }
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-close
+    fn Close(&self) -> Rc<Promise> {
+        let error = match self.stream.get() {
+            Some(ref stream) if stream.close_queued_or_in_flight() => {
+                Error::Type("The stream is already closing".to_owned())
+            },
+            Some(stream) => return stream.close(),
+            None => Error::Type("The writer has no stream".to_owned()),
+        };
+        let promise = Promise::new(&self.global());
+        promise.reject_error(error);
+        promise
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-release-lock
+    fn ReleaseLock(&self) {
+        self.release();
+    }
+
+    // https://streams.spec.whatwg.org/#default-writer-write
+    fn Write(&self, cx: JSContext, chunk: HandleValue) -> Rc<Promise> {
+        self.write(cx, chunk)
+    }
+}
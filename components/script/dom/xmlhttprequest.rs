@@ -36,6 +36,7 @@ use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::xmlhttprequesteventtarget::XMLHttpRequestEventTarget;
 use crate::dom::xmlhttprequestupload::XMLHttpRequestUpload;
+use crate::dom::xmlserializer::serialize_xml;
 use crate::fetch::FetchCanceller;
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use crate::script_runtime::JSContext;
@@ -78,6 +79,7 @@ use std::slice;
 use std::str::{self, FromStr};
 use std::sync::{Arc, Mutex};
 use url::Position;
+use xml5ever::serialize::TraversalScope as XmlTraversalScope;
 
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 enum XMLHttpRequestState {
@@ -423,6 +425,13 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
                 *self.request_method.borrow_mut() = parsed_method;
                 *self.request_url.borrow_mut() = Some(parsed_url);
                 self.sync.set(!r#async);
+                if self.sync_in_window() {
+                    self.global().report_deprecation(
+                        "SyncXHR",
+                        "Synchronous XMLHttpRequest on the main thread is deprecated \
+                         because of its detrimental effects to the end user's experience.",
+                    );
+                }
                 *self.request_headers.borrow_mut() = HeaderMap::new();
                 self.send_flag.set(false);
                 *self.status_text.borrow_mut() = ByteString::new(vec![]);
@@ -1575,7 +1584,7 @@ impl Extractable for FormData {
         (
             bytes,
             Some(DOMString::from(format!(
-                "multipart/form-data;boundary={}",
+                "multipart/form-data; boundary={}",
                 boundary
             ))),
         )
@@ -1594,6 +1603,9 @@ impl Extractable for URLSearchParams {
 }
 
 fn serialize_document(doc: &Document) -> Fallible<DOMString> {
+    if !doc.is_html_document() {
+        return serialize_xml(doc.upcast(), XmlTraversalScope::IncludeNode, false);
+    }
     let mut writer = vec![];
     match serialize(&mut writer, &doc.upcast::<Node>(), SerializeOpts::default()) {
         Ok(_) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
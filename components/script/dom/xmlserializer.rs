@@ -2,16 +2,29 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::XMLSerializerBinding;
 use crate::dom::bindings::codegen::Bindings::XMLSerializerBinding::XMLSerializerMethods;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::inheritance::{CharacterDataTypeId, NodeTypeId, TextTypeId};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::xmlname::{xml_name_type, XMLName};
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::documenttype::DocumentType;
+use crate::dom::element::Element;
+use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::node::Node;
+use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
-use xml5ever::serialize::{serialize, SerializeOpts, TraversalScope};
+use html5ever::Namespace;
+use std::collections::{HashMap, HashSet};
+use xml5ever::serialize::TraversalScope;
 
 #[dom_struct]
 pub struct XMLSerializer {
@@ -41,20 +54,479 @@ impl XMLSerializer {
 }
 
 impl XMLSerializerMethods for XMLSerializer {
-    // https://w3c.github.io/DOM-Parsing/#the-xmlserializer-interface
+    // https://w3c.github.io/DOM-Parsing/#dom-xmlserializer-serializetostring
     fn SerializeToString(&self, root: &Node) -> Fallible<DOMString> {
-        let mut writer = vec![];
-        match serialize(
-            &mut writer,
-            &root,
-            SerializeOpts {
-                traversal_scope: TraversalScope::IncludeNode,
+        serialize_xml(root, TraversalScope::IncludeNode, false)
+    }
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-concept-serialize-xml>
+///
+/// With `TraversalScope::ChildrenOnly`, each child of `node` is serialized
+/// in turn, as for the `innerHTML` getter of elements in XML documents.
+/// Throws an `InvalidStateError` if `require_well_formed` is set and the
+/// result would not be well-formed XML.
+pub fn serialize_xml(
+    node: &Node,
+    traversal_scope: TraversalScope,
+    require_well_formed: bool,
+) -> Fallible<DOMString> {
+    let mut serializer = XmlSerializer {
+        markup: String::new(),
+        prefix_index: 1,
+        require_well_formed,
+    };
+    let mut prefix_map = NamespacePrefixMap::new();
+    prefix_map.insert(ns!(xml), vec![String::from("xml")]);
+    match traversal_scope {
+        TraversalScope::IncludeNode => {
+            serializer.serialize_node(node, &ns!(), &mut prefix_map)?;
+        },
+        TraversalScope::ChildrenOnly(_) => {
+            serializer.serialize_children(node, &ns!(), &mut prefix_map)?;
+        },
+    }
+    Ok(DOMString::from(serializer.markup))
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-namespace-prefix-map>
+///
+/// The null namespace is represented by the empty namespace.
+type NamespacePrefixMap = HashMap<Namespace, Vec<String>>;
+
+/// The state of an XML serialization in progress.
+struct XmlSerializer {
+    markup: String,
+    /// <https://w3c.github.io/DOM-Parsing/#dfn-generated-namespace-prefix-index>
+    prefix_index: u32,
+    require_well_formed: bool,
+}
+
+impl XmlSerializer {
+    fn check_well_formed(&self, well_formed: bool) -> Fallible<()> {
+        if self.require_well_formed && !well_formed {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-algorithm
+    fn serialize_node(
+        &mut self,
+        node: &Node,
+        namespace: &Namespace,
+        prefix_map: &mut NamespacePrefixMap,
+    ) -> Fallible<()> {
+        match node.type_id() {
+            NodeTypeId::Element(_) => {
+                self.serialize_element(node.downcast::<Element>().unwrap(), namespace, prefix_map)
+            },
+            NodeTypeId::Document(_) => {
+                let document = node.downcast::<Document>().unwrap();
+                self.check_well_formed(document.GetDocumentElement().is_some())?;
+                self.serialize_children(node, namespace, prefix_map)
+            },
+            NodeTypeId::DocumentFragment(_) => self.serialize_children(node, namespace, prefix_map),
+            NodeTypeId::DocumentType => {
+                let doctype = node.downcast::<DocumentType>().unwrap();
+                let public_id = doctype.public_id();
+                let system_id = doctype.system_id();
+                self.check_well_formed(
+                    public_id.chars().all(is_pubid_char) &&
+                        !(system_id.contains('"') && system_id.contains('\'')),
+                )?;
+                self.markup.push_str("<!DOCTYPE ");
+                self.markup.push_str(doctype.name());
+                if !public_id.is_empty() {
+                    self.markup.push_str(" PUBLIC \"");
+                    self.markup.push_str(public_id);
+                    self.markup.push('"');
+                }
+                if !system_id.is_empty() {
+                    if public_id.is_empty() {
+                        self.markup.push_str(" SYSTEM");
+                    }
+                    self.markup.push_str(" \"");
+                    self.markup.push_str(system_id);
+                    self.markup.push('"');
+                }
+                self.markup.push('>');
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Comment) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.check_well_formed(
+                    data.chars().all(is_xml_char) && !data.contains("--") && !data.ends_with('-'),
+                )?;
+                self.markup.push_str("<!--");
+                self.markup.push_str(&data);
+                self.markup.push_str("-->");
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(TextTypeId::CDATASection)) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.check_well_formed(data.chars().all(is_xml_char) && !data.contains("]]>"))?;
+                self.markup.push_str("<![CDATA[");
+                self.markup.push_str(&data);
+                self.markup.push_str("]]>");
+                Ok(())
             },
-        ) {
-            Ok(_) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
-            Err(_) => Err(Error::Type(String::from(
-                "root must be a Node or an Attr object",
-            ))),
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(TextTypeId::Text)) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.check_well_formed(data.chars().all(is_xml_char))?;
+                for c in data.chars() {
+                    match c {
+                        '&' => self.markup.push_str("&amp;"),
+                        '<' => self.markup.push_str("&lt;"),
+                        '>' => self.markup.push_str("&gt;"),
+                        c => self.markup.push(c),
+                    }
+                }
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::ProcessingInstruction) => {
+                let pi = node.downcast::<ProcessingInstruction>().unwrap();
+                let target = pi.target();
+                let data = pi.upcast::<CharacterData>().data();
+                self.check_well_formed(
+                    !target.contains(':') &&
+                        !target.eq_ignore_ascii_case("xml") &&
+                        data.chars().all(is_xml_char) &&
+                        !data.contains("?>"),
+                )?;
+                self.markup.push_str("<?");
+                self.markup.push_str(target);
+                self.markup.push(' ');
+                self.markup.push_str(&data);
+                self.markup.push_str("?>");
+                Ok(())
+            },
+        }
+    }
+
+    fn serialize_children(
+        &mut self,
+        node: &Node,
+        namespace: &Namespace,
+        prefix_map: &mut NamespacePrefixMap,
+    ) -> Fallible<()> {
+        for child in node.children() {
+            self.serialize_node(&child, namespace, prefix_map)?;
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#xml-serializing-an-element-node
+    fn serialize_element(
+        &mut self,
+        element: &Element,
+        namespace: &Namespace,
+        prefix_map: &NamespacePrefixMap,
+    ) -> Fallible<()> {
+        let local_name = element.local_name();
+        // Step 1.
+        self.check_well_formed(
+            !local_name.contains(':') && xml_name_type(local_name) != XMLName::InvalidXMLName,
+        )?;
+
+        // Steps 2-5.
+        self.markup.push('<');
+        let qualified_name;
+        let mut ignore_namespace_definition_attribute = false;
+
+        // Steps 6-8.
+        let mut map = prefix_map.clone();
+        let mut local_prefixes_map = HashMap::new();
+        let local_default_namespace =
+            record_namespace_information(element, &mut map, &mut local_prefixes_map);
+
+        // Steps 9-10.
+        let mut inherited_namespace = namespace.clone();
+        let element_namespace = element.namespace();
+
+        if inherited_namespace == *element_namespace {
+            // Step 11.
+            if local_default_namespace.is_some() {
+                ignore_namespace_definition_attribute = true;
+            }
+            qualified_name = if *element_namespace == ns!(xml) {
+                format!("xml:{}", local_name)
+            } else {
+                local_name.to_string()
+            };
+            self.markup.push_str(&qualified_name);
+        } else {
+            // Step 12.1.
+            let mut prefix = element.prefix().as_ref().map(|prefix| prefix.to_string());
+            // Step 12.2.
+            let mut candidate_prefix =
+                retrieve_preferred_prefix(prefix.as_ref(), &map, element_namespace);
+            // Step 12.3.
+            if prefix.as_ref().map_or(false, |prefix| prefix == "xmlns") {
+                self.check_well_formed(false)?;
+                candidate_prefix = prefix.clone();
+            }
+
+            if let Some(candidate_prefix) = candidate_prefix {
+                // Step 12.4.
+                qualified_name = format!("{}:{}", candidate_prefix, local_name);
+                if let Some(ref local_default_namespace) = local_default_namespace {
+                    if **local_default_namespace != *ns!(xml) {
+                        inherited_namespace = Namespace::from(&**local_default_namespace);
+                    }
+                }
+                self.markup.push_str(&qualified_name);
+            } else if let Some(mut prefix) = prefix.take() {
+                // Step 12.5.
+                if local_prefixes_map.contains_key(&prefix) {
+                    prefix = self.generate_prefix(&mut map, element_namespace);
+                } else {
+                    map.entry(element_namespace.clone())
+                        .or_insert_with(Vec::new)
+                        .push(prefix.clone());
+                }
+                qualified_name = format!("{}:{}", prefix, local_name);
+                self.markup.push_str(&qualified_name);
+                self.markup.push_str(" xmlns:");
+                self.markup.push_str(&prefix);
+                self.markup.push_str("=\"");
+                self.serialize_attribute_value(element_namespace)?;
+                self.markup.push('"');
+                if let Some(ref local_default_namespace) = local_default_namespace {
+                    inherited_namespace = Namespace::from(&**local_default_namespace);
+                }
+            } else if local_default_namespace
+                .as_ref()
+                .map_or(true, |local_default_namespace| {
+                    **local_default_namespace != **element_namespace
+                })
+            {
+                // Step 12.6.
+                ignore_namespace_definition_attribute = true;
+                qualified_name = local_name.to_string();
+                inherited_namespace = element_namespace.clone();
+                self.markup.push_str(&qualified_name);
+                self.markup.push_str(" xmlns=\"");
+                self.serialize_attribute_value(element_namespace)?;
+                self.markup.push('"');
+            } else {
+                // Step 12.7.
+                qualified_name = local_name.to_string();
+                inherited_namespace = element_namespace.clone();
+                self.markup.push_str(&qualified_name);
+            }
+        }
+
+        // Step 13.
+        self.serialize_attributes(
+            element,
+            &mut map,
+            &local_prefixes_map,
+            ignore_namespace_definition_attribute,
+        )?;
+
+        // Steps 14-17.
+        let node = element.upcast::<Node>();
+        if node.children_count() == 0 {
+            if *element_namespace != ns!(html) {
+                self.markup.push_str("/>");
+                return Ok(());
+            }
+            if element.is_void() {
+                self.markup.push_str(" />");
+                return Ok(());
+            }
         }
+        self.markup.push('>');
+
+        // Steps 18-19.
+        match element.downcast::<HTMLTemplateElement>() {
+            Some(template) => self.serialize_children(
+                template.Content().upcast(),
+                &inherited_namespace,
+                &mut map,
+            )?,
+            None => self.serialize_children(node, &inherited_namespace, &mut map)?,
+        }
+
+        // Step 20.
+        self.markup.push_str("</");
+        self.markup.push_str(&qualified_name);
+        self.markup.push('>');
+        Ok(())
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-of-the-attributes
+    fn serialize_attributes(
+        &mut self,
+        element: &Element,
+        map: &mut NamespacePrefixMap,
+        local_prefixes_map: &HashMap<String, Namespace>,
+        ignore_namespace_definition_attribute: bool,
+    ) -> Fallible<()> {
+        // Step 2.
+        let mut localname_set = HashSet::new();
+        for attr in element.attrs().iter() {
+            let attr_namespace = attr.namespace();
+            let attr_prefix = attr.prefix().map(|prefix| prefix.to_string());
+            let local_name = attr.local_name();
+            let value = attr.value();
+
+            // Steps 3.1-3.2.
+            self.check_well_formed(
+                localname_set.insert((attr_namespace.clone(), local_name.clone())),
+            )?;
+
+            // Steps 3.4-3.5.
+            let mut candidate_prefix = None;
+            if *attr_namespace != ns!() {
+                // Step 3.5.1.
+                candidate_prefix =
+                    retrieve_preferred_prefix(attr_prefix.as_ref(), map, attr_namespace);
+                if *attr_namespace == ns!(xmlns) {
+                    // Step 3.5.2.1.
+                    let redundant = &**value == &*ns!(xml) ||
+                        (attr_prefix.is_none() && ignore_namespace_definition_attribute) ||
+                        (attr_prefix.is_some() &&
+                            local_prefixes_map
+                                .get(&**local_name)
+                                .map_or(true, |namespace| **namespace != **value));
+                    if redundant {
+                        continue;
+                    }
+                    // Steps 3.5.2.2-3.5.2.3.
+                    self.check_well_formed(&**value != &*ns!(xmlns) && !value.is_empty())?;
+                    // Step 3.5.2.4.
+                    if attr_prefix
+                        .as_ref()
+                        .map_or(false, |prefix| prefix == "xmlns")
+                    {
+                        candidate_prefix = attr_prefix.clone();
+                    }
+                } else if candidate_prefix.is_none() {
+                    // Step 3.5.3.
+                    let prefix = self.generate_prefix(map, attr_namespace);
+                    self.markup.push_str(" xmlns:");
+                    self.markup.push_str(&prefix);
+                    self.markup.push_str("=\"");
+                    self.serialize_attribute_value(attr_namespace)?;
+                    self.markup.push('"');
+                    candidate_prefix = Some(prefix);
+                }
+            }
+
+            // Steps 3.6-3.7.
+            self.markup.push(' ');
+            if let Some(candidate_prefix) = candidate_prefix {
+                self.markup.push_str(&candidate_prefix);
+                self.markup.push(':');
+            }
+
+            // Step 3.8.
+            self.check_well_formed(
+                !local_name.contains(':') &&
+                    xml_name_type(local_name) != XMLName::InvalidXMLName &&
+                    !(&**local_name == "xmlns" && *attr_namespace == ns!()),
+            )?;
+
+            // Step 3.9.
+            self.markup.push_str(local_name);
+            self.markup.push_str("=\"");
+            self.serialize_attribute_value(&value)?;
+            self.markup.push('"');
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#dfn-serializing-an-attribute-value
+    fn serialize_attribute_value(&mut self, value: &str) -> Fallible<()> {
+        self.check_well_formed(value.chars().all(is_xml_char))?;
+        for c in value.chars() {
+            match c {
+                '&' => self.markup.push_str("&amp;"),
+                '"' => self.markup.push_str("&quot;"),
+                '<' => self.markup.push_str("&lt;"),
+                '>' => self.markup.push_str("&gt;"),
+                c => self.markup.push(c),
+            }
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#dfn-generating-a-prefix
+    fn generate_prefix(&mut self, map: &mut NamespacePrefixMap, namespace: &Namespace) -> String {
+        let prefix = format!("ns{}", self.prefix_index);
+        self.prefix_index += 1;
+        map.entry(namespace.clone())
+            .or_insert_with(Vec::new)
+            .push(prefix.clone());
+        prefix
+    }
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-recording-the-namespace-information>
+///
+/// Returns the value of the element's default namespace declaration, if any.
+fn record_namespace_information(
+    element: &Element,
+    map: &mut NamespacePrefixMap,
+    local_prefixes_map: &mut HashMap<String, Namespace>,
+) -> Option<DOMString> {
+    let mut default_namespace_attr_value = None;
+    for attr in element.attrs().iter() {
+        if *attr.namespace() != ns!(xmlns) {
+            continue;
+        }
+        let value = DOMString::from(&**attr.value());
+        if attr.prefix().is_none() {
+            // Step 2.2.1.
+            default_namespace_attr_value = Some(value);
+            continue;
+        }
+        // Step 2.2.2.
+        let prefix_definition = attr.local_name().to_string();
+        let namespace_definition = Namespace::from(&*value);
+        if namespace_definition == ns!(xml) {
+            continue;
+        }
+        let prefixes = map
+            .entry(namespace_definition.clone())
+            .or_insert_with(Vec::new);
+        if prefixes.contains(&prefix_definition) {
+            continue;
+        }
+        prefixes.push(prefix_definition.clone());
+        local_prefixes_map.insert(prefix_definition, namespace_definition);
+    }
+    default_namespace_attr_value
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-retrieving-a-preferred-prefix-string>
+fn retrieve_preferred_prefix(
+    preferred_prefix: Option<&String>,
+    map: &NamespacePrefixMap,
+    namespace: &Namespace,
+) -> Option<String> {
+    let candidates = map.get(namespace)?;
+    candidates
+        .iter()
+        .find(|candidate| Some(*candidate) == preferred_prefix)
+        .or_else(|| candidates.last())
+        .cloned()
+}
+
+/// <https://www.w3.org/TR/xml/#NT-Char>
+fn is_xml_char(c: char) -> bool {
+    match c {
+        '\u{9}' | '\u{A}' | '\u{D}' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' => true,
+        c => c >= '\u{10000}',
+    }
+}
+
+/// <https://www.w3.org/TR/xml/#NT-PubidChar>
+fn is_pubid_char(c: char) -> bool {
+    match c {
+        ' ' | '\r' | '\n' | 'a'..='z' | 'A'..='Z' | '0'..='9' => true,
+        c => "-'()+,./:=?;!*#@$_%".contains(c),
     }
 }
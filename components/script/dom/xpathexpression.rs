@@ -0,0 +1,92 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling::Rethrow;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use crate::dom::bindings::codegen::Bindings::XPathNSResolverBinding::XPathNSResolver;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::dom::xpathresult::XPathResult;
+use crate::xpath::{self, Evaluator, Expr, XPathNode};
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+// https://dom.spec.whatwg.org/#interface-xpathexpression
+#[dom_struct]
+pub struct XPathExpression {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    expr: Expr,
+    #[ignore_malloc_size_of = "Rc<XPathNSResolver> has shared ownership"]
+    resolver: Option<Rc<XPathNSResolver>>,
+}
+
+impl XPathExpression {
+    fn new_inherited(
+        window: &Window,
+        expr: Expr,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> XPathExpression {
+        XPathExpression {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            expr,
+            resolver,
+        }
+    }
+
+    /// Parses `expression`, throwing a `SyntaxError` if it is not a valid
+    /// XPath expression.
+    pub fn new(
+        window: &Window,
+        expression: &str,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        let expr = xpath::parse(expression).map_err(|error| {
+            debug!("invalid XPath expression {:?}: {:?}", expression, error);
+            Error::Syntax
+        })?;
+        Ok(reflect_dom_object(
+            Box::new(XPathExpression::new_inherited(window, expr, resolver)),
+            window,
+            XPathExpressionBinding::Wrap,
+        ))
+    }
+
+    fn lookup_namespace_uri(&self, prefix: &str) -> Fallible<Option<String>> {
+        let resolver = match self.resolver {
+            Some(ref resolver) => resolver,
+            None => return Ok(None),
+        };
+        let namespace = resolver.LookupNamespaceURI__(Some(DOMString::from(prefix)), Rethrow)?;
+        Ok(namespace
+            .map(String::from)
+            .filter(|namespace| !namespace.is_empty()))
+    }
+}
+
+impl XPathExpressionMethods for XPathExpression {
+    // https://dom.spec.whatwg.org/#dom-xpathexpression-evaluate
+    fn Evaluate(
+        &self,
+        context_node: &Node,
+        type_: u16,
+        _result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        let document = context_node.owner_doc();
+        let evaluator = Evaluator {
+            is_html_document: document.is_html_document(),
+            resolve_prefix: &|prefix: &str| self.lookup_namespace_uri(prefix),
+        };
+        let value =
+            evaluator.evaluate(&self.expr, XPathNode::Node(DomRoot::from_ref(context_node)))?;
+        // A new result is always returned, rather than reusing `result`.
+        XPathResult::new(&self.window, type_, value, &document)
+    }
+}
@@ -0,0 +1,215 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding;
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultConstants;
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::xpath::{Value, XPathNode};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+// https://dom.spec.whatwg.org/#interface-xpathresult
+#[dom_struct]
+pub struct XPathResult {
+    reflector_: Reflector,
+    result_type: u16,
+    number_value: f64,
+    string_value: DOMString,
+    boolean_value: bool,
+    /// The nodes of a node-set result. Attributes are not DOM nodes, and are
+    /// left out.
+    nodes: Vec<Dom<Node>>,
+    /// The index of the node that `iterateNext()` returns next.
+    iterator_index: Cell<usize>,
+    /// The document of the context node, whose mutations invalidate iterators.
+    document: Dom<Document>,
+    /// The version of `document` when the result was created.
+    document_version: u64,
+}
+
+impl XPathResult {
+    fn new_inherited(result_type: u16, value: Value, document: &Document) -> XPathResult {
+        let (number_value, string_value, boolean_value, nodes) = match value {
+            Value::NodeSet(nodes) => (
+                0.,
+                DOMString::new(),
+                false,
+                nodes
+                    .iter()
+                    .filter_map(|node| match *node {
+                        XPathNode::Node(ref node) => Some(Dom::from_ref(&**node)),
+                        XPathNode::Attribute(_) => None,
+                    })
+                    .collect(),
+            ),
+            value => (
+                value.number(),
+                DOMString::from(value.string()),
+                value.boolean(),
+                vec![],
+            ),
+        };
+        XPathResult {
+            reflector_: Reflector::new(),
+            result_type,
+            number_value,
+            string_value,
+            boolean_value,
+            nodes,
+            iterator_index: Cell::new(0),
+            document: Dom::from_ref(document),
+            document_version: document.upcast::<Node>().inclusive_descendants_version(),
+        }
+    }
+
+    /// Converts `value` to the result type `type_` as requested by
+    /// `evaluate()`, throwing a `TypeError` if a node-set was requested for
+    /// another type of value.
+    pub fn new(
+        window: &Window,
+        type_: u16,
+        value: Value,
+        document: &Document,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        let (result_type, value) = match (type_, value) {
+            (XPathResultConstants::ANY_TYPE, Value::NodeSet(nodes)) => (
+                XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE,
+                Value::NodeSet(nodes),
+            ),
+            (XPathResultConstants::ANY_TYPE, Value::Number(number)) => {
+                (XPathResultConstants::NUMBER_TYPE, Value::Number(number))
+            },
+            (XPathResultConstants::ANY_TYPE, Value::String(string)) => {
+                (XPathResultConstants::STRING_TYPE, Value::String(string))
+            },
+            (XPathResultConstants::ANY_TYPE, Value::Boolean(boolean)) => {
+                (XPathResultConstants::BOOLEAN_TYPE, Value::Boolean(boolean))
+            },
+            (XPathResultConstants::NUMBER_TYPE, value) => (type_, Value::Number(value.number())),
+            (XPathResultConstants::STRING_TYPE, value) => (type_, Value::String(value.string())),
+            (XPathResultConstants::BOOLEAN_TYPE, value) => (type_, Value::Boolean(value.boolean())),
+            (
+                XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE
+                    ..=XPathResultConstants::FIRST_ORDERED_NODE_TYPE,
+                Value::NodeSet(nodes),
+            ) => (type_, Value::NodeSet(nodes)),
+            (
+                XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE
+                    ..=XPathResultConstants::FIRST_ORDERED_NODE_TYPE,
+                _,
+            ) => {
+                return Err(Error::Type(
+                    "The XPath expression does not evaluate to a node-set".to_owned(),
+                ));
+            },
+            _ => return Err(Error::NotSupported),
+        };
+        Ok(reflect_dom_object(
+            Box::new(XPathResult::new_inherited(result_type, value, document)),
+            window,
+            XPathResultBinding::Wrap,
+        ))
+    }
+
+    fn is_iterator(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE ||
+            self.result_type == XPathResultConstants::ORDERED_NODE_ITERATOR_TYPE
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_SNAPSHOT_TYPE ||
+            self.result_type == XPathResultConstants::ORDERED_NODE_SNAPSHOT_TYPE
+    }
+
+    fn check_type(&self, valid: bool) -> Fallible<()> {
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::Type(format!(
+                "The XPath result type {} does not support this operation",
+                self.result_type
+            )))
+        }
+    }
+}
+
+impl XPathResultMethods for XPathResult {
+    // https://dom.spec.whatwg.org/#dom-xpathresult-resulttype
+    fn ResultType(&self) -> u16 {
+        self.result_type
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-numbervalue
+    fn GetNumberValue(&self) -> Fallible<f64> {
+        self.check_type(self.result_type == XPathResultConstants::NUMBER_TYPE)?;
+        Ok(self.number_value)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-stringvalue
+    fn GetStringValue(&self) -> Fallible<DOMString> {
+        self.check_type(self.result_type == XPathResultConstants::STRING_TYPE)?;
+        Ok(self.string_value.clone())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-booleanvalue
+    fn GetBooleanValue(&self) -> Fallible<bool> {
+        self.check_type(self.result_type == XPathResultConstants::BOOLEAN_TYPE)?;
+        Ok(self.boolean_value)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-singlenodevalue
+    fn GetSingleNodeValue(&self) -> Fallible<Option<DomRoot<Node>>> {
+        self.check_type(
+            self.result_type == XPathResultConstants::ANY_UNORDERED_NODE_TYPE ||
+                self.result_type == XPathResultConstants::FIRST_ORDERED_NODE_TYPE,
+        )?;
+        Ok(self.nodes.first().map(|node| DomRoot::from_ref(&**node)))
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-invaliditeratorstate
+    fn InvalidIteratorState(&self) -> bool {
+        self.is_iterator() &&
+            self.document
+                .upcast::<Node>()
+                .inclusive_descendants_version() !=
+                self.document_version
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotlength
+    fn GetSnapshotLength(&self) -> Fallible<u32> {
+        self.check_type(self.is_snapshot())?;
+        Ok(self.nodes.len() as u32)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-iteratenext
+    fn IterateNext(&self) -> Fallible<Option<DomRoot<Node>>> {
+        self.check_type(self.is_iterator())?;
+        if self.InvalidIteratorState() {
+            return Err(Error::InvalidState);
+        }
+        let index = self.iterator_index.get();
+        let node = self.nodes.get(index).map(|node| DomRoot::from_ref(&**node));
+        if node.is_some() {
+            self.iterator_index.set(index + 1);
+        }
+        Ok(node)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotitem
+    fn SnapshotItem(&self, index: u32) -> Fallible<Option<DomRoot<Node>>> {
+        self.check_type(self.is_snapshot())?;
+        Ok(self
+            .nodes
+            .get(index as usize)
+            .map(|node| DomRoot::from_ref(&**node)))
+    }
+}
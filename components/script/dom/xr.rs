@@ -13,6 +13,7 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::gamepad::Gamepad;
@@ -35,6 +36,11 @@ use webvr_traits::{WebVRDisplayData, WebVRDisplayEvent, WebVREvent, WebVRMsg};
 use webvr_traits::{WebVRGamepadData, WebVRGamepadEvent, WebVRGamepadState};
 use webxr_api::{Error as XRError, Session, SessionMode};
 
+/// The feature descriptors that sessions can be requested with
+///
+/// https://immersive-web.github.io/webxr/#feature-descriptor
+const SUPPORTED_FEATURES: &[&str] = &["viewer", "local", "local-floor", "hit-test", "anchors"];
+
 #[dom_struct]
 pub struct XR {
     eventtarget: EventTarget,
@@ -106,6 +112,37 @@ impl Into<SessionMode> for XRSessionMode {
     }
 }
 
+/// Returns the features a session is enabled with, or `None` if
+/// one of the required features isn't supported
+///
+/// https://immersive-web.github.io/webxr/#resolve-the-requested-features
+fn resolve_features(mode: XRSessionMode, init: &XRSessionInit) -> Option<Vec<String>> {
+    // Step 2-3
+    let mut features = vec!["viewer".to_owned()];
+    if mode != XRSessionMode::Inline {
+        features.push("local".to_owned());
+    }
+    let mut add = |feature: &DOMString| {
+        let feature = feature.to_string();
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    };
+    // Step 5-6
+    for feature in init.requiredFeatures.iter().flatten() {
+        if !SUPPORTED_FEATURES.contains(&&**feature) {
+            return None;
+        }
+        add(feature);
+    }
+    for feature in init.optionalFeatures.iter().flatten() {
+        if SUPPORTED_FEATURES.contains(&&**feature) {
+            add(feature);
+        }
+    }
+    Some(features)
+}
+
 impl XRMethods for XR {
     /// https://immersive-web.github.io/webxr/#dom-xr-supportssessionmode
     fn SupportsSession(&self, mode: XRSessionMode) -> Rc<Promise> {
@@ -153,7 +190,7 @@ impl XRMethods for XR {
     fn RequestSession(
         &self,
         mode: XRSessionMode,
-        _: &XRSessionInit,
+        init: &XRSessionInit,
         comp: InCompartment,
     ) -> Rc<Promise> {
         let promise = Promise::new_in_current_compartment(&self.global(), comp);
@@ -168,6 +205,15 @@ impl XRMethods for XR {
             return promise;
         }
 
+        // https://immersive-web.github.io/webxr/#resolve-the-requested-features
+        let features = match resolve_features(mode, init) {
+            Some(features) => features,
+            None => {
+                promise.reject_error(Error::NotSupported);
+                return promise;
+            },
+        };
+
         self.set_pending();
 
         let promise = Promise::new_in_current_compartment(&self.global(), comp);
@@ -185,6 +231,7 @@ impl XRMethods for XR {
                 // router doesn't know this is only called once
                 let trusted = trusted.take().unwrap();
                 let this = this.clone();
+                let features = features.clone();
                 let message: Result<Session, webxr_api::Error> = if let Ok(message) = message.to() {
                     message
                 } else {
@@ -193,7 +240,7 @@ impl XRMethods for XR {
                 };
                 let _ = task_source.queue_with_canceller(
                     task!(request_session: move || {
-                        this.root().session_obtained(message, trusted.root());
+                        this.root().session_obtained(message, trusted.root(), features);
                     }),
                     &canceller,
                 );
@@ -211,7 +258,12 @@ impl XRMethods for XR {
 }
 
 impl XR {
-    fn session_obtained(&self, response: Result<Session, XRError>, promise: Rc<Promise>) {
+    fn session_obtained(
+        &self,
+        response: Result<Session, XRError>,
+        promise: Rc<Promise>,
+        features: Vec<String>,
+    ) {
         let session = match response {
             Ok(session) => session,
             Err(_) => {
@@ -220,7 +272,7 @@ impl XR {
             },
         };
 
-        let session = XRSession::new(&self.global(), session);
+        let session = XRSession::new(&self.global(), session, features);
         self.set_active_immersive_session(&session);
         promise.resolve_native(&session);
     }
@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRAnchorBinding;
+use crate::dom::bindings::codegen::Bindings::XRAnchorBinding::XRAnchorMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrsession::{ApiPose, XRSession};
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct XRAnchor {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    /// The pose of the anchor, until it's deleted
+    #[ignore_malloc_size_of = "defined in euclid"]
+    pose: Cell<Option<ApiPose>>,
+    anchor_space: MutNullableDom<XRSpace>,
+}
+
+impl XRAnchor {
+    fn new_inherited(session: &XRSession, pose: ApiPose) -> XRAnchor {
+        XRAnchor {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            pose: Cell::new(Some(pose)),
+            anchor_space: Default::default(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, session: &XRSession, pose: ApiPose) -> DomRoot<XRAnchor> {
+        reflect_dom_object(
+            Box::new(XRAnchor::new_inherited(session, pose)),
+            global,
+            XRAnchorBinding::Wrap,
+        )
+    }
+
+    /// The pose of the anchor, or `None` once it's no longer tracked
+    pub fn pose(&self) -> Option<ApiPose> {
+        self.pose.get()
+    }
+}
+
+impl XRAnchorMethods for XRAnchor {
+    /// https://immersive-web.github.io/anchors/#dom-xranchor-anchorspace
+    fn AnchorSpace(&self) -> DomRoot<XRSpace> {
+        self.anchor_space
+            .or_init(|| XRSpace::new_anchorspace(&self.global(), &self.session, self))
+    }
+
+    /// https://immersive-web.github.io/anchors/#dom-xranchor-delete
+    fn Delete(&self) {
+        self.pose.set(None);
+    }
+}
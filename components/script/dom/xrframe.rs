@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::compartments::InCompartment;
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding;
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
 use crate::dom::bindings::error::Error;
@@ -9,13 +10,19 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::xranchor::XRAnchor;
+use crate::dom::xrhittestresult::XRHitTestResult;
+use crate::dom::xrhittestsource::XRHitTestSource;
 use crate::dom::xrpose::XRPose;
 use crate::dom::xrreferencespace::XRReferenceSpace;
-use crate::dom::xrsession::XRSession;
+use crate::dom::xrrigidtransform::XRRigidTransform;
+use crate::dom::xrsession::{ApiPose, XRSession};
 use crate::dom::xrspace::XRSpace;
 use crate::dom::xrviewerpose::XRViewerPose;
 use dom_struct::dom_struct;
 use std::cell::Cell;
+use std::rc::Rc;
 use webxr_api::Frame;
 
 #[dom_struct]
@@ -56,6 +63,40 @@ impl XRFrame {
     pub fn set_animation_frame(&self, animation_frame: bool) {
         self.animation_frame.set(animation_frame);
     }
+
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+
+    /// Gets the pose of a space in this frame, if it's tracked
+    pub fn get_pose(&self, space: &XRSpace) -> Option<ApiPose> {
+        space.get_pose(&self.data)
+    }
+
+    /// Creates an anchor at a pose of this frame
+    ///
+    /// https://immersive-web.github.io/anchors/#create-an-anchor-from-frame
+    pub fn create_anchor(&self, pose: Option<ApiPose>, comp: InCompartment) -> Rc<Promise> {
+        let global = self.global();
+        let p = Promise::new_in_current_compartment(&global, comp);
+        // Step 1
+        if !self.session.has_feature("anchors") {
+            p.reject_error(Error::NotSupported);
+            return p;
+        }
+        // Step 2
+        if !self.active.get() || self.session.is_ended() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        // Step 3-6: XXX anchors don't follow the tracking of the device,
+        // they stay where they were created.
+        match pose {
+            Some(pose) => p.resolve_native(&XRAnchor::new(&global, &self.session, pose)),
+            None => p.reject_error(Error::InvalidState),
+        }
+        p
+    }
 }
 
 impl XRFrameMethods for XRFrame {
@@ -106,4 +147,37 @@ impl XRFrameMethods for XRFrame {
         let pose = relative_to.inverse().pre_transform(&space);
         Ok(Some(XRPose::new(&self.global(), pose)))
     }
+
+    /// https://immersive-web.github.io/hit-test/#dom-xrframe-gethittestresults
+    fn GetHitTestResults(
+        &self,
+        source: &XRHitTestSource,
+    ) -> Result<Vec<DomRoot<XRHitTestResult>>, Error> {
+        // Step 1-2
+        if !self.active.get() || !self.animation_frame.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3-5
+        Ok(source
+            .hit_test(self)
+            .map(|pose| XRHitTestResult::new(&self.global(), self, pose))
+            .into_iter()
+            .collect())
+    }
+
+    /// https://immersive-web.github.io/anchors/#dom-xrframe-createanchor
+    fn CreateAnchor(
+        &self,
+        pose: &XRRigidTransform,
+        space: &XRSpace,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let pose = if self.session == space.session() {
+            self.get_pose(space)
+                .map(|space| space.pre_transform(&pose.transform()))
+        } else {
+            None
+        };
+        self.create_anchor(pose, comp)
+    }
 }
@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::compartments::InCompartment;
+use crate::dom::bindings::codegen::Bindings::XRHitTestResultBinding;
+use crate::dom::bindings::codegen::Bindings::XRHitTestResultBinding::XRHitTestResultMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::xrframe::XRFrame;
+use crate::dom::xrpose::XRPose;
+use crate::dom::xrsession::ApiPose;
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct XRHitTestResult {
+    reflector_: Reflector,
+    frame: Dom<XRFrame>,
+    #[ignore_malloc_size_of = "defined in euclid"]
+    pose: ApiPose,
+}
+
+impl XRHitTestResult {
+    fn new_inherited(frame: &XRFrame, pose: ApiPose) -> XRHitTestResult {
+        XRHitTestResult {
+            reflector_: Reflector::new(),
+            frame: Dom::from_ref(frame),
+            pose,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, frame: &XRFrame, pose: ApiPose) -> DomRoot<XRHitTestResult> {
+        reflect_dom_object(
+            Box::new(XRHitTestResult::new_inherited(frame, pose)),
+            global,
+            XRHitTestResultBinding::Wrap,
+        )
+    }
+}
+
+impl XRHitTestResultMethods for XRHitTestResult {
+    /// https://immersive-web.github.io/hit-test/#dom-xrhittestresult-getpose
+    fn GetPose(&self, base_space: &XRSpace) -> Result<Option<DomRoot<XRPose>>, Error> {
+        if !self.frame.is_active() {
+            return Err(Error::InvalidState);
+        }
+        let base_pose = match self.frame.get_pose(base_space) {
+            Some(pose) => pose,
+            None => return Ok(None),
+        };
+        let pose = base_pose.inverse().pre_transform(&self.pose);
+        Ok(Some(XRPose::new(&self.global(), pose)))
+    }
+
+    /// https://immersive-web.github.io/anchors/#dom-xrhittestresult-createanchor
+    fn CreateAnchor(&self, comp: InCompartment) -> Rc<Promise> {
+        self.frame.create_anchor(Some(self.pose), comp)
+    }
+}
@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRHitTestSourceBinding;
+use crate::dom::bindings::codegen::Bindings::XRHitTestSourceBinding::{
+    XRHitTestOptionsInit, XRHitTestSourceMethods, XRHitTestTrackableType,
+};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrframe::XRFrame;
+use crate::dom::xrsession::ApiPose;
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use euclid::{Angle, RigidTransform3D, Rotation3D, Vector3D};
+use std::cell::Cell;
+
+/// The height of the floor in native space, which is where hit tests find
+/// a plane, like local-floor reference spaces assume
+const FLOOR_HEIGHT: f32 = -2.;
+
+#[dom_struct]
+pub struct XRHitTestSource {
+    reflector_: Reflector,
+    space: Dom<XRSpace>,
+    entity_types: Vec<XRHitTestTrackableType>,
+    canceled: Cell<bool>,
+}
+
+impl XRHitTestSource {
+    fn new_inherited(options: &XRHitTestOptionsInit) -> XRHitTestSource {
+        XRHitTestSource {
+            reflector_: Reflector::new(),
+            space: Dom::from_ref(&*options.space),
+            // https://immersive-web.github.io/hit-test/#dom-xrhittestoptionsinit-entitytypes
+            entity_types: options
+                .entityTypes
+                .clone()
+                .unwrap_or_else(|| vec![XRHitTestTrackableType::Plane]),
+            canceled: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, options: &XRHitTestOptionsInit) -> DomRoot<XRHitTestSource> {
+        reflect_dom_object(
+            Box::new(XRHitTestSource::new_inherited(options)),
+            global,
+            XRHitTestSourceBinding::Wrap,
+        )
+    }
+
+    /// Casts the ray of the source in a frame, and returns the pose of
+    /// the place it hits, if any
+    ///
+    /// XXX the only plane we know of is the floor, so this is where the
+    /// results are. Devices should tell us about the world around them.
+    pub fn hit_test(&self, frame: &XRFrame) -> Option<ApiPose> {
+        if self.canceled.get() || !self.entity_types.contains(&XRHitTestTrackableType::Plane) {
+            return None;
+        }
+        // https://immersive-web.github.io/hit-test/#xrray
+        // The ray starts at the origin of the space and goes along its -Z axis
+        let pose = frame.get_pose(&self.space)?;
+        let origin = pose.translation;
+        let direction = pose.rotation.transform_vector3d(Vector3D::new(0., 0., -1.));
+        if direction.y >= 0. {
+            return None;
+        }
+        let distance = (FLOOR_HEIGHT - origin.y) / direction.y;
+        if distance < 0. {
+            return None;
+        }
+        let point = origin + direction * distance;
+
+        // The Y axis of the result is the normal of the plane, and its
+        // Z axis points toward the origin of the ray.
+        let towards_origin = origin - point;
+        let angle = towards_origin.x.atan2(towards_origin.z);
+        let rotation = Rotation3D::around_y(Angle::radians(angle));
+        Some(RigidTransform3D::new(rotation, point))
+    }
+}
+
+impl XRHitTestSourceMethods for XRHitTestSource {
+    /// https://immersive-web.github.io/hit-test/#dom-xrhittestsource-cancel
+    fn Cancel(&self) -> ErrorResult {
+        // Step 1
+        if self.canceled.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2-3
+        self.canceled.set(true);
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@
 
 use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding;
 use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::{
-    XRHandedness, XRInputSourceMethods,
+    XRHandedness, XRInputSourceMethods, XRTargetRayMode,
 };
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
@@ -12,7 +12,7 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::xrsession::XRSession;
 use crate::dom::xrspace::XRSpace;
 use dom_struct::dom_struct;
-use webxr_api::{Handedness, InputId, InputSource};
+use webxr_api::{Handedness, InputId, InputSource, TargetRayMode};
 
 #[dom_struct]
 pub struct XRInputSource {
@@ -52,6 +52,10 @@ impl XRInputSource {
     pub fn id(&self) -> InputId {
         self.info.id
     }
+
+    pub fn info(&self) -> InputSource {
+        self.info
+    }
 }
 
 impl XRInputSourceMethods for XRInputSource {
@@ -64,6 +68,15 @@ impl XRInputSourceMethods for XRInputSource {
         }
     }
 
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetraymode
+    fn TargetRayMode(&self) -> XRTargetRayMode {
+        match self.info.target_ray_mode {
+            TargetRayMode::Gaze => XRTargetRayMode::Gaze,
+            TargetRayMode::TrackedPointer => XRTargetRayMode::Tracked_pointer,
+            TargetRayMode::Screen => XRTargetRayMode::Screen,
+        }
+    }
+
     /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetrayspace
     fn TargetRaySpace(&self) -> DomRoot<XRSpace> {
         self.target_ray_space.or_init(|| {
@@ -75,7 +88,7 @@ impl XRInputSourceMethods for XRInputSource {
     /// https://immersive-web.github.io/webxr/#dom-xrinputsource-gripspace
     fn GetGripSpace(&self) -> Option<DomRoot<XRSpace>> {
         if self.info.supports_grip {
-            Some(self.target_ray_space.or_init(|| {
+            Some(self.grip_space.or_init(|| {
                 let global = self.global();
                 XRSpace::new_inputspace(&global, &self.session, &self, true)
             }))
@@ -11,12 +11,15 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::xrinputsource::XRInputSource;
 use crate::dom::xrsession::XRSession;
 use dom_struct::dom_struct;
-use webxr_api::InputId;
+use webxr_api::{Frame, InputId, InputSource};
 
 #[dom_struct]
 pub struct XRInputSourceArray {
     reflector_: Reflector,
     input_sources: DomRefCell<Vec<Dom<XRInputSource>>>,
+    /// The input sources that were removed, in case they come back
+    #[ignore_malloc_size_of = "Defined in rust-webxr"]
+    disconnected: DomRefCell<Vec<InputSource>>,
 }
 
 impl XRInputSourceArray {
@@ -24,6 +27,7 @@ impl XRInputSourceArray {
         XRInputSourceArray {
             reflector_: Reflector::new(),
             input_sources: DomRefCell::new(vec![]),
+            disconnected: DomRefCell::new(vec![]),
         }
     }
 
@@ -35,17 +39,59 @@ impl XRInputSourceArray {
         )
     }
 
-    pub fn set_initial_inputs(&self, session: &XRSession) {
+    /// Adds the input sources the session starts with, and returns them
+    pub fn set_initial_inputs(&self, session: &XRSession) -> Vec<DomRoot<XRInputSource>> {
         let mut input_sources = self.input_sources.borrow_mut();
         let global = self.global();
+        let mut added = vec![];
         session.with_session(|sess| {
             for info in sess.initial_inputs() {
-                // XXXManishearth we should be able to listen for updates
-                // to the input sources
                 let input = XRInputSource::new(&global, &session, *info);
                 input_sources.push(Dom::from_ref(&input));
+                added.push(input);
             }
         });
+        added
+    }
+
+    /// Updates the input sources with the ones that are tracked in a frame,
+    /// and returns the ones that were added and removed
+    ///
+    /// https://immersive-web.github.io/webxr/#list-of-active-input-sources
+    pub fn update_inputs(
+        &self,
+        session: &XRSession,
+        frame: &Frame,
+    ) -> (Vec<DomRoot<XRInputSource>>, Vec<DomRoot<XRInputSource>>) {
+        let tracked = |id: InputId| frame.inputs.iter().any(|input| input.id == id);
+        let mut input_sources = self.input_sources.borrow_mut();
+        let mut disconnected = self.disconnected.borrow_mut();
+
+        let mut removed = vec![];
+        input_sources.retain(|input| {
+            if tracked(input.id()) {
+                return true;
+            }
+            disconnected.push(input.info());
+            removed.push(DomRoot::from_ref(&**input));
+            false
+        });
+
+        // Input sources that come back are new objects, as they are when
+        // they are connected again.
+        let global = self.global();
+        let mut added = vec![];
+        disconnected.retain(|info| {
+            if !tracked(info.id) {
+                return true;
+            }
+            let input = XRInputSource::new(&global, session, *info);
+            input_sources.push(Dom::from_ref(&input));
+            added.push(input);
+            false
+        });
+
+        (added, removed)
     }
 
     pub fn find(&self, id: InputId) -> Option<DomRoot<XRInputSource>> {
@@ -0,0 +1,27 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::root::Dom;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct XRLayer {
+    eventtarget: EventTarget,
+    session: Dom<XRSession>,
+}
+
+impl XRLayer {
+    pub fn new_inherited(session: &XRSession) -> XRLayer {
+        XRLayer {
+            eventtarget: EventTarget::new_inherited(),
+            session: Dom::from_ref(session),
+        }
+    }
+
+    pub fn session(&self) -> &XRSession {
+        &self.session
+    }
+}
@@ -2,14 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::XRRenderStateBinding::{self, XRRenderStateMethods};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrlayer::XRLayer;
 use crate::dom::xrwebgllayer::XRWebGLLayer;
-
+use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, UndefinedValue};
 use std::cell::Cell;
 
 #[dom_struct]
@@ -18,6 +24,11 @@ pub struct XRRenderState {
     depth_near: Cell<f64>,
     depth_far: Cell<f64>,
     layer: MutNullableDom<XRWebGLLayer>,
+    /// https://immersive-web.github.io/layers/#dom-xrrenderstate-layers
+    layers: DomRefCell<Vec<Dom<XRLayer>>>,
+    /// The `layers` array, until they change
+    #[ignore_malloc_size_of = "mozjs"]
+    layers_array: Heap<JSVal>,
 }
 
 impl XRRenderState {
@@ -31,6 +42,8 @@ impl XRRenderState {
             depth_near: Cell::new(depth_near),
             depth_far: Cell::new(depth_far),
             layer: MutNullableDom::new(layer),
+            layers: DomRefCell::new(vec![]),
+            layers_array: Heap::default(),
         }
     }
 
@@ -48,12 +61,19 @@ impl XRRenderState {
     }
 
     pub fn clone_object(&self) -> DomRoot<Self> {
-        XRRenderState::new(
+        let state = XRRenderState::new(
             &self.global(),
             self.depth_near.get(),
             self.depth_far.get(),
             self.layer.get().as_ref().map(|x| &**x),
-        )
+        );
+        *state.layers.borrow_mut() = self
+            .layers
+            .borrow()
+            .iter()
+            .map(|layer| Dom::from_ref(&**layer))
+            .collect();
+        state
     }
 
     pub fn set_depth_near(&self, depth: f64) {
@@ -65,6 +85,15 @@ impl XRRenderState {
     pub fn set_layer(&self, layer: Option<&XRWebGLLayer>) {
         self.layer.set(layer)
     }
+    /// Sets the layers to the given one, which becomes the base layer
+    pub fn set_layers(&self, layer: Option<&XRWebGLLayer>) {
+        self.layer.set(layer);
+        *self.layers.borrow_mut() = layer
+            .map(|layer| Dom::from_ref(layer.upcast::<XRLayer>()))
+            .into_iter()
+            .collect();
+        self.layers_array.set(UndefinedValue());
+    }
 }
 
 impl XRRenderStateMethods for XRRenderState {
@@ -82,4 +111,21 @@ impl XRRenderStateMethods for XRRenderState {
     fn GetBaseLayer(&self) -> Option<DomRoot<XRWebGLLayer>> {
         self.layer.get()
     }
+
+    /// https://immersive-web.github.io/layers/#dom-xrrenderstate-layers
+    #[allow(unsafe_code)]
+    fn Layers(&self, cx: JSContext) -> JSVal {
+        if self.layers_array.get().is_undefined() {
+            let layers: Vec<DomRoot<XRLayer>> = self
+                .layers
+                .borrow()
+                .iter()
+                .map(|layer| DomRoot::from_ref(&**layer))
+                .collect();
+            rooted!(in(*cx) let mut array = UndefinedValue());
+            unsafe { layers.to_jsval(*cx, array.handle_mut()) };
+            self.layers_array.set(array.get());
+        }
+        self.layers_array.get()
+    }
 }
@@ -8,6 +8,7 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::XRHitTestSourceBinding::XRHitTestOptionsInit;
 use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceType;
 use crate::dom::bindings::codegen::Bindings::XRRenderStateBinding::XRRenderStateInit;
 use crate::dom::bindings::codegen::Bindings::XRRenderStateBinding::XRRenderStateMethods;
@@ -30,8 +31,11 @@ use crate::dom::node::Node;
 use crate::dom::node::NodeDamage;
 use crate::dom::promise::Promise;
 use crate::dom::xrframe::XRFrame;
+use crate::dom::xrhittestsource::XRHitTestSource;
+use crate::dom::xrinputsource::XRInputSource;
 use crate::dom::xrinputsourcearray::XRInputSourceArray;
 use crate::dom::xrinputsourceevent::XRInputSourceEvent;
+use crate::dom::xrinputsourceschangeevent::XRInputSourcesChangeEvent;
 use crate::dom::xrreferencespace::XRReferenceSpace;
 use crate::dom::xrrenderstate::XRRenderState;
 use crate::dom::xrsessionevent::XRSessionEvent;
@@ -78,6 +82,8 @@ pub struct XRSession {
     /// Opaque framebuffers need to know the session is "outside of a requestAnimationFrame"
     /// https://immersive-web.github.io/webxr/#opaque-framebuffer
     outside_raf: Cell<bool>,
+    /// https://immersive-web.github.io/webxr/#xrsession-list-of-enabled-features
+    enabled_features: Vec<String>,
 }
 
 impl XRSession {
@@ -85,6 +91,7 @@ impl XRSession {
         session: Session,
         render_state: &XRRenderState,
         input_sources: &XRInputSourceArray,
+        enabled_features: Vec<String>,
     ) -> XRSession {
         XRSession {
             eventtarget: EventTarget::new_inherited(),
@@ -104,10 +111,15 @@ impl XRSession {
             end_promises: DomRefCell::new(vec![]),
             ended: Cell::new(false),
             outside_raf: Cell::new(true),
+            enabled_features,
         }
     }
 
-    pub fn new(global: &GlobalScope, session: Session) -> DomRoot<XRSession> {
+    pub fn new(
+        global: &GlobalScope,
+        session: Session,
+        enabled_features: Vec<String>,
+    ) -> DomRoot<XRSession> {
         let render_state = XRRenderState::new(global, 0.1, 1000.0, None);
         let input_sources = XRInputSourceArray::new(global);
         let ret = reflect_dom_object(
@@ -115,11 +127,13 @@ impl XRSession {
                 session,
                 &render_state,
                 &input_sources,
+                enabled_features,
             )),
             global,
             XRSessionBinding::Wrap,
         );
-        input_sources.set_initial_inputs(&ret);
+        let initial_inputs = input_sources.set_initial_inputs(&ret);
+        ret.queue_input_sources_change(initial_inputs, vec![]);
         ret.attach_event_handler();
         ret.setup_raf_loop();
         ret
@@ -134,6 +148,47 @@ impl XRSession {
         self.ended.get()
     }
 
+    /// Whether the feature was requested, and is supported
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.enabled_features.iter().any(|f| f == feature)
+    }
+
+    /// Queues a task to fire an `inputsourceschange` event, if any input
+    /// source was added or removed
+    ///
+    /// https://immersive-web.github.io/webxr/#event-types
+    fn queue_input_sources_change(
+        &self,
+        added: Vec<DomRoot<XRInputSource>>,
+        removed: Vec<DomRoot<XRInputSource>>,
+    ) {
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        let this = Trusted::new(self);
+        let added: Vec<_> = added.iter().map(|input| Trusted::new(&**input)).collect();
+        let removed: Vec<_> = removed.iter().map(|input| Trusted::new(&**input)).collect();
+        let global = self.global();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(xr_input_sources_change: move || {
+                let this = this.root();
+                let added: Vec<_> = added.iter().map(|input| input.root()).collect();
+                let removed: Vec<_> = removed.iter().map(|input| input.root()).collect();
+                let event = XRInputSourcesChangeEvent::new(
+                    &this.global(),
+                    atom!("inputsourceschange"),
+                    false,
+                    false,
+                    &this,
+                    &added,
+                    &removed,
+                );
+                event.upcast::<Event>().fire(this.upcast());
+            }),
+            &global,
+        );
+    }
+
     fn setup_raf_loop(&self) {
         assert!(
             self.raf_sender.borrow().is_none(),
@@ -307,6 +362,9 @@ impl XRSession {
             self.session.borrow_mut().apply_event(event)
         }
 
+        let (added, removed) = self.input_sources.update_inputs(self, &frame);
+        self.queue_input_sources_change(added, removed);
+
         // Step 2
         let base_layer = match self.active_render_state.get().GetBaseLayer() {
             Some(layer) => layer,
@@ -351,6 +409,13 @@ impl XRSessionMethods for XRSession {
     /// https://immersive-web.github.io/webxr/#eventdef-xrsession-end
     event_handler!(end, GetOnend, SetOnend);
 
+    /// https://immersive-web.github.io/webxr/#eventdef-xrsession-inputsourceschange
+    event_handler!(
+        inputsourceschange,
+        GetOninputsourceschange,
+        SetOninputsourceschange
+    );
+
     /// https://immersive-web.github.io/webxr/#eventdef-xrsession-select
     event_handler!(select, GetOnselect, SetOnselect);
 
@@ -394,6 +459,36 @@ impl XRSessionMethods for XRSession {
             }
         }
 
+        // https://immersive-web.github.io/layers/#updaterenderstatechanges
+        let layer = match init.layers {
+            Some(ref layers) => {
+                if init.baseLayer.is_some() {
+                    return Err(Error::NotSupported);
+                }
+                if layers
+                    .iter()
+                    .any(|layer| Dom::from_ref(layer.session()) != Dom::from_ref(self))
+                {
+                    return Err(Error::Type(
+                        "The layers must be created for this session".into(),
+                    ));
+                }
+                // XXX composite more than one layer
+                if layers.len() > 1 {
+                    return Err(Error::NotSupported);
+                }
+                let layer = match layers.first() {
+                    Some(layer) => Some(
+                        DomRoot::downcast::<XRWebGLLayer>(layer.clone())
+                            .ok_or(Error::NotSupported)?,
+                    ),
+                    None => None,
+                };
+                Some(layer)
+            },
+            None => None,
+        };
+
         // XXXManishearth step 4:
         // If newState’s inlineVerticalFieldOfView is set and session is an
         // immersive session, throw an InvalidStateError and abort these steps.
@@ -410,6 +505,9 @@ impl XRSessionMethods for XRSession {
         if let Some(ref layer) = init.baseLayer {
             pending.set_layer(Some(&layer))
         }
+        if let Some(layer) = layer {
+            pending.set_layers(layer.as_ref().map(|layer| &**layer));
+        }
 
         if init.depthFar.is_some() || init.depthNear.is_some() {
             self.session
@@ -472,6 +570,32 @@ impl XRSessionMethods for XRSession {
         p
     }
 
+    /// https://immersive-web.github.io/hit-test/#dom-xrsession-requesthittestsource
+    fn RequestHitTestSource(
+        &self,
+        options: &XRHitTestOptionsInit,
+        comp: InCompartment,
+    ) -> Rc<Promise> {
+        let p = Promise::new_in_current_compartment(&self.global(), comp);
+        // Step 2
+        if !self.has_feature("hit-test") {
+            p.reject_error(Error::NotSupported);
+            return p;
+        }
+        // Step 3
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        if Dom::from_ref(options.space.session()) != Dom::from_ref(self) {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        // Step 4-7
+        p.resolve_native(&XRHitTestSource::new(&self.global(), options));
+        p
+    }
+
     /// https://immersive-web.github.io/webxr/#dom-xrsession-inputsources
     fn InputSources(&self) -> DomRoot<XRInputSourceArray> {
         DomRoot::from_ref(&*self.input_sources)
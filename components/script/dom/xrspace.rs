@@ -8,6 +8,7 @@ use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xranchor::XRAnchor;
 use crate::dom::xrinputsource::XRInputSource;
 use crate::dom::xrreferencespace::XRReferenceSpace;
 use crate::dom::xrsession::{cast_transform, ApiPose, XRSession};
@@ -19,6 +20,7 @@ pub struct XRSpace {
     eventtarget: EventTarget,
     session: Dom<XRSession>,
     input_source: MutNullableDom<XRInputSource>,
+    anchor: MutNullableDom<XRAnchor>,
     /// If we're an input space, are we an aim space or a grip space?
     is_grip_space: bool,
 }
@@ -29,6 +31,7 @@ impl XRSpace {
             eventtarget: EventTarget::new_inherited(),
             session: Dom::from_ref(session),
             input_source: Default::default(),
+            anchor: Default::default(),
             is_grip_space: false,
         }
     }
@@ -42,6 +45,7 @@ impl XRSpace {
             eventtarget: EventTarget::new_inherited(),
             session: Dom::from_ref(session),
             input_source: MutNullableDom::new(Some(input)),
+            anchor: Default::default(),
             is_grip_space,
         }
     }
@@ -58,6 +62,28 @@ impl XRSpace {
             XRSpaceBinding::Wrap,
         )
     }
+
+    fn new_anchorspace_inner(session: &XRSession, anchor: &XRAnchor) -> XRSpace {
+        XRSpace {
+            eventtarget: EventTarget::new_inherited(),
+            session: Dom::from_ref(session),
+            input_source: Default::default(),
+            anchor: MutNullableDom::new(Some(anchor)),
+            is_grip_space: false,
+        }
+    }
+
+    pub fn new_anchorspace(
+        global: &GlobalScope,
+        session: &XRSession,
+        anchor: &XRAnchor,
+    ) -> DomRoot<XRSpace> {
+        reflect_dom_object(
+            Box::new(XRSpace::new_anchorspace_inner(session, anchor)),
+            global,
+            XRSpaceBinding::Wrap,
+        )
+    }
 }
 
 impl XRSpace {
@@ -75,17 +101,15 @@ impl XRSpace {
             //
             // Also, the below code is quadratic, so this API may need an overhaul anyway
             let id = source.id();
-            // XXXManishearth once we have dynamic inputs we'll need to handle this better
-            let frame = base_pose
-                .inputs
-                .iter()
-                .find(|i| i.id == id)
-                .expect("no input found");
+            // The input source may no longer be tracked
+            let frame = base_pose.inputs.iter().find(|i| i.id == id)?;
             if self.is_grip_space {
                 frame.grip_origin.map(cast_transform)
             } else {
                 frame.target_ray_origin.map(cast_transform)
             }
+        } else if let Some(anchor) = self.anchor.get() {
+            anchor.pose()
         } else {
             unreachable!()
         }
@@ -9,12 +9,13 @@ use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerIn
 use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerMethods;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::error::Fallible;
-use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::webglframebuffer::WebGLFramebuffer;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::window::Window;
+use crate::dom::xrlayer::XRLayer;
 use crate::dom::xrsession::XRSession;
 use crate::dom::xrview::XRView;
 use crate::dom::xrviewport::XRViewport;
@@ -26,7 +27,7 @@ use webxr_api::Views;
 
 #[dom_struct]
 pub struct XRWebGLLayer {
-    reflector_: Reflector,
+    xrlayer: XRLayer,
     antialias: bool,
     depth: bool,
     stencil: bool,
@@ -34,7 +35,6 @@ pub struct XRWebGLLayer {
     #[ignore_malloc_size_of = "ids don't malloc"]
     swap_chain_id: WebXRSwapChainId,
     context: Dom<WebGLRenderingContext>,
-    session: Dom<XRSession>,
     framebuffer: Dom<WebGLFramebuffer>,
 }
 
@@ -47,14 +47,13 @@ impl XRWebGLLayer {
         framebuffer: &WebGLFramebuffer,
     ) -> XRWebGLLayer {
         XRWebGLLayer {
-            reflector_: Reflector::new(),
+            xrlayer: XRLayer::new_inherited(session),
             antialias: init.antialias,
             depth: init.depth,
             stencil: init.stencil,
             alpha: init.alpha,
             swap_chain_id,
             context: Dom::from_ref(context),
-            session: Dom::from_ref(session),
             framebuffer: Dom::from_ref(framebuffer),
         }
     }
@@ -124,7 +123,7 @@ impl XRWebGLLayer {
     }
 
     pub fn session(&self) -> &XRSession {
-        &self.session
+        self.xrlayer.session()
     }
 
     pub fn swap_buffers(&self) {
@@ -187,11 +186,11 @@ impl XRWebGLLayerMethods for XRWebGLLayer {
 
     /// https://immersive-web.github.io/webxr/#dom-xrwebgllayer-getviewport
     fn GetViewport(&self, view: &XRView) -> Option<DomRoot<XRViewport>> {
-        if self.session != view.session() {
+        if Dom::from_ref(self.session()) != view.session() {
             return None;
         }
 
-        let views = self.session.with_session(|s| s.views().clone());
+        let views = self.session().with_session(|s| s.views().clone());
 
         let viewport = match (view.Eye(), views) {
             (XREye::None, Views::Mono(view)) => view.viewport,
@@ -3,31 +3,37 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::compartments::{enter_realm, InCompartment};
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseBinding::ResponseMethods;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DOMResponseType;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::Guard;
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::readablestreamdefaultreader::ReadableStreamDefaultReader;
 use crate::dom::request::Request;
 use crate::dom::response::Response;
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::network_listener::{
     self, submit_timing_data, NetworkListener, PreInvoke, ResourceTimingListener,
 };
-use crate::task_source::TaskSourceName;
-use ipc_channel::ipc;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crossbeam_channel::Receiver;
+use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use net_traits::request::{
-    CorsSettings, CredentialsMode, Destination, RequestBuilder, RequestMode,
+    BodyChunkRequest, CorsSettings, CredentialsMode, Destination, RequestBuilder, RequestMode,
 };
 use net_traits::request::{Request as NetTraitsRequest, ServiceWorkersMode};
 use net_traits::CoreResourceMsg::Fetch as NetTraitsFetch;
@@ -36,15 +42,18 @@ use net_traits::{FetchChannels, FetchResponseListener, NetworkError};
 use net_traits::{FetchMetadata, FilteredMetadata, Metadata};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use servo_url::ServoUrl;
-use std::mem;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 struct FetchContext {
     fetch_promise: Option<TrustedPromise>,
     response_object: Trusted<Response>,
-    body: Vec<u8>,
+    /// The signal of the request, kept alive along with the abort algorithm
+    /// cancelling this fetch until the fetch ends.
+    signal: Trusted<AbortSignal>,
     resource_timing: ResourceFetchTiming,
+    /// The URL of the request, which names its resource timing entry.
+    url: ServoUrl,
 }
 
 /// RAII fetch canceller object. By default initialized to not having a canceller
@@ -106,6 +115,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         headers: request.headers.clone(),
         unsafe_request: request.unsafe_request,
         body: request.body.clone(),
+        body_stream: None,
         service_workers_mode: ServiceWorkersMode::All,
         destination: request.destination,
         synchronous: request.synchronous,
@@ -146,18 +156,37 @@ pub fn Fetch(
     let response = Response::new(global);
 
     // Step 2
-    let request = match Request::Constructor(global, input, init) {
+    let request_object = match Request::Constructor(global, input, init) {
         Err(e) => {
             promise.reject_error(e);
             return promise;
         },
-        Ok(r) => r.get_request(),
+        Ok(r) => r,
     };
+    let request = request_object.get_request();
+    let signal = request_object.Signal();
+    if signal.aborted() {
+        let cx = global.get_cx();
+        rooted!(in(*cx) let reason = signal.reason());
+        promise.reject(cx, reason.handle());
+        return promise;
+    }
     let timing_type = request.timing_type();
 
     let mut request_init = request_init_from_request(request);
     request_init.csp_list = global.get_csp_list().clone();
 
+    // A stream body is read as the net component asks for its chunks.
+    if let Some(stream) = request_object.body_stream() {
+        match stream_request_body(global, &stream) {
+            Ok(body_stream) => request_init.body_stream = Some(body_stream),
+            Err(e) => {
+                promise.reject_error(e);
+                return promise;
+            },
+        }
+    }
+
     // Step 3
     if global.downcast::<ServiceWorkerGlobalScope>().is_some() {
         request_init.service_workers_mode = ServiceWorkersMode::None;
@@ -166,13 +195,24 @@ pub fn Fetch(
     // Step 4
     response.Headers().set_guard(Guard::Immutable);
 
+    // Aborting the signal rejects the promise, errors the body of the response,
+    // and terminates the fetch in the net component.
+    let mut canceller = FetchCanceller::new();
+    let cancel_receiver = canceller.initialize();
+    signal.add_abort_algorithm(AbortAlgorithm::Fetch {
+        promise: promise.clone(),
+        response: Dom::from_ref(&*response),
+        canceller,
+    });
+
     // Step 5
     let (action_sender, action_receiver) = ipc::channel().unwrap();
     let fetch_context = Arc::new(Mutex::new(FetchContext {
         fetch_promise: Some(TrustedPromise::new(promise.clone())),
         response_object: Trusted::new(&*response),
-        body: vec![],
+        signal: Trusted::new(&*signal),
         resource_timing: ResourceFetchTiming::new(timing_type),
+        url: request_init.url.clone(),
     }));
     let listener = NetworkListener {
         context: fetch_context,
@@ -189,14 +229,65 @@ pub fn Fetch(
     core_resource_thread
         .send(NetTraitsFetch(
             request_init,
-            FetchChannels::ResponseMsg(action_sender, None),
+            FetchChannels::ResponseMsg(action_sender, Some(cancel_receiver)),
         ))
         .unwrap();
 
     promise
 }
 
-impl PreInvoke for FetchContext {}
+/// Locks `stream`, and returns the channel on which the net component asks
+/// for the chunks of the request body it is the source of. Each chunk is
+/// read from `stream` in a networking task.
+fn stream_request_body(
+    global: &GlobalScope,
+    stream: &ReadableStream,
+) -> Fallible<IpcSender<BodyChunkRequest>> {
+    ReadableStreamDefaultReader::new(global, stream)?;
+    let (body_sender, body_receiver) = ipc::channel().unwrap();
+    let stream = Trusted::new(stream);
+    let task_source = global.networking_task_source();
+    let canceller = global.task_canceller(TaskSourceName::Networking);
+    let mut chunk_sender = None;
+    ROUTER.add_route(
+        body_receiver.to_opaque(),
+        Box::new(move |message| {
+            let stream = stream.clone();
+            match message.to() {
+                Ok(BodyChunkRequest::Connect(sender)) => chunk_sender = Some(sender),
+                Ok(BodyChunkRequest::Chunk) => {
+                    let sender = match chunk_sender {
+                        Some(ref sender) => sender.clone(),
+                        None => return,
+                    };
+                    let _ = task_source.queue_with_canceller(
+                        task!(read_request_body_chunk: move || {
+                            stream.root().read_upload_chunk(sender);
+                        }),
+                        &canceller,
+                    );
+                },
+                Ok(BodyChunkRequest::Done) => {
+                    let _ = task_source.queue_with_canceller(
+                        task!(cancel_request_body: move || {
+                            stream.root().cancel_upload();
+                        }),
+                        &canceller,
+                    );
+                },
+                Err(_) => {},
+            }
+        }),
+    );
+    Ok(body_sender)
+}
+
+impl PreInvoke for FetchContext {
+    fn should_invoke(&self) -> bool {
+        // An aborted fetch has already settled the promise and the body.
+        !self.signal.root().aborted()
+    }
+}
 
 impl FetchResponseListener for FetchContext {
     fn process_request_body(&mut self) {
@@ -259,15 +350,21 @@ impl FetchResponseListener for FetchContext {
         self.fetch_promise = Some(TrustedPromise::new(promise));
     }
 
-    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
-        self.response_object.root().stream_chunk(chunk.as_slice());
-        self.body.append(&mut chunk);
+    fn process_response_chunk(&mut self, chunk: Vec<u8>) {
+        let response = self.response_object.root();
+        let _ac = enter_realm(&*response);
+        response.stream_chunk(chunk.as_slice());
     }
 
-    fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
+    fn process_response_eof(&mut self, result: Result<ResourceFetchTiming, NetworkError>) {
         let response = self.response_object.root();
         let _ac = enter_realm(&*response);
-        response.finish(mem::replace(&mut self.body, vec![]));
+        if result.is_err() {
+            response.error_stream();
+        }
+        response.finish();
+        // The fetch is over, aborting the signal must no longer affect it.
+        self.signal.root().remove_fetch(&response);
         // TODO
         // ... trailerObject is not supported in Servo yet.
     }
@@ -291,10 +388,7 @@ impl FetchResponseListener for FetchContext {
 
 impl ResourceTimingListener for FetchContext {
     fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
-        (
-            InitiatorType::Fetch,
-            self.resource_timing_global().get_url().clone(),
-        )
+        (InitiatorType::Fetch, self.url.clone())
     }
 
     fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
@@ -314,36 +408,73 @@ pub fn load_whole_resource(
     core_resource_thread: &CoreResourceThread,
     global: &GlobalScope,
 ) -> Result<(Metadata, Vec<u8>), NetworkError> {
-    let (action_sender, action_receiver) = ipc::channel().unwrap();
-    let url = request.url.clone();
-    core_resource_thread
-        .send(CoreResourceMsg::Fetch(
-            request,
-            FetchChannels::ResponseMsg(action_sender, None),
-        ))
-        .unwrap();
+    WholeResourceLoad::start(request, core_resource_thread).finish(global)
+}
 
-    let mut buf = vec![];
-    let mut metadata = None;
-    loop {
-        match action_receiver.recv().unwrap() {
-            FetchResponseMsg::ProcessRequestBody | FetchResponseMsg::ProcessRequestEOF => (),
-            FetchResponseMsg::ProcessResponse(Ok(m)) => {
-                metadata = Some(match m {
-                    FetchMetadata::Unfiltered(m) => m,
-                    FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
-                })
-            },
-            FetchResponseMsg::ProcessResponseChunk(data) => buf.extend_from_slice(&data),
-            FetchResponseMsg::ProcessResponseEOF(Ok(_)) => {
-                let metadata = metadata.unwrap();
-                if let Some(timing) = &metadata.timing {
-                    submit_timing_data(global, url, InitiatorType::Other, &timing);
-                }
-                return Ok((metadata, buf));
-            },
-            FetchResponseMsg::ProcessResponse(Err(e)) |
-            FetchResponseMsg::ProcessResponseEOF(Err(e)) => return Err(e),
+/// A synchronous load of a whole resource, whose response is buffered
+/// from the moment it is started, so that several loads can be started
+/// before waiting for any of them to finish.
+pub struct WholeResourceLoad {
+    url: ServoUrl,
+    action_receiver: Receiver<FetchResponseMsg>,
+    cancel_sender: IpcSender<()>,
+}
+
+impl WholeResourceLoad {
+    pub fn start(request: RequestBuilder, core_resource_thread: &CoreResourceThread) -> Self {
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let (cancel_sender, cancel_receiver) = ipc::channel().unwrap();
+        let url = request.url.clone();
+        core_resource_thread
+            .send(CoreResourceMsg::Fetch(
+                request,
+                FetchChannels::ResponseMsg(action_sender, Some(cancel_receiver)),
+            ))
+            .unwrap();
+        WholeResourceLoad {
+            url,
+            action_receiver: ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(action_receiver),
+            cancel_sender,
+        }
+    }
+
+    /// Wait for the whole response to the load.
+    pub fn finish(self, global: &GlobalScope) -> Result<(Metadata, Vec<u8>), NetworkError> {
+        // A terminated worker stops waiting, and cancels the load.
+        let never = crossbeam_channel::never();
+        let termination_receiver = global
+            .downcast::<WorkerGlobalScope>()
+            .and_then(|worker| worker.termination_receiver())
+            .unwrap_or(&never);
+
+        let mut buf = vec![];
+        let mut metadata = None;
+        loop {
+            let msg = select! {
+                recv(self.action_receiver) -> msg => msg.unwrap(),
+                recv(termination_receiver) -> _ => {
+                    let _ = self.cancel_sender.send(());
+                    return Err(NetworkError::Internal("Worker terminated".into()));
+                },
+            };
+            match msg {
+                FetchResponseMsg::ProcessRequestBody | FetchResponseMsg::ProcessRequestEOF => (),
+                FetchResponseMsg::ProcessResponse(Ok(m)) => {
+                    metadata = Some(match m {
+                        FetchMetadata::Unfiltered(m) => m,
+                        FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+                    })
+                },
+                FetchResponseMsg::ProcessResponseChunk(data) => buf.extend_from_slice(&data),
+                FetchResponseMsg::ProcessResponseEOF(Ok(timing)) => {
+                    if timing.timing_type == ResourceTimingType::Resource {
+                        submit_timing_data(global, self.url, InitiatorType::Other, &timing);
+                    }
+                    return Ok((metadata.unwrap(), buf));
+                },
+                FetchResponseMsg::ProcessResponse(Err(e)) |
+                FetchResponseMsg::ProcessResponseEOF(Err(e)) => return Err(e),
+            }
         }
     }
 }
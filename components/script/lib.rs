@@ -66,6 +66,8 @@ mod dom;
 #[warn(deprecated)]
 mod canvas_state;
 #[warn(deprecated)]
+mod cdm;
+#[warn(deprecated)]
 mod compartments;
 mod euclidext;
 #[warn(deprecated)]
@@ -77,17 +79,25 @@ mod init;
 #[warn(deprecated)]
 mod layout_image;
 #[warn(deprecated)]
+mod media_segment_parser;
+#[warn(deprecated)]
 mod mem;
 #[warn(deprecated)]
 mod microtask;
 #[warn(deprecated)]
 mod network_listener;
 #[warn(deprecated)]
+mod reporting;
+#[warn(deprecated)]
+mod script_module;
+#[warn(deprecated)]
 pub mod script_runtime;
 #[warn(deprecated)]
 #[allow(unsafe_code)]
 pub mod script_thread;
 #[warn(deprecated)]
+mod security_manager;
+#[warn(deprecated)]
 mod serviceworker_manager;
 #[warn(deprecated)]
 mod serviceworkerjob;
@@ -111,6 +121,10 @@ mod timers;
 mod unpremultiplytable;
 #[warn(deprecated)]
 mod webdriver_handlers;
+#[warn(deprecated)]
+mod webvtt;
+#[warn(deprecated)]
+mod xpath;
 
 pub use init::{init, init_service_workers};
 pub use script_runtime::JSEngineSetup;
@@ -129,6 +143,7 @@ pub mod layout_exports {
     pub use crate::dom::characterdata::LayoutCharacterDataHelpers;
     pub use crate::dom::document::{Document, LayoutDocumentHelpers, PendingRestyle};
     pub use crate::dom::element::{Element, LayoutElementHelpers, RawLayoutElementHelpers};
+    pub use crate::dom::htmlslotelement::{HTMLSlotElement, LayoutHTMLSlotElementHelpers};
     pub use crate::dom::node::NodeFlags;
     pub use crate::dom::node::{LayoutNodeHelpers, Node};
     pub use crate::dom::shadowroot::{LayoutShadowRootHelpers, ShadowRoot};
@@ -0,0 +1,875 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Segment parsers for the byte stream formats of Media Source Extensions.
+//!
+//! Only as much of the container is parsed as is needed to split appended
+//! bytes into initialization and media segments, and to find the
+//! presentation interval of each media segment. The segments are handed to
+//! the media player untouched, but for the timestamps of media segments,
+//! which are rewritten to apply the `timestampOffset` of their SourceBuffer.
+//!
+//! <https://w3c.github.io/mse-byte-stream-format-registry/>
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A byte stream format supported by `SourceBuffer`.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum ByteStreamFormat {
+    /// <https://w3c.github.io/mse-byte-stream-format-isobmff/>
+    IsoBmff,
+    /// <https://w3c.github.io/mse-byte-stream-format-webm/>
+    WebM,
+}
+
+impl ByteStreamFormat {
+    /// Returns the byte stream format of a MIME type, ignoring its
+    /// parameters.
+    pub fn from_mime_type(mime_type: &str) -> Option<ByteStreamFormat> {
+        let essence = mime_type.split(';').next().unwrap_or("").trim();
+        if essence.eq_ignore_ascii_case("video/mp4") || essence.eq_ignore_ascii_case("audio/mp4") {
+            Some(ByteStreamFormat::IsoBmff)
+        } else if essence.eq_ignore_ascii_case("video/webm") ||
+            essence.eq_ignore_ascii_case("audio/webm")
+        {
+            Some(ByteStreamFormat::WebM)
+        } else {
+            None
+        }
+    }
+}
+
+/// A complete segment found in the appended bytes.
+#[derive(Debug)]
+pub enum Segment {
+    /// <https://w3c.github.io/media-source/#init-segment>
+    Initialization {
+        /// The duration of the presentation in seconds, if known.
+        duration: Option<f64>,
+        data: Vec<u8>,
+    },
+    /// <https://w3c.github.io/media-source/#media-segment>
+    Media {
+        /// The presentation interval of the segment, in seconds.
+        start: f64,
+        end: f64,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The bytes do not follow the byte stream format.
+    InvalidData,
+    /// A media segment was appended before any initialization segment.
+    MissingInitializationSegment,
+}
+
+/// <https://w3c.github.io/media-source/#sourcebuffer-segment-parser-loop>
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct SegmentParser {
+    format: ByteStreamFormat,
+    /// Timescales of the tracks of the last initialization segment, by
+    /// track ID, for ISO BMFF.
+    timescales: HashMap<u32, u32>,
+    /// Default sample durations from the `trex` boxes of the last
+    /// initialization segment, by track ID, for ISO BMFF.
+    default_sample_durations: HashMap<u32, u32>,
+    /// The number of nanoseconds per timecode unit, for WebM.
+    timecode_scale: u64,
+    /// Whether an initialization segment has been parsed.
+    received_initialization_segment: bool,
+    /// Whether the parser is within the body of a WebM `Segment` element,
+    /// which usually has an unknown size.
+    in_webm_segment: bool,
+}
+
+impl SegmentParser {
+    pub fn new(format: ByteStreamFormat) -> SegmentParser {
+        SegmentParser {
+            format,
+            timescales: HashMap::new(),
+            default_sample_durations: HashMap::new(),
+            timecode_scale: DEFAULT_TIMECODE_SCALE,
+            received_initialization_segment: false,
+            in_webm_segment: false,
+        }
+    }
+
+    pub fn received_initialization_segment(&self) -> bool {
+        self.received_initialization_segment
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-reset-parser-state>
+    ///
+    /// Partially parsed segments live in the caller's input buffer, so only
+    /// the position within a WebM `Segment` is forgotten here.
+    pub fn reset(&mut self) {
+        self.in_webm_segment = false;
+    }
+
+    /// Removes the complete segments at the front of `input` and returns
+    /// them. Bytes of an incomplete segment are left in `input` until more
+    /// data is appended.
+    pub fn parse(&mut self, input: &mut Vec<u8>) -> Result<Vec<Segment>, ParseError> {
+        let (segments, consumed) = match self.format {
+            ByteStreamFormat::IsoBmff => self.parse_iso_bmff(input)?,
+            ByteStreamFormat::WebM => self.parse_webm(input)?,
+        };
+        input.drain(..consumed);
+        Ok(segments)
+    }
+
+    fn parse_iso_bmff(&mut self, input: &[u8]) -> Result<(Vec<Segment>, usize), ParseError> {
+        let mut segments = vec![];
+        let mut consumed = 0;
+        let mut offset = 0;
+        // The start of the segment whose boxes are being gathered.
+        let mut segment_start = None;
+        // The presentation interval of the last `moof` box, until its `mdat`
+        // box completes the media segment.
+        let mut pending_interval = None;
+        while let Some((box_type, header_size, box_size)) = read_box_header(&input[offset..])? {
+            if input.len() - offset < box_size {
+                break;
+            }
+            let payload = &input[offset + header_size..offset + box_size];
+            let end = offset + box_size;
+            match &box_type {
+                b"ftyp" => {
+                    segment_start = Some(offset);
+                },
+                b"moov" => {
+                    let duration = self.parse_moov(payload)?;
+                    let start = segment_start.take().unwrap_or(offset);
+                    segments.push(Segment::Initialization {
+                        duration,
+                        data: input[start..end].to_vec(),
+                    });
+                    self.received_initialization_segment = true;
+                    consumed = end;
+                },
+                b"moof" => {
+                    if !self.received_initialization_segment {
+                        return Err(ParseError::MissingInitializationSegment);
+                    }
+                    pending_interval = Some(self.parse_moof(payload)?);
+                    segment_start.get_or_insert(offset);
+                },
+                b"mdat" => {
+                    let (start, end_time) =
+                        pending_interval.take().ok_or(ParseError::InvalidData)?;
+                    let start_offset = segment_start.take().unwrap_or(offset);
+                    segments.push(Segment::Media {
+                        start,
+                        end: end_time,
+                        data: input[start_offset..end].to_vec(),
+                    });
+                    consumed = end;
+                },
+                b"styp" | b"sidx" | b"prft" | b"emsg" => {
+                    segment_start.get_or_insert(offset);
+                },
+                _ => {},
+            }
+            offset = end;
+            if segment_start.is_none() {
+                consumed = offset;
+            }
+        }
+        Ok((segments, consumed))
+    }
+
+    /// Records the track timescales of a `moov` box, returning the duration
+    /// of the presentation if it is known.
+    fn parse_moov(&mut self, moov: &[u8]) -> Result<Option<f64>, ParseError> {
+        self.timescales.clear();
+        self.default_sample_durations.clear();
+        let mut movie_timescale = 0;
+        let mut duration = None;
+        for (box_type, payload) in child_boxes(moov)? {
+            match &box_type {
+                b"mvhd" => {
+                    let mut reader = Reader::new(payload);
+                    let version = reader.read_version_and_flags()?.0;
+                    reader.skip(if version == 1 { 16 } else { 8 })?;
+                    movie_timescale = reader.read_u32()?;
+                    let movie_duration = if version == 1 {
+                        reader.read_u64()?
+                    } else {
+                        reader.read_u32()? as u64
+                    };
+                    if movie_timescale != 0 &&
+                        movie_duration != 0 &&
+                        movie_duration != u32::max_value() as u64 &&
+                        movie_duration != u64::max_value()
+                    {
+                        duration = Some(movie_duration as f64 / movie_timescale as f64);
+                    }
+                },
+                b"trak" => {
+                    let (track_id, timescale) = parse_trak(payload)?;
+                    self.timescales.insert(track_id, timescale);
+                },
+                b"mvex" => {
+                    for (box_type, payload) in child_boxes(payload)? {
+                        let mut reader = Reader::new(payload);
+                        match &box_type {
+                            b"mehd" => {
+                                let version = reader.read_version_and_flags()?.0;
+                                let fragment_duration = if version == 1 {
+                                    reader.read_u64()?
+                                } else {
+                                    reader.read_u32()? as u64
+                                };
+                                if movie_timescale != 0 && fragment_duration != 0 {
+                                    duration =
+                                        Some(fragment_duration as f64 / movie_timescale as f64);
+                                }
+                            },
+                            b"trex" => {
+                                reader.read_version_and_flags()?;
+                                let track_id = reader.read_u32()?;
+                                reader.skip(4)?;
+                                let default_sample_duration = reader.read_u32()?;
+                                self.default_sample_durations
+                                    .insert(track_id, default_sample_duration);
+                            },
+                            _ => {},
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+        if self.timescales.is_empty() {
+            return Err(ParseError::InvalidData);
+        }
+        Ok(duration)
+    }
+
+    /// Returns the presentation interval of the track fragments of a `moof`
+    /// box, in seconds.
+    fn parse_moof(&self, moof: &[u8]) -> Result<(f64, f64), ParseError> {
+        let mut interval: Option<(f64, f64)> = None;
+        for (box_type, traf) in child_boxes(moof)? {
+            if &box_type != b"traf" {
+                continue;
+            }
+            let mut track_id = None;
+            let mut default_sample_duration = None;
+            let mut base_media_decode_time = 0;
+            let mut duration = 0;
+            for (box_type, payload) in child_boxes(traf)? {
+                let mut reader = Reader::new(payload);
+                match &box_type {
+                    b"tfhd" => {
+                        let flags = reader.read_version_and_flags()?.1;
+                        let id = reader.read_u32()?;
+                        if flags & 0x1 != 0 {
+                            reader.skip(8)?;
+                        }
+                        if flags & 0x2 != 0 {
+                            reader.skip(4)?;
+                        }
+                        if flags & 0x8 != 0 {
+                            default_sample_duration = Some(reader.read_u32()?);
+                        }
+                        track_id = Some(id);
+                    },
+                    b"tfdt" => {
+                        let version = reader.read_version_and_flags()?.0;
+                        base_media_decode_time = if version == 1 {
+                            reader.read_u64()?
+                        } else {
+                            reader.read_u32()? as u64
+                        };
+                    },
+                    b"trun" => {
+                        let track_id = track_id.ok_or(ParseError::InvalidData)?;
+                        let default_sample_duration = default_sample_duration
+                            .or_else(|| self.default_sample_durations.get(&track_id).cloned())
+                            .unwrap_or(0);
+                        let flags = reader.read_version_and_flags()?.1;
+                        let sample_count = reader.read_u32()?;
+                        if flags & 0x1 != 0 {
+                            reader.skip(4)?;
+                        }
+                        if flags & 0x4 != 0 {
+                            reader.skip(4)?;
+                        }
+                        for _ in 0..sample_count {
+                            let sample_duration = if flags & 0x100 != 0 {
+                                reader.read_u32()?
+                            } else {
+                                default_sample_duration
+                            };
+                            duration += sample_duration as u64;
+                            for &(flag, size) in &[(0x200, 4), (0x400, 4), (0x800, 4)] {
+                                if flags & flag != 0 {
+                                    reader.skip(size)?;
+                                }
+                            }
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            let track_id = track_id.ok_or(ParseError::InvalidData)?;
+            let timescale = *self
+                .timescales
+                .get(&track_id)
+                .ok_or(ParseError::InvalidData)? as f64;
+            let start = base_media_decode_time as f64 / timescale;
+            let end = (base_media_decode_time + duration) as f64 / timescale;
+            interval = Some(match interval {
+                Some((min_start, max_end)) => (min_start.min(start), max_end.max(end)),
+                None => (start, end),
+            });
+        }
+        interval.ok_or(ParseError::InvalidData)
+    }
+
+    fn parse_webm(&mut self, input: &[u8]) -> Result<(Vec<Segment>, usize), ParseError> {
+        let mut segments = vec![];
+        let mut consumed = 0;
+        let mut offset = 0;
+        // The start of the initialization segment being gathered.
+        let mut segment_start = None;
+        let mut duration = None;
+        while let Some((id, header_size, size)) = read_ebml_element_header(&input[offset..])? {
+            match id {
+                EBML_HEADER_ID => {
+                    segment_start = Some(offset);
+                    self.in_webm_segment = false;
+                    self.timecode_scale = DEFAULT_TIMECODE_SCALE;
+                    duration = None;
+                },
+                SEGMENT_ID => {
+                    if segment_start.is_none() {
+                        return Err(ParseError::InvalidData);
+                    }
+                    // Descend into the segment, whose children make up both
+                    // the rest of the initialization segment and the media
+                    // segments.
+                    self.in_webm_segment = true;
+                    offset += header_size;
+                    continue;
+                },
+                CLUSTER_ID => {
+                    if !self.received_initialization_segment || !self.in_webm_segment {
+                        return Err(ParseError::MissingInitializationSegment);
+                    }
+                    let cluster_end = match size {
+                        Some(size) => offset + header_size + size,
+                        None => match find_unknown_size_cluster_end(input, offset + header_size)? {
+                            Some(end) => end,
+                            None => break,
+                        },
+                    };
+                    if cluster_end > input.len() {
+                        break;
+                    }
+                    let (start, end) =
+                        self.parse_cluster(&input[offset + header_size..cluster_end])?;
+                    segments.push(Segment::Media {
+                        start,
+                        end,
+                        data: input[offset..cluster_end].to_vec(),
+                    });
+                    offset = cluster_end;
+                    consumed = offset;
+                    continue;
+                },
+                _ => {},
+            }
+            let size = size.ok_or(ParseError::InvalidData)?;
+            let end = offset + header_size + size;
+            if end > input.len() {
+                break;
+            }
+            let payload = &input[offset + header_size..end];
+            match id {
+                INFO_ID => {
+                    for (id, payload) in child_elements(payload)? {
+                        match id {
+                            TIMECODE_SCALE_ID => self.timecode_scale = read_uint(payload)?,
+                            DURATION_ID => duration = Some(read_float(payload)?),
+                            _ => {},
+                        }
+                    }
+                },
+                TRACKS_ID => {
+                    let start = segment_start.take().ok_or(ParseError::InvalidData)?;
+                    let duration = duration
+                        .map(|duration| duration * self.timecode_scale as f64 / 1_000_000_000.);
+                    segments.push(Segment::Initialization {
+                        duration,
+                        data: input[start..end].to_vec(),
+                    });
+                    self.received_initialization_segment = true;
+                    consumed = end;
+                },
+                _ => {},
+            }
+            offset = end;
+            if segment_start.is_none() {
+                consumed = offset;
+            }
+        }
+        Ok((segments, consumed))
+    }
+
+    /// Shifts the timestamps of the media segment `data`, as returned by
+    /// `parse`, by `offset` seconds. Timestamps that would become negative
+    /// are clamped to zero.
+    pub fn apply_timestamp_offset(
+        &self,
+        data: &mut Vec<u8>,
+        offset: f64,
+    ) -> Result<(), ParseError> {
+        if offset == 0. {
+            return Ok(());
+        }
+        match self.format {
+            ByteStreamFormat::IsoBmff => self.offset_iso_bmff(data, offset),
+            ByteStreamFormat::WebM => {
+                *data = self.offset_webm_cluster(data, offset)?;
+                Ok(())
+            },
+        }
+    }
+
+    /// Rewrites the base media decode time of each track fragment, in the
+    /// `tfdt` boxes of the `moof` box of a media segment.
+    fn offset_iso_bmff(&self, data: &mut [u8], offset: f64) -> Result<(), ParseError> {
+        for (box_type, moof) in child_box_ranges(data, 0..data.len())? {
+            if &box_type != b"moof" {
+                continue;
+            }
+            for (box_type, traf) in child_box_ranges(data, moof)? {
+                if &box_type != b"traf" {
+                    continue;
+                }
+                let mut track_id = None;
+                for (box_type, payload) in child_box_ranges(data, traf)? {
+                    match &box_type {
+                        b"tfhd" => {
+                            let mut reader = Reader::new(&data[payload]);
+                            reader.read_version_and_flags()?;
+                            track_id = Some(reader.read_u32()?);
+                        },
+                        b"tfdt" => {
+                            let track_id = track_id.ok_or(ParseError::InvalidData)?;
+                            let timescale = *self
+                                .timescales
+                                .get(&track_id)
+                                .ok_or(ParseError::InvalidData)?;
+                            let shift = (offset * timescale as f64).round() as i64;
+                            let version = Reader::new(&data[payload.clone()])
+                                .read_version_and_flags()?
+                                .0;
+                            let field = if version == 1 { 8 } else { 4 };
+                            let start = payload.start + 4;
+                            if payload.end < start + field {
+                                return Err(ParseError::InvalidData);
+                            }
+                            let time = read_uint(&data[start..start + field])? as i64;
+                            let time = (time + shift).max(0) as u64;
+                            if field == 4 && time > u32::max_value() as u64 {
+                                return Err(ParseError::InvalidData);
+                            }
+                            write_uint(&mut data[start..start + field], time);
+                        },
+                        _ => {},
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of the cluster `data` whose timecode is shifted. The
+    /// shifted timecode may not fit in the original element, so the cluster
+    /// is written again with an 8 bytes timecode and a known size.
+    fn offset_webm_cluster(&self, data: &[u8], offset: f64) -> Result<Vec<u8>, ParseError> {
+        let (id, header_size, size) =
+            read_ebml_element_header(data)?.ok_or(ParseError::InvalidData)?;
+        if id != CLUSTER_ID {
+            return Err(ParseError::InvalidData);
+        }
+        let end = size.map_or(data.len(), |size| header_size + size);
+        if end > data.len() {
+            return Err(ParseError::InvalidData);
+        }
+        let shift = (offset * 1_000_000_000. / self.timecode_scale as f64).round() as i64;
+        let mut payload = vec![];
+        let mut position = header_size;
+        while position < end {
+            let (id, header_size, size) =
+                read_ebml_element_header(&data[position..])?.ok_or(ParseError::InvalidData)?;
+            let element_end = position + header_size + size.ok_or(ParseError::InvalidData)?;
+            if element_end > end {
+                return Err(ParseError::InvalidData);
+            }
+            if id == TIMECODE_ID {
+                let timecode = read_uint(&data[position + header_size..element_end])? as i64;
+                let mut value = [0; 8];
+                write_uint(&mut value, (timecode + shift).max(0) as u64);
+                // The ID, then a size of 8 as a 1 byte vint.
+                payload.extend_from_slice(&[TIMECODE_ID as u8, 0x88]);
+                payload.extend_from_slice(&value);
+            } else {
+                payload.extend_from_slice(&data[position..element_end]);
+            }
+            position = element_end;
+        }
+        let mut cluster = CLUSTER_ID.to_be_bytes().to_vec();
+        // The size as an 8 bytes vint, whose first byte is the length marker.
+        let mut size = (payload.len() as u64).to_be_bytes();
+        size[0] = 0x01;
+        cluster.extend_from_slice(&size);
+        cluster.extend(payload);
+        Ok(cluster)
+    }
+
+    /// Returns the presentation interval of the blocks of a cluster, in
+    /// seconds.
+    fn parse_cluster(&self, cluster: &[u8]) -> Result<(f64, f64), ParseError> {
+        let mut timecode = None;
+        let mut block_times = vec![];
+        let mut block_end = None;
+        for (id, payload) in child_elements(cluster)? {
+            match id {
+                TIMECODE_ID => timecode = Some(read_uint(payload)? as i64),
+                SIMPLE_BLOCK_ID => block_times.push(read_block_timecode(payload)?),
+                BLOCK_GROUP_ID => {
+                    let mut time = None;
+                    let mut duration = None;
+                    for (id, payload) in child_elements(payload)? {
+                        match id {
+                            BLOCK_ID => time = Some(read_block_timecode(payload)?),
+                            BLOCK_DURATION_ID => duration = Some(read_uint(payload)? as i64),
+                            _ => {},
+                        }
+                    }
+                    let time = time.ok_or(ParseError::InvalidData)?;
+                    if let Some(duration) = duration {
+                        block_end = Some(
+                            block_end.map_or(time + duration, |end: i64| end.max(time + duration)),
+                        );
+                    }
+                    block_times.push(time);
+                },
+                _ => {},
+            }
+        }
+        let timecode = timecode.ok_or(ParseError::InvalidData)?;
+        block_times.sort();
+        let first = block_times.first().cloned().unwrap_or(0);
+        let last = block_times.last().cloned().unwrap_or(0);
+        // Blocks without a duration are assumed to last as long as the
+        // average interval between blocks.
+        let average_interval = if block_times.len() > 1 {
+            (last - first) / (block_times.len() as i64 - 1)
+        } else {
+            0
+        };
+        let end = block_end.map_or(last + average_interval, |end| {
+            end.max(last + average_interval)
+        });
+        let scale = self.timecode_scale as f64 / 1_000_000_000.;
+        Ok((
+            (timecode + first) as f64 * scale,
+            (timecode + end) as f64 * scale,
+        ))
+    }
+}
+
+/// Reads the header of an ISO BMFF box, returning its type, the size of the
+/// header and the size of the whole box, or `None` if the header is
+/// incomplete.
+fn read_box_header(data: &[u8]) -> Result<Option<([u8; 4], usize, usize)>, ParseError> {
+    let mut reader = Reader::new(data);
+    let size = match reader.read_u32() {
+        Ok(size) => size as u64,
+        Err(_) => return Ok(None),
+    };
+    let box_type = match reader.read_bytes(4) {
+        Ok(box_type) => [box_type[0], box_type[1], box_type[2], box_type[3]],
+        Err(_) => return Ok(None),
+    };
+    let (header_size, size) = match size {
+        // A box extending to the end of the file can't be delimited in a
+        // byte stream.
+        0 => return Err(ParseError::InvalidData),
+        1 => match reader.read_u64() {
+            Ok(size) => (16, size),
+            Err(_) => return Ok(None),
+        },
+        size => (8, size),
+    };
+    if size < header_size as u64 {
+        return Err(ParseError::InvalidData);
+    }
+    Ok(Some((box_type, header_size, size as usize)))
+}
+
+/// Splits the payload of a container box into its child boxes.
+fn child_boxes(data: &[u8]) -> Result<Vec<([u8; 4], &[u8])>, ParseError> {
+    let mut boxes = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let (box_type, header_size, size) =
+            read_box_header(&data[offset..])?.ok_or(ParseError::InvalidData)?;
+        if data.len() - offset < size {
+            return Err(ParseError::InvalidData);
+        }
+        boxes.push((box_type, &data[offset + header_size..offset + size]));
+        offset += size;
+    }
+    Ok(boxes)
+}
+
+/// Splits the payload of the container box at `range` of `data` into its
+/// child boxes, returning their types and the ranges of their payloads.
+fn child_box_ranges(
+    data: &[u8],
+    range: Range<usize>,
+) -> Result<Vec<([u8; 4], Range<usize>)>, ParseError> {
+    let mut boxes = vec![];
+    let mut offset = range.start;
+    while offset < range.end {
+        let (box_type, header_size, size) =
+            read_box_header(&data[offset..range.end])?.ok_or(ParseError::InvalidData)?;
+        if range.end - offset < size {
+            return Err(ParseError::InvalidData);
+        }
+        boxes.push((box_type, offset + header_size..offset + size));
+        offset += size;
+    }
+    Ok(boxes)
+}
+
+/// Returns the ID and timescale of the track described by a `trak` box.
+fn parse_trak(trak: &[u8]) -> Result<(u32, u32), ParseError> {
+    let mut track_id = None;
+    let mut timescale = None;
+    for (box_type, payload) in child_boxes(trak)? {
+        match &box_type {
+            b"tkhd" => {
+                let mut reader = Reader::new(payload);
+                let version = reader.read_version_and_flags()?.0;
+                reader.skip(if version == 1 { 16 } else { 8 })?;
+                track_id = Some(reader.read_u32()?);
+            },
+            b"mdia" => {
+                for (box_type, payload) in child_boxes(payload)? {
+                    if &box_type == b"mdhd" {
+                        let mut reader = Reader::new(payload);
+                        let version = reader.read_version_and_flags()?.0;
+                        reader.skip(if version == 1 { 16 } else { 8 })?;
+                        timescale = Some(reader.read_u32()?);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    match (track_id, timescale) {
+        (Some(track_id), Some(timescale)) if timescale != 0 => Ok((track_id, timescale)),
+        _ => Err(ParseError::InvalidData),
+    }
+}
+
+const DEFAULT_TIMECODE_SCALE: u64 = 1_000_000;
+
+const EBML_HEADER_ID: u32 = 0x1A45DFA3;
+const SEGMENT_ID: u32 = 0x18538067;
+const INFO_ID: u32 = 0x1549A966;
+const TIMECODE_SCALE_ID: u32 = 0x2AD7B1;
+const DURATION_ID: u32 = 0x4489;
+const TRACKS_ID: u32 = 0x1654AE6B;
+const CLUSTER_ID: u32 = 0x1F43B675;
+const TIMECODE_ID: u32 = 0xE7;
+const SIMPLE_BLOCK_ID: u32 = 0xA3;
+const BLOCK_GROUP_ID: u32 = 0xA0;
+const BLOCK_ID: u32 = 0xA1;
+const BLOCK_DURATION_ID: u32 = 0x9B;
+
+/// The IDs of the elements that can appear within a cluster.
+const CLUSTER_CHILD_IDS: [u32; 7] = [
+    TIMECODE_ID,
+    SIMPLE_BLOCK_ID,
+    BLOCK_GROUP_ID,
+    0xA7,   // Position
+    0xAB,   // PrevSize
+    0x5854, // SilentTracks
+    0xAF,   // EncryptedBlock
+];
+
+/// Reads an EBML variable-length integer, returning its value with or
+/// without the length marker, and its length in bytes. Returns `None` if the
+/// integer is incomplete.
+fn read_vint(data: &[u8], keep_marker: bool) -> Result<Option<(u64, usize)>, ParseError> {
+    let first = match data.first() {
+        Some(&first) => first,
+        None => return Ok(None),
+    };
+    if first == 0 {
+        return Err(ParseError::InvalidData);
+    }
+    let length = first.leading_zeros() as usize + 1;
+    if data.len() < length {
+        return Ok(None);
+    }
+    let mut value = if keep_marker {
+        first as u64
+    } else {
+        first as u64 & ((1 << (8 - length)) - 1)
+    };
+    for &byte in &data[1..length] {
+        value = (value << 8) | byte as u64;
+    }
+    Ok(Some((value, length)))
+}
+
+/// Reads the header of an EBML element, returning its ID, the size of the
+/// header and the size of its payload, `None` meaning an unknown size.
+/// Returns `None` if the header is incomplete.
+fn read_ebml_element_header(
+    data: &[u8],
+) -> Result<Option<(u32, usize, Option<usize>)>, ParseError> {
+    let (id, id_length) = match read_vint(data, true)? {
+        Some(vint) => vint,
+        None => return Ok(None),
+    };
+    if id_length > 4 {
+        return Err(ParseError::InvalidData);
+    }
+    let (size, size_length) = match read_vint(&data[id_length..], false)? {
+        Some(vint) => vint,
+        None => return Ok(None),
+    };
+    let unknown_size = (1 << (7 * size_length)) - 1;
+    let size = if size == unknown_size {
+        None
+    } else {
+        Some(size as usize)
+    };
+    Ok(Some((id as u32, id_length + size_length, size)))
+}
+
+/// Splits the payload of a master element into its children.
+fn child_elements(data: &[u8]) -> Result<Vec<(u32, &[u8])>, ParseError> {
+    let mut elements = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let (id, header_size, size) =
+            read_ebml_element_header(&data[offset..])?.ok_or(ParseError::InvalidData)?;
+        let size = size.ok_or(ParseError::InvalidData)?;
+        let end = offset + header_size + size;
+        if end > data.len() {
+            return Err(ParseError::InvalidData);
+        }
+        elements.push((id, &data[offset + header_size..end]));
+        offset = end;
+    }
+    Ok(elements)
+}
+
+/// Finds the end of a cluster of unknown size starting at `offset`, which is
+/// the start of the first element that is not a child of the cluster.
+/// Returns `None` if the end has not been appended yet.
+fn find_unknown_size_cluster_end(
+    data: &[u8],
+    mut offset: usize,
+) -> Result<Option<usize>, ParseError> {
+    while let Some((id, header_size, size)) = read_ebml_element_header(&data[offset..])? {
+        if !CLUSTER_CHILD_IDS.contains(&id) {
+            return Ok(Some(offset));
+        }
+        offset += header_size + size.ok_or(ParseError::InvalidData)?;
+        if offset > data.len() {
+            return Ok(None);
+        }
+    }
+    Ok(None)
+}
+
+fn read_uint(data: &[u8]) -> Result<u64, ParseError> {
+    if data.len() > 8 {
+        return Err(ParseError::InvalidData);
+    }
+    Ok(data
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | byte as u64))
+}
+
+/// Writes `value` as a big-endian integer filling `data`.
+fn write_uint(data: &mut [u8], mut value: u64) {
+    for byte in data.iter_mut().rev() {
+        *byte = value as u8;
+        value >>= 8;
+    }
+}
+
+fn read_float(data: &[u8]) -> Result<f64, ParseError> {
+    let bits = read_uint(data)?;
+    match data.len() {
+        4 => Ok(f32::from_bits(bits as u32) as f64),
+        8 => Ok(f64::from_bits(bits)),
+        _ => Err(ParseError::InvalidData),
+    }
+}
+
+/// Reads the timecode of a `Block` or `SimpleBlock`, relative to its cluster.
+fn read_block_timecode(block: &[u8]) -> Result<i64, ParseError> {
+    let (_, track_number_length) = read_vint(block, false)?.ok_or(ParseError::InvalidData)?;
+    let mut reader = Reader::new(&block[track_number_length..]);
+    Ok(reader.read_u16()? as i16 as i64)
+}
+
+/// A big-endian reader over the payload of a box.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], ParseError> {
+        if self.data.len() < count {
+            return Err(ParseError::InvalidData);
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), ParseError> {
+        self.read_bytes(count).map(|_| ())
+    }
+
+    fn read_u16(&mut self) -> Result<u16, ParseError> {
+        Ok(read_uint(self.read_bytes(2)?)? as u16)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ParseError> {
+        Ok(read_uint(self.read_bytes(4)?)? as u32)
+    }
+
+    fn read_u64(&mut self) -> Result<u64, ParseError> {
+        read_uint(self.read_bytes(8)?)
+    }
+
+    /// Reads the version and flags of a full box.
+    fn read_version_and_flags(&mut self) -> Result<(u8, u32), ParseError> {
+        let value = self.read_u32()?;
+        Ok(((value >> 24) as u8, value & 0xFF_FFFF))
+    }
+}
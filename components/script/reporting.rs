@@ -0,0 +1,206 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Generation and delivery of reports.
+//! <https://w3c.github.io/reporting/>
+
+use crate::dom::globalscope::GlobalScope;
+use crate::security_manager::{strip_url_for_reports, CSPViolationReport};
+use http::header::{self, HeaderMap, HeaderValue};
+use hyper::Method;
+use net_traits::request::{CredentialsMode, Destination, Referrer, RequestBuilder, RequestMode};
+use net_traits::{CoreResourceMsg, FetchChannels, IpcSend};
+use serde_json::{json, Value as JsonValue};
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+
+/// The body of a report, along with its type.
+#[derive(Clone)]
+pub enum ReportBody {
+    /// <https://wicg.github.io/deprecation-reporting/#deprecation-report>
+    Deprecation(DeprecationReport),
+    /// <https://wicg.github.io/intervention-reporting/#intervention-report>
+    Intervention(InterventionReport),
+    /// <https://w3c.github.io/webappsec-csp/#reporting>
+    CSPViolation(CSPViolationReport),
+}
+
+impl ReportBody {
+    /// <https://w3c.github.io/reporting/#report-type>
+    pub fn type_(&self) -> &'static str {
+        match *self {
+            ReportBody::Deprecation(_) => "deprecation",
+            ReportBody::Intervention(_) => "intervention",
+            ReportBody::CSPViolation(_) => "csp-violation",
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        match *self {
+            ReportBody::Deprecation(ref report) => json!({
+                "id": report.id,
+                "message": report.message,
+                "sourceFile": report.source_file,
+                "lineNumber": report.line_number,
+                "columnNumber": report.column_number,
+            }),
+            ReportBody::Intervention(ref report) => json!({
+                "id": report.id,
+                "message": report.message,
+                "sourceFile": report.source_file,
+                "lineNumber": report.line_number,
+                "columnNumber": report.column_number,
+            }),
+            ReportBody::CSPViolation(ref report) => json!({
+                "documentURL": report.document_url,
+                "referrer": report.referrer,
+                "blockedURL": report.blocked_url,
+                "effectiveDirective": report.effective_directive,
+                "originalPolicy": report.original_policy,
+                "sample": report.sample,
+                "disposition": if report.report_only { "report" } else { "enforce" },
+                "statusCode": 0,
+            }),
+        }
+    }
+}
+
+/// The use of a feature that will be removed from the web platform.
+#[derive(Clone)]
+pub struct DeprecationReport {
+    pub id: String,
+    pub message: String,
+    pub source_file: Option<String>,
+    pub line_number: Option<u32>,
+    pub column_number: Option<u32>,
+}
+
+/// A request of the page that the user agent decided not to honour.
+#[derive(Clone)]
+pub struct InterventionReport {
+    pub id: String,
+    pub message: String,
+    pub source_file: Option<String>,
+    pub line_number: Option<u32>,
+    pub column_number: Option<u32>,
+}
+
+/// <https://w3c.github.io/reporting/#concept-reports>
+#[derive(Clone)]
+pub struct Report {
+    pub url: String,
+    pub body: ReportBody,
+}
+
+impl Report {
+    pub fn new(global: &GlobalScope, body: ReportBody) -> Report {
+        Report {
+            url: strip_url_for_reports(global.get_url()),
+            body,
+        }
+    }
+
+    /// <https://w3c.github.io/reporting/#serialize-reports>
+    fn to_json(&self, user_agent: &str) -> JsonValue {
+        json!({
+            // The report is delivered as soon as it is generated.
+            "age": 0,
+            "type": self.body.type_(),
+            "url": self.url,
+            "user_agent": user_agent,
+            "body": self.body.to_json(),
+        })
+    }
+}
+
+/// Parses the endpoints declared by the `Reporting-Endpoints` header of a
+/// response, falling back to the legacy `Report-To` header,
+/// <https://w3c.github.io/reporting/#process-header>
+pub fn parse_reporting_endpoints(
+    headers: &HeaderMap,
+    base: &ServoUrl,
+) -> HashMap<String, ServoUrl> {
+    let mut endpoints = HashMap::new();
+    // TODO: accept the other potentially trustworthy origins, like localhost.
+    let is_trustworthy = |url: &ServoUrl| url.is_secure_scheme();
+
+    for value in headers.get_all("reporting-endpoints").iter() {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        // A structured dictionary of strings, whose parameters are ignored.
+        for member in value.split(',') {
+            let member = member.split(';').next().unwrap_or("");
+            let mut parts = member.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let url = parts.next().unwrap_or("").trim();
+            if name.is_empty() || url.len() < 2 || !url.starts_with('"') || !url.ends_with('"') {
+                continue;
+            }
+            let url = match ServoUrl::parse_with_base(Some(base), &url[1..url.len() - 1]) {
+                Ok(url) => url,
+                Err(_) => continue,
+            };
+            if is_trustworthy(&url) {
+                endpoints.insert(name.to_owned(), url);
+            }
+        }
+    }
+    if !endpoints.is_empty() {
+        return endpoints;
+    }
+
+    // https://www.w3.org/TR/2018/WD-reporting-1-20180925/#header
+    for value in headers.get_all("report-to").iter() {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let groups: Vec<JsonValue> = match serde_json::from_str(&format!("[{}]", value)) {
+            Ok(groups) => groups,
+            Err(_) => continue,
+        };
+        for group in groups {
+            let name = group["group"].as_str().unwrap_or("default").to_owned();
+            let url = group["endpoints"]
+                .as_array()
+                .and_then(|endpoints| endpoints.first())
+                .and_then(|endpoint| endpoint["url"].as_str())
+                .and_then(|url| ServoUrl::parse_with_base(Some(base), url).ok());
+            match url {
+                Some(ref url) if is_trustworthy(url) => {
+                    endpoints.entry(name).or_insert_with(|| url.clone());
+                },
+                _ => {},
+            }
+        }
+    }
+    endpoints
+}
+
+/// Sends a report to an endpoint, the response is ignored,
+/// <https://w3c.github.io/reporting/#try-delivery>
+pub fn deliver_report(global: &GlobalScope, endpoint: ServoUrl, report: &Report) {
+    let body = JsonValue::Array(vec![report.to_json(&global.get_user_agent())]);
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/reports+json"),
+    );
+    let request = RequestBuilder::new(endpoint)
+        .method(Method::POST)
+        .headers(headers)
+        .body(Some(body.to_string().into_bytes()))
+        .destination(Destination::Report)
+        .mode(RequestMode::CorsMode)
+        .use_cors_preflight(true)
+        .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+        .origin(global.origin().immutable().clone())
+        .referrer(Some(Referrer::NoReferrer))
+        .pipeline_id(Some(global.pipeline_id()));
+    let _ = global
+        .core_resource_thread()
+        .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
+}
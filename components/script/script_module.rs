@@ -0,0 +1,918 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The script module provides the fetching, instantiation and evaluation of
+//! [module scripts](https://html.spec.whatwg.org/multipage/#module-script).
+//!
+//! Top-level module scripts are currently only supported in workers, whose
+//! threads are allowed to block: every module of a graph is fetched
+//! synchronously, and compiled as soon as it arrives so that its own imports
+//! can be discovered.
+//!
+//! Module graphs requested by `import()` are fetched asynchronously instead,
+//! so that dynamic imports work from any global, classic scripts and event
+//! handlers included.
+
+use crate::dom::bindings::conversions::jsstring_to_str;
+use crate::dom::bindings::error::{report_pending_exception, throw_dom_exception, Error};
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::settings_stack::AutoEntryScript;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlscriptelement::SCRIPT_JS_MIMES;
+use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::promise::Promise;
+use crate::fetch::load_whole_resource;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::script_runtime::JSContext as SafeJSContext;
+use crate::task_source::TaskSourceName;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::FinishDynamicModuleImport;
+use js::jsapi::{CompileModule, ModuleEvaluate, ModuleInstantiate, SetModulePrivate};
+use js::jsapi::{GetRequestedModuleSpecifier, GetRequestedModules};
+use js::jsapi::{Handle as RawHandle, HandleValue as RawHandleValue};
+use js::jsapi::{JSAutoRealm, JSContext, JSObject, JSString};
+use js::jsapi::{JS_GetArrayLength, JS_GetElement};
+use js::jsval::UndefinedValue;
+use js::panic::{maybe_resume_unwind, wrap_panic};
+use js::rust::{transform_u16_to_source_text, CompileOptionsWrapper, Handle};
+use mime::Mime;
+use net_traits::request::{CredentialsMode, Destination, Referrer, RequestBuilder, RequestMode};
+use net_traits::CoreResourceMsg::Fetch as NetTraitsFetch;
+use net_traits::{FetchChannels, FetchMetadata, FetchResponseListener, Metadata, NetworkError};
+use net_traits::{IpcSend, ResourceFetchTiming, ResourceTimingType};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use servo_url::ServoUrl;
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::panic::AssertUnwindSafe;
+use std::ptr;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// <https://html.spec.whatwg.org/multipage/#fetch-a-module-worker-script-tree>
+///
+/// `request` is the request for the top-level module script. The module records
+/// of the whole graph are stored in the module map of `global`, and the final URL
+/// of the top-level module script is returned.
+pub fn fetch_module_worker_script_graph(
+    global: &GlobalScope,
+    request: RequestBuilder,
+) -> Result<ServoUrl, ()> {
+    global.disallow_further_import_maps();
+
+    // Module scripts are always fetched in "cors" mode.
+    let request = request.mode(RequestMode::CorsMode);
+    let mut visited = HashSet::new();
+    fetch_module_script_tree(global, request, &mut visited)
+}
+
+/// <https://html.spec.whatwg.org/multipage/#internal-module-script-graph-fetching-procedure>
+fn fetch_module_script_tree(
+    global: &GlobalScope,
+    request: RequestBuilder,
+    visited: &mut HashSet<ServoUrl>,
+) -> Result<ServoUrl, ()> {
+    let url = request.url.clone();
+    visited.insert(url.clone());
+
+    // https://html.spec.whatwg.org/multipage/#fetch-a-single-module-script
+    let (metadata, bytes) =
+        load_whole_resource(request.clone(), &global.resource_threads().sender(), global)
+            .map_err(|_| warn!("error loading module script {}", url))?;
+    let is_javascript = metadata.content_type.map_or(false, |content_type| {
+        let mime: Mime = content_type.into_inner().into();
+        SCRIPT_JS_MIMES.contains(&mime.essence_str())
+    });
+    if !is_javascript {
+        warn!("module script {} does not have a JavaScript MIME type", url);
+        return Err(());
+    }
+    let base_url = metadata.final_url;
+    let source = String::from_utf8_lossy(&bytes);
+
+    let specifiers = compile_module_script(global, &url, &base_url, &source)?;
+
+    // https://html.spec.whatwg.org/multipage/#fetch-the-descendants-of-a-module-script
+    for specifier in specifiers {
+        let child_url = match resolve_module_specifier(global, &base_url, &specifier) {
+            Some(child_url) => child_url,
+            None => {
+                report_module_error(
+                    global,
+                    Error::Type(format!("Invalid module specifier \"{}\"", specifier)),
+                );
+                return Err(());
+            },
+        };
+        if visited.contains(&child_url) {
+            continue;
+        }
+        // Descendants are fetched with the same destination and settings as their parent.
+        let mut child_request = request.clone();
+        child_request.url = child_url;
+        child_request.url_list = vec![];
+        fetch_module_script_tree(global, child_request, visited)?;
+    }
+
+    Ok(base_url)
+}
+
+/// <https://html.spec.whatwg.org/multipage/#creating-a-module-script>
+///
+/// Compiles `source` into a module record, adds it to the module map of `global`
+/// under `base_url`, the final URL of the response to the request for `url`,
+/// and returns the specifiers of the modules it imports.
+#[allow(unsafe_code)]
+fn compile_module_script(
+    global: &GlobalScope,
+    url: &ServoUrl,
+    base_url: &ServoUrl,
+    source: &str,
+) -> Result<Vec<String>, ()> {
+    let cx = global.get_cx();
+    let _ac = JSAutoRealm::new(*cx, global.reflector().get_jsobject().get());
+    let _aes = AutoEntryScript::new(global);
+
+    let filename = CString::new(base_url.as_str()).unwrap();
+    let options = CompileOptionsWrapper::new(*cx, filename.as_ptr(), 1);
+    let source: Vec<u16> = source.encode_utf16().collect();
+    let mut source = transform_u16_to_source_text(&source);
+
+    rooted!(in(*cx) let mut module = ptr::null_mut::<JSObject>());
+    unsafe {
+        if !CompileModule(*cx, options.ptr, &mut source, module.handle_mut().into()) {
+            warn!("failed to compile module script {}", url);
+            report_pending_exception(*cx, true);
+            return Err(());
+        }
+
+        // Imports are resolved against the URL of the response, which the
+        // resolve hook finds in the private value of the module record.
+        rooted!(in(*cx) let mut private = UndefinedValue());
+        base_url.as_str().to_jsval(*cx, private.handle_mut());
+        SetModulePrivate(module.get(), &*private);
+    }
+    global.set_module_map(url.clone(), base_url.clone(), module.handle().into());
+
+    let mut specifiers = vec![];
+    unsafe {
+        rooted!(in(*cx) let requested = GetRequestedModules(*cx, module.handle().into()));
+        let mut length = 0;
+        if !JS_GetArrayLength(*cx, requested.handle().into(), &mut length) {
+            report_pending_exception(*cx, true);
+            return Err(());
+        }
+        for index in 0..length {
+            rooted!(in(*cx) let mut element = UndefinedValue());
+            if !JS_GetElement(
+                *cx,
+                requested.handle().into(),
+                index,
+                element.handle_mut().into(),
+            ) {
+                report_pending_exception(*cx, true);
+                return Err(());
+            }
+            let specifier = GetRequestedModuleSpecifier(*cx, element.handle().into());
+            specifiers.push(String::from(jsstring_to_str(*cx, specifier)));
+        }
+    }
+    Ok(specifiers)
+}
+
+/// <https://html.spec.whatwg.org/multipage/#run-a-module-script>
+///
+/// Instantiates and evaluates the module script whose response came from `url`.
+#[allow(unsafe_code)]
+pub fn run_module_script(global: &GlobalScope, url: &ServoUrl) -> Result<(), ()> {
+    let cx = global.get_cx();
+    let _ac = JSAutoRealm::new(*cx, global.reflector().get_jsobject().get());
+    let _aes = AutoEntryScript::new(global);
+
+    let module = global.get_module_map_entry(url).ok_or(())?;
+    rooted!(in(*cx) let module = module);
+    let result = unsafe {
+        ModuleInstantiate(*cx, module.handle().into()) &&
+            ModuleEvaluate(*cx, module.handle().into())
+    };
+    if !result {
+        unsafe { report_pending_exception(*cx, true) };
+    }
+    maybe_resume_unwind();
+    if result {
+        Ok(())
+    } else {
+        Err(())
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/#resolve-a-module-specifier>
+///
+/// Specifiers are first looked up in the import map of `global`, so bare
+/// specifiers only resolve when they are mapped by it.
+fn resolve_module_specifier(
+    global: &GlobalScope,
+    base_url: &ServoUrl,
+    specifier: &str,
+) -> Option<ServoUrl> {
+    // Step 6.
+    let serialized_base_url = base_url.as_str();
+
+    // Step 7.
+    let as_url = resolve_url_like_module_specifier(specifier, base_url);
+
+    // Step 8.
+    let normalized_specifier = as_url.as_ref().map_or(specifier, |url| url.as_str());
+
+    // Step 9.
+    let import_map = global.import_map();
+    for (scope_prefix, scope_imports) in &import_map.scopes {
+        if scope_prefix == serialized_base_url ||
+            (scope_prefix.ends_with('/') && serialized_base_url.starts_with(&**scope_prefix))
+        {
+            match resolve_imports_match(normalized_specifier, as_url.as_ref(), scope_imports) {
+                Ok(Some(url)) => return Some(url),
+                Ok(None) => {},
+                Err(()) => return None,
+            }
+        }
+    }
+
+    // Step 10.
+    match resolve_imports_match(normalized_specifier, as_url.as_ref(), &import_map.imports) {
+        Ok(Some(url)) => return Some(url),
+        Ok(None) => {},
+        Err(()) => return None,
+    }
+
+    // Step 11-12, bare specifiers that are not mapped do not resolve.
+    as_url
+}
+
+/// <https://html.spec.whatwg.org/multipage/#resolving-an-imports-match>
+///
+/// Returns an error if the specifier is blocked by a null entry of `imports`,
+/// or backtracks above its mapped prefix.
+fn resolve_imports_match(
+    normalized_specifier: &str,
+    as_url: Option<&ServoUrl>,
+    imports: &ModuleSpecifierMap,
+) -> Result<Option<ServoUrl>, ()> {
+    for (specifier_key, resolution_result) in imports {
+        // Step 1.1.
+        if specifier_key == normalized_specifier {
+            return resolution_result.clone().map(Some).ok_or(());
+        }
+
+        // Step 1.2.
+        if specifier_key.ends_with('/') &&
+            normalized_specifier.starts_with(&**specifier_key) &&
+            as_url.map_or(true, |url| is_special_scheme(url.scheme()))
+        {
+            let resolution_result = resolution_result.as_ref().ok_or(())?;
+            let after_prefix = &normalized_specifier[specifier_key.len()..];
+            let url = resolution_result.join(after_prefix).map_err(|_| ())?;
+            if !url.as_str().starts_with(resolution_result.as_str()) {
+                return Err(());
+            }
+            return Ok(Some(url));
+        }
+    }
+    Ok(None)
+}
+
+/// <https://url.spec.whatwg.org/#special-scheme>
+fn is_special_scheme(scheme: &str) -> bool {
+    match scheme {
+        "ftp" | "file" | "http" | "https" | "ws" | "wss" => true,
+        _ => false,
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/#resolving-a-url-like-module-specifier>
+fn resolve_url_like_module_specifier(specifier: &str, base_url: &ServoUrl) -> Option<ServoUrl> {
+    // Step 1.
+    if specifier.starts_with("/") || specifier.starts_with("./") || specifier.starts_with("../") {
+        return base_url.join(specifier).ok();
+    }
+    // Step 2-4.
+    ServoUrl::parse(specifier).ok()
+}
+
+/// A specifier map of an import map, sorted in descending order of its keys
+/// so that longer prefixes are matched first. A `None` address blocks the
+/// specifier from resolving.
+///
+/// <https://html.spec.whatwg.org/multipage/#module-specifier-map>
+type ModuleSpecifierMap = Vec<(String, Option<ServoUrl>)>;
+
+/// <https://html.spec.whatwg.org/multipage/#import-map>
+#[derive(Default, JSTraceable, MallocSizeOf)]
+pub struct ImportMap {
+    imports: ModuleSpecifierMap,
+    /// The specifier maps of the scopes, sorted in descending order of their
+    /// URL prefixes.
+    scopes: Vec<(String, ModuleSpecifierMap)>,
+}
+
+impl ImportMap {
+    /// <https://html.spec.whatwg.org/multipage/#parse-an-import-map-string>
+    pub fn parse(input: &str, base_url: &ServoUrl) -> Result<ImportMap, Error> {
+        // Step 1.
+        let parsed: JsonValue = serde_json::from_str(input).map_err(|_| Error::Syntax)?;
+
+        // Step 2.
+        let parsed = match parsed {
+            JsonValue::Object(parsed) => parsed,
+            _ => {
+                return Err(Error::Type(
+                    "The top-level value of an import map must be a JSON object".to_owned(),
+                ))
+            },
+        };
+
+        // Step 3-4.
+        let imports = match parsed.get("imports") {
+            Some(JsonValue::Object(imports)) => {
+                sort_and_normalize_module_specifier_map(imports, base_url)
+            },
+            Some(_) => {
+                return Err(Error::Type(
+                    "The \"imports\" key of an import map must be a JSON object".to_owned(),
+                ))
+            },
+            None => vec![],
+        };
+
+        // Step 5-6.
+        let scopes = match parsed.get("scopes") {
+            Some(JsonValue::Object(scopes)) => sort_and_normalize_scopes(scopes, base_url)?,
+            Some(_) => {
+                return Err(Error::Type(
+                    "The \"scopes\" key of an import map must be a JSON object".to_owned(),
+                ))
+            },
+            None => vec![],
+        };
+
+        // Step 7.
+        for key in parsed.keys() {
+            if key != "imports" && key != "scopes" {
+                warn!("Invalid top-level key \"{}\" in import map, ignoring", key);
+            }
+        }
+
+        // Step 8.
+        Ok(ImportMap { imports, scopes })
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/#sorting-and-normalizing-a-module-specifier-map>
+fn sort_and_normalize_module_specifier_map(
+    original_map: &JsonMap<String, JsonValue>,
+    base_url: &ServoUrl,
+) -> ModuleSpecifierMap {
+    let mut normalized: ModuleSpecifierMap = vec![];
+    for (specifier_key, value) in original_map {
+        // Step 2.1-2.2.
+        let normalized_specifier_key = match normalize_specifier_key(specifier_key, base_url) {
+            Some(key) => key,
+            None => continue,
+        };
+
+        // Step 2.3.
+        let address = match value {
+            JsonValue::String(address) => address,
+            _ => {
+                warn!("Invalid address for import map key \"{}\"", specifier_key);
+                normalized.push((normalized_specifier_key, None));
+                continue;
+            },
+        };
+
+        // Step 2.4-2.5.
+        let address_url = match resolve_url_like_module_specifier(address, base_url) {
+            Some(url) => url,
+            None => {
+                warn!(
+                    "Invalid address \"{}\" for import map key \"{}\"",
+                    address, specifier_key
+                );
+                normalized.push((normalized_specifier_key, None));
+                continue;
+            },
+        };
+
+        // Step 2.6.
+        if specifier_key.ends_with('/') && !address_url.as_str().ends_with('/') {
+            warn!(
+                "Invalid address \"{}\" for package specifier key \"{}\"",
+                address, specifier_key
+            );
+            normalized.push((normalized_specifier_key, None));
+            continue;
+        }
+
+        // Step 2.7.
+        normalized.push((normalized_specifier_key, Some(address_url)));
+    }
+
+    // Step 3.
+    normalized.sort_by(|a, b| b.0.cmp(&a.0));
+    normalized.dedup_by(|a, b| a.0 == b.0);
+    normalized
+}
+
+/// <https://html.spec.whatwg.org/multipage/#sorting-and-normalizing-scopes>
+fn sort_and_normalize_scopes(
+    original_map: &JsonMap<String, JsonValue>,
+    base_url: &ServoUrl,
+) -> Result<Vec<(String, ModuleSpecifierMap)>, Error> {
+    let mut normalized = vec![];
+    for (scope_prefix, potential_specifier_map) in original_map {
+        // Step 2.1.
+        let potential_specifier_map = match potential_specifier_map {
+            JsonValue::Object(map) => map,
+            _ => {
+                return Err(Error::Type(format!(
+                    "The value of the scope \"{}\" must be a JSON object",
+                    scope_prefix
+                )))
+            },
+        };
+
+        // Step 2.2-2.3.
+        let scope_prefix_url = match base_url.join(scope_prefix) {
+            Ok(url) => url,
+            Err(_) => {
+                warn!("Invalid scope prefix \"{}\" in import map", scope_prefix);
+                continue;
+            },
+        };
+
+        // Step 2.4-2.5.
+        normalized.push((
+            scope_prefix_url.into_string(),
+            sort_and_normalize_module_specifier_map(potential_specifier_map, base_url),
+        ));
+    }
+
+    // Step 3.
+    normalized.sort_by(|a, b| b.0.cmp(&a.0));
+    normalized.dedup_by(|a, b| a.0 == b.0);
+    Ok(normalized)
+}
+
+/// <https://html.spec.whatwg.org/multipage/#normalizing-a-specifier-key>
+fn normalize_specifier_key(specifier_key: &str, base_url: &ServoUrl) -> Option<String> {
+    // Step 1.
+    if specifier_key.is_empty() {
+        warn!("Invalid empty specifier key in import map");
+        return None;
+    }
+
+    // Step 2-4.
+    Some(
+        resolve_url_like_module_specifier(specifier_key, base_url)
+            .map_or_else(|| specifier_key.to_owned(), |url| url.into_string()),
+    )
+}
+
+#[allow(unsafe_code)]
+fn report_module_error(global: &GlobalScope, error: Error) {
+    let cx = global.get_cx();
+    let _ac = JSAutoRealm::new(*cx, global.reflector().get_jsobject().get());
+    throw_dom_exception(cx, global, error);
+    unsafe { report_pending_exception(*cx, true) };
+}
+
+/// The base URL of the script or module stored in `reference_private`, falling
+/// back to the API base URL of `global` for scripts without one, such as event
+/// handlers.
+#[allow(unsafe_code)]
+unsafe fn referencing_base_url(
+    cx: *mut JSContext,
+    global: &GlobalScope,
+    reference_private: RawHandleValue,
+) -> ServoUrl {
+    let reference_private = Handle::from_raw(reference_private);
+    if reference_private.is_string() {
+        if let Ok(url) = ServoUrl::parse(&jsstring_to_str(cx, reference_private.to_string())) {
+            return url;
+        }
+    }
+    global.api_base_url()
+}
+
+/// The state of a module graph fetched for a call to `import()`.
+struct DynamicModuleImport {
+    /// The promise returned by `import()`, taken once the import finishes.
+    promise: Option<TrustedPromise>,
+    /// The specifier passed to `import()`.
+    specifier: String,
+    /// The private value of the referencing script, if it was a base URL.
+    reference_private: Option<String>,
+    /// The URL of the top-level module of the graph.
+    url: ServoUrl,
+    /// The request that modules of the graph are fetched with.
+    request: RequestBuilder,
+    /// The URLs of the modules fetched so far.
+    visited: HashSet<ServoUrl>,
+    /// The number of modules that are still being fetched.
+    pending: usize,
+}
+
+type SharedDynamicModuleImport = Arc<Mutex<DynamicModuleImport>>;
+
+/// <https://html.spec.whatwg.org/multipage/#hostimportmoduledynamically(referencingscriptormodule,-specifier,-promisecapability)>
+fn fetch_dynamic_module_graph(
+    global: &GlobalScope,
+    promise: Rc<Promise>,
+    specifier: String,
+    reference_private: Option<String>,
+    url: ServoUrl,
+) {
+    // Module scripts are always fetched in "cors" mode, with the
+    // "same-origin" credentials mode.
+    let request = RequestBuilder::new(url.clone())
+        .destination(Destination::Script)
+        .mode(RequestMode::CorsMode)
+        .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+        .origin(global.origin().immutable().clone())
+        .pipeline_id(Some(global.pipeline_id()))
+        .referrer(Some(Referrer::ReferrerUrl(global.get_url())))
+        .referrer_policy(global.get_referrer_policy());
+
+    global.disallow_further_import_maps();
+
+    let mut visited = HashSet::new();
+    visited.insert(url.clone());
+    let import = Arc::new(Mutex::new(DynamicModuleImport {
+        promise: Some(TrustedPromise::new(promise)),
+        specifier,
+        reference_private,
+        url: url.clone(),
+        request,
+        visited,
+        pending: 0,
+    }));
+
+    // A module that is already in the module map is not fetched again.
+    if global.get_module_map_entry(&url).is_some() {
+        return finish_dynamic_module_import(global, &import, Ok(()));
+    }
+    fetch_dynamic_module(global, &import, url);
+}
+
+/// Starts fetching the module at `url` as part of the graph of `import`.
+fn fetch_dynamic_module(global: &GlobalScope, import: &SharedDynamicModuleImport, url: ServoUrl) {
+    let mut request = {
+        let mut import = import.lock().unwrap();
+        import.pending += 1;
+        import.request.clone()
+    };
+    request.url = url.clone();
+    request.url_list = vec![];
+
+    let context = Arc::new(Mutex::new(ModuleContext {
+        import: import.clone(),
+        global: Trusted::new(global),
+        url,
+        data: vec![],
+        metadata: None,
+        status: Ok(()),
+        resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+    }));
+
+    let (action_sender, action_receiver) = ipc::channel().unwrap();
+    let listener = NetworkListener {
+        context,
+        task_source: global.networking_task_source(),
+        canceller: Some(global.task_canceller(TaskSourceName::Networking)),
+    };
+    ROUTER.add_route(
+        action_receiver.to_opaque(),
+        Box::new(move |message| {
+            listener.notify_fetch(message.to().unwrap());
+        }),
+    );
+    global
+        .core_resource_thread()
+        .send(NetTraitsFetch(
+            request,
+            FetchChannels::ResponseMsg(action_sender, None),
+        ))
+        .unwrap();
+}
+
+/// Instantiates and evaluates the graph of `import` if `result` is successful,
+/// then settles the promise returned by `import()`.
+#[allow(unsafe_code)]
+fn finish_dynamic_module_import(
+    global: &GlobalScope,
+    import: &SharedDynamicModuleImport,
+    result: Result<(), Error>,
+) {
+    let mut import = import.lock().unwrap();
+    let promise = match import.promise.take() {
+        Some(promise) => promise.root(),
+        None => return,
+    };
+
+    let cx = global.get_cx();
+    let _ac = JSAutoRealm::new(*cx, global.reflector().get_jsobject().get());
+    let _aes = AutoEntryScript::new(global);
+
+    match result.and_then(|_| {
+        global
+            .get_module_map_entry(&import.url)
+            .ok_or(Error::Type("Failed to load module script".to_owned()))
+    }) {
+        Ok(module) => {
+            // An exception left pending by a failed evaluation
+            // rejects the promise below.
+            rooted!(in(*cx) let module = module);
+            unsafe {
+                let _ = ModuleInstantiate(*cx, module.handle().into()) &&
+                    ModuleEvaluate(*cx, module.handle().into());
+            }
+        },
+        Err(error) => throw_dom_exception(cx, global, error),
+    }
+
+    unsafe {
+        rooted!(in(*cx) let mut reference_private = UndefinedValue());
+        if let Some(ref private) = import.reference_private {
+            private.to_jsval(*cx, reference_private.handle_mut());
+        }
+        rooted!(in(*cx) let mut specifier = UndefinedValue());
+        import.specifier.to_jsval(*cx, specifier.handle_mut());
+        rooted!(in(*cx) let specifier = specifier.to_string());
+
+        // Resolves the promise with the namespace of the module found through
+        // the resolve hook, or rejects it with the pending exception.
+        if !FinishDynamicModuleImport(
+            *cx,
+            reference_private.handle().into(),
+            specifier.handle().into(),
+            promise.reflector().get_jsobject().into(),
+        ) {
+            report_pending_exception(*cx, true);
+        }
+    }
+    maybe_resume_unwind();
+}
+
+/// The context required for fetching a single module of a graph requested by
+/// `import()`.
+struct ModuleContext {
+    /// The dynamic import that the module is part of.
+    import: SharedDynamicModuleImport,
+    /// The global that the module is fetched for.
+    global: Trusted<GlobalScope>,
+    /// The initial URL requested.
+    url: ServoUrl,
+    /// The response body received to date.
+    data: Vec<u8>,
+    /// The response metadata received to date.
+    metadata: Option<Metadata>,
+    /// Indicates whether the request failed, and why.
+    status: Result<(), NetworkError>,
+    /// Timing object for this resource.
+    resource_timing: ResourceFetchTiming,
+}
+
+impl ModuleContext {
+    /// <https://html.spec.whatwg.org/multipage/#fetch-a-single-module-script>
+    /// step 9-13, returning the specifiers imported by the module.
+    fn compile(
+        &mut self,
+        global: &GlobalScope,
+        response: Result<ResourceFetchTiming, NetworkError>,
+    ) -> Result<(ServoUrl, Vec<String>), ()> {
+        response
+            .and(self.status.clone())
+            .map_err(|error| warn!("error loading module script {}: {:?}", self.url, error))?;
+        let metadata = self.metadata.take().ok_or(())?;
+        let is_javascript = metadata.content_type.map_or(false, |content_type| {
+            let mime: Mime = content_type.into_inner().into();
+            SCRIPT_JS_MIMES.contains(&mime.essence_str())
+        });
+        if !is_javascript {
+            warn!(
+                "module script {} does not have a JavaScript MIME type",
+                self.url
+            );
+            return Err(());
+        }
+        let base_url = metadata.final_url;
+        let source = String::from_utf8_lossy(&self.data);
+        let specifiers = compile_module_script(global, &self.url, &base_url, &source)?;
+        Ok((base_url, specifiers))
+    }
+}
+
+impl FetchResponseListener for ModuleContext {
+    fn process_request_body(&mut self) {}
+
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>) {
+        self.metadata = metadata.ok().map(|meta| match meta {
+            FetchMetadata::Unfiltered(m) => m,
+            FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+        });
+
+        let status_code = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.status.as_ref().map(|&(code, _)| code))
+            .unwrap_or(0);
+
+        self.status = match status_code {
+            200..=299 => Ok(()),
+            _ => Err(NetworkError::Internal(format!(
+                "HTTP error code {}",
+                status_code
+            ))),
+        };
+    }
+
+    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
+        if self.status.is_ok() {
+            self.data.append(&mut chunk);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#fetch-the-descendants-of-a-module-script>
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let global = self.global.root();
+        let import = self.import.clone();
+
+        // Another module of the graph already failed to load.
+        if import.lock().unwrap().promise.is_none() {
+            return;
+        }
+
+        let (base_url, specifiers) = match self.compile(&global, response) {
+            Ok(module) => module,
+            Err(()) => {
+                let error = Error::Type(format!("Failed to load module script {}", self.url));
+                return finish_dynamic_module_import(&global, &import, Err(error));
+            },
+        };
+
+        let mut children = vec![];
+        for specifier in specifiers {
+            let child_url = match resolve_module_specifier(&global, &base_url, &specifier) {
+                Some(child_url) => child_url,
+                None => {
+                    let error = Error::Type(format!("Invalid module specifier \"{}\"", specifier));
+                    return finish_dynamic_module_import(&global, &import, Err(error));
+                },
+            };
+            let mut import = import.lock().unwrap();
+            if global.get_module_map_entry(&child_url).is_none() &&
+                import.visited.insert(child_url.clone())
+            {
+                children.push(child_url);
+            }
+        }
+        for child_url in children {
+            fetch_dynamic_module(&global, &import, child_url);
+        }
+
+        let pending = {
+            let mut import = import.lock().unwrap();
+            import.pending -= 1;
+            import.pending
+        };
+        if pending == 0 {
+            finish_dynamic_module_import(&global, &import, Ok(()));
+        }
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for ModuleContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (InitiatorType::Other, self.url.clone())
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.global.root()
+    }
+}
+
+impl PreInvoke for ModuleContext {}
+
+/// The dynamic import hook of the JS engine, used by `import()` calls in
+/// classic scripts, module scripts and event handlers.
+///
+/// <https://html.spec.whatwg.org/multipage/#hostimportmoduledynamically(referencingscriptormodule,-specifier,-promisecapability)>
+#[allow(unsafe_code)]
+pub unsafe extern "C" fn host_import_module_dynamically(
+    cx: *mut JSContext,
+    reference_private: RawHandleValue,
+    specifier: RawHandle<*mut JSString>,
+    promise: RawHandle<*mut JSObject>,
+) -> bool {
+    wrap_panic(
+        AssertUnwindSafe(|| {
+            let global = GlobalScope::from_context(cx);
+            let base_url = referencing_base_url(cx, &global, reference_private);
+
+            // Step 2.
+            let specifier = String::from(jsstring_to_str(cx, Handle::from_raw(specifier).get()));
+            let url = match resolve_module_specifier(&global, &base_url, &specifier) {
+                Some(url) => url,
+                None => {
+                    throw_dom_exception(
+                        SafeJSContext::from_ptr(cx),
+                        &global,
+                        Error::Type(format!("Invalid module specifier \"{}\"", specifier)),
+                    );
+                    return false;
+                },
+            };
+
+            let reference_private = Handle::from_raw(reference_private);
+            let reference_private = if reference_private.is_string() {
+                Some(String::from(jsstring_to_str(
+                    cx,
+                    reference_private.to_string(),
+                )))
+            } else {
+                None
+            };
+            let promise = Promise::new_with_js_promise(
+                Handle::from_raw(promise),
+                SafeJSContext::from_ptr(cx),
+            );
+
+            // Step 3-4, the promise is settled on the networking task source once
+            // the whole module graph has been fetched.
+            fetch_dynamic_module_graph(&global, promise, specifier, reference_private, url);
+            true
+        }),
+        false,
+    )
+}
+
+/// The module resolve hook of the JS engine, used when instantiating a module
+/// record to find the module records of its imports.
+///
+/// <https://html.spec.whatwg.org/multipage/#hostresolveimportedmodule(referencingscriptormodule,-specifier)>
+#[allow(unsafe_code, non_snake_case)]
+pub unsafe extern "C" fn HostResolveImportedModule(
+    cx: *mut JSContext,
+    reference_private: RawHandleValue,
+    specifier: RawHandle<*mut JSString>,
+) -> *mut JSObject {
+    wrap_panic(
+        AssertUnwindSafe(|| {
+            let global = GlobalScope::from_context(cx);
+
+            // Step 2.
+            let base_url = referencing_base_url(cx, &global, reference_private);
+
+            // Step 5.
+            let specifier = jsstring_to_str(cx, Handle::from_raw(specifier).get());
+            let url = resolve_module_specifier(&global, &base_url, &specifier);
+
+            // Step 6-7, the module graph was fetched before instantiation,
+            // so every import that resolves can be found in the module map.
+            match url.and_then(|url| global.get_module_map_entry(&url)) {
+                Some(module) => module,
+                None => {
+                    throw_dom_exception(
+                        SafeJSContext::from_ptr(cx),
+                        &global,
+                        Error::Type(format!(
+                            "Unable to resolve module specifier \"{}\"",
+                            specifier
+                        )),
+                    );
+                    ptr::null_mut()
+                },
+            }
+        }),
+        ptr::null_mut(),
+    )
+}
@@ -14,15 +14,17 @@ use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DO
 use crate::dom::bindings::conversions::get_dom_class;
 use crate::dom::bindings::conversions::private_from_object;
 use crate::dom::bindings::conversions::root_from_handleobject;
-use crate::dom::bindings::error::{throw_dom_exception, Error};
+use crate::dom::bindings::error::{throw_dom_exception, Error, ErrorInfo};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{trace_refcounted_objects, LiveDOMReferences};
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::trace_roots;
 use crate::dom::bindings::settings_stack;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::{trace_traceables, JSTraceable};
 use crate::dom::bindings::utils::DOM_CALLBACKS;
+use crate::dom::console::Console;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
@@ -30,10 +32,12 @@ use crate::dom::promise::Promise;
 use crate::dom::promiserejectionevent::PromiseRejectionEvent;
 use crate::dom::response::Response;
 use crate::microtask::{EnqueuedPromiseCallback, Microtask, MicrotaskQueue};
+use crate::script_module::{host_import_module_dynamically, HostResolveImportedModule};
 use crate::script_thread::trace_thread;
 use crate::task::TaskBox;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::{TaskSource, TaskSourceName};
+use content_security_policy::CheckResult;
 use js::glue::{CollectServoSizes, CreateJobQueue, DeleteJobQueue, DispatchableRun};
 use js::glue::{JobQueueTraps, RUST_js_GetErrorMessage, SetBuildId, StreamConsumerConsumeChunk};
 use js::glue::{
@@ -43,6 +47,7 @@ use js::jsapi::ContextOptionsRef;
 use js::jsapi::InitConsumeStreamCallback;
 use js::jsapi::InitDispatchToEventLoop;
 use js::jsapi::MimeType;
+use js::jsapi::SetModuleDynamicImportHook;
 use js::jsapi::StreamConsumer as JSStreamConsumer;
 use js::jsapi::{BuildIdCharVector, DisableIncrementalGC, GCDescription, GCProgress};
 use js::jsapi::{Dispatchable as JSRunnable, Dispatchable_MaybeShuttingDown};
@@ -54,18 +59,22 @@ use js::jsapi::{
     JSJitCompilerOption, JS_SetOffthreadIonCompilationEnabled, JS_SetParallelParsingEnabled,
 };
 use js::jsapi::{JSObject, PromiseRejectionHandlingState, SetPreserveWrapperCallback};
+use js::jsapi::{JSSecurityCallbacks, JS_SetSecurityCallbacks};
+use js::jsapi::{JS_GetRuntime, JS_RequestInterruptCallback, SetModuleResolveHook};
 use js::jsapi::{SetJobQueue, SetProcessBuildIdOp, SetPromiseRejectionTrackerCallback};
 use js::jsval::UndefinedValue;
 use js::panic::wrap_panic;
 use js::rust::wrappers::{GetPromiseIsHandled, JS_GetPromiseResult};
 use js::rust::Handle;
 use js::rust::HandleObject as RustHandleObject;
+use js::rust::HandleValue as RustHandleValue;
 use js::rust::IntoHandle;
 use js::rust::ParentRuntime;
 use js::rust::Runtime as RustRuntime;
 use js::rust::{JSEngine, JSEngineHandle};
 use malloc_size_of::MallocSizeOfOps;
 use msg::constellation_msg::PipelineId;
+use net_traits::trim_http_whitespace;
 use profile_traits::mem::{Report, ReportKind, ReportsChan};
 use servo_config::opts;
 use servo_config::pref;
@@ -79,7 +88,7 @@ use std::os::raw::c_void;
 use std::panic::AssertUnwindSafe;
 use std::ptr;
 use std::rc::Rc;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use style::thread_state::{self, ThreadState};
@@ -91,6 +100,11 @@ static JOB_QUEUE_TRAPS: JobQueueTraps = JobQueueTraps {
     empty: Some(empty),
 };
 
+static SECURITY_CALLBACKS: JSSecurityCallbacks = JSSecurityCallbacks {
+    contentSecurityPolicyAllows: Some(content_security_policy_allows),
+    subsumes: None,
+};
+
 /// Common messages used to control the event loops in both the script and the worker
 pub enum CommonScriptMsg {
     /// Requests that the script thread measure its memory usage. The results are sent back via the
@@ -219,6 +233,26 @@ unsafe extern "C" fn enqueue_promise_job(
     )
 }
 
+/// SM callback for `eval` and `new Function`, which are blocked without the
+/// `unsafe-eval` source in a script-src directive.
+/// https://w3c.github.io/webappsec-csp/#can-compile-strings
+#[allow(unsafe_code)]
+unsafe extern "C" fn content_security_policy_allows(cx: *mut RawJSContext) -> bool {
+    wrap_panic(
+        AssertUnwindSafe(|| {
+            let global = GlobalScope::from_context(cx);
+            let csp_list = match global.get_csp_list() {
+                Some(csp_list) => csp_list,
+                None => return true,
+            };
+            let (result, violations) = csp_list.is_js_evaluation_allowed();
+            global.report_csp_violations(violations, None);
+            result == CheckResult::Allowed
+        }),
+        false,
+    )
+}
+
 #[allow(unsafe_code, unrooted_must_root)]
 /// https://html.spec.whatwg.org/multipage/#the-hostpromiserejectiontracker-implementation
 unsafe extern "C" fn promise_rejection_tracker(
@@ -354,8 +388,8 @@ pub fn notify_about_rejected_promises(global: &GlobalScope) {
                         let event_status = event.upcast::<Event>().fire(&target);
 
                         // Step 4-3.
-                        if event_status == EventStatus::Canceled {
-                            // TODO: The promise rejection is not handled; we need to add it back to the list.
+                        if event_status == EventStatus::NotCanceled {
+                            report_unhandled_rejection(&target.global(), reason.handle());
                         }
 
                         // Step 4-4.
@@ -370,6 +404,59 @@ pub fn notify_about_rejected_promises(global: &GlobalScope) {
     }
 }
 
+/// Reports a rejection that no `unhandledrejection` listener handled to the
+/// console, the way uncaught exceptions are.
+#[allow(unsafe_code)]
+unsafe fn report_unhandled_rejection(global: &GlobalScope, reason: RustHandleValue) {
+    let error_info = ErrorInfo::from_value(reason, *global.get_cx());
+    let message = if error_info.filename.is_empty() {
+        format!("Unhandled promise rejection: {}", error_info.message)
+    } else {
+        format!(
+            "Unhandled promise rejection at {}:{}:{} {}",
+            error_info.filename, error_info.lineno, error_info.column, error_info.message
+        )
+    };
+    Console::Error(global, vec![DOMString::from(message)]);
+}
+
+struct InterruptibleContext(*mut RawJSContext);
+
+#[allow(unsafe_code)]
+unsafe impl Send for InterruptibleContext {}
+
+/// A JS context running on another thread, whose interrupt callback
+/// can be requested from this thread, as long as the context is alive.
+#[derive(Clone, Default)]
+pub struct ContextForRequestInterrupt(Arc<Mutex<Option<InterruptibleContext>>>);
+
+impl ContextForRequestInterrupt {
+    /// Set the context, from the thread it runs on.
+    /// The context is unset when the returned guard is dropped,
+    /// which must happen before the context is destroyed.
+    pub fn set(&self, cx: *mut RawJSContext) -> ContextForRequestInterruptGuard {
+        *self.0.lock().unwrap() = Some(InterruptibleContext(cx));
+        ContextForRequestInterruptGuard(self.clone())
+    }
+
+    /// Request the interrupt callback of the context to be called,
+    /// on the thread where it runs.
+    #[allow(unsafe_code)]
+    pub fn request_interrupt(&self) {
+        if let Some(ref context) = *self.0.lock().unwrap() {
+            unsafe { JS_RequestInterruptCallback(context.0) };
+        }
+    }
+}
+
+pub struct ContextForRequestInterruptGuard(ContextForRequestInterrupt);
+
+impl Drop for ContextForRequestInterruptGuard {
+    fn drop(&mut self) {
+        *(self.0).0.lock().unwrap() = None;
+    }
+}
+
 #[derive(JSTraceable)]
 pub struct Runtime {
     rt: RustRuntime,
@@ -497,6 +584,12 @@ unsafe fn new_rt_and_cx_with_parent(
     );
     SetJobQueue(cx, job_queue);
     SetPromiseRejectionTrackerCallback(cx, Some(promise_rejection_tracker), ptr::null_mut());
+    JS_SetSecurityCallbacks(cx, &SECURITY_CALLBACKS);
+
+    // Imports of module scripts are looked up in the module map of their global,
+    // which import() fetches missing module graphs into.
+    SetModuleResolveHook(JS_GetRuntime(cx), Some(HostResolveImportedModule));
+    SetModuleDynamicImportHook(JS_GetRuntime(cx), Some(host_import_module_dynamically));
 
     set_gc_zeal_options(cx);
 
@@ -915,16 +1008,13 @@ unsafe extern "C" fn consume_stream(
         let mimetype = unwrapped_source.Headers().extract_mime_type();
 
         //Step 2.3 If mimeType is not `application/wasm`, return with a TypeError and abort these substeps.
-        match &mimetype[..] {
-            b"application/wasm" | b"APPLICATION/wasm" | b"APPLICATION/WASM" => {},
-            _ => {
-                throw_dom_exception(
-                    cx,
-                    &global,
-                    Error::Type("Response has unsupported MIME type".to_string()),
-                );
-                return false;
-            },
+        if !trim_http_whitespace(&mimetype).eq_ignore_ascii_case(b"application/wasm") {
+            throw_dom_exception(
+                cx,
+                &global,
+                Error::Type("Response has unsupported MIME type".to_string()),
+            );
+            return false;
         }
 
         //Step 2.4 If response is not CORS-same-origin, return with a TypeError and abort these substeps.
@@ -969,7 +1059,11 @@ unsafe extern "C" fn consume_stream(
             );
             return false;
         }
-        unwrapped_source.set_stream_consumer(Some(StreamConsumer(_consumer)));
+        // Step 2.7 onwards happen in the engine, which compiles the body as
+        // its chunks are handed to the consumer.
+        let consumer = StreamConsumer(_consumer);
+        consumer.note_response_urls(Some(unwrapped_source.Url().0), None);
+        unwrapped_source.set_stream_consumer(consumer);
     } else {
         //Step 3 Upon rejection of source, return with reason.
         throw_dom_exception(
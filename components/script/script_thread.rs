@@ -22,7 +22,7 @@ use crate::devtools;
 use crate::document_loader::DocumentLoader;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
-    DocumentMethods, DocumentReadyState,
+    DocumentMethods, DocumentReadyState, DocumentVisibilityState,
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
@@ -51,11 +51,13 @@ use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmliframeelement::{HTMLIFrameElement, NavigationType};
+use crate::dom::htmlslotelement::HTMLSlotElement;
 use crate::dom::mutationobserver::MutationObserver;
 use crate::dom::node::{
     from_untrusted_node_address, window_from_node, Node, NodeDamage, ShadowIncluding,
 };
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performancelongtasktiming::PerformanceLongTaskTiming;
 use crate::dom::performancepainttiming::PerformancePaintTiming;
 use crate::dom::serviceworker::TrustedServiceWorkerAddress;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
@@ -81,6 +83,7 @@ use crate::task_source::media_element::MediaElementTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::user_interaction::UserInteractionTaskSource;
@@ -94,7 +97,7 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::CSSError;
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, EventLoopWaker};
+use embedder_traits::{EmbedderMsg, EventLoopWaker, PermissionName, ScreenOrientationState};
 use euclid::default::{Point2D, Rect};
 use euclid::Vector2D;
 use headers::ReferrerPolicy as ReferrerPolicyHeader;
@@ -130,8 +133,8 @@ use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
 use script_layout_interface::message::{self, LayoutThreadInit, Msg, ReflowGoal};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::CompositorEvent::{
-    CompositionEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent, TouchEvent,
-    WheelEvent,
+    CompositionEvent, GamepadEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent,
+    MouseMovementEvent, PenEvent, ResizeEvent, TouchEvent, WheelEvent,
 };
 use script_traits::StructuredSerializedData;
 use script_traits::{CompositorEvent, ConstellationControlMsg};
@@ -141,10 +144,11 @@ use script_traits::{
 use script_traits::{InitialScriptState, JsEvalResult, LayoutMsg, LoadData, LoadOrigin};
 use script_traits::{MediaSessionActionType, MouseButton, MouseEventType, NewLayoutInfo};
 use script_traits::{Painter, ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory};
-use script_traits::{ScriptToConstellationChan, TimerSchedulerMsg};
+use script_traits::{PenState, ScriptToConstellationChan, TimerSchedulerMsg};
 use script_traits::{TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta};
 use script_traits::{UpdatePipelineIdReason, WebrenderIpcSender, WindowSizeData, WindowSizeType};
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -166,7 +170,7 @@ use time::{at_utc, get_time, precise_time_ns, Timespec};
 use url::Position;
 use webgpu::WebGPU;
 use webrender_api::units::LayoutPixel;
-use webrender_api::DocumentId;
+use webrender_api::{DocumentId, ExternalScrollId};
 use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
@@ -570,6 +574,8 @@ pub struct ScriptThread {
 
     port_message_sender: Box<dyn ScriptChan>,
 
+    posted_task_sender: Box<dyn ScriptChan>,
+
     timer_task_sender: Box<dyn ScriptChan>,
 
     remote_event_task_sender: Box<dyn ScriptChan>,
@@ -627,6 +633,9 @@ pub struct ScriptThread {
     /// The unit of related similar-origin browsing contexts' list of MutationObserver objects
     mutation_observers: DomRefCell<Vec<Dom<MutationObserver>>>,
 
+    /// The slots waiting for a slotchange event, <https://dom.spec.whatwg.org/#signal-slot-list>
+    signal_slots: DomRefCell<Vec<Dom<HTMLSlotElement>>>,
+
     /// A handle to the WebGL thread
     webgl_chan: Option<WebGLPipeline>,
 
@@ -882,6 +891,28 @@ impl ScriptThread {
         })
     }
 
+    pub fn add_signal_slot(slot: &HTMLSlotElement) {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread
+                .signal_slots
+                .borrow_mut()
+                .push(Dom::from_ref(slot));
+        })
+    }
+
+    pub fn take_signal_slots() -> Vec<DomRoot<HTMLSlotElement>> {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread
+                .signal_slots
+                .borrow_mut()
+                .drain(..)
+                .map(|slot| DomRoot::from_ref(&*slot))
+                .collect()
+        })
+    }
+
     pub fn mark_document_with_no_blocked_loads(doc: &Document) {
         SCRIPT_THREAD_ROOT.with(|root| {
             if let Some(script_thread) = root.get() {
@@ -979,9 +1010,10 @@ impl ScriptThread {
                 let sender = script_thread.script_sender.clone();
                 let task = task!(navigate_javascript: move || {
                     // Important re security. See https://github.com/servo/servo/issues/23373
-                    // TODO: check according to https://w3c.github.io/webappsec-csp/#should-block-navigation-request
                     if let Some(window) = trusted_global.root().downcast::<Window>() {
-                        if ScriptThread::check_load_origin(&load_data.load_origin, &window.get_url().origin()) {
+                        if ScriptThread::check_load_origin(&load_data.load_origin, &window.get_url().origin()) &&
+                            !window.Document().should_javascript_url_navigation_be_blocked(&load_data.url)
+                        {
                             ScriptThread::eval_js_url(&trusted_global.root(), &mut load_data);
                             sender
                                 .send((pipeline_id, ScriptMsg::LoadUrl(load_data, replace)))
@@ -1307,6 +1339,7 @@ impl ScriptThread {
             port_message_sender: boxed_script_sender.clone(),
             file_reading_task_sender: boxed_script_sender.clone(),
             performance_timeline_task_sender: boxed_script_sender.clone(),
+            posted_task_sender: boxed_script_sender.clone(),
             timer_task_sender: boxed_script_sender.clone(),
             remote_event_task_sender: boxed_script_sender.clone(),
 
@@ -1336,6 +1369,8 @@ impl ScriptThread {
 
             mutation_observers: Default::default(),
 
+            signal_slots: Default::default(),
+
             layout_to_constellation_chan: state.layout_to_constellation_chan,
 
             webgl_chan: state.webgl_chan,
@@ -1577,6 +1612,20 @@ impl ScriptThread {
             docs.clear();
         }
 
+        // https://html.spec.whatwg.org/multipage/#event-loop-processing-model step 7.7
+        // Resizes re-evaluate media queries as they happen, but other media
+        // features, such as the user preferences, can change at any time.
+        let windows: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .filter(|(_, document)| document.is_fully_active())
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .collect();
+        for window in windows {
+            window.evaluate_media_queries_if_changed();
+        }
+
         // https://html.spec.whatwg.org/multipage/#event-loop-processing-model step 7.12
 
         // Issue batched reflows on any pages that require it (e.g. if images loaded)
@@ -1604,6 +1653,9 @@ impl ScriptThread {
                 // minimize unnecessary work.
                 window.reflow(ReflowGoal::Full, ReflowReason::MissingExplicitReflow);
             }
+
+            // https://w3c.github.io/event-timing/#mark-paint-timing
+            window.report_pending_event_timings();
         }
 
         true
@@ -1698,20 +1750,26 @@ impl ScriptThread {
                     FocusIFrame(id, ..) => Some(id),
                     WebDriverScriptCommand(id, ..) => Some(id),
                     TickAllAnimations(id) => Some(id),
+                    SmoothScrollEnded(id, ..) => Some(id),
                     // FIXME https://github.com/servo/servo/issues/15079
                     TransitionEnd(..) => None,
                     WebFontLoaded(id) => Some(id),
+                    RestyleAnimatedElements(id) => Some(id),
                     DispatchIFrameLoadEvent {
                         target: _,
                         parent: id,
                         child: _,
                     } => Some(id),
                     DispatchStorageEvent(id, ..) => Some(id),
+                    DispatchPermissionChange(id, ..) => Some(id),
                     ReportCSSError(id, ..) => Some(id),
                     Reload(id, ..) => Some(id),
                     WebVREvents(id, ..) => Some(id),
                     PaintMetric(..) => None,
+                    FramePresented(id, ..) => Some(id),
                     ExitFullScreen(id, ..) => Some(id),
+                    ExitPointerLock(id) => Some(id),
+                    ChangeScreenOrientation(id, ..) => Some(id),
                     MediaSessionAction(..) => None,
                 }
             },
@@ -1804,9 +1862,33 @@ impl ScriptThread {
             }
             doc.record_tti_if_necessary();
         }
+        if end - start > MAX_TASK_NS {
+            self.report_long_task(pipeline_id, start, end);
+        }
         value
     }
 
+    /// Reports a task that ran for too long to all the documents of this thread,
+    /// which share its event loop.
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    fn report_long_task(&self, pipeline_id: Option<PipelineId>, start: u64, end: u64) {
+        let documents: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .collect();
+        let culprit = pipeline_id.and_then(|id| self.documents.borrow().find_document(id));
+        for document in documents {
+            PerformanceLongTaskTiming::report(
+                &document,
+                culprit.as_ref().map(|d| &**d),
+                start,
+                end,
+            );
+        }
+    }
+
     fn handle_msg_from_constellation(&self, msg: ConstellationControlMsg) {
         match msg {
             ConstellationControlMsg::StopDelayingLoadEventsMode(pipeline_id) => {
@@ -1903,12 +1985,18 @@ impl ScriptThread {
             ConstellationControlMsg::TickAllAnimations(pipeline_id) => {
                 self.handle_tick_all_animations(pipeline_id)
             },
+            ConstellationControlMsg::SmoothScrollEnded(pipeline_id, scroll_id) => {
+                self.handle_smooth_scroll_ended(pipeline_id, scroll_id)
+            },
             ConstellationControlMsg::TransitionEnd(unsafe_node, name, duration) => {
                 self.handle_transition_event(unsafe_node, name, duration)
             },
             ConstellationControlMsg::WebFontLoaded(pipeline_id) => {
                 self.handle_web_font_loaded(pipeline_id)
             },
+            ConstellationControlMsg::RestyleAnimatedElements(pipeline_id) => {
+                self.handle_restyle_animated_elements(pipeline_id)
+            },
             ConstellationControlMsg::DispatchIFrameLoadEvent {
                 target: browsing_context_id,
                 parent: parent_id,
@@ -1922,6 +2010,9 @@ impl ScriptThread {
                 old_value,
                 new_value,
             ) => self.handle_storage_event(pipeline_id, storage, url, key, old_value, new_value),
+            ConstellationControlMsg::DispatchPermissionChange(pipeline_id, name) => {
+                self.handle_permission_change(pipeline_id, name)
+            },
             ConstellationControlMsg::ReportCSSError(pipeline_id, filename, line, column, msg) => {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg)
             },
@@ -1935,9 +2026,18 @@ impl ScriptThread {
             ConstellationControlMsg::PaintMetric(pipeline_id, metric_type, metric_value) => {
                 self.handle_paint_metric(pipeline_id, metric_type, metric_value)
             },
+            ConstellationControlMsg::FramePresented(pipeline_id, paint_time) => {
+                self.handle_frame_presented(pipeline_id, paint_time)
+            },
             ConstellationControlMsg::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action)
             },
+            ConstellationControlMsg::ExitPointerLock(pipeline_id) => {
+                self.handle_exit_pointer_lock(pipeline_id)
+            },
+            ConstellationControlMsg::ChangeScreenOrientation(pipeline_id, orientation) => {
+                self.handle_change_screen_orientation(pipeline_id, orientation)
+            },
             msg @ ConstellationControlMsg::AttachLayout(..) |
             msg @ ConstellationControlMsg::Viewport(..) |
             msg @ ConstellationControlMsg::SetScrollState(..) |
@@ -2282,6 +2382,24 @@ impl ScriptThread {
         }
     }
 
+    fn handle_exit_pointer_lock(&self, id: PipelineId) {
+        let document = self.documents.borrow().find_document(id);
+        if let Some(document) = document {
+            document.exit_pointer_lock();
+        }
+    }
+
+    fn handle_change_screen_orientation(
+        &self,
+        id: PipelineId,
+        orientation: ScreenOrientationState,
+    ) {
+        let document = self.documents.borrow().find_document(id);
+        if let Some(document) = document {
+            document.window().Screen().orientation_changed(orientation);
+        }
+    }
+
     fn handle_viewport(&self, id: PipelineId, rect: Rect<f32>) {
         let document = self.documents.borrow().find_document(id);
         if let Some(document) = document {
@@ -2441,6 +2559,12 @@ impl ScriptThread {
         match window {
             Some(window) => {
                 window.alter_resource_utilization(visible);
+                let visibility_state = if visible {
+                    DocumentVisibilityState::Visible
+                } else {
+                    DocumentVisibilityState::Hidden
+                };
+                window.Document().update_visibility_state(visibility_state);
                 return;
             },
             None => {
@@ -2770,6 +2894,10 @@ impl ScriptThread {
         PortMessageQueue(self.port_message_sender.clone(), pipeline_id)
     }
 
+    pub fn posted_task_source(&self, pipeline_id: PipelineId) -> PostedTaskSource {
+        PostedTaskSource(self.posted_task_sender.clone(), pipeline_id)
+    }
+
     pub fn file_reading_task_source(&self, pipeline_id: PipelineId) -> FileReadingTaskSource {
         FileReadingTaskSource(self.file_reading_task_sender.clone(), pipeline_id)
     }
@@ -2904,6 +3032,14 @@ impl ScriptThread {
         document.run_the_animation_frame_callbacks();
     }
 
+    fn handle_smooth_scroll_ended(&self, id: PipelineId, scroll_id: ExternalScrollId) {
+        let window = match { self.documents.borrow().find_window(id) } {
+            Some(window) => window,
+            None => return warn!("Message sent to closed pipeline {}.", id),
+        };
+        window.handle_smooth_scroll_ended(scroll_id);
+    }
+
     /// Handles firing of transition events.
     fn handle_transition_event(
         &self,
@@ -2964,6 +3100,18 @@ impl ScriptThread {
         }
     }
 
+    /// Handles layout asking for the elements that animations run on to be restyled. Does nothing
+    /// if the page no longer exists.
+    fn handle_restyle_animated_elements(&self, pipeline_id: PipelineId) {
+        let document = self.documents.borrow().find_document(pipeline_id);
+        if let Some(document) = document {
+            document.dirty_animated_elements();
+            document
+                .window()
+                .reflow(ReflowGoal::Full, ReflowReason::AnimationTick);
+        }
+    }
+
     /// Handles a worklet being loaded. Does nothing if the page no longer exists.
     fn handle_worklet_loaded(&self, pipeline_id: PipelineId) {
         let document = self.documents.borrow().find_document(pipeline_id);
@@ -2995,6 +3143,18 @@ impl ScriptThread {
         storage.queue_storage_event(url, key, old_value, new_value);
     }
 
+    fn handle_permission_change(&self, pipeline_id: PipelineId, name: PermissionName) {
+        let window = match { self.documents.borrow().find_window(pipeline_id) } {
+            None => return warn!("Permission change sent to closed pipeline {}.", pipeline_id),
+            Some(window) => window,
+        };
+
+        window
+            .Navigator()
+            .Permissions()
+            .permission_state_changed(name);
+    }
+
     /// Notify the containing document of a child iframe that has completed loading.
     fn handle_iframe_load_event(
         &self,
@@ -3176,6 +3336,7 @@ impl ScriptThread {
             self.performance_timeline_task_source(incomplete.pipeline_id)
                 .clone(),
             self.port_message_queue(incomplete.pipeline_id),
+            self.posted_task_source(incomplete.pipeline_id),
             self.user_interaction_task_source(incomplete.pipeline_id),
             self.remote_event_task_source(incomplete.pipeline_id),
             self.timer_task_source(incomplete.pipeline_id),
@@ -3297,6 +3458,30 @@ impl ScriptThread {
             incomplete.canceller,
         );
         document.set_ready_state(DocumentReadyState::Loading);
+        if !incomplete.is_visible {
+            document.update_visibility_state(DocumentVisibilityState::Hidden);
+            window.alter_resource_utilization(false);
+        }
+
+        // A top-level document served with `Cross-Origin-Opener-Policy: same-origin` and
+        // `Cross-Origin-Embedder-Policy: require-corp` is cross-origin isolated.
+        // Neither policy is enforced yet, so this stays behind a pref.
+        // TODO: Nested documents, which would need the embedder policy of their parent.
+        let cross_origin_isolated = pref!(dom.cross_origin_isolation.enabled) &&
+            window_proxy.parent().is_none() &&
+            metadata.headers.as_ref().map_or(false, |headers| {
+                let has_value = |name: &str, value: &str| {
+                    headers
+                        .get(name)
+                        .and_then(|header| header.to_str().ok())
+                        .map_or(false, |header| header.trim().eq_ignore_ascii_case(value))
+                };
+                has_value("cross-origin-opener-policy", "same-origin") &&
+                    has_value("cross-origin-embedder-policy", "require-corp")
+            });
+        window
+            .upcast::<GlobalScope>()
+            .set_cross_origin_isolated(cross_origin_isolated);
 
         self.documents
             .borrow_mut()
@@ -3348,10 +3533,6 @@ impl ScriptThread {
             window.suspend();
         }
 
-        if !incomplete.is_visible {
-            window.alter_resource_utilization(false);
-        }
-
         document.get_current_parser().unwrap()
     }
 
@@ -3430,7 +3611,7 @@ impl ScriptThread {
                 );
             },
 
-            MouseMoveEvent(point, node_address, pressed_mouse_buttons) => {
+            MouseMoveEvent(point, node_address, point_in_node, pressed_mouse_buttons) => {
                 let document = match { self.documents.borrow().find_document(pipeline_id) } {
                     Some(document) => document,
                     None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
@@ -3445,6 +3626,7 @@ impl ScriptThread {
                     point,
                     &self.topmost_mouse_over_target,
                     node_address,
+                    point_in_node,
                     pressed_mouse_buttons,
                 );
 
@@ -3494,6 +3676,14 @@ impl ScriptThread {
                 }
             },
 
+            MouseMovementEvent(movement, pressed_mouse_buttons) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document.handle_mouse_movement_event(movement, pressed_mouse_buttons);
+            },
+
             TouchEvent(event_type, identifier, point, node_address) => {
                 let touch_result = self.handle_touch_event(
                     pipeline_id,
@@ -3505,7 +3695,6 @@ impl ScriptThread {
                 match (event_type, touch_result) {
                     (TouchEventType::Down, TouchEventResult::Processed(handled)) => {
                         let result = if handled {
-                            // TODO: Wait to see if preventDefault is called on the first touchmove event.
                             EventResult::DefaultAllowed
                         } else {
                             EventResult::DefaultPrevented
@@ -3513,12 +3702,29 @@ impl ScriptThread {
                         let message = ScriptMsg::TouchEventProcessed(result);
                         self.script_sender.send((pipeline_id, message)).unwrap();
                     },
+                    (TouchEventType::Move, TouchEventResult::Processed(false)) => {
+                        // Canceling a touchmove event prevents the page from
+                        // scrolling or zooming for the rest of the touch sequence.
+                        let message = ScriptMsg::TouchEventProcessed(EventResult::DefaultPrevented);
+                        self.script_sender.send((pipeline_id, message)).unwrap();
+                    },
                     _ => {
                         // TODO: Calling preventDefault on a touchup event should prevent clicks.
                     },
                 }
             },
 
+            PenEvent(event_type, identifier, point, node_address, state) => {
+                self.handle_pen_event(
+                    pipeline_id,
+                    event_type,
+                    identifier,
+                    point,
+                    node_address,
+                    state,
+                );
+            },
+
             WheelEvent(delta, point, node_address) => {
                 self.handle_wheel_event(pipeline_id, delta, point, node_address);
             },
@@ -3538,6 +3744,14 @@ impl ScriptThread {
                 };
                 document.dispatch_composition_event(composition_event);
             },
+
+            GamepadEvent(gamepad_event) => {
+                let window = match { self.documents.borrow().find_window(pipeline_id) } {
+                    Some(window) => window,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                window.Navigator().handle_gamepad_event(gamepad_event);
+            },
         }
 
         ScriptThread::set_user_interacting(false);
@@ -3592,6 +3806,29 @@ impl ScriptThread {
         )
     }
 
+    fn handle_pen_event(
+        &self,
+        pipeline_id: PipelineId,
+        event_type: TouchEventType,
+        identifier: TouchId,
+        point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>,
+        state: PenState,
+    ) {
+        let document = match { self.documents.borrow().find_document(pipeline_id) } {
+            Some(document) => document,
+            None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+        };
+        document.handle_pen_event(
+            self.js_runtime.rt(),
+            event_type,
+            identifier,
+            point,
+            node_address,
+            state,
+        );
+    }
+
     fn handle_wheel_event(
         &self,
         pipeline_id: PipelineId,
@@ -3899,6 +4136,15 @@ impl ScriptThread {
         }
     }
 
+    fn handle_frame_presented(&self, pipeline_id: PipelineId, paint_time: u64) {
+        let window = self.documents.borrow().find_window(pipeline_id);
+        if let Some(window) = window {
+            window
+                .Performance()
+                .report_presented_event_entries(paint_time);
+        }
+    }
+
     fn handle_media_session_action(&self, pipeline_id: PipelineId, action: MediaSessionActionType) {
         if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
             let media_session = window.Navigator().MediaSession();
@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reporting of Content Security Policy violations.
+//! <https://w3c.github.io/webappsec-csp/#reporting>
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
+use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::{
+    SecurityPolicyViolationEventDisposition, SecurityPolicyViolationEventInit,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::securitypolicyviolationevent::SecurityPolicyViolationEvent;
+use crate::dom::window::Window;
+use crate::task::TaskOnce;
+use content_security_policy as csp;
+use servo_url::ServoUrl;
+
+/// The information about a violation that is reported to the page.
+/// <https://w3c.github.io/webappsec-csp/#violation>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct CSPViolationReport {
+    pub document_url: String,
+    pub referrer: String,
+    pub blocked_url: String,
+    pub effective_directive: String,
+    pub original_policy: String,
+    pub sample: String,
+    pub report_only: bool,
+}
+
+impl CSPViolationReport {
+    /// <https://w3c.github.io/webappsec-csp/#create-violation-for-global>
+    pub fn new(global: &GlobalScope, violation: csp::Violation) -> CSPViolationReport {
+        let blocked_url = match violation.resource {
+            csp::ViolationResource::Url(url) => strip_url_for_reports(ServoUrl::from_url(url)),
+            csp::ViolationResource::Inline { .. } => "inline".to_owned(),
+            _ => "eval".to_owned(),
+        };
+        let referrer = match global.downcast::<Window>() {
+            Some(window) => window.Document().Referrer().into(),
+            None => String::new(),
+        };
+        CSPViolationReport {
+            document_url: strip_url_for_reports(global.get_url()),
+            referrer,
+            blocked_url,
+            effective_directive: violation.directive.name.clone(),
+            original_policy: violation.policy.to_string(),
+            sample: String::new(),
+            report_only: violation.policy.disposition == csp::PolicyDisposition::Report,
+        }
+    }
+
+    fn event_init(&self) -> SecurityPolicyViolationEventInit {
+        SecurityPolicyViolationEventInit {
+            parent: EventInit {
+                bubbles: true,
+                cancelable: false,
+            },
+            documentURI: USVString(self.document_url.clone()),
+            referrer: USVString(self.referrer.clone()),
+            blockedURI: USVString(self.blocked_url.clone()),
+            violatedDirective: DOMString::from(self.effective_directive.clone()),
+            effectiveDirective: DOMString::from(self.effective_directive.clone()),
+            originalPolicy: DOMString::from(self.original_policy.clone()),
+            // TODO: the location of the script that caused the violation.
+            sourceFile: USVString(String::new()),
+            sample: DOMString::from(self.sample.clone()),
+            disposition: if self.report_only {
+                SecurityPolicyViolationEventDisposition::Report
+            } else {
+                SecurityPolicyViolationEventDisposition::Enforce
+            },
+            // TODO: the status code of the document's response.
+            statusCode: 0,
+            lineNumber: 0,
+            columnNumber: 0,
+        }
+    }
+}
+
+/// A task that fires a `securitypolicyviolation` event at the target of a violation.
+/// <https://w3c.github.io/webappsec-csp/#report-violation>
+pub struct CSPViolationReporter {
+    target: Trusted<EventTarget>,
+    report: CSPViolationReport,
+}
+
+impl CSPViolationReporter {
+    pub fn new(target: &EventTarget, report: CSPViolationReport) -> CSPViolationReporter {
+        CSPViolationReporter {
+            target: Trusted::new(target),
+            report,
+        }
+    }
+}
+
+impl TaskOnce for CSPViolationReporter {
+    fn run_once(self) {
+        let target = self.target.root();
+        let event = SecurityPolicyViolationEvent::new(
+            &target.global(),
+            atom!("securitypolicyviolation"),
+            EventBubbles::Bubbles,
+            EventCancelable::NotCancelable,
+            &self.report.event_init(),
+        );
+        event.upcast::<Event>().fire(&target);
+    }
+}
+
+/// <https://w3c.github.io/webappsec-csp/#strip-url-for-use-in-reports>
+pub fn strip_url_for_reports(mut url: ServoUrl) -> String {
+    // Step 1.
+    match url.scheme() {
+        "http" | "https" => {},
+        scheme => return scheme.to_owned(),
+    }
+    // Step 2-4.
+    url.set_fragment(None);
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    // Step 5.
+    url.into_string()
+}
@@ -14,7 +14,7 @@ use crossbeam_channel::{unbounded, Receiver, RecvError, Sender};
 use devtools_traits::{DevtoolsPageInfo, ScriptToDevtoolsControlMsg};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
-use net_traits::{CoreResourceMsg, CustomResponseMediator};
+use net_traits::{CoreResourceMsg, CustomResponseMediator, ServiceWorkerResponse};
 use script_traits::{DOMMessage, SWManagerMsg, SWManagerSenders, ScopeThings, ServiceWorkerMsg};
 use servo_config::pref;
 use servo_url::ServoUrl;
@@ -187,10 +187,10 @@ impl ServiceWorkerManager {
                     }
                 }
             } else {
-                let _ = mediator.response_chan.send(None);
+                let _ = mediator.response_chan.send(ServiceWorkerResponse::Fallback);
             }
         } else {
-            let _ = mediator.response_chan.send(None);
+            let _ = mediator.response_chan.send(ServiceWorkerResponse::Fallback);
         }
         true
     }
@@ -125,7 +125,9 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                     None => return false,
                 };
                 match task_source {
-                    TaskSourceName::PerformanceTimeline => return true,
+                    TaskSourceName::PerformanceTimeline | TaskSourceName::BackgroundPostedTask => {
+                        return true
+                    },
                     _ => {
                         // A task that will not be throttled, start counting "business"
                         self.taken_task_counter
@@ -136,6 +138,12 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             })
             .collect();
 
+        // 5. Let user-blocking posted tasks run before the other tasks.
+        // https://wicg.github.io/scheduling-apis/#scheduler-task-queue
+        incoming.sort_by_key(|msg| {
+            msg.task_source_name() != Some(&TaskSourceName::UserBlockingPostedTask)
+        });
+
         for msg in incoming {
             if let Some(pipeline_id) = msg.pipeline_id() {
                 if !fully_active.contains(&pipeline_id) {
@@ -9,6 +9,7 @@ pub mod media_element;
 pub mod networking;
 pub mod performance_timeline;
 pub mod port_message;
+pub mod posted_task;
 pub mod remote_event;
 pub mod timer;
 pub mod user_interaction;
@@ -36,6 +37,9 @@ pub enum TaskSourceName {
     MediaElement,
     Websocket,
     Timer,
+    UserBlockingPostedTask,
+    UserVisiblePostedTask,
+    BackgroundPostedTask,
 }
 
 impl TaskSourceName {
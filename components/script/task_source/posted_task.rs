@@ -0,0 +1,68 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::TaskPriority;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory};
+use crate::task::{TaskCanceller, TaskOnce};
+use crate::task_source::TaskSourceName;
+use msg::constellation_msg::PipelineId;
+use std::fmt;
+use std::result::Result;
+
+/// https://wicg.github.io/scheduling-apis/#posted-task-task-source
+///
+/// Its tasks are queued under a different `TaskSourceName` for each
+/// priority, which is what the task queue orders them by, so it doesn't
+/// implement `TaskSource`.
+#[derive(JSTraceable)]
+pub struct PostedTaskSource(pub Box<dyn ScriptChan + Send + 'static>, pub PipelineId);
+
+impl Clone for PostedTaskSource {
+    fn clone(&self) -> PostedTaskSource {
+        PostedTaskSource(self.0.clone(), self.1.clone())
+    }
+}
+
+impl fmt::Debug for PostedTaskSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PostedTaskSource(...)")
+    }
+}
+
+impl PostedTaskSource {
+    pub fn name(priority: TaskPriority) -> TaskSourceName {
+        match priority {
+            TaskPriority::User_blocking => TaskSourceName::UserBlockingPostedTask,
+            TaskPriority::User_visible => TaskSourceName::UserVisiblePostedTask,
+            TaskPriority::Background => TaskSourceName::BackgroundPostedTask,
+        }
+    }
+
+    pub fn queue_with_canceller<T>(
+        &self,
+        task: T,
+        priority: TaskPriority,
+        canceller: &TaskCanceller,
+    ) -> Result<(), ()>
+    where
+        T: TaskOnce + 'static,
+    {
+        let msg = CommonScriptMsg::Task(
+            ScriptThreadEventCategory::ScriptEvent,
+            Box::new(canceller.wrap_task(task)),
+            Some(self.1),
+            PostedTaskSource::name(priority),
+        );
+        self.0.send(msg).map_err(|_| ())
+    }
+
+    pub fn queue<T>(&self, task: T, priority: TaskPriority, global: &GlobalScope) -> Result<(), ()>
+    where
+        T: TaskOnce + 'static,
+    {
+        let canceller = global.task_canceller(PostedTaskSource::name(priority));
+        self.queue_with_canceller(task, priority, &canceller)
+    }
+}
@@ -24,7 +24,7 @@ impl TaskSource for WebsocketTaskSource {
         T: TaskOnce + 'static,
     {
         self.0.send(CommonScriptMsg::Task(
-            ScriptThreadEventCategory::NetworkEvent,
+            ScriptThreadEventCategory::WebSocketEvent,
             Box::new(canceller.wrap_task(task)),
             Some(self.1),
             WebsocketTaskSource::NAME,
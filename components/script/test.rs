@@ -15,6 +15,20 @@ pub mod area {
     pub use crate::dom::htmlareaelement::{Area, Shape};
 }
 
+pub mod media_segment_parser {
+    pub use crate::media_segment_parser::{ByteStreamFormat, ParseError, Segment, SegmentParser};
+}
+
+pub mod payment {
+    pub use crate::dom::bindings::codegen::Bindings::PaymentRequestBinding::{
+        PaymentCurrencyAmount, PaymentItem, PaymentShippingOption,
+    };
+    pub use crate::dom::bindings::error::Error;
+    pub use crate::dom::paymentrequest::validate_payment_method_identifier;
+    pub use crate::dom::paymentrequest::{check_and_canonicalize_amount, shipping_options};
+    pub use crate::dom::paymentrequest::{is_valid_decimal_monetary_value, total_item};
+}
+
 pub mod size_of {
     use crate::dom::characterdata::CharacterData;
     use crate::dom::element::Element;
@@ -63,6 +77,33 @@ pub mod srcset {
     pub use crate::dom::htmlimageelement::{parse_a_srcset_attribute, Descriptor, ImageSource};
 }
 
+pub mod subtlecrypto {
+    pub use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::KeyUsage;
+    pub use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::KeyFormat;
+    pub use crate::dom::cryptokey::KeyData;
+    pub use crate::dom::subtlecrypto::{AlgorithmName, CipherParams, ImportParams, KeyMaterial};
+    pub use crate::dom::subtlecrypto::{Sha, SignatureParams};
+}
+
 pub mod timeranges {
     pub use crate::dom::timeranges::TimeRangesContainer;
 }
+
+pub mod webauthn {
+    pub use crate::dom::document::get_registrable_domain_suffix_of_or_is_equal_to;
+    pub use crate::dom::publickeycredential::{
+        anonymize_attestation, attestation_object, attested_credential_id,
+    };
+    pub use embedder_traits::webauthn::MakeCredentialResponse;
+}
+
+pub mod webvtt {
+    pub use crate::webvtt::{parse, parse_cue_text, CueElementKind, CueNode, CueSettings};
+    pub use crate::webvtt::{LineAlignment, TextAlignment, WritingDirection};
+}
+
+pub mod xpath {
+    pub use crate::xpath::{compare, number_to_string, round, string_to_number};
+    pub use crate::xpath::{parse, Axis, BinaryOperator, Expr, NodeTest, ParseError, PathStart};
+    pub use crate::xpath::{Step, Value};
+}
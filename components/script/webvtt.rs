@@ -0,0 +1,580 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A parser for WebVTT files and cue text.
+//!
+//! <https://w3c.github.io/webvtt/#file-parsing>
+
+use std::iter::Peekable;
+use std::str::Lines;
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-writing-direction>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WritingDirection {
+    Horizontal,
+    VerticalGrowingLeft,
+    VerticalGrowingRight,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-line-alignment>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineAlignment {
+    Start,
+    Center,
+    End,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-position-alignment>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionAlignment {
+    LineLeft,
+    Center,
+    LineRight,
+    Auto,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-text-alignment>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextAlignment {
+    Start,
+    Center,
+    End,
+    Left,
+    Right,
+}
+
+/// The settings of a cue, with their default values when the cue timings
+/// line does not override them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CueSettings {
+    pub writing_direction: WritingDirection,
+    pub snap_to_lines: bool,
+    /// `None` stands for `auto`.
+    pub line: Option<f64>,
+    pub line_alignment: LineAlignment,
+    /// `None` stands for `auto`.
+    pub position: Option<f64>,
+    pub position_alignment: PositionAlignment,
+    pub size: f64,
+    pub text_alignment: TextAlignment,
+}
+
+impl Default for CueSettings {
+    fn default() -> CueSettings {
+        CueSettings {
+            writing_direction: WritingDirection::Horizontal,
+            snap_to_lines: true,
+            line: None,
+            line_alignment: LineAlignment::Start,
+            position: None,
+            position_alignment: PositionAlignment::Auto,
+            size: 100.,
+            text_alignment: TextAlignment::Center,
+        }
+    }
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue>
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cue {
+    pub id: String,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub settings: CueSettings,
+    /// The raw cue text, to be parsed with `parse_cue_text`.
+    pub text: String,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-parser-algorithm>
+///
+/// Returns `None` if the input does not start with a WebVTT file
+/// signature. Regions and style sheets are skipped.
+pub fn parse(input: &str) -> Option<Vec<Cue>> {
+    // Steps 1-3, normalizing line terminators as `Lines` only splits on
+    // LF and CRLF.
+    let input = input.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let input = input.replace('\r', "\n");
+    let mut lines = input.lines().peekable();
+
+    // Steps 5-9.
+    let signature = lines.next()?;
+    if !signature.starts_with("WEBVTT") {
+        return None;
+    }
+    match signature[6..].chars().next() {
+        None | Some(' ') | Some('\t') => {},
+        Some(_) => return None,
+    }
+
+    // Steps 10-13, skipping the header.
+    while let Some(line) = lines.peek() {
+        if line.is_empty() || line.contains("-->") {
+            break;
+        }
+        lines.next();
+    }
+
+    // Steps 14-17.
+    let mut cues = vec![];
+    loop {
+        while lines.peek().map_or(false, |line| line.is_empty()) {
+            lines.next();
+        }
+        if lines.peek().is_none() {
+            break;
+        }
+        if let Some(cue) = collect_block(&mut lines) {
+            cues.push(cue);
+        }
+    }
+    Some(cues)
+}
+
+/// <https://w3c.github.io/webvtt/#collect-a-webvtt-block>
+///
+/// Blocks without cue timings, such as comments, style sheets and regions,
+/// are skipped.
+fn collect_block(lines: &mut Peekable<Lines>) -> Option<Cue> {
+    let mut line_count = 0;
+    let mut buffer = vec![];
+    let mut id = String::new();
+    let mut timings = None;
+    let mut seen_arrow = false;
+
+    while let Some(&line) = lines.peek() {
+        if line.is_empty() {
+            break;
+        }
+        line_count += 1;
+        if line.contains("-->") {
+            if seen_arrow || line_count > 2 {
+                // This line starts the next block.
+                break;
+            }
+            lines.next();
+            seen_arrow = true;
+            id = buffer.join("\n");
+            buffer.clear();
+            timings = parse_timings_and_settings(line);
+            continue;
+        }
+        lines.next();
+        buffer.push(line);
+    }
+
+    let (start_time, end_time, settings) = timings?;
+    Some(Cue {
+        id,
+        start_time,
+        end_time,
+        settings,
+        text: buffer.join("\n"),
+    })
+}
+
+/// <https://w3c.github.io/webvtt/#collect-webvtt-cue-timings-and-settings>
+fn parse_timings_and_settings(line: &str) -> Option<(f64, f64, CueSettings)> {
+    let mut parts = line.splitn(2, "-->");
+    let start_time = parse_timestamp(parts.next()?.trim_matches(is_whitespace))?;
+    let rest = parts.next()?.trim_start_matches(is_whitespace);
+    let end = rest.find(is_whitespace).unwrap_or(rest.len());
+    let end_time = parse_timestamp(&rest[..end])?;
+    Some((start_time, end_time, parse_settings(&rest[end..])))
+}
+
+/// <https://w3c.github.io/webvtt/#collect-a-webvtt-timestamp>
+pub fn parse_timestamp(input: &str) -> Option<f64> {
+    let values: Vec<&str> = input.split(':').collect();
+    let (hours, minutes, seconds) = match values.len() {
+        2 => ("0", values[0], values[1]),
+        3 => (values[0], values[1], values[2]),
+        _ => return None,
+    };
+    let all_digits = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(hours) || minutes.len() != 2 || !all_digits(minutes) {
+        return None;
+    }
+    let mut seconds = seconds.splitn(2, '.');
+    let (seconds, fraction) = (seconds.next()?, seconds.next()?);
+    if seconds.len() != 2 || !all_digits(seconds) || fraction.len() != 3 || !all_digits(fraction) {
+        return None;
+    }
+    let hours: f64 = hours.parse().ok()?;
+    let minutes: f64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    let fraction: f64 = fraction.parse().ok()?;
+    if minutes > 59. || seconds > 59. {
+        return None;
+    }
+    Some(hours * 3600. + minutes * 60. + seconds + fraction / 1000.)
+}
+
+/// <https://w3c.github.io/webvtt/#parse-the-webvtt-cue-settings>
+fn parse_settings(input: &str) -> CueSettings {
+    let mut settings = CueSettings::default();
+    for setting in input.split(is_whitespace).filter(|s| !s.is_empty()) {
+        let colon = match setting.find(':') {
+            Some(0) | None => continue,
+            Some(colon) => colon,
+        };
+        let (name, value) = (&setting[..colon], &setting[colon + 1..]);
+        if value.is_empty() {
+            continue;
+        }
+        match name {
+            "vertical" => match value {
+                "rl" => settings.writing_direction = WritingDirection::VerticalGrowingLeft,
+                "lr" => settings.writing_direction = WritingDirection::VerticalGrowingRight,
+                _ => {},
+            },
+            "line" => {
+                let mut parts = value.splitn(2, ',');
+                let line = parts.next().unwrap();
+                let alignment = match parts.next() {
+                    None => LineAlignment::Start,
+                    Some("start") => LineAlignment::Start,
+                    Some("center") => LineAlignment::Center,
+                    Some("end") => LineAlignment::End,
+                    Some(_) => continue,
+                };
+                let (line, snap_to_lines) = if line.ends_with('%') {
+                    match parse_percentage(line) {
+                        Some(line) => (line, false),
+                        None => continue,
+                    }
+                } else {
+                    match parse_line_number(line) {
+                        Some(line) => (line, true),
+                        None => continue,
+                    }
+                };
+                settings.line = Some(line);
+                settings.snap_to_lines = snap_to_lines;
+                settings.line_alignment = alignment;
+            },
+            "position" => {
+                let mut parts = value.splitn(2, ',');
+                let position = match parse_percentage(parts.next().unwrap()) {
+                    Some(position) => position,
+                    None => continue,
+                };
+                let alignment = match parts.next() {
+                    None => PositionAlignment::Auto,
+                    Some("line-left") => PositionAlignment::LineLeft,
+                    Some("center") => PositionAlignment::Center,
+                    Some("line-right") => PositionAlignment::LineRight,
+                    Some(_) => continue,
+                };
+                settings.position = Some(position);
+                settings.position_alignment = alignment;
+            },
+            "size" => {
+                if let Some(size) = parse_percentage(value) {
+                    settings.size = size;
+                }
+            },
+            "align" => match value {
+                "start" => settings.text_alignment = TextAlignment::Start,
+                "center" => settings.text_alignment = TextAlignment::Center,
+                "end" => settings.text_alignment = TextAlignment::End,
+                "left" => settings.text_alignment = TextAlignment::Left,
+                "right" => settings.text_alignment = TextAlignment::Right,
+                _ => {},
+            },
+            // Regions are not supported.
+            _ => {},
+        }
+    }
+    settings
+}
+
+/// <https://w3c.github.io/webvtt/#parse-a-percentage-string>
+fn parse_percentage(input: &str) -> Option<f64> {
+    if !input.ends_with('%') {
+        return None;
+    }
+    let number = &input[..input.len() - 1];
+    if !is_decimal(number) {
+        return None;
+    }
+    let value: f64 = number.parse().ok()?;
+    if value < 0. || value > 100. {
+        return None;
+    }
+    Some(value)
+}
+
+/// Parses the line number of a `line` setting, which may be negative.
+fn parse_line_number(input: &str) -> Option<f64> {
+    let number = if input.starts_with('-') {
+        &input[1..]
+    } else {
+        input
+    };
+    if !is_decimal(number) {
+        return None;
+    }
+    input.parse().ok()
+}
+
+/// Whether `input` is one or more digits, optionally followed by a full
+/// stop and one or more digits.
+fn is_decimal(input: &str) -> bool {
+    let mut parts = input.splitn(2, '.');
+    let all_digits = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+    all_digits(parts.next().unwrap()) && parts.next().map_or(true, all_digits)
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\n' || c == '\x0c'
+}
+
+/// The kind of an internal node of the cue text.
+///
+/// <https://w3c.github.io/webvtt/#webvtt-internal-node-object>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CueElementKind {
+    Class,
+    Italic,
+    Bold,
+    Underline,
+    Ruby,
+    RubyText,
+    Voice,
+    Language,
+}
+
+impl CueElementKind {
+    fn from_tag_name(name: &str) -> Option<CueElementKind> {
+        match name {
+            "c" => Some(CueElementKind::Class),
+            "i" => Some(CueElementKind::Italic),
+            "b" => Some(CueElementKind::Bold),
+            "u" => Some(CueElementKind::Underline),
+            "ruby" => Some(CueElementKind::Ruby),
+            "rt" => Some(CueElementKind::RubyText),
+            "v" => Some(CueElementKind::Voice),
+            "lang" => Some(CueElementKind::Language),
+            _ => None,
+        }
+    }
+
+    fn tag_name(&self) -> &'static str {
+        match *self {
+            CueElementKind::Class => "c",
+            CueElementKind::Italic => "i",
+            CueElementKind::Bold => "b",
+            CueElementKind::Underline => "u",
+            CueElementKind::Ruby => "ruby",
+            CueElementKind::RubyText => "rt",
+            CueElementKind::Voice => "v",
+            CueElementKind::Language => "lang",
+        }
+    }
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-node-object>
+#[derive(Clone, Debug, PartialEq)]
+pub enum CueNode {
+    Text(String),
+    Timestamp(f64),
+    Element {
+        kind: CueElementKind,
+        classes: Vec<String>,
+        /// The voice name of `v` elements and the language of `lang`
+        /// elements.
+        annotation: Option<String>,
+        children: Vec<CueNode>,
+    },
+}
+
+enum Token {
+    Text(String),
+    StartTag {
+        name: String,
+        classes: Vec<String>,
+        annotation: String,
+    },
+    EndTag(String),
+    Timestamp(String),
+}
+
+/// <https://w3c.github.io/webvtt/#cue-text-parsing-rules>
+pub fn parse_cue_text(input: &str) -> Vec<CueNode> {
+    // The open elements, with the nodes of the root at the bottom.
+    let mut stack: Vec<(Option<CueNode>, Vec<CueNode>)> = vec![(None, vec![])];
+    let mut chars = input.chars().peekable();
+
+    fn close(stack: &mut Vec<(Option<CueNode>, Vec<CueNode>)>) {
+        let (element, nodes) = stack.pop().unwrap();
+        let mut element = element.unwrap();
+        if let CueNode::Element {
+            ref mut children, ..
+        } = element
+        {
+            *children = nodes;
+        }
+        stack.last_mut().unwrap().1.push(element);
+    }
+
+    fn current_kind(stack: &[(Option<CueNode>, Vec<CueNode>)]) -> Option<CueElementKind> {
+        match stack.last().unwrap().0 {
+            Some(CueNode::Element { kind, .. }) => Some(kind),
+            _ => None,
+        }
+    }
+
+    while let Some(token) = next_token(&mut chars) {
+        match token {
+            Token::Text(text) => stack.last_mut().unwrap().1.push(CueNode::Text(text)),
+            Token::StartTag {
+                name,
+                classes,
+                annotation,
+            } => {
+                let kind = match CueElementKind::from_tag_name(&name) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                if kind == CueElementKind::RubyText &&
+                    current_kind(&stack) != Some(CueElementKind::Ruby)
+                {
+                    continue;
+                }
+                let annotation = match kind {
+                    CueElementKind::Voice | CueElementKind::Language => Some(annotation),
+                    _ => None,
+                };
+                stack.push((
+                    Some(CueNode::Element {
+                        kind,
+                        classes,
+                        annotation,
+                        children: vec![],
+                    }),
+                    vec![],
+                ));
+            },
+            Token::EndTag(name) => {
+                let current = match current_kind(&stack) {
+                    Some(current) => current,
+                    None => continue,
+                };
+                if current.tag_name() == name {
+                    close(&mut stack);
+                } else if name == "ruby" && current == CueElementKind::RubyText {
+                    close(&mut stack);
+                    close(&mut stack);
+                }
+            },
+            Token::Timestamp(timestamp) => {
+                if let Some(time) = parse_timestamp(&timestamp) {
+                    stack.last_mut().unwrap().1.push(CueNode::Timestamp(time));
+                }
+            },
+        }
+    }
+
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().unwrap().1
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-text-tokenizer>
+fn next_token<I: Iterator<Item = char>>(chars: &mut Peekable<I>) -> Option<Token> {
+    match *chars.peek()? {
+        '<' => {
+            chars.next();
+            let mut tag = String::new();
+            while let Some(c) = chars.next() {
+                if c == '>' {
+                    break;
+                }
+                tag.push(c);
+            }
+            if tag.starts_with('/') {
+                let name = tag[1..].split(is_whitespace).next().unwrap_or("");
+                return Some(Token::EndTag(name.split('.').next().unwrap().to_owned()));
+            }
+            if tag.starts_with(|c: char| c.is_ascii_digit()) {
+                return Some(Token::Timestamp(tag));
+            }
+            let (tag, annotation) = match tag.find(is_whitespace) {
+                Some(index) => (&tag[..index], &tag[index..]),
+                None => (&tag[..], ""),
+            };
+            let mut parts = tag.split('.');
+            let name = parts.next().unwrap().to_owned();
+            let classes = parts
+                .filter(|class| !class.is_empty())
+                .map(|class| class.to_owned())
+                .collect();
+            let annotation = annotation
+                .split(is_whitespace)
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(Token::StartTag {
+                name,
+                classes,
+                annotation: decode_character_references(&annotation),
+            })
+        },
+        _ => {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '<' {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            Some(Token::Text(decode_character_references(&text)))
+        },
+    }
+}
+
+/// Decodes the character references that may appear in cue text.
+fn decode_character_references(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(ampersand) = rest.find('&') {
+        output.push_str(&rest[..ampersand]);
+        rest = &rest[ampersand..];
+        let reference = rest.find(';').and_then(|semicolon| {
+            let name = &rest[1..semicolon];
+            let decoded = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                "lrm" => Some('\u{200e}'),
+                "rlm" => Some('\u{200f}'),
+                _ if name.starts_with("#x") || name.starts_with("#X") => {
+                    u32::from_str_radix(&name[2..], 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                },
+                _ if name.starts_with('#') => name[1..].parse().ok().and_then(std::char::from_u32),
+                _ => None,
+            };
+            decoded.map(|c| (c, semicolon))
+        });
+        match reference {
+            Some((c, semicolon)) => {
+                output.push(c);
+                rest = &rest[semicolon + 1..];
+            },
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    output.push_str(rest);
+    output
+}
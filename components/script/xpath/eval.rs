@@ -0,0 +1,833 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The evaluation of parsed XPath 1.0 expressions over the DOM.
+
+use super::parser::{Axis, BinaryOperator, Expr, NodeTest, PathStart, Step};
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::comment::Comment;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::processinginstruction::ProcessingInstruction;
+use crate::dom::text::Text;
+use html5ever::Namespace;
+use std::cmp::Ordering;
+
+/// A node of the XPath data model. Attributes are not nodes in the DOM, so
+/// they are kept apart.
+#[derive(Clone)]
+pub enum XPathNode {
+    Node(DomRoot<Node>),
+    Attribute(DomRoot<Attr>),
+}
+
+impl PartialEq for XPathNode {
+    fn eq(&self, other: &XPathNode) -> bool {
+        match (self, other) {
+            (XPathNode::Node(a), XPathNode::Node(b)) => a == b,
+            (XPathNode::Attribute(a), XPathNode::Attribute(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl XPathNode {
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-string-value>
+    pub fn string_value(&self) -> String {
+        match *self {
+            XPathNode::Attribute(ref attr) => String::from(&**attr.value()),
+            XPathNode::Node(ref node) => {
+                if let Some(data) = node.downcast::<CharacterData>() {
+                    return String::from(data.data().clone());
+                }
+                node.traverse_preorder(ShadowIncluding::No)
+                    .filter_map(DomRoot::downcast::<Text>)
+                    .map(|text| String::from(text.upcast::<CharacterData>().data().clone()))
+                    .collect()
+            },
+        }
+    }
+
+    /// The node that this node belongs to in the DOM tree, which is the
+    /// owner element of an attribute.
+    fn tree_node(&self) -> Option<DomRoot<Node>> {
+        match *self {
+            XPathNode::Node(ref node) => Some(node.clone()),
+            XPathNode::Attribute(ref attr) => attr.owner().map(DomRoot::upcast),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-document-order>
+    fn compare_document_order(&self, other: &XPathNode) -> Ordering {
+        let (a, b) = match (self.tree_node(), other.tree_node()) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Ordering::Equal,
+        };
+        if a != b {
+            return if a.is_before(&b) {
+                Ordering::Less
+            } else if b.is_before(&a) {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            };
+        }
+        // Attributes follow their element, in the order they were set.
+        self.attribute_index(&a).cmp(&other.attribute_index(&a))
+    }
+
+    fn attribute_index(&self, owner: &Node) -> Option<usize> {
+        match *self {
+            XPathNode::Node(_) => None,
+            XPathNode::Attribute(ref attr) => owner
+                .downcast::<Element>()
+                .and_then(|element| element.attrs().iter().position(|a| **a == **attr)),
+        }
+    }
+}
+
+/// The result of evaluating an expression.
+///
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#section-Expressions>
+pub enum Value {
+    /// A node-set, in document order and without duplicates.
+    NodeSet(Vec<XPathNode>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-boolean>
+    pub fn boolean(&self) -> bool {
+        match *self {
+            Value::NodeSet(ref nodes) => !nodes.is_empty(),
+            Value::Boolean(boolean) => boolean,
+            Value::Number(number) => number != 0. && !number.is_nan(),
+            Value::String(ref string) => !string.is_empty(),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-number>
+    pub fn number(&self) -> f64 {
+        match *self {
+            Value::NodeSet(_) => string_to_number(&self.string()),
+            Value::Boolean(boolean) => {
+                if boolean {
+                    1.
+                } else {
+                    0.
+                }
+            },
+            Value::Number(number) => number,
+            Value::String(ref string) => string_to_number(string),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-string>
+    pub fn string(&self) -> String {
+        match *self {
+            Value::NodeSet(ref nodes) => nodes
+                .first()
+                .map_or_else(String::new, XPathNode::string_value),
+            Value::Boolean(boolean) => boolean.to_string(),
+            Value::Number(number) => number_to_string(number),
+            Value::String(ref string) => string.clone(),
+        }
+    }
+
+    fn into_node_set(self) -> Fallible<Vec<XPathNode>> {
+        match self {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => Err(Error::Type("The expression is not a node-set".to_owned())),
+        }
+    }
+}
+
+/// Converts a string to a number, as the `number()` function does.
+pub fn string_to_number(string: &str) -> f64 {
+    let string = string.trim_matches(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r');
+    let digits = if string.starts_with('-') {
+        &string[1..]
+    } else {
+        string
+    };
+    let valid = !digits.is_empty() &&
+        digits != "." &&
+        digits.chars().all(|c| c.is_ascii_digit() || c == '.') &&
+        digits.chars().filter(|&c| c == '.').count() <= 1;
+    if valid {
+        string.parse().unwrap_or(::std::f64::NAN)
+    } else {
+        ::std::f64::NAN
+    }
+}
+
+/// Converts a number to a string, as the `string()` function does.
+pub fn number_to_string(number: f64) -> String {
+    if number.is_nan() {
+        "NaN".to_owned()
+    } else if number == 0. {
+        "0".to_owned()
+    } else if number.is_infinite() {
+        if number > 0. {
+            "Infinity".to_owned()
+        } else {
+            "-Infinity".to_owned()
+        }
+    } else {
+        number.to_string()
+    }
+}
+
+/// The state needed to evaluate an expression, beyond its context.
+pub struct Evaluator<'a> {
+    /// Whether the nodes are evaluated in an HTML document, where unprefixed
+    /// names match elements of the HTML namespace.
+    pub is_html_document: bool,
+    /// Returns the namespace URI bound to a prefix, if any.
+    pub resolve_prefix: &'a dyn Fn(&str) -> Fallible<Option<String>>,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-context-node>
+struct Context {
+    node: XPathNode,
+    position: usize,
+    size: usize,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Evaluates `expr` with `node` as the context node.
+    pub fn evaluate(&self, expr: &Expr, node: XPathNode) -> Fallible<Value> {
+        self.evaluate_in(
+            expr,
+            &Context {
+                node,
+                position: 1,
+                size: 1,
+            },
+        )
+    }
+
+    fn evaluate_in(&self, expr: &Expr, context: &Context) -> Fallible<Value> {
+        match *expr {
+            Expr::Binary(ref left, operator, ref right) => {
+                self.evaluate_binary(left, operator, right, context)
+            },
+            Expr::Negate(ref expr) => Ok(Value::Number(-self.evaluate_in(expr, context)?.number())),
+            Expr::Path(ref start, ref steps) => {
+                let mut nodes = match *start {
+                    PathStart::Root => vec![root(&context.node)],
+                    PathStart::ContextNode => vec![context.node.clone()],
+                    PathStart::Filter(ref filter) => {
+                        self.evaluate_in(filter, context)?.into_node_set()?
+                    },
+                };
+                for step in steps {
+                    nodes = self.evaluate_step(step, nodes)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            },
+            Expr::Filter(ref primary, ref predicates) => {
+                let mut nodes = self.evaluate_in(primary, context)?.into_node_set()?;
+                for predicate in predicates {
+                    nodes = self.filter(nodes, predicate)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            },
+            Expr::Literal(ref literal) => Ok(Value::String(literal.clone())),
+            Expr::Number(number) => Ok(Value::Number(number)),
+            Expr::Function(ref name, ref arguments) => self.call_function(name, arguments, context),
+        }
+    }
+
+    fn evaluate_binary(
+        &self,
+        left: &Expr,
+        operator: BinaryOperator,
+        right: &Expr,
+        context: &Context,
+    ) -> Fallible<Value> {
+        let left = self.evaluate_in(left, context)?;
+        let boolean = match operator {
+            BinaryOperator::Or => left.boolean() || self.evaluate_in(right, context)?.boolean(),
+            BinaryOperator::And => left.boolean() && self.evaluate_in(right, context)?.boolean(),
+            BinaryOperator::Equal |
+            BinaryOperator::NotEqual |
+            BinaryOperator::Less |
+            BinaryOperator::LessOrEqual |
+            BinaryOperator::Greater |
+            BinaryOperator::GreaterOrEqual => {
+                let right = self.evaluate_in(right, context)?;
+                compare(operator, &left, &right)
+            },
+            BinaryOperator::Union => {
+                let mut nodes = left.into_node_set()?;
+                nodes.extend(self.evaluate_in(right, context)?.into_node_set()?);
+                sort_in_document_order(&mut nodes);
+                return Ok(Value::NodeSet(nodes));
+            },
+            BinaryOperator::Add |
+            BinaryOperator::Subtract |
+            BinaryOperator::Multiply |
+            BinaryOperator::Divide |
+            BinaryOperator::Modulo => {
+                let left = left.number();
+                let right = self.evaluate_in(right, context)?.number();
+                return Ok(Value::Number(match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide => left / right,
+                    _ => left % right,
+                }));
+            },
+        };
+        Ok(Value::Boolean(boolean))
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#section-Location-Steps>
+    fn evaluate_step(&self, step: &Step, nodes: Vec<XPathNode>) -> Fallible<Vec<XPathNode>> {
+        let needs_sorting = nodes.len() > 1 || step.axis.is_reverse();
+        let mut result = vec![];
+        for node in nodes {
+            let mut selected = vec![];
+            for candidate in axis_nodes(step.axis, &node) {
+                if self.matches(&step.node_test, step.axis, &candidate)? {
+                    selected.push(candidate);
+                }
+            }
+            // Predicates filter the nodes in the order of the axis.
+            for predicate in &step.predicates {
+                selected = self.filter(selected, predicate)?;
+            }
+            result.extend(selected);
+        }
+        if needs_sorting {
+            sort_in_document_order(&mut result);
+        }
+        Ok(result)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#predicates>
+    fn filter(&self, nodes: Vec<XPathNode>, predicate: &Expr) -> Fallible<Vec<XPathNode>> {
+        let size = nodes.len();
+        let mut result = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+            };
+            let keep = match self.evaluate_in(predicate, &context)? {
+                Value::Number(number) => number == context.position as f64,
+                value => value.boolean(),
+            };
+            if keep {
+                result.push(context.node);
+            }
+        }
+        Ok(result)
+    }
+
+    fn resolve(&self, prefix: &str) -> Fallible<Namespace> {
+        // The `xml` prefix is bound by definition, and doesn't need to be
+        // declared.
+        if prefix == "xml" {
+            return Ok(ns!(xml));
+        }
+        match (self.resolve_prefix)(prefix)? {
+            Some(namespace) => Ok(Namespace::from(namespace)),
+            None => Err(Error::Namespace),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#node-tests>
+    fn matches(&self, node_test: &NodeTest, axis: Axis, node: &XPathNode) -> Fallible<bool> {
+        let node = match *node {
+            XPathNode::Attribute(ref attr) => {
+                // Attributes are only matched by name on the attribute axis.
+                return Ok(match *node_test {
+                    NodeTest::Node => true,
+                    NodeTest::Any => axis == Axis::Attribute,
+                    NodeTest::Namespace(ref prefix) => {
+                        axis == Axis::Attribute && *attr.namespace() == self.resolve(prefix)?
+                    },
+                    NodeTest::Name(ref prefix, ref local_name) => {
+                        let namespace = match *prefix {
+                            Some(ref prefix) => self.resolve(prefix)?,
+                            None => ns!(),
+                        };
+                        axis == Axis::Attribute &&
+                            *attr.namespace() == namespace &&
+                            &**attr.local_name() == &**local_name
+                    },
+                    _ => false,
+                });
+            },
+            XPathNode::Node(ref node) => node,
+        };
+
+        Ok(match *node_test {
+            NodeTest::Node => true,
+            NodeTest::Text => node.is::<Text>(),
+            NodeTest::Comment => node.is::<Comment>(),
+            NodeTest::ProcessingInstruction(ref target) => node
+                .downcast::<ProcessingInstruction>()
+                .map_or(false, |pi| {
+                    target
+                        .as_ref()
+                        .map_or(true, |target| &**pi.target() == &**target)
+                }),
+            // The principal node type of every axis but the attribute
+            // axis is the element.
+            NodeTest::Any => axis != Axis::Attribute && node.is::<Element>(),
+            NodeTest::Namespace(ref prefix) => match node.downcast::<Element>() {
+                Some(element) if axis != Axis::Attribute => {
+                    *element.namespace() == self.resolve(prefix)?
+                },
+                _ => false,
+            },
+            NodeTest::Name(ref prefix, ref local_name) => match node.downcast::<Element>() {
+                Some(element) if axis != Axis::Attribute => match *prefix {
+                    Some(ref prefix) => {
+                        *element.namespace() == self.resolve(prefix)? &&
+                            &**element.local_name() == &**local_name
+                    },
+                    // https://html.spec.whatwg.org/multipage/#interactions-with-xpath-and-xslt
+                    None if self.is_html_document && *element.namespace() == ns!(html) => {
+                        element.local_name().eq_ignore_ascii_case(local_name)
+                    },
+                    None => {
+                        *element.namespace() == ns!() && &**element.local_name() == &**local_name
+                    },
+                },
+                _ => false,
+            },
+        })
+    }
+
+    fn argument(&self, arguments: &[Expr], index: usize, context: &Context) -> Fallible<Value> {
+        self.evaluate_in(&arguments[index], context)
+    }
+
+    /// The string value of the argument at `index`, or of the context node if
+    /// the argument was omitted.
+    fn string_argument(
+        &self,
+        arguments: &[Expr],
+        index: usize,
+        context: &Context,
+    ) -> Fallible<String> {
+        if arguments.len() > index {
+            Ok(self.argument(arguments, index, context)?.string())
+        } else {
+            Ok(context.node.string_value())
+        }
+    }
+
+    /// The first node of the node-set argument at `index`, or the context node
+    /// if the argument was omitted.
+    fn node_argument(
+        &self,
+        arguments: &[Expr],
+        index: usize,
+        context: &Context,
+    ) -> Fallible<Option<XPathNode>> {
+        if arguments.len() > index {
+            Ok(self
+                .argument(arguments, index, context)?
+                .into_node_set()?
+                .into_iter()
+                .next())
+        } else {
+            Ok(Some(context.node.clone()))
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#corelib>
+    fn call_function(&self, name: &str, arguments: &[Expr], context: &Context) -> Fallible<Value> {
+        let (min, max) = match name {
+            "last" | "position" | "true" | "false" => (0, 0),
+            "local-name" | "namespace-uri" | "name" | "string" | "string-length" |
+            "normalize-space" | "number" => (0, 1),
+            "count" | "id" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round" => {
+                (1, 1)
+            },
+            "starts-with" | "contains" | "substring-before" | "substring-after" => (2, 2),
+            "substring" => (2, 3),
+            "translate" => (3, 3),
+            "concat" => (2, usize::max_value()),
+            _ => return Err(Error::Type(format!("Unknown XPath function {}()", name))),
+        };
+        if arguments.len() < min || arguments.len() > max {
+            return Err(Error::Type(format!(
+                "Wrong number of arguments for XPath function {}()",
+                name
+            )));
+        }
+
+        let string = |index| self.argument(arguments, index, context).map(|v| v.string());
+        let number = |index| self.argument(arguments, index, context).map(|v| v.number());
+
+        Ok(match name {
+            // Node-set functions.
+            "last" => Value::Number(context.size as f64),
+            "position" => Value::Number(context.position as f64),
+            "count" => {
+                Value::Number(self.argument(arguments, 0, context)?.into_node_set()?.len() as f64)
+            },
+            "id" => Value::NodeSet(self.id(self.argument(arguments, 0, context)?, context)),
+            "local-name" | "namespace-uri" | "name" => {
+                let node = self.node_argument(arguments, 0, context)?;
+                Value::String(node.map_or_else(String::new, |node| match name {
+                    "local-name" => local_name(&node),
+                    "namespace-uri" => namespace_uri(&node),
+                    _ => qualified_name(&node),
+                }))
+            },
+
+            // String functions.
+            "string" => Value::String(self.string_argument(arguments, 0, context)?),
+            "concat" => Value::String(
+                (0..arguments.len())
+                    .map(string)
+                    .collect::<Fallible<String>>()?,
+            ),
+            "starts-with" => Value::Boolean(string(0)?.starts_with(&*string(1)?)),
+            "contains" => Value::Boolean(string(0)?.contains(&*string(1)?)),
+            "substring-before" => {
+                let (string, pattern) = (string(0)?, string(1)?);
+                Value::String(
+                    string
+                        .find(&*pattern)
+                        .map_or_else(String::new, |index| string[..index].to_owned()),
+                )
+            },
+            "substring-after" => {
+                let (string, pattern) = (string(0)?, string(1)?);
+                Value::String(string.find(&*pattern).map_or_else(String::new, |index| {
+                    string[index + pattern.len()..].to_owned()
+                }))
+            },
+            "substring" => {
+                let string = string(0)?;
+                let start = round(number(1)?);
+                let end = if arguments.len() > 2 {
+                    start + round(number(2)?)
+                } else {
+                    ::std::f64::INFINITY
+                };
+                Value::String(
+                    string
+                        .chars()
+                        .enumerate()
+                        .filter(|&(index, _)| {
+                            let position = (index + 1) as f64;
+                            position >= start && position < end
+                        })
+                        .map(|(_, c)| c)
+                        .collect(),
+                )
+            },
+            "string-length" => {
+                Value::Number(self.string_argument(arguments, 0, context)?.chars().count() as f64)
+            },
+            "normalize-space" => Value::String(
+                self.string_argument(arguments, 0, context)?
+                    .split(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r')
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            "translate" => {
+                let from: Vec<char> = string(1)?.chars().collect();
+                let to: Vec<char> = string(2)?.chars().collect();
+                Value::String(
+                    string(0)?
+                        .chars()
+                        .filter_map(|c| match from.iter().position(|&f| f == c) {
+                            Some(index) => to.get(index).cloned(),
+                            None => Some(c),
+                        })
+                        .collect(),
+                )
+            },
+
+            // Boolean functions.
+            "boolean" => Value::Boolean(self.argument(arguments, 0, context)?.boolean()),
+            "not" => Value::Boolean(!self.argument(arguments, 0, context)?.boolean()),
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            "lang" => Value::Boolean(lang(&context.node, &string(0)?)),
+
+            // Number functions.
+            "number" => Value::Number(if arguments.is_empty() {
+                string_to_number(&context.node.string_value())
+            } else {
+                number(0)?
+            }),
+            "sum" => Value::Number(
+                self.argument(arguments, 0, context)?
+                    .into_node_set()?
+                    .iter()
+                    .map(|node| string_to_number(&node.string_value()))
+                    .sum(),
+            ),
+            "floor" => Value::Number(number(0)?.floor()),
+            "ceiling" => Value::Number(number(0)?.ceil()),
+            _ => Value::Number(round(number(0)?)),
+        })
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-id>
+    fn id(&self, value: Value, context: &Context) -> Vec<XPathNode> {
+        let ids: Vec<String> = match value {
+            Value::NodeSet(nodes) => nodes.iter().map(XPathNode::string_value).collect(),
+            value => vec![value.string()],
+        };
+        let document = match context.node.tree_node() {
+            Some(node) => node.owner_doc(),
+            None => return vec![],
+        };
+        let mut nodes: Vec<XPathNode> = ids
+            .iter()
+            .flat_map(|ids| ids.split_whitespace())
+            .filter_map(|id| document.GetElementById(id.into()))
+            .map(|element| XPathNode::Node(DomRoot::upcast(element)))
+            .collect();
+        sort_in_document_order(&mut nodes);
+        nodes
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-round>
+pub fn round(number: f64) -> f64 {
+    if number.is_nan() || number.is_infinite() {
+        number
+    } else if number < 0. && number >= -0.5 {
+        -0.
+    } else {
+        (number + 0.5).floor()
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-lang>
+fn lang(node: &XPathNode, language: &str) -> bool {
+    let node = match node.tree_node() {
+        Some(node) => node,
+        None => return false,
+    };
+    let attribute = node
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .filter_map(|element| element.get_attribute(&ns!(xml), &local_name!("lang")))
+        .next();
+    let value = match attribute {
+        Some(attribute) => String::from(&**attribute.value()).to_lowercase(),
+        None => return false,
+    };
+    let language = language.to_lowercase();
+    value == language || (value.starts_with(&*language) && value[language.len()..].starts_with('-'))
+}
+
+fn local_name(node: &XPathNode) -> String {
+    match *node {
+        XPathNode::Attribute(ref attr) => attr.local_name().to_string(),
+        XPathNode::Node(ref node) => {
+            if let Some(element) = node.downcast::<Element>() {
+                element.local_name().to_string()
+            } else if let Some(pi) = node.downcast::<ProcessingInstruction>() {
+                String::from(pi.target().clone())
+            } else {
+                String::new()
+            }
+        },
+    }
+}
+
+fn namespace_uri(node: &XPathNode) -> String {
+    match *node {
+        XPathNode::Attribute(ref attr) => attr.namespace().to_string(),
+        XPathNode::Node(ref node) => node
+            .downcast::<Element>()
+            .map_or_else(String::new, |element| element.namespace().to_string()),
+    }
+}
+
+fn qualified_name(node: &XPathNode) -> String {
+    let (prefix, local_name) = match *node {
+        XPathNode::Attribute(ref attr) => (attr.prefix().cloned(), local_name(node)),
+        XPathNode::Node(ref element) => match element.downcast::<Element>() {
+            Some(element) => (element.prefix().clone(), local_name(node)),
+            None => (None, local_name(node)),
+        },
+    };
+    match prefix {
+        Some(prefix) => format!("{}:{}", prefix, local_name),
+        None => local_name,
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#booleans>
+pub fn compare(operator: BinaryOperator, left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (&Value::NodeSet(ref left), &Value::NodeSet(ref right)) => {
+            let right: Vec<_> = right.iter().map(XPathNode::string_value).collect();
+            left.iter().any(|left| {
+                let left = Value::String(left.string_value());
+                right
+                    .iter()
+                    .any(|right| compare_values(operator, &left, &Value::String(right.clone())))
+            })
+        },
+        (&Value::NodeSet(_), &Value::Boolean(_)) | (&Value::Boolean(_), &Value::NodeSet(_)) => {
+            compare_values(
+                operator,
+                &Value::Boolean(left.boolean()),
+                &Value::Boolean(right.boolean()),
+            )
+        },
+        (&Value::NodeSet(ref left), right) => left
+            .iter()
+            .any(|left| compare_values(operator, &Value::String(left.string_value()), right)),
+        (left, &Value::NodeSet(ref right)) => right
+            .iter()
+            .any(|right| compare_values(operator, left, &Value::String(right.string_value()))),
+        (left, right) => compare_values(operator, left, right),
+    }
+}
+
+/// Compares two values that are not node-sets.
+fn compare_values(operator: BinaryOperator, left: &Value, right: &Value) -> bool {
+    match operator {
+        BinaryOperator::Equal | BinaryOperator::NotEqual => {
+            let equal = match (left, right) {
+                (&Value::Boolean(_), _) | (_, &Value::Boolean(_)) => {
+                    left.boolean() == right.boolean()
+                },
+                (&Value::Number(_), _) | (_, &Value::Number(_)) => left.number() == right.number(),
+                _ => left.string() == right.string(),
+            };
+            equal == (operator == BinaryOperator::Equal)
+        },
+        BinaryOperator::Less => left.number() < right.number(),
+        BinaryOperator::LessOrEqual => left.number() <= right.number(),
+        BinaryOperator::Greater => left.number() > right.number(),
+        _ => left.number() >= right.number(),
+    }
+}
+
+fn sort_in_document_order(nodes: &mut Vec<XPathNode>) {
+    nodes.sort_by(XPathNode::compare_document_order);
+    nodes.dedup();
+}
+
+/// The root of the tree containing `node`.
+fn root(node: &XPathNode) -> XPathNode {
+    match node.tree_node() {
+        Some(node) => XPathNode::Node(
+            node.inclusive_ancestors(ShadowIncluding::No)
+                .last()
+                .unwrap(),
+        ),
+        None => node.clone(),
+    }
+}
+
+/// The nodes of `axis` from `node`, in the order of the axis.
+///
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#axes>
+fn axis_nodes(axis: Axis, node: &XPathNode) -> Vec<XPathNode> {
+    let node = match *node {
+        XPathNode::Attribute(ref attr) => {
+            let owner = attr.owner().map(DomRoot::upcast::<Node>);
+            return match axis {
+                Axis::SelfAxis | Axis::DescendantOrSelf => vec![node.clone()],
+                Axis::AncestorOrSelf => {
+                    let mut nodes = vec![node.clone()];
+                    if let Some(owner) = owner {
+                        nodes.extend(axis_nodes(Axis::AncestorOrSelf, &XPathNode::Node(owner)));
+                    }
+                    nodes
+                },
+                Axis::Parent | Axis::Ancestor | Axis::Preceding => {
+                    let owner = match owner {
+                        Some(owner) => XPathNode::Node(owner),
+                        None => return vec![],
+                    };
+                    match axis {
+                        Axis::Parent => vec![owner],
+                        Axis::Ancestor => axis_nodes(Axis::AncestorOrSelf, &owner),
+                        _ => axis_nodes(Axis::Preceding, &owner),
+                    }
+                },
+                Axis::Following => match owner {
+                    Some(owner) => {
+                        let owner = XPathNode::Node(owner);
+                        let mut nodes = axis_nodes(Axis::Descendant, &owner);
+                        nodes.extend(axis_nodes(Axis::Following, &owner));
+                        nodes
+                    },
+                    None => vec![],
+                },
+                _ => vec![],
+            };
+        },
+        XPathNode::Node(ref node) => node,
+    };
+
+    let nodes: Vec<DomRoot<Node>> = match axis {
+        Axis::Ancestor => node.ancestors().collect(),
+        Axis::AncestorOrSelf => node.inclusive_ancestors(ShadowIncluding::No).collect(),
+        Axis::Attribute => {
+            return node.downcast::<Element>().map_or(vec![], |element| {
+                element
+                    .attrs()
+                    .iter()
+                    // Namespace declarations are not attributes in XPath.
+                    .filter(|attr| *attr.namespace() != ns!(xmlns))
+                    .map(|attr| XPathNode::Attribute(DomRoot::from_ref(&**attr)))
+                    .collect()
+            });
+        },
+        Axis::Child => node.children().collect(),
+        Axis::Descendant => node
+            .traverse_preorder(ShadowIncluding::No)
+            .skip(1)
+            .collect(),
+        Axis::DescendantOrSelf => node.traverse_preorder(ShadowIncluding::No).collect(),
+        Axis::Following => node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .flat_map(|ancestor| ancestor.following_siblings())
+            .flat_map(|sibling| {
+                sibling
+                    .traverse_preorder(ShadowIncluding::No)
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        Axis::FollowingSibling => node.following_siblings().collect(),
+        // Namespace nodes are not supported.
+        Axis::Namespace => vec![],
+        Axis::Parent => node.GetParentNode().into_iter().collect(),
+        Axis::Preceding => node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .flat_map(|ancestor| ancestor.preceding_siblings())
+            .flat_map(|sibling| {
+                let mut nodes: Vec<_> = sibling.traverse_preorder(ShadowIncluding::No).collect();
+                nodes.reverse();
+                nodes
+            })
+            .collect(),
+        Axis::PrecedingSibling => node.preceding_siblings().collect(),
+        Axis::SelfAxis => vec![node.clone()],
+    };
+    nodes.into_iter().map(XPathNode::Node).collect()
+}
@@ -0,0 +1,18 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An implementation of [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/),
+//! which backs `document.evaluate()` and the `XPathEvaluator` interface.
+//!
+//! Expressions are parsed once into an [`Expr`](parser/enum.Expr.html), and
+//! evaluated over the DOM as many times as needed. Namespace nodes are not
+//! supported, and attributes, which aren't DOM nodes, can be selected and
+//! compared but are left out of the node-sets returned to script.
+
+mod eval;
+mod parser;
+
+pub use self::eval::{compare, number_to_string, round, string_to_number};
+pub use self::eval::{Evaluator, Value, XPathNode};
+pub use self::parser::{parse, Axis, BinaryOperator, Expr, NodeTest, ParseError, PathStart, Step};
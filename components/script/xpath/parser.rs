@@ -0,0 +1,704 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The tokenizer and parser of XPath 1.0 expressions.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#section-Expressions>
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum Expr {
+    Binary(Box<Expr>, BinaryOperator, Box<Expr>),
+    Negate(Box<Expr>),
+    /// A location path, optionally starting from the result of a filter
+    /// expression instead of the context node or the root.
+    Path(PathStart, Vec<Step>),
+    /// A primary expression followed by predicates.
+    Filter(Box<Expr>, Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Function(String, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Union,
+}
+
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum PathStart {
+    /// An absolute location path.
+    Root,
+    /// A relative location path.
+    ContextNode,
+    /// A path following a filter expression, such as `id("foo")/bar`.
+    Filter(Box<Expr>),
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-Step>
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct Step {
+    pub axis: Axis,
+    pub node_test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#axes>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfAxis,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Axis> {
+        Some(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfAxis,
+            _ => return None,
+        })
+    }
+
+    /// Whether the nodes of the axis are in reverse document order.
+    pub fn is_reverse(&self) -> bool {
+        match *self {
+            Axis::Ancestor | Axis::AncestorOrSelf | Axis::Preceding | Axis::PrecedingSibling => {
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#node-tests>
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum NodeTest {
+    /// `*`, any node of the principal node type of the axis.
+    Any,
+    /// `prefix:*`, any node of the principal node type in a namespace.
+    Namespace(String),
+    /// A qualified name.
+    Name(Option<String>, String),
+    /// `node()`
+    Node,
+    /// `text()`
+    Text,
+    /// `comment()`
+    Comment,
+    /// `processing-instruction()`, with an optional target.
+    ProcessingInstruction(Option<String>),
+}
+
+/// The reason an expression could not be parsed.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    UnknownAxis(String),
+    /// Variables can't be bound through the DOM API.
+    Variable(String),
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// `*` as the multiplication operator.
+    Multiply,
+    /// `and`, `or`, `mod` or `div`.
+    OperatorName(String),
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    /// `*`, `prefix:*` or a qualified name.
+    NameTest(NodeTest),
+    NodeType(String),
+    FunctionName(String),
+    AxisName(String),
+}
+
+impl Token {
+    /// Whether a `*` or a name following this token is an operator, following
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>.
+    fn precedes_operator(&self) -> bool {
+        match *self {
+            Token::At |
+            Token::ColonColon |
+            Token::LeftParen |
+            Token::LeftBracket |
+            Token::Comma |
+            Token::Slash |
+            Token::DoubleSlash |
+            Token::Pipe |
+            Token::Plus |
+            Token::Minus |
+            Token::Equal |
+            Token::NotEqual |
+            Token::Less |
+            Token::LessOrEqual |
+            Token::Greater |
+            Token::GreaterOrEqual |
+            Token::Multiply |
+            Token::OperatorName(_) => false,
+            _ => true,
+        }
+    }
+}
+
+fn is_xml_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\n' || c == '\r'
+}
+
+fn is_name_start_char(c: char) -> bool {
+    c == '_' || c.is_alphabetic() || ((c as u32) > 0x7F && !c.is_whitespace())
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c) || c == '-' || c == '.' || c.is_ascii_digit()
+}
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map_or(false, |&c| is_xml_whitespace(c)) {
+            self.chars.next();
+        }
+    }
+
+    fn consume_if(&mut self, expected: char) -> bool {
+        if self.chars.peek() == Some(&expected) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_ncname(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !is_name_char(c) {
+                break;
+            }
+            name.push(c);
+            self.chars.next();
+        }
+        name
+    }
+
+    fn consume_number(&mut self, mut number: String) -> Result<Token, ParseError> {
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_digit() && c != '.' {
+                break;
+            }
+            number.push(c);
+            self.chars.next();
+        }
+        number
+            .parse()
+            .map(Token::Number)
+            .map_err(|_| ParseError::UnexpectedToken(number))
+    }
+
+    fn after_operand(&self) -> bool {
+        self.tokens
+            .last()
+            .map_or(false, |token| token.precedes_operator())
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        self.skip_whitespace();
+        let c = match self.chars.next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let token = match c {
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Equal,
+            '!' if self.consume_if('=') => Token::NotEqual,
+            '<' if self.consume_if('=') => Token::LessOrEqual,
+            '<' => Token::Less,
+            '>' if self.consume_if('=') => Token::GreaterOrEqual,
+            '>' => Token::Greater,
+            ':' if self.consume_if(':') => Token::ColonColon,
+            '/' if self.consume_if('/') => Token::DoubleSlash,
+            '/' => Token::Slash,
+            '.' if self.consume_if('.') => Token::DotDot,
+            '.' if self.chars.peek().map_or(false, |c| c.is_ascii_digit()) => {
+                self.consume_number(".".to_owned())?
+            },
+            '.' => Token::Dot,
+            '"' | '\'' => {
+                let mut literal = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some(end) if end == c => break,
+                        Some(c) => literal.push(c),
+                        None => return Err(ParseError::UnexpectedEnd),
+                    }
+                }
+                Token::Literal(literal)
+            },
+            '0'..='9' => self.consume_number(c.to_string())?,
+            '$' => Token::Variable(self.consume_ncname()),
+            '*' if self.after_operand() => Token::Multiply,
+            '*' => Token::NameTest(NodeTest::Any),
+            c if is_name_start_char(c) => {
+                let mut name = c.to_string();
+                name.push_str(&self.consume_ncname());
+                if self.after_operand() {
+                    match &*name {
+                        "and" | "or" | "mod" | "div" => Token::OperatorName(name),
+                        _ => return Err(ParseError::UnexpectedToken(name)),
+                    }
+                } else {
+                    self.name_token(name)?
+                }
+            },
+            c => return Err(ParseError::UnexpectedToken(c.to_string())),
+        };
+        Ok(Some(token))
+    }
+
+    /// Disambiguates a name that is not an operator name.
+    fn name_token(&mut self, name: String) -> Result<Token, ParseError> {
+        // A QName, or a `prefix:*` name test. The colon of a QName can't be
+        // surrounded by whitespace, unlike the one of an axis specifier.
+        let mut lookahead = self.chars.clone();
+        if lookahead.next() == Some(':') {
+            match lookahead.next() {
+                Some('*') => {
+                    self.chars.next();
+                    self.chars.next();
+                    return Ok(Token::NameTest(NodeTest::Namespace(name)));
+                },
+                Some(c) if is_name_start_char(c) => {
+                    self.chars.next();
+                    let local_name = self.consume_ncname();
+                    return Ok(self.qualified_name_token(Some(name), local_name));
+                },
+                _ => {},
+            }
+        }
+
+        // An axis name.
+        self.skip_whitespace();
+        let mut lookahead = self.chars.clone();
+        if lookahead.next() == Some(':') && lookahead.next() == Some(':') {
+            return Ok(Token::AxisName(name));
+        }
+
+        Ok(self.qualified_name_token(None, name))
+    }
+
+    fn qualified_name_token(&mut self, prefix: Option<String>, local_name: String) -> Token {
+        self.skip_whitespace();
+        if self.chars.peek() != Some(&'(') {
+            return Token::NameTest(NodeTest::Name(prefix, local_name));
+        }
+        match (prefix, &*local_name) {
+            (None, "comment") |
+            (None, "text") |
+            (None, "processing-instruction") |
+            (None, "node") => Token::NodeType(local_name),
+            (None, _) => Token::FunctionName(local_name),
+            (Some(prefix), _) => Token::FunctionName(format!("{}:{}", prefix, local_name)),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokenizer = Tokenizer {
+        chars: input.chars().peekable(),
+        tokens: vec![],
+    };
+    while let Some(token) = tokenizer.next_token()? {
+        tokenizer.tokens.push(token);
+    }
+    Ok(tokenizer.tokens)
+}
+
+/// Parses an XPath 1.0 expression.
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+    };
+    let expr = parser.parse_or_expr()?;
+    match parser.next() {
+        None => Ok(expr),
+        Some(token) => Err(ParseError::UnexpectedToken(format!("{:?}", token))),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn consume_if(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        match self.next() {
+            Some(ref token) if *token == expected => Ok(()),
+            Some(token) => Err(ParseError::UnexpectedToken(format!("{:?}", token))),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn binary<F>(
+        &mut self,
+        operand: F,
+        operators: &[(Token, BinaryOperator)],
+    ) -> Result<Expr, ParseError>
+    where
+        F: Fn(&mut Parser) -> Result<Expr, ParseError>,
+    {
+        let mut expr = operand(self)?;
+        'operands: loop {
+            for &(ref token, operator) in operators {
+                if self.consume_if(token) {
+                    expr = Expr::Binary(Box::new(expr), operator, Box::new(operand(self)?));
+                    continue 'operands;
+                }
+            }
+            return Ok(expr);
+        }
+    }
+
+    fn parse_or_expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            Parser::parse_and_expr,
+            &[(Token::OperatorName("or".to_owned()), BinaryOperator::Or)],
+        )
+    }
+
+    fn parse_and_expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            Parser::parse_equality_expr,
+            &[(Token::OperatorName("and".to_owned()), BinaryOperator::And)],
+        )
+    }
+
+    fn parse_equality_expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            Parser::parse_relational_expr,
+            &[
+                (Token::Equal, BinaryOperator::Equal),
+                (Token::NotEqual, BinaryOperator::NotEqual),
+            ],
+        )
+    }
+
+    fn parse_relational_expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            Parser::parse_additive_expr,
+            &[
+                (Token::Less, BinaryOperator::Less),
+                (Token::LessOrEqual, BinaryOperator::LessOrEqual),
+                (Token::Greater, BinaryOperator::Greater),
+                (Token::GreaterOrEqual, BinaryOperator::GreaterOrEqual),
+            ],
+        )
+    }
+
+    fn parse_additive_expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            Parser::parse_multiplicative_expr,
+            &[
+                (Token::Plus, BinaryOperator::Add),
+                (Token::Minus, BinaryOperator::Subtract),
+            ],
+        )
+    }
+
+    fn parse_multiplicative_expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            Parser::parse_unary_expr,
+            &[
+                (Token::Multiply, BinaryOperator::Multiply),
+                (
+                    Token::OperatorName("div".to_owned()),
+                    BinaryOperator::Divide,
+                ),
+                (
+                    Token::OperatorName("mod".to_owned()),
+                    BinaryOperator::Modulo,
+                ),
+            ],
+        )
+    }
+
+    fn parse_unary_expr(&mut self) -> Result<Expr, ParseError> {
+        if self.consume_if(&Token::Minus) {
+            return Ok(Expr::Negate(Box::new(self.parse_unary_expr()?)));
+        }
+        self.binary(
+            Parser::parse_path_expr,
+            &[(Token::Pipe, BinaryOperator::Union)],
+        )
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-PathExpr>
+    fn parse_path_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = match self.peek() {
+            Some(Token::Variable(_)) |
+            Some(Token::LeftParen) |
+            Some(Token::Literal(_)) |
+            Some(Token::Number(_)) |
+            Some(Token::FunctionName(_)) => {
+                let primary = self.parse_primary_expr()?;
+                let predicates = self.parse_predicates()?;
+                let filter = if predicates.is_empty() {
+                    primary
+                } else {
+                    Expr::Filter(Box::new(primary), predicates)
+                };
+                match self.peek() {
+                    Some(Token::Slash) | Some(Token::DoubleSlash) => {
+                        PathStart::Filter(Box::new(filter))
+                    },
+                    _ => return Ok(filter),
+                }
+            },
+            Some(Token::Slash) | Some(Token::DoubleSlash) => PathStart::Root,
+            _ => PathStart::ContextNode,
+        };
+
+        let mut steps = vec![];
+        match start {
+            PathStart::ContextNode => self.parse_relative_location_path(&mut steps)?,
+            _ => {
+                if self.consume_if(&Token::DoubleSlash) {
+                    steps.push(descendant_or_self_step());
+                    self.parse_relative_location_path(&mut steps)?;
+                } else {
+                    self.expect(Token::Slash)?;
+                    // A lone `/` selects the root node.
+                    if self.starts_step() {
+                        self.parse_relative_location_path(&mut steps)?;
+                    }
+                }
+            },
+        }
+        Ok(Expr::Path(start, steps))
+    }
+
+    fn starts_step(&self) -> bool {
+        match self.peek() {
+            Some(Token::Dot) |
+            Some(Token::DotDot) |
+            Some(Token::At) |
+            Some(Token::AxisName(_)) |
+            Some(Token::NameTest(_)) |
+            Some(Token::NodeType(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-RelativeLocationPath>
+    fn parse_relative_location_path(&mut self, steps: &mut Vec<Step>) -> Result<(), ParseError> {
+        loop {
+            steps.push(self.parse_step()?);
+            if self.consume_if(&Token::DoubleSlash) {
+                steps.push(descendant_or_self_step());
+            } else if !self.consume_if(&Token::Slash) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-Step>
+    fn parse_step(&mut self) -> Result<Step, ParseError> {
+        let axis = match self.next() {
+            Some(Token::Dot) => {
+                return Ok(Step {
+                    axis: Axis::SelfAxis,
+                    node_test: NodeTest::Node,
+                    predicates: vec![],
+                });
+            },
+            Some(Token::DotDot) => {
+                return Ok(Step {
+                    axis: Axis::Parent,
+                    node_test: NodeTest::Node,
+                    predicates: vec![],
+                });
+            },
+            Some(Token::At) => Axis::Attribute,
+            Some(Token::AxisName(name)) => {
+                self.expect(Token::ColonColon)?;
+                Axis::from_name(&name).ok_or(ParseError::UnknownAxis(name))?
+            },
+            Some(_) => {
+                self.position -= 1;
+                Axis::Child
+            },
+            None => return Err(ParseError::UnexpectedEnd),
+        };
+
+        let node_test = match self.next() {
+            Some(Token::NameTest(node_test)) => node_test,
+            Some(Token::NodeType(node_type)) => {
+                self.expect(Token::LeftParen)?;
+                let node_test = match &*node_type {
+                    "comment" => NodeTest::Comment,
+                    "text" => NodeTest::Text,
+                    "node" => NodeTest::Node,
+                    _ => match self.peek() {
+                        Some(Token::Literal(target)) => {
+                            let target = target.clone();
+                            self.position += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        },
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(Token::RightParen)?;
+                node_test
+            },
+            Some(token) => return Err(ParseError::UnexpectedToken(format!("{:?}", token))),
+            None => return Err(ParseError::UnexpectedEnd),
+        };
+
+        Ok(Step {
+            axis,
+            node_test,
+            predicates: self.parse_predicates()?,
+        })
+    }
+
+    fn parse_predicates(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut predicates = vec![];
+        while self.consume_if(&Token::LeftBracket) {
+            predicates.push(self.parse_or_expr()?);
+            self.expect(Token::RightBracket)?;
+        }
+        Ok(predicates)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-PrimaryExpr>
+    fn parse_primary_expr(&mut self) -> Result<Expr, ParseError> {
+        match self.next() {
+            Some(Token::Variable(name)) => Err(ParseError::Variable(name)),
+            Some(Token::LeftParen) => {
+                let expr = self.parse_or_expr()?;
+                self.expect(Token::RightParen)?;
+                Ok(expr)
+            },
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal)),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::FunctionName(name)) => {
+                self.expect(Token::LeftParen)?;
+                let mut arguments = vec![];
+                if !self.consume_if(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.parse_or_expr()?);
+                        if self.consume_if(&Token::RightParen) {
+                            break;
+                        }
+                        self.expect(Token::Comma)?;
+                    }
+                }
+                Ok(Expr::Function(name, arguments))
+            },
+            Some(token) => Err(ParseError::UnexpectedToken(format!("{:?}", token))),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+}
+
+/// The step that `//` abbreviates.
+fn descendant_or_self_step() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        node_test: NodeTest::Node,
+        predicates: vec![],
+    }
+}
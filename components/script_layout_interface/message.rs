@@ -21,11 +21,14 @@ use servo_atoms::Atom;
 use servo_url::ServoUrl;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use style::animation::Animation;
 use style::context::QuirksMode;
 use style::dom::OpaqueNode;
+use style::font_face::EffectiveSources;
 use style::properties::PropertyId;
 use style::selector_parser::PseudoElement;
 use style::stylesheets::Stylesheet;
+use style::values::computed::font::FamilyName;
 
 /// Asynchronous messages that script can send to layout.
 pub enum Msg {
@@ -103,6 +106,26 @@ pub enum Msg {
 
     /// Request the current number of animations that are running.
     GetRunningAnimations(IpcSender<usize>),
+
+    /// Asks layout to tell script when the next display list it builds has been
+    /// painted.
+    ObserveNextFramePresentation,
+
+    /// Creates, updates or cancels an animation that script created with
+    /// `Element.animate()`.
+    UpdateScriptAnimation(Animation),
+
+    /// Loads a font that script created with `new FontFace()`, and sends
+    /// whether it loaded once it's done.
+    LoadWebFont(FamilyName, WebFontSource, IpcSender<bool>),
+}
+
+/// Where a font that script created is loaded from.
+pub enum WebFontSource {
+    /// The `url()` and `local()` sources of a `src` descriptor.
+    Sources(EffectiveSources),
+    /// The data of a font file.
+    Data(Vec<u8>),
 }
 
 #[derive(Debug, PartialEq)]
@@ -126,6 +149,7 @@ pub enum QueryMsg {
     // garbage values such as `0xdeadbeef as *const _`, this is unsound.
     NodeScrollIdQuery(TrustedNodeAddress),
     ResolvedStyleQuery(TrustedNodeAddress, Option<PseudoElement>, PropertyId),
+    ResolvedFontStyleQuery(TrustedNodeAddress, PropertyId, String),
     StyleQuery(TrustedNodeAddress),
     ElementInnerTextQuery(TrustedNodeAddress),
     InnerWindowDimensionsQuery(BrowsingContextId),
@@ -156,6 +180,7 @@ impl ReflowGoal {
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
                 QueryMsg::ResolvedStyleQuery(..) |
+                QueryMsg::ResolvedFontStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
                 QueryMsg::StyleQuery(_) => false,
             },
@@ -177,6 +202,7 @@ impl ReflowGoal {
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
                 QueryMsg::ResolvedStyleQuery(..) |
+                QueryMsg::ResolvedFontStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
                 QueryMsg::StyleQuery(_) => false,
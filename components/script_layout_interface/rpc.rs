@@ -7,6 +7,7 @@ use euclid::default::Rect;
 use euclid::Size2D;
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
+use style::properties::style_structs::Font;
 use style::properties::ComputedValues;
 use style_traits::CSSPixel;
 use webrender_api::ExternalScrollId;
@@ -32,6 +33,8 @@ pub trait LayoutRPC {
     fn node_scroll_id(&self) -> NodeScrollIdResponse;
     /// Query layout for the resolved value of a given CSS property
     fn resolved_style(&self) -> ResolvedStyleResponse;
+    /// Query layout for the resolved font style of a canvas context.
+    fn resolved_font_style(&self) -> Option<Arc<Font>>;
     fn offset_parent(&self) -> OffsetParentResponse;
     /// Requests the styles for an element. Contains a `None` value if the element is in a `display:
     /// none` subtree.
@@ -20,13 +20,13 @@ mod script_msg;
 pub mod transferable;
 pub mod webdriver_msg;
 
-use crate::transferable::MessagePortImpl;
+use crate::transferable::{ImageBitmapImpl, MessagePortImpl, OffscreenCanvasImpl};
 use crate::webdriver_msg::{LoadStatus, WebDriverScriptCommand};
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{Cursor, EventLoopWaker};
+use embedder_traits::{Cursor, EventLoopWaker, PermissionName, ScreenOrientationState};
 use euclid::{default::Point2D, Length, Rect, Scale, Size2D, UnknownUnit, Vector2D};
 use gfx_traits::Epoch;
 use http::HeaderMap;
@@ -71,7 +71,7 @@ use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub use crate::script_msg::{
     DOMMessage, HistoryEntryReplacement, SWManagerMsg, SWManagerSenders, ScopeThings,
-    ServiceWorkerMsg,
+    ServiceWorkerMsg, SharedWorkerControlMsg, SharedWorkerKey,
 };
 pub use crate::script_msg::{
     EventResult, IFrameSize, IFrameSizeMsg, LayoutMsg, LogEntry, ScriptMsg,
@@ -293,6 +293,10 @@ pub enum ConstellationControlMsg {
     ResizeInactive(PipelineId, WindowSizeData),
     /// Window switched from fullscreen mode.
     ExitFullScreen(PipelineId),
+    /// The user unlocked the mouse pointer.
+    ExitPointerLock(PipelineId),
+    /// The screen was rotated.
+    ChangeScreenOrientation(PipelineId, ScreenOrientationState),
     /// Notifies the script that the document associated with this pipeline should 'unload'.
     UnloadDocument(PipelineId),
     /// Notifies the script that a pipeline should be closed.
@@ -361,11 +365,16 @@ pub enum ConstellationControlMsg {
     WebDriverScriptCommand(PipelineId, WebDriverScriptCommand),
     /// Notifies script thread that all animations are done
     TickAllAnimations(PipelineId),
+    /// Notifies script thread that a smooth scroll of the given scroll node has ended
+    SmoothScrollEnded(PipelineId, ExternalScrollId),
     /// Notifies the script thread of a transition end
     TransitionEnd(UntrustedNodeAddress, String, f64),
     /// Notifies the script thread that a new Web font has been loaded, and thus the page should be
     /// reflowed.
     WebFontLoaded(PipelineId),
+    /// Notifies the script thread that the elements which the animations it created run on should
+    /// be restyled, so that layout can advance these animations.
+    RestyleAnimatedElements(PipelineId),
     /// Cause a `load` event to be dispatched at the appropriate iframe element.
     DispatchIFrameLoadEvent {
         /// The frame that has been marked as loaded.
@@ -385,6 +394,9 @@ pub enum ConstellationControlMsg {
         Option<String>,
         Option<String>,
     ),
+    /// Cause the `change` event to be fired at the permission status objects
+    /// of the given permission in the appropriate window.
+    DispatchPermissionChange(PipelineId, PermissionName),
     /// Report an error from a CSS parser for the given pipeline
     ReportCSSError(PipelineId, String, u32, u32, String),
     /// Reload the given page.
@@ -393,6 +405,9 @@ pub enum ConstellationControlMsg {
     WebVREvents(PipelineId, Vec<WebVREvent>),
     /// Notifies the script thread about a new recorded paint metric.
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
+    /// Notifies the script thread of the time at which the first frame requested
+    /// with `ObserveNextFramePresentation` has been painted.
+    FramePresented(PipelineId, u64),
     /// Notifies the media session about a user requested media session action.
     MediaSessionAction(PipelineId, MediaSessionActionType),
 }
@@ -424,15 +439,21 @@ impl fmt::Debug for ConstellationControlMsg {
             FocusIFrame(..) => "FocusIFrame",
             WebDriverScriptCommand(..) => "WebDriverScriptCommand",
             TickAllAnimations(..) => "TickAllAnimations",
+            SmoothScrollEnded(..) => "SmoothScrollEnded",
             TransitionEnd(..) => "TransitionEnd",
             WebFontLoaded(..) => "WebFontLoaded",
+            RestyleAnimatedElements(..) => "RestyleAnimatedElements",
             DispatchIFrameLoadEvent { .. } => "DispatchIFrameLoadEvent",
             DispatchStorageEvent(..) => "DispatchStorageEvent",
+            DispatchPermissionChange(..) => "DispatchPermissionChange",
             ReportCSSError(..) => "ReportCSSError",
             Reload(..) => "Reload",
             WebVREvents(..) => "WebVREvents",
             PaintMetric(..) => "PaintMetric",
+            FramePresented(..) => "FramePresented",
             ExitFullScreen(..) => "ExitFullScreen",
+            ExitPointerLock(..) => "ExitPointerLock",
+            ChangeScreenOrientation(..) => "ChangeScreenOrientation",
             MediaSessionAction(..) => "MediaSessionAction",
         };
         write!(formatter, "ConstellationControlMsg::{}", variant)
@@ -481,6 +502,21 @@ pub enum TouchEventType {
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct TouchId(pub i32);
 
+/// The state of a pen that is in contact with the screen.
+///
+/// <https://w3c.github.io/pointerevents/#pointerevent-interface>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PenState {
+    /// The normalized pressure of the pen, in the range [0, 1].
+    pub pressure: f32,
+    /// The plane angle between the Y-Z plane and the plane containing both
+    /// the pen axis and the Y axis, in degrees in the range [-90, 90].
+    pub tilt_x: i32,
+    /// The plane angle between the X-Z plane and the plane containing both
+    /// the pen axis and the X axis, in degrees in the range [-90, 90].
+    pub tilt_y: i32,
+}
+
 /// The mouse button involved in the event.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum MouseButton {
@@ -546,6 +582,13 @@ pub enum CompositorEvent {
     MouseMoveEvent(
         Option<Point2D<f32>>,
         Option<UntrustedNodeAddress>,
+        Option<Point2D<f32>>,
+        // Bitmask of MouseButton values representing the currently pressed buttons
+        u16,
+    ),
+    /// The mouse was moved by the given distance while the pointer is locked.
+    MouseMovementEvent(
+        Vector2D<f32, UnknownUnit>,
         // Bitmask of MouseButton values representing the currently pressed buttons
         u16,
     ),
@@ -556,12 +599,22 @@ pub enum CompositorEvent {
         Point2D<f32>,
         Option<UntrustedNodeAddress>,
     ),
+    /// A pen event was generated with a pen ID, location and pen state.
+    PenEvent(
+        TouchEventType,
+        TouchId,
+        Point2D<f32>,
+        Option<UntrustedNodeAddress>,
+        PenState,
+    ),
     /// A wheel event was generated with a delta in the X, Y, and/or Z directions
     WheelEvent(WheelDelta, Point2D<f32>, Option<UntrustedNodeAddress>),
     /// A key was pressed.
     KeyboardEvent(KeyboardEvent),
     /// An event from the IME is dispatched.
     CompositionEvent(CompositionEvent),
+    /// An input of a gamepad changed, or a gamepad was connected or disconnected.
+    GamepadEvent(GamepadEvent),
 }
 
 /// Requests a TimerEvent-Message be sent after the given duration.
@@ -861,6 +914,8 @@ pub enum ConstellationMsg {
     ),
     /// Requests that the constellation instruct layout to begin a new tick of the animation.
     TickAnimation(PipelineId, AnimationTickType),
+    /// Inform the constellation that a smooth scroll of the given scroll node has ended.
+    SmoothScrollEnded(PipelineId, ExternalScrollId),
     /// Dispatch a webdriver command
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
@@ -877,6 +932,8 @@ pub enum ConstellationMsg {
     SendError(Option<TopLevelBrowsingContextId>, String),
     /// Make browser visible.
     SelectBrowser(TopLevelBrowsingContextId),
+    /// Change whether a top level browsing context can be seen by the user.
+    ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
     /// Forward an event to the script task of the given pipeline.
     ForwardEvent(PipelineId, CompositorEvent),
     /// Requesting a change to the onscreen cursor.
@@ -887,8 +944,14 @@ pub enum ConstellationMsg {
     DisableProfiler,
     /// Request to exit from fullscreen mode
     ExitFullScreen(TopLevelBrowsingContextId),
+    /// Request to unlock the mouse pointer
+    ExitPointerLock(TopLevelBrowsingContextId),
+    /// Inform the constellation that the screen was rotated.
+    ChangeScreenOrientation(ScreenOrientationState),
     /// Media session action.
     MediaSessionAction(MediaSessionActionType),
+    /// Inform the constellation of a gamepad event.
+    Gamepad(GamepadEvent),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -906,6 +969,7 @@ impl fmt::Debug for ConstellationMsg {
             TraverseHistory(..) => "TraverseHistory",
             WindowSize(..) => "WindowSize",
             TickAnimation(..) => "TickAnimation",
+            SmoothScrollEnded(..) => "SmoothScrollEnded",
             WebDriverCommand(..) => "WebDriverCommand",
             Reload(..) => "Reload",
             LogEntry(..) => "LogEntry",
@@ -914,12 +978,16 @@ impl fmt::Debug for ConstellationMsg {
             CloseBrowser(..) => "CloseBrowser",
             SendError(..) => "SendError",
             SelectBrowser(..) => "SelectBrowser",
+            ChangeBrowserVisibility(..) => "ChangeBrowserVisibility",
             ForwardEvent(..) => "ForwardEvent",
             SetCursor(..) => "SetCursor",
             EnableProfiler(..) => "EnableProfiler",
             DisableProfiler => "DisableProfiler",
             ExitFullScreen(..) => "ExitFullScreen",
+            ExitPointerLock(..) => "ExitPointerLock",
+            ChangeScreenOrientation(..) => "ChangeScreenOrientation",
             MediaSessionAction(..) => "MediaSessionAction",
+            Gamepad(..) => "Gamepad",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
@@ -952,6 +1020,10 @@ pub struct WorkerGlobalScopeInit {
     pub is_headless: bool,
     /// An optional string allowing the user agnet to be set for testing.
     pub user_agent: Cow<'static, str>,
+    /// True if the owner of the worker is cross-origin isolated
+    pub cross_origin_isolated: bool,
+    /// The time origin of the worker, the moment it was created by its owner
+    pub navigation_start_precise: u64,
 }
 
 /// Common entities representing a network load origin
@@ -1041,6 +1113,27 @@ pub struct StructuredSerializedData {
     pub serialized: Vec<u8>,
     /// Transferred objects.
     pub ports: Option<HashMap<MessagePortId, MessagePortImpl>>,
+    /// Transferred offscreen canvases, in the order they were transferred.
+    pub offscreen_canvases: Option<Vec<OffscreenCanvasImpl>>,
+    /// Transferred image bitmaps, in the order they were transferred.
+    pub image_bitmaps: Option<Vec<ImageBitmapImpl>>,
+    /// The keys of the ports of the readable and writable sides of
+    /// transferred transform streams, in the order they were transferred.
+    pub transform_streams: Option<Vec<(u64, u64)>>,
+    /// References to the shared memory found in `serialized`,
+    /// for data that can only be read by another agent of the same process.
+    #[serde(skip)]
+    #[ignore_malloc_size_of = "Defined in mozjs"]
+    pub shared_memory: Option<SharedMemoryRefs>,
+}
+
+/// Keeps alive the shared memory referenced by serialized data, until it is dropped.
+pub struct SharedMemoryRefs(pub Box<dyn Send>);
+
+impl fmt::Debug for SharedMemoryRefs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedMemoryRefs")
+    }
 }
 
 /// A task on the https://html.spec.whatwg.org/multipage/#port-message-queue
@@ -1052,6 +1145,42 @@ pub struct PortMessageTask {
     pub data: StructuredSerializedData,
 }
 
+/// A message sent to all the broadcast channels of a given name and origin.
+/// <https://html.spec.whatwg.org/multipage/#dom-broadcastchannel-postmessage>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BroadcastMsg {
+    /// The origin of this message.
+    pub origin: ImmutableOrigin,
+    /// The name of the channel.
+    pub channel_name: String,
+    /// A data-holder for serialized data.
+    pub data: StructuredSerializedData,
+}
+
+impl Clone for BroadcastMsg {
+    fn clone(&self) -> BroadcastMsg {
+        // Nothing can be transferred along with a broadcast message,
+        // so only the serialized data needs to be copied.
+        debug_assert!(self.data.ports.is_none());
+        debug_assert!(self.data.offscreen_canvases.is_none());
+        debug_assert!(self.data.image_bitmaps.is_none());
+        debug_assert!(self.data.transform_streams.is_none());
+        debug_assert!(self.data.shared_memory.is_none());
+        BroadcastMsg {
+            origin: self.origin.clone(),
+            channel_name: self.channel_name.clone(),
+            data: StructuredSerializedData {
+                serialized: self.data.serialized.clone(),
+                ports: None,
+                offscreen_canvases: None,
+                image_bitmaps: None,
+                transform_streams: None,
+                shared_memory: None,
+            },
+        }
+    }
+}
+
 /// Messages for communication between the constellation and a global managing ports.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum MessagePortMsg {
@@ -1067,6 +1196,32 @@ pub enum MessagePortMsg {
     NewTask(MessagePortId, PortMessageTask),
 }
 
+/// The index of a gamepad, chosen by the embedder when the gamepad is connected.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct GamepadIndex(pub usize);
+
+/// An event from a gamepad connected to the embedder,
+/// https://w3c.github.io/gamepad/#receiving-inputs
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum GamepadEvent {
+    /// A gamepad was connected, with its name and its number of axes and buttons.
+    Connected(GamepadIndex, String, usize, usize),
+    /// A gamepad was disconnected.
+    Disconnected(GamepadIndex),
+    /// An input of a gamepad changed.
+    Updated(GamepadIndex, GamepadUpdateType),
+}
+
+/// A change of an input of a gamepad, the inputs are indexed as in the standard gamepad,
+/// https://w3c.github.io/gamepad/#remapping
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum GamepadUpdateType {
+    /// The value of an axis changed, between -1.0 and 1.0.
+    Axis(usize, f64),
+    /// The value of a button changed, between 0.0 and 1.0.
+    Button(usize, f64),
+}
+
 /// The type of MediaSession action.
 /// https://w3c.github.io/mediasession/#enumdef-mediasessionaction
 #[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
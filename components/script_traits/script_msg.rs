@@ -4,6 +4,7 @@
 
 use crate::AnimationState;
 use crate::AuxiliaryBrowsingContextLoadInfo;
+use crate::BroadcastMsg;
 use crate::DocumentState;
 use crate::IFrameLoadInfoWithData;
 use crate::LayoutControlMsg;
@@ -16,15 +17,16 @@ use crate::WorkerGlobalScopeInit;
 use crate::WorkerScriptLoadOrigin;
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use embedder_traits::{EmbedderMsg, MediaSessionEvent, PermissionName, ScreenOrientationState};
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::Size2D;
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use msg::constellation_msg::{
-    BrowsingContextId, MessagePortId, MessagePortRouterId, PipelineId, TopLevelBrowsingContextId,
+    BroadcastChannelRouterId, BrowsingContextId, MessagePortId, MessagePortRouterId, PipelineId,
+    TopLevelBrowsingContextId,
 };
-use msg::constellation_msg::{HistoryStateId, TraversalDirection};
+use msg::constellation_msg::{HistoryStateId, SharedWorkerId, TraversalDirection};
 use net_traits::request::RequestBuilder;
 use net_traits::storage_thread::StorageType;
 use net_traits::CoreResourceMsg;
@@ -34,7 +36,8 @@ use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPoint};
+use webrender_api::ExternalScrollId;
 
 /// A particular iframe's size, associated with a browsing context.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -140,6 +143,21 @@ pub enum ScriptMsg {
     RemoveMessagePort(MessagePortId),
     /// Entangle two message-ports.
     EntanglePorts(MessagePortId, MessagePortId),
+    /// A global has started managing broadcast-channels.
+    NewBroadcastChannelRouter(
+        BroadcastChannelRouterId,
+        IpcSender<BroadcastMsg>,
+        ImmutableOrigin,
+    ),
+    /// A global has stopped managing broadcast-channels.
+    RemoveBroadcastChannelRouter(BroadcastChannelRouterId, ImmutableOrigin),
+    /// A global started managing broadcast channels for a given channel-name.
+    NewBroadcastChannelNameInRouter(BroadcastChannelRouterId, String, ImmutableOrigin),
+    /// A global stopped managing broadcast channels for a given channel-name.
+    RemoveBroadcastChannelNameInRouter(BroadcastChannelRouterId, String, ImmutableOrigin),
+    /// Broadcast a message to all same-origin broadcast channels,
+    /// excluding the source of the broadcast.
+    ScheduleBroadcast(BroadcastChannelRouterId, BroadcastMsg),
     /// Forward a message to the embedder.
     ForwardToEmbedder(EmbedderMsg),
     /// Requests are sent to constellation and fetches are checked manually
@@ -154,6 +172,8 @@ pub enum ScriptMsg {
         Option<String>,
         Option<String>,
     ),
+    /// Notify every same-origin pipeline that the state of a permission changed.
+    BroadcastPermissionChange(ImmutableOrigin, PermissionName),
     /// Indicates whether this pipeline is currently running animations.
     ChangeRunningAnimationsState(AnimationState),
     /// Requests that a new 2D canvas thread be created. (This is done in the constellation because
@@ -235,6 +255,10 @@ pub enum ScriptMsg {
     SetFinalUrl(ServoUrl),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Smoothly scroll the given scroll node to a new position.
+    SmoothScroll(ExternalScrollId, LayoutPoint),
+    /// Stop smoothly scrolling the given scroll node, and move it to a new position instead.
+    StopSmoothScroll(ExternalScrollId, LayoutPoint),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<String>, LogEntry),
     /// Discard the document.
@@ -248,12 +272,27 @@ pub enum ScriptMsg {
     ForwardDOMMessage(DOMMessage, ServoUrl),
     /// Store the data required to activate a service worker for the given scope
     RegisterServiceWorker(ScopeThings, ServoUrl),
+    /// Connect to the shared worker with the given key, registering the control-sender
+    /// as a new shared worker with the given id if none is running.
+    /// Replies `true` if the caller is responsible for starting the new worker.
+    ConnectToSharedWorker(
+        SharedWorkerKey,
+        SharedWorkerId,
+        StructuredSerializedData,
+        IpcSender<SharedWorkerControlMsg>,
+        IpcSender<bool>,
+    ),
+    /// The shared worker with the given key and id has closed itself, and should
+    /// not receive new connections.
+    SharedWorkerClosed(SharedWorkerKey, SharedWorkerId),
     /// Get Window Informations size and position
     GetClientWindow(IpcSender<(DeviceIntSize, DeviceIntPoint)>),
     /// Get the screen size (pixel)
     GetScreenSize(IpcSender<DeviceIntSize>),
     /// Get the available screen size (pixel)
     GetScreenAvailSize(IpcSender<DeviceIntSize>),
+    /// Get the orientation of the screen, if the embedder reported it
+    GetScreenOrientation(IpcSender<Option<ScreenOrientationState>>),
     /// Notifies the constellation about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(PipelineId, MediaSessionEvent),
@@ -271,10 +310,16 @@ impl fmt::Debug for ScriptMsg {
             RerouteMessagePort(..) => "RerouteMessagePort",
             RemoveMessagePort(..) => "RemoveMessagePort",
             MessagePortShipped(..) => "MessagePortShipped",
+            NewBroadcastChannelRouter(..) => "NewBroadcastChannelRouter",
+            RemoveBroadcastChannelRouter(..) => "RemoveBroadcastChannelRouter",
+            NewBroadcastChannelNameInRouter(..) => "NewBroadcastChannelNameInRouter",
+            RemoveBroadcastChannelNameInRouter(..) => "RemoveBroadcastChannelNameInRouter",
+            ScheduleBroadcast(..) => "ScheduleBroadcast",
             EntanglePorts(..) => "EntanglePorts",
             ForwardToEmbedder(..) => "ForwardToEmbedder",
             InitiateNavigateRequest(..) => "InitiateNavigateRequest",
             BroadcastStorageEvent(..) => "BroadcastStorageEvent",
+            BroadcastPermissionChange(..) => "BroadcastPermissionChange",
             ChangeRunningAnimationsState(..) => "ChangeRunningAnimationsState",
             CreateCanvasPaintThread(..) => "CreateCanvasPaintThread",
             Focus => "Focus",
@@ -299,15 +344,20 @@ impl fmt::Debug for ScriptMsg {
             SetDocumentState(..) => "SetDocumentState",
             SetFinalUrl(..) => "SetFinalUrl",
             TouchEventProcessed(..) => "TouchEventProcessed",
+            SmoothScroll(..) => "SmoothScroll",
+            StopSmoothScroll(..) => "StopSmoothScroll",
             LogEntry(..) => "LogEntry",
             DiscardDocument => "DiscardDocument",
             DiscardTopLevelBrowsingContext => "DiscardTopLevelBrowsingContext",
             PipelineExited => "PipelineExited",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
             RegisterServiceWorker(..) => "RegisterServiceWorker",
+            ConnectToSharedWorker(..) => "ConnectToSharedWorker",
+            SharedWorkerClosed(..) => "SharedWorkerClosed",
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
+            GetScreenOrientation(..) => "GetScreenOrientation",
             MediaSessionEvent(..) => "MediaSessionEvent",
         };
         write!(formatter, "ScriptMsg::{}", variant)
@@ -338,6 +388,30 @@ pub struct DOMMessage {
     pub data: StructuredSerializedData,
 }
 
+/// https://html.spec.whatwg.org/multipage/#dom-sharedworker
+///
+/// Constructing a `SharedWorker` with the same origin, name and script url
+/// connects to the same running worker.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SharedWorkerKey {
+    /// The origin of the constructing global
+    pub origin: ImmutableOrigin,
+    /// The name given in the worker options
+    pub name: String,
+    /// The script url
+    pub url: ServoUrl,
+}
+
+/// Messages sent to a running shared worker
+#[derive(Debug, Deserialize, Serialize)]
+pub enum SharedWorkerControlMsg {
+    /// A new `SharedWorker` connected, the data holds the transferred port
+    /// to deliver in a `connect` event.
+    Connect(StructuredSerializedData),
+    /// All documents connected to the worker have gone away
+    Terminate,
+}
+
 /// Channels to allow service worker manager to communicate with constellation and resource thread
 pub struct SWManagerSenders {
     /// sender for communicating with constellation
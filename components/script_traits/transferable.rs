@@ -8,8 +8,11 @@
 //! to depend on script.
 
 use crate::PortMessageTask;
+use canvas_traits::canvas::{CanvasId, CanvasMsg};
+use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::MessagePortId;
 use std::collections::VecDeque;
+use std::fmt;
 
 #[derive(Debug, Deserialize, MallocSizeOf, Serialize)]
 enum MessagePortState {
@@ -158,3 +161,60 @@ impl MessagePortImpl {
         self.state = MessagePortState::Detached;
     }
 }
+
+/// The connection of an OffscreenCanvas to its placeholder canvas element,
+/// which can live in another event-loop.
+/// <https://html.spec.whatwg.org/multipage/#offscreencanvas-placeholder>
+#[derive(Clone, Deserialize, MallocSizeOf, Serialize)]
+pub struct OffscreenCanvasPlaceholder {
+    /// The paint thread rendering the bitmap displayed by the placeholder canvas element.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    pub ipc_renderer: IpcSender<CanvasMsg>,
+
+    /// The id of the canvas in the paint thread.
+    pub canvas_id: CanvasId,
+
+    /// Used to notify the placeholder canvas element that a new frame was committed.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    pub commit_chan: IpcSender<()>,
+}
+
+impl fmt::Debug for OffscreenCanvasPlaceholder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OffscreenCanvasPlaceholder({:?})", self.canvas_id)
+    }
+}
+
+/// The data backing a transferred OffscreenCanvas.
+/// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-steps>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct OffscreenCanvasImpl {
+    /// The width of the bitmap.
+    pub width: u64,
+
+    /// The height of the bitmap.
+    pub height: u64,
+
+    /// The placeholder canvas element, if any.
+    pub placeholder: Option<OffscreenCanvasPlaceholder>,
+}
+
+/// The data backing a transferred ImageBitmap.
+/// <https://html.spec.whatwg.org/multipage/#the-imagebitmap-interface:transfer-steps>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ImageBitmapImpl {
+    /// The width of the bitmap.
+    pub width: u32,
+
+    /// The height of the bitmap.
+    pub height: u32,
+
+    /// The bitmap data, as BGRA pixels.
+    pub bitmap_data: Vec<u8>,
+
+    /// Whether the colors of the bitmap data are premultiplied by their alpha.
+    pub alpha_premultiplied: bool,
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-imagebitmap-origin-clean>
+    pub origin_clean: bool,
+}
@@ -607,11 +607,20 @@ where
                 self.compositor.on_mouse_window_move_event_class(cursor);
             },
 
+            WindowEvent::MouseWindowMovement(movement) => {
+                self.compositor.on_mouse_window_movement(movement);
+            },
+
             WindowEvent::Touch(event_type, identifier, location) => {
                 self.compositor
                     .on_touch_event(event_type, identifier, location);
             },
 
+            WindowEvent::Pen(event_type, identifier, location, state) => {
+                self.compositor
+                    .on_pen_event(event_type, identifier, location, state);
+            },
+
             WindowEvent::Wheel(delta, location) => {
                 self.compositor.on_wheel_event(delta, location);
             },
@@ -658,6 +667,26 @@ where
                 }
             },
 
+            WindowEvent::ChangeScreenOrientation(orientation) => {
+                let msg = ConstellationMsg::ChangeScreenOrientation(orientation);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending screen orientation change to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::ExitPointerLock(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::ExitPointerLock(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending exit pointer lock to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             WindowEvent::Reload(top_level_browsing_context_id) => {
                 let msg = ConstellationMsg::Reload(top_level_browsing_context_id);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -705,6 +734,16 @@ where
                 }
             },
 
+            WindowEvent::ChangeBrowserVisibility(ctx, visible) => {
+                let msg = ConstellationMsg::ChangeBrowserVisibility(ctx, visible);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ChangeBrowserVisibility message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             WindowEvent::CloseBrowser(ctx) => {
                 let msg = ConstellationMsg::CloseBrowser(ctx);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -734,6 +773,13 @@ where
                     );
                 }
             },
+
+            WindowEvent::Gamepad(event) => {
+                let msg = ConstellationMsg::Gamepad(event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending gamepad event to constellation failed ({:?}).", e);
+                }
+            },
         }
     }
 
@@ -874,7 +920,7 @@ fn create_constellation(
 
     let resource_sender = public_resource_threads.sender();
 
-    let webgpu = webgpu::WebGPU::new();
+    let webgpu = webgpu::WebGPU::new(webrender_api_sender.clone());
 
     let initial_state = InitialConstellationState {
         compositor_proxy,
@@ -199,6 +199,210 @@ impl fmt::Debug for KeyframesAnimationState {
     }
 }
 
+/// The timing of an animation that script created, in seconds.
+///
+/// https://drafts.csswg.org/web-animations/#the-effecttiming-dictionaries
+#[derive(Clone, Debug)]
+pub struct ScriptAnimationTiming {
+    /// The delay before the animation starts.
+    pub delay: f64,
+    /// The delay after the animation ends.
+    pub end_delay: f64,
+    /// Whether the animation applies before it starts.
+    pub fill_backwards: bool,
+    /// Whether the animation applies after it ends.
+    pub fill_forwards: bool,
+    /// The iteration the animation starts at, which can be a fraction.
+    pub iteration_start: f64,
+    /// The number of iterations, which can be infinite.
+    pub iterations: f64,
+    /// The duration of a single iteration.
+    pub duration: f64,
+    /// The direction of the iterations.
+    pub direction: AnimationDirection,
+    /// The timing function applied to the progress of each iteration.
+    pub easing: TimingFunction,
+}
+
+impl ScriptAnimationTiming {
+    /// https://drafts.csswg.org/web-animations/#active-duration
+    pub fn active_duration(&self) -> f64 {
+        if self.duration == 0. || self.iterations == 0. {
+            0.
+        } else {
+            self.duration * self.iterations
+        }
+    }
+
+    /// https://drafts.csswg.org/web-animations/#end-time
+    pub fn end_time(&self) -> f64 {
+        (self.delay + self.active_duration() + self.end_delay).max(0.)
+    }
+
+    /// The transformed progress of the animation at the given local time and
+    /// its current iteration, or None if the animation doesn't apply at that
+    /// time. `backwards` is whether the animation plays backwards.
+    ///
+    /// https://drafts.csswg.org/web-animations/#calculating-the-transformed-progress
+    pub fn progress(&self, local_time: f64, backwards: bool) -> Option<(f64, f64)> {
+        let active_duration = self.active_duration();
+        let end_time = self.end_time();
+        let before_active_boundary = self.delay.min(end_time).max(0.);
+        let active_after_boundary = (self.delay + active_duration).min(end_time).max(0.);
+
+        // https://drafts.csswg.org/web-animations/#animation-effect-phases-and-states
+        let before = local_time < before_active_boundary ||
+            (backwards && local_time == before_active_boundary);
+        let after = local_time > active_after_boundary ||
+            (!backwards && local_time == active_after_boundary);
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-active-time
+        let active_time = if before {
+            if !self.fill_backwards {
+                return None;
+            }
+            (local_time - self.delay).max(0.)
+        } else if after {
+            if !self.fill_forwards {
+                return None;
+            }
+            (local_time - self.delay).min(active_duration).max(0.)
+        } else {
+            local_time - self.delay
+        };
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-overall-progress
+        let overall_progress = if self.duration == 0. {
+            if before {
+                0.
+            } else {
+                self.iterations
+            }
+        } else {
+            active_time / self.duration
+        } + self.iteration_start;
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-simple-iteration-progress
+        let mut simple_progress = if overall_progress.is_infinite() {
+            self.iteration_start % 1.
+        } else {
+            overall_progress % 1.
+        };
+        if simple_progress == 0. &&
+            !before &&
+            active_time == active_duration &&
+            self.iterations != 0.
+        {
+            simple_progress = 1.;
+        }
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-current-iteration
+        let current_iteration = if after && self.iterations.is_infinite() {
+            f64::INFINITY
+        } else if simple_progress == 1. {
+            overall_progress.floor() - 1.
+        } else {
+            overall_progress.floor()
+        };
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-directed-progress
+        let odd_iteration = current_iteration.is_finite() && current_iteration % 2. != 0.;
+        let forwards = match self.direction {
+            AnimationDirection::Normal => true,
+            AnimationDirection::Reverse => false,
+            AnimationDirection::Alternate => !odd_iteration,
+            AnimationDirection::AlternateReverse => odd_iteration,
+        };
+        let directed_progress = if forwards {
+            simple_progress
+        } else {
+            1. - simple_progress
+        };
+
+        let epsilon = 1. / (200. * self.duration.max(0.001));
+        let transformed_progress = timing_function_output(&self.easing, directed_progress, epsilon);
+        Some((transformed_progress, current_iteration))
+    }
+}
+
+/// The state of an animation that script created, and that it sends to
+/// layout each time it changes.
+#[derive(Clone)]
+pub struct ScriptAnimationState {
+    /// The timing of the animation.
+    pub timing: ScriptAnimationTiming,
+    /// The local time of the animation when script sent it, in seconds.
+    pub current_time: f64,
+    /// The rate the local time of the animation advances at.
+    pub playback_rate: f64,
+    /// Whether the animation is paused, in which case its local time doesn't
+    /// advance.
+    pub paused: bool,
+    /// The time of the layout timer when layout received this state, from
+    /// which the local time advances.
+    pub synced_at: Option<f64>,
+    /// Whether script cancelled this animation, or replaced its effect.
+    pub expired: bool,
+    /// The style of the node without the animation, which the keyframes
+    /// that don't specify a property take its value from. Layout fills it.
+    pub cascade_style: Option<Arc<ComputedValues>>,
+}
+
+impl ScriptAnimationState {
+    /// The local time of the animation at the given time of the layout timer.
+    pub fn local_time(&self, now: f64) -> f64 {
+        match self.synced_at {
+            Some(synced_at) if !self.paused => {
+                self.current_time + (now - synced_at) * self.playback_rate
+            },
+            _ => self.current_time,
+        }
+    }
+
+    /// Whether the animation reached its end, or its start if it plays
+    /// backwards, at the given time of the layout timer.
+    pub fn is_finished(&self, now: f64) -> bool {
+        let local_time = self.local_time(now);
+        if self.playback_rate > 0. {
+            local_time >= self.timing.end_time()
+        } else if self.playback_rate < 0. {
+            local_time <= 0.
+        } else {
+            false
+        }
+    }
+
+    /// Whether the animation still needs to be applied to the style of its
+    /// node at the given time of the layout timer.
+    pub fn is_running(&self, now: f64) -> bool {
+        if self.expired {
+            return false;
+        }
+        if !self.is_finished(now) {
+            return true;
+        }
+        if self.playback_rate < 0. {
+            self.timing.fill_backwards
+        } else {
+            self.timing.fill_forwards
+        }
+    }
+}
+
+impl fmt::Debug for ScriptAnimationState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScriptAnimationState")
+            .field("timing", &self.timing)
+            .field("current_time", &self.current_time)
+            .field("playback_rate", &self.playback_rate)
+            .field("paused", &self.paused)
+            .field("synced_at", &self.synced_at)
+            .field("expired", &self.expired)
+            .field("cascade_style", &())
+            .finish()
+    }
+}
+
 /// State relating to an animation.
 #[derive(Clone, Debug)]
 pub enum Animation {
@@ -216,6 +420,9 @@ pub enum Animation {
         Atom,
         KeyframesAnimationState,
     ),
+    /// An animation that script created with `Element.animate()`, identified
+    /// by an id that script gives it.
+    Script(OpaqueNode, KeyframesAnimation, u64, ScriptAnimationState),
 }
 
 impl Animation {
@@ -225,6 +432,7 @@ impl Animation {
         match *self {
             Animation::Transition(..) => false,
             Animation::Keyframes(_, _, _, ref state) => state.expired,
+            Animation::Script(_, _, _, ref state) => state.expired,
         }
     }
 
@@ -234,6 +442,7 @@ impl Animation {
         match *self {
             Animation::Transition(ref node, _, _) => node,
             Animation::Keyframes(ref node, _, _, _) => node,
+            Animation::Script(ref node, _, _, _) => node,
         }
     }
 
@@ -242,7 +451,7 @@ impl Animation {
     pub fn is_transition(&self) -> bool {
         match *self {
             Animation::Transition(..) => true,
-            Animation::Keyframes(..) => false,
+            Animation::Keyframes(..) | Animation::Script(..) => false,
         }
     }
 }
@@ -342,51 +551,7 @@ impl PropertyAnimation {
     /// Update the given animation at a given point of progress.
     pub fn update(&self, style: &mut ComputedValues, time: f64) {
         let epsilon = 1. / (200. * (self.duration.seconds() as f64));
-        let progress = match self.timing_function {
-            GenericTimingFunction::CubicBezier { x1, y1, x2, y2 } => {
-                Bezier::new(x1, y1, x2, y2).solve(time, epsilon)
-            },
-            GenericTimingFunction::Steps(steps, pos) => {
-                let mut current_step = (time * (steps as f64)).floor() as i32;
-
-                if pos == StepPosition::Start ||
-                    pos == StepPosition::JumpStart ||
-                    pos == StepPosition::JumpBoth
-                {
-                    current_step = current_step + 1;
-                }
-
-                // FIXME: We should update current_step according to the "before flag".
-                // In order to get the before flag, we have to know the current animation phase
-                // and whether the iteration is reversed. For now, we skip this calculation.
-                // (i.e. Treat before_flag is unset,)
-                // https://drafts.csswg.org/css-easing/#step-timing-function-algo
-
-                if time >= 0.0 && current_step < 0 {
-                    current_step = 0;
-                }
-
-                let jumps = match pos {
-                    StepPosition::JumpBoth => steps + 1,
-                    StepPosition::JumpNone => steps - 1,
-                    StepPosition::JumpStart |
-                    StepPosition::JumpEnd |
-                    StepPosition::Start |
-                    StepPosition::End => steps,
-                };
-
-                if time <= 1.0 && current_step > jumps {
-                    current_step = jumps;
-                }
-
-                (current_step as f64) / (jumps as f64)
-            },
-            GenericTimingFunction::Keyword(keyword) => {
-                let (x1, x2, y1, y2) = keyword.to_bezier();
-                Bezier::new(x1, x2, y1, y2).solve(time, epsilon)
-            },
-        };
-
+        let progress = timing_function_output(&self.timing_function, time, epsilon);
         self.property.update(style, progress);
     }
 
@@ -402,6 +567,54 @@ impl PropertyAnimation {
     }
 }
 
+/// Applies a timing function to the given input progress.
+fn timing_function_output(timing_function: &TimingFunction, time: f64, epsilon: f64) -> f64 {
+    match *timing_function {
+        GenericTimingFunction::CubicBezier { x1, y1, x2, y2 } => {
+            Bezier::new(x1, y1, x2, y2).solve(time, epsilon)
+        },
+        GenericTimingFunction::Steps(steps, pos) => {
+            let mut current_step = (time * (steps as f64)).floor() as i32;
+
+            if pos == StepPosition::Start ||
+                pos == StepPosition::JumpStart ||
+                pos == StepPosition::JumpBoth
+            {
+                current_step = current_step + 1;
+            }
+
+            // FIXME: We should update current_step according to the "before flag".
+            // In order to get the before flag, we have to know the current animation phase
+            // and whether the iteration is reversed. For now, we skip this calculation.
+            // (i.e. Treat before_flag is unset,)
+            // https://drafts.csswg.org/css-easing/#step-timing-function-algo
+
+            if time >= 0.0 && current_step < 0 {
+                current_step = 0;
+            }
+
+            let jumps = match pos {
+                StepPosition::JumpBoth => steps + 1,
+                StepPosition::JumpNone => steps - 1,
+                StepPosition::JumpStart |
+                StepPosition::JumpEnd |
+                StepPosition::Start |
+                StepPosition::End => steps,
+            };
+
+            if time <= 1.0 && current_step > jumps {
+                current_step = jumps;
+            }
+
+            (current_step as f64) / (jumps as f64)
+        },
+        GenericTimingFunction::Keyword(keyword) => {
+            let (x1, x2, y1, y2) = keyword.to_bezier();
+            Bezier::new(x1, x2, y1, y2).solve(time, epsilon)
+        },
+    }
+}
+
 /// Inserts transitions into the queue of running animations as applicable for
 /// the given style difference. This is called from the layout worker threads.
 /// Returns true if any animations were kicked off and false otherwise.
@@ -843,6 +1056,63 @@ where
             *style = new_style;
             AnimationUpdate::Regular
         },
+        Animation::Script(_, ref animation, _, ref state) => {
+            let backwards = state.playback_rate < 0.;
+            let local_time = state.local_time(context.timer.seconds());
+            let progress = match state.timing.progress(local_time, backwards) {
+                Some((progress, _)) => progress,
+                None => return AnimationUpdate::Regular,
+            };
+
+            debug_assert!(animation.steps.len() >= 2);
+
+            // The keyframes to interpolate between, which are the first or the
+            // last two when the timing function takes the progress out of
+            // the [0, 1] range.
+            let target_position = animation
+                .steps
+                .iter()
+                .skip(1)
+                .position(|step| progress <= step.start_percentage.0 as f64)
+                .map_or(animation.steps.len() - 1, |position| position + 1);
+            let last_keyframe = &animation.steps[target_position - 1];
+            let target_keyframe = &animation.steps[target_position];
+
+            let cascade_style = state.cascade_style.as_ref().unwrap_or(style);
+            let from_style = compute_style_for_animation_step::<E>(
+                context,
+                last_keyframe,
+                &**style,
+                cascade_style,
+                font_metrics_provider,
+            );
+            let target_style = compute_style_for_animation_step::<E>(
+                context,
+                target_keyframe,
+                &from_style,
+                cascade_style,
+                font_metrics_provider,
+            );
+
+            let relative_timespan =
+                (target_keyframe.start_percentage.0 - last_keyframe.start_percentage.0) as f64;
+            let relative_progress = if relative_timespan == 0. {
+                1.
+            } else {
+                (progress - last_keyframe.start_percentage.0 as f64) / relative_timespan
+            };
+
+            let mut new_style = (*style).clone();
+            for property in animation.properties_changed.iter() {
+                if let Some(property) =
+                    AnimatedProperty::from_longhand(property, &from_style, &target_style)
+                {
+                    property.update(Arc::make_mut(&mut new_style), relative_progress);
+                }
+            }
+            *style = new_style;
+            AnimationUpdate::Regular
+        },
     }
 }
 
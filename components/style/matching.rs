@@ -451,6 +451,22 @@ trait PrivateMatchMethods: TElement {
 
         let new_animations_sender = &context.thread_local.new_animations_sender;
         let this_opaque = self.as_node().opaque();
+
+        // The animations that script created take the values that their
+        // keyframes don't specify from the new style.
+        let had_running_animations = shared_context
+            .running_animations
+            .read()
+            .contains_key(&this_opaque);
+        if had_running_animations {
+            let mut all_running_animations = shared_context.running_animations.write();
+            for animation in all_running_animations.get_mut(&this_opaque).unwrap() {
+                if let animation::Animation::Script(_, _, _, ref mut state) = *animation {
+                    state.cascade_style = Some(new_values.clone());
+                }
+            }
+        }
+
         // Trigger any present animations if necessary.
         animation::maybe_start_animations(
             *self,
@@ -620,9 +636,15 @@ trait PrivateMatchMethods: TElement {
 
         let mut all_running_animations = context.running_animations.write();
         for mut running_animation in all_running_animations.get_mut(&this_opaque).unwrap() {
-            if let Animation::Transition(_, _, ref frame) = *running_animation {
-                possibly_expired_animations.push(frame.property_animation.clone());
-                continue;
+            match *running_animation {
+                Animation::Transition(_, _, ref frame) => {
+                    possibly_expired_animations.push(frame.property_animation.clone());
+                    continue;
+                },
+                // Script decides when these end, and layout applies them to
+                // the new style.
+                Animation::Script(..) => continue,
+                Animation::Keyframes(..) => {},
             }
 
             let update = animation::update_style_for_animation::<Self>(
@@ -633,7 +655,7 @@ trait PrivateMatchMethods: TElement {
             );
 
             match *running_animation {
-                Animation::Transition(..) => unreachable!(),
+                Animation::Transition(..) | Animation::Script(..) => unreachable!(),
                 Animation::Keyframes(_, _, _, ref mut state) => match update {
                     AnimationUpdate::Regular => {},
                     AnimationUpdate::AnimationCanceled => {
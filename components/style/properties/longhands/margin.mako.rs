@@ -33,7 +33,7 @@
         "scroll-margin-%s" % side[0],
         "Length",
         "computed::Length::zero()",
-        engines="gecko",
+        engines="gecko servo-2013 servo-2020",
         logical=side[1],
         logical_group="scroll-margin",
         spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-margin-%s" % side[0],
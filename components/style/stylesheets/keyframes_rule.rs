@@ -160,6 +160,12 @@ impl KeyframeSelector {
         &self.0
     }
 
+    /// Creates a selector for the given percentages, such as the offsets of
+    /// the keyframes that script gives to `Element.animate()`.
+    pub fn new(percentages: Vec<KeyframePercentage>) -> KeyframeSelector {
+        KeyframeSelector(percentages)
+    }
+
     /// A dummy public function so we can write a unit test for this.
     pub fn new_for_unit_testing(percentages: Vec<KeyframePercentage>) -> KeyframeSelector {
         KeyframeSelector(percentages)
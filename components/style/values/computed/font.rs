@@ -83,6 +83,7 @@ impl ToAnimatedValue for FontWeight {
     ToCss,
     ToResolvedValue,
 )]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
 /// The computed value of font-size
 pub struct FontSize {
     /// The size.
@@ -180,7 +181,7 @@ impl ToAnimatedValue for FontSize {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, ToResolvedValue)]
-#[cfg_attr(feature = "servo", derive(Hash, MallocSizeOf))]
+#[cfg_attr(feature = "servo", derive(Deserialize, Hash, MallocSizeOf, Serialize))]
 /// Specifies a prioritized list of font family names or generic family names.
 pub struct FontFamily {
     /// The actual list of family names.
@@ -423,7 +424,18 @@ impl SingleFontFamily {
 }
 
 #[cfg(feature = "servo")]
-#[derive(Clone, Debug, Eq, Hash, MallocSizeOf, PartialEq, ToResolvedValue, ToShmem)]
+#[derive(
+    Clone,
+    Debug,
+    Deserialize,
+    Eq,
+    Hash,
+    MallocSizeOf,
+    PartialEq,
+    Serialize,
+    ToResolvedValue,
+    ToShmem,
+)]
 /// A list of SingleFontFamily
 pub struct FontFamilyList(Box<[SingleFontFamily]>);
 
@@ -12,7 +12,24 @@ pub extern crate wgpu_native as wgpu;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use servo_config::pref;
-use wgpu::{adapter_get_info, adapter_request_device};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ptr;
+use std::sync::{mpsc, Arc};
+use wgpu::{
+    adapter_get_info, adapter_request_device, buffer_destroy, buffer_map_async, buffer_unmap,
+    command_encoder_begin_compute_pass, command_encoder_begin_render_pass,
+    command_encoder_copy_texture_to_buffer, command_encoder_finish, compute_pass_dispatch,
+    compute_pass_end_pass, compute_pass_set_pipeline, device_create_bind_group_layout,
+    device_create_buffer, device_create_command_encoder, device_create_compute_pipeline,
+    device_create_pipeline_layout, device_create_render_pipeline, device_create_shader_module,
+    device_create_texture, device_poll, queue_submit, render_pass_draw, render_pass_end_pass,
+    render_pass_set_blend_color, render_pass_set_pipeline, render_pass_set_scissor_rect,
+    render_pass_set_viewport, texture_create_view, texture_destroy, BindGroupLayoutBinding,
+    BindGroupLayoutId, BufferId, CommandBufferId, CommandEncoderId, ComputePassId,
+    ComputePipelineId, PipelineLayoutId, RenderPassId, RenderPipelineId, ShaderModuleId, TextureId,
+    TextureViewId,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebGPUResponse {
@@ -22,6 +39,59 @@ pub enum WebGPUResponse {
 
 pub type WebGPUResponseResult = Result<WebGPUResponse, String>;
 
+/// A command of a compute pass, recorded on the content side and replayed
+/// on the WGPU thread once the pass ends.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum ComputePassCommand {
+    SetPipeline(ComputePipelineId),
+    Dispatch(u32, u32, u32),
+}
+
+/// A command of a render pass or a render bundle, recorded on the content
+/// side and replayed on the WGPU thread once the pass ends.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RenderPassCommand {
+    SetPipeline(RenderPipelineId),
+    SetBlendColor(wgpu::Color),
+    SetViewport(f32, f32, f32, f32, f32, f32),
+    SetScissorRect(u32, u32, u32, u32),
+    Draw(u32, u32, u32, u32),
+}
+
+/// A programmable stage, whose entry point is turned into a C string on the
+/// WGPU thread.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProgrammableStage {
+    pub module: ShaderModuleId,
+    pub entry_point: String,
+}
+
+/// The owned parts of a `wgpu::RenderPipelineDescriptor`, which is built on
+/// the WGPU thread.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RenderPipelineDescriptor {
+    pub layout: PipelineLayoutId,
+    pub vertex_stage: ProgrammableStage,
+    pub fragment_stage: Option<ProgrammableStage>,
+    pub primitive_topology: wgpu::PrimitiveTopology,
+    pub rasterization_state: wgpu::RasterizationStateDescriptor,
+    pub color_states: Vec<wgpu::ColorStateDescriptor>,
+    pub index_format: wgpu::IndexFormat,
+    pub sample_count: u32,
+    pub sample_mask: u32,
+    pub alpha_to_coverage_enabled: bool,
+}
+
+/// The owned parts of a `wgpu::RenderPassColorAttachmentDescriptor`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RenderPassColorAttachment {
+    pub attachment: TextureViewId,
+    pub resolve_target: Option<TextureViewId>,
+    pub load_op: wgpu::LoadOp,
+    pub store_op: wgpu::StoreOp,
+    pub clear_color: wgpu::Color,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebGPURequest {
     RequestAdapter(
@@ -35,6 +105,74 @@ pub enum WebGPURequest {
         wgpu::DeviceDescriptor,
         wgpu::DeviceId,
     ),
+    CreateBindGroupLayout(
+        IpcSender<WebGPUBindGroupLayout>,
+        WebGPUDevice,
+        BindGroupLayoutId,
+        Vec<BindGroupLayoutBinding>,
+    ),
+    CreatePipelineLayout(
+        IpcSender<WebGPUPipelineLayout>,
+        WebGPUDevice,
+        PipelineLayoutId,
+        Vec<BindGroupLayoutId>,
+    ),
+    CreateShaderModule(
+        IpcSender<WebGPUShaderModule>,
+        WebGPUDevice,
+        ShaderModuleId,
+        Vec<u32>,
+    ),
+    CreateComputePipeline(
+        IpcSender<WebGPUComputePipeline>,
+        WebGPUDevice,
+        ComputePipelineId,
+        PipelineLayoutId,
+        ShaderModuleId,
+        String,
+    ),
+    CreateRenderPipeline(
+        IpcSender<WebGPURenderPipeline>,
+        WebGPUDevice,
+        RenderPipelineId,
+        RenderPipelineDescriptor,
+    ),
+    CreateTexture(
+        IpcSender<WebGPUTexture>,
+        WebGPUDevice,
+        TextureId,
+        wgpu::TextureDescriptor,
+    ),
+    CreateTextureView(
+        IpcSender<WebGPUTextureView>,
+        WebGPUTexture,
+        TextureViewId,
+        wgpu::TextureViewDescriptor,
+    ),
+    DestroyTexture(WebGPUTexture),
+    CreateCommandEncoder(
+        IpcSender<WebGPUCommandEncoder>,
+        WebGPUDevice,
+        CommandEncoderId,
+    ),
+    RunComputePass(WebGPUCommandEncoder, ComputePassId, Vec<ComputePassCommand>),
+    RunRenderPass(
+        WebGPUCommandEncoder,
+        RenderPassId,
+        Vec<RenderPassColorAttachment>,
+        Vec<RenderPassCommand>,
+    ),
+    CommandEncoderFinish(IpcSender<WebGPUCommandBuffer>, WebGPUCommandEncoder),
+    Submit(WebGPUQueue, Vec<CommandBufferId>),
+    CreateSwapChain(
+        IpcSender<webrender_api::ImageKey>,
+        WebGPUDevice,
+        BufferId,
+        u32,
+        u32,
+    ),
+    SwapChainPresent(webrender_api::ImageKey, WebGPUTexture, CommandEncoderId),
+    DestroySwapChain(webrender_api::ImageKey),
     Exit(IpcSender<()>),
 }
 
@@ -42,7 +180,7 @@ pub enum WebGPURequest {
 pub struct WebGPU(pub IpcSender<WebGPURequest>);
 
 impl WebGPU {
-    pub fn new() -> Option<Self> {
+    pub fn new(webrender_api_sender: webrender_api::RenderApiSender) -> Option<Self> {
         if !pref!(dom.webgpu.enabled) {
             return None;
         }
@@ -60,7 +198,7 @@ impl WebGPU {
         if let Err(e) = std::thread::Builder::new()
             .name("WGPU".to_owned())
             .spawn(move || {
-                WGPU::new(receiver).run();
+                WGPU::new(receiver, webrender_api_sender).run();
             })
         {
             warn!("Failed to spwan WGPU thread ({})", e);
@@ -76,24 +214,108 @@ impl WebGPU {
     }
 }
 
+/// The readback buffer and the WebRender image of a canvas swap chain, whose
+/// texture is copied into the image whenever it is presented.
+struct PresentationData {
+    device: WebGPUDevice,
+    buffer: BufferId,
+    image_descriptor: webrender_api::ImageDescriptor,
+}
+
 struct WGPU {
     receiver: IpcReceiver<WebGPURequest>,
     global: wgpu::Global,
     adapters: Vec<WebGPUAdapter>,
     // Track invalid adapters https://gpuweb.github.io/gpuweb/#invalid
     _invalid_adapters: Vec<WebGPUAdapter>,
+    webrender_api: webrender_api::RenderApi,
+    present_data: HashMap<webrender_api::ImageKey, PresentationData>,
 }
 
 impl WGPU {
-    fn new(receiver: IpcReceiver<WebGPURequest>) -> Self {
+    fn new(
+        receiver: IpcReceiver<WebGPURequest>,
+        webrender_api_sender: webrender_api::RenderApiSender,
+    ) -> Self {
         WGPU {
             receiver,
             global: wgpu::Global::new("webgpu-native"),
             adapters: Vec::new(),
             _invalid_adapters: Vec::new(),
+            webrender_api: webrender_api_sender.create_api(),
+            present_data: HashMap::new(),
         }
     }
 
+    /// Copies `texture` into the WebRender image of the swap chain of
+    /// `image_key`, through its readback buffer.
+    fn present(
+        &self,
+        image_key: webrender_api::ImageKey,
+        texture: WebGPUTexture,
+        encoder_id: CommandEncoderId,
+    ) {
+        let data = match self.present_data.get(&image_key) {
+            Some(data) => data,
+            None => return warn!("Unknown WebGPU swap chain {:?}", image_key),
+        };
+        let (device, buffer) = (data.device.0, data.buffer);
+        let size = data.image_descriptor.size;
+        let row_pitch = data.image_descriptor.stride.unwrap() as u32;
+        let buffer_size = (row_pitch * size.height as u32) as wgpu::BufferAddress;
+
+        let descriptor = wgpu::CommandEncoderDescriptor { todo: 0 };
+        let encoder = gfx_select!(encoder_id => device_create_command_encoder(&self.global, device, &descriptor, encoder_id));
+        let source = wgpu::TextureCopyView {
+            texture: texture.0,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d::ZERO,
+        };
+        let destination = wgpu::BufferCopyView {
+            buffer,
+            offset: 0,
+            row_pitch,
+            image_height: size.height as u32,
+        };
+        let copy_size = wgpu::Extent3d {
+            width: size.width as u32,
+            height: size.height as u32,
+            depth: 1,
+        };
+        gfx_select!(encoder => command_encoder_copy_texture_to_buffer(&self.global, encoder, &source, &destination, copy_size));
+        let descriptor = wgpu::CommandBufferDescriptor { todo: 0 };
+        let command_buffer =
+            gfx_select!(encoder => command_encoder_finish(&self.global, encoder, &descriptor));
+        gfx_select!(device => queue_submit(&self.global, device, &[command_buffer]));
+
+        // Mapping completes once the device is polled until the copy is done.
+        let (sender, receiver) = mpsc::channel();
+        let operation = wgpu::BufferMapOperation::Read(
+            0..buffer_size,
+            Box::new(move |result: wgpu::BufferMapAsyncResult<&[u8]>| {
+                let _ = sender.send(result.map(|pixels| pixels.to_vec()));
+            }),
+        );
+        gfx_select!(buffer => buffer_map_async(&self.global, buffer, wgpu::BufferUsage::MAP_READ, operation));
+        gfx_select!(device => device_poll(&self.global, device, true));
+        let pixels = receiver.try_recv();
+        gfx_select!(buffer => buffer_unmap(&self.global, buffer));
+        let pixels = match pixels {
+            Ok(Ok(pixels)) => pixels,
+            _ => return warn!("Failed to read back WebGPU swap chain {:?}", image_key),
+        };
+
+        let mut txn = webrender_api::Transaction::new();
+        txn.update_image(
+            image_key,
+            data.image_descriptor,
+            webrender_api::ImageData::Raw(Arc::new(pixels)),
+            &webrender_api::DirtyRect::All,
+        );
+        self.webrender_api.update_resources(txn.resource_updates);
+    }
+
     fn deinit(self) {
         self.global.delete()
     }
@@ -141,6 +363,285 @@ impl WGPU {
                         )
                     }
                 },
+                WebGPURequest::CreateBindGroupLayout(sender, device, id, bindings) => {
+                    let descriptor = wgpu::BindGroupLayoutDescriptor {
+                        bindings: bindings.as_ptr(),
+                        bindings_length: bindings.len(),
+                    };
+                    let bgl_id = gfx_select!(id => device_create_bind_group_layout(&self.global, device.0, &descriptor, id));
+                    let bgl = WebGPUBindGroupLayout(bgl_id);
+                    if let Err(e) = sender.send(bgl) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateBindGroupLayout ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::CreatePipelineLayout(sender, device, id, bind_group_layouts) => {
+                    let descriptor = wgpu::PipelineLayoutDescriptor {
+                        bind_group_layouts: bind_group_layouts.as_ptr(),
+                        bind_group_layouts_length: bind_group_layouts.len(),
+                    };
+                    let pl_id = gfx_select!(id => device_create_pipeline_layout(&self.global, device.0, &descriptor, id));
+                    let pipeline_layout = WebGPUPipelineLayout(pl_id);
+                    if let Err(e) = sender.send(pipeline_layout) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreatePipelineLayout ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::CreateShaderModule(sender, device, id, program) => {
+                    let descriptor = wgpu::ShaderModuleDescriptor {
+                        code: wgpu::U32Array {
+                            bytes: program.as_ptr(),
+                            length: program.len(),
+                        },
+                    };
+                    let sm_id = gfx_select!(id => device_create_shader_module(&self.global, device.0, &descriptor, id));
+                    let shader_module = WebGPUShaderModule(sm_id);
+                    if let Err(e) = sender.send(shader_module) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateShaderModule ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::CreateComputePipeline(
+                    sender,
+                    device,
+                    id,
+                    layout,
+                    module,
+                    entry_point,
+                ) => {
+                    // Entry points with interior nul bytes are rejected on the content side.
+                    let entry_point = CString::new(entry_point).unwrap_or_default();
+                    let descriptor = wgpu::ComputePipelineDescriptor {
+                        layout,
+                        compute_stage: wgpu::ProgrammableStageDescriptor {
+                            module,
+                            entry_point: entry_point.as_ptr(),
+                        },
+                    };
+                    let cp_id = gfx_select!(id => device_create_compute_pipeline(&self.global, device.0, &descriptor, id));
+                    let compute_pipeline = WebGPUComputePipeline(cp_id);
+                    if let Err(e) = sender.send(compute_pipeline) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateComputePipeline ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::CreateRenderPipeline(sender, device, id, descriptor) => {
+                    // Entry points with interior nul bytes are rejected on the content side.
+                    let vertex_entry_point =
+                        CString::new(descriptor.vertex_stage.entry_point).unwrap_or_default();
+                    let fragment_entry_point = descriptor
+                        .fragment_stage
+                        .as_ref()
+                        .map(|stage| CString::new(stage.entry_point.clone()).unwrap_or_default());
+                    let fragment_stage = descriptor.fragment_stage.as_ref().map(|stage| {
+                        wgpu::ProgrammableStageDescriptor {
+                            module: stage.module,
+                            entry_point: fragment_entry_point.as_ref().unwrap().as_ptr(),
+                        }
+                    });
+                    let wgpu_descriptor = wgpu::RenderPipelineDescriptor {
+                        layout: descriptor.layout,
+                        vertex_stage: wgpu::ProgrammableStageDescriptor {
+                            module: descriptor.vertex_stage.module,
+                            entry_point: vertex_entry_point.as_ptr(),
+                        },
+                        fragment_stage: fragment_stage
+                            .as_ref()
+                            .map_or(ptr::null(), |stage| stage as *const _),
+                        primitive_topology: descriptor.primitive_topology,
+                        rasterization_state: &descriptor.rasterization_state,
+                        color_states: descriptor.color_states.as_ptr(),
+                        color_states_length: descriptor.color_states.len(),
+                        depth_stencil_state: ptr::null(),
+                        vertex_input: wgpu::VertexInputDescriptor {
+                            index_format: descriptor.index_format,
+                            vertex_buffers: ptr::null(),
+                            vertex_buffers_length: 0,
+                        },
+                        sample_count: descriptor.sample_count,
+                        sample_mask: descriptor.sample_mask,
+                        alpha_to_coverage_enabled: descriptor.alpha_to_coverage_enabled,
+                    };
+                    let rp_id = gfx_select!(id => device_create_render_pipeline(&self.global, device.0, &wgpu_descriptor, id));
+                    let render_pipeline = WebGPURenderPipeline(rp_id);
+                    if let Err(e) = sender.send(render_pipeline) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateRenderPipeline ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::CreateTexture(sender, device, id, descriptor) => {
+                    let texture_id = gfx_select!(id => device_create_texture(&self.global, device.0, &descriptor, id));
+                    let texture = WebGPUTexture(texture_id);
+                    if let Err(e) = sender.send(texture) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateTexture ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::CreateTextureView(sender, texture, id, descriptor) => {
+                    let view_id = gfx_select!(id => texture_create_view(&self.global, texture.0, Some(&descriptor), id));
+                    let texture_view = WebGPUTextureView(view_id);
+                    if let Err(e) = sender.send(texture_view) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateTextureView ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::DestroyTexture(texture) => {
+                    gfx_select!(texture.0 => texture_destroy(&self.global, texture.0));
+                },
+                WebGPURequest::CreateCommandEncoder(sender, device, id) => {
+                    let descriptor = wgpu::CommandEncoderDescriptor { todo: 0 };
+                    let ce_id = gfx_select!(id => device_create_command_encoder(&self.global, device.0, &descriptor, id));
+                    let command_encoder = WebGPUCommandEncoder(ce_id);
+                    if let Err(e) = sender.send(command_encoder) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateCommandEncoder ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::RunComputePass(encoder, id, commands) => {
+                    let descriptor = wgpu::ComputePassDescriptor { todo: 0 };
+                    let pass_id = gfx_select!(id => command_encoder_begin_compute_pass(&self.global, encoder.0, &descriptor, id));
+                    for command in commands {
+                        match command {
+                            ComputePassCommand::SetPipeline(pipeline) => {
+                                gfx_select!(pass_id => compute_pass_set_pipeline(&self.global, pass_id, pipeline))
+                            },
+                            ComputePassCommand::Dispatch(x, y, z) => {
+                                gfx_select!(pass_id => compute_pass_dispatch(&self.global, pass_id, x, y, z))
+                            },
+                        }
+                    }
+                    gfx_select!(pass_id => compute_pass_end_pass(&self.global, pass_id));
+                },
+                WebGPURequest::RunRenderPass(encoder, id, color_attachments, commands) => {
+                    let color_attachments = color_attachments
+                        .iter()
+                        .map(|color| wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: color.attachment,
+                            resolve_target: color
+                                .resolve_target
+                                .as_ref()
+                                .map_or(ptr::null(), |view| view as *const _),
+                            load_op: color.load_op,
+                            store_op: color.store_op,
+                            clear_color: color.clear_color,
+                        })
+                        .collect::<Vec<_>>();
+                    let descriptor = wgpu::RenderPassDescriptor {
+                        color_attachments: color_attachments.as_ptr(),
+                        color_attachments_length: color_attachments.len(),
+                        depth_stencil_attachment: ptr::null(),
+                    };
+                    let pass_id = gfx_select!(id => command_encoder_begin_render_pass(&self.global, encoder.0, &descriptor, id));
+                    for command in commands {
+                        match command {
+                            RenderPassCommand::SetPipeline(pipeline) => {
+                                gfx_select!(pass_id => render_pass_set_pipeline(&self.global, pass_id, pipeline))
+                            },
+                            RenderPassCommand::SetBlendColor(color) => {
+                                gfx_select!(pass_id => render_pass_set_blend_color(&self.global, pass_id, &color))
+                            },
+                            RenderPassCommand::SetViewport(x, y, w, h, min_depth, max_depth) => {
+                                gfx_select!(pass_id => render_pass_set_viewport(&self.global, pass_id, x, y, w, h, min_depth, max_depth))
+                            },
+                            RenderPassCommand::SetScissorRect(x, y, w, h) => {
+                                gfx_select!(pass_id => render_pass_set_scissor_rect(&self.global, pass_id, x, y, w, h))
+                            },
+                            RenderPassCommand::Draw(
+                                vertex_count,
+                                instance_count,
+                                first_vertex,
+                                first_instance,
+                            ) => {
+                                gfx_select!(pass_id => render_pass_draw(&self.global, pass_id, vertex_count, instance_count, first_vertex, first_instance))
+                            },
+                        }
+                    }
+                    gfx_select!(pass_id => render_pass_end_pass(&self.global, pass_id));
+                },
+                WebGPURequest::CommandEncoderFinish(sender, encoder) => {
+                    let descriptor = wgpu::CommandBufferDescriptor { todo: 0 };
+                    let cb_id = gfx_select!(encoder.0 => command_encoder_finish(&self.global, encoder.0, &descriptor));
+                    let command_buffer = WebGPUCommandBuffer(cb_id);
+                    if let Err(e) = sender.send(command_buffer) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CommandEncoderFinish ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::Submit(queue, command_buffers) => {
+                    gfx_select!(queue.0 => queue_submit(&self.global, queue.0, &command_buffers));
+                },
+                WebGPURequest::CreateSwapChain(sender, device, buffer_id, width, height) => {
+                    // Rows of buffers that textures are copied into are
+                    // aligned to 256 bytes.
+                    let row_pitch = (width * 4 + 255) & !255;
+                    let buffer_size = row_pitch as usize * height as usize;
+                    let descriptor = wgpu::BufferDescriptor {
+                        size: buffer_size as wgpu::BufferAddress,
+                        usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+                    };
+                    let _ = gfx_select!(buffer_id => device_create_buffer(&self.global, device.0, &descriptor, buffer_id));
+
+                    let image_key = self.webrender_api.generate_image_key();
+                    let image_descriptor = webrender_api::ImageDescriptor {
+                        size: webrender_api::units::DeviceIntSize::new(width as i32, height as i32),
+                        stride: Some(row_pitch as i32),
+                        format: webrender_api::ImageFormat::BGRA8,
+                        offset: 0,
+                        is_opaque: false,
+                        allow_mipmaps: false,
+                    };
+                    let mut txn = webrender_api::Transaction::new();
+                    txn.add_image(
+                        image_key,
+                        image_descriptor,
+                        webrender_api::ImageData::Raw(Arc::new(vec![0; buffer_size])),
+                        None,
+                    );
+                    self.webrender_api.update_resources(txn.resource_updates);
+                    self.present_data.insert(
+                        image_key,
+                        PresentationData {
+                            device,
+                            buffer: buffer_id,
+                            image_descriptor,
+                        },
+                    );
+                    if let Err(e) = sender.send(image_key) {
+                        warn!(
+                            "Failed to send response to WebGPURequest::CreateSwapChain ({})",
+                            e
+                        )
+                    }
+                },
+                WebGPURequest::SwapChainPresent(image_key, texture, encoder_id) => {
+                    self.present(image_key, texture, encoder_id);
+                },
+                WebGPURequest::DestroySwapChain(image_key) => {
+                    if let Some(data) = self.present_data.remove(&image_key) {
+                        gfx_select!(data.buffer => buffer_destroy(&self.global, data.buffer));
+                        let mut txn = webrender_api::Transaction::new();
+                        txn.delete_image(image_key);
+                        self.webrender_api.update_resources(txn.resource_updates);
+                    }
+                },
                 WebGPURequest::Exit(sender) => {
                     self.deinit();
                     if let Err(e) = sender.send(()) {
@@ -170,3 +671,13 @@ macro_rules! webgpu_resource {
 
 webgpu_resource!(WebGPUAdapter, wgpu::AdapterId);
 webgpu_resource!(WebGPUDevice, wgpu::DeviceId);
+webgpu_resource!(WebGPUBindGroupLayout, wgpu::BindGroupLayoutId);
+webgpu_resource!(WebGPUPipelineLayout, wgpu::PipelineLayoutId);
+webgpu_resource!(WebGPUShaderModule, wgpu::ShaderModuleId);
+webgpu_resource!(WebGPUComputePipeline, wgpu::ComputePipelineId);
+webgpu_resource!(WebGPURenderPipeline, wgpu::RenderPipelineId);
+webgpu_resource!(WebGPUTexture, wgpu::TextureId);
+webgpu_resource!(WebGPUTextureView, wgpu::TextureViewId);
+webgpu_resource!(WebGPUCommandEncoder, wgpu::CommandEncoderId);
+webgpu_resource!(WebGPUCommandBuffer, wgpu::CommandBufferId);
+webgpu_resource!(WebGPUQueue, wgpu::QueueId);
@@ -7,6 +7,7 @@
 use crate::browser::Browser;
 use crate::embedder::EmbedderCallbacks;
 use crate::events_loop::EventsLoop;
+use crate::gamepad::GamepadSupport;
 use crate::window_trait::WindowPortsMethods;
 use crate::{headed_window, headless_window};
 use glutin::WindowId;
@@ -31,6 +32,7 @@ pub struct App {
     browser: RefCell<Browser<dyn WindowPortsMethods>>,
     event_queue: RefCell<Vec<WindowEvent>>,
     suspended: Cell<bool>,
+    gamepad: Option<GamepadSupport>,
 }
 
 impl App {
@@ -77,12 +79,21 @@ impl App {
 
         register_window(window);
 
+        // Gamepads are polled on their own thread, which wakes up the event loop.
+        let gamepad = if opts::get().headless {
+            None
+        } else {
+            let waker = events_loop.borrow().create_event_loop_waker();
+            Some(GamepadSupport::new(waker))
+        };
+
         let app = App {
             event_queue: RefCell::new(vec![]),
             events_loop,
             browser: RefCell::new(browser),
             servo: RefCell::new(servo),
             suspended: Cell::new(false),
+            gamepad,
         };
 
         app.run_loop();
@@ -188,6 +199,9 @@ impl App {
                 app_events.extend(window.get_events());
             }
         });
+        if let Some(ref gamepad) = self.gamepad {
+            app_events.extend(gamepad.get_events());
+        }
 
         // FIXME: this could be handled by Servo. We don't need
         // a repaint_synchronously function exposed.
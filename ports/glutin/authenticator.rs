@@ -0,0 +1,257 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A platform authenticator that keeps its credentials in memory, until
+//! the ones of the operating system and security keys are supported.
+
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::error::ErrorStack;
+use openssl::nid::Nid;
+use openssl::pkey::Private;
+use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
+use servo::embedder_traits::webauthn::{Authenticator, AuthenticatorAttachment};
+use servo::embedder_traits::webauthn::{AuthenticatorError, GetAssertionRequest};
+use servo::embedder_traits::webauthn::{GetAssertionResponse, MakeCredentialRequest};
+use servo::embedder_traits::webauthn::{MakeCredentialResponse, User};
+use servo::servo_config::opts;
+use tinyfiledialogs::{self, MessageBoxIcon, YesNo};
+
+/// The COSE identifier of ECDSA with SHA-256, the only algorithm supported.
+const ES256: i32 = -7;
+
+/// The flags of the authenticator data.
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+struct Credential {
+    id: Vec<u8>,
+    rp_id: String,
+    user: User,
+    key: EcKey<Private>,
+    sign_count: u32,
+}
+
+pub struct SoftwareAuthenticator {
+    credentials: Vec<Credential>,
+    /// The PIN the user is verified with, chosen the first time it's needed.
+    pin: Option<String>,
+}
+
+impl SoftwareAuthenticator {
+    pub fn new() -> SoftwareAuthenticator {
+        SoftwareAuthenticator {
+            credentials: Vec::new(),
+            pin: None,
+        }
+    }
+
+    fn verify_user(&mut self) -> Result<(), AuthenticatorError> {
+        match self.pin {
+            Some(ref pin) => {
+                match tinyfiledialogs::password_box("Web Authentication", "Enter your PIN:") {
+                    Some(ref entered) if entered == pin => Ok(()),
+                    _ => Err(AuthenticatorError::NotAllowed),
+                }
+            },
+            None => {
+                let pin = tinyfiledialogs::password_box(
+                    "Web Authentication",
+                    "Choose a PIN to protect your credentials:",
+                )
+                .filter(|pin| !pin.is_empty())
+                .ok_or(AuthenticatorError::NotAllowed)?;
+                self.pin = Some(pin);
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Authenticator for SoftwareAuthenticator {
+    fn make_credential(
+        &mut self,
+        request: MakeCredentialRequest,
+    ) -> Result<MakeCredentialResponse, AuthenticatorError> {
+        if opts::get().headless {
+            return Err(AuthenticatorError::NotAllowed);
+        }
+        if request.attachment == Some(AuthenticatorAttachment::CrossPlatform) {
+            return Err(AuthenticatorError::NotAllowed);
+        }
+        if !request.algorithms.contains(&ES256) {
+            return Err(AuthenticatorError::NotSupported);
+        }
+        let excluded = self.credentials.iter().any(|credential| {
+            credential.rp_id == request.rp.id && request.exclude_list.contains(&credential.id)
+        });
+
+        let message = format!(
+            "Do you want to create a credential for {} as {}?",
+            request.rp.id, request.user.name
+        );
+        if !ask_consent(&message) {
+            return Err(AuthenticatorError::NotAllowed);
+        }
+        if excluded {
+            return Err(AuthenticatorError::InvalidState);
+        }
+        let mut flags = USER_PRESENT | ATTESTED_CREDENTIAL_DATA;
+        if request.user_verification {
+            self.verify_user()?;
+            flags |= USER_VERIFIED;
+        }
+
+        let credential =
+            new_credential(request.rp.id, request.user).map_err(|_| AuthenticatorError::Unknown)?;
+        let mut authenticator_data = authenticator_data(&credential, flags);
+        attested_credential_data(&mut authenticator_data, &credential)
+            .map_err(|_| AuthenticatorError::Unknown)?;
+
+        // A self attestation, signed with the key of the credential itself.
+        let signature = sign(
+            &credential.key,
+            &authenticator_data,
+            &request.client_data_hash,
+        )
+        .map_err(|_| AuthenticatorError::Unknown)?;
+        let mut attestation_statement = vec![0xa2, 0x63, b'a', b'l', b'g', 0x26];
+        attestation_statement.extend_from_slice(&[0x63, b's', b'i', b'g', 0x58]);
+        attestation_statement.push(signature.len() as u8);
+        attestation_statement.extend_from_slice(&signature);
+
+        self.credentials.push(credential);
+        Ok(MakeCredentialResponse {
+            format: "packed".to_owned(),
+            authenticator_data,
+            attestation_statement,
+        })
+    }
+
+    fn get_assertion(
+        &mut self,
+        request: GetAssertionRequest,
+    ) -> Result<GetAssertionResponse, AuthenticatorError> {
+        if opts::get().headless {
+            return Err(AuthenticatorError::NotAllowed);
+        }
+        // The most recent credential is used when several are allowed.
+        let index = self
+            .credentials
+            .iter()
+            .rposition(|credential| {
+                credential.rp_id == request.rp_id &&
+                    (request.allow_list.is_empty() ||
+                        request.allow_list.contains(&credential.id))
+            })
+            .ok_or(AuthenticatorError::NotAllowed)?;
+
+        let message = format!(
+            "Do you want to sign in to {} as {}?",
+            request.rp_id, self.credentials[index].user.name
+        );
+        if !ask_consent(&message) {
+            return Err(AuthenticatorError::NotAllowed);
+        }
+        let mut flags = USER_PRESENT;
+        if request.user_verification {
+            self.verify_user()?;
+            flags |= USER_VERIFIED;
+        }
+
+        let credential = &mut self.credentials[index];
+        credential.sign_count += 1;
+        let authenticator_data = authenticator_data(credential, flags);
+        let signature = sign(
+            &credential.key,
+            &authenticator_data,
+            &request.client_data_hash,
+        )
+        .map_err(|_| AuthenticatorError::Unknown)?;
+        Ok(GetAssertionResponse {
+            credential_id: credential.id.clone(),
+            authenticator_data,
+            signature,
+            user_handle: Some(credential.user.id.clone()),
+        })
+    }
+
+    fn is_user_verifying_platform_authenticator(&self) -> bool {
+        !opts::get().headless
+    }
+}
+
+fn ask_consent(message: &str) -> bool {
+    match tinyfiledialogs::message_box_yes_no(
+        "Web Authentication",
+        message,
+        MessageBoxIcon::Question,
+        YesNo::No,
+    ) {
+        YesNo::Yes => true,
+        YesNo::No => false,
+    }
+}
+
+fn new_credential(rp_id: String, user: User) -> Result<Credential, ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let mut id = vec![0; 16];
+    rand_bytes(&mut id)?;
+    Ok(Credential {
+        id,
+        rp_id,
+        user,
+        key: EcKey::generate(&group)?,
+        sign_count: 0,
+    })
+}
+
+/// The hash of the relying party id, the flags and the signature counter,
+/// <https://w3c.github.io/webauthn/#sctn-authenticator-data>
+fn authenticator_data(credential: &Credential, flags: u8) -> Vec<u8> {
+    let mut data = sha256(credential.rp_id.as_bytes()).to_vec();
+    data.push(flags);
+    data.extend_from_slice(&credential.sign_count.to_be_bytes());
+    data
+}
+
+/// Appends the AAGUID of the authenticator, which is all zeros since it has
+/// no model, the id of the credential and its public key as a COSE key,
+/// <https://w3c.github.io/webauthn/#sctn-attested-credential-data>
+fn attested_credential_data(data: &mut Vec<u8>, credential: &Credential) -> Result<(), ErrorStack> {
+    data.extend_from_slice(&[0; 16]);
+    data.extend_from_slice(&(credential.id.len() as u16).to_be_bytes());
+    data.extend_from_slice(&credential.id);
+
+    let mut x = BigNum::new()?;
+    let mut y = BigNum::new()?;
+    let mut context = BigNumContext::new()?;
+    credential.key.public_key().affine_coordinates_gfp(
+        credential.key.group(),
+        &mut x,
+        &mut y,
+        &mut context,
+    )?;
+    // {1: 2 (EC2), 3: -7 (ES256), -1: 1 (P-256), -2: x, -3: y}
+    data.extend_from_slice(&[0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20]);
+    data.extend_from_slice(&x.to_vec_padded(32)?);
+    data.extend_from_slice(&[0x22, 0x58, 0x20]);
+    data.extend_from_slice(&y.to_vec_padded(32)?);
+    Ok(())
+}
+
+/// The DER encoding of the ECDSA signature of the authenticator data followed
+/// by the hash of the client data.
+fn sign(
+    key: &EcKey<Private>,
+    authenticator_data: &[u8],
+    client_data_hash: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut signed = authenticator_data.to_vec();
+    signed.extend_from_slice(client_data_hash);
+    EcdsaSig::sign(&sha256(&signed), key)?.to_der()
+}
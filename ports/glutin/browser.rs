@@ -2,19 +2,26 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::authenticator::SoftwareAuthenticator;
 use crate::keyutils::{CMD_OR_ALT, CMD_OR_CONTROL};
+use crate::speech::EspeakSynthesizer;
 use crate::window_trait::{WindowPortsMethods, LINE_HEIGHT};
 use euclid::{Point2D, Vector2D};
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
-use servo::compositing::windowing::{WebRenderDebugOption, WindowEvent};
-use servo::embedder_traits::{EmbedderMsg, FilterPattern};
+use servo::compositing::windowing::{WebRenderDebugOption, WindowEvent, WindowMethods};
+use servo::embedder_traits::payment::PaymentSheetEvent;
+use servo::embedder_traits::speech::SpeechSynthesizer;
+use servo::embedder_traits::webauthn::Authenticator;
+use servo::embedder_traits::{EmbedderMsg, FilterPattern, PermissionPrompt, PermissionRequest};
+use servo::embedder_traits::{ScreenOrientationState, ScreenOrientationType};
 use servo::msg::constellation_msg::TopLevelBrowsingContextId as BrowserId;
-use servo::msg::constellation_msg::TraversalDirection;
+use servo::msg::constellation_msg::{InputMethodType, TraversalDirection};
 use servo::net_traits::pub_domains::is_reg_domain;
 use servo::script_traits::TouchEventType;
 use servo::servo_config::opts;
 use servo::servo_config::pref;
 use servo::servo_url::ServoUrl;
+use servo::webrender_api::units::{DeviceIntPoint, DeviceIntSize};
 use servo::webrender_api::ScrollLocation;
 use clipboard::{ClipboardContext, ClipboardProvider};
 use std::env;
@@ -24,7 +31,7 @@ use std::mem;
 use std::rc::Rc;
 use std::thread;
 use std::time::Duration;
-use tinyfiledialogs::{self, MessageBoxIcon};
+use tinyfiledialogs::{self, DefaultColorValue, MessageBoxIcon};
 
 pub struct Browser<Window: WindowPortsMethods + ?Sized> {
     current_url: Option<ServoUrl>,
@@ -46,6 +53,12 @@ pub struct Browser<Window: WindowPortsMethods + ?Sized> {
     event_queue: Vec<WindowEvent>,
     clipboard_ctx: Option<ClipboardContext>,
     shutdown_requested: bool,
+    /// Whether the window, and so the browser, can be seen.
+    visible: bool,
+    /// The authenticator Web Authentication credentials are created with.
+    authenticator: SoftwareAuthenticator,
+    /// The speech synthesizer the utterances of `speechSynthesis` are spoken with.
+    speech_synthesizer: EspeakSynthesizer,
 }
 
 enum LoadingState {
@@ -77,6 +90,9 @@ where
             },
             event_queue: Vec::new(),
             shutdown_requested: false,
+            visible: true,
+            authenticator: SoftwareAuthenticator::new(),
+            speech_synthesizer: EspeakSynthesizer::new(),
         }
     }
 
@@ -90,6 +106,12 @@ where
                 WindowEvent::Keyboard(key_event) => {
                     self.handle_key_from_window(key_event);
                 },
+                WindowEvent::Resize => {
+                    // Minimizing the window resizes it to nothing, so this
+                    // is where the browser is hidden or shown again.
+                    self.set_visible(!self.window.is_minimized());
+                    self.event_queue.push(event);
+                },
                 event => {
                     self.event_queue.push(event);
                 },
@@ -97,6 +119,17 @@ where
         }
     }
 
+    fn set_visible(&mut self, visible: bool) {
+        if self.visible == visible {
+            return;
+        }
+        self.visible = visible;
+        if let Some(id) = self.browser_id {
+            self.event_queue
+                .push(WindowEvent::ChangeBrowserVisibility(id, visible));
+        }
+    }
+
     pub fn shutdown_requested(&self) -> bool {
         self.shutdown_requested
     }
@@ -174,7 +207,11 @@ where
             })
             .shortcut(Modifiers::empty(), Key::Escape, || {
                 let state = self.window.get_fullscreen();
-                if state {
+                if self.window.get_pointer_lock() {
+                    if let Some(id) = self.browser_id {
+                        self.event_queue.push(WindowEvent::ExitPointerLock(id));
+                    }
+                } else if state {
                     if let Some(id) = self.browser_id {
                         let event = WindowEvent::ExitFullScreen(id);
                         self.event_queue.push(event);
@@ -294,10 +331,15 @@ where
                     self.window.set_title(&title);
                 },
                 EmbedderMsg::MoveTo(point) => {
-                    self.window.set_position(point);
+                    // Only the browser that is shown can move the window.
+                    if browser_id.is_some() && browser_id == self.browser_id {
+                        self.window.set_position(point);
+                    }
                 },
                 EmbedderMsg::ResizeTo(size) => {
-                    self.window.set_inner_size(size);
+                    if browser_id.is_some() && browser_id == self.browser_id {
+                        self.window.set_inner_size(size);
+                    }
                 },
                 EmbedderMsg::Alert(message, sender) => {
                     if !opts::get().headless {
@@ -352,6 +394,19 @@ where
                     self.event_queue
                         .push(WindowEvent::SelectBrowser(new_browser_id));
                 },
+                EmbedderMsg::SetWindowFeatures(features) => {
+                    // Browsers are opened in the existing window, so the
+                    // requested position and size are only applied to popups.
+                    if features.popup {
+                        if let (Some(x), Some(y)) = (features.left, features.top) {
+                            self.window.set_position(DeviceIntPoint::new(x, y));
+                        }
+                        if let (Some(width), Some(height)) = (features.width, features.height) {
+                            self.window
+                                .set_inner_size(DeviceIntSize::new(width, height));
+                        }
+                    }
+                },
                 EmbedderMsg::Keyboard(key_event) => {
                     self.handle_key_from_servo(browser_id, key_event);
                 },
@@ -394,6 +449,30 @@ where
                 EmbedderMsg::SetFullscreenState(state) => {
                     self.window.set_fullscreen(state);
                 },
+                EmbedderMsg::SetPointerLockState(state) => {
+                    self.window.set_pointer_lock(state);
+                },
+                EmbedderMsg::LockScreenOrientation(lock, sender) => {
+                    // A desktop screen never rotates, so only the locks that
+                    // its current orientation already satisfies are supported.
+                    let screen = self.window.get_coordinates().screen;
+                    let orientation_type = if screen.width >= screen.height {
+                        ScreenOrientationType::LandscapePrimary
+                    } else {
+                        ScreenOrientationType::PortraitPrimary
+                    };
+                    let orientation = ScreenOrientationState {
+                        orientation_type,
+                        angle: 0,
+                    };
+                    if let Err(e) = sender.send(lock.allows(&orientation)) {
+                        let reason =
+                            format!("Failed to send LockScreenOrientation response: {}", e);
+                        self.event_queue
+                            .push(WindowEvent::SendError(browser_id, reason));
+                    }
+                },
+                EmbedderMsg::UnlockScreenOrientation => {},
                 EmbedderMsg::LoadStart => {
                     self.loading_state = Some(LoadingState::Connecting);
                 },
@@ -436,6 +515,83 @@ where
                         self.event_queue.push(WindowEvent::SendError(None, reason));
                     };
                 },
+                EmbedderMsg::ShowInputPicker(kind, value, sender) => {
+                    let chosen = if opts::get().headless {
+                        None
+                    } else {
+                        get_input_value(kind, &value)
+                    };
+                    if let Err(e) = sender.send(chosen) {
+                        let reason = format!("Failed to send ShowInputPicker response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::PromptPermission(prompt, sender) => {
+                    // The dialog is modal, it must not block the event loop.
+                    let _ = thread::Builder::new()
+                        .name("permission prompt".to_owned())
+                        .spawn(move || {
+                            if let Err(e) = sender.send(prompt_user(prompt)) {
+                                warn!("Failed to send PromptPermission response: {}", e);
+                            }
+                        });
+                },
+                EmbedderMsg::ShowNotification(notification) => {
+                    if !opts::get().headless {
+                        let _ = thread::Builder::new()
+                            .name("display notification".to_owned())
+                            .spawn(move || {
+                                tinyfiledialogs::message_box_ok(
+                                    &notification.title,
+                                    &notification.body,
+                                    MessageBoxIcon::Info,
+                                );
+                            });
+                    }
+                },
+                // The message box is dismissed by the user only.
+                EmbedderMsg::CloseNotification(_) => {},
+                EmbedderMsg::Authenticate(request, sender) => {
+                    let result = self.authenticator.handle_request(request);
+                    if let Err(e) = sender.send(result) {
+                        let reason = format!("Failed to send Authenticate response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::IsUserVerifyingPlatformAuthenticatorAvailable(sender) => {
+                    let available = self
+                        .authenticator
+                        .is_user_verifying_platform_authenticator();
+                    if let Err(e) = sender.send(available) {
+                        let reason = format!(
+                            "Failed to send IsUserVerifyingPlatformAuthenticatorAvailable response: {}",
+                            e
+                        );
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::GetSupportedPaymentMethods(sender) => {
+                    // No payment handler is available yet.
+                    if let Err(e) = sender.send(vec![]) {
+                        let reason =
+                            format!("Failed to send GetSupportedPaymentMethods response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::ShowPaymentSheet(_, sender) => {
+                    if let Err(e) = sender.send(PaymentSheetEvent::Abort) {
+                        let reason = format!("Failed to send ShowPaymentSheet response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::AbortPayment(_, sender) => {
+                    if let Err(e) = sender.send(true) {
+                        let reason = format!("Failed to send AbortPayment response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::UpdatePaymentSheet(..) | EmbedderMsg::CompletePayment(..) => {},
+                EmbedderMsg::Speech(request) => self.speech_synthesizer.handle_request(request),
                 EmbedderMsg::ShowIME(_kind) => {
                     debug!("ShowIME received");
                 },
@@ -491,6 +647,42 @@ fn platform_get_selected_devices(devices: Vec<String>) -> Option<String> {
     None
 }
 
+#[cfg(target_os = "linux")]
+fn prompt_user(prompt: PermissionPrompt) -> PermissionRequest {
+    if opts::get().headless {
+        return PermissionRequest::Denied;
+    }
+
+    let message = match prompt {
+        PermissionPrompt::Request(permission_name) => {
+            format!("Do you want to grant permission for {:?}?", permission_name)
+        },
+        PermissionPrompt::Insecure(permission_name) => {
+            format!(
+                "The {:?} feature is only safe to use in secure context, but servo can't guarantee\n\
+                 that the current context is secure. Do you want to proceed and grant permission?",
+                permission_name
+            )
+        },
+    };
+
+    match tinyfiledialogs::message_box_yes_no(
+        "Permission request dialog",
+        &message,
+        MessageBoxIcon::Question,
+        tinyfiledialogs::YesNo::No,
+    ) {
+        tinyfiledialogs::YesNo::Yes => PermissionRequest::Granted,
+        tinyfiledialogs::YesNo::No => PermissionRequest::Denied,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn prompt_user(_prompt: PermissionPrompt) -> PermissionRequest {
+    // TODO popup only supported on linux
+    PermissionRequest::Denied
+}
+
 fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Option<Vec<String>> {
     let picker_name = if multiple_files {
         "Pick files"
@@ -524,6 +716,24 @@ fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Opt
         .expect("Thread spawning failed")
 }
 
+fn get_input_value(kind: InputMethodType, value: &str) -> Option<String> {
+    let prompt = match kind {
+        InputMethodType::Color => {
+            let default = if value.is_empty() { "#000000" } else { value };
+            return tinyfiledialogs::color_chooser_dialog(
+                "Pick a color",
+                DefaultColorValue::Hex(default),
+            )
+            .map(|(hex, _)| hex.to_lowercase());
+        },
+        InputMethodType::Date => "Enter a date (YYYY-MM-DD):",
+        InputMethodType::Time => "Enter a time (HH:MM):",
+        InputMethodType::DatetimeLocal => "Enter a date and time (YYYY-MM-DDTHH:MM):",
+        _ => return None,
+    };
+    tinyfiledialogs::input_box("Pick a value", prompt, value)
+}
+
 fn sanitize_url(request: &str) -> Option<ServoUrl> {
     let request = request.trim();
     ServoUrl::parse(&request)
@@ -0,0 +1,135 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Polls the connected gamepads and turns their input into window events.
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use servo::compositing::windowing::WindowEvent;
+use servo::embedder_traits::EventLoopWaker;
+use servo::script_traits::{GamepadEvent, GamepadIndex, GamepadUpdateType};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// The number of axes and buttons of the standard gamepad,
+/// <https://w3c.github.io/gamepad/#remapping>
+const STANDARD_AXES: usize = 4;
+const STANDARD_BUTTONS: usize = 17;
+
+pub struct GamepadSupport {
+    receiver: Receiver<WindowEvent>,
+}
+
+impl GamepadSupport {
+    pub fn new(event_loop_waker: Box<dyn EventLoopWaker>) -> GamepadSupport {
+        let (sender, receiver) = channel();
+        thread::Builder::new()
+            .name("Gamepad".to_owned())
+            .spawn(move || poll_gamepads(sender, event_loop_waker))
+            .expect("Thread spawning failed");
+        GamepadSupport { receiver }
+    }
+
+    pub fn get_events(&self) -> Vec<WindowEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+fn poll_gamepads(sender: Sender<WindowEvent>, event_loop_waker: Box<dyn EventLoopWaker>) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(error) => return warn!("Failed to initialize gamepad support ({:?}).", error),
+    };
+
+    let send = |event| {
+        if sender.send(WindowEvent::Gamepad(event)).is_err() {
+            return false;
+        }
+        event_loop_waker.wake();
+        true
+    };
+
+    for (id, gamepad) in gilrs.gamepads() {
+        let index = GamepadIndex(id.into());
+        let event = GamepadEvent::Connected(
+            index,
+            gamepad.name().to_owned(),
+            STANDARD_AXES,
+            STANDARD_BUTTONS,
+        );
+        if !send(event) {
+            return;
+        }
+    }
+
+    loop {
+        while let Some(event) = gilrs.next_event() {
+            let index = GamepadIndex(event.id.into());
+            let event = match event.event {
+                EventType::Connected => GamepadEvent::Connected(
+                    index,
+                    gilrs.gamepad(event.id).name().to_owned(),
+                    STANDARD_AXES,
+                    STANDARD_BUTTONS,
+                ),
+                EventType::Disconnected => GamepadEvent::Disconnected(index),
+                EventType::ButtonChanged(button, value, _) => match standard_button(button) {
+                    Some(button) => GamepadEvent::Updated(
+                        index,
+                        GamepadUpdateType::Button(button, value as f64),
+                    ),
+                    None => continue,
+                },
+                EventType::AxisChanged(axis, value, _) => match standard_axis(axis, value) {
+                    Some((axis, value)) => {
+                        GamepadEvent::Updated(index, GamepadUpdateType::Axis(axis, value))
+                    },
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if !send(event) {
+                return;
+            }
+        }
+        thread::sleep(Duration::from_millis(16));
+    }
+}
+
+/// The index of a button in the standard gamepad layout.
+fn standard_button(button: Button) -> Option<usize> {
+    match button {
+        Button::South => Some(0),
+        Button::East => Some(1),
+        Button::West => Some(2),
+        Button::North => Some(3),
+        Button::LeftTrigger => Some(4),
+        Button::RightTrigger => Some(5),
+        Button::LeftTrigger2 => Some(6),
+        Button::RightTrigger2 => Some(7),
+        Button::Select => Some(8),
+        Button::Start => Some(9),
+        Button::LeftThumb => Some(10),
+        Button::RightThumb => Some(11),
+        Button::DPadUp => Some(12),
+        Button::DPadDown => Some(13),
+        Button::DPadLeft => Some(14),
+        Button::DPadRight => Some(15),
+        Button::Mode => Some(16),
+        _ => None,
+    }
+}
+
+/// The index and value of an axis in the standard gamepad layout, where
+/// negative values point up and left.
+fn standard_axis(axis: Axis, value: f32) -> Option<(usize, f64)> {
+    let value = value as f64;
+    match axis {
+        Axis::LeftStickX => Some((0, value)),
+        Axis::LeftStickY => Some((1, -value)),
+        Axis::RightStickX => Some((2, value)),
+        Axis::RightStickY => Some((3, -value)),
+        _ => None,
+    }
+}
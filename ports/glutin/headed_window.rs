@@ -72,6 +72,7 @@ pub struct Window {
     last_pressed: Cell<Option<KeyboardEvent>>,
     animation_state: Cell<AnimationState>,
     fullscreen: Cell<bool>,
+    pointer_locked: Cell<bool>,
     gl: Rc<dyn gl::Gl>,
     xr_rotation: Cell<Rotation3D<f32, UnknownUnit, UnknownUnit>>,
     xr_translation: Cell<Vector3D<f32, UnknownUnit>>,
@@ -206,6 +207,7 @@ impl Window {
             gl: gl.clone(),
             animation_state: Cell::new(AnimationState::Idle),
             fullscreen: Cell::new(false),
+            pointer_locked: Cell::new(false),
             inner_size: Cell::new(inner_size),
             primary_monitor,
             screen_size,
@@ -371,6 +373,12 @@ impl Window {
             },
         }
     }
+
+    /// The middle of the window, where the cursor is kept while it is locked.
+    fn window_center(&self) -> LogicalPosition {
+        let size = self.inner_size.get();
+        LogicalPosition::new((size.width / 2) as f64, (size.height / 2) as f64)
+    }
 }
 
 impl WindowPortsMethods for Window {
@@ -421,6 +429,28 @@ impl WindowPortsMethods for Window {
         return self.fullscreen.get();
     }
 
+    fn set_pointer_lock(&self, state: bool) {
+        if self.pointer_locked.get() == state {
+            return;
+        }
+        self.pointer_locked.set(state);
+        let gl_context = self.gl_context.borrow();
+        let window = gl_context.window();
+        if let Err(e) = window.grab_cursor(state) {
+            warn!("Failed to grab the cursor ({}).", e);
+        }
+        window.hide_cursor(state);
+        if state {
+            if let Err(e) = window.set_cursor_position(self.window_center()) {
+                warn!("Failed to move the cursor ({}).", e);
+            }
+        }
+    }
+
+    fn get_pointer_lock(&self) -> bool {
+        self.pointer_locked.get()
+    }
+
     fn set_cursor(&self, cursor: Cursor) {
         use glutin::MouseCursor;
 
@@ -468,6 +498,11 @@ impl WindowPortsMethods for Window {
         self.animation_state.get() == AnimationState::Animating
     }
 
+    fn is_minimized(&self) -> bool {
+        let size = self.inner_size.get();
+        size.width == 0 || size.height == 0
+    }
+
     fn id(&self) -> glutin::WindowId {
         self.gl_context.borrow().window().id()
     }
@@ -481,6 +516,26 @@ impl WindowPortsMethods for Window {
                     self.handle_mouse(button, state, self.mouse_pos.get());
                 }
             },
+            glutin::WindowEvent::CursorMoved { position, .. } if self.pointer_locked.get() => {
+                // Keep the hidden cursor in the middle of the window, and only
+                // report how far it moved away from there.
+                let hidpi_factor = self.device_hidpi_factor().get() as f64;
+                let center = self.window_center();
+                let (x, y): (i32, i32) = position.to_physical(hidpi_factor).into();
+                let (center_x, center_y): (i32, i32) = center.to_physical(hidpi_factor).into();
+                if (x, y) == (center_x, center_y) {
+                    return;
+                }
+                let movement = Vector2D::new((x - center_x) as f32, (y - center_y) as f32);
+                self.event_queue
+                    .borrow_mut()
+                    .push(WindowEvent::MouseWindowMovement(movement));
+                let _ = self
+                    .gl_context
+                    .borrow()
+                    .window()
+                    .set_cursor_position(center);
+            },
             glutin::WindowEvent::CursorMoved { position, .. } => {
                 let pos = position.to_physical(self.device_hidpi_factor().get() as f64);
                 let (x, y): (i32, i32) = pos.into();
@@ -554,6 +609,18 @@ impl WindowPortsMethods for Window {
                     self.event_queue.borrow_mut().push(WindowEvent::Resize);
                 }
             },
+            glutin::WindowEvent::HiDpiFactorChanged(hidpi_factor) => {
+                // The window moved to a monitor with a different scale factor,
+                // so the size in device pixels changed even if the logical
+                // size didn't.
+                let size = self.gl_context.borrow().window().get_inner_size();
+                if let Some(size) = size {
+                    self.gl_context
+                        .borrow_mut()
+                        .resize(size.to_physical(hidpi_factor));
+                }
+                self.event_queue.borrow_mut().push(WindowEvent::Resize);
+            },
             _ => {},
         }
     }
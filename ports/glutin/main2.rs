@@ -11,16 +11,19 @@ extern crate log;
 extern crate sig;
 
 mod app;
+mod authenticator;
 mod backtrace;
 mod browser;
 mod context;
 mod embedder;
 mod events_loop;
+mod gamepad;
 mod headed_window;
 mod headless_window;
 mod keyutils;
 mod resources;
 mod skia_symbols;
+mod speech;
 mod window_trait;
 
 use app::App;
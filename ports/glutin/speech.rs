@@ -0,0 +1,267 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A speech synthesizer that speaks with eSpeak NG, or eSpeak, when either
+//! is installed.
+
+use servo::embedder_traits::speech::{SpeechEvent, SpeechSynthesisError, SpeechSynthesizer};
+use servo::embedder_traits::speech::{SpeechUtterance, SpeechVoice};
+use servo::ipc_channel::ipc::IpcSender;
+use servo::servo_config::opts;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The programs that can speak, by order of preference.
+const PROGRAMS: &[&str] = &["espeak-ng", "espeak"];
+
+/// What the URIs of the voices start with, before the name eSpeak knows them by.
+const VOICE_URI_PREFIX: &str = "urn:espeak:";
+
+/// The default rate of eSpeak, in words per minute.
+const DEFAULT_SPEED: f32 = 175.;
+
+struct Utterance {
+    id: String,
+    /// The process that speaks the utterance, until it exits or is killed.
+    process: Arc<Mutex<Option<Child>>>,
+    events: IpcSender<SpeechEvent>,
+}
+
+pub struct EspeakSynthesizer {
+    /// The program that speaks and its voices, once they were looked for.
+    program: Option<Option<(&'static str, Vec<SpeechVoice>)>>,
+    current: Option<Utterance>,
+}
+
+impl EspeakSynthesizer {
+    pub fn new() -> EspeakSynthesizer {
+        EspeakSynthesizer {
+            program: None,
+            current: None,
+        }
+    }
+
+    fn program(&mut self) -> Option<&(&'static str, Vec<SpeechVoice>)> {
+        if opts::get().headless {
+            return None;
+        }
+        self.program
+            .get_or_insert_with(|| {
+                PROGRAMS.iter().find_map(|program| {
+                    let output = Command::new(program).arg("--voices").output().ok()?;
+                    if !output.status.success() {
+                        return None;
+                    }
+                    let voices = parse_voices(&String::from_utf8_lossy(&output.stdout));
+                    Some((*program, voices))
+                })
+            })
+            .as_ref()
+    }
+
+    /// Stops speaking the current utterance, if any, and reports that it was
+    /// interrupted.
+    fn interrupt(&mut self) {
+        if let Some(utterance) = self.current.take() {
+            if kill(&utterance.process) {
+                let _ = utterance
+                    .events
+                    .send(SpeechEvent::Error(SpeechSynthesisError::Interrupted));
+            }
+        }
+    }
+
+    fn current(&self, id: &str) -> Option<&Utterance> {
+        self.current.as_ref().filter(|utterance| utterance.id == id)
+    }
+}
+
+impl SpeechSynthesizer for EspeakSynthesizer {
+    fn voices(&mut self) -> Vec<SpeechVoice> {
+        self.program()
+            .map(|(_, voices)| voices.clone())
+            .unwrap_or_default()
+    }
+
+    fn speak(&mut self, utterance: SpeechUtterance, events: IpcSender<SpeechEvent>) {
+        self.interrupt();
+        let program = match self.program() {
+            Some((program, _)) => *program,
+            None => {
+                let _ = events.send(SpeechEvent::Error(
+                    SpeechSynthesisError::SynthesisUnavailable,
+                ));
+                return;
+            },
+        };
+
+        let mut command = Command::new(program);
+        let voice = match utterance.voice_uri {
+            Some(ref uri) => uri.trim_start_matches(VOICE_URI_PREFIX).to_owned(),
+            None => utterance.lang.to_lowercase(),
+        };
+        if !voice.is_empty() {
+            command.arg("-v").arg(voice);
+        }
+        let amplitude = (utterance.volume * 100.).round();
+        let speed = (utterance.rate * DEFAULT_SPEED).max(80.).min(450.).round();
+        let pitch = (utterance.pitch * 50.).min(99.).round();
+        command
+            .arg("-a")
+            .arg(amplitude.to_string())
+            .arg("-s")
+            .arg(speed.to_string())
+            .arg("-p")
+            .arg(pitch.to_string())
+            .arg("--stdin")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                warn!("Failed to run {}: {}", program, e);
+                let _ = events.send(SpeechEvent::Error(SpeechSynthesisError::SynthesisFailed));
+                return;
+            },
+        };
+        let _ = events.send(SpeechEvent::Start);
+
+        let stdin = child.stdin.take();
+        let process = Arc::new(Mutex::new(Some(child)));
+        let waited_process = process.clone();
+        let sender = events.clone();
+        let text = utterance.text;
+        let spawned = thread::Builder::new()
+            .name("SpeechSynthesizer".to_owned())
+            .spawn(move || {
+                if let Some(mut stdin) = stdin {
+                    // The process is killed when the utterance is cancelled.
+                    let _ = stdin.write_all(text.as_bytes());
+                }
+                loop {
+                    {
+                        let mut process = waited_process.lock().unwrap();
+                        let status = match *process {
+                            Some(ref mut child) => child.try_wait(),
+                            // The utterance was cancelled or interrupted.
+                            None => return,
+                        };
+                        let event = match status {
+                            Ok(None) => None,
+                            Ok(Some(status)) if status.success() => Some(SpeechEvent::End),
+                            Ok(Some(_)) | Err(_) => {
+                                Some(SpeechEvent::Error(SpeechSynthesisError::SynthesisFailed))
+                            },
+                        };
+                        if let Some(event) = event {
+                            *process = None;
+                            let _ = sender.send(event);
+                            return;
+                        }
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to spawn the speech synthesizer thread: {}", e);
+            kill(&process);
+            let _ = events.send(SpeechEvent::Error(SpeechSynthesisError::SynthesisFailed));
+            return;
+        }
+
+        self.current = Some(Utterance {
+            id: utterance.id,
+            process,
+            events,
+        });
+    }
+
+    fn pause(&mut self, id: &str) {
+        if let Some(utterance) = self.current(id) {
+            if let Some(ref child) = *utterance.process.lock().unwrap() {
+                if set_stopped(child, true) {
+                    let _ = utterance.events.send(SpeechEvent::Pause);
+                }
+            }
+        }
+    }
+
+    fn resume(&mut self, id: &str) {
+        if let Some(utterance) = self.current(id) {
+            if let Some(ref child) = *utterance.process.lock().unwrap() {
+                if set_stopped(child, false) {
+                    let _ = utterance.events.send(SpeechEvent::Resume);
+                }
+            }
+        }
+    }
+
+    fn cancel(&mut self, id: &str) {
+        match self.current.take() {
+            Some(ref utterance) if utterance.id == id => {
+                kill(&utterance.process);
+            },
+            current => self.current = current,
+        }
+    }
+}
+
+/// Parses the output of `espeak --voices`, a table with a header line and
+/// then a line per voice, such as:
+///
+/// ```text
+/// Pty Language       Age/Gender VoiceName          File                 Other Languages
+///  5  en-us           --/M      English_(America)  gmw/en-US            (en 2)
+/// ```
+fn parse_voices(output: &str) -> Vec<SpeechVoice> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let lang = columns.nth(1)?;
+            let name = columns.nth(1)?;
+            Some(SpeechVoice {
+                uri: format!("{}{}", VOICE_URI_PREFIX, lang),
+                name: name.replace('_', " "),
+                lang: lang.to_owned(),
+                local_service: true,
+                default: lang == "en",
+            })
+        })
+        .collect()
+}
+
+/// Kills the process that speaks an utterance, and returns whether it was
+/// still speaking.
+fn kill(process: &Mutex<Option<Child>>) -> bool {
+    match process.lock().unwrap().take() {
+        Some(mut child) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            true
+        },
+        None => false,
+    }
+}
+
+#[cfg(unix)]
+fn set_stopped(child: &Child, stopped: bool) -> bool {
+    let signal = if stopped {
+        libc::SIGSTOP
+    } else {
+        libc::SIGCONT
+    };
+    unsafe { libc::kill(child.id() as libc::pid_t, signal) == 0 }
+}
+
+#[cfg(not(unix))]
+fn set_stopped(_child: &Child, _stopped: bool) -> bool {
+    // TODO: suspend the process on Windows.
+    false
+}
@@ -21,9 +21,17 @@ pub trait WindowPortsMethods: WindowMethods + webxr::glwindow::GlWindow {
     fn get_fullscreen(&self) -> bool;
     fn winit_event_to_servo_event(&self, event: glutin::WindowEvent);
     fn is_animating(&self) -> bool;
+    /// Whether the window is minimized, and its content can't be seen.
+    fn is_minimized(&self) -> bool {
+        false
+    }
     fn set_title(&self, _title: &str) {}
     fn set_inner_size(&self, _size: DeviceIntSize) {}
     fn set_position(&self, _point: DeviceIntPoint) {}
     fn set_fullscreen(&self, _state: bool) {}
+    fn set_pointer_lock(&self, _state: bool) {}
+    fn get_pointer_lock(&self) -> bool {
+        false
+    }
     fn set_cursor(&self, _cursor: Cursor) {}
 }
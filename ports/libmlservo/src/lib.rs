@@ -372,6 +372,10 @@ impl HostTrait for HostCallbacks {
     fn on_allow_navigation(&self, _url: String) -> bool {
         true
     }
+    fn prompt_permission(&self, _message: String) -> bool {
+        // TODO: there is no UI to ask the user yet.
+        false
+    }
     fn on_url_changed(&self, url: String) {
         if let Ok(cstr) = CString::new(url.as_str()) {
             if let Some(url_update) = self.url_update.0 {
@@ -8,15 +8,20 @@ extern crate log;
 pub mod gl_glue;
 
 pub use servo::embedder_traits::MediaSessionPlaybackState;
-pub use servo::script_traits::{MediaSessionActionType, MouseButton};
+pub use servo::script_traits::{MediaSessionActionType, MouseButton, PenState};
 
 use getopts::Options;
 use servo::compositing::windowing::{
     AnimationState, EmbedderCoordinates, EmbedderMethods, MouseWindowEvent, WindowEvent,
     WindowMethods,
 };
+use servo::embedder_traits::payment::PaymentSheetEvent;
 use servo::embedder_traits::resources::{self, Resource, ResourceReaderMethods};
-use servo::embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use servo::embedder_traits::speech::{SpeechEvent, SpeechRequest, SpeechSynthesisError};
+use servo::embedder_traits::webauthn::AuthenticatorError;
+use servo::embedder_traits::{
+    EmbedderMsg, MediaSessionEvent, PermissionPrompt, PermissionRequest, ScreenOrientationState,
+};
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
 use servo::msg::constellation_msg::TraversalDirection;
@@ -108,6 +113,9 @@ pub trait HostTrait {
     fn on_title_changed(&self, title: String);
     /// Allow Navigation.
     fn on_allow_navigation(&self, url: String) -> bool;
+    /// A page requests a permission, the message explains what is requested.
+    /// Returns whether the user granted the permission.
+    fn prompt_permission(&self, message: String) -> bool;
     /// Page URL has changed.
     fn on_url_changed(&self, url: String);
     /// Back/forward state has changed.
@@ -304,6 +312,24 @@ impl ServoGlue {
         self.process_event(event)
     }
 
+    /// Let the page know whether it can be seen, when the view is hidden or
+    /// shown again.
+    pub fn change_visibility(&mut self, visible: bool) -> Result<(), &'static str> {
+        info!("change_visibility");
+        let browser_id = self.get_browser_id()?;
+        let event = WindowEvent::ChangeBrowserVisibility(browser_id, visible);
+        self.process_event(event)
+    }
+
+    /// Let the page know that the screen was rotated.
+    pub fn change_screen_orientation(
+        &mut self,
+        orientation: ScreenOrientationState,
+    ) -> Result<(), &'static str> {
+        info!("change_screen_orientation");
+        self.process_event(WindowEvent::ChangeScreenOrientation(orientation))
+    }
+
     /// Redraw the page.
     pub fn refresh(&mut self) -> Result<(), &'static str> {
         info!("refresh");
@@ -409,6 +435,62 @@ impl ServoGlue {
         self.process_event(event)
     }
 
+    /// Pen event: pen touches the screen
+    pub fn pen_down(
+        &mut self,
+        x: f32,
+        y: f32,
+        pointer_id: i32,
+        state: PenState,
+    ) -> Result<(), &'static str> {
+        self.pen_event(TouchEventType::Down, x, y, pointer_id, state)
+    }
+
+    /// Pen event: move pen on the screen
+    pub fn pen_move(
+        &mut self,
+        x: f32,
+        y: f32,
+        pointer_id: i32,
+        state: PenState,
+    ) -> Result<(), &'static str> {
+        self.pen_event(TouchEventType::Move, x, y, pointer_id, state)
+    }
+
+    /// Pen event: lift pen from the screen
+    pub fn pen_up(
+        &mut self,
+        x: f32,
+        y: f32,
+        pointer_id: i32,
+        state: PenState,
+    ) -> Result<(), &'static str> {
+        self.pen_event(TouchEventType::Up, x, y, pointer_id, state)
+    }
+
+    /// Cancel pen event
+    pub fn pen_cancel(
+        &mut self,
+        x: f32,
+        y: f32,
+        pointer_id: i32,
+        state: PenState,
+    ) -> Result<(), &'static str> {
+        self.pen_event(TouchEventType::Cancel, x, y, pointer_id, state)
+    }
+
+    fn pen_event(
+        &mut self,
+        event_type: TouchEventType,
+        x: f32,
+        y: f32,
+        pointer_id: i32,
+        state: PenState,
+    ) -> Result<(), &'static str> {
+        let event = WindowEvent::Pen(event_type, TouchId(pointer_id), Point2D::new(x, y), state);
+        self.process_event(event)
+    }
+
     /// Register a mouse movement.
     pub fn mouse_move(&mut self, x: f32, y: f32) -> Result<(), &'static str> {
         let point = Point2D::new(x, y);
@@ -540,6 +622,25 @@ impl ServoGlue {
                 EmbedderMsg::AllowUnload(sender) => {
                     let _ = sender.send(true);
                 },
+                EmbedderMsg::PromptPermission(prompt, sender) => {
+                    let message = match prompt {
+                        PermissionPrompt::Request(name) => {
+                            format!("Do you want to grant permission for {:?}?", name)
+                        },
+                        PermissionPrompt::Insecure(name) => format!(
+                            "The {:?} feature is only safe to use in secure context, \
+                             but servo can't guarantee that the current context is secure. \
+                             Do you want to proceed and grant permission?",
+                            name
+                        ),
+                    };
+                    let answer = if self.callbacks.host_callbacks.prompt_permission(message) {
+                        PermissionRequest::Granted
+                    } else {
+                        PermissionRequest::Denied
+                    };
+                    let _ = sender.send(answer);
+                },
                 EmbedderMsg::Alert(message, sender) => {
                     info!("Alert: {}", message);
                     self.callbacks.host_callbacks.on_alert(message);
@@ -587,6 +688,59 @@ impl ServoGlue {
                 EmbedderMsg::HideIME => {
                     self.callbacks.host_callbacks.on_ime_state_changed(false);
                 },
+                EmbedderMsg::LockScreenOrientation(_, sender) => {
+                    // TODO: ask the host to lock the orientation of the view.
+                    if let Err(e) = sender.send(false) {
+                        warn!("Failed to send LockScreenOrientation response: {}", e);
+                    }
+                },
+                EmbedderMsg::Authenticate(_, sender) => {
+                    // TODO: give access to the authenticators of the host.
+                    if let Err(e) = sender.send(Err(AuthenticatorError::NotAllowed)) {
+                        warn!("Failed to send Authenticate response: {}", e);
+                    }
+                },
+                EmbedderMsg::IsUserVerifyingPlatformAuthenticatorAvailable(sender) => {
+                    if let Err(e) = sender.send(false) {
+                        warn!(
+                            "Failed to send IsUserVerifyingPlatformAuthenticatorAvailable response: {}",
+                            e
+                        );
+                    }
+                },
+                EmbedderMsg::GetSupportedPaymentMethods(sender) => {
+                    // TODO: let the host handle payment requests.
+                    if let Err(e) = sender.send(vec![]) {
+                        warn!("Failed to send GetSupportedPaymentMethods response: {}", e);
+                    }
+                },
+                EmbedderMsg::ShowPaymentSheet(_, sender) => {
+                    if let Err(e) = sender.send(PaymentSheetEvent::Abort) {
+                        warn!("Failed to send ShowPaymentSheet response: {}", e);
+                    }
+                },
+                EmbedderMsg::AbortPayment(_, sender) => {
+                    if let Err(e) = sender.send(true) {
+                        warn!("Failed to send AbortPayment response: {}", e);
+                    }
+                },
+                EmbedderMsg::Speech(request) => match request {
+                    // TODO: let the host speak utterances.
+                    SpeechRequest::GetVoices(sender) => {
+                        if let Err(e) = sender.send(vec![]) {
+                            warn!("Failed to send the speech voices: {}", e);
+                        }
+                    },
+                    SpeechRequest::Speak(_, events) => {
+                        let error = SpeechSynthesisError::SynthesisUnavailable;
+                        if let Err(e) = events.send(SpeechEvent::Error(error)) {
+                            warn!("Failed to send the speech event: {}", e);
+                        }
+                    },
+                    SpeechRequest::Pause(_) |
+                    SpeechRequest::Resume(_) |
+                    SpeechRequest::Cancel(_) => {},
+                },
                 EmbedderMsg::MediaSessionEvent(event) => {
                     match event {
                         MediaSessionEvent::SetMetadata(metadata) => {
@@ -612,14 +766,22 @@ impl ServoGlue {
                 },
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
+                EmbedderMsg::ShowInputPicker(..) |
                 EmbedderMsg::MoveTo(..) |
                 EmbedderMsg::ResizeTo(..) |
+                EmbedderMsg::SetWindowFeatures(..) |
                 EmbedderMsg::Keyboard(..) |
                 EmbedderMsg::SetCursor(..) |
                 EmbedderMsg::NewFavicon(..) |
                 EmbedderMsg::HeadParsed |
                 EmbedderMsg::SetFullscreenState(..) |
+                EmbedderMsg::SetPointerLockState(..) |
+                EmbedderMsg::UnlockScreenOrientation |
                 EmbedderMsg::Panic(..) |
+                EmbedderMsg::ShowNotification(..) |
+                EmbedderMsg::CloseNotification(..) |
+                EmbedderMsg::UpdatePaymentSheet(..) |
+                EmbedderMsg::CompletePayment(..) |
                 EmbedderMsg::ReportProfile(..) => {},
             }
         }
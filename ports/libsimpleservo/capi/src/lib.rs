@@ -18,7 +18,7 @@ use log::LevelFilter;
 use simpleservo::{self, gl_glue, ServoGlue, SERVO};
 use simpleservo::{
     Coordinates, EventLoopWaker, HostTrait, InitOptions, MediaSessionActionType,
-    MediaSessionPlaybackState, MouseButton, VRInitOptions,
+    MediaSessionPlaybackState, MouseButton, PenState, VRInitOptions,
 };
 use std::ffi::{CStr, CString};
 #[cfg(target_os = "windows")]
@@ -210,6 +210,7 @@ pub struct CHostCallbacks {
     pub on_load_ended: extern "C" fn(),
     pub on_title_changed: extern "C" fn(title: *const c_char),
     pub on_allow_navigation: extern "C" fn(url: *const c_char) -> bool,
+    pub prompt_permission: extern "C" fn(message: *const c_char) -> bool,
     pub on_url_changed: extern "C" fn(url: *const c_char),
     pub on_history_changed: extern "C" fn(can_go_back: bool, can_go_forward: bool),
     pub on_animating_changed: extern "C" fn(animating: bool),
@@ -598,6 +599,79 @@ pub extern "C" fn touch_cancel(x: f32, y: f32, pointer_id: i32) {
     });
 }
 
+#[no_mangle]
+pub extern "C" fn pen_down(
+    x: f32,
+    y: f32,
+    pointer_id: i32,
+    pressure: f32,
+    tilt_x: i32,
+    tilt_y: i32,
+) {
+    catch_any_panic(|| {
+        debug!("pen down");
+        let state = PenState {
+            pressure,
+            tilt_x,
+            tilt_y,
+        };
+        call(|s| s.pen_down(x, y, pointer_id, state));
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn pen_up(x: f32, y: f32, pointer_id: i32, pressure: f32, tilt_x: i32, tilt_y: i32) {
+    catch_any_panic(|| {
+        debug!("pen up");
+        let state = PenState {
+            pressure,
+            tilt_x,
+            tilt_y,
+        };
+        call(|s| s.pen_up(x, y, pointer_id, state));
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn pen_move(
+    x: f32,
+    y: f32,
+    pointer_id: i32,
+    pressure: f32,
+    tilt_x: i32,
+    tilt_y: i32,
+) {
+    catch_any_panic(|| {
+        debug!("pen move");
+        let state = PenState {
+            pressure,
+            tilt_x,
+            tilt_y,
+        };
+        call(|s| s.pen_move(x, y, pointer_id, state));
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn pen_cancel(
+    x: f32,
+    y: f32,
+    pointer_id: i32,
+    pressure: f32,
+    tilt_x: i32,
+    tilt_y: i32,
+) {
+    catch_any_panic(|| {
+        debug!("pen cancel");
+        let state = PenState {
+            pressure,
+            tilt_x,
+            tilt_y,
+        };
+        call(|s| s.pen_cancel(x, y, pointer_id, state));
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn pinchzoom_start(factor: f32, x: i32, y: i32) {
     catch_any_panic(|| {
@@ -726,6 +800,12 @@ impl HostTrait for HostCallbacks {
         (self.0.on_allow_navigation)(url.as_ptr())
     }
 
+    fn prompt_permission(&self, message: String) -> bool {
+        debug!("prompt_permission");
+        let message = CString::new(message).expect("Can't create string");
+        (self.0.prompt_permission)(message.as_ptr())
+    }
+
     fn on_url_changed(&self, url: String) {
         debug!("on_url_changed");
         let url = CString::new(url).expect("Can't create string");
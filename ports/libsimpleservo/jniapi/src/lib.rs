@@ -469,6 +469,26 @@ impl HostTrait for HostCallbacks {
         }
     }
 
+    fn prompt_permission(&self, message: String) -> bool {
+        debug!("prompt_permission");
+        let env = self.jvm.get_env().unwrap();
+        let s = match new_string(&env, &message) {
+            Ok(s) => s,
+            Err(_) => return false,
+        };
+        let s = JValue::from(JObject::from(s));
+        let granted = env.call_method(
+            self.callbacks.as_obj(),
+            "onPromptPermission",
+            "(Ljava/lang/String;)Z",
+            &[s],
+        );
+        match granted {
+            Ok(granted) => granted.z().unwrap(),
+            Err(_) => false,
+        }
+    }
+
     fn on_url_changed(&self, url: String) {
         debug!("on_url_changed");
         let env = self.jvm.get_env().unwrap();
@@ -28,6 +28,7 @@ import org.mozilla.servoview.ServoView;
 import org.mozilla.servoview.Servo;
 
 import java.io.File;
+import java.util.concurrent.CountDownLatch;
 
 public class MainActivity extends Activity implements Servo.Client {
 
@@ -151,6 +152,27 @@ public class MainActivity extends Activity implements Servo.Client {
       alert.show();
     }
 
+    @Override
+    public boolean onPromptPermission(String message) {
+        // Servo waits for the answer, the dialog is shown on the UI thread.
+        final boolean[] granted = {false};
+        final CountDownLatch answered = new CountDownLatch(1);
+        runOnUiThread(() -> {
+            AlertDialog.Builder builder = new AlertDialog.Builder(this);
+            builder.setMessage(message);
+            builder.setPositiveButton(android.R.string.yes, (dialog, which) -> granted[0] = true);
+            builder.setNegativeButton(android.R.string.no, null);
+            builder.setOnDismissListener(dialog -> answered.countDown());
+            builder.create().show();
+        });
+        try {
+            answered.await();
+        } catch (InterruptedException e) {
+            return false;
+        }
+        return granted[0];
+    }
+
     @Override
     public void onLoadStarted() {
         mReloadButton.setEnabled(false);
@@ -98,6 +98,8 @@ public class JNIServo {
 
         void onAlert(String message);
 
+        boolean onPromptPermission(String message);
+
         void onAnimatingChanged(boolean animating);
 
         void onLoadStarted();
@@ -177,6 +177,8 @@ public class Servo {
 
         boolean onAllowNavigation(String url);
 
+        boolean onPromptPermission(String message);
+
         void onLoadStarted();
 
         void onLoadEnded();
@@ -256,6 +258,10 @@ public class Servo {
             return mClient.onAllowNavigation(url);
         }
 
+        public boolean onPromptPermission(String message) {
+            return mClient.onPromptPermission(message);
+        }
+
         public void onLoadStarted() {
             mRunCallback.inUIThread(() -> mClient.onLoadStarted());
         }
@@ -280,12 +280,10 @@ public class ServoView extends GLSurfaceView
         mScaleGestureDetector.onTouchEvent(e);
 
         int action = e.getActionMasked();
-        float x = e.getX();
-        
-        float y = e.getY();
-        
         int pointerIndex = e.getActionIndex();
         int pointerId = e.getPointerId(pointerIndex);
+        float x = e.getX(pointerIndex);
+        float y = e.getY(pointerIndex);
         switch (action) {
             case (MotionEvent.ACTION_DOWN):
                 mServo.touchDown(x, y, pointerId);
@@ -296,15 +294,26 @@ public class ServoView extends GLSurfaceView
                 mCurY = (int) y;
                 mLastY = mCurY;
                 return true;
+            case (MotionEvent.ACTION_POINTER_DOWN):
+                mServo.touchDown(x, y, pointerId);
+                return true;
             case (MotionEvent.ACTION_MOVE):
-                mCurX = (int) x;
-                mCurY = (int) y;
-                mServo.touchMove(x, y, pointerId);
+                mCurX = (int) e.getX();
+                mCurY = (int) e.getY();
+                // A move event carries the location of every pointer.
+                for (int i = 0; i < e.getPointerCount(); i++) {
+                    mServo.touchMove(e.getX(i), e.getY(i), e.getPointerId(i));
+                }
                 return true;
             case (MotionEvent.ACTION_UP):
+            case (MotionEvent.ACTION_POINTER_UP):
                 mServo.touchUp(x, y, pointerId);
+                return true;
             case (MotionEvent.ACTION_CANCEL):
-                mServo.touchCancel(x, y, pointerId);
+                // A cancel event ends the gesture of every pointer.
+                for (int i = 0; i < e.getPointerCount(); i++) {
+                    mServo.touchCancel(e.getX(i), e.getY(i), e.getPointerId(i));
+                }
                 return true;
             default:
                 return true;
@@ -35,6 +35,10 @@ bool on_allow_navigation(const char *url) {
   return sServo->Delegate().OnServoAllowNavigation(char2hstring(url));
 };
 
+bool prompt_permission(const char *message) {
+  return sServo->Delegate().OnServoPromptPermission(char2hstring(message));
+}
+
 void on_animating_changed(bool aAnimating) {
   sServo->Delegate().OnServoAnimatingChanged(aAnimating);
 }
@@ -118,6 +122,7 @@ Servo::Servo(hstring url, hstring args, GLsizei width, GLsizei height,
   c.on_animating_changed = &on_animating_changed;
   c.on_shutdown_complete = &on_shutdown_complete;
   c.on_allow_navigation = &on_allow_navigation;
+  c.prompt_permission = &prompt_permission;
   c.on_ime_state_changed = &on_ime_state_changed;
   c.get_clipboard_contents = &get_clipboard_contents;
   c.set_clipboard_contents = &set_clipboard_contents;
@@ -32,6 +32,7 @@ public:
   virtual void OnServoAlert(hstring) = 0;
   virtual void OnServoURLChanged(hstring) = 0;
   virtual bool OnServoAllowNavigation(hstring) = 0;
+  virtual bool OnServoPromptPermission(hstring) = 0;
   virtual void OnServoAnimatingChanged(bool) = 0;
   virtual void OnServoIMEStateChanged(bool) = 0;
   virtual void Flush() = 0;
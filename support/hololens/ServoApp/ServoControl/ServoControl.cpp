@@ -427,6 +427,22 @@ bool ServoControl::OnServoAllowNavigation(hstring uri) {
   return !mTransient;
 }
 
+bool ServoControl::OnServoPromptPermission(hstring message) {
+  // Servo waits for the answer, the dialog is shown on the UI thread.
+  std::promise<bool> granted;
+  RunOnUIThread([=, &granted] {
+    Windows::UI::Popups::MessageDialog dialog{message};
+    dialog.Commands().Append(Windows::UI::Popups::UICommand{L"Allow"});
+    dialog.Commands().Append(Windows::UI::Popups::UICommand{L"Deny"});
+    dialog.DefaultCommandIndex(1);
+    dialog.CancelCommandIndex(1);
+    dialog.ShowAsync().Completed([&granted](auto const &operation, auto) {
+      granted.set_value(operation.GetResults().Label() == L"Allow");
+    });
+  });
+  return granted.get_future().get();
+}
+
 void ServoControl::OnServoAnimatingChanged(bool animating) {
   EnterCriticalSection(&mGLLock);
   mAnimating = animating;
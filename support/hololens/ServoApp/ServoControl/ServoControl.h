@@ -103,6 +103,7 @@ struct ServoControl : ServoControlT<ServoControl>, public servo::ServoDelegate {
   virtual void Flush();
   virtual void MakeCurrent();
   virtual bool OnServoAllowNavigation(winrt::hstring);
+  virtual bool OnServoPromptPermission(winrt::hstring);
   virtual void OnServoAnimatingChanged(bool);
   virtual void OnServoIMEStateChanged(bool);
   virtual void OnServoMediaSessionMetadata(winrt::hstring, winrt::hstring,
@@ -7,6 +7,7 @@ use ipc_channel::ipc;
 use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use profile_traits::time::{ProfilerChan, TimerMetadata};
+use script_traits::ConstellationControlMsg;
 use servo_url::ServoUrl;
 
 struct DummyProfilerMetadataFactory {}
@@ -122,3 +123,91 @@ fn test_first_contentful_paint_setter() {
         "first paint is set"
     );
 }
+
+#[test]
+fn test_paint_metrics_are_only_set_once() {
+    let first_epoch = Epoch(0);
+    let paint_time_metrics = test_common(false, first_epoch);
+    let dummy_profiler_metadata_factory = DummyProfilerMetadataFactory {};
+    let second_epoch = Epoch(1);
+    paint_time_metrics.maybe_observe_paint_time(
+        &dummy_profiler_metadata_factory,
+        second_epoch,
+        true,
+    );
+
+    let first_paint_time = time::precise_time_ns();
+    paint_time_metrics.maybe_set_metric(first_epoch, first_paint_time);
+    let first_paint = paint_time_metrics.get_first_paint();
+    assert!(first_paint.is_some(), "first paint is set");
+    assert_eq!(
+        paint_time_metrics.get_first_contentful_paint(),
+        None,
+        "first contentful paint is None"
+    );
+
+    let second_paint_time = first_paint_time + 1;
+    paint_time_metrics.maybe_set_metric(second_epoch, second_paint_time);
+    assert_eq!(
+        paint_time_metrics.get_first_paint(),
+        first_paint,
+        "first paint is not overwritten"
+    );
+    assert!(
+        paint_time_metrics.get_first_contentful_paint().unwrap() > first_paint.unwrap(),
+        "first contentful paint is set by the later frame"
+    );
+}
+
+#[test]
+fn test_frame_presentation_is_reported_after_paint_metrics() {
+    let (sender, _) = ipc::channel().unwrap();
+    let profiler_chan = ProfilerChan(sender);
+    let (layout_sender, _) = ipc::channel().unwrap();
+    let (script_sender, script_receiver) = ipc::channel().unwrap();
+    let mut paint_time_metrics = PaintTimeMetrics::new(
+        TEST_PIPELINE_ID,
+        profiler_chan,
+        layout_sender,
+        script_sender,
+        ServoUrl::parse("about:blank").unwrap(),
+    );
+    paint_time_metrics.set_navigation_start(time::precise_time_ns());
+    let dummy_profiler_metadata_factory = DummyProfilerMetadataFactory {};
+
+    let first_epoch = Epoch(0);
+    paint_time_metrics.maybe_observe_paint_time(
+        &dummy_profiler_metadata_factory,
+        first_epoch,
+        true,
+    );
+    paint_time_metrics.maybe_set_metric(first_epoch, time::precise_time_ns());
+    assert!(
+        paint_time_metrics.get_first_contentful_paint().is_some(),
+        "first contentful paint is set"
+    );
+    while script_receiver.try_recv().is_ok() {}
+
+    let second_epoch = Epoch(1);
+    paint_time_metrics.observe_next_frame_presentation();
+    paint_time_metrics.maybe_observe_paint_time(
+        &dummy_profiler_metadata_factory,
+        second_epoch,
+        false,
+    );
+    let paint_time = time::precise_time_ns();
+    paint_time_metrics.maybe_set_metric(second_epoch, paint_time);
+    match script_receiver.try_recv() {
+        Ok(ConstellationControlMsg::FramePresented(pipeline_id, time)) => {
+            assert_eq!(pipeline_id, TEST_PIPELINE_ID);
+            assert_eq!(time, paint_time, "the paint time of the frame is reported");
+        },
+        _ => panic!("the frame presentation is reported"),
+    }
+
+    paint_time_metrics.maybe_set_metric(second_epoch, paint_time + 1);
+    assert!(
+        script_receiver.try_recv().is_err(),
+        "the frame presentation is only reported once"
+    );
+}
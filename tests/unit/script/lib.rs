@@ -9,13 +9,25 @@ mod htmlareaelement;
 #[cfg(test)]
 mod htmlimageelement;
 #[cfg(test)]
+mod media_segment_parser;
+#[cfg(test)]
 mod origin;
+#[cfg(test)]
+mod payment;
 #[cfg(all(test, target_pointer_width = "64"))]
 mod size_of;
 #[cfg(test)]
+mod subtlecrypto;
+#[cfg(test)]
 mod textinput;
 #[cfg(test)]
 mod timeranges;
+#[cfg(test)]
+mod webauthn;
+#[cfg(test)]
+mod webvtt;
+#[cfg(test)]
+mod xpath;
 
 /**
 ```compile_fail,E0277
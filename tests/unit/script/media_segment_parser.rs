@@ -0,0 +1,407 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::media_segment_parser::{ByteStreamFormat, ParseError, Segment, SegmentParser};
+
+fn iso_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut data = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(box_type);
+    data.extend_from_slice(payload);
+    data
+}
+
+fn full_box(box_type: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = (((version as u32) << 24) | flags).to_be_bytes().to_vec();
+    data.extend_from_slice(payload);
+    iso_box(box_type, &data)
+}
+
+/// An initialization segment with one track of timescale 90000, whose
+/// samples last 3000 units by default, in a movie of 2 seconds.
+fn iso_initialization_segment() -> Vec<u8> {
+    let mut mvhd = vec![0; 8];
+    mvhd.extend_from_slice(&1000u32.to_be_bytes());
+    mvhd.extend_from_slice(&2000u32.to_be_bytes());
+    let mut tkhd = vec![0; 8];
+    tkhd.extend_from_slice(&1u32.to_be_bytes());
+    let mut mdhd = vec![0; 8];
+    mdhd.extend_from_slice(&90000u32.to_be_bytes());
+    let mut trex = 1u32.to_be_bytes().to_vec();
+    trex.extend_from_slice(&[0; 4]);
+    trex.extend_from_slice(&3000u32.to_be_bytes());
+
+    let mut trak = full_box(b"tkhd", 0, 0, &tkhd);
+    trak.extend(iso_box(b"mdia", &full_box(b"mdhd", 0, 0, &mdhd)));
+    let mut moov = full_box(b"mvhd", 0, 0, &mvhd);
+    moov.extend(iso_box(b"trak", &trak));
+    moov.extend(iso_box(b"mvex", &full_box(b"trex", 0, 0, &trex)));
+
+    let mut data = iso_box(b"ftyp", b"isom\0\0\0\0");
+    data.extend(iso_box(b"moov", &moov));
+    data
+}
+
+/// A `moof` box for 30 samples of track 1, starting at `base_media_decode_time`.
+fn iso_moof(base_media_decode_time: u32) -> Vec<u8> {
+    let mut traf = full_box(b"tfhd", 0, 0, &1u32.to_be_bytes());
+    traf.extend(full_box(
+        b"tfdt",
+        0,
+        0,
+        &base_media_decode_time.to_be_bytes(),
+    ));
+    traf.extend(full_box(b"trun", 0, 0, &30u32.to_be_bytes()));
+    iso_box(b"moof", &iso_box(b"traf", &traf))
+}
+
+fn iso_media_segment(base_media_decode_time: u32) -> Vec<u8> {
+    let mut data = iso_moof(base_media_decode_time);
+    data.extend(iso_box(b"mdat", &[1, 2, 3, 4]));
+    data
+}
+
+fn iso_parser() -> SegmentParser {
+    let mut parser = SegmentParser::new(ByteStreamFormat::IsoBmff);
+    let segments = parser.parse(&mut iso_initialization_segment()).unwrap();
+    assert_eq!(segments.len(), 1);
+    parser
+}
+
+fn media_interval(segments: &[Segment]) -> (f64, f64) {
+    match segments {
+        [Segment::Media { start, end, .. }] => (*start, *end),
+        _ => panic!("expected a single media segment, got {:?}", segments),
+    }
+}
+
+#[test]
+fn byte_stream_formats() {
+    assert_eq!(
+        ByteStreamFormat::from_mime_type("video/mp4; codecs=\"avc1.42E01E\""),
+        Some(ByteStreamFormat::IsoBmff)
+    );
+    assert_eq!(
+        ByteStreamFormat::from_mime_type("AUDIO/WEBM"),
+        Some(ByteStreamFormat::WebM)
+    );
+    assert_eq!(ByteStreamFormat::from_mime_type("video/ogg"), None);
+}
+
+#[test]
+fn iso_bmff_initialization_segment() {
+    let mut parser = SegmentParser::new(ByteStreamFormat::IsoBmff);
+    let mut input = iso_initialization_segment();
+    let expected = input.clone();
+    match &*parser.parse(&mut input).unwrap() {
+        [Segment::Initialization { duration, data }] => {
+            assert_eq!(*duration, Some(2.));
+            assert_eq!(*data, expected);
+        },
+        segments => panic!("expected an initialization segment, got {:?}", segments),
+    }
+    assert!(input.is_empty());
+    assert!(parser.received_initialization_segment());
+}
+
+#[test]
+fn iso_bmff_media_segment() {
+    let mut parser = iso_parser();
+    let segments = parser.parse(&mut iso_media_segment(90000)).unwrap();
+    assert_eq!(media_interval(&segments), (1., 2.));
+}
+
+#[test]
+fn iso_bmff_media_segment_without_initialization_segment() {
+    let mut parser = SegmentParser::new(ByteStreamFormat::IsoBmff);
+    assert_eq!(
+        parser.parse(&mut iso_media_segment(0)).unwrap_err(),
+        ParseError::MissingInitializationSegment
+    );
+}
+
+#[test]
+fn iso_bmff_truncated_boxes_wait_for_more_data() {
+    let mut parser = iso_parser();
+    let segment = iso_media_segment(0);
+    for split in 1..segment.len() {
+        let mut input = segment[..split].to_vec();
+        assert!(parser.parse(&mut input).unwrap().is_empty());
+        assert_eq!(input.len(), split);
+        input.extend_from_slice(&segment[split..]);
+        assert_eq!(parser.parse(&mut input).unwrap().len(), 1);
+        assert!(input.is_empty());
+    }
+}
+
+#[test]
+fn iso_bmff_truncated_child_boxes() {
+    let mut parser = iso_parser();
+    let traf = [
+        full_box(b"tfhd", 0, 0, &1u32.to_be_bytes()),
+        iso_box(b"tfdt", &[0, 0]),
+    ]
+    .concat();
+    let mut input = iso_box(b"moof", &iso_box(b"traf", &traf));
+    input.extend(iso_box(b"mdat", &[]));
+    assert_eq!(
+        parser.parse(&mut input).unwrap_err(),
+        ParseError::InvalidData
+    );
+
+    // A child box claiming to be larger than its parent.
+    let mut moof = iso_box(b"traf", &[]);
+    moof[3] = 0xFF;
+    let mut input = iso_box(b"moof", &moof);
+    input.extend(iso_box(b"mdat", &[]));
+    assert_eq!(
+        parser.parse(&mut input).unwrap_err(),
+        ParseError::InvalidData
+    );
+}
+
+#[test]
+fn iso_bmff_box_sizes_smaller_than_their_header() {
+    let mut parser = SegmentParser::new(ByteStreamFormat::IsoBmff);
+    let mut input = vec![0, 0, 0, 4, b'f', b't', b'y', b'p'];
+    assert_eq!(
+        parser.parse(&mut input).unwrap_err(),
+        ParseError::InvalidData
+    );
+    let mut input = vec![0, 0, 0, 0, b'f', b't', b'y', b'p'];
+    assert_eq!(
+        parser.parse(&mut input).unwrap_err(),
+        ParseError::InvalidData
+    );
+}
+
+#[test]
+fn iso_bmff_timestamp_offset() {
+    let mut parser = iso_parser();
+    let mut data = iso_media_segment(90000);
+    parser.apply_timestamp_offset(&mut data, 1.5).unwrap();
+    let segments = parser.parse(&mut data).unwrap();
+    assert_eq!(media_interval(&segments), (2.5, 3.5));
+}
+
+#[test]
+fn iso_bmff_negative_timestamp_offset_is_clamped() {
+    let mut parser = iso_parser();
+    let mut data = iso_media_segment(90000);
+    parser.apply_timestamp_offset(&mut data, -5.).unwrap();
+    let segments = parser.parse(&mut data).unwrap();
+    assert_eq!(media_interval(&segments), (0., 1.));
+}
+
+#[test]
+fn iso_bmff_timestamp_offset_overflowing_a_32_bits_tfdt() {
+    let parser = iso_parser();
+    let mut data = iso_media_segment(0);
+    assert_eq!(
+        parser.apply_timestamp_offset(&mut data, 100_000.),
+        Err(ParseError::InvalidData)
+    );
+}
+
+#[test]
+fn iso_bmff_timestamp_offset_with_truncated_tfdt() {
+    let parser = iso_parser();
+    let tfhd = full_box(b"tfhd", 0, 0, &1u32.to_be_bytes());
+    for tfdt in &[iso_box(b"tfdt", &[]), iso_box(b"tfdt", &[0, 0, 0, 0, 0, 1])] {
+        let traf = [&tfhd[..], tfdt].concat();
+        let mut data = iso_box(b"moof", &iso_box(b"traf", &traf));
+        assert_eq!(
+            parser.apply_timestamp_offset(&mut data, 1.),
+            Err(ParseError::InvalidData)
+        );
+    }
+}
+
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+fn element_id(id: u32) -> Vec<u8> {
+    id.to_be_bytes()
+        .iter()
+        .cloned()
+        .skip_while(|&byte| byte == 0)
+        .collect()
+}
+
+/// An EBML element, with its size written as an 8 bytes vint.
+fn element(id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = element_id(id);
+    let mut size = (payload.len() as u64).to_be_bytes();
+    size[0] = 0x01;
+    data.extend_from_slice(&size);
+    data.extend_from_slice(payload);
+    data
+}
+
+fn simple_block(timecode: i16) -> Vec<u8> {
+    let mut payload = vec![0x81];
+    payload.extend_from_slice(&timecode.to_be_bytes());
+    payload.push(0x80);
+    element(0xA3, &payload)
+}
+
+/// The EBML header, the start of a segment of unknown size and its `Info`
+/// and `Tracks` elements, for a presentation of 2 seconds.
+fn webm_initialization_segment() -> Vec<u8> {
+    let info = [
+        element(0x2AD7B1, &1_000_000u32.to_be_bytes()),
+        element(0x4489, &2000f64.to_bits().to_be_bytes()),
+    ]
+    .concat();
+    let mut data = element(0x1A45DFA3, &[]);
+    data.extend(element_id(0x18538067));
+    data.extend_from_slice(&UNKNOWN_SIZE);
+    data.extend(element(0x1549A966, &info));
+    data.extend(element(0x1654AE6B, &[]));
+    data
+}
+
+/// The children of a cluster at `timecode` milliseconds, with two blocks
+/// 500 milliseconds apart.
+fn webm_cluster_payload(timecode: u16) -> Vec<u8> {
+    [
+        element(0xE7, &timecode.to_be_bytes()),
+        simple_block(0),
+        simple_block(500),
+    ]
+    .concat()
+}
+
+fn webm_cluster(timecode: u16) -> Vec<u8> {
+    element(0x1F43B675, &webm_cluster_payload(timecode))
+}
+
+fn webm_parser() -> SegmentParser {
+    let mut parser = SegmentParser::new(ByteStreamFormat::WebM);
+    let segments = parser.parse(&mut webm_initialization_segment()).unwrap();
+    assert_eq!(segments.len(), 1);
+    parser
+}
+
+#[test]
+fn webm_initialization_segment_and_clusters() {
+    let mut parser = SegmentParser::new(ByteStreamFormat::WebM);
+    let mut input = webm_initialization_segment();
+    let expected = input.clone();
+    input.extend(webm_cluster(1000));
+    let segments = parser.parse(&mut input).unwrap();
+    match &*segments {
+        [Segment::Initialization { duration, data }, Segment::Media { start, end, .. }] => {
+            assert_eq!(*duration, Some(2.));
+            assert_eq!(*data, expected);
+            assert_eq!((*start, *end), (1., 2.));
+        },
+        segments => panic!("expected two segments, got {:?}", segments),
+    }
+    assert!(input.is_empty());
+}
+
+#[test]
+fn webm_cluster_without_initialization_segment() {
+    let mut parser = SegmentParser::new(ByteStreamFormat::WebM);
+    assert_eq!(
+        parser.parse(&mut webm_cluster(0)).unwrap_err(),
+        ParseError::MissingInitializationSegment
+    );
+}
+
+#[test]
+fn webm_truncated_elements_wait_for_more_data() {
+    let mut parser = webm_parser();
+    let cluster = webm_cluster(0);
+    for split in 1..cluster.len() {
+        let mut input = cluster[..split].to_vec();
+        assert!(parser.parse(&mut input).unwrap().is_empty());
+        assert_eq!(input.len(), split);
+        input.extend_from_slice(&cluster[split..]);
+        assert_eq!(parser.parse(&mut input).unwrap().len(), 1);
+        assert!(input.is_empty());
+    }
+}
+
+#[test]
+fn webm_truncated_child_elements() {
+    let mut parser = webm_parser();
+    // A block too short to hold its timecode.
+    let payload = [element(0xE7, &[0]), element(0xA3, &[0x81, 0])].concat();
+    assert_eq!(
+        parser
+            .parse(&mut element(0x1F43B675, &payload))
+            .unwrap_err(),
+        ParseError::InvalidData
+    );
+
+    // A child element claiming to be larger than its cluster.
+    let mut payload = element(0xE7, &[0]);
+    payload[8] = 0x10;
+    assert_eq!(
+        parser
+            .parse(&mut element(0x1F43B675, &payload))
+            .unwrap_err(),
+        ParseError::InvalidData
+    );
+}
+
+#[test]
+fn webm_unknown_size_cluster() {
+    let mut parser = webm_parser();
+    let mut input = element_id(0x1F43B675);
+    input.extend_from_slice(&UNKNOWN_SIZE);
+    input.extend(webm_cluster_payload(1000));
+    // The end of the cluster is only known once the next element starts.
+    assert!(parser.parse(&mut input).unwrap().is_empty());
+    let first_cluster_length = input.len();
+
+    input.extend(webm_cluster(2000));
+    let segments = parser.parse(&mut input).unwrap();
+    assert_eq!(segments.len(), 2);
+    match &segments[0] {
+        Segment::Media { start, end, data } => {
+            assert_eq!((*start, *end), (1., 2.));
+            assert_eq!(data.len(), first_cluster_length);
+        },
+        segment => panic!("expected a media segment, got {:?}", segment),
+    }
+    assert_eq!(media_interval(&segments[1..]), (2., 3.));
+}
+
+#[test]
+fn webm_timestamp_offset() {
+    let mut parser = webm_parser();
+    let mut data = webm_cluster(1000);
+    parser.apply_timestamp_offset(&mut data, 1.5).unwrap();
+    let segments = parser.parse(&mut data).unwrap();
+    assert_eq!(media_interval(&segments), (2.5, 3.5));
+
+    let mut data = webm_cluster(1000);
+    parser.apply_timestamp_offset(&mut data, -5.).unwrap();
+    let segments = parser.parse(&mut data).unwrap();
+    assert_eq!(media_interval(&segments), (0., 1.));
+}
+
+#[test]
+fn webm_timestamp_offset_of_unknown_size_cluster() {
+    let mut parser = webm_parser();
+    let mut data = element_id(0x1F43B675);
+    data.extend_from_slice(&UNKNOWN_SIZE);
+    data.extend(webm_cluster_payload(1000));
+    parser.apply_timestamp_offset(&mut data, 1.).unwrap();
+    // The rewritten cluster has a known size.
+    let segments = parser.parse(&mut data).unwrap();
+    assert_eq!(media_interval(&segments), (2., 3.));
+}
+
+#[test]
+fn webm_timestamp_offset_of_truncated_cluster() {
+    let parser = webm_parser();
+    let mut data = webm_cluster(1000);
+    data.pop();
+    assert_eq!(
+        parser.apply_timestamp_offset(&mut data, 1.),
+        Err(ParseError::InvalidData)
+    );
+}
@@ -0,0 +1,322 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::subtlecrypto::{AlgorithmName, CipherParams, ImportParams, KeyFormat};
+use script::test::subtlecrypto::{KeyData, KeyMaterial, KeyUsage, Sha, SignatureParams};
+
+fn hex(string: &str) -> Vec<u8> {
+    (0..string.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&string[index..index + 2], 16).unwrap())
+        .collect()
+}
+
+const MESSAGE: &[u8] = b"Hello, world!";
+
+// The NIST SP 800-38A vectors of AES-128.
+const AES_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+const AES_PLAINTEXT: &str = concat!(
+    "6bc1bee22e409f96e93d7e117393172a",
+    "ae2d8a571e03ac9c9eb76fac45af8e51",
+);
+
+// A 1024-bit RSA key, and the results of operations with SHA-256 on MESSAGE.
+const RSA_PKCS8: &str = concat!(
+    "30820278020100300d06092a864886f70d0101010500048202623082025e020100028181",
+    "009d0dd0d7f3d4c82318b47cc5d0e9391049b6ca120ef17de07ceb4cb8ae1b2254c77af4",
+    "d34d51f9b1bbd389b1e8c32358fad843d381bfaa2ac92e6f1d3521a0a521addaf877a938",
+    "39038f6c74906a0bf2ed7b6c74cd894594ed6aed42ba8f534636c38cb9c92b4b670cbb9e",
+    "150f94f39490ea75603d46aeb896f7a489f84187af020301000102818100981573e25b3c",
+    "f6dd7da3f19b8175070c2dc5b3220f946cc7c14ffe086c98dc02eaf4b736130ec9d401c5",
+    "93f65326fc08c3483f4e06bbfa65da517bc9f5d46d81567c02e385db160149db22edd7ea",
+    "a82573521b489526b859a861148af141981960010ae8504a2b4710e73f14cafd9df93323",
+    "6e120529c2a21431c54203a288a1024100ce80cf9f4aa6c140ca97c28388c4c820c89284",
+    "7f7e27d0bd2669b8005dbf588dd7497e26f29e144fdbe916606b8fc6d628f8ec4cb78d84",
+    "41f7fcf590d68a647f024100c2b2c3cc4019702388d4f4156d89dbd4f853520fcf89ef1d",
+    "85062e9d8a571c9eeb4185de7f01d3cf0119c8b5fde37cff759c95f984ececcb68bc98c3",
+    "808c84d10241008428ee37dd1802008c6839e5e2a74609ad341036642fe928d527bdca06",
+    "358ae0245375b849782039c7dd5223dd53040755eb9197bbb5875a8cf2c8daac639fd302",
+    "4100b9a3270b1d66e3c40dd835ee24661883a44e15655bd5c176dcf9fb025d00181af266",
+    "a41473f6b314a1910b0d73492f4a87c2484103dbbf1f66fac3914c852eb1024067337b4b",
+    "c7da7538ab64493834675c2ad9858e65391f3240f463b98e122b095a6703799686d2f105",
+    "26afbfe6517dea8cb81ec490bdd7a7270eea778fcc02edba",
+);
+
+const RSA_SPKI: &str = concat!(
+    "30819f300d06092a864886f70d010101050003818d00308189028181009d0dd0d7f3d4c8",
+    "2318b47cc5d0e9391049b6ca120ef17de07ceb4cb8ae1b2254c77af4d34d51f9b1bbd389",
+    "b1e8c32358fad843d381bfaa2ac92e6f1d3521a0a521addaf877a93839038f6c74906a0b",
+    "f2ed7b6c74cd894594ed6aed42ba8f534636c38cb9c92b4b670cbb9e150f94f39490ea75",
+    "603d46aeb896f7a489f84187af0203010001",
+);
+
+const RSA_PKCS1_V1_5_SIGNATURE: &str = concat!(
+    "1f51a43da1a66990adf0f0e459e9f79dccf14c5c5d9ec3b51d26ae375678a8536c3415ef",
+    "8354a3fbcc6f050bbb3ad93dac19fdf595ac2b8108e4a942459e76d6c5a3140d1ec18954",
+    "c308db7d00f5798d0acaa39b7865d6a13a4d10967842d73631cef33b06ce3535ec00b2db",
+    "2b085ed4b5675f9f28b7abe0ffcb4889e848b27d",
+);
+
+const RSA_PSS_SIGNATURE: &str = concat!(
+    "8204b9629711c764e31db19f763396c68aa21254310a24c61fcde1e5069fbceaef09357d",
+    "867ae636b27dbd63c717c566fa3d3f87be73f3d1a62433f70838897a0aa110be09007f61",
+    "ba387f39dc0a3e148b33aaceed44f71a3ac4a06d7556ad546b222bf4666d5fce130e5f75",
+    "8033ef18bf8194c66141bda964b6734b55b70781",
+);
+
+const RSA_OAEP_CIPHERTEXT: &str = concat!(
+    "35271d9d59f5fe073abfaf3089b471c2bd45498ff816bf136f83b16578a7c9b5b92e1182",
+    "5efc906f1b6d2d9106d7bb721572c5589b1573ef6173a2fd28c383349d1d7a3b56052f8b",
+    "97ae800f12b73a6a78d58cc15256c04ebcc6b0c8fd6eeb6df2efab23fc5ac306361126a1",
+    "267b81d17896cd0c27c64166b7d0ac95f762122e",
+);
+
+fn import(params: ImportParams, format: KeyFormat, key: &str, usages: &[KeyUsage]) -> KeyData {
+    params
+        .import(format, &KeyMaterial::Bytes(hex(key)), false, usages)
+        .unwrap()
+}
+
+fn aes_key(name: AlgorithmName, key: &str) -> KeyData {
+    import(
+        ImportParams::Aes { name },
+        KeyFormat::Raw,
+        key,
+        &[KeyUsage::Encrypt, KeyUsage::Decrypt],
+    )
+}
+
+fn hmac_key(hash: Sha) -> KeyData {
+    import(
+        ImportParams::Hmac { hash, length: None },
+        KeyFormat::Raw,
+        "4a656665",
+        &[KeyUsage::Sign, KeyUsage::Verify],
+    )
+}
+
+fn rsa_key(name: AlgorithmName, format: KeyFormat, usage: KeyUsage) -> KeyData {
+    let key = match format {
+        KeyFormat::Pkcs8 => RSA_PKCS8,
+        _ => RSA_SPKI,
+    };
+    import(
+        ImportParams::Rsa {
+            name,
+            hash: Sha::Sha256,
+        },
+        format,
+        key,
+        &[usage],
+    )
+}
+
+#[test]
+fn aes_ctr_vectors() {
+    let key = aes_key(AlgorithmName::AesCtr, AES_KEY);
+    let params = CipherParams::AesCtr {
+        counter: hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"),
+        length: 128,
+    };
+    let ciphertext = hex(concat!(
+        "874d6191b620e3261bef6864990db6ce",
+        "9806f66b7970fdff8617187bb9fffdff",
+    ));
+    assert_eq!(
+        params.encrypt(&key, &hex(AES_PLAINTEXT)).unwrap(),
+        ciphertext
+    );
+    assert_eq!(
+        params.decrypt(&key, &ciphertext).unwrap(),
+        hex(AES_PLAINTEXT)
+    );
+}
+
+#[test]
+fn aes_ctr_increments_the_rightmost_bits_of_the_counter() {
+    let key = aes_key(AlgorithmName::AesCtr, AES_KEY);
+    // The counter wraps around to f0f1...fe00 rather than f0f1...ff00.
+    let params = CipherParams::AesCtr {
+        counter: hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"),
+        length: 8,
+    };
+    let ciphertext = hex(concat!(
+        "874d6191b620e3261bef6864990db6ce",
+        "e3256531c56fd498e4670c36587faba6",
+    ));
+    assert_eq!(
+        params.encrypt(&key, &hex(AES_PLAINTEXT)).unwrap(),
+        ciphertext
+    );
+
+    // The counter can't repeat itself.
+    let params = CipherParams::AesCtr {
+        counter: hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"),
+        length: 1,
+    };
+    assert!(params.encrypt(&key, &[0; 33]).is_err());
+    assert!(params.encrypt(&key, &[0; 32]).is_ok());
+}
+
+#[test]
+fn aes_cbc_vectors() {
+    let key = aes_key(AlgorithmName::AesCbc, AES_KEY);
+    let params = CipherParams::AesCbc {
+        iv: hex("000102030405060708090a0b0c0d0e0f"),
+    };
+    // The NIST ciphertext, followed by the block of PKCS#7 padding.
+    let ciphertext = hex(concat!(
+        "7649abac8119b246cee98e9b12e9197d",
+        "5086cb9b507219ee95db113a917678b2",
+        "55e21d7100b988ffec32feeafaf23538",
+    ));
+    assert_eq!(
+        params.encrypt(&key, &hex(AES_PLAINTEXT)).unwrap(),
+        ciphertext
+    );
+    assert_eq!(
+        params.decrypt(&key, &ciphertext).unwrap(),
+        hex(AES_PLAINTEXT)
+    );
+    assert!(params.decrypt(&key, &ciphertext[..32]).is_err());
+
+    let params = CipherParams::AesCbc { iv: vec![0; 8] };
+    assert!(params.encrypt(&key, &hex(AES_PLAINTEXT)).is_err());
+}
+
+#[test]
+fn aes_gcm_vectors() {
+    // Test case 4 of the specification of GCM.
+    let key = aes_key(AlgorithmName::AesGcm, "feffe9928665731c6d6a8f9467308308");
+    let plaintext = hex(concat!(
+        "d9313225f88406e5a55909c5aff5269a",
+        "86a7a9531534f7da2e4c303d8a318a72",
+        "1c3c0c95956809532fcf0e2449a6b525",
+        "b16aedf5aa0de657ba637b39",
+    ));
+    let encrypted = hex(concat!(
+        "42831ec2217774244b7221b784d0d49c",
+        "e3aa212f2c02a4e035c17e2329aca12e",
+        "21d514b25466931c7d8f6a5aac84aa05",
+        "1ba30b396a0aac973d58e091",
+    ));
+    let tag = hex("5bc94fbc3221a5db94fae95ae7121a47");
+    let params = |tag_length| CipherParams::AesGcm {
+        iv: hex("cafebabefacedbaddecaf888"),
+        additional_data: Some(hex("feedfacedeadbeeffeedfacedeadbeefabaddad2")),
+        tag_length,
+    };
+
+    let ciphertext = [&*encrypted, &*tag].concat();
+    assert_eq!(params(128).encrypt(&key, &plaintext).unwrap(), ciphertext);
+    assert_eq!(params(128).decrypt(&key, &ciphertext).unwrap(), plaintext);
+
+    // Shorter tags are truncated.
+    let ciphertext = [&*encrypted, &tag[..12]].concat();
+    assert_eq!(params(96).encrypt(&key, &plaintext).unwrap(), ciphertext);
+    assert_eq!(params(96).decrypt(&key, &ciphertext).unwrap(), plaintext);
+
+    let mut tampered = ciphertext.clone();
+    tampered[0] ^= 1;
+    assert!(params(96).decrypt(&key, &tampered).is_err());
+    assert!(params(100).encrypt(&key, &plaintext).is_err());
+}
+
+#[test]
+fn hmac_vectors() {
+    // Test case 2 of RFC 2202 and RFC 4231.
+    let data = b"what do ya want for nothing?";
+    let vectors = [
+        (Sha::Sha1, "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+        (
+            Sha::Sha256,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ),
+        (
+            Sha::Sha512,
+            concat!(
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554",
+                "9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+        ),
+    ];
+    for &(hash, signature) in &vectors {
+        let key = hmac_key(hash);
+        let signature = hex(signature);
+        assert_eq!(SignatureParams::Hmac.sign(&key, data).unwrap(), signature);
+        assert!(SignatureParams::Hmac
+            .verify(&key, &signature, data)
+            .unwrap());
+        assert!(!SignatureParams::Hmac
+            .verify(&key, &signature[1..], data)
+            .unwrap());
+        assert!(!SignatureParams::Hmac
+            .verify(&key, &signature, b"what do ya want for something?")
+            .unwrap());
+    }
+}
+
+#[test]
+fn rsassa_pkcs1_v1_5_vectors() {
+    let private_key = rsa_key(
+        AlgorithmName::RsassaPkcs1V1_5,
+        KeyFormat::Pkcs8,
+        KeyUsage::Sign,
+    );
+    let public_key = rsa_key(
+        AlgorithmName::RsassaPkcs1V1_5,
+        KeyFormat::Spki,
+        KeyUsage::Verify,
+    );
+    let params = SignatureParams::RsassaPkcs1V1_5;
+    let signature = hex(RSA_PKCS1_V1_5_SIGNATURE);
+    assert_eq!(params.sign(&private_key, MESSAGE).unwrap(), signature);
+    assert!(params.verify(&public_key, &signature, MESSAGE).unwrap());
+    assert!(!params.verify(&public_key, &signature, b"Hello").unwrap());
+    assert!(!params
+        .verify(&public_key, &signature[1..], MESSAGE)
+        .unwrap());
+}
+
+#[test]
+fn rsa_pss_vectors() {
+    let private_key = rsa_key(AlgorithmName::RsaPss, KeyFormat::Pkcs8, KeyUsage::Sign);
+    let public_key = rsa_key(AlgorithmName::RsaPss, KeyFormat::Spki, KeyUsage::Verify);
+    let params = SignatureParams::RsaPss { salt_length: 32 };
+    let signature = hex(RSA_PSS_SIGNATURE);
+    assert!(params.verify(&public_key, &signature, MESSAGE).unwrap());
+    assert!(!params.verify(&public_key, &signature, b"Hello").unwrap());
+    // The salt length is part of the signature.
+    let params_without_salt = SignatureParams::RsaPss { salt_length: 0 };
+    assert!(!params_without_salt
+        .verify(&public_key, &signature, MESSAGE)
+        .unwrap());
+
+    // Signatures are randomized.
+    let signature = params.sign(&private_key, MESSAGE).unwrap();
+    assert_ne!(signature, hex(RSA_PSS_SIGNATURE));
+    assert!(params.verify(&public_key, &signature, MESSAGE).unwrap());
+}
+
+#[test]
+fn rsa_oaep_vectors() {
+    let private_key = rsa_key(AlgorithmName::RsaOaep, KeyFormat::Pkcs8, KeyUsage::Decrypt);
+    let public_key = rsa_key(AlgorithmName::RsaOaep, KeyFormat::Spki, KeyUsage::Encrypt);
+    let params = CipherParams::RsaOaep {
+        label: Some(b"label".to_vec()),
+    };
+    let ciphertext = hex(RSA_OAEP_CIPHERTEXT);
+    assert_eq!(params.decrypt(&private_key, &ciphertext).unwrap(), MESSAGE);
+    let without_label = CipherParams::RsaOaep { label: None };
+    assert!(without_label.decrypt(&private_key, &ciphertext).is_err());
+
+    // Encryption is randomized.
+    let encrypted = params.encrypt(&public_key, MESSAGE).unwrap();
+    assert_ne!(encrypted, ciphertext);
+    assert_eq!(params.decrypt(&private_key, &encrypted).unwrap(), MESSAGE);
+
+    // Keys can only be used for their own operation.
+    assert!(params.encrypt(&private_key, MESSAGE).is_err());
+    assert!(params.decrypt(&public_key, &ciphertext).is_err());
+}
@@ -85,3 +85,23 @@ fn add_overlapping() {
     time_ranges.add(6., 9.).unwrap();
     check(&time_ranges, "[-1,11)");
 }
+
+#[test]
+fn remove() {
+    let mut time_ranges = TimeRangesContainer::new();
+
+    time_ranges.add(0., 10.).unwrap();
+    time_ranges.add(20., 30.).unwrap();
+
+    time_ranges.remove(10., 20.);
+    check(&time_ranges, "[0,10), [20,30)");
+
+    time_ranges.remove(2., 4.);
+    check(&time_ranges, "[0,2), [4,10), [20,30)");
+
+    time_ranges.remove(8., 25.);
+    check(&time_ranges, "[0,2), [4,8), [25,30)");
+
+    time_ranges.remove(-1., f64::INFINITY);
+    check(&time_ranges, "");
+}
@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::webauthn::get_registrable_domain_suffix_of_or_is_equal_to;
+use script::test::webauthn::MakeCredentialResponse;
+use script::test::webauthn::{anonymize_attestation, attestation_object, attested_credential_id};
+use servo_url::Host;
+
+const AAGUID: [u8; 16] = [0xaa; 16];
+const CREDENTIAL_ID: [u8; 4] = [1, 2, 3, 4];
+
+/// Authenticator data with the given flags, followed by attested credential
+/// data whose credential id is `CREDENTIAL_ID` and whose public key is an
+/// empty CBOR map.
+fn authenticator_data(flags: u8) -> Vec<u8> {
+    let mut data = vec![0x11; 32];
+    data.push(flags);
+    data.extend_from_slice(&[0, 0, 0, 1]);
+    data.extend_from_slice(&AAGUID);
+    data.extend_from_slice(&(CREDENTIAL_ID.len() as u16).to_be_bytes());
+    data.extend_from_slice(&CREDENTIAL_ID);
+    data.push(0xa0);
+    data
+}
+
+fn response(format: &str, authenticator_data: Vec<u8>) -> MakeCredentialResponse {
+    MakeCredentialResponse {
+        format: format.to_owned(),
+        authenticator_data,
+        // {"alg": -7}
+        attestation_statement: vec![0xa1, 0x63, b'a', b'l', b'g', 0x26],
+    }
+}
+
+fn domain(domain: &str) -> Host {
+    Host::Domain(domain.to_owned())
+}
+
+#[test]
+fn test_attested_credential_id() {
+    // User present, user verified and attested credential data included.
+    let data = authenticator_data(0x45);
+    assert_eq!(attested_credential_id(&data), Some(&CREDENTIAL_ID[..]));
+}
+
+#[test]
+fn test_attested_credential_id_without_attested_credential_data() {
+    let data = authenticator_data(0x05);
+    assert_eq!(attested_credential_id(&data), None);
+}
+
+#[test]
+fn test_attested_credential_id_truncated() {
+    let data = authenticator_data(0x45);
+    assert_eq!(attested_credential_id(&data[..57]), None);
+    assert_eq!(attested_credential_id(&data[..54]), None);
+    assert_eq!(attested_credential_id(&data[..32]), None);
+    assert_eq!(attested_credential_id(&[]), None);
+}
+
+#[test]
+fn test_anonymize_attestation() {
+    let mut response = response("packed", authenticator_data(0x45));
+    anonymize_attestation(&mut response);
+    assert_eq!(response.format, "none");
+    assert_eq!(response.attestation_statement, [0xa0]);
+
+    let mut expected = authenticator_data(0x45);
+    for byte in expected[37..53].iter_mut() {
+        *byte = 0;
+    }
+    assert_eq!(response.authenticator_data, expected);
+    assert_eq!(
+        attested_credential_id(&response.authenticator_data),
+        Some(&CREDENTIAL_ID[..])
+    );
+}
+
+#[test]
+fn test_anonymize_none_attestation() {
+    let mut response = response("none", authenticator_data(0x45));
+    anonymize_attestation(&mut response);
+    assert_eq!(response.format, "none");
+    assert_eq!(response.authenticator_data, authenticator_data(0x45));
+}
+
+#[test]
+fn test_attestation_object() {
+    let data = authenticator_data(0x45);
+    let object = attestation_object(&response("packed", data.clone()));
+
+    let mut expected = vec![0xa3];
+    expected.push(0x63);
+    expected.extend_from_slice(b"fmt");
+    expected.push(0x66);
+    expected.extend_from_slice(b"packed");
+    expected.push(0x67);
+    expected.extend_from_slice(b"attStmt");
+    expected.extend_from_slice(&[0xa1, 0x63, b'a', b'l', b'g', 0x26]);
+    expected.push(0x68);
+    expected.extend_from_slice(b"authData");
+    expected.extend_from_slice(&[0x58, data.len() as u8]);
+    expected.extend_from_slice(&data);
+    assert_eq!(object, expected);
+}
+
+#[test]
+fn test_attestation_object_byte_string_lengths() {
+    let header = |length: usize| {
+        let object = attestation_object(&response("none", vec![0; length]));
+        // The header of the byte string follows the "authData" key, 33 bytes
+        // in the object.
+        assert_eq!(&object[25..33], b"authData");
+        object[33..object.len() - length].to_vec()
+    };
+    assert_eq!(header(0), [0x40]);
+    assert_eq!(header(23), [0x57]);
+    assert_eq!(header(24), [0x58, 24]);
+    assert_eq!(header(255), [0x58, 0xff]);
+    assert_eq!(header(256), [0x59, 0x01, 0x00]);
+    assert_eq!(header(70000), [0x5a, 0x00, 0x01, 0x11, 0x70]);
+}
+
+#[test]
+fn test_relying_party_id() {
+    let origin = || domain("login.example.com");
+    assert_eq!(
+        get_registrable_domain_suffix_of_or_is_equal_to("login.example.com", origin()),
+        Some(origin())
+    );
+    assert_eq!(
+        get_registrable_domain_suffix_of_or_is_equal_to("example.com", origin()),
+        Some(domain("example.com"))
+    );
+    assert_eq!(
+        get_registrable_domain_suffix_of_or_is_equal_to("EXAMPLE.com", origin()),
+        Some(domain("example.com"))
+    );
+}
+
+#[test]
+fn test_relying_party_id_rejected() {
+    let origin = || domain("login.example.com");
+    for rp_id in &[
+        "",
+        "com",
+        "other.com",
+        "ample.com",
+        "www.login.example.com",
+        "invalid domain.com",
+        "127.0.0.1",
+    ] {
+        assert_eq!(
+            get_registrable_domain_suffix_of_or_is_equal_to(rp_id, origin()),
+            None,
+            "{} is accepted",
+            rp_id
+        );
+    }
+}
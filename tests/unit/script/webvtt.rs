@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::webvtt::{parse, parse_cue_text, CueElementKind, CueNode, CueSettings};
+use script::test::webvtt::{LineAlignment, TextAlignment, WritingDirection};
+
+#[test]
+fn rejects_missing_signature() {
+    assert!(parse("").is_none());
+    assert!(parse("WEBVTTX\n").is_none());
+    assert!(parse("00:00.000 --> 00:01.000\nHello\n").is_none());
+}
+
+#[test]
+fn parses_cues() {
+    let cues = parse(
+        "\u{feff}WEBVTT - Some title\r\nKind: captions\r\n\r\n\
+         NOTE a comment\r\n\r\n\
+         intro\r\n00:00.500 --> 00:02.000\r\nHello\r\nworld\r\n\r\n\
+         01:00:00.000 --> 01:00:01.250\r\nLater\r\n",
+    )
+    .unwrap();
+    assert_eq!(cues.len(), 2);
+    assert_eq!(cues[0].id, "intro");
+    assert_eq!(cues[0].start_time, 0.5);
+    assert_eq!(cues[0].end_time, 2.);
+    assert_eq!(cues[0].text, "Hello\nworld");
+    assert_eq!(cues[0].settings, CueSettings::default());
+    assert_eq!(cues[1].id, "");
+    assert_eq!(cues[1].start_time, 3600.);
+    assert_eq!(cues[1].end_time, 3601.25);
+}
+
+#[test]
+fn skips_cues_with_invalid_timings() {
+    let cues = parse(
+        "WEBVTT\n\n00:00.5 --> 00:01.000\nBad\n\n\
+         00:61.000 --> 00:62.000\nBad\n\n\
+         00:01.000 --> 00:02.000\nGood\n",
+    )
+    .unwrap();
+    assert_eq!(cues.len(), 1);
+    assert_eq!(cues[0].text, "Good");
+}
+
+#[test]
+fn parses_cue_settings() {
+    let cues = parse(
+        "WEBVTT\n\n00:00.000 --> 00:01.000 vertical:rl line:-2,end position:25%,line-left \
+         size:50% align:left bogus:1 line:foo\nText\n",
+    )
+    .unwrap();
+    let settings = &cues[0].settings;
+    assert_eq!(
+        settings.writing_direction,
+        WritingDirection::VerticalGrowingLeft
+    );
+    assert_eq!(settings.line, Some(-2.));
+    assert!(settings.snap_to_lines);
+    assert_eq!(settings.line_alignment, LineAlignment::End);
+    assert_eq!(settings.position, Some(25.));
+    assert_eq!(settings.size, 50.);
+    assert_eq!(settings.text_alignment, TextAlignment::Left);
+
+    let cues = parse("WEBVTT\n\n00:00.000 --> 00:01.000 line:80% size:120%\nText\n").unwrap();
+    let settings = &cues[0].settings;
+    assert_eq!(settings.line, Some(80.));
+    assert!(!settings.snap_to_lines);
+    assert_eq!(settings.size, 100.);
+}
+
+#[test]
+fn parses_cue_text() {
+    let nodes = parse_cue_text("<v.loud Roger Bingham>Hi &amp; <b>bye<00:00:01.000></v>");
+    assert_eq!(
+        nodes,
+        vec![CueNode::Element {
+            kind: CueElementKind::Voice,
+            classes: vec!["loud".to_owned()],
+            annotation: Some("Roger Bingham".to_owned()),
+            children: vec![
+                CueNode::Text("Hi & ".to_owned()),
+                CueNode::Element {
+                    kind: CueElementKind::Bold,
+                    classes: vec![],
+                    annotation: None,
+                    children: vec![CueNode::Text("bye".to_owned()), CueNode::Timestamp(1.)],
+                },
+            ],
+        }]
+    );
+}
+
+#[test]
+fn ignores_unknown_and_misplaced_tags() {
+    let nodes = parse_cue_text("<foo>a</foo><rt>b</rt><ruby>c<rt>d</ruby>");
+    assert_eq!(
+        nodes,
+        vec![
+            CueNode::Text("a".to_owned()),
+            CueNode::Text("b".to_owned()),
+            CueNode::Element {
+                kind: CueElementKind::Ruby,
+                classes: vec![],
+                annotation: None,
+                children: vec![
+                    CueNode::Text("c".to_owned()),
+                    CueNode::Element {
+                        kind: CueElementKind::RubyText,
+                        classes: vec![],
+                        annotation: None,
+                        children: vec![CueNode::Text("d".to_owned())],
+                    },
+                ],
+            },
+        ]
+    );
+}
@@ -0,0 +1,311 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xpath::{compare, number_to_string, round, string_to_number};
+use script::test::xpath::{parse, Axis, BinaryOperator, Expr, NodeTest, ParseError, PathStart};
+use script::test::xpath::{Step, Value};
+
+fn step(axis: Axis, node_test: NodeTest) -> Step {
+    Step {
+        axis,
+        node_test,
+        predicates: vec![],
+    }
+}
+
+fn child(name: &str) -> Step {
+    step(Axis::Child, NodeTest::Name(None, name.to_owned()))
+}
+
+fn relative(steps: Vec<Step>) -> Expr {
+    Expr::Path(PathStart::ContextNode, steps)
+}
+
+fn binary(left: Expr, operator: BinaryOperator, right: Expr) -> Expr {
+    Expr::Binary(Box::new(left), operator, Box::new(right))
+}
+
+#[test]
+fn parses_location_paths() {
+    assert_eq!(parse("a/b"), Ok(relative(vec![child("a"), child("b")])));
+    assert_eq!(parse("/"), Ok(Expr::Path(PathStart::Root, vec![])));
+    assert_eq!(
+        parse("/a"),
+        Ok(Expr::Path(PathStart::Root, vec![child("a")]))
+    );
+    assert_eq!(
+        parse("child::a / descendant :: x:b/attribute::*"),
+        Ok(relative(vec![
+            child("a"),
+            step(
+                Axis::Descendant,
+                NodeTest::Name(Some("x".to_owned()), "b".to_owned())
+            ),
+            step(Axis::Attribute, NodeTest::Any),
+        ]))
+    );
+    assert_eq!(
+        parse("x:*/text()/comment()/node()/processing-instruction('p')"),
+        Ok(relative(vec![
+            step(Axis::Child, NodeTest::Namespace("x".to_owned())),
+            step(Axis::Child, NodeTest::Text),
+            step(Axis::Child, NodeTest::Comment),
+            step(Axis::Child, NodeTest::Node),
+            step(
+                Axis::Child,
+                NodeTest::ProcessingInstruction(Some("p".to_owned()))
+            ),
+        ]))
+    );
+}
+
+#[test]
+fn parses_abbreviations() {
+    let descendant_or_self = step(Axis::DescendantOrSelf, NodeTest::Node);
+    assert_eq!(
+        parse("//a"),
+        Ok(Expr::Path(
+            PathStart::Root,
+            vec![descendant_or_self.clone(), child("a")]
+        ))
+    );
+    assert_eq!(
+        parse("a//b"),
+        Ok(relative(vec![child("a"), descendant_or_self, child("b")]))
+    );
+    assert_eq!(
+        parse("./../@id"),
+        Ok(relative(vec![
+            step(Axis::SelfAxis, NodeTest::Node),
+            step(Axis::Parent, NodeTest::Node),
+            step(Axis::Attribute, NodeTest::Name(None, "id".to_owned())),
+        ]))
+    );
+}
+
+#[test]
+fn parses_operators_by_precedence() {
+    assert_eq!(
+        parse("1 + 2 * 3 = 7 or 1 and 0"),
+        Ok(binary(
+            binary(
+                binary(
+                    Expr::Number(1.),
+                    BinaryOperator::Add,
+                    binary(Expr::Number(2.), BinaryOperator::Multiply, Expr::Number(3.)),
+                ),
+                BinaryOperator::Equal,
+                Expr::Number(7.),
+            ),
+            BinaryOperator::Or,
+            binary(Expr::Number(1.), BinaryOperator::And, Expr::Number(0.)),
+        ))
+    );
+    assert_eq!(
+        parse("8 - 4 - 2"),
+        Ok(binary(
+            binary(Expr::Number(8.), BinaryOperator::Subtract, Expr::Number(4.)),
+            BinaryOperator::Subtract,
+            Expr::Number(2.),
+        ))
+    );
+    assert_eq!(
+        parse("-a | b"),
+        Ok(Expr::Negate(Box::new(binary(
+            relative(vec![child("a")]),
+            BinaryOperator::Union,
+            relative(vec![child("b")]),
+        ))))
+    );
+}
+
+#[test]
+fn disambiguates_operator_names() {
+    // The first `div` and the `*` after `/` are name tests, the others are
+    // operators.
+    assert_eq!(
+        parse("div div div * */*"),
+        Ok(binary(
+            binary(
+                relative(vec![child("div")]),
+                BinaryOperator::Divide,
+                relative(vec![child("div")]),
+            ),
+            BinaryOperator::Multiply,
+            relative(vec![
+                step(Axis::Child, NodeTest::Any),
+                step(Axis::Child, NodeTest::Any)
+            ]),
+        ))
+    );
+    assert_eq!(
+        parse("mod mod .5"),
+        Ok(binary(
+            relative(vec![child("mod")]),
+            BinaryOperator::Modulo,
+            Expr::Number(0.5),
+        ))
+    );
+}
+
+#[test]
+fn parses_predicates_and_filters() {
+    let mut a = child("a");
+    a.predicates = vec![
+        Expr::Number(1.),
+        binary(
+            Expr::Function("last".to_owned(), vec![]),
+            BinaryOperator::GreaterOrEqual,
+            Expr::Number(2.),
+        ),
+    ];
+    assert_eq!(parse("a[1][last() >= 2]"), Ok(relative(vec![a])));
+
+    let id = Expr::Function(
+        "id".to_owned(),
+        vec![Expr::Literal("x".to_owned()), Expr::Literal("y".to_owned())],
+    );
+    assert_eq!(
+        parse("id('x', \"y\")/b"),
+        Ok(Expr::Path(
+            PathStart::Filter(Box::new(id)),
+            vec![child("b")]
+        ))
+    );
+    assert_eq!(
+        parse("(//a)[2]"),
+        Ok(Expr::Filter(
+            Box::new(Expr::Path(
+                PathStart::Root,
+                vec![step(Axis::DescendantOrSelf, NodeTest::Node), child("a")]
+            )),
+            vec![Expr::Number(2.)]
+        ))
+    );
+    assert_eq!(parse("x:f()"), Ok(Expr::Function("x:f".to_owned(), vec![])));
+}
+
+#[test]
+fn rejects_invalid_expressions() {
+    assert_eq!(
+        parse("foo::a"),
+        Err(ParseError::UnknownAxis("foo".to_owned()))
+    );
+    assert_eq!(parse("$x + 1"), Err(ParseError::Variable("x".to_owned())));
+    assert_eq!(parse("a/"), Err(ParseError::UnexpectedEnd));
+    assert_eq!(parse("count(a"), Err(ParseError::UnexpectedEnd));
+    assert_eq!(parse("'a"), Err(ParseError::UnexpectedEnd));
+    assert!(parse("").is_err());
+    assert!(parse("a b").is_err());
+    assert!(parse("1 2").is_err());
+    assert!(parse("a[1").is_err());
+    assert!(parse("#").is_err());
+}
+
+#[test]
+fn converts_values() {
+    assert!(Value::Boolean(true).boolean());
+    assert!(!Value::Number(0.).boolean());
+    assert!(!Value::Number(::std::f64::NAN).boolean());
+    assert!(Value::Number(-1.).boolean());
+    assert!(!Value::String(String::new()).boolean());
+    assert!(Value::String("false".to_owned()).boolean());
+    assert!(!Value::NodeSet(vec![]).boolean());
+
+    assert_eq!(Value::Boolean(true).number(), 1.);
+    assert_eq!(Value::String(" 4 ".to_owned()).number(), 4.);
+    assert!(Value::NodeSet(vec![]).number().is_nan());
+
+    assert_eq!(Value::Boolean(false).string(), "false");
+    assert_eq!(Value::Number(2.).string(), "2");
+    assert_eq!(Value::NodeSet(vec![]).string(), "");
+}
+
+#[test]
+fn converts_strings_to_numbers() {
+    assert_eq!(string_to_number("12"), 12.);
+    assert_eq!(string_to_number(" \t12.5\n"), 12.5);
+    assert_eq!(string_to_number("-3"), -3.);
+    assert_eq!(string_to_number(".5"), 0.5);
+    assert_eq!(string_to_number("5."), 5.);
+    for invalid in &[
+        "", " ", "-", ".", "+1", "1e3", "1.2.3", "0x10", "Infinity", "1 2",
+    ] {
+        assert!(string_to_number(invalid).is_nan(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn converts_numbers_to_strings() {
+    assert_eq!(number_to_string(1.), "1");
+    assert_eq!(number_to_string(-1.5), "-1.5");
+    assert_eq!(number_to_string(0.1), "0.1");
+    assert_eq!(number_to_string(-0.), "0");
+    assert_eq!(number_to_string(1e21), "1000000000000000000000");
+    assert_eq!(number_to_string(::std::f64::NAN), "NaN");
+    assert_eq!(number_to_string(::std::f64::INFINITY), "Infinity");
+    assert_eq!(number_to_string(::std::f64::NEG_INFINITY), "-Infinity");
+}
+
+#[test]
+fn rounds_numbers() {
+    assert_eq!(round(2.5), 3.);
+    assert_eq!(round(2.4), 2.);
+    assert_eq!(round(-2.5), -2.);
+    assert_eq!(round(-2.6), -3.);
+    let negative_zero = round(-0.3);
+    assert_eq!(negative_zero, 0.);
+    assert!(negative_zero.is_sign_negative());
+    assert!(round(::std::f64::NAN).is_nan());
+    assert_eq!(round(::std::f64::INFINITY), ::std::f64::INFINITY);
+}
+
+#[test]
+fn compares_values() {
+    let number = Value::Number;
+    let string = |string: &str| Value::String(string.to_owned());
+
+    // Booleans take precedence over numbers, which take precedence over
+    // strings.
+    assert!(compare(
+        BinaryOperator::Equal,
+        &Value::Boolean(true),
+        &string("x")
+    ));
+    assert!(compare(BinaryOperator::Equal, &number(1.), &string(" 1 ")));
+    assert!(compare(
+        BinaryOperator::NotEqual,
+        &string("1"),
+        &string("1.0")
+    ));
+    assert!(!compare(
+        BinaryOperator::Equal,
+        &number(::std::f64::NAN),
+        &number(::std::f64::NAN)
+    ));
+
+    // Relational operators always compare numbers.
+    assert!(compare(BinaryOperator::Less, &string("2"), &string("10")));
+    assert!(compare(
+        BinaryOperator::GreaterOrEqual,
+        &number(2.),
+        &Value::Boolean(true)
+    ));
+    assert!(!compare(
+        BinaryOperator::LessOrEqual,
+        &string("a"),
+        &string("a")
+    ));
+
+    // An empty node-set only equals false.
+    let empty = Value::NodeSet(vec![]);
+    assert!(compare(
+        BinaryOperator::Equal,
+        &empty,
+        &Value::Boolean(false)
+    ));
+    assert!(!compare(BinaryOperator::Equal, &empty, &string("")));
+    assert!(!compare(BinaryOperator::NotEqual, &empty, &string("")));
+    assert!(!compare(BinaryOperator::Less, &number(0.), &empty));
+}
@@ -0,0 +1,183 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use style::animation::{ScriptAnimationState, ScriptAnimationTiming};
+use style::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
+use style::values::generics::easing::{StepPosition, TimingFunction, TimingKeyword};
+
+fn timing(duration: f64, iterations: f64) -> ScriptAnimationTiming {
+    ScriptAnimationTiming {
+        delay: 0.,
+        end_delay: 0.,
+        fill_backwards: false,
+        fill_forwards: false,
+        iteration_start: 0.,
+        iterations,
+        duration,
+        direction: AnimationDirection::Normal,
+        easing: TimingFunction::Keyword(TimingKeyword::Linear),
+    }
+}
+
+fn state(timing: ScriptAnimationTiming, current_time: f64) -> ScriptAnimationState {
+    ScriptAnimationState {
+        timing,
+        current_time,
+        playback_rate: 1.,
+        paused: false,
+        synced_at: Some(10.),
+        expired: false,
+        cascade_style: None,
+    }
+}
+
+fn assert_progress(
+    timing: &ScriptAnimationTiming,
+    local_time: f64,
+    backwards: bool,
+    expected: Option<(f64, f64)>,
+) {
+    let progress = timing.progress(local_time, backwards);
+    match (progress, expected) {
+        (Some((progress, iteration)), Some((expected_progress, expected_iteration))) => {
+            assert!(
+                (progress - expected_progress).abs() < 1e-2,
+                "progress {} isn't {}",
+                progress,
+                expected_progress
+            );
+            assert_eq!(iteration, expected_iteration);
+        },
+        (progress, expected) => assert_eq!(progress, expected),
+    }
+}
+
+#[test]
+fn test_script_animation_end_time() {
+    let mut timing = timing(2., 3.);
+    timing.delay = 1.;
+    timing.end_delay = 0.5;
+    assert_eq!(timing.active_duration(), 6.);
+    assert_eq!(timing.end_time(), 7.5);
+
+    timing.end_delay = -10.;
+    assert_eq!(timing.end_time(), 0.);
+
+    timing.duration = 0.;
+    assert_eq!(timing.active_duration(), 0.);
+}
+
+#[test]
+fn test_script_animation_progress_in_active_phase() {
+    let timing = timing(2., 1.);
+    assert_progress(&timing, 0., false, Some((0., 0.)));
+    assert_progress(&timing, 0.5, false, Some((0.25, 0.)));
+    assert_progress(&timing, 1., false, Some((0.5, 0.)));
+}
+
+#[test]
+fn test_script_animation_progress_with_delay_and_fill() {
+    let mut timing = timing(2., 1.);
+    timing.delay = 1.;
+    assert_progress(&timing, 0.5, false, None);
+    assert_progress(&timing, 3., false, None);
+
+    timing.fill_backwards = true;
+    assert_progress(&timing, 0.5, false, Some((0., 0.)));
+    assert_progress(&timing, 3., false, None);
+
+    timing.fill_forwards = true;
+    assert_progress(&timing, 4., false, Some((1., 0.)));
+}
+
+#[test]
+fn test_script_animation_progress_at_phase_boundaries() {
+    let mut timing = timing(2., 1.);
+    // The animation is in its before phase at its start when it plays
+    // backwards, and in its after phase at its end when it plays forwards.
+    assert_progress(&timing, 0., true, None);
+    assert_progress(&timing, 2., false, None);
+    assert_progress(&timing, 2., true, Some((1., 0.)));
+
+    timing.fill_forwards = true;
+    assert_progress(&timing, 2., false, Some((1., 0.)));
+}
+
+#[test]
+fn test_script_animation_progress_of_iterations() {
+    let mut timing = timing(2., 3.);
+    timing.fill_forwards = true;
+    assert_progress(&timing, 3., false, Some((0.5, 1.)));
+    assert_progress(&timing, 5., false, Some((0.5, 2.)));
+    // The last iteration ends at its end, rather than the next one starting.
+    assert_progress(&timing, 6., false, Some((1., 2.)));
+
+    timing.iteration_start = 0.5;
+    assert_progress(&timing, 0., false, Some((0.5, 0.)));
+    assert_progress(&timing, 1., false, Some((0., 1.)));
+}
+
+#[test]
+fn test_script_animation_progress_with_direction() {
+    let mut timing = timing(2., 2.);
+    timing.direction = AnimationDirection::Reverse;
+    assert_progress(&timing, 0.5, false, Some((0.75, 0.)));
+
+    timing.direction = AnimationDirection::Alternate;
+    assert_progress(&timing, 0.5, false, Some((0.25, 0.)));
+    assert_progress(&timing, 2.5, false, Some((0.75, 1.)));
+
+    timing.direction = AnimationDirection::AlternateReverse;
+    assert_progress(&timing, 0.5, false, Some((0.75, 0.)));
+    assert_progress(&timing, 2.5, false, Some((0.25, 1.)));
+}
+
+#[test]
+fn test_script_animation_progress_with_easing() {
+    let mut timing = timing(1., 1.);
+    timing.easing = TimingFunction::Steps(4, StepPosition::End);
+    assert_progress(&timing, 0.3, false, Some((0.25, 0.)));
+    assert_progress(&timing, 0.6, false, Some((0.5, 0.)));
+}
+
+#[test]
+fn test_script_animation_local_time() {
+    let mut state = state(timing(2., 1.), 0.5);
+    assert_eq!(state.local_time(11.), 1.5);
+
+    state.playback_rate = -2.;
+    assert_eq!(state.local_time(10.25), 0.);
+
+    state.paused = true;
+    assert_eq!(state.local_time(11.), 0.5);
+
+    state.paused = false;
+    state.synced_at = None;
+    assert_eq!(state.local_time(11.), 0.5);
+}
+
+#[test]
+fn test_script_animation_is_running() {
+    let mut state = state(timing(2., 1.), 0.);
+    assert!(state.is_running(11.));
+    assert!(!state.is_finished(11.));
+    assert!(state.is_finished(12.));
+    assert!(!state.is_running(12.));
+
+    state.timing.fill_forwards = true;
+    assert!(state.is_running(12.));
+
+    state.playback_rate = -1.;
+    state.current_time = 1.;
+    assert!(state.is_finished(11.));
+    assert!(!state.is_running(11.));
+    state.timing.fill_backwards = true;
+    assert!(state.is_running(11.));
+
+    state.playback_rate = 0.;
+    assert!(!state.is_finished(100.));
+
+    state.expired = true;
+    assert!(!state.is_running(11.));
+}
@@ -25,6 +25,7 @@ extern crate style_traits;
 extern crate test;
 
 mod animated_properties;
+mod animation;
 mod attr;
 mod custom_properties;
 mod logical_geometry;